@@ -15,7 +15,9 @@ use ropey::Rope;
 
 use rholang_language_server::tree_sitter;
 use rholang_language_server::parsers::rholang::parse_to_ir;
-use rholang_language_server::ir::rholang_node::RholangNode;
+use rholang_language_server::ir::rholang_node::{
+    RholangNode, compute_absolute_positions_parallel, compute_absolute_positions_sequential,
+};
 use rholang_language_server::ir::transforms::symbol_table_builder::SymbolTableBuilder;
 use rholang_language_server::ir::visitor::Visitor;
 use rholang_language_server::ir::symbol_table::SymbolTable;
@@ -399,6 +401,39 @@ fn bench_position_calculations(c: &mut Criterion) {
     group.finish();
 }
 
+/// Generate a flat composition of sends; about 7 IR nodes per send
+fn generate_many_sends(count: usize) -> String {
+    let mut code = String::from("new x in {\n");
+    for i in 0..count {
+        code.push_str(&format!("  x!({}, [{}, \"s\"]) |\n", i, i));
+    }
+    code.push_str("  Nil\n}");
+    code
+}
+
+fn bench_absolute_positions(c: &mut Criterion) {
+    let mut group = c.benchmark_group("absolute_positions");
+    group.sample_size(20);
+
+    // ~10k and ~100k nodes
+    for sends in [1_500, 15_000] {
+        let code = generate_many_sends(sends);
+        let tree = tree_sitter::parse_code(&code);
+        let rope = Rope::from_str(&code);
+        let ir = parse_to_ir(&tree, &rope);
+        let nodes = compute_absolute_positions_sequential(&ir).len();
+
+        group.bench_with_input(BenchmarkId::new("sequential", nodes), &ir, |b, ir| {
+            b.iter(|| black_box(compute_absolute_positions_sequential(ir)))
+        });
+        group.bench_with_input(BenchmarkId::new("parallel", nodes), &ir, |b, ir| {
+            b.iter(|| black_box(compute_absolute_positions_parallel(ir)))
+        });
+    }
+
+    group.finish();
+}
+
 // ============================================================================
 // Benchmark: Metadata Allocation
 // ============================================================================
@@ -496,6 +531,7 @@ criterion_group! {
         bench_symbol_table_building,
         bench_visitor_traversal,
         bench_position_calculations,
+        bench_absolute_positions,
         bench_metadata_allocation,
         bench_end_to_end_pipeline
 }
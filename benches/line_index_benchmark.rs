@@ -0,0 +1,138 @@
+//! Microbenchmarks for position conversions
+//!
+//! Compares the cached `LineIndex` against the ad-hoc rope scans it replaces:
+//! - line/character → byte offset
+//! - byte offset → line/character
+//! - building the index and updating it incrementally after an edit
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use ropey::Rope;
+use std::time::Duration;
+
+use rholang_language_server::lsp::line_index::LineIndex;
+
+/// Generates a Rholang document with `lines` lines of contract code.
+fn generate_document(lines: usize) -> String {
+    let mut text = String::with_capacity(lines * 48);
+    text.push_str("new stdout(`rho:io:stdout`) in {\n");
+    for i in 0..lines.saturating_sub(2) {
+        text.push_str(&format!("  contract c{}(@x, ret) = {{ ret!(x + {}) }} |\n", i, i));
+    }
+    text.push_str("  Nil\n}\n");
+    text
+}
+
+/// Rope-based conversion used before the line index existed.
+fn rope_offset(rope: &Rope, line: usize, character: usize) -> Option<usize> {
+    if line >= rope.len_lines() {
+        return None;
+    }
+    let line_start = rope.try_line_to_byte(line).ok()?;
+    let line_text = rope.line(line);
+    Some(line_start + line_text.char_to_byte(character.min(line_text.len_chars())))
+}
+
+fn bench_position_to_offset(c: &mut Criterion) {
+    let mut group = c.benchmark_group("position_to_offset");
+
+    for lines in [100, 1_000, 10_000] {
+        let rope = Rope::from_str(&generate_document(lines));
+        let index = LineIndex::from_rope(&rope);
+        let queries: Vec<(usize, usize)> = (0..lines).step_by(7).map(|line| (line, 12)).collect();
+
+        group.bench_with_input(BenchmarkId::new("rope_scan", lines), &queries, |b, queries| {
+            b.iter(|| {
+                for &(line, character) in queries {
+                    black_box(rope_offset(&rope, line, character));
+                }
+            })
+        });
+
+        group.bench_with_input(BenchmarkId::new("line_index", lines), &queries, |b, queries| {
+            b.iter(|| {
+                for &(line, character) in queries {
+                    black_box(index.offset(line, character, &rope));
+                }
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_offset_to_position(c: &mut Criterion) {
+    let mut group = c.benchmark_group("offset_to_position");
+
+    for lines in [100, 1_000, 10_000] {
+        let rope = Rope::from_str(&generate_document(lines));
+        let index = LineIndex::from_rope(&rope);
+        let offsets: Vec<usize> = (0..rope.len_bytes()).step_by(97).collect();
+
+        group.bench_with_input(BenchmarkId::new("rope_scan", lines), &offsets, |b, offsets| {
+            b.iter(|| {
+                for &byte in offsets {
+                    let line = rope.byte_to_line(byte);
+                    let column = rope.byte_to_char(byte) - rope.line_to_char(line);
+                    black_box((line, column));
+                }
+            })
+        });
+
+        group.bench_with_input(BenchmarkId::new("line_index", lines), &offsets, |b, offsets| {
+            b.iter(|| {
+                for &byte in offsets {
+                    black_box(index.position(byte, &rope));
+                }
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_index_maintenance(c: &mut Criterion) {
+    let mut group = c.benchmark_group("line_index_maintenance");
+
+    for lines in [1_000, 10_000] {
+        let rope = Rope::from_str(&generate_document(lines));
+
+        group.bench_with_input(BenchmarkId::new("build", lines), &rope, |b, rope| {
+            b.iter(|| black_box(LineIndex::from_rope(rope)))
+        });
+
+        // Typing a newline in the middle of the document
+        let mut edited = rope.clone();
+        let edit_char = edited.line_to_char(lines / 2) + 4;
+        let edit_byte = edited.char_to_byte(edit_char);
+        edited.insert(edit_char, "\n");
+        let base_index = LineIndex::from_rope(&rope);
+
+        group.bench_with_input(BenchmarkId::new("incremental_edit", lines), &edited, |b, edited| {
+            b.iter(|| {
+                let mut index = base_index.clone();
+                index.apply_edit(edited, edit_byte, edit_byte, 1);
+                black_box(index)
+            })
+        });
+
+        group.bench_with_input(BenchmarkId::new("rebuild_after_edit", lines), &edited, |b, edited| {
+            b.iter(|| black_box(LineIndex::from_rope(edited)))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default()
+        .sample_size(50)
+        .measurement_time(Duration::from_secs(5))
+        .warm_up_time(Duration::from_secs(1));
+    targets =
+        bench_position_to_offset,
+        bench_offset_to_position,
+        bench_index_maintenance
+}
+
+criterion_main!(benches);
@@ -7,6 +7,7 @@ use std::hash::{Hash, Hasher};
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     tonic_build::compile_protos("proto/repl.proto")?;
     tonic_build::compile_protos("proto/lsp.proto")?;
+    tonic_build::compile_protos("proto/deploy.proto")?;
 
     // Ensure tree-sitter grammar is regenerated with named comments for LSP use
     ensure_rholang_parser_with_named_comments()?;
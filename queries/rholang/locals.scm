@@ -1,10 +1,31 @@
-; Minimal locals.scm for Rholang
+; locals.scm for Rholang
+;
+; Used for navigation when the IR is degraded by parse errors, so only
+; constructs whose binders are plain variables are described.
 
 ; Source file is root scope
 (source_file) @local.scope
 
-; Blocks create scopes
+; Binding constructs and blocks create scopes
 (block) @local.scope
+(new) @local.scope
+(contract) @local.scope
+(input) @local.scope
+(let) @local.scope
+
+; `new x, y(`uri`) in …`
+(name_decl (var) @local.definition)
+
+; Contract formals that are plain variables
+(contract formals: (_ (var) @local.definition))
+
+; Variables bound by `for` receipts
+(linear_bind names: (_ (var) @local.definition))
+(repeated_bind names: (_ (var) @local.definition))
+(peek_bind names: (_ (var) @local.definition))
+
+; `let x = …`
+(decl names: (_ (var) @local.definition))
 
 ; Variables are references
 (var) @local.reference
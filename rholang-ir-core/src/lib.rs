@@ -0,0 +1,16 @@
+//! Core IR types of the Rholang language server
+//!
+//! The position, node base and rope types that the language server's IR is
+//! built on, without the server itself, so that formatters, code generators
+//! and other tooling can share them without pulling in tokio or tower-lsp.
+//!
+//! - [`semantic_node`]: `Position`, `NodeBase`, `SemanticNode` and metadata;
+//! - [`line_index`]: row/column ↔ byte conversions over a rope;
+//! - [`rope`]: allocation-free rope slicing.
+//!
+//! The `rholang-language-server` crate re-exports these under their original
+//! paths (`ir::semantic_node`, `lsp::line_index`, `parsers::rholang::helpers`).
+
+pub mod line_index;
+pub mod rope;
+pub mod semantic_node;
@@ -0,0 +1,325 @@
+//! Precomputed line-start index for position conversions
+//!
+//! Row/column ↔ byte conversions happen on nearly every request (hover,
+//! goto-definition, signature help, didChange). `LineIndex` caches the byte
+//! offset of every line start so that:
+//!
+//! - line → byte is an O(1) lookup
+//! - byte → line is an O(log n) binary search
+//! - column ↔ byte only touches the single line involved
+//!
+//! Lines are split on the LSP line terminators `\n`, `\r\n` and `\r`. Columns
+//! are offsets within the line in the code units of the index's
+//! [`PositionEncoding`], UTF-16 unless the client negotiated UTF-8.
+//! IR positions keep tree-sitter's byte columns, so they must be converted
+//! with [`LineIndex::position`] before being sent to a client, and client
+//! positions with [`LineIndex::offset`] before being compared with them.
+//!
+//! The index is built once per document version and updated incrementally on
+//! edits via [`LineIndex::apply_edit`], which only rescans the edited range.
+
+use ropey::Rope;
+
+/// Code units that LSP position columns count (LSP 3.17 `PositionEncodingKind`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum PositionEncoding {
+    /// Bytes of the UTF-8 encoding
+    #[serde(rename = "utf-8")]
+    Utf8,
+    /// UTF-16 code units, the LSP default every client supports
+    #[default]
+    #[serde(rename = "utf-16")]
+    Utf16,
+}
+
+impl PositionEncoding {
+    /// Number of code units of `c`.
+    pub fn units(self, c: char) -> usize {
+        match self {
+            Self::Utf8 => c.len_utf8(),
+            Self::Utf16 => c.len_utf16(),
+        }
+    }
+
+    /// Number of code units of `s`.
+    pub fn units_str(self, s: &str) -> usize {
+        match self {
+            Self::Utf8 => s.len(),
+            Self::Utf16 => s.encode_utf16().count(),
+        }
+    }
+}
+
+/// Byte offsets of line starts for a single document version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineIndex {
+    /// Byte offset of the start of each line; always begins with 0
+    line_starts: Vec<usize>,
+    /// Length of the indexed text in bytes
+    len_bytes: usize,
+    /// Code units of columns
+    encoding: PositionEncoding,
+}
+
+/// Returns true if a line starts between bytes `prev` and `cur`.
+///
+/// A `\r` immediately followed by `\n` is a single terminator, so the line
+/// starts after the `\n`, not between the two bytes.
+fn is_line_start(prev: Option<u8>, cur: Option<u8>) -> bool {
+    match prev {
+        Some(b'\n') => true,
+        Some(b'\r') => cur != Some(b'\n'),
+        _ => false,
+    }
+}
+
+impl LineIndex {
+    /// Builds the index for `text`.
+    pub fn new(text: &str) -> Self {
+        Self::from_bytes(text.bytes(), text.len())
+    }
+
+    /// Builds the index for `rope` without materializing it as a `String`.
+    pub fn from_rope(rope: &Rope) -> Self {
+        Self::from_bytes(rope.bytes(), rope.len_bytes())
+    }
+
+    fn from_bytes(bytes: impl Iterator<Item = u8>, len_bytes: usize) -> Self {
+        let mut line_starts = vec![0];
+        let mut bytes = bytes.peekable();
+        let mut offset = 0;
+        while let Some(byte) = bytes.next() {
+            offset += 1;
+            if is_line_start(Some(byte), bytes.peek().copied()) {
+                line_starts.push(offset);
+            }
+        }
+        Self { line_starts, len_bytes, encoding: PositionEncoding::default() }
+    }
+
+    /// Counts columns in `encoding` instead of UTF-16 code units.
+    pub fn with_encoding(mut self, encoding: PositionEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Code units that columns count.
+    pub fn encoding(&self) -> PositionEncoding {
+        self.encoding
+    }
+
+    /// Number of lines (a trailing terminator starts a final empty line).
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Length of the indexed text in bytes.
+    pub fn len_bytes(&self) -> usize {
+        self.len_bytes
+    }
+
+    /// Byte offset of the start of `line`.
+    pub fn line_start(&self, line: usize) -> Option<usize> {
+        self.line_starts.get(line).copied()
+    }
+
+    /// Byte offset just past the end of `line`, including its terminator.
+    pub fn line_end(&self, line: usize) -> Option<usize> {
+        if line >= self.line_starts.len() {
+            return None;
+        }
+        Some(self.line_starts.get(line + 1).copied().unwrap_or(self.len_bytes))
+    }
+
+    /// Line containing byte offset `byte` (clamped to the last line).
+    pub fn line_of_byte(&self, byte: usize) -> usize {
+        self.line_starts.partition_point(|&start| start <= byte).saturating_sub(1)
+    }
+
+    /// Converts a (line, character) position to a byte offset in `rope`.
+    ///
+    /// Characters past the end of the line clamp to the end of the line's
+    /// content (before its terminator), and a character inside a multi-unit
+    /// char (a surrogate pair, or a UTF-8 sequence) to the start of the char. Returns `None` if `line` is out of
+    /// bounds. `rope` must be the text this index was built for.
+    pub fn offset(&self, line: usize, character: usize, rope: &Rope) -> Option<usize> {
+        let start = self.line_start(line)?;
+        let end = self.line_end(line)?;
+
+        let mut offset = start;
+        let mut units = 0;
+        for c in rope.byte_slice(start..end).chars() {
+            units += self.encoding.units(c);
+            if units > character || matches!(c, '\n' | '\r') {
+                break;
+            }
+            offset += c.len_utf8();
+        }
+        Some(offset)
+    }
+
+    /// Converts a byte offset in `rope` to a (line, character) position.
+    ///
+    /// Returns `None` if `byte` is past the end of the text.
+    pub fn position(&self, byte: usize, rope: &Rope) -> Option<(usize, usize)> {
+        if byte > self.len_bytes {
+            return None;
+        }
+        let line = self.line_of_byte(byte);
+        let start = self.line_starts[line];
+        let column = rope.byte_slice(start..byte).chars().map(|c| self.encoding.units(c)).sum();
+        Some((line, column))
+    }
+
+    /// Updates the index after `old_start..old_end` bytes were replaced by
+    /// `new_len` bytes.
+    ///
+    /// `rope` is the text after the edit. Only line starts that depend on the
+    /// edited bytes are rescanned; all later line starts are shifted.
+    pub fn apply_edit(&mut self, rope: &Rope, start: usize, old_end: usize, new_len: usize) {
+        let delta = new_len as isize - (old_end - start) as isize;
+
+        // Line starts in [start, old_end] depend on an edited byte (either the
+        // byte before or at the offset); line 0 always starts at 0
+        let lo = self.line_starts.partition_point(|&p| p < start.max(1));
+        let hi = self.line_starts.partition_point(|&p| p <= old_end);
+
+        for line_start in &mut self.line_starts[hi..] {
+            *line_start = (*line_start as isize + delta) as usize;
+        }
+
+        let len_bytes = rope.len_bytes();
+        let scan_start = start.max(1);
+        let scan_end = (start + new_len).min(len_bytes);
+        let mut rescanned = Vec::new();
+        if scan_start <= scan_end {
+            let mut bytes = rope.bytes_at(scan_start - 1);
+            let mut prev = bytes.next();
+            for offset in scan_start..=scan_end {
+                let cur = bytes.next();
+                if is_line_start(prev, cur) {
+                    rescanned.push(offset);
+                }
+                prev = cur;
+            }
+        }
+
+        self.line_starts.splice(lo..hi, rescanned);
+        self.len_bytes = len_bytes;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quickcheck::{QuickCheck, TestResult};
+
+    #[test]
+    fn test_line_starts_for_all_terminators() {
+        let index = LineIndex::new("a\nbc\r\nd\re\n");
+        assert_eq!(index.line_count(), 5);
+        assert_eq!(index.line_start(1), Some(2));
+        assert_eq!(index.line_start(2), Some(6));
+        assert_eq!(index.line_start(3), Some(8));
+        assert_eq!(index.line_start(4), Some(10));
+        assert_eq!(index.line_end(1), Some(6));
+        assert_eq!(index.line_start(5), None);
+        assert_eq!(index, LineIndex::from_rope(&Rope::from_str("a\nbc\r\nd\re\n")));
+    }
+
+    #[test]
+    fn test_offset_and_position_round_trip() {
+        let text = "new x in {\n  x!(\"héllo\")\r\n}";
+        let rope = Rope::from_str(text);
+        let index = LineIndex::from_rope(&rope);
+
+        let byte = index.offset(1, 8, &rope).unwrap();
+        assert_eq!(&text[byte..byte + 3], "llo");
+        assert_eq!(index.position(byte, &rope), Some((1, 8)));
+
+        // Columns past the end clamp before the "\r\n" terminator
+        let end = index.offset(1, 100, &rope).unwrap();
+        assert_eq!(&text[end..end + 2], "\r\n");
+
+        assert_eq!(index.offset(3, 0, &rope), None);
+        assert_eq!(index.position(text.len() + 1, &rope), None);
+    }
+
+    #[test]
+    fn test_columns_count_utf16_code_units() {
+        // The crab is one char, four bytes and two UTF-16 code units
+        let text = "x!(\"🦀\", y)";
+        let rope = Rope::from_str(text);
+        let index = LineIndex::from_rope(&rope);
+
+        let byte = index.offset(0, 7, &rope).unwrap();
+        assert_eq!(&text[byte..], ", y)");
+        assert_eq!(index.position(byte, &rope), Some((0, 7)));
+
+        // Inside the surrogate pair: the start of the crab
+        let inside = index.offset(0, 5, &rope).unwrap();
+        assert_eq!(&text[inside..], "🦀\", y)");
+    }
+
+    #[test]
+    fn test_columns_count_utf8_bytes() {
+        let text = "x!(\"🦀\", y)";
+        let rope = Rope::from_str(text);
+        let index = LineIndex::from_rope(&rope).with_encoding(PositionEncoding::Utf8);
+
+        let byte = index.offset(0, 9, &rope).unwrap();
+        assert_eq!(&text[byte..], ", y)");
+        assert_eq!(index.position(byte, &rope), Some((0, 9)));
+
+        // Inside the UTF-8 sequence: the start of the crab
+        let inside = index.offset(0, 6, &rope).unwrap();
+        assert_eq!(&text[inside..], "🦀\", y)");
+    }
+
+    #[test]
+    fn test_apply_edit_joins_crlf() {
+        // Inserting "\n" after a lone "\r" turns two line breaks into one
+        let mut rope = Rope::from_str("a\rb");
+        let mut index = LineIndex::from_rope(&rope);
+        rope.insert(2, "\n");
+        index.apply_edit(&rope, 2, 2, 1);
+        assert_eq!(index, LineIndex::from_rope(&rope));
+        assert_eq!(index.line_count(), 2);
+    }
+
+    #[test]
+    fn test_apply_edit_matches_rebuild() {
+        // Bias generated text towards line terminators so CR/LF pairs are
+        // frequently created and split at edit boundaries
+        fn with_terminators(s: String) -> String {
+            s.chars()
+                .map(|c| match c as u32 % 4 {
+                    0 => '\n',
+                    1 => '\r',
+                    _ => c,
+                })
+                .collect()
+        }
+
+        fn prop(text: String, start: usize, len: usize, insert: String) -> TestResult {
+            let text = with_terminators(text);
+            let insert = with_terminators(insert);
+            let mut rope = Rope::from_str(&text);
+            let total = rope.len_chars();
+            let start_char = if total == 0 { 0 } else { start % (total + 1) };
+            let end_char = (start_char + len % 8).min(total);
+
+            let mut index = LineIndex::from_rope(&rope);
+            let start_byte = rope.char_to_byte(start_char);
+            let old_end_byte = rope.char_to_byte(end_char);
+            rope.remove(start_char..end_char);
+            rope.insert(start_char, &insert);
+            index.apply_edit(&rope, start_byte, old_end_byte, insert.len());
+
+            TestResult::from_bool(index == LineIndex::from_rope(&rope))
+        }
+        QuickCheck::new()
+            .tests(500)
+            .quickcheck(prop as fn(String, usize, usize, String) -> TestResult);
+    }
+}
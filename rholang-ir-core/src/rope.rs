@@ -0,0 +1,113 @@
+//! Rope slicing without needless allocation
+//!
+//! Converting Tree-Sitter nodes to IR slices the source rope for every token.
+//! [`CowRopeSlice`] borrows the text when a range lies within one rope chunk,
+//! and [`safe_cow_slice`]/[`safe_byte_slice`] turn invalid ranges into empty
+//! text instead of panicking.
+
+use std::borrow::Cow;
+
+use ropey::{Rope, RopeSlice};
+use tracing::warn;
+
+/// Borrowed view of a rope byte range that only allocates when it must
+///
+/// Most token-sized ranges (identifiers, literals, operators) fall inside a
+/// single rope chunk, in which case the text is borrowed directly from the
+/// rope. Only ranges spanning a chunk boundary are copied into a `String`.
+/// Comparisons and character iteration never allocate.
+#[derive(Debug, Clone, Copy)]
+pub struct CowRopeSlice<'a> {
+    slice: RopeSlice<'a>,
+}
+
+impl<'a> CowRopeSlice<'a> {
+    /// Creates a slice over `start..end` bytes, or `None` if the range is invalid.
+    pub fn new(rope: &'a Rope, start: usize, end: usize) -> Option<Self> {
+        if end > rope.len_bytes() || start > end {
+            return None;
+        }
+        rope.get_byte_slice(start..end).map(|slice| Self { slice })
+    }
+
+    /// Returns the text, borrowed if the range lies within a single chunk.
+    pub fn as_cow(&self) -> Cow<'a, str> {
+        match self.slice.as_str() {
+            Some(text) => Cow::Borrowed(text),
+            None => Cow::Owned(self.slice.to_string()),
+        }
+    }
+
+    /// Returns true if `as_cow` would borrow instead of allocate.
+    pub fn is_contiguous(&self) -> bool {
+        self.slice.as_str().is_some()
+    }
+
+    /// Length of the slice in bytes.
+    pub fn len_bytes(&self) -> usize {
+        self.slice.len_bytes()
+    }
+
+    /// Iterates over the characters of the slice without allocating.
+    pub fn chars(&self) -> ropey::iter::Chars<'a> {
+        self.slice.chars()
+    }
+
+    /// Converts the slice into an owned `String` with a single allocation.
+    pub fn into_owned(self) -> String {
+        self.as_cow().into_owned()
+    }
+}
+
+impl PartialEq<str> for CowRopeSlice<'_> {
+    fn eq(&self, other: &str) -> bool {
+        self.slice == other
+    }
+}
+
+impl PartialEq<&str> for CowRopeSlice<'_> {
+    fn eq(&self, other: &&str) -> bool {
+        self.slice == *other
+    }
+}
+
+/// Slice a rope by byte range without copying when possible, returning an empty
+/// string on invalid range
+///
+/// # Arguments
+/// * `rope` - The source code rope
+/// * `start` - Starting byte offset
+/// * `end` - Ending byte offset
+///
+/// # Returns
+/// The sliced text (borrowed from the rope when contiguous), or empty string if the range is invalid
+pub fn safe_cow_slice(rope: &Rope, start: usize, end: usize) -> Cow<'_, str> {
+    match CowRopeSlice::new(rope, start, end) {
+        Some(slice) => slice.as_cow(),
+        None => {
+            warn!(
+                "Invalid byte range {}-{} (rope len={})",
+                start,
+                end,
+                rope.len_bytes()
+            );
+            Cow::Borrowed("")
+        }
+    }
+}
+
+/// Safely slice a rope by byte range, returning empty string on invalid range
+///
+/// Use this only when the IR needs an owned `String`; prefer `safe_cow_slice`
+/// or `CowRopeSlice` for comparisons and temporary text.
+///
+/// # Arguments
+/// * `rope` - The source code rope
+/// * `start` - Starting byte offset
+/// * `end` - Ending byte offset
+///
+/// # Returns
+/// The sliced string, or empty string if the range is invalid
+pub fn safe_byte_slice(rope: &Rope, start: usize, end: usize) -> String {
+    safe_cow_slice(rope, start, end).into_owned()
+}
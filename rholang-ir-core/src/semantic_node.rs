@@ -0,0 +1,512 @@
+//! Language-agnostic semantic IR foundation
+//!
+//! Positions, the node base every IR node embeds, semantic categories,
+//! metadata, and the [`SemanticNode`] trait that language-specific nodes
+//! (Rholang, MeTTa, …) implement. Downcasting to the concrete node types and
+//! rebuilding them lives with those types in the language server.
+//!
+//! Design principles:
+//! - Language-agnostic: Common interface for all language IRs
+//! - Semantic: Represents meaning, not just syntax
+//! - Extensible: Metadata system allows language-specific data
+//! - Type-safe: Rust type system ensures correct usage
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Represents the position of a node relative to the previous node's end position in the source code.
+/// Used to compute absolute positions dynamically during traversal.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RelativePosition {
+    pub delta_lines: i32,    // Difference in line numbers from the previous node's end
+    pub delta_columns: i32,  // Difference in column numbers, or start column if on a new line
+    pub delta_bytes: usize,  // Difference in byte offsets from the previous node's end
+}
+
+/// Represents an absolute position in the source code, computed when needed from relative positions.
+/// Coordinates are zero-based (row, column, byte).
+///
+/// Note: Hash and Eq are based on (row, column) only. The byte field is metadata for O(1) seeking.
+/// Two positions are considered equal if they refer to the same (row, column) location.
+#[derive(Debug, Clone, Copy, Ord, PartialOrd, serde::Serialize, serde::Deserialize)]
+pub struct Position {
+    pub row: usize,    // Line number (0-based)
+    pub column: usize, // Column number (0-based)
+    pub byte: usize,   // Byte offset from the start of the source code (metadata, not part of equality)
+}
+
+// Custom equality: positions match on (row, col) only
+impl PartialEq for Position {
+    fn eq(&self, other: &Self) -> bool {
+        self.row == other.row && self.column == other.column
+    }
+}
+
+impl Eq for Position {}
+
+// Custom hash: hash only (row, col), not byte
+impl std::hash::Hash for Position {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.row.hash(state);
+        self.column.hash(state);
+    }
+}
+
+/// Base structure for all Intermediate Representation (IR) nodes, encapsulating positional and textual metadata.
+/// Provides the foundation for tracking node locations and source text.
+#[derive(Debug, Clone)]
+pub struct NodeBase {
+    start: Position,                  // Absolute start position (preserved from Tree-Sitter)
+    content_length: usize,            // "Soft" length: content up to last child (for semantic operations)
+    syntactic_length: usize,          // "Hard" length: includes closing delimiters (for reconstruction)
+    span_lines: usize,                // Number of lines spanned by the node
+    span_columns: usize,              // Columns on the last line
+}
+
+impl NodeBase {
+    /// Creates a new NodeBase instance with the specified attributes.
+    ///
+    /// # Arguments
+    /// * `start` - Absolute start position (preserved from Tree-Sitter)
+    /// * `content_length` - Soft length: content up to last child (for semantics)
+    /// * `syntactic_length` - Hard length: includes closing delimiters (for reconstruction)
+    /// * `span_lines` - Number of lines spanned
+    /// * `span_columns` - Columns on the last line
+    pub fn new(
+        start: Position,
+        content_length: usize,
+        syntactic_length: usize,
+        span_lines: usize,
+        span_columns: usize,
+    ) -> Self {
+        NodeBase {
+            start,
+            content_length,
+            syntactic_length,
+            span_lines,
+            span_columns,
+        }
+    }
+
+    /// Convenience constructor for nodes without closing delimiters.
+    /// Sets syntactic_length = content_length.
+    pub fn new_simple(
+        start: Position,
+        length: usize,
+        span_lines: usize,
+        span_columns: usize,
+    ) -> Self {
+        NodeBase {
+            start,
+            content_length: length,
+            syntactic_length: length,
+            span_lines,
+            span_columns,
+        }
+    }
+
+    /// Returns the absolute start position of the node.
+    pub fn start(&self) -> Position {
+        self.start
+    }
+
+    /// Returns the content length (soft length) - content up to last child.
+    /// Use this for semantic operations and understanding node structure.
+    pub fn content_length(&self) -> usize {
+        self.content_length
+    }
+
+    /// Returns the syntactic length (hard length) - includes closing delimiters.
+    /// Use this for position reconstruction to compute next sibling's start.
+    pub fn syntactic_length(&self) -> usize {
+        self.syntactic_length
+    }
+
+    /// Returns the length of the node's text in bytes.
+    /// DEPRECATED: Use content_length() or syntactic_length() instead.
+    /// Defaults to syntactic_length for backward compatibility.
+    #[deprecated(since = "0.1.0", note = "Use content_length() or syntactic_length() instead")]
+    pub fn length(&self) -> usize {
+        self.syntactic_length
+    }
+
+    /// Returns the number of lines spanned by the node.
+    pub fn span_lines(&self) -> usize {
+        self.span_lines
+    }
+
+    /// Returns the number of columns on the last line spanned by the node.
+    pub fn span_columns(&self) -> usize {
+        self.span_columns
+    }
+
+    /// Computes the absolute end position of this node.
+    pub fn end(&self) -> Position {
+        Position {
+            row: self.start.row + self.span_lines,
+            column: if self.span_lines > 0 {
+                self.span_columns
+            } else {
+                self.start.column + self.span_columns
+            },
+            byte: self.start.byte + self.syntactic_length,
+        }
+    }
+}
+
+/// High-level semantic categories for language-agnostic IR traversal
+///
+/// These categories represent universal programming language constructs that exist
+/// across most languages. Language-specific nodes should map to one of these categories
+/// to enable generic analysis and transformation.
+///
+/// # Design Philosophy
+/// - Language-agnostic: No language-specific variants
+/// - Semantic: Based on meaning, not syntax
+/// - Coarse-grained: High-level categorization, not exhaustive
+/// - Extensible: LanguageSpecific for constructs that don't fit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SemanticCategory {
+    /// Literal values (numbers, strings, booleans, nil)
+    Literal,
+
+    /// Variable references
+    Variable,
+
+    /// Name/variable binding (let, new, contract params, etc.)
+    Binding,
+
+    /// Function/method invocation
+    Invocation,
+
+    /// Pattern matching (match, case)
+    Match,
+
+    /// Collections (lists, sets, maps, tuples)
+    Collection,
+
+    /// Conditional expressions (if/then/else)
+    Conditional,
+
+    /// Block/sequential composition
+    Block,
+
+    /// Language-specific construct that doesn't fit universal categories
+    LanguageSpecific,
+
+    /// Unknown or error node
+    Unknown,
+}
+
+impl fmt::Display for SemanticCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SemanticCategory::Literal => write!(f, "Literal"),
+            SemanticCategory::Variable => write!(f, "Variable"),
+            SemanticCategory::Binding => write!(f, "Binding"),
+            SemanticCategory::Invocation => write!(f, "Invocation"),
+            SemanticCategory::Match => write!(f, "Match"),
+            SemanticCategory::Collection => write!(f, "Collection"),
+            SemanticCategory::Conditional => write!(f, "Conditional"),
+            SemanticCategory::Block => write!(f, "Block"),
+            SemanticCategory::LanguageSpecific => write!(f, "LanguageSpecific"),
+            SemanticCategory::Unknown => write!(f, "Unknown"),
+        }
+    }
+}
+
+/// Extensible metadata storage for semantic nodes
+///
+/// Allows transforms to attach arbitrary typed data to nodes without
+/// modifying the core node structure.
+pub type Metadata = HashMap<String, Arc<dyn Any + Send + Sync>>;
+
+/// Core trait for all semantic IR nodes across languages
+///
+/// This trait provides a language-agnostic interface for working with IR nodes.
+/// All language-specific node types must implement this trait.
+///
+/// # Design Philosophy
+/// - **Language-agnostic**: No language-specific enums or types
+/// - **Semantic**: Focuses on meaning rather than syntax
+/// - **Type-safe downcasting**: Use Rust's `Any` trait for concrete types
+/// - **Category-based**: High-level semantic categories for generic code
+///
+/// # Usage Patterns
+///
+/// ## Generic/Language-Agnostic Code
+/// Use `semantic_category()` to work with nodes without knowing their concrete type:
+/// ```rust,ignore
+/// fn count_variables(node: &dyn SemanticNode) -> usize {
+///     match node.semantic_category() {
+///         SemanticCategory::Variable => 1,
+///         _ => node.children().iter().map(|c| count_variables(*c)).sum(),
+///     }
+/// }
+/// ```
+///
+/// ## Language-Specific Code
+/// Use downcasting to access language-specific structure:
+/// ```rust,ignore
+/// if let Some(rho_node) = node.as_rholang() {
+///     match rho_node {
+///         RholangNode::Par { procs, .. } => { /* handle parallel composition */ }
+///         _ => {}
+///     }
+/// }
+/// ```
+///
+/// # Thread Safety
+/// All implementations must be `Send + Sync` to support concurrent LSP operations.
+pub trait SemanticNode: Send + Sync + fmt::Debug + Any {
+    /// Returns the base node information (position, span, length)
+    fn base(&self) -> &NodeBase;
+
+    /// Returns the node's metadata (for extensibility)
+    fn metadata(&self) -> Option<&Metadata>;
+
+    /// Returns a mutable reference to the node's metadata
+    fn metadata_mut(&mut self) -> Option<&mut Metadata>;
+
+    /// Returns the high-level semantic category for this node
+    ///
+    /// This enables language-agnostic traversal and analysis. Language-specific
+    /// nodes should map to the most appropriate universal category, or return
+    /// `SemanticCategory::LanguageSpecific` for unique constructs.
+    fn semantic_category(&self) -> SemanticCategory {
+        SemanticCategory::Unknown
+    }
+
+    /// Returns a human-readable type name for this node
+    ///
+    /// Format: "Language::NodeType" (e.g., "Rholang::Par", "MeTTa::SExpr")
+    /// or just "NodeType" for universal constructs.
+    fn type_name(&self) -> &'static str {
+        "Unknown"
+    }
+
+    /// Returns the number of child nodes
+    ///
+    /// This enables index-based traversal without lifetime issues.
+    /// Returns 0 for leaf nodes (e.g., literals, variables).
+    fn children_count(&self) -> usize {
+        0
+    }
+
+    /// Returns the child node at the specified index
+    ///
+    /// # Arguments
+    /// - `index`: Zero-based index of the child to retrieve
+    ///
+    /// # Returns
+    /// - `Some(&dyn SemanticNode)` if the index is valid
+    /// - `None` if the index is out of bounds
+    ///
+    /// Children are ordered consistently with source order.
+    fn child_at(&self, index: usize) -> Option<&dyn SemanticNode> {
+        let _ = index;
+        None
+    }
+
+    /// Downcasts this node to `&dyn Any` for type-safe casting
+    ///
+    /// Use this with `downcast_ref::<ConcreteType>()` to access language-specific structure.
+    fn as_any(&self) -> &dyn Any;
+
+    /// Returns the absolute start position of this node.
+    ///
+    /// # Returns
+    /// The absolute position where this node starts
+    fn start(&self) -> Position {
+        self.base().start()
+    }
+
+    /// Returns the absolute end position of this node.
+    ///
+    /// # Returns
+    /// The absolute position where this node ends
+    fn end(&self) -> Position {
+        self.base().end()
+    }
+}
+
+/// Helper function to create an empty metadata map
+pub fn empty_metadata() -> Metadata {
+    HashMap::new()
+}
+
+/// Helper function to create metadata with a single entry
+pub fn metadata_with<T: Any + Send + Sync>(key: &str, value: T) -> Metadata {
+    let mut map = HashMap::new();
+    map.insert(key.to_string(), Arc::new(value) as Arc<dyn Any + Send + Sync>);
+    map
+}
+
+/// Helper function to get a typed value from metadata
+pub fn get_metadata<'a, T: Any + Send + Sync>(metadata: &'a Metadata, key: &str) -> Option<&'a T> {
+    metadata
+        .get(key)
+        .and_then(|arc| arc.downcast_ref::<T>())
+}
+
+/// Helper function to insert a typed value into metadata
+pub fn insert_metadata<T: Any + Send + Sync>(
+    metadata: &mut Metadata,
+    key: &str,
+    value: T,
+) {
+    metadata.insert(key.to_string(), Arc::new(value) as Arc<dyn Any + Send + Sync>);
+}
+
+/// Generic visitor trait for language-agnostic IR traversal
+///
+/// This visitor works with any IR that implements SemanticNode, providing
+/// a unified way to traverse and transform IR trees regardless of the source language.
+///
+/// Unlike the language-specific Visitor trait (for Rholang RholangNode), this visitor
+/// operates at the semantic level using SemanticCategory discrimination.
+///
+/// # Example
+/// ```rust,ignore
+/// struct CountVariables {
+///     count: usize,
+/// }
+///
+/// impl GenericVisitor for CountVariables {
+///     fn visit_node(&mut self, node: &dyn SemanticNode) {
+///         if matches!(node.semantic_category(), SemanticCategory::Variable) {
+///             self.count += 1;
+///         }
+///         self.visit_children(node);
+///     }
+/// }
+/// ```
+pub trait GenericVisitor {
+    /// Visit a semantic node
+    ///
+    /// Override this method to implement custom visiting logic.
+    /// Call `visit_children()` to recursively visit child nodes.
+    fn visit_node(&mut self, node: &dyn SemanticNode) {
+        self.visit_children(node);
+    }
+
+    /// Visit all children of a node
+    ///
+    /// This is a helper method that visits each child node using index-based traversal.
+    /// Override to customize child traversal order or filtering.
+    fn visit_children(&mut self, node: &dyn SemanticNode) {
+        let count = node.children_count();
+        for i in 0..count {
+            if let Some(child) = node.child_at(i) {
+                self.visit_node(child);
+            }
+        }
+    }
+
+    /// Visit a node based on its semantic category
+    ///
+    /// This method dispatches to type-specific handlers based on SemanticCategory.
+    /// Override specific handlers (visit_literal, visit_variable, etc.) to
+    /// customize behavior for specific semantic categories.
+    fn visit_typed(&mut self, node: &dyn SemanticNode) {
+        match node.semantic_category() {
+            SemanticCategory::Literal => self.visit_literal(node),
+            SemanticCategory::Variable => self.visit_variable(node),
+            SemanticCategory::Binding => self.visit_binding(node),
+            SemanticCategory::Invocation => self.visit_invocation(node),
+            SemanticCategory::Match => self.visit_match(node),
+            SemanticCategory::Collection => self.visit_collection(node),
+            SemanticCategory::Conditional => self.visit_conditional(node),
+            SemanticCategory::Block => self.visit_block(node),
+            SemanticCategory::LanguageSpecific => self.visit_language_specific(node),
+            SemanticCategory::Unknown => self.visit_node(node),
+        }
+    }
+
+    // Type-specific visitor methods (can be overridden)
+
+    fn visit_literal(&mut self, node: &dyn SemanticNode) {
+        self.visit_node(node);
+    }
+
+    fn visit_variable(&mut self, node: &dyn SemanticNode) {
+        self.visit_node(node);
+    }
+
+    fn visit_binding(&mut self, node: &dyn SemanticNode) {
+        self.visit_node(node);
+    }
+
+    fn visit_invocation(&mut self, node: &dyn SemanticNode) {
+        self.visit_node(node);
+    }
+
+    fn visit_match(&mut self, node: &dyn SemanticNode) {
+        self.visit_node(node);
+    }
+
+    fn visit_collection(&mut self, node: &dyn SemanticNode) {
+        self.visit_node(node);
+    }
+
+    fn visit_conditional(&mut self, node: &dyn SemanticNode) {
+        self.visit_node(node);
+    }
+
+    fn visit_block(&mut self, node: &dyn SemanticNode) {
+        self.visit_node(node);
+    }
+
+    fn visit_language_specific(&mut self, node: &dyn SemanticNode) {
+        self.visit_node(node);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_semantic_category_display() {
+        assert_eq!(SemanticCategory::Literal.to_string(), "Literal");
+        assert_eq!(SemanticCategory::Variable.to_string(), "Variable");
+        assert_eq!(SemanticCategory::LanguageSpecific.to_string(), "LanguageSpecific");
+    }
+
+    #[test]
+    fn test_metadata_helpers() {
+        let mut metadata = empty_metadata();
+        assert_eq!(metadata.len(), 0);
+
+        insert_metadata(&mut metadata, "test_key", 42i32);
+        assert_eq!(get_metadata::<i32>(&metadata, "test_key"), Some(&42));
+        assert_eq!(get_metadata::<String>(&metadata, "test_key"), None);
+    }
+
+    #[test]
+    fn test_metadata_with() {
+        let metadata = metadata_with("count", 100usize);
+        assert_eq!(get_metadata::<usize>(&metadata, "count"), Some(&100));
+    }
+
+    // Test GenericVisitor
+    struct NodeCounter {
+        count: usize,
+    }
+
+    impl GenericVisitor for NodeCounter {
+        fn visit_node(&mut self, node: &dyn SemanticNode) {
+            self.count += 1;
+            self.visit_children(node);
+        }
+    }
+
+    #[test]
+    fn test_generic_visitor() {
+        let counter = NodeCounter { count: 0 };
+        // Would need actual nodes to test, but the trait compiles
+        assert_eq!(counter.count, 0);
+    }
+}
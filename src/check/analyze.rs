@@ -0,0 +1,184 @@
+//! One-shot analysis of a document on stdin (`analyze --stdin`)
+//!
+//! `rholang-language-server analyze --stdin --format json` reads one Rholang
+//! document from stdin, runs the analysis `check` runs on a file (syntax and
+//! semantic errors, configured lints), collects its document symbols and,
+//! with `--formatted`, formats it, then prints the result and exits. This
+//! serves editor plugins that do not speak LSP and shell scripts:
+//!
+//! ```sh
+//! rholang-language-server analyze --stdin --format json --path src/token.rho < src/token.rho
+//! ```
+//!
+//! The JSON report is one object with LSP-shaped fields:
+//!
+//! ```json
+//! { "path": "src/token.rho", "diagnostics": [ … ], "symbols": [ … ], "formatted": "…" }
+//! ```
+//!
+//! `--format text` prints the diagnostics as `check` does instead. Either
+//! way, the exit status is 1 when any error was reported.
+
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use ropey::Rope;
+use serde::Serialize;
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, DocumentSymbol};
+use tracing::{debug, warn};
+
+use crate::ir::rholang_node::compute_absolute_positions;
+use crate::ir::transforms::document_symbol_visitor::collect_document_symbols;
+use crate::lsp::formatting::{format_document, FormatConfig};
+use crate::lsp::semantic_validator::SemanticValidator;
+use crate::lsp::style_lints::StyleLintConfig;
+use crate::tree_sitter::{parse_code, parse_to_ir};
+
+use super::{check_source, format_diagnostic};
+
+/// Name of the document when `--path` is not given
+const STDIN_NAME: &str = "<stdin>";
+
+/// Output of the `analyze` subcommand
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// `path:line:column: severity: message` lines, as printed by `check`
+    #[default]
+    Text,
+    /// One JSON report
+    Json,
+}
+
+/// Arguments of the `analyze` subcommand
+#[derive(clap::Args, Debug, Clone)]
+pub struct AnalyzeArgs {
+    /// Read the document from stdin (currently the only source)
+    #[arg(long, required = true)]
+    pub stdin: bool,
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+    /// Path the document is reported under; `.rholangrc.toml` is read from the current directory
+    #[arg(long, value_name = "PATH")]
+    pub path: Option<PathBuf>,
+    /// Also format the document (JSON output only)
+    #[arg(long)]
+    pub formatted: bool,
+}
+
+/// Result of analyzing one document
+#[derive(Debug, Clone, Serialize)]
+pub struct AnalysisReport {
+    pub path: String,
+    pub diagnostics: Vec<Diagnostic>,
+    pub symbols: Vec<DocumentSymbol>,
+    /// The formatted document; `None` unless asked for, or if the document has syntax errors
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub formatted: Option<String>,
+}
+
+impl AnalysisReport {
+    fn has_errors(&self) -> bool {
+        self.diagnostics.iter().any(|d| d.severity == Some(DiagnosticSeverity::ERROR))
+    }
+}
+
+/// Runs the analysis of the document on stdin and returns the process exit code.
+pub async fn run(args: AnalyzeArgs) -> io::Result<i32> {
+    let mut text = String::new();
+    io::stdin().read_to_string(&mut text)?;
+
+    let root = std::env::current_dir()?;
+    let validator = match SemanticValidator::new() {
+        Ok(validator) => Some(validator),
+        Err(e) => {
+            warn!("Semantic validation unavailable: {}", e);
+            None
+        }
+    };
+    let path = args.path.clone().unwrap_or_else(|| PathBuf::from(STDIN_NAME));
+    let format_config = args.formatted.then(|| FormatConfig::load(&root));
+    let report = analyze_source(
+        &path,
+        &text,
+        &StyleLintConfig::load(&root),
+        validator.as_ref(),
+        format_config.as_ref(),
+    )
+    .await;
+    debug!("Analyzed {}: {} diagnostic(s)", report.path, report.diagnostics.len());
+
+    match args.format {
+        OutputFormat::Text => {
+            for diagnostic in &report.diagnostics {
+                println!("{}", format_diagnostic(&path, diagnostic));
+            }
+        }
+        OutputFormat::Json => {
+            let json = serde_json::to_string(&report).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+            println!("{}", json);
+        }
+    }
+    Ok(if report.has_errors() { 1 } else { 0 })
+}
+
+/// Analyzes the Rholang source `text` of `path`; formats it too when `format` is given.
+pub async fn analyze_source(
+    path: &Path,
+    text: &str,
+    config: &StyleLintConfig,
+    validator: Option<&SemanticValidator>,
+    format: Option<&FormatConfig>,
+) -> AnalysisReport {
+    let diagnostics = check_source(path, text, config, validator).await;
+
+    let tree = parse_code(text);
+    let rope = Rope::from_str(text);
+    let ir = parse_to_ir(&tree, &rope);
+    let positions = compute_absolute_positions(&ir);
+    let symbols = collect_document_symbols(&ir, &positions);
+
+    let formatted = format.and_then(|config| format_document(text, &config.options(2)));
+    AnalysisReport { path: path.display().to_string(), diagnostics, symbols, formatted }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_report_holds_diagnostics_symbols_and_formatting() {
+        let source = "contract log(@msg) = {\nNil\n}\n|\nnew x in {\n  x!(1) |\n  x!(1)\n}";
+        let report = analyze_source(
+            Path::new("log.rho"),
+            source,
+            &StyleLintConfig::default(),
+            None,
+            Some(&FormatConfig::default()),
+        )
+        .await;
+        assert!(!report.has_errors());
+        assert_eq!(report.diagnostics.len(), 1);
+        assert!(report.symbols.iter().any(|symbol| symbol.name == "log"), "{:?}", report.symbols);
+        assert!(report.formatted.as_deref().is_some_and(|formatted| formatted.contains("  Nil")));
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["path"], "log.rho");
+        assert_eq!(json["diagnostics"][0]["range"]["start"]["line"], 6);
+    }
+
+    #[tokio::test]
+    async fn test_syntax_errors_fail_without_formatting() {
+        let report = analyze_source(
+            Path::new(STDIN_NAME),
+            "new x in {\n  x!(1)\n",
+            &StyleLintConfig::default(),
+            None,
+            Some(&FormatConfig::default()),
+        )
+        .await;
+        assert!(report.has_errors());
+        assert_eq!(report.formatted, None);
+        assert!(serde_json::to_value(&report).unwrap().get("formatted").is_none());
+    }
+}
@@ -0,0 +1,128 @@
+//! Changed lines from unified diffs
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Lines changed in a file, 0-based, as sorted inclusive ranges
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChangedLines {
+    ranges: Vec<(u32, u32)>,
+}
+
+impl ChangedLines {
+    /// Marks lines `start..=end` as changed.
+    pub fn add(&mut self, start: u32, end: u32) {
+        self.ranges.push((start, end));
+        self.ranges.sort_unstable();
+        let mut merged: Vec<(u32, u32)> = Vec::with_capacity(self.ranges.len());
+        for &(start, end) in &self.ranges {
+            match merged.last_mut() {
+                Some(last) if start <= last.1.saturating_add(1) => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        self.ranges = merged;
+    }
+
+    /// Whether any line in `start..=end` changed.
+    pub fn intersects(&self, start: u32, end: u32) -> bool {
+        self.ranges.iter().any(|&(from, to)| from <= end && start <= to)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+}
+
+/// Lines added or modified per file in a unified diff, such as the output
+/// of `git diff --unified=0`.
+///
+/// Paths are taken from the `+++` headers with the `b/` prefix removed;
+/// deleted files are skipped. A hunk that only removes lines marks the lines
+/// around the removal, so that breakage it causes there is still reported.
+pub fn parse_unified_diff(diff: &str) -> BTreeMap<PathBuf, ChangedLines> {
+    let mut files: BTreeMap<PathBuf, ChangedLines> = BTreeMap::new();
+    let mut current: Option<PathBuf> = None;
+
+    for line in diff.lines() {
+        if let Some(header) = line.strip_prefix("+++ ") {
+            let path = header.split('\t').next().unwrap_or(header).trim_end();
+            current = if path == "/dev/null" {
+                None
+            } else {
+                Some(PathBuf::from(path.strip_prefix("b/").unwrap_or(path)))
+            };
+        } else if line.starts_with("@@ ") {
+            let (Some(path), Some((start, count))) = (&current, new_file_range(line)) else { continue };
+            let lines = files.entry(path.clone()).or_default();
+            if count > 0 {
+                lines.add(start - 1, start + count - 2);
+            } else {
+                // Removal after line `start` (1-based)
+                lines.add(start.saturating_sub(1), start);
+            }
+        }
+    }
+    files
+}
+
+/// `(start, count)` of the new file side of a hunk header `@@ -a,b +c,d @@`.
+fn new_file_range(header: &str) -> Option<(u32, u32)> {
+    let added = header.split_whitespace().find_map(|part| part.strip_prefix('+'))?;
+    let (start, count) = match added.split_once(',') {
+        Some((start, count)) => (start.parse().ok()?, count.parse().ok()?),
+        None => (added.parse().ok()?, 1),
+    };
+    Some((start, count))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "\
+diff --git a/contracts/bank.rho b/contracts/bank.rho
+index 1111111..2222222 100644
+--- a/contracts/bank.rho
++++ b/contracts/bank.rho
+@@ -3 +3 @@ new bank in {
+-  bank!(1)
++  bank!(2)
+@@ -10,0 +11,3 @@ contract deposit(@amount) = {
++  a |
++  b |
++  c
+@@ -20,2 +23,0 @@
+-  old
+-  lines
+diff --git a/old.rho b/old.rho
+deleted file mode 100644
+--- a/old.rho
++++ /dev/null
+@@ -1,2 +0,0 @@
+-Nil
+-Nil
+";
+
+    #[test]
+    fn test_parse_unified_diff() {
+        let files = parse_unified_diff(DIFF);
+        assert_eq!(files.len(), 1);
+        let lines = &files[&PathBuf::from("contracts/bank.rho")];
+        assert_eq!(lines.ranges, vec![(2, 2), (10, 12), (22, 23)]);
+
+        assert!(lines.intersects(0, 2));
+        assert!(lines.intersects(12, 30));
+        assert!(!lines.intersects(3, 9));
+        assert!(!lines.intersects(24, 40));
+    }
+
+    #[test]
+    fn test_adjacent_ranges_merge() {
+        let mut lines = ChangedLines::default();
+        lines.add(5, 6);
+        lines.add(0, 1);
+        lines.add(2, 4);
+        assert_eq!(lines.ranges, vec![(0, 6)]);
+    }
+}
@@ -0,0 +1,67 @@
+//! Command-line formatting check (`fmt --check`)
+//!
+//! `rholang-language-server fmt --check [PATHS…]` prints one
+//! `path:line:column: warning: …` line per Rholang file whose formatting
+//! differs from the canonical style (see `crate::lsp::format_check`) and
+//! exits with status 1 if there is any. Files are never rewritten.
+
+use std::io;
+use std::path::PathBuf;
+
+use tracing::{debug, warn};
+
+use crate::lsp::format_check::{check_format, FormatStatus};
+use crate::lsp::line_index::PositionEncoding;
+
+use super::{all_files, format_diagnostic};
+
+/// Arguments of the `fmt` subcommand
+#[derive(clap::Args, Debug, Clone)]
+pub struct FmtArgs {
+    /// Files or directories to check (default: the current directory)
+    pub paths: Vec<PathBuf>,
+    /// Report unformatted files instead of rewriting them (currently the only mode)
+    #[arg(long, required = true)]
+    pub check: bool,
+}
+
+/// Runs the formatting check and returns the process exit code.
+pub fn run(args: FmtArgs) -> io::Result<i32> {
+    let root = std::env::current_dir()?;
+    let targets = all_files(&args.paths);
+    debug!("Checking the formatting of {} file(s)", targets.len());
+
+    let (mut unformatted, mut unparsable) = (0, 0);
+    for target in &targets {
+        let text = match std::fs::read_to_string(&target.path) {
+            Ok(text) => text,
+            Err(e) => {
+                warn!("Skipping {}: {}", target.path.display(), e);
+                continue;
+            }
+        };
+        let display = target.path.strip_prefix(&root).unwrap_or(&target.path);
+        let status = check_format(&text, PositionEncoding::default());
+        match &status {
+            FormatStatus::Formatted => {}
+            FormatStatus::Unparsable => {
+                unparsable += 1;
+                warn!("Not checked, syntax errors: {}", display.display());
+            }
+            FormatStatus::Unformatted { .. } => {
+                unformatted += 1;
+                if let Some(diagnostic) = status.to_diagnostic() {
+                    println!("{}", format_diagnostic(display, &diagnostic));
+                }
+            }
+        }
+    }
+
+    eprintln!(
+        "{} of {} file(s) need formatting, {} not checked because of syntax errors",
+        unformatted,
+        targets.len(),
+        unparsable
+    );
+    Ok(if unformatted > 0 { 1 } else { 0 })
+}
@@ -0,0 +1,288 @@
+//! Command-line checking (`check`): diagnostics without an editor
+//!
+//! `rholang-language-server check [PATHS…]` reports the diagnostics the
+//! server would publish for the Rholang files under `PATHS` (default: the
+//! current directory): syntax errors, semantic errors from the embedded
+//! interpreter, and the lints configured in `.rholangrc.toml`.
+//!
+//! With `--diff <BASE>`, only files changed relative to the git ref `BASE`
+//! are analyzed and only diagnostics touching changed lines are reported,
+//! which keeps pre-commit hooks fast on large repositories:
+//!
+//! ```sh
+//! rholang-language-server check --diff HEAD          # staged and unstaged changes
+//! git diff -U0 origin/main | rholang-language-server check --diff -
+//! ```
+//!
+//! `--diff -` reads a unified diff from stdin instead of running git. Each
+//! diagnostic is printed as `path:line:column: severity: message`, and the
+//! exit status is 1 when any error was reported.
+//!
+//! `rholang-language-server fmt --check [PATHS…]` checks formatting instead
+//! (see [`format`]), and `rholang-language-server analyze --stdin` analyzes
+//! a single document read from stdin (see [`analyze`]).
+
+pub mod analyze;
+pub mod diff;
+pub mod format;
+
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use ropey::Rope;
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Url};
+use tracing::{debug, warn};
+use walkdir::WalkDir;
+
+use crate::ir::rholang_node::compute_absolute_positions;
+use crate::ir::transforms::documentation_attacher::DocumentationAttacher;
+use crate::ir::visitor::Visitor;
+use crate::lsp::conventions::check_conventions;
+use crate::lsp::diagnostic_registry;
+use crate::lsp::duplicate_sends::find_duplicate_sends;
+use crate::lsp::line_index::LineIndex;
+use crate::lsp::param_docs::find_param_doc_mismatches;
+use crate::lsp::semantic_validator::SemanticValidator;
+use crate::lsp::style_lints::{check_naming, StyleLintConfig};
+use crate::lsp::syntax_errors::syntax_diagnostics;
+use crate::tree_sitter::{parse_code, parse_to_document_ir};
+use crate::validators::lint::run_lints;
+
+use diff::{parse_unified_diff, ChangedLines};
+
+/// Value of `--diff` that reads the diff from stdin
+pub const DIFF_FROM_STDIN: &str = "-";
+
+/// Arguments of the `check` subcommand
+#[derive(clap::Args, Debug, Clone)]
+pub struct CheckArgs {
+    /// Files or directories to check (default: the current directory)
+    pub paths: Vec<PathBuf>,
+    /// Only check lines changed relative to this git ref; `-` reads a unified diff from stdin
+    #[arg(long, value_name = "BASE")]
+    pub diff: Option<String>,
+}
+
+/// A file to check, with the lines to report on (`None` for all)
+struct Target {
+    path: PathBuf,
+    changed: Option<ChangedLines>,
+}
+
+/// Runs the check and returns the process exit code.
+pub async fn run(args: CheckArgs) -> io::Result<i32> {
+    let root = std::env::current_dir()?;
+    let config = StyleLintConfig::load(&root);
+    let targets = match &args.diff {
+        None => all_files(&args.paths),
+        Some(base) => changed_files(base, &root, &args.paths)?,
+    };
+    debug!("Checking {} file(s)", targets.len());
+
+    let validator = match SemanticValidator::new() {
+        Ok(validator) => Some(validator),
+        Err(e) => {
+            warn!("Semantic validation unavailable: {}", e);
+            None
+        }
+    };
+
+    let (mut errors, mut warnings) = (0, 0);
+    for target in &targets {
+        let text = match std::fs::read_to_string(&target.path) {
+            Ok(text) => text,
+            Err(e) => {
+                warn!("Skipping {}: {}", target.path.display(), e);
+                continue;
+            }
+        };
+        let display = target.path.strip_prefix(&root).unwrap_or(&target.path);
+        for diagnostic in check_source(&target.path, &text, &config, validator.as_ref()).await {
+            let (start, end) = (diagnostic.range.start.line, diagnostic.range.end.line);
+            if target.changed.as_ref().is_some_and(|changed| !changed.intersects(start, end)) {
+                continue;
+            }
+            match diagnostic.severity {
+                Some(DiagnosticSeverity::ERROR) => errors += 1,
+                Some(DiagnosticSeverity::WARNING) => warnings += 1,
+                _ => {}
+            }
+            println!("{}", format_diagnostic(display, &diagnostic));
+        }
+    }
+
+    eprintln!("{} error(s), {} warning(s) in {} file(s)", errors, warnings, targets.len());
+    Ok(if errors > 0 { 1 } else { 0 })
+}
+
+/// Diagnostics of the Rholang source `text` of `path`, in document order.
+pub async fn check_source(
+    path: &Path,
+    text: &str,
+    config: &StyleLintConfig,
+    validator: Option<&SemanticValidator>,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = syntax_diagnostics(text);
+    if diagnostics.is_empty() {
+        if let Some(validator) = validator {
+            diagnostics.extend(validator.validate(text).await);
+        }
+    }
+
+    let tree = parse_code(text);
+    let rope = Rope::from_str(text);
+    // Attached documentation is needed to check `@param` directives
+    let document_ir = parse_to_document_ir(&tree, &rope);
+    let ir = DocumentationAttacher::new(document_ir.clone()).visit_node(&document_ir.root);
+    let positions = compute_absolute_positions(&ir);
+    let line_index = LineIndex::new(text);
+    diagnostics.extend(
+        check_naming(&ir, &positions, &rope, &line_index, &config.naming)
+            .iter()
+            .map(|violation| violation.to_diagnostic(config.naming.severity)),
+    );
+    diagnostics.extend(
+        check_conventions(&ir, &positions, &rope, &line_index, &config.conventions)
+            .iter()
+            .map(|violation| violation.to_diagnostic(config.conventions.severity)),
+    );
+    let uri = std::path::absolute(path)
+        .ok()
+        .and_then(|path| Url::from_file_path(path).ok())
+        .unwrap_or_else(|| Url::parse("file:///").expect("valid URL"));
+    diagnostics.extend(run_lints(&uri, &ir, &positions, &rope, &line_index, &config.rules));
+    diagnostics.extend(
+        find_duplicate_sends(&ir, &positions, &rope, &line_index)
+            .iter()
+            .map(|duplicate| duplicate.to_diagnostic(&uri)),
+    );
+    diagnostics.extend(
+        find_param_doc_mismatches(&ir, &positions, &rope, &line_index)
+            .iter()
+            .map(|mismatch| mismatch.to_diagnostic()),
+    );
+
+    diagnostic_registry::stamp(&uri, &mut diagnostics);
+    diagnostics.sort_by_key(|d| (d.range.start.line, d.range.start.character));
+    diagnostics
+}
+
+/// `path:line:column: severity: message [code]`, with 1-based positions and
+/// the stable code of registered diagnostics.
+pub fn format_diagnostic(path: &Path, diagnostic: &Diagnostic) -> String {
+    let severity = match diagnostic.severity {
+        Some(DiagnosticSeverity::ERROR) => "error",
+        Some(DiagnosticSeverity::WARNING) => "warning",
+        Some(DiagnosticSeverity::INFORMATION) => "info",
+        _ => "hint",
+    };
+    let code = match &diagnostic.code {
+        Some(NumberOrString::String(code)) => {
+            format!(" [{}]", diagnostic_registry::lookup(code).map_or(code.as_str(), |kind| kind.code))
+        }
+        Some(NumberOrString::Number(code)) => format!(" [{}]", code),
+        None => String::new(),
+    };
+    format!(
+        "{}:{}:{}: {}: {}{}",
+        path.display(),
+        diagnostic.range.start.line + 1,
+        diagnostic.range.start.character + 1,
+        severity,
+        diagnostic.message,
+        code
+    )
+}
+
+fn is_rholang_file(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "rho")
+}
+
+/// Every Rholang file under `paths`, skipping hidden directories.
+fn all_files(paths: &[PathBuf]) -> Vec<Target> {
+    let default = [PathBuf::from(".")];
+    let paths = if paths.is_empty() { &default[..] } else { paths };
+    let mut files = Vec::new();
+    for path in paths {
+        let walker = WalkDir::new(path)
+            .into_iter()
+            .filter_entry(|entry| entry.depth() == 0 || !entry.file_name().to_string_lossy().starts_with('.'));
+        for entry in walker.filter_map(Result::ok) {
+            if entry.file_type().is_file() && is_rholang_file(entry.path()) {
+                files.push(Target { path: entry.into_path(), changed: None });
+            }
+        }
+    }
+    files
+}
+
+/// The Rholang files changed relative to `base` (or in the diff on stdin),
+/// restricted to `paths` if any are given.
+fn changed_files(base: &str, root: &Path, paths: &[PathBuf]) -> io::Result<Vec<Target>> {
+    let diff = if base == DIFF_FROM_STDIN {
+        let mut diff = String::new();
+        io::stdin().read_to_string(&mut diff)?;
+        diff
+    } else {
+        git_diff(base)?
+    };
+
+    let targets = parse_unified_diff(&diff)
+        .into_iter()
+        .filter(|(path, changed)| is_rholang_file(path) && !changed.is_empty())
+        .map(|(path, changed)| Target { path: root.join(path), changed: Some(changed) })
+        .filter(|target| paths.is_empty() || paths.iter().any(|path| target.path.starts_with(root.join(path))))
+        .filter(|target| target.path.is_file())
+        .collect();
+    Ok(targets)
+}
+
+/// `git diff` of the working tree against `base`, with paths relative to the current directory.
+fn git_diff(base: &str) -> io::Result<String> {
+    let output = Command::new("git")
+        .args(["diff", "--unified=0", "--no-color", "--no-ext-diff", "--relative", base, "--", "*.rho"])
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("git diff {} failed: {}", base, String::from_utf8_lossy(&output.stderr).trim()),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp::lsp_types::{Position, Range};
+
+    #[tokio::test]
+    async fn test_check_source_reports_lints_and_syntax_errors() {
+        let path = Path::new("dup.rho");
+        let config = StyleLintConfig::default();
+
+        let diagnostics = check_source(path, "new x in {\n  x!(1) |\n  x!(1)\n}", &config, None).await;
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].range.start.line, 2);
+        assert_eq!(diagnostics[0].code, Some(NumberOrString::String("RHO101".to_string())));
+
+        let diagnostics = check_source(path, "new x in {\n  x!(1)\n", &config, None).await;
+        assert!(diagnostics.iter().any(|d| d.severity == Some(DiagnosticSeverity::ERROR)));
+    }
+
+    #[test]
+    fn test_format_diagnostic() {
+        let diagnostic = Diagnostic {
+            range: Range::new(Position::new(2, 2), Position::new(2, 7)),
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: Some(NumberOrString::String("duplicate-send".to_string())),
+            message: "Duplicate send `x!(1)` in the same parallel composition".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            format_diagnostic(Path::new("src/dup.rho"), &diagnostic),
+            "src/dup.rho:3:3: warning: Duplicate send `x!(1)` in the same parallel composition [RHO101]"
+        );
+    }
+}
@@ -0,0 +1,317 @@
+//! Live analysis statistics over HTTP (`--debug-console PORT`)
+//!
+//! A minimal HTTP endpoint on `127.0.0.1:PORT` for performance
+//! investigations without attaching a profiler:
+//!
+//! - `GET /` renders plain-text tables of the open documents of every
+//!   connection with their IR sizes, the parse cache and parse statistics,
+//!   the depth of the background queues, and the latencies of the most
+//!   recent requests. Browsers reload it every two seconds; in a terminal use
+//!   `watch -n1 curl -s localhost:PORT`;
+//! - `GET /json` returns the same [`ConsoleSnapshot`] as JSON.
+//!
+//! Request latencies are measured at the transport, from the arrival of a
+//! request to its response, so they include the time spent queued.
+
+use std::io;
+use std::sync::Arc;
+use std::time::Instant;
+
+use dashmap::DashMap;
+use serde::Serialize;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{debug, info, warn};
+
+use crate::lsp::backend::RholangBackend;
+use crate::lsp::shared_documents::ConnectionId;
+use crate::metrics::metrics;
+use crate::parsers::rholang::parse_cache_stats;
+
+/// Seconds between reloads of the text page in a browser
+const REFRESH_SECS: u32 = 2;
+
+/// Statistics of one open document
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentStats {
+    pub uri: String,
+    pub language: String,
+    pub version: i32,
+    pub bytes: usize,
+    pub lines: usize,
+    /// Nodes of the document's IR; 0 until it is indexed
+    pub ir_nodes: usize,
+    pub incremental_parses: usize,
+    pub full_parses: usize,
+}
+
+/// Items waiting in the background queues of a connection
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueDepths {
+    pub document_changes: usize,
+    pub indexing: usize,
+    pub diagnostics: usize,
+    pub analysis_running: usize,
+    pub analysis_threads: usize,
+}
+
+/// Statistics of one client connection
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionStats {
+    pub connection: ConnectionId,
+    pub documents: Vec<DocumentStats>,
+    pub indexed_documents: usize,
+    pub queues: QueueDepths,
+}
+
+/// Process-wide cache and parse statistics
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheStats {
+    pub parse_cache_entries: usize,
+    pub parse_cache_capacity: usize,
+    pub parse_cache_hit_rate: f64,
+    pub incremental_parses: u64,
+    pub full_parses: u64,
+    pub incremental_parse_rate: f64,
+}
+
+/// A recently answered request
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentRequest {
+    pub method: String,
+    pub latency_ms: f64,
+    /// Milliseconds since the response was sent
+    pub age_ms: u64,
+}
+
+/// Everything shown by the debug console
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsoleSnapshot {
+    pub uptime_secs: u64,
+    pub caches: CacheStats,
+    pub connections: Vec<ConnectionStats>,
+    /// Newest first
+    pub recent_requests: Vec<RecentRequest>,
+}
+
+/// Backends of the live connections, shared with the transport
+#[derive(Clone)]
+pub struct DebugConsole {
+    started: Instant,
+    backends: Arc<DashMap<ConnectionId, RholangBackend>>,
+}
+
+impl Default for DebugConsole {
+    fn default() -> Self {
+        DebugConsole { started: Instant::now(), backends: Arc::new(DashMap::new()) }
+    }
+}
+
+impl std::fmt::Debug for DebugConsole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DebugConsole").field("connections", &self.backends.len()).finish()
+    }
+}
+
+impl DebugConsole {
+    /// Shows `backend` until [`DebugConsole::unregister`] is called for `connection`.
+    pub fn register(&self, connection: ConnectionId, backend: RholangBackend) {
+        self.backends.insert(connection, backend);
+    }
+
+    pub fn unregister(&self, connection: ConnectionId) {
+        self.backends.remove(&connection);
+    }
+
+    /// Current statistics of the process and every connection.
+    pub async fn snapshot(&self) -> ConsoleSnapshot {
+        let mut backends: Vec<RholangBackend> = self.backends.iter().map(|entry| entry.value().clone()).collect();
+        backends.sort_by_key(|backend| backend.connection_id());
+        let mut connections = Vec::with_capacity(backends.len());
+        for backend in backends {
+            connections.push(backend.debug_stats().await);
+        }
+
+        let summary = metrics().summary();
+        let cache = parse_cache_stats();
+        let caches = CacheStats {
+            parse_cache_entries: cache.size,
+            parse_cache_capacity: cache.capacity,
+            parse_cache_hit_rate: summary.parse_cache_hit_rate,
+            incremental_parses: summary.incremental_parses,
+            full_parses: summary.full_parses,
+            incremental_parse_rate: summary.incremental_parse_rate,
+        };
+        let recent_requests = metrics()
+            .recent_requests()
+            .into_iter()
+            .rev()
+            .map(|request| RecentRequest {
+                method: request.method,
+                latency_ms: request.micros as f64 / 1000.0,
+                age_ms: request.finished.elapsed().as_millis() as u64,
+            })
+            .collect();
+
+        ConsoleSnapshot { uptime_secs: self.started.elapsed().as_secs(), caches, connections, recent_requests }
+    }
+
+    /// Binds `127.0.0.1:port` and serves the console on a background task.
+    pub async fn serve(&self, port: u16) -> io::Result<()> {
+        let listener = TcpListener::bind(("127.0.0.1", port)).await?;
+        info!("Debug console listening on http://{}", listener.local_addr()?);
+        let console = self.clone();
+        tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((stream, peer)) => {
+                        let console = console.clone();
+                        tokio::spawn(async move {
+                            if let Err(e) = console.respond(stream).await {
+                                debug!("Debug console request from {} failed: {}", peer, e);
+                            }
+                        });
+                    }
+                    Err(e) => warn!("Failed to accept debug console connection: {}", e),
+                }
+            }
+        });
+        Ok(())
+    }
+
+    async fn respond(&self, stream: TcpStream) -> io::Result<()> {
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).await?;
+        // Headers are not needed
+        loop {
+            let mut header = String::new();
+            if reader.read_line(&mut header).await? == 0 || header.trim().is_empty() {
+                break;
+            }
+        }
+
+        let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+        let (status, content_type, body) = match path {
+            "/" => ("200 OK", "text/plain; charset=utf-8", render_text(&self.snapshot().await)),
+            "/json" => (
+                "200 OK",
+                "application/json",
+                serde_json::to_string_pretty(&self.snapshot().await).map_err(io::Error::other)?,
+            ),
+            _ => ("404 Not Found", "text/plain; charset=utf-8", "Not found; try / or /json\n".to_string()),
+        };
+        let refresh = if path == "/" { format!("Refresh: {}\r\n", REFRESH_SECS) } else { String::new() };
+        let head = format!(
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\n{}Connection: close\r\n\r\n",
+            status,
+            content_type,
+            body.len(),
+            refresh
+        );
+        let mut stream = reader.into_inner();
+        stream.write_all(head.as_bytes()).await?;
+        stream.write_all(body.as_bytes()).await?;
+        stream.shutdown().await
+    }
+}
+
+/// `snapshot` as plain-text tables.
+pub fn render_text(snapshot: &ConsoleSnapshot) -> String {
+    let mut out = format!("rholang-language-server debug console (up {}s)\n\n", snapshot.uptime_secs);
+    let caches = &snapshot.caches;
+    out.push_str(&format!(
+        "Parse cache   {}/{} entries, {:.1}% hits\nParses        {} incremental, {} full ({:.1}% incremental)\n",
+        caches.parse_cache_entries,
+        caches.parse_cache_capacity,
+        caches.parse_cache_hit_rate * 100.0,
+        caches.incremental_parses,
+        caches.full_parses,
+        caches.incremental_parse_rate * 100.0
+    ));
+
+    for connection in &snapshot.connections {
+        let queues = &connection.queues;
+        out.push_str(&format!(
+            "\nConnection {}: {} open, {} indexed\nQueues        changes {}, indexing {}, diagnostics {}, analysis {}/{} threads busy\n",
+            connection.connection,
+            connection.documents.len(),
+            connection.indexed_documents,
+            queues.document_changes,
+            queues.indexing,
+            queues.diagnostics,
+            queues.analysis_running,
+            queues.analysis_threads
+        ));
+        out.push_str(&format!(
+            "  {:>7} {:>9} {:>7} {:>9} {:>11}  {:<8} {}\n",
+            "version", "bytes", "lines", "IR nodes", "incr/full", "language", "uri"
+        ));
+        for document in &connection.documents {
+            out.push_str(&format!(
+                "  {:>7} {:>9} {:>7} {:>9} {:>11}  {:<8} {}\n",
+                document.version,
+                document.bytes,
+                document.lines,
+                document.ir_nodes,
+                format!("{}/{}", document.incremental_parses, document.full_parses),
+                document.language,
+                document.uri
+            ));
+        }
+    }
+
+    out.push_str(&format!("\nRecent requests ({})\n", snapshot.recent_requests.len()));
+    out.push_str(&format!("  {:>10} {:>8}  {}\n", "latency ms", "ago s", "method"));
+    for request in &snapshot.recent_requests {
+        out.push_str(&format!(
+            "  {:>10.2} {:>8.1}  {}\n",
+            request.latency_ms,
+            request.age_ms as f64 / 1000.0,
+            request.method
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_text() {
+        let snapshot = ConsoleSnapshot {
+            uptime_secs: 12,
+            caches: CacheStats { parse_cache_entries: 3, parse_cache_capacity: 1000, parse_cache_hit_rate: 0.5, ..Default::default() },
+            connections: vec![ConnectionStats {
+                connection: 1,
+                documents: vec![DocumentStats {
+                    uri: "file:///a.rho".to_string(),
+                    language: "Rholang".to_string(),
+                    version: 4,
+                    bytes: 120,
+                    lines: 6,
+                    ir_nodes: 42,
+                    incremental_parses: 3,
+                    full_parses: 1,
+                }],
+                indexed_documents: 2,
+                queues: QueueDepths { indexing: 5, analysis_threads: 4, ..Default::default() },
+            }],
+            recent_requests: vec![RecentRequest { method: "textDocument/hover".to_string(), latency_ms: 1.25, age_ms: 300 }],
+        };
+
+        let text = render_text(&snapshot);
+        assert!(text.contains("3/1000 entries, 50.0% hits"));
+        assert!(text.contains("Connection 1: 1 open, 2 indexed"));
+        assert!(text.contains("indexing 5"));
+        assert!(text.contains("      4       120       6        42         3/1  Rholang  file:///a.rho"));
+        assert!(text.contains("      1.25      0.3  textDocument/hover"));
+    }
+}
@@ -0,0 +1,240 @@
+//! Deterministic mode for reproducible integration tests
+//!
+//! The mode is off unless a test turns it on explicitly, before the server
+//! starts: in process with [`enable`], or with the server's
+//! `--deterministic [SEED]` flag (seed 0 when omitted). Nothing in the
+//! environment turns it on.
+//!
+//! In this mode the server:
+//!
+//! - runs every debounce and batching window on a [`VirtualClock`] that only
+//!   moves when the test advances it, in process through [`clock`] or over
+//!   the connection with [`ADVANCE_CLOCK_METHOD`], so debounced work
+//!   (validation, symbol linking, refreshes, diagnostics flushes) runs exactly
+//!   when a test lets the window elapse instead of after a wall-clock delay;
+//! - processes the events of a debounce window in URI order rather than in
+//!   hash map order;
+//! - starts its generated ids (document ids, semantic tokens result ids) at
+//!   the seed;
+//! - runs the Tokio runtime with one worker thread, and the rayon and
+//!   analysis pools with one thread each;
+//! - never cuts an analysis pass short at its time budget.
+//!
+//! It is meant for tests only: a real client never advances the clock, so
+//! its edits would never be validated.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::time::{Duration, Instant};
+
+use once_cell::sync::OnceCell;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+
+/// Custom request advancing the virtual clock in deterministic mode
+pub const ADVANCE_CLOCK_METHOD: &str = "rholang/advanceClock";
+
+static SEED: OnceCell<u64> = OnceCell::new();
+
+static CLOCK: OnceCell<VirtualClock> = OnceCell::new();
+
+/// A debounce or batching window, on the virtual clock in deterministic mode
+/// and on the Tokio clock otherwise.
+pub type Sleep = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// Turns deterministic mode on with `seed`. Call it before the server is
+/// built: ids and pools already created keep their settings. The mode cannot
+/// be turned off again, and a second call keeps the first seed.
+pub fn enable(seed: u64) {
+    SEED.get_or_init(|| seed);
+    CLOCK.get_or_init(VirtualClock::new);
+}
+
+/// Whether the server runs in deterministic mode.
+pub fn enabled() -> bool {
+    SEED.get().is_some()
+}
+
+/// First value of generated ids: the seed in deterministic mode, else 0.
+pub fn id_seed() -> u64 {
+    SEED.get().copied().unwrap_or(0)
+}
+
+/// The virtual clock, in deterministic mode only.
+pub fn clock() -> Option<&'static VirtualClock> {
+    CLOCK.get()
+}
+
+/// The current time of the clock debounce windows run on.
+pub fn now() -> Instant {
+    match clock() {
+        Some(clock) => clock.now(),
+        None => Instant::now(),
+    }
+}
+
+/// A debounce or batching window of length `window`.
+pub fn sleep(window: Duration) -> Sleep {
+    match clock() {
+        Some(clock) => clock.sleep(window),
+        None => Box::pin(tokio::time::sleep(window)),
+    }
+}
+
+/// Number of threads for a pool that would otherwise get `threads`.
+pub fn threads(threads: usize) -> usize {
+    if enabled() {
+        1
+    } else {
+        threads
+    }
+}
+
+/// Parameters of [`ADVANCE_CLOCK_METHOD`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdvanceClockParams {
+    /// How far to advance the clock; when absent, up to its next timer
+    #[serde(default)]
+    pub milliseconds: Option<u64>,
+}
+
+/// Result of [`ADVANCE_CLOCK_METHOD`]: the clock after advancing it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClockReading {
+    /// Virtual time elapsed since the server started
+    pub elapsed_ms: u64,
+    /// Windows still waiting for the clock to reach them
+    pub pending_timers: usize,
+}
+
+/// A clock that stands still until it is advanced, firing the windows that
+/// fall due on the way.
+pub struct VirtualClock {
+    epoch: Instant,
+    state: Mutex<ClockState>,
+}
+
+#[derive(Default)]
+struct ClockState {
+    elapsed: Duration,
+    timers: Vec<(Duration, oneshot::Sender<()>)>,
+}
+
+impl VirtualClock {
+    pub fn new() -> Self {
+        Self {
+            epoch: Instant::now(),
+            state: Mutex::new(ClockState::default()),
+        }
+    }
+
+    /// Virtual time elapsed since the clock was created.
+    pub fn elapsed(&self) -> Duration {
+        self.state.lock().elapsed
+    }
+
+    /// The current virtual time.
+    pub fn now(&self) -> Instant {
+        self.epoch + self.elapsed()
+    }
+
+    /// A window that ends once the clock has advanced by `window`.
+    pub fn sleep(&self, window: Duration) -> Sleep {
+        if window.is_zero() {
+            return Box::pin(std::future::ready(()));
+        }
+        let (sender, receiver) = oneshot::channel();
+        let mut state = self.state.lock();
+        // Windows dropped before ending (superseded debounces) never fire
+        state.timers.retain(|(_, sender)| !sender.is_closed());
+        let deadline = state.elapsed + window;
+        state.timers.push((deadline, sender));
+        Box::pin(async move {
+            let _ = receiver.await;
+        })
+    }
+
+    /// Virtual time at which the next pending window ends.
+    pub fn next_deadline(&self) -> Option<Duration> {
+        let mut state = self.state.lock();
+        state.timers.retain(|(_, sender)| !sender.is_closed());
+        state.timers.iter().map(|(deadline, _)| *deadline).min()
+    }
+
+    /// Moves the clock forward to `target` (never backwards), ending the
+    /// windows due by then in deadline order.
+    pub fn advance_to(&self, target: Duration) {
+        let mut state = self.state.lock();
+        if target > state.elapsed {
+            state.elapsed = target;
+        }
+        let elapsed = state.elapsed;
+        let (mut due, pending): (Vec<_>, Vec<_>) =
+            state.timers.drain(..).partition(|(deadline, _)| *deadline <= elapsed);
+        state.timers = pending;
+        drop(state);
+
+        due.sort_by_key(|(deadline, _)| *deadline);
+        for (_, sender) in due {
+            let _ = sender.send(());
+        }
+    }
+
+    /// The clock's time and number of pending windows.
+    pub fn reading(&self) -> ClockReading {
+        let pending_timers = {
+            let mut state = self.state.lock();
+            state.timers.retain(|(_, sender)| !sender.is_closed());
+            state.timers.len()
+        };
+        ClockReading {
+            elapsed_ms: self.elapsed().as_millis() as u64,
+            pending_timers,
+        }
+    }
+}
+
+impl Default for VirtualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::FutureExt;
+
+    use super::*;
+
+    #[test]
+    fn test_virtual_clock_fires_windows_when_advanced() {
+        let clock = VirtualClock::new();
+        let start = clock.now();
+        let mut short = clock.sleep(Duration::from_millis(50));
+        let mut long = clock.sleep(Duration::from_millis(300));
+        assert!((&mut short).now_or_never().is_none());
+
+        assert_eq!(clock.next_deadline(), Some(Duration::from_millis(50)));
+        clock.advance_to(Duration::from_millis(50));
+        assert!((&mut short).now_or_never().is_some());
+        assert!((&mut long).now_or_never().is_none());
+        assert_eq!(clock.now() - start, Duration::from_millis(50));
+
+        assert_eq!(clock.next_deadline(), Some(Duration::from_millis(300)));
+        clock.advance_to(Duration::from_secs(1));
+        assert!(long.now_or_never().is_some());
+        assert_eq!(clock.reading(), ClockReading { elapsed_ms: 1000, pending_timers: 0 });
+    }
+
+    #[test]
+    fn test_virtual_clock_forgets_dropped_windows() {
+        let clock = VirtualClock::new();
+        drop(clock.sleep(Duration::from_millis(100)));
+        let _kept = clock.sleep(Duration::from_millis(200));
+        assert_eq!(clock.next_deadline(), Some(Duration::from_millis(200)));
+        assert!(clock.sleep(Duration::ZERO).now_or_never().is_some());
+    }
+}
@@ -0,0 +1,426 @@
+//! Write-ahead journal of document edits for crash forensics
+//!
+//! With `--edit-journal`, the server appends one JSON line per document
+//! lifecycle event to `edits-<session>.log` in the log directory, *before*
+//! the event is applied:
+//!
+//! ```text
+//! {"ts":"2025-10-29T15:19:49.123Z","event":"open","uri":"file:///a.rho","version":1,"length":120,"hash":"9f2c41d07a6b1e35","encoding":"utf-16"}
+//! {"ts":"2025-10-29T15:19:50.004Z","event":"change","uri":"file:///a.rho","version":2,"changes":[{"range":...,"text":"x"}]}
+//! {"ts":"2025-10-29T15:19:58.310Z","event":"close","uri":"file:///a.rho"}
+//! ```
+//!
+//! Only `didChange` deltas are journaled; opened documents are identified by
+//! length and hash, and their text comes from the wire log (`--wire-log`) or
+//! a file on disk. The journal is a ring of two segments: when the current
+//! one exceeds [`MAX_SEGMENT_BYTES`] it replaces `edits-<session>.1.log`.
+//!
+//! `rholang-language-server replay-journal <JOURNAL> --wire-log <WIRE_LOG>`
+//! rebuilds the documents as the server last saw them and prints them.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use ropey::Rope;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use time::format_description::BorrowedFormatItem;
+use time::macros::format_description;
+use tower_lsp::lsp_types::{TextDocumentContentChangeEvent, Url};
+use tracing::{info, warn};
+
+use crate::lsp::document::apply_text_changes;
+use crate::lsp::line_index::PositionEncoding;
+
+/// Size at which the current journal segment is rotated
+pub const MAX_SEGMENT_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Timestamp in journal file names
+const FILE_TIMESTAMP: &[BorrowedFormatItem<'static>] = format_description!("[year][month][day]-[hour][minute][second]");
+
+/// Timestamp of journal entries
+const ENTRY_TIMESTAMP: &[BorrowedFormatItem<'static>] =
+    format_description!("[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:3]Z");
+
+/// A journaled document event
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "event", rename_all = "camelCase")]
+pub enum JournalEvent {
+    Open {
+        uri: Url,
+        version: i32,
+        /// Length of the opened text in bytes
+        length: usize,
+        /// [`content_hash`] of the opened text
+        hash: String,
+        /// Code units of the columns of the document's changes
+        #[serde(default)]
+        encoding: PositionEncoding,
+    },
+    Change {
+        uri: Url,
+        version: i32,
+        changes: Vec<TextDocumentContentChangeEvent>,
+    },
+    Close {
+        uri: Url,
+    },
+}
+
+#[derive(Serialize)]
+struct JournalEntry<'a> {
+    ts: String,
+    #[serde(flatten)]
+    event: &'a JournalEvent,
+}
+
+/// Stable FNV-1a hash of a document text, as 16 hex digits
+pub fn content_hash(text: &str) -> String {
+    let hash = text.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    });
+    format!("{:016x}", hash)
+}
+
+struct Segment {
+    file: fs::File,
+    path: PathBuf,
+    written: u64,
+}
+
+/// Edit journal shared by all connections; a no-op unless enabled
+#[derive(Clone)]
+pub struct EditJournal {
+    segment: Arc<Mutex<Option<Segment>>>,
+}
+
+impl EditJournal {
+    /// A journal that records nothing
+    pub fn disabled() -> Self {
+        EditJournal { segment: Arc::new(Mutex::new(None)) }
+    }
+
+    /// Create a journal at `edits-<timestamp>-<pid>.log` in `log_dir`
+    pub fn new(log_dir: &Path) -> io::Result<Self> {
+        let timestamp = time::OffsetDateTime::now_utc()
+            .format(FILE_TIMESTAMP)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        let path = log_dir.join(format!("edits-{}-{}.log", timestamp, std::process::id()));
+        let journal = Self::at(path.clone())?;
+        info!("Edit journal: {:?}", path);
+        Ok(journal)
+    }
+
+    /// Create a journal writing to `path`
+    pub fn at(path: PathBuf) -> io::Result<Self> {
+        let file = fs::OpenOptions::new().create(true).append(true).open(&path)?;
+        let written = file.metadata()?.len();
+        Ok(EditJournal { segment: Arc::new(Mutex::new(Some(Segment { file, path, written }))) })
+    }
+
+    /// Check if journaling is enabled
+    pub fn is_enabled(&self) -> bool {
+        self.segment.lock().map(|segment| segment.is_some()).unwrap_or(false)
+    }
+
+    pub fn record_open(&self, uri: &Url, version: i32, text: &str, encoding: PositionEncoding) {
+        if self.is_enabled() {
+            self.record(&JournalEvent::Open {
+                uri: uri.clone(),
+                version,
+                length: text.len(),
+                hash: content_hash(text),
+                encoding,
+            });
+        }
+    }
+
+    pub fn record_change(&self, uri: &Url, version: i32, changes: &[TextDocumentContentChangeEvent]) {
+        if self.is_enabled() {
+            self.record(&JournalEvent::Change { uri: uri.clone(), version, changes: changes.to_vec() });
+        }
+    }
+
+    pub fn record_close(&self, uri: &Url) {
+        if self.is_enabled() {
+            self.record(&JournalEvent::Close { uri: uri.clone() });
+        }
+    }
+
+    fn record(&self, event: &JournalEvent) {
+        let Ok(mut guard) = self.segment.lock() else {
+            return;
+        };
+        let Some(segment) = guard.as_mut() else {
+            return;
+        };
+        // Formatting a UTC time with a fixed description cannot fail
+        let ts = time::OffsetDateTime::now_utc().format(ENTRY_TIMESTAMP).unwrap_or_default();
+        let Ok(line) = serde_json::to_string(&JournalEntry { ts, event }) else {
+            return;
+        };
+        if segment.written + line.len() as u64 + 1 > MAX_SEGMENT_BYTES && segment.written > 0 {
+            if let Err(e) = segment.rotate() {
+                warn!("Failed to rotate edit journal {:?}: {}", segment.path, e);
+            }
+        }
+        // Flushed line by line so the journal survives a crash
+        if writeln!(segment.file, "{}", line).and_then(|_| segment.file.flush()).is_ok() {
+            segment.written += line.len() as u64 + 1;
+        }
+    }
+}
+
+impl Segment {
+    /// Moves the current segment to the previous-segment path and starts a new one.
+    fn rotate(&mut self) -> io::Result<()> {
+        fs::rename(&self.path, previous_segment(&self.path))?;
+        self.file = fs::OpenOptions::new().create(true).append(true).open(&self.path)?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+// Implement Debug to avoid exposing internal file handle
+impl std::fmt::Debug for EditJournal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EditJournal").field("enabled", &self.is_enabled()).finish()
+    }
+}
+
+/// `edits-X.log` → `edits-X.1.log`
+pub fn previous_segment(path: &Path) -> PathBuf {
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+    path.with_file_name(format!("{}.1.log", stem))
+}
+
+/// Reads the events of a journal, oldest first, including its previous segment.
+pub fn read_journal(path: &Path) -> io::Result<Vec<JournalEvent>> {
+    let mut text = fs::read_to_string(previous_segment(path)).unwrap_or_default();
+    text.push_str(&fs::read_to_string(path)?);
+    // A crash may leave the last line truncated; skip anything unparseable
+    Ok(text.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+}
+
+/// Texts of `textDocument/didOpen` notifications in a wire log, last one per URI.
+pub fn opened_texts(wire_log: &str) -> BTreeMap<Url, String> {
+    let mut texts = BTreeMap::new();
+    for line in wire_log.lines().filter(|line| line.starts_with('{')) {
+        let Ok(message) = serde_json::from_str::<Value>(line) else {
+            continue;
+        };
+        if message.get("method").and_then(Value::as_str) != Some("textDocument/didOpen") {
+            continue;
+        }
+        let document = &message["params"]["textDocument"];
+        let uri = document["uri"].as_str().and_then(|uri| Url::parse(uri).ok());
+        if let (Some(uri), Some(text)) = (uri, document["text"].as_str()) {
+            texts.insert(uri, text.to_string());
+        }
+    }
+    texts
+}
+
+/// A document rebuilt from the journal
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayedDocument {
+    pub uri: Url,
+    pub version: i32,
+    pub text: String,
+    /// Whether the base text matched the length and hash journaled at open
+    pub base_matches: bool,
+    /// Whether the journal ends with the document closed
+    pub closed: bool,
+}
+
+/// Replays the journaled changes of each document onto its opened text.
+///
+/// `base` gives the text of a document when it was opened; documents
+/// without one are skipped. Replay of a document stops after `until_version`.
+pub fn replay(
+    events: &[JournalEvent],
+    base: impl Fn(&Url) -> Option<String>,
+    until_version: Option<i32>,
+) -> Vec<ReplayedDocument> {
+    let mut documents: BTreeMap<Url, (Rope, PositionEncoding, ReplayedDocument)> = BTreeMap::new();
+    for event in events {
+        match event {
+            JournalEvent::Open { uri, version, length, hash, encoding } => {
+                let Some(text) = base(uri) else {
+                    documents.remove(uri);
+                    continue;
+                };
+                let base_matches = text.len() == *length && content_hash(&text) == *hash;
+                let document = ReplayedDocument {
+                    uri: uri.clone(),
+                    version: *version,
+                    text: String::new(),
+                    base_matches,
+                    closed: false,
+                };
+                documents.insert(uri.clone(), (Rope::from_str(&text), *encoding, document));
+            }
+            JournalEvent::Change { uri, version, changes } => {
+                if let Some((rope, encoding, document)) = documents.get_mut(uri) {
+                    if until_version.is_some_and(|until| *version > until) {
+                        continue;
+                    }
+                    apply_text_changes(rope, changes, *encoding);
+                    document.version = *version;
+                }
+            }
+            JournalEvent::Close { uri } => {
+                if let Some((_, _, document)) = documents.get_mut(uri) {
+                    document.closed = true;
+                }
+            }
+        }
+    }
+    documents
+        .into_values()
+        .map(|(rope, _, mut document)| {
+            document.text = rope.to_string();
+            document
+        })
+        .collect()
+}
+
+/// Arguments of the `replay-journal` subcommand
+#[derive(clap::Args, Debug, Clone)]
+pub struct ReplayJournalArgs {
+    /// Edit journal (`edits-<session>.log`)
+    pub journal: PathBuf,
+    /// Wire log of the same session, for the text of opened documents
+    #[arg(long, value_name = "WIRE_LOG")]
+    pub wire_log: Option<PathBuf>,
+    /// Only replay this document
+    #[arg(long)]
+    pub uri: Option<Url>,
+    /// Stop after this document version
+    #[arg(long, value_name = "VERSION")]
+    pub until_version: Option<i32>,
+}
+
+/// Runs `replay-journal`, printing the rebuilt documents, and returns the exit code.
+///
+/// Without a wire log, opened texts are read from the documents' files.
+pub fn run_replay(args: ReplayJournalArgs) -> io::Result<i32> {
+    let events = read_journal(&args.journal)?;
+    let opened = match &args.wire_log {
+        Some(path) => Some(opened_texts(&fs::read_to_string(path)?)),
+        None => None,
+    };
+    let base = |uri: &Url| match &opened {
+        Some(opened) => opened.get(uri).cloned(),
+        None => uri.to_file_path().ok().and_then(|path| fs::read_to_string(path).ok()),
+    };
+
+    let documents: Vec<_> = replay(&events, base, args.until_version)
+        .into_iter()
+        .filter(|document| args.uri.as_ref().is_none_or(|uri| *uri == document.uri))
+        .collect();
+    if documents.is_empty() {
+        eprintln!("No replayable documents in {:?}", args.journal);
+        return Ok(1);
+    }
+    for document in &documents {
+        if !document.base_matches {
+            eprintln!("warning: opened text of {} differs from the journaled hash; replay may be inexact", document.uri);
+        }
+        if documents.len() > 1 {
+            println!("==> {} (version {}{}) <==", document.uri, document.version, if document.closed { ", closed" } else { "" });
+        }
+        print!("{}", document.text);
+        if documents.len() > 1 {
+            println!();
+        }
+    }
+    Ok(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp::lsp_types::{Position, Range};
+
+    fn uri() -> Url {
+        Url::parse("file:///a.rho").unwrap()
+    }
+
+    fn insert(line: u32, character: u32, text: &str) -> TextDocumentContentChangeEvent {
+        TextDocumentContentChangeEvent {
+            range: Some(Range::new(Position::new(line, character), Position::new(line, character))),
+            range_length: None,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_journal_round_trip_and_replay() {
+        let dir = std::env::temp_dir().join(format!("edit-journal-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("edits-test.log");
+        let _ = fs::remove_file(&path);
+
+        let base = "new x in {\n  Nil\n}";
+        let journal = EditJournal::at(path.clone()).unwrap();
+        journal.record_open(&uri(), 1, base, PositionEncoding::Utf16);
+        journal.record_change(&uri(), 2, &[insert(1, 5, " | x!(1)")]);
+        journal.record_change(&uri(), 3, &[insert(1, 2, "// hi\n  ")]);
+        journal.record_close(&uri());
+
+        let events = read_journal(&path).unwrap();
+        assert_eq!(events.len(), 4);
+
+        let replayed = replay(&events, |_| Some(base.to_string()), None);
+        assert_eq!(replayed.len(), 1);
+        assert_eq!(replayed[0].text, "new x in {\n  // hi\n  Nil | x!(1)\n}");
+        assert_eq!(replayed[0].version, 3);
+        assert!(replayed[0].base_matches && replayed[0].closed);
+
+        let replayed = replay(&events, |_| Some(base.to_string()), Some(2));
+        assert_eq!(replayed[0].text, "new x in {\n  Nil | x!(1)\n}");
+
+        let replayed = replay(&events, |_| Some("changed on disk".to_string()), None);
+        assert!(!replayed[0].base_matches);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_replay_uses_journaled_encoding() {
+        let open = |encoding| JournalEvent::Open { uri: uri(), version: 1, length: 0, hash: String::new(), encoding };
+        let change = JournalEvent::Change { uri: uri(), version: 2, changes: vec![insert(0, 2, "!")] };
+        // `é` is one UTF-16 unit but two UTF-8 bytes
+        let replayed = replay(&[open(PositionEncoding::Utf16), change.clone()], |_| Some("éx".to_string()), None);
+        assert_eq!(replayed[0].text, "éx!");
+        let replayed = replay(&[open(PositionEncoding::Utf8), change], |_| Some("éx".to_string()), None);
+        assert_eq!(replayed[0].text, "é!x");
+
+        // Journals without an encoding are UTF-16
+        let event: JournalEvent =
+            serde_json::from_str(r#"{"event":"open","uri":"file:///a.rho","version":1,"length":0,"hash":""}"#).unwrap();
+        assert_eq!(event, open(PositionEncoding::Utf16));
+    }
+
+    #[test]
+    fn test_opened_texts_from_wire_log() {
+        let wire_log = concat!(
+            "[2025-10-29T15:19:49.123Z] <<< NOTIFICATION \n",
+            "Content-Length: 120\r\n",
+            "\r\n",
+            r#"{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{"textDocument":{"uri":"file:///a.rho","languageId":"rholang","version":1,"text":"Nil"}}}"#,
+            "\n\n",
+        );
+        let texts = opened_texts(wire_log);
+        assert_eq!(texts.get(&uri()).map(String::as_str), Some("Nil"));
+    }
+
+    #[test]
+    fn test_previous_segment() {
+        assert_eq!(previous_segment(Path::new("/logs/edits-1-2.log")), PathBuf::from("/logs/edits-1-2.1.log"));
+    }
+}
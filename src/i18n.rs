@@ -0,0 +1,263 @@
+//! Localization of user-visible messages
+//!
+//! Diagnostic messages, hover boilerplate and code action titles are looked
+//! up by key in message catalogs instead of being written inline:
+//!
+//! ```rust,ignore
+//! let message = i18n::tr("lint.duplicate-send", &[("send", &text)]);
+//! ```
+//!
+//! A catalog is a TOML file named after its locale (`fr.toml`, `pt-BR.toml`)
+//! whose tables group keys (`[lint]` + `duplicate-send = "..."` is the key
+//! `lint.duplicate-send`); `{name}` placeholders are replaced with values.
+//! English is built in (`locales/en.toml`). Further catalogs are read from
+//! the directory in `RHOLANG_LOCALES_DIR`, the `localesPath` initialization
+//! option, or `locales/` in the user config directory, so translations can
+//! be added without rebuilding the server.
+//!
+//! The locale comes from the `locale` sent in `initialize`. A message
+//! missing from the catalog of `pt-BR` is taken from `pt`, then English.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use tracing::{debug, info, warn};
+
+/// Locale of the built-in catalog, used for missing messages
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// Environment variable naming a directory of catalogs
+pub const LOCALES_DIR_ENV: &str = "RHOLANG_LOCALES_DIR";
+
+/// Initialization option naming a directory of catalogs
+pub const LOCALES_PATH_OPTION: &str = "localesPath";
+
+const ENGLISH: &str = include_str!("../locales/en.toml");
+
+/// Messages of one locale, by dotted key
+#[derive(Debug, Clone, Default)]
+pub struct Catalog {
+    messages: HashMap<String, String>,
+}
+
+impl Catalog {
+    /// Parses a TOML catalog.
+    pub fn parse(source: &str) -> Result<Self, toml::de::Error> {
+        let table: toml::Table = toml::from_str(source)?;
+        let mut messages = HashMap::new();
+        flatten(&table, "", &mut messages);
+        Ok(Catalog { messages })
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.messages.get(key).map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+}
+
+fn flatten(table: &toml::Table, prefix: &str, messages: &mut HashMap<String, String>) {
+    for (key, value) in table {
+        let key = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+        match value {
+            toml::Value::String(message) => {
+                messages.insert(key, message.clone());
+            }
+            toml::Value::Table(table) => flatten(table, &key, messages),
+            _ => warn!("Ignoring non-string message {}", key),
+        }
+    }
+}
+
+/// Catalogs of a locale and its fallbacks, most specific first
+#[derive(Debug, Clone)]
+pub struct Localizer {
+    locale: String,
+    catalogs: Vec<Catalog>,
+}
+
+impl Default for Localizer {
+    fn default() -> Self {
+        Localizer::english()
+    }
+}
+
+impl Localizer {
+    /// The built-in English messages.
+    pub fn english() -> Self {
+        let english = Catalog::parse(ENGLISH).expect("built-in English catalog is valid TOML");
+        Localizer { locale: DEFAULT_LOCALE.to_string(), catalogs: vec![english] }
+    }
+
+    /// Messages for `locale`, read from catalogs in `dirs` and falling back to English.
+    pub fn for_locale(locale: &str, dirs: &[PathBuf]) -> Self {
+        let mut localizer = Localizer::english();
+        let mut fallbacks = Vec::new();
+        for candidate in locale_chain(locale).into_iter().take_while(|candidate| candidate != DEFAULT_LOCALE) {
+            if let Some(catalog) = dirs.iter().find_map(|dir| load_catalog(dir, &candidate)) {
+                fallbacks.push(catalog);
+            }
+        }
+        if fallbacks.is_empty() {
+            debug!("No catalog for locale '{}'; using English", locale);
+        }
+        fallbacks.append(&mut localizer.catalogs);
+        localizer.catalogs = fallbacks;
+        localizer.locale = locale.to_string();
+        localizer
+    }
+
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    /// The message `key` with `{placeholders}` replaced by `args`; the key itself if unknown.
+    pub fn message(&self, key: &str, args: &[(&str, &dyn Display)]) -> String {
+        match self.catalogs.iter().find_map(|catalog| catalog.get(key)) {
+            Some(template) => substitute(template, args),
+            None => {
+                warn!("Missing message '{}'", key);
+                key.to_string()
+            }
+        }
+    }
+}
+
+/// `pt-BR` → `["pt-br", "pt"]`; `en_US` → `["en-us", "en"]`.
+fn locale_chain(locale: &str) -> Vec<String> {
+    let normalized = locale.trim().replace('_', "-").to_lowercase();
+    let mut chain = vec![normalized.clone()];
+    if let Some((language, _)) = normalized.split_once('-') {
+        chain.push(language.to_string());
+    }
+    chain
+}
+
+/// The catalog of `locale` in `dir`, matching file names case-insensitively.
+fn load_catalog(dir: &Path, locale: &str) -> Option<Catalog> {
+    let entries = std::fs::read_dir(dir).ok()?;
+    let path = entries.filter_map(Result::ok).map(|entry| entry.path()).find(|path| {
+        path.extension().is_some_and(|ext| ext == "toml")
+            && path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .is_some_and(|stem| stem.replace('_', "-").eq_ignore_ascii_case(locale))
+    })?;
+    let source = std::fs::read_to_string(&path).ok()?;
+    match Catalog::parse(&source) {
+        Ok(catalog) => {
+            info!("Loaded {} message(s) from {}", catalog.len(), path.display());
+            Some(catalog)
+        }
+        Err(e) => {
+            warn!("Ignoring invalid catalog {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+fn substitute(template: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let value = after
+            .find('}')
+            .and_then(|close| args.iter().find(|(name, _)| *name == &after[..close]).map(|(_, value)| (close, value)));
+        match value {
+            Some((close, value)) => {
+                out.push_str(&value.to_string());
+                rest = &after[close + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Directories searched for catalogs: `extra`, `RHOLANG_LOCALES_DIR`, then
+/// `locales/` in the user config directory.
+pub fn catalog_dirs(extra: Option<&Path>) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    found.extend(extra.map(Path::to_path_buf));
+    found.extend(std::env::var_os(LOCALES_DIR_ENV).map(PathBuf::from));
+    found.extend(dirs::config_dir().map(|dir| dir.join("f1r3fly-io").join("rholang-language-server").join("locales")));
+    found
+}
+
+/// Localizer used by [`tr`]
+static LOCALIZER: Lazy<RwLock<Arc<Localizer>>> = Lazy::new(|| RwLock::new(Arc::new(Localizer::english())));
+
+/// Switches messages to `locale` (English when `None`).
+pub fn set_locale(locale: Option<&str>, extra_dir: Option<&Path>) {
+    let localizer = match locale {
+        Some(locale) => Localizer::for_locale(locale, &catalog_dirs(extra_dir)),
+        None => Localizer::english(),
+    };
+    info!("Messages localized for '{}'", localizer.locale());
+    *LOCALIZER.write() = Arc::new(localizer);
+}
+
+/// The current localizer.
+pub fn localizer() -> Arc<Localizer> {
+    LOCALIZER.read().clone()
+}
+
+/// The message `key` in the current locale, with `{placeholders}` replaced by `args`.
+pub fn tr(key: &str, args: &[(&str, &dyn Display)]) -> String {
+    LOCALIZER.read().message(key, args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_english_messages() {
+        let english = Localizer::english();
+        assert_eq!(
+            english.message("lint.duplicate-send", &[("send", &"x!(1)")]),
+            "Duplicate send `x!(1)` in the same parallel composition"
+        );
+        assert_eq!(english.message("identifier.uri-name", &[]), "URI-bound name");
+        // Unknown placeholders are kept, unknown keys returned as is
+        assert_eq!(english.message("conventions.required-uri", &[]), "The top-level `new` should declare a name for `{uri}`");
+        assert_eq!(english.message("no.such-key", &[]), "no.such-key");
+    }
+
+    #[test]
+    fn test_locale_fallback_chain() {
+        let dir = std::env::temp_dir().join(format!("rholang-locales-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("pt.toml"), "[hover]\ndeprecated = \"**Obsoleto**\"\n").unwrap();
+        std::fs::write(dir.join("pt_BR.toml"), "[action]\nrename = \"Renomear '{name}' para '{suggestion}'\"\n")
+            .unwrap();
+
+        let localizer = Localizer::for_locale("pt-BR", &[dir.clone()]);
+        assert_eq!(
+            localizer.message("action.rename", &[("name", &"my_var"), ("suggestion", &"myVar")]),
+            "Renomear 'my_var' para 'myVar'"
+        );
+        assert_eq!(localizer.message("hover.deprecated", &[]), "**Obsoleto**");
+        assert_eq!(localizer.message("hover.pattern-examples", &[]), "**Matches values like**");
+
+        let unknown = Localizer::for_locale("de", &[dir.clone()]);
+        assert_eq!(unknown.message("hover.deprecated", &[]), "**Deprecated**");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
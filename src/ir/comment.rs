@@ -9,6 +9,7 @@
 //! position tracking, enabling:
 //! - Directive parsing (e.g., `// @metta` language markers)
 //! - Documentation extraction (e.g., `///` or `/**` doc comments)
+//! - Doc directives (e.g., `// @deprecated reason`, `// @expand template(args)`)
 //! - Source code reconstruction
 
 use crate::ir::rholang_node::node_types::CommentKind;
@@ -16,6 +17,12 @@ use crate::ir::semantic_node::{NodeBase, Position};
 use ropey::Rope;
 use tree_sitter::Node as TSNode;
 
+/// Doc directive names that are never treated as language directives
+pub const DOC_DIRECTIVES: &[&str] = &["deprecated", "template", "expand", "end", "param", "returns", "return"];
+
+/// Doc directives documenting a contract's parameters and return value
+pub const PARAM_DIRECTIVES: &[&str] = &["param", "returns", "return"];
+
 /// Represents a comment in the source code with position and content
 ///
 /// Comments are stored in a separate channel from the main IR tree,
@@ -159,6 +166,12 @@ impl CommentNode {
 
         // Check for @language directive
         if let Some(stripped) = content.strip_prefix('@') {
+            // `@param amount: …` is a doc directive whatever follows the colon
+            let keyword = stripped.split(|c: char| c == ':' || c.is_whitespace()).next().unwrap_or("");
+            if DOC_DIRECTIVES.contains(&keyword) {
+                return None;
+            }
+
             // Handle both `@metta` and `@language: metta` formats
             let lang = if let Some(colon_pos) = stripped.find(':') {
                 // Format: @language: metta
@@ -171,7 +184,7 @@ impl CommentNode {
             // Extract just the language name (stop at first whitespace)
             let lang_name = lang.split_whitespace().next()?;
 
-            if !lang_name.is_empty() {
+            if !lang_name.is_empty() && !DOC_DIRECTIVES.contains(&lang_name) {
                 return Some(lang_name.to_string());
             }
         }
@@ -179,6 +192,85 @@ impl CommentNode {
         None
     }
 
+    /// Parse a `@deprecated` doc directive from the comment text
+    ///
+    /// Recognizes `// @deprecated reason`, `/// @deprecated reason` and
+    /// `/* @deprecated reason */`, including on any line of a multi-line block
+    /// comment.
+    ///
+    /// # Returns
+    /// `Some(reason)` if the comment contains the directive (an empty string
+    /// when no reason is given), `None` otherwise
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let comment = CommentNode { text: "// @deprecated use transfer2".to_string(), .. };
+    /// assert_eq!(comment.deprecation(), Some("use transfer2".to_string()));
+    /// ```
+    pub fn deprecation(&self) -> Option<String> {
+        Self::extract_deprecation(&self.text)
+    }
+
+    /// Extract the `@deprecated` reason from comment text (internal helper)
+    fn extract_deprecation(text: &str) -> Option<String> {
+        let content = match text {
+            s if s.starts_with("//") => s.trim_start_matches('/'),
+            s if s.starts_with("/*") => s.trim_start_matches("/*").trim_end_matches("*/"),
+            _ => return None,
+        };
+
+        content.lines().find_map(|line| {
+            let line = line.trim().trim_start_matches('*').trim_start();
+            let rest = line.strip_prefix("@deprecated")?;
+            if rest.is_empty() || rest.starts_with(char::is_whitespace) {
+                Some(rest.trim().to_string())
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Parse `@param` and `@returns` directives from a regular comment
+    ///
+    /// Recognizes `// @param name description`, `// @returns description`
+    /// (or `@return`) and the same lines inside a `/* */` block comment. Doc
+    /// comments yield nothing: their tags are already part of `doc_text()`.
+    ///
+    /// # Returns
+    /// The directive lines, each starting with `@`, in order
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let comment = CommentNode { text: "// @param amount how much".to_string(), .. };
+    /// assert_eq!(comment.param_directives(), vec!["@param amount how much".to_string()]);
+    /// ```
+    pub fn param_directives(&self) -> Vec<String> {
+        if self.is_doc_comment {
+            return Vec::new();
+        }
+        Self::extract_param_directives(&self.text)
+    }
+
+    /// Extract `@param` and `@returns` lines from comment text (internal helper)
+    fn extract_param_directives(text: &str) -> Vec<String> {
+        let content = match text {
+            s if s.starts_with("//") => s.trim_start_matches('/'),
+            s if s.starts_with("/*") => s.trim_start_matches("/*").trim_end_matches("*/"),
+            _ => return Vec::new(),
+        };
+
+        content
+            .lines()
+            .filter_map(|line| {
+                let line = line.trim().trim_start_matches('*').trim();
+                let keyword = line.strip_prefix('@')?.split_whitespace().next()?;
+                PARAM_DIRECTIVES.contains(&keyword).then(|| line.to_string())
+            })
+            .collect()
+    }
+
     /// Get documentation text with comment delimiters stripped
     ///
     /// For line comments (`///`), strips the leading `///` and any trailing whitespace.
@@ -295,6 +387,46 @@ mod tests {
         assert_eq!(CommentNode::extract_directive("/* block comment */"), None);
     }
 
+    #[test]
+    fn test_extract_directive_ignores_doc_directives() {
+        assert_eq!(CommentNode::extract_directive("// @deprecated use bar"), None);
+        assert_eq!(CommentNode::extract_directive("// @expand logger(info, stdout)"), None);
+        assert_eq!(CommentNode::extract_directive("// @end"), None);
+    }
+
+    #[test]
+    fn test_extract_deprecation() {
+        assert_eq!(
+            CommentNode::extract_deprecation("// @deprecated use transfer2"),
+            Some("use transfer2".to_string())
+        );
+        assert_eq!(
+            CommentNode::extract_deprecation("/// @deprecated"),
+            Some(String::new())
+        );
+        assert_eq!(
+            CommentNode::extract_deprecation("/**\n * Old API\n * @deprecated  superseded by v2\n */"),
+            Some("superseded by v2".to_string())
+        );
+        assert_eq!(CommentNode::extract_deprecation("// @deprecatedly"), None);
+        assert_eq!(CommentNode::extract_deprecation("// not @deprecated"), None);
+    }
+
+    #[test]
+    fn test_extract_param_directives() {
+        assert_eq!(
+            CommentNode::extract_param_directives("// @param amount how much to move"),
+            vec!["@param amount how much to move".to_string()]
+        );
+        assert_eq!(
+            CommentNode::extract_param_directives("/*\n * @param from source vault\n * @returns true on success\n */"),
+            vec!["@param from source vault".to_string(), "@returns true on success".to_string()]
+        );
+        assert!(CommentNode::extract_param_directives("// @parameters are checked").is_empty());
+        assert!(CommentNode::extract_param_directives("// see @param below").is_empty());
+        assert_eq!(CommentNode::extract_directive("// @param amount: how much"), None);
+    }
+
     #[test]
     fn test_doc_text_line_comment() {
         let comment = CommentNode {
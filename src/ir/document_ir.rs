@@ -306,6 +306,77 @@ impl DocumentIR {
         consecutive_docs
     }
 
+    /// Get the `@deprecated` directive applying to a declaration at a position
+    ///
+    /// Looks at the block of consecutive comments (line, block or doc comments)
+    /// that ends on the line immediately before `pos` or earlier on the same
+    /// line, e.g.:
+    ///
+    /// ```text
+    /// // @deprecated use transfer2
+    /// contract transfer(@amount) = { Nil }
+    /// ```
+    ///
+    /// # Returns
+    /// The reason from the first `@deprecated` directive in the block (empty
+    /// if none was given), or `None` if the declaration is not deprecated
+    pub fn deprecation_before(&self, pos: &Position) -> Option<String> {
+        self.comment_block_before(pos).iter().find_map(|comment| comment.deprecation())
+    }
+
+    /// Get the `@param` and `@returns` directives of regular comments
+    /// documenting a declaration at a position
+    ///
+    /// Looks at the same comment block as `deprecation_before`, e.g.:
+    ///
+    /// ```text
+    /// // Moves funds between two vaults
+    /// // @param amount how much to move
+    /// // @returns true on success
+    /// contract transfer(@amount, ret) = { Nil }
+    /// ```
+    ///
+    /// # Returns
+    /// The directive lines in order, ready for `StructuredDocumentation::parse`
+    pub fn param_directives_before(&self, pos: &Position) -> Vec<String> {
+        self.comment_block_before(pos)
+            .iter()
+            .flat_map(|comment| comment.param_directives())
+            .collect()
+    }
+
+    /// The block of consecutive comments ending on the line immediately
+    /// before `pos` or earlier on the same line, empty if there is none
+    fn comment_block_before(&self, pos: &Position) -> Vec<&CommentNode> {
+        let origin = Position { row: 0, column: 0, byte: 0 };
+        let mut block: Vec<&CommentNode> = Vec::new();
+        let mut block_end_row: Option<usize> = None;
+
+        for comment in &self.comments {
+            let comment_start = comment.absolute_position(origin);
+            let comment_end = comment.absolute_end(comment_start);
+
+            // Stop at the first comment at or after the target position
+            if comment_start.row > pos.row
+                || (comment_start.row == pos.row && comment_start.column >= pos.column)
+            {
+                break;
+            }
+
+            // A gap of more than one line starts a new block
+            if block_end_row.is_some_and(|row| comment_start.row > row + 1) {
+                block.clear();
+            }
+            block.push(comment);
+            block_end_row = Some(comment_end.row);
+        }
+
+        match block_end_row {
+            Some(row) if pos.row <= row + 1 => block,
+            _ => Vec::new(),
+        }
+    }
+
     /// Get all doc comments in the document
     ///
     /// Returns an iterator over all comments that are documentation comments
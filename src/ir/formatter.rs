@@ -1,5 +1,7 @@
 
-use super::rholang_node::{RholangNode, BinOperator, RholangSendType, RholangBundleType, UnaryOperator, RholangVarRefKind, CommentKind};
+use super::rholang_node::{RholangNode, BinOperator, RholangSendType, RholangBundleType, UnaryOperator, RholangVarRefKind, CommentKind, Position, position_children};
+use super::semantic_node::SemanticNode;
+use std::collections::HashMap;
 use std::sync::Arc;
 use ropey::Rope;
 
@@ -17,9 +19,17 @@ use ropey::Rope;
 pub fn format_node(node: &Arc<RholangNode>, indent: bool, indent_size: Option<usize>, rope: &Rope, root: &Arc<RholangNode>) -> String {
     if indent {
         let size = indent_size.unwrap_or(2);
-        format_node_helper(node, 0, size, rope, root)
+        format_node_helper(node, size, rope, root)
     } else {
-        format_node_helper(node, 0, 0, rope, root)
+        format_node_helper(node, 0, rope, root)
+    }
+}
+
+/// The process inside the braces of a body, which its construct prints itself.
+fn body(proc: &Arc<RholangNode>) -> &Arc<RholangNode> {
+    match &**proc {
+        RholangNode::Block { proc, .. } => proc,
+        _ => proc,
     }
 }
 
@@ -27,78 +37,79 @@ pub fn format_node(node: &Arc<RholangNode>, indent: bool, indent_size: Option<us
 ///
 /// # Arguments
 /// * `node` - The IR node to format.
-/// * `level` - Current indentation level.
 /// * `indent_size` - Size of each indentation level (0 for no indentation).
 /// * `rope` - The Rope containing the source text.
 /// * `root` - The root node for position calculations.
 ///
 /// # Returns
 /// A string representing the formatted node with appropriate indentation.
-fn format_node_helper(node: &Arc<RholangNode>, level: usize, indent_size: usize, rope: &Rope, root: &Arc<RholangNode>) -> String {
-    let indent = if indent_size > 0 { " ".repeat(level * indent_size) } else { "".to_string() };
+fn format_node_helper(node: &Arc<RholangNode>, indent_size: usize, rope: &Rope, root: &Arc<RholangNode>) -> String {
     match &**node {
         RholangNode::Par { left: Some(l), right: Some(r), .. } => {
-            let left_text = format_node_helper(l, level, indent_size, rope, root);
-            let right_text = format_node_helper(r, level, indent_size, rope, root);
+            let left_text = format_node_helper(l, indent_size, rope, root);
+            let right_text = format_node_helper(r, indent_size, rope, root);
             format!("{} | {}", left_text, right_text)
         }
-        // N-ary Par or incomplete Par (should not occur in practice, but needed for exhaustiveness)
+        RholangNode::Par { processes: Some(processes), .. } => {
+            processes.iter().map(|p| format_node_helper(p, indent_size, rope, root)).collect::<Vec<_>>().join(" | ")
+        }
+        // Incomplete Par (should not occur in practice, but needed for exhaustiveness)
         RholangNode::Par { .. } => {
             "<par>".to_string()
         }
         RholangNode::SendSync { channel, inputs, cont, .. } => {
-            let inputs_str = inputs.iter().map(|i| format_node_helper(i, level, indent_size, rope, root)).collect::<Vec<_>>().join(", ");
-            let cont_text = format_node_helper(cont, level, indent_size, rope, root);
-            format!("{}!?({}; {})", format_node_helper(channel, level, indent_size, rope, root), inputs_str, cont_text)
+            let inputs_str = inputs.iter().map(|i| format_node_helper(i, indent_size, rope, root)).collect::<Vec<_>>().join(", ");
+            let cont_text = format_node_helper(cont, indent_size, rope, root);
+            format!("{}!?({}; {})", format_node_helper(channel, indent_size, rope, root), inputs_str, cont_text)
         }
         RholangNode::Send { channel, send_type, inputs, .. } => {
-            let inputs_str = inputs.iter().map(|i| format_node_helper(i, level, indent_size, rope, root)).collect::<Vec<_>>().join(", ");
+            let inputs_str = inputs.iter().map(|i| format_node_helper(i, indent_size, rope, root)).collect::<Vec<_>>().join(", ");
             let send_op = match send_type {
                 RholangSendType::Single => "!",
                 RholangSendType::Multiple => "!!",
             };
-            format!("{}{}({})", format_node_helper(channel, level, indent_size, rope, root), send_op, inputs_str)
+            format!("{}{}({})", format_node_helper(channel, indent_size, rope, root), send_op, inputs_str)
         }
         RholangNode::New { decls, proc, .. } => {
-            let decls_str = decls.iter().map(|d| format_node_helper(d, level, indent_size, rope, root)).collect::<Vec<_>>().join(", ");
-            let proc_text = format_node_helper(proc, level + 1, indent_size, rope, root);
+            let decls_str = decls.iter().map(|d| format_node_helper(d, indent_size, rope, root)).collect::<Vec<_>>().join(", ");
+            let proc_text = format_node_helper(body(proc), indent_size, rope, root);
             let indented_proc = if indent_size > 0 {
-                proc_text.lines().map(|line| format!("{}{}", " ".repeat((level + 1) * indent_size), line)).collect::<Vec<_>>().join("\n")
+                proc_text.lines().map(|line| format!("{}{}", " ".repeat(indent_size), line)).collect::<Vec<_>>().join("\n")
             } else {
                 proc_text
             };
-            format!("new {} in {{\n{}\n{}}}", decls_str, indented_proc, indent)
+            format!("new {} in {{\n{}\n}}", decls_str, indented_proc)
         }
         RholangNode::IfElse { condition, consequence, alternative, .. } => {
-            let cond_text = format_node_helper(condition, level, indent_size, rope, root);
-            let then_text = format_node_helper(consequence, level + 1, indent_size, rope, root);
+            let cond_text = format_node_helper(condition, indent_size, rope, root);
+            let then_text = format_node_helper(body(consequence), indent_size, rope, root);
             let indented_then = if indent_size > 0 {
-                then_text.lines().map(|line| format!("{}{}", " ".repeat((level + 1) * indent_size), line)).collect::<Vec<_>>().join("\n")
+                then_text.lines().map(|line| format!("{}{}", " ".repeat(indent_size), line)).collect::<Vec<_>>().join("\n")
             } else {
                 then_text
             };
             let else_str = if let Some(alt) = alternative {
-                let alt_text = format_node_helper(alt, level + 1, indent_size, rope, root);
+                let alt_text = format_node_helper(body(alt), indent_size, rope, root);
                 let indented_alt = if indent_size > 0 {
-                    alt_text.lines().map(|line| format!("{}{}", " ".repeat((level + 1) * indent_size), line)).collect::<Vec<_>>().join("\n")
+                    alt_text.lines().map(|line| format!("{}{}", " ".repeat(indent_size), line)).collect::<Vec<_>>().join("\n")
                 } else {
                     alt_text
                 };
-                format!(" else {{\n{}\n{}}}", indented_alt, indent)
+                format!(" else {{\n{}\n}}", indented_alt)
             } else {
                 "".to_string()
             };
-            format!("if ({}) {{\n{}\n{}}}{}", cond_text, indented_then, indent, else_str)
+            format!("if ({}) {{\n{}\n}}{}", cond_text, indented_then, else_str)
         }
         RholangNode::Let { decls, proc, .. } => {
-            let decls_str = decls.iter().map(|d| format_node_helper(d, level, indent_size, rope, root)).collect::<Vec<_>>().join("; ");
-            let proc_text = format_node_helper(proc, level + 1, indent_size, rope, root);
+            let decls_str = decls.iter().map(|d| format_node_helper(d, indent_size, rope, root)).collect::<Vec<_>>().join("; ");
+            let proc_text = format_node_helper(body(proc), indent_size, rope, root);
             let indented_proc = if indent_size > 0 {
-                proc_text.lines().map(|line| format!("{}{}", " ".repeat((level + 1) * indent_size), line)).collect::<Vec<_>>().join("\n")
+                proc_text.lines().map(|line| format!("{}{}", " ".repeat(indent_size), line)).collect::<Vec<_>>().join("\n")
             } else {
                 proc_text
             };
-            format!("let {} in {{\n{}\n{}}}", decls_str, indented_proc, indent)
+            format!("let {} in {{\n{}\n}}", decls_str, indented_proc)
         }
         RholangNode::Bundle { bundle_type, proc, .. } => {
             let prefix = match bundle_type {
@@ -107,79 +118,89 @@ fn format_node_helper(node: &Arc<RholangNode>, level: usize, indent_size: usize,
                 RholangBundleType::Equiv => "bundle0",
                 RholangBundleType::ReadWrite => "bundle",
             };
-            let proc_text = format_node_helper(proc, level + 1, indent_size, rope, root);
+            let proc_text = format_node_helper(body(proc), indent_size, rope, root);
             let indented_proc = if indent_size > 0 {
-                proc_text.lines().map(|line| format!("{}{}", " ".repeat((level + 1) * indent_size), line)).collect::<Vec<_>>().join("\n")
+                proc_text.lines().map(|line| format!("{}{}", " ".repeat(indent_size), line)).collect::<Vec<_>>().join("\n")
             } else {
                 proc_text
             };
-            format!("{} {{\n{}\n{}}}", prefix, indented_proc, indent)
+            format!("{} {{\n{}\n}}", prefix, indented_proc)
         }
         RholangNode::Match { expression, cases, .. } => {
-            let expr_text = format_node_helper(expression, level, indent_size, rope, root);
+            let expr_text = format_node_helper(expression, indent_size, rope, root);
             let cases_str = cases.iter().map(|(pat, proc)| {
-                let pat_text = format_node_helper(pat, level, indent_size, rope, root);
-                let proc_text = format_node_helper(proc, level + 1, indent_size, rope, root);
+                let pat_text = format_node_helper(pat, indent_size, rope, root);
+                let proc_text = format_node_helper(body(proc), indent_size, rope, root);
                 let indented_proc = if indent_size > 0 {
-                    proc_text.lines().map(|line| format!("{}{}", " ".repeat((level + 1) * indent_size), line)).collect::<Vec<_>>().join("\n")
+                    proc_text.lines().map(|line| format!("{}{}", " ".repeat(indent_size), line)).collect::<Vec<_>>().join("\n")
                 } else {
                     proc_text
                 };
-                format!("{} => {{\n{}\n{}}}", pat_text, indented_proc, indent)
+                format!("{} => {{\n{}\n}}", pat_text, indented_proc)
             }).collect::<Vec<_>>().join("\n");
-            format!("match {} {{\n{}\n{}}}", expr_text, cases_str, indent)
+            let indented_cases = if indent_size > 0 {
+                cases_str.lines().map(|line| format!("{}{}", " ".repeat(indent_size), line)).collect::<Vec<_>>().join("\n")
+            } else {
+                cases_str
+            };
+            format!("match {} {{\n{}\n}}", expr_text, indented_cases)
         }
         RholangNode::Choice { branches, .. } => {
             let branches_str = branches.iter().map(|(inputs, proc)| {
-                let inputs_str = inputs.iter().map(|i| format_node_helper(i, level, indent_size, rope, root)).collect::<Vec<_>>().join(" & ");
-                let proc_text = format_node_helper(proc, level + 1, indent_size, rope, root);
+                let inputs_str = inputs.iter().map(|i| format_node_helper(i, indent_size, rope, root)).collect::<Vec<_>>().join(" & ");
+                let proc_text = format_node_helper(body(proc), indent_size, rope, root);
                 let indented_proc = if indent_size > 0 {
-                    proc_text.lines().map(|line| format!("{}{}", " ".repeat((level + 1) * indent_size), line)).collect::<Vec<_>>().join("\n")
+                    proc_text.lines().map(|line| format!("{}{}", " ".repeat(indent_size), line)).collect::<Vec<_>>().join("\n")
                 } else {
                     proc_text
                 };
-                format!("{} => {{\n{}\n{}}}", inputs_str, indented_proc, indent)
+                format!("{} => {{\n{}\n}}", inputs_str, indented_proc)
             }).collect::<Vec<_>>().join("\n");
-            format!("select {{\n{}\n{}}}", branches_str, indent)
+            let indented_branches = if indent_size > 0 {
+                branches_str.lines().map(|line| format!("{}{}", " ".repeat(indent_size), line)).collect::<Vec<_>>().join("\n")
+            } else {
+                branches_str
+            };
+            format!("select {{\n{}\n}}", indented_branches)
         }
         RholangNode::Contract { name, formals, formals_remainder, proc, .. } => {
-            let formals_str = formals.iter().map(|f| format_node_helper(f, level, indent_size, rope, root)).collect::<Vec<_>>().join(", ");
-            let remainder_str = formals_remainder.as_ref().map(|r| format!("...{}", format_node_helper(r, level, indent_size, rope, root))).unwrap_or_default();
+            let formals_str = formals.iter().map(|f| format_node_helper(f, indent_size, rope, root)).collect::<Vec<_>>().join(", ");
+            let remainder_str = formals_remainder.as_ref().map(|r| format!("...{}", format_node_helper(r, indent_size, rope, root))).unwrap_or_default();
             let formals_with_rem = if formals_remainder.is_some() { format!("{}{}", formals_str, if !formals_str.is_empty() { "," } else { "" }) } else { formals_str };
-            let proc_text = format_node_helper(proc, level + 1, indent_size, rope, root);
+            let proc_text = format_node_helper(body(proc), indent_size, rope, root);
             let indented_proc = if indent_size > 0 {
-                proc_text.lines().map(|line| format!("{}{}", " ".repeat((level + 1) * indent_size), line)).collect::<Vec<_>>().join("\n")
+                proc_text.lines().map(|line| format!("{}{}", " ".repeat(indent_size), line)).collect::<Vec<_>>().join("\n")
             } else {
                 proc_text
             };
-            format!("contract {}({}{}) = {{\n{}\n{}}}", format_node_helper(name, level, indent_size, rope, root), formals_with_rem, remainder_str, indented_proc, indent)
+            format!("contract {}({}{}) = {{\n{}\n}}", format_node_helper(name, indent_size, rope, root), formals_with_rem, remainder_str, indented_proc)
         }
         RholangNode::Input { receipts, proc, .. } => {
-            let receipts_str = receipts.iter().map(|binds| binds.iter().map(|b| format_node_helper(b, level, indent_size, rope, root)).collect::<Vec<_>>().join(" & ")).collect::<Vec<_>>().join("; ");
-            let proc_text = format_node_helper(proc, level + 1, indent_size, rope, root);
+            let receipts_str = receipts.iter().map(|binds| binds.iter().map(|b| format_node_helper(b, indent_size, rope, root)).collect::<Vec<_>>().join(" & ")).collect::<Vec<_>>().join("; ");
+            let proc_text = format_node_helper(body(proc), indent_size, rope, root);
             let indented_proc = if indent_size > 0 {
-                proc_text.lines().map(|line| format!("{}{}", " ".repeat((level + 1) * indent_size), line)).collect::<Vec<_>>().join("\n")
+                proc_text.lines().map(|line| format!("{}{}", " ".repeat(indent_size), line)).collect::<Vec<_>>().join("\n")
             } else {
                 proc_text
             };
-            format!("for ({}) {{\n{}\n{}}}", receipts_str, indented_proc, indent)
+            format!("for ({}) {{\n{}\n}}", receipts_str, indented_proc)
         }
         RholangNode::Block { proc, .. } => {
-            let proc_text = format_node_helper(proc, level + 1, indent_size, rope, root);
+            let proc_text = format_node_helper(proc, indent_size, rope, root);
             let indented_proc = if indent_size > 0 {
-                proc_text.lines().map(|line| format!("{}{}", " ".repeat((level + 1) * indent_size), line)).collect::<Vec<_>>().join("\n")
+                proc_text.lines().map(|line| format!("{}{}", " ".repeat(indent_size), line)).collect::<Vec<_>>().join("\n")
             } else {
                 proc_text
             };
-            format!("{{\n{}\n{}}}", indented_proc, indent)
+            format!("{{\n{}\n}}", indented_proc)
         }
         RholangNode::Parenthesized { expr, .. } => {
-            let expr_text = format_node_helper(expr, level, indent_size, rope, root);
+            let expr_text = format_node_helper(expr, indent_size, rope, root);
             format!("({})", expr_text)
         }
         RholangNode::BinOp { op, left, right, .. } => {
-            let left_text = format_node_helper(left, level, indent_size, rope, root);
-            let right_text = format_node_helper(right, level, indent_size, rope, root);
+            let left_text = format_node_helper(left, indent_size, rope, root);
+            let right_text = format_node_helper(right, indent_size, rope, root);
             let op_str = match op {
                 BinOperator::Or => "or",
                 BinOperator::And => "and",
@@ -204,7 +225,7 @@ fn format_node_helper(node: &Arc<RholangNode>, level: usize, indent_size: usize,
             format!("({} {} {})", left_text, op_str, right_text)
         }
         RholangNode::UnaryOp { op, operand, .. } => {
-            let operand_text = format_node_helper(operand, level, indent_size, rope, root);
+            let operand_text = format_node_helper(operand, indent_size, rope, root);
             match op {
                 UnaryOperator::Not => format!("not {}", operand_text),
                 UnaryOperator::Neg => format!("-{}", operand_text),
@@ -212,75 +233,83 @@ fn format_node_helper(node: &Arc<RholangNode>, level: usize, indent_size: usize,
             }
         }
         RholangNode::Method { receiver, name, args, .. } => {
-            let args_str = args.iter().map(|a| format_node_helper(a, level, indent_size, rope, root)).collect::<Vec<_>>().join(", ");
-            format!("{}.{}({})", format_node_helper(receiver, level, indent_size, rope, root), name, args_str)
+            let args_str = args.iter().map(|a| format_node_helper(a, indent_size, rope, root)).collect::<Vec<_>>().join(", ");
+            format!("{}.{}({})", format_node_helper(receiver, indent_size, rope, root), name, args_str)
         }
-        RholangNode::Eval { name, .. } => format!("*{}", format_node_helper(name, level, indent_size, rope, root)),
-        RholangNode::Quote { quotable, .. } => format!("@{}", format_node_helper(quotable, level, indent_size, rope, root)),
+        RholangNode::Eval { name, .. } => format!("*{}", format_node_helper(name, indent_size, rope, root)),
+        RholangNode::Quote { quotable, .. } => format!("@{}", format_node_helper(quotable, indent_size, rope, root)),
         RholangNode::VarRef { kind, var, .. } => {
             let kind_str = match kind {
                 RholangVarRefKind::Bind => "=",
                 RholangVarRefKind::Unforgeable => "=*",
             };
-            format!("{}{}", kind_str, format_node_helper(var, level, indent_size, rope, root))
+            format!("{}{}", kind_str, format_node_helper(var, indent_size, rope, root))
         }
         RholangNode::BoolLiteral { value, .. } => value.to_string(),
         RholangNode::LongLiteral { value, .. } => value.to_string(),
-        RholangNode::StringLiteral { value, .. } => format!("\"{}\"", value),
+        RholangNode::StringLiteral { value, .. } => {
+            // The source keeps the escapes that were resolved in `value`
+            let source = if rope.len_bytes() > 0 { node.text(rope, root).to_string() } else { String::new() };
+            if source.len() >= 2 && source.starts_with('"') && source.ends_with('"') {
+                source
+            } else {
+                format!("\"{}\"", value)
+            }
+        }
         RholangNode::UriLiteral { value, .. } => format!("`{}`", value),
         RholangNode::Nil { .. } => "Nil".to_string(),
         RholangNode::List { elements, remainder, .. } => {
-            let elements_str = elements.iter().map(|e| format_node_helper(e, level, indent_size, rope, root)).collect::<Vec<_>>().join(", ");
-            let remainder_str = remainder.as_ref().map(|r| format!("...{}", format_node_helper(r, level, indent_size, rope, root))).unwrap_or_default();
+            let elements_str = elements.iter().map(|e| format_node_helper(e, indent_size, rope, root)).collect::<Vec<_>>().join(", ");
+            let remainder_str = remainder.as_ref().map(|r| format!("...{}", format_node_helper(r, indent_size, rope, root))).unwrap_or_default();
             format!("[{}{}]", elements_str, if remainder.is_some() { format!(",{}", remainder_str) } else { String::new() })
         }
         RholangNode::Set { elements, remainder, .. } => {
-            let elements_str = elements.iter().map(|e| format_node_helper(e, level, indent_size, rope, root)).collect::<Vec<_>>().join(", ");
-            let remainder_str = remainder.as_ref().map(|r| format!("...{}", format_node_helper(r, level, indent_size, rope, root))).unwrap_or_default();
+            let elements_str = elements.iter().map(|e| format_node_helper(e, indent_size, rope, root)).collect::<Vec<_>>().join(", ");
+            let remainder_str = remainder.as_ref().map(|r| format!("...{}", format_node_helper(r, indent_size, rope, root))).unwrap_or_default();
             format!("Set({}{})", elements_str, if remainder.is_some() { format!(",{}", remainder_str) } else { String::new() })
         }
         RholangNode::Map { pairs, remainder, .. } => {
-            let pairs_str = pairs.iter().map(|(k, v)| format!("{}: {}", format_node_helper(k, level, indent_size, rope, root), format_node_helper(v, level, indent_size, rope, root))).collect::<Vec<_>>().join(", ");
-            let remainder_str = remainder.as_ref().map(|r| format!("...{}", format_node_helper(r, level, indent_size, rope, root))).unwrap_or_default();
+            let pairs_str = pairs.iter().map(|(k, v)| format!("{}: {}", format_node_helper(k, indent_size, rope, root), format_node_helper(v, indent_size, rope, root))).collect::<Vec<_>>().join(", ");
+            let remainder_str = remainder.as_ref().map(|r| format!("...{}", format_node_helper(r, indent_size, rope, root))).unwrap_or_default();
             format!("{{{}{}}}", pairs_str, if remainder.is_some() { format!(",{}", remainder_str) } else { String::new() })
         }
         RholangNode::Pathmap { elements, remainder, .. } => {
-            let elements_str = elements.iter().map(|e| format_node_helper(e, level, indent_size, rope, root)).collect::<Vec<_>>().join(", ");
-            let remainder_str = remainder.as_ref().map(|r| format!("...{}", format_node_helper(r, level, indent_size, rope, root))).unwrap_or_default();
+            let elements_str = elements.iter().map(|e| format_node_helper(e, indent_size, rope, root)).collect::<Vec<_>>().join(", ");
+            let remainder_str = remainder.as_ref().map(|r| format!("...{}", format_node_helper(r, indent_size, rope, root))).unwrap_or_default();
             format!("{{| {}{}|}}", elements_str, if remainder.is_some() { format!(",{}", remainder_str) } else { String::new() })
         }
         RholangNode::Tuple { elements, .. } => {
-            let elements_str = elements.iter().map(|e| format_node_helper(e, level, indent_size, rope, root)).collect::<Vec<_>>().join(", ");
+            let elements_str = elements.iter().map(|e| format_node_helper(e, indent_size, rope, root)).collect::<Vec<_>>().join(", ");
             format!("({})", elements_str)
         }
         RholangNode::Var { name, .. } => name.clone(),
         RholangNode::NameDecl { var, uri, .. } => {
-            if let Some(uri_node) = uri { format!("{}({})", format_node_helper(var, level, indent_size, rope, root), format_node_helper(uri_node, level, indent_size, rope, root)) } else { format_node_helper(var, level, indent_size, rope, root) }
+            if let Some(uri_node) = uri { format!("{}({})", format_node_helper(var, indent_size, rope, root), format_node_helper(uri_node, indent_size, rope, root)) } else { format_node_helper(var, indent_size, rope, root) }
         }
         RholangNode::Decl { names, names_remainder, procs, .. } => {
-            let names_str = names.iter().map(|n| format_node_helper(n, level, indent_size, rope, root)).collect::<Vec<_>>().join(", ");
-            let remainder_str = names_remainder.as_ref().map(|r| format!("...{}", format_node_helper(r, level, indent_size, rope, root))).unwrap_or_default();
+            let names_str = names.iter().map(|n| format_node_helper(n, indent_size, rope, root)).collect::<Vec<_>>().join(", ");
+            let remainder_str = names_remainder.as_ref().map(|r| format!("...{}", format_node_helper(r, indent_size, rope, root))).unwrap_or_default();
             let names_with_rem = if names_remainder.is_some() { format!("{}{}", names_str, if !names_str.is_empty() { "," } else { "" }) } else { names_str };
-            let procs_str = procs.iter().map(|p| format_node_helper(p, level, indent_size, rope, root)).collect::<Vec<_>>().join(", ");
+            let procs_str = procs.iter().map(|p| format_node_helper(p, indent_size, rope, root)).collect::<Vec<_>>().join(", ");
             format!("{}{} = {}", names_with_rem, remainder_str, procs_str)
         }
         RholangNode::LinearBind { names, remainder, source, .. } => {
-            let names_str = names.iter().map(|n| format_node_helper(n, level, indent_size, rope, root)).collect::<Vec<_>>().join(", ");
-            let remainder_str = remainder.as_ref().map(|r| format!("...{}", format_node_helper(r, level, indent_size, rope, root))).unwrap_or_default();
+            let names_str = names.iter().map(|n| format_node_helper(n, indent_size, rope, root)).collect::<Vec<_>>().join(", ");
+            let remainder_str = remainder.as_ref().map(|r| format!("...{}", format_node_helper(r, indent_size, rope, root))).unwrap_or_default();
             let names_with_rem = if remainder.is_some() { format!("{}{}", names_str, if !names_str.is_empty() { "," } else { "" }) } else { names_str };
-            format!("{}{} <- {}", names_with_rem, remainder_str, format_node_helper(source, level, indent_size, rope, root))
+            format!("{}{} <- {}", names_with_rem, remainder_str, format_node_helper(source, indent_size, rope, root))
         }
         RholangNode::RepeatedBind { names, remainder, source, .. } => {
-            let names_str = names.iter().map(|n| format_node_helper(n, level, indent_size, rope, root)).collect::<Vec<_>>().join(", ");
-            let remainder_str = remainder.as_ref().map(|r| format!("...{}", format_node_helper(r, level, indent_size, rope, root))).unwrap_or_default();
+            let names_str = names.iter().map(|n| format_node_helper(n, indent_size, rope, root)).collect::<Vec<_>>().join(", ");
+            let remainder_str = remainder.as_ref().map(|r| format!("...{}", format_node_helper(r, indent_size, rope, root))).unwrap_or_default();
             let names_with_rem = if remainder.is_some() { format!("{}{}", names_str, if !names_str.is_empty() { "," } else { "" }) } else { names_str };
-            format!("{}{} <= {}", names_with_rem, remainder_str, format_node_helper(source, level, indent_size, rope, root))
+            format!("{}{} <= {}", names_with_rem, remainder_str, format_node_helper(source, indent_size, rope, root))
         }
         RholangNode::PeekBind { names, remainder, source, .. } => {
-            let names_str = names.iter().map(|n| format_node_helper(n, level, indent_size, rope, root)).collect::<Vec<_>>().join(", ");
-            let remainder_str = remainder.as_ref().map(|r| format!("...{}", format_node_helper(r, level, indent_size, rope, root))).unwrap_or_default();
+            let names_str = names.iter().map(|n| format_node_helper(n, indent_size, rope, root)).collect::<Vec<_>>().join(", ");
+            let remainder_str = remainder.as_ref().map(|r| format!("...{}", format_node_helper(r, indent_size, rope, root))).unwrap_or_default();
             let names_with_rem = if remainder.is_some() { format!("{}{}", names_str, if !names_str.is_empty() { "," } else { "" }) } else { names_str };
-            format!("{}{} <<- {}", names_with_rem, remainder_str, format_node_helper(source, level, indent_size, rope, root))
+            format!("{}{} <<- {}", names_with_rem, remainder_str, format_node_helper(source, indent_size, rope, root))
         }
         RholangNode::Comment { kind, .. } => {
             let text = node.text(rope, root).to_string();
@@ -291,33 +320,95 @@ fn format_node_helper(node: &Arc<RholangNode>, level: usize, indent_size: usize,
         }
         RholangNode::Wildcard { .. } => "_".to_string(),
         RholangNode::SimpleType { value, .. } => value.clone(),
-        RholangNode::ReceiveSendSource { name, .. } => format!("{}?!", format_node_helper(name, level, indent_size, rope, root)),
+        RholangNode::ReceiveSendSource { name, .. } => format!("{}?!", format_node_helper(name, indent_size, rope, root)),
         RholangNode::SendReceiveSource { name, inputs, .. } => {
-            let inputs_str = inputs.iter().map(|i| format_node_helper(i, level, indent_size, rope, root)).collect::<Vec<_>>().join(", ");
-            format!("{}!?({})", format_node_helper(name, level, indent_size, rope, root), inputs_str)
+            let inputs_str = inputs.iter().map(|i| format_node_helper(i, indent_size, rope, root)).collect::<Vec<_>>().join(", ");
+            format!("{}!?({})", format_node_helper(name, indent_size, rope, root), inputs_str)
         }
         RholangNode::Error { children, .. } => {
             let children_str = children
                 .iter()
-                .map(|child| format_node_helper(child, level, indent_size, rope, root))
+                .map(|child| format_node_helper(child, indent_size, rope, root))
                 .collect::<Vec<_>>()
                 .join("\n");
             format!("/* ERROR: \n{} */", children_str)
         }
         RholangNode::Disjunction { left, right, .. } => {
-            let left_text = format_node_helper(left, level, indent_size, rope, root);
-            let right_text = format_node_helper(right, level, indent_size, rope, root);
+            let left_text = format_node_helper(left, indent_size, rope, root);
+            let right_text = format_node_helper(right, indent_size, rope, root);
             format!("{} \\/ {}", left_text, right_text)
         }
         RholangNode::Conjunction { left, right, .. } => {
-            let left_text = format_node_helper(left, level, indent_size, rope, root);
-            let right_text = format_node_helper(right, level, indent_size, rope, root);
+            let left_text = format_node_helper(left, indent_size, rope, root);
+            let right_text = format_node_helper(right, indent_size, rope, root);
             format!("{} /\\ {}", left_text, right_text)
         }
         RholangNode::Negation { operand, .. } => {
-            let operand_text = format_node_helper(operand, level, indent_size, rope, root);
+            let operand_text = format_node_helper(operand, indent_size, rope, root);
             format!("~{}", operand_text)
         }
         RholangNode::Unit { .. } => format!("()"),
     }
 }
+
+/// Reproduces the source text of `node` from the IR in fidelity mode.
+///
+/// Unlike [`format_node`], nothing is normalized: each node contributes the
+/// rope text around its children (keywords, punctuation, whitespace and
+/// comments) and each child its own reconstruction, recursively. The result
+/// therefore equals the original text only if every computed range in
+/// `positions` lies inside its parent's and siblings do not overlap; a range
+/// that drifted is reported as an error instead.
+///
+/// # Arguments
+/// * `node` - The IR node to reproduce.
+/// * `positions` - Absolute positions from `compute_absolute_positions`.
+/// * `rope` - The Rope containing the source text.
+///
+/// # Returns
+/// The source text of `node`, or a description of the first inconsistent range.
+pub fn format_node_fidelity(
+    node: &RholangNode,
+    positions: &HashMap<usize, (Position, Position)>,
+    rope: &Rope,
+) -> Result<String, String> {
+    let span = |node: &RholangNode| positions.get(&(node as *const RholangNode as usize)).copied();
+    let (start, end) = span(node).ok_or_else(|| format!("{} has no computed range", node.type_name()))?;
+    let slice = |from: usize, to: usize| {
+        rope.get_byte_slice(from..to)
+            .map(|slice| slice.to_string())
+            .ok_or_else(|| format!("{} range {}..{} does not slice the source", node.type_name(), from, to))
+    };
+    if start.byte > end.byte {
+        return Err(format!("{} range {}..{} is reversed", node.type_name(), start.byte, end.byte));
+    }
+
+    let mut children = Vec::new();
+    for child in position_children(node) {
+        let (child_start, child_end) =
+            span(child).ok_or_else(|| format!("{} has no computed range", child.type_name()))?;
+        children.push((&**child, child_start, child_end));
+    }
+    children.sort_by_key(|(_, child_start, child_end)| (child_start.byte, child_end.byte));
+
+    let mut text = String::new();
+    let mut cursor = start.byte;
+    for (child, child_start, child_end) in children {
+        if child_start.byte < cursor || child_end.byte > end.byte {
+            return Err(format!(
+                "{} range {}..{} escapes {} range {}..{} or overlaps a sibling",
+                child.type_name(),
+                child_start.byte,
+                child_end.byte,
+                node.type_name(),
+                start.byte,
+                end.byte
+            ));
+        }
+        text.push_str(&slice(cursor, child_start.byte)?);
+        text.push_str(&format_node_fidelity(child, positions, rope)?);
+        cursor = child_end.byte;
+    }
+    text.push_str(&slice(cursor, end.byte)?);
+    Ok(text)
+}
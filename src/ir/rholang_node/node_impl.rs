@@ -14,7 +14,7 @@ use std::collections::HashMap;
 #[cfg(test)]
 use rpds::Vector;
 #[cfg(test)]
-use super::node_operations::{match_pat, match_contract};
+use super::node_operations::{collect_calls, contract_names_equal, match_pat, match_contract, strip_quote_eval};
 
 impl RholangNode {
     /// Returns the processes in a Par node, handling both binary and n-ary forms.
@@ -3129,6 +3129,31 @@ mod tests {
         assert!(!match_contract(&channel, &bad_inputs, &contract));
     }
 
+    #[test]
+    fn test_strip_quote_eval() {
+        let code = "new x in { x!(1) | @*x!(2) | @*@(*x)!(3) | @x!(4) }";
+        let tree = parse_code(code);
+        let rope = Rope::from_str(code);
+        let ir = parse_to_ir(&tree, &rope);
+        let mut calls = Vec::new();
+        collect_calls(&ir, &mut calls);
+        let channels: Vec<_> = calls
+            .iter()
+            .map(|call| match &**call {
+                RholangNode::Send { channel, .. } => channel.clone(),
+                other => panic!("Expected Send node, got {:?}", other),
+            })
+            .collect();
+        assert_eq!(channels.len(), 4);
+        for channel in &channels[..3] {
+            assert!(matches!(strip_quote_eval(channel), RholangNode::Var { name, .. } if name == "x"));
+            assert!(contract_names_equal(channel, &channels[0]));
+        }
+        // `@x` quotes the process `x`, another name
+        assert!(matches!(strip_quote_eval(&channels[3]), RholangNode::Quote { .. }));
+        assert!(!contract_names_equal(&channels[3], &channels[0]));
+    }
+
     #[test]
     fn test_match_pat_set() {
         let p_e = Vector::new_with_ptr_kind()
@@ -228,12 +228,48 @@ pub fn match_pat(pat: &Arc<RholangNode>, concrete: &Arc<RholangNode>, subst: &mu
     }
 }
 
+/// Strips quote/eval round-trips from a name or process.
+///
+/// `@*x` is the name `x` and `*@P` is the process `P`, so both reduce to their
+/// innermost operand, as do longer chains like `@*@*x` and parenthesized ones
+/// like `@(*x)`. Other nodes, including a lone `@P` or `*x`, are returned as
+/// they are.
+pub fn strip_quote_eval(node: &RholangNode) -> &RholangNode {
+    let mut node = node;
+    loop {
+        let inner = match node {
+            RholangNode::Quote { quotable, .. } => match strip_parentheses(quotable) {
+                RholangNode::Eval { name, .. } => name,
+                _ => return node,
+            },
+            RholangNode::Eval { name, .. } => match strip_parentheses(name) {
+                RholangNode::Quote { quotable, .. } => quotable,
+                _ => return node,
+            },
+            _ => return node,
+        };
+        node = strip_parentheses(inner);
+    }
+}
+
+fn strip_parentheses(node: &RholangNode) -> &RholangNode {
+    match node {
+        RholangNode::Parenthesized { expr, .. } => strip_parentheses(expr),
+        _ => node,
+    }
+}
+
 /// Matches a contract against a call's channel and inputs.
 /// Check if two nodes are equal for contract name matching (avoids pattern matching's Var unification)
+///
+/// Quote/eval round-trips are stripped first (see [`strip_quote_eval`]), so
+/// `@*foo` names contract `foo`.
 pub fn contract_names_equal(a: &Arc<RholangNode>, b: &Arc<RholangNode>) -> bool {
-    match (&**a, &**b) {
-        // Fast path: pointer equality
-        _ if Arc::ptr_eq(a, b) => true,
+    Arc::ptr_eq(a, b) || names_equal(strip_quote_eval(a), strip_quote_eval(b))
+}
+
+fn names_equal(a: &RholangNode, b: &RholangNode) -> bool {
+    match (a, b) {
         // Var nodes: compare names by reference (cheap since names are strings in Arc)
         (RholangNode::Var { name: a_name, .. }, RholangNode::Var { name: b_name, .. }) => a_name == b_name,
         // StringLiteral nodes: compare values (for quoted contract names like @"myContract")
@@ -275,7 +311,7 @@ pub fn match_contract(channel: &Arc<RholangNode>, inputs: &RholangNodeVector, co
             return false;
         }
         for (f, a) in formals.iter().zip(inputs.iter()) {
-            if !match_pat(f, a, &mut subst) {
+            if !match_formal(f, a, &mut subst) {
                 return false;
             }
         }
@@ -310,6 +346,19 @@ pub fn match_contract(channel: &Arc<RholangNode>, inputs: &RholangNodeVector, co
     }
 }
 
+/// Matches a contract formal against a send argument.
+///
+/// A formal `@P` receives the name quoting the process sent, so it matches a
+/// process argument (`"a"`, `*x`) when `P` matches that process.
+fn match_formal(formal: &Arc<RholangNode>, arg: &Arc<RholangNode>, subst: &mut HashMap<String, Arc<RholangNode>>) -> bool {
+    match (&**formal, &**arg) {
+        (RholangNode::Quote { quotable, .. }, concrete) if !matches!(concrete, RholangNode::Quote { .. }) => {
+            match_pat(quotable, arg, subst)
+        }
+        _ => match_pat(formal, arg, subst),
+    }
+}
+
 /// Collects all contract nodes from the IR tree.
 pub fn collect_contracts(node: &Arc<RholangNode>, contracts: &mut Vec<Arc<RholangNode>>) {
     match &**node {
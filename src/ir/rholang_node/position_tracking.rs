@@ -11,31 +11,208 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use rayon::prelude::*;
 use tracing::{debug, trace};
 
 use super::node_types::*;
 pub use super::node_types::Position;
 
+/// Trees with at least this many nodes are indexed on the rayon pool
+pub const PARALLEL_POSITION_THRESHOLD: usize = 20_000;
+
+/// Subtrees smaller than this are indexed on the current worker
+const PARALLEL_SUBTREE_MIN: usize = 2_048;
+
+const ORIGIN: Position = Position { row: 0, column: 0, byte: 0 };
+
 /// Build a position index for fast position lookups by node pointer.
 ///
 /// Despite the name, this function no longer "computes" positions - it simply
 /// extracts absolute positions that are already stored in NodeBase and builds
 /// a HashMap for O(1) lookup by node address.
 ///
+/// Trees of [`PARALLEL_POSITION_THRESHOLD`] nodes or more are indexed in
+/// parallel (see [`compute_absolute_positions_parallel`]).
+///
 /// # Historical Note
 /// Before the 2025-11 migration, this function computed absolute positions from
 /// relative deltas. Now it's just a position indexer since NodeBase stores positions directly.
 pub fn compute_absolute_positions(root: &Arc<RholangNode>) -> HashMap<usize, (Position, Position)> {
+    let mut sizes = Vec::new();
+    subtree_sizes(root, &mut sizes);
+    if sizes[0] >= PARALLEL_POSITION_THRESHOLD {
+        return positions_from_sizes(root, &sizes);
+    }
+    let mut positions = HashMap::with_capacity(sizes[0]);
+    compute_positions_helper(root, ORIGIN, &mut positions);
+    positions
+}
+
+/// Single-threaded [`compute_absolute_positions`], whatever the tree size.
+pub fn compute_absolute_positions_sequential(root: &Arc<RholangNode>) -> HashMap<usize, (Position, Position)> {
     let mut positions = HashMap::new();
-    let initial_prev_end = Position {
-        row: 0,
-        column: 0,
-        byte: 0,
-    };
-    compute_positions_helper(root, initial_prev_end, &mut positions);
+    compute_positions_helper(root, ORIGIN, &mut positions);
     positions
 }
 
+/// Parallel [`compute_absolute_positions`], whatever the tree size.
+///
+/// Works in two passes over the pre-order of the tree: the first records
+/// the size of every subtree, which gives each subtree its own slice of a
+/// flat entry buffer; the second fills the slices of large subtrees
+/// concurrently. The index is then built from the buffer.
+pub fn compute_absolute_positions_parallel(root: &Arc<RholangNode>) -> HashMap<usize, (Position, Position)> {
+    let mut sizes = Vec::new();
+    subtree_sizes(root, &mut sizes);
+    positions_from_sizes(root, &sizes)
+}
+
+fn positions_from_sizes(root: &Arc<RholangNode>, sizes: &[usize]) -> HashMap<usize, (Position, Position)> {
+    let mut entries = vec![(0, (ORIGIN, ORIGIN)); sizes[0]];
+    fill_positions(root, sizes, &mut entries);
+    let mut positions = HashMap::with_capacity(entries.len());
+    positions.extend(entries);
+    positions
+}
+
+/// Appends the subtree size of `node` and of each of its descendants, in pre-order.
+fn subtree_sizes(node: &Arc<RholangNode>, sizes: &mut Vec<usize>) -> usize {
+    let index = sizes.len();
+    sizes.push(0);
+    let size = 1 + position_children(node).into_iter().map(|child| subtree_sizes(child, sizes)).sum::<usize>();
+    sizes[index] = size;
+    size
+}
+
+/// Writes the entries of the subtree rooted at `node` to `entries`, in pre-order.
+///
+/// `sizes` and `entries` both start at `node` and span its subtree.
+fn fill_positions(node: &Arc<RholangNode>, sizes: &[usize], entries: &mut [(usize, (Position, Position))]) {
+    let base = node.base();
+    entries[0] = (&**node as *const RholangNode as usize, (base.start(), base.end()));
+
+    let mut rest_sizes = &sizes[1..sizes[0]];
+    let mut rest_entries = &mut entries[1..];
+    let mut subtrees = Vec::new();
+    for child in position_children(node) {
+        let (child_sizes, sizes_after) = rest_sizes.split_at(rest_sizes[0]);
+        let (child_entries, entries_after) = std::mem::take(&mut rest_entries).split_at_mut(child_sizes[0]);
+        subtrees.push((child, child_sizes, child_entries));
+        rest_sizes = sizes_after;
+        rest_entries = entries_after;
+    }
+
+    if sizes[0] >= PARALLEL_SUBTREE_MIN {
+        subtrees
+            .into_par_iter()
+            .for_each(|(child, child_sizes, child_entries)| fill_positions(child, child_sizes, child_entries));
+    } else {
+        for (child, child_sizes, child_entries) in subtrees {
+            fill_positions(child, child_sizes, child_entries);
+        }
+    }
+}
+
+/// The children of `node` whose positions [`compute_absolute_positions`]
+/// indexes, in source order.
+pub fn position_children(node: &RholangNode) -> Vec<&Arc<RholangNode>> {
+    let mut children = Vec::new();
+    match node {
+        RholangNode::Par { left: Some(left), right: Some(right), .. } => children.extend([left, right]),
+        RholangNode::Par { processes: Some(procs), .. } => children.extend(procs.iter()),
+        RholangNode::SendSync { channel, inputs, cont, .. } => {
+            children.push(channel);
+            children.extend(inputs.iter());
+            children.push(cont);
+        }
+        RholangNode::Send { channel, inputs, .. } => {
+            children.push(channel);
+            children.extend(inputs.iter());
+        }
+        RholangNode::New { decls, proc, .. } | RholangNode::Let { decls, proc, .. } => {
+            children.extend(decls.iter());
+            children.push(proc);
+        }
+        RholangNode::IfElse { condition, consequence, alternative, .. } => {
+            children.extend([condition, consequence]);
+            children.extend(alternative);
+        }
+        RholangNode::Bundle { proc, .. } | RholangNode::Block { proc, .. } => children.push(proc),
+        RholangNode::Match { expression, cases, .. } => {
+            children.push(expression);
+            for (pattern, proc) in cases.iter() {
+                children.extend([pattern, proc]);
+            }
+        }
+        RholangNode::Choice { branches, .. } => {
+            for (inputs, proc) in branches.iter() {
+                children.extend(inputs.iter());
+                children.push(proc);
+            }
+        }
+        RholangNode::Contract { name, formals, formals_remainder, proc, .. } => {
+            children.push(name);
+            children.extend(formals.iter());
+            children.extend(formals_remainder);
+            children.push(proc);
+        }
+        RholangNode::Input { receipts, proc, .. } => {
+            for receipt in receipts.iter() {
+                children.extend(receipt.iter());
+            }
+            children.push(proc);
+        }
+        RholangNode::Parenthesized { expr, .. } => children.push(expr),
+        RholangNode::BinOp { left, right, .. }
+        | RholangNode::Disjunction { left, right, .. }
+        | RholangNode::Conjunction { left, right, .. } => children.extend([left, right]),
+        RholangNode::UnaryOp { operand, .. } | RholangNode::Negation { operand, .. } => children.push(operand),
+        RholangNode::Method { receiver, args, .. } => {
+            children.push(receiver);
+            children.extend(args.iter());
+        }
+        RholangNode::Eval { name, .. } | RholangNode::ReceiveSendSource { name, .. } => children.push(name),
+        RholangNode::Quote { quotable, .. } => children.push(quotable),
+        RholangNode::VarRef { var, .. } => children.push(var),
+        RholangNode::List { elements, remainder, .. }
+        | RholangNode::Set { elements, remainder, .. }
+        | RholangNode::Pathmap { elements, remainder, .. } => {
+            children.extend(elements.iter());
+            children.extend(remainder);
+        }
+        RholangNode::Map { pairs, remainder, .. } => {
+            for (key, value) in pairs.iter() {
+                children.extend([key, value]);
+            }
+            children.extend(remainder);
+        }
+        RholangNode::Tuple { elements, .. } => children.extend(elements.iter()),
+        RholangNode::NameDecl { var, uri, .. } => {
+            children.push(var);
+            children.extend(uri);
+        }
+        RholangNode::Decl { names, names_remainder, procs, .. } => {
+            children.extend(names.iter());
+            children.extend(names_remainder);
+            children.extend(procs.iter());
+        }
+        RholangNode::LinearBind { names, remainder, source, .. }
+        | RholangNode::RepeatedBind { names, remainder, source, .. }
+        | RholangNode::PeekBind { names, remainder, source, .. } => {
+            children.extend(names.iter());
+            children.extend(remainder);
+            children.push(source);
+        }
+        RholangNode::SendReceiveSource { name, inputs, .. } => {
+            children.push(name);
+            children.extend(inputs.iter());
+        }
+        RholangNode::Error { children: error_children, .. } => children.extend(error_children.iter()),
+        _ => {}
+    }
+    children
+}
+
 /// Recursively index positions for all nodes in the IR tree.
 ///
 /// Extracts absolute positions from NodeBase and stores them in a HashMap
@@ -248,6 +425,11 @@ fn compute_positions_helper(
             elements,
             remainder,
             ..
+        }
+        | RholangNode::Pathmap {
+            elements,
+            remainder,
+            ..
         } => {
             for elem in elements {
                 current_prev = compute_positions_helper(elem, current_prev, positions);
@@ -422,6 +604,35 @@ pub fn find_node_at_position_with_path(
     best.map(|(node, p, _)| (node, p))
 }
 
+/// The processes of an n-ary `Par` whose span may contain `pos`.
+///
+/// Processes are in source order, so the first candidate is found by binary
+/// search on span ends and the scan stops at the first process starting
+/// after `pos`. Falls back to every process if one has no indexed span.
+fn candidate_processes<'a>(
+    procs: &'a RholangNodeVector,
+    pos: Position,
+    positions: &'a HashMap<usize, (Position, Position)>,
+) -> impl Iterator<Item = &'a Arc<RholangNode>> + 'a {
+    let span = |proc: &Arc<RholangNode>| positions.get(&(&**proc as *const RholangNode as usize)).copied();
+    let (mut low, mut high) = (0, procs.len());
+    while low < high {
+        let mid = low + (high - low) / 2;
+        match procs.get(mid).and_then(span) {
+            Some((_, end)) if end.byte < pos.byte => low = mid + 1,
+            Some(_) => high = mid,
+            None => {
+                low = 0;
+                break;
+            }
+        }
+    }
+    procs
+        .iter()
+        .skip(low)
+        .take_while(move |proc| span(proc).is_none_or(|(start, _)| start.byte <= pos.byte))
+}
+
 fn traverse_with_path(
     node: &Arc<RholangNode>,
     pos: Position,
@@ -441,6 +652,10 @@ fn traverse_with_path(
                 trace!("Found better match at depth {} for position {}", depth, pos.byte);
                 *best = Some((node.clone(), path.clone(), depth));
             }
+        } else {
+            // Descendants lie within the node's span
+            path.pop();
+            return;
         }
     }
     match &**node {
@@ -449,7 +664,7 @@ fn traverse_with_path(
             traverse_with_path(right, pos, positions, path, best, depth + 1);
         }
         RholangNode::Par { processes: Some(procs), .. } => {
-            for proc in procs.iter() {
+            for proc in candidate_processes(procs, pos, positions) {
                 traverse_with_path(proc, pos, positions, path, best, depth + 1);
             }
         }
@@ -542,7 +757,7 @@ fn traverse_with_path(
         RholangNode::Eval { name, .. } => traverse_with_path(name, pos, positions, path, best, depth + 1),
         RholangNode::Quote { quotable, .. } => traverse_with_path(quotable, pos, positions, path, best, depth + 1),
         RholangNode::VarRef { var, .. } => traverse_with_path(var, pos, positions, path, best, depth + 1),
-        RholangNode::List { elements, remainder, .. } => {
+        RholangNode::List { elements, remainder, .. } | RholangNode::Pathmap { elements, remainder, .. } => {
             for elem in elements {
                 traverse_with_path(elem, pos, positions, path, best, depth + 1);
             }
@@ -659,6 +874,9 @@ fn traverse(
                 trace!("Found better match at depth {} for position {}", depth, pos.byte);
                 *best = Some((node.clone(), start, depth));
             }
+        } else {
+            // Descendants lie within the node's span
+            return;
         }
     }
     match &**node {
@@ -667,7 +885,7 @@ fn traverse(
             traverse(right, pos, positions, best, depth + 1);
         }
         RholangNode::Par { processes: Some(procs), .. } => {
-            for proc in procs.iter() {
+            for proc in candidate_processes(procs, pos, positions) {
                 traverse(proc, pos, positions, best, depth + 1);
             }
         }
@@ -758,7 +976,7 @@ fn traverse(
         RholangNode::Eval { name, .. } => traverse(name, pos, positions, best, depth + 1),
         RholangNode::Quote { quotable, .. } => traverse(quotable, pos, positions, best, depth + 1),
         RholangNode::VarRef { var, .. } => traverse(var, pos, positions, best, depth + 1),
-        RholangNode::List { elements, remainder, .. } => {
+        RholangNode::List { elements, remainder, .. } | RholangNode::Pathmap { elements, remainder, .. } => {
             for elem in elements {
                 traverse(elem, pos, positions, best, depth + 1);
             }
@@ -873,3 +1091,57 @@ pub fn find_node_at_position(
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree_sitter::{parse_code, parse_to_ir, parse_with_positions};
+    use ropey::Rope;
+
+    #[test]
+    fn test_parallel_positions_match_sequential() {
+        let mut source = String::from("new x, y in {\n");
+        for i in 0..1_000 {
+            source.push_str(&format!("  x!({}, [{}, \"s\"]) | for (@v <- y) {{ y!(v + {}) }} |\n", i, i, i));
+        }
+        source.push_str("  Nil\n}\n");
+        let tree = parse_code(&source);
+        let rope = Rope::from_str(&source);
+        let ir = parse_to_ir(&tree, &rope);
+
+        let sequential = compute_absolute_positions_sequential(&ir);
+        let parallel = compute_absolute_positions_parallel(&ir);
+        assert!(sequential.len() > PARALLEL_SUBTREE_MIN);
+        assert_eq!(parallel.len(), sequential.len());
+        for (key, (start, end)) in &sequential {
+            let (parallel_start, parallel_end) = parallel[key];
+            assert_eq!((start.byte, end.byte), (parallel_start.byte, parallel_end.byte));
+            assert_eq!((*start, *end), (parallel_start, parallel_end));
+        }
+    }
+
+    #[test]
+    fn test_lookup_in_wide_par() {
+        let mut source = String::from("new x in {\n");
+        for i in 0..500 {
+            source.push_str(&format!("  x!({}) |\n", i));
+        }
+        source.push_str("  Nil\n}\n");
+        let (ir, positions) = parse_with_positions(&source);
+
+        // `x!(321)` is on line 322; its literal starts at column 5
+        let line_start = Rope::from_str(&source).line_to_byte(322);
+        let position = Position { row: 322, column: 5, byte: line_start + 5 };
+        let node = find_node_at_position(&ir, &positions, position).expect("node at position");
+        assert!(matches!(&*node, RholangNode::LongLiteral { value: 321, .. }), "found {:?}", node);
+
+        let (node, path) = find_node_at_position_with_path(&ir, &positions, position).expect("node at position");
+        assert!(matches!(&*node, RholangNode::LongLiteral { value: 321, .. }));
+        assert!(Arc::ptr_eq(&path[0], &ir));
+        assert!(path.iter().any(|n| matches!(&**n, RholangNode::Send { .. })));
+
+        // Past the end of the document: nothing below the root matches
+        let end = Position { row: 9_999, column: 0, byte: usize::MAX };
+        assert!(find_node_at_position(&ir, &positions, end).is_none());
+    }
+}
@@ -5,6 +5,7 @@
 //! - Contract signature matching for goto-definition
 //! - Overload resolution (multiple contracts with same name)
 //! - Pattern-aware navigation (e.g., map key paths)
+//! - Invocation templates for completion (signatures by contract name)
 //!
 //! # Architecture
 //!
@@ -26,6 +27,7 @@
 //! echo!("hello")  // Query finds echo contract
 //! ```
 
+use std::collections::HashMap;
 use std::sync::Arc;
 use pathmap::PathMap;
 use pathmap::zipper::{ZipperMoving, ZipperValues, ZipperWriting};
@@ -66,6 +68,11 @@ pub struct PatternMetadata {
 
     /// Optional: Parameter names if available from the source
     pub param_names: Option<Vec<String>>,
+
+    /// Whether each parameter is a name pattern (`ret`) rather than a
+    /// quoted process pattern (`@x`)
+    #[serde(default)]
+    pub param_is_name: Vec<bool>,
 }
 
 /// Pattern matching index for Rholang contracts using PathMap
@@ -81,6 +88,9 @@ pub struct RholangPatternIndex {
     /// MORK Space for symbol interning
     /// Shared across all pattern serialization
     space: Arc<Space>,
+
+    /// Signatures by contract name, mirroring the trie entries
+    signatures: HashMap<String, Vec<PatternMetadata>>,
 }
 
 // Manual Debug implementation since mork::space::Space doesn't implement Debug
@@ -89,6 +99,7 @@ impl std::fmt::Debug for RholangPatternIndex {
         f.debug_struct("RholangPatternIndex")
             .field("patterns", &self.patterns)
             .field("space", &"<Space>")
+            .field("signatures", &self.signatures)
             .finish()
     }
 }
@@ -99,6 +110,7 @@ impl RholangPatternIndex {
         Self {
             patterns: PathMap::new(),
             space: Arc::new(Space::new()),
+            signatures: HashMap::new(),
         }
     }
 
@@ -152,8 +164,14 @@ impl RholangPatternIndex {
             arity: params.len(),
             param_patterns: param_patterns.clone(),
             param_names,
+            param_is_name: params.iter().map(|p| !matches!(p.as_ref(), RholangNode::Quote { .. })).collect(),
         };
 
+        // Same path, same entry: replace the signature like the trie value
+        let signatures = self.signatures.entry(name.clone()).or_default();
+        signatures.retain(|existing| existing.param_patterns != metadata.param_patterns);
+        signatures.push(metadata.clone());
+
         // Use WriteZipper to insert into PathMap
         let mut wz = self.patterns.write_zipper();
         for segment in &path {
@@ -164,6 +182,11 @@ impl RholangPatternIndex {
         Ok(())
     }
 
+    /// All indexed signatures of the contracts named `name`, in indexing order
+    pub fn signatures(&self, name: &str) -> &[PatternMetadata] {
+        self.signatures.get(name).map(Vec::as_slice).unwrap_or(&[])
+    }
+
     /// Query contracts matching a call-site pattern
     ///
     /// Converts the call-site arguments to MORK patterns and searches the index
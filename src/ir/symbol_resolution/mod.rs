@@ -40,6 +40,7 @@ pub mod filters;
 pub mod global;
 pub mod generic;
 pub mod pattern_aware_resolver;
+pub mod registry_uri;
 
 pub use lexical_scope::LexicalScopeResolver;
 pub use composable::ComposableSymbolResolver;
@@ -47,6 +48,7 @@ pub use filters::{MettaPatternFilter, ChainedFilter};
 pub use global::GlobalVirtualSymbolResolver;
 pub use generic::GenericSymbolResolver;
 pub use pattern_aware_resolver::PatternAwareContractResolver;
+pub use registry_uri::{RegistryBinding, RegistryUriBindings, RegistryUriResolver};
 
 /// Resolution confidence level for symbol locations
 ///
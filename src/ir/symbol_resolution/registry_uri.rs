@@ -0,0 +1,274 @@
+//! Registry URI resolver
+//!
+//! Resolves registry URIs to the contracts bound to them across the workspace.
+//! A document publishes a contract by binding its name to a URI,
+//! `new counter(`rho:id:abc`) in { contract counter(ret) = { … } }`, and other
+//! documents reach it through `lookup!(`rho:id:abc`, *ch)` or by declaring
+//! `new counter(`rho:id:abc`)` themselves.
+//!
+//! Each indexed document carries its [`RegistryUriBindings`]; the
+//! [`RegistryUriResolver`] looks a URI up in all of them.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use ropey::Rope;
+use tower_lsp::lsp_types::{Position as LspPosition, Range};
+use tracing::debug;
+
+use crate::ir::rholang_node::{collect_contracts, Metadata, NodeBase, RholangNode, RholangNodeVector};
+use crate::ir::semantic_node::Position;
+use crate::ir::visitor::Visitor;
+use crate::lsp::line_index::{lsp_range, LineIndex};
+use crate::lsp::models::WorkspaceState;
+
+use super::{
+    SymbolResolver, SymbolLocation, ResolutionContext, ResolutionConfidence, SymbolKind,
+};
+
+/// A `new` name declared with a registry URI
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegistryBinding {
+    /// Registry URI, e.g. `rho:id:abc`
+    pub registry_uri: String,
+    /// Declared name
+    pub name: String,
+    /// Range of the declared name
+    pub decl: Range,
+    /// Range of the contract defined on the name in the `new` body, if any
+    pub contract: Option<Range>,
+}
+
+/// Registry URI declarations and URI literals of one document
+#[derive(Debug, Clone, Default)]
+pub struct RegistryUriBindings {
+    bindings: Vec<RegistryBinding>,
+    /// Every URI literal with its range, in document order
+    literals: Vec<(String, Range)>,
+}
+
+impl RegistryUriBindings {
+    /// Collects the URI-bound `new` names and URI literals of `ir`.
+    pub fn collect(
+        ir: &Arc<RholangNode>,
+        positions: &HashMap<usize, (Position, Position)>,
+        text: &Rope,
+        line_index: &LineIndex,
+    ) -> Self {
+        let collector = BindingCollector { positions, text, line_index, bindings: RefCell::new(Self::default()) };
+        collector.visit_node(ir);
+        collector.bindings.into_inner()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bindings.is_empty()
+    }
+
+    /// All URI-bound names, in document order
+    pub fn bindings(&self) -> &[RegistryBinding] {
+        &self.bindings
+    }
+
+    /// Bindings of `registry_uri` that define a contract
+    pub fn definitions<'a>(&'a self, registry_uri: &'a str) -> impl Iterator<Item = &'a RegistryBinding> + 'a {
+        self.bindings
+            .iter()
+            .filter(move |binding| binding.registry_uri == registry_uri && binding.contract.is_some())
+    }
+
+    /// The URI literal under `position`, e.g. the argument of a `lookup!` call
+    pub fn literal_at(&self, position: LspPosition) -> Option<&str> {
+        self.literals
+            .iter()
+            .find(|(_, range)| range.start <= position && position <= range.end)
+            .map(|(value, _)| value.as_str())
+    }
+
+    /// The registry URI of a name declared at `start` without a contract of its own
+    pub fn imported_at(&self, start: LspPosition) -> Option<&str> {
+        self.bindings
+            .iter()
+            .find(|binding| binding.decl.start == start && binding.contract.is_none())
+            .map(|binding| binding.registry_uri.as_str())
+    }
+}
+
+struct BindingCollector<'a> {
+    positions: &'a HashMap<usize, (Position, Position)>,
+    text: &'a Rope,
+    line_index: &'a LineIndex,
+    bindings: RefCell<RegistryUriBindings>,
+}
+
+impl BindingCollector<'_> {
+    fn range_of(&self, node: &Arc<RholangNode>) -> Option<Range> {
+        self.positions.get(&(Arc::as_ptr(node) as usize)).map(|span| lsp_range(self.text, self.line_index, span))
+    }
+}
+
+impl Visitor for BindingCollector<'_> {
+    fn visit_new(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        decls: &RholangNodeVector,
+        proc: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        let mut contracts = Vec::new();
+        collect_contracts(proc, &mut contracts);
+
+        for decl in decls.iter() {
+            let RholangNode::NameDecl { var, uri: Some(uri), .. } = &**decl else { continue };
+            let (RholangNode::Var { name, .. }, RholangNode::UriLiteral { value, .. }) = (&**var, &**uri) else {
+                continue;
+            };
+            let Some(decl_range) = self.range_of(var) else { continue };
+            let contract = contracts
+                .iter()
+                .find(|contract| {
+                    matches!(&**contract, RholangNode::Contract { name: contract_name, .. }
+                        if matches!(&**contract_name, RholangNode::Var { name: n, .. } if n == name))
+                })
+                .and_then(|contract| self.range_of(contract));
+            self.bindings.borrow_mut().bindings.push(RegistryBinding {
+                registry_uri: value.clone(),
+                name: name.clone(),
+                decl: decl_range,
+                contract,
+            });
+        }
+
+        for decl in decls.iter() {
+            self.visit_node(decl);
+        }
+        self.visit_node(proc);
+        Arc::clone(node)
+    }
+
+    fn visit_uri_literal(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        value: &String,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        if let Some(range) = self.range_of(node) {
+            self.bindings.borrow_mut().literals.push((value.clone(), range));
+        }
+        Arc::clone(node)
+    }
+}
+
+/// Resolves a registry URI to the contracts bound to it in any indexed document
+///
+/// The symbol name is the URI itself (`rho:id:abc`). Only bindings that define
+/// a contract are definitions, so system URIs such as `rho:io:stdout`, which
+/// are declared everywhere but defined nowhere, resolve to nothing.
+pub struct RegistryUriResolver {
+    workspace: Arc<WorkspaceState>,
+}
+
+impl RegistryUriResolver {
+    /// Create a new registry URI resolver
+    pub fn new(workspace: Arc<WorkspaceState>) -> Self {
+        Self { workspace }
+    }
+}
+
+impl SymbolResolver for RegistryUriResolver {
+    fn resolve_symbol(
+        &self,
+        symbol_name: &str,
+        _position: &Position,
+        _context: &ResolutionContext,
+    ) -> Vec<SymbolLocation> {
+        let mut locations: Vec<SymbolLocation> = self.workspace
+            .documents
+            .iter()
+            .flat_map(|entry| {
+                let uri = entry.key().clone();
+                entry.value()
+                    .registry_bindings
+                    .definitions(symbol_name)
+                    .filter_map(|binding| binding.contract)
+                    .map(|range| SymbolLocation {
+                        uri: uri.clone(),
+                        range,
+                        kind: SymbolKind::Function,
+                        confidence: ResolutionConfidence::Exact,
+                        metadata: None,
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        locations.sort_by(|a, b| {
+            a.uri.as_str().cmp(b.uri.as_str()).then(a.range.start.cmp(&b.range.start))
+        });
+
+        debug!(
+            "RegistryUriResolver: Found {} contracts bound to '{}'",
+            locations.len(),
+            symbol_name
+        );
+
+        locations
+    }
+
+    fn supports_language(&self, language: &str) -> bool {
+        language == "rholang"
+    }
+
+    fn name(&self) -> &'static str {
+        "RegistryUriResolver"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::tree_sitter::parse_with_positions;
+
+    fn bindings(source: &str) -> RegistryUriBindings {
+        let rope = Rope::from_str(source);
+        let (ir, positions) = parse_with_positions(source);
+        RegistryUriBindings::collect(&ir, &positions, &rope, &LineIndex::new(source))
+    }
+
+    #[test]
+    fn test_contract_bound_to_uri_is_a_definition() {
+        let source = "new counter(`rho:id:counter`), stdout(`rho:io:stdout`) in {\n  contract counter(ret) = { ret!(0) }\n}";
+        let bindings = bindings(source);
+
+        assert_eq!(bindings.bindings().len(), 2);
+        let definitions: Vec<_> = bindings.definitions("rho:id:counter").collect();
+        assert_eq!(definitions.len(), 1);
+        assert_eq!(definitions[0].name, "counter");
+        assert_eq!(definitions[0].contract.unwrap().start, LspPosition { line: 1, character: 2 });
+        assert_eq!(bindings.definitions("rho:io:stdout").count(), 0);
+    }
+
+    #[test]
+    fn test_lookup_literal_and_imported_name() {
+        let source = "new lookup(`rho:registry:lookup`), c(`rho:id:counter`), ch in {\n  lookup!(`rho:id:counter`, *ch) | c!(*ch)\n}";
+        let bindings = bindings(source);
+
+        assert_eq!(bindings.literal_at(LspPosition { line: 1, character: 14 }), Some("rho:id:counter"));
+        assert_eq!(bindings.literal_at(LspPosition { line: 1, character: 35 }), None);
+
+        let imported = bindings.bindings().iter().find(|binding| binding.name == "c").unwrap();
+        assert_eq!(bindings.imported_at(imported.decl.start), Some("rho:id:counter"));
+        assert_eq!(bindings.definitions("rho:id:counter").count(), 0);
+    }
+
+    #[test]
+    fn test_literal_ranges_count_utf16_units() {
+        let source = "new lookup(`rho:registry:lookup`), ch in {\n  ch!(\"é🦀\") | lookup!(`rho:id:counter`, *ch)\n}";
+        let bindings = bindings(source);
+
+        assert_eq!(bindings.literal_at(LspPosition { line: 1, character: 24 }), Some("rho:id:counter"));
+        assert_eq!(bindings.literal_at(LspPosition { line: 1, character: 40 }), None);
+    }
+}
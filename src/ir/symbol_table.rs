@@ -2,6 +2,7 @@ use dashmap::DashMap;
 use rustc_hash::FxBuildHasher;  // Phase 2 optimization: ~2x faster than default hasher
 use std::sync::Arc;
 use crate::ir::rholang_node::{Position, RholangNode};
+use crate::ir::structured_documentation::ParamDoc;
 use tower_lsp::lsp_types::Url;
 use rpds::Vector;
 use archery::ArcK;
@@ -44,6 +45,10 @@ pub struct Symbol {
     pub contract_identifier_node: Option<Arc<RholangNode>>,
     /// Documentation extracted from doc comments (Phase 5: Completion Item Documentation)
     pub documentation: Option<String>,
+    /// Reason from a `@deprecated` doc directive (empty if none was given)
+    pub deprecated: Option<String>,
+    /// Contract parameters documented by `@param` directives, in directive order
+    pub param_docs: Vec<ParamDoc>,
 }
 
 impl Symbol {
@@ -58,6 +63,8 @@ impl Symbol {
             contract_pattern: None,
             contract_identifier_node: None,
             documentation: None,
+            deprecated: None,
+            param_docs: Vec::new(),
         }
     }
 
@@ -83,6 +90,8 @@ impl Symbol {
             }),
             contract_identifier_node: None,
             documentation: None,
+            deprecated: None,
+            param_docs: Vec::new(),
         }
     }
 
@@ -11,9 +11,11 @@
 //! - Attaches doc text as metadata to declaration nodes
 //! - Works with contracts, new bindings, and let bindings
 //!
-//! # Metadata Key
+//! # Metadata Keys
 //!
 //! Documentation is stored in node metadata with the key `"documentation"`.
+//! A `@deprecated` doc directive on a contract or `new` name is stored as its
+//! reason (a `String`, empty if none was given) under the key `"deprecated"`.
 //! Access it with:
 //! ```rust,ignore
 //! if let Some(metadata) = node.metadata() {
@@ -39,6 +41,20 @@ use crate::ir::structured_documentation::StructuredDocumentation;
 /// Metadata key for attached documentation
 pub const DOC_METADATA_KEY: &str = "documentation";
 
+/// Metadata key for the reason of a `@deprecated` doc directive
+pub const DEPRECATION_METADATA_KEY: &str = "deprecated";
+
+/// Returns `metadata` with `key` set to `value`, preserving existing entries.
+fn with_metadata_entry(
+    metadata: &Option<Arc<Metadata>>,
+    key: &str,
+    value: Arc<dyn Any + Send + Sync>,
+) -> Option<Arc<Metadata>> {
+    let mut meta = metadata.as_ref().map_or_else(HashMap::new, |m| (**m).clone());
+    meta.insert(key.to_string(), value);
+    Some(Arc::new(meta))
+}
+
 /// Attaches documentation comments to declaration nodes
 ///
 /// This visitor traverses the IR tree and for each declaration node,
@@ -87,14 +103,12 @@ impl DocumentationAttacher {
         // Phase 7: Get ALL consecutive doc comments (not just the last one)
         let doc_comments = self.document_ir.doc_comments_before(node_pos);
 
-        if doc_comments.is_empty() {
-            return None;
-        }
-
-        // Extract cleaned text from each comment (need to collect Strings first)
+        // Extract cleaned text from each comment (need to collect Strings first),
+        // followed by `// @param` and `// @returns` directives in regular comments
         let doc_text_strings: Vec<String> = doc_comments
             .iter()
             .filter_map(|comment| comment.doc_text())
+            .chain(self.document_ir.param_directives_before(node_pos))
             .collect();
 
         if doc_text_strings.is_empty() {
@@ -118,6 +132,26 @@ impl DocumentationAttacher {
         Some(structured)
     }
 
+    /// Attaches a preceding `@deprecated` directive to a `new` name declaration
+    fn attach_name_deprecation(&self, decl: &Arc<RholangNode>) -> Arc<RholangNode> {
+        let RholangNode::NameDecl { base, var, uri, metadata } = &**decl else {
+            return Arc::clone(decl);
+        };
+        let deprecation = self
+            .positions
+            .get(&(Arc::as_ptr(decl) as usize))
+            .and_then(|(start, _end)| self.document_ir.deprecation_before(start));
+
+        match deprecation {
+            Some(reason) => Arc::new(RholangNode::NameDecl {
+                base: base.clone(),
+                var: var.clone(),
+                uri: uri.clone(),
+                metadata: with_metadata_entry(metadata, DEPRECATION_METADATA_KEY, Arc::new(reason)),
+            }),
+            None => Arc::clone(decl),
+        }
+    }
 }
 
 impl Visitor for DocumentationAttacher {
@@ -133,14 +167,10 @@ impl Visitor for DocumentationAttacher {
         metadata: &Option<Arc<Metadata>>,
     ) -> Arc<RholangNode> {
         // Phase 7: Extract structured documentation using new method
-        let structured_doc = {
-            let node_ptr = Arc::as_ptr(node) as usize;
-            if let Some((node_pos, _node_end)) = self.positions.get(&node_ptr) {
-                self.extract_structured_documentation(node_pos)
-            } else {
-                None
-            }
-        };
+        let node_ptr = Arc::as_ptr(node) as usize;
+        let node_pos = self.positions.get(&node_ptr).map(|(start, _end)| start);
+        let structured_doc = node_pos.and_then(|pos| self.extract_structured_documentation(pos));
+        let deprecation = node_pos.and_then(|pos| self.document_ir.deprecation_before(pos));
 
         // Visit children
         let new_name = self.visit_node(name);
@@ -148,7 +178,7 @@ impl Visitor for DocumentationAttacher {
 
         // Check if children changed or if we need to attach documentation
         let children_changed = !Arc::ptr_eq(name, &new_name) || !Arc::ptr_eq(proc, &new_proc);
-        let need_new_node = children_changed || structured_doc.is_some();
+        let need_new_node = children_changed || structured_doc.is_some() || deprecation.is_some();
 
         if need_new_node {
             // Prepare metadata with documentation if needed
@@ -169,6 +199,10 @@ impl Visitor for DocumentationAttacher {
             } else {
                 metadata.clone()
             };
+            let new_metadata = match deprecation {
+                Some(reason) => with_metadata_entry(&new_metadata, DEPRECATION_METADATA_KEY, Arc::new(reason)),
+                None => new_metadata,
+            };
 
             Arc::new(RholangNode::Contract {
                 base: base.clone(),
@@ -202,11 +236,18 @@ impl Visitor for DocumentationAttacher {
             }
         };
 
+        // Attach `@deprecated` directives to the individual names they precede
+        let new_decls: RholangNodeVector = decls
+            .iter()
+            .map(|decl| self.attach_name_deprecation(decl))
+            .collect();
+
         // Visit children
         let new_proc = self.visit_node(proc);
 
         // Check if children changed or if we need to attach documentation
-        let children_changed = !Arc::ptr_eq(proc, &new_proc);
+        let children_changed = !Arc::ptr_eq(proc, &new_proc)
+            || decls.iter().zip(new_decls.iter()).any(|(a, b)| !Arc::ptr_eq(a, b));
         let need_new_node = children_changed || structured_doc.is_some();
 
         if need_new_node {
@@ -230,7 +271,7 @@ impl Visitor for DocumentationAttacher {
 
             Arc::new(RholangNode::New {
                 base: base.clone(),
-                decls: decls.clone(),
+                decls: new_decls,
                 proc: new_proc,
                 metadata: new_metadata,
             })
@@ -438,4 +479,65 @@ contract bar() = { Nil }
             panic!("Expected Contract node");
         }
     }
+
+    #[test]
+    fn test_attach_deprecation_to_contract() {
+        let source = r#"
+// @deprecated use greet2 instead
+/// Sends a greeting
+contract greet(@name) = {
+    Nil
+}
+"#;
+        let tree = parse_code(source);
+        let rope = Rope::from_str(source);
+        let document_ir = parse_to_document_ir(&tree, &rope);
+
+        let attacher = DocumentationAttacher::new(document_ir.clone());
+        let documented_ir = attacher.visit_node(&document_ir.root);
+
+        if let RholangNode::Contract { metadata, .. } = documented_ir.as_ref() {
+            let meta = metadata.as_ref().expect("Contract should have metadata");
+            let reason = meta
+                .get(DEPRECATION_METADATA_KEY)
+                .and_then(|r| r.downcast_ref::<String>())
+                .expect("Contract should be marked deprecated");
+            assert_eq!(reason, "use greet2 instead");
+            assert!(meta.contains_key(DOC_METADATA_KEY), "Documentation should still be attached");
+        } else {
+            panic!("Expected Contract node, got: {:?}", documented_ir);
+        }
+    }
+
+    #[test]
+    fn test_attach_deprecation_to_new_name() {
+        let source = r#"new
+  // @deprecated
+  oldChan,
+  newChan
+in {
+  Nil
+}
+"#;
+        let tree = parse_code(source);
+        let rope = Rope::from_str(source);
+        let document_ir = parse_to_document_ir(&tree, &rope);
+
+        let attacher = DocumentationAttacher::new(document_ir.clone());
+        let documented_ir = attacher.visit_node(&document_ir.root);
+
+        let RholangNode::New { decls, .. } = documented_ir.as_ref() else {
+            panic!("Expected New node, got: {:?}", documented_ir);
+        };
+        let deprecations: Vec<Option<String>> = decls
+            .iter()
+            .map(|decl| {
+                decl.metadata()
+                    .and_then(|m| m.get(DEPRECATION_METADATA_KEY))
+                    .and_then(|r| r.downcast_ref::<String>())
+                    .cloned()
+            })
+            .collect();
+        assert_eq!(deprecations, vec![Some(String::new()), None]);
+    }
 }
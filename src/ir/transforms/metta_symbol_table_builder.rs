@@ -573,6 +573,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "metta")]
     fn test_parser_to_symbol_table() {
         use crate::parsers::MettaParser;
 
@@ -610,6 +611,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "metta")]
     fn test_grounded_query_syntax() {
         use crate::parsers::MettaParser;
 
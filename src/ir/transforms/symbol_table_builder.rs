@@ -8,7 +8,7 @@ use rpds::Vector;
 use tower_lsp::lsp_types::Url;
 use tracing::trace;
 
-use crate::ir::rholang_node::{Metadata, RholangNode, RholangNodeVector, NodeBase, Position, RholangSendType};
+use crate::ir::rholang_node::{strip_quote_eval, Metadata, RholangNode, RholangNodeVector, NodeBase, Position, RholangSendType};
 use crate::ir::symbol_table::{Symbol, SymbolTable, SymbolType};
 use crate::ir::type_extraction::{TypeChecker, TypeExtractor};
 use crate::ir::visitor::Visitor;
@@ -133,6 +133,16 @@ impl SymbolTableBuilder {
         node.with_metadata(Some(Arc::new(data)))
     }
 
+    /// Returns the `@deprecated` reason attached by the documentation attacher, if any.
+    fn deprecation_reason(metadata: &Option<Arc<Metadata>>) -> Option<String> {
+        use crate::ir::transforms::documentation_attacher::DEPRECATION_METADATA_KEY;
+
+        metadata.as_ref()?
+            .get(DEPRECATION_METADATA_KEY)?
+            .downcast_ref::<String>()
+            .cloned()
+    }
+
     /// Updates a node's metadata with the current symbol table and optional symbol.
     fn update_with_current_table<'b>(
         &self,
@@ -192,9 +202,10 @@ impl SymbolTableBuilder {
 
     /// Extract contract name from a channel node
     ///
-    /// Handles both `foo` (Var) and `@"foo"` (Quote) syntax
+    /// Handles both `foo` (Var) and `@"foo"` (Quote) syntax, also through
+    /// quote/eval round-trips like `@*foo`
     fn extract_contract_name(&self, channel: &Arc<RholangNode>) -> Option<String> {
-        match &**channel {
+        match strip_quote_eval(channel) {
             RholangNode::Var { name, .. } => Some(name.clone()),
             RholangNode::Quote { quotable, .. } => {
                 match &**quotable {
@@ -960,17 +971,18 @@ impl Visitor for SymbolTableBuilder {
     ) -> Arc<RholangNode> {
         let new_table = self.push_scope();
         for d in decls.iter() {
-            if let RholangNode::NameDecl { var, .. } = &**d {
+            if let RholangNode::NameDecl { var, metadata: decl_metadata, .. } = &**d {
                 if let RholangNode::Var { name, .. } = &**var {
                     if !name.is_empty() {  // Skip empty variable names
                         let location = var.absolute_start(&self.root);
-                        let symbol = Arc::new(Symbol::new(
+                        let mut symbol = Symbol::new(
                             name.clone(),
                             SymbolType::Variable,
                             self.current_uri.clone(),
                             location,
-                        ));
-                        new_table.insert(symbol);
+                        );
+                        symbol.deprecated = Self::deprecation_reason(decl_metadata);
+                        new_table.insert(Arc::new(symbol));
                     }
                 }
             }
@@ -1015,6 +1027,8 @@ impl Visitor for SymbolTableBuilder {
                                 contract_pattern: None,
                                 contract_identifier_node: None,
                                 documentation: None,
+                                deprecated: None,
+                                param_docs: Vec::new(),
                             });
                             new_table.insert(symbol);
                             trace!("Declared variable '{}' in let scope at {:?}", var_name, decl_loc);
@@ -1161,6 +1175,7 @@ impl Visitor for SymbolTableBuilder {
                     // Phase 7: Try StructuredDocumentation first (new format)
                     if let Some(structured_doc) = doc_any.downcast_ref::<StructuredDocumentation>() {
                         symbol.documentation = Some(structured_doc.to_plain_text());
+                        symbol.param_docs = structured_doc.params.clone();
                         trace!("Extracted structured documentation for contract '{}': summary length = {}, params = {}",
                             contract_name, structured_doc.summary.len(), structured_doc.params.len());
                     }
@@ -1172,6 +1187,8 @@ impl Visitor for SymbolTableBuilder {
                 }
             }
 
+            symbol.deprecated = Self::deprecation_reason(metadata);
+
             // Store complex identifier node for structural matching (Phase 2)
             if let Some(complex_node) = identifier_node {
                 symbol.contract_identifier_node = Some(complex_node);
@@ -43,6 +43,8 @@
 
 mod visitor_trait;
 
+pub mod node_path;
+
 // Experimental submodules for future trait composition
 // These are not yet integrated but show the planned structure
 pub mod literals;
@@ -0,0 +1,73 @@
+//! Kinds of the IR nodes a thread is visiting, for crash reports
+//!
+//! [`Visitor::visit_node`](super::Visitor::visit_node) enters a frame for
+//! every node it dispatches, so the frames of a thread spell the path from
+//! the root to the node being visited, such as
+//! `Rholang::Par > Rholang::New > Rholang::Send`. When a visit panics, the
+//! first frame to unwind saves the path, which the code catching the panic
+//! picks up with [`take_at_panic`].
+
+use std::cell::RefCell;
+
+thread_local! {
+    static PATH: RefCell<Vec<&'static str>> = const { RefCell::new(Vec::new()) };
+    static AT_PANIC: RefCell<Option<Vec<&'static str>>> = const { RefCell::new(None) };
+}
+
+/// A node being visited, until dropped
+#[must_use]
+pub struct Frame(());
+
+/// Enters a node of kind `kind`.
+pub fn enter(kind: &'static str) -> Frame {
+    PATH.with(|path| path.borrow_mut().push(kind));
+    Frame(())
+}
+
+impl Drop for Frame {
+    fn drop(&mut self) {
+        PATH.with(|path| {
+            let mut path = path.borrow_mut();
+            if std::thread::panicking() {
+                AT_PANIC.with(|at_panic| {
+                    at_panic.borrow_mut().get_or_insert_with(|| path.clone());
+                });
+            }
+            path.pop();
+        });
+    }
+}
+
+/// Forgets the path saved by an earlier panic on this thread.
+pub fn clear() {
+    AT_PANIC.with(|at_panic| at_panic.borrow_mut().take());
+}
+
+/// The path of the node being visited when this thread last panicked, if
+/// it panicked in a visit since the last [`clear`].
+pub fn take_at_panic() -> Option<Vec<&'static str>> {
+    AT_PANIC.with(|at_panic| at_panic.borrow_mut().take())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::panic::catch_unwind;
+
+    #[test]
+    fn test_path_at_panic() {
+        clear();
+        let result = catch_unwind(|| -> i32 {
+            let _par = enter("Rholang::Par");
+            {
+                let _new = enter("Rholang::New");
+            }
+            let _send = enter("Rholang::Send");
+            panic!("boom");
+        });
+        assert!(result.is_err());
+        assert_eq!(take_at_panic(), Some(vec!["Rholang::Par", "Rholang::Send"]));
+        assert_eq!(take_at_panic(), None);
+        assert!(PATH.with(|path| path.borrow().is_empty()));
+    }
+}
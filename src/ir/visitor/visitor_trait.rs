@@ -3,7 +3,8 @@ use rpds::Vector;
 use archery::ArcK;
 
 use super::super::rholang_node::{RholangNode, RholangNodeVector, Metadata, CommentKind, RholangSendType, RholangBundleType, BinOperator, UnaryOperator, RholangVarRefKind, Position};
-use super::super::semantic_node::{NodeBase};
+use super::super::semantic_node::{NodeBase, SemanticNode};
+use super::node_path;
 
 /// Provides a visitor pattern for traversing and transforming the Rholang Intermediate Representation (IR) tree.
 /// This module enables implementors to define custom logic for processing each node type, facilitating operations
@@ -12,6 +13,7 @@ pub trait Visitor {
 
     /// Entry point for visiting an IR node, dispatching to the appropriate type-specific method.
     /// Implementors typically do not override this method unless custom dispatching is needed.
+    /// The kind of the node is on the thread's [`node_path`] while it is visited.
     ///
     /// # Arguments
     /// * node - The node to visit.
@@ -19,6 +21,7 @@ pub trait Visitor {
     /// # Returns
     /// The transformed node, or the original if unchanged.
     fn visit_node(&self, node: &Arc<RholangNode>) -> Arc<RholangNode> {
+        let _frame = node_path::enter(SemanticNode::type_name(node.as_ref()));
         match &**node {
             RholangNode::Par { base, left: Some(left), right: Some(right), processes: None, metadata } => self.visit_par(node, base, left, right, metadata),
             // N-ary Par - visit all processes
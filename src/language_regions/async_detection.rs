@@ -301,7 +301,8 @@ fn detect_regions_batch_blocking(
     }
 }
 
-#[cfg(test)]
+// The default detectors only keep MeTTa regions when MeTTa is compiled in
+#[cfg(all(test, feature = "metta"))]
 mod tests {
     use super::*;
 
@@ -0,0 +1,230 @@
+//! Detection result caching keyed by send content
+//!
+//! Whether the string literals of a send are embedded MeTTa depends only on
+//! the text of the send: the channel literal it targets and the literals (or
+//! `++` concatenations) it sends. The semantic detector therefore caches the
+//! regions found in each send under a hash of that text, relative to the
+//! send's start. After an edit, sends whose text is unchanged are relocated
+//! from the cache without re-examining their literals; only sends in edited
+//! regions, whose text and hash changed, are examined again.
+//!
+//! The cache lives in the detector instance registered with the
+//! `DetectorRegistry`, so it is shared by every request to the async
+//! detection worker and survives between changes.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use lsp_types::{Position, Range};
+use parking_lot::Mutex;
+use tracing::trace;
+use tree_sitter::Node as TSNode;
+
+use super::concatenation::ConcatPart;
+use super::LanguageRegion;
+
+/// Sends remembered before the cache starts over
+pub const MAX_CACHED_SENDS: usize = 4096;
+
+/// Start of the node a cached result belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Origin {
+    pub byte: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Origin {
+    pub fn of(node: &TSNode) -> Self {
+        Origin { byte: node.start_byte(), line: node.start_position().row, column: node.start_position().column }
+    }
+
+    /// `line:column` relative to this origin.
+    fn relative(&self, line: usize, column: usize) -> (usize, usize) {
+        if line == self.line {
+            (0, column - self.column)
+        } else {
+            (line - self.line, column)
+        }
+    }
+
+    /// Relative `line:column` placed at this origin.
+    fn absolute(&self, line: usize, column: usize) -> (usize, usize) {
+        if line == 0 {
+            (self.line, self.column + column)
+        } else {
+            (self.line + line, column)
+        }
+    }
+}
+
+/// Hits and misses since the cache was created
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+/// Regions found in sends, by hash of the send text
+#[derive(Debug, Default)]
+pub struct DetectionCache {
+    entries: Mutex<HashMap<(u64, usize), Arc<Vec<LanguageRegion>>>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl DetectionCache {
+    /// The regions of the send `text` starting at `origin`, from the cache or
+    /// from `detect` (whose regions use absolute positions).
+    pub fn get_or_detect(
+        &self,
+        text: &str,
+        origin: Origin,
+        detect: impl FnOnce() -> Vec<LanguageRegion>,
+    ) -> Vec<LanguageRegion> {
+        let key = (hash(text), text.len());
+        let cached = self.entries.lock().get(&key).cloned();
+        if let Some(relative) = cached {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return relative
+                .iter()
+                .map(|region| shift(region, |line, column| origin.absolute(line, column), |byte| origin.byte + byte))
+                .collect();
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let regions = detect();
+        let relative: Vec<LanguageRegion> = regions
+            .iter()
+            .map(|region| shift(region, |line, column| origin.relative(line, column), |byte| byte - origin.byte))
+            .collect();
+        let mut entries = self.entries.lock();
+        if entries.len() >= MAX_CACHED_SENDS {
+            trace!("Detection cache full; starting over");
+            entries.clear();
+        }
+        entries.insert(key, Arc::new(relative));
+        regions
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats { hits: self.hits.load(Ordering::Relaxed), misses: self.misses.load(Ordering::Relaxed) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.lock().is_empty()
+    }
+}
+
+fn hash(text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// `region` with its positions mapped by `position` and byte offsets by `byte`.
+fn shift(
+    region: &LanguageRegion,
+    position: impl Fn(usize, usize) -> (usize, usize),
+    byte: impl Fn(usize) -> usize,
+) -> LanguageRegion {
+    let (start_line, start_column) = position(region.start_line, region.start_column);
+    let range = |range: &Range| {
+        let point = |p: &Position| {
+            let (line, character) = position(p.line as usize, p.character as usize);
+            Position { line: line as u32, character: character as u32 }
+        };
+        Range { start: point(&range.start), end: point(&range.end) }
+    };
+    let concatenation_chain = region.concatenation_chain.as_ref().map(|chain| {
+        let mut chain = chain.clone();
+        chain.full_range = range(&chain.full_range);
+        for part in &mut chain.parts {
+            match part {
+                ConcatPart::Literal { original_range, .. } | ConcatPart::Hole { original_range } => {
+                    *original_range = range(original_range);
+                }
+            }
+        }
+        chain
+    });
+    LanguageRegion {
+        start_byte: byte(region.start_byte),
+        end_byte: byte(region.end_byte),
+        start_line,
+        start_column,
+        concatenation_chain,
+        ..region.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language_regions::SemanticDetector;
+    use crate::tree_sitter::parse_code;
+    use ropey::Rope;
+
+    fn detect(detector: &SemanticDetector, source: &str) -> Vec<LanguageRegion> {
+        let tree = parse_code(source);
+        detector.detect_regions_cached(source, &tree, &Rope::from_str(source))
+    }
+
+    fn uncached(source: &str) -> Vec<LanguageRegion> {
+        let tree = parse_code(source);
+        SemanticDetector::detect_regions(source, &tree, &Rope::from_str(source))
+    }
+
+    fn summary(regions: &[LanguageRegion]) -> Vec<(usize, usize, usize, usize, String)> {
+        regions
+            .iter()
+            .map(|r| (r.start_byte, r.end_byte, r.start_line, r.start_column, r.content.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_unchanged_sends_are_relocated_from_the_cache() {
+        let detector = SemanticDetector::new();
+        let before = "@\"rho:metta:compile\"!(\"(= a 1)\") |\n@\"rho:io:stdout\"!(\"hi\")";
+        assert_eq!(summary(&detect(&detector, before)), summary(&uncached(before)));
+        let first = detector.cache_stats();
+        assert_eq!(first.hits, 0);
+
+        // An edit above both sends moves them without changing their text
+        let after = format!("new x in {{ Nil }} |\n  {}", before);
+        assert_eq!(summary(&detect(&detector, &after)), summary(&uncached(&after)));
+        assert_eq!(detector.cache_stats().hits, 2);
+        assert_eq!(detector.cache_stats().misses, first.misses);
+
+        // Only the edited send is examined again
+        let edited = after.replace("(= a 1)", "(= a 2)");
+        let regions = detect(&detector, &edited);
+        assert_eq!(summary(&regions), summary(&uncached(&edited)));
+        assert_eq!(detector.cache_stats().misses, first.misses + 1);
+    }
+
+    #[test]
+    fn test_concatenation_ranges_are_relocated() {
+        let detector = SemanticDetector::new();
+        let send = "@\"rho:metta:compile\"!(\"(= \" ++ name ++ \" 1)\")";
+        detect(&detector, send);
+        let moved = format!("\n\n   {}", send);
+        let regions = detect(&detector, &moved);
+        let expected = uncached(&moved);
+        assert_eq!(detector.cache_stats().hits, 1);
+
+        let ranges = |regions: &[LanguageRegion]| -> Vec<Range> {
+            let chain = regions[0].concatenation_chain.as_ref().unwrap();
+            std::iter::once(chain.full_range).chain(chain.parts.iter().map(|part| *part.original_range())).collect()
+        };
+        assert_eq!(ranges(&regions), ranges(&expected));
+        assert_eq!(ranges(&regions)[0].start.line, 2);
+    }
+}
@@ -27,20 +27,25 @@ use tree_sitter::Tree;
 ///
 /// ```rust,ignore
 /// let mut registry = DetectorRegistry::new();
-/// registry.register(Arc::new(SemanticDetector));
+/// registry.register(Arc::new(SemanticDetector::new()));
 /// registry.register(Arc::new(DirectiveParser));
 ///
 /// let regions = registry.detect_all(source, &tree, &rope);
 /// ```
 pub struct DetectorRegistry {
     detectors: Vec<Arc<dyn VirtualDocumentDetector>>,
+    /// Languages whose regions are kept, or `None` to keep every region
+    languages: Option<&'static [&'static str]>,
 }
 
 impl DetectorRegistry {
     /// Creates a new empty detector registry
+    ///
+    /// Regions of every language are kept.
     pub fn new() -> Self {
         Self {
             detectors: Vec::new(),
+            languages: None,
         }
     }
 
@@ -50,14 +55,19 @@ impl DetectorRegistry {
     /// - `DirectiveParser` - Comment directive detection (priority 100)
     /// - `SemanticDetector` - Semantic analysis detection (priority 50)
     /// - `ChannelFlowAnalyzer` - Channel flow detection (priority 25)
+    ///
+    /// Only regions of the languages compiled into this build
+    /// ([`super::SUPPORTED_LANGUAGES`]) are kept, so that a build without
+    /// the `metta` feature creates no virtual documents for MeTTa code.
     pub fn with_defaults() -> Self {
-        use super::{DirectiveParser, SemanticDetector, ChannelFlowAnalyzer};
+        use super::{DirectiveParser, SemanticDetector, ChannelFlowAnalyzer, SUPPORTED_LANGUAGES};
 
         let mut registry = Self::new();
+        registry.languages = Some(SUPPORTED_LANGUAGES);
 
         // Register detectors in priority order (higher priority first)
         registry.register(Arc::new(DirectiveParser));
-        registry.register(Arc::new(SemanticDetector));
+        registry.register(Arc::new(SemanticDetector::new()));
         registry.register(Arc::new(ChannelFlowAnalyzer::new()));
 
         debug!(
@@ -171,6 +181,7 @@ impl DetectorRegistry {
 
             all_regions.extend(regions);
         }
+        self.retain_supported(&mut all_regions);
 
         let initial_count = all_regions.len();
 
@@ -187,6 +198,23 @@ impl DetectorRegistry {
         deduplicated
     }
 
+    /// Drops the regions of languages this registry does not keep.
+    fn retain_supported(&self, regions: &mut Vec<LanguageRegion>) {
+        let Some(languages) = self.languages else {
+            return;
+        };
+        regions.retain(|region| {
+            let supported = languages.contains(&region.language.as_str());
+            if !supported {
+                debug!(
+                    "Ignoring {} region at byte {}-{}: language not enabled in this build",
+                    region.language, region.start_byte, region.end_byte
+                );
+            }
+            supported
+        });
+    }
+
     /// Deduplicates overlapping regions, keeping the first occurrence
     ///
     /// Since detectors run in priority order and results are collected
@@ -280,6 +308,7 @@ impl DetectorRegistry {
             all_regions.extend(regions);
         }
 
+        self.retain_supported(&mut all_regions);
         all_regions
     }
 
@@ -718,6 +747,25 @@ mod tests {
     }
 
     #[test]
+    fn test_defaults_drop_languages_not_enabled() {
+        use crate::tree_sitter::parse_code;
+
+        let mut registry = DetectorRegistry::with_defaults();
+        registry.register(Arc::new(MockDetector {
+            name: "mock",
+            priority: 200,
+            regions: vec![create_mock_region("lang1")],
+            can_parallel: false,
+        }));
+        let source = "Nil";
+        let tree = parse_code(source);
+        let rope = Rope::from_str(source);
+        assert!(registry.detect_all(source, &tree, &rope).is_empty());
+        assert!(registry.detect_all_with_parallelism(source, &tree, &rope).is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "metta")]
     fn test_directive_overrides_semantic_detection() {
         use crate::tree_sitter::parse_code;
 
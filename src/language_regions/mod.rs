@@ -1,7 +1,9 @@
 pub mod directive_parser;
 pub mod semantic_detector;
+pub mod detection_cache;
 pub mod channel_flow_analyzer;
 pub mod virtual_document;
+pub mod virtual_edit;
 pub mod concatenation;
 pub mod detector;
 pub mod detector_registry;
@@ -9,9 +11,24 @@ pub mod async_detection;
 
 pub use directive_parser::{DirectiveParser, LanguageRegion, RegionSource};
 pub use semantic_detector::SemanticDetector;
+pub use detection_cache::{CacheStats, DetectionCache};
 pub use channel_flow_analyzer::ChannelFlowAnalyzer;
-pub use virtual_document::{VirtualDocument, VirtualDocumentRegistry};
+pub use virtual_document::{canonical_virtual_uri, VirtualDocument, VirtualDocumentRegistry, VIRTUAL_URI_SCHEME};
+pub use virtual_edit::{translate_host_edit, VirtualEdit};
 pub use concatenation::{ConcatPart, ConcatenationChain, HoledPositionMap, extract_concatenation_chain};
 pub use detector::VirtualDocumentDetector;
 pub use detector_registry::DetectorRegistry;
 pub use async_detection::{DetectionWorkerHandle, DetectionRequest, DetectionResult, spawn_detection_worker};
+
+/// Embedded languages this build can analyze, per the enabled cargo features
+/// (`metta` for MeTTa). Regions of other languages are dropped by the
+/// default [`DetectorRegistry`].
+pub const SUPPORTED_LANGUAGES: &[&str] = &[
+    #[cfg(feature = "metta")]
+    "metta",
+];
+
+/// Whether regions of `language` can be analyzed by this build.
+pub fn is_supported_language(language: &str) -> bool {
+    SUPPORTED_LANGUAGES.contains(&language)
+}
@@ -8,12 +8,45 @@ use tree_sitter::{Node as TSNode, Tree};
 use ropey::Rope;
 use tracing::{debug, trace};
 
+use super::detection_cache::{CacheStats, DetectionCache, Origin};
 use super::{LanguageRegion, RegionSource};
 
 /// Semantic analyzer for detecting embedded language regions
-pub struct SemanticDetector;
+///
+/// Through [`VirtualDocumentDetector`](super::detector::VirtualDocumentDetector)
+/// the detector remembers the regions found in each send, so sends left
+/// unchanged by an edit are not examined again.
+#[derive(Debug, Default)]
+pub struct SemanticDetector {
+    cache: DetectionCache,
+}
 
 impl SemanticDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Like [`detect_regions`](Self::detect_regions), reusing the regions of
+    /// sends whose text was seen before.
+    pub fn detect_regions_cached(&self, source: &str, tree: &Tree, _rope: &Rope) -> Vec<LanguageRegion> {
+        let mut regions = Vec::new();
+        self.find_metta_sends_cached(&tree.root_node(), source, &mut regions);
+
+        let stats = self.cache.stats();
+        debug!(
+            "Semantic detector found {} regions (cache: {} hits, {} misses)",
+            regions.len(),
+            stats.hits,
+            stats.misses
+        );
+        regions
+    }
+
+    /// Hits and misses of the send cache.
+    pub fn cache_stats(&self) -> CacheStats {
+        self.cache.stats()
+    }
+
     /// Detects embedded language regions by analyzing semantic patterns
     ///
     /// Currently detects:
@@ -56,6 +89,30 @@ impl SemanticDetector {
         }
     }
 
+    /// Like [`find_metta_sends`](Self::find_metta_sends), looking each
+    /// outermost send up in the cache by its text
+    fn find_metta_sends_cached<'a>(
+        &self,
+        node: &TSNode<'a>,
+        source: &'a str,
+        regions: &mut Vec<LanguageRegion>,
+    ) {
+        if node.kind() == "send" {
+            let text = &source[node.byte_range()];
+            regions.extend(self.cache.get_or_detect(text, Origin::of(node), || {
+                let mut found = Vec::new();
+                Self::find_metta_sends(node, source, &mut found);
+                found
+            }));
+            return;
+        }
+
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            self.find_metta_sends_cached(&child, source, regions);
+        }
+    }
+
     /// Checks if a send operation is to a MeTTa compiler channel
     fn check_send_for_metta<'a>(
         send_node: &TSNode<'a>,
@@ -317,7 +374,7 @@ impl super::detector::VirtualDocumentDetector for SemanticDetector {
     }
 
     fn detect(&self, source: &str, tree: &Tree, rope: &Rope) -> Vec<LanguageRegion> {
-        Self::detect_regions(source, tree, rope)
+        self.detect_regions_cached(source, tree, rope)
     }
 
     fn priority(&self) -> i32 {
@@ -330,8 +387,8 @@ impl super::detector::VirtualDocumentDetector for SemanticDetector {
     }
 
     fn supports_incremental(&self) -> bool {
-        // Could support incremental by only re-scanning modified send nodes
-        false
+        // Sends whose text is unchanged are served from the cache
+        true
     }
 }
 
@@ -10,6 +10,21 @@ use tracing::{debug, trace, warn};
 
 use super::LanguageRegion;
 
+/// URI scheme clients may use to open a virtual document directly
+///
+/// `rholang-virtual:///path/to/file.rho#metta:0` names the same document as
+/// `file:///path/to/file.rho#metta:0`.
+pub const VIRTUAL_URI_SCHEME: &str = "rholang-virtual";
+
+/// Returns the registry URI for a `rholang-virtual:` alias, or `uri` itself.
+pub fn canonical_virtual_uri(uri: &Url) -> Url {
+    if uri.scheme() != VIRTUAL_URI_SCHEME {
+        return uri.clone();
+    }
+    let rest = &uri.as_str()[VIRTUAL_URI_SCHEME.len() + 1..];
+    Url::parse(&format!("file:{}", rest)).unwrap_or_else(|_| uri.clone())
+}
+
 /// A virtual document representing an embedded language region
 #[derive(Debug)]
 pub struct VirtualDocument {
@@ -29,6 +44,8 @@ pub struct VirtualDocument {
     pub parent_end: LspPosition,
     /// Byte offset mapping: virtual byte -> parent byte
     pub byte_offset: usize,
+    /// Length of the region in the parent document, in bytes
+    pub byte_len: usize,
     /// Diagnostics for this virtual document (in virtual coordinates)
     pub diagnostics: Vec<Diagnostic>,
     /// Optional concatenation chain for holed virtual documents
@@ -60,6 +77,7 @@ impl Clone for VirtualDocument {
             parent_start: self.parent_start,
             parent_end: self.parent_end,
             byte_offset: self.byte_offset,
+            byte_len: self.byte_len,
             diagnostics: self.diagnostics.clone(),
             concatenation_chain: self.concatenation_chain.clone(),
             // Don't clone caches - create fresh empty caches
@@ -89,31 +107,7 @@ impl VirtualDocument {
             line: region.start_line as u32,
             character: region.start_column as u32,
         };
-
-        // Calculate accurate end position by counting lines and columns in content
-        let parent_end = {
-            let lines: Vec<&str> = region.content.lines().collect();
-            let num_lines = lines.len();
-
-            if num_lines == 0 {
-                // Empty content
-                parent_start
-            } else if num_lines == 1 {
-                // Single-line region
-                LspPosition {
-                    line: region.start_line as u32,
-                    character: (region.start_column + region.content.len()) as u32,
-                }
-            } else {
-                // Multi-line region: end line = start line + (num lines - 1)
-                // end column = length of last line
-                let last_line_len = lines.last().map(|s| s.len()).unwrap_or(0);
-                LspPosition {
-                    line: (region.start_line + num_lines - 1) as u32,
-                    character: last_line_len as u32,
-                }
-            }
-        };
+        let parent_end = Self::end_in_parent(parent_start, &region.content);
 
         VirtualDocument {
             uri,
@@ -124,6 +118,7 @@ impl VirtualDocument {
             parent_start,
             parent_end,
             byte_offset: region.start_byte,
+            byte_len: region.end_byte.saturating_sub(region.start_byte),
             diagnostics: Vec::new(),
             concatenation_chain: region.concatenation_chain.as_ref().map(|chain| Arc::new(chain.clone())),
             holed_position_map: RwLock::new(None),
@@ -133,6 +128,90 @@ impl VirtualDocument {
         }
     }
 
+    /// Calculates the end position in the parent by counting lines and columns in content
+    fn end_in_parent(parent_start: LspPosition, content: &str) -> LspPosition {
+        let lines: Vec<&str> = content.lines().collect();
+        let num_lines = lines.len();
+
+        if num_lines == 0 {
+            // Empty content
+            parent_start
+        } else if num_lines == 1 {
+            // Single-line region
+            LspPosition {
+                line: parent_start.line,
+                character: parent_start.character + content.len() as u32,
+            }
+        } else {
+            // Multi-line region: end line = start line + (num lines - 1)
+            // end column = length of last line
+            let last_line_len = lines.last().map(|s| s.len()).unwrap_or(0);
+            LspPosition {
+                line: parent_start.line + num_lines as u32 - 1,
+                character: last_line_len as u32,
+            }
+        }
+    }
+
+    /// Moves this document to `region`, which has the same content, keeping
+    /// its parsed tree, IR and symbol table
+    pub fn relocated(&self, region: &LanguageRegion) -> Self {
+        let mut moved = Self::new(self.parent_uri.clone(), region, self.region_index);
+        moved.diagnostics = self.diagnostics.clone();
+        if let (Ok(mut to), Ok(from)) = (moved.cached_tree.write(), self.cached_tree.read()) {
+            *to = from.clone();
+        }
+        if let (Ok(mut to), Ok(from)) = (moved.cached_ir.write(), self.cached_ir.read()) {
+            *to = from.clone();
+        }
+        if let (Ok(mut to), Ok(from)) = (moved.cached_symbol_table.write(), self.cached_symbol_table.read()) {
+            *to = from.clone();
+        }
+        moved
+    }
+
+    /// Applies an edit translated from the parent document (see
+    /// `super::virtual_edit`), reparsing incrementally from the cached tree
+    pub fn apply_edit(&mut self, edit: &super::virtual_edit::VirtualEdit) {
+        use tree_sitter::{InputEdit, Point};
+
+        let point = |content: &str, byte: usize| {
+            let line_start = content[..byte].rfind('\n').map_or(0, |newline| newline + 1);
+            Point { row: content[..byte].matches('\n').count(), column: byte - line_start }
+        };
+        let start_position = point(&self.content, edit.start_byte);
+        let old_end_position = point(&self.content, edit.old_end_byte);
+        let old_tree = self.cached_tree.read().ok().and_then(|cache| cache.as_ref().map(|tree| (**tree).clone()));
+
+        self.content.replace_range(edit.start_byte..edit.old_end_byte, &edit.text);
+        self.byte_len = self.content.len();
+        self.parent_end = Self::end_in_parent(self.parent_start, &self.content);
+        self.invalidate_cache();
+
+        let Some(mut old_tree) = old_tree else { return };
+        let new_end_byte = edit.start_byte + edit.text.len();
+        old_tree.edit(&InputEdit {
+            start_byte: edit.start_byte,
+            old_end_byte: edit.old_end_byte,
+            new_end_byte,
+            start_position,
+            old_end_position,
+            new_end_position: point(&self.content, new_end_byte),
+        });
+        #[cfg(feature = "metta")]
+        if self.language == "metta" {
+            let mut parser = tree_sitter::Parser::new();
+            if parser.set_language(&tree_sitter_metta::language()).is_ok() {
+                if let Some(new_tree) = parser.parse(&self.content, Some(&old_tree)) {
+                    trace!("Incremental parse succeeded for virtual document: {}", self.uri);
+                    if let Ok(mut cache) = self.cached_tree.write() {
+                        *cache = Some(Arc::new(new_tree));
+                    }
+                }
+            }
+        }
+    }
+
     /// Maps a position in the virtual document to a position in the parent document
     ///
     /// # Arguments
@@ -314,6 +393,7 @@ impl VirtualDocument {
 
             // Parse based on language
             match self.language.as_str() {
+                #[cfg(feature = "metta")]
                 "metta" => {
                     use crate::parsers::MettaParser;
 
@@ -384,6 +464,7 @@ impl VirtualDocument {
 
             // Parse based on language
             match self.language.as_str() {
+                #[cfg(feature = "metta")]
                 "metta" => {
                     use tree_sitter::Parser;
 
@@ -483,6 +564,7 @@ impl VirtualDocument {
     /// If a cached tree exists, it's passed to the parser as the old tree.
     pub fn update_content_incremental(&mut self, new_content: String) {
         // Get the old tree for incremental parsing
+        #[cfg(feature = "metta")]
         let old_tree = if let Ok(cache) = self.cached_tree.read() {
             cache.as_ref().map(|t| (**t).clone())
         } else {
@@ -496,6 +578,7 @@ impl VirtualDocument {
         self.invalidate_cache();
 
         // If we had an old tree, eagerly reparse with incremental parsing
+        #[cfg(feature = "metta")]
         if let Some(old_tree) = old_tree {
             if self.language == "metta" {
                 use tree_sitter::Parser;
@@ -552,6 +635,7 @@ impl VirtualDocument {
     /// Returns the mapped diagnostics for publishing to parent
     pub fn validate(&mut self) -> Result<Vec<Diagnostic>, String> {
         match self.language.as_str() {
+            #[cfg(feature = "metta")]
             "metta" => self.validate_metta(),
             _ => {
                 warn!("Unsupported language for validation: {}", self.language);
@@ -561,6 +645,7 @@ impl VirtualDocument {
     }
 
     /// Validates MeTTa content
+    #[cfg(feature = "metta")]
     fn validate_metta(&mut self) -> Result<Vec<Diagnostic>, String> {
         use crate::validators::MettaValidator;
 
@@ -773,13 +858,33 @@ impl VirtualDocumentRegistry {
             parent_uri
         );
 
-        // Clear existing virtual documents for this parent
-        self.unregister_parent(parent_uri);
+        // Clear existing virtual documents for this parent, keeping them
+        // around so unchanged content is not parsed again
+        let mut previous = HashMap::new();
+        if let Some(virtual_uris) = self.parent_to_virtual.remove(parent_uri) {
+            for uri in virtual_uris {
+                if let Some(doc) = self.documents.remove(&uri) {
+                    previous.insert(uri, doc);
+                }
+            }
+        }
 
         let mut virtual_uris = Vec::new();
 
         for (index, region) in regions.iter().enumerate() {
-            let virtual_doc = Arc::new(VirtualDocument::new(parent_uri.clone(), region, index));
+            let fresh = VirtualDocument::new(parent_uri.clone(), region, index);
+            let virtual_doc = match previous.get(&fresh.uri) {
+                Some(old)
+                    if old.language == fresh.language
+                        && old.content == fresh.content
+                        && old.concatenation_chain.is_none()
+                        && fresh.concatenation_chain.is_none() =>
+                {
+                    trace!("Reusing unchanged virtual document {}", fresh.uri);
+                    Arc::new(old.relocated(region))
+                }
+                _ => Arc::new(fresh),
+            };
             trace!(
                 "Created virtual document: {} for language {}",
                 virtual_doc.uri,
@@ -794,6 +899,45 @@ impl VirtualDocumentRegistry {
             .insert(parent_uri.clone(), virtual_uris);
     }
 
+    /// Applies the edit of `parent_uri` replacing bytes `start..old_end` with
+    /// `text` to the virtual document whose literal contains it
+    ///
+    /// Virtual documents after the edit have their byte offsets shifted so
+    /// that later edits of the same change still translate; their positions
+    /// are refreshed when the parent is next detected.
+    ///
+    /// # Returns
+    /// The edited virtual document and the edit made to it, or None if no
+    /// virtual document could be edited in place
+    pub fn apply_host_edit(
+        &mut self,
+        parent_uri: &Url,
+        start: usize,
+        old_end: usize,
+        text: &str,
+    ) -> Option<(Arc<VirtualDocument>, super::virtual_edit::VirtualEdit)> {
+        let virtual_uris = self.parent_to_virtual.get(parent_uri)?.clone();
+        let delta = text.len() as isize - (old_end - start) as isize;
+        let mut edited = None;
+
+        for uri in virtual_uris {
+            let Some(doc_arc) = self.documents.remove(&uri) else { continue };
+            let mut doc = Arc::try_unwrap(doc_arc).unwrap_or_else(|arc| (*arc).clone());
+            if old_end <= doc.byte_offset && !(start == old_end && start == doc.byte_offset) {
+                doc.byte_offset = doc.byte_offset.saturating_add_signed(delta);
+            } else if let Some(edit) = super::virtual_edit::translate_host_edit(&doc, start, old_end, text) {
+                doc.apply_edit(&edit);
+                let doc = Arc::new(doc);
+                edited = Some((doc.clone(), edit));
+                self.documents.insert(uri, doc);
+                continue;
+            }
+            self.documents.insert(uri, Arc::new(doc));
+        }
+
+        edited
+    }
+
     /// Unregisters all virtual documents for a parent document
     ///
     /// # Arguments
@@ -837,7 +981,12 @@ impl VirtualDocumentRegistry {
 
     /// Checks if a URI refers to a virtual document
     pub fn is_virtual(&self, uri: &Url) -> bool {
-        uri.fragment().is_some() && self.documents.contains_key(uri)
+        uri.fragment().is_some() && self.documents.contains_key(&canonical_virtual_uri(uri))
+    }
+
+    /// Gets a virtual document by its URI or its `rholang-virtual:` alias
+    pub fn resolve(&self, uri: &Url) -> Option<Arc<VirtualDocument>> {
+        self.documents.get(&canonical_virtual_uri(uri)).cloned()
     }
 
     /// Gets the parent URI for a virtual document
@@ -1106,4 +1255,20 @@ mod tests {
         assert!(registry.is_virtual(virtual_uri));
         assert!(!registry.is_virtual(&parent_uri));
     }
+
+    #[test]
+    fn test_resolve_virtual_scheme_alias() {
+        let mut registry = VirtualDocumentRegistry::new();
+        let parent_uri = Url::parse("file:///ws/test.rho").unwrap();
+        registry.register_regions(&parent_uri, &[create_test_region()]);
+
+        let alias = Url::parse("rholang-virtual:///ws/test.rho#metta:0").unwrap();
+        assert_eq!(canonical_virtual_uri(&alias).as_str(), "file:///ws/test.rho#metta:0");
+        assert!(registry.is_virtual(&alias));
+        let document = registry.resolve(&alias).unwrap();
+        assert_eq!(document.parent_uri, parent_uri);
+
+        assert!(registry.resolve(&Url::parse("rholang-virtual:///ws/test.rho#metta:1").unwrap()).is_none());
+        assert_eq!(canonical_virtual_uri(&parent_uri), parent_uri);
+    }
 }
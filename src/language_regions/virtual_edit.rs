@@ -0,0 +1,140 @@
+//! Translation of host document edits into virtual document edits
+//!
+//! Typing inside a large embedded MeTTa string used to rebuild its virtual
+//! document from scratch: re-extract the literal, reparse it, rebuild its
+//! symbol table. When an edit of the host falls entirely inside the interior
+//! of a plain string literal, the same edit can be made to the virtual
+//! document instead, which then reparses incrementally from its old tree.
+//!
+//! An edit is translated only when the literal's host text and virtual
+//! content are byte-for-byte identical, that is when the literal has no
+//! escape sequences and the inserted text adds none (`\` or `"`). Holed
+//! documents built from `++` concatenations, edits touching a quote and
+//! edits spanning several regions are left to full re-extraction.
+
+use tower_lsp::lsp_types::{Position, Range, TextDocumentContentChangeEvent};
+
+use super::VirtualDocument;
+
+/// An edit of a virtual document's content
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VirtualEdit {
+    /// Start of the replaced bytes in the virtual content
+    pub start_byte: usize,
+    /// End of the replaced bytes in the virtual content
+    pub old_end_byte: usize,
+    pub text: String,
+    /// The replaced range in the virtual content before the edit
+    pub range: Range,
+}
+
+impl VirtualEdit {
+    /// The edit as a ranged `didChange` content change.
+    pub fn to_change_event(&self) -> TextDocumentContentChangeEvent {
+        TextDocumentContentChangeEvent { range: Some(self.range), range_length: None, text: self.text.clone() }
+    }
+}
+
+/// Translates the host edit replacing bytes `start..old_end` with `text`
+/// into an edit of `document`, if it falls inside the document's literal.
+pub fn translate_host_edit(document: &VirtualDocument, start: usize, old_end: usize, text: &str) -> Option<VirtualEdit> {
+    if document.concatenation_chain.is_some()
+        || document.byte_len != document.content.len()
+        || text.contains(['\\', '"'])
+    {
+        return None;
+    }
+    let interior = document.byte_offset..=document.byte_offset + document.byte_len;
+    if !interior.contains(&start) || !interior.contains(&old_end) {
+        return None;
+    }
+
+    let start_byte = start - document.byte_offset;
+    let old_end_byte = old_end - document.byte_offset;
+    if !document.content.is_char_boundary(start_byte) || !document.content.is_char_boundary(old_end_byte) {
+        return None;
+    }
+    Some(VirtualEdit {
+        start_byte,
+        old_end_byte,
+        text: text.to_string(),
+        range: Range {
+            start: position_of(&document.content, start_byte),
+            end: position_of(&document.content, old_end_byte),
+        },
+    })
+}
+
+/// Line and character (in chars) of `byte` in `content`.
+pub(super) fn position_of(content: &str, byte: usize) -> Position {
+    let before = &content[..byte];
+    let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+    Position {
+        line: before.matches('\n').count() as u32,
+        character: before[line_start..].chars().count() as u32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language_regions::{LanguageRegion, RegionSource};
+    use tower_lsp::lsp_types::Url;
+
+    /// Virtual document for the literal in `host`.
+    fn document(host: &str) -> VirtualDocument {
+        let start = host.find('"').unwrap() + 1;
+        let end = host.rfind('"').unwrap();
+        let region = LanguageRegion {
+            language: "metta".to_string(),
+            start_byte: start,
+            end_byte: end,
+            start_line: 0,
+            start_column: start - 1,
+            source: RegionSource::SemanticAnalysis,
+            content: host[start..end].replace("\\\"", "\""),
+            concatenation_chain: None,
+        };
+        VirtualDocument::new(Url::parse("file:///host.rho").unwrap(), &region, 0)
+    }
+
+    #[test]
+    fn test_edits_inside_the_literal_are_translated() {
+        let host = "@\"rho:metta:compile\"!(\"(= (f $x)\n  (+ $x 1))\")";
+        let mut virtual_doc = document(host);
+        let offset = virtual_doc.byte_offset;
+
+        // Replace `1` on the second line with `2`
+        let one = host.rfind('1').unwrap();
+        let edit = translate_host_edit(&virtual_doc, one, one + 1, "2").unwrap();
+        assert_eq!(edit.range.start, Position { line: 1, character: 8 });
+        assert_eq!(edit.start_byte, one - offset);
+
+        virtual_doc.apply_edit(&edit);
+        assert_eq!(virtual_doc.content, "(= (f $x)\n  (+ $x 2))");
+        assert_eq!(virtual_doc.byte_len, virtual_doc.content.len());
+
+        // Inserting at the very end of the interior is still inside
+        let end = offset + virtual_doc.byte_len;
+        assert!(translate_host_edit(&virtual_doc, end, end, " ").is_some());
+    }
+
+    #[test]
+    fn test_untranslatable_edits() {
+        let host = "@\"rho:metta:compile\"!(\"(= a 1)\")";
+        let virtual_doc = document(host);
+        let offset = virtual_doc.byte_offset;
+
+        // Touches the opening quote
+        assert_eq!(translate_host_edit(&virtual_doc, offset - 1, offset + 1, "("), None);
+        // Adds an escape
+        assert_eq!(translate_host_edit(&virtual_doc, offset, offset, "\\n"), None);
+        // Outside the literal
+        assert_eq!(translate_host_edit(&virtual_doc, 0, 1, "@"), None);
+
+        // The literal already has an escape, so bytes no longer line up
+        let escaped = document("@\"rho:metta:compile\"!(\"(= a \\\"b\\\")\")");
+        assert_ne!(escaped.byte_len, escaped.content.len());
+        assert_eq!(translate_host_edit(&escaped, escaped.byte_offset, escaped.byte_offset, "x"), None);
+    }
+}
@@ -1,4 +1,9 @@
 #![recursion_limit = "1024"]
+pub mod check;
+pub mod debug_console;
+pub mod deterministic;
+pub mod edit_journal;
+pub mod i18n;
 pub mod ir;
 pub mod language_regions;
 pub mod logging;
@@ -6,6 +11,9 @@ pub mod lsp;
 pub mod metrics;
 pub mod parsers;
 pub mod rnode_apis;
+pub mod strict_lsp;
+pub mod supervisor;
+pub mod transport;
 pub mod tree_sitter;
 pub mod validators;
 pub mod wire_logger;
@@ -15,7 +15,7 @@ const LOG_RETENTION_DAYS: u64 = 7;
 /// - Linux: ~/.cache/f1r3fly-io/rholang-language-server/
 /// - macOS: ~/Library/Caches/f1r3fly-io/rholang-language-server/
 /// - Windows: %LOCALAPPDATA%\f1r3fly-io\rholang-language-server\
-fn get_log_dir() -> io::Result<PathBuf> {
+pub fn get_log_dir() -> io::Result<PathBuf> {
     let cache_dir = dirs::cache_dir()
         .ok_or_else(|| io::Error::new(
             io::ErrorKind::NotFound,
@@ -44,8 +44,10 @@ fn cleanup_old_logs(log_dir: &PathBuf) -> io::Result<()> {
             if let Ok(metadata) = entry.metadata() {
                 if metadata.is_file() {
                     if let Some(name) = entry.file_name().to_str() {
-                        // Clean up both session and wire logs
-                        if (name.starts_with("session-") || name.starts_with("wire-")) && name.ends_with(".log") {
+                        // Clean up session and wire logs and edit journals
+                        if (name.starts_with("session-") || name.starts_with("wire-") || name.starts_with("edits-"))
+                            && name.ends_with(".log")
+                        {
                             if let Ok(modified) = metadata.modified() {
                                 if let Ok(age) = now.duration_since(modified) {
                                     if age > retention {
@@ -0,0 +1,172 @@
+//! Time budget for the diagnostics of one analysis pass
+//!
+//! Validation runs in stages: parsing, semantic validation (one stage per
+//! top-level process with the built-in interpreter, or a single request to
+//! the validation backend), then the lints and hints of virtual documents,
+//! deprecations, style, duplicate sends, `@param` directives and phlo costs.
+//! On pathological inputs the later stages could hold back diagnostics the
+//! earlier ones already found, so each pass gets a time budget:
+//!
+//! ```json
+//! { "analysisBudget": { "budgetMs": 2000 } }
+//! ```
+//!
+//! The budget is checked between stages, and a request to the validation
+//! backend is abandoned when it runs out. Once it has run out, the remaining
+//! stages are skipped and the diagnostics found so far are published with an
+//! `analysis-partial` information diagnostic at the top of the file naming
+//! the skipped stages. A stage already running when the budget runs out
+//! finishes first. `budgetMs: 0` turns the budget off, as does the
+//! deterministic mode of `crate::deterministic`.
+
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Position, Range};
+
+use crate::i18n::tr;
+
+/// Initialization option configuring the budget
+pub const ANALYSIS_BUDGET_OPTION: &str = "analysisBudget";
+
+/// Diagnostic source of the partial analysis notice
+pub const ANALYSIS_SOURCE: &str = "rholang-analysis";
+/// Diagnostic code of the partial analysis notice
+pub const ANALYSIS_PARTIAL_CODE: &str = "analysis-partial";
+
+/// The `analysisBudget` initialization option
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AnalysisBudgetConfig {
+    /// Time an analysis pass may take before stages are skipped; 0 for no limit
+    pub budget_ms: u64,
+}
+
+impl Default for AnalysisBudgetConfig {
+    fn default() -> Self {
+        AnalysisBudgetConfig { budget_ms: 5000 }
+    }
+}
+
+impl AnalysisBudgetConfig {
+    /// Reads the `analysisBudget` initialization option; missing fields keep their defaults.
+    pub fn from_init_options(init_options: Option<&serde_json::Value>) -> Self {
+        init_options
+            .and_then(|opts| opts.get(ANALYSIS_BUDGET_OPTION))
+            .and_then(|config| serde_json::from_value(config.clone()).ok())
+            .unwrap_or_default()
+    }
+
+    /// A budget for a pass starting now.
+    pub fn start(&self) -> AnalysisBudget {
+        if self.budget_ms == 0 || crate::deterministic::enabled() {
+            AnalysisBudget::unlimited()
+        } else {
+            AnalysisBudget::new(Duration::from_millis(self.budget_ms))
+        }
+    }
+}
+
+/// The time left to one analysis pass and the stages it skipped
+#[derive(Debug, Clone)]
+pub struct AnalysisBudget {
+    deadline: Option<Instant>,
+    skipped: Vec<&'static str>,
+}
+
+impl AnalysisBudget {
+    pub fn new(budget: Duration) -> Self {
+        AnalysisBudget { deadline: Some(Instant::now() + budget), skipped: Vec::new() }
+    }
+
+    pub fn unlimited() -> Self {
+        AnalysisBudget { deadline: None, skipped: Vec::new() }
+    }
+
+    /// Time left, or `None` without a limit.
+    pub fn remaining(&self) -> Option<Duration> {
+        self.deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining().is_some_and(|remaining| remaining.is_zero())
+    }
+
+    /// Records `stage` as skipped.
+    pub fn skip(&mut self, stage: &'static str) {
+        if !self.skipped.contains(&stage) {
+            self.skipped.push(stage);
+        }
+    }
+
+    /// Runs `stage` if time is left, else records it as skipped and returns nothing.
+    pub fn run<T>(&mut self, stage: &'static str, f: impl FnOnce() -> Vec<T>) -> Vec<T> {
+        if self.is_exhausted() {
+            self.skip(stage);
+            return Vec::new();
+        }
+        f()
+    }
+
+    /// Stages skipped so far, in the order they were skipped.
+    pub fn skipped(&self) -> &[&'static str] {
+        &self.skipped
+    }
+
+    pub fn is_partial(&self) -> bool {
+        !self.skipped.is_empty()
+    }
+
+    /// The notice published at the top of a file whose analysis was cut short.
+    pub fn partial_diagnostic(&self) -> Option<Diagnostic> {
+        if !self.is_partial() {
+            return None;
+        }
+        let top = Position::new(0, 0);
+        Some(Diagnostic {
+            range: Range::new(top, top),
+            severity: Some(DiagnosticSeverity::INFORMATION),
+            code: Some(NumberOrString::String(ANALYSIS_PARTIAL_CODE.to_string())),
+            source: Some(ANALYSIS_SOURCE.to_string()),
+            message: tr("analysis.partial", &[("stages", &self.skipped.join(", "))]),
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_config_from_init_options() {
+        let config = AnalysisBudgetConfig::from_init_options(Some(&json!({ "analysisBudget": { "budgetMs": 250 } })));
+        assert_eq!(config.budget_ms, 250);
+        assert_eq!(AnalysisBudgetConfig::from_init_options(None).budget_ms, 5000);
+        assert_eq!(AnalysisBudgetConfig { budget_ms: 0 }.start().remaining(), None);
+    }
+
+    #[test]
+    fn test_stages_after_the_deadline_are_skipped() {
+        let mut budget = AnalysisBudget::new(Duration::ZERO);
+        assert!(budget.is_exhausted());
+        assert_eq!(budget.run("style", || vec![1, 2]), Vec::<i32>::new());
+        budget.skip("semantic");
+        budget.skip("style");
+        assert_eq!(budget.skipped(), ["style", "semantic"]);
+
+        let notice = budget.partial_diagnostic().unwrap();
+        assert_eq!(notice.range.start, Position::new(0, 0));
+        assert_eq!(notice.code, Some(NumberOrString::String(ANALYSIS_PARTIAL_CODE.to_string())));
+        assert!(notice.message.contains("style, semantic"));
+    }
+
+    #[test]
+    fn test_unlimited_budget_runs_every_stage() {
+        let mut budget = AnalysisBudget::unlimited();
+        assert_eq!(budget.run("style", || vec![1, 2]), vec![1, 2]);
+        assert!(!budget.is_partial());
+        assert!(budget.partial_diagnostic().is_none());
+    }
+}
@@ -0,0 +1,215 @@
+//! Adaptive analysis level for very large files
+//!
+//! Generated or concatenated Rholang files can run to megabytes, where some
+//! features cost more than they are worth on every keystroke. Each open
+//! document gets an [`AnalysisLevel`] from its size:
+//!
+//! | Level     | Default threshold              | Disabled features                          |
+//! |-----------|--------------------------------|--------------------------------------------|
+//! | `full`    |                                | none                                       |
+//! | `reduced` | over 1 MiB or 25,000 lines     | full-document semantic tokens              |
+//! | `minimal` | over 5 MiB or 100,000 lines    | also workspace symbols in completion lists |
+//!
+//! Diagnostics and navigation (hover, goto-definition, references, rename)
+//! are never disabled, and neither are the semantic tokens of a range (see
+//! `crate::lsp::semantic_tokens_range`). Thresholds come from the `analysisLevels`
+//! initialization option:
+//!
+//! ```json
+//! { "analysisLevels": { "reducedBytes": 1048576, "reducedLines": 25000,
+//!                       "minimalBytes": 5242880, "minimalLines": 100000 } }
+//! ```
+//!
+//! Whenever the level of a document changes, and when a document opens at a
+//! degraded level, the server sends a `rholang/analysisLevel` notification
+//! listing the disabled features so the client can explain them:
+//!
+//! ```json
+//! { "uri": "file:///path/to/big.rho", "level": "reduced",
+//!   "degraded": ["semanticTokensFull"], "bytes": 2097152, "lines": 41000 }
+//! ```
+
+use dashmap::DashMap;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::notification::Notification;
+use tower_lsp::lsp_types::Url;
+
+/// Initialization option with the size thresholds
+pub const ANALYSIS_LEVELS_OPTION: &str = "analysisLevels";
+
+/// How much analysis a document gets, from most to least
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AnalysisLevel {
+    #[default]
+    Full,
+    Reduced,
+    Minimal,
+}
+
+/// A feature turned off for large documents
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DegradedFeature {
+    /// `textDocument/semanticTokens/full`
+    SemanticTokensFull,
+    /// Contracts of the whole workspace in completion lists
+    CompletionIndex,
+}
+
+impl AnalysisLevel {
+    /// Features disabled at this level.
+    pub fn degraded_features(self) -> Vec<DegradedFeature> {
+        match self {
+            AnalysisLevel::Full => Vec::new(),
+            AnalysisLevel::Reduced => vec![DegradedFeature::SemanticTokensFull],
+            AnalysisLevel::Minimal => vec![DegradedFeature::SemanticTokensFull, DegradedFeature::CompletionIndex],
+        }
+    }
+
+    /// Whether `feature` runs at this level.
+    pub fn allows(self, feature: DegradedFeature) -> bool {
+        !self.degraded_features().contains(&feature)
+    }
+}
+
+/// Document sizes above which the level drops
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AnalysisThresholds {
+    pub reduced_bytes: usize,
+    pub reduced_lines: usize,
+    pub minimal_bytes: usize,
+    pub minimal_lines: usize,
+}
+
+impl Default for AnalysisThresholds {
+    fn default() -> Self {
+        AnalysisThresholds {
+            reduced_bytes: 1024 * 1024,
+            reduced_lines: 25_000,
+            minimal_bytes: 5 * 1024 * 1024,
+            minimal_lines: 100_000,
+        }
+    }
+}
+
+impl AnalysisThresholds {
+    /// Reads the `analysisLevels` initialization option; missing fields keep their defaults.
+    pub fn from_init_options(init_options: Option<&serde_json::Value>) -> Self {
+        init_options
+            .and_then(|opts| opts.get(ANALYSIS_LEVELS_OPTION))
+            .and_then(|levels| serde_json::from_value(levels.clone()).ok())
+            .unwrap_or_default()
+    }
+
+    /// Level of a document of `bytes` bytes and `lines` lines.
+    pub fn level_for(&self, bytes: usize, lines: usize) -> AnalysisLevel {
+        if bytes > self.minimal_bytes || lines > self.minimal_lines {
+            AnalysisLevel::Minimal
+        } else if bytes > self.reduced_bytes || lines > self.reduced_lines {
+            AnalysisLevel::Reduced
+        } else {
+            AnalysisLevel::Full
+        }
+    }
+}
+
+/// Parameters of the `rholang/analysisLevel` notification
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct AnalysisLevelParams {
+    pub uri: Url,
+    pub level: AnalysisLevel,
+    pub degraded: Vec<DegradedFeature>,
+    pub bytes: usize,
+    pub lines: usize,
+}
+
+/// `rholang/analysisLevel`
+pub enum AnalysisLevelNotification {}
+
+impl Notification for AnalysisLevelNotification {
+    type Params = AnalysisLevelParams;
+    const METHOD: &'static str = "rholang/analysisLevel";
+}
+
+/// Analysis levels of the open documents
+#[derive(Debug, Default)]
+pub struct AnalysisLevels {
+    thresholds: RwLock<AnalysisThresholds>,
+    levels: DashMap<Url, AnalysisLevel>,
+}
+
+impl AnalysisLevels {
+    pub fn set_thresholds(&self, thresholds: AnalysisThresholds) {
+        *self.thresholds.write() = thresholds;
+    }
+
+    /// Level of `uri`; documents never sized get the full analysis.
+    pub fn level(&self, uri: &Url) -> AnalysisLevel {
+        self.levels.get(uri).map_or(AnalysisLevel::Full, |level| *level)
+    }
+
+    /// Records the size of `uri` and returns the notification to send if its
+    /// level changed (a new document only if it starts degraded).
+    pub fn update(&self, uri: &Url, bytes: usize, lines: usize) -> Option<AnalysisLevelParams> {
+        let level = self.thresholds.read().level_for(bytes, lines);
+        let previous = self.levels.insert(uri.clone(), level).unwrap_or_default();
+        (previous != level).then(|| AnalysisLevelParams {
+            uri: uri.clone(),
+            level,
+            degraded: level.degraded_features(),
+            bytes,
+            lines,
+        })
+    }
+
+    /// Forgets a closed document.
+    pub fn remove(&self, uri: &Url) {
+        self.levels.remove(uri);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_levels_by_size() {
+        let thresholds = AnalysisThresholds::from_init_options(Some(&json!({
+            "analysisLevels": { "reducedBytes": 100, "minimalLines": 50 }
+        })));
+        assert_eq!(thresholds.reduced_lines, AnalysisThresholds::default().reduced_lines);
+        assert_eq!(thresholds.level_for(100, 10), AnalysisLevel::Full);
+        assert_eq!(thresholds.level_for(101, 10), AnalysisLevel::Reduced);
+        assert_eq!(thresholds.level_for(10, 51), AnalysisLevel::Minimal);
+
+        assert!(AnalysisLevel::Full.allows(DegradedFeature::SemanticTokensFull));
+        assert!(!AnalysisLevel::Reduced.allows(DegradedFeature::SemanticTokensFull));
+        assert!(AnalysisLevel::Reduced.allows(DegradedFeature::CompletionIndex));
+        assert!(!AnalysisLevel::Minimal.allows(DegradedFeature::CompletionIndex));
+    }
+
+    #[test]
+    fn test_notifies_level_changes_only() {
+        let levels = AnalysisLevels::default();
+        levels.set_thresholds(AnalysisThresholds { reduced_bytes: 100, minimal_bytes: 1000, ..Default::default() });
+        let uri = Url::parse("file:///big.rho").unwrap();
+
+        assert_eq!(levels.update(&uri, 10, 1), None);
+        let reduced = levels.update(&uri, 500, 1).unwrap();
+        assert_eq!(reduced.level, AnalysisLevel::Reduced);
+        assert_eq!(
+            serde_json::to_value(&reduced).unwrap(),
+            json!({ "uri": "file:///big.rho", "level": "reduced", "degraded": ["semanticTokensFull"],
+                    "bytes": 500, "lines": 1 })
+        );
+        assert_eq!(levels.update(&uri, 600, 1), None);
+        assert_eq!(levels.update(&uri, 50, 1).map(|params| params.level), Some(AnalysisLevel::Full));
+
+        levels.remove(&uri);
+        assert_eq!(levels.level(&uri), AnalysisLevel::Full);
+    }
+}
@@ -0,0 +1,219 @@
+//! Dedicated thread pool for CPU-heavy analysis
+//!
+//! Parsing, IR conversion and symbol table builds can take tens of
+//! milliseconds on large files. Run on the tokio runtime (or on its blocking
+//! pool, which grows without bound), a burst of them starves the reactor and
+//! delays unrelated requests such as hover or completion. [`AnalysisPool`]
+//! runs them on a rayon pool of its own, sized to the machine's cores minus
+//! one for the reactor, and bridges the results back with a oneshot channel.
+//!
+//! A semaphore with one permit per thread bounds the work handed to rayon:
+//! further jobs wait asynchronously for a permit instead of piling up in the
+//! pool's queue, so a newer job for the same document is not stuck behind a
+//! long backlog. `RHOLANG_ANALYSIS_THREADS` overrides the pool size.
+//!
+//! A panicking job does not take its thread down: [`AnalysisPool::run_isolated`]
+//! returns the panic with the IR node path it happened at (see
+//! `crate::ir::visitor::node_path`), for crash reports.
+
+use std::any::Any;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::sync::{oneshot, Semaphore};
+use tracing::{debug, info, warn};
+
+use crate::ir::visitor::node_path;
+
+/// Environment variable overriding the number of analysis threads
+pub const ANALYSIS_THREADS_ENV: &str = "RHOLANG_ANALYSIS_THREADS";
+
+/// Waits for a permit longer than this are logged
+const SLOW_QUEUE_WAIT: Duration = Duration::from_millis(50);
+
+/// A job that panicked
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JobPanic {
+    /// Panic message
+    pub message: String,
+    /// Kinds of the IR nodes being visited, from the root, if it panicked in a visit
+    pub node_path: Vec<&'static str>,
+}
+
+/// Thread pool for CPU-bound analysis, shared by all documents
+#[derive(Clone)]
+pub struct AnalysisPool {
+    pool: Arc<rayon::ThreadPool>,
+    permits: Arc<Semaphore>,
+    threads: usize,
+}
+
+impl std::fmt::Debug for AnalysisPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AnalysisPool").field("threads", &self.threads).finish()
+    }
+}
+
+impl AnalysisPool {
+    /// Creates a pool with `threads` threads (at least one).
+    pub fn new(threads: usize) -> Result<Self, rayon::ThreadPoolBuildError> {
+        let threads = threads.max(1);
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .thread_name(|i| format!("rholang-analysis-{}", i))
+            .build()?;
+        Ok(AnalysisPool { pool: Arc::new(pool), permits: Arc::new(Semaphore::new(threads)), threads })
+    }
+
+    /// Creates a pool sized by `RHOLANG_ANALYSIS_THREADS`, or [`default_threads`];
+    /// a single thread in deterministic mode.
+    pub fn from_env() -> Result<Self, rayon::ThreadPoolBuildError> {
+        let threads = match std::env::var(ANALYSIS_THREADS_ENV) {
+            Ok(value) => value.trim().parse().unwrap_or_else(|_| {
+                warn!("Ignoring invalid {}={:?}", ANALYSIS_THREADS_ENV, value);
+                default_threads()
+            }),
+            Err(_) => default_threads(),
+        };
+        let threads = crate::deterministic::threads(threads);
+        info!("Analysis thread pool: {} thread(s)", threads);
+        Self::new(threads)
+    }
+
+    /// Number of threads, which is also the number of jobs running at once.
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Number of jobs running on the pool.
+    pub fn running(&self) -> usize {
+        self.threads - self.permits.available_permits()
+    }
+
+    /// Runs `job` on the pool and returns its result.
+    ///
+    /// Rayon parallel iterators inside `job` use this pool too. A panic in
+    /// `job` is returned as an error instead of unwinding into the caller.
+    pub async fn run<F, R>(&self, job: F) -> Result<R, String>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        self.run_isolated(job).await?.map_err(|_| "Analysis job panicked".to_string())
+    }
+
+    /// Runs `job` on the pool like [`run`](Self::run), returning a panic in
+    /// `job` as the inner error.
+    pub async fn run_isolated<F, R>(&self, job: F) -> Result<Result<R, JobPanic>, String>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let queued = Instant::now();
+        let _permit = self.permits.acquire().await.map_err(|_| "Analysis pool closed".to_string())?;
+        let waited = queued.elapsed();
+        if waited > SLOW_QUEUE_WAIT {
+            debug!("Analysis job waited {:?} for a thread", waited);
+        }
+
+        let (result_tx, result_rx) = oneshot::channel();
+        self.pool.spawn(move || {
+            node_path::clear();
+            let result = catch_unwind(AssertUnwindSafe(job)).map_err(|payload| JobPanic {
+                message: panic_message(payload.as_ref()),
+                node_path: node_path::take_at_panic().unwrap_or_default(),
+            });
+            let _ = result_tx.send(result);
+        });
+        result_rx.await.map_err(|_| "Analysis job dropped".to_string())
+    }
+}
+
+/// Message of a panic with payload `payload`.
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "<unknown panic payload>".to_string()
+    }
+}
+
+/// Available cores minus one for the tokio reactor, at least one.
+pub fn default_threads() -> usize {
+    std::thread::available_parallelism().map_or(1, |n| n.get().saturating_sub(1).max(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn spin(duration: Duration) {
+        let start = Instant::now();
+        while start.elapsed() < duration {
+            std::hint::spin_loop();
+        }
+    }
+
+    /// On a single-threaded runtime, analysis jobs must not delay a timer.
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_reactor_stays_responsive_under_load() {
+        let pool = AnalysisPool::new(2).unwrap();
+        let jobs: Vec<_> = (0..8)
+            .map(|_| {
+                let pool = pool.clone();
+                tokio::spawn(async move { pool.run(|| spin(Duration::from_millis(100))).await })
+            })
+            .collect();
+
+        let start = Instant::now();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let latency = start.elapsed();
+        assert!(latency < Duration::from_millis(150), "timer delayed to {:?}", latency);
+
+        for job in jobs {
+            job.await.unwrap().unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrency_limit_and_panics() {
+        let pool = AnalysisPool::new(2).unwrap();
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let jobs: Vec<_> = (0..6)
+            .map(|_| {
+                let (pool, running, peak) = (pool.clone(), running.clone(), peak.clone());
+                tokio::spawn(async move {
+                    pool.run(move || {
+                        let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                        peak.fetch_max(now, Ordering::SeqCst);
+                        spin(Duration::from_millis(20));
+                        running.fetch_sub(1, Ordering::SeqCst);
+                    })
+                    .await
+                })
+            })
+            .collect();
+        for job in jobs {
+            job.await.unwrap().unwrap();
+        }
+        assert!(peak.load(Ordering::SeqCst) <= 2);
+
+        assert!(pool.run(|| -> i32 { panic!("boom") }).await.is_err());
+        assert_eq!(pool.run(|| 6 * 7).await, Ok(42));
+
+        let panic = pool
+            .run_isolated(|| -> i32 {
+                let _frame = node_path::enter("Rholang::Send");
+                panic!("boom {}", 42)
+            })
+            .await
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(panic, JobPanic { message: "boom 42".to_string(), node_path: vec!["Rholang::Send"] });
+    }
+}
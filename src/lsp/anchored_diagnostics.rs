@@ -0,0 +1,201 @@
+//! Diagnostics that follow edits between validations
+//!
+//! Validation runs after a debounce and can take a while (RNode round trips),
+//! so while the user keeps typing the client shows the diagnostics of an
+//! older version at their old line/column positions, underlining the wrong
+//! tokens. The backend therefore remembers the last published diagnostics of
+//! each open document anchored to byte offsets of its text, shifts the
+//! anchors through the byte edits of every `didChange` and republishes them
+//! for the new version until the next validation replaces them.
+//!
+//! An anchor before an edit stays put and one after it moves by the change
+//! in length. A range that an edit cuts into is trimmed to the edit, and a
+//! diagnostic whose whole range is rewritten is dropped: its token is gone.
+
+use dashmap::DashMap;
+use ropey::Rope;
+use tower_lsp::lsp_types::{Diagnostic, Position as LspPosition, Range, Url};
+
+use crate::lsp::line_index::LineIndex;
+use crate::lsp::models::ByteEdit;
+
+/// A diagnostic with its range as byte offsets of the text it was anchored to
+#[derive(Debug, Clone)]
+struct AnchoredDiagnostic {
+    diagnostic: Diagnostic,
+    start: usize,
+    end: usize,
+}
+
+/// Anchored diagnostics of one document version
+#[derive(Debug, Clone)]
+struct AnchoredSet {
+    version: i32,
+    diagnostics: Vec<AnchoredDiagnostic>,
+}
+
+/// Last published diagnostics of the open documents, anchored to their text
+#[derive(Debug, Default)]
+pub struct AnchoredDiagnostics {
+    documents: DashMap<Url, AnchoredSet>,
+}
+
+impl AnchoredDiagnostics {
+    /// Anchors `diagnostics`, published for `version` of `uri`, to `text`.
+    ///
+    /// `text` and `line_index` must be the text of `version`.
+    pub fn record(&self, uri: &Url, version: i32, diagnostics: &[Diagnostic], text: &Rope, line_index: &LineIndex) {
+        let diagnostics = diagnostics
+            .iter()
+            .filter_map(|diagnostic| {
+                let start = offset(line_index, text, diagnostic.range.start)?;
+                let end = offset(line_index, text, diagnostic.range.end)?.max(start);
+                Some(AnchoredDiagnostic { diagnostic: diagnostic.clone(), start, end })
+            })
+            .collect();
+        self.documents.insert(uri.clone(), AnchoredSet { version, diagnostics });
+    }
+
+    /// Moves the diagnostics of `uri` from `previous` to `version` through `edits`.
+    ///
+    /// `edits` are the byte edits that turned the text of `previous` into
+    /// `text`, in order. Returns the diagnostics at their new ranges, or
+    /// `None` if nothing is anchored to `previous` (the anchors are then
+    /// dropped, as they no longer match the text).
+    pub fn shift(
+        &self,
+        uri: &Url,
+        previous: i32,
+        version: i32,
+        edits: &[ByteEdit],
+        text: &Rope,
+        line_index: &LineIndex,
+    ) -> Option<Vec<Diagnostic>> {
+        let mut set = self.documents.get_mut(uri)?;
+        if set.version != previous {
+            drop(set);
+            self.documents.remove(uri);
+            return None;
+        }
+
+        for edit in edits {
+            set.diagnostics.retain_mut(|anchored| shift_anchor(anchored, edit));
+        }
+        set.version = version;
+
+        let diagnostics = set
+            .diagnostics
+            .iter()
+            .filter_map(|anchored| {
+                let (start_line, start_character) = line_index.position(anchored.start, text)?;
+                let (end_line, end_character) = line_index.position(anchored.end, text)?;
+                let mut diagnostic = anchored.diagnostic.clone();
+                diagnostic.range = Range::new(
+                    LspPosition::new(start_line as u32, start_character as u32),
+                    LspPosition::new(end_line as u32, end_character as u32),
+                );
+                Some(diagnostic)
+            })
+            .collect();
+        Some(diagnostics)
+    }
+
+    /// Forgets the diagnostics of `uri`.
+    pub fn remove(&self, uri: &Url) {
+        self.documents.remove(uri);
+    }
+}
+
+fn offset(line_index: &LineIndex, text: &Rope, position: LspPosition) -> Option<usize> {
+    line_index.offset(position.line as usize, position.character as usize, text)
+}
+
+/// Shifts `anchored` through `edit`; `false` if the edit rewrote its whole range.
+fn shift_anchor(anchored: &mut AnchoredDiagnostic, edit: &ByteEdit) -> bool {
+    let inserted_end = edit.start + edit.text.len();
+    let rewritten = edit.old_end > edit.start
+        && edit.start <= anchored.start
+        && anchored.end <= edit.old_end
+        && (anchored.start < anchored.end || anchored.start < edit.old_end);
+    if rewritten {
+        return false;
+    }
+
+    anchored.start = if anchored.start < edit.start {
+        anchored.start
+    } else if anchored.start >= edit.old_end {
+        anchored.start - edit.old_end + inserted_end
+    } else {
+        inserted_end
+    };
+    anchored.end = if anchored.end <= edit.start {
+        anchored.end
+    } else if anchored.end > edit.old_end {
+        anchored.end - edit.old_end + inserted_end
+    } else {
+        edit.start
+    };
+    anchored.end = anchored.end.max(anchored.start);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnostic(start: (u32, u32), end: (u32, u32), message: &str) -> Diagnostic {
+        Diagnostic {
+            range: Range::new(LspPosition::new(start.0, start.1), LspPosition::new(end.0, end.1)),
+            message: message.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Applies `edit` to `text` as the document would.
+    fn apply(text: &mut Rope, line_index: &mut LineIndex, edit: &ByteEdit) {
+        let start = text.byte_to_char(edit.start);
+        let end = text.byte_to_char(edit.old_end);
+        text.remove(start..end);
+        text.insert(start, &edit.text);
+        line_index.apply_edit(text, edit.start, edit.old_end, edit.text.len());
+    }
+
+    #[test]
+    fn test_diagnostics_follow_edits() {
+        let uri = Url::parse("file:///a.rho").unwrap();
+        let mut text = Rope::from_str("new x in {\n  y!(1)\n}\n");
+        let mut line_index = LineIndex::from_rope(&text);
+        let anchored = AnchoredDiagnostics::default();
+        anchored.record(&uri, 1, &[diagnostic((1, 2), (1, 3), "unbound y")], &text, &line_index);
+
+        // A line inserted above moves the squiggle down with its token
+        let edits = vec![ByteEdit { start: 11, old_end: 11, text: "  x!(0) |\n".to_string() }];
+        apply(&mut text, &mut line_index, &edits[0]);
+        let shifted = anchored.shift(&uri, 1, 2, &edits, &text, &line_index).unwrap();
+        assert_eq!(shifted[0].range, Range::new(LspPosition::new(2, 2), LspPosition::new(2, 3)));
+
+        // Typing on the same line before the token moves it right
+        let edits = vec![ByteEdit { start: 23, old_end: 23, text: "zz | ".to_string() }];
+        apply(&mut text, &mut line_index, &edits[0]);
+        let shifted = anchored.shift(&uri, 2, 3, &edits, &text, &line_index).unwrap();
+        assert_eq!(shifted[0].range, Range::new(LspPosition::new(2, 7), LspPosition::new(2, 8)));
+        assert_eq!(text.line(2).to_string(), "  zz | y!(1)\n");
+
+        // Versions that skip the anchored one drop the anchors
+        assert!(anchored.shift(&uri, 7, 8, &[], &text, &line_index).is_none());
+        assert!(anchored.shift(&uri, 3, 4, &[], &text, &line_index).is_none());
+    }
+
+    #[test]
+    fn test_rewritten_ranges() {
+        let mut anchored = AnchoredDiagnostic { diagnostic: Diagnostic::default(), start: 10, end: 20 };
+        // Cutting into the end trims the range to the edit
+        assert!(shift_anchor(&mut anchored, &ByteEdit { start: 15, old_end: 25, text: "ab".to_string() }));
+        assert_eq!((anchored.start, anchored.end), (10, 15));
+        // Cutting into the start moves it past the inserted text
+        assert!(shift_anchor(&mut anchored, &ByteEdit { start: 5, old_end: 12, text: "c".to_string() }));
+        assert_eq!((anchored.start, anchored.end), (6, 9));
+        // Rewriting the whole range drops the diagnostic
+        assert!(!shift_anchor(&mut anchored, &ByteEdit { start: 6, old_end: 9, text: "xyz".to_string() }));
+    }
+}
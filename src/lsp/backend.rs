@@ -1,7 +1,6 @@
-use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
-use std::sync::atomic::{AtomicI32, AtomicU32, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU32, AtomicU64, Ordering};
 use std::sync::mpsc::Receiver;
 
 use dashmap::DashMap;
@@ -11,7 +10,7 @@ use tokio::task;
 
 use tower_lsp::{Client, LanguageServer, jsonrpc};
 use tower_lsp::lsp_types::{
-    DeclarationCapability, Diagnostic, DiagnosticSeverity,
+    DeclarationCapability, Diagnostic,
     DidChangeTextDocumentParams, DidCloseTextDocumentParams,
     DidOpenTextDocumentParams, DidSaveTextDocumentParams, DocumentHighlight,
     DocumentHighlightKind, DocumentHighlightParams, GotoDefinitionParams,
@@ -30,11 +29,12 @@ use tower_lsp::jsonrpc::Result as LspResult;
 
 use tracing::{debug, error, info, trace, warn};
 
-use ropey::Rope;
-
 use notify::{RecommendedWatcher, RecursiveMode, Watcher};
 use walkdir::WalkDir;
 
+use crate::deterministic;
+use crate::edit_journal::EditJournal;
+use crate::lsp::shared_documents::{ConnectionId, SharedDocuments};
 use crate::ir::pipeline::Pipeline;
 use crate::ir::rholang_node::{RholangNode, Position as IrPosition, compute_absolute_positions, collect_contracts, collect_calls, match_contract, find_node_at_position_with_path, find_node_at_position};
 use crate::ir::symbol_table::{Symbol, SymbolTable, SymbolType};
@@ -46,12 +46,38 @@ use crate::language_regions::{
     DetectorRegistry, spawn_detection_worker,
 };
 use crate::lsp::models::{CachedDocument, LspDocument, LspDocumentHistory, LspDocumentState, WorkspaceState};
+use crate::lsp::semantic_tokens_delta::SemanticTokensCache;
+use crate::lsp::semantic_tokens_range::ChunkedTokens;
 use crate::lsp::semantic_validator::SemanticValidator;
 use crate::lsp::diagnostic_provider::{BackendConfig, DiagnosticProvider, create_provider};
+use crate::lsp::analysis_budget::{AnalysisBudget, AnalysisBudgetConfig};
+use crate::lsp::analysis_level::AnalysisLevels;
+use crate::lsp::rnode_validation::{RnodeValidation, DEFAULT_RNODE_ADDRESS};
+use crate::lsp::typing_diagnostics::TypingDiagnostics;
+use crate::lsp::analysis_pool::AnalysisPool;
+use crate::lsp::capabilities::NegotiatedCapabilities;
+use crate::lsp::completion_ranking::CompletionUsage;
+use crate::lsp::buffer_overlay::BufferOverlay;
+use crate::lsp::disk_index::DiskIndex;
+use crate::lsp::features::adapters::AdapterRegistry;
+use crate::lsp::features::code_actions::rholang_providers;
+use crate::lsp::library_paths::Libraries;
+use crate::lsp::anchored_diagnostics::AnchoredDiagnostics;
+use crate::lsp::formatting::FormatConfig;
+use crate::lsp::deploy_signing::DeploySettings;
+use crate::lsp::document_links::DocumentLinksConfig;
+use crate::lsp::inlay_hints::InlayHintsConfig;
+use crate::lsp::readiness::ReadinessTracker;
+use crate::lsp::cost_estimation::CostEstimationConfig;
+use crate::lsp::crash_reports::CrashReporter;
+use crate::lsp::settings::Settings;
+use crate::lsp::style_lints::StyleLintConfig;
+use crate::lsp::syntax_errors::parsing_error_diagnostic;
+use crate::lsp::validation_scheduler::ValidationScheduler;
+use crate::lsp::workspace_trust::WorkspaceTrust;
 use crate::tree_sitter::{parse_code, parse_to_ir};
 
 use rholang_parser::RholangParser;
-use rholang_parser::parser::errors::ParsingError;
 use validated::Validated;
 
 // Import types from backend submodules
@@ -64,10 +90,54 @@ mod symbols;
 mod handlers;
 mod indexing;
 mod unified_handlers;
+mod commands;
+mod custom_requests;
+mod deprecations;
+mod name_tokens;
+mod style;
+mod duplicate_sends;
+mod on_disk;
+mod rename_conflicts;
+mod receive_patterns;
+mod virtual_document_events;
+mod document_status;
+mod pattern_examples;
+mod analysis_level;
+mod typing_diagnostics;
+mod task_markers;
+mod shared_documents;
+mod debug_console;
+mod return_channels;
+mod locals_fallback;
+mod name_escapes;
+mod workspace_folders;
+mod registry_definitions;
+mod refresh;
+mod completion_cost;
+mod formatting;
+mod anchored_diagnostics;
+mod code_actions;
+mod call_hierarchy;
+mod inlay_hints;
+mod document_resync;
+mod normalized_form;
+mod semantic_tokens;
+mod index_checkpoint;
+mod document_links;
+mod deploy;
+mod readiness;
+mod param_docs;
+mod cost_estimation;
+mod crash_reports;
+mod binder_names;
+mod settings;
+mod move_contract;
+mod validation;
 
 pub use state::RholangBackend;
 use state::{DocumentChangeEvent, IndexingTask, WorkspaceChangeEvent, WorkspaceChangeType};
 use utils::SemanticTokensBuilder;
+use validation::Publication;
 
 impl RholangBackend {
     /// Creates a new instance of the Rholang backend with the given client and connections.
@@ -80,6 +150,9 @@ impl RholangBackend {
         grpc_address: Option<String>,
         client_process_id: Option<u32>,
         pid_channel: Option<tokio::sync::mpsc::Sender<u32>>,
+        edit_journal: EditJournal,
+        shared_documents: SharedDocuments,
+        connection_id: ConnectionId,
     ) -> anyhow::Result<Self> {
         // Determine backend configuration
         let backend_config = if let Some(addr) = grpc_address {
@@ -118,6 +191,11 @@ impl RholangBackend {
             None
         };
 
+        let rnode_validation = match &backend_config {
+            BackendConfig::Grpc(address) => RnodeValidation::new(address.clone(), Some(diagnostic_provider.clone())),
+            BackendConfig::Rust => RnodeValidation::new(DEFAULT_RNODE_ADDRESS.to_string(), None),
+        };
+
         let (tx, rx) = std::sync::mpsc::channel();
 
         // Create reactive channels
@@ -126,7 +204,6 @@ impl RholangBackend {
         let (indexing_tx, indexing_rx) = tokio::sync::mpsc::channel::<IndexingTask>(500);
         let (link_symbols_tx, link_symbols_rx) = tokio::sync::mpsc::channel::<()>(5000);
         let (diagnostics_tx, diagnostics_rx) = tokio::sync::mpsc::channel::<state::DiagnosticUpdate>(5000);
-        let validation_cancel = Arc::new(tokio::sync::Mutex::new(HashMap::new()));
         let (shutdown_tx, _) = tokio::sync::broadcast::channel::<()>(1);
 
         // Create hot observable for workspace changes (ReactiveX Phase 2)
@@ -144,17 +221,22 @@ impl RholangBackend {
         let detector_registry = Arc::new(DetectorRegistry::with_defaults());
         let detection_worker = spawn_detection_worker(detector_registry.clone());
 
+        // CPU-bound analysis runs on its own pool so it cannot starve the reactor
+        let analysis_pool = AnalysisPool::from_env()?;
+
         let backend = Self {
             client: client.clone(),
             documents_by_uri: Arc::new(DashMap::new()),
             documents_by_id: Arc::new(DashMap::new()),
-            serial_document_id: Arc::new(AtomicU32::new(0)),
+            serial_document_id: Arc::new(AtomicU32::new(deterministic::id_seed() as u32)),
             diagnostic_provider,
             semantic_validator,
+            rnode_validation: Arc::new(rnode_validation),
+            next_rnode_run: Arc::new(AtomicU64::new(deterministic::id_seed())),
             client_process_id: Arc::new(tokio::sync::Mutex::new(client_process_id)),
             pid_channel,
             doc_change_tx: doc_change_tx.clone(),
-            validation_cancel: validation_cancel.clone(),
+            validation_scheduler: Arc::new(ValidationScheduler::default()),
             indexing_tx: indexing_tx.clone(),
             workspace: Arc::new(WorkspaceState::new()),
             file_watcher: Arc::new(Mutex::new(None)),
@@ -162,14 +244,53 @@ impl RholangBackend {
             file_sender: Arc::new(Mutex::new(tx)),
             version_counter: Arc::new(AtomicI32::new(0)),
             root_dir: Arc::new(RwLock::new(None)),
+            workspace_roots: Arc::new(parking_lot::RwLock::new(Vec::new())),
             shutdown_tx: Arc::new(shutdown_tx),
             virtual_docs: Arc::new(RwLock::new(VirtualDocumentRegistry::new())),
             workspace_changes: Arc::new(workspace_tx),
             diagnostics_published: Arc::new(diagnostics_published_tx),
             link_symbols_tx: link_symbols_tx.clone(),
             diagnostics_tx: diagnostics_tx.clone(),
+            anchored_diagnostics: Arc::new(AnchoredDiagnostics::default()),
             detection_worker,
             detector_registry,
+            workspace_trust: Arc::new(WorkspaceTrust::default()),
+            deploy_settings: Arc::new(parking_lot::RwLock::new(DeploySettings::default())),
+            style_lints: Arc::new(parking_lot::RwLock::new(StyleLintConfig::default())),
+            format_config: Arc::new(parking_lot::RwLock::new(FormatConfig::default())),
+            code_action_providers: Arc::new(rholang_providers()),
+            inlay_hints: Arc::new(parking_lot::RwLock::new(InlayHintsConfig::default())),
+            document_links: Arc::new(parking_lot::RwLock::new(DocumentLinksConfig::default())),
+            disk_index: Arc::new(DiskIndex::default()),
+            buffer_overlay: Arc::new(BufferOverlay::default()),
+            virtual_doc_events: Arc::new(parking_lot::Mutex::new(None)),
+            document_status: Arc::new(AtomicBool::new(false)),
+            document_resync: Arc::new(AtomicBool::new(false)),
+            desynced_documents: Arc::new(DashMap::new()),
+            normalized_form: Arc::new(AtomicBool::new(false)),
+            normalized_forms: Arc::new(DashMap::new()),
+            semantic_tokens: Arc::new(SemanticTokensCache::starting_at(deterministic::id_seed())),
+            chunked_tokens: Arc::new(ChunkedTokens::default()),
+            index_checkpoints: Arc::new(parking_lot::Mutex::new(None)),
+            readiness: Arc::new(ReadinessTracker::default()),
+            readiness_events: Arc::new(AtomicBool::new(false)),
+            cost_estimation: Arc::new(parking_lot::RwLock::new(CostEstimationConfig::default())),
+            cost_estimates: Arc::new(DashMap::new()),
+            settings: Arc::new(parking_lot::RwLock::new(Settings::default())),
+            edit_journal,
+            shared_documents,
+            connection_id,
+            completion_usage: Arc::new(CompletionUsage::default()),
+            cost_aware_completion: Arc::new(parking_lot::RwLock::new(None)),
+            adapter_registry: Arc::new(parking_lot::RwLock::new(AdapterRegistry::with_defaults())),
+            analysis_pool,
+            capabilities: Arc::new(parking_lot::RwLock::new(NegotiatedCapabilities::default())),
+            analysis_levels: Arc::new(AnalysisLevels::default()),
+            analysis_budget: Arc::new(parking_lot::RwLock::new(AnalysisBudgetConfig::default())),
+            ir_memos: Arc::new(DashMap::new()),
+            typing_diagnostics: Arc::new(TypingDiagnostics::default()),
+            libraries: Arc::new(Libraries::default()),
+            crash_reporter: Arc::new(CrashReporter::from_env()),
         };
 
         // Spawn reactive document change debouncer
@@ -184,6 +305,9 @@ impl RholangBackend {
         // Spawn debounced diagnostics publisher
         Self::spawn_debounced_diagnostics_publisher(backend.clone(), diagnostics_rx);
 
+        // Spawn refresh requests for decorations that depend on other files
+        Self::spawn_derived_data_refresher(backend.clone());
+
         Ok(backend)
     }
 
@@ -196,7 +320,7 @@ impl RholangBackend {
 
         tokio::spawn(async move {
             use std::collections::HashMap;
-            use tokio::time::{sleep, Duration};
+            use tokio::time::Duration;
 
             let mut pending_changes: HashMap<Url, DocumentChangeEvent> = HashMap::new();
             let debounce_duration = Duration::from_millis(300);
@@ -208,48 +332,16 @@ impl RholangBackend {
                         // Store/update pending change
                         pending_changes.insert(event.uri.clone(), event);
                     }
-                    _ = sleep(debounce_duration), if !pending_changes.is_empty() => {
+                    _ = deterministic::sleep(debounce_duration), if !pending_changes.is_empty() => {
                         // Timeout reached, process all pending changes
-                        for (uri, event) in pending_changes.drain() {
-                            // Cancel any in-flight validation for this URI
-                            if let Some(cancel_tx) = backend.validation_cancel.lock().await.remove(&uri) {
-                                let _ = cancel_tx.send(());
-                                trace!("Cancelled previous validation for {}", uri);
-                            }
-
-                            // Create cancellation token for this validation
-                            let (cancel_tx, cancel_rx) = tokio::sync::oneshot::channel();
-                            backend.validation_cancel.lock().await.insert(uri.clone(), cancel_tx);
-
-                            // Spawn validation with cancellation
-                            let backend_clone = backend.clone();
-                            let uri_clone = uri.clone();
-                            let document = event.document.clone();
-                            let text = event.text.clone();
-                            let version = event.version;
-
+                        let mut changes: Vec<_> = pending_changes.drain().collect();
+                        if deterministic::enabled() {
+                            changes.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+                        }
+                        for (uri, event) in changes {
+                            let backend = backend.clone();
                             tokio::spawn(async move {
-                                tokio::select! {
-                                    result = backend_clone.validate(document.clone(), &text, version) => {
-                                        match result {
-                                            Ok(diagnostics) => {
-                                                if document.version().await == version {
-                                                    backend_clone.client.publish_diagnostics(
-                                                        uri_clone.clone(),
-                                                        diagnostics,
-                                                        Some(version)
-                                                    ).await;
-                                                }
-                                                // Remove cancellation token
-                                                backend_clone.validation_cancel.lock().await.remove(&uri_clone);
-                                            }
-                                            Err(e) => error!("Validation failed for {}: {}", uri_clone, e),
-                                        }
-                                    }
-                                    _ = cancel_rx => {
-                                        debug!("Validation cancelled for {}", uri_clone);
-                                    }
-                                }
+                                backend.run_validation(uri, event.document, event.text, event.version, Publication::Settled).await;
                             });
                         }
                     }
@@ -305,14 +397,8 @@ impl RholangBackend {
 
                     // Process queue by priority
                     while let Some(PrioritizedTask(_, task)) = queue.pop() {
-                        match backend.index_file(&task.uri, &task.text, 0, None).await {
-                            Ok(cached_doc) => {
-                                backend.workspace.documents.insert(
-                                    task.uri.clone(),
-                                    Arc::new(cached_doc)
-                                );
-                                trace!("Indexed {} (priority {})", task.uri, task.priority);
-                            }
+                        match backend.index_disk_contents(&task.uri, &task.text).await {
+                            Ok(_) => trace!("Indexed {} (priority {})", task.uri, task.priority),
                             Err(e) => warn!("Failed to index {}: {}", task.uri, e),
                         }
                     }
@@ -405,24 +491,38 @@ impl RholangBackend {
         text: &str,
         version: i32
     ) -> Result<Vec<Diagnostic>, String> {
-        let state = document.state.read().await;
-        if state.version != version {
-            debug!("Skipping validation for outdated version {} (current: {})",
-                   version, state.version);
-            return Ok(Vec::new());
-        }
+        // Read what is needed and let go of the document, so edits are not held up
+        let uri = {
+            let state = document.state.read().await;
+            if state.version != version {
+                debug!("Skipping validation for outdated version {} (current: {})",
+                       version, state.version);
+                return Ok(Vec::new());
+            }
+            state.uri.clone()
+        };
+        // Stages left when the budget runs out are skipped, see `crate::lsp::analysis_budget`
+        let mut budget = self.analysis_budget.read().start();
 
         // Detect language and route to appropriate validator
         use crate::lsp::models::DocumentLanguage;
-        let language = DocumentLanguage::from_uri(&state.uri);
+        let language = DocumentLanguage::from_uri(&uri);
 
         if language == DocumentLanguage::Metta {
             // Validate MeTTa file
-            use crate::validators::MettaValidator;
-            debug!("Validating MeTTa file: {}", state.uri);
-            let validator = MettaValidator::new();
-            let diagnostics = validator.validate(text);
-            return Ok(diagnostics);
+            #[cfg(feature = "metta")]
+            {
+                use crate::validators::MettaValidator;
+                debug!("Validating MeTTa file: {}", uri);
+                let validator = MettaValidator::new();
+                let diagnostics = validator.validate(text);
+                return Ok(diagnostics);
+            }
+            #[cfg(not(feature = "metta"))]
+            {
+                debug!("Not validating MeTTa file {}: MeTTa support is not enabled in this build", uri);
+                return Ok(Vec::new());
+            }
         }
 
         // Local validation with parser reuse for semantic validation (Rholang)
@@ -440,57 +540,46 @@ impl RholangBackend {
                 error!("Syntax validation failed with {} errors", total_errors);
                 let diagnostics = failures.into_iter().flat_map(|failure| {
                     failure.errors.into_iter().map(|err| {
-                        let range = Range {
-                            start: LspPosition {
-                                line: (err.span.start.line - 1) as u32,
-                                character: (err.span.start.col - 1) as u32,
-                            },
-                            end: LspPosition {
-                                line: (err.span.end.line - 1) as u32,
-                                character: (err.span.end.col - 1) as u32,
-                            },
-                        };
-                        let message = match err.error {
-                            ParsingError::SyntaxError { sexp } => format!("Syntax error: {}", sexp),
-                            ParsingError::MissingToken(token) => format!("Missing token: {}", token),
-                            ParsingError::Unexpected(c) => format!("Unexpected character: {}", c),
-                            ParsingError::UnexpectedVar => "Unexpected variable".to_string(),
-                            ParsingError::UnexpectedMatchAfter { rule, offender } => format!("Unexpected {} after {}", offender, rule),
-                            ParsingError::NumberOutOfRange => "Number out of range".to_string(),
-                            ParsingError::DuplicateNameDecl { first, second } => format!("Duplicate name declaration at {} and {}", first, second),
-                            ParsingError::MalformedLetDecl { lhs_arity, rhs_arity } => format!("Malformed let declaration: LHS arity {} != RHS arity {}", lhs_arity, rhs_arity),
-                            ParsingError::UnexpectedQuote => "Unexpected quote character".to_string(),
-                        };
-                        Diagnostic {
-                            range,
-                            severity: Some(DiagnosticSeverity::ERROR),
-                            source: Some("rholang-parser".to_string()),
-                            message,
-                            ..Default::default()
-                        }
+                        parsing_error_diagnostic(
+                            (err.span.start.line as usize, err.span.start.col as usize),
+                            (err.span.end.line as usize, err.span.end.col as usize),
+                            err.error,
+                        )
                     }).collect::<Vec<_>>()
                 }).collect::<Vec<_>>();
                 (diagnostics, None)
             }
         };
 
+        // Parsing may take a while; the client may have moved on meanwhile
+        if self.validation_scheduler.is_superseded(&uri, version) {
+            debug!("Abandoning validation of {} after parsing: version {} is stale", uri, version);
+            return Ok(Vec::new());
+        }
+
         // Semantic validation (if no syntax errors)
         if local_diagnostics.is_empty() {
+            // RNode switched on at runtime takes over from the interpreter
+            let rnode = self.rnode_validation.provider();
+
             // OPTIMIZATION: If using Rust backend and have pre-parsed AST, use validate_parsed to avoid re-parsing
-            if let Some(validator) = &self.semantic_validator {
+            if let (None, Some(validator)) = (&rnode, &self.semantic_validator) {
                 if let Some(procs) = parsed_ast {
+                    let mut forms = Vec::new();
                     if procs.len() == 1 {
-                        debug!("Running optimized semantic validation with pre-parsed AST for URI={}", state.uri);
+                        debug!("Running optimized semantic validation with pre-parsed AST for URI={}", uri);
                         let ast = procs.into_iter().next().unwrap();
-                        let semantic_diagnostics = validator.validate_parsed(ast, &parser);
+                        let semantic_diagnostics = budget
+                            .run("semantic validation", || self.validate_process(validator, ast, &parser, &mut forms));
+                        self.store_normalized_forms(&uri, version, forms);
                         if !semantic_diagnostics.is_empty() {
                             info!("Semantic validation found {} errors for URI={} (version={})",
-                                  semantic_diagnostics.len(), state.uri, version);
-                            let all_diags = self.aggregate_with_virtual_diagnostics(&state.uri, semantic_diagnostics).await;
+                                  semantic_diagnostics.len(), uri, version);
+                            let all_diags = self.aggregate_with_virtual_diagnostics(&uri, semantic_diagnostics, &mut budget).await;
                             return Ok(all_diags);
                         }
-                        debug!("Semantic validation passed for URI={}", state.uri);
-                        let all_diags = self.aggregate_with_virtual_diagnostics(&state.uri, vec![]).await;
+                        debug!("Semantic validation passed for URI={}", uri);
+                        let all_diags = self.aggregate_with_virtual_diagnostics(&uri, vec![], &mut budget).await;
                         return Ok(all_diags);
                     } else {
                         // Multiple procs - validate each one separately
@@ -498,61 +587,141 @@ impl RholangBackend {
                         debug!("Multiple top-level processes detected ({}), validating each separately", num_procs);
                         let mut all_diagnostics = Vec::new();
                         for ast in &procs {
-                            let diagnostics = validator.validate_parsed(*ast, &parser);
+                            if self.validation_scheduler.is_superseded(&uri, version) {
+                                debug!("Abandoning validation of {}: version {} is stale", uri, version);
+                                return Ok(Vec::new());
+                            }
+                            if budget.is_exhausted() {
+                                warn!("Analysis budget exhausted after {} diagnostics for URI={}", all_diagnostics.len(), uri);
+                                budget.skip("semantic validation");
+                                break;
+                            }
+                            let diagnostics = self.validate_process(validator, *ast, &parser, &mut forms);
                             all_diagnostics.extend(diagnostics);
                         }
+                        self.store_normalized_forms(&uri, version, forms);
                         if !all_diagnostics.is_empty() {
                             info!("Semantic validation found {} errors across {} processes for URI={} (version={})",
-                                  all_diagnostics.len(), num_procs, state.uri, version);
-                            let final_diags = self.aggregate_with_virtual_diagnostics(&state.uri, all_diagnostics).await;
+                                  all_diagnostics.len(), num_procs, uri, version);
+                            let final_diags = self.aggregate_with_virtual_diagnostics(&uri, all_diagnostics, &mut budget).await;
                             return Ok(final_diags);
                         }
                         debug!("Semantic validation passed for all {} processes", num_procs);
-                        let final_diags = self.aggregate_with_virtual_diagnostics(&state.uri, vec![]).await;
+                        let final_diags = self.aggregate_with_virtual_diagnostics(&uri, vec![], &mut budget).await;
                         return Ok(final_diags);
                     }
                 }
             }
 
             // Use generic diagnostic provider (works for both Rust and gRPC backends)
+            let provider = match rnode {
+                Some(rnode) => rnode,
+                None if self.diagnostic_provider.verifies_with_rnode() => {
+                    debug!("RNode validation is off; skipping semantic validation for URI={}", uri);
+                    let all_diags = self.aggregate_with_virtual_diagnostics(&uri, vec![], &mut budget).await;
+                    return Ok(all_diags);
+                }
+                None => self.diagnostic_provider.clone(),
+            };
             debug!("Running semantic validation via {} backend for URI={}",
-                   self.diagnostic_provider.backend_name(), state.uri);
-            let semantic_diagnostics = self.diagnostic_provider.validate(text).await;
+                   provider.backend_name(), uri);
+            let semantic_diagnostics = match budget.remaining() {
+                None => provider.validate(text).await,
+                Some(remaining) => match tokio::time::timeout(remaining, provider.validate(text)).await {
+                    Ok(diagnostics) => diagnostics,
+                    Err(_) => {
+                        warn!("{} validation exceeded the analysis budget for URI={}", provider.backend_name(), uri);
+                        budget.skip("semantic validation");
+                        Vec::new()
+                    }
+                },
+            };
 
             if !semantic_diagnostics.is_empty() {
                 info!("{} validation found {} errors for URI={} (version={})",
-                      self.diagnostic_provider.backend_name(),
-                      semantic_diagnostics.len(), state.uri, version);
+                      provider.backend_name(),
+                      semantic_diagnostics.len(), uri, version);
             } else {
                 debug!("{} validation passed for URI={}",
-                       self.diagnostic_provider.backend_name(), state.uri);
+                       provider.backend_name(), uri);
             }
 
-            let all_diags = self.aggregate_with_virtual_diagnostics(&state.uri, semantic_diagnostics).await;
+            let all_diags = self.aggregate_with_virtual_diagnostics(&uri, semantic_diagnostics, &mut budget).await;
             Ok(all_diags)
         } else {
             // Return syntax errors if present
-            debug!("Syntax errors found for URI={}, skipping semantic validation", state.uri);
-            let all_diags = self.aggregate_with_virtual_diagnostics(&state.uri, local_diagnostics).await;
+            debug!("Syntax errors found for URI={}, skipping semantic validation", uri);
+            let all_diags = self.aggregate_with_virtual_diagnostics(&uri, local_diagnostics, &mut budget).await;
             Ok(all_diags)
         }
     }
 
     /// Aggregates diagnostics from parent document and virtual documents
+    ///
+    /// Stages reached after `budget` ran out are skipped, and the diagnostics
+    /// of a partial analysis end with a notice at the top of the file.
     async fn aggregate_with_virtual_diagnostics(
         &self,
         uri: &Url,
         mut parent_diagnostics: Vec<Diagnostic>,
+        budget: &mut AnalysisBudget,
     ) -> Vec<Diagnostic> {
         let mut virtual_docs = self.virtual_docs.write().await;
-        let virtual_diagnostics = virtual_docs.validate_all_for_parent(uri);
+        let virtual_diagnostics = budget.run("embedded languages", || virtual_docs.validate_all_for_parent(uri));
         if !virtual_diagnostics.is_empty() {
             debug!("Adding {} diagnostics from virtual documents", virtual_diagnostics.len());
             parent_diagnostics.extend(virtual_diagnostics);
         }
+        drop(virtual_docs);
+
+        let deprecation_diagnostics = budget.run("deprecations", || self.deprecation_diagnostics(uri));
+        if !deprecation_diagnostics.is_empty() {
+            debug!("Adding {} deprecation hints", deprecation_diagnostics.len());
+            parent_diagnostics.extend(deprecation_diagnostics);
+        }
+
+        let style_diagnostics = budget.run("style lints", || self.style_diagnostics(uri));
+        if !style_diagnostics.is_empty() {
+            debug!("Adding {} style lint diagnostics", style_diagnostics.len());
+            parent_diagnostics.extend(style_diagnostics);
+        }
+
+        let lint_diagnostics = budget.run("lint rules", || self.lint_diagnostics(uri));
+        if !lint_diagnostics.is_empty() {
+            debug!("Adding {} lint rule diagnostics", lint_diagnostics.len());
+            parent_diagnostics.extend(lint_diagnostics);
+        }
+
+        let duplicate_send_diagnostics = budget.run("duplicate sends", || self.duplicate_send_diagnostics(uri));
+        if !duplicate_send_diagnostics.is_empty() {
+            debug!("Adding {} duplicate send warnings", duplicate_send_diagnostics.len());
+            parent_diagnostics.extend(duplicate_send_diagnostics);
+        }
+
+        let param_doc_diagnostics = budget.run("@param directives", || self.param_doc_diagnostics(uri));
+        if !param_doc_diagnostics.is_empty() {
+            debug!("Adding {} @param mismatch warnings", param_doc_diagnostics.len());
+            parent_diagnostics.extend(param_doc_diagnostics);
+        }
+
+        let cost_diagnostics = budget.run("phlo costs", || self.cost_diagnostics(uri));
+        if !cost_diagnostics.is_empty() {
+            debug!("Adding {} phlo cost warnings", cost_diagnostics.len());
+            parent_diagnostics.extend(cost_diagnostics);
+        }
+
+        if let Some(notice) = budget.partial_diagnostic() {
+            info!("Publishing partial diagnostics for {}; skipped {:?}", uri, budget.skipped());
+            parent_diagnostics.push(notice);
+        }
         parent_diagnostics
     }
 
+    /// Language adapters by language id; embedders register extra languages here at startup.
+    pub fn adapter_registry(&self) -> Arc<parking_lot::RwLock<AdapterRegistry>> {
+        self.adapter_registry.clone()
+    }
+
     /// Looks up the IR node and its symbol table at a given position in the document.
     ///
     /// Priority 2b: Removed inverted_index from return type - now in rholang_symbols.
@@ -649,23 +818,4 @@ impl RholangBackend {
 
         locations
     }
-
-    /// Computes the byte offset from a line and character position in the source text.
-    pub fn byte_offset_from_position(text: &Rope, line: usize, character: usize) -> Option<usize> {
-        // Check if line is within bounds
-        if line >= text.len_lines() {
-            debug!("Line {} out of bounds (rope has {} lines)", line, text.len_lines());
-            return None;
-        }
-
-        text.try_line_to_byte(line).ok().map(|line_start_byte| {
-            let line_text = text.line(line);
-            let char_offset = character.min(line_text.len_chars());
-            let byte_in_line = line_text.char_to_byte(char_offset);
-            let total_byte = line_start_byte + byte_in_line;
-            debug!("byte_offset_from_position: line={}, character={}, line_start_byte={}, char_offset={}, byte_in_line={}, total_byte={}, line_text={:?}, total_text_len={}",
-                line, character, line_start_byte, char_offset, byte_in_line, total_byte, line_text.to_string(), text.len_bytes());
-            total_byte
-        })
-    }
 }
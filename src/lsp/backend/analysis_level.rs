@@ -0,0 +1,23 @@
+//! Adaptive analysis level notifications for the LSP backend
+//!
+//! Sizes each opened or edited document against the thresholds of
+//! `crate::lsp::analysis_level` and sends `rholang/analysisLevel` when its
+//! level changes.
+
+use tower_lsp::lsp_types::Url;
+use tracing::info;
+
+use crate::lsp::analysis_level::AnalysisLevelNotification;
+
+use super::state::RholangBackend;
+
+impl RholangBackend {
+    /// Updates the analysis level of `uri` from its current `text`.
+    pub(super) async fn update_analysis_level(&self, uri: &Url, text: &str) {
+        let lines = text.bytes().filter(|&byte| byte == b'\n').count() + 1;
+        if let Some(params) = self.analysis_levels.update(uri, text.len(), lines) {
+            info!("Analysis level of {} ({} bytes, {} lines): {:?}", uri, params.bytes, params.lines, params.level);
+            self.client.send_notification::<AnalysisLevelNotification>(params).await;
+        }
+    }
+}
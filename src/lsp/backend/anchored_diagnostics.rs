@@ -0,0 +1,65 @@
+//! Diagnostics that follow edits for the LSP backend
+//!
+//! Anchors every published diagnostic to the text of its version and, after
+//! each incremental change, republishes the anchored diagnostics at their
+//! shifted ranges until validation of the new text catches up (see
+//! `crate::lsp::anchored_diagnostics`).
+
+use tower_lsp::lsp_types::{Diagnostic, Url};
+use tracing::{debug, trace};
+
+use crate::lsp::models::ByteEdit;
+
+use super::state::{DiagnosticUpdate, RholangBackend};
+
+impl RholangBackend {
+    /// Anchors the diagnostics queued for `version` of `uri` to its text.
+    ///
+    /// Diagnostics of a version the document has already moved past are not
+    /// anchored; clearing them (no version) forgets the anchors.
+    pub(super) async fn anchor_diagnostics(&self, uri: &Url, version: Option<i32>, diagnostics: &[Diagnostic]) {
+        let Some(version) = version else {
+            self.anchored_diagnostics.remove(uri);
+            return;
+        };
+        let Some(document) = self.documents_by_uri.get(uri).map(|r| r.value().clone()) else {
+            return;
+        };
+        let state = document.state.read().await;
+        if state.version == version {
+            self.anchored_diagnostics.record(uri, version, diagnostics, &state.text, &state.line_index);
+        }
+    }
+
+    /// Republishes the anchored diagnostics of `uri`, moved from `previous` to
+    /// `version` through `edits`.
+    ///
+    /// A whole-document change (`edits` is `None`) drops the anchors: the
+    /// diagnostics stay where they are until validation replaces them.
+    pub(super) async fn shift_diagnostics(&self, uri: &Url, previous: i32, version: i32, edits: Option<&[ByteEdit]>) {
+        let Some(edits) = edits else {
+            self.anchored_diagnostics.remove(uri);
+            return;
+        };
+        let Some(document) = self.documents_by_uri.get(uri).map(|r| r.value().clone()) else {
+            return;
+        };
+        let shifted = {
+            let state = document.state.read().await;
+            if state.version != version {
+                return;
+            }
+            self.anchored_diagnostics.shift(uri, previous, version, edits, &state.text, &state.line_index)
+        };
+        let Some(mut diagnostics) = shifted else {
+            return;
+        };
+
+        trace!("Shifted {} diagnostics of {} to version {}", diagnostics.len(), uri, version);
+        self.capabilities.read().adapt_diagnostics(&mut diagnostics);
+        let update = DiagnosticUpdate { uri: uri.clone(), diagnostics, version: Some(version) };
+        if self.diagnostics_tx.send(update).await.is_err() {
+            debug!("Diagnostics publisher stopped; not republishing shifted diagnostics of {}", uri);
+        }
+    }
+}
@@ -0,0 +1,25 @@
+//! Binder name completion for the LSP backend
+//!
+//! Suggests names for a binder being written after `new` or in a `for`
+//! pattern from the channel, contract or system URI it is used with (see
+//! `crate::lsp::binder_names`).
+
+use tower_lsp::lsp_types::{CompletionItem, Position as LspPosition};
+use tracing::debug;
+
+use crate::lsp::binder_names::{binder_context, suggest_binder_names};
+use crate::lsp::models::CachedDocument;
+
+use super::state::RholangBackend;
+
+impl RholangBackend {
+    /// Binder name completions at `position` in `doc`, if a fresh binder is being written there.
+    pub(super) fn binder_name_completions(&self, doc: &CachedDocument, position: LspPosition) -> Vec<CompletionItem> {
+        let Some(context) = binder_context(&doc.text, &doc.line_index, position) else {
+            return Vec::new();
+        };
+        let names = suggest_binder_names(&context, position, &doc.ir, &doc.positions, &doc.text, &doc.line_index);
+        debug!("{} binder name(s) for {:?}", names.len(), context.site);
+        names.iter().enumerate().map(|(rank, name)| name.to_completion_item(&context, rank)).collect()
+    }
+}
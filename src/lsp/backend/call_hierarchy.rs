@@ -0,0 +1,163 @@
+//! Call hierarchy for the LSP backend
+//!
+//! Runs the per-document functions of `crate::lsp::call_hierarchy` over the
+//! workspace: incoming calls come from every document's indexed sends
+//! (`global_calls`), outgoing calls are matched against every indexed
+//! contract (`global_contracts`).
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use tower_lsp::lsp_types::{
+    CallHierarchyIncomingCall, CallHierarchyItem, CallHierarchyOutgoingCall, Position as LspPosition, Range,
+    SymbolKind, Url,
+};
+use tracing::debug;
+
+use crate::ir::rholang_node::{collect_contracts, RholangNode};
+use crate::lsp::call_hierarchy::{
+    calls_contract, contract_for_item, contract_item, contract_named_at, enclosing_contract, file_item, node_range,
+    send_at, send_parts, sends_in,
+};
+
+use super::state::RholangBackend;
+
+impl RholangBackend {
+    /// Every indexed contract with the document declaring it.
+    pub(super) fn indexed_contracts(&self) -> Vec<(Url, Arc<RholangNode>)> {
+        self.workspace
+            .global_contracts
+            .iter()
+            .flat_map(|entry| {
+                let uri = entry.key().clone();
+                entry.value().iter().map(move |contract| (uri.clone(), contract.clone())).collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// Call hierarchy item of `contract`, declared in the document `uri`.
+    fn indexed_contract_item(&self, uri: &Url, contract: &Arc<RholangNode>) -> Option<CallHierarchyItem> {
+        let doc = self.workspace.documents.get(uri).map(|entry| entry.value().clone())?;
+        contract_item(uri, contract, &doc.positions, &doc.text, &doc.line_index)
+    }
+
+    /// The contracts at `position` in `uri`: the contract named there, or the
+    /// contracts called by the send whose channel is there.
+    pub(super) fn call_hierarchy_items_at(&self, uri: &Url, position: LspPosition) -> Option<Vec<CallHierarchyItem>> {
+        let doc = self.workspace.documents.get(uri).map(|entry| entry.value().clone())?;
+        if let Some(contract) = contract_named_at(&doc.ir, &doc.positions, &doc.text, &doc.line_index, position) {
+            return contract_item(uri, &contract, &doc.positions, &doc.text, &doc.line_index).map(|item| vec![item]);
+        }
+
+        let send = send_at(&doc.ir, &doc.positions, &doc.text, &doc.line_index, position)?;
+        let items: Vec<CallHierarchyItem> = self
+            .indexed_contracts()
+            .iter()
+            .filter(|(_, contract)| calls_contract(&send, contract))
+            .filter_map(|(contract_uri, contract)| self.indexed_contract_item(contract_uri, contract))
+            .collect();
+        debug!("{} contract(s) called at {}:{:?}", items.len(), uri, position);
+        (!items.is_empty()).then_some(items)
+    }
+
+    /// The calls of the contract `item`, grouped by the contract (or the top
+    /// level of the document) they are made from.
+    pub(super) fn incoming_calls_of(&self, item: &CallHierarchyItem) -> Vec<CallHierarchyIncomingCall> {
+        let Some(declaring) = self.workspace.documents.get(&item.uri).map(|entry| entry.value().clone()) else {
+            return Vec::new();
+        };
+        let Some(contract) = contract_for_item(&declaring.ir, &declaring.positions, &declaring.text, &declaring.line_index, item)
+        else {
+            return Vec::new();
+        };
+
+        let indexed_calls: Vec<(Url, Vec<Arc<RholangNode>>)> = self
+            .workspace
+            .global_calls
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect();
+        let mut incoming = Vec::new();
+        for (uri, sends) in indexed_calls {
+            let Some(doc) = self.workspace.documents.get(&uri).map(|entry| entry.value().clone()) else {
+                continue;
+            };
+            let mut contracts = Vec::new();
+            collect_contracts(&doc.ir, &mut contracts);
+
+            // Callers in document order, each with the channels of its calls
+            let mut callers: Vec<(Option<Arc<RholangNode>>, Vec<Range>)> = Vec::new();
+            for send in sends.iter().filter(|send| calls_contract(send, &contract)) {
+                let Some(range) = send_parts(send)
+                    .and_then(|(channel, _)| node_range(channel, &doc.positions, &doc.text, &doc.line_index))
+                else {
+                    continue;
+                };
+                let caller = enclosing_contract(&contracts, &doc.positions, send);
+                let key = caller.as_ref().map(Arc::as_ptr);
+                match callers.iter_mut().find(|(other, _)| other.as_ref().map(Arc::as_ptr) == key) {
+                    Some((_, ranges)) => ranges.push(range),
+                    None => callers.push((caller, vec![range])),
+                }
+            }
+
+            for (caller, from_ranges) in callers {
+                let from = match caller {
+                    Some(caller) => contract_item(&uri, &caller, &doc.positions, &doc.text, &doc.line_index),
+                    None => Some(file_item(&uri, &doc.ir, &doc.positions, &doc.text, &doc.line_index)),
+                };
+                if let Some(from) = from {
+                    incoming.push(CallHierarchyIncomingCall { from, from_ranges });
+                }
+            }
+        }
+        debug!("{} incoming caller(s) of `{}`", incoming.len(), item.name);
+        incoming
+    }
+
+    /// The contracts called from the body of the contract `item` (or from the
+    /// top level of a document, for a file item), grouped by contract.
+    pub(super) fn outgoing_calls_of(&self, item: &CallHierarchyItem) -> Vec<CallHierarchyOutgoingCall> {
+        let Some(doc) = self.workspace.documents.get(&item.uri).map(|entry| entry.value().clone()) else {
+            return Vec::new();
+        };
+        let sends = if item.kind == SymbolKind::FILE {
+            sends_in(&doc.ir, None, &doc.positions)
+        } else {
+            let Some(contract) = contract_for_item(&doc.ir, &doc.positions, &doc.text, &doc.line_index, item) else {
+                return Vec::new();
+            };
+            sends_in(&doc.ir, Some(&contract), &doc.positions)
+        };
+
+        let contracts = self.indexed_contracts();
+        let mut called: HashMap<usize, Vec<Range>> = HashMap::new();
+        let mut order = Vec::new();
+        for send in &sends {
+            let Some(range) = send_parts(send)
+                .and_then(|(channel, _)| node_range(channel, &doc.positions, &doc.text, &doc.line_index))
+            else {
+                continue;
+            };
+            for (i, (_, contract)) in contracts.iter().enumerate() {
+                if calls_contract(send, contract) {
+                    called.entry(i).or_insert_with(|| {
+                        order.push(i);
+                        Vec::new()
+                    }).push(range);
+                }
+            }
+        }
+
+        let outgoing: Vec<CallHierarchyOutgoingCall> = order
+            .into_iter()
+            .filter_map(|i| {
+                let (uri, contract) = &contracts[i];
+                let to = self.indexed_contract_item(uri, contract)?;
+                Some(CallHierarchyOutgoingCall { to, from_ranges: called.remove(&i).unwrap_or_default() })
+            })
+            .collect();
+        debug!("{} contract(s) called from `{}`", outgoing.len(), item.name);
+        outgoing
+    }
+}
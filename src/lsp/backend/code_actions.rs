@@ -0,0 +1,37 @@
+//! Quick fix providers for the LSP backend
+//!
+//! Runs the registered `CodeActionProvider`s (see
+//! `crate::lsp::features::code_actions`) over the cached parse of the document.
+
+use tower_lsp::lsp_types::{CodeActionOrCommand, CodeActionParams};
+use tracing::debug;
+
+use crate::lsp::features::code_actions::{code_actions, CodeActionContext};
+use crate::lsp::models::DocumentLanguage;
+
+use super::state::RholangBackend;
+
+impl RholangBackend {
+    /// Quick fixes of the code action providers for `params`.
+    pub(super) fn provider_quick_fixes(&self, params: &CodeActionParams) -> Vec<CodeActionOrCommand> {
+        let uri = &params.text_document.uri;
+        let Some(doc) = self.workspace.documents.get(uri).map(|entry| entry.value().clone()) else {
+            return Vec::new();
+        };
+        if doc.language == DocumentLanguage::Metta {
+            return Vec::new();
+        }
+
+        let context = CodeActionContext {
+            uri,
+            text: &doc.text,
+            line_index: &doc.line_index,
+            tree: &doc.tree,
+            range: params.range,
+            diagnostics: &params.context.diagnostics,
+        };
+        let actions = code_actions(&self.code_action_providers, &context);
+        debug!("{} quick fix(es) from providers for {:?} in {}", actions.len(), params.range, uri);
+        actions
+    }
+}
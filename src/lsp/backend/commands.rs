@@ -0,0 +1,719 @@
+//! `workspace/executeCommand` handling for the LSP backend
+//!
+//! This module contains the server-side commands advertised in
+//! `ServerCapabilities::execute_command_provider`. Commands that execute code
+//! on RNode are gated by the workspace trust model in
+//! `crate::lsp::workspace_trust`; `rholang.deploy` signs deploys with the key
+//! of `crate::lsp::deploy_signing`.
+//!
+//! `rholang.expandPreview` renders `@expand` directives into a preview
+//! document (see `crate::lsp::expansion`). `rholang.deadChannels` reports
+//! unused and one-sided `new` names (see `crate::lsp::dead_channels`).
+//! `rholang.extractInterface` documents the contracts of a document or the
+//! workspace (see `crate::lsp::contract_interface`).
+//! `rholang.completionAccepted` counts an accepted completion item for
+//! ranking (see `crate::lsp::completion_ranking`).
+//! `rholang.toggleRnodeValidation` switches validation against RNode on or
+//! off (see `crate::lsp::rnode_validation`).
+//! `rholang.structuralReplace` rewrites the constructs matching a structural
+//! query (see `crate::lsp::structural_replace`).
+//! `rholang.todos` lists the `TODO`/`FIXME`/`NOTE` markers in comments (see
+//! `crate::lsp::task_markers`).
+//! `rholang.responseSends` lists the sends answering on a `*ret` return
+//! channel (see `crate::lsp::return_channels`).
+//! `rholang.nameEscapes` reports the `new` names handed out of their scope
+//! (see `crate::lsp::name_escapes`).
+//! `rholang.formatCheckWorkspace` lists the Rholang documents that differ from
+//! the formatter's output (see `crate::lsp::format_check`).
+//! `rholang.showScopes` returns the chain of lexical scopes at a position (see
+//! `crate::lsp::scope_chain`).
+//! `rholang.evaluateFile` evaluates a document on RNode and streams its output
+//! to the client (see `crate::lsp::evaluation`).
+//! `rholang.clearCache` deletes the persistent caches (see
+//! `crate::lsp::cache_schema`).
+
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use serde::Deserialize;
+use serde_json::Value;
+use tower_lsp::jsonrpc;
+use tower_lsp::Client;
+use tower_lsp::lsp_types::{ExecuteCommandParams, MessageActionItem, MessageType, Url};
+use tracing::{debug, info, trace, warn};
+
+use crate::lsp::cache_schema::{clear_dir, CLEAR_CACHE_COMMAND};
+use crate::lsp::completion_ranking::{unix_now, COMPLETION_ACCEPTED_COMMAND};
+use crate::lsp::contract_interface::{build_interface_report, extract_interfaces, ExtractInterfaceParams};
+use crate::lsp::cost_estimation::ESTIMATE_COST_COMMAND;
+use crate::lsp::dead_channels::{build_report, find_dead_channels, DeadChannelsParams};
+use crate::lsp::diagnostic_provider::DiagnosticProvider;
+use crate::lsp::evaluation::{output_lines, EvaluateFileArgs, EvaluationOutput, EvaluationOutputParams, EVALUATE_FILE_COMMAND};
+use crate::lsp::expansion::{build_preview, collect_templates, ExpandPreviewParams};
+use crate::lsp::format_check::{check_format, FormatCheckReport, FORMAT_CHECK_WORKSPACE_COMMAND};
+use crate::lsp::grpc_validator::GrpcValidator;
+use crate::lsp::index_checkpoint::default_checkpoint_dir;
+use crate::lsp::models::DocumentLanguage;
+use crate::lsp::move_contract::MOVE_CONTRACT_COMMAND;
+use crate::lsp::name_escapes::{self, find_name_escapes, NameEscapesParams, NAME_ESCAPES_COMMAND};
+use crate::lsp::return_channels::{ResponseSendsParams, RESPONSE_SENDS_COMMAND};
+use crate::lsp::rnode_validation::{ToggleRnodeValidationArgs, TOGGLE_RNODE_VALIDATION_COMMAND};
+use crate::lsp::scope_chain::{scope_chain, ShowScopesParams, SHOW_SCOPES_COMMAND};
+use crate::lsp::structural_query::{find_matches, StructuralQuery};
+use crate::lsp::structural_replace::{
+    annotated_edit, check_template, plan_document, RejectedDocument, StructuralReplaceParams,
+    StructuralReplaceReport, STRUCTURAL_REPLACE_COMMAND,
+};
+use crate::lsp::task_markers::{TodosParams, TodosReport, TODOS_COMMAND};
+use crate::lsp::text_normalization::TextFormat;
+use crate::lsp::workspace_folders::folder_of;
+use crate::lsp::workspace_trust::{DeployDenied, TrustDecision};
+use crate::rnode_apis::repl::{EvalRequest, repl_client::ReplClient};
+
+use super::state::RholangBackend;
+
+/// Deploys the given document to an allowlisted RNode endpoint
+pub const DEPLOY_COMMAND: &str = "rholang.deploy";
+
+/// Previews the expansion of `@expand` directives in a document
+pub const EXPAND_PREVIEW_COMMAND: &str = "rholang.expandPreview";
+
+/// Reports unused and one-sided `new` names in a document or the workspace
+pub const DEAD_CHANNELS_COMMAND: &str = "rholang.deadChannels";
+
+/// Generates an API document of the contracts in a document or the workspace
+pub const EXTRACT_INTERFACE_COMMAND: &str = "rholang.extractInterface";
+
+/// All commands handled by `execute_command`
+pub const COMMANDS: &[&str] = &[
+    DEPLOY_COMMAND,
+    EXPAND_PREVIEW_COMMAND,
+    DEAD_CHANNELS_COMMAND,
+    EXTRACT_INTERFACE_COMMAND,
+    COMPLETION_ACCEPTED_COMMAND,
+    TOGGLE_RNODE_VALIDATION_COMMAND,
+    STRUCTURAL_REPLACE_COMMAND,
+    TODOS_COMMAND,
+    RESPONSE_SENDS_COMMAND,
+    NAME_ESCAPES_COMMAND,
+    FORMAT_CHECK_WORKSPACE_COMMAND,
+    SHOW_SCOPES_COMMAND,
+    EVALUATE_FILE_COMMAND,
+    CLEAR_CACHE_COMMAND,
+    ESTIMATE_COST_COMMAND,
+    MOVE_CONTRACT_COMMAND,
+];
+
+/// Label of the trust prompt action that grants trust
+const TRUST_ACTION: &str = "Trust Workspace";
+/// Label of the trust prompt action that refuses trust
+const DONT_TRUST_ACTION: &str = "Don't Trust";
+
+/// Arguments of the `rholang.deploy` command
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DeployArgs {
+    /// Document to deploy
+    uri: Url,
+    /// Target endpoint (`host:port`); defaults to the first allowlisted endpoint
+    #[serde(default)]
+    endpoint: Option<String>,
+}
+
+impl RholangBackend {
+    /// Dispatches a `workspace/executeCommand` request.
+    pub(super) async fn dispatch_command(&self, params: ExecuteCommandParams) -> jsonrpc::Result<Option<Value>> {
+        debug!("executeCommand: {} with {} argument(s)", params.command, params.arguments.len());
+        match params.command.as_str() {
+            DEPLOY_COMMAND => self.deploy_command(params.arguments).await,
+            EXPAND_PREVIEW_COMMAND => self.expand_preview_command(params.arguments).await,
+            DEAD_CHANNELS_COMMAND => self.dead_channels_command(params.arguments).await,
+            EXTRACT_INTERFACE_COMMAND => self.extract_interface_command(params.arguments).await,
+            COMPLETION_ACCEPTED_COMMAND => self.completion_accepted_command(params.arguments),
+            TOGGLE_RNODE_VALIDATION_COMMAND => self.toggle_rnode_validation_command(params.arguments).await,
+            STRUCTURAL_REPLACE_COMMAND => self.structural_replace_command(params.arguments).await,
+            TODOS_COMMAND => self.todos_command(params.arguments),
+            RESPONSE_SENDS_COMMAND => self.response_sends_command(params.arguments),
+            NAME_ESCAPES_COMMAND => self.name_escapes_command(params.arguments),
+            FORMAT_CHECK_WORKSPACE_COMMAND => self.format_check_workspace_command(),
+            SHOW_SCOPES_COMMAND => self.show_scopes_command(params.arguments),
+            EVALUATE_FILE_COMMAND => self.evaluate_file_command(params.arguments).await,
+            CLEAR_CACHE_COMMAND => self.clear_cache_command(),
+            ESTIMATE_COST_COMMAND => self.estimate_cost_command(params.arguments).await,
+            MOVE_CONTRACT_COMMAND => self.move_contract_command(params.arguments).await,
+            other => {
+                warn!("Unknown command: {}", other);
+                Err(jsonrpc::Error::invalid_params(format!("Unknown command: {}", other)))
+            }
+        }
+    }
+
+    /// Handles `rholang.deploy`: signs a document and deploys it to RNode.
+    ///
+    /// Refuses when no deploy key is configured, for untrusted workspaces and
+    /// for endpoints off the allowlist. The endpoint defaults to
+    /// `deploy.validatorUrl`, then to the first allowlisted endpoint.
+    async fn deploy_command(&self, arguments: Vec<Value>) -> jsonrpc::Result<Option<Value>> {
+        let args: DeployArgs = arguments
+            .into_iter()
+            .next()
+            .and_then(|arg| serde_json::from_value(arg).ok())
+            .ok_or_else(|| jsonrpc::Error::invalid_params("Expected { uri, endpoint? } argument"))?;
+
+        // No prompt for a deploy that could not be signed anyway
+        let (key_check, validator_url) = {
+            let settings = self.deploy_settings.read();
+            (settings.key().map(|_| ()), settings.validator_url.clone())
+        };
+        if let Err(reason) = key_check {
+            warn!("Deploy refused: {}", reason);
+            self.client.show_message(MessageType::WARNING, format!("Deploy refused: {}", reason)).await;
+            return Err(command_error(jsonrpc::ErrorCode::InvalidRequest, reason));
+        }
+
+        let requested = args.endpoint.or(validator_url);
+        let endpoint = match self.ensure_deploy_permitted(&args.uri, requested.as_deref()).await {
+            Ok(endpoint) => endpoint,
+            Err(denied) => {
+                warn!("{}", denied);
+                self.client.show_message(MessageType::WARNING, &denied).await;
+                return Err(command_error(jsonrpc::ErrorCode::InvalidRequest, denied.to_string()));
+            }
+        };
+
+        let term = self.document_text(&args.uri).await.ok_or_else(|| {
+            jsonrpc::Error::invalid_params(format!("Document not found: {}", args.uri))
+        })?;
+
+        info!("Deploying {} to RNode at {}", args.uri, endpoint);
+        let receipt = self
+            .deploy_signed(&args.uri, &endpoint, term)
+            .await
+            .map_err(|reason| command_error(jsonrpc::ErrorCode::InternalError, reason))?;
+
+        Ok(Some(serde_json::json!({
+            "endpoint": endpoint,
+            "deployId": receipt.deploy_id,
+            "result": receipt.result,
+        })))
+    }
+
+    /// Handles `rholang.evaluateFile`: evaluates a document on RNode and streams its output.
+    ///
+    /// The endpoint defaults to the RNode validating documents, if any; it is
+    /// subject to the same allowlist and workspace trust as `rholang.deploy`.
+    async fn evaluate_file_command(&self, arguments: Vec<Value>) -> jsonrpc::Result<Option<Value>> {
+        let args: EvaluateFileArgs = arguments
+            .into_iter()
+            .next()
+            .and_then(|arg| serde_json::from_value(arg).ok())
+            .ok_or_else(|| jsonrpc::Error::invalid_params("Expected { uri, endpoint? } argument"))?;
+
+        let requested = args.endpoint.clone().or_else(|| {
+            self.rnode_validation.is_enabled().then(|| self.rnode_validation.status().address)
+        });
+        let endpoint = match self.ensure_deploy_permitted(&args.uri, requested.as_deref()).await {
+            Ok(endpoint) => endpoint,
+            Err(denied) => {
+                warn!("{}", denied);
+                self.client.show_message(MessageType::WARNING, &denied).await;
+                return Err(command_error(jsonrpc::ErrorCode::InvalidRequest, denied.to_string()));
+            }
+        };
+
+        let program = self.document_text(&args.uri).await.ok_or_else(|| {
+            jsonrpc::Error::invalid_params(format!("Document not found: {}", args.uri))
+        })?;
+
+        let evaluation = self.next_rnode_run.fetch_add(1, Ordering::Relaxed);
+        info!("Evaluating {} on RNode at {} (evaluation {})", args.uri, endpoint, evaluation);
+        self.client
+            .send_notification::<EvaluationOutput>(EvaluationOutputParams::started(args.uri.clone(), evaluation, endpoint.clone()))
+            .await;
+
+        let result = match ReplClient::connect(format!("http://{}", endpoint)).await {
+            Ok(mut client) => client
+                .eval(EvalRequest { program, print_unmatched_sends_only: false })
+                .await
+                .map(|response| response.into_inner().output)
+                .map_err(|e| format!("Evaluation on {} failed: {}", endpoint, e)),
+            Err(e) => Err(format!("Failed to connect to RNode at {}: {}", endpoint, e)),
+        };
+
+        match result {
+            Ok(output) => {
+                for line in output_lines(&output) {
+                    self.client.log_message(MessageType::LOG, format!("[{}] {}", args.uri, line)).await;
+                    self.client
+                        .send_notification::<EvaluationOutput>(EvaluationOutputParams::output(args.uri.clone(), evaluation, line.to_string()))
+                        .await;
+                }
+                self.client
+                    .send_notification::<EvaluationOutput>(EvaluationOutputParams::finished(args.uri.clone(), evaluation))
+                    .await;
+                Ok(Some(serde_json::json!({
+                    "evaluation": evaluation,
+                    "endpoint": endpoint,
+                    "output": output,
+                })))
+            }
+            Err(reason) => {
+                warn!("{}", reason);
+                self.client.log_message(MessageType::ERROR, &reason).await;
+                self.client
+                    .send_notification::<EvaluationOutput>(EvaluationOutputParams::failed(args.uri, evaluation, reason.clone()))
+                    .await;
+                Err(command_error(jsonrpc::ErrorCode::InternalError, reason))
+            }
+        }
+    }
+
+    /// Handles `rholang.expandPreview`.
+    ///
+    /// Templates are collected from every indexed document; templates declared
+    /// in the previewed document itself take precedence.
+    async fn expand_preview_command(&self, arguments: Vec<Value>) -> jsonrpc::Result<Option<Value>> {
+        let params: ExpandPreviewParams = arguments
+            .into_iter()
+            .next()
+            .and_then(|arg| serde_json::from_value(arg).ok())
+            .ok_or_else(|| jsonrpc::Error::invalid_params("Expected { uri, position? } argument"))?;
+
+        let text = self.document_text(&params.uri).await.ok_or_else(|| {
+            jsonrpc::Error::invalid_params(format!("Document not found: {}", params.uri))
+        })?;
+
+        let mut templates = HashMap::new();
+        for entry in self.workspace.documents.iter() {
+            if entry.key() == &params.uri {
+                continue;
+            }
+            for template in collect_templates(entry.key(), &entry.value().text.to_string()) {
+                templates.entry(template.name.clone()).or_insert(template);
+            }
+        }
+        for template in collect_templates(&params.uri, &text) {
+            templates.insert(template.name.clone(), template);
+        }
+
+        let preview = build_preview(&params.uri, &text, &templates, params.position);
+        debug!("Expansion preview for {}: {} block(s)", params.uri, preview.mappings.len());
+        serde_json::to_value(preview)
+            .map(Some)
+            .map_err(|e| command_error(jsonrpc::ErrorCode::InternalError, format!("Failed to serialize preview: {}", e)))
+    }
+
+    /// Handles `rholang.deadChannels`.
+    ///
+    /// Without a `uri` argument every indexed Rholang document is scanned.
+    async fn dead_channels_command(&self, arguments: Vec<Value>) -> jsonrpc::Result<Option<Value>> {
+        let params: DeadChannelsParams = match arguments.into_iter().next() {
+            None | Some(Value::Null) => DeadChannelsParams::default(),
+            Some(arg) => serde_json::from_value(arg)
+                .map_err(|_| jsonrpc::Error::invalid_params("Expected { uri? } argument"))?,
+        };
+
+        let documents: Vec<_> = match &params.uri {
+            Some(uri) => {
+                let doc = self.workspace.documents.get(uri).map(|entry| entry.value().clone()).ok_or_else(|| {
+                    jsonrpc::Error::invalid_params(format!("Document not found: {}", uri))
+                })?;
+                vec![(uri.clone(), doc)]
+            }
+            None => self
+                .workspace
+                .documents
+                .iter()
+                .filter(|entry| entry.value().language == DocumentLanguage::Rholang)
+                .map(|entry| (entry.key().clone(), entry.value().clone()))
+                .collect(),
+        };
+
+        let channels: Vec<_> = documents
+            .iter()
+            .flat_map(|(uri, doc)| find_dead_channels(uri, &doc.ir, &doc.positions, &doc.text, &doc.line_index))
+            .collect();
+        debug!("Dead channel report: {} channel(s) in {} document(s)", channels.len(), documents.len());
+        let report = build_report(params.uri.as_ref(), channels, documents.len());
+        serde_json::to_value(report)
+            .map(Some)
+            .map_err(|e| command_error(jsonrpc::ErrorCode::InternalError, format!("Failed to serialize report: {}", e)))
+    }
+
+    /// Handles `rholang.extractInterface`.
+    ///
+    /// Without a `uri` argument every indexed Rholang document is described.
+    async fn extract_interface_command(&self, arguments: Vec<Value>) -> jsonrpc::Result<Option<Value>> {
+        let params: ExtractInterfaceParams = match arguments.into_iter().next() {
+            None | Some(Value::Null) => ExtractInterfaceParams::default(),
+            Some(arg) => serde_json::from_value(arg)
+                .map_err(|_| jsonrpc::Error::invalid_params("Expected { uri?, format? } argument"))?,
+        };
+
+        let documents: Vec<_> = match &params.uri {
+            Some(uri) => {
+                let doc = self.workspace.documents.get(uri).map(|entry| entry.value().clone()).ok_or_else(|| {
+                    jsonrpc::Error::invalid_params(format!("Document not found: {}", uri))
+                })?;
+                vec![(uri.clone(), doc)]
+            }
+            None => self
+                .workspace
+                .documents
+                .iter()
+                .filter(|entry| entry.value().language == DocumentLanguage::Rholang)
+                .map(|entry| (entry.key().clone(), entry.value().clone()))
+                .collect(),
+        };
+
+        let contracts: Vec<_> = documents
+            .iter()
+            .flat_map(|(uri, doc)| extract_interfaces(uri, &doc.ir, &doc.positions, &doc.text, &doc.line_index, &doc.tree))
+            .collect();
+        debug!("Contract interface: {} contract(s) in {} document(s)", contracts.len(), documents.len());
+        let report = build_interface_report(params.uri.as_ref(), params.format, contracts, documents.len());
+        serde_json::to_value(report)
+            .map(Some)
+            .map_err(|e| command_error(jsonrpc::ErrorCode::InternalError, format!("Failed to serialize interface: {}", e)))
+    }
+
+    /// Handles `rholang.toggleRnodeValidation`, connecting to RNode when
+    /// switching it on, then validates the open documents again.
+    ///
+    /// A failed connection leaves validation as it was and is reported in the
+    /// returned status and to the user.
+    async fn toggle_rnode_validation_command(&self, arguments: Vec<Value>) -> jsonrpc::Result<Option<Value>> {
+        let args: ToggleRnodeValidationArgs = match arguments.into_iter().next() {
+            None | Some(Value::Null) => ToggleRnodeValidationArgs::default(),
+            Some(arg) => serde_json::from_value(arg)
+                .map_err(|_| jsonrpc::Error::invalid_params("Expected { enabled?, address? } argument"))?,
+        };
+
+        let (enabled, address) = self.rnode_validation.target(&args);
+        self.switch_rnode_validation(enabled, address).await;
+
+        self.revalidate_open_documents().await;
+        serde_json::to_value(self.rnode_validation.status())
+            .map(Some)
+            .map_err(|e| command_error(jsonrpc::ErrorCode::InternalError, format!("Failed to serialize status: {}", e)))
+    }
+
+    /// Switches RNode validation on against `address`, or off.
+    ///
+    /// A validator that cannot be reached leaves validation as it was and is
+    /// reported to the user.
+    pub(super) async fn switch_rnode_validation(&self, enabled: bool, address: String) {
+        if enabled {
+            match GrpcValidator::new(address.clone()).await {
+                Ok(validator) => {
+                    info!("RNode validation switched on ({})", address);
+                    validator.set_position_encoding(self.capabilities.read().position_encoding);
+                    self.rnode_validation.enable(address, Arc::new(Box::new(validator)));
+                }
+                Err(e) => {
+                    warn!("Failed to switch RNode validation on: {}", e);
+                    self.client
+                        .show_message(MessageType::WARNING, format!("RNode validation stays off: {}", e))
+                        .await;
+                    self.rnode_validation.fail(address, e.to_string());
+                }
+            }
+        } else {
+            info!("RNode validation switched off");
+            self.rnode_validation.disable();
+        }
+    }
+
+    /// Handles `rholang.structuralReplace`: previews the rewrite of every match
+    /// of a structural query as an annotated workspace edit, and asks the
+    /// client to apply it when requested.
+    async fn structural_replace_command(&self, arguments: Vec<Value>) -> jsonrpc::Result<Option<Value>> {
+        let params: StructuralReplaceParams = arguments
+            .into_iter()
+            .next()
+            .and_then(|arg| serde_json::from_value(arg).ok())
+            .ok_or_else(|| jsonrpc::Error::invalid_params("Expected { query, template, uri?, apply? } argument"))?;
+        let query = StructuralQuery::parse(&params.query).map_err(jsonrpc::Error::invalid_params)?;
+        check_template(&params.template).map_err(jsonrpc::Error::invalid_params)?;
+
+        let documents: Vec<_> = match &params.uri {
+            Some(uri) => {
+                let doc = self.workspace.documents.get(uri).map(|entry| entry.value().clone()).ok_or_else(|| {
+                    jsonrpc::Error::invalid_params(format!("Document not found: {}", uri))
+                })?;
+                vec![(uri.clone(), doc)]
+            }
+            None => self
+                .workspace
+                .documents
+                .iter()
+                .filter(|entry| entry.value().language == DocumentLanguage::Rholang)
+                .map(|entry| (entry.key().clone(), entry.value().clone()))
+                .collect(),
+        };
+
+        let mut report = StructuralReplaceReport::default();
+        let mut edits = Vec::new();
+        for (uri, doc) in &documents {
+            let matches = find_matches(&query, uri, &doc.ir, &doc.positions, &doc.text, &doc.line_index);
+            if matches.is_empty() {
+                continue;
+            }
+            if self.libraries.contains(uri) {
+                let reason = "Read-only library file".to_string();
+                report.rejected.push(RejectedDocument { uri: uri.clone(), reason });
+                continue;
+            }
+            // Only open documents have a version the client knows, and their line endings
+            let open = self.documents_by_uri.get(uri).map(|entry| entry.value().clone());
+            let format = match &open {
+                Some(document) => document.state.read().await.format,
+                None => TextFormat::default(),
+            };
+            let version = open.is_some().then_some(doc.version);
+            let template = format.apply_line_endings(&params.template);
+            match plan_document(uri, version, &doc.text, matches, &template) {
+                Ok((edit, replacements, skipped)) => {
+                    report.replacements += replacements;
+                    report.skipped_nested += skipped;
+                    edits.push(edit);
+                }
+                Err(reason) => report.rejected.push(RejectedDocument { uri: uri.clone(), reason }),
+            }
+        }
+        edits.sort_by(|a, b| a.text_document.uri.as_str().cmp(b.text_document.uri.as_str()));
+        report.edit = annotated_edit(&params.query, edits);
+        debug!(
+            "Structural replace {:?}: {} replacement(s), {} nested, {} document(s) rejected",
+            params.query,
+            report.replacements,
+            report.skipped_nested,
+            report.rejected.len()
+        );
+
+        if params.apply && report.replacements > 0 {
+            let response = self
+                .client
+                .apply_edit(report.edit.clone())
+                .await
+                .map_err(|e| command_error(jsonrpc::ErrorCode::InternalError, format!("Failed to apply edit: {}", e)))?;
+            if let Some(reason) = &response.failure_reason {
+                warn!("Client did not apply the structural replace: {}", reason);
+            }
+            report.applied = response.applied;
+        }
+
+        serde_json::to_value(report)
+            .map(Some)
+            .map_err(|e| command_error(jsonrpc::ErrorCode::InternalError, format!("Failed to serialize report: {}", e)))
+    }
+
+    /// Handles `rholang.todos`: the task markers of a document or the workspace.
+    fn todos_command(&self, arguments: Vec<Value>) -> jsonrpc::Result<Option<Value>> {
+        let params: TodosParams = match arguments.into_iter().next() {
+            None | Some(Value::Null) => TodosParams::default(),
+            Some(arg) => serde_json::from_value(arg).map_err(|_| jsonrpc::Error::invalid_params("Expected { uri? } argument"))?,
+        };
+        if let Some(uri) = &params.uri {
+            if !self.workspace.documents.contains_key(uri) {
+                return Err(jsonrpc::Error::invalid_params(format!("Document not found: {}", uri)));
+            }
+        }
+
+        let report = TodosReport { tasks: self.task_list(params.uri.as_ref()) };
+        serde_json::to_value(report)
+            .map(Some)
+            .map_err(|e| command_error(jsonrpc::ErrorCode::InternalError, format!("Failed to serialize tasks: {}", e)))
+    }
+
+    /// Handles `rholang.responseSends`; `null` when the position is not on a return channel.
+    fn response_sends_command(&self, arguments: Vec<Value>) -> jsonrpc::Result<Option<Value>> {
+        let params: ResponseSendsParams = arguments
+            .into_iter()
+            .next()
+            .and_then(|arg| serde_json::from_value(arg).ok())
+            .ok_or_else(|| jsonrpc::Error::invalid_params("Expected { uri, position } argument"))?;
+        if !self.workspace.documents.contains_key(&params.uri) {
+            return Err(jsonrpc::Error::invalid_params(format!("Document not found: {}", params.uri)));
+        }
+
+        let Some(report) = self.response_sends_at(&params.uri, params.position) else {
+            return Ok(Some(Value::Null));
+        };
+        serde_json::to_value(report)
+            .map(Some)
+            .map_err(|e| command_error(jsonrpc::ErrorCode::InternalError, format!("Failed to serialize response sends: {}", e)))
+    }
+
+    /// Handles `rholang.showScopes`.
+    fn show_scopes_command(&self, arguments: Vec<Value>) -> jsonrpc::Result<Option<Value>> {
+        let params: ShowScopesParams = arguments
+            .into_iter()
+            .next()
+            .and_then(|arg| serde_json::from_value(arg).ok())
+            .ok_or_else(|| jsonrpc::Error::invalid_params("Expected { uri, position } argument"))?;
+        let doc = self.workspace.documents.get(&params.uri).map(|entry| entry.value().clone()).ok_or_else(|| {
+            jsonrpc::Error::invalid_params(format!("Document not found: {}", params.uri))
+        })?;
+        if doc.language == DocumentLanguage::Metta {
+            return Ok(Some(Value::Null));
+        }
+
+        let chain = scope_chain(&doc.ir, &doc.positions, &doc.text, &doc.line_index, params.position);
+        debug!("{} scope(s) at {}:{:?}", chain.scopes.len(), params.uri, params.position);
+        serde_json::to_value(chain)
+            .map(Some)
+            .map_err(|e| command_error(jsonrpc::ErrorCode::InternalError, format!("Failed to serialize scopes: {}", e)))
+    }
+
+    /// Handles `rholang.nameEscapes`.
+    ///
+    /// Without a `uri` argument every indexed Rholang document is scanned.
+    fn name_escapes_command(&self, arguments: Vec<Value>) -> jsonrpc::Result<Option<Value>> {
+        let params: NameEscapesParams = match arguments.into_iter().next() {
+            None | Some(Value::Null) => NameEscapesParams::default(),
+            Some(arg) => serde_json::from_value(arg)
+                .map_err(|_| jsonrpc::Error::invalid_params("Expected { uri? } argument"))?,
+        };
+
+        let documents: Vec<_> = match &params.uri {
+            Some(uri) => {
+                let doc = self.workspace.documents.get(uri).map(|entry| entry.value().clone()).ok_or_else(|| {
+                    jsonrpc::Error::invalid_params(format!("Document not found: {}", uri))
+                })?;
+                vec![(uri.clone(), doc)]
+            }
+            None => self
+                .workspace
+                .documents
+                .iter()
+                .filter(|entry| entry.value().language == DocumentLanguage::Rholang)
+                .map(|entry| (entry.key().clone(), entry.value().clone()))
+                .collect(),
+        };
+
+        let names: Vec<_> = documents
+            .iter()
+            .flat_map(|(uri, doc)| find_name_escapes(uri, &doc.ir, &doc.positions, &doc.text, &doc.line_index))
+            .collect();
+        debug!("Name escape report: {} name(s) in {} document(s)", names.len(), documents.len());
+        let report = name_escapes::build_report(params.uri.as_ref(), names, documents.len());
+        serde_json::to_value(report)
+            .map(Some)
+            .map_err(|e| command_error(jsonrpc::ErrorCode::InternalError, format!("Failed to serialize report: {}", e)))
+    }
+
+    /// Handles `rholang.clearCache`: deletes the indexing checkpoints, in use or not.
+    fn clear_cache_command(&self) -> jsonrpc::Result<Option<Value>> {
+        let removed = match self.index_checkpoints.lock().as_mut() {
+            Some(checkpoints) => checkpoints.clear(),
+            None => default_checkpoint_dir().map_or(Ok(0), |dir| clear_dir(&dir)),
+        }
+        .map_err(|e| command_error(jsonrpc::ErrorCode::InternalError, format!("Failed to clear the cache: {}", e)))?;
+        info!("Cleared {} cache file(s)", removed);
+        Ok(Some(serde_json::json!({ "removed": removed })))
+    }
+
+    /// Handles `rholang.formatCheckWorkspace` over every indexed Rholang document.
+    fn format_check_workspace_command(&self) -> jsonrpc::Result<Option<Value>> {
+        let mut documents: Vec<_> = self
+            .workspace
+            .documents
+            .iter()
+            .filter(|entry| entry.value().language == DocumentLanguage::Rholang)
+            .map(|entry| (entry.key().clone(), entry.value().text.to_string()))
+            .collect();
+        documents.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+
+        let encoding = self.capabilities.read().position_encoding;
+        let mut report = FormatCheckReport::default();
+        for (uri, text) in &documents {
+            report.record(uri, &check_format(text, encoding));
+        }
+        debug!(
+            "Format check: {} of {} document(s) unformatted, {} unparsable",
+            report.unformatted, report.checked, report.unparsable
+        );
+        serde_json::to_value(report)
+            .map(Some)
+            .map_err(|e| command_error(jsonrpc::ErrorCode::InternalError, format!("Failed to serialize report: {}", e)))
+    }
+
+    /// Handles `rholang.completionAccepted`, sent by the client with the label of
+    /// the accepted completion item.
+    fn completion_accepted_command(&self, arguments: Vec<Value>) -> jsonrpc::Result<Option<Value>> {
+        let label = arguments
+            .first()
+            .and_then(Value::as_str)
+            .ok_or_else(|| jsonrpc::Error::invalid_params("Expected the completion label"))?;
+        trace!("Completion accepted: {}", label);
+        self.completion_usage.record(label, unix_now());
+        Ok(None)
+    }
+
+    /// Checks the deploy allowlist and the trust of the workspace folder
+    /// containing `uri`, prompting the user if needed.
+    ///
+    /// Returns the normalized endpoint to deploy to. The endpoint check runs
+    /// first so that the user is never prompted for a deploy that would be
+    /// refused anyway. Concurrent deploys share one prompt per workspace folder.
+    pub(super) async fn ensure_deploy_permitted(&self, uri: &Url, endpoint: Option<&str>) -> Result<String, DeployDenied> {
+        let endpoint = self.workspace_trust.resolve_endpoint(endpoint)?;
+        let root = uri
+            .to_file_path()
+            .ok()
+            .and_then(|path| folder_of(&path, &self.workspace_roots.read()).cloned())
+            .ok_or(DeployDenied::NoWorkspace)?;
+
+        let message = format!(
+            "Allow workspace {} to deploy code to RNode at {}?",
+            root.display(),
+            endpoint
+        );
+        let client = self.client.clone();
+        match self.workspace_trust.decide(&root, || prompt_workspace_trust(client, message)).await {
+            TrustDecision::Trusted => Ok(endpoint),
+            TrustDecision::Untrusted => Err(DeployDenied::UntrustedWorkspace(root)),
+        }
+    }
+
+    /// Returns the current text of a document, preferring the open editor buffer.
+    pub(super) async fn document_text(&self, uri: &Url) -> Option<String> {
+        if let Some(document) = self.documents_by_uri.get(uri).map(|r| r.value().clone()) {
+            return Some(document.text().await);
+        }
+        if let Some(cached) = self.workspace.documents.get(uri) {
+            return Some(cached.text.to_string());
+        }
+        uri.to_file_path().ok().and_then(|path| std::fs::read_to_string(path).ok())
+    }
+}
+
+/// Asks the user to trust a workspace via `window/showMessageRequest`.
+///
+/// A dismissed prompt or a client error counts as "not trusted".
+async fn prompt_workspace_trust(client: Client, message: String) -> TrustDecision {
+    let actions = vec![
+        MessageActionItem { title: TRUST_ACTION.to_string(), properties: Default::default() },
+        MessageActionItem { title: DONT_TRUST_ACTION.to_string(), properties: Default::default() },
+    ];
+
+    match client.show_message_request(MessageType::WARNING, message, Some(actions)).await {
+        Ok(Some(item)) if item.title == TRUST_ACTION => TrustDecision::Trusted,
+        Ok(_) => TrustDecision::Untrusted,
+        Err(e) => {
+            warn!("Workspace trust prompt failed: {}", e);
+            TrustDecision::Untrusted
+        }
+    }
+}
+
+/// Builds a JSON-RPC error carrying a user-facing message.
+pub(super) fn command_error(code: jsonrpc::ErrorCode, message: String) -> jsonrpc::Error {
+    jsonrpc::Error {
+        code,
+        message: message.into(),
+        data: None,
+    }
+}
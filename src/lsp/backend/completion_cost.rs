@@ -0,0 +1,50 @@
+//! Cost-aware completion for the LSP backend
+//!
+//! Estimates the cost of the contracts offered by completion from their
+//! bodies across the workspace and downranks the costly ones (see
+//! `crate::lsp::completion_cost`).
+
+use std::collections::HashMap;
+
+use tower_lsp::lsp_types::{CompletionItem, Position as LspPosition};
+use tracing::debug;
+
+use crate::ir::rholang_node::Position as IrPosition;
+use crate::lsp::completion_cost::{apply_cost_hints, in_loop_at, ContractCost};
+use crate::lsp::models::CachedDocument;
+
+use super::state::RholangBackend;
+
+impl RholangBackend {
+    /// Whether completion items carry cost hints: as the client chose, or
+    /// while RNode validation is active.
+    fn cost_aware_completion(&self) -> bool {
+        self.cost_aware_completion.read().unwrap_or_else(|| self.rnode_validation.is_enabled())
+    }
+
+    /// Adds cost hints to the contract items of a completion at `position` in `doc`.
+    pub(super) fn apply_completion_costs(&self, doc: &CachedDocument, position: LspPosition, items: &mut Vec<CompletionItem>) {
+        if !self.cost_aware_completion() {
+            return;
+        }
+
+        let mut costs: HashMap<String, ContractCost> = HashMap::new();
+        for entry in self.workspace.global_contracts.iter() {
+            for (name, cost) in entry.value().iter().filter_map(ContractCost::estimate) {
+                costs.entry(name).or_default().merge(&cost);
+            }
+        }
+        costs.retain(|_, cost| cost.level().is_some());
+
+        let in_loop = doc
+            .line_index
+            .offset(position.line as usize, position.character as usize, &doc.text)
+            .is_some_and(|byte| {
+                let ir_position = IrPosition { row: position.line as usize, column: position.character as usize, byte };
+                in_loop_at(&doc.ir, &doc.positions, ir_position)
+            });
+        debug!("Cost hints for {} costly contract(s), completing inside a loop: {}", costs.len(), in_loop);
+
+        apply_cost_hints(items, &costs, in_loop);
+    }
+}
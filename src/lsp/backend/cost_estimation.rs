@@ -0,0 +1,111 @@
+//! Phlogiston cost estimation for the LSP backend
+//!
+//! Handles `rholang.estimateCost` (see `crate::lsp::cost_estimation`) and
+//! serves the costs it stores as inlay hints and warnings while the
+//! document is unchanged.
+
+use serde_json::Value;
+use tower_lsp::jsonrpc;
+use tower_lsp::lsp_types::{Diagnostic, InlayHint, MessageType, Range, Url};
+use tracing::{debug, info, warn};
+
+use crate::lsp::cost_estimation::{
+    parse_deployment_cost, top_level_processes, CostEstimates, EstimateCostArgs, ProcessCost,
+};
+use crate::lsp::refresh::RefreshSet;
+use crate::rnode_apis::repl::{repl_client::ReplClient, EvalRequest};
+
+use super::commands::command_error;
+use super::state::RholangBackend;
+
+impl RholangBackend {
+    /// Handles `rholang.estimateCost`: evaluates each top-level process of a
+    /// document on the RNode validating documents and records its cost.
+    pub(super) async fn estimate_cost_command(&self, arguments: Vec<Value>) -> jsonrpc::Result<Option<Value>> {
+        let args: EstimateCostArgs = arguments
+            .into_iter()
+            .next()
+            .and_then(|arg| serde_json::from_value(arg).ok())
+            .ok_or_else(|| jsonrpc::Error::invalid_params("Expected { uri } argument"))?;
+
+        if !self.rnode_validation.is_enabled() {
+            let reason = "Cost estimation needs RNode validation to be on".to_string();
+            self.client.show_message(MessageType::WARNING, &reason).await;
+            return Err(command_error(jsonrpc::ErrorCode::InvalidRequest, reason));
+        }
+        let address = self.rnode_validation.status().address;
+        let endpoint = match self.ensure_deploy_permitted(&args.uri, Some(&address)).await {
+            Ok(endpoint) => endpoint,
+            Err(denied) => {
+                warn!("{}", denied);
+                self.client.show_message(MessageType::WARNING, &denied).await;
+                return Err(command_error(jsonrpc::ErrorCode::InvalidRequest, denied.to_string()));
+            }
+        };
+
+        let doc = self
+            .workspace
+            .documents
+            .get(&args.uri)
+            .map(|entry| entry.value().clone())
+            .ok_or_else(|| jsonrpc::Error::invalid_params(format!("Document not found: {}", args.uri)))?;
+        let processes = top_level_processes(&doc.ir, &doc.positions, &doc.text, &doc.line_index);
+        info!("Estimating the cost of {} process(es) of {} on RNode at {}", processes.len(), args.uri, endpoint);
+
+        let mut client = ReplClient::connect(format!("http://{}", endpoint)).await.map_err(|e| {
+            command_error(jsonrpc::ErrorCode::InternalError, format!("Failed to connect to RNode at {}: {}", endpoint, e))
+        })?;
+        let mut costs = Vec::with_capacity(processes.len());
+        for process in processes {
+            let estimate = client
+                .eval(EvalRequest { program: process.program, print_unmatched_sends_only: false })
+                .await
+                .map_err(|e| format!("Evaluation on {} failed: {}", endpoint, e))
+                .and_then(|response| parse_deployment_cost(&response.into_inner().output));
+            debug!("Cost of the process at {:?}: {:?}", process.range.start, estimate);
+            let (cost, error) = match estimate {
+                Ok(cost) => (Some(cost), None),
+                Err(reason) => (None, Some(reason)),
+            };
+            costs.push(ProcessCost { range: process.range, cost, error });
+        }
+
+        self.cost_estimates
+            .insert(args.uri.clone(), CostEstimates { content_hash: doc.content_hash, costs: costs.clone() });
+        let refresh = RefreshSet { inlay_hints: true, ..Default::default() }.supported_by(&self.capabilities.read());
+        self.send_refresh_requests(refresh).await;
+        self.revalidate_open_documents().await;
+
+        Ok(Some(serde_json::json!({ "endpoint": endpoint, "costs": costs })))
+    }
+
+    /// The estimated costs of `uri`, if they were computed for its current text.
+    fn current_cost_estimates(&self, uri: &Url) -> Option<Vec<ProcessCost>> {
+        let content_hash = self.workspace.documents.get(uri)?.content_hash;
+        let estimates = self.cost_estimates.get(uri)?;
+        (estimates.content_hash == content_hash).then(|| estimates.costs.clone())
+    }
+
+    /// Cost inlay hints of `uri` within `range`.
+    pub(super) fn cost_inlay_hints(&self, uri: &Url, range: Range) -> Vec<InlayHint> {
+        if !self.cost_estimation.read().inlay_hints {
+            return Vec::new();
+        }
+        self.current_cost_estimates(uri)
+            .unwrap_or_default()
+            .iter()
+            .filter(|cost| range.start <= cost.range.start && cost.range.start <= range.end)
+            .filter_map(ProcessCost::to_inlay_hint)
+            .collect()
+    }
+
+    /// Warnings for the processes of `uri` costing more than `warnAbove`.
+    pub(super) fn cost_diagnostics(&self, uri: &Url) -> Vec<Diagnostic> {
+        let warn_above = self.cost_estimation.read().warn_above;
+        self.current_cost_estimates(uri)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|cost| cost.to_diagnostic(warn_above))
+            .collect()
+    }
+}
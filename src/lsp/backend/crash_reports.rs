@@ -0,0 +1,54 @@
+//! Crash reports for the LSP backend
+//!
+//! Writes a report (see `crate::lsp::crash_reports`) when the analysis of a
+//! document panics, and offers to open it.
+
+use tower_lsp::lsp_types::{MessageActionItem, MessageType, ShowDocumentParams, Url};
+use tracing::{debug, error, warn};
+
+use crate::lsp::analysis_pool::JobPanic;
+use crate::lsp::crash_reports::OPEN_REPORT_ACTION;
+
+use super::state::RholangBackend;
+
+impl RholangBackend {
+    /// Reports `panic` in the analysis of `uri`, whose text is `text`.
+    pub(super) fn report_crash(&self, uri: &Url, text: &str, panic: &JobPanic) {
+        error!("Analysis of {} panicked at {}: {}", uri, panic.node_path.join(" > "), panic.message);
+        let Some(report) = self.crash_reporter.report(uri, text, panic) else {
+            debug!("Crash in {} was reported already", uri);
+            return;
+        };
+        let path = match self.crash_reporter.write(&report) {
+            Ok(path) => path,
+            Err(e) => {
+                warn!("Failed to write crash report: {}", e);
+                return;
+            }
+        };
+        let Ok(report_uri) = Url::from_file_path(&path) else {
+            return;
+        };
+
+        let message = format!(
+            "The Rholang language server failed to analyze {}. A crash report without your code was written to {}.",
+            uri.path_segments().and_then(|mut segments| segments.next_back()).unwrap_or(uri.as_str()),
+            path.display()
+        );
+        let client = self.client.clone();
+        tokio::spawn(async move {
+            let actions = vec![MessageActionItem { title: OPEN_REPORT_ACTION.to_string(), properties: Default::default() }];
+            match client.show_message_request(MessageType::ERROR, message, Some(actions)).await {
+                Ok(Some(item)) if item.title == OPEN_REPORT_ACTION => {
+                    let params =
+                        ShowDocumentParams { uri: report_uri, external: Some(false), take_focus: Some(true), selection: None };
+                    if let Err(e) = client.show_document(params).await {
+                        warn!("Failed to open crash report: {}", e);
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Crash report prompt failed: {}", e),
+            }
+        });
+    }
+}
@@ -0,0 +1,183 @@
+//! Custom (non-standard) LSP requests served by the backend
+//!
+//! These are registered on the `LspService` builder via
+//! [`RholangBackend::register_custom_methods`] and are used by the VS Code
+//! extension for views that have no LSP equivalent.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use tower_lsp::jsonrpc;
+use tower_lsp::LspServiceBuilder;
+use tracing::debug;
+
+use crate::deterministic::{self, AdvanceClockParams, ClockReading, ADVANCE_CLOCK_METHOD};
+use crate::lsp::contracts_outline::{
+    collect_contract_outlines, count_calls, ContractsParams, ContractsResult, CONTRACTS_METHOD,
+};
+use crate::lsp::document_symbol_query::{
+    query_symbols, DocumentSymbolQueryParams, DocumentSymbolQueryResult, DEFAULT_QUERY_LIMIT,
+    DOCUMENT_SYMBOL_QUERY_METHOD,
+};
+use crate::lsp::health::{HealthReport, HEALTH_METHOD, SYNTAX_ONLY_VALIDATOR};
+use crate::lsp::matching_construct::{
+    find_matching_construct, MatchingConstruct, MatchingConstructParams, MATCHING_CONSTRUCT_METHOD,
+};
+use crate::lsp::structural_query::{
+    find_matches, QueryParams, QueryResult, StructuralQuery, MAX_QUERY_MATCHES, QUERY_METHOD,
+};
+
+use super::state::RholangBackend;
+
+impl RholangBackend {
+    /// Registers all custom requests on the service builder.
+    pub fn register_custom_methods(
+        builder: LspServiceBuilder<RholangBackend>,
+    ) -> LspServiceBuilder<RholangBackend> {
+        builder
+            .custom_method(CONTRACTS_METHOD, RholangBackend::contracts)
+            .custom_method(MATCHING_CONSTRUCT_METHOD, RholangBackend::matching_construct)
+            .custom_method(HEALTH_METHOD, RholangBackend::health)
+            .custom_method(QUERY_METHOD, RholangBackend::query)
+            .custom_method(DOCUMENT_SYMBOL_QUERY_METHOD, RholangBackend::document_symbol_query)
+            .custom_method(ADVANCE_CLOCK_METHOD, RholangBackend::advance_clock)
+    }
+
+    /// Handles `rholang/contracts`: lists contracts in one document, or in the
+    /// whole workspace when no URI is given, with workspace-wide call counts.
+    pub async fn contracts(&self, params: ContractsParams) -> jsonrpc::Result<ContractsResult> {
+        debug!("rholang/contracts: uri={:?}", params.uri);
+
+        let mut call_counts = HashMap::new();
+        for entry in self.workspace.documents.iter() {
+            count_calls(&entry.value().ir, &mut call_counts);
+        }
+
+        let mut contracts = Vec::new();
+        match &params.uri {
+            Some(uri) => {
+                let doc = self.workspace.documents.get(uri).ok_or_else(|| {
+                    jsonrpc::Error::invalid_params(format!("Document not found: {}", uri))
+                })?;
+                contracts.extend(collect_contract_outlines(uri, &doc.ir, &doc.positions, &doc.text, &doc.line_index));
+            }
+            None => {
+                for entry in self.workspace.documents.iter() {
+                    let doc = entry.value();
+                    contracts.extend(collect_contract_outlines(entry.key(), &doc.ir, &doc.positions, &doc.text, &doc.line_index));
+                }
+            }
+        }
+
+        for contract in &mut contracts {
+            contract.calls = call_counts.get(&contract.name).copied().unwrap_or(0);
+        }
+        contracts.sort_by(|a, b| {
+            a.uri
+                .as_str()
+                .cmp(b.uri.as_str())
+                .then_with(|| a.range.cmp(&b.range))
+        });
+
+        Ok(ContractsResult { contracts })
+    }
+
+    /// Handles `rholang/matchingConstruct`: returns the partner tokens of the
+    /// delimiter or keyword at the given position, or `null` if there is none.
+    pub async fn matching_construct(
+        &self,
+        params: MatchingConstructParams,
+    ) -> jsonrpc::Result<Option<MatchingConstruct>> {
+        let uri = &params.text_document.uri;
+        debug!("rholang/matchingConstruct: {}:{:?}", uri, params.position);
+
+        let doc = self.workspace.documents.get(uri).ok_or_else(|| {
+            jsonrpc::Error::invalid_params(format!("Document not found: {}", uri))
+        })?;
+        Ok(find_matching_construct(&doc.tree, &doc.text, &doc.line_index, params.position))
+    }
+
+    /// Handles `rholang/health`: the validator in use and the number of open
+    /// and indexed documents.
+    pub async fn health(&self) -> jsonrpc::Result<HealthReport> {
+        let validator = match self.rnode_validation.provider() {
+            Some(rnode) => rnode.backend_name(),
+            None if self.diagnostic_provider.verifies_with_rnode() => SYNTAX_ONLY_VALIDATOR,
+            None => self.diagnostic_provider.backend_name(),
+        };
+        Ok(HealthReport {
+            validator: validator.to_string(),
+            rnode_validation: self.rnode_validation.status(),
+            open_documents: self.documents_by_uri.len(),
+            indexed_documents: self.workspace.documents.len(),
+        })
+    }
+
+    /// Handles `rholang/query`: finds the sends, contracts or receives matching
+    /// a structural pattern in one document, or in the whole workspace.
+    pub async fn query(&self, params: QueryParams) -> jsonrpc::Result<QueryResult> {
+        debug!("rholang/query: {:?} uri={:?}", params.query, params.uri);
+        let query = StructuralQuery::parse(&params.query).map_err(jsonrpc::Error::invalid_params)?;
+
+        let mut matches = Vec::new();
+        match &params.uri {
+            Some(uri) => {
+                let doc = self.workspace.documents.get(uri).ok_or_else(|| {
+                    jsonrpc::Error::invalid_params(format!("Document not found: {}", uri))
+                })?;
+                matches.extend(find_matches(&query, uri, &doc.ir, &doc.positions, &doc.text, &doc.line_index));
+            }
+            None => {
+                for entry in self.workspace.documents.iter() {
+                    let doc = entry.value();
+                    matches.extend(find_matches(&query, entry.key(), &doc.ir, &doc.positions, &doc.text, &doc.line_index));
+                }
+            }
+        }
+
+        matches.sort_by(|a, b| {
+            a.uri
+                .as_str()
+                .cmp(b.uri.as_str())
+                .then_with(|| a.range.start.cmp(&b.range.start))
+        });
+        let truncated = matches.len() > MAX_QUERY_MATCHES;
+        matches.truncate(MAX_QUERY_MATCHES);
+        Ok(QueryResult { matches, truncated })
+    }
+
+    /// Handles `rholang/documentSymbolQuery`: the symbols of one document
+    /// fuzzily matching a query, best first.
+    pub async fn document_symbol_query(
+        &self,
+        params: DocumentSymbolQueryParams,
+    ) -> jsonrpc::Result<DocumentSymbolQueryResult> {
+        let uri = &params.text_document.uri;
+        debug!("rholang/documentSymbolQuery: {:?} uri={}", params.query, uri);
+
+        let doc = self.workspace.documents.get(uri).ok_or_else(|| {
+            jsonrpc::Error::invalid_params(format!("Document not found: {}", uri))
+        })?;
+        let limit = params.limit.unwrap_or(DEFAULT_QUERY_LIMIT);
+        Ok(query_symbols(doc.symbol_index.symbols(), &params.query, limit))
+    }
+
+    /// Handles `rholang/advanceClock`: moves the virtual clock of
+    /// deterministic mode forward by the given time, or to its next window,
+    /// letting the tasks woken at each deadline run before moving on.
+    pub async fn advance_clock(&self, params: AdvanceClockParams) -> jsonrpc::Result<ClockReading> {
+        let clock = deterministic::clock().ok_or_else(jsonrpc::Error::method_not_found)?;
+        let target = match params.milliseconds {
+            Some(milliseconds) => clock.elapsed() + Duration::from_millis(milliseconds),
+            None => clock.next_deadline().unwrap_or_else(|| clock.elapsed()),
+        };
+        debug!("rholang/advanceClock: {:?} -> {:?}", clock.elapsed(), target);
+
+        while let Some(deadline) = clock.next_deadline().filter(|deadline| *deadline <= target) {
+            clock.advance_to(deadline);
+            tokio::task::yield_now().await;
+        }
+        clock.advance_to(target);
+        Ok(clock.reading())
+    }
+}
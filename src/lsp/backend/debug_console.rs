@@ -0,0 +1,63 @@
+//! Statistics of a connection for the debug console
+//!
+//! Collects what `crate::debug_console` shows about this backend: its open
+//! documents with their IR sizes and parse counts, and the depth of its
+//! background queues.
+
+use tokio::sync::mpsc::Sender;
+
+use crate::debug_console::{ConnectionStats, DocumentStats, QueueDepths};
+use crate::lsp::shared_documents::ConnectionId;
+
+use super::state::RholangBackend;
+
+/// Items waiting in the channel behind `sender`
+fn queued<T>(sender: &Sender<T>) -> usize {
+    sender.max_capacity() - sender.capacity()
+}
+
+impl RholangBackend {
+    /// This connection's id, shared with `crate::lsp::shared_documents`.
+    pub fn connection_id(&self) -> ConnectionId {
+        self.connection_id
+    }
+
+    /// Open documents and queue depths of this connection.
+    pub async fn debug_stats(&self) -> ConnectionStats {
+        let mut open: Vec<_> = self
+            .documents_by_uri
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect();
+        open.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+
+        let mut documents = Vec::with_capacity(open.len());
+        for (uri, document) in open {
+            let state = document.state.read().await;
+            let cached = self.workspace.documents.get(&uri).map(|entry| entry.value().clone());
+            documents.push(DocumentStats {
+                uri: uri.to_string(),
+                language: cached.as_ref().map_or_else(|| "-".to_string(), |doc| format!("{:?}", doc.language)),
+                version: state.version,
+                bytes: state.text.len_bytes(),
+                lines: state.text.len_lines(),
+                ir_nodes: cached.as_ref().map_or(0, |doc| doc.positions.len()),
+                incremental_parses: state.parse_counts.incremental,
+                full_parses: state.parse_counts.full,
+            });
+        }
+
+        ConnectionStats {
+            connection: self.connection_id,
+            documents,
+            indexed_documents: self.workspace.documents.len(),
+            queues: QueueDepths {
+                document_changes: queued(&self.doc_change_tx),
+                indexing: queued(&self.indexing_tx),
+                diagnostics: queued(&self.diagnostics_tx),
+                analysis_running: self.analysis_pool.running(),
+                analysis_threads: self.analysis_pool.threads(),
+            },
+        }
+    }
+}
@@ -0,0 +1,99 @@
+//! Signed deploys for the LSP backend
+//!
+//! Signs a document with the configured deploy key (see
+//! `crate::lsp::deploy_signing`) and sends it to RNode's DeployService,
+//! reporting each step to the client with `$/progress`.
+
+use std::sync::atomic::Ordering;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tower_lsp::lsp_types::notification::Progress;
+use tower_lsp::lsp_types::{
+    NumberOrString, ProgressParams, ProgressParamsValue, Url, WorkDoneProgress, WorkDoneProgressBegin,
+    WorkDoneProgressEnd, WorkDoneProgressReport,
+};
+use tracing::info;
+
+use crate::lsp::deploy_signing::sign_deploy;
+use crate::rnode_apis::deploy::deploy_response::Message as DeployOutcome;
+use crate::rnode_apis::deploy::deploy_service_client::DeployServiceClient;
+
+use super::state::RholangBackend;
+
+/// Outcome of a deploy RNode accepted
+#[derive(Debug, Clone)]
+pub(super) struct DeployReceipt {
+    /// Hex signature of the deploy, which RNode uses as its id
+    pub deploy_id: String,
+    /// RNode's answer, e.g. `Success! DeployId is: …`
+    pub result: String,
+}
+
+impl RholangBackend {
+    /// Signs `term`, the text of `uri`, and deploys it to RNode at `endpoint`.
+    ///
+    /// The endpoint must already have passed the allowlist and trust checks.
+    pub(super) async fn deploy_signed(&self, uri: &Url, endpoint: &str, term: String) -> Result<DeployReceipt, String> {
+        let settings = self.deploy_settings.read().clone();
+        let key = settings.key()?;
+        let token = NumberOrString::String(format!("rholang-deploy-{}", self.next_rnode_run.fetch_add(1, Ordering::Relaxed)));
+
+        self.deploy_progress(&token, WorkDoneProgress::Begin(WorkDoneProgressBegin {
+            title: "Deploying to RNode".to_string(),
+            message: Some(format!("Signing {}", uri)),
+            percentage: Some(0),
+            cancellable: Some(false),
+        }))
+        .await;
+
+        let result = async {
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_millis() as i64)
+                .unwrap_or_default();
+            let deploy = sign_deploy(term, timestamp, &settings, &key)?;
+            let deploy_id = hex::encode(&deploy.sig);
+
+            self.deploy_progress(&token, WorkDoneProgress::Report(WorkDoneProgressReport {
+                message: Some(format!("Sending to {}", endpoint)),
+                percentage: Some(50),
+                cancellable: Some(false),
+            }))
+            .await;
+            let mut client = DeployServiceClient::connect(format!("http://{}", endpoint))
+                .await
+                .map_err(|e| format!("Failed to connect to RNode at {}: {}", endpoint, e))?;
+            let response = client
+                .do_deploy(deploy)
+                .await
+                .map_err(|e| format!("Deploy to {} failed: {}", endpoint, e))?
+                .into_inner();
+            match response.message {
+                Some(DeployOutcome::Result(result)) => Ok(DeployReceipt { deploy_id, result }),
+                Some(DeployOutcome::Error(error)) => Err(format!("RNode rejected the deploy: {}", error.messages.join("; "))),
+                None => Err("RNode sent an empty deploy response".to_string()),
+            }
+        }
+        .await;
+
+        let message = match &result {
+            Ok(receipt) => {
+                info!("Deployed {} to {} as {}", uri, endpoint, receipt.deploy_id);
+                receipt.result.clone()
+            }
+            Err(reason) => reason.clone(),
+        };
+        self.deploy_progress(&token, WorkDoneProgress::End(WorkDoneProgressEnd { message: Some(message) }))
+            .await;
+        result
+    }
+
+    async fn deploy_progress(&self, token: &NumberOrString, progress: WorkDoneProgress) {
+        self.client
+            .send_notification::<Progress>(ProgressParams {
+                token: token.clone(),
+                value: ProgressParamsValue::WorkDone(progress),
+            })
+            .await;
+    }
+}
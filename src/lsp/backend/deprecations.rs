@@ -0,0 +1,69 @@
+//! Deprecation hints for the LSP backend
+//!
+//! Resolves references to `@deprecated` contracts and names (see
+//! `crate::lsp::deprecation`) for diagnostics and semantic tokens.
+
+use tower_lsp::lsp_types::{Diagnostic, Range, Url};
+use tracing::debug;
+
+use crate::lsp::deprecation::{DeprecatedDeclarations, DeprecatedUsage};
+use crate::lsp::semantic_modifiers::{MODIFIER_DEPRECATED, TOKEN_FUNCTION, TOKEN_VARIABLE};
+
+use super::state::RholangBackend;
+use super::utils::SemanticTokensBuilder;
+
+impl RholangBackend {
+    /// References to deprecated declarations in the indexed document `uri`.
+    ///
+    /// Deprecated contracts are collected from the whole workspace so that
+    /// calls to a contract deprecated in another file are reported too.
+    pub(super) fn deprecated_usages(&self, uri: &Url) -> Vec<DeprecatedUsage> {
+        let Some(doc) = self.workspace.documents.get(uri).map(|entry| entry.value().clone()) else {
+            return Vec::new();
+        };
+
+        let mut declarations = DeprecatedDeclarations::default();
+        for entry in self.workspace.documents.iter() {
+            declarations.extend(&entry.value().deprecations);
+        }
+        if declarations.is_empty() {
+            return Vec::new();
+        }
+
+        let usages = declarations.find_usages(uri, &doc.ir, &doc.positions, &doc.text, &doc.line_index);
+        debug!("Found {} deprecated references in {}", usages.len(), uri);
+        usages
+    }
+
+    /// Hint diagnostics tagged `DEPRECATED` for references in `uri`.
+    pub(super) fn deprecation_diagnostics(&self, uri: &Url) -> Vec<Diagnostic> {
+        self.deprecated_usages(uri)
+            .iter()
+            .map(DeprecatedUsage::to_diagnostic)
+            .collect()
+    }
+
+    /// Adds a `deprecated` semantic token for each deprecated reference in `uri`.
+    ///
+    /// Returns the number of tokens added.
+    pub(super) fn add_deprecation_semantic_tokens(&self, builder: &mut SemanticTokensBuilder, uri: &Url) -> usize {
+        let mut added = 0;
+        for usage in self.deprecated_usages(uri) {
+            // Names never span lines; skip anything that does
+            let Range { start, end } = usage.range;
+            if start.line != end.line {
+                continue;
+            }
+            let token_type = if usage.is_contract { TOKEN_FUNCTION } else { TOKEN_VARIABLE };
+            builder.push_with_modifiers(
+                start.line,
+                start.character,
+                end.character - start.character,
+                token_type,
+                MODIFIER_DEPRECATED,
+            );
+            added += 1;
+        }
+        added
+    }
+}
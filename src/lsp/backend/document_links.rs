@@ -0,0 +1,72 @@
+//! Document links for the LSP backend
+//!
+//! Turns the linked literals of `crate::lsp::document_links` into links:
+//! registry URIs open the configured explorer, or else the contract bound to
+//! the URI in the workspace index; relative `.rho` paths open the file.
+
+use tower_lsp::lsp_types::{DocumentLink, Url};
+use tracing::debug;
+
+use crate::ir::semantic_node::Position;
+use crate::ir::symbol_resolution::{RegistryUriResolver, ResolutionContext, SymbolResolver};
+use crate::lsp::document_links::{literal_links, resolve_relative_path, LinkTarget};
+use crate::lsp::models::DocumentLanguage;
+
+use super::state::RholangBackend;
+
+impl RholangBackend {
+    /// Links of `uri`, or `None` for unknown and MeTTa documents.
+    pub(super) fn collect_document_links(&self, uri: &Url) -> Option<Vec<DocumentLink>> {
+        let doc = self.workspace.documents.get(uri).map(|entry| entry.value().clone())?;
+        if doc.language == DocumentLanguage::Metta {
+            return None;
+        }
+        let config = self.document_links.read().clone();
+        if !config.enabled {
+            return Some(Vec::new());
+        }
+        let document_dir = uri
+            .to_file_path()
+            .ok()
+            .and_then(|path| path.parent().map(|dir| dir.to_path_buf()));
+
+        let links: Vec<DocumentLink> = literal_links(&doc.ir, &doc.positions, &doc.text, &doc.line_index)
+            .into_iter()
+            .filter_map(|link| {
+                let (target, tooltip) = match &link.target {
+                    LinkTarget::Registry(registry_uri) => match config.explorer_url(registry_uri) {
+                        Some(url) => (Url::parse(&url).ok()?, format!("Open {} in the registry explorer", registry_uri)),
+                        None => (self.registry_contract_url(uri, registry_uri)?, format!("Go to the contract bound to {}", registry_uri)),
+                    },
+                    LinkTarget::File(relative) => {
+                        let path = resolve_relative_path(document_dir.as_deref()?, relative)?;
+                        (Url::from_file_path(&path).ok()?, format!("Open {}", relative))
+                    }
+                };
+                Some(DocumentLink { range: link.range, target: Some(target), tooltip: Some(tooltip), data: None })
+            })
+            .collect();
+        debug!("{} document link(s) in {}", links.len(), uri);
+        Some(links)
+    }
+
+    /// URL of the first contract bound to `registry_uri` in the workspace,
+    /// with the line of the contract as fragment (`file:///a.rho#L3`).
+    fn registry_contract_url(&self, uri: &Url, registry_uri: &str) -> Option<Url> {
+        let context = ResolutionContext {
+            uri: uri.clone(),
+            scope_id: None,
+            ir_node: None,
+            language: "rholang".to_string(),
+            parent_uri: None,
+        };
+        let query = Position { row: 0, column: 0, byte: 0 };
+        let symbol = RegistryUriResolver::new(self.workspace.clone())
+            .resolve_symbol(registry_uri, &query, &context)
+            .into_iter()
+            .next()?;
+        let mut target = symbol.uri;
+        target.set_fragment(Some(&format!("L{}", symbol.range.start.line + 1)));
+        Some(target)
+    }
+}
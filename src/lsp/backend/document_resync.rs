@@ -0,0 +1,112 @@
+//! Recovery from documents out of sync with the client
+//!
+//! Checks each `didChange` with `crate::lsp::document_resync::find_desync`
+//! before it is applied. A change that does not fit the text is dropped, the
+//! document's diagnostics are cleared, and its text is recovered from the
+//! client with `rholang/resyncDocument`, or, for clients without it, from the
+//! next change replacing the whole document.
+
+use std::sync::atomic::Ordering;
+
+use tower_lsp::LanguageServer;
+use tower_lsp::lsp_types::{
+    DidChangeTextDocumentParams, MessageType, TextDocumentContentChangeEvent, Url, VersionedTextDocumentIdentifier,
+};
+use tracing::{info, warn};
+
+use crate::lsp::document_resync::{DesyncReason, ResyncDocument, ResyncDocumentParams};
+use crate::lsp::models::LspDocument;
+use crate::metrics::metrics;
+
+use super::state::RholangBackend;
+
+/// Resync requests made in a row before giving up, when the client keeps
+/// returning text older than changes it has sent
+const MAX_RESYNC_ATTEMPTS: usize = 3;
+
+impl RholangBackend {
+    /// Whether `changes` for `version` may be applied to `document`.
+    ///
+    /// When they may not, the document is marked out of sync and its
+    /// recovery starts.
+    pub(super) async fn check_sync(
+        &self,
+        uri: &Url,
+        document: &LspDocument,
+        changes: &[TextDocumentContentChangeEvent],
+        version: i32,
+    ) -> bool {
+        let replaces_text = changes.iter().any(|change| change.range.is_none());
+        if !replaces_text {
+            if let Some(mut latest) = self.desynced_documents.get_mut(uri) {
+                *latest = (*latest).max(version);
+                warn!("Ignoring change {} of {}: {}", version, uri, DesyncReason::AwaitingResync);
+                return false;
+            }
+        }
+
+        let Some(reason) = document.find_desync(changes, version).await else {
+            if self.desynced_documents.remove(uri).is_some() {
+                info!("{} is back in sync at version {}", uri, version);
+            }
+            return true;
+        };
+
+        metrics().record_document_desync();
+        warn!("{} is out of sync with the client ({}); dropping change {}", uri, reason, version);
+        self.desynced_documents.insert(uri.clone(), version);
+        // Diagnostics of a diverged text point at the wrong code
+        self.queue_diagnostics(uri.clone(), Vec::new(), None).await;
+
+        if self.document_resync.load(Ordering::Relaxed) {
+            let backend = self.clone();
+            let uri = uri.clone();
+            tokio::spawn(async move { backend.resync_document(uri, version, reason).await });
+        } else {
+            self.client
+                .show_message(
+                    MessageType::WARNING,
+                    format!("{} is out of sync with the editor ({}); reopen it to restore diagnostics", uri, reason),
+                )
+                .await;
+        }
+        false
+    }
+
+    /// Replaces the text of `uri` with the client's, asking again while the
+    /// returned text predates changes already received.
+    async fn resync_document(&self, uri: Url, version: i32, reason: DesyncReason) {
+        let mut params = ResyncDocumentParams { uri: uri.clone(), version, reason };
+        for _ in 0..MAX_RESYNC_ATTEMPTS {
+            let result = match self.client.send_request::<ResyncDocument>(params.clone()).await {
+                Ok(result) => result,
+                Err(e) => {
+                    warn!("rholang/resyncDocument failed for {}: {}", uri, e);
+                    return;
+                }
+            };
+            let Some(latest) = self.desynced_documents.get(&uri).map(|entry| *entry.value()) else {
+                // Reopened or replaced meanwhile
+                return;
+            };
+            if result.version < latest {
+                params = ResyncDocumentParams {
+                    uri: uri.clone(),
+                    version: latest,
+                    reason: DesyncReason::VersionGap { expected: latest, received: result.version },
+                };
+                continue;
+            }
+
+            info!("Resynchronizing {} at version {}", uri, result.version);
+            let change = TextDocumentContentChangeEvent { range: None, range_length: None, text: result.text };
+            self.did_change(DidChangeTextDocumentParams {
+                text_document: VersionedTextDocumentIdentifier::new(uri.clone(), result.version),
+                content_changes: vec![change],
+            })
+            .await;
+            return;
+        }
+        warn!("Gave up resynchronizing {} after {} attempts", uri, MAX_RESYNC_ATTEMPTS);
+    }
+}
@@ -0,0 +1,39 @@
+//! Document status notifications for the LSP backend
+//!
+//! Sends `rholang/documentStatus` (see `crate::lsp::document_status`) after
+//! each analysis pass, when the client asked for it.
+
+use std::sync::atomic::Ordering;
+
+use tower_lsp::lsp_types::{Diagnostic, Url};
+use tracing::debug;
+
+use crate::lsp::document_status::{DocumentStatus, DocumentStatusParams};
+use crate::lsp::models::DocumentLanguage;
+
+use super::state::RholangBackend;
+
+impl RholangBackend {
+    /// Reports the health of `uri` from the diagnostics of its latest analysis pass.
+    pub(super) async fn announce_document_status(&self, uri: &Url, version: Option<i32>, diagnostics: &[Diagnostic]) {
+        if !self.document_status.load(Ordering::Relaxed) {
+            return;
+        }
+        let rnode_validated = DocumentLanguage::from_uri(uri) == DocumentLanguage::Rholang
+            && self.rnode_validation.provider().is_some_and(|rnode| rnode.verifies_with_rnode());
+        let params = DocumentStatusParams::from_diagnostics(uri.clone(), version, diagnostics, rnode_validated);
+        debug!("Document status of {}: {:?}", uri, params.status);
+        self.client.send_notification::<DocumentStatus>(params).await;
+    }
+
+    /// Reports `uri` as degraded after an analysis pass that did not finish.
+    pub(super) async fn announce_degraded_document(&self, uri: &Url, version: Option<i32>) {
+        if !self.document_status.load(Ordering::Relaxed) {
+            return;
+        }
+        debug!("Document status of {}: degraded", uri);
+        self.client
+            .send_notification::<DocumentStatus>(DocumentStatusParams::degraded(uri.clone(), version))
+            .await;
+    }
+}
@@ -0,0 +1,24 @@
+//! Duplicate send warnings for the LSP backend
+//!
+//! Reports identical sends in the same parallel composition (see
+//! `crate::lsp::duplicate_sends`).
+
+use tower_lsp::lsp_types::{Diagnostic, Url};
+use tracing::debug;
+
+use crate::lsp::duplicate_sends::find_duplicate_sends;
+
+use super::state::RholangBackend;
+
+impl RholangBackend {
+    /// Duplicate send warnings for the indexed document `uri`.
+    pub(super) fn duplicate_send_diagnostics(&self, uri: &Url) -> Vec<Diagnostic> {
+        let Some(doc) = self.workspace.documents.get(uri).map(|entry| entry.value().clone()) else {
+            return Vec::new();
+        };
+
+        let duplicates = find_duplicate_sends(&doc.ir, &doc.positions, &doc.text, &doc.line_index);
+        debug!("Found {} duplicate sends in {}", duplicates.len(), uri);
+        duplicates.iter().map(|duplicate| duplicate.to_diagnostic(uri)).collect()
+    }
+}
@@ -0,0 +1,82 @@
+//! Document formatting for the LSP backend
+//!
+//! Answers `textDocument/formatting` with the IR formatter and the layout
+//! settings of `.rholangrc.toml` (see `crate::lsp::formatting`), and range
+//! and on-type formatting by re-indenting lines (see `crate::lsp::indentation`).
+
+use std::path::Path;
+
+use tower_lsp::lsp_types::{FormattingOptions, Position as LspPosition, Range, TextEdit, Url};
+use tracing::{debug, info};
+
+use crate::lsp::formatting::{format_document, whole_document_edit, FormatConfig};
+use crate::lsp::indentation::Indentation;
+use crate::lsp::models::DocumentLanguage;
+
+use super::state::RholangBackend;
+
+impl RholangBackend {
+    /// Loads the formatting settings from `.rholangrc.toml` in the workspace root.
+    pub(super) fn load_format_config(&self, root: &Path) {
+        let config = FormatConfig::load(root);
+        if config != FormatConfig::default() {
+            info!("Formatting settings: {:?}", config);
+        }
+        *self.format_config.write() = config;
+    }
+
+    /// The edits formatting the open document `uri` with the editor's `tab_size`.
+    ///
+    /// Documents with syntax errors and MeTTa documents are left unchanged.
+    pub(super) fn format_document_edits(&self, uri: &Url, tab_size: u32) -> Option<Vec<TextEdit>> {
+        let doc = self.workspace.documents.get(uri).map(|entry| entry.value().clone())?;
+        if doc.language == DocumentLanguage::Metta {
+            return None;
+        }
+        let options = self.effective_format_config().options(tab_size);
+        let text = doc.text.to_string();
+        let Some(formatted) = format_document(&text, &options) else {
+            debug!("Not formatting {}", uri);
+            return None;
+        };
+        let encoding = self.capabilities.read().position_encoding;
+        Some(whole_document_edit(&text, formatted, encoding).into_iter().collect())
+    }
+
+    /// Edits re-indenting the lines of `range` in `uri`.
+    pub(super) fn range_indent_edits(&self, uri: &Url, range: Range, options: &FormattingOptions) -> Option<Vec<TextEdit>> {
+        let doc = self.workspace.documents.get(uri).map(|entry| entry.value().clone())?;
+        if doc.language == DocumentLanguage::Metta {
+            return None;
+        }
+        let text = doc.text.to_string();
+        let edits = Indentation::new(&doc.tree, &text).range_edits(range, &self.indent_unit(options));
+        debug!("Re-indenting {} line(s) of {}", edits.len(), uri);
+        Some(edits)
+    }
+
+    /// Edits re-indenting the line of `position` in `uri` after `ch` was typed.
+    pub(super) fn on_type_indent_edits(
+        &self,
+        uri: &Url,
+        position: LspPosition,
+        ch: &str,
+        options: &FormattingOptions,
+    ) -> Option<Vec<TextEdit>> {
+        let doc = self.workspace.documents.get(uri).map(|entry| entry.value().clone())?;
+        if doc.language == DocumentLanguage::Metta {
+            return None;
+        }
+        let text = doc.text.to_string();
+        Some(Indentation::new(&doc.tree, &text).on_type_edits(position, ch, &self.indent_unit(options)))
+    }
+
+    /// One level of indentation: a tab, or the configured number of spaces.
+    fn indent_unit(&self, options: &FormattingOptions) -> String {
+        if options.insert_spaces {
+            " ".repeat(self.effective_format_config().options(options.tab_size).indent_width)
+        } else {
+            "\t".to_string()
+        }
+    }
+}
@@ -10,6 +10,7 @@
 
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::sync::atomic::Ordering;
 
 use tower_lsp::{LanguageServer, jsonrpc};
 use tower_lsp::lsp_types::{
@@ -19,16 +20,27 @@ use tower_lsp::lsp_types::{
     DocumentHighlightKind, DocumentHighlightParams, GotoDefinitionParams,
     GotoDefinitionResponse, InitializedParams, InitializeParams,
     InitializeResult, Location, Position as LspPosition, Range, ReferenceParams,
-    RenameParams, ServerCapabilities, TextDocumentSyncCapability,
-    TextDocumentSyncKind, TextEdit, Url, WorkspaceEdit, DocumentSymbolParams,
+    RenameParams, ServerCapabilities, TextDocumentSyncCapability, TextDocumentSyncKind,
+    TextEdit, Url, WorkspaceEdit, DocumentSymbolParams,
     DocumentSymbolResponse, WorkspaceSymbolParams, WorkspaceSymbol,
     SymbolInformation, Hover, HoverContents, HoverParams, MarkupContent, MarkupKind,
-    SemanticTokensParams, SemanticTokensResult, SemanticTokensLegend,
+    SemanticTokensParams, SemanticTokensResult, SemanticTokensLegend, SemanticTokensDeltaParams,
+    SemanticTokens, SemanticTokensRangeParams, SemanticTokensRangeResult,
+    SemanticTokensFullDeltaResult,
     SemanticTokenType, SemanticTokensFullOptions, SemanticTokensServerCapabilities,
     SemanticTokensOptions, SignatureHelp, SignatureHelpParams, SignatureInformation,
     ParameterInformation, ParameterLabel, SignatureHelpOptions, CompletionParams,
     CompletionResponse, CompletionItem, CompletionItemKind, CompletionOptions,
-    CompletionOptionsCompletionItem,
+    CompletionOptionsCompletionItem, ExecuteCommandOptions, ExecuteCommandParams,
+    CodeActionKind, CodeActionOptions, CodeActionParams, CodeActionProviderCapability,
+    CodeActionResponse, FoldingRange, FoldingRangeParams, FoldingRangeProviderCapability,
+    DidChangeConfigurationParams, DidChangeWatchedFilesParams, DidChangeWorkspaceFoldersParams, OneOf, WorkspaceFoldersServerCapabilities,
+    WorkspaceServerCapabilities, CodeLens, CodeLensOptions, CodeLensParams, DocumentFormattingParams,
+    DocumentRangeFormattingParams, DocumentOnTypeFormattingParams, DocumentOnTypeFormattingOptions,
+    CallHierarchyServerCapability, CallHierarchyPrepareParams, CallHierarchyItem, CallHierarchyIncomingCallsParams,
+    CallHierarchyIncomingCall, CallHierarchyOutgoingCallsParams, CallHierarchyOutgoingCall,
+    InlayHint, InlayHintParams, SelectionRange, SelectionRangeParams, SelectionRangeProviderCapability,
+    DocumentLink, DocumentLinkOptions, DocumentLinkParams,
 };
 use tower_lsp::lsp_types::request::{GotoDeclarationParams, GotoDeclarationResponse};
 use tower_lsp::jsonrpc::Result as LspResult;
@@ -38,16 +50,46 @@ use tracing::{debug, error, info, trace, warn};
 use ropey::Rope;
 
 use notify::{RecommendedWatcher, RecursiveMode, Watcher};
-use walkdir::WalkDir;
 
 use crate::ir::rholang_node::{RholangNode, Position as IrPosition, find_node_at_position_with_path, find_node_at_position, compute_absolute_positions};
 use crate::ir::symbol_table::SymbolType;
 use crate::ir::transforms::document_symbol_visitor::collect_document_symbols;
 
+use super::commands;
+use super::move_contract;
 use super::state::RholangBackend;
-use super::state::{DocumentChangeEvent, IndexingTask};
-use super::utils::SemanticTokensBuilder;
-use crate::lsp::models::{LspDocument, LspDocumentHistory, LspDocumentState};
+use super::state::DocumentChangeEvent;
+use super::validation::Publication;
+use crate::i18n;
+use crate::lsp::analysis_budget::AnalysisBudgetConfig;
+use crate::lsp::analysis_level::{AnalysisThresholds, DegradedFeature};
+use crate::lsp::capabilities::NegotiatedCapabilities;
+use crate::lsp::completion_cost;
+use crate::lsp::completion_ranking::{self, rank_completions, unix_now};
+use crate::lsp::folding;
+use crate::lsp::selection_range::selection_range;
+use crate::lsp::indentation::MORE_TRIGGER_CHARACTERS;
+use crate::lsp::index_checkpoint::{self, IndexCheckpoints};
+use crate::lsp::deploy_signing::DeploySettings;
+use crate::lsp::document_links::DocumentLinksConfig;
+use crate::lsp::inlay_hints::InlayHintsConfig;
+use crate::lsp::cost_estimation::CostEstimationConfig;
+use crate::lsp::settings::Feature;
+use crate::lsp::invocation_templates::{at_send_position, invocation_items};
+use crate::lsp::library_paths::library_paths_from_init_options;
+use crate::lsp::line_index::LineIndex;
+use crate::lsp::models::{LspDocument, LspDocumentHistory, LspDocumentState, ParseCounts};
+use crate::lsp::text_normalization::{normalize, TextFormat};
+use crate::lsp::typing_diagnostics::TypingDiagnosticsConfig;
+use crate::tree_sitter::parse_code;
+use crate::lsp::virtual_document_events;
+use crate::lsp::workspace_folders::workspace_roots;
+use crate::lsp::document_status;
+use crate::lsp::document_resync;
+use crate::lsp::normalized_form;
+use crate::lsp::readiness;
+use crate::lsp::semantic_modifiers;
+use crate::lsp::workspace_trust::DeployTrustConfig;
 
 #[tower_lsp::async_trait]
 impl LanguageServer for RholangBackend {
@@ -77,87 +119,109 @@ impl LanguageServer for RholangBackend {
             }
         }
 
-        let mut root_guard = self.root_dir.write().await;
-        if let Some(root_uri) = params.root_uri {
-            if let Ok(root_path) = root_uri.to_file_path() {
-                *root_guard = Some(root_path.clone());
-                drop(root_guard);
-
-                // Phase 2 optimization: Count files first, then set indexing state before queuing
-                let file_paths: Vec<_> = WalkDir::new(&root_path)
-                    .into_iter()
-                    .filter_map(|e| e.ok())
-                    .filter(|e| e.path().extension().map_or(false, |ext| ext == "rho"))
-                    .collect();
-
-                let file_count = file_paths.len();
-
-                if file_count > 0 {
-                    // Set indexing state to InProgress before queuing tasks
-                    {
-                        let mut state = self.workspace.indexing_state.write().await;
-                        *state = crate::lsp::models::IndexingState::InProgress {
-                            total: file_count,
-                            completed: 0,
-                        };
-                    }
+        // Configure the deploy allowlist and key before any command can run
+        self.workspace_trust.set_config(DeployTrustConfig::from_env_or_init_options(
+            params.initialization_options.as_ref(),
+        ));
+        *self.deploy_settings.write() = DeploySettings::from_env_or_init_options(params.initialization_options.as_ref());
 
-                    // Send initial progress notification
-                    self.client.send_notification::<tower_lsp::lsp_types::notification::Progress>(
-                        tower_lsp::lsp_types::ProgressParams {
-                            token: tower_lsp::lsp_types::NumberOrString::String("workspace-indexing".to_string()),
-                            value: tower_lsp::lsp_types::ProgressParamsValue::WorkDone(
-                                tower_lsp::lsp_types::WorkDoneProgress::Begin(
-                                    tower_lsp::lsp_types::WorkDoneProgressBegin {
-                                        title: "Indexing workspace".to_string(),
-                                        message: Some(format!("Found {} files", file_count)),
-                                        percentage: Some(0),
-                                        cancellable: Some(false),
-                                    }
-                                )
-                            ),
-                        }
-                    ).await;
-
-                    // Queue all .rho files for progressive indexing
-                    let mut queued_count = 0;
-                    for entry in file_paths {
-                        let uri = Url::from_file_path(entry.path()).unwrap();
-                        let text = std::fs::read_to_string(entry.path()).unwrap_or_default();
-
-                        // All files get priority 1 during initialization
-                        // Files will be prioritized to 0 when opened via did_open
-                        let task = IndexingTask {
-                            uri: uri.clone(),
-                            text,
-                            priority: 1,
-                        };
+        if virtual_document_events::enabled_in(params.initialization_options.as_ref()) {
+            info!("Client subscribed to virtual document lifecycle notifications");
+            *self.virtual_doc_events.lock() = Some(Default::default());
+        }
 
-                        if let Err(e) = self.indexing_tx.send(task).await {
-                            error!("Failed to queue indexing task for {}: {}", uri, e);
-                        } else {
-                            queued_count += 1;
-                        }
-                    }
-                    info!("Queued {} .rho files for progressive indexing", queued_count);
-                } else {
-                    info!("No .rho files found in workspace");
+        if document_status::enabled_in(params.initialization_options.as_ref()) {
+            info!("Client subscribed to document status notifications");
+            self.document_status.store(true, Ordering::Relaxed);
+        }
+
+        if document_resync::enabled_in(params.initialization_options.as_ref()) {
+            info!("Client answers document resync requests");
+            self.document_resync.store(true, Ordering::Relaxed);
+        }
+
+        // Features consult these instead of assuming a modern client
+        let capabilities = NegotiatedCapabilities::from_client(&params.capabilities);
+        info!("Negotiated client capabilities: {:?}", capabilities);
+        *self.capabilities.write() = capabilities.clone();
+        // Positions of every document and artifact count columns in the negotiated encoding
+        self.disk_index.set_encoding(capabilities.position_encoding);
+        self.diagnostic_provider.set_position_encoding(capabilities.position_encoding);
+        if let Some(rnode) = self.rnode_validation.provider() {
+            rnode.set_position_encoding(capabilities.position_encoding);
+        }
+
+        if index_checkpoint::enabled_in(params.initialization_options.as_ref()) {
+            match index_checkpoint::default_checkpoint_dir() {
+                Some(dir) => {
+                    info!("Workspace indexing checkpoints in {}", dir.display());
+                    *self.index_checkpoints.lock() = Some(IndexCheckpoints::new(dir, capabilities.position_encoding));
                 }
+                None => warn!("No cache directory for indexing checkpoints"),
+            }
+        }
+
+        if readiness::enabled_in(params.initialization_options.as_ref()) {
+            info!("Client subscribed to readiness notifications");
+            self.readiness_events.store(true, Ordering::Relaxed);
+        }
+
+        if normalized_form::enabled_in(params.initialization_options.as_ref()) {
+            info!("Normalized forms requested in hover");
+            self.normalized_form.store(true, Ordering::Relaxed);
+        }
 
-                let tx = self.file_sender.lock().unwrap().clone();
-                let mut watcher = RecommendedWatcher::new(
-                    move |res| { let _ = tx.send(res); },
-                    notify::Config::default()
-                ).map_err(|_| jsonrpc::Error::internal_error())?;
-                watcher.watch(&root_path, RecursiveMode::Recursive).map_err(|_| jsonrpc::Error::internal_error())?;
-                *self.file_watcher.lock().unwrap() = Some(watcher);
+        self.adapter_registry.write().configure(params.initialization_options.as_ref());
+        self.analysis_levels
+            .set_thresholds(AnalysisThresholds::from_init_options(params.initialization_options.as_ref()));
+        self.typing_diagnostics
+            .set_config(TypingDiagnosticsConfig::from_init_options(params.initialization_options.as_ref()));
+        *self.analysis_budget.write() = AnalysisBudgetConfig::from_init_options(params.initialization_options.as_ref());
+        *self.inlay_hints.write() = InlayHintsConfig::from_init_options(params.initialization_options.as_ref());
+        *self.cost_estimation.write() = CostEstimationConfig::from_init_options(params.initialization_options.as_ref());
+        *self.document_links.write() = DocumentLinksConfig::from_init_options(params.initialization_options.as_ref());
+
+        // Diagnostics, hovers and code action titles use the client's locale
+        let locales_path = params
+            .initialization_options
+            .as_ref()
+            .and_then(|opts| opts.get(i18n::LOCALES_PATH_OPTION))
+            .and_then(|path| path.as_str())
+            .map(std::path::PathBuf::from);
+        i18n::set_locale(params.locale.as_deref(), locales_path.as_deref());
+
+        let track_completions = completion_ranking::enabled_in(params.initialization_options.as_ref());
+        *self.cost_aware_completion.write() = completion_cost::option_in(params.initialization_options.as_ref());
+
+        // Runtime settings may also come with the initialization options
+        if let Some(init_options) = params.initialization_options.as_ref() {
+            self.apply_settings(init_options).await;
+        }
 
-                // Spawn reactive file watcher event batcher
-                Self::spawn_reactive_file_watcher(self.clone(), self.file_events.clone());
+        // The first workspace folder holds the lint settings and completion usage
+        let roots = workspace_roots(params.workspace_folders.as_deref(), params.root_uri.as_ref());
+        if let Some(root_path) = roots.first() {
+            *self.root_dir.write().await = Some(root_path.clone());
+            self.load_style_lints(root_path);
+            self.load_format_config(root_path);
+            if track_completions {
+                self.completion_usage.open(root_path);
             } else {
-                warn!("Failed to convert root_uri to path: {}. Skipping workspace indexing and file watching.", root_uri);
+                info!("Completion usage tracking disabled by the client");
             }
         }
+        if !roots.is_empty() {
+            *self.workspace_roots.write() = roots.clone();
+            self.index_workspace_roots(&roots).await;
+            self.watch_workspace_roots(&roots);
+        }
+
+        // Shared libraries outside the workspace, indexed after the workspace files
+        let root_dir = self.root_dir.read().await.clone();
+        let library_paths = library_paths_from_init_options(params.initialization_options.as_ref(), root_dir.as_deref());
+        if !library_paths.is_empty() {
+            self.preindex_libraries(&library_paths, root_dir.as_deref()).await;
+        }
 
         // Define semantic token legend
         let token_types = vec![
@@ -173,14 +237,31 @@ impl LanguageServer for RholangBackend {
 
         Ok(InitializeResult {
             capabilities: ServerCapabilities {
+                position_encoding: Some(capabilities.position_encoding_kind()),
+                // Whole-document changes are accepted too, even mixed with ranged ones
                 text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::INCREMENTAL)),
                 rename_provider: Some(tower_lsp::lsp_types::OneOf::Left(true)),
                 declaration_provider: Some(DeclarationCapability::Simple(true)),
+                call_hierarchy_provider: Some(CallHierarchyServerCapability::Simple(true)),
+                inlay_hint_provider: Some(OneOf::Left(true)),
+                document_link_provider: Some(DocumentLinkOptions {
+                    resolve_provider: Some(false),
+                    work_done_progress_options: Default::default(),
+                }),
                 definition_provider: Some(tower_lsp::lsp_types::OneOf::Left(true)),
                 references_provider: Some(tower_lsp::lsp_types::OneOf::Left(true)),
                 document_symbol_provider: Some(tower_lsp::lsp_types::OneOf::Left(true)),
                 workspace_symbol_provider: Some(tower_lsp::lsp_types::OneOf::Left(true)),
                 document_highlight_provider: Some(tower_lsp::lsp_types::OneOf::Left(true)),
+                folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
+                selection_range_provider: Some(SelectionRangeProviderCapability::Simple(true)),
+                code_lens_provider: Some(CodeLensOptions { resolve_provider: Some(false) }),
+                document_formatting_provider: Some(OneOf::Left(true)),
+                document_range_formatting_provider: Some(OneOf::Left(true)),
+                document_on_type_formatting_provider: Some(DocumentOnTypeFormattingOptions {
+                    first_trigger_character: "}".to_string(),
+                    more_trigger_character: Some(MORE_TRIGGER_CHARACTERS.iter().map(|c| c.to_string()).collect()),
+                }),
                 hover_provider: Some(tower_lsp::lsp_types::HoverProviderCapability::Simple(true)),
                 signature_help_provider: Some(SignatureHelpOptions {
                     trigger_characters: Some(vec!["!".to_string(), "(".to_string(), ",".to_string()]),
@@ -192,21 +273,37 @@ impl LanguageServer for RholangBackend {
                     all_commit_characters: None,
                     resolve_provider: Some(false),
                     completion_item: Some(CompletionOptionsCompletionItem {
-                        label_details_support: Some(true),
+                        label_details_support: Some(capabilities.label_details),
                     }),
                     work_done_progress_options: Default::default(),
                 }),
-                semantic_tokens_provider: Some(SemanticTokensServerCapabilities::SemanticTokensOptions(
-                    SemanticTokensOptions {
+                // Only relative-format tokens are produced
+                semantic_tokens_provider: capabilities.semantic_tokens.then(|| {
+                    SemanticTokensServerCapabilities::SemanticTokensOptions(SemanticTokensOptions {
                         legend: SemanticTokensLegend {
                             token_types,
-                            token_modifiers: vec![],
+                            token_modifiers: semantic_modifiers::token_modifiers(),
                         },
-                        full: Some(SemanticTokensFullOptions::Bool(true)),
-                        range: None,
+                        full: Some(SemanticTokensFullOptions::Delta { delta: Some(true) }),
+                        range: Some(true),
                         ..Default::default()
-                    }
-                )),
+                    })
+                }),
+                code_action_provider: Some(CodeActionProviderCapability::Options(CodeActionOptions {
+                    code_action_kinds: Some(vec![CodeActionKind::QUICKFIX, move_contract::REFACTOR_MOVE]),
+                    ..Default::default()
+                })),
+                execute_command_provider: Some(ExecuteCommandOptions {
+                    commands: commands::COMMANDS.iter().map(|c| c.to_string()).collect(),
+                    work_done_progress_options: Default::default(),
+                }),
+                workspace: Some(WorkspaceServerCapabilities {
+                    workspace_folders: Some(WorkspaceFoldersServerCapabilities {
+                        supported: Some(true),
+                        change_notifications: Some(OneOf::Left(true)),
+                    }),
+                    file_operations: None,
+                }),
                 ..Default::default()
             },
             ..Default::default()
@@ -217,6 +314,25 @@ impl LanguageServer for RholangBackend {
     async fn initialized(&self, params: InitializedParams) {
         info!("Initialized");
         debug!("Initialized params: {:?}", params);
+        self.startup_readiness().await;
+        self.register_file_watchers().await;
+    }
+
+    /// Updates the workspace index for files changed outside the editor.
+    async fn did_change_watched_files(&self, params: DidChangeWatchedFilesParams) {
+        debug!("didChangeWatchedFiles: {} change(s)", params.changes.len());
+        self.apply_watched_file_changes(params.changes).await;
+    }
+
+    /// Indexes added workspace folders and drops the files of removed ones.
+    async fn did_change_workspace_folders(&self, params: DidChangeWorkspaceFoldersParams) {
+        self.change_workspace_folders(params.event).await;
+    }
+
+    /// Applies changed settings (see `crate::lsp::settings`).
+    async fn did_change_configuration(&self, params: DidChangeConfigurationParams) {
+        info!("workspace/didChangeConfiguration");
+        self.change_configuration(params.settings).await;
     }
 
     /// Handles the LSP shutdown request.
@@ -237,12 +353,31 @@ impl LanguageServer for RholangBackend {
         let text = params.text_document.text;
         let version = params.text_document.version;
 
+        if self.is_virtual_document_uri(&uri).await {
+            debug!("Opened virtual document {}; served from the virtual document registry", uri);
+            return;
+        }
+        self.edit_journal.record_open(&uri, version, &text, self.capabilities.read().position_encoding);
+        self.crash_reporter.note_request("textDocument/didOpen", &uri, Some(version));
+        self.register_shared_document(&uri);
+        self.desynced_documents.remove(&uri);
+
+        // Without a BOM and with lone `\r` as `\n`; positions are unchanged
+        let format = TextFormat::detect(&text);
+        if format.bom || format.mixed_line_endings {
+            debug!("Normalizing {} (BOM: {}, line endings: {:?}, mixed: {})",
+                uri, format.bom, format.line_ending, format.mixed_line_endings);
+        }
+        let text = normalize(&text).into_owned();
+
         let mut root_guard = self.root_dir.write().await;
         if root_guard.is_none() {
             if let Ok(path) = uri.to_file_path() {
                 if let Some(parent) = path.parent() {
                     *root_guard = Some(parent.to_owned());
                     drop(root_guard);
+                    self.load_style_lints(parent);
+                    self.load_format_config(parent);
 
                     let dir = parent.to_owned();
                     // Use parallel indexing for initial workspace scan (4-8x faster)
@@ -266,30 +401,49 @@ impl LanguageServer for RholangBackend {
             drop(root_guard);
         }
 
+        self.update_analysis_level(&uri, &text).await;
         let document_id = self.next_document_id();
+        let rope = Rope::from_str(&text);
+        debug!("Created rope from text with {} lines for URI {}", rope.len_lines(), uri);
+        debug!("Text: {:?}", &text);
+        let line_index = LineIndex::from_rope(&rope).with_encoding(self.capabilities.read().position_encoding);
+        // Parsed once here; later changes reparse incrementally from this tree
+        let tree = {
+            let text = text.clone();
+            match self.analysis_pool.run(move || parse_code(&text)).await {
+                Ok(tree) => tree,
+                Err(e) => {
+                    error!("Failed to parse {}: {}", uri, e);
+                    return;
+                }
+            }
+        };
         let document = std::sync::Arc::new(LspDocument {
             id: document_id,
             state: tokio::sync::RwLock::new(LspDocumentState {
                 uri: uri.clone(),
-                text: {
-                    let rope = Rope::from_str(&text);
-                    debug!("Created rope from text with {} lines for URI {}", rope.len_lines(), uri);
-                    debug!("Text: {:?}", &text);
-                    rope
-                },
+                text: rope,
+                line_index,
+                tree: Some(tree.clone()),
+                parse_counts: ParseCounts { incremental: 0, full: 1 },
                 version,
                 history: LspDocumentHistory {
                     text: text.clone(),
                     changes: Vec::new(),
                 },
+                last_edits: None,
+                dirty: None,
+                format,
             }),
         });
         // DashMap provides lock-free concurrent access (Phase 3 optimization)
         self.documents_by_uri.insert(uri.clone(), document.clone());
         self.documents_by_id.insert(document_id, document.clone());
+        // From now on the index takes the document from its buffer, not from disk
+        self.buffer_overlay.open(&uri);
 
         // Index file and update workspace in a single batched write lock
-        match self.index_file(&uri, &text, version, None).await {
+        match self.index_file(&uri, &text, version, Some(tree)).await {
             Ok(cached_doc) => {
                 self.update_workspace_document(&uri, std::sync::Arc::new(cached_doc)).await;
                 self.link_symbols().await;
@@ -299,18 +453,9 @@ impl LanguageServer for RholangBackend {
 
         // Spawn async validation task
         let backend = self.clone();
-        let uri_clone = uri.clone();
-        let document_clone = document.clone();
-        let text_clone = text.clone();
+        let text = Arc::new(text);
         tokio::spawn(async move {
-            match backend.validate(document_clone.clone(), &text_clone, version).await {
-                Ok(diagnostics) => {
-                    if document_clone.version().await == version {
-                        backend.client.publish_diagnostics(uri_clone, diagnostics, Some(version)).await;
-                    }
-                }
-                Err(e) => error!("Validation failed for URI={}: {}", uri_clone, e),
-            }
+            backend.run_validation(uri, document, text, version, Publication::Settled).await;
         });
     }
 
@@ -320,9 +465,34 @@ impl LanguageServer for RholangBackend {
         let version = params.text_document.version;
         info!("textDocument/didChange: uri={}, version={}", uri, version);
         debug!("didChange params: {:?}", params);
+        if self.is_virtual_document_uri(&uri).await {
+            // Virtual documents follow edits to their parent document
+            debug!("Ignoring direct change to virtual document {}", uri);
+            return;
+        }
+        if !self.arbitrate_edit(&uri, version).await {
+            return;
+        }
+        self.crash_reporter.note_request("textDocument/didChange", &uri, Some(version));
+        // Journaled before applying, so a crash while applying is reproducible
+        self.edit_journal.record_change(&uri, version, &params.content_changes);
+        // Validating the previous versions is wasted work from here on, debounced or not
+        self.validation_scheduler.supersede(&uri, version);
         // DashMap::get returns a guard that dereferences to the value
         if let Some(document) = self.documents_by_uri.get(&uri).map(|r| r.value().clone()) {
+            // Edits that do not fit the text would corrupt it; recover the client's text instead
+            if !self.check_sync(&uri, &document, &params.content_changes, version).await {
+                return;
+            }
+            let previous = document.version().await;
             if let Some((text, tree)) = document.apply(params.content_changes, version).await {
+                let edits = document.last_edits().await;
+                // Keep published diagnostics on their tokens until validation catches up
+                self.shift_diagnostics(&uri, previous, version, edits.as_deref()).await;
+                if let Some(edits) = edits {
+                    self.forward_host_edits(&uri, version, &edits).await;
+                }
+                self.update_analysis_level(&uri, &text).await;
                 match self.index_file(&uri, &text, version, Some(tree)).await {
                     Ok(cached_doc) => {
                         self.update_workspace_document(&uri, std::sync::Arc::new(cached_doc)).await;
@@ -351,11 +521,17 @@ impl LanguageServer for RholangBackend {
         }
     }
 
-    /// Handles saving a text document (no-op since validation is on change).
+    /// Handles saving a text document, escalating syntax errors softened while typing.
     async fn did_save(&self, params: DidSaveTextDocumentParams) {
         info!("textDocument/didSave: uri={}", params.text_document.uri);
         debug!("didSave params: {:?}", params);
-        // Validation occurs on open and change; no additional action needed here
+        // Validation occurs on open and change; saving only ends the typing period
+        let uri = params.text_document.uri;
+        if let Some(document) = self.documents_by_uri.get(&uri).map(|r| r.value().clone()) {
+            let version = document.version().await;
+            self.crash_reporter.note_request("textDocument/didSave", &uri, Some(version));
+            self.escalate_typing_diagnostics(&uri, version).await;
+        }
     }
 
     /// Handles closing a text document, removing it from state and clearing diagnostics.
@@ -363,23 +539,43 @@ impl LanguageServer for RholangBackend {
         let uri = params.text_document.uri.clone();
         info!("textDocument/didClose: uri={}", uri);
         debug!("didClose params: {:?}", params);
+        if self.is_virtual_document_uri(&uri).await {
+            debug!("Closed virtual document {}", uri);
+            return;
+        }
+        self.edit_journal.record_close(&uri);
+        self.crash_reporter.note_request("textDocument/didClose", &uri, None);
+        self.release_shared_document(&uri);
+        self.desynced_documents.remove(&uri);
+        self.normalized_forms.remove(&uri);
+        self.semantic_tokens.remove(&uri);
+        self.chunked_tokens.remove(&uri);
+        self.analysis_levels.remove(&uri);
+        self.typing_diagnostics.remove(&uri);
+        self.ir_memos.remove(&uri);
+        self.validation_scheduler.remove(&uri);
         // DashMap::remove returns Option<(K, V)>
         if let Some((_key, document)) = self.documents_by_uri.remove(&uri) {
             self.documents_by_id.remove(&document.id);
             info!("Closed document: {}, id: {}", uri, document.id);
 
             // Unregister any virtual documents associated with this parent
-            let mut virtual_docs = self.virtual_docs.write().await;
-            virtual_docs.unregister_parent(&uri);
+            self.virtual_docs.write().await.unregister_parent(&uri);
+            self.announce_virtual_documents(&uri).await;
+
+            // Unsaved edits leave the index with the buffer
+            self.buffer_overlay.close(&uri);
+            self.restore_disk_index(&uri).await;
         } else {
             warn!("Failed to find document with URI={}", uri);
         }
-        self.client.publish_diagnostics(uri, Vec::new(), None).await;
+        self.queue_diagnostics(uri, Vec::new(), None).await;
     }
 
     /// Handles renaming a symbol, updating all references across the workspace.
     async fn rename(&self, params: RenameParams) -> LspResult<Option<WorkspaceEdit>> {
         debug!("rename request for {:?}", params);
+        self.hint_partial_result("textDocument/rename").await;
 
         // Eagerly ensure symbols are linked before rename operation
         if self.needs_symbol_linking().await {
@@ -387,8 +583,40 @@ impl LanguageServer for RholangBackend {
             self.link_symbols().await;
         }
 
+        // Refuse renames that would change what an occurrence refers to
+        if let Some(report) = self.rename_conflicts(&params) {
+            return Err(report.into_error());
+        }
+
         // Use unified handler (Phase 4c: replaces 70+ lines of language-specific logic)
-        Ok(self.unified_rename(params).await)
+        let edit = self.unified_rename(params).await;
+
+        // Library documents are read-only
+        if let Some(edit) = &edit {
+            let read_only = self.libraries.read_only(edit.changes.iter().flat_map(|changes| changes.keys()));
+            if !read_only.is_empty() {
+                let files = read_only.iter().map(Url::as_str).collect::<Vec<_>>().join(", ");
+                return Err(jsonrpc::Error::invalid_params(format!(
+                    "Rename would edit read-only library file(s): {}",
+                    files
+                )));
+            }
+        }
+        Ok(edit)
+    }
+
+    async fn code_action(&self, params: CodeActionParams) -> LspResult<Option<CodeActionResponse>> {
+        debug!("code_action request for {:?}", params.range);
+
+        // Quick fixes are renames; make sure references are linked first
+        if self.needs_symbol_linking().await {
+            self.link_symbols().await;
+        }
+
+        let mut actions = self.naming_quick_fixes(&params).await;
+        actions.extend(self.provider_quick_fixes(&params));
+        actions.extend(self.move_contract_actions(&params));
+        Ok(if actions.is_empty() { None } else { Some(actions) })
     }
     async fn goto_definition(&self, params: GotoDefinitionParams) -> LspResult<Option<GotoDefinitionResponse>> {
         let start = std::time::Instant::now();
@@ -396,9 +624,14 @@ impl LanguageServer for RholangBackend {
         let position = params.text_document_position_params.position;
 
         debug!("goto_definition request for {} at {:?}", uri, position);
+        self.hint_partial_result("textDocument/definition").await;
 
         // Use unified handler (Phase 4c: replaces 300+ lines of language-specific logic)
         let goto_result = self.unified_goto_definition(uri, position).await;
+        // Registry URIs and names bound to them lead to the contracts published in other files
+        let goto_result = self.registry_definition(uri, position, goto_result.as_ref()).or(goto_result);
+        // Documents whose IR is mostly errors resolve within their error-free regions
+        let goto_result = goto_result.or_else(|| self.locals_definition(uri, position));
 
         // Log the result for debugging
         match &goto_result {
@@ -445,9 +678,31 @@ impl LanguageServer for RholangBackend {
         }
     }
 
+    /// Resolves the contract named, or called, at the cursor for the call hierarchy.
+    async fn prepare_call_hierarchy(&self, params: CallHierarchyPrepareParams) -> LspResult<Option<Vec<CallHierarchyItem>>> {
+        let uri = params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
+        debug!("prepareCallHierarchy request for {} at {:?}", uri, position);
+        Ok(self.call_hierarchy_items_at(&uri, position))
+    }
+
+    /// Lists the sends calling a contract, grouped by the contract they are in.
+    async fn incoming_calls(&self, params: CallHierarchyIncomingCallsParams) -> LspResult<Option<Vec<CallHierarchyIncomingCall>>> {
+        debug!("callHierarchy/incomingCalls request for `{}` in {}", params.item.name, params.item.uri);
+        self.hint_partial_result("callHierarchy/incomingCalls").await;
+        Ok(Some(self.incoming_calls_of(&params.item)))
+    }
+
+    /// Lists the contracts called from the body of a contract.
+    async fn outgoing_calls(&self, params: CallHierarchyOutgoingCallsParams) -> LspResult<Option<Vec<CallHierarchyOutgoingCall>>> {
+        debug!("callHierarchy/outgoingCalls request for `{}` in {}", params.item.name, params.item.uri);
+        Ok(Some(self.outgoing_calls_of(&params.item)))
+    }
+
     /// Handles finding all references to a symbol.
     async fn references(&self, params: ReferenceParams) -> LspResult<Option<Vec<Location>>> {
         debug!("references request for {:?}", params);
+        self.hint_partial_result("textDocument/references").await;
 
         // Eagerly ensure symbols are linked before references operation
         if self.needs_symbol_linking().await {
@@ -455,8 +710,18 @@ impl LanguageServer for RholangBackend {
             self.link_symbols().await;
         }
 
+        let uri = params.text_document_position.text_document.uri.clone();
+        let position = params.text_document_position.position;
+
         // Use unified handler (Phase 4c: replaces 180+ lines of language-specific logic)
-        Ok(self.unified_references(params).await)
+        let mut locations = self.unified_references(params).await;
+
+        // Files under the root that are not indexed (yet) are searched on demand
+        let unindexed = self.unindexed_references(&uri, position).await;
+        if !unindexed.is_empty() {
+            locations.get_or_insert_with(Vec::new).extend(unindexed);
+        }
+        Ok(locations)
     }
     async fn document_symbol(&self, params: DocumentSymbolParams) -> LspResult<Option<DocumentSymbolResponse>> {
         let uri = params.text_document.uri;
@@ -482,25 +747,125 @@ impl LanguageServer for RholangBackend {
             };
 
             debug!("Found {} symbols in document {}", symbols.len(), uri);
-            Ok(Some(DocumentSymbolResponse::Nested(symbols)))
+            Ok(Some(self.capabilities.read().adapt_document_symbols(&uri, symbols)))
         } else {
             debug!("Document not found: {}", uri);
             Ok(None)
         }
     }
 
+    /// Provides folding ranges, with a separate range for each match case and select branch.
+    async fn folding_range(&self, params: FoldingRangeParams) -> LspResult<Option<Vec<FoldingRange>>> {
+        let uri = params.text_document.uri;
+        debug!("Handling foldingRange request for {}", uri);
+        let Some(doc) = self.workspace.documents.get(&uri).map(|entry| entry.value().clone()) else {
+            debug!("Document not found: {}", uri);
+            return Ok(None);
+        };
+        if doc.language == crate::lsp::models::DocumentLanguage::Metta {
+            return Ok(None);
+        }
+        let mut ranges = folding::folding_ranges(&doc.tree, &doc.ir, &doc.positions, &doc.text, &doc.line_index);
+        self.capabilities.read().adapt_folding_ranges(&mut ranges);
+        debug!("Found {} folding ranges in document {}", ranges.len(), uri);
+        Ok(Some(ranges))
+    }
+
+    /// Provides the ranges an expanding selection grows through at each position.
+    async fn selection_range(&self, params: SelectionRangeParams) -> LspResult<Option<Vec<SelectionRange>>> {
+        let uri = params.text_document.uri;
+        debug!("Handling selectionRange request for {} at {} position(s)", uri, params.positions.len());
+        let Some(doc) = self.workspace.documents.get(&uri).map(|entry| entry.value().clone()) else {
+            debug!("Document not found: {}", uri);
+            return Ok(None);
+        };
+        if doc.language == crate::lsp::models::DocumentLanguage::Metta {
+            return Ok(None);
+        }
+        let ranges = params
+            .positions
+            .iter()
+            .map(|&position| selection_range(&doc.ir, &doc.positions, &doc.text, &doc.line_index, position))
+            .collect();
+        Ok(Some(ranges))
+    }
+
+    /// Provides a lens above each contract with its workspace-wide call count,
+    /// and above each formal answered on with its response sends.
+    async fn code_lens(&self, params: CodeLensParams) -> LspResult<Option<Vec<CodeLens>>> {
+        let uri = params.text_document.uri;
+        debug!("Handling codeLens request for {}", uri);
+        if !self.feature_enabled(Feature::CodeLens) {
+            return Ok(None);
+        }
+        let calls = self.call_lenses(&uri);
+        let responses = self.response_lenses(&uri);
+        if calls.is_none() && responses.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(calls.into_iter().flatten().chain(responses).collect()))
+    }
+
+    /// Reformats a whole document with the IR formatter, keeping its comments.
+    async fn formatting(&self, params: DocumentFormattingParams) -> LspResult<Option<Vec<TextEdit>>> {
+        let uri = params.text_document.uri;
+        debug!("Handling formatting request for {}", uri);
+        Ok(self.format_document_edits(&uri, params.options.tab_size))
+    }
+
+    /// Re-indents the lines of a range.
+    async fn range_formatting(&self, params: DocumentRangeFormattingParams) -> LspResult<Option<Vec<TextEdit>>> {
+        let uri = params.text_document.uri;
+        debug!("Handling rangeFormatting request for {} at {:?}", uri, params.range);
+        Ok(self.range_indent_edits(&uri, params.range, &params.options))
+    }
+
+    /// Re-indents the current line after a newline or a closing bracket.
+    async fn on_type_formatting(&self, params: DocumentOnTypeFormattingParams) -> LspResult<Option<Vec<TextEdit>>> {
+        let uri = params.text_document_position.text_document.uri;
+        let position = params.text_document_position.position;
+        debug!("Handling onTypeFormatting request for {:?} at {:?} in {}", params.ch, position, uri);
+        Ok(self.on_type_indent_edits(&uri, position, &params.ch, &params.options))
+    }
+
+    /// Provides parameter name hints at calls and arity hints on `for` binds.
+    async fn inlay_hint(&self, params: InlayHintParams) -> LspResult<Option<Vec<InlayHint>>> {
+        let uri = params.text_document.uri;
+        debug!("Handling inlayHint request for {} at {:?}", uri, params.range);
+        if !self.feature_enabled(Feature::InlayHints) {
+            return Ok(None);
+        }
+        Ok(self.document_inlay_hints(&uri, params.range))
+    }
+
+    /// Links registry URIs to their explorer page or contract, and relative `.rho` paths to their file.
+    async fn document_link(&self, params: DocumentLinkParams) -> LspResult<Option<Vec<DocumentLink>>> {
+        let uri = params.text_document.uri;
+        debug!("Handling documentLink request for {}", uri);
+        if !self.feature_enabled(Feature::DocumentLinks) {
+            return Ok(None);
+        }
+        Ok(self.collect_document_links(&uri))
+    }
+
     /// Searches for workspace symbols matching the query.
     async fn symbol(&self, params: WorkspaceSymbolParams) -> LspResult<Option<Vec<SymbolInformation>>> {
         let query = params.query;
         debug!("Handling workspace symbol request with query '{}'", query);
+        self.hint_partial_result("workspace/symbol").await;
 
         // Ultra-fast path: Use suffix array for O(m log n + k) substring search
         // This is significantly faster than O(documents × symbols × name_length) filtering
-        let symbols: Vec<SymbolInformation> = self.workspace.documents
+        let mut symbols: Vec<SymbolInformation> = self.workspace.documents
             .iter()
             .flat_map(|entry| entry.value().symbol_index.search(&query))
             .collect();
 
+        // Files under the root that are not indexed (yet) are loaded on demand
+        for artifact in self.unindexed_artifacts().await {
+            symbols.extend(artifact.symbols.search(&query));
+        }
+
         debug!("Found {} matching workspace symbols via suffix array", symbols.len());
         Ok(Some(symbols))
     }
@@ -527,6 +892,14 @@ impl LanguageServer for RholangBackend {
         // Check if position is within a virtual document (embedded language)
         {
             let virtual_docs = self.virtual_docs.read().await;
+            if let Some(virtual_doc) = virtual_docs.resolve(&uri) {
+                // The virtual document itself was opened; positions are already virtual
+                drop(virtual_docs);
+                if virtual_doc.language == "metta" {
+                    return self.document_highlight_metta(&virtual_doc, position, true).await;
+                }
+                return Ok(None);
+            }
             if let Some((virtual_uri, virtual_position, virtual_doc)) =
                 virtual_docs.find_virtual_document_at_position(&uri, position)
             {
@@ -538,7 +911,7 @@ impl LanguageServer for RholangBackend {
 
                 // Get highlights from virtual document (MeTTa)
                 if virtual_doc.language == "metta" {
-                    return self.document_highlight_metta(&virtual_doc, virtual_position, position).await;
+                    return self.document_highlight_metta(&virtual_doc, virtual_position, false).await;
                 }
             }
         }
@@ -548,7 +921,7 @@ impl LanguageServer for RholangBackend {
             Some(s) => s,
             None => {
                 debug!("No symbol at position");
-                return Ok(None);
+                return Ok(self.locals_highlights(&uri, position));
             }
         };
 
@@ -564,6 +937,11 @@ impl LanguageServer for RholangBackend {
             .collect();
 
         debug!("Found {} highlights", highlights.len());
+        if highlights.is_empty() {
+            if let Some(locals) = self.locals_highlights(&uri, position) {
+                return Ok(Some(locals));
+            }
+        }
 
         Ok(Some(highlights))
     }
@@ -573,9 +951,15 @@ impl LanguageServer for RholangBackend {
         let position = params.text_document_position_params.position;
 
         debug!("Hover request at {}:{:?}", uri, position);
+        self.hint_partial_result("textDocument/hover").await;
 
         // Use unified handler (Phase 4c: replaces 200+ lines of language-specific logic)
-        Ok(self.unified_hover(uri, position).await)
+        let hover = self.unified_hover(uri, position).await;
+        let hover = self.with_pattern_examples(uri, position, hover);
+        let hover = self.with_response_sends(uri, position, hover);
+        let hover = self.with_name_escapes(uri, position, hover);
+        let hover = self.with_normalized_form(uri, position, hover);
+        Ok(hover.map(|hover| self.capabilities.read().adapt_hover(hover)))
     }
 
     /// Provides signature help for contract calls
@@ -595,10 +979,10 @@ impl LanguageServer for RholangBackend {
         };
 
         // Convert LSP position to byte offset
-        let byte_offset = match Self::byte_offset_from_position(
-            &doc.text,
+        let byte_offset = match doc.line_index.offset(
             position.line as usize,
             position.character as usize,
+            &doc.text,
         ) {
             Some(offset) => offset,
             None => {
@@ -663,9 +1047,10 @@ impl LanguageServer for RholangBackend {
                                     let label = param_names.get(i)
                                         .cloned()
                                         .unwrap_or_else(|| format!("param{}", i + 1));
+                                    let documentation = Self::parameter_documentation(symbol, &label);
                                     ParameterInformation {
                                         label: ParameterLabel::Simple(label),
-                                        documentation: None,
+                                        documentation,
                                     }
                                 })
                                 .collect();
@@ -714,9 +1099,10 @@ impl LanguageServer for RholangBackend {
                                 let label = param_names.get(i)
                                     .cloned()
                                     .unwrap_or_else(|| format!("param{}", i + 1));
+                                let documentation = Self::parameter_documentation(symbol, &label);
                                 ParameterInformation {
                                     label: ParameterLabel::Simple(label),
-                                    documentation: None,
+                                    documentation,
                                 }
                             })
                             .collect();
@@ -790,6 +1176,14 @@ impl LanguageServer for RholangBackend {
         let position = params.text_document_position.position;
 
         debug!("Completion request at {}:{:?}", uri, position);
+        self.hint_partial_result("textDocument/completion").await;
+
+        // In the pattern of `for (… <- retCh)`: only the shapes sent back on retCh make sense
+        let mut receive_patterns = self.receive_pattern_completions(&uri, position);
+        if !receive_patterns.is_empty() {
+            self.capabilities.read().adapt_completions(&mut receive_patterns);
+            return Ok(Some(CompletionResponse::Array(receive_patterns)));
+        }
 
         // Get document
         let doc = match self.workspace.documents.get(&uri) {
@@ -802,13 +1196,24 @@ impl LanguageServer for RholangBackend {
 
         let mut completions = Vec::new();
 
+        // Where a process starts, contract names expand to invocation templates
+        let Some(send_position) = at_send_position(&doc.text, &doc.line_index, position) else {
+            debug!("Completion position {:?} is outside {}", position, uri);
+            return Ok(None);
+        };
+
         // Get all contract symbols from global table using pattern-based lookup
         // This is O(1) for accessing the entire contract index
         let global_table = self.workspace.global_table.read().await;
 
         // Collect all unique contract names from the pattern index
-        // This gives us O(1) access to all contracts
-        let all_symbols = global_table.collect_all_symbols();
+        // This gives us O(1) access to all contracts; very large documents skip them
+        let all_symbols = if self.analysis_levels.level(&uri).allows(DegradedFeature::CompletionIndex) {
+            global_table.collect_all_symbols()
+        } else {
+            debug!("Workspace symbols left out of completion for large document {}", uri);
+            Vec::new()
+        };
 
         let mut contract_names_seen = std::collections::HashSet::new();
 
@@ -847,6 +1252,17 @@ impl LanguageServer for RholangBackend {
                         ))
                     };
 
+                    if send_position {
+                        let templates = match self.workspace.global_index.read() {
+                            Ok(index) => invocation_items(index.pattern_index.signatures(&symbol.name), documentation.clone()),
+                            Err(_) => Vec::new(),
+                        };
+                        if !templates.is_empty() {
+                            completions.extend(templates);
+                            continue;
+                        }
+                    }
+
                     completions.push(CompletionItem {
                         label: symbol.name.clone(),
                         kind: Some(CompletionItemKind::FUNCTION),
@@ -915,6 +1331,23 @@ impl LanguageServer for RholangBackend {
             });
         }
 
+        // A fresh binder also gets names derived from what it is used with, after everything else
+        let binder_names: Vec<CompletionItem> = self
+            .binder_name_completions(&doc, position)
+            .into_iter()
+            .filter(|item| completions.iter().all(|existing| existing.label != item.label))
+            .collect();
+        completions.extend(binder_names);
+
+        // Contracts and channels the workspace accepts most often come first
+        rank_completions(&mut completions, &self.completion_usage, unix_now());
+        // Calls to contracts with persistent sends or replicated receives go last
+        self.apply_completion_costs(&doc, position, &mut completions);
+        if let Some(max_items) = self.settings.read().completion.max_items {
+            completions.truncate(max_items);
+        }
+        self.capabilities.read().adapt_completions(&mut completions);
+
         debug!("Returning {} completion items", completions.len());
 
         if completions.is_empty() {
@@ -930,43 +1363,53 @@ impl LanguageServer for RholangBackend {
     ) -> LspResult<Option<SemanticTokensResult>> {
         let uri = params.text_document.uri;
         debug!("Semantic tokens request for: {}", uri);
-
-        // Get virtual documents for this file
-        let virtual_docs_guard = self.virtual_docs.read().await;
-        let virtual_docs_list = virtual_docs_guard.get_by_parent(&uri);
-
-        if virtual_docs_list.is_empty() {
-            debug!("No virtual documents (embedded languages) found for {}", uri);
+        if !self.feature_enabled(Feature::SemanticTokens) {
             return Ok(None);
         }
+        let Some(tokens) = self.document_semantic_tokens(&uri).await else {
+            self.semantic_tokens.remove(&uri);
+            return Ok(None);
+        };
+        Ok(Some(SemanticTokensResult::Tokens(self.semantic_tokens.full(&uri, tokens))))
+    }
 
-        // Build semantic tokens for all embedded language regions
-        let mut tokens_builder = SemanticTokensBuilder::new();
-
-        for virtual_doc in virtual_docs_list {
-            debug!(
-                "Processing {} virtual document at line {} (bytes {})",
-                virtual_doc.language, virtual_doc.parent_start.line, virtual_doc.byte_offset
-            );
-
-            // Only process MeTTa regions for now
-            if virtual_doc.language == "metta" {
-                // Use VirtualDocument directly - it now caches parsed trees
-                self.add_metta_semantic_tokens(&mut tokens_builder, &virtual_doc).await;
-            }
+    /// Sends the edits since the token set the client holds
+    /// (see `crate::lsp::semantic_tokens_delta`).
+    async fn semantic_tokens_full_delta(
+        &self,
+        params: SemanticTokensDeltaParams,
+    ) -> LspResult<Option<SemanticTokensFullDeltaResult>> {
+        let uri = params.text_document.uri;
+        debug!("Semantic tokens delta request for: {} (since {})", uri, params.previous_result_id);
+        if !self.feature_enabled(Feature::SemanticTokens) {
+            return Ok(None);
         }
-        drop(virtual_docs_guard);
-
-        let tokens_data = tokens_builder.build();
+        let Some(tokens) = self.document_semantic_tokens(&uri).await else {
+            self.semantic_tokens.remove(&uri);
+            return Ok(None);
+        };
+        Ok(Some(self.semantic_tokens.delta(&uri, &params.previous_result_id, tokens)))
+    }
 
-        debug!("Generated {} semantic tokens", tokens_data.len());
+    /// Sends the tokens of the visible range, at every document size
+    /// (see `crate::lsp::semantic_tokens_range`).
+    async fn semantic_tokens_range(
+        &self,
+        params: SemanticTokensRangeParams,
+    ) -> LspResult<Option<SemanticTokensRangeResult>> {
+        let uri = params.text_document.uri;
+        debug!("Semantic tokens range request for: {} ({:?})", uri, params.range);
+        if !self.feature_enabled(Feature::SemanticTokens) {
+            return Ok(None);
+        }
+        let tokens = self.range_semantic_tokens(&uri, &params.range).await;
+        Ok(tokens.map(|data| SemanticTokensRangeResult::Tokens(SemanticTokens { result_id: None, data })))
+    }
 
-        Ok(Some(SemanticTokensResult::Tokens(
-            tower_lsp::lsp_types::SemanticTokens {
-                result_id: None,
-                data: tokens_data,
-            }
-        )))
+    /// Executes a server-side command (see `commands::COMMANDS`).
+    async fn execute_command(&self, params: ExecuteCommandParams) -> LspResult<Option<serde_json::Value>> {
+        info!("workspace/executeCommand: {}", params.command);
+        self.dispatch_command(params).await
     }
 }
 
@@ -1018,6 +1461,18 @@ impl RholangBackend {
         }
     }
 
+    /// Documentation of the formal labelled `label` (`amount` or `@amount`)
+    /// from the contract's `@param` directives.
+    fn parameter_documentation(
+        symbol: &crate::ir::symbol_table::Symbol,
+        label: &str,
+    ) -> Option<tower_lsp::lsp_types::Documentation> {
+        let name = label.trim_start_matches('@');
+        symbol.param_docs.iter()
+            .find(|doc| doc.name.trim_start_matches('@') == name && !doc.description.is_empty())
+            .map(|doc| tower_lsp::lsp_types::Documentation::String(doc.description.clone()))
+    }
+
     /// Filters global contracts using pattern-based lookup for better performance.
     /// Returns (Url, Arc<RholangNode>) tuples for contracts matching the pattern.
     ///
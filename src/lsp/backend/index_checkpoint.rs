@@ -0,0 +1,67 @@
+//! Resumable workspace indexing for the LSP backend
+//!
+//! Skips the workspace files recorded by the checkpoints of
+//! `crate::lsp::index_checkpoint`, pinning their summaries in the disk index
+//! instead, and records each file the progressive indexer completes.
+
+use std::path::PathBuf;
+
+use tower_lsp::lsp_types::Url;
+use tracing::info;
+
+use crate::lsp::disk_index::DiskArtifact;
+use crate::lsp::models::CachedDocument;
+
+use super::state::RholangBackend;
+
+impl RholangBackend {
+    /// The files of `paths`, found under `roots`, that are not recorded as
+    /// indexed by a checkpoint and must be indexed.
+    pub(super) fn resume_from_checkpoints(&self, roots: &[PathBuf], paths: Vec<PathBuf>) -> Vec<PathBuf> {
+        let mut guard = self.index_checkpoints.lock();
+        let Some(checkpoints) = guard.as_mut() else {
+            return paths;
+        };
+        for root in roots {
+            checkpoints.for_root(root);
+        }
+
+        let total = paths.len();
+        let remaining: Vec<PathBuf> = paths
+            .into_iter()
+            .filter(|path| {
+                let Some(root) = roots.iter().filter(|root| path.starts_with(root)).max_by_key(|root| root.components().count()) else {
+                    return true;
+                };
+                match checkpoints.for_root(root).resume(path) {
+                    Some((artifact, stamp)) => {
+                        self.disk_index.pin(path, artifact, stamp);
+                        false
+                    }
+                    None => true,
+                }
+            })
+            .collect();
+        if remaining.len() < total {
+            info!("Resuming workspace indexing: {} of {} files indexed earlier", total - remaining.len(), total);
+        }
+        remaining
+    }
+
+    /// Records `doc`, just indexed by the progressive indexer, in the
+    /// checkpoint of its workspace folder.
+    pub(super) fn checkpoint_indexed(&self, uri: &Url, doc: &CachedDocument) {
+        let mut guard = self.index_checkpoints.lock();
+        let (Some(checkpoints), Ok(path)) = (guard.as_mut(), uri.to_file_path()) else {
+            return;
+        };
+        checkpoints.record(&path, &DiskArtifact::from_ir(uri, &doc.ir, &doc.positions, &doc.text, &doc.line_index));
+    }
+
+    /// Compacts the checkpoints once workspace indexing is complete.
+    pub(super) fn compact_checkpoints(&self) {
+        if let Some(checkpoints) = self.index_checkpoints.lock().as_mut() {
+            checkpoints.compact();
+        }
+    }
+}
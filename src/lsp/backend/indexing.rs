@@ -10,6 +10,7 @@
 //! - Parallel batch indexing using Rayon
 
 use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
@@ -25,14 +26,21 @@ use walkdir::WalkDir;
 use crate::ir::pipeline::Pipeline;
 use crate::ir::rholang_node::{RholangNode, compute_absolute_positions, collect_contracts, collect_calls};
 use crate::ir::symbol_table::SymbolTable;
+use crate::ir::symbol_resolution::RegistryUriBindings;
 use crate::ir::transforms::symbol_table_builder::SymbolTableBuilder;
 use crate::ir::transforms::symbol_index_builder::SymbolIndexBuilder;
 use crate::ir::transforms::documentation_attacher::DocumentationAttacher;
 use crate::language_regions::{ChannelFlowAnalyzer, DirectiveParser, SemanticDetector};
+use crate::lsp::buffer_overlay::DiskClaim;
+use crate::lsp::deprecation::DeprecatedDeclarations;
+use crate::lsp::library_paths::{library_files, LIBRARY_INDEX_PRIORITY};
+use crate::lsp::line_index::{LineIndex, PositionEncoding};
 use crate::lsp::models::{CachedDocument, DocumentLanguage};
+use crate::lsp::text_normalization::normalize;
+use crate::parsers::rholang::{parse_to_document_ir_reusing, ConversionMemo, DirtyRanges};
 use crate::tree_sitter::{parse_code, parse_to_ir, parse_to_document_ir};
 
-use super::state::{RholangBackend, WorkspaceChangeEvent, WorkspaceChangeType};
+use super::state::{IndexingTask, RholangBackend, WorkspaceChangeEvent, WorkspaceChangeType};
 
 impl RholangBackend {
     /// Processes a parsed IR node through the transformation pipeline to build symbols and metadata (blocking version for CPU-bound work on Rayon).
@@ -43,7 +51,7 @@ impl RholangBackend {
     ///
     /// # Performance
     /// This function performs CPU-intensive work (parsing, IR transformation, symbol building) and should
-    /// be called via `tokio::task::spawn_blocking` or from a Rayon thread pool.
+    /// be called on the analysis pool (see `crate::lsp::analysis_pool`).
     pub(super) fn process_document_blocking(
         document_ir: Arc<crate::ir::DocumentIR>,
         uri: &Url,
@@ -53,6 +61,7 @@ impl RholangBackend {
         global_index: Arc<std::sync::RwLock<crate::ir::global_index::GlobalSymbolIndex>>,
         version_counter: &Arc<std::sync::atomic::AtomicI32>,
         rholang_symbols: Option<Arc<crate::lsp::rholang_contracts::RholangContracts>>,
+        encoding: PositionEncoding,
     ) -> Result<CachedDocument, String> {
         // Extract semantic IR from DocumentIR
         let ir = document_ir.root.clone();
@@ -153,6 +162,10 @@ impl RholangBackend {
         let symbol_index = Arc::new(crate::lsp::symbol_index::SymbolIndex::new(workspace_symbols));
         debug!("Built suffix array index for {} symbols in {}", symbol_index.len(), uri);
 
+        let deprecations = Arc::new(DeprecatedDeclarations::collect(uri, &transformed_ir, &positions, Some(&document_ir)));
+        let line_index = Arc::new(LineIndex::from_rope(text).with_encoding(encoding));
+        let registry_bindings = Arc::new(RegistryUriBindings::collect(&transformed_ir, &positions, text, &line_index));
+
         Ok(CachedDocument {
             ir: transformed_ir,
             document_ir: Some(document_ir),  // Phase 1: Populated with comment channel
@@ -163,29 +176,56 @@ impl RholangBackend {
             symbol_table,
             inverted_index,
             version,
+            line_index,
             text: text.clone(),
             positions,
             symbol_index,
+            deprecations,
+            registry_bindings,
             content_hash,
         })
     }
 
     /// Processes a parsed IR node through the transformation pipeline to build symbols and metadata.
     ///
-    /// This async wrapper delegates CPU-intensive work to `process_document_blocking` via `spawn_blocking`
-    /// to prevent blocking the tokio runtime.
-    pub(super) async fn process_document(&self, document_ir: Arc<crate::ir::DocumentIR>, uri: &Url, text: &Rope, content_hash: u64) -> Result<CachedDocument, String> {
+    /// This async wrapper delegates CPU-intensive work to `process_document_blocking` on the analysis
+    /// pool to prevent blocking the tokio runtime. Without a `document_ir`, the text is also parsed
+    /// there (reusing `tree` if given) and converted to IR.
+    ///
+    /// A given `tree` is converted keeping a memo of its IR subtrees, returned with the document;
+    /// with `reuse`, the memo of the previous conversion and what changed since, the subtrees it
+    /// leaves unchanged are shared instead of converted again.
+    pub(super) async fn process_document(
+        &self,
+        document_ir: Option<Arc<crate::ir::DocumentIR>>,
+        tree: Option<tree_sitter::Tree>,
+        reuse: Option<(Arc<ConversionMemo>, DirtyRanges)>,
+        uri: &Url,
+        text: &Rope,
+        content_hash: u64,
+    ) -> Result<(CachedDocument, Option<ConversionMemo>), String> {
         // Lock and clone global_table for use in blocking task
         let global_table = Arc::new(self.workspace.global_table.read().await.clone());
         let global_index = self.workspace.global_index.clone();
         let rholang_symbols = Some(self.workspace.rholang_symbols.clone());
 
-        // Delegate CPU-intensive work to blocking thread pool
+        // Delegate CPU-intensive work to the analysis pool
         let uri_clone = uri.clone();
         let text_clone = text.clone();
         let version_counter = self.version_counter.clone();
-
-        tokio::task::spawn_blocking(move || {
+        let encoding = self.capabilities.read().position_encoding;
+
+        let result = self.analysis_pool.run_isolated(move || {
+            let (document_ir, memo) = match (document_ir, tree) {
+                (Some(document_ir), _) => (document_ir, None),
+                (None, Some(tree)) => {
+                    let (document_ir, memo, stats) = parse_to_document_ir_reusing(&tree, &text_clone, reuse);
+                    debug!("Converted {} to IR: {} subtrees reused, {} nodes converted",
+                        uri_clone, stats.reused, stats.converted);
+                    (document_ir, Some(memo))
+                }
+                (None, None) => (parse_to_document_ir(&parse_code(&text_clone.to_string()), &text_clone), None),
+            };
             Self::process_document_blocking(
                 document_ir,
                 &uri_clone,
@@ -195,10 +235,15 @@ impl RholangBackend {
                 global_index,
                 &version_counter,
                 rholang_symbols,
+                encoding,
             )
+            .map(|cached| (cached, memo))
+        })
+        .await?;
+        result.unwrap_or_else(|panic| {
+            self.report_crash(uri, &text.to_string(), &panic);
+            Err(format!("Analysis of {} panicked: {}", uri, panic.message))
         })
-        .await
-        .map_err(|e| format!("Failed to spawn blocking task: {}", e))?
     }
 
     /// Processes a parsed IR node through the transformation pipeline to build symbols and metadata (DEPRECATED - use process_document instead).
@@ -288,6 +333,10 @@ impl RholangBackend {
         let symbol_index = Arc::new(crate::lsp::symbol_index::SymbolIndex::new(workspace_symbols));
         debug!("Built suffix array index for {} symbols in {}", symbol_index.len(), uri);
 
+        let deprecations = Arc::new(DeprecatedDeclarations::collect(uri, &transformed_ir, &positions, None));
+        let line_index = Arc::new(LineIndex::from_rope(text).with_encoding(self.capabilities.read().position_encoding));
+        let registry_bindings = Arc::new(RegistryUriBindings::collect(&transformed_ir, &positions, text, &line_index));
+
         Ok(CachedDocument {
             ir: transformed_ir,
             document_ir: None, // TODO: Populate in Phase 1 implementation
@@ -298,9 +347,12 @@ impl RholangBackend {
             symbol_table,
             inverted_index,
             version,
+            line_index,
             text: text.clone(),
             positions,
             symbol_index,
+            deprecations,
+            registry_bindings,
             content_hash,
         })
     }
@@ -310,10 +362,12 @@ impl RholangBackend {
         &self,
         uri: &Url,
         text: &str,
-        _version: i32,
+        version: i32,
         tree: Option<tree_sitter::Tree>,
     ) -> Result<CachedDocument, String> {
-        use std::collections::hash_map::DefaultHasher;
+        // Files read from disk may have a BOM or `\r` line endings; open documents are already normalized
+        let normalized = normalize(text);
+        let text = normalized.as_ref();
 
         // Compute fast hash of content for change detection
         let mut hasher = DefaultHasher::new();
@@ -339,17 +393,24 @@ impl RholangBackend {
         match language {
             DocumentLanguage::Metta => {
                 // Handle MeTTa files
-                self.index_metta_file(uri, text, _version, content_hash).await
+                self.index_metta_file(uri, text, version, content_hash).await
             }
             DocumentLanguage::Rholang | DocumentLanguage::Unknown => {
                 // Handle Rholang files (existing logic)
                 // Note: We intentionally do NOT clear old symbols here - that will be done
                 // in a single batched workspace update by the caller to minimize lock duration
 
-                let tree = Arc::new(tree.unwrap_or_else(|| parse_code(text)));
+                // Parsing and IR conversion run on the analysis pool with the symbol table build;
+                // open documents reuse the IR of their previous version where it is unchanged
+                let reuse = match tree {
+                    Some(_) => self.ir_reuse(uri, version).await,
+                    None => None,
+                };
                 let rope = Rope::from_str(text);
-                let document_ir = parse_to_document_ir(&tree, &rope);
-                let cached = self.process_document(document_ir, uri, &rope, content_hash).await?;
+                let (cached, memo) = self.process_document(None, tree, reuse, uri, &rope, content_hash).await?;
+                if let Some(memo) = memo {
+                    self.ir_memos.insert(uri.clone(), (version, Arc::new(memo)));
+                }
 
                 // Detect embedded language regions asynchronously using hybrid rayon worker
                 // This approach provides 18-19x better throughput than synchronous detection
@@ -371,16 +432,21 @@ impl RholangBackend {
                 // - Parallel detection via rayon
                 let all_regions = detection_result.regions;
 
-                if !all_regions.is_empty() {
+                {
                     debug!("Registering {} virtual documents for {}", all_regions.len(), uri);
+                    // Registered even when empty, so regions that were edited away are dropped
                     let mut virtual_docs = self.virtual_docs.write().await;
                     virtual_docs.register_regions(uri, &all_regions);
+                    self.chunked_tokens.remove(uri);
 
-                    // Validate virtual documents and get diagnostics
-                    // Note: We don't publish diagnostics here; that's done in validate()
-                    let _virtual_diagnostics = virtual_docs.validate_all_for_parent(uri);
-                    debug!("Validated {} virtual documents for {}", all_regions.len(), uri);
+                    if !all_regions.is_empty() {
+                        // Validate virtual documents and get diagnostics
+                        // Note: We don't publish diagnostics here; that's done in validate()
+                        let _virtual_diagnostics = virtual_docs.validate_all_for_parent(uri);
+                        debug!("Validated {} virtual documents for {}", all_regions.len(), uri);
+                    }
                 }
+                self.announce_virtual_documents(uri).await;
 
                 // Collect contracts and calls (CPU-bound work without holding lock)
                 let mut contracts = Vec::new();
@@ -397,7 +463,17 @@ impl RholangBackend {
         }
     }
 
+    /// The IR memo of the version of an open document that `version` was
+    /// changed from, with the bytes the change touched.
+    async fn ir_reuse(&self, uri: &Url, version: i32) -> Option<(Arc<ConversionMemo>, DirtyRanges)> {
+        let document = self.documents_by_uri.get(uri).map(|entry| entry.value().clone())?;
+        let (since, dirty) = document.dirty_ranges(version).await?;
+        let memo = self.ir_memos.get(uri).filter(|entry| entry.0 == since).map(|entry| entry.1.clone())?;
+        Some((memo, dirty))
+    }
+
     /// Indexes a MeTTa file by parsing and creating a cached document
+    #[cfg(feature = "metta")]
     pub(super) async fn index_metta_file(
         &self,
         uri: &Url,
@@ -464,6 +540,8 @@ impl RholangBackend {
 
         let rope = Rope::from_str(text);
         let positions = Arc::new(HashMap::new());
+        let deprecations = Arc::new(DeprecatedDeclarations::default());
+        let registry_bindings = Arc::new(RegistryUriBindings::default());
 
         let cached_doc = CachedDocument {
             ir: placeholder_ir,
@@ -475,9 +553,12 @@ impl RholangBackend {
             symbol_table,
             inverted_index,
             version,
+            line_index: Arc::new(LineIndex::from_rope(&rope).with_encoding(self.capabilities.read().position_encoding)),
             text: rope,
             positions,
             symbol_index,
+            deprecations,
+            registry_bindings,
             content_hash,
         };
 
@@ -494,28 +575,128 @@ impl RholangBackend {
         Ok(cached_doc)
     }
 
-    /// Handles file system events by re-indexing changed .rho files that are not open.
+    /// Refuses MeTTa files in builds without the `metta` feature.
+    #[cfg(not(feature = "metta"))]
+    pub(super) async fn index_metta_file(
+        &self,
+        uri: &Url,
+        _text: &str,
+        _version: i32,
+        _content_hash: u64,
+    ) -> Result<CachedDocument, String> {
+        Err(format!("Cannot index {}: MeTTa support is not enabled in this build", uri))
+    }
+
+    /// Handles file system events by re-indexing changed .rho files that are
+    /// not open, and dropping deleted ones from the index.
     pub(super) async fn handle_file_change(&self, path: PathBuf) {
         if path.extension().map_or(false, |ext| ext == "rho") {
+            self.disk_index.invalidate(&path);
             if let Ok(uri) = Url::from_file_path(&path) {
-                // DashMap::contains_key is lock-free
-                if self.documents_by_uri.contains_key(&uri) {
+                if !path.exists() {
+                    self.remove_workspace_file(&uri).await;
+                    return;
+                }
+                // Open documents are indexed from their buffer, with its unsaved edits
+                if self.buffer_overlay.is_open(&uri) {
                     debug!("Skipping update for opened document: {}", uri);
                     return;
                 }
                 let text = std::fs::read_to_string(&path).unwrap_or_default();
-                match self.index_file(&uri, &text, 0, None).await {
-                    Ok(cached_doc) => {
-                        self.update_workspace_document(&uri, Arc::new(cached_doc)).await;
+                match self.index_disk_contents(&uri, &text).await {
+                    Ok(Some(_)) => {
                         self.link_symbols().await;
                         info!("Updated cache for file: {}", uri);
                     }
+                    Ok(None) => debug!("Document {} was opened while updating it", uri),
                     Err(e) => warn!("Failed to index file {}: {}", uri, e),
                 }
             }
         }
     }
 
+    /// Indexes `text`, read from disk for `uri`, and stores it in the
+    /// workspace index unless the document is open, whose buffer takes
+    /// precedence (see `crate::lsp::buffer_overlay`).
+    ///
+    /// Returns `None` if the open buffer was kept.
+    pub(super) async fn index_disk_contents(&self, uri: &Url, text: &str) -> Result<Option<Arc<CachedDocument>>, String> {
+        let Some(claim) = self.buffer_overlay.disk_claim(uri) else {
+            return Ok(None);
+        };
+        let cached_doc = Arc::new(self.index_file(uri, text, 0, None).await?);
+        if !self.buffer_overlay.holds(&claim) {
+            // Opened meanwhile, and maybe closed again; indexing has overwritten the buffer's symbols
+            self.reindex_open_buffer(uri).await;
+            return Ok(None);
+        }
+        self.update_workspace_document(uri, cached_doc.clone()).await;
+        Ok(Some(cached_doc))
+    }
+
+    /// Indexes `uri` again from its open buffer, if it is still open.
+    async fn reindex_open_buffer(&self, uri: &Url) {
+        let Some(document) = self.documents_by_uri.get(uri).map(|r| r.value().clone()) else {
+            // Closed meanwhile: its disk contents were indexed again on close
+            debug!("Not reindexing closed document {}", uri);
+            return;
+        };
+        let (text, version) = (document.text().await, document.version().await);
+        match self.index_file(uri, &text, version, None).await {
+            Ok(cached_doc) => self.update_workspace_document(uri, Arc::new(cached_doc)).await,
+            Err(e) => warn!("Failed to reindex open document {}: {}", uri, e),
+        }
+    }
+
+    /// Replaces the index entry of `uri`, just closed, by its disk contents,
+    /// dropping the unsaved edits of its buffer; removes it from the index if
+    /// it has no file.
+    pub(super) async fn restore_disk_index(&self, uri: &Url) {
+        let text = match uri.to_file_path() {
+            Ok(path) => {
+                self.disk_index.invalidate(&path);
+                std::fs::read_to_string(&path).ok()
+            }
+            Err(()) => None,
+        };
+        let Some(text) = text else {
+            self.remove_workspace_file(uri).await;
+            return;
+        };
+        match self.index_disk_contents(uri, &text).await {
+            Ok(Some(_)) => {
+                self.link_symbols().await;
+                debug!("Restored the disk contents of {} in the index", uri);
+            }
+            Ok(None) => debug!("Kept {}, opened again, in the index", uri),
+            Err(e) => warn!("Failed to index file {}: {}", uri, e),
+        }
+    }
+
+    /// Queues the `.rho` files of the library directories `paths` for indexing
+    /// after the workspace files, and records them as read-only libraries.
+    ///
+    /// Files under the workspace `root` are left to workspace indexing.
+    pub(super) async fn preindex_libraries(&self, paths: &[PathBuf], root: Option<&Path>) {
+        let files = library_files(paths, root);
+        info!("Queueing {} library file(s) from {} library path(s)", files.len(), paths.len());
+        for path in files {
+            let Ok(uri) = Url::from_file_path(&path) else { continue };
+            let text = match std::fs::read_to_string(&path) {
+                Ok(text) => text,
+                Err(e) => {
+                    warn!("Failed to read library file {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            self.libraries.insert(uri.clone());
+            let task = IndexingTask { uri: uri.clone(), text, priority: LIBRARY_INDEX_PRIORITY };
+            if let Err(e) = self.indexing_tx.send(task).await {
+                warn!("Failed to queue library file {}: {}", uri, e);
+            }
+        }
+    }
+
     /// Indexes all .rho files in the given directory (non-recursively).
     ///
     /// This version uses sequential processing. For parallel batch indexing of many files,
@@ -527,14 +708,12 @@ impl RholangBackend {
                     if entry.file_type().is_file() && entry.path().extension().map_or(false, |ext| ext == "rho") {
                         let uri = Url::from_file_path(entry.path()).expect("Failed to create URI from path");
                         // DashMap::contains_key is lock-free
-                        if !self.documents_by_uri.contains_key(&uri)
+                        if !self.buffer_overlay.is_open(&uri)
                             && !self.workspace.documents.contains_key(&uri) {
                             if let Ok(text) = std::fs::read_to_string(entry.path()) {
-                                match self.index_file(&uri, &text, 0, None).await {
-                                    Ok(cached_doc) => {
-                                        self.update_workspace_document(&uri, Arc::new(cached_doc)).await;
-                                        debug!("Indexed file: {}", uri);
-                                    }
+                                match self.index_disk_contents(&uri, &text).await {
+                                    Ok(Some(_)) => debug!("Indexed file: {}", uri),
+                                    Ok(None) => debug!("Skipping opened document: {}", uri),
                                     Err(e) => warn!("Failed to index file {}: {}", uri, e),
                                 }
                             }
@@ -578,20 +757,30 @@ impl RholangBackend {
 
         info!("Found {} .rho files to index in {:?}", paths.len(), dir);
 
-        // Get workspace state snapshot for filtering
+        // Get workspace state snapshot for filtering; open documents are indexed from their buffer
         // DashMap::iter() provides lock-free iteration
-        let existing_docs: Vec<Url> = self.documents_by_uri.iter().map(|entry| entry.key().clone()).collect();
+        let claims: HashMap<Url, DiskClaim> = paths
+            .iter()
+            .filter_map(|path| Url::from_file_path(path).ok())
+            .filter_map(|uri| self.buffer_overlay.disk_claim(&uri).map(|claim| (uri, claim)))
+            .collect();
+        let existing_docs: Vec<Url> = paths
+            .iter()
+            .filter_map(|path| Url::from_file_path(path).ok())
+            .filter(|uri| !claims.contains_key(uri))
+            .collect();
         let workspace_docs: Vec<Url> = self.workspace.documents.iter().map(|entry| entry.key().clone()).collect();
 
         // Phase 2: Parse and process files in parallel using Rayon
-        // CRITICAL: Wrap Rayon work in spawn_blocking to prevent blocking Tokio runtime
+        // CRITICAL: Run the Rayon work on the analysis pool to prevent blocking Tokio runtime
         // Lock and clone global_table for use in blocking task
         let global_table = Arc::new(self.workspace.global_table.read().await.clone());
         let global_index = self.workspace.global_index.clone();
         let version_counter = self.version_counter.clone();
         let rholang_symbols = Some(self.workspace.rholang_symbols.clone());
+        let encoding = self.capabilities.read().position_encoding;
 
-        let results: Vec<(Url, Result<CachedDocument, String>)> = tokio::task::spawn_blocking(move || {
+        let results: Vec<(Url, Result<CachedDocument, String>)> = self.analysis_pool.run(move || {
             paths
                 .par_iter()
                 .filter_map(|path| {
@@ -608,7 +797,6 @@ impl RholangBackend {
                             let tree = Arc::new(parse_code(&text));
                             let document_ir = parse_to_document_ir(&tree, &rope);
 
-                            use std::collections::hash_map::DefaultHasher;
                             let mut hasher = DefaultHasher::new();
                             text.hash(&mut hasher);
                             let content_hash = hasher.finish();
@@ -623,6 +811,7 @@ impl RholangBackend {
                                 global_index.clone(),
                                 &version_counter,
                                 rholang_symbols.clone(),
+                                encoding,
                             );
 
                             return Some((uri, result));
@@ -640,15 +829,17 @@ impl RholangBackend {
             results.len(), elapsed, results.len() as f64 / elapsed.as_secs_f64());
 
         // Phase 3: Batch insert into workspace using batched updates
-        let indexed_uris: Vec<Url> = results.iter()
-            .filter_map(|(uri, result)| if result.is_ok() { Some(uri.clone()) } else { None })
-            .collect();
-
+        let mut indexed_uris: Vec<Url> = Vec::with_capacity(results.len());
         for (uri, result) in results {
             match result {
+                Ok(_) if !claims.get(&uri).is_some_and(|claim| self.buffer_overlay.holds(claim)) => {
+                    // Opened while it was parsed; its buffer takes precedence
+                    self.reindex_open_buffer(&uri).await;
+                }
                 Ok(cached_doc) => {
                     self.update_workspace_document(&uri, Arc::new(cached_doc)).await;
                     debug!("Indexed file: {}", uri);
+                    indexed_uris.push(uri);
                 }
                 Err(e) => warn!("Failed to index file {}: {}", uri, e),
             }
@@ -676,10 +867,13 @@ impl RholangBackend {
                         detection_result.regions.len(), uri);
                     let mut virtual_docs = self.virtual_docs.write().await;
                     virtual_docs.register_regions(uri, &detection_result.regions);
+                    self.chunked_tokens.remove(uri);
 
                     // Validate virtual documents
                     let _virtual_diagnostics = virtual_docs.validate_all_for_parent(uri);
                     debug!("Validated {} virtual documents for {}", detection_result.regions.len(), uri);
+                    drop(virtual_docs);
+                    self.announce_virtual_documents(uri).await;
                 }
             }
         }
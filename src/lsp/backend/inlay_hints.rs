@@ -0,0 +1,32 @@
+//! Inlay hints for the LSP backend
+//!
+//! Computes the hints of `crate::lsp::inlay_hints` from the cached IR of a
+//! document, matching its sends against every indexed contract.
+
+use std::sync::Arc;
+
+use tower_lsp::lsp_types::{InlayHint, Range, Url};
+use tracing::debug;
+
+use crate::ir::rholang_node::RholangNode;
+use crate::lsp::inlay_hints::inlay_hints;
+use crate::lsp::models::DocumentLanguage;
+
+use super::state::RholangBackend;
+
+impl RholangBackend {
+    /// Inlay hints of `uri` within `range`, or `None` for unknown and MeTTa documents.
+    pub(super) fn document_inlay_hints(&self, uri: &Url, range: Range) -> Option<Vec<InlayHint>> {
+        let doc = self.workspace.documents.get(uri).map(|entry| entry.value().clone())?;
+        if doc.language == DocumentLanguage::Metta {
+            return None;
+        }
+        let config = *self.inlay_hints.read();
+        let contracts: Vec<Arc<RholangNode>> =
+            self.indexed_contracts().into_iter().map(|(_, contract)| contract).collect();
+        let mut hints = inlay_hints(&config, &doc.ir, &doc.positions, &doc.text, &doc.line_index, &contracts, range);
+        hints.extend(self.cost_inlay_hints(uri, range));
+        debug!("{} inlay hint(s) in {} at {:?}", hints.len(), uri, range);
+        Some(hints)
+    }
+}
@@ -0,0 +1,58 @@
+//! Tree-Sitter locals navigation for the LSP backend
+//!
+//! When the IR of a Rholang document is degraded by syntax errors, highlights
+//! and go-to-definition fall back to `locals.scm` (see
+//! `crate::lsp::locals_fallback`) once the symbol-based lookup finds nothing.
+
+use tower_lsp::lsp_types::{
+    DocumentHighlight, DocumentHighlightKind, GotoDefinitionResponse, Location, Position as LspPosition, Url,
+};
+use tracing::debug;
+
+use crate::lsp::locals_fallback::{is_degraded, local_occurrences, LocalOccurrences};
+use crate::lsp::models::DocumentLanguage;
+
+use super::state::RholangBackend;
+
+impl RholangBackend {
+    /// Occurrences of the name at `position` from `locals.scm`, if the IR of `uri` is degraded.
+    fn degraded_locals(&self, uri: &Url, position: LspPosition) -> Option<LocalOccurrences> {
+        let doc = self.workspace.documents.get(uri).map(|entry| entry.value().clone())?;
+        if doc.language == DocumentLanguage::Metta || !is_degraded(&doc.ir, &doc.positions, doc.text.len_bytes()) {
+            return None;
+        }
+        let occurrences = local_occurrences(&doc.tree, &doc.text.to_string(), position)?;
+        debug!(
+            "IR of {} is degraded; {} occurrence(s) of `{}` from locals",
+            uri,
+            occurrences.occurrences.len(),
+            occurrences.name
+        );
+        Some(occurrences)
+    }
+
+    /// Highlights of the name at `position` from `locals.scm`, the binding one as a write.
+    pub(super) fn locals_highlights(&self, uri: &Url, position: LspPosition) -> Option<Vec<DocumentHighlight>> {
+        let locals = self.degraded_locals(uri, position)?;
+        Some(
+            locals
+                .occurrences
+                .into_iter()
+                .map(|range| DocumentHighlight {
+                    range,
+                    kind: Some(if Some(range) == locals.definition {
+                        DocumentHighlightKind::WRITE
+                    } else {
+                        DocumentHighlightKind::READ
+                    }),
+                })
+                .collect(),
+        )
+    }
+
+    /// The binding of the name at `position` from `locals.scm`.
+    pub(super) fn locals_definition(&self, uri: &Url, position: LspPosition) -> Option<GotoDefinitionResponse> {
+        let range = self.degraded_locals(uri, position)?.definition?;
+        Some(GotoDefinitionResponse::Scalar(Location { uri: uri.clone(), range }))
+    }
+}
@@ -48,7 +48,7 @@
 use std::sync::Arc;
 use tower_lsp::lsp_types::{
     DocumentHighlight, DocumentHighlightKind, GotoDefinitionResponse, Hover, HoverContents,
-    Location, MarkupContent, MarkupKind, Position as LspPosition, Range, TextEdit,
+    Location, MarkupContent, MarkupKind, Position as LspPosition, Range, TextEdit, Url,
     WorkspaceEdit,
 };
 use tracing::{debug, error};
@@ -56,7 +56,7 @@ use tracing::{debug, error};
 use crate::ir::metta_node::MettaNode;
 use crate::ir::semantic_node::{Position as IrPosition, SemanticNode};
 use crate::ir::symbol_resolution::global::AsyncGlobalVirtualSymbolResolver;
-use crate::language_regions::VirtualDocument;
+use crate::language_regions::{VirtualDocument, VIRTUAL_URI_SCHEME};
 use crate::lsp::models::CachedDocument;
 
 use super::state::RholangBackend;
@@ -65,6 +65,15 @@ use super::utils::SemanticTokensBuilder;
 type LspResult<T> = Result<T, tower_lsp::jsonrpc::Error>;
 
 impl RholangBackend {
+    /// Whether `uri` names a virtual document rather than a Rholang file.
+    ///
+    /// Editors may open virtual documents directly (by their fragment URI or
+    /// a `rholang-virtual:` alias); they are served from the registry and
+    /// never indexed as Rholang.
+    pub(super) async fn is_virtual_document_uri(&self, uri: &Url) -> bool {
+        uri.scheme() == VIRTUAL_URI_SCHEME || self.virtual_docs.read().await.is_virtual(uri)
+    }
+
     /// Provides hover information for MeTTa files
     pub(super) async fn hover_metta(
         &self,
@@ -382,11 +391,14 @@ impl RholangBackend {
     }
 
     /// Document highlights for MeTTa symbols
+    ///
+    /// Ranges are mapped to the parent document unless `in_virtual_coordinates`
+    /// is set, as for a virtual document the editor opened directly.
     pub(super) async fn document_highlight_metta(
         &self,
         virtual_doc: &Arc<VirtualDocument>,
         virtual_position: LspPosition,
-        _parent_position: LspPosition,
+        in_virtual_coordinates: bool,
     ) -> LspResult<Option<Vec<DocumentHighlight>>> {
         use crate::ir::transforms::metta_symbol_table_builder::*;
 
@@ -501,7 +513,11 @@ impl RholangBackend {
         let highlights: Vec<DocumentHighlight> = references
             .iter()
             .map(|occ| {
-                let parent_range = virtual_doc.map_range_to_parent(occ.range);
+                let parent_range = if in_virtual_coordinates {
+                    occ.range
+                } else {
+                    virtual_doc.map_range_to_parent(occ.range)
+                };
                 debug!(
                     "  Mapping MeTTa highlight '{}': virtual L{}:C{}-{} -> parent L{}:C{}-{}",
                     occ.name,
@@ -954,11 +970,16 @@ impl RholangBackend {
     }
 
     /// Find all references to a MeTTa symbol
+    ///
+    /// Locations are mapped to the parent document, unless `direct_uri` is
+    /// given: the virtual document was opened directly under that URI and
+    /// locations stay in virtual coordinates.
     pub(super) async fn references_metta(
         &self,
         virtual_doc: &Arc<VirtualDocument>,
         virtual_position: LspPosition,
         include_declaration: bool,
+        direct_uri: Option<&Url>,
     ) -> LspResult<Option<Vec<Location>>> {
         // Get symbol table
         let symbol_table = match virtual_doc.get_or_build_symbol_table() {
@@ -992,12 +1013,15 @@ impl RholangBackend {
                     !occ.is_definition
                 }
             })
-            .map(|occ| {
-                let parent_range = virtual_doc.map_range_to_parent(occ.range);
-                Location {
+            .map(|occ| match direct_uri {
+                Some(uri) => Location {
+                    uri: uri.clone(),
+                    range: occ.range,
+                },
+                None => Location {
                     uri: virtual_doc.parent_uri.clone(),
-                    range: parent_range,
-                }
+                    range: virtual_doc.map_range_to_parent(occ.range),
+                },
             })
             .collect();
 
@@ -0,0 +1,172 @@
+//! Moving contracts between files for the LSP backend
+//!
+//! Offers the "Move contract to file..." refactoring and runs
+//! `rholang.moveContract` (see `crate::lsp::move_contract`), asking the user
+//! for the target file when the command names none.
+
+use std::path::Path;
+
+use serde_json::Value;
+use tower_lsp::jsonrpc;
+use tower_lsp::lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, Command, MessageActionItem, MessageType,
+    Position as LspPosition, Url,
+};
+use tracing::{debug, info, warn};
+
+use crate::i18n::tr;
+use crate::lsp::models::DocumentLanguage;
+use crate::lsp::move_contract::{
+    find_movable_contract, move_contract_edit, MoveContractArgs, MoveTarget, MOVE_CONTRACT_COMMAND,
+};
+
+use super::commands::command_error;
+use super::state::RholangBackend;
+
+/// Code action kind of the move refactoring
+pub(super) const REFACTOR_MOVE: CodeActionKind = CodeActionKind::new("refactor.move");
+
+/// Most files of the directory offered as move targets
+const MAX_TARGET_CHOICES: usize = 8;
+
+fn file_name(uri: &Url) -> Option<String> {
+    let path = uri.to_file_path().ok()?;
+    Some(path.file_name()?.to_string_lossy().into_owned())
+}
+
+impl RholangBackend {
+    /// "Move contract to file..." for the contract at the start of the requested range.
+    pub(super) fn move_contract_actions(&self, params: &CodeActionParams) -> Vec<CodeActionOrCommand> {
+        let uri = &params.text_document.uri;
+        let Some(doc) = self.workspace.documents.get(uri).map(|entry| entry.value().clone()) else {
+            return Vec::new();
+        };
+        if doc.language == DocumentLanguage::Metta {
+            return Vec::new();
+        }
+        let position = params.range.start;
+        let Some(contract) = find_movable_contract(&doc.ir, &doc.positions, &doc.text, &doc.line_index, position) else {
+            return Vec::new();
+        };
+
+        let title = tr("action.move-contract", &[("name", &contract.name)]);
+        let args = MoveContractArgs { uri: uri.clone(), position, target: None };
+        vec![CodeActionOrCommand::CodeAction(CodeAction {
+            title: title.clone(),
+            kind: Some(REFACTOR_MOVE),
+            command: Some(Command {
+                title,
+                command: MOVE_CONTRACT_COMMAND.to_string(),
+                arguments: serde_json::to_value(args).ok().map(|args| vec![args]),
+            }),
+            ..Default::default()
+        })]
+    }
+
+    /// Handles `rholang.moveContract`: moves the contract at a position to
+    /// another file with a single workspace edit.
+    pub(super) async fn move_contract_command(&self, arguments: Vec<Value>) -> jsonrpc::Result<Option<Value>> {
+        let args: MoveContractArgs = arguments
+            .into_iter()
+            .next()
+            .and_then(|arg| serde_json::from_value(arg).ok())
+            .ok_or_else(|| jsonrpc::Error::invalid_params("Expected { uri, position, target? } argument"))?;
+
+        let doc = self
+            .workspace
+            .documents
+            .get(&args.uri)
+            .map(|entry| entry.value().clone())
+            .ok_or_else(|| jsonrpc::Error::invalid_params(format!("Document not found: {}", args.uri)))?;
+        let contract = find_movable_contract(&doc.ir, &doc.positions, &doc.text, &doc.line_index, args.position).ok_or_else(|| {
+            jsonrpc::Error::invalid_params(format!("No movable contract at {:?} in {}", args.position, args.uri))
+        })?;
+
+        let target = match args.target {
+            Some(target) => target,
+            None => match self.prompt_move_target(&args.uri, &contract.name).await {
+                Some(target) => target,
+                None => {
+                    debug!("Moving contract {} cancelled", contract.name);
+                    return Ok(None);
+                }
+            },
+        };
+        if target == args.uri {
+            return Err(jsonrpc::Error::invalid_params("The contract is already in the target file"));
+        }
+
+        let target = self.move_target(target).await;
+        info!("Moving contract {} from {} to {}", contract.name, args.uri, target.uri());
+        let edit = move_contract_edit(&args.uri, &contract, &target);
+        let response = self
+            .client
+            .apply_edit(edit.clone())
+            .await
+            .map_err(|e| command_error(jsonrpc::ErrorCode::InternalError, format!("Failed to apply edit: {}", e)))?;
+        if let Some(reason) = &response.failure_reason {
+            warn!("Client did not move contract {}: {}", contract.name, reason);
+        }
+
+        Ok(Some(serde_json::json!({
+            "target": target.uri(),
+            "applied": response.applied,
+            "edit": edit,
+        })))
+    }
+
+    /// An existing file to append to, or a file to create.
+    async fn move_target(&self, uri: Url) -> MoveTarget {
+        let Some(text) = self.document_text(&uri).await else {
+            return MoveTarget::NewFile(uri);
+        };
+        let last_line = text.rsplit('\n').next().unwrap_or("");
+        let encoding = self.capabilities.read().position_encoding;
+        let end = LspPosition::new(text.matches('\n').count() as u32, encoding.units_str(last_line) as u32);
+        MoveTarget::Existing { uri, end, empty: text.trim().is_empty() }
+    }
+
+    /// Asks the user where to move contract `name` of `source`: to a new
+    /// `<name>.rho` next to it or to another Rholang file of its directory.
+    async fn prompt_move_target(&self, source: &Url, name: &str) -> Option<Url> {
+        let path = source.to_file_path().ok()?;
+        let dir = path.parent()?;
+        let new_file = Url::from_file_path(dir.join(format!("{}.rho", name))).ok()?;
+
+        let mut siblings: Vec<Url> = self
+            .workspace
+            .documents
+            .iter()
+            .map(|entry| entry.key().clone())
+            .filter(|uri| uri != source && *uri != new_file)
+            .filter(|uri| {
+                uri.to_file_path().is_ok_and(|path| {
+                    path.parent() == Some(dir) && path.extension() == Some(Path::new("rho").as_os_str())
+                })
+            })
+            .collect();
+        siblings.sort();
+        siblings.truncate(MAX_TARGET_CHOICES);
+
+        let new_file_title = match self.document_text(&new_file).await {
+            Some(_) => file_name(&new_file)?,
+            None => tr("action.move-contract-new-file", &[("file", &file_name(&new_file)?)]),
+        };
+        let mut choices = vec![(new_file_title, new_file)];
+        choices.extend(siblings.into_iter().filter_map(|uri| Some((file_name(&uri)?, uri))));
+
+        let actions = choices
+            .iter()
+            .map(|(title, _)| MessageActionItem { title: title.clone(), properties: Default::default() })
+            .collect();
+        let message = format!("Move contract '{}' to:", name);
+        match self.client.show_message_request(MessageType::INFO, message, Some(actions)).await {
+            Ok(Some(item)) => choices.into_iter().find(|(title, _)| *title == item.title).map(|(_, uri)| uri),
+            Ok(None) => None,
+            Err(e) => {
+                warn!("Move target prompt failed: {}", e);
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,36 @@
+//! Escape paths of `new` names in hovers
+//!
+//! Appends the escape paths computed by `crate::lsp::name_escapes` to the
+//! hover of a `new` name that is handed out of its scope.
+
+use tower_lsp::lsp_types::{Hover, HoverContents, MarkupContent, MarkupKind, Position as LspPosition, Url};
+
+use crate::lsp::name_escapes::{escapes_at, escapes_markdown};
+
+use super::state::RholangBackend;
+
+impl RholangBackend {
+    /// Adds the escape paths of the `new` name at `position` in `uri` to `hover`.
+    pub(super) fn with_name_escapes(&self, uri: &Url, position: LspPosition, hover: Option<Hover>) -> Option<Hover> {
+        let Some(doc) = self.workspace.documents.get(uri).map(|entry| entry.value().clone()) else {
+            return hover;
+        };
+        let Some(name) = escapes_at(uri, &doc.ir, &doc.positions, &doc.text, &doc.line_index, position) else {
+            return hover;
+        };
+        let section = escapes_markdown(&name);
+
+        match hover {
+            Some(Hover { contents: HoverContents::Markup(mut markup), range }) => {
+                markup.value.push_str("\n\n---\n\n");
+                markup.value.push_str(&section);
+                Some(Hover { contents: HoverContents::Markup(markup), range })
+            }
+            Some(other) => Some(other),
+            None => Some(Hover {
+                contents: HoverContents::Markup(MarkupContent { kind: MarkupKind::Markdown, value: section }),
+                range: None,
+            }),
+        }
+    }
+}
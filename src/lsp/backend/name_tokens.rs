@@ -0,0 +1,34 @@
+//! Semantic tokens for Rholang names in the LSP backend
+//!
+//! Emits a token per resolved name, with the modifiers computed by
+//! `crate::lsp::semantic_modifiers`.
+
+use tower_lsp::lsp_types::Url;
+use tracing::debug;
+
+use crate::lsp::models::DocumentLanguage;
+use crate::lsp::semantic_modifiers::classify_names;
+
+use super::state::RholangBackend;
+use super::utils::SemanticTokensBuilder;
+
+impl RholangBackend {
+    /// Adds a semantic token for each name of the indexed document `uri`.
+    ///
+    /// Returns the number of tokens added.
+    pub(super) fn add_name_semantic_tokens(&self, builder: &mut SemanticTokensBuilder, uri: &Url) -> usize {
+        let Some(doc) = self.workspace.documents.get(uri).map(|entry| entry.value().clone()) else {
+            return 0;
+        };
+        if doc.language != DocumentLanguage::Rholang {
+            return 0;
+        }
+
+        let tokens = classify_names(&doc.ir, &doc.positions, &doc.text, &doc.line_index);
+        debug!("Classified {} names in {}", tokens.len(), uri);
+        for token in &tokens {
+            builder.push_with_modifiers(token.line, token.start, token.length, token.token_type, token.modifiers);
+        }
+        tokens.len()
+    }
+}
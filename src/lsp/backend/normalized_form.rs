@@ -0,0 +1,80 @@
+//! Normalized form of top-level processes in hovers
+//!
+//! Keeps the forms produced while the embedded interpreter validates a
+//! document (see `crate::lsp::normalized_form`) and appends the one of the
+//! process under the cursor to the hover.
+
+use std::sync::atomic::Ordering;
+
+use rholang_parser::ast::AnnProc;
+use rholang_parser::RholangParser;
+use tower_lsp::lsp_types::{Diagnostic, Hover, HoverContents, MarkupContent, MarkupKind, Position as LspPosition, Url};
+use tracing::debug;
+
+use crate::lsp::normalized_form::{normalized_form_markdown, NormalizedForm, NormalizedForms};
+use crate::lsp::semantic_validator::SemanticValidator;
+
+use super::state::RholangBackend;
+
+impl RholangBackend {
+    /// Validates a top-level process, adding its normalized form to `forms`
+    /// when normalized forms are on and it is valid.
+    pub(super) fn validate_process<'a>(
+        &self,
+        validator: &SemanticValidator,
+        ast: AnnProc<'a>,
+        parser: &'a RholangParser<'a>,
+        forms: &mut Vec<NormalizedForm>,
+    ) -> Vec<Diagnostic> {
+        if !self.normalized_form.load(Ordering::Relaxed) {
+            return validator.validate_parsed(ast, parser);
+        }
+        match validator.normalize_parsed(ast, parser) {
+            Ok(form) => {
+                forms.push(form);
+                Vec::new()
+            }
+            Err(diagnostics) => diagnostics,
+        }
+    }
+
+    /// Replaces the normalized forms of `uri` with those of `version`.
+    pub(super) fn store_normalized_forms(&self, uri: &Url, version: i32, forms: Vec<NormalizedForm>) {
+        if !self.normalized_form.load(Ordering::Relaxed) {
+            return;
+        }
+        debug!("{} normalized form(s) for {} (version {})", forms.len(), uri, version);
+        self.normalized_forms.insert(uri.clone(), NormalizedForms { version, forms });
+    }
+
+    /// Adds the normalized form of the top-level process at `position` in
+    /// `uri` to `hover`, if it was computed for the current text.
+    pub(super) fn with_normalized_form(&self, uri: &Url, position: LspPosition, hover: Option<Hover>) -> Option<Hover> {
+        let Some(doc) = self.workspace.documents.get(uri).map(|entry| entry.value().clone()) else {
+            return hover;
+        };
+        let Some(forms) = self.normalized_forms.get(uri) else {
+            return hover;
+        };
+        if forms.version != doc.version {
+            return hover;
+        }
+        let Some(form) = forms.at(position) else {
+            return hover;
+        };
+        let section = normalized_form_markdown(form);
+
+        match hover {
+            Some(Hover { contents: HoverContents::Markup(mut markup), range }) => {
+                markup.value.push_str("\n\n---\n\n");
+                markup.value.push_str(&section);
+                Some(Hover { contents: HoverContents::Markup(markup), range })
+            }
+            Some(other) => Some(other),
+            None => Some(Hover {
+                contents: HoverContents::Markup(MarkupContent { kind: MarkupKind::Markdown, value: section }),
+                range: None,
+            }),
+        }
+    }
+}
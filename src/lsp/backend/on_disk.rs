@@ -0,0 +1,79 @@
+//! On-demand loading of workspace files that are not indexed
+//!
+//! Supplements `workspace/symbol` and `textDocument/references` with
+//! index-only artifacts (see `crate::lsp::disk_index`) for `.rho` files under
+//! the workspace root that are neither open nor in the workspace index yet.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use tower_lsp::lsp_types::{Location, Position as LspPosition, Url};
+use tracing::{debug, warn};
+use walkdir::WalkDir;
+
+use crate::ir::symbol_table::SymbolType;
+use crate::lsp::disk_index::DiskArtifact;
+
+use super::state::RholangBackend;
+
+impl RholangBackend {
+    /// Artifacts for the unindexed `.rho` files under the workspace root.
+    ///
+    /// Files are parsed on a blocking thread; unchanged files are served from
+    /// the disk index's LRU.
+    pub(super) async fn unindexed_artifacts(&self) -> Vec<Arc<DiskArtifact>> {
+        let Some(root) = self.root_dir.read().await.clone() else {
+            return Vec::new();
+        };
+        let indexed: HashSet<Url> = self
+            .workspace
+            .documents
+            .iter()
+            .map(|entry| entry.key().clone())
+            .chain(self.documents_by_uri.iter().map(|entry| entry.key().clone()))
+            .collect();
+
+        let disk_index = self.disk_index.clone();
+        let loaded = self.analysis_pool.run(move || {
+            WalkDir::new(&root)
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter(|e| e.file_type().is_file() && e.path().extension().map_or(false, |ext| ext == "rho"))
+                .filter(|e| Url::from_file_path(e.path()).map_or(false, |uri| !indexed.contains(&uri)))
+                .filter_map(|e| disk_index.load(e.path()))
+                .collect::<Vec<_>>()
+        })
+        .await;
+
+        match loaded {
+            Ok(artifacts) => {
+                if !artifacts.is_empty() {
+                    debug!("Using {} index-only artifacts for unindexed files", artifacts.len());
+                }
+                artifacts
+            }
+            Err(e) => {
+                warn!("Failed to load unindexed workspace files: {}", e);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Sends to the contract at `position` from unindexed workspace files.
+    ///
+    /// Only contracts are matched: other symbols cannot be referenced from
+    /// another file.
+    pub(super) async fn unindexed_references(&self, uri: &Url, position: LspPosition) -> Vec<Location> {
+        let Some(symbol) = self.get_symbol_at_position(uri, position).await else {
+            return Vec::new();
+        };
+        if !matches!(symbol.symbol_type, SymbolType::Contract) {
+            return Vec::new();
+        }
+        self.unindexed_artifacts()
+            .await
+            .iter()
+            .flat_map(|artifact| artifact.references(&symbol.name))
+            .collect()
+    }
+}
@@ -0,0 +1,24 @@
+//! Parameter documentation warnings for the LSP backend
+//!
+//! Reports `@param` directives that do not match the formals of the
+//! contract they document (see `crate::lsp::param_docs`).
+
+use tower_lsp::lsp_types::{Diagnostic, Url};
+use tracing::debug;
+
+use crate::lsp::param_docs::find_param_doc_mismatches;
+
+use super::state::RholangBackend;
+
+impl RholangBackend {
+    /// Parameter documentation warnings for the indexed document `uri`.
+    pub(super) fn param_doc_diagnostics(&self, uri: &Url) -> Vec<Diagnostic> {
+        let Some(doc) = self.workspace.documents.get(uri).map(|entry| entry.value().clone()) else {
+            return Vec::new();
+        };
+
+        let mismatches = find_param_doc_mismatches(&doc.ir, &doc.positions, &doc.text, &doc.line_index);
+        debug!("Found {} @param mismatches in {}", mismatches.len(), uri);
+        mismatches.iter().map(|mismatch| mismatch.to_diagnostic()).collect()
+    }
+}
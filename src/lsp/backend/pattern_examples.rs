@@ -0,0 +1,57 @@
+//! Match pattern examples for the LSP backend
+//!
+//! Appends example matching values (see `crate::lsp::pattern_examples`) to
+//! the hover of a `match` case pattern.
+
+use std::sync::Arc;
+
+use tower_lsp::lsp_types::{Hover, HoverContents, MarkupContent, MarkupKind, Position as LspPosition, Url};
+use tracing::debug;
+
+use crate::ir::rholang_node::Position as IrPosition;
+use crate::lsp::line_index::lsp_range;
+use crate::lsp::pattern_examples::{case_pattern_at, examples_markdown};
+
+use super::state::RholangBackend;
+
+impl RholangBackend {
+    /// Adds example values to `hover` when `position` is on a case pattern of a `match` in `uri`.
+    pub(super) fn with_pattern_examples(&self, uri: &Url, position: LspPosition, hover: Option<Hover>) -> Option<Hover> {
+        let Some(doc) = self.workspace.documents.get(uri).map(|entry| entry.value().clone()) else {
+            return hover;
+        };
+        let line = position.line as usize;
+        let (Some(byte), Some(line_start)) =
+            (doc.line_index.offset(line, position.character as usize, &doc.text), doc.line_index.line_start(line))
+        else {
+            return hover;
+        };
+        let ir_position = IrPosition { row: line, column: byte - line_start, byte };
+        let Some(pattern) = case_pattern_at(&doc.ir, &doc.positions, ir_position) else {
+            return hover;
+        };
+        let Some(section) = examples_markdown(&pattern) else {
+            return hover;
+        };
+        debug!("Adding match pattern examples to hover at {}:{:?}", uri, position);
+
+        match hover {
+            Some(Hover { contents: HoverContents::Markup(mut markup), range }) => {
+                markup.value.push_str("\n\n---\n\n");
+                markup.value.push_str(&section);
+                Some(Hover { contents: HoverContents::Markup(markup), range })
+            }
+            Some(other) => Some(other),
+            None => {
+                let range = doc
+                    .positions
+                    .get(&(Arc::as_ptr(&pattern) as usize))
+                    .map(|span| lsp_range(&doc.text, &doc.line_index, span));
+                Some(Hover {
+                    contents: HoverContents::Markup(MarkupContent { kind: MarkupKind::Markdown, value: section }),
+                    range,
+                })
+            }
+        }
+    }
+}
@@ -5,13 +5,18 @@
 
 use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use futures::StreamExt;
 use tokio_stream::wrappers::ReceiverStream;
 use tracing::{debug, error, info, trace};
 
+use crate::deterministic;
+use crate::lsp::diagnostic_registry;
+use crate::lsp::diagnostics_publisher::{DiagnosticsPublisher, PendingDiagnostics};
+
 use super::state::{DiagnosticUpdate, DocumentChangeEvent, IndexingTask, RholangBackend};
+use super::validation::Publication;
 use super::streams::{self, BackendEvent, StreamExt as CustomStreamExt};
 
 impl RholangBackend {
@@ -94,9 +99,8 @@ impl RholangBackend {
     /// This replaces the imperative debouncer with a declarative stream that:
     /// - Groups events by URI
     /// - Debounces each URI independently with 100ms
-    /// - Automatically cancels previous validations (via manual cancellation tokens)
-    /// - Processes validations concurrently with 10-second timeout
-    /// - Provides timeout protection against stuck validations
+    /// - Validates through `run_validation`, which cancels the validation of
+    ///   older versions and times out stuck validations
     pub(super) fn spawn_reactive_document_debouncer(
         backend: RholangBackend,
         doc_change_rx: tokio::sync::mpsc::Receiver<DocumentChangeEvent>,
@@ -120,8 +124,7 @@ impl RholangBackend {
             );
 
             // Per-URI debounce state
-            let mut uri_debouncers: HashMap<tower_lsp::lsp_types::Url, tokio::time::Instant> =
-                HashMap::new();
+            let mut uri_debouncers: HashMap<tower_lsp::lsp_types::Url, Instant> = HashMap::new();
             let debounce_duration = Duration::from_millis(100);
 
             // Manual debounce implementation with per-URI tracking
@@ -133,12 +136,12 @@ impl RholangBackend {
                 tokio::select! {
                     Some(event) = reactive_stream.next() => {
                         // Store event and update timestamp
-                        uri_debouncers.insert(event.uri.clone(), tokio::time::Instant::now());
+                        uri_debouncers.insert(event.uri.clone(), deterministic::now());
                         pending_events.insert(event.uri.clone(), event);
                     }
-                    _ = tokio::time::sleep(Duration::from_millis(50)) => {
+                    _ = deterministic::sleep(Duration::from_millis(50)) => {
                         // Check which URIs are ready to process
-                        let now = tokio::time::Instant::now();
+                        let now = deterministic::now();
                         let mut ready_uris = Vec::new();
 
                         for (uri, timestamp) in &uri_debouncers {
@@ -146,48 +149,17 @@ impl RholangBackend {
                                 ready_uris.push(uri.clone());
                             }
                         }
+                        if deterministic::enabled() {
+                            ready_uris.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+                        }
 
                         // Process ready events
                         for uri in ready_uris {
                             uri_debouncers.remove(&uri);
                             if let Some(event) = pending_events.remove(&uri) {
-                                // Cancel previous validation for this URI
-                                if let Some(cancel_tx) = backend.validation_cancel.lock().await.remove(&uri) {
-                                    let _ = cancel_tx.send(());
-                                    trace!("Cancelled previous validation for {}", uri);
-                                }
-
-                                // Create new cancellation token
-                                let (cancel_tx, cancel_rx) = tokio::sync::oneshot::channel();
-                                backend.validation_cancel.lock().await.insert(uri.clone(), cancel_tx);
-
-                                // Spawn validation with timeout
-                                let backend_clone = backend.clone();
-                                let uri_clone = uri.clone();
-                                let text_clone = event.text.clone();
-                                let version_clone = event.version;
+                                let backend = backend.clone();
                                 tokio::spawn(async move {
-                                    tokio::select! {
-                                        result = tokio::time::timeout(
-                                            Duration::from_secs(10),
-                                            backend_clone.validate(event.document.clone(), &text_clone, event.version)
-                                        ) => {
-                                            match result {
-                                                Ok(Ok(diagnostics)) => {
-                                                    trace!("Validation completed for {}", uri_clone);
-                                                    // Publish diagnostics to client
-                                                    if event.document.version().await == version_clone {
-                                                        backend_clone.client.publish_diagnostics(uri_clone.clone(), diagnostics, Some(version_clone)).await;
-                                                    }
-                                                }
-                                                Ok(Err(e)) => error!("Validation failed for {}: {}", uri_clone, e),
-                                                Err(_) => error!("Validation timeout for {}", uri_clone),
-                                            }
-                                        }
-                                        _ = cancel_rx => {
-                                            debug!("Validation cancelled for {}", uri_clone);
-                                        }
-                                    }
+                                    backend.run_validation(uri, event.document, event.text, event.version, Publication::Typing).await;
                                 });
                             }
                         }
@@ -262,10 +234,12 @@ impl RholangBackend {
                 debug!("Processing indexing batch of {} tasks", queue.len());
 
                 // Get total from indexing state if this is first batch
+                // (files resumed from a checkpoint are completed already)
                 if total_files == 0 {
-                    if let crate::lsp::models::IndexingState::InProgress { total, .. } =
+                    if let crate::lsp::models::IndexingState::InProgress { total, completed } =
                         *backend.workspace.indexing_state.read().await {
                         total_files = total;
+                        files_completed = completed;
                     }
                 }
 
@@ -278,9 +252,13 @@ impl RholangBackend {
                         break;
                     }
 
-                    match backend.index_file(&task.uri, &task.text, 0, None).await {
-                        Ok(cached_doc) => {
-                            backend.update_workspace_document(&task.uri, std::sync::Arc::new(cached_doc)).await;
+                    // Open documents keep the index entry of their buffer, with its unsaved edits
+                    match backend.index_disk_contents(&task.uri, &task.text).await {
+                        Ok(indexed) => {
+                            match indexed {
+                                Some(cached_doc) => backend.checkpoint_indexed(&task.uri, &cached_doc),
+                                None => debug!("Keeping the open buffer of {} in the index", task.uri),
+                            }
                             files_completed += 1;
 
                             // Phase 2: Update indexing state and send progress
@@ -295,7 +273,7 @@ impl RholangBackend {
 
                                 // Send progress notification every 10 files or at completion
                                 if files_completed % 10 == 0 || files_completed == total_files {
-                                    let percentage = ((files_completed as f64 / total_files as f64) * 100.0) as u32;
+                                    let percentage = crate::lsp::index_checkpoint::percentage(files_completed, total_files);
                                     backend.client.send_notification::<tower_lsp::lsp_types::notification::Progress>(
                                         tower_lsp::lsp_types::ProgressParams {
                                             token: tower_lsp::lsp_types::NumberOrString::String("workspace-indexing".to_string()),
@@ -342,6 +320,8 @@ impl RholangBackend {
                     ).await;
 
                     info!("Workspace indexing complete: {} files indexed", files_completed);
+                    backend.compact_checkpoints();
+                    backend.advance_readiness(crate::lsp::readiness::ReadinessStage::IndexReady).await;
 
                     // Reset counters for next indexing cycle (e.g., workspace refresh)
                     files_completed = 0;
@@ -407,7 +387,7 @@ impl RholangBackend {
                     }
                     BackendEvent::IndexingTask(task) => {
                         debug!("Processing indexing task: {}", task.uri);
-                        if let Err(e) = backend.index_file(&task.uri, &task.text, 0, None).await {
+                        if let Err(e) = backend.index_disk_contents(&task.uri, &task.text).await {
                             error!("Failed to index {}: {}", task.uri, e);
                         }
                     }
@@ -482,98 +462,114 @@ impl RholangBackend {
     ///
     /// This function creates a background task that batches diagnostic updates before
     /// publishing them to the LSP client. This reduces LSP protocol overhead and
-    /// prevents UI flicker during rapid typing.
+    /// prevents UI flicker during rapid typing or cross-file revalidation.
     ///
     /// Features:
-    /// - Batches diagnostics with 150ms timeout window
-    /// - Deduplicates: keeps only the latest diagnostics per URI
-    /// - Version checking: only publishes if document version matches
-    /// - Safe: All diagnostic updates are eventually published, none are dropped
+    /// - Flushes pending diagnostics every 50ms
+    /// - Coalesces: keeps only the latest diagnostics per URI
+    /// - Deduplicates: skips payloads identical to the last one published for a URI
+    /// - Rate limits bursts with a token bucket (see `DiagnosticsRateLimit`)
+    /// - Safe: deferred updates stay queued and are flushed on shutdown, none are dropped
     pub(super) fn spawn_debounced_diagnostics_publisher(
         backend: RholangBackend,
-        diagnostics_rx: tokio::sync::mpsc::Receiver<DiagnosticUpdate>,
+        mut diagnostics_rx: tokio::sync::mpsc::Receiver<DiagnosticUpdate>,
     ) {
         let mut shutdown_rx = backend.shutdown_tx.subscribe();
 
         tokio::spawn(async move {
-            use std::collections::HashMap;
-
-            // Create stream from receiver
-            let diagnostics_stream = ReceiverStream::new(diagnostics_rx);
-
-            // Apply reactive operators with batching
-            let mut reactive_stream = Box::pin(
-                diagnostics_stream
-                    // Batch diagnostics with 150ms timeout window
-                    .chunk_timeout(50, Duration::from_millis(150))
-                    // Take until shutdown
-                    .take_until(async move {
-                        let _ = shutdown_rx.recv().await;
-                        info!("Debounced diagnostics publisher received shutdown signal");
-                    })
-            );
+            let mut publisher = DiagnosticsPublisher::default();
+            let flush_window = Duration::from_millis(50);
+            let mut flush = deterministic::sleep(flush_window);
 
-            // Process batches
-            while let Some(batch) = reactive_stream.next().await {
-                // Deduplicate: keep only latest diagnostics per URI
-                let mut latest_by_uri: HashMap<tower_lsp::lsp_types::Url, DiagnosticUpdate> =
-                    HashMap::new();
-
-                for update in batch {
-                    // Always keep the latest update for each URI
-                    latest_by_uri
-                        .entry(update.uri.clone())
-                        .and_modify(|existing| {
-                            // Keep the one with higher version, or the newer one if versions are equal
-                            match (existing.version, update.version) {
-                                (Some(existing_ver), Some(new_ver)) if new_ver > existing_ver => {
-                                    *existing = update.clone();
-                                }
-                                (None, Some(_)) => {
-                                    *existing = update.clone();
-                                }
-                                _ => {}
+            loop {
+                tokio::select! {
+                    update = diagnostics_rx.recv() => match update {
+                        Some(update) => {
+                            // Each batch gets a full window, not what is left of one that ran out while idle
+                            if !publisher.has_pending() {
+                                flush = deterministic::sleep(flush_window);
                             }
-                        })
-                        .or_insert(update);
-                }
-
-                trace!(
-                    "Diagnostics batch: {} updates deduplicated to {} unique URIs",
-                    latest_by_uri.len(),
-                    latest_by_uri.len()
-                );
-
-                // Publish deduplicated diagnostics
-                for (uri, update) in latest_by_uri {
-                    let diagnostic_count = update.diagnostics.len();
-
-                    // Publish diagnostics to client
-                    backend
-                        .client
-                        .publish_diagnostics(uri.clone(), update.diagnostics, update.version)
-                        .await;
-
-                    // Broadcast completion event for tests/subscribers
-                    let _ = backend.diagnostics_published.send(crate::lsp::backend::state::DiagnosticPublished {
-                        uri,
-                        version: update.version,
-                        diagnostic_count,
-                    });
+                            publisher.enqueue(update.uri, update.diagnostics, update.version);
+                        }
+                        None => break,
+                    },
+                    _ = &mut flush, if publisher.has_pending() => {
+                        flush = deterministic::sleep(flush_window);
+                        let ready = publisher.drain_ready(deterministic::now());
+                        if publisher.has_pending() {
+                            trace!(
+                                "Diagnostics rate limited: {} URIs deferred for {:?}",
+                                publisher.pending_count(),
+                                publisher.next_ready_in()
+                            );
+                        }
+                        Self::publish_diagnostics_batch(&backend, ready).await;
+                    }
+                    _ = shutdown_rx.recv() => {
+                        info!("Debounced diagnostics publisher received shutdown signal");
+                        break;
+                    }
                 }
+            }
 
-                debug!("Published diagnostics batch");
+            // Final flush: publish everything still queued, ignoring the rate limit
+            while let Ok(update) = diagnostics_rx.try_recv() {
+                publisher.enqueue(update.uri, update.diagnostics, update.version);
             }
+            Self::publish_diagnostics_batch(&backend, publisher.drain_all()).await;
 
             info!("Debounced diagnostics publisher task terminated");
         });
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Publishes a batch of diagnostics and broadcasts a completion event per URI.
+    async fn publish_diagnostics_batch(backend: &RholangBackend, batch: Vec<PendingDiagnostics>) {
+        if batch.is_empty() {
+            return;
+        }
+        let batch_size = batch.len();
+
+        for update in batch {
+            let diagnostic_count = update.diagnostics.len();
+
+            // Publish diagnostics to client
+            backend
+                .client
+                .publish_diagnostics(update.uri.clone(), update.diagnostics, update.version)
+                .await;
+
+            // Broadcast completion event for tests/subscribers
+            let _ = backend.diagnostics_published.send(crate::lsp::backend::state::DiagnosticPublished {
+                uri: update.uri,
+                version: update.version,
+                diagnostic_count,
+            });
+        }
+
+        debug!("Published diagnostics batch of {} URIs", batch_size);
+    }
 
-    // Tests would go here to verify reactive behavior
-    // For now, the integration tests in the main codebase cover this
+    /// Queues diagnostics for `uri` on the debounced diagnostics publisher,
+    /// with their registered codes (see `crate::lsp::diagnostic_registry`).
+    ///
+    /// Falls back to publishing directly if the publisher task has stopped.
+    pub(super) async fn queue_diagnostics(
+        &self,
+        uri: tower_lsp::lsp_types::Url,
+        diagnostics: Vec<tower_lsp::lsp_types::Diagnostic>,
+        version: Option<i32>,
+    ) {
+        let mut diagnostics = diagnostics;
+        self.settings.read().diagnostics.apply(&mut diagnostics);
+        self.announce_document_status(&uri, version, &diagnostics).await;
+        diagnostic_registry::stamp(&uri, &mut diagnostics);
+        self.broadcast_diagnostics(&uri, &diagnostics).await;
+        self.anchor_diagnostics(&uri, version, &diagnostics).await;
+        self.capabilities.read().adapt_diagnostics(&mut diagnostics);
+        let update = DiagnosticUpdate { uri, diagnostics, version };
+        if let Err(tokio::sync::mpsc::error::SendError(update)) = self.diagnostics_tx.send(update).await {
+            debug!("Diagnostics publisher stopped; publishing {} directly", update.uri);
+            self.client.publish_diagnostics(update.uri, update.diagnostics, update.version).await;
+        }
+    }
 }
@@ -0,0 +1,53 @@
+//! Startup readiness notifications for the LSP backend
+//!
+//! Advances the `crate::lsp::readiness` stage as startup progresses and, when
+//! the client set `readiness`, announces each transition and each request
+//! answered before the stage it needs.
+
+use std::sync::atomic::Ordering;
+
+use tracing::{debug, info};
+
+use crate::lsp::models::IndexingState;
+use crate::lsp::readiness::{PartialResult, Readiness, ReadinessStage};
+
+use super::state::RholangBackend;
+
+impl RholangBackend {
+    /// Moves to `stage`, announcing the transition. Stages past
+    /// `parsingReady` wait for `initialized`, which catches up on them.
+    pub(super) async fn advance_readiness(&self, stage: ReadinessStage) {
+        if stage > ReadinessStage::ParsingReady && self.readiness.stage() < ReadinessStage::ParsingReady {
+            return;
+        }
+        let Some(transition) = self.readiness.advance(stage) else {
+            return;
+        };
+        info!("Readiness: {:?} -> {:?}", transition.previous, transition.stage);
+        if self.readiness_events.load(Ordering::Relaxed) {
+            self.client.send_notification::<Readiness>(transition).await;
+        }
+    }
+
+    /// Reaches `parsingReady`, and `indexReady` as well when no workspace
+    /// indexing is left running.
+    pub(super) async fn startup_readiness(&self) {
+        self.advance_readiness(ReadinessStage::ParsingReady).await;
+        let indexing = matches!(*self.workspace.indexing_state.read().await, IndexingState::InProgress { .. });
+        if !indexing {
+            self.advance_readiness(ReadinessStage::IndexReady).await;
+        }
+    }
+
+    /// Tells the client that the answer to `method` may be incomplete and
+    /// worth retrying, if the stage it needs has not been reached.
+    pub(super) async fn hint_partial_result(&self, method: &str) {
+        let Some(partial) = self.readiness.partial(method) else {
+            return;
+        };
+        debug!("Answering {} at {:?}, before {:?}", method, partial.stage, partial.requires);
+        if self.readiness_events.load(Ordering::Relaxed) {
+            self.client.send_notification::<PartialResult>(partial).await;
+        }
+    }
+}
@@ -0,0 +1,52 @@
+//! Receive pattern completion for the LSP backend
+//!
+//! Suggests destructuring patterns for `for (… <- retCh)` from what the
+//! contracts `retCh` is passed to send back (see `crate::lsp::receive_patterns`).
+
+use std::sync::Arc;
+
+use tower_lsp::lsp_types::{CompletionItem, Position as LspPosition, Url};
+use tracing::debug;
+
+use crate::ir::rholang_node::{collect_contracts, RholangNode};
+use crate::lsp::contracts_outline::node_name;
+use crate::lsp::receive_patterns::{accepts_arity, receive_context, suggest_receive_patterns};
+
+use super::state::RholangBackend;
+
+impl RholangBackend {
+    /// Receive pattern completions for the bind at `position` in `uri`, if any.
+    pub(super) fn receive_pattern_completions(&self, uri: &Url, position: LspPosition) -> Vec<CompletionItem> {
+        let Some(doc) = self.workspace.documents.get(uri).map(|entry| entry.value().clone()) else {
+            return Vec::new();
+        };
+        let Some(context) = receive_context(&doc.text, &doc.line_index, position) else {
+            return Vec::new();
+        };
+
+        let mut contracts = Vec::new();
+        for entry in self.workspace.documents.iter() {
+            collect_contracts(&entry.value().ir, &mut contracts);
+        }
+        let resolve = |name: &str, arity: usize| -> Vec<Arc<RholangNode>> {
+            contracts
+                .iter()
+                .filter(|contract| match &***contract {
+                    RholangNode::Contract { name: contract_name, .. } => {
+                        node_name(contract_name).as_deref() == Some(name) && accepts_arity(contract, arity)
+                    }
+                    _ => false,
+                })
+                .cloned()
+                .collect()
+        };
+
+        let patterns = suggest_receive_patterns(&doc.ir, &context.channel, &resolve);
+        debug!("{} receive pattern(s) for binds on '{}'", patterns.len(), context.channel);
+        patterns
+            .iter()
+            .enumerate()
+            .map(|(rank, pattern)| pattern.to_completion_item(&context, rank))
+            .collect()
+    }
+}
@@ -0,0 +1,104 @@
+//! Refresh requests and call-count code lenses for the LSP backend
+//!
+//! Watches workspace changes and asks the client to re-request semantic
+//! tokens, code lenses and inlay hints once cross-file data they depend on
+//! has changed (see `crate::lsp::refresh`).
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use futures::StreamExt;
+use tokio_stream::wrappers::WatchStream;
+use tower_lsp::lsp_types::{CodeLens, Url};
+use tracing::{debug, info, warn};
+
+use crate::lsp::contracts_outline::{call_count_lenses, collect_contract_outlines, count_calls};
+use crate::lsp::models::DocumentLanguage;
+use crate::lsp::refresh::{DerivedDataFingerprint, RefreshSet, RefreshTracker};
+
+use super::state::RholangBackend;
+use super::streams::StreamExt as CustomStreamExt;
+
+/// Quiet period after the last workspace change before fingerprinting
+const REFRESH_DEBOUNCE: Duration = Duration::from_millis(300);
+
+impl RholangBackend {
+    /// Spawns the task sending refresh requests after cross-file changes.
+    ///
+    /// Workspace change events are debounced so that indexing a batch of files
+    /// produces at most one round of refresh requests.
+    pub(super) fn spawn_derived_data_refresher(backend: RholangBackend) {
+        let mut shutdown_rx = backend.shutdown_tx.subscribe();
+        let changes = WatchStream::new(backend.workspace_changes.subscribe());
+
+        tokio::spawn(async move {
+            let mut reactive_stream = Box::pin(
+                changes
+                    .debounce_time(REFRESH_DEBOUNCE)
+                    .take_until(async move {
+                        let _ = shutdown_rx.recv().await;
+                        info!("Derived data refresher received shutdown signal");
+                    }),
+            );
+
+            let mut tracker = RefreshTracker::default();
+            while let Some(change) = reactive_stream.next().await {
+                let fingerprint = backend.derived_data_fingerprint();
+                let refresh = tracker.update(fingerprint).supported_by(&backend.capabilities.read());
+                debug!("Workspace change {:?}: refreshing {:?}", change.change_type, refresh);
+                if !refresh.is_empty() {
+                    backend.send_refresh_requests(refresh).await;
+                }
+            }
+
+            info!("Derived data refresher task terminated");
+        });
+    }
+
+    fn derived_data_fingerprint(&self) -> DerivedDataFingerprint {
+        let documents: Vec<_> = self
+            .workspace
+            .documents
+            .iter()
+            .filter(|entry| entry.value().language != DocumentLanguage::Metta)
+            .map(|entry| entry.value().clone())
+            .collect();
+        DerivedDataFingerprint::compute(documents.iter().map(|doc| (&doc.ir, &*doc.deprecations)))
+    }
+
+    pub(super) async fn send_refresh_requests(&self, refresh: RefreshSet) {
+        if refresh.semantic_tokens {
+            if let Err(e) = self.client.semantic_tokens_refresh().await {
+                warn!("workspace/semanticTokens/refresh failed: {}", e);
+            }
+        }
+        if refresh.code_lens {
+            if let Err(e) = self.client.code_lens_refresh().await {
+                warn!("workspace/codeLens/refresh failed: {}", e);
+            }
+        }
+        if refresh.inlay_hints {
+            if let Err(e) = self.client.inlay_hint_refresh().await {
+                warn!("workspace/inlayHint/refresh failed: {}", e);
+            }
+        }
+    }
+
+    /// Call-count lenses for the contracts of `uri`, counting sends across the workspace.
+    pub(super) fn call_lenses(&self, uri: &Url) -> Option<Vec<CodeLens>> {
+        let doc = self.workspace.documents.get(uri).map(|entry| entry.value().clone())?;
+        if doc.language == DocumentLanguage::Metta {
+            return None;
+        }
+        let contracts = collect_contract_outlines(uri, &doc.ir, &doc.positions, &doc.text, &doc.line_index);
+        if contracts.is_empty() {
+            return Some(Vec::new());
+        }
+
+        let mut counts = HashMap::new();
+        for entry in self.workspace.documents.iter() {
+            count_calls(&entry.value().ir, &mut counts);
+        }
+        Some(call_count_lenses(&contracts, &counts))
+    }
+}
@@ -0,0 +1,57 @@
+//! Cross-document navigation through registry URIs
+//!
+//! Go-to-definition on a URI literal (`lookup!(`rho:id:abc`, *ch)`), or on a
+//! name declared with a URI but not defined in the document
+//! (`new counter(`rho:id:abc`)`), jumps to the contracts bound to that URI in
+//! any indexed document (see `crate::ir::symbol_resolution::registry_uri`).
+
+use tower_lsp::lsp_types::{GotoDefinitionResponse, Location, Position as LspPosition, Url};
+use tracing::debug;
+
+use crate::ir::semantic_node::Position;
+use crate::ir::symbol_resolution::{RegistryUriResolver, ResolutionContext, SymbolResolver};
+
+use super::state::RholangBackend;
+
+impl RholangBackend {
+    /// Contracts bound to the registry URI at `position` in `uri`.
+    ///
+    /// `local` is the in-document definition found for `position`; when it is a
+    /// URI-bound `new` name without a contract, the URI of that name is followed.
+    pub(super) fn registry_definition(
+        &self,
+        uri: &Url,
+        position: LspPosition,
+        local: Option<&GotoDefinitionResponse>,
+    ) -> Option<GotoDefinitionResponse> {
+        let doc = self.workspace.documents.get(uri).map(|entry| entry.value().clone())?;
+        let bindings = &doc.registry_bindings;
+        let registry_uri = bindings.literal_at(position).or_else(|| match local {
+            Some(GotoDefinitionResponse::Scalar(location)) if location.uri == *uri => {
+                bindings.imported_at(location.range.start)
+            }
+            _ => None,
+        })?;
+
+        let context = ResolutionContext {
+            uri: uri.clone(),
+            scope_id: None,
+            ir_node: None,
+            language: "rholang".to_string(),
+            parent_uri: None,
+        };
+        let query = Position { row: position.line as usize, column: position.character as usize, byte: 0 };
+        let mut locations: Vec<Location> = RegistryUriResolver::new(self.workspace.clone())
+            .resolve_symbol(registry_uri, &query, &context)
+            .into_iter()
+            .map(|symbol| Location { uri: symbol.uri, range: symbol.range })
+            .collect();
+        debug!("Registry URI {} is bound to {} contract(s)", registry_uri, locations.len());
+
+        match locations.len() {
+            0 => None,
+            1 => locations.pop().map(GotoDefinitionResponse::Scalar),
+            _ => Some(GotoDefinitionResponse::Array(locations)),
+        }
+    }
+}
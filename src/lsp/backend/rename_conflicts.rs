@@ -0,0 +1,45 @@
+//! Rename conflict checks for the LSP backend
+//!
+//! Rejects renames that would change which declaration an occurrence refers
+//! to (see `crate::lsp::rename_conflicts`).
+
+use tower_lsp::lsp_types::RenameParams;
+use tracing::debug;
+
+use crate::lsp::rename_conflicts::{find_rename_conflicts, RenameConflictReport};
+
+use super::state::RholangBackend;
+
+impl RholangBackend {
+    /// Conflicts of the rename in `params`, or `None` if the rename is safe.
+    pub(super) fn rename_conflicts(&self, params: &RenameParams) -> Option<RenameConflictReport> {
+        let uri = &params.text_document_position.text_document.uri;
+        let doc = self.workspace.documents.get(uri).map(|entry| entry.value().clone())?;
+
+        let (old_name, conflicts) = find_rename_conflicts(
+            &doc.ir,
+            &doc.positions,
+            &doc.text,
+            &doc.line_index,
+            params.text_document_position.position,
+            &params.new_name,
+        )?;
+        if conflicts.is_empty() {
+            return None;
+        }
+
+        debug!(
+            "Rename of '{}' to '{}' in {} has {} conflict(s)",
+            old_name,
+            params.new_name,
+            uri,
+            conflicts.len()
+        );
+        Some(RenameConflictReport {
+            uri: uri.clone(),
+            old_name,
+            new_name: params.new_name.clone(),
+            conflicts,
+        })
+    }
+}
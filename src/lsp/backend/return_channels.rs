@@ -0,0 +1,129 @@
+//! Return-channel navigation for the LSP backend
+//!
+//! Resolves the `*ret` convention (see `crate::lsp::return_channels`) against
+//! the workspace: a formal's response sends come from its own contract, while
+//! a channel passed as `*ret` is followed to every indexed contract matching
+//! the call. Serves `rholang.responseSends`, the lenses above the formals
+//! answered on and above the receives waiting on a `*ret` argument, and a
+//! hover section counting the response sends.
+
+use std::sync::Arc;
+
+use tower_lsp::lsp_types::{
+    CodeLens, Hover, HoverContents, Location, MarkupContent, MarkupKind, Position as LspPosition, Url,
+};
+use tracing::debug;
+
+use crate::ir::rholang_node::{match_contract, RholangNode};
+use crate::lsp::models::CachedDocument;
+use crate::lsp::return_channels::{
+    response_send_lenses, response_sends, response_sends_lens, return_channel_at, return_receives, ResponseSendsReport,
+    ReturnChannel, RESPONSE_SENDS_COMMAND,
+};
+
+use super::state::RholangBackend;
+
+impl RholangBackend {
+    /// The sends answering on the return channel at `position` in `uri`, or
+    /// `None` if there is no return channel there.
+    pub(super) fn response_sends_at(&self, uri: &Url, position: LspPosition) -> Option<ResponseSendsReport> {
+        let doc = self.workspace.documents.get(uri).map(|entry| entry.value().clone())?;
+        let channel = return_channel_at(&doc.ir, &doc.positions, &doc.text, &doc.line_index, position)?;
+        let sends = self.channel_sends(uri, &doc, &channel);
+        debug!("{} response send(s) on `{}` at {}:{:?}", sends.len(), channel.name(), uri, position);
+        Some(ResponseSendsReport { channel: channel.name().to_string(), sends })
+    }
+
+    /// The sends answering on `channel`, a return channel of `doc` at `uri`.
+    fn channel_sends(&self, uri: &Url, doc: &CachedDocument, channel: &ReturnChannel) -> Vec<Location> {
+        match channel {
+            ReturnChannel::Formal { contract, index, .. } => {
+                response_sends(contract, *index, &doc.ir, &doc.positions, &doc.text, &doc.line_index)
+                    .into_iter()
+                    .map(|range| Location { uri: uri.clone(), range })
+                    .collect()
+            }
+            ReturnChannel::Argument { calls, .. } => {
+                let contracts: Vec<(Url, Arc<RholangNode>)> = self
+                    .workspace
+                    .global_contracts
+                    .iter()
+                    .flat_map(|entry| {
+                        let uri = entry.key().clone();
+                        entry.value().iter().map(move |contract| (uri.clone(), contract.clone())).collect::<Vec<_>>()
+                    })
+                    .collect();
+                let mut sends = Vec::new();
+                for call in calls {
+                    for (contract_uri, contract) in &contracts {
+                        if !match_contract(&call.channel, &call.inputs, contract) {
+                            continue;
+                        }
+                        let Some(declaring) = self.workspace.documents.get(contract_uri).map(|entry| entry.value().clone())
+                        else {
+                            continue;
+                        };
+                        sends.extend(
+                            response_sends(
+                                contract,
+                                call.index,
+                                &declaring.ir,
+                                &declaring.positions,
+                                &declaring.text,
+                                &declaring.line_index,
+                            )
+                            .into_iter()
+                            .map(|range| Location { uri: contract_uri.clone(), range }),
+                        );
+                    }
+                }
+                sends
+            }
+        }
+    }
+
+    /// Response-send lenses for the contract formals of `uri` and for its
+    /// receives waiting on a `*ret` argument, e.g. `for (x <- ret)`.
+    pub(super) fn response_lenses(&self, uri: &Url) -> Vec<CodeLens> {
+        let Some(doc) = self.workspace.documents.get(uri).map(|entry| entry.value().clone()) else {
+            return Vec::new();
+        };
+        let mut lenses = response_send_lenses(uri, &doc.ir, &doc.positions, &doc.text, &doc.line_index);
+        for (position, channel) in return_receives(&doc.ir, &doc.positions, &doc.text, &doc.line_index) {
+            let sends = self.channel_sends(uri, &doc, &channel).len();
+            if sends > 0 {
+                lenses.push(response_sends_lens(uri, position, sends));
+            }
+        }
+        lenses
+    }
+
+    /// Adds the number of response sends to `hover` when `position` is on a return channel in `uri`.
+    pub(super) fn with_response_sends(&self, uri: &Url, position: LspPosition, hover: Option<Hover>) -> Option<Hover> {
+        let Some(report) = self.response_sends_at(uri, position) else {
+            return hover;
+        };
+        if report.sends.is_empty() {
+            return hover;
+        }
+        let section = format!(
+            "**Response sends on `{}`:** {} (`{}` lists them)",
+            report.channel,
+            report.sends.len(),
+            RESPONSE_SENDS_COMMAND
+        );
+
+        match hover {
+            Some(Hover { contents: HoverContents::Markup(mut markup), range }) => {
+                markup.value.push_str("\n\n---\n\n");
+                markup.value.push_str(&section);
+                Some(Hover { contents: HoverContents::Markup(markup), range })
+            }
+            Some(other) => Some(other),
+            None => Some(Hover {
+                contents: HoverContents::Markup(MarkupContent { kind: MarkupKind::Markdown, value: section }),
+                range: None,
+            }),
+        }
+    }
+}
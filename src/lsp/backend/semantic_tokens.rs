@@ -0,0 +1,93 @@
+//! Semantic tokens of a document
+//!
+//! Collects the tokens served by `textDocument/semanticTokens/full`,
+//! `textDocument/semanticTokens/full/delta` and
+//! `textDocument/semanticTokens/range` from the current IR of the document
+//! and its embedded-language regions, kept by chunk of lines per content
+//! (see `crate::lsp::semantic_tokens_range`).
+
+use std::sync::Arc;
+
+use tower_lsp::lsp_types::{Range, SemanticToken, Url};
+use tracing::debug;
+
+use crate::lsp::analysis_level::DegradedFeature;
+use crate::lsp::semantic_tokens_range::{AbsoluteToken, TokenChunks};
+
+use super::state::RholangBackend;
+use super::utils::SemanticTokensBuilder;
+
+impl RholangBackend {
+    /// The tokens of `uri`, or `None` if it has none or they are disabled
+    /// for its size.
+    pub(super) async fn document_semantic_tokens(&self, uri: &Url) -> Option<Vec<SemanticToken>> {
+        if !self.analysis_levels.level(uri).allows(DegradedFeature::SemanticTokensFull) {
+            debug!("Semantic tokens disabled for large document {}", uri);
+            return None;
+        }
+        // The viewport comes first; its chunks are then usually cached
+        self.chunked_tokens.full_job_turn(uri).await;
+        let chunks = self.token_chunks(uri).await?;
+        Some(chunks.all())
+    }
+
+    /// The tokens of `uri` overlapping `range`, served at every document size.
+    pub(super) async fn range_semantic_tokens(&self, uri: &Url, range: &Range) -> Option<Vec<SemanticToken>> {
+        let _job = self.chunked_tokens.range_job(uri);
+        let chunks = self.token_chunks(uri).await?;
+        Some(chunks.in_range(range))
+    }
+
+    /// The token chunks of the current content of `uri`, classified on first use.
+    async fn token_chunks(&self, uri: &Url) -> Option<Arc<TokenChunks>> {
+        let content_hash = self.workspace.documents.get(uri).map(|doc| doc.content_hash);
+        if let Some(chunks) = content_hash.and_then(|hash| self.chunked_tokens.get(uri, hash)) {
+            return Some(chunks);
+        }
+
+        let chunks = Arc::new(TokenChunks::new(content_hash.unwrap_or_default(), self.collect_semantic_tokens(uri).await?));
+        if content_hash.is_some() {
+            self.chunked_tokens.insert(uri, chunks.clone());
+        }
+        Some(chunks)
+    }
+
+    /// Classifies the tokens of `uri`, or `None` if it has none.
+    async fn collect_semantic_tokens(&self, uri: &Url) -> Option<Vec<AbsoluteToken>> {
+        // Names with their declaration/definition/readonly/defaultLibrary modifiers;
+        // references to deprecated contracts and names add the `deprecated` modifier,
+        // and TODO/FIXME/NOTE markers in comments get the `todo` modifier
+        let mut tokens_builder = SemanticTokensBuilder::new();
+        let name_count = self.add_name_semantic_tokens(&mut tokens_builder, uri);
+        let deprecated_count = self.add_deprecation_semantic_tokens(&mut tokens_builder, uri);
+        let task_count = self.add_task_semantic_tokens(&mut tokens_builder, uri);
+
+        // Get virtual documents for this file
+        let virtual_docs_guard = self.virtual_docs.read().await;
+        let virtual_docs_list = virtual_docs_guard.get_by_parent(uri);
+
+        if virtual_docs_list.is_empty() && name_count == 0 && deprecated_count == 0 && task_count == 0 {
+            debug!("No virtual documents (embedded languages) found for {}", uri);
+            return None;
+        }
+
+        // Build semantic tokens for all embedded language regions
+        for virtual_doc in virtual_docs_list {
+            debug!(
+                "Processing {} virtual document at line {} (bytes {})",
+                virtual_doc.language, virtual_doc.parent_start.line, virtual_doc.byte_offset
+            );
+
+            // Only process MeTTa regions for now
+            if virtual_doc.language == "metta" {
+                // Use VirtualDocument directly - it now caches parsed trees
+                self.add_metta_semantic_tokens(&mut tokens_builder, &virtual_doc).await;
+            }
+        }
+        drop(virtual_docs_guard);
+
+        let tokens_data = tokens_builder.build_absolute();
+        debug!("Generated {} semantic tokens", tokens_data.len());
+        Some(tokens_data)
+    }
+}
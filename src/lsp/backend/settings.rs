@@ -0,0 +1,100 @@
+//! Runtime settings for the LSP backend
+//!
+//! Applies the settings of `crate::lsp::settings` from the initialization
+//! options and from `workspace/didChangeConfiguration`, then validates the
+//! open documents again and asks the client to refresh what they affect.
+
+use serde_json::Value;
+use tower_lsp::lsp_types::{ConfigurationItem, MessageType};
+use tracing::{debug, info, warn};
+
+use crate::lsp::analysis_budget::{AnalysisBudgetConfig, ANALYSIS_BUDGET_OPTION};
+use crate::lsp::completion_cost::{self, COST_AWARE_COMPLETION_OPTION};
+use crate::lsp::cost_estimation::{CostEstimationConfig, COST_ESTIMATION_OPTION};
+use crate::lsp::document_links::{DocumentLinksConfig, DOCUMENT_LINKS_OPTION};
+use crate::lsp::formatting::FormatConfig;
+use crate::lsp::inlay_hints::{InlayHintsConfig, INLAY_HINTS_OPTION};
+use crate::lsp::refresh::RefreshSet;
+use crate::lsp::settings::{Feature, Settings, SETTINGS_SECTION};
+
+use super::state::RholangBackend;
+
+impl RholangBackend {
+    /// Applies settings from the initialization options or a configuration change.
+    ///
+    /// Invalid settings are reported to the user and leave the current ones in place.
+    pub(super) async fn apply_settings(&self, value: &Value) {
+        let settings = match Settings::from_value(value) {
+            Ok(settings) => settings,
+            Err(e) => {
+                warn!("Ignoring invalid settings: {}", e);
+                self.client.show_message(MessageType::WARNING, format!("Invalid Rholang settings: {}", e)).await;
+                return;
+            }
+        };
+        debug!("Applying settings: {:?}", settings);
+
+        // Feature options of the initialization options may change at runtime too
+        let section = value.get(SETTINGS_SECTION).unwrap_or(value);
+        if section.get(INLAY_HINTS_OPTION).is_some() {
+            *self.inlay_hints.write() = InlayHintsConfig::from_init_options(Some(section));
+        }
+        if section.get(DOCUMENT_LINKS_OPTION).is_some() {
+            *self.document_links.write() = DocumentLinksConfig::from_init_options(Some(section));
+        }
+        if section.get(COST_ESTIMATION_OPTION).is_some() {
+            *self.cost_estimation.write() = CostEstimationConfig::from_init_options(Some(section));
+        }
+        if section.get(ANALYSIS_BUDGET_OPTION).is_some() {
+            *self.analysis_budget.write() = AnalysisBudgetConfig::from_init_options(Some(section));
+        }
+        if section.get(COST_AWARE_COMPLETION_OPTION).is_some() {
+            *self.cost_aware_completion.write() = completion_cost::option_in(Some(section));
+        }
+        if let Some(cost_aware) = settings.completion.cost_aware {
+            *self.cost_aware_completion.write() = Some(cost_aware);
+        }
+
+        let status = self.rnode_validation.status();
+        if let Some((enabled, address)) = settings.rnode.target(status.enabled, &status.address) {
+            info!("Settings switch RNode validation to {} ({})", enabled, address);
+            self.switch_rnode_validation(enabled, address).await;
+        }
+
+        *self.settings.write() = settings;
+    }
+
+    /// Handles `workspace/didChangeConfiguration`, pulling the `rholang`
+    /// section with `workspace/configuration` when the client sent none.
+    pub(super) async fn change_configuration(&self, settings: Value) {
+        let settings = if settings.is_null() && self.capabilities.read().workspace_configuration {
+            let item = ConfigurationItem { scope_uri: None, section: Some(SETTINGS_SECTION.to_string()) };
+            match self.client.configuration(vec![item]).await {
+                Ok(mut values) if !values.is_empty() => values.swap_remove(0),
+                Ok(_) => Value::Null,
+                Err(e) => {
+                    warn!("Failed to pull settings: {}", e);
+                    return;
+                }
+            }
+        } else {
+            settings
+        };
+
+        self.apply_settings(&settings).await;
+        self.revalidate_open_documents().await;
+        let refresh =
+            RefreshSet { semantic_tokens: true, code_lens: true, inlay_hints: true }.supported_by(&self.capabilities.read());
+        self.send_refresh_requests(refresh).await;
+    }
+
+    /// Whether the settings leave `feature` on.
+    pub(super) fn feature_enabled(&self, feature: Feature) -> bool {
+        self.settings.read().features.is_enabled(feature)
+    }
+
+    /// The formatting settings of `.rholangrc.toml` with the client's overrides.
+    pub(super) fn effective_format_config(&self) -> FormatConfig {
+        self.settings.read().formatting.apply_to(self.format_config.read().clone())
+    }
+}
@@ -0,0 +1,78 @@
+//! Documents open in several connections for the LSP backend
+//!
+//! Registers this connection's open documents in the process-wide
+//! `crate::lsp::shared_documents` registry, arbitrates edits by the
+//! configured conflict policy, and forwards the writer's diagnostics to the
+//! other connections holding a document.
+
+use tower_lsp::lsp_types::{Diagnostic, MessageType, Url};
+use tracing::{debug, info, warn};
+
+use crate::lsp::shared_documents::{
+    ConflictResolution, DocumentConflict, DocumentConflictParams, EditArbitration,
+};
+
+use super::state::RholangBackend;
+
+impl RholangBackend {
+    /// Records that this connection opened `uri`.
+    pub(super) fn register_shared_document(&self, uri: &Url) {
+        if self.shared_documents.open(self.connection_id, uri, self.client.clone()) {
+            info!("{} is also open in another connection", uri);
+        }
+    }
+
+    /// Records that this connection closed `uri`.
+    pub(super) fn release_shared_document(&self, uri: &Url) {
+        self.shared_documents.close(self.connection_id, uri);
+    }
+
+    /// Arbitrates an edit of `uri` by this connection, notifying the
+    /// connections involved. Returns whether the edit may be applied.
+    pub(super) async fn arbitrate_edit(&self, uri: &Url, version: i32) -> bool {
+        let policy = self.shared_documents.policy();
+        match self.shared_documents.edit(self.connection_id, uri) {
+            EditArbitration::Accepted { superseded } => {
+                if !superseded.is_empty() {
+                    debug!("Edit {} of {} supersedes {} other connection(s)", version, uri, superseded.len());
+                }
+                for client in superseded {
+                    let params = DocumentConflictParams {
+                        uri: uri.clone(),
+                        version,
+                        resolution: ConflictResolution::Superseded,
+                        policy,
+                    };
+                    client.send_notification::<DocumentConflict>(params).await;
+                }
+                true
+            }
+            EditArbitration::Rejected { writer } => {
+                warn!("Rejected edit {} of {}: connection {} is editing it", version, uri, writer);
+                self.client
+                    .show_message(
+                        MessageType::ERROR,
+                        format!("{} is being edited in another connection; the change was not applied", uri),
+                    )
+                    .await;
+                let params = DocumentConflictParams {
+                    uri: uri.clone(),
+                    version,
+                    resolution: ConflictResolution::Rejected,
+                    policy,
+                };
+                self.client.send_notification::<DocumentConflict>(params).await;
+                false
+            }
+        }
+    }
+
+    /// Publishes `diagnostics` of `uri` to the other connections holding it,
+    /// if this connection is its writer.
+    pub(super) async fn broadcast_diagnostics(&self, uri: &Url, diagnostics: &[Diagnostic]) {
+        for client in self.shared_documents.followers(self.connection_id, uri) {
+            // Versions are numbered per client, so none is sent
+            client.publish_diagnostics(uri.clone(), diagnostics.to_vec(), None).await;
+        }
+    }
+}
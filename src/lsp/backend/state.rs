@@ -3,10 +3,9 @@
 //! This module defines the RholangBackend struct, which maintains all state
 //! for the LSP server including document cache, workspace index, and validation.
 
-use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
-use std::sync::atomic::{AtomicI32, AtomicU32};
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU32, AtomicU64};
 use std::sync::mpsc::{Receiver, Sender};
 
 use dashmap::DashMap;
@@ -15,10 +14,42 @@ use tower_lsp::Client;
 use tower_lsp::lsp_types::Url;
 use notify::RecommendedWatcher;
 
+use crate::edit_journal::EditJournal;
 use crate::language_regions::{VirtualDocumentRegistry, DetectionWorkerHandle, DetectorRegistry};
+use crate::lsp::analysis_budget::AnalysisBudgetConfig;
+use crate::lsp::analysis_level::AnalysisLevels;
+use crate::lsp::analysis_pool::AnalysisPool;
+use crate::lsp::shared_documents::{ConnectionId, SharedDocuments};
+use crate::lsp::capabilities::NegotiatedCapabilities;
+use crate::lsp::completion_ranking::CompletionUsage;
+use crate::lsp::index_checkpoint::IndexCheckpoints;
+use crate::lsp::readiness::ReadinessTracker;
+use crate::lsp::cost_estimation::{CostEstimates, CostEstimationConfig};
+use crate::lsp::crash_reports::CrashReporter;
+use crate::lsp::settings::Settings;
 use crate::lsp::models::{LspDocument, WorkspaceState};
+use crate::lsp::normalized_form::NormalizedForms;
+use crate::lsp::semantic_tokens_delta::SemanticTokensCache;
+use crate::lsp::semantic_tokens_range::ChunkedTokens;
 use crate::lsp::semantic_validator::SemanticValidator;
 use crate::lsp::diagnostic_provider::DiagnosticProvider;
+use crate::lsp::buffer_overlay::BufferOverlay;
+use crate::lsp::disk_index::DiskIndex;
+use crate::lsp::features::adapters::AdapterRegistry;
+use crate::lsp::features::code_actions::CodeActionProvider;
+use crate::lsp::library_paths::Libraries;
+use crate::lsp::rnode_validation::RnodeValidation;
+use crate::lsp::anchored_diagnostics::AnchoredDiagnostics;
+use crate::lsp::deploy_signing::DeploySettings;
+use crate::lsp::document_links::DocumentLinksConfig;
+use crate::lsp::formatting::FormatConfig;
+use crate::lsp::inlay_hints::InlayHintsConfig;
+use crate::lsp::style_lints::StyleLintConfig;
+use crate::lsp::typing_diagnostics::TypingDiagnostics;
+use crate::lsp::validation_scheduler::ValidationScheduler;
+use crate::lsp::virtual_document_events::VirtualDocumentAnnouncer;
+use crate::lsp::workspace_trust::WorkspaceTrust;
+use crate::parsers::rholang::ConversionMemo;
 
 /// Document change event for debouncing
 #[derive(Debug, Clone)]
@@ -42,7 +73,7 @@ pub(super) struct DiagnosticUpdate {
 pub(super) struct IndexingTask {
     pub(super) uri: Url,
     pub(super) text: String,
-    pub(super) priority: u8,  // 0 = high (current file), 1 = normal
+    pub(super) priority: u8,  // 0 = high (current file), 1 = normal, 2 = library
 }
 
 /// Workspace change event for hot observable pattern
@@ -93,11 +124,16 @@ pub struct RholangBackend {
     pub(super) diagnostic_provider: Arc<Box<dyn DiagnosticProvider>>,
     /// Direct access to SemanticValidator for validate_parsed optimization (if using Rust backend)
     pub(super) semantic_validator: Option<SemanticValidator>,
+    /// RNode validator switched on at startup or by `rholang.toggleRnodeValidation`
+    pub(super) rnode_validation: Arc<RnodeValidation>,
+    /// Id of the next evaluation or deploy sent to RNode
+    pub(super) next_rnode_run: Arc<AtomicU64>,
     pub(super) client_process_id: Arc<tokio::sync::Mutex<Option<u32>>>,
     pub(super) pid_channel: Option<tokio::sync::mpsc::Sender<u32>>,
     // Reactive channels
     pub(super) doc_change_tx: tokio::sync::mpsc::Sender<DocumentChangeEvent>,
-    pub(super) validation_cancel: Arc<tokio::sync::Mutex<HashMap<Url, tokio::sync::oneshot::Sender<()>>>>,
+    /// Cancels the validation of stale document versions, see `crate::lsp::validation_scheduler`
+    pub(super) validation_scheduler: Arc<ValidationScheduler>,
     pub(super) indexing_tx: tokio::sync::mpsc::Sender<IndexingTask>,
     /// Workspace state with lock-free concurrent collections (Phase 1 optimization)
     /// No outer RwLock needed - internal DashMaps provide lock-free concurrent access
@@ -107,6 +143,8 @@ pub struct RholangBackend {
     pub(super) file_sender: Arc<Mutex<Sender<notify::Result<notify::Event>>>>,
     pub(super) version_counter: Arc<AtomicI32>,
     pub(super) root_dir: Arc<RwLock<Option<PathBuf>>>,
+    /// Workspace folders whose `.rho` files are indexed
+    pub(super) workspace_roots: Arc<parking_lot::RwLock<Vec<PathBuf>>>,
     pub(super) shutdown_tx: Arc<tokio::sync::broadcast::Sender<()>>,
     /// Virtual document registry for embedded language regions
     pub(super) virtual_docs: Arc<RwLock<VirtualDocumentRegistry>>,
@@ -122,11 +160,87 @@ pub struct RholangBackend {
     /// Channel to request debounced diagnostic publishing
     /// Sending diagnostics to this channel batches them before publishing to the client
     pub(super) diagnostics_tx: tokio::sync::mpsc::Sender<DiagnosticUpdate>,
+    /// Last queued diagnostics of open documents, anchored to their text to follow edits
+    pub(super) anchored_diagnostics: Arc<AnchoredDiagnostics>,
     /// Async virtual document detection worker (hybrid spawn_blocking + rayon)
     /// Provides 18-19x faster throughput than blocking detection
     pub(super) detection_worker: DetectionWorkerHandle,
     /// Detector registry for virtual document detection
     pub(super) detector_registry: Arc<DetectorRegistry>,
+    /// Workspace trust decisions and RNode endpoint allowlist for deploy commands
+    pub(super) workspace_trust: Arc<WorkspaceTrust>,
+    /// Deploy key and parameters of `rholang.deploy`
+    pub(super) deploy_settings: Arc<parking_lot::RwLock<DeploySettings>>,
+    /// Identifier style lint settings from `.rholangrc.toml`
+    pub(super) style_lints: Arc<parking_lot::RwLock<StyleLintConfig>>,
+    /// Formatting settings from the `[format]` section of `.rholangrc.toml`
+    pub(super) format_config: Arc<parking_lot::RwLock<FormatConfig>>,
+    /// Quick fix providers run on every code action request
+    pub(super) code_action_providers: Arc<Vec<Box<dyn CodeActionProvider>>>,
+    /// Inlay hints enabled by the `inlayHints` initialization option
+    pub(super) inlay_hints: Arc<parking_lot::RwLock<InlayHintsConfig>>,
+    /// Document link settings from the `documentLinks` initialization option
+    pub(super) document_links: Arc<parking_lot::RwLock<DocumentLinksConfig>>,
+    /// Index-only artifacts for workspace files that are not open or indexed
+    pub(super) disk_index: Arc<DiskIndex>,
+    /// Open documents, indexed from their buffer rather than from disk, see `crate::lsp::buffer_overlay`
+    pub(super) buffer_overlay: Arc<BufferOverlay>,
+    /// Virtual documents announced to the client; `None` unless it set `virtualDocumentEvents`
+    pub(super) virtual_doc_events: Arc<parking_lot::Mutex<Option<VirtualDocumentAnnouncer>>>,
+    /// Whether the client set `documentStatus` to receive `rholang/documentStatus`
+    pub(super) document_status: Arc<AtomicBool>,
+    /// Whether the client set `documentResync` to answer `rholang/resyncDocument`
+    pub(super) document_resync: Arc<AtomicBool>,
+    /// Documents out of sync with the client, with the latest version of a change ignored since
+    pub(super) desynced_documents: Arc<DashMap<Url, i32>>,
+    /// Whether validation keeps the normalized form of top-level processes
+    pub(super) normalized_form: Arc<AtomicBool>,
+    /// Normalized forms of the last validated version of each document
+    pub(super) normalized_forms: Arc<DashMap<Url, NormalizedForms>>,
+    /// Semantic tokens last sent for each document, the base of delta requests
+    pub(super) semantic_tokens: Arc<SemanticTokensCache>,
+    /// Token chunks served to range requests, see `crate::lsp::semantic_tokens_range`
+    pub(super) chunked_tokens: Arc<ChunkedTokens>,
+    /// Indexing checkpoints of the workspace folders; `None` unless the client set `indexCheckpoints`
+    pub(super) index_checkpoints: Arc<parking_lot::Mutex<Option<IndexCheckpoints>>>,
+    /// Startup readiness stage, consulted by cross-document requests
+    pub(super) readiness: Arc<ReadinessTracker>,
+    /// Whether the client set `readiness` to receive `rholang/readiness` and `rholang/partialResult`
+    pub(super) readiness_events: Arc<AtomicBool>,
+    /// `costEstimation` settings from the client
+    pub(super) cost_estimation: Arc<parking_lot::RwLock<CostEstimationConfig>>,
+    /// Costs estimated by `rholang.estimateCost`, per document
+    pub(super) cost_estimates: Arc<DashMap<Url, CostEstimates>>,
+    /// Settings from the initialization options or the last configuration change
+    pub(super) settings: Arc<parking_lot::RwLock<Settings>>,
+    /// Write-ahead journal of document edits (`--edit-journal`)
+    pub(super) edit_journal: EditJournal,
+    /// Accepted completions of the workspace, used to rank completion items
+    pub(super) completion_usage: Arc<CompletionUsage>,
+    /// `costAwareCompletion` from the client; `None` follows RNode validation
+    pub(super) cost_aware_completion: Arc<parking_lot::RwLock<Option<bool>>>,
+    /// Language adapters consulted by the unified handlers, by language id
+    pub(super) adapter_registry: Arc<parking_lot::RwLock<AdapterRegistry>>,
+    /// Thread pool for parsing, IR conversion and symbol table builds
+    pub(super) analysis_pool: AnalysisPool,
+    /// What the client declared it supports in `initialize`
+    pub(super) capabilities: Arc<parking_lot::RwLock<NegotiatedCapabilities>>,
+    /// Analysis level of each open document, lowered for very large files
+    pub(super) analysis_levels: Arc<AnalysisLevels>,
+    /// Time each analysis pass may take before its remaining stages are skipped
+    pub(super) analysis_budget: Arc<parking_lot::RwLock<AnalysisBudgetConfig>>,
+    /// IR subtrees of the last conversion of each open document, with its version
+    pub(super) ir_memos: Arc<DashMap<Url, (i32, Arc<ConversionMemo>)>>,
+    /// Syntax errors published softened while the user types
+    pub(super) typing_diagnostics: Arc<TypingDiagnostics>,
+    /// Read-only library documents indexed at startup
+    pub(super) libraries: Arc<Libraries>,
+    /// Documents open in any connection, for conflict arbitration (`--open-conflict`)
+    pub(super) shared_documents: SharedDocuments,
+    /// This connection's id in `shared_documents`
+    pub(super) connection_id: ConnectionId,
+    /// Writes crash reports of panics in the analysis of a document, see `crate::lsp::crash_reports`
+    pub(super) crash_reporter: Arc<CrashReporter>,
 }
 
 // Manual Debug implementation since DiagnosticProvider doesn't implement Debug
@@ -12,6 +12,8 @@ use futures::Stream;
 use tokio::sync::mpsc;
 use tokio_stream::wrappers::ReceiverStream;
 
+use crate::deterministic;
+
 
 use super::state::{DocumentChangeEvent, IndexingTask};
 
@@ -99,7 +101,7 @@ pub struct DebounceStream<S: Stream> {
     stream: S,
     duration: Duration,
     pending: Option<S::Item>,
-    sleep: Option<Pin<Box<tokio::time::Sleep>>>,
+    sleep: Option<deterministic::Sleep>,
 }
 
 impl<S: Stream> DebounceStream<S> {
@@ -140,7 +142,7 @@ where
                 Poll::Ready(Some(item)) => {
                     // New item arrived, reset timer
                     this.pending = Some(item);
-                    this.sleep = Some(Box::pin(tokio::time::sleep(this.duration)));
+                    this.sleep = Some(deterministic::sleep(this.duration));
                     // Wake up when timer expires
                     if let Some(sleep) = this.sleep.as_mut() {
                         let _ = sleep.as_mut().poll(cx);
@@ -172,7 +174,7 @@ pub struct ChunkTimeoutStream<S: Stream> {
     max_size: usize,
     duration: Duration,
     buffer: Vec<S::Item>,
-    sleep: Option<Pin<Box<tokio::time::Sleep>>>,
+    sleep: Option<deterministic::Sleep>,
 }
 
 impl<S: Stream> ChunkTimeoutStream<S> {
@@ -218,7 +220,7 @@ where
 
                     // Start timer if this is first item
                     if this.buffer.len() == 1 {
-                        this.sleep = Some(Box::pin(tokio::time::sleep(this.duration)));
+                        this.sleep = Some(deterministic::sleep(this.duration));
                     }
 
                     // Emit chunk if buffer full
@@ -0,0 +1,130 @@
+//! Identifier style lints for the LSP backend
+//!
+//! Reports naming-convention violations configured in `.rholangrc.toml` (see
+//! `crate::lsp::style_lints`) and offers the suggested names as quick fixes
+//! that go through the regular rename. File structure conventions from the
+//! same file (see `crate::lsp::conventions`) and lint rules (see
+//! `crate::validators::lint`) are reported alongside.
+
+use std::path::Path;
+
+use tower_lsp::lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, Diagnostic, RenameParams,
+    TextDocumentPositionParams, Url,
+};
+use tracing::{debug, info, warn};
+
+use crate::i18n::tr;
+use crate::lsp::conventions::check_conventions;
+use crate::lsp::style_lints::{check_naming, StyleLintConfig, STYLE_LINT_SOURCE};
+use crate::validators::lint::run_lints;
+
+use super::state::RholangBackend;
+
+impl RholangBackend {
+    /// Loads the style lint settings from `.rholangrc.toml` in the workspace root.
+    pub(super) fn load_style_lints(&self, root: &Path) {
+        let config = StyleLintConfig::load(root);
+        if config.naming.is_enabled() {
+            info!("Naming lints enabled: {:?}", config.naming);
+        }
+        if config.conventions.is_enabled() {
+            info!("File structure conventions enabled: {:?}", config.conventions);
+        }
+        if config.rules.is_enabled() {
+            info!("Lint rules enabled: {:?}", config.rules);
+        }
+        for code in config.rules.unknown_rules() {
+            warn!("Ignoring unknown lint rule '{}' in [lints.rules]", code);
+        }
+        *self.style_lints.write() = config;
+    }
+
+    /// Naming-convention and file structure diagnostics for the indexed document `uri`.
+    pub(super) fn style_diagnostics(&self, uri: &Url) -> Vec<Diagnostic> {
+        let (naming, conventions) = {
+            let config = self.style_lints.read();
+            (config.naming.clone(), config.conventions.clone())
+        };
+        if !naming.is_enabled() && !conventions.is_enabled() {
+            return Vec::new();
+        }
+        let Some(doc) = self.workspace.documents.get(uri).map(|entry| entry.value().clone()) else {
+            return Vec::new();
+        };
+
+        let violations = check_naming(&doc.ir, &doc.positions, &doc.text, &doc.line_index, &naming);
+        debug!("Found {} naming violations in {}", violations.len(), uri);
+        let mut diagnostics: Vec<Diagnostic> = violations
+            .iter()
+            .map(|violation| violation.to_diagnostic(naming.severity))
+            .collect();
+
+        let violations = check_conventions(&doc.ir, &doc.positions, &doc.text, &doc.line_index, &conventions);
+        debug!("Found {} convention violations in {}", violations.len(), uri);
+        diagnostics.extend(
+            violations
+                .iter()
+                .map(|violation| violation.to_diagnostic(conventions.severity)),
+        );
+        diagnostics
+    }
+
+    /// Diagnostics of the lint rules enabled for the indexed document `uri`.
+    pub(super) fn lint_diagnostics(&self, uri: &Url) -> Vec<Diagnostic> {
+        let rules = self.style_lints.read().rules.clone();
+        if !rules.is_enabled() {
+            return Vec::new();
+        }
+        let Some(doc) = self.workspace.documents.get(uri).map(|entry| entry.value().clone()) else {
+            return Vec::new();
+        };
+        let diagnostics = run_lints(uri, &doc.ir, &doc.positions, &doc.text, &doc.line_index, &rules);
+        debug!("Found {} lint rule violations in {}", diagnostics.len(), uri);
+        diagnostics
+    }
+
+    /// "Rename" quick fixes for the naming diagnostics in `params`.
+    ///
+    /// Each fix carries the workspace edit the rename request would produce,
+    /// so references in other files are renamed too.
+    pub(super) async fn naming_quick_fixes(&self, params: &CodeActionParams) -> Vec<CodeActionOrCommand> {
+        let mut actions = Vec::new();
+        for diagnostic in &params.context.diagnostics {
+            if diagnostic.source.as_deref() != Some(STYLE_LINT_SOURCE) {
+                continue;
+            }
+            let Some(data) = diagnostic.data.as_ref() else { continue };
+            let (Some(name), Some(suggestion)) = (data["name"].as_str(), data["suggestion"].as_str()) else {
+                continue;
+            };
+
+            let rename = RenameParams {
+                text_document_position: TextDocumentPositionParams {
+                    text_document: params.text_document.clone(),
+                    position: diagnostic.range.start,
+                },
+                new_name: suggestion.to_string(),
+                work_done_progress_params: Default::default(),
+            };
+            if self.rename_conflicts(&rename).is_some() {
+                debug!("Not offering rename of '{}' to '{}': it would conflict", name, suggestion);
+                continue;
+            }
+            let Some(edit) = self.unified_rename(rename).await else {
+                debug!("No rename edit for '{}' at {:?}", name, diagnostic.range.start);
+                continue;
+            };
+
+            actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                title: tr("action.rename", &[("name", &name), ("suggestion", &suggestion)]),
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![diagnostic.clone()]),
+                edit: Some(edit),
+                is_preferred: Some(true),
+                ..Default::default()
+            }));
+        }
+        actions
+    }
+}
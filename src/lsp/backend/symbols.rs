@@ -17,6 +17,7 @@ use tracing::{debug, info, trace};
 
 use crate::ir::rholang_node::{RholangNode, Position as IrPosition, find_node_at_position_with_path};
 use crate::ir::symbol_table::{Symbol, SymbolTable, SymbolType};
+use crate::lsp::readiness::ReadinessStage;
 
 use super::state::{RholangBackend, WorkspaceChangeEvent, WorkspaceChangeType};
 
@@ -62,6 +63,8 @@ impl RholangBackend {
     /// - per-document inverted_index (now in rholang_symbols with local keys)
     pub(crate) async fn link_symbols(&self) {
         debug!("link_symbols: Resolving forward references and broadcasting event");
+        // Deprecations come from other documents, so kept tokens may be stale now
+        self.chunked_tokens.clear();
 
         // Get all contract names from rholang_symbols
         let contract_names = self.workspace.rholang_symbols.contract_names();
@@ -171,6 +174,7 @@ impl RholangBackend {
 
         info!("link_symbols: Completed for {} files, {} symbols, {} forward references resolved",
               file_count, symbol_count, references_added);
+        self.advance_readiness(ReadinessStage::SymbolsReady).await;
     }
 
     /// Links symbols across all virtual documents in the workspace.
@@ -287,15 +291,17 @@ impl RholangBackend {
         let text = &doc.text;
 
         // Convert LSP position to byte offset
-        let byte_offset = Self::byte_offset_from_position(
-            text,
+        let byte_offset = doc.line_index.offset(
             position.line as usize,
             position.character as usize,
+            text,
         )?;
 
+        // IR columns are byte columns, not UTF-16 characters
+        let line_start = doc.line_index.line_start(position.line as usize)?;
         let pos = IrPosition {
             row: position.line as usize,
-            column: position.character as usize,
+            column: byte_offset - line_start,
             byte: byte_offset,
         };
 
@@ -624,6 +630,8 @@ impl RholangBackend {
                                 contract_pattern: None,
                                 contract_identifier_node: None,
                                 documentation: None,
+                                deprecated: None,
+                                param_docs: Vec::new(),
                             }));
                         }
                     }
@@ -655,6 +663,8 @@ impl RholangBackend {
                 contract_pattern: None,
                 contract_identifier_node: None,
                 documentation: None,
+                deprecated: None,
+                param_docs: Vec::new(),
             }));
         }
 
@@ -694,6 +704,8 @@ impl RholangBackend {
                 contract_pattern: None,
                 contract_identifier_node: None,
                 documentation: None,
+                deprecated: None,
+                param_docs: Vec::new(),
             }));
         }
 
@@ -750,6 +762,8 @@ impl RholangBackend {
                         contract_pattern: None,
                         contract_identifier_node: None,
                         documentation: None,
+                        deprecated: None,
+                        param_docs: Vec::new(),
                     }));
                 }
             }
@@ -795,6 +809,8 @@ impl RholangBackend {
                         contract_pattern: None,
                         contract_identifier_node: None,
                         documentation: None,
+                        deprecated: None,
+                        param_docs: Vec::new(),
                     }));
                 }
             }
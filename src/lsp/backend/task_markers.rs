@@ -0,0 +1,62 @@
+//! `TODO`/`FIXME`/`NOTE` comment markers in the LSP backend
+//!
+//! Highlights the markers found by `crate::lsp::task_markers` and collects
+//! them into the task list of the `rholang.todos` command.
+
+use tower_lsp::lsp_types::Url;
+use tracing::debug;
+
+use crate::lsp::models::DocumentLanguage;
+use crate::lsp::semantic_modifiers::{MODIFIER_TODO, TOKEN_COMMENT};
+use crate::lsp::task_markers::{find_task_markers, TaskItem};
+
+use super::state::RholangBackend;
+use super::utils::SemanticTokensBuilder;
+
+impl RholangBackend {
+    /// Adds a `comment` token with the `todo` modifier for each task marker
+    /// of the indexed document `uri`.
+    ///
+    /// Returns the number of tokens added.
+    pub(super) fn add_task_semantic_tokens(&self, builder: &mut SemanticTokensBuilder, uri: &Url) -> usize {
+        let Some(doc) = self.workspace.documents.get(uri).map(|entry| entry.value().clone()) else {
+            return 0;
+        };
+        if doc.language != DocumentLanguage::Rholang {
+            return 0;
+        }
+
+        let markers = find_task_markers(&doc.tree, &doc.text);
+        for marker in &markers {
+            builder.push_with_modifiers(marker.line, marker.start, marker.length, TOKEN_COMMENT, MODIFIER_TODO);
+        }
+        markers.len()
+    }
+
+    /// The task markers of `uri`, or of every indexed Rholang document, sorted
+    /// by URI and position.
+    pub(super) fn task_list(&self, uri: Option<&Url>) -> Vec<TaskItem> {
+        let mut tasks: Vec<TaskItem> = self
+            .workspace
+            .documents
+            .iter()
+            .filter(|entry| uri.is_none_or(|uri| entry.key() == uri))
+            .filter(|entry| entry.value().language == DocumentLanguage::Rholang)
+            .flat_map(|entry| {
+                let doc = entry.value();
+                find_task_markers(&doc.tree, &doc.text)
+                    .into_iter()
+                    .map(|marker| TaskItem {
+                        uri: entry.key().clone(),
+                        range: marker.range(),
+                        kind: marker.kind,
+                        text: marker.text,
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        tasks.sort_by(|a, b| a.uri.as_str().cmp(b.uri.as_str()).then_with(|| a.range.start.cmp(&b.range.start)));
+        debug!("Found {} task marker(s)", tasks.len());
+        tasks
+    }
+}
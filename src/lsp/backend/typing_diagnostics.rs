@@ -0,0 +1,45 @@
+//! Softened syntax errors while typing for the LSP backend
+//!
+//! Publishes the diagnostics of edited documents through
+//! `crate::lsp::typing_diagnostics`, and escalates softened syntax errors
+//! once the document settles or is saved.
+
+use tower_lsp::lsp_types::{Diagnostic, Url};
+use tracing::debug;
+
+use super::state::RholangBackend;
+
+impl RholangBackend {
+    /// Queues the diagnostics of `version` of `uri`, validated after an edit,
+    /// with syntax errors softened if the client asked for it.
+    pub(super) async fn queue_typing_diagnostics(&self, uri: Url, diagnostics: Vec<Diagnostic>, version: i32) {
+        let published = self.typing_diagnostics.soften(&uri, version, diagnostics);
+        self.queue_diagnostics(uri.clone(), published, Some(version)).await;
+        if !self.typing_diagnostics.is_softened(&uri, version) {
+            return;
+        }
+
+        let backend = self.clone();
+        let delay = self.typing_diagnostics.config().settle_delay();
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            backend.escalate_typing_diagnostics(&uri, version).await;
+        });
+    }
+
+    /// Publishes the syntax errors of `version` of `uri` as errors, if they
+    /// were softened and nothing was typed since.
+    pub(super) async fn escalate_typing_diagnostics(&self, uri: &Url, version: i32) {
+        let Some(document) = self.documents_by_uri.get(uri).map(|r| r.value().clone()) else {
+            self.typing_diagnostics.remove(uri);
+            return;
+        };
+        if document.version().await != version {
+            return;
+        }
+        if let Some(diagnostics) = self.typing_diagnostics.escalate(uri, version) {
+            debug!("Escalating softened syntax errors of {} (version {})", uri, version);
+            self.queue_diagnostics(uri.clone(), diagnostics, Some(version)).await;
+        }
+    }
+}
@@ -62,6 +62,7 @@ use tower_lsp::lsp_types::{
 use tracing::{debug, trace, warn};
 
 use crate::ir::semantic_node::{Position, SemanticNode};
+use crate::language_regions::VIRTUAL_URI_SCHEME;
 use crate::lsp::features::{
     goto_definition::GenericGotoDefinition,
     hover::GenericHover,
@@ -91,6 +92,9 @@ pub enum LanguageContext {
         all_roots: Vec<Arc<dyn SemanticNode>>, // All top-level MeTTa nodes
         symbol_table: Arc<crate::ir::transforms::metta_symbol_table_builder::MettaSymbolTable>,
         virtual_doc: Arc<crate::language_regions::VirtualDocument>,
+        /// The request names the virtual document itself (it was opened
+        /// directly), so positions and results use virtual coordinates
+        direct: bool,
     },
     /// Other embedded language (future)
     #[allow(dead_code)]
@@ -118,6 +122,15 @@ impl LanguageContext {
             }
         }
     }
+
+    /// Language id used to look up the adapter
+    fn language_id(&self) -> &str {
+        match self {
+            LanguageContext::Rholang { .. } => "rholang",
+            LanguageContext::MettaVirtual { virtual_doc, .. } => &virtual_doc.language,
+            LanguageContext::Other { language, .. } => language,
+        }
+    }
 }
 
 impl RholangBackend {
@@ -142,13 +155,13 @@ impl RholangBackend {
     ) -> Option<LanguageContext> {
         debug!("detect_language: uri={}, position={:?}", uri, position);
 
-        // Check if this is a virtual document URI (has fragment like #metta:0)
-        if let Some(fragment) = uri.fragment() {
-            debug!("Detected virtual document URI with fragment: {}", fragment);
+        // Check if this is a virtual document URI (fragment like #metta:0, or a rholang-virtual: alias)
+        if uri.fragment().is_some() || uri.scheme() == VIRTUAL_URI_SCHEME {
+            debug!("Detected virtual document URI: {}", uri);
 
             // Look up virtual document in registry
             let virtual_docs = self.virtual_docs.read().await;
-            if let Some(virtual_doc) = virtual_docs.get(uri) {
+            if let Some(virtual_doc) = virtual_docs.resolve(uri) {
                 debug!(
                     "Found virtual document: language={}, parent={}",
                     virtual_doc.language, virtual_doc.parent_uri
@@ -190,12 +203,13 @@ impl RholangBackend {
                     }
 
                     return Some(LanguageContext::MettaVirtual {
-                        virtual_uri: uri.clone(),
+                        virtual_uri: virtual_doc.uri.clone(),
                         parent_uri: virtual_doc.parent_uri.clone(),
                         root,
                         all_roots,
                         symbol_table: symbol_table.unwrap(),
                         virtual_doc: virtual_doc.clone(),
+                        direct: true,
                     });
                 } else {
                     warn!("Failed to parse IR for virtual document");
@@ -260,6 +274,7 @@ impl RholangBackend {
                             all_roots,
                             symbol_table: symbol_table.unwrap(),
                             virtual_doc: virtual_doc.clone(),
+                            direct: false,
                         });
                     } else {
                         warn!("Failed to parse IR for virtual document");
@@ -297,7 +312,8 @@ impl RholangBackend {
     ///
     /// # Resolution Strategies
     ///
-    /// This function routes to three different symbol resolution strategies:
+    /// The adapter is looked up in the `AdapterRegistry` by the context's
+    /// language id. The built-in registrations provide three strategies:
     ///
     /// 1. **Rholang**: Uses `RholangSymbolResolver` with hierarchical symbol table
     ///    - Lexical scoping with parent chain traversal
@@ -311,30 +327,19 @@ impl RholangBackend {
     ///    - Single flat namespace (no lexical hierarchy)
     ///    - Multiple declarations/definitions per symbol
     ///    - Cross-document linking via global_virtual_symbols
-    ///    - Default for future embedded languages
+    ///    - Fallback for languages without a registered adapter
     fn get_adapter(&self, context: &LanguageContext) -> Option<LanguageAdapter> {
-        match context {
-            LanguageContext::Rholang { symbol_table, .. } => {
-                Some(crate::lsp::features::adapters::create_rholang_adapter(
-                    symbol_table.clone(),
-                    self.workspace.global_index.clone(),
-                ))
-            }
+        use crate::lsp::features::adapters::{AdapterInputs, AdapterSymbols};
+
+        let (symbols, parent_uri) = match context {
+            LanguageContext::Rholang { symbol_table, .. } => (AdapterSymbols::Rholang(symbol_table.clone()), None),
             LanguageContext::MettaVirtual { symbol_table, parent_uri, .. } => {
-                Some(crate::lsp::features::adapters::create_metta_adapter(
-                    symbol_table.clone(),
-                    self.workspace.clone(),
-                    parent_uri.clone(),
-                ))
-            }
-            LanguageContext::Other { language, .. } => {
-                // Use generic global scope resolver for unknown languages
-                Some(crate::lsp::features::adapters::create_generic_adapter(
-                    self.workspace.clone(),
-                    language.clone(),
-                ))
+                (AdapterSymbols::Metta(symbol_table.clone()), Some(parent_uri.clone()))
             }
-        }
+            LanguageContext::Other { .. } => (AdapterSymbols::None, None),
+        };
+        let inputs = AdapterInputs { workspace: self.workspace.clone(), symbols, parent_uri };
+        Some(self.adapter_registry.read().adapter_for(context.language_id(), &inputs))
     }
 
     /// Unified goto-definition handler
@@ -416,21 +421,12 @@ impl RholangBackend {
                 all_roots,
                 virtual_doc,
                 symbol_table,
+                direct,
                 ..
             } => {
                 // Convert parent position to virtual position
-                debug!("MettaVirtual handler: About to call map_from_parent with position {:?}, all_roots.len()={}", position, all_roots.len());
-                let virtual_position = match virtual_doc.map_from_parent(position) {
-                    Some(pos) => pos,
-                    None => {
-                        debug!("Position {:?} is outside virtual document range", position);
-                        return None;
-                    }
-                };
-                debug!(
-                    "Mapped parent position {:?} to virtual position {:?}",
-                    position, virtual_position
-                );
+                debug!("MettaVirtual handler: mapping position {:?} (direct={}), all_roots.len()={}", position, direct, all_roots.len());
+                let virtual_position = to_virtual_position(&virtual_doc, position, direct)?;
                 let ir_position = lsp_to_ir_position(virtual_position);
 
                 // Check if the adapter has a specialized goto-definition provider
@@ -450,38 +446,8 @@ impl RholangBackend {
 
                     // Get result from specialized provider (in virtual coordinates)
                     if let Some(result) = goto_def_provider.goto_definition(&context).await {
-                        debug!("Specialized provider returned result in virtual coordinates, mapping to parent");
-
-                        // Map from virtual to parent coordinates
-                        let mapped_result = match result {
-                            GotoDefinitionResponse::Scalar(loc) => {
-                                let parent_range = virtual_doc.map_range_to_parent(loc.range);
-                                debug!("Mapped virtual range {:?} to parent range {:?}", loc.range, parent_range);
-                                GotoDefinitionResponse::Scalar(Location {
-                                    uri: virtual_doc.parent_uri.clone(),
-                                    range: parent_range,
-                                })
-                            }
-                            GotoDefinitionResponse::Array(locs) => {
-                                let parent_locs: Vec<Location> = locs
-                                    .into_iter()
-                                    .map(|loc| {
-                                        let parent_range = virtual_doc.map_range_to_parent(loc.range);
-                                        Location {
-                                            uri: virtual_doc.parent_uri.clone(),
-                                            range: parent_range,
-                                        }
-                                    })
-                                    .collect();
-                                GotoDefinitionResponse::Array(parent_locs)
-                            }
-                            GotoDefinitionResponse::Link(link) => {
-                                // Links don't need mapping, just return as-is
-                                GotoDefinitionResponse::Link(link)
-                            }
-                        };
-
-                        return Some(mapped_result);
+                        debug!("Specialized provider returned result in virtual coordinates");
+                        return Some(from_virtual_definition(&virtual_doc, result, direct, uri));
                     } else {
                         return None;
                     }
@@ -509,36 +475,7 @@ impl RholangBackend {
                         {
                             debug!("Found definition in root node {} (virtual coordinates)", i);
 
-                            // Map the result from virtual to parent coordinates
-                            let mapped_result = match result {
-                                GotoDefinitionResponse::Scalar(loc) => {
-                                    let parent_range = virtual_doc.map_range_to_parent(loc.range);
-                                    debug!("Mapped virtual range {:?} to parent range {:?}", loc.range, parent_range);
-                                    GotoDefinitionResponse::Scalar(Location {
-                                        uri: virtual_doc.parent_uri.clone(),
-                                        range: parent_range,
-                                    })
-                                }
-                                GotoDefinitionResponse::Array(locs) => {
-                                    let parent_locs: Vec<Location> = locs
-                                        .into_iter()
-                                        .map(|loc| {
-                                            let parent_range = virtual_doc.map_range_to_parent(loc.range);
-                                            Location {
-                                                uri: virtual_doc.parent_uri.clone(),
-                                                range: parent_range,
-                                            }
-                                        })
-                                        .collect();
-                                    GotoDefinitionResponse::Array(parent_locs)
-                                }
-                                GotoDefinitionResponse::Link(link) => {
-                                    // Links don't need mapping, just return as-is
-                                    GotoDefinitionResponse::Link(link)
-                                }
-                            };
-
-                            return Some(mapped_result);
+                            return Some(from_virtual_definition(&virtual_doc, result, direct, uri));
                         }
                     }
 
@@ -633,21 +570,12 @@ impl RholangBackend {
                 parent_uri,
                 all_roots,
                 virtual_doc,
+                direct,
                 ..
             } => {
                 // Convert parent position to virtual position
-                debug!("MettaVirtual handler: About to call map_from_parent with position {:?}, all_roots.len()={}", position, all_roots.len());
-                let virtual_position = match virtual_doc.map_from_parent(position) {
-                    Some(pos) => pos,
-                    None => {
-                        debug!("Position {:?} is outside virtual document range", position);
-                        return None;
-                    }
-                };
-                debug!(
-                    "Mapped parent position {:?} to virtual position {:?}",
-                    position, virtual_position
-                );
+                debug!("MettaVirtual handler: mapping position {:?} (direct={}), all_roots.len()={}", position, direct, all_roots.len());
+                let virtual_position = to_virtual_position(&virtual_doc, position, direct)?;
                 let ir_position = lsp_to_ir_position(virtual_position);
 
                 // Iterate through all top-level nodes to find one that contains this position
@@ -744,9 +672,17 @@ impl RholangBackend {
         // Extract root and URI from context
         let (root, doc_uri) = match context {
             LanguageContext::Rholang { uri, root, .. } => (root, uri),
-            LanguageContext::MettaVirtual {
-                virtual_uri, root, ..
-            } => (root, virtual_uri),
+            LanguageContext::MettaVirtual { virtual_doc, direct, .. } => {
+                // Virtual documents are not in the workspace cache; resolve
+                // against their own symbol table
+                let virtual_position = to_virtual_position(&virtual_doc, position, direct)?;
+                let direct_uri = direct.then_some(uri);
+                return self
+                    .references_metta(&virtual_doc, virtual_position, include_declaration, direct_uri)
+                    .await
+                    .ok()
+                    .flatten();
+            }
             LanguageContext::Other { uri, root, .. } => (root, uri),
         };
 
@@ -1136,6 +1072,61 @@ impl RholangBackend {
     }
 }
 
+/// Maps a request position into virtual document coordinates.
+///
+/// Requests on a directly opened virtual document are already in them.
+fn to_virtual_position(
+    virtual_doc: &crate::language_regions::VirtualDocument,
+    position: LspPosition,
+    direct: bool,
+) -> Option<LspPosition> {
+    if direct {
+        return Some(position);
+    }
+    match virtual_doc.map_from_parent(position) {
+        Some(virtual_position) => {
+            debug!("Mapped parent position {:?} to virtual position {:?}", position, virtual_position);
+            Some(virtual_position)
+        }
+        None => {
+            debug!("Position {:?} is outside virtual document range", position);
+            None
+        }
+    }
+}
+
+/// Maps a definition found in virtual coordinates back to what the client asked about.
+///
+/// Parent-document requests get parent coordinates. Direct requests keep
+/// virtual coordinates, reported under the URI the client used (which may be
+/// a `rholang-virtual:` alias).
+fn from_virtual_definition(
+    virtual_doc: &crate::language_regions::VirtualDocument,
+    result: GotoDefinitionResponse,
+    direct: bool,
+    request_uri: &Url,
+) -> GotoDefinitionResponse {
+    let map = |loc: Location| {
+        if !direct {
+            let parent_range = virtual_doc.map_range_to_parent(loc.range);
+            debug!("Mapped virtual range {:?} to parent range {:?}", loc.range, parent_range);
+            Location { uri: virtual_doc.parent_uri.clone(), range: parent_range }
+        } else if loc.uri == virtual_doc.uri {
+            Location { uri: request_uri.clone(), range: loc.range }
+        } else {
+            loc
+        }
+    };
+    match result {
+        GotoDefinitionResponse::Scalar(loc) => GotoDefinitionResponse::Scalar(map(loc)),
+        GotoDefinitionResponse::Array(locs) => {
+            GotoDefinitionResponse::Array(locs.into_iter().map(map).collect())
+        }
+        // Links don't need mapping, just return as-is
+        GotoDefinitionResponse::Link(link) => GotoDefinitionResponse::Link(link),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1168,3 +1159,4 @@ mod tests {
         };
     }
 }
+
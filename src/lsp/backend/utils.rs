@@ -1,60 +1,78 @@
 //! Utility types and functions for the LSP backend
 
-use tower_lsp::lsp_types::SemanticToken;
+use crate::lsp::semantic_tokens_range::AbsoluteToken;
 
-/// Helper for building semantic tokens using delta encoding
+/// Helper for collecting semantic tokens at absolute positions
 ///
-/// LSP semantic tokens use delta encoding where each token's position
-/// is relative to the previous token, reducing payload size.
+/// LSP semantic tokens use delta encoding where each token's position is
+/// relative to the previous token, reducing payload size; the collected tokens
+/// are encoded by `crate::lsp::semantic_tokens_range`. Tokens may be pushed in
+/// any order (e.g. embedded-language tokens and Rholang tokens from separate
+/// passes); they are sorted by position when building, and tokens pushed at
+/// the same position are merged, keeping the first token's type and length
+/// and the union of the modifiers.
 pub(super) struct SemanticTokensBuilder {
-    tokens: Vec<SemanticToken>,
-    prev_line: u32,
-    prev_start: u32,
+    /// Absolute tokens: (line, start, length, token type, modifiers bitset)
+    tokens: Vec<AbsoluteToken>,
 }
 
 impl SemanticTokensBuilder {
     pub(super) fn new() -> Self {
-        Self {
-            tokens: Vec::new(),
-            prev_line: 0,
-            prev_start: 0,
-        }
+        Self { tokens: Vec::new() }
     }
 
     /// Add a semantic token with absolute position
-    ///
-    /// The builder automatically converts to delta encoding
     pub(super) fn push(&mut self, line: u32, start: u32, length: u32, token_type: u32) {
-        let delta_line = if line >= self.prev_line {
-            line - self.prev_line
-        } else {
-            // Should not happen in well-formed code
-            0
-        };
-
-        let delta_start = if delta_line == 0 && start >= self.prev_start {
-            start - self.prev_start
-        } else if delta_line == 0 {
-            // Should not happen - tokens on same line should be in order
-            0
-        } else {
-            start
-        };
-
-        self.tokens.push(SemanticToken {
-            delta_line,
-            delta_start,
-            length,
-            token_type,
-            token_modifiers_bitset: 0,
-        });
+        self.push_with_modifiers(line, start, length, token_type, 0);
+    }
 
-        self.prev_line = line;
-        self.prev_start = start;
+    /// Add a semantic token with absolute position and a modifiers bitset
+    pub(super) fn push_with_modifiers(&mut self, line: u32, start: u32, length: u32, token_type: u32, modifiers: u32) {
+        self.tokens.push((line, start, length, token_type, modifiers));
     }
 
-    /// Build the final vector of semantic tokens
-    pub(super) fn build(self) -> Vec<SemanticToken> {
+    /// The tokens sorted by position, tokens at the same position merged
+    pub(super) fn build_absolute(mut self) -> Vec<AbsoluteToken> {
+        self.tokens.sort_by_key(|&(line, start, ..)| (line, start));
+        self.tokens.dedup_by(|later, first| {
+            let same_position = (later.0, later.1) == (first.0, first.1);
+            if same_position {
+                first.4 |= later.4;
+            }
+            same_position
+        });
         self.tokens
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lsp::semantic_tokens_range::encode;
+
+    #[test]
+    fn test_tokens_are_sorted_and_delta_encoded() {
+        let mut builder = SemanticTokensBuilder::new();
+        builder.push(2, 4, 3, 1);
+        builder.push_with_modifiers(0, 6, 5, 2, 1);
+        builder.push(2, 0, 2, 3);
+
+        let tokens = encode(&builder.build_absolute());
+        let encoded: Vec<(u32, u32, u32, u32, u32)> = tokens
+            .iter()
+            .map(|t| (t.delta_line, t.delta_start, t.length, t.token_type, t.token_modifiers_bitset))
+            .collect();
+        assert_eq!(encoded, vec![(0, 6, 5, 2, 1), (2, 0, 2, 3, 0), (0, 4, 3, 1, 0)]);
+    }
+
+    #[test]
+    fn test_tokens_at_same_position_are_merged() {
+        let mut builder = SemanticTokensBuilder::new();
+        builder.push_with_modifiers(1, 2, 4, 6, 0b10);
+        builder.push_with_modifiers(1, 2, 4, 5, 0b01);
+
+        let tokens = encode(&builder.build_absolute());
+        assert_eq!(tokens.len(), 1);
+        assert_eq!((tokens[0].token_type, tokens[0].token_modifiers_bitset), (6, 0b11));
+    }
+}
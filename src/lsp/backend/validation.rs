@@ -0,0 +1,93 @@
+//! Scheduled validation for the LSP backend
+//!
+//! Runs every validation of a document version under a ticket of
+//! `crate::lsp::validation_scheduler`, so the validation of a version the
+//! client has already edited away stops at its next await point and only
+//! the diagnostics of the latest version are published.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::future::join_all;
+use tower_lsp::lsp_types::Url;
+use tracing::{debug, error, trace};
+
+use crate::lsp::models::LspDocument;
+
+use super::state::RholangBackend;
+
+/// A validation running longer than this is abandoned and its document
+/// reported degraded
+const VALIDATION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How the diagnostics of a validation are published
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Publication {
+    /// After an edit, with syntax errors softened while typing
+    Typing,
+    /// As they are
+    Settled,
+}
+
+impl RholangBackend {
+    /// Validates `version` of `document` and publishes its diagnostics,
+    /// unless a newer version arrives, the document closes or the caller
+    /// drops the returned future first.
+    pub(super) async fn run_validation(
+        &self,
+        uri: Url,
+        document: Arc<LspDocument>,
+        text: Arc<String>,
+        version: i32,
+        publication: Publication,
+    ) {
+        let ticket = self.validation_scheduler.schedule(&uri, version);
+        if ticket.is_cancelled() {
+            trace!("Not validating stale version {} of {}", version, uri);
+            return;
+        }
+        // Cancelled by `$/cancelRequest` too, which drops the request handler awaiting this
+        let _cancel_on_drop = ticket.token().drop_guard();
+
+        let result = tokio::select! {
+            _ = ticket.cancelled() => {
+                debug!("Validation of {} (version {}) cancelled", uri, version);
+                return;
+            }
+            result = tokio::time::timeout(VALIDATION_TIMEOUT, self.validate(document.clone(), &text, version)) => result,
+        };
+        let current = self.validation_scheduler.finish(&ticket);
+        match result {
+            Ok(Ok(diagnostics)) => {
+                if !current || document.version().await != version {
+                    trace!("Dropping diagnostics of stale version {} of {}", version, uri);
+                    return;
+                }
+                trace!("Validation completed for {}", uri);
+                match publication {
+                    Publication::Typing => self.queue_typing_diagnostics(uri, diagnostics, version).await,
+                    Publication::Settled => self.queue_diagnostics(uri, diagnostics, Some(version)).await,
+                }
+            }
+            Ok(Err(e)) => error!("Validation failed for {}: {}", uri, e),
+            Err(_) => {
+                error!("Validation timeout for {}", uri);
+                self.announce_degraded_document(&uri, Some(version)).await;
+            }
+        }
+    }
+
+    /// Validates every open document again and publishes its diagnostics.
+    ///
+    /// Returns once every validation finished or was cancelled.
+    pub(super) async fn revalidate_open_documents(&self) {
+        let documents: Vec<_> =
+            self.documents_by_uri.iter().map(|entry| (entry.key().clone(), entry.value().clone())).collect();
+        debug!("Revalidating {} open document(s)", documents.len());
+        join_all(documents.into_iter().map(|(uri, document)| async move {
+            let (text, version) = (document.text().await, document.version().await);
+            self.run_validation(uri, document, Arc::new(text), version, Publication::Settled).await;
+        }))
+        .await;
+    }
+}
@@ -0,0 +1,103 @@
+//! Virtual document lifecycle notifications for the LSP backend
+//!
+//! Sends `rholang/virtualDocument{Created,Updated,Changed,Removed}` (see
+//! `crate::lsp::virtual_document_events`) for the documents open in the
+//! editor, when the client asked for them.
+
+use tower_lsp::lsp_types::Url;
+use tracing::debug;
+
+use crate::lsp::document::ByteEdit;
+use crate::lsp::virtual_document_events::{
+    VirtualDocumentChangeParams, VirtualDocumentChanged, VirtualDocumentCreated, VirtualDocumentEvent,
+    VirtualDocumentRemoved, VirtualDocumentUpdated,
+};
+
+use super::state::RholangBackend;
+
+impl RholangBackend {
+    /// Makes the edits of `parent` (at `version`) that fall inside embedded
+    /// literals to their virtual documents, and forwards them to the client
+    /// as `rholang/virtualDocumentChanged`.
+    ///
+    /// Runs before `parent` is re-indexed, which then finds the edited
+    /// virtual documents up to date instead of re-extracting them.
+    pub(super) async fn forward_host_edits(&self, parent: &Url, version: i32, edits: &[ByteEdit]) {
+        let mut changed = Vec::new();
+        {
+            let mut virtual_docs = self.virtual_docs.write().await;
+            for edit in edits {
+                if let Some((document, virtual_edit)) =
+                    virtual_docs.apply_host_edit(parent, edit.start, edit.old_end, &edit.text)
+                {
+                    changed.push((document, virtual_edit));
+                }
+            }
+        }
+        if changed.is_empty() || self.virtual_doc_events.lock().is_none() {
+            return;
+        }
+
+        debug!("Forwarding {} edit(s) of {} to its virtual documents", changed.len(), parent);
+        for (document, virtual_edit) in changed {
+            let announced = self
+                .virtual_doc_events
+                .lock()
+                .as_mut()
+                .is_some_and(|announcer| announcer.record(&document));
+            if !announced {
+                // Created by the next announcement instead
+                continue;
+            }
+            let params = VirtualDocumentChangeParams {
+                uri: document.uri.clone(),
+                parent_uri: parent.clone(),
+                version,
+                content_changes: vec![virtual_edit.to_change_event()],
+            };
+            self.client.send_notification::<VirtualDocumentChanged>(params).await;
+        }
+    }
+
+    /// Tells the client how the virtual documents of `parent` changed since
+    /// the last announcement.
+    ///
+    /// A parent that is not open in the editor has its announced documents removed.
+    pub(super) async fn announce_virtual_documents(&self, parent: &Url) {
+        if self.virtual_doc_events.lock().is_none() {
+            return;
+        }
+
+        let events = if self.documents_by_uri.contains_key(parent) {
+            let documents = self.virtual_docs.read().await.get_by_parent(parent);
+            self.virtual_doc_events
+                .lock()
+                .as_mut()
+                .map(|announcer| announcer.sync(parent, &documents))
+                .unwrap_or_default()
+        } else {
+            self.virtual_doc_events
+                .lock()
+                .as_mut()
+                .map(|announcer| announcer.forget(parent))
+                .unwrap_or_default()
+        };
+
+        if !events.is_empty() {
+            debug!("Sending {} virtual document event(s) for {}", events.len(), parent);
+        }
+        for event in events {
+            match event {
+                VirtualDocumentEvent::Created(params) => {
+                    self.client.send_notification::<VirtualDocumentCreated>(params).await
+                }
+                VirtualDocumentEvent::Updated(params) => {
+                    self.client.send_notification::<VirtualDocumentUpdated>(params).await
+                }
+                VirtualDocumentEvent::Removed(params) => {
+                    self.client.send_notification::<VirtualDocumentRemoved>(params).await
+                }
+            }
+        }
+    }
+}
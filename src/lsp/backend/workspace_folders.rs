@@ -0,0 +1,218 @@
+//! Workspace folder indexing for the LSP backend
+//!
+//! Queues the `.rho` files of the workspace folders (see
+//! `crate::lsp::workspace_folders`) for progressive indexing, watches the
+//! folders, and keeps the cross-file index in step with
+//! `workspace/didChangeWatchedFiles` and `workspace/didChangeWorkspaceFolders`.
+
+use std::path::PathBuf;
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use tower_lsp::lsp_types::{
+    DidChangeWatchedFilesRegistrationOptions, FileChangeType, FileEvent, FileSystemWatcher, GlobPattern,
+    NumberOrString, ProgressParams, ProgressParamsValue, Registration, Url, WorkDoneProgress, WorkDoneProgressBegin,
+    WorkDoneProgressEnd, WorkspaceFoldersChangeEvent,
+};
+use tracing::{debug, error, info, warn};
+
+use crate::lsp::index_checkpoint::percentage;
+use crate::lsp::models::IndexingState;
+use crate::lsp::readiness::ReadinessStage;
+use crate::lsp::workspace_folders::{
+    in_workspace, outermost, rholang_files, workspace_roots, RHOLANG_FILES_GLOB, WATCHED_FILES_REGISTRATION_ID,
+};
+
+use super::state::{IndexingTask, RholangBackend};
+
+impl RholangBackend {
+    /// Queues every `.rho` file under `roots` for progressive indexing,
+    /// reporting progress under the `workspace-indexing` token.
+    pub(super) async fn index_workspace_roots(&self, roots: &[PathBuf]) {
+        let file_paths = rholang_files(roots);
+        let file_count = file_paths.len();
+        if file_count == 0 {
+            info!("No .rho files found in {} workspace folder(s)", roots.len());
+            return;
+        }
+
+        // Files recorded by a checkpoint count as indexed already
+        let file_paths = self.resume_from_checkpoints(roots, file_paths);
+        let resumed = file_count - file_paths.len();
+
+        // Set indexing state to InProgress before queuing tasks
+        {
+            let mut state = self.workspace.indexing_state.write().await;
+            *state = IndexingState::InProgress { total: file_count, completed: resumed };
+        }
+
+        let message = if resumed > 0 {
+            format!("Found {} files, {} indexed earlier", file_count, resumed)
+        } else {
+            format!("Found {} files", file_count)
+        };
+        self.client
+            .send_notification::<tower_lsp::lsp_types::notification::Progress>(ProgressParams {
+                token: NumberOrString::String("workspace-indexing".to_string()),
+                value: ProgressParamsValue::WorkDone(WorkDoneProgress::Begin(WorkDoneProgressBegin {
+                    title: "Indexing workspace".to_string(),
+                    message: Some(message),
+                    percentage: Some(percentage(resumed, file_count)),
+                    cancellable: Some(false),
+                })),
+            })
+            .await;
+
+        if file_paths.is_empty() {
+            *self.workspace.indexing_state.write().await = IndexingState::Complete;
+            self.client
+                .send_notification::<tower_lsp::lsp_types::notification::Progress>(ProgressParams {
+                    token: NumberOrString::String("workspace-indexing".to_string()),
+                    value: ProgressParamsValue::WorkDone(WorkDoneProgress::End(WorkDoneProgressEnd {
+                        message: Some(format!("Indexed {} files", file_count)),
+                    })),
+                })
+                .await;
+            info!("All {} .rho files were indexed in an earlier session", file_count);
+            self.advance_readiness(ReadinessStage::IndexReady).await;
+            return;
+        }
+
+        let mut queued_count = 0;
+        for path in file_paths {
+            let Ok(uri) = Url::from_file_path(&path) else { continue };
+            let text = std::fs::read_to_string(&path).unwrap_or_default();
+
+            // Files are prioritized to 0 when opened via did_open
+            let task = IndexingTask { uri: uri.clone(), text, priority: 1 };
+            if let Err(e) = self.indexing_tx.send(task).await {
+                error!("Failed to queue indexing task for {}: {}", uri, e);
+            } else {
+                queued_count += 1;
+            }
+        }
+        info!("Queued {} .rho files from {} workspace folder(s) for progressive indexing", queued_count, roots.len());
+    }
+
+    /// Watches `roots` recursively, starting the file watcher on first use.
+    pub(super) fn watch_workspace_roots(&self, roots: &[PathBuf]) {
+        let mut guard = self.file_watcher.lock().unwrap();
+        if guard.is_none() {
+            let tx = self.file_sender.lock().unwrap().clone();
+            match RecommendedWatcher::new(move |res| { let _ = tx.send(res); }, notify::Config::default()) {
+                Ok(watcher) => {
+                    *guard = Some(watcher);
+                    Self::spawn_reactive_file_watcher(self.clone(), self.file_events.clone());
+                }
+                Err(e) => {
+                    warn!("Failed to start the file watcher: {}", e);
+                    return;
+                }
+            }
+        }
+        let watcher = guard.as_mut().expect("watcher started above");
+        for root in roots {
+            if let Err(e) = watcher.watch(root, RecursiveMode::Recursive) {
+                warn!("Failed to watch workspace folder {}: {}", root.display(), e);
+            }
+        }
+    }
+
+    /// Asks the client to report changes to `.rho` files, if it supports dynamic registration.
+    pub(super) async fn register_file_watchers(&self) {
+        if !self.capabilities.read().watched_files_registration {
+            return;
+        }
+        let options = DidChangeWatchedFilesRegistrationOptions {
+            watchers: vec![FileSystemWatcher {
+                glob_pattern: GlobPattern::String(RHOLANG_FILES_GLOB.to_string()),
+                kind: None,
+            }],
+        };
+        let registration = Registration {
+            id: WATCHED_FILES_REGISTRATION_ID.to_string(),
+            method: "workspace/didChangeWatchedFiles".to_string(),
+            register_options: serde_json::to_value(options).ok(),
+        };
+        if let Err(e) = self.client.register_capability(vec![registration]).await {
+            warn!("Failed to register watched files: {}", e);
+        }
+    }
+
+    /// Applies `workspace/didChangeWatchedFiles` events to the workspace index.
+    pub(super) async fn apply_watched_file_changes(&self, changes: Vec<FileEvent>) {
+        for change in changes {
+            let Ok(path) = change.uri.to_file_path() else { continue };
+            if path.extension().is_none_or(|ext| ext != "rho") {
+                continue;
+            }
+            debug!("Watched file {:?}: {}", change.typ, change.uri);
+            if change.typ == FileChangeType::DELETED {
+                self.remove_workspace_file(&change.uri).await;
+            } else {
+                self.handle_file_change(path).await;
+            }
+        }
+    }
+
+    /// Indexes the added workspace folders and drops the files of the removed ones.
+    pub(super) async fn change_workspace_folders(&self, event: WorkspaceFoldersChangeEvent) {
+        let removed = workspace_roots(Some(&event.removed), None);
+        let added = workspace_roots(Some(&event.added), None);
+        let roots = {
+            let mut roots = self.workspace_roots.write();
+            roots.retain(|root| !removed.contains(root));
+            roots.extend(added.iter().cloned());
+            *roots = outermost(std::mem::take(&mut *roots));
+            roots.clone()
+        };
+        info!("Workspace folders changed: {} added, {} removed", added.len(), removed.len());
+
+        if let Some(watcher) = self.file_watcher.lock().unwrap().as_mut() {
+            for root in removed.iter().filter(|root| !in_workspace(root, &roots)) {
+                let _ = watcher.unwatch(root);
+            }
+        }
+        let stale: Vec<Url> = self
+            .workspace
+            .documents
+            .iter()
+            .map(|entry| entry.key().clone())
+            .filter(|uri| !self.libraries.contains(uri))
+            .filter(|uri| {
+                uri.to_file_path()
+                    .is_ok_and(|path| in_workspace(&path, &removed) && !in_workspace(&path, &roots))
+            })
+            .collect();
+        for uri in &stale {
+            self.remove_workspace_file(uri).await;
+        }
+
+        let added: Vec<PathBuf> = added.into_iter().filter(|root| roots.contains(root)).collect();
+        if !added.is_empty() {
+            self.index_workspace_roots(&added).await;
+            self.watch_workspace_roots(&added);
+        }
+    }
+
+    /// Removes a deleted or no longer included file from the cross-file index.
+    /// Documents open in the editor stay indexed from their buffer.
+    pub(super) async fn remove_workspace_file(&self, uri: &Url) {
+        if self.documents_by_uri.contains_key(uri) {
+            debug!("Keeping open document in the index: {}", uri);
+            return;
+        }
+        if let Ok(path) = uri.to_file_path() {
+            self.disk_index.invalidate(&path);
+        }
+        if self.workspace.documents.remove(uri).is_none() {
+            return;
+        }
+        self.workspace.global_contracts.remove(uri);
+        self.workspace.global_calls.remove(uri);
+        self.workspace.global_table.read().await.symbols.retain(|_, symbol| &symbol.declaration_uri != uri);
+        self.workspace.rholang_symbols.remove_contracts_from_uri(uri);
+        self.workspace.rholang_symbols.remove_references_from_uri(uri);
+        self.link_symbols().await;
+        info!("Removed from the workspace index: {}", uri);
+    }
+}
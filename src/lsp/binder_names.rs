@@ -0,0 +1,405 @@
+//! Name suggestions for fresh binders
+//!
+//! When completing a name declared by `new` or bound by the pattern of a
+//! `for` bind, completion also offers names derived from what the binder is
+//! used with, as low-priority items after the others:
+//!
+//! - the last segment of a system channel's URI: `new |(`rho:rchain:deployerId`)`
+//!   suggests `deployerId`;
+//! - for a receive, the contract the channel is passed to (`getBalance!(*retCh)`
+//!   suggests `balance`) and the channel itself (`retCh` suggests `resp`,
+//!   `ackCh` suggests `ack`, `balanceCh` suggests `balance`);
+//! - names used further down without a declaration.
+//!
+//! Names already declared around the cursor are left out, so a suggestion
+//! never shadows one.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use ropey::Rope;
+use tower_lsp::lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionTextEdit, Documentation, Position as LspPosition, Range, TextEdit,
+};
+
+use crate::ir::rholang_node::{collect_calls, Position as IrPosition, RholangNode};
+use crate::lsp::line_index::{line_chars, line_column, LineIndex};
+use crate::lsp::receive_patterns::{bind_source, is_identifier, passes_channel, preceded_by_for};
+use crate::lsp::scopes::{contains, BinderOrigin, ScopeAnalysis, UseRole};
+
+/// How many names used without a declaration are suggested
+const MAX_FREE_NAMES: usize = 5;
+
+/// Verbs stripped from contract names: `getBalance` sends back a `balance`
+const VERBS: &[&str] = &["get", "fetch", "read", "lookup", "find", "query", "load", "create", "make"];
+
+/// Channel names (without a `Ch` suffix) carrying replies
+const REPLY_CHANNELS: &[&str] = &["ret", "return", "result", "resp", "response", "reply", "k", "cb", "callback", "out"];
+
+/// Channel names (without a `Ch` suffix) carrying acknowledgements
+const ACK_CHANNELS: &[&str] = &["ack", "done", "ok", "confirm", "confirmation"];
+
+/// Where a fresh binder is being written
+#[derive(Debug, Clone, PartialEq)]
+pub enum BinderSite {
+    /// A `new` declaration, with the URI of its system channel: `new x(`rho:io:stdout`)`
+    New { uri: Option<String> },
+    /// The pattern of a `for` bind, with the channel it receives from
+    Receive { channel: Option<String> },
+}
+
+/// The fresh binder under the cursor
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinderContext {
+    pub site: BinderSite,
+    /// Range of the binder's name, replaced by the completion
+    pub range: Range,
+}
+
+/// Finds the fresh binder at `position`: a declaration of `new`, or a
+/// top-level binder in the pattern of a `for` bind.
+///
+/// Only the current line is looked at: `new a, |` and `for (@x, | <- ch)`
+/// are found, a declaration on the line after `new a,` is not.
+pub fn binder_context(text: &Rope, line_index: &LineIndex, position: LspPosition) -> Option<BinderContext> {
+    let (chars, cursor) = line_chars(text, line_index, position)?;
+
+    // The name around the cursor, which the completion replaces
+    let start = (0..cursor).rev().take_while(|&i| is_name_char(chars[i])).last().unwrap_or(cursor);
+    let end = (cursor..chars.len()).find(|&i| !is_name_char(chars[i])).unwrap_or(chars.len());
+    if start < end && !(chars[start].is_alphabetic() || chars[start] == '_') {
+        return None;
+    }
+    // A quoted binder: `for (@x <- ch)`
+    let head = if start > 0 && chars[start - 1] == '@' { start - 1 } else { start };
+
+    let site = new_site(&chars, head, end).or_else(|| receive_site(&chars, head, end))?;
+    Some(BinderContext {
+        site,
+        range: Range {
+            start: LspPosition { line: position.line, character: line_column(line_index, &chars, start) },
+            end: LspPosition { line: position.line, character: line_column(line_index, &chars, end) },
+        },
+    })
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '\''
+}
+
+/// The `new` site if the text before `head` ends in its declarations: `new ` or `new a, `.
+fn new_site(chars: &[char], head: usize, end: usize) -> Option<BinderSite> {
+    let keyword = rfind_keyword(&chars[..head], "new")?;
+    let declarations = &chars[keyword + 3..head];
+    let last = declarations.iter().rev().find(|c| !c.is_whitespace());
+    let in_declarations = !declarations.is_empty()
+        && matches!(last, None | Some(','))
+        && !declarations.iter().fold(false, |in_uri, &c| in_uri != (c == '`'))
+        && !declarations.iter().any(|c| matches!(c, '{' | '}' | '|' | '!' | '='))
+        && rfind_keyword(declarations, "in").is_none();
+    if !in_declarations {
+        return None;
+    }
+
+    let after: String = chars[end..].iter().collect();
+    let uri = after
+        .trim_start()
+        .strip_prefix('(')
+        .and_then(|rest| rest.trim_start().strip_prefix('`'))
+        .and_then(|rest| rest.split_once('`'))
+        .map(|(uri, _)| uri.to_string());
+    Some(BinderSite::New { uri })
+}
+
+/// The receive site if the text before `head` starts a top-level binder of a
+/// bind pattern: `for (`, `for (@a, ` or `for (a <- x & `.
+fn receive_site(chars: &[char], head: usize, end: usize) -> Option<BinderSite> {
+    let open = (0..head).rev().find(|&i| chars[i] == '(' && preceded_by_for(chars, i))?;
+    let mut depth = 0i32;
+    let mut in_source = false;
+    for (i, &c) in chars.iter().enumerate().take(head).skip(open + 1) {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => {
+                depth -= 1;
+                if depth < 0 {
+                    return None;
+                }
+            }
+            '&' | ';' if depth == 0 => in_source = false,
+            '<' if depth == 0 && matches!(chars.get(i + 1), Some('-' | '=' | '<')) => in_source = true,
+            _ => {}
+        }
+    }
+    let last = chars[open + 1..head].iter().rev().find(|c| !c.is_whitespace());
+    if depth != 0 || in_source || !matches!(last, None | Some(',' | '&' | ';')) {
+        return None;
+    }
+
+    let channel = bind_source(chars, end, 0).map(|(_, channel)| channel).filter(|channel| !channel.is_empty());
+    Some(BinderSite::Receive { channel })
+}
+
+/// Index of the last occurrence of the word `keyword` in `chars`.
+fn rfind_keyword(chars: &[char], keyword: &str) -> Option<usize> {
+    let keyword: Vec<char> = keyword.chars().collect();
+    let len = keyword.len();
+    (0..=chars.len().checked_sub(len)?).rev().find(|&i| {
+        chars[i..i + len] == keyword[..]
+            && (i == 0 || !is_name_char(chars[i - 1]))
+            && !chars.get(i + len).is_some_and(|c| is_name_char(*c))
+    })
+}
+
+/// A suggested binder name
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinderName {
+    pub name: String,
+    /// What the name was derived from, shown as the item's documentation
+    pub reason: String,
+}
+
+impl BinderName {
+    /// Converts the suggestion into a completion replacing the binder's name.
+    pub fn to_completion_item(&self, context: &BinderContext, rank: usize) -> CompletionItem {
+        CompletionItem {
+            label: self.name.clone(),
+            kind: Some(CompletionItemKind::VARIABLE),
+            detail: Some("binder name".to_string()),
+            documentation: Some(Documentation::String(self.reason.clone())),
+            // After every other item, costly contracts included (see `crate::lsp::completion_cost`)
+            sort_text: Some(format!("~~~{:03}", rank)),
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit { range: context.range, new_text: self.name.clone() })),
+            ..Default::default()
+        }
+    }
+}
+
+/// Suggests names for the binder of `context`, at `position` in the document
+/// whose IR is `ir`, most specific first.
+pub fn suggest_binder_names(
+    context: &BinderContext,
+    position: LspPosition,
+    ir: &Arc<RholangNode>,
+    positions: &HashMap<usize, (IrPosition, IrPosition)>,
+    text: &Rope,
+    line_index: &LineIndex,
+) -> Vec<BinderName> {
+    let mut names = Vec::new();
+    let mut channel = None;
+    match &context.site {
+        BinderSite::New { uri: Some(uri) } => {
+            if let Some(name) = name_from_uri(uri) {
+                names.push(BinderName { name, reason: format!("Named after the system channel `{}`", uri) });
+            }
+        }
+        BinderSite::Receive { channel: Some(source) } => {
+            let mut calls = Vec::new();
+            collect_calls(ir, &mut calls);
+            for (contract, _, _) in calls.iter().filter_map(|call| passes_channel(call, source)) {
+                if let Some(name) = name_from_contract(&contract) {
+                    names.push(BinderName {
+                        name,
+                        reason: format!("Named after `{}`, which `{}` is passed to", contract, source),
+                    });
+                }
+            }
+            names.extend(names_from_channel(source));
+            channel = Some(source.as_str());
+        }
+        BinderSite::New { uri: None } | BinderSite::Receive { channel: None } => {}
+    }
+
+    // Patterns usually bind values, so only names passed around count there
+    let any_role = matches!(context.site, BinderSite::New { .. });
+    let analysis = ScopeAnalysis::build(ir, positions);
+    let offset = line_index.offset(position.line as usize, position.character as usize, text);
+    let mut declared = HashSet::new();
+    if let (Some(analysis), Some(offset)) = (&analysis, offset) {
+        names.extend(free_names_below(analysis, offset, any_role));
+        declared = declared_at(analysis, offset);
+    }
+
+    let mut seen = HashSet::new();
+    names.retain(|suggestion| {
+        Some(suggestion.name.as_str()) != channel
+            && !declared.contains(suggestion.name.as_str())
+            && seen.insert(suggestion.name.clone())
+    });
+    names
+}
+
+/// `deployerId` for `rho:rchain:deployerId`; registry ids (`rho:id:…`) name nothing.
+fn name_from_uri(uri: &str) -> Option<String> {
+    let segments: Vec<&str> = uri.split(':').collect();
+    if segments.len() < 2 || segments[1] == "id" {
+        return None;
+    }
+    segments.last().filter(|segment| is_identifier(segment)).map(|segment| segment.to_string())
+}
+
+/// `balance` for `getBalance` or `get_balance`, `transferResult` for `transfer`.
+fn name_from_contract(contract: &str) -> Option<String> {
+    if !is_identifier(contract) {
+        return None;
+    }
+    for verb in VERBS {
+        let Some(rest) = contract.strip_prefix(verb) else { continue };
+        if let Some(rest) = rest.strip_prefix('_').filter(|rest| is_identifier(rest)) {
+            return Some(rest.to_string());
+        }
+        let mut chars = rest.chars();
+        if let Some(first) = chars.next().filter(|c| c.is_uppercase()) {
+            return Some(first.to_lowercase().chain(chars).collect());
+        }
+    }
+    let suffix = if contract.contains('_') { "_result" } else { "Result" };
+    Some(format!("{}{}", contract, suffix))
+}
+
+/// Names for what is received on `channel`: `resp` and `result` on reply
+/// channels, `ack` on acknowledgement channels, `balance` on `balanceCh`.
+fn names_from_channel(channel: &str) -> Vec<BinderName> {
+    let stem = ["Channel", "Chan", "Ch", "_channel", "_chan", "_ch"]
+        .iter()
+        .find_map(|suffix| channel.strip_suffix(suffix).filter(|stem| !stem.is_empty()))
+        .unwrap_or(channel);
+    let lower = stem.to_lowercase();
+    let (names, reason) = if REPLY_CHANNELS.contains(&lower.as_str()) {
+        (vec!["resp", "result"], format!("Reply received on the return channel `{}`", channel))
+    } else if ACK_CHANNELS.contains(&lower.as_str()) {
+        (vec!["ack"], format!("Acknowledgement received on `{}`", channel))
+    } else if stem != channel {
+        (vec![stem], format!("Named after the channel `{}`", channel))
+    } else {
+        return Vec::new();
+    };
+    names.into_iter().map(|name| BinderName { name: name.to_string(), reason: reason.clone() }).collect()
+}
+
+/// Names used after byte `offset` without a declaration, in order of first use.
+fn free_names_below(analysis: &ScopeAnalysis, offset: usize, any_role: bool) -> Vec<BinderName> {
+    let mut first_uses: Vec<(usize, &str)> = analysis.scopes[0]
+        .binders
+        .iter()
+        .enumerate()
+        .filter(|(_, binder)| binder.origin == BinderOrigin::Implicit)
+        .filter_map(|(index, binder)| {
+            analysis
+                .uses_of(0, index)
+                .filter(|u| any_role || u.role == UseRole::Other)
+                .map(|u| u.span.0.byte)
+                .filter(|&start| start > offset)
+                .min()
+                .map(|start| (start, binder.name.as_str()))
+        })
+        .collect();
+    first_uses.sort();
+    first_uses
+        .into_iter()
+        .take(MAX_FREE_NAMES)
+        .map(|(_, name)| BinderName {
+            name: name.to_string(),
+            reason: "Used further down without a declaration".to_string(),
+        })
+        .collect()
+}
+
+/// Names declared in the scopes around byte `offset`, except the binder written there.
+fn declared_at(analysis: &ScopeAnalysis, offset: usize) -> HashSet<&str> {
+    analysis
+        .chain_at(offset)
+        .into_iter()
+        .flat_map(|scope| analysis.scopes[scope].binders.iter())
+        .filter(|binder| binder.origin != BinderOrigin::Implicit && !contains(&binder.span, offset))
+        .map(|binder| binder.name.as_str())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree_sitter::parse_with_positions;
+
+    fn at(line: u32, character: u32) -> LspPosition {
+        LspPosition { line, character }
+    }
+
+    fn suggestions(source: &str, position: LspPosition) -> Vec<String> {
+        let rope = Rope::from_str(source);
+        let (ir, positions) = parse_with_positions(source);
+        let line_index = LineIndex::from_rope(&rope);
+        let context = binder_context(&rope, &line_index, position).unwrap();
+        suggest_binder_names(&context, position, &ir, &positions, &rope, &line_index)
+            .into_iter()
+            .map(|name| name.name)
+            .collect()
+    }
+
+    #[test]
+    fn test_binder_context() {
+        let text = Rope::from_str("new retCh in {\n  for ( <- retCh) { Nil }\n}");
+        let context = binder_context(&text, &LineIndex::from_rope(&text), at(1, 7)).unwrap();
+        assert_eq!(context.site, BinderSite::Receive { channel: Some("retCh".to_string()) });
+        assert_eq!((context.range.start, context.range.end), (at(1, 7), at(1, 7)));
+
+        // Quoted binder of the second bind of a join, partly typed
+        let text = Rope::from_str("for (@x <- a & @re <= ackCh) { Nil }");
+        let context = binder_context(&text, &LineIndex::from_rope(&text), at(0, 18)).unwrap();
+        assert_eq!(context.site, BinderSite::Receive { channel: Some("ackCh".to_string()) });
+        assert_eq!((context.range.start, context.range.end), (at(0, 16), at(0, 18)));
+
+        let text = Rope::from_str("new a, (`rho:rchain:deployerId`) in { Nil }");
+        let context = binder_context(&text, &LineIndex::from_rope(&text), at(0, 7)).unwrap();
+        assert_eq!(context.site, BinderSite::New { uri: Some("rho:rchain:deployerId".to_string()) });
+
+        let text = Rope::from_str("new x in { y }");
+        let context = binder_context(&text, &LineIndex::from_rope(&text), at(0, 4)).unwrap();
+        assert_eq!(context.site, BinderSite::New { uri: None });
+        assert_eq!((context.range.start, context.range.end), (at(0, 4), at(0, 5)));
+
+        // In the body, on a receive's source, or after `renew`
+        assert!(binder_context(&text, &LineIndex::from_rope(&text), at(0, 11)).is_none());
+        assert!(binder_context(&Rope::from_str("for (x <- ch) { Nil }"), &LineIndex::new("for (x <- ch) { Nil }"), at(0, 10)).is_none());
+        assert!(binder_context(&Rope::from_str("renew x"), &LineIndex::new("renew x"), at(0, 6)).is_none());
+    }
+
+    #[test]
+    fn test_derived_names() {
+        assert_eq!(name_from_uri("rho:rchain:deployerId").as_deref(), Some("deployerId"));
+        assert_eq!(name_from_uri("rho:io:stdout").as_deref(), Some("stdout"));
+        assert_eq!(name_from_uri("rho:id:3yicxut5xtx5tnmnneta7actof4yse3xangw4awzt8c8owqmddgyms"), None);
+
+        assert_eq!(name_from_contract("getBalance").as_deref(), Some("balance"));
+        assert_eq!(name_from_contract("lookup_user").as_deref(), Some("user"));
+        assert_eq!(name_from_contract("transfer").as_deref(), Some("transferResult"));
+        assert_eq!(name_from_contract("getter").as_deref(), Some("getterResult"));
+
+        let names = |channel: &str| -> Vec<String> {
+            names_from_channel(channel).into_iter().map(|name| name.name).collect()
+        };
+        assert_eq!(names("retCh"), vec!["resp", "result"]);
+        assert_eq!(names("ackChannel"), vec!["ack"]);
+        assert_eq!(names("balance_ch"), vec!["balance"]);
+        assert!(names("inbox").is_empty());
+    }
+
+    #[test]
+    fn test_receive_binder_names() {
+        let source = r#"new retCh, ackCh in {
+  getBalance!("alice", *retCh)
+  | for (x <- retCh) { stdout!(balance) }
+  | for (y <- ackCh) { Nil }
+}"#;
+        assert_eq!(suggestions(source, at(2, 9)), vec!["balance", "resp", "result"]);
+        assert_eq!(suggestions(source, at(3, 9)), vec!["ack"]);
+    }
+
+    #[test]
+    fn test_new_binder_names() {
+        let source = r#"new deployerId(`rho:rchain:deployerId`), x in {
+  x!(*deployerId) | log!(*x) | for (@msg <- inbox) { Nil }
+}"#;
+        assert_eq!(suggestions(source, at(0, 4)), vec!["deployerId", "log", "inbox"]);
+    }
+}
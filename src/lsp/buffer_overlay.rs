@@ -0,0 +1,140 @@
+//! Precedence of open buffers over disk contents in the workspace index
+//!
+//! `workspace/symbol`, references and rename read the workspace index, which
+//! is fed from two sources: the contents of files on disk, indexed at
+//! startup, by the file watcher and for workspace folders, and the buffers
+//! of documents open in the editor, indexed on every edit. A buffer holds
+//! the user's unsaved edits, so while a document is open its buffer is the
+//! only source the index may take it from:
+//!
+//! - indexing from disk first takes a [`DiskClaim`], refused for an open
+//!   document, and only stores its result if the claim still
+//!   [holds](BufferOverlay::holds), as the document may have been opened
+//!   while the file was being read and parsed;
+//! - opening or closing a document voids every claim taken before, so
+//!   contents read before a buffer was edited and saved never replace what
+//!   the index takes from disk when the buffer closes.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use dashmap::DashMap;
+use tower_lsp::lsp_types::Url;
+
+/// Permission to index a document from disk, given while it is not open
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiskClaim {
+    uri: Url,
+    /// Epoch of the last time the document was opened or closed, 0 if never
+    epoch: u64,
+}
+
+impl DiskClaim {
+    pub fn uri(&self) -> &Url {
+        &self.uri
+    }
+}
+
+/// Buffer state of one document
+#[derive(Debug, Clone, Copy)]
+struct Buffer {
+    open: bool,
+    /// Epoch of the last time the document was opened or closed
+    epoch: u64,
+}
+
+/// Which documents the workspace index takes from their open buffer
+#[derive(Debug, Default)]
+pub struct BufferOverlay {
+    /// Documents opened at least once; closed ones are kept for their epoch
+    buffers: DashMap<Url, Buffer>,
+    last_epoch: AtomicU64,
+}
+
+impl BufferOverlay {
+    /// Records that `uri` is open, voiding its disk claims.
+    pub fn open(&self, uri: &Url) {
+        self.transition(uri, true);
+    }
+
+    /// Records that `uri` was closed, voiding its disk claims.
+    pub fn close(&self, uri: &Url) {
+        self.transition(uri, false);
+    }
+
+    fn transition(&self, uri: &Url, open: bool) {
+        let epoch = self.last_epoch.fetch_add(1, Ordering::Relaxed) + 1;
+        self.buffers.insert(uri.clone(), Buffer { open, epoch });
+    }
+
+    /// Whether the index takes `uri` from its open buffer.
+    pub fn is_open(&self, uri: &Url) -> bool {
+        self.buffers.get(uri).is_some_and(|buffer| buffer.open)
+    }
+
+    /// A claim to index `uri` from disk, or `None` while it is open.
+    pub fn disk_claim(&self, uri: &Url) -> Option<DiskClaim> {
+        match self.buffers.get(uri).map(|buffer| *buffer) {
+            Some(Buffer { open: true, .. }) => None,
+            Some(Buffer { epoch, .. }) => Some(DiskClaim { uri: uri.clone(), epoch }),
+            None => Some(DiskClaim { uri: uri.clone(), epoch: 0 }),
+        }
+    }
+
+    /// Whether the disk contents indexed under `claim` may be stored: the
+    /// document was neither opened nor closed since the claim was taken.
+    pub fn holds(&self, claim: &DiskClaim) -> bool {
+        self.buffers.get(&claim.uri).map_or(0, |buffer| buffer.epoch) == claim.epoch
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uri() -> Url {
+        Url::parse("file:///workspace/contract.rho").unwrap()
+    }
+
+    #[test]
+    fn test_open_document_refuses_disk_claims() {
+        let overlay = BufferOverlay::default();
+        assert!(overlay.disk_claim(&uri()).is_some());
+
+        overlay.open(&uri());
+        assert!(overlay.is_open(&uri()));
+        assert!(overlay.disk_claim(&uri()).is_none());
+    }
+
+    #[test]
+    fn test_opening_voids_claims_in_flight() {
+        let overlay = BufferOverlay::default();
+        let claim = overlay.disk_claim(&uri()).unwrap();
+        assert!(overlay.holds(&claim));
+
+        overlay.open(&uri());
+        assert!(!overlay.holds(&claim));
+    }
+
+    #[test]
+    fn test_claims_taken_before_an_edit_session_stay_void() {
+        let overlay = BufferOverlay::default();
+        let stale = overlay.disk_claim(&uri()).unwrap();
+        overlay.open(&uri());
+        overlay.close(&uri());
+        assert!(!overlay.is_open(&uri()));
+        assert!(!overlay.holds(&stale));
+
+        let fresh = overlay.disk_claim(&uri()).unwrap();
+        assert!(overlay.holds(&fresh));
+    }
+
+    #[test]
+    fn test_claims_are_per_document() {
+        let overlay = BufferOverlay::default();
+        let other = Url::parse("file:///workspace/other.rho").unwrap();
+        let claim = overlay.disk_claim(&other).unwrap();
+        overlay.open(&uri());
+        assert!(overlay.holds(&claim));
+        assert_eq!(claim.uri(), &other);
+    }
+}
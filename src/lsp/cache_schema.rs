@@ -0,0 +1,158 @@
+//! Schema versions of the persistent caches
+//!
+//! Files the server keeps across sessions (the indexing checkpoints of
+//! `crate::lsp::index_checkpoint`) start with a header line naming their
+//! format and its version:
+//!
+//! ```json
+//! { "schema": "index-checkpoint", "version": 1 }
+//! ```
+//!
+//! When a cache is opened, a header of the current version is accepted; an
+//! older version is brought up to date by the [`Migration`]s of its
+//! [`CacheSchema`], one record at a time; anything else (no header, another
+//! format, a newer version, an older version without a migration) makes the
+//! cache invalid and it is discarded rather than read. Changing the format of
+//! a cache therefore means bumping its version and, to keep existing caches,
+//! adding a migration from the previous one.
+//!
+//! The `rholang.clearCache` command deletes every cache file.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::debug;
+
+/// Clears the persistent caches
+pub const CLEAR_CACHE_COMMAND: &str = "rholang.clearCache";
+
+/// Rewrites one record of version `from` to version `from + 1`; `None` drops the record.
+pub type Migration = fn(Value) -> Option<Value>;
+
+/// Header line of a cache file
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SchemaHeader {
+    pub schema: String,
+    pub version: u32,
+}
+
+/// Format of one kind of cache file
+#[derive(Debug, Clone, Copy)]
+pub struct CacheSchema {
+    /// Name written in the header
+    pub name: &'static str,
+    /// Version written by this build
+    pub version: u32,
+    /// Migration `i` upgrades version `first_migrated + i` records
+    pub migrations: &'static [Migration],
+    /// Oldest version [`CacheSchema::migrations`] start from
+    pub first_migrated: u32,
+}
+
+/// What to do with a cache file, judging by its header
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaCheck {
+    /// Written by this build's format
+    Current,
+    /// Written by an older format that migrates; the value is its version
+    Migrate(u32),
+    /// Unreadable by this build; discard it
+    Invalid(String),
+}
+
+impl CacheSchema {
+    /// The header this build writes.
+    pub fn header(&self) -> SchemaHeader {
+        SchemaHeader { schema: self.name.to_string(), version: self.version }
+    }
+
+    /// The header line this build writes, without a newline.
+    pub fn header_line(&self) -> String {
+        serde_json::to_string(&self.header()).expect("headers serialize")
+    }
+
+    /// Judges a cache file by its first line.
+    pub fn check(&self, first_line: Option<&str>) -> SchemaCheck {
+        let Some(header) = first_line.and_then(|line| serde_json::from_str::<SchemaHeader>(line).ok()) else {
+            return SchemaCheck::Invalid("no schema header".to_string());
+        };
+        if header.schema != self.name {
+            return SchemaCheck::Invalid(format!("schema {} instead of {}", header.schema, self.name));
+        }
+        let migrated_up_to = self.first_migrated + self.migrations.len() as u32;
+        match header.version {
+            version if version == self.version => SchemaCheck::Current,
+            version if version >= self.first_migrated && version < self.version && migrated_up_to == self.version => {
+                SchemaCheck::Migrate(version)
+            }
+            version => SchemaCheck::Invalid(format!("version {} while this build reads {}", version, self.version)),
+        }
+    }
+
+    /// Upgrades `record`, written with `version`, to the current version.
+    pub fn migrate(&self, mut record: Value, version: u32) -> Option<Value> {
+        let first = version.checked_sub(self.first_migrated)? as usize;
+        for migration in self.migrations.get(first..)? {
+            record = migration(record)?;
+        }
+        Some(record)
+    }
+}
+
+/// Deletes the files under `dir`, returning how many there were.
+pub fn clear_dir(dir: &Path) -> io::Result<usize> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e),
+    };
+    let mut removed = 0;
+    for path in entries.filter_map(Result::ok).map(|entry| entry.path()).collect::<Vec<PathBuf>>() {
+        if path.is_file() {
+            fs::remove_file(&path)?;
+            removed += 1;
+        }
+    }
+    debug!("Removed {} cache file(s) from {}", removed, dir.display());
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rename_field(mut record: Value) -> Option<Value> {
+        let value = record.as_object_mut()?.remove("old")?;
+        record["new"] = value;
+        Some(record)
+    }
+
+    const SCHEMA: CacheSchema = CacheSchema {
+        name: "test-cache",
+        version: 3,
+        migrations: &[rename_field, Some],
+        first_migrated: 1,
+    };
+
+    #[test]
+    fn test_check_headers() {
+        assert_eq!(SCHEMA.check(Some(&SCHEMA.header_line())), SchemaCheck::Current);
+        assert_eq!(SCHEMA.check(Some(r#"{"schema":"test-cache","version":1}"#)), SchemaCheck::Migrate(1));
+        assert!(matches!(SCHEMA.check(Some(r#"{"schema":"test-cache","version":0}"#)), SchemaCheck::Invalid(_)));
+        assert!(matches!(SCHEMA.check(Some(r#"{"schema":"test-cache","version":4}"#)), SchemaCheck::Invalid(_)));
+        assert!(matches!(SCHEMA.check(Some(r#"{"schema":"other","version":3}"#)), SchemaCheck::Invalid(_)));
+        assert!(matches!(SCHEMA.check(Some(r#"{"path":"/a.rho"}"#)), SchemaCheck::Invalid(_)));
+        assert!(matches!(SCHEMA.check(None), SchemaCheck::Invalid(_)));
+    }
+
+    #[test]
+    fn test_migrate_records() {
+        assert_eq!(SCHEMA.migrate(json!({ "old": 1 }), 1), Some(json!({ "new": 1 })));
+        assert_eq!(SCHEMA.migrate(json!({ "new": 1 }), 2), Some(json!({ "new": 1 })));
+        assert_eq!(SCHEMA.migrate(json!({ "other": 1 }), 1), None, "records a migration rejects are dropped");
+    }
+}
@@ -0,0 +1,233 @@
+//! Call hierarchy of contracts
+//!
+//! A call is a send (`!` or `!?`) whose channel and arguments match a
+//! contract, as decided by [`match_contract`]. The hierarchy items are
+//! contracts; sends that are not inside any contract are attributed to their
+//! document, shown as a `File` item.
+//!
+//! - `textDocument/prepareCallHierarchy` on a contract name yields that
+//!   contract; on the channel of a send, the contracts the send calls.
+//! - Incoming calls of a contract are grouped by the contract (or document)
+//!   the calling sends are in.
+//! - Outgoing calls of a contract are the sends in its body that call a
+//!   contract, grouped by the contract called.
+//!
+//! The functions here work on one document at a time; the backend runs them
+//! over the workspace's contract and call indexes (`global_contracts`,
+//! `global_calls`).
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use ropey::Rope;
+use tower_lsp::lsp_types::{CallHierarchyItem, Position as LspPosition, Range, SymbolKind, Url};
+
+use crate::ir::rholang_node::{collect_calls, collect_contracts, match_contract, Position as IrPosition, RholangNode};
+use crate::lsp::contracts_outline::{contract_signature, node_name};
+use crate::lsp::line_index::{lsp_range, LineIndex};
+
+/// Absolute positions of the IR nodes of a document, by node address
+pub type Positions = HashMap<usize, (IrPosition, IrPosition)>;
+
+fn node_key(node: &RholangNode) -> usize {
+    node as *const RholangNode as usize
+}
+
+/// LSP range of `node`, in the document `text`.
+pub fn node_range(node: &RholangNode, positions: &Positions, text: &Rope, line_index: &LineIndex) -> Option<Range> {
+    positions.get(&node_key(node)).map(|span| lsp_range(text, line_index, span))
+}
+
+/// Whether `node` spans byte `offset`, ends included.
+fn node_contains(node: &RholangNode, positions: &Positions, offset: usize) -> bool {
+    positions.get(&node_key(node)).is_some_and(|(start, end)| start.byte <= offset && offset <= end.byte)
+}
+
+/// Channel and arguments of a send.
+pub fn send_parts(send: &RholangNode) -> Option<(&Arc<RholangNode>, &crate::ir::rholang_node::RholangNodeVector)> {
+    match send {
+        RholangNode::Send { channel, inputs, .. } | RholangNode::SendSync { channel, inputs, .. } => Some((channel, inputs)),
+        _ => None,
+    }
+}
+
+/// Whether `send` calls `contract`.
+pub fn calls_contract(send: &RholangNode, contract: &Arc<RholangNode>) -> bool {
+    send_parts(send).is_some_and(|(channel, inputs)| match_contract(channel, inputs, contract))
+}
+
+/// Call hierarchy item of `contract`, declared in `uri`.
+pub fn contract_item(
+    uri: &Url,
+    contract: &Arc<RholangNode>,
+    positions: &Positions,
+    text: &Rope,
+    line_index: &LineIndex,
+) -> Option<CallHierarchyItem> {
+    let RholangNode::Contract { name, .. } = &**contract else {
+        return None;
+    };
+    Some(CallHierarchyItem {
+        name: node_name(name)?,
+        kind: SymbolKind::FUNCTION,
+        tags: None,
+        detail: contract_signature(contract, positions, text),
+        uri: uri.clone(),
+        range: node_range(contract, positions, text, line_index)?,
+        selection_range: node_range(name, positions, text, line_index)?,
+        data: None,
+    })
+}
+
+/// Call hierarchy item standing for the top level of the document `uri`.
+pub fn file_item(uri: &Url, ir: &Arc<RholangNode>, positions: &Positions, text: &Rope, line_index: &LineIndex) -> CallHierarchyItem {
+    let name = uri.path_segments().and_then(|mut segments| segments.next_back()).unwrap_or("").to_string();
+    let range = node_range(ir, positions, text, line_index).unwrap_or_default();
+    CallHierarchyItem {
+        name,
+        kind: SymbolKind::FILE,
+        tags: None,
+        detail: None,
+        uri: uri.clone(),
+        range,
+        selection_range: Range::new(range.start, range.start),
+        data: None,
+    }
+}
+
+/// The contract of `ir`, the IR of `text`, whose name is at `position`.
+pub fn contract_named_at(
+    ir: &Arc<RholangNode>,
+    positions: &Positions,
+    text: &Rope,
+    line_index: &LineIndex,
+    position: LspPosition,
+) -> Option<Arc<RholangNode>> {
+    let offset = line_index.offset(position.line as usize, position.character as usize, text)?;
+    let mut contracts = Vec::new();
+    collect_contracts(ir, &mut contracts);
+    contracts.into_iter().find(|contract| match &**contract {
+        RholangNode::Contract { name, .. } => node_contains(name, positions, offset),
+        _ => false,
+    })
+}
+
+/// The send of `ir`, the IR of `text`, whose channel is at `position`.
+pub fn send_at(
+    ir: &Arc<RholangNode>,
+    positions: &Positions,
+    text: &Rope,
+    line_index: &LineIndex,
+    position: LspPosition,
+) -> Option<Arc<RholangNode>> {
+    let offset = line_index.offset(position.line as usize, position.character as usize, text)?;
+    let mut sends = Vec::new();
+    collect_calls(ir, &mut sends);
+    sends
+        .into_iter()
+        .find(|send| send_parts(send).is_some_and(|(channel, _)| node_contains(channel, positions, offset)))
+}
+
+/// The contract of `ir`, the IR of `text`, an item returned by
+/// [`contract_item`] stands for.
+pub fn contract_for_item(
+    ir: &Arc<RholangNode>,
+    positions: &Positions,
+    text: &Rope,
+    line_index: &LineIndex,
+    item: &CallHierarchyItem,
+) -> Option<Arc<RholangNode>> {
+    contract_named_at(ir, positions, text, line_index, item.selection_range.start)
+}
+
+/// The innermost of `contracts` whose body holds `send`.
+pub fn enclosing_contract(
+    contracts: &[Arc<RholangNode>],
+    positions: &Positions,
+    send: &RholangNode,
+) -> Option<Arc<RholangNode>> {
+    let (send_start, send_end) = positions.get(&node_key(send))?;
+    contracts
+        .iter()
+        .filter_map(|contract| Some((contract, positions.get(&node_key(contract))?)))
+        .filter(|(_, (start, end))| start.byte <= send_start.byte && send_end.byte <= end.byte)
+        .max_by_key(|(_, (start, _))| start.byte)
+        .map(|(contract, _)| contract.clone())
+}
+
+/// The sends in the body of `contract`, or at the top level of `ir` (outside
+/// every contract) when `contract` is `None`.
+pub fn sends_in(ir: &Arc<RholangNode>, contract: Option<&Arc<RholangNode>>, positions: &Positions) -> Vec<Arc<RholangNode>> {
+    let mut sends = Vec::new();
+    match contract.map(|contract| &**contract) {
+        Some(RholangNode::Contract { proc, .. }) => collect_calls(proc, &mut sends),
+        Some(_) => {}
+        None => {
+            collect_calls(ir, &mut sends);
+            let mut contracts = Vec::new();
+            collect_contracts(ir, &mut contracts);
+            sends.retain(|send| enclosing_contract(&contracts, positions, send).is_none());
+        }
+    }
+    sends
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree_sitter::parse_with_positions;
+
+    fn parse(source: &str) -> (Arc<RholangNode>, Positions, Rope, LineIndex) {
+        let rope = Rope::from_str(source);
+        let (ir, positions) = parse_with_positions(source);
+        (ir, positions, rope, LineIndex::new(source))
+    }
+
+    const SOURCE: &str = r#"new transfer, log in {
+  contract transfer(@from, @to, ret) = {
+    log!("moving") | ret!(true)
+  } |
+  contract log(@message) = { Nil } |
+  transfer!("a", "b", *log) |
+  transfer!("a")
+}"#;
+
+    #[test]
+    fn test_prepare_and_items() {
+        let uri = Url::parse("file:///bank.rho").unwrap();
+        let (ir, positions, rope, line_index) = parse(SOURCE);
+
+        let transfer = contract_named_at(&ir, &positions, &rope, &line_index, LspPosition::new(1, 12)).unwrap();
+        let item = contract_item(&uri, &transfer, &positions, &rope, &line_index).unwrap();
+        assert_eq!(item.name, "transfer");
+        assert_eq!(item.detail.as_deref(), Some("contract transfer(@from, @to, ret)"));
+        assert_eq!(item.selection_range.start, LspPosition::new(1, 11));
+        assert!(Arc::ptr_eq(&contract_for_item(&ir, &positions, &rope, &line_index, &item).unwrap(), &transfer));
+
+        // On the channel of a call, the send is found; the arity decides what it calls
+        let call = send_at(&ir, &positions, &rope, &line_index, LspPosition::new(5, 3)).unwrap();
+        assert!(calls_contract(&call, &transfer));
+        let bad_call = send_at(&ir, &positions, &rope, &line_index, LspPosition::new(6, 3)).unwrap();
+        assert!(!calls_contract(&bad_call, &transfer));
+
+        assert_eq!(file_item(&uri, &ir, &positions, &rope, &line_index).name, "bank.rho");
+    }
+
+    #[test]
+    fn test_sends_by_enclosing_contract() {
+        let (ir, positions, rope, line_index) = parse(SOURCE);
+        let mut contracts = Vec::new();
+        collect_contracts(&ir, &mut contracts);
+        let transfer = contract_named_at(&ir, &positions, &rope, &line_index, LspPosition::new(1, 12)).unwrap();
+        let log = contract_named_at(&ir, &positions, &rope, &line_index, LspPosition::new(4, 12)).unwrap();
+
+        let body = sends_in(&ir, Some(&transfer), &positions);
+        assert_eq!(body.len(), 2);
+        assert!(body.iter().any(|send| calls_contract(send, &log)));
+        assert!(Arc::ptr_eq(&enclosing_contract(&contracts, &positions, &body[0]).unwrap(), &transfer));
+
+        let top_level = sends_in(&ir, None, &positions);
+        assert_eq!(top_level.len(), 2);
+        assert!(top_level.iter().all(|send| enclosing_contract(&contracts, &positions, send).is_none()));
+    }
+}
@@ -0,0 +1,447 @@
+//! Client capabilities negotiated at `initialize`
+//!
+//! Features used to assume a modern client (VS Code): Markdown hovers,
+//! snippet completions, nested document symbols, relative semantic tokens.
+//! Minimal clients such as Vim or Kakoune plugins may support none of these
+//! and show raw `**Markdown**` or `${1:snippet}` text, or drop the response
+//! entirely. [`NegotiatedCapabilities`] records what the client declared and
+//! the `adapt_*` methods rewrite responses into forms it understands:
+//!
+//! | Capability missing              | Fallback                                  |
+//! |---------------------------------|-------------------------------------------|
+//! | Markdown hovers / documentation | Plain text, fences and emphasis removed   |
+//! | Snippet completions             | Plain insert text, placeholders expanded  |
+//! | Completion label details        | Label details dropped                     |
+//! | Hierarchical document symbols   | Flat `SymbolInformation` with containers  |
+//! | Diagnostic related information  | Related information dropped               |
+//! | Diagnostic tags                 | Tags dropped                              |
+//! | Relative semantic tokens        | Semantic tokens not advertised            |
+//!
+//! The position encoding is negotiated the LSP 3.17 way: the first of the
+//! client's `general.positionEncodings` that the server supports (UTF-8 or
+//! UTF-16), or UTF-16 when it lists none of them.
+
+use tower_lsp::lsp_types::{
+    ClientCapabilities, CompletionItem, CompletionTextEdit, Diagnostic, DiagnosticTag, Documentation,
+    DocumentSymbol, FoldingRange, DocumentSymbolResponse, Hover, HoverContents, InsertTextFormat, MarkedString, MarkupContent,
+    MarkupKind, PositionEncodingKind, SymbolInformation, TokenFormat, Url,
+};
+
+use crate::lsp::line_index::PositionEncoding;
+
+/// What the client supports, as declared in its `ClientCapabilities`
+///
+/// `Default` is a client that declared nothing.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NegotiatedCapabilities {
+    /// Hover contents may be Markdown
+    pub hover_markdown: bool,
+    /// Completion item documentation may be Markdown
+    pub completion_markdown: bool,
+    /// Completion items may use snippet syntax
+    pub snippets: bool,
+    /// Completion items may carry `labelDetails`
+    pub label_details: bool,
+    /// `textDocument/documentSymbol` may return nested `DocumentSymbol`s
+    pub hierarchical_symbols: bool,
+    /// Semantic tokens in the relative format are supported
+    pub semantic_tokens: bool,
+    /// Diagnostics may carry related information
+    pub related_information: bool,
+    /// Diagnostic tags the client renders
+    pub diagnostic_tags: Vec<DiagnosticTag>,
+    /// `window/workDoneProgress` is supported
+    pub work_done_progress: bool,
+    /// Folding ranges are whole lines; start and end characters are ignored
+    pub line_folding_only: bool,
+    /// `workspace/didChangeWatchedFiles` watchers may be registered dynamically
+    pub watched_files_registration: bool,
+    /// `workspace/semanticTokens/refresh` is supported
+    pub semantic_tokens_refresh: bool,
+    /// `workspace/codeLens/refresh` is supported
+    pub code_lens_refresh: bool,
+    /// `workspace/inlayHint/refresh` is supported
+    pub inlay_hint_refresh: bool,
+    /// Settings can be pulled with `workspace/configuration`
+    pub workspace_configuration: bool,
+    /// Code units of the columns of LSP positions
+    pub position_encoding: PositionEncoding,
+}
+
+impl NegotiatedCapabilities {
+    /// Reads the capabilities a client declared in `initialize`.
+    pub fn from_client(capabilities: &ClientCapabilities) -> Self {
+        let text_document = capabilities.text_document.as_ref();
+        let completion_item = text_document
+            .and_then(|td| td.completion.as_ref())
+            .and_then(|completion| completion.completion_item.as_ref());
+        let diagnostics = text_document.and_then(|td| td.publish_diagnostics.as_ref());
+        let workspace = capabilities.workspace.as_ref();
+
+        NegotiatedCapabilities {
+            hover_markdown: supports_markdown(
+                text_document.and_then(|td| td.hover.as_ref()).and_then(|hover| hover.content_format.as_ref()),
+            ),
+            completion_markdown: supports_markdown(completion_item.and_then(|item| item.documentation_format.as_ref())),
+            snippets: completion_item.and_then(|item| item.snippet_support).unwrap_or(false),
+            label_details: completion_item.and_then(|item| item.label_details_support).unwrap_or(false),
+            hierarchical_symbols: text_document
+                .and_then(|td| td.document_symbol.as_ref())
+                .and_then(|symbols| symbols.hierarchical_document_symbol_support)
+                .unwrap_or(false),
+            semantic_tokens: text_document
+                .and_then(|td| td.semantic_tokens.as_ref())
+                .is_some_and(|tokens| tokens.formats.contains(&TokenFormat::RELATIVE)),
+            related_information: diagnostics.and_then(|d| d.related_information).unwrap_or(false),
+            diagnostic_tags: diagnostics
+                .and_then(|d| d.tag_support.as_ref())
+                .map(|tags| tags.value_set.clone())
+                .unwrap_or_default(),
+            work_done_progress: capabilities
+                .window
+                .as_ref()
+                .and_then(|window| window.work_done_progress)
+                .unwrap_or(false),
+            line_folding_only: text_document
+                .and_then(|td| td.folding_range.as_ref())
+                .and_then(|folding| folding.line_folding_only)
+                .unwrap_or(false),
+            watched_files_registration: workspace
+                .and_then(|workspace| workspace.did_change_watched_files.as_ref())
+                .and_then(|watched| watched.dynamic_registration)
+                .unwrap_or(false),
+            semantic_tokens_refresh: workspace
+                .and_then(|workspace| workspace.semantic_tokens.as_ref())
+                .and_then(|tokens| tokens.refresh_support)
+                .unwrap_or(false),
+            code_lens_refresh: workspace
+                .and_then(|workspace| workspace.code_lens.as_ref())
+                .and_then(|lens| lens.refresh_support)
+                .unwrap_or(false),
+            inlay_hint_refresh: workspace
+                .and_then(|workspace| workspace.inlay_hint.as_ref())
+                .and_then(|hints| hints.refresh_support)
+                .unwrap_or(false),
+            workspace_configuration: workspace.and_then(|workspace| workspace.configuration).unwrap_or(false),
+            position_encoding: capabilities
+                .general
+                .as_ref()
+                .and_then(|general| general.position_encodings.as_ref())
+                .and_then(|encodings| encodings.iter().find_map(position_encoding))
+                .unwrap_or_default(),
+        }
+    }
+
+    /// The negotiated encoding, as returned in `ServerCapabilities::position_encoding`.
+    pub fn position_encoding_kind(&self) -> PositionEncodingKind {
+        match self.position_encoding {
+            PositionEncoding::Utf8 => PositionEncodingKind::UTF8,
+            PositionEncoding::Utf16 => PositionEncodingKind::UTF16,
+        }
+    }
+
+    /// Converts Markdown hover contents to plain text if the client cannot render Markdown.
+    pub fn adapt_hover(&self, hover: Hover) -> Hover {
+        if self.hover_markdown {
+            return hover;
+        }
+        let contents = match hover.contents {
+            HoverContents::Markup(markup) => HoverContents::Markup(plaintext(markup)),
+            HoverContents::Scalar(MarkedString::LanguageString(code)) => {
+                HoverContents::Scalar(MarkedString::String(code.value))
+            }
+            HoverContents::Array(strings) => HoverContents::Array(
+                strings
+                    .into_iter()
+                    .map(|string| match string {
+                        MarkedString::LanguageString(code) => MarkedString::String(code.value),
+                        plain => plain,
+                    })
+                    .collect(),
+            ),
+            contents => contents,
+        };
+        Hover { contents, range: hover.range }
+    }
+
+    /// Rewrites completion items the client could not display or insert correctly.
+    pub fn adapt_completions(&self, items: &mut [CompletionItem]) {
+        for item in items {
+            if !self.snippets && item.insert_text_format == Some(InsertTextFormat::SNIPPET) {
+                item.insert_text = item.insert_text.take().map(|text| snippet_to_plaintext(&text));
+                if let Some(CompletionTextEdit::Edit(edit)) = &mut item.text_edit {
+                    edit.new_text = snippet_to_plaintext(&edit.new_text);
+                }
+                item.insert_text_format = Some(InsertTextFormat::PLAIN_TEXT);
+            }
+            if !self.completion_markdown {
+                if let Some(Documentation::MarkupContent(markup)) = item.documentation.take() {
+                    item.documentation = Some(Documentation::String(plaintext(markup).value));
+                }
+            }
+            if !self.label_details {
+                item.label_details = None;
+            }
+        }
+    }
+
+    /// Nested symbols, or the same symbols flattened for clients without hierarchy support.
+    pub fn adapt_document_symbols(&self, uri: &Url, symbols: Vec<DocumentSymbol>) -> DocumentSymbolResponse {
+        if self.hierarchical_symbols {
+            return DocumentSymbolResponse::Nested(symbols);
+        }
+        let mut flat = Vec::new();
+        flatten_symbols(uri, symbols, None, &mut flat);
+        DocumentSymbolResponse::Flat(flat)
+    }
+
+    /// Drops the start and end characters of folding ranges for clients that fold whole lines.
+    pub fn adapt_folding_ranges(&self, ranges: &mut [FoldingRange]) {
+        if self.line_folding_only {
+            for range in ranges {
+                range.start_character = None;
+                range.end_character = None;
+            }
+        }
+    }
+
+    /// Drops diagnostic fields the client did not declare support for.
+    pub fn adapt_diagnostics(&self, diagnostics: &mut [Diagnostic]) {
+        for diagnostic in diagnostics {
+            if !self.related_information {
+                diagnostic.related_information = None;
+            }
+            if let Some(tags) = &mut diagnostic.tags {
+                tags.retain(|tag| self.diagnostic_tags.contains(tag));
+                if tags.is_empty() {
+                    diagnostic.tags = None;
+                }
+            }
+        }
+    }
+}
+
+/// The encoding of `kind`, if the server supports it.
+fn position_encoding(kind: &PositionEncodingKind) -> Option<PositionEncoding> {
+    if *kind == PositionEncodingKind::UTF8 {
+        Some(PositionEncoding::Utf8)
+    } else if *kind == PositionEncodingKind::UTF16 {
+        Some(PositionEncoding::Utf16)
+    } else {
+        None
+    }
+}
+
+fn supports_markdown(formats: Option<&Vec<MarkupKind>>) -> bool {
+    formats.is_some_and(|formats| formats.contains(&MarkupKind::Markdown))
+}
+
+fn plaintext(markup: MarkupContent) -> MarkupContent {
+    match markup.kind {
+        MarkupKind::Markdown => MarkupContent { kind: MarkupKind::PlainText, value: markdown_to_plaintext(&markup.value) },
+        MarkupKind::PlainText => markup,
+    }
+}
+
+/// Markdown as readable plain text: code fences, rules, heading marks,
+/// emphasis and inline code markers removed.
+pub fn markdown_to_plaintext(markdown: &str) -> String {
+    let mut lines = Vec::new();
+    for line in markdown.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            continue;
+        }
+        if trimmed == "---" {
+            lines.push(String::new());
+            continue;
+        }
+        let line = line.trim_start_matches('#').trim_start_matches(' ');
+        lines.push(line.replace("**", "").replace('`', ""));
+    }
+    // Removed fences and rules can leave runs of blank lines
+    let mut text = String::new();
+    for line in lines {
+        if line.is_empty() && (text.is_empty() || text.ends_with("\n\n")) {
+            continue;
+        }
+        text.push_str(&line);
+        text.push('\n');
+    }
+    text.trim_end().to_string()
+}
+
+/// Snippet text as plain text: tab stops removed, placeholders and choices
+/// replaced by their default text, escapes resolved.
+pub fn snippet_to_plaintext(snippet: &str) -> String {
+    let chars: Vec<char> = snippet.chars().collect();
+    let mut out = String::new();
+    expand_snippet(&chars, &mut 0, &mut out, false);
+    out
+}
+
+/// Expands `chars[*i..]` into `out`, stopping at the `}` closing a placeholder if `nested`.
+fn expand_snippet(chars: &[char], i: &mut usize, out: &mut String, nested: bool) {
+    while *i < chars.len() {
+        match chars[*i] {
+            '\\' if *i + 1 < chars.len() => {
+                out.push(chars[*i + 1]);
+                *i += 2;
+            }
+            '}' if nested => {
+                *i += 1;
+                return;
+            }
+            '$' if chars.get(*i + 1).is_some_and(|c| c.is_ascii_digit()) => {
+                *i += 1;
+                while chars.get(*i).is_some_and(|c| c.is_ascii_digit()) {
+                    *i += 1;
+                }
+            }
+            '$' if chars.get(*i + 1) == Some(&'{') => {
+                *i += 2;
+                while chars.get(*i).is_some_and(|c| c.is_ascii_alphanumeric() || *c == '_') {
+                    *i += 1;
+                }
+                match chars.get(*i) {
+                    Some(':') => {
+                        *i += 1;
+                        expand_snippet(chars, i, out, true);
+                    }
+                    Some('|') => {
+                        // Choice: keep the first option
+                        *i += 1;
+                        let mut first = true;
+                        while *i < chars.len() && chars[*i] != '|' {
+                            if chars[*i] == ',' {
+                                first = false;
+                            } else if first {
+                                out.push(chars[*i]);
+                            }
+                            *i += 1;
+                        }
+                        *i += 2; // `|}`
+                    }
+                    _ => *i += 1, // `}` of `${1}`
+                }
+            }
+            c => {
+                out.push(c);
+                *i += 1;
+            }
+        }
+    }
+}
+
+#[allow(deprecated)]
+fn flatten_symbols(uri: &Url, symbols: Vec<DocumentSymbol>, container: Option<&str>, out: &mut Vec<SymbolInformation>) {
+    for symbol in symbols {
+        out.push(SymbolInformation {
+            name: symbol.name.clone(),
+            kind: symbol.kind,
+            tags: symbol.tags.clone(),
+            deprecated: symbol.deprecated,
+            location: tower_lsp::lsp_types::Location { uri: uri.clone(), range: symbol.range },
+            container_name: container.map(str::to_string),
+        });
+        if let Some(children) = symbol.children {
+            flatten_symbols(uri, children, Some(&symbol.name), out);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tower_lsp::lsp_types::{Position, Range, SymbolKind};
+
+    fn client(capabilities: serde_json::Value) -> NegotiatedCapabilities {
+        NegotiatedCapabilities::from_client(&serde_json::from_value(capabilities).unwrap())
+    }
+
+    #[test]
+    fn test_from_client_capabilities() {
+        assert_eq!(client(json!({})), NegotiatedCapabilities::default());
+
+        let modern = client(json!({
+            "textDocument": {
+                "hover": { "contentFormat": ["markdown", "plaintext"] },
+                "completion": { "completionItem": { "snippetSupport": true, "documentationFormat": ["markdown"] } },
+                "documentSymbol": { "hierarchicalDocumentSymbolSupport": true },
+                "semanticTokens": { "requests": {}, "tokenTypes": [], "tokenModifiers": [], "formats": ["relative"] },
+                "publishDiagnostics": { "relatedInformation": true, "tagSupport": { "valueSet": [2] } },
+                "foldingRange": { "lineFoldingOnly": true }
+            },
+            "workspace": {
+                "didChangeWatchedFiles": { "dynamicRegistration": true },
+                "semanticTokens": { "refreshSupport": true },
+                "codeLens": { "refreshSupport": true }
+            }
+        }));
+        assert!(modern.hover_markdown && modern.completion_markdown && modern.snippets && modern.line_folding_only);
+        assert!(modern.hierarchical_symbols && modern.semantic_tokens && modern.related_information);
+        assert!(modern.watched_files_registration && modern.semantic_tokens_refresh && modern.code_lens_refresh);
+        assert!(!modern.inlay_hint_refresh);
+        assert!(!modern.label_details);
+        assert_eq!(modern.diagnostic_tags, vec![DiagnosticTag::DEPRECATED]);
+    }
+
+    #[test]
+    fn test_position_encoding_negotiation() {
+        let encoding = |encodings: serde_json::Value| {
+            client(json!({ "general": { "positionEncodings": encodings } })).position_encoding_kind()
+        };
+        assert_eq!(encoding(json!(["utf-8", "utf-16"])), PositionEncodingKind::UTF8);
+        assert_eq!(encoding(json!(["utf-32", "utf-16", "utf-8"])), PositionEncodingKind::UTF16);
+        assert_eq!(encoding(json!(["utf-32"])), PositionEncodingKind::UTF16);
+        assert_eq!(client(json!({})).position_encoding_kind(), PositionEncodingKind::UTF16);
+    }
+
+    #[test]
+    fn test_minimal_client_fallbacks() {
+        let minimal = NegotiatedCapabilities::default();
+
+        let hover = Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: "**transfer**\n```rholang\ncontract transfer(x) = { Nil }\n```\n\n---\n\nMoves `x`".to_string(),
+            }),
+            range: None,
+        };
+        let HoverContents::Markup(markup) = minimal.adapt_hover(hover).contents else { panic!("markup") };
+        assert_eq!(markup.kind, MarkupKind::PlainText);
+        assert_eq!(markup.value, "transfer\ncontract transfer(x) = { Nil }\n\nMoves x");
+
+        let mut items = vec![CompletionItem {
+            label: "transfer".to_string(),
+            insert_text: Some("transfer!(${1:from}, ${2|a,b|}, $0)".to_string()),
+            insert_text_format: Some(InsertTextFormat::SNIPPET),
+            ..Default::default()
+        }];
+        minimal.adapt_completions(&mut items);
+        assert_eq!(items[0].insert_text.as_deref(), Some("transfer!(from, a, )"));
+        assert_eq!(items[0].insert_text_format, Some(InsertTextFormat::PLAIN_TEXT));
+
+        let range = Range::new(Position::new(0, 0), Position::new(2, 1));
+        #[allow(deprecated)]
+        let symbol = |name: &str, children| DocumentSymbol {
+            name: name.to_string(),
+            detail: None,
+            kind: SymbolKind::FUNCTION,
+            tags: None,
+            deprecated: None,
+            range,
+            selection_range: range,
+            children,
+        };
+        let uri = Url::parse("file:///a.rho").unwrap();
+        let DocumentSymbolResponse::Flat(flat) =
+            minimal.adapt_document_symbols(&uri, vec![symbol("outer", Some(vec![symbol("inner", None)]))])
+        else {
+            panic!("flat symbols")
+        };
+        assert_eq!(flat.len(), 2);
+        assert_eq!(flat[1].container_name.as_deref(), Some("outer"));
+    }
+}
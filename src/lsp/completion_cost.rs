@@ -0,0 +1,335 @@
+//! Cost hints for contract completions
+//!
+//! Some contracts are expensive to call: every `!!` in their body leaves a
+//! datum in the tuple space, every `for (… <= …)` or nested `contract`
+//! installs another listener, and a persistent send inside such a loop (or in
+//! a contract that calls itself) multiplies both. [`ContractCost::estimate`]
+//! finds these constructs in a contract body; calling a contract that does a
+//! persistent send from inside a loop is costly in the same way.
+//!
+//! With cost-aware completion on, [`apply_cost_hints`] appends the cost to the
+//! detail of the items invoking costly contracts and sorts them after the
+//! others. It is on by default while RNode validation is active, since code
+//! checked against RNode is meant to be deployed and paid for; the
+//! `costAwareCompletion` initialization option turns it on or off explicitly.
+
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+
+use tower_lsp::lsp_types::{CompletionItem, CompletionItemKind};
+
+use crate::i18n::tr;
+use crate::ir::rholang_node::{
+    find_node_at_position_with_path, Metadata, NodeBase, Position as IrPosition, RholangNode, RholangNodeVector,
+    RholangReceiptVector, RholangSendType,
+};
+use crate::ir::visitor::Visitor;
+use crate::lsp::contracts_outline::node_name;
+
+/// Initialization option turning cost-aware completion on or off
+pub const COST_AWARE_COMPLETION_OPTION: &str = "costAwareCompletion";
+
+/// The client's explicit choice, if it made one.
+pub fn option_in(init_options: Option<&serde_json::Value>) -> Option<bool> {
+    init_options
+        .and_then(|opts| opts.get(COST_AWARE_COMPLETION_OPTION))
+        .and_then(|enabled| enabled.as_bool())
+}
+
+/// A costly construct in a contract body
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CostReason {
+    /// A `!!` send, leaving a datum on every call
+    PersistentSend,
+    /// A `for (… <= …)` or nested `contract`, installing a listener on every call
+    ReplicatedReceive,
+    /// A send to the contract itself
+    Recursion,
+    /// A persistent send inside a replicated receive or a recursive contract
+    PersistentSendInLoop,
+}
+
+impl CostReason {
+    fn message_key(self) -> &'static str {
+        match self {
+            CostReason::PersistentSend => "completion.cost-persistent-send",
+            CostReason::ReplicatedReceive => "completion.cost-replicated-receive",
+            CostReason::Recursion => "completion.cost-recursion",
+            CostReason::PersistentSendInLoop => "completion.cost-persistent-send-in-loop",
+        }
+    }
+}
+
+/// How costly a call is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CostLevel {
+    Moderate,
+    High,
+}
+
+/// Costly constructs reached by one call of a contract
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContractCost {
+    pub reasons: BTreeSet<CostReason>,
+}
+
+impl ContractCost {
+    /// Name and cost of a `Contract` node; `None` for other nodes and unnamed contracts.
+    pub fn estimate(contract: &Arc<RholangNode>) -> Option<(String, ContractCost)> {
+        let RholangNode::Contract { name, proc, .. } = &**contract else {
+            return None;
+        };
+        let name = node_name(name)?;
+        let visitor = CostVisitor { contract: &name, loop_depth: Cell::new(0), reasons: RefCell::new(BTreeSet::new()) };
+        visitor.visit_node(proc);
+
+        let mut reasons = visitor.reasons.into_inner();
+        if reasons.contains(&CostReason::Recursion) && reasons.contains(&CostReason::PersistentSend) {
+            reasons.insert(CostReason::PersistentSendInLoop);
+        }
+        Some((name, ContractCost { reasons }))
+    }
+
+    /// Adds the reasons of another overload of the same contract.
+    pub fn merge(&mut self, other: &ContractCost) {
+        self.reasons.extend(other.reasons.iter().copied());
+    }
+
+    /// Cost of a call made from inside a loop, where persistent sends repeat.
+    pub fn in_loop(&self) -> ContractCost {
+        let mut reasons = self.reasons.clone();
+        if reasons.contains(&CostReason::PersistentSend) {
+            reasons.insert(CostReason::PersistentSendInLoop);
+        }
+        ContractCost { reasons }
+    }
+
+    /// `None` for a cheap contract.
+    pub fn level(&self) -> Option<CostLevel> {
+        if self.reasons.contains(&CostReason::PersistentSendInLoop) {
+            Some(CostLevel::High)
+        } else if self.reasons.is_empty() {
+            None
+        } else {
+            Some(CostLevel::Moderate)
+        }
+    }
+
+    /// Detail suffix, e.g. `cost: high (persistent send in a loop)`.
+    pub fn hint(&self) -> Option<String> {
+        let key = match self.level()? {
+            CostLevel::Moderate => "completion.cost-moderate",
+            CostLevel::High => "completion.cost-high",
+        };
+        let reasons: Vec<String> = self.reasons.iter().map(|reason| tr(reason.message_key(), &[])).collect();
+        Some(tr(key, &[("reasons", &reasons.join(", "))]))
+    }
+}
+
+struct CostVisitor<'a> {
+    contract: &'a str,
+    loop_depth: Cell<usize>,
+    reasons: RefCell<BTreeSet<CostReason>>,
+}
+
+impl CostVisitor<'_> {
+    fn in_loop(&self, proc: &Arc<RholangNode>) {
+        self.loop_depth.set(self.loop_depth.get() + 1);
+        self.visit_node(proc);
+        self.loop_depth.set(self.loop_depth.get() - 1);
+    }
+}
+
+impl Visitor for CostVisitor<'_> {
+    fn visit_send(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        channel: &Arc<RholangNode>,
+        send_type: &RholangSendType,
+        _send_type_pos: &IrPosition,
+        inputs: &RholangNodeVector,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        let mut reasons = self.reasons.borrow_mut();
+        if *send_type == RholangSendType::Multiple {
+            reasons.insert(CostReason::PersistentSend);
+            if self.loop_depth.get() > 0 {
+                reasons.insert(CostReason::PersistentSendInLoop);
+            }
+        }
+        if node_name(channel).as_deref() == Some(self.contract) {
+            reasons.insert(CostReason::Recursion);
+        }
+        drop(reasons);
+
+        for input in inputs.iter() {
+            self.visit_node(input);
+        }
+        Arc::clone(node)
+    }
+
+    fn visit_input(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        receipts: &RholangReceiptVector,
+        proc: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        if is_replicated(receipts) {
+            self.reasons.borrow_mut().insert(CostReason::ReplicatedReceive);
+            self.in_loop(proc);
+        } else {
+            self.visit_node(proc);
+        }
+        Arc::clone(node)
+    }
+
+    fn visit_contract(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        _name: &Arc<RholangNode>,
+        _formals: &RholangNodeVector,
+        _formals_remainder: &Option<Arc<RholangNode>>,
+        proc: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.reasons.borrow_mut().insert(CostReason::ReplicatedReceive);
+        self.in_loop(proc);
+        Arc::clone(node)
+    }
+}
+
+fn is_replicated(receipts: &RholangReceiptVector) -> bool {
+    receipts
+        .iter()
+        .flat_map(|receipt| receipt.iter())
+        .any(|bind| matches!(&**bind, RholangNode::RepeatedBind { .. }))
+}
+
+/// Whether `position` is inside a contract body or a `for (… <= …)`, where a
+/// call runs once per message.
+pub fn in_loop_at(
+    ir: &Arc<RholangNode>,
+    positions: &HashMap<usize, (IrPosition, IrPosition)>,
+    position: IrPosition,
+) -> bool {
+    let Some((_, path)) = find_node_at_position_with_path(ir, positions, position) else {
+        return false;
+    };
+    path.iter().any(|node| match &**node {
+        RholangNode::Contract { .. } => true,
+        RholangNode::Input { receipts, .. } => is_replicated(receipts),
+        _ => false,
+    })
+}
+
+/// Appends cost hints to the contract items of `items` and sorts costly items
+/// after the others, the most costly last.
+///
+/// Ranks already given by `sort_text` are kept within each cost level.
+pub fn apply_cost_hints(items: &mut Vec<CompletionItem>, costs: &HashMap<String, ContractCost>, in_loop: bool) {
+    let mut ranked: Vec<(Option<CostLevel>, CompletionItem)> = items
+        .drain(..)
+        .map(|mut item| (add_cost_hint(&mut item, costs, in_loop), item))
+        .collect();
+    ranked.sort_by_key(|(level, _)| *level);
+    items.extend(ranked.into_iter().map(|(_, item)| item));
+}
+
+/// Adds the cost hint of a contract item; returns its cost level.
+fn add_cost_hint(item: &mut CompletionItem, costs: &HashMap<String, ContractCost>, in_loop: bool) -> Option<CostLevel> {
+    if item.kind != Some(CompletionItemKind::FUNCTION) {
+        return None;
+    }
+    let cost = costs.get(item.filter_text.as_deref().unwrap_or(&item.label))?;
+    let cost = if in_loop { cost.in_loop() } else { cost.clone() };
+    let (level, hint) = (cost.level()?, cost.hint()?);
+
+    item.detail = Some(match item.detail.take() {
+        Some(detail) => format!("{} · {}", detail, hint),
+        None => hint,
+    });
+    let prefix = match level {
+        CostLevel::Moderate => "~",
+        CostLevel::High => "~~",
+    };
+    item.sort_text = Some(format!("{}{}", prefix, item.sort_text.as_deref().unwrap_or(&item.label)));
+    Some(level)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ropey::Rope;
+
+    use crate::ir::rholang_node::collect_contracts;
+    use crate::tree_sitter::{parse_code, parse_to_ir, parse_with_positions};
+
+    fn costs(source: &str) -> HashMap<String, ContractCost> {
+        let ir = parse_to_ir(&parse_code(source), &Rope::from_str(source));
+        let mut contracts = Vec::new();
+        collect_contracts(&ir, &mut contracts);
+        contracts.iter().filter_map(ContractCost::estimate).collect()
+    }
+
+    fn reasons(cost: &ContractCost) -> Vec<CostReason> {
+        cost.reasons.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_estimate_contract_costs() {
+        let costs = costs(
+            r#"contract cheap(ret) = { ret!(1) } |
+contract publish(@v) = { @"board"!!(v) } |
+contract watch(ch) = { for (@x <= ch) { @"log"!!(x) } } |
+contract countdown(@n) = { if (n > 0) { countdown!(n - 1) } }"#,
+        );
+
+        assert_eq!(costs["cheap"].level(), None);
+        assert_eq!(reasons(&costs["publish"]), vec![CostReason::PersistentSend]);
+        assert_eq!(costs["publish"].level(), Some(CostLevel::Moderate));
+        assert_eq!(costs["publish"].in_loop().level(), Some(CostLevel::High));
+        assert_eq!(
+            reasons(&costs["watch"]),
+            vec![CostReason::PersistentSend, CostReason::ReplicatedReceive, CostReason::PersistentSendInLoop]
+        );
+        assert_eq!(costs["watch"].level(), Some(CostLevel::High));
+        assert_eq!(reasons(&costs["countdown"]), vec![CostReason::Recursion]);
+        assert_eq!(costs["watch"].hint().as_deref(), Some("cost: high (persistent send, replicated receive, persistent send in a loop)"));
+    }
+
+    #[test]
+    fn test_costly_items_sort_last_with_hint() {
+        let costs = costs(r#"contract publish(@v) = { @"board"!!(v) } | contract cheap(ret) = { ret!(1) }"#);
+        let item = |label: &str, kind| CompletionItem {
+            label: label.to_string(),
+            kind: Some(kind),
+            detail: Some("contract (1)".to_string()),
+            ..Default::default()
+        };
+        let mut items = vec![
+            item("publish", CompletionItemKind::FUNCTION),
+            item("cheap", CompletionItemKind::FUNCTION),
+            item("new", CompletionItemKind::KEYWORD),
+        ];
+        apply_cost_hints(&mut items, &costs, false);
+
+        let labels: Vec<_> = items.iter().map(|item| item.label.as_str()).collect();
+        assert_eq!(labels, vec!["cheap", "new", "publish"]);
+        assert_eq!(items[2].detail.as_deref(), Some("contract (1) · cost: moderate (persistent send)"));
+        assert_eq!(items[2].sort_text.as_deref(), Some("~publish"));
+        assert_eq!(items[0].sort_text, None);
+    }
+
+    #[test]
+    fn test_in_loop_at() {
+        let source = "contract loop(ch) = {\n  Nil\n} |\nNil";
+        let (ir, positions) = parse_with_positions(source);
+        assert!(in_loop_at(&ir, &positions, IrPosition { row: 1, column: 3, byte: 25 }));
+        assert!(!in_loop_at(&ir, &positions, IrPosition { row: 3, column: 1, byte: 33 }));
+    }
+}
@@ -0,0 +1,235 @@
+//! Completion ranking by per-workspace usage
+//!
+//! Each accepted completion is counted per workspace, and [`rank_completions`]
+//! orders the next completion lists by how often and how recently each label
+//! was accepted, so the contracts and channels a project uses most float to
+//! the top. Items never accepted keep their original relative order below the
+//! used ones.
+//!
+//! Acceptances are reported by the client running the command attached to
+//! each item ([`COMPLETION_ACCEPTED_COMMAND`]). Counts are stored as JSON in
+//! the user cache directory, one file per workspace root, and never leave the
+//! machine. Clients can turn tracking off with the `completionUsageTracking`
+//! initialization option; no file is read or written then.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{Command, CompletionItem};
+use tracing::{debug, warn};
+
+use crate::edit_journal::content_hash;
+
+/// Initialization option disabling usage tracking when `false`
+pub const COMPLETION_USAGE_OPTION: &str = "completionUsageTracking";
+
+/// Command run by the client when a completion item is accepted
+pub const COMPLETION_ACCEPTED_COMMAND: &str = "rholang.completionAccepted";
+
+/// Labels remembered per workspace; the least recently used are dropped first
+pub const MAX_TRACKED_LABELS: usize = 500;
+
+/// Age after which an acceptance counts half as much
+const HALF_LIFE_SECS: f64 = 14.0 * 24.0 * 60.0 * 60.0;
+
+/// Whether the client left usage tracking enabled (the default).
+pub fn enabled_in(init_options: Option<&serde_json::Value>) -> bool {
+    init_options
+        .and_then(|opts| opts.get(COMPLETION_USAGE_OPTION))
+        .and_then(|enabled| enabled.as_bool())
+        .unwrap_or(true)
+}
+
+/// How often and when a label was last accepted
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageEntry {
+    pub count: u32,
+    /// Seconds since the Unix epoch
+    pub last_used: u64,
+}
+
+impl UsageEntry {
+    /// Frequency weighted by recency: the count halves every [`HALF_LIFE_SECS`].
+    pub fn score(&self, now: u64) -> f64 {
+        let age = now.saturating_sub(self.last_used) as f64;
+        f64::from(self.count) * 0.5_f64.powf(age / HALF_LIFE_SECS)
+    }
+}
+
+#[derive(Debug, Default)]
+struct UsageStore {
+    /// File the counts are saved to; `None` while tracking is off
+    path: Option<PathBuf>,
+    entries: HashMap<String, UsageEntry>,
+}
+
+/// Accepted completions of the current workspace
+///
+/// Starts disabled; [`CompletionUsage::open`] loads the counts of a workspace
+/// root once the server knows it.
+#[derive(Debug, Default)]
+pub struct CompletionUsage {
+    store: Mutex<UsageStore>,
+}
+
+impl CompletionUsage {
+    /// Loads the counts of the workspace at `root` from the user cache directory.
+    pub fn open(&self, root: &Path) {
+        match usage_file(root) {
+            Ok(path) => self.open_at(path),
+            Err(e) => warn!("Completion usage tracking unavailable: {}", e),
+        }
+    }
+
+    /// Loads the counts stored at `path` and saves later acceptances there.
+    pub fn open_at(&self, path: PathBuf) {
+        let entries = match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                warn!("Ignoring unreadable completion usage file {}: {}", path.display(), e);
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+        debug!("Loaded usage of {} completion label(s) from {}", entries.len(), path.display());
+        *self.store.lock() = UsageStore { path: Some(path), entries };
+    }
+
+    /// Whether acceptances are being tracked.
+    pub fn is_enabled(&self) -> bool {
+        self.store.lock().path.is_some()
+    }
+
+    /// Counts an acceptance of `label` at `now` and saves the counts.
+    pub fn record(&self, label: &str, now: u64) {
+        let mut store = self.store.lock();
+        let Some(path) = store.path.clone() else { return };
+        let entry = store.entries.entry(label.to_string()).or_insert(UsageEntry { count: 0, last_used: now });
+        entry.count = entry.count.saturating_add(1);
+        entry.last_used = now;
+
+        if store.entries.len() > MAX_TRACKED_LABELS {
+            let mut by_age: Vec<(String, u64)> =
+                store.entries.iter().map(|(label, entry)| (label.clone(), entry.last_used)).collect();
+            by_age.sort_by_key(|(_, last_used)| *last_used);
+            for (label, _) in by_age.into_iter().take(store.entries.len() - MAX_TRACKED_LABELS) {
+                store.entries.remove(&label);
+            }
+        }
+
+        if let Err(e) = save(&path, &store.entries) {
+            warn!("Failed to save completion usage to {}: {}", path.display(), e);
+        }
+    }
+
+    /// Ranking score of `label` at `now`; 0 if it was never accepted.
+    pub fn score(&self, label: &str, now: u64) -> f64 {
+        self.store.lock().entries.get(label).map_or(0.0, |entry| entry.score(now))
+    }
+}
+
+/// Orders `items` by usage score, keeping the original order between equal
+/// scores, and sets their `sort_text` accordingly. When tracking is enabled,
+/// each item also carries the command reporting its acceptance.
+pub fn rank_completions(items: &mut Vec<CompletionItem>, usage: &CompletionUsage, now: u64) {
+    if !usage.is_enabled() {
+        return;
+    }
+    let mut scored: Vec<(f64, CompletionItem)> =
+        items.drain(..).map(|item| (usage.score(&item.label, now), item)).collect();
+    scored.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+
+    let width = scored.len().to_string().len();
+    items.extend(scored.into_iter().enumerate().map(|(rank, (_, mut item))| {
+        item.sort_text = Some(format!("{:0width$}", rank, width = width));
+        item.command = Some(Command {
+            title: String::new(),
+            command: COMPLETION_ACCEPTED_COMMAND.to_string(),
+            arguments: Some(vec![serde_json::Value::String(item.label.clone())]),
+        });
+        item
+    }));
+}
+
+/// Seconds since the Unix epoch.
+pub fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/// `completion-usage/<hash of root>.json` in the user cache directory.
+fn usage_file(root: &Path) -> io::Result<PathBuf> {
+    let dir = crate::logging::get_log_dir()?.join("completion-usage");
+    fs::create_dir_all(&dir)?;
+    Ok(dir.join(format!("{}.json", content_hash(&root.to_string_lossy()))))
+}
+
+fn save(path: &Path, entries: &HashMap<String, UsageEntry>) -> io::Result<()> {
+    let json = serde_json::to_string(entries).map_err(io::Error::other)?;
+    // Write then rename, so a crash never leaves a truncated file
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, json)?;
+    fs::rename(&tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn item(label: &str) -> CompletionItem {
+        CompletionItem { label: label.to_string(), ..Default::default() }
+    }
+
+    fn labels(items: &[CompletionItem]) -> Vec<&str> {
+        items.iter().map(|item| item.label.as_str()).collect()
+    }
+
+    #[test]
+    fn test_frequent_and_recent_labels_rank_first() {
+        let path = std::env::temp_dir().join(format!("completion-usage-test-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let usage = CompletionUsage::default();
+        usage.open_at(path.clone());
+
+        let day = 24 * 60 * 60;
+        let now = 100 * day;
+        for _ in 0..3 {
+            usage.record("transfer", now - 60 * day);
+        }
+        usage.record("stdout", now - day);
+        usage.record("stdout", now);
+
+        let mut items = vec![item("new"), item("transfer"), item("stdout"), item("match")];
+        rank_completions(&mut items, &usage, now);
+        assert_eq!(labels(&items), vec!["stdout", "transfer", "new", "match"]);
+        assert_eq!(items[0].sort_text.as_deref(), Some("0"));
+        assert_eq!(
+            items[0].command.as_ref().map(|command| command.command.as_str()),
+            Some(COMPLETION_ACCEPTED_COMMAND)
+        );
+
+        // Counts survive a restart
+        let reopened = CompletionUsage::default();
+        reopened.open_at(path.clone());
+        assert_eq!(reopened.score("stdout", now), usage.score("stdout", now));
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_disabled_tracking_leaves_items_untouched() {
+        let usage = CompletionUsage::default();
+        usage.record("transfer", 0);
+        let mut items = vec![item("new"), item("transfer")];
+        rank_completions(&mut items, &usage, 0);
+        assert_eq!(labels(&items), vec!["new", "transfer"]);
+        assert!(items.iter().all(|item| item.sort_text.is_none() && item.command.is_none()));
+
+        assert!(enabled_in(None));
+        assert!(!enabled_in(Some(&json!({ "completionUsageTracking": false }))));
+    }
+}
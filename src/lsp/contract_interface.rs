@@ -0,0 +1,375 @@
+//! Contract interface documents for the `rholang.extractInterface` command
+//!
+//! Describes the API a file (or the whole workspace) exposes: for each
+//! contract, its formals, the doc comment attached from the comment channel
+//! (summary, `@param` and `@return`), the channels its body reads from and
+//! sends on, the registry URIs of the system names it uses
+//! (`new lookup(`rho:registry:lookup`)`), and the embedded languages the
+//! channel flow analyzer finds in strings it sends.
+//!
+//! The command returns an [`InterfaceReport`] with the entries as JSON and a
+//! rendering in the requested format, Markdown by default, that the editor can
+//! open as a read-only `rholang-interface:` document:
+//!
+//! ```json
+//! { "uri": "rholang-interface:/workspace", "format": "markdown",
+//!   "content": "# Contract interface\n…",
+//!   "contracts": [ { "name": "transfer", "uri": "file:///…", "range": [3, 0, 8, 1],
+//!                    "signature": "contract transfer(@from, @to, ret)",
+//!                    "formals": [ { "pattern": "@from", "doc": "source vault" }, … ],
+//!                    "doc": "Moves funds between vaults", "reads": [], "writes": ["ret"],
+//!                    "registryUris": ["rho:registry:lookup"], "embeddedLanguages": [] } ] }
+//! ```
+
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write as _;
+use std::sync::Arc;
+
+use ropey::Rope;
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::Url;
+use tree_sitter::Tree;
+
+use crate::ir::rholang_node::{collect_contracts, Metadata, NodeBase, Position as IrPosition, RholangNode};
+use crate::ir::structured_documentation::StructuredDocumentation;
+use crate::ir::transforms::documentation_attacher::DOC_METADATA_KEY;
+use crate::ir::visitor::Visitor;
+use crate::language_regions::ChannelFlowAnalyzer;
+use crate::lsp::contracts_outline::{node_name, node_source};
+use crate::lsp::line_index::{lsp_range, LineIndex};
+use crate::lsp::scopes::{BinderOrigin, ScopeAnalysis, Span, UseRole};
+
+/// URI scheme of interface documents
+pub const INTERFACE_SCHEME: &str = "rholang-interface";
+
+/// Format of the rendered interface document
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InterfaceFormat {
+    #[default]
+    Markdown,
+    Json,
+}
+
+/// Parameters of the `rholang.extractInterface` command
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ExtractInterfaceParams {
+    /// Only describe this document; defaults to the whole workspace
+    #[serde(default)]
+    pub uri: Option<Url>,
+    #[serde(default)]
+    pub format: InterfaceFormat,
+}
+
+/// A contract formal and its `@param` documentation
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct FormalInterface {
+    /// Source of the pattern, e.g. `@amount`
+    pub pattern: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+}
+
+/// Interface of one contract
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractInterface {
+    pub name: String,
+    pub uri: Url,
+    /// `[startLine, startCharacter, endLine, endCharacter]` of the contract
+    pub range: [u32; 4],
+    pub signature: String,
+    pub formals: Vec<FormalInterface>,
+    /// Summary of the doc comment
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+    /// `@return` documentation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub returns: Option<String>,
+    /// Channels the body receives from, sorted
+    pub reads: Vec<String>,
+    /// Channels the body sends on, sorted
+    pub writes: Vec<String>,
+    /// URIs of the system names the body uses, sorted
+    pub registry_uris: Vec<String>,
+    /// Languages of embedded code the body sends, sorted
+    pub embedded_languages: Vec<String>,
+}
+
+/// Result of the `rholang.extractInterface` command
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct InterfaceReport {
+    /// URI of the interface document
+    pub uri: Url,
+    pub format: InterfaceFormat,
+    /// Interface document text, in `format`
+    pub content: String,
+    pub contracts: Vec<ContractInterface>,
+}
+
+/// Describes the contracts of a document, in document order.
+pub fn extract_interfaces(
+    uri: &Url,
+    ir: &Arc<RholangNode>,
+    positions: &HashMap<usize, (IrPosition, IrPosition)>,
+    text: &Rope,
+    line_index: &LineIndex,
+    tree: &Tree,
+) -> Vec<ContractInterface> {
+    let mut contracts = Vec::new();
+    collect_contracts(ir, &mut contracts);
+    if contracts.is_empty() {
+        return Vec::new();
+    }
+
+    let analysis = ScopeAnalysis::build(ir, positions);
+    let declared_uris = {
+        let collector = UriCollector { positions, uris: RefCell::new(HashMap::new()) };
+        collector.visit_node(ir);
+        collector.uris.into_inner()
+    };
+    let regions = ChannelFlowAnalyzer::analyze(&text.to_string(), tree, text);
+
+    let mut interfaces: Vec<ContractInterface> = contracts
+        .iter()
+        .filter_map(|contract| {
+            let RholangNode::Contract { name, formals, formals_remainder, metadata, .. } = &**contract else {
+                return None;
+            };
+            let contract_name = node_name(name)?;
+            let &(start, end) = positions.get(&(Arc::as_ptr(contract) as usize))?;
+            let name_span = positions.get(&(Arc::as_ptr(name) as usize)).copied();
+            let doc = structured_doc(metadata);
+
+            let mut patterns: Vec<String> = formals.iter().map(|formal| node_source(formal, positions, text)).collect();
+            if let Some(remainder) = formals_remainder {
+                patterns.push(format!("...{}", node_source(remainder, positions, text)));
+            }
+            let signature = format!("contract {}({})", contract_name, patterns.join(", "));
+            let formals = patterns
+                .into_iter()
+                .map(|pattern| {
+                    let bare = pattern.trim_start_matches("...").trim_start_matches(['@', '*']);
+                    let doc = doc
+                        .as_ref()
+                        .and_then(|doc| doc.params.iter().find(|param| param.name == bare))
+                        .map(|param| param.description.clone());
+                    FormalInterface { pattern, doc }
+                })
+                .collect();
+
+            let (mut reads, mut writes, mut registry_uris) = (BTreeSet::new(), BTreeSet::new(), BTreeSet::new());
+            for u in analysis.iter().flat_map(|analysis| analysis.uses.iter()) {
+                if !within(&u.span, start, end) || name_span.is_some_and(|span| within(&u.span, span.0, span.1)) {
+                    continue;
+                }
+                match u.role {
+                    UseRole::Receive => reads.insert(u.name.clone()),
+                    UseRole::Send => writes.insert(u.name.clone()),
+                    UseRole::Other => false,
+                };
+                let Some(analysis) = &analysis else { continue };
+                let binder = &analysis.scopes[u.binder.0].binders[u.binder.1];
+                if binder.origin == (BinderOrigin::New { uri: true }) {
+                    registry_uris.extend(declared_uris.get(&binder.span.0.byte).cloned());
+                }
+            }
+            let embedded_languages: BTreeSet<String> = regions
+                .iter()
+                .filter(|region| (start.byte..end.byte).contains(&region.start_byte))
+                .map(|region| region.language.clone())
+                .collect();
+
+            let range = lsp_range(text, line_index, &(start, end));
+            Some(ContractInterface {
+                name: contract_name,
+                uri: uri.clone(),
+                range: [range.start.line, range.start.character, range.end.line, range.end.character],
+                signature,
+                formals,
+                doc: doc.as_ref().map(|doc| doc.summary.trim().to_string()).filter(|summary| !summary.is_empty()),
+                returns: doc.and_then(|doc| doc.returns),
+                reads: reads.into_iter().collect(),
+                writes: writes.into_iter().collect(),
+                registry_uris: registry_uris.into_iter().collect(),
+                embedded_languages: embedded_languages.into_iter().collect(),
+            })
+        })
+        .collect();
+
+    interfaces.sort_by_key(|contract| (contract.range[0], contract.range[1]));
+    interfaces
+}
+
+/// URI of the interface document for `target`, or for the whole workspace.
+pub fn interface_uri(target: Option<&Url>) -> Url {
+    let path = target.map(|uri| uri.path()).unwrap_or("/workspace");
+    Url::parse(&format!("{}:{}", INTERFACE_SCHEME, path)).expect("valid interface URI")
+}
+
+/// Renders the interface of `contracts`, found in `documents` scanned documents.
+pub fn build_interface_report(
+    target: Option<&Url>,
+    format: InterfaceFormat,
+    mut contracts: Vec<ContractInterface>,
+    documents: usize,
+) -> InterfaceReport {
+    contracts.sort_by(|a, b| a.uri.as_str().cmp(b.uri.as_str()).then(a.range.cmp(&b.range)));
+    let content = match format {
+        InterfaceFormat::Markdown => render_markdown(&contracts, documents),
+        InterfaceFormat::Json => serde_json::to_string_pretty(&contracts).unwrap_or_default(),
+    };
+    InterfaceReport { uri: interface_uri(target), format, content, contracts }
+}
+
+fn render_markdown(contracts: &[ContractInterface], documents: usize) -> String {
+    let mut content = String::from("# Contract interface\n\n");
+    let _ = writeln!(content, "{} contract(s) in {} document(s)", contracts.len(), documents);
+    let mut current: Option<&Url> = None;
+    for contract in contracts {
+        if current != Some(&contract.uri) {
+            let _ = write!(content, "\n## {}\n", contract.uri);
+            current = Some(&contract.uri);
+        }
+        let _ = write!(content, "\n### `{}`\n\n", contract.signature);
+        if let Some(doc) = &contract.doc {
+            let _ = write!(content, "{}\n\n", doc);
+        }
+        if !contract.formals.is_empty() {
+            content.push_str("**Parameters**\n\n");
+            for formal in &contract.formals {
+                match &formal.doc {
+                    Some(doc) => {
+                        let _ = writeln!(content, "- `{}`: {}", formal.pattern, doc);
+                    }
+                    None => {
+                        let _ = writeln!(content, "- `{}`", formal.pattern);
+                    }
+                }
+            }
+            content.push('\n');
+        }
+        if let Some(returns) = &contract.returns {
+            let _ = writeln!(content, "- **Returns:** {}", returns);
+        }
+        for (label, values) in [
+            ("Reads", &contract.reads),
+            ("Writes", &contract.writes),
+            ("Registry URIs", &contract.registry_uris),
+            ("Embedded languages", &contract.embedded_languages),
+        ] {
+            if !values.is_empty() {
+                let list: Vec<String> = values.iter().map(|value| format!("`{}`", value)).collect();
+                let _ = writeln!(content, "- **{}:** {}", label, list.join(", "));
+            }
+        }
+    }
+    content
+}
+
+fn within(span: &Span, start: IrPosition, end: IrPosition) -> bool {
+    span.0.byte >= start.byte && span.1.byte <= end.byte
+}
+
+/// The doc comment attached to a contract by the documentation attacher.
+fn structured_doc(metadata: &Option<Arc<Metadata>>) -> Option<StructuredDocumentation> {
+    let doc = metadata.as_ref()?.get(DOC_METADATA_KEY)?;
+    doc.downcast_ref::<StructuredDocumentation>().cloned().or_else(|| {
+        doc.downcast_ref::<String>().map(|text| StructuredDocumentation { summary: text.clone(), ..StructuredDocumentation::new() })
+    })
+}
+
+/// Collects the URIs of `new` declarations, by byte offset of the declared name
+struct UriCollector<'a> {
+    positions: &'a HashMap<usize, (IrPosition, IrPosition)>,
+    uris: RefCell<HashMap<usize, String>>,
+}
+
+impl Visitor for UriCollector<'_> {
+    fn visit_name_decl(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        var: &Arc<RholangNode>,
+        uri: &Option<Arc<RholangNode>>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        if let (Some(uri), Some((start, _))) = (uri, self.positions.get(&(Arc::as_ptr(var) as usize))) {
+            if let RholangNode::UriLiteral { value, .. } = &**uri {
+                self.uris.borrow_mut().insert(start.byte, value.clone());
+            }
+        }
+        Arc::clone(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::rholang_node::compute_absolute_positions;
+    use crate::ir::transforms::documentation_attacher::DocumentationAttacher;
+    use crate::tree_sitter::{parse_code, parse_to_document_ir};
+
+    fn interfaces(source: &str) -> Vec<ContractInterface> {
+        let uri = Url::parse("file:///vault.rho").unwrap();
+        let tree = parse_code(source);
+        let rope = Rope::from_str(source);
+        let document_ir = parse_to_document_ir(&tree, &rope);
+        let ir = DocumentationAttacher::new(document_ir.clone()).visit_node(&document_ir.root);
+        let positions = compute_absolute_positions(&ir);
+        extract_interfaces(&uri, &ir, &positions, &rope, &LineIndex::new(source), &tree)
+    }
+
+    #[test]
+    fn test_contract_interface_from_ir_and_docs() {
+        let source = r#"new transfer, balances, stdout(`rho:io:stdout`), lookup(`rho:registry:lookup`) in {
+  /// Moves funds between two vaults
+  /// @param amount how much to move
+  /// @return true on success
+  contract transfer(@from, @to, @amount, ret) = {
+    for (@state <- balances) {
+      balances!(state) | stdout!("moved") | ret!(true)
+    }
+  } |
+  contract @"ping"(ret) = { ret!("pong") }
+}"#;
+        let contracts = interfaces(source);
+        assert_eq!(contracts.len(), 2);
+
+        let transfer = &contracts[0];
+        assert_eq!(transfer.signature, "contract transfer(@from, @to, @amount, ret)");
+        assert_eq!(transfer.doc.as_deref(), Some("Moves funds between two vaults"));
+        assert_eq!(transfer.returns.as_deref(), Some("true on success"));
+        assert_eq!(transfer.formals[2].doc.as_deref(), Some("how much to move"));
+        assert_eq!(transfer.formals[0].doc, None);
+        assert_eq!(transfer.reads, vec!["balances"]);
+        assert_eq!(transfer.writes, vec!["balances", "ret", "stdout"]);
+        assert_eq!(transfer.registry_uris, vec!["rho:io:stdout"]);
+
+        let ping = &contracts[1];
+        assert_eq!(ping.name, "ping");
+        assert!(ping.reads.is_empty());
+        assert_eq!(ping.writes, vec!["ret"]);
+        assert!(ping.registry_uris.is_empty());
+    }
+
+    #[test]
+    fn test_markdown_and_json_rendering() {
+        let contracts = interfaces("contract hello(@name, ret) = { ret!(name) }");
+        let uri = Url::parse("file:///vault.rho").unwrap();
+
+        let markdown = build_interface_report(Some(&uri), InterfaceFormat::Markdown, contracts.clone(), 1);
+        assert_eq!(markdown.uri.as_str(), "rholang-interface:/vault.rho");
+        assert!(markdown.content.starts_with("# Contract interface\n\n1 contract(s) in 1 document(s)"));
+        assert!(markdown.content.contains("### `contract hello(@name, ret)`"));
+        assert!(markdown.content.contains("- `@name`\n"));
+        assert!(markdown.content.contains("- **Writes:** `ret`"));
+
+        let json = build_interface_report(None, InterfaceFormat::Json, contracts, 1);
+        assert_eq!(json.uri.as_str(), "rholang-interface:/workspace");
+        let parsed: Vec<ContractInterface> = serde_json::from_str(&json.content).unwrap();
+        assert_eq!(parsed, json.contracts);
+        assert_eq!(serde_json::to_value(&json).unwrap()["format"], "json");
+    }
+}
@@ -0,0 +1,302 @@
+//! Contracts outline data for the `rholang/contracts` custom request
+//!
+//! Produces a compact, flat list of the contracts declared in a document (or
+//! the whole workspace) for a dedicated "Contracts" tree view in the editor.
+//! Each entry carries the contract's signature, a one-line doc summary and the
+//! number of sends to the contract's name across the workspace.
+//!
+//! # Request
+//!
+//! ```json
+//! { "uri": "file:///path/to/file.rho" }   // omit `uri` for the whole workspace
+//! ```
+//!
+//! # Response
+//!
+//! ```json
+//! { "contracts": [
+//!     { "name": "transfer", "uri": "file:///…", "range": [3, 0, 8, 1],
+//!       "signature": "contract transfer(@from, @to, @amount, ret)",
+//!       "doc": "Moves funds between vaults", "calls": 4 }
+//! ] }
+//! ```
+//!
+//! `range` is `[startLine, startCharacter, endLine, endCharacter]` of the whole
+//! contract; `doc` is omitted when the contract has no documentation.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use ropey::Rope;
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{CodeLens, Command, Position, Range, Url};
+
+use crate::i18n::tr;
+use crate::ir::rholang_node::{collect_calls, collect_contracts, Position as IrPosition, RholangNode};
+use crate::ir::structured_documentation::StructuredDocumentation;
+use crate::ir::transforms::documentation_attacher::DOC_METADATA_KEY;
+use crate::lsp::line_index::{lsp_range, LineIndex};
+
+/// Custom request method name
+pub const CONTRACTS_METHOD: &str = "rholang/contracts";
+
+/// Maximum length of a doc summary before it is truncated
+const MAX_DOC_SUMMARY_CHARS: usize = 120;
+
+/// Parameters of `rholang/contracts`
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ContractsParams {
+    /// Document to list; `None` lists every contract in the workspace
+    #[serde(default)]
+    pub uri: Option<Url>,
+}
+
+/// Result of `rholang/contracts`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct ContractsResult {
+    pub contracts: Vec<ContractOutline>,
+}
+
+/// A single contract in the outline
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ContractOutline {
+    /// Contract name (without quoting, e.g. `transfer` for `@"transfer"`)
+    pub name: String,
+    /// Document declaring the contract
+    pub uri: Url,
+    /// `[startLine, startCharacter, endLine, endCharacter]`
+    pub range: [u32; 4],
+    /// Contract header, e.g. `contract transfer(@from, ret)`
+    pub signature: String,
+    /// First line of the attached documentation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+    /// Number of sends to this contract's name across the workspace
+    pub calls: usize,
+}
+
+/// Returns the plain name of a contract or channel node, if it has one.
+///
+/// Handles `foo`, `@"foo"`, `*foo` and `@foo` forms.
+pub fn node_name(node: &RholangNode) -> Option<String> {
+    match node {
+        RholangNode::Var { name, .. } if !name.is_empty() => Some(name.clone()),
+        RholangNode::StringLiteral { value, .. } => Some(value.clone()),
+        RholangNode::Quote { quotable, .. } => node_name(quotable),
+        RholangNode::Eval { name, .. } => node_name(name),
+        _ => None,
+    }
+}
+
+/// Collects outline entries (with `calls` left at 0) for every contract in `ir`.
+pub fn collect_contract_outlines(
+    uri: &Url,
+    ir: &Arc<RholangNode>,
+    positions: &HashMap<usize, (IrPosition, IrPosition)>,
+    text: &Rope,
+    line_index: &LineIndex,
+) -> Vec<ContractOutline> {
+    let mut contracts = Vec::new();
+    collect_contracts(ir, &mut contracts);
+
+    contracts
+        .iter()
+        .filter_map(|contract| {
+            let RholangNode::Contract { name, metadata, .. } = &**contract else {
+                return None;
+            };
+            let contract_name = node_name(name)?;
+            let signature = contract_signature(contract, positions, text)?;
+
+            let doc = metadata
+                .as_ref()
+                .and_then(|meta| meta.get(DOC_METADATA_KEY))
+                .and_then(|doc_any| {
+                    doc_any
+                        .downcast_ref::<StructuredDocumentation>()
+                        .map(|doc| doc.summary.clone())
+                        .or_else(|| doc_any.downcast_ref::<String>().cloned())
+                })
+                .and_then(|doc| summarize_doc(&doc));
+
+            let range = positions
+                .get(&(&**contract as *const RholangNode as usize))
+                .map(|span| {
+                    let range = lsp_range(text, line_index, span);
+                    [range.start.line, range.start.character, range.end.line, range.end.character]
+                })
+                .unwrap_or_default();
+
+            Some(ContractOutline {
+                signature,
+                name: contract_name,
+                uri: uri.clone(),
+                range,
+                doc,
+                calls: 0,
+            })
+        })
+        .collect()
+}
+
+/// Header of a contract with its formals as written, e.g. `contract transfer(@from, ret)`.
+pub fn contract_signature(
+    contract: &Arc<RholangNode>,
+    positions: &HashMap<usize, (IrPosition, IrPosition)>,
+    text: &Rope,
+) -> Option<String> {
+    let RholangNode::Contract { name, formals, formals_remainder, .. } = &**contract else {
+        return None;
+    };
+    let mut params: Vec<String> = formals
+        .iter()
+        .map(|formal| node_source(formal, positions, text))
+        .collect();
+    if let Some(remainder) = formals_remainder {
+        params.push(format!("...{}", node_source(remainder, positions, text)));
+    }
+    Some(format!("contract {}({})", node_name(name)?, params.join(", ")))
+}
+
+/// Adds the number of sends per channel name in `ir` to `counts`.
+pub fn count_calls(ir: &Arc<RholangNode>, counts: &mut HashMap<String, usize>) {
+    let mut calls = Vec::new();
+    collect_calls(ir, &mut calls);
+    for call in &calls {
+        let channel = match &**call {
+            RholangNode::Send { channel, .. } | RholangNode::SendSync { channel, .. } => channel,
+            _ => continue,
+        };
+        if let Some(name) = node_name(channel) {
+            *counts.entry(name).or_insert(0) += 1;
+        }
+    }
+}
+
+/// Code lenses above each contract with the number of sends to it across the workspace.
+pub fn call_count_lenses(contracts: &[ContractOutline], counts: &HashMap<String, usize>) -> Vec<CodeLens> {
+    contracts
+        .iter()
+        .map(|contract| {
+            let calls = counts.get(&contract.name).copied().unwrap_or(0);
+            let title = if calls == 1 { tr("lens.calls-one", &[]) } else { tr("lens.calls", &[("count", &calls)]) };
+            let start = Position::new(contract.range[0], contract.range[1]);
+            CodeLens {
+                range: Range::new(start, start),
+                // Informational only; clients show a lens without a command id as plain text
+                command: Some(Command { title, command: String::new(), arguments: None }),
+                data: None,
+            }
+        })
+        .collect()
+}
+
+/// Returns the source text of `node` with runs of whitespace collapsed.
+pub fn node_source(node: &Arc<RholangNode>, positions: &HashMap<usize, (IrPosition, IrPosition)>, text: &Rope) -> String {
+    let key = &**node as *const RholangNode as usize;
+    let Some((start, end)) = positions.get(&key) else {
+        return "_".to_string();
+    };
+    match text.get_byte_slice(start.byte..end.byte) {
+        Some(slice) => slice.chars().collect::<String>().split_whitespace().collect::<Vec<_>>().join(" "),
+        None => "_".to_string(),
+    }
+}
+
+/// Reduces documentation to its first non-empty line, truncated for display.
+fn summarize_doc(doc: &str) -> Option<String> {
+    let line = doc.lines().map(str::trim).find(|line| !line.is_empty())?;
+    if line.chars().count() > MAX_DOC_SUMMARY_CHARS {
+        let truncated: String = line.chars().take(MAX_DOC_SUMMARY_CHARS - 1).collect();
+        Some(format!("{}…", truncated.trim_end()))
+    } else {
+        Some(line.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::rholang_node::compute_absolute_positions;
+    use crate::ir::transforms::documentation_attacher::DocumentationAttacher;
+    use crate::ir::visitor::Visitor;
+    use crate::tree_sitter::{parse_code, parse_to_document_ir};
+
+    fn outline(source: &str) -> (Vec<ContractOutline>, HashMap<String, usize>) {
+        let uri = Url::parse("file:///contracts.rho").unwrap();
+        let tree = parse_code(source);
+        let rope = Rope::from_str(source);
+        let document_ir = parse_to_document_ir(&tree, &rope);
+        let ir = DocumentationAttacher::new(document_ir.clone()).visit_node(&document_ir.root);
+        let positions = compute_absolute_positions(&ir);
+
+        let mut counts = HashMap::new();
+        count_calls(&ir, &mut counts);
+        (collect_contract_outlines(&uri, &ir, &positions, &rope, &LineIndex::new(source)), counts)
+    }
+
+    #[test]
+    fn test_contract_outline_signature_and_doc() {
+        let source = r#"new transfer, stdout(`rho:io:stdout`) in {
+  /// Moves funds between two vaults
+  /// @param amount how much to move
+  contract transfer(@from, @to,
+                    @amount, ret) = {
+    ret!(true)
+  } |
+  contract @"log"(@message, ...@rest) = { stdout!(message) } |
+  transfer!("a", "b", 10, *stdout) |
+  transfer!("b", "a", 5, *stdout)
+}"#;
+        let (contracts, counts) = outline(source);
+        assert_eq!(contracts.len(), 2);
+
+        let transfer = contracts.iter().find(|c| c.name == "transfer").unwrap();
+        assert_eq!(transfer.signature, "contract transfer(@from, @to, @amount, ret)");
+        assert_eq!(transfer.doc.as_deref(), Some("Moves funds between two vaults"));
+        assert_eq!(transfer.range[0], 3);
+
+        let log = contracts.iter().find(|c| c.name == "log").unwrap();
+        assert_eq!(log.signature, "contract log(@message, ...@rest)");
+        assert_eq!(log.doc, None);
+
+        assert_eq!(counts.get("transfer"), Some(&2));
+        assert_eq!(counts.get("log"), None);
+
+        let lenses = call_count_lenses(&contracts, &counts);
+        let titles: Vec<_> = lenses.iter().map(|lens| lens.command.as_ref().unwrap().title.as_str()).collect();
+        assert_eq!(titles, vec!["2 calls", "0 calls"]);
+        assert_eq!(lenses[0].range.start, Position::new(3, 2));
+    }
+
+    #[test]
+    fn test_compact_json_omits_missing_doc() {
+        let entry = ContractOutline {
+            name: "foo".to_string(),
+            uri: Url::parse("file:///foo.rho").unwrap(),
+            range: [0, 0, 2, 1],
+            signature: "contract foo(@x)".to_string(),
+            doc: None,
+            calls: 3,
+        };
+        let json = serde_json::to_value(&entry).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "name": "foo",
+                "uri": "file:///foo.rho",
+                "range": [0, 0, 2, 1],
+                "signature": "contract foo(@x)",
+                "calls": 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_summarize_doc_truncates() {
+        assert_eq!(summarize_doc("\n  First line\nSecond"), Some("First line".to_string()));
+        assert_eq!(summarize_doc("   "), None);
+        let long = "x".repeat(200);
+        assert_eq!(summarize_doc(&long).unwrap().chars().count(), MAX_DOC_SUMMARY_CHARS);
+    }
+}
@@ -0,0 +1,467 @@
+//! File structure conventions configured in `.rholangrc.toml`
+//!
+//! Some teams require every file to have the same shape, for example one
+//! top-level `new` wrapping everything, with `stdout` among its names. The
+//! checks are off unless enabled in the `[lints.conventions]` section:
+//!
+//! ```toml
+//! [lints.conventions]
+//! severity = "error"                   # error | warning | information | hint
+//! single_top_level_new = true          # new ... in { everything }
+//! required_uris = ["rho:io:stdout"]    # declared by the top-level new
+//! banned = ["bundle", "peek"]          # constructs not allowed anywhere
+//! ```
+//!
+//! Bannable constructs are `bundle`, `match`, `select`, `let`, `if`,
+//! `persistent_send` (`!!`), `sync_send` (`!?`), `repeated_receive` (`<=`)
+//! and `peek` (`<<-`).
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use ropey::Rope;
+use serde::Deserialize;
+use tower_lsp::lsp_types::{Diagnostic, NumberOrString, Range};
+
+use crate::i18n::tr;
+use crate::ir::rholang_node::{
+    Metadata, NodeBase, Position as IrPosition, RholangBranchVector, RholangBundleType, RholangNode,
+    RholangNodePairVector, RholangNodeVector, RholangSendType,
+};
+use crate::ir::visitor::Visitor;
+use crate::lsp::line_index::{lsp_range, LineIndex};
+use crate::lsp::style_lints::{LintSeverity, STYLE_LINT_SOURCE};
+
+/// Diagnostic code for file structure violations
+pub const STRUCTURE_LINT_CODE: &str = "file-structure";
+/// Diagnostic code for banned constructs
+pub const BANNED_CONSTRUCT_CODE: &str = "banned-construct";
+
+/// A construct that can be banned
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Construct {
+    Bundle,
+    Match,
+    Select,
+    Let,
+    If,
+    PersistentSend,
+    SyncSend,
+    RepeatedReceive,
+    Peek,
+}
+
+impl fmt::Display for Construct {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Construct::Bundle => "`bundle`",
+            Construct::Match => "`match`",
+            Construct::Select => "`select`",
+            Construct::Let => "`let`",
+            Construct::If => "`if`",
+            Construct::PersistentSend => "Persistent send (`!!`)",
+            Construct::SyncSend => "Synchronous send (`!?`)",
+            Construct::RepeatedReceive => "Repeated receive (`<=`)",
+            Construct::Peek => "Peek (`<<-`)",
+        })
+    }
+}
+
+/// `[lints.conventions]` section of `.rholangrc.toml`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ConventionLintConfig {
+    pub severity: LintSeverity,
+    /// The file is a single `new` wrapping every process
+    pub single_top_level_new: bool,
+    /// System URIs the top-level `new` must declare a name for
+    pub required_uris: Vec<String>,
+    /// Constructs not allowed anywhere in the file
+    pub banned: Vec<Construct>,
+}
+
+impl ConventionLintConfig {
+    /// Whether any convention is enabled
+    pub fn is_enabled(&self) -> bool {
+        self.single_top_level_new || !self.required_uris.is_empty() || !self.banned.is_empty()
+    }
+}
+
+/// A part of a file breaking a configured convention
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConventionViolation {
+    pub code: &'static str,
+    pub message: String,
+    pub range: Range,
+}
+
+impl ConventionViolation {
+    pub fn to_diagnostic(&self, severity: LintSeverity) -> Diagnostic {
+        Diagnostic {
+            range: self.range,
+            severity: Some(severity.into()),
+            code: Some(NumberOrString::String(self.code.to_string())),
+            source: Some(STYLE_LINT_SOURCE.to_string()),
+            message: self.message.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Checks `ir` against the configured file structure conventions.
+pub fn check_conventions(
+    ir: &Arc<RholangNode>,
+    positions: &HashMap<usize, (IrPosition, IrPosition)>,
+    text: &Rope,
+    line_index: &LineIndex,
+    config: &ConventionLintConfig,
+) -> Vec<ConventionViolation> {
+    if !config.is_enabled() {
+        return Vec::new();
+    }
+    let span = |node: &Arc<RholangNode>| positions.get(&(Arc::as_ptr(node) as usize)).copied();
+    let range = |span: &(IrPosition, IrPosition)| lsp_range(text, line_index, span);
+    let mut violations = Vec::new();
+
+    let mut top_level = Vec::new();
+    collect_top_level(ir, &mut top_level);
+    let news: Vec<&Arc<RholangNode>> = top_level
+        .iter()
+        .copied()
+        .filter(|process| matches!(&***process, RholangNode::New { .. }))
+        .collect();
+
+    if config.single_top_level_new {
+        let message = tr(if news.is_empty() { "conventions.single-new-missing" } else { "conventions.outside-new" }, &[]);
+        for process in &top_level {
+            if news.first().is_some_and(|wrapper| Arc::ptr_eq(wrapper, process)) {
+                continue;
+            }
+            let Some(span) = span(process) else { continue };
+            violations.push(ConventionViolation { code: STRUCTURE_LINT_CODE, message: message.clone(), range: range(&span) });
+        }
+    }
+
+    if !config.required_uris.is_empty() {
+        let declared: Vec<&str> = news
+            .iter()
+            .flat_map(|new| match &***new {
+                RholangNode::New { decls, .. } => decls.iter().filter_map(|decl| declared_uri(decl)).collect(),
+                _ => Vec::new(),
+            })
+            .collect();
+        // Reported on the `new` keyword, or at the top of a file without one
+        let keyword = match news.first().and_then(|new| span(new)) {
+            Some((start, _)) => range(&(start, keyword_end(start, "new"))),
+            None => Range::default(),
+        };
+        for uri in &config.required_uris {
+            if !declared.contains(&uri.as_str()) {
+                violations.push(ConventionViolation {
+                    code: STRUCTURE_LINT_CODE,
+                    message: tr("conventions.required-uri", &[("uri", uri)]),
+                    range: keyword,
+                });
+            }
+        }
+    }
+
+    if !config.banned.is_empty() {
+        let finder = BannedConstructFinder {
+            banned: &config.banned,
+            positions,
+            text,
+            line_index,
+            violations: RefCell::new(Vec::new()),
+        };
+        finder.visit_node(ir);
+        violations.extend(finder.violations.into_inner());
+    }
+
+    violations.sort_by_key(|v| (v.range.start.line, v.range.start.character));
+    violations
+}
+
+/// Collects the top-level processes of a file, skipping comments and `Nil`.
+fn collect_top_level<'n>(node: &'n Arc<RholangNode>, processes: &mut Vec<&'n Arc<RholangNode>>) {
+    match &**node {
+        RholangNode::Par { processes: Some(children), .. } => {
+            for child in children.iter() {
+                collect_top_level(child, processes);
+            }
+        }
+        RholangNode::Par { left: Some(left), right: Some(right), .. } => {
+            collect_top_level(left, processes);
+            collect_top_level(right, processes);
+        }
+        RholangNode::Comment { .. } | RholangNode::Nil { .. } => {}
+        _ => processes.push(node),
+    }
+}
+
+fn declared_uri(decl: &Arc<RholangNode>) -> Option<&str> {
+    let RholangNode::NameDecl { uri: Some(uri), .. } = &**decl else { return None };
+    match &**uri {
+        RholangNode::UriLiteral { value, .. } => Some(value.as_str()),
+        _ => None,
+    }
+}
+
+/// End of `keyword` written at `start`.
+fn keyword_end(start: IrPosition, keyword: &str) -> IrPosition {
+    IrPosition {
+        row: start.row,
+        column: start.column + keyword.len(),
+        byte: start.byte + keyword.len(),
+    }
+}
+
+/// Collects the banned constructs of a document
+struct BannedConstructFinder<'a> {
+    banned: &'a [Construct],
+    positions: &'a HashMap<usize, (IrPosition, IrPosition)>,
+    text: &'a Rope,
+    line_index: &'a LineIndex,
+    violations: RefCell<Vec<ConventionViolation>>,
+}
+
+impl BannedConstructFinder<'_> {
+    /// Reports `node` if `construct` is banned, on its `keyword` if given.
+    fn check(&self, node: &Arc<RholangNode>, construct: Construct, keyword: Option<&str>) {
+        if !self.banned.contains(&construct) {
+            return;
+        }
+        let Some(&(start, end)) = self.positions.get(&(Arc::as_ptr(node) as usize)) else { return };
+        let end = keyword.map_or(end, |keyword| keyword_end(start, keyword));
+        self.violations.borrow_mut().push(ConventionViolation {
+            code: BANNED_CONSTRUCT_CODE,
+            message: tr("conventions.banned", &[("construct", &construct)]),
+            range: lsp_range(self.text, self.line_index, &(start, end)),
+        });
+    }
+}
+
+impl Visitor for BannedConstructFinder<'_> {
+    fn visit_bundle(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        _bundle_type: &RholangBundleType,
+        proc: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.check(node, Construct::Bundle, Some("bundle"));
+        self.visit_node(proc);
+        Arc::clone(node)
+    }
+
+    fn visit_match(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        expression: &Arc<RholangNode>,
+        cases: &RholangNodePairVector,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.check(node, Construct::Match, Some("match"));
+        self.visit_node(expression);
+        for (pattern, body) in cases.iter() {
+            self.visit_node(pattern);
+            self.visit_node(body);
+        }
+        Arc::clone(node)
+    }
+
+    fn visit_choice(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        branches: &RholangBranchVector,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.check(node, Construct::Select, Some("select"));
+        for (binds, body) in branches.iter() {
+            for bind in binds.iter() {
+                self.visit_node(bind);
+            }
+            self.visit_node(body);
+        }
+        Arc::clone(node)
+    }
+
+    fn visit_let(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        decls: &RholangNodeVector,
+        proc: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.check(node, Construct::Let, Some("let"));
+        for decl in decls.iter() {
+            self.visit_node(decl);
+        }
+        self.visit_node(proc);
+        Arc::clone(node)
+    }
+
+    fn visit_ifelse(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        condition: &Arc<RholangNode>,
+        consequence: &Arc<RholangNode>,
+        alternative: &Option<Arc<RholangNode>>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.check(node, Construct::If, Some("if"));
+        self.visit_node(condition);
+        self.visit_node(consequence);
+        if let Some(alternative) = alternative {
+            self.visit_node(alternative);
+        }
+        Arc::clone(node)
+    }
+
+    fn visit_send(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        channel: &Arc<RholangNode>,
+        send_type: &RholangSendType,
+        _send_type_pos: &IrPosition,
+        inputs: &RholangNodeVector,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        if *send_type == RholangSendType::Multiple {
+            self.check(node, Construct::PersistentSend, None);
+        }
+        self.visit_node(channel);
+        for input in inputs.iter() {
+            self.visit_node(input);
+        }
+        Arc::clone(node)
+    }
+
+    fn visit_send_sync(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        channel: &Arc<RholangNode>,
+        inputs: &RholangNodeVector,
+        cont: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.check(node, Construct::SyncSend, None);
+        self.visit_node(channel);
+        for input in inputs.iter() {
+            self.visit_node(input);
+        }
+        self.visit_node(cont);
+        Arc::clone(node)
+    }
+
+    fn visit_repeated_bind(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        _names: &RholangNodeVector,
+        _remainder: &Option<Arc<RholangNode>>,
+        source: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.check(node, Construct::RepeatedReceive, None);
+        self.visit_node(source);
+        Arc::clone(node)
+    }
+
+    fn visit_peek_bind(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        _names: &RholangNodeVector,
+        _remainder: &Option<Arc<RholangNode>>,
+        source: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.check(node, Construct::Peek, None);
+        self.visit_node(source);
+        Arc::clone(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lsp::style_lints::StyleLintConfig;
+    use crate::tree_sitter::parse_with_positions;
+
+    fn violations(source: &str, config: &ConventionLintConfig) -> Vec<(&'static str, String, usize)> {
+        let rope = Rope::from_str(source);
+        let (ir, positions) = parse_with_positions(source);
+        check_conventions(&ir, &positions, &rope, &LineIndex::new(source), config)
+            .into_iter()
+            .map(|v| (v.code, v.message, v.range.start.line as usize))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_conventions() {
+        let config = StyleLintConfig::from_toml_str(
+            r#"
+            [lints.conventions]
+            single_top_level_new = true
+            required_uris = ["rho:io:stdout"]
+            banned = ["bundle", "persistent_send"]
+            "#,
+        )
+        .unwrap();
+        assert!(config.conventions.single_top_level_new);
+        assert_eq!(config.conventions.banned, vec![Construct::Bundle, Construct::PersistentSend]);
+        assert!(config.conventions.is_enabled());
+        assert!(!config.naming.is_enabled());
+
+        assert!(StyleLintConfig::from_toml_str("[lints.conventions]\nbanned = [\"goto\"]").is_err());
+    }
+
+    #[test]
+    fn test_single_top_level_new() {
+        let config = ConventionLintConfig {
+            single_top_level_new: true,
+            required_uris: vec!["rho:io:stdout".to_string()],
+            ..Default::default()
+        };
+
+        let good = "// Header\nnew stdout(`rho:io:stdout`), x in {\n  x!(1)\n}";
+        assert!(violations(good, &config).is_empty());
+
+        let bad = "new x in {\n  x!(1)\n} |\nNil |\nfor (_ <- @0) { Nil }";
+        assert_eq!(
+            violations(bad, &config),
+            vec![
+                (STRUCTURE_LINT_CODE, "The top-level `new` should declare a name for `rho:io:stdout`".to_string(), 0),
+                (STRUCTURE_LINT_CODE, "Process outside the file's top-level `new`".to_string(), 4),
+            ]
+        );
+
+        let no_new = "contract foo() = { Nil }";
+        assert_eq!(violations(no_new, &config).len(), 2);
+    }
+
+    #[test]
+    fn test_banned_constructs() {
+        let config = ConventionLintConfig {
+            banned: vec![Construct::Bundle, Construct::PersistentSend, Construct::Peek],
+            ..Default::default()
+        };
+        let source = "new x in {\n  bundle+ { x!(1) } |\n  x!!(2) |\n  for (_ <<- x) { x!(3) }\n}";
+        let found: Vec<_> = violations(source, &config).into_iter().map(|(code, _, row)| (code, row)).collect();
+        assert_eq!(
+            found,
+            vec![(BANNED_CONSTRUCT_CODE, 1), (BANNED_CONSTRUCT_CODE, 2), (BANNED_CONSTRUCT_CODE, 3)]
+        );
+    }
+}
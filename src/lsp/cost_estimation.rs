@@ -0,0 +1,290 @@
+//! Phlogiston cost estimation on RNode (`rholang.estimateCost`)
+//!
+//! When RNode validation is on, the command evaluates each top-level process
+//! of a document on RNode's REPL service, which runs it without deploying
+//! it, and reads the cost RNode reports:
+//!
+//! ```json
+//! { "command": "rholang.estimateCost", "arguments": [{ "uri": "file:///a.rho" }] }
+//! ```
+//!
+//! The processes of a file wrapped in a single top-level `new` are the
+//! processes of its body, each evaluated inside a copy of the `new` so the
+//! names it uses are bound. Costs are shown as inlay hints before each
+//! process, and processes costing more than `warnAbove` are reported as
+//! `phlo-cost` warnings, until the document changes:
+//!
+//! ```json
+//! { "costEstimation": { "warnAbove": 100000, "inlayHints": true } }
+//! ```
+//!
+//! Like `rholang.evaluateFile`, the command runs only in trusted workspaces
+//! and against allowlisted endpoints.
+
+use std::sync::Arc;
+
+use ropey::Rope;
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{
+    Diagnostic, DiagnosticSeverity, InlayHint, InlayHintLabel, NumberOrString, Range, Url,
+};
+
+use crate::i18n::tr;
+use crate::ir::rholang_node::RholangNode;
+use crate::lsp::call_hierarchy::{node_range, Positions};
+use crate::lsp::line_index::LineIndex;
+
+/// Estimates the phlogiston cost of the top-level processes of a document
+pub const ESTIMATE_COST_COMMAND: &str = "rholang.estimateCost";
+
+/// Initialization option configuring cost estimation
+pub const COST_ESTIMATION_OPTION: &str = "costEstimation";
+
+/// Diagnostic source of cost warnings
+pub const PHLO_COST_SOURCE: &str = "rholang-rnode";
+/// Diagnostic code of cost warnings
+pub const PHLO_COST_CODE: &str = "phlo-cost";
+
+/// The `costEstimation` initialization option
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CostEstimationConfig {
+    /// Processes costing more phlo than this are reported as warnings
+    pub warn_above: Option<u64>,
+    /// Whether estimated costs are shown as inlay hints
+    pub inlay_hints: bool,
+}
+
+impl Default for CostEstimationConfig {
+    fn default() -> Self {
+        CostEstimationConfig { warn_above: None, inlay_hints: true }
+    }
+}
+
+impl CostEstimationConfig {
+    /// Reads the `costEstimation` initialization option; missing fields keep their defaults.
+    pub fn from_init_options(init_options: Option<&serde_json::Value>) -> Self {
+        init_options
+            .and_then(|opts| opts.get(COST_ESTIMATION_OPTION))
+            .and_then(|config| serde_json::from_value(config.clone()).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// Arguments of the `rholang.estimateCost` command
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct EstimateCostArgs {
+    pub uri: Url,
+}
+
+/// A top-level process and the program evaluated to estimate its cost
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TopLevelProcess {
+    pub range: Range,
+    pub program: String,
+}
+
+/// The estimated cost of one top-level process
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessCost {
+    pub range: Range,
+    /// Phlogiston RNode charged for evaluating the process
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost: Option<u64>,
+    /// Why no cost is known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ProcessCost {
+    /// An inlay hint with the cost before the process, if it is known.
+    pub fn to_inlay_hint(&self) -> Option<InlayHint> {
+        let cost = self.cost?;
+        Some(InlayHint {
+            position: self.range.start,
+            label: InlayHintLabel::String(tr("cost.hint", &[("cost", &cost)])),
+            kind: None,
+            text_edits: None,
+            tooltip: None,
+            padding_left: None,
+            padding_right: Some(true),
+            data: None,
+        })
+    }
+
+    /// A warning on the process if it costs more than `warn_above`.
+    pub fn to_diagnostic(&self, warn_above: Option<u64>) -> Option<Diagnostic> {
+        let (cost, limit) = (self.cost?, warn_above?);
+        (cost > limit).then(|| Diagnostic {
+            range: Range::new(self.range.start, self.range.start),
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: Some(NumberOrString::String(PHLO_COST_CODE.to_string())),
+            source: Some(PHLO_COST_SOURCE.to_string()),
+            message: tr("cost.warning", &[("cost", &cost), ("limit", &limit)]),
+            ..Default::default()
+        })
+    }
+}
+
+/// Estimated costs of one version of a document
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CostEstimates {
+    /// `content_hash` of the document the costs were estimated for
+    pub content_hash: u64,
+    pub costs: Vec<ProcessCost>,
+}
+
+/// The processes of a parallel composition, flattening nested `Par` nodes
+/// and blocks, skipping comments and `Nil`.
+fn par_branches<'n>(node: &'n Arc<RholangNode>, branches: &mut Vec<&'n Arc<RholangNode>>) {
+    match &**node {
+        RholangNode::Par { processes: Some(processes), .. } => {
+            for process in processes.iter() {
+                par_branches(process, branches);
+            }
+        }
+        RholangNode::Par { left: Some(left), right: Some(right), .. } => {
+            par_branches(left, branches);
+            par_branches(right, branches);
+        }
+        RholangNode::Block { proc, .. } => par_branches(proc, branches),
+        RholangNode::Comment { .. } | RholangNode::Nil { .. } => {}
+        _ => branches.push(node),
+    }
+}
+
+/// Source text of `node`.
+fn node_text(node: &RholangNode, positions: &Positions, text: &Rope) -> Option<String> {
+    let (start, end) = positions.get(&(node as *const RholangNode as usize))?;
+    (end.byte <= text.len_bytes()).then(|| text.byte_slice(start.byte..end.byte).to_string())
+}
+
+/// The top-level processes of a document, with the program evaluated for each.
+pub fn top_level_processes(
+    ir: &Arc<RholangNode>,
+    positions: &Positions,
+    text: &Rope,
+    line_index: &LineIndex,
+) -> Vec<TopLevelProcess> {
+    let mut branches = Vec::new();
+    par_branches(ir, &mut branches);
+
+    // A single top-level `new`: estimate its body's processes with the names bound
+    if let [single] = branches[..] {
+        if let RholangNode::New { decls, proc, .. } = &**single {
+            let decls_text: Option<Vec<String>> = decls.iter().map(|decl| node_text(decl, positions, text)).collect();
+            let mut body = Vec::new();
+            par_branches(proc, &mut body);
+            if let (Some(decls_text), true) = (decls_text, body.len() > 1) {
+                let decls_text = decls_text.join(", ");
+                return body
+                    .into_iter()
+                    .filter_map(|process| {
+                        Some(TopLevelProcess {
+                            range: node_range(process, positions, text, line_index)?,
+                            program: format!("new {} in {{\n{}\n}}", decls_text, node_text(process, positions, text)?),
+                        })
+                    })
+                    .collect();
+            }
+        }
+    }
+
+    branches
+        .into_iter()
+        .filter_map(|process| {
+            Some(TopLevelProcess {
+                range: node_range(process, positions, text, line_index)?,
+                program: node_text(process, positions, text)?,
+            })
+        })
+        .collect()
+}
+
+/// The cost in RNode's REPL output, e.g. `Deployment cost: Cost(4521,…)` or
+/// `Deployment cost: CostAccount(7,Cost(340))`, or the errors RNode reported.
+pub fn parse_deployment_cost(output: &str) -> Result<u64, String> {
+    if let Some(errors) = output.split_once("Errors received during evaluation:").map(|(_, rest)| rest) {
+        let errors = errors.split("Storage Contents:").next().unwrap_or(errors);
+        return Err(errors.trim().to_string());
+    }
+    let line = output
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("Deployment cost:"))
+        .ok_or_else(|| "RNode did not report a deployment cost".to_string())?;
+    let digits_from = line.rfind("Cost(").map_or(0, |at| at + "Cost(".len());
+    let digits: String = line[digits_from..]
+        .chars()
+        .skip_while(|c| !c.is_ascii_digit())
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    digits.parse().map_err(|_| format!("Unrecognized deployment cost: {}", line.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tower_lsp::lsp_types::Position;
+
+    use crate::tree_sitter::parse_with_positions;
+
+    fn processes(source: &str) -> Vec<(u32, String)> {
+        let rope = Rope::from_str(source);
+        let (ir, positions) = parse_with_positions(source);
+        top_level_processes(&ir, &positions, &rope, &LineIndex::new(source))
+            .into_iter()
+            .map(|process| (process.range.start.line, process.program))
+            .collect()
+    }
+
+    #[test]
+    fn test_top_level_processes() {
+        assert_eq!(
+            processes("@\"a\"!(1)\n|\n@\"b\"!(2)"),
+            vec![(0, "@\"a\"!(1)".to_string()), (2, "@\"b\"!(2)".to_string())]
+        );
+        assert_eq!(
+            processes("new x, y in {\n  x!(1) |\n  for (_ <- y) { Nil }\n}"),
+            vec![
+                (1, "new x, y in {\nx!(1)\n}".to_string()),
+                (2, "new x, y in {\nfor (_ <- y) { Nil }\n}".to_string()),
+            ]
+        );
+        // A `new` with a single process is estimated as a whole
+        assert_eq!(processes("new x in { x!(1) }"), vec![(0, "new x in { x!(1) }".to_string())]);
+    }
+
+    #[test]
+    fn test_parse_deployment_cost() {
+        assert_eq!(parse_deployment_cost("Deployment cost: Cost(4521,)\nStorage Contents:\n @{\"a\"}!(1)"), Ok(4521));
+        assert_eq!(parse_deployment_cost("Deployment cost: CostAccount(7,Cost(340))\n"), Ok(340));
+        assert_eq!(
+            parse_deployment_cost("Deployment cost: Cost(12,)\nErrors received during evaluation:\nOutOfPhlogistons\nStorage Contents:\n"),
+            Err("OutOfPhlogistons".to_string())
+        );
+        assert!(parse_deployment_cost("Storage Contents:\n").is_err());
+    }
+
+    #[test]
+    fn test_hints_and_warnings() {
+        let cost = ProcessCost { range: Range::new(Position::new(2, 2), Position::new(4, 3)), cost: Some(5000), error: None };
+        assert_eq!(cost.to_inlay_hint().unwrap().position, Position::new(2, 2));
+        assert!(cost.to_diagnostic(None).is_none());
+        assert!(cost.to_diagnostic(Some(10_000)).is_none());
+        let warning = cost.to_diagnostic(Some(1000)).unwrap();
+        assert_eq!(warning.code, Some(NumberOrString::String(PHLO_COST_CODE.to_string())));
+
+        let failed = ProcessCost { cost: None, error: Some("OutOfPhlogistons".to_string()), ..cost };
+        assert!(failed.to_inlay_hint().is_none());
+        assert!(failed.to_diagnostic(Some(1000)).is_none());
+    }
+
+    #[test]
+    fn test_config() {
+        let config = CostEstimationConfig::from_init_options(Some(&json!({ "costEstimation": { "warnAbove": 100 } })));
+        assert_eq!(config, CostEstimationConfig { warn_above: Some(100), inlay_hints: true });
+        assert_eq!(CostEstimationConfig::from_init_options(None), CostEstimationConfig::default());
+    }
+}
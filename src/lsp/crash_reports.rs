@@ -0,0 +1,271 @@
+//! Crash reports of panics in the analysis of a document
+//!
+//! The analysis of a document runs isolated on the analysis pool (see
+//! `crate::lsp::analysis_pool`), so a panic fails that document instead of
+//! the server. Each such panic is written as a JSON report to the crash
+//! directory, `crashes/` in the server's cache directory unless
+//! `RHOLANG_CRASH_DIR` names another one, and the user is offered to open it:
+//!
+//! ```json
+//! {
+//!   "timestamp": "2025-11-02T10:14:03.512Z",
+//!   "serverVersion": "0.1.0 (c1a0a24f)",
+//!   "grammarVersion": "0.1.0 (ABI 15)",
+//!   "document": { "lines": 120, "bytes": 3912 },
+//!   "message": "index out of bounds: the len is <n> but the index is <n>",
+//!   "nodePath": ["Rholang::Par", "Rholang::New", "Rholang::Send"],
+//!   "lastRequest": { "method": "textDocument/didChange", "version": 7, "sameDocument": true }
+//! }
+//! ```
+//!
+//! Reports are redacted so they can be attached to a bug report as they are:
+//! they hold no source text and no path, and quoted text and numbers are
+//! removed from the panic message. The same panic at the same node path is
+//! reported once per document and session.
+
+use std::collections::HashSet;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::Url;
+
+use crate::lsp::analysis_pool::JobPanic;
+
+/// Environment variable naming the crash directory
+pub const CRASH_DIR_ENV: &str = "RHOLANG_CRASH_DIR";
+
+/// Title of the action opening a crash report
+pub const OPEN_REPORT_ACTION: &str = "Open Report";
+
+/// Nodes kept from the root side of a longer node path
+const MAX_NODE_PATH: usize = 64;
+
+/// A redacted crash report
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReport {
+    pub timestamp: String,
+    pub server_version: String,
+    pub grammar_version: String,
+    pub document: DocumentSummary,
+    /// Redacted panic message
+    pub message: String,
+    /// Kinds of the IR nodes being visited, from the root; empty if the
+    /// panic happened outside a visit
+    pub node_path: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_request: Option<LastRequest>,
+}
+
+/// Size of the document whose analysis panicked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct DocumentSummary {
+    pub lines: usize,
+    pub bytes: usize,
+}
+
+/// The last document notification received before the panic
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LastRequest {
+    pub method: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<i32>,
+    /// Whether it was about the document whose analysis panicked
+    pub same_document: bool,
+}
+
+/// Writes crash reports and remembers what they need to know
+#[derive(Debug)]
+pub struct CrashReporter {
+    /// Crash directory; reports are not written without one
+    dir: Option<PathBuf>,
+    /// Last document notification, with its document
+    last_request: Mutex<Option<(String, Url, Option<i32>)>>,
+    /// Documents and panics already reported
+    reported: Mutex<HashSet<(Url, String, Vec<&'static str>)>>,
+    written: AtomicU32,
+}
+
+impl CrashReporter {
+    /// A reporter writing to `dir`.
+    pub fn new(dir: Option<PathBuf>) -> Self {
+        CrashReporter {
+            dir,
+            last_request: Mutex::new(None),
+            reported: Mutex::new(HashSet::new()),
+            written: AtomicU32::new(0),
+        }
+    }
+
+    /// A reporter writing to `RHOLANG_CRASH_DIR`, or to `crashes/` in the cache directory.
+    pub fn from_env() -> Self {
+        let dir = std::env::var_os(CRASH_DIR_ENV).map(PathBuf::from).or_else(|| {
+            dirs::cache_dir().map(|cache| cache.join("f1r3fly-io").join("rholang-language-server").join("crashes"))
+        });
+        Self::new(dir)
+    }
+
+    /// Notes the document notification `method` for `uri` at `version`.
+    pub fn note_request(&self, method: &str, uri: &Url, version: Option<i32>) {
+        *self.last_request.lock() = Some((method.to_string(), uri.clone(), version));
+    }
+
+    /// The report of `panic` in the analysis of `uri`, whose text is `text`,
+    /// or `None` if the same panic was reported for `uri` already.
+    pub fn report(&self, uri: &Url, text: &str, panic: &JobPanic) -> Option<CrashReport> {
+        let message = redact_message(&panic.message);
+        let key = (uri.clone(), message.clone(), panic.node_path.clone());
+        if !self.reported.lock().insert(key) {
+            return None;
+        }
+        let last_request = self.last_request.lock().as_ref().map(|(method, last_uri, version)| LastRequest {
+            method: method.clone(),
+            version: *version,
+            same_document: last_uri == uri,
+        });
+        Some(CrashReport {
+            timestamp: chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string(),
+            server_version: server_version(),
+            grammar_version: grammar_version(),
+            document: DocumentSummary { lines: text.lines().count(), bytes: text.len() },
+            message,
+            node_path: panic.node_path.iter().take(MAX_NODE_PATH).map(|kind| kind.to_string()).collect(),
+            last_request,
+        })
+    }
+
+    /// Writes `report` to the crash directory and returns its path.
+    pub fn write(&self, report: &CrashReport) -> io::Result<PathBuf> {
+        let Some(dir) = &self.dir else {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "no crash directory"));
+        };
+        std::fs::create_dir_all(dir)?;
+        let path = report_path(dir, self.written.fetch_add(1, Ordering::Relaxed));
+        let json = serde_json::to_string_pretty(report).map_err(io::Error::other)?;
+        std::fs::write(&path, json)?;
+        Ok(path)
+    }
+}
+
+/// Path of the `index`th report of this process in `dir`.
+fn report_path(dir: &Path, index: u32) -> PathBuf {
+    let stamp = chrono::Utc::now().format("%Y%m%d-%H%M%S");
+    dir.join(format!("crash-{}-{}-{}.json", stamp, std::process::id(), index))
+}
+
+/// Version of the server, with its commit
+pub fn server_version() -> String {
+    format!("{} ({})", env!("CARGO_PKG_VERSION"), env!("BUILD_GIT_HASH"))
+}
+
+/// Version of the Rholang tree-sitter grammar, with its ABI version
+pub fn grammar_version() -> String {
+    let language: tree_sitter::Language = rholang_tree_sitter::LANGUAGE.into();
+    match language.metadata() {
+        Some(metadata) => format!(
+            "{}.{}.{} (ABI {})",
+            metadata.major_version,
+            metadata.minor_version,
+            metadata.patch_version,
+            language.abi_version()
+        ),
+        None => format!("ABI {}", language.abi_version()),
+    }
+}
+
+/// `message` without quoted text and numbers, which may come from the
+/// document or from the user's paths.
+pub fn redact_message(message: &str) -> String {
+    let mut redacted = String::with_capacity(message.len());
+    let mut chars = message.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' | '\'' | '`' => {
+                // Apostrophes inside words do not quote anything
+                if c == '\'' && redacted.chars().next_back().is_some_and(char::is_alphanumeric) {
+                    redacted.push(c);
+                    continue;
+                }
+                if chars.clone().any(|next| next == c) {
+                    for next in chars.by_ref() {
+                        if next == c {
+                            break;
+                        }
+                    }
+                    redacted.push(c);
+                    redacted.push_str("<redacted>");
+                    redacted.push(c);
+                } else {
+                    redacted.push(c);
+                }
+            }
+            c if c.is_ascii_digit() => {
+                while chars.peek().is_some_and(|next| next.is_ascii_digit()) {
+                    chars.next();
+                }
+                redacted.push_str("<n>");
+            }
+            '/' | '\\' if chars.peek().is_some_and(|next| !next.is_whitespace()) => {
+                while chars.peek().is_some_and(|next| !next.is_whitespace()) {
+                    chars.next();
+                }
+                redacted.push_str("<path>");
+            }
+            c => redacted.push(c),
+        }
+    }
+    redacted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn panic(message: &str) -> JobPanic {
+        JobPanic { message: message.to_string(), node_path: vec!["Rholang::Par", "Rholang::Send"] }
+    }
+
+    #[test]
+    fn test_redact_message() {
+        assert_eq!(
+            redact_message("index out of bounds: the len is 3 but the index is 12"),
+            "index out of bounds: the len is <n> but the index is <n>"
+        );
+        assert_eq!(redact_message("unexpected token \"secretKey\" in 'x'"), "unexpected token \"<redacted>\" in '<redacted>'");
+        assert_eq!(redact_message("can't open /home/alice/keys.rho"), "can't open <path>");
+        assert_eq!(redact_message("a / b"), "a / b");
+    }
+
+    #[test]
+    fn test_report() {
+        let dir = std::env::temp_dir().join(format!("rholang-crash-reports-{}", std::process::id()));
+        let reporter = CrashReporter::new(Some(dir.clone()));
+        let uri = Url::parse("file:///home/alice/project/contract.rho").unwrap();
+        reporter.note_request("textDocument/didChange", &uri, Some(7));
+
+        let report = reporter.report(&uri, "new x in {\n  x!(1)\n}", &panic("boom at 3")).unwrap();
+        assert_eq!(report.document, DocumentSummary { lines: 3, bytes: 20 });
+        assert_eq!(report.message, "boom at <n>");
+        assert_eq!(report.node_path, vec!["Rholang::Par", "Rholang::Send"]);
+        assert_eq!(
+            report.last_request,
+            Some(LastRequest { method: "textDocument/didChange".to_string(), version: Some(7), same_document: true })
+        );
+
+        // The same panic is reported once per document
+        assert!(reporter.report(&uri, "", &panic("boom at 4")).is_none());
+        let other = Url::parse("file:///home/alice/project/other.rho").unwrap();
+        assert!(!reporter.report(&other, "", &panic("boom at 3")).unwrap().last_request.unwrap().same_document);
+
+        let path = reporter.write(&report).unwrap();
+        let written: CrashReport = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(written, report);
+        let json = std::fs::read_to_string(&path).unwrap();
+        assert!(!json.contains("alice") && !json.contains("x!(1)"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
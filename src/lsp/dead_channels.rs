@@ -0,0 +1,238 @@
+//! Dead channel report for the `rholang.deadChannels` command
+//!
+//! Lists the `new` names that look like leftovers in large legacy contracts:
+//!
+//! - **unused**: declared but never used;
+//! - **used once**: a single use, so nothing can be on the other end;
+//! - **only sent on** / **only received from**: every use is the channel of
+//!   a send, or every use is the source of a receive (or a contract's name),
+//!   so no message ever crosses it.
+//!
+//! A name passed anywhere else, for example as a message or a contract
+//! argument, escapes and is not reported as one-sided. System names declared
+//! with a URI (`stdout(`rho:io:stdout`)`) are never reported.
+//!
+//! The command returns a [`DeadChannelReport`] with the entries as JSON and a
+//! Markdown rendering the editor can open as a read-only
+//! `rholang-dead-channels:` document:
+//!
+//! ```json
+//! { "uri": "rholang-dead-channels:/workspace",
+//!   "content": "# Dead channels\n…",
+//!   "channels": [ { "uri": "file:///path/to/file.rho", "name": "ack", "kind": "onlySent",
+//!                   "message": "'ack' is only sent on; nothing receives from it",
+//!                   "declaration": { "start": …, "end": … }, "uses": [ … ] } ] }
+//! ```
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::sync::Arc;
+
+use ropey::Rope;
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{Range, Url};
+
+use crate::ir::rholang_node::{Position as IrPosition, RholangNode};
+use crate::lsp::line_index::{lsp_range, LineIndex};
+use crate::lsp::scopes::{BinderOrigin, ScopeAnalysis, UseRole};
+
+/// URI scheme of report documents
+pub const REPORT_SCHEME: &str = "rholang-dead-channels";
+
+/// Parameters of the `rholang.deadChannels` command
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct DeadChannelsParams {
+    /// Only report this document; defaults to the whole workspace
+    #[serde(default)]
+    pub uri: Option<Url>,
+}
+
+/// Why a channel is reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DeadChannelKind {
+    Unused,
+    UsedOnce,
+    OnlySent,
+    OnlyReceived,
+}
+
+/// A reported `new` name
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadChannel {
+    pub uri: Url,
+    pub name: String,
+    pub kind: DeadChannelKind,
+    pub message: String,
+    /// Range of the name in its `new` declaration
+    pub declaration: Range,
+    pub uses: Vec<Range>,
+}
+
+/// Result of the `rholang.deadChannels` command
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct DeadChannelReport {
+    /// URI of the report document
+    pub uri: Url,
+    /// Report document text (Markdown)
+    pub content: String,
+    pub channels: Vec<DeadChannel>,
+}
+
+/// Finds the dead `new` names of a document, in document order.
+pub fn find_dead_channels(
+    uri: &Url,
+    ir: &Arc<RholangNode>,
+    positions: &HashMap<usize, (IrPosition, IrPosition)>,
+    text: &Rope,
+    line_index: &LineIndex,
+) -> Vec<DeadChannel> {
+    let Some(analysis) = ScopeAnalysis::build(ir, positions) else {
+        return Vec::new();
+    };
+
+    let mut channels = Vec::new();
+    for (scope, declared) in analysis.scopes.iter().enumerate() {
+        for (index, binder) in declared.binders.iter().enumerate() {
+            if binder.origin != (BinderOrigin::New { uri: false }) {
+                continue;
+            }
+            let uses: Vec<_> = analysis.uses_of(scope, index).collect();
+            let sent = uses.iter().filter(|u| u.role == UseRole::Send).count();
+            let received = uses.iter().filter(|u| u.role == UseRole::Receive).count();
+
+            let (kind, message) = match uses.len() {
+                0 => (DeadChannelKind::Unused, format!("'{}' is declared but never used", binder.name)),
+                1 => (DeadChannelKind::UsedOnce, format!("'{}' is used only once", binder.name)),
+                n if sent == n => (
+                    DeadChannelKind::OnlySent,
+                    format!("'{}' is only sent on; nothing receives from it", binder.name),
+                ),
+                n if received == n => (
+                    DeadChannelKind::OnlyReceived,
+                    format!("'{}' is only received from; nothing sends on it", binder.name),
+                ),
+                _ => continue,
+            };
+            channels.push(DeadChannel {
+                uri: uri.clone(),
+                name: binder.name.clone(),
+                kind,
+                message,
+                declaration: lsp_range(text, line_index, &binder.span),
+                uses: uses.iter().map(|u| lsp_range(text, line_index, &u.span)).collect(),
+            });
+        }
+    }
+
+    channels.sort_by_key(|channel| (channel.declaration.start.line, channel.declaration.start.character));
+    channels
+}
+
+/// URI of the report for `target`, or for the whole workspace.
+pub fn report_uri(target: Option<&Url>) -> Url {
+    let path = target.map(|uri| uri.path()).unwrap_or("/workspace");
+    Url::parse(&format!("{}:{}", REPORT_SCHEME, path)).expect("valid report URI")
+}
+
+/// Renders the report of `channels`, found in `documents` scanned documents.
+pub fn build_report(target: Option<&Url>, mut channels: Vec<DeadChannel>, documents: usize) -> DeadChannelReport {
+    channels.sort_by(|a, b| {
+        a.uri
+            .as_str()
+            .cmp(b.uri.as_str())
+            .then((a.declaration.start.line, a.declaration.start.character).cmp(&(b.declaration.start.line, b.declaration.start.character)))
+    });
+
+    let mut content = String::from("# Dead channels\n\n");
+    let _ = writeln!(content, "{} channel(s) in {} document(s)", channels.len(), documents);
+    let mut current: Option<&Url> = None;
+    for channel in &channels {
+        if current != Some(&channel.uri) {
+            let _ = write!(content, "\n## {}\n\n", channel.uri);
+            current = Some(&channel.uri);
+        }
+        let _ = writeln!(
+            content,
+            "- line {}: {}",
+            channel.declaration.start.line + 1,
+            channel.message
+        );
+    }
+
+    DeadChannelReport {
+        uri: report_uri(target),
+        content,
+        channels,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree_sitter::parse_with_positions;
+
+    fn dead(source: &str) -> Vec<(String, DeadChannelKind)> {
+        let uri = Url::parse("file:///legacy.rho").unwrap();
+        let rope = Rope::from_str(source);
+        let (ir, positions) = parse_with_positions(source);
+        find_dead_channels(&uri, &ir, &positions, &rope, &LineIndex::new(source))
+            .into_iter()
+            .map(|channel| (channel.name, channel.kind))
+            .collect()
+    }
+
+    #[test]
+    fn test_classifies_new_names() {
+        let source = r#"new stdout(`rho:io:stdout`), unused, once, sink, source, ok, passed in {
+  once!(1) |
+  sink!(1) | sink!(2) |
+  for (_ <- source) { Nil } | for (_ <- source) { Nil } |
+  ok!(1) | for (_ <- ok) { Nil } |
+  stdout!(*passed) | passed!(3)
+}"#;
+        assert_eq!(
+            dead(source),
+            vec![
+                ("unused".to_string(), DeadChannelKind::Unused),
+                ("once".to_string(), DeadChannelKind::UsedOnce),
+                ("sink".to_string(), DeadChannelKind::OnlySent),
+                ("source".to_string(), DeadChannelKind::OnlyReceived),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_shadowed_uses_and_contracts() {
+        // The inner `x` shadows the outer one, which is therefore unused
+        let source = "new x in {\n  new x in { x!(1) | for (_ <- x) { Nil } }\n}";
+        assert_eq!(dead(source), vec![("x".to_string(), DeadChannelKind::Unused)]);
+
+        // A contract on a `new` name receives on it
+        let source = "new service in {\n  contract service(@n) = { Nil } | service!(1)\n}";
+        assert!(dead(source).is_empty());
+    }
+
+    #[test]
+    fn test_report_rendering() {
+        let uri = Url::parse("file:///legacy.rho").unwrap();
+        let channel = |name: &str, line: u32| DeadChannel {
+            uri: uri.clone(),
+            name: name.to_string(),
+            kind: DeadChannelKind::Unused,
+            message: format!("'{}' is declared but never used", name),
+            declaration: Range::new(tower_lsp::lsp_types::Position::new(line, 4), tower_lsp::lsp_types::Position::new(line, 5)),
+            uses: Vec::new(),
+        };
+        let report = build_report(None, vec![channel("b", 3), channel("a", 0)], 2);
+        assert_eq!(report.uri.as_str(), "rholang-dead-channels:/workspace");
+        assert_eq!(
+            report.content,
+            "# Dead channels\n\n2 channel(s) in 2 document(s)\n\n## file:///legacy.rho\n\n\
+             - line 1: 'a' is declared but never used\n\
+             - line 4: 'b' is declared but never used\n"
+        );
+        assert_eq!(report_uri(Some(&uri)).as_str(), "rholang-dead-channels:/legacy.rho");
+    }
+}
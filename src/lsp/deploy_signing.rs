@@ -0,0 +1,196 @@
+//! Signed deploys to RNode (`rholang.deploy`)
+//!
+//! A deploy is the document's text wrapped in a `DeployDataProto` and signed
+//! with the deployer's secp256k1 key: the Blake2b-256 hash of the deploy data,
+//! encoded without `deployer`, `sig` and `sigAlgorithm`, is signed and the
+//! DER signature sent along with the uncompressed public key, as RNode
+//! expects.
+//!
+//! The key and deploy parameters come from the `deploy` section of the
+//! initialization options, next to the endpoint allowlist of
+//! `crate::lsp::workspace_trust`:
+//!
+//! ```json
+//! { "deploy": { "allowedEndpoints": ["localhost:40401"], "validatorUrl": "localhost:40401",
+//!               "phloLimit": 500000, "phloPrice": 1, "shardId": "root" } }
+//! ```
+//!
+//! The private key (64 hex digits) is read from the `RHOLANG_DEPLOY_PRIVATE_KEY`
+//! environment variable, or else from `deploy.privateKey`; keeping it in the
+//! environment keeps it out of editor settings. It is never logged.
+
+use std::fmt;
+
+use blake2::digest::consts::U32;
+use blake2::{Blake2b, Digest};
+use k256::ecdsa::signature::hazmat::PrehashSigner;
+use k256::ecdsa::{Signature, SigningKey};
+use prost::Message;
+use serde::Deserialize;
+
+use crate::rnode_apis::deploy::DeployDataProto;
+
+/// Environment variable holding the deployer's private key, in hex
+pub const PRIVATE_KEY_ENV: &str = "RHOLANG_DEPLOY_PRIVATE_KEY";
+
+/// Signature algorithm name RNode expects
+pub const SIG_ALGORITHM: &str = "secp256k1";
+
+type Blake2b256 = Blake2b<U32>;
+
+/// A deployer's secp256k1 private key
+#[derive(Clone)]
+pub struct DeployKey(SigningKey);
+
+impl DeployKey {
+    /// Parses a private key written as 64 hex digits, optionally `0x`-prefixed.
+    pub fn from_hex(hex_key: &str) -> Result<Self, String> {
+        let hex_key = hex_key.trim();
+        let bytes = hex::decode(hex_key.strip_prefix("0x").unwrap_or(hex_key))
+            .map_err(|_| "the deploy key is not hexadecimal".to_string())?;
+        SigningKey::from_slice(&bytes)
+            .map(DeployKey)
+            .map_err(|_| "the deploy key is not a secp256k1 private key".to_string())
+    }
+
+    /// The uncompressed public key, the deployer of the deploys signed with this key.
+    pub fn public_key(&self) -> Vec<u8> {
+        self.0.verifying_key().to_encoded_point(false).as_bytes().to_vec()
+    }
+}
+
+impl fmt::Debug for DeployKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DeployKey(public: {})", hex::encode(self.public_key()))
+    }
+}
+
+/// The `deploy` section of the initialization options, past the allowlist
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DeploySettings {
+    /// Private key in hex; overridden by [`PRIVATE_KEY_ENV`]
+    private_key: Option<String>,
+    /// Endpoint deploys go to when the command names none
+    pub validator_url: Option<String>,
+    pub phlo_limit: i64,
+    pub phlo_price: i64,
+    pub shard_id: String,
+    pub valid_after_block_number: i64,
+}
+
+impl Default for DeploySettings {
+    fn default() -> Self {
+        DeploySettings {
+            private_key: None,
+            validator_url: None,
+            phlo_limit: 500_000,
+            phlo_price: 1,
+            shard_id: "root".to_string(),
+            valid_after_block_number: 0,
+        }
+    }
+}
+
+impl fmt::Debug for DeploySettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeploySettings")
+            .field("private_key", &self.private_key.as_ref().map(|_| "<redacted>"))
+            .field("validator_url", &self.validator_url)
+            .field("phlo_limit", &self.phlo_limit)
+            .field("phlo_price", &self.phlo_price)
+            .field("shard_id", &self.shard_id)
+            .field("valid_after_block_number", &self.valid_after_block_number)
+            .finish()
+    }
+}
+
+impl DeploySettings {
+    /// Reads the `deploy` initialization option and [`PRIVATE_KEY_ENV`].
+    pub fn from_env_or_init_options(init_options: Option<&serde_json::Value>) -> Self {
+        let mut settings = Self::from_init_options(init_options);
+        if let Ok(key) = std::env::var(PRIVATE_KEY_ENV) {
+            settings.private_key = Some(key);
+        }
+        settings
+    }
+
+    /// Reads the `deploy` initialization option; missing fields keep their defaults.
+    pub fn from_init_options(init_options: Option<&serde_json::Value>) -> Self {
+        init_options
+            .and_then(|opts| opts.get("deploy"))
+            .and_then(|deploy| serde_json::from_value(deploy.clone()).ok())
+            .unwrap_or_default()
+    }
+
+    /// The configured key; an error says why deploys cannot be signed.
+    pub fn key(&self) -> Result<DeployKey, String> {
+        match &self.private_key {
+            Some(key) => DeployKey::from_hex(key),
+            None => Err(format!("no deploy key: set {} or deploy.privateKey", PRIVATE_KEY_ENV)),
+        }
+    }
+}
+
+/// The deploy of `term` at `timestamp` (milliseconds), signed with `key`.
+pub fn sign_deploy(term: String, timestamp: i64, settings: &DeploySettings, key: &DeployKey) -> Result<DeployDataProto, String> {
+    let mut deploy = DeployDataProto {
+        deployer: Vec::new(),
+        term,
+        timestamp,
+        sig: Vec::new(),
+        sig_algorithm: String::new(),
+        phlo_price: settings.phlo_price,
+        phlo_limit: settings.phlo_limit,
+        valid_after_block_number: settings.valid_after_block_number,
+        shard_id: settings.shard_id.clone(),
+    };
+    let hash = Blake2b256::digest(deploy.encode_to_vec());
+    let signature: Signature = key.0.sign_prehash(&hash).map_err(|e| format!("signing failed: {}", e))?;
+    deploy.deployer = key.public_key();
+    deploy.sig = signature.to_der().as_bytes().to_vec();
+    deploy.sig_algorithm = SIG_ALGORITHM.to_string();
+    Ok(deploy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k256::ecdsa::signature::hazmat::PrehashVerifier;
+    use k256::ecdsa::VerifyingKey;
+    use serde_json::json;
+
+    const KEY: &str = "0x5f668a7ee96d944a4494cc947e4005e172d7ab3461ee5538f1f2a45a835e9657";
+
+    #[test]
+    fn test_signed_deploy_verifies() {
+        let key = DeployKey::from_hex(KEY).unwrap();
+        let settings = DeploySettings::default();
+        let deploy = sign_deploy("Nil".to_string(), 1_700_000_000_000, &settings, &key).unwrap();
+        assert_eq!(deploy.deployer.len(), 65);
+        assert_eq!(deploy.sig_algorithm, SIG_ALGORITHM);
+        assert_eq!(deploy.phlo_limit, 500_000);
+
+        let unsigned = DeployDataProto { deployer: Vec::new(), sig: Vec::new(), sig_algorithm: String::new(), ..deploy.clone() };
+        let hash = Blake2b256::digest(unsigned.encode_to_vec());
+        let verifying = VerifyingKey::from_sec1_bytes(&deploy.deployer).unwrap();
+        let signature = Signature::from_der(&deploy.sig).unwrap();
+        assert!(verifying.verify_prehash(&hash, &signature).is_ok());
+    }
+
+    #[test]
+    fn test_settings_and_keys() {
+        let settings = DeploySettings::from_init_options(Some(&json!({
+            "deploy": { "allowedEndpoints": ["localhost:40401"], "privateKey": KEY, "phloLimit": 100, "shardId": "test" }
+        })));
+        assert_eq!(settings.phlo_limit, 100);
+        assert_eq!(settings.phlo_price, 1);
+        assert_eq!(settings.shard_id, "test");
+        assert!(settings.key().is_ok());
+        assert!(!format!("{:?}", settings).contains("5f668a7e"), "keys are never printed");
+
+        assert!(DeploySettings::default().key().is_err());
+        assert!(DeployKey::from_hex("xyz").is_err());
+        assert!(DeployKey::from_hex("00").is_err());
+    }
+}
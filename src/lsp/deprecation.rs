@@ -0,0 +1,461 @@
+//! `@deprecated` doc directives and references to deprecated declarations
+//!
+//! A `// @deprecated reason` comment (or a `@deprecated` tag in a doc comment)
+//! on the line immediately above a contract or a `new` name marks it
+//! deprecated:
+//!
+//! ```text
+//! new
+//!   // @deprecated use vault2
+//!   vault,
+//!   vault2
+//! in {
+//!   // @deprecated use transfer2
+//!   contract transfer(@amount, ret) = { ... }
+//! }
+//! ```
+//!
+//! Each document's deprecated declarations are collected at indexing time
+//! ([`DeprecatedDeclarations::collect`]). References to them are reported as
+//! hint diagnostics tagged `DiagnosticTag::DEPRECATED` (rendered with a
+//! strike-through) and as semantic tokens with the `deprecated` modifier.
+//! Contracts are matched by name across the workspace; `new` names are matched
+//! by their declaration within the document.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use ropey::Rope;
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, DiagnosticTag, NumberOrString, Range, Url};
+
+use crate::i18n::tr;
+use crate::ir::rholang_node::{Metadata, NodeBase, Position as IrPosition, RholangNode, RholangNodeVector};
+use crate::ir::symbol_table::{Symbol, SymbolType};
+use crate::ir::transforms::documentation_attacher::DEPRECATION_METADATA_KEY;
+use crate::ir::visitor::Visitor;
+use crate::ir::DocumentIR;
+use crate::lsp::line_index::{lsp_range, LineIndex};
+
+/// Diagnostic source for deprecation hints
+pub const DEPRECATION_SOURCE: &str = "rholang-deprecation";
+/// Diagnostic code for references to deprecated declarations
+pub const DEPRECATED_USAGE_CODE: &str = "deprecated-usage";
+
+/// Deprecated declarations of one document (or merged across a workspace)
+#[derive(Debug, Clone, Default)]
+pub struct DeprecatedDeclarations {
+    /// Deprecated contract name -> reason
+    contracts: HashMap<String, String>,
+    /// Declaration of a deprecated name -> reason
+    names: HashMap<(Url, IrPosition), String>,
+}
+
+/// A reference to a deprecated declaration
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeprecatedUsage {
+    /// Referenced name
+    pub name: String,
+    /// Reason given by the `@deprecated` directive (may be empty)
+    pub reason: String,
+    /// Whether the referenced declaration is a contract
+    pub is_contract: bool,
+    /// LSP range of the reference
+    pub range: Range,
+}
+
+impl DeprecatedDeclarations {
+    /// Collects the deprecated contracts and `new` names declared in `ir`.
+    ///
+    /// Directives are read from `DEPRECATION_METADATA_KEY` metadata when the
+    /// documentation attacher has run, and otherwise from the comment channel
+    /// of `document_ir`.
+    pub fn collect(
+        uri: &Url,
+        ir: &Arc<RholangNode>,
+        positions: &HashMap<usize, (IrPosition, IrPosition)>,
+        document_ir: Option<&DocumentIR>,
+    ) -> Self {
+        let collector = DeclarationCollector {
+            uri,
+            positions,
+            document_ir,
+            scopes: RefCell::new(Vec::new()),
+            declarations: RefCell::new(Self::default()),
+        };
+        collector.visit_node(ir);
+        collector.declarations.into_inner()
+    }
+
+    /// Returns true if no declarations are deprecated.
+    pub fn is_empty(&self) -> bool {
+        self.contracts.is_empty() && self.names.is_empty()
+    }
+
+    /// Adds the declarations of `other` (e.g. another document's).
+    pub fn extend(&mut self, other: &DeprecatedDeclarations) {
+        self.contracts.extend(other.contracts.iter().map(|(k, v)| (k.clone(), v.clone())));
+        self.names.extend(other.names.iter().map(|(k, v)| (k.clone(), v.clone())));
+    }
+
+    /// Reason for a deprecated contract name.
+    pub fn contract(&self, name: &str) -> Option<&str> {
+        self.contracts.get(name).map(String::as_str)
+    }
+
+    /// Finds references to deprecated declarations in `ir`, in source order.
+    pub fn find_usages(
+        &self,
+        uri: &Url,
+        ir: &Arc<RholangNode>,
+        positions: &HashMap<usize, (IrPosition, IrPosition)>,
+        text: &Rope,
+        line_index: &LineIndex,
+    ) -> Vec<DeprecatedUsage> {
+        let finder = UsageFinder {
+            uri,
+            positions,
+            text,
+            line_index,
+            declarations: self,
+            usages: RefCell::new(Vec::new()),
+        };
+        finder.visit_node(ir);
+
+        let mut usages = finder.usages.into_inner();
+        usages.sort_by_key(|usage| (usage.range.start.line, usage.range.start.character));
+        usages
+    }
+
+    /// Reason for a deprecated reference resolved to `symbol` (if any).
+    fn resolve(&self, name: &str, symbol: Option<&Symbol>) -> Option<(String, bool)> {
+        if let Some(symbol) = symbol {
+            if let Some(reason) = &symbol.deprecated {
+                return Some((reason.clone(), symbol.symbol_type == SymbolType::Contract));
+            }
+            let key = (symbol.declaration_uri.clone(), symbol.declaration_location);
+            if let Some(reason) = self.names.get(&key) {
+                return Some((reason.clone(), false));
+            }
+            if symbol.symbol_type != SymbolType::Contract {
+                return None;
+            }
+        }
+        // Unbound or contract references resolve by name across the workspace
+        self.contracts.get(name).map(|reason| (reason.clone(), true))
+    }
+}
+
+impl DeprecatedUsage {
+    /// Hint diagnostic tagged `DEPRECATED` for this reference.
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let key = match (self.is_contract, self.reason.is_empty()) {
+            (true, true) => "deprecation.contract",
+            (true, false) => "deprecation.contract-reason",
+            (false, true) => "deprecation.name",
+            (false, false) => "deprecation.name-reason",
+        };
+        let message = tr(key, &[("name", &self.name), ("reason", &self.reason)]);
+        Diagnostic {
+            range: self.range,
+            severity: Some(DiagnosticSeverity::HINT),
+            code: Some(NumberOrString::String(DEPRECATED_USAGE_CODE.to_string())),
+            source: Some(DEPRECATION_SOURCE.to_string()),
+            message,
+            tags: Some(vec![DiagnosticTag::DEPRECATED]),
+            ..Default::default()
+        }
+    }
+}
+
+/// Formats the hover notice for a deprecation reason.
+pub fn deprecation_notice(reason: &str) -> String {
+    if reason.is_empty() {
+        tr("hover.deprecated", &[])
+    } else {
+        tr("hover.deprecated-reason", &[("reason", &reason)])
+    }
+}
+
+/// Reads the deprecation reason attached as node metadata.
+pub fn metadata_deprecation(metadata: &Metadata) -> Option<&str> {
+    metadata
+        .get(DEPRECATION_METADATA_KEY)
+        .and_then(|reason| reason.downcast_ref::<String>())
+        .map(String::as_str)
+}
+
+/// Reads the symbol a reference was resolved to by the symbol table builder.
+pub fn referenced_symbol(metadata: &Metadata) -> Option<&Arc<Symbol>> {
+    metadata
+        .get("referenced_symbol")
+        .and_then(|symbol| symbol.downcast_ref::<Arc<Symbol>>())
+}
+
+/// Returns the plain name of a contract identifier (`foo` or `@"foo"`).
+fn identifier_name(node: &RholangNode) -> Option<&str> {
+    match node {
+        RholangNode::Var { name, .. } if !name.is_empty() => Some(name),
+        RholangNode::Quote { quotable, .. } => match &**quotable {
+            RholangNode::StringLiteral { value, .. } => Some(value),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Collects deprecated declarations, tracking `new` scopes so a deprecated
+/// contract also deprecates the `new` name it is bound to.
+struct DeclarationCollector<'a> {
+    uri: &'a Url,
+    positions: &'a HashMap<usize, (IrPosition, IrPosition)>,
+    document_ir: Option<&'a DocumentIR>,
+    /// Names bound by enclosing `new`s -> position of their declaration
+    scopes: RefCell<Vec<HashMap<String, IrPosition>>>,
+    declarations: RefCell<DeprecatedDeclarations>,
+}
+
+impl DeclarationCollector<'_> {
+    fn start(&self, node: &Arc<RholangNode>) -> Option<IrPosition> {
+        self.positions.get(&(Arc::as_ptr(node) as usize)).map(|(start, _)| *start)
+    }
+
+    fn deprecation(&self, node: &Arc<RholangNode>) -> Option<String> {
+        if let Some(reason) = node.metadata().and_then(|m| metadata_deprecation(m)) {
+            return Some(reason.to_string());
+        }
+        let start = self.start(node)?;
+        self.document_ir?.deprecation_before(&start)
+    }
+}
+
+impl Visitor for DeclarationCollector<'_> {
+    fn visit_new(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        decls: &RholangNodeVector,
+        proc: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        let mut scope = HashMap::new();
+        for decl in decls.iter() {
+            let RholangNode::NameDecl { var, .. } = &**decl else { continue };
+            let (RholangNode::Var { name, .. }, Some(var_start)) = (&**var, self.start(var)) else { continue };
+            scope.insert(name.clone(), var_start);
+            if let Some(reason) = self.deprecation(decl) {
+                self.declarations.borrow_mut().names.insert((self.uri.clone(), var_start), reason);
+            }
+        }
+
+        self.scopes.borrow_mut().push(scope);
+        self.visit_node(proc);
+        self.scopes.borrow_mut().pop();
+        Arc::clone(node)
+    }
+
+    fn visit_contract(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        name: &Arc<RholangNode>,
+        _formals: &RholangNodeVector,
+        _formals_remainder: &Option<Arc<RholangNode>>,
+        proc: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        if let (Some(contract_name), Some(reason)) = (identifier_name(name), self.deprecation(node)) {
+            let bound_at = self
+                .scopes
+                .borrow()
+                .iter()
+                .rev()
+                .find_map(|scope| scope.get(contract_name).copied());
+
+            let mut declarations = self.declarations.borrow_mut();
+            if let Some(var_start) = bound_at {
+                declarations.names.insert((self.uri.clone(), var_start), reason.clone());
+            }
+            declarations.contracts.insert(contract_name.to_string(), reason);
+        }
+
+        self.visit_node(proc);
+        Arc::clone(node)
+    }
+}
+
+/// Finds references to deprecated declarations, skipping declaration sites.
+struct UsageFinder<'a> {
+    uri: &'a Url,
+    positions: &'a HashMap<usize, (IrPosition, IrPosition)>,
+    text: &'a Rope,
+    line_index: &'a LineIndex,
+    declarations: &'a DeprecatedDeclarations,
+    usages: RefCell<Vec<DeprecatedUsage>>,
+}
+
+impl UsageFinder<'_> {
+    fn record(&self, node: &Arc<RholangNode>, name: &str, (reason, is_contract): (String, bool)) {
+        if let Some(span) = self.positions.get(&(Arc::as_ptr(node) as usize)) {
+            self.usages.borrow_mut().push(DeprecatedUsage {
+                name: name.to_string(),
+                reason,
+                is_contract,
+                range: lsp_range(self.text, self.line_index, span),
+            });
+        }
+    }
+}
+
+impl Visitor for UsageFinder<'_> {
+    fn visit_new(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        _decls: &RholangNodeVector,
+        proc: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.visit_node(proc);
+        Arc::clone(node)
+    }
+
+    fn visit_contract(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        _name: &Arc<RholangNode>,
+        _formals: &RholangNodeVector,
+        _formals_remainder: &Option<Arc<RholangNode>>,
+        proc: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.visit_node(proc);
+        Arc::clone(node)
+    }
+
+    fn visit_quote(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        quotable: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        match identifier_name(node) {
+            Some(name) if matches!(&**quotable, RholangNode::StringLiteral { .. }) => {
+                if let Some(reason) = self.declarations.contract(name) {
+                    self.record(node, name, (reason.to_string(), true));
+                }
+            }
+            _ => {
+                self.visit_node(quotable);
+            }
+        }
+        Arc::clone(node)
+    }
+
+    fn visit_var(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        name: &String,
+        metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        if name.is_empty() {
+            return Arc::clone(node);
+        }
+        let symbol = metadata.as_deref().and_then(referenced_symbol);
+
+        // A declaration (e.g. a `let` binding) is not a reference
+        if let (Some(symbol), Some((start, _))) = (symbol, self.positions.get(&(Arc::as_ptr(node) as usize))) {
+            if &symbol.declaration_uri == self.uri && symbol.declaration_location == *start {
+                return Arc::clone(node);
+            }
+        }
+
+        if let Some(deprecation) = self.declarations.resolve(name, symbol.map(|s| &**s)) {
+            self.record(node, name, deprecation);
+        }
+        Arc::clone(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::rholang_node::compute_absolute_positions;
+    use crate::ir::symbol_table::SymbolTable;
+    use crate::ir::transforms::symbol_table_builder::SymbolTableBuilder;
+    use crate::tree_sitter::{parse_code, parse_to_document_ir};
+
+    fn usages(source: &str) -> Vec<DeprecatedUsage> {
+        let uri = Url::parse("file:///deprecation.rho").unwrap();
+        let tree = parse_code(source);
+        let rope = Rope::from_str(source);
+        let document_ir = parse_to_document_ir(&tree, &rope);
+
+        let global_table = Arc::new(SymbolTable::new(None));
+        let builder = SymbolTableBuilder::new(document_ir.root.clone(), uri.clone(), global_table, None);
+        let ir = builder.visit_node(&document_ir.root);
+        let positions = compute_absolute_positions(&ir);
+
+        let declarations = DeprecatedDeclarations::collect(&uri, &ir, &positions, Some(&document_ir));
+        declarations.find_usages(&uri, &ir, &positions, &rope, &LineIndex::new(source))
+    }
+
+    #[test]
+    fn test_deprecated_contract_references() {
+        let source = r#"new transfer, transfer2, stdout(`rho:io:stdout`) in {
+  // @deprecated use transfer2
+  contract transfer(@amount) = { stdout!(amount) } |
+  contract transfer2(@amount) = { stdout!(amount) } |
+  transfer!(1) |
+  transfer2!(2)
+}"#;
+        let found = usages(source);
+        assert_eq!(found.len(), 1, "Expected one deprecated reference, got {:?}", found);
+        assert_eq!(found[0].name, "transfer");
+        assert_eq!(found[0].reason, "use transfer2");
+        assert!(found[0].is_contract);
+        assert_eq!((found[0].range.start.line, found[0].range.start.character), (4, 2));
+
+        let diagnostic = found[0].to_diagnostic();
+        assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::HINT));
+        assert_eq!(diagnostic.tags, Some(vec![DiagnosticTag::DEPRECATED]));
+        assert_eq!(diagnostic.message, "Contract 'transfer' is deprecated: use transfer2");
+    }
+
+    #[test]
+    fn test_deprecated_new_name_references() {
+        let source = r#"new
+  // @deprecated
+  oldChan,
+  newChan
+in {
+  oldChan!(1) | newChan!(2) | for (@x <- oldChan) { Nil }
+}"#;
+        let found = usages(source);
+        let rows: Vec<(u32, u32)> = found.iter().map(|u| (u.range.start.line, u.range.start.character)).collect();
+        assert_eq!(rows, vec![(5, 2), (5, 41)]);
+        assert!(found.iter().all(|u| u.name == "oldChan" && !u.is_contract && u.reason.is_empty()));
+        assert_eq!(found[0].to_diagnostic().message, "Name 'oldChan' is deprecated");
+    }
+
+    #[test]
+    fn test_quoted_contract_name_references() {
+        let source = r#"// @deprecated
+contract @"legacy"(@x) = { Nil } |
+@"legacy"!(1)"#;
+        let found = usages(source);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].name, "legacy");
+        assert_eq!(found[0].range.start.line, 2);
+    }
+
+    #[test]
+    fn test_no_usages_without_directive() {
+        let source = r#"// regular comment
+contract foo(@x) = { Nil } | foo!(1)"#;
+        assert!(usages(source).is_empty());
+    }
+}
@@ -5,6 +5,14 @@
 
 use tower_lsp::lsp_types::Diagnostic;
 
+use crate::lsp::line_index::PositionEncoding;
+
+/// Diagnostic code a provider attaches when it could not validate the
+/// document at all (server unreachable, internal error)
+pub const VALIDATOR_UNAVAILABLE_CODE: &str = "validator-unavailable";
+/// Diagnostic code of the errors RNode reports for a document
+pub const RNODE_ERROR_CODE: &str = "rnode-error";
+
 /// Common interface for all diagnostic/validation backends
 ///
 /// This trait allows the LSP backend to work with different validation implementations:
@@ -17,12 +25,22 @@ pub trait DiagnosticProvider: Send + Sync {
     ///
     /// This method should:
     /// 1. Parse and validate the source code
-    /// 2. Return any syntax or semantic errors as LSP diagnostics
+    /// 2. Return any syntax or semantic errors as LSP diagnostics, with a
+    ///    code registered in `crate::lsp::diagnostic_registry`
     /// 3. Return an empty vec if the code is valid
     async fn validate(&self, source: &str) -> Vec<Diagnostic>;
 
     /// Get a human-readable name for this backend (for logging/debugging)
     fn backend_name(&self) -> &'static str;
+
+    /// Whether a clean result means the code was checked by RNode itself
+    fn verifies_with_rnode(&self) -> bool {
+        false
+    }
+
+    /// Sets the position encoding negotiated with the client, for providers
+    /// that translate the positions of their diagnostics themselves.
+    fn set_position_encoding(&self, _encoding: PositionEncoding) {}
 }
 
 /// Configuration for selecting a diagnostic backend
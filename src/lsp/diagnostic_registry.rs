@@ -0,0 +1,188 @@
+//! Stable codes and documentation of published diagnostics
+//!
+//! Producers give their diagnostics a descriptive code, such as
+//! `duplicate-send`. Before diagnostics are published, [`stamp`] replaces a
+//! registered code with its stable `RHO` code and links the diagnostic to its
+//! entry in [`docs/DIAGNOSTICS.md`](DOCS_URL), where the editor shows it
+//! next to the code. Severity overrides (see `crate::lsp::settings`) accept
+//! either code.
+//!
+//! | Range    | Diagnostics                                                |
+//! |----------|------------------------------------------------------------|
+//! | `RHO0xx` | errors from the parser, the interpreter or RNode           |
+//! | `RHO1xx` | warnings from the analysis of a document                   |
+//! | `RHO2xx` | style lints and conventions from `.rholangrc.toml`         |
+//! | `RHO3xx` | lint rules from `[lints.rules]`                            |
+//!
+//! Codes are never reused: an entry that is no longer produced stays here.
+//!
+//! Producers that do not know the URI of the document they diagnose, such as
+//! the semantic validators, point related information at
+//! [`same_document`], which [`stamp`] resolves.
+
+use tower_lsp::lsp_types::{
+    CodeDescription, Diagnostic, DiagnosticRelatedInformation, Location, NumberOrString, Range, Url,
+};
+
+use crate::lsp::analysis_budget::ANALYSIS_PARTIAL_CODE;
+use crate::lsp::conventions::{BANNED_CONSTRUCT_CODE, STRUCTURE_LINT_CODE};
+use crate::lsp::cost_estimation::PHLO_COST_CODE;
+use crate::lsp::deprecation::DEPRECATED_USAGE_CODE;
+use crate::lsp::diagnostic_provider::{RNODE_ERROR_CODE, VALIDATOR_UNAVAILABLE_CODE};
+use crate::lsp::duplicate_sends::DUPLICATE_SEND_CODE;
+use crate::lsp::format_check::UNFORMATTED_CODE;
+use crate::lsp::param_docs::PARAM_DOC_CODE;
+use crate::lsp::semantic_validator::{
+    CONTEXT_MISMATCH_CODE, DUPLICATE_BINDER_CODE, RECEIVE_ON_SAME_CHANNEL_CODE, SEMANTIC_ERROR_CODE,
+    TOP_LEVEL_PATTERN_CODE, UNBOUND_VARIABLE_CODE, UNSUPPORTED_OPERATION_CODE,
+};
+use crate::lsp::style_lints::NAMING_LINT_CODE;
+use crate::lsp::syntax_errors::SYNTAX_ERROR_CODE;
+
+/// Page documenting every registered diagnostic
+pub const DOCS_URL: &str = "https://github.com/F1R3FLY-io/rholang-language-server/blob/main/docs/DIAGNOSTICS.md";
+
+/// URI standing for the diagnosed document in related information
+const SAME_DOCUMENT_URI: &str = "rholang-document:self";
+
+/// A registered diagnostic
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiagnosticKind {
+    /// Stable code, such as `RHO101`
+    pub code: &'static str,
+    /// Code given by the producer, such as `duplicate-send`
+    pub name: &'static str,
+}
+
+impl DiagnosticKind {
+    /// Link to the entry of the diagnostic in [`DOCS_URL`]
+    pub fn documentation(&self) -> Url {
+        Url::parse(&format!("{}#{}", DOCS_URL, self.code.to_lowercase())).expect("valid URL")
+    }
+}
+
+const fn kind(code: &'static str, name: &'static str) -> DiagnosticKind {
+    DiagnosticKind { code, name }
+}
+
+/// Every registered diagnostic, in code order
+pub const REGISTRY: &[DiagnosticKind] = &[
+    kind("RHO001", SYNTAX_ERROR_CODE),
+    kind("RHO002", UNBOUND_VARIABLE_CODE),
+    kind("RHO003", CONTEXT_MISMATCH_CODE),
+    kind("RHO004", DUPLICATE_BINDER_CODE),
+    kind("RHO005", RECEIVE_ON_SAME_CHANNEL_CODE),
+    kind("RHO006", TOP_LEVEL_PATTERN_CODE),
+    kind("RHO007", UNSUPPORTED_OPERATION_CODE),
+    kind("RHO008", SEMANTIC_ERROR_CODE),
+    kind("RHO009", RNODE_ERROR_CODE),
+    kind("RHO010", VALIDATOR_UNAVAILABLE_CODE),
+    kind("RHO101", DUPLICATE_SEND_CODE),
+    kind("RHO102", PARAM_DOC_CODE),
+    kind("RHO103", PHLO_COST_CODE),
+    kind("RHO104", DEPRECATED_USAGE_CODE),
+    kind("RHO105", ANALYSIS_PARTIAL_CODE),
+    kind("RHO201", NAMING_LINT_CODE),
+    kind("RHO202", STRUCTURE_LINT_CODE),
+    kind("RHO203", BANNED_CONSTRUCT_CODE),
+    kind("RHO204", UNFORMATTED_CODE),
+    kind("RHO301", "unused-new-name"),
+    kind("RHO302", "unused-contract-formal"),
+    kind("RHO303", "send-never-received"),
+    kind("RHO304", "shadowed-name"),
+    kind("RHO305", "nil-only-branches"),
+    kind("RHO306", "deprecated-syntax"),
+];
+
+/// The diagnostic registered under `code`, stable or descriptive
+pub fn lookup(code: &str) -> Option<&'static DiagnosticKind> {
+    REGISTRY.iter().find(|kind| kind.code == code || kind.name == code)
+}
+
+/// The registered diagnostic `diagnostic` is an instance of
+pub fn kind_of(diagnostic: &Diagnostic) -> Option<&'static DiagnosticKind> {
+    match &diagnostic.code {
+        Some(NumberOrString::String(code)) => lookup(code),
+        _ => None,
+    }
+}
+
+/// URI of the diagnosed document, for producers that do not know it
+pub fn same_document() -> Url {
+    Url::parse(SAME_DOCUMENT_URI).expect("valid URL")
+}
+
+/// Related information at `range` of the diagnosed document
+pub fn related_here(range: Range, message: impl Into<String>) -> DiagnosticRelatedInformation {
+    DiagnosticRelatedInformation { location: Location { uri: same_document(), range }, message: message.into() }
+}
+
+/// Gives the registered `diagnostics` of `uri` their stable code and a link
+/// to their documentation, and points related information given with
+/// [`same_document`] at `uri`.
+///
+/// Stamping diagnostics twice changes nothing.
+pub fn stamp(uri: &Url, diagnostics: &mut [Diagnostic]) {
+    for diagnostic in diagnostics {
+        if let Some(kind) = kind_of(diagnostic) {
+            diagnostic.code = Some(NumberOrString::String(kind.code.to_string()));
+            diagnostic.code_description = Some(CodeDescription { href: kind.documentation() });
+        }
+        for related in diagnostic.related_information.iter_mut().flatten() {
+            if related.location.uri.as_str() == SAME_DOCUMENT_URI {
+                related.location.uri = uri.clone();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validators::lint::all_rules;
+    use tower_lsp::lsp_types::Position;
+
+    #[test]
+    fn test_codes_are_unique_and_well_formed() {
+        for (i, kind) in REGISTRY.iter().enumerate() {
+            assert!(kind.code.len() == 6 && kind.code.starts_with("RHO"), "{}", kind.code);
+            assert!(kind.code[3..].chars().all(|c| c.is_ascii_digit()), "{}", kind.code);
+            for other in &REGISTRY[i + 1..] {
+                assert!(kind.code < other.code, "{} is out of order", other.code);
+                assert_ne!(kind.name, other.name);
+            }
+        }
+        for rule in all_rules() {
+            assert!(lookup(rule.code()).is_some(), "lint rule {} is not registered", rule.code());
+        }
+    }
+
+    #[test]
+    fn test_stamp() {
+        let uri = Url::parse("file:///workspace/contract.rho").unwrap();
+        let range = Range::new(Position::new(0, 4), Position::new(0, 5));
+        let mut diagnostics = vec![
+            Diagnostic {
+                code: Some(NumberOrString::String(DUPLICATE_BINDER_CODE.to_string())),
+                related_information: Some(vec![related_here(range, "first bound here")]),
+                ..Default::default()
+            },
+            Diagnostic { code: Some(NumberOrString::String("metta-error".to_string())), ..Default::default() },
+        ];
+        stamp(&uri, &mut diagnostics);
+        let once = diagnostics.clone();
+        stamp(&uri, &mut diagnostics);
+        assert_eq!(diagnostics, once);
+
+        assert_eq!(diagnostics[0].code, Some(NumberOrString::String("RHO004".to_string())));
+        assert_eq!(
+            diagnostics[0].code_description.as_ref().map(|d| d.href.as_str()),
+            Some(format!("{}#rho004", DOCS_URL).as_str())
+        );
+        assert_eq!(diagnostics[0].related_information.as_ref().unwrap()[0].location, Location { uri, range });
+
+        // Unregistered diagnostics keep their code
+        assert_eq!(diagnostics[1].code, Some(NumberOrString::String("metta-error".to_string())));
+        assert_eq!(diagnostics[1].code_description, None);
+    }
+}
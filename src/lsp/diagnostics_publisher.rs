@@ -0,0 +1,287 @@
+//! Deduplicating, rate-limited queue for `textDocument/publishDiagnostics`
+//!
+//! Cross-file revalidation on large workspaces can produce hundreds of
+//! diagnostic updates per second, most of them identical to what the client
+//! already shows. This module holds the pure queueing logic used by the
+//! backend's diagnostics publisher task:
+//!
+//! - **Coalescing**: only the latest pending update per URI is kept
+//! - **Deduplication**: an update identical (same version and payload) to the
+//!   last one published for that URI is dropped
+//! - **Rate limiting**: a token bucket bounds publishes per second; updates
+//!   that exceed the budget stay pending instead of being dropped
+//! - **Final flush**: `drain_all` ignores the rate limit so shutdown (or any
+//!   caller) can always publish a consistent final state
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, Instant};
+
+use tower_lsp::lsp_types::{Diagnostic, Url};
+
+/// Token bucket parameters for diagnostics publishing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiagnosticsRateLimit {
+    /// Maximum number of publishes allowed in a single burst
+    pub burst: u32,
+    /// Sustained publishes per second once the burst is spent
+    pub per_second: u32,
+}
+
+impl Default for DiagnosticsRateLimit {
+    fn default() -> Self {
+        Self { burst: 50, per_second: 100 }
+    }
+}
+
+/// A diagnostics update ready to be sent to the client.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingDiagnostics {
+    pub uri: Url,
+    pub diagnostics: Vec<Diagnostic>,
+    pub version: Option<i32>,
+}
+
+/// Simple token bucket refilled continuously at `per_second`.
+#[derive(Debug)]
+struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(limit: DiagnosticsRateLimit, now: Instant) -> Self {
+        let capacity = limit.burst.max(1) as f64;
+        Self {
+            capacity,
+            tokens: capacity,
+            refill_per_sec: limit.per_second.max(1) as f64,
+            last_refill: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+    }
+
+    fn try_take(&mut self, now: Instant) -> bool {
+        self.refill(now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Time until at least one token is available.
+    fn time_until_available(&self) -> Duration {
+        if self.tokens >= 1.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((1.0 - self.tokens) / self.refill_per_sec)
+        }
+    }
+}
+
+/// Coalescing, deduplicating and rate-limited diagnostics queue.
+#[derive(Debug)]
+pub struct DiagnosticsPublisher {
+    /// Latest pending update per URI
+    pending: HashMap<Url, PendingDiagnostics>,
+    /// URIs in first-enqueued order, for fair publishing under rate limiting
+    order: VecDeque<Url>,
+    /// Fingerprint of the last update published per URI
+    last_published: HashMap<Url, u64>,
+    bucket: TokenBucket,
+}
+
+impl DiagnosticsPublisher {
+    /// Creates an empty publisher with the given rate limit.
+    pub fn new(limit: DiagnosticsRateLimit) -> Self {
+        Self {
+            pending: HashMap::new(),
+            order: VecDeque::new(),
+            last_published: HashMap::new(),
+            bucket: TokenBucket::new(limit, Instant::now()),
+        }
+    }
+
+    /// Queues an update, replacing any older pending update for the same URI.
+    ///
+    /// A pending update is only kept over the new one if both carry a version
+    /// and the pending version is newer (a stale validation finishing late).
+    pub fn enqueue(&mut self, uri: Url, diagnostics: Vec<Diagnostic>, version: Option<i32>) {
+        if let Some(existing) = self.pending.get(&uri) {
+            if let (Some(existing_ver), Some(new_ver)) = (existing.version, version) {
+                if existing_ver > new_ver {
+                    return;
+                }
+            }
+        } else {
+            self.order.push_back(uri.clone());
+        }
+        self.pending.insert(uri.clone(), PendingDiagnostics { uri, diagnostics, version });
+    }
+
+    /// Returns true if any update is waiting to be published.
+    pub fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Number of URIs with a pending update.
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Time until the rate limiter will allow the next publish.
+    pub fn next_ready_in(&self) -> Duration {
+        self.bucket.time_until_available()
+    }
+
+    /// Takes as many pending updates as the rate limit allows at `now`.
+    ///
+    /// Duplicates of the last published update are discarded without
+    /// consuming a token.
+    pub fn drain_ready(&mut self, now: Instant) -> Vec<PendingDiagnostics> {
+        let mut ready = Vec::new();
+        while let Some(uri) = self.order.front().cloned() {
+            let Some(update) = self.pending.get(&uri) else {
+                self.order.pop_front();
+                continue;
+            };
+            if self.is_duplicate(update) {
+                self.order.pop_front();
+                self.pending.remove(&uri);
+                continue;
+            }
+            if !self.bucket.try_take(now) {
+                break;
+            }
+            self.order.pop_front();
+            if let Some(update) = self.pending.remove(&uri) {
+                self.mark_published(&update);
+                ready.push(update);
+            }
+        }
+        ready
+    }
+
+    /// Takes every pending (non-duplicate) update, ignoring the rate limit.
+    pub fn drain_all(&mut self) -> Vec<PendingDiagnostics> {
+        let mut ready = Vec::new();
+        while let Some(uri) = self.order.pop_front() {
+            if let Some(update) = self.pending.remove(&uri) {
+                if !self.is_duplicate(&update) {
+                    self.mark_published(&update);
+                    ready.push(update);
+                }
+            }
+        }
+        ready
+    }
+
+    fn is_duplicate(&self, update: &PendingDiagnostics) -> bool {
+        self.last_published.get(&update.uri) == Some(&fingerprint(update))
+    }
+
+    fn mark_published(&mut self, update: &PendingDiagnostics) {
+        self.last_published.insert(update.uri.clone(), fingerprint(update));
+    }
+}
+
+impl Default for DiagnosticsPublisher {
+    fn default() -> Self {
+        Self::new(DiagnosticsRateLimit::default())
+    }
+}
+
+/// Hashes the version and serialized payload of an update.
+fn fingerprint(update: &PendingDiagnostics) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    update.version.hash(&mut hasher);
+    serde_json::to_vec(&update.diagnostics)
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp::lsp_types::{Position, Range};
+
+    fn uri(name: &str) -> Url {
+        Url::parse(&format!("file:///{}.rho", name)).unwrap()
+    }
+
+    fn diag(message: &str) -> Diagnostic {
+        Diagnostic {
+            range: Range::new(Position::new(0, 0), Position::new(0, 1)),
+            message: message.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_coalesces_latest_per_uri() {
+        let mut publisher = DiagnosticsPublisher::default();
+        publisher.enqueue(uri("a"), vec![diag("first")], Some(1));
+        publisher.enqueue(uri("a"), vec![diag("second")], Some(2));
+
+        let ready = publisher.drain_ready(Instant::now());
+        assert_eq!(ready.len(), 1);
+        assert_eq!(ready[0].diagnostics[0].message, "second");
+        assert_eq!(ready[0].version, Some(2));
+    }
+
+    #[test]
+    fn test_stale_version_does_not_replace_newer() {
+        let mut publisher = DiagnosticsPublisher::default();
+        publisher.enqueue(uri("a"), vec![diag("new")], Some(3));
+        publisher.enqueue(uri("a"), vec![diag("old")], Some(2));
+
+        let ready = publisher.drain_all();
+        assert_eq!(ready[0].diagnostics[0].message, "new");
+    }
+
+    #[test]
+    fn test_identical_payload_is_deduplicated() {
+        let mut publisher = DiagnosticsPublisher::default();
+        publisher.enqueue(uri("a"), vec![diag("x")], Some(1));
+        assert_eq!(publisher.drain_ready(Instant::now()).len(), 1);
+
+        publisher.enqueue(uri("a"), vec![diag("x")], Some(1));
+        assert!(publisher.drain_ready(Instant::now()).is_empty());
+        assert!(!publisher.has_pending());
+
+        // Same payload for a new version must still be published
+        publisher.enqueue(uri("a"), vec![diag("x")], Some(2));
+        assert_eq!(publisher.drain_ready(Instant::now()).len(), 1);
+    }
+
+    #[test]
+    fn test_rate_limit_defers_but_never_drops() {
+        let mut publisher = DiagnosticsPublisher::new(DiagnosticsRateLimit { burst: 2, per_second: 10 });
+        let start = Instant::now();
+        for i in 0..5 {
+            publisher.enqueue(uri(&format!("f{}", i)), vec![], Some(1));
+        }
+
+        assert_eq!(publisher.drain_ready(start).len(), 2);
+        assert_eq!(publisher.pending_count(), 3);
+        assert!(publisher.next_ready_in() > Duration::ZERO);
+
+        // After 100ms one more token is available
+        assert_eq!(publisher.drain_ready(start + Duration::from_millis(100)).len(), 1);
+
+        // Final flush publishes everything that remains
+        assert_eq!(publisher.drain_all().len(), 2);
+        assert!(!publisher.has_pending());
+    }
+}
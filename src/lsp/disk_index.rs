@@ -0,0 +1,354 @@
+//! Index-only artifacts for workspace files that are neither open nor indexed
+//!
+//! `workspace/symbol` and `textDocument/references` answer from the indexed
+//! documents in `WorkspaceState`. Files under the workspace root can be missing
+//! from it: while the initial indexing is still running, or when the root was
+//! inferred from the first opened document. For those files the backend loads
+//! a [`DiskArtifact`] on demand: the file is parsed and reduced to its contract
+//! declarations and the channel names of its sends. No symbol table, inverted
+//! index or per-document LSP state is built, and nothing is added to the global
+//! symbol tables.
+//!
+//! Artifacts are kept in a [`DiskIndex`], an LRU bounded by entry count and
+//! keyed by path. An entry is reloaded when the file's modification time or
+//! length changes. Artifacts resumed from an indexing checkpoint (see
+//! `crate::lsp::index_checkpoint`) are pinned instead: they stand in for
+//! documents that are not indexed again, so they are not evicted.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use parking_lot::Mutex;
+use ropey::Rope;
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{Location, Range, SymbolInformation, SymbolKind, Url};
+use tracing::{debug, trace};
+
+use crate::ir::rholang_node::{collect_calls, collect_contracts, compute_absolute_positions, Position as IrPosition, RholangNode};
+use crate::lsp::contracts_outline::node_name;
+use crate::lsp::line_index::{lsp_range, LineIndex, PositionEncoding};
+use crate::lsp::symbol_index::SymbolIndex;
+use crate::tree_sitter::{parse_code, parse_to_document_ir};
+
+/// Default number of artifacts kept in memory
+pub const DEFAULT_DISK_INDEX_CAPACITY: usize = 256;
+
+/// What the index knows about one on-disk file
+#[derive(Debug)]
+pub struct DiskArtifact {
+    pub uri: Url,
+    /// Contract declarations, searchable like an indexed document's symbols
+    pub symbols: SymbolIndex,
+    /// Ranges of send channels (`name!(…)`), by channel name
+    pub calls: HashMap<String, Vec<Range>>,
+}
+
+impl DiskArtifact {
+    /// Parses `text` and extracts contract declarations and send sites, with
+    /// columns in `encoding`.
+    pub fn from_text(uri: &Url, text: &str, encoding: PositionEncoding) -> Self {
+        let tree = parse_code(text);
+        let rope = Rope::from_str(text);
+        let ir = parse_to_document_ir(&tree, &rope).root.clone();
+        let positions = compute_absolute_positions(&ir);
+        Self::from_ir(uri, &ir, &positions, &rope, &LineIndex::new(text).with_encoding(encoding))
+    }
+
+    /// Extracts contract declarations and send sites from an already parsed
+    /// document.
+    pub fn from_ir(
+        uri: &Url,
+        ir: &Arc<RholangNode>,
+        positions: &HashMap<usize, (IrPosition, IrPosition)>,
+        text: &Rope,
+        line_index: &LineIndex,
+    ) -> Self {
+        let range_of = |node: &Arc<RholangNode>| {
+            positions.get(&(Arc::as_ptr(node) as usize)).map(|span| lsp_range(text, line_index, span))
+        };
+
+        let mut contracts = Vec::new();
+        collect_contracts(ir, &mut contracts);
+        let symbols = contracts
+            .iter()
+            .filter_map(|contract| {
+                let RholangNode::Contract { name, .. } = &**contract else { return None };
+                #[allow(deprecated)]
+                Some(SymbolInformation {
+                    name: node_name(name)?,
+                    kind: SymbolKind::FUNCTION,
+                    location: Location { uri: uri.clone(), range: range_of(name)? },
+                    container_name: None,
+                    tags: None,
+                    deprecated: None,
+                })
+            })
+            .collect();
+
+        let mut sends = Vec::new();
+        collect_calls(ir, &mut sends);
+        let mut calls: HashMap<String, Vec<Range>> = HashMap::new();
+        for send in &sends {
+            let (RholangNode::Send { channel, .. } | RholangNode::SendSync { channel, .. }) = &**send else {
+                continue;
+            };
+            if let (Some(name), Some(range)) = (node_name(channel), range_of(channel)) {
+                calls.entry(name).or_default().push(range);
+            }
+        }
+
+        DiskArtifact { uri: uri.clone(), symbols: SymbolIndex::new(symbols), calls }
+    }
+
+    /// Sends to `name` in this file.
+    pub fn references(&self, name: &str) -> Vec<Location> {
+        self.calls
+            .get(name)
+            .map(|ranges| {
+                ranges
+                    .iter()
+                    .map(|range| Location { uri: self.uri.clone(), range: *range })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// File metadata used to detect stale artifacts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct FileStamp {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+impl FileStamp {
+    /// Stamp of the file at `path`, or `None` if it cannot be read.
+    pub fn of(path: &Path) -> Option<Self> {
+        let metadata = std::fs::metadata(path).ok()?;
+        Some(FileStamp { modified: metadata.modified().ok(), len: metadata.len() })
+    }
+}
+
+#[derive(Debug)]
+struct Entry {
+    artifact: Arc<DiskArtifact>,
+    stamp: FileStamp,
+    last_used: u64,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    by_path: HashMap<PathBuf, Entry>,
+    /// Artifacts standing in for indexed documents, never evicted
+    pinned: HashMap<PathBuf, Entry>,
+    clock: u64,
+    /// Encoding of the columns of loaded artifacts
+    encoding: PositionEncoding,
+}
+
+/// LRU cache of [`DiskArtifact`]s
+#[derive(Debug)]
+pub struct DiskIndex {
+    capacity: usize,
+    entries: Mutex<Entries>,
+}
+
+impl Default for DiskIndex {
+    fn default() -> Self {
+        Self::new(DEFAULT_DISK_INDEX_CAPACITY)
+    }
+}
+
+impl DiskIndex {
+    /// Creates an index holding at most `capacity` artifacts (at least one).
+    pub fn new(capacity: usize) -> Self {
+        DiskIndex { capacity: capacity.max(1), entries: Mutex::new(Entries::default()) }
+    }
+
+    /// Loads later artifacts with columns in `encoding`, the one negotiated
+    /// with the client; cached artifacts in another encoding are dropped.
+    pub fn set_encoding(&self, encoding: PositionEncoding) {
+        let mut entries = self.entries.lock();
+        if entries.encoding != encoding {
+            entries.by_path.clear();
+            entries.encoding = encoding;
+        }
+    }
+
+    /// Returns the artifact for `path`, loading it if it is missing or stale.
+    ///
+    /// Returns `None` if the file cannot be read. Parsing happens outside the
+    /// lock, so concurrent loads of different files do not block each other.
+    pub fn load(&self, path: &Path) -> Option<Arc<DiskArtifact>> {
+        let stamp = FileStamp::of(path)?;
+        let encoding = {
+            let mut entries = self.entries.lock();
+            entries.clock += 1;
+            let now = entries.clock;
+            if let Some(entry) = entries.pinned.get(path).filter(|entry| entry.stamp == stamp) {
+                trace!("Disk index hit for pinned {}", path.display());
+                return Some(entry.artifact.clone());
+            }
+            if let Some(entry) = entries.by_path.get_mut(path) {
+                if entry.stamp == stamp {
+                    entry.last_used = now;
+                    trace!("Disk index hit for {}", path.display());
+                    return Some(entry.artifact.clone());
+                }
+            }
+            entries.encoding
+        };
+
+        let uri = Url::from_file_path(path).ok()?;
+        let text = std::fs::read_to_string(path).ok()?;
+        let artifact = Arc::new(DiskArtifact::from_text(&uri, &text, encoding));
+        debug!("Loaded index-only artifact for {} ({} contracts)", uri, artifact.symbols.len());
+
+        let mut entries = self.entries.lock();
+        entries.clock += 1;
+        let now = entries.clock;
+        entries.pinned.remove(path);
+        entries.by_path.insert(
+            path.to_path_buf(),
+            Entry { artifact: artifact.clone(), stamp, last_used: now },
+        );
+        while entries.by_path.len() > self.capacity {
+            let Some(oldest) = entries
+                .by_path
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(path, _)| path.clone())
+            else {
+                break;
+            };
+            trace!("Evicting {} from disk index", oldest.display());
+            entries.by_path.remove(&oldest);
+        }
+        Some(artifact)
+    }
+
+    /// Keeps `artifact`, built from the file at `path` as of `stamp`, until
+    /// the file changes; it is not counted against the capacity.
+    pub fn pin(&self, path: &Path, artifact: Arc<DiskArtifact>, stamp: FileStamp) {
+        self.entries.lock().pinned.insert(path.to_path_buf(), Entry { artifact, stamp, last_used: 0 });
+    }
+
+    /// Drops the artifact for `path`, if any.
+    pub fn invalidate(&self, path: &Path) {
+        let mut entries = self.entries.lock();
+        entries.by_path.remove(path);
+        entries.pinned.remove(path);
+    }
+
+    /// Number of artifacts in memory
+    pub fn len(&self) -> usize {
+        let entries = self.entries.lock();
+        entries.by_path.len() + entries.pinned.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp::lsp_types::Position as LspPosition;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rholang-disk-index-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_artifact_contracts_and_calls() {
+        let uri = Url::parse("file:///vault.rho").unwrap();
+        let source = "contract transfer(@amount, ret) = { ret!(amount) }\n| transfer!(10, *ack)\n| @\"transfer\"!(5, *ack)";
+        let artifact = DiskArtifact::from_text(&uri, source, PositionEncoding::Utf16);
+
+        let symbols = artifact.symbols.search("trans");
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols[0].name, "transfer");
+        assert_eq!(symbols[0].location.range.start, LspPosition { line: 0, character: 9 });
+
+        let references = artifact.references("transfer");
+        assert_eq!(references.len(), 2);
+        assert_eq!(references[0].range.start, LspPosition { line: 1, character: 2 });
+        assert_eq!(references[1].range.start.line, 2);
+        assert!(artifact.references("missing").is_empty());
+    }
+
+    #[test]
+    fn test_artifact_ranges_count_utf16_units() {
+        let uri = Url::parse("file:///vault.rho").unwrap();
+        let source = r#"contract transfer(ret) = { ret!("é") } | transfer!(1)"#;
+        let artifact = DiskArtifact::from_text(&uri, source, PositionEncoding::Utf16);
+        let references = artifact.references("transfer");
+        assert_eq!(references.len(), 1);
+        assert_eq!(references[0].range.start, LspPosition { line: 0, character: 41 });
+        assert_eq!(references[0].range.end, LspPosition { line: 0, character: 49 });
+
+        // "é" is two bytes for a client that negotiated UTF-8
+        let artifact = DiskArtifact::from_text(&uri, source, PositionEncoding::Utf8);
+        assert_eq!(artifact.references("transfer")[0].range.start, LspPosition { line: 0, character: 42 });
+    }
+
+    #[test]
+    fn test_lru_eviction_and_reload() {
+        let dir = temp_dir("lru");
+        let paths: Vec<PathBuf> = (0..3)
+            .map(|i| {
+                let path = dir.join(format!("f{}.rho", i));
+                std::fs::write(&path, format!("contract c{}() = {{ Nil }}", i)).unwrap();
+                path
+            })
+            .collect();
+
+        let index = DiskIndex::new(2);
+        let first = index.load(&paths[0]).unwrap();
+        index.load(&paths[1]).unwrap();
+        // Touch f0 so that f1 is the least recently used
+        assert!(Arc::ptr_eq(&first, &index.load(&paths[0]).unwrap()));
+        index.load(&paths[2]).unwrap();
+        assert_eq!(index.len(), 2);
+        assert!(Arc::ptr_eq(&first, &index.load(&paths[0]).unwrap()), "f0 should still be cached");
+
+        // A changed file is reloaded
+        std::fs::write(&paths[0], "contract renamed() = { Nil } | contract other() = { Nil }").unwrap();
+        let reloaded = index.load(&paths[0]).unwrap();
+        assert_eq!(reloaded.symbols.search("renamed").len(), 1);
+
+        index.invalidate(&paths[0]);
+        assert_eq!(index.len(), 1);
+        assert!(index.load(&dir.join("missing.rho")).is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_pinned_artifacts() {
+        let dir = temp_dir("pinned");
+        let path = dir.join("pinned.rho");
+        std::fs::write(&path, "contract kept() = { Nil }").unwrap();
+        let uri = Url::from_file_path(&path).unwrap();
+
+        let index = DiskIndex::new(1);
+        let pinned = Arc::new(DiskArtifact::from_text(&uri, "contract kept() = { Nil }", PositionEncoding::Utf16));
+        index.pin(&path, pinned.clone(), FileStamp::of(&path).unwrap());
+        let other = dir.join("other.rho");
+        std::fs::write(&other, "contract other() = { Nil }").unwrap();
+        index.load(&other).unwrap();
+        assert!(Arc::ptr_eq(&pinned, &index.load(&path).unwrap()), "pinned artifacts are not evicted");
+
+        // Changing the file replaces the pinned artifact
+        std::fs::write(&path, "contract changed() = { Nil } | Nil").unwrap();
+        assert_eq!(index.load(&path).unwrap().symbols.search("changed").len(), 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -4,17 +4,75 @@ use ropey::Rope;
 
 use tower_lsp::lsp_types::{Position, TextDocumentContentChangeEvent, Url};
 
-use tree_sitter::Tree;
+use tree_sitter::{InputEdit, Point, Tree};
 
-use crate::tree_sitter::{parse_code, update_tree};
+use crate::lsp::document_resync::{self, DesyncReason};
+use crate::lsp::line_index::{LineIndex, PositionEncoding};
+use crate::lsp::text_normalization::{normalize, normalize_inserted, TextFormat};
+use crate::parsers::rholang::DirtyRanges;
+use crate::tree_sitter::{parse_code, update_tree, ParseKind};
 
-pub use crate::lsp::models::{LspDocument, LspDocumentState, VersionedChanges};
+pub use crate::lsp::models::{ByteEdit, LspDocument, LspDocumentState, ParseCounts, VersionedChanges};
 
-/// Converts an LSP position to a byte offset in the Rope.
-fn position_to_byte_offset(position: &Position, text: &Rope) -> usize {
-    let line = position.line as usize;
-    let char = position.character as usize;
-    text.line_to_char(line) + char
+/// A content change resolved against the current rope.
+#[derive(Debug, PartialEq, Eq)]
+enum RopeEdit<'a> {
+    /// Replace the characters in `start..end` with `text`
+    Replace { start: usize, end: usize, text: &'a str },
+    /// Replace the whole document (Full sync, or a range-less change)
+    Full(&'a str),
+}
+
+impl<'a> RopeEdit<'a> {
+    /// Converts an LSP change event into a rope edit.
+    ///
+    /// Ranges are clamped to the document: positions past the end of a line
+    /// resolve to the end of that line, and lines past the end of the document
+    /// resolve to the end of the document. Reversed ranges are normalized.
+    fn from_change(change: &'a TextDocumentContentChangeEvent, text: &Rope, line_index: &LineIndex) -> Self {
+        match change.range {
+            Some(range) => {
+                let start = position_to_char_index(&range.start, text, line_index);
+                let end = position_to_char_index(&range.end, text, line_index);
+                RopeEdit::Replace {
+                    start: start.min(end),
+                    end: start.max(end),
+                    text: &change.text,
+                }
+            }
+            None => RopeEdit::Full(&change.text),
+        }
+    }
+}
+
+/// Converts an LSP position to a char index in the Rope, clamped to the document.
+fn position_to_char_index(position: &Position, text: &Rope, line_index: &LineIndex) -> usize {
+    line_index
+        .offset(position.line as usize, position.character as usize, text)
+        .map_or(text.len_chars(), |byte| text.byte_to_char(byte))
+}
+
+/// Applies content changes to `text` without maintaining a syntax tree.
+///
+/// Ranges, with columns in `encoding`, are resolved and clamped exactly as in
+/// `LspDocumentState::apply`.
+pub fn apply_text_changes(text: &mut Rope, changes: &[TextDocumentContentChangeEvent], encoding: PositionEncoding) {
+    let mut line_index = LineIndex::from_rope(text).with_encoding(encoding);
+    for change in changes {
+        match RopeEdit::from_change(change, text, &line_index) {
+            RopeEdit::Replace { start, end, text: new_text } => {
+                let start_byte = text.char_to_byte(start);
+                let old_end_byte = text.char_to_byte(end);
+                text.remove(start..end);
+                text.insert(start, new_text);
+                line_index.apply_edit(text, start_byte, old_end_byte, new_text.len());
+            }
+            RopeEdit::Full(new_text) => {
+                *text = Rope::from_str(new_text);
+                line_index = LineIndex::from_rope(text).with_encoding(encoding);
+            }
+        }
+    }
 }
 
 impl PartialEq for VersionedChanges {
@@ -39,6 +97,11 @@ impl Ord for VersionedChanges {
 
 impl LspDocumentState {
     /// Applies a list of content changes to the document state, updating the text and syntax tree incrementally.
+    /// Ranged (incremental) and whole-document (full) changes may be mixed in a single batch.
+    ///
+    /// Each ranged change edits the tree kept from the previous call and
+    /// reparses only what changed; the tree is parsed from scratch only for a
+    /// whole-document change or when there is no previous tree.
     /// Returns the updated text and tree if the version is newer, otherwise an error.
     pub fn apply(
         &mut self,
@@ -48,25 +111,74 @@ impl LspDocumentState {
         if version <= self.version {
             return Err(format!("Version {} not newer than {}", version, self.version));
         }
-        let mut tree = parse_code(&self.text.to_string());
+        let mut edits = Some(Vec::with_capacity(changes.len()));
+        let mut dirty = DirtyRanges::default();
+        let mut tree = match self.tree.take() {
+            Some(tree) => tree,
+            None => {
+                self.parse_counts.record(ParseKind::Full);
+                dirty.mark_everything();
+                parse_code(&self.text.to_string())
+            }
+        };
         for change in &changes {
-            if let Some(range) = change.range {
-                let start = position_to_byte_offset(&range.start, &self.text);
-                let end = position_to_byte_offset(&range.end, &self.text);
-                self.text.remove(start..end);
-                self.text.insert(start, &change.text);
-                tree = update_tree(&tree, &self.text.to_string(), start, end, change.text.len());
-            } else {
-                self.text = Rope::from_str(&change.text);
-                tree = parse_code(&self.text.to_string());
+            match RopeEdit::from_change(change, &self.text, &self.line_index) {
+                RopeEdit::Replace { start, end, text } => {
+                    let followed_by_lf = end < self.text.len_chars() && self.text.char(end) == '\n';
+                    let normalized = normalize_inserted(text, followed_by_lf);
+                    let text = normalized.as_ref();
+                    // Byte offsets and points must be taken from the text before the edit
+                    let start_byte = self.text.char_to_byte(start);
+                    let old_end_byte = self.text.char_to_byte(end);
+                    let start_position = byte_to_point(&self.line_index, start_byte);
+                    let old_end_position = byte_to_point(&self.line_index, old_end_byte);
+                    self.text.remove(start..end);
+                    self.text.insert(start, text);
+                    self.line_index.apply_edit(&self.text, start_byte, old_end_byte, text.len());
+
+                    let new_end_byte = start_byte + text.len();
+                    let edit = InputEdit {
+                        start_byte,
+                        old_end_byte,
+                        new_end_byte,
+                        start_position,
+                        old_end_position,
+                        new_end_position: byte_to_point(&self.line_index, new_end_byte),
+                    };
+                    let (new_tree, kind) = update_tree(&tree, &self.text.to_string(), &edit, &mut dirty);
+                    self.parse_counts.record(kind);
+                    tree = new_tree;
+                    if let Some(edits) = edits.as_mut() {
+                        edits.push(ByteEdit { start: start_byte, old_end: old_end_byte, text: text.to_string() });
+                    }
+                }
+                RopeEdit::Full(text) => {
+                    self.format = TextFormat::detect(text);
+                    self.text = Rope::from_str(&normalize(text));
+                    self.line_index = LineIndex::from_rope(&self.text).with_encoding(self.line_index.encoding());
+                    self.parse_counts.record(ParseKind::Full);
+                    tree = parse_code(&self.text.to_string());
+                    edits = None;
+                    dirty.mark_everything();
+                }
             }
         }
+        self.tree = Some(tree.clone());
+        self.last_edits = edits;
+        self.dirty = Some((self.version, dirty));
         self.history.changes.push(VersionedChanges { version, changes });
         self.version = version;
         Ok((self.text.to_string(), tree))
     }
 }
 
+/// Converts a byte offset to a Tree-Sitter point (row, byte column) with the
+/// line index of the text containing it.
+fn byte_to_point(line_index: &LineIndex, byte: usize) -> Point {
+    let row = line_index.line_of_byte(byte);
+    Point { row, column: byte - line_index.line_start(row).unwrap_or(0) }
+}
+
 impl LspDocument {
     /// Returns the URI of the document.
     pub async fn uri(&self) -> Url {
@@ -83,6 +195,21 @@ impl LspDocument {
         self.state.read().await.version
     }
 
+    /// Byte edits made by the last change, or `None` if it replaced the whole document.
+    pub async fn last_edits(&self) -> Option<Vec<ByteEdit>> {
+        self.state.read().await.last_edits.clone()
+    }
+
+    /// The version the change to `version` was applied to and the bytes it
+    /// touched, if `version` is the current version.
+    pub async fn dirty_ranges(&self, version: i32) -> Option<(i32, DirtyRanges)> {
+        let state = self.state.read().await;
+        if state.version != version {
+            return None;
+        }
+        state.dirty.clone()
+    }
+
     /// Returns the number of lines in the document.
     pub async fn num_lines(&self) -> usize {
         self.state.read().await.text.len_lines()
@@ -114,6 +241,13 @@ impl LspDocument {
         (last_line, last_column)
     }
 
+    /// Why `changes` for `version` do not fit the current text, if they do not
+    /// (see `crate::lsp::document_resync`).
+    pub async fn find_desync(&self, changes: &[TextDocumentContentChangeEvent], version: i32) -> Option<DesyncReason> {
+        let state = self.state.read().await;
+        document_resync::find_desync(&state.text, &state.line_index, state.version, version, changes)
+    }
+
     /// Applies changes to the document, updating text and tree.
     pub async fn apply(
         &self,
@@ -140,11 +274,17 @@ mod tests {
             state: RwLock::new(LspDocumentState {
                 uri: Url::parse(uri).unwrap(),
                 text: Rope::from_str(text),
+                line_index: LineIndex::new(text),
+                tree: None,
+                parse_counts: ParseCounts::default(),
                 version: 0,
                 history: LspDocumentHistory {
                     text: text.to_string(),
                     changes: vec![],
                 },
+                last_edits: None,
+                dirty: None,
+                format: TextFormat::default(),
             }),
         })
     }
@@ -231,4 +371,191 @@ mod tests {
         assert_eq!(doc.text().await, "new text", "Text should remain from previous change");
         assert_eq!(doc.version().await, 1, "Version should not change");
     }
+
+    fn ranged(start: (u32, u32), end: (u32, u32), text: &str) -> TextDocumentContentChangeEvent {
+        TextDocumentContentChangeEvent {
+            range: Some(Range {
+                start: Position { line: start.0, character: start.1 },
+                end: Position { line: end.0, character: end.1 },
+            }),
+            range_length: None,
+            text: text.to_string(),
+        }
+    }
+
+    fn full(text: &str) -> TextDocumentContentChangeEvent {
+        TextDocumentContentChangeEvent {
+            range: None,
+            range_length: None,
+            text: text.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_apply_mixed_full_and_incremental() {
+        // A client may send a full replacement followed by ranged edits in one batch
+        let doc = create_test_document("file:///test.rho", "old contents");
+        let changes = vec![
+            full("new Nil in {\n  Nil\n}"),
+            ranged((1, 2), (1, 5), "x!(1)"),
+        ];
+
+        let result = doc.apply(changes, 1).await.map(|(text, _)| text);
+        assert_eq!(result.unwrap(), "new Nil in {\n  x!(1)\n}");
+
+        // Followed by a full-sync-only client replacing the document again
+        let result = doc.apply(vec![full("Nil")], 2).await.map(|(text, tree)| (text, tree.root_node().has_error()));
+        assert_eq!(result, Some(("Nil".to_string(), false)));
+    }
+
+    #[tokio::test]
+    async fn test_apply_multiline_incremental() {
+        let doc = create_test_document("file:///test.rho", "line one\nline two\nline three");
+        let result = doc.apply(vec![ranged((0, 5), (2, 4), "1\nline")], 1).await.map(|(text, _)| text);
+        assert_eq!(result.unwrap(), "line 1\nline three");
+    }
+
+    #[tokio::test]
+    async fn test_apply_clamps_out_of_range_positions() {
+        // Columns past the end of a line clamp to the line end (before "\r\n"),
+        // lines past the end of the document clamp to the document end
+        let doc = create_test_document("file:///test.rho", "ab\r\ncd");
+        let result = doc.apply(vec![ranged((0, 99), (0, 99), "!")], 1).await.map(|(text, _)| text);
+        assert_eq!(result.unwrap(), "ab!\r\ncd");
+
+        let result = doc.apply(vec![ranged((10, 0), (10, 0), "?")], 2).await.map(|(text, _)| text);
+        assert_eq!(result.unwrap(), "ab!\r\ncd?");
+    }
+
+    #[tokio::test]
+    async fn test_apply_normalizes_bom_and_cr_line_endings() {
+        let doc = create_test_document("file:///test.rho", "Nil");
+        let result = doc.apply(vec![full("\u{feff}new x in {\r  x!(1)\r}")], 1).await;
+        let (text, tree) = result.unwrap();
+        assert_eq!(text, "new x in {\n  x!(1)\n}");
+        assert_eq!(tree.root_node().end_position().row, 2);
+        assert!(doc.state.read().await.format.bom);
+
+        // The client types a new line the way it sees the document: with `\r`
+        let result = doc.apply(vec![ranged((1, 7), (1, 7), " |\r  Nil")], 2).await;
+        let (text, tree) = result.unwrap();
+        assert_eq!(text, "new x in {\n  x!(1) |\n  Nil\n}");
+        assert!(!tree.root_node().has_error());
+        assert_eq!(tree.root_node().end_position().row, 3);
+    }
+
+    #[test]
+    fn test_apply_text_changes() {
+        let mut text = Rope::from_str("new x in {\n  Nil\n}");
+        let utf16 = PositionEncoding::Utf16;
+        apply_text_changes(&mut text, &[ranged((1, 2), (1, 5), "x!(1)"), ranged((9, 0), (9, 0), " | Nil")], utf16);
+        assert_eq!(text.to_string(), "new x in {\n  x!(1)\n} | Nil");
+
+        apply_text_changes(&mut text, &[full("Nil")], utf16);
+        assert_eq!(text.to_string(), "Nil");
+
+        // Columns of a client that negotiated UTF-8 count the bytes of "é"
+        let mut text = Rope::from_str("x!(\"é\", 1)");
+        apply_text_changes(&mut text, &[ranged((0, 9), (0, 10), "2")], PositionEncoding::Utf8);
+        assert_eq!(text.to_string(), "x!(\"é\", 2)");
+    }
+
+    #[test]
+    fn test_rope_edit_from_change() {
+        let rope = Rope::from_str("hello\nworld");
+        let index = LineIndex::from_rope(&rope);
+        let change = ranged((1, 3), (1, 0), "X");
+        assert_eq!(
+            RopeEdit::from_change(&change, &rope, &index),
+            RopeEdit::Replace { start: 6, end: 9, text: "X" }
+        );
+        let change = full("all");
+        assert_eq!(RopeEdit::from_change(&change, &rope, &index), RopeEdit::Full("all"));
+    }
+
+    /// Every node of `tree` with its kind and byte/point span, in document order.
+    fn node_spans(tree: &Tree) -> Vec<(String, usize, usize, Point, Point)> {
+        fn walk(node: tree_sitter::Node, spans: &mut Vec<(String, usize, usize, Point, Point)>) {
+            spans.push((node.kind().to_string(), node.start_byte(), node.end_byte(), node.start_position(), node.end_position()));
+            let mut cursor = node.walk();
+            for child in node.children(&mut cursor) {
+                walk(child, spans);
+            }
+        }
+        let mut spans = Vec::new();
+        walk(tree.root_node(), &mut spans);
+        spans
+    }
+
+    fn large_source(contracts: usize) -> String {
+        let mut source: String = (0..contracts)
+            .map(|i| format!("contract c{i}(@x, ret) = {{\n  ret!(x + {i})\n}} |\n"))
+            .collect();
+        source.push_str("Nil\n");
+        source
+    }
+
+    #[tokio::test]
+    async fn test_small_edit_in_large_file_reparses_incrementally() {
+        let source = large_source(1000);
+        let original = parse_code(&source);
+        let doc = create_test_document("file:///large.rho", &source);
+        doc.state.write().await.tree = Some(original.clone());
+
+        // "  ret!(x + 500)" -> "  ret!(x)"
+        let (text, tree) = doc.apply(vec![ranged((1501, 7), (1501, 14), "x")], 1).await.unwrap();
+
+        assert_eq!(doc.state.read().await.parse_counts, ParseCounts { incremental: 1, full: 0 });
+        assert_eq!(node_spans(&tree), node_spans(&parse_code(&text)));
+
+        // Only the edited line changed structurally
+        let start_byte = LineIndex::new(&source).line_start(1501).unwrap() + 7;
+        let mut edited = original;
+        edited.edit(&InputEdit {
+            start_byte,
+            old_end_byte: start_byte + 7,
+            new_end_byte: start_byte + 1,
+            start_position: Point { row: 1501, column: 7 },
+            old_end_position: Point { row: 1501, column: 14 },
+            new_end_position: Point { row: 1501, column: 8 },
+        });
+        let changed: Vec<_> = edited.changed_ranges(&tree).collect();
+        assert!(!changed.is_empty());
+        assert!(
+            changed.iter().all(|range| range.start_point.row == 1501 && range.end_point.row == 1501),
+            "changed ranges should be confined to the edited line: {:?}",
+            changed
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tree_is_reused_across_changes() {
+        let doc = create_test_document("file:///reuse.rho", "new x in {\n  x!(1)\n}");
+
+        // Without a previous tree the first change needs one full parse
+        doc.apply(vec![ranged((1, 5), (1, 6), "2")], 1).await.unwrap();
+        assert_eq!(doc.dirty_ranges(1).await, Some((0, DirtyRanges::everything())));
+
+        // Multi-line insertion shifts the rows of everything after it
+        let (text, tree) = doc.apply(vec![ranged((1, 7), (1, 7), " |\n  x!(3) |\n  x!(4)")], 2).await.unwrap();
+        assert_eq!(text, "new x in {\n  x!(2) |\n  x!(3) |\n  x!(4)\n}");
+        assert_eq!(node_spans(&tree), node_spans(&parse_code(&text)));
+
+        // Deleting a whole line, in a batch with a second ranged change
+        let (text, tree) = doc
+            .apply(vec![ranged((2, 0), (3, 0), ""), ranged((2, 5), (2, 6), "5")], 3)
+            .await
+            .unwrap();
+        assert_eq!(text, "new x in {\n  x!(2) |\n  x!(5)\n}");
+        assert_eq!(node_spans(&tree), node_spans(&parse_code(&text)));
+        assert_eq!(doc.state.read().await.parse_counts, ParseCounts { incremental: 4, full: 1 });
+        let (since, dirty) = doc.dirty_ranges(3).await.unwrap();
+        assert_eq!(since, 2);
+        assert!(dirty.is_unchanged(0, 9));
+        assert!(!dirty.is_unchanged(20, 30));
+        assert_eq!(doc.dirty_ranges(2).await, None);
+
+        doc.apply(vec![full("Nil")], 4).await.unwrap();
+        assert_eq!(doc.state.read().await.parse_counts, ParseCounts { incremental: 4, full: 2 });
+    }
 }
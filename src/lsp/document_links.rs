@@ -0,0 +1,215 @@
+//! Document links (`textDocument/documentLink`)
+//!
+//! Two kinds of literal become clickable:
+//!
+//! - **Registry URIs**: `rho:id:*` and `rho:lang:*` URI literals, e.g. the URI
+//!   of `new counter(`rho:id:abc`)` or of a `lookup!` call. They open the
+//!   registry explorer when the `documentLinks.registryExplorerUrl`
+//!   initialization option is set, with `{uri}` replaced by the URI, and
+//!   otherwise the contract bound to the URI in the workspace, if any.
+//! - **File references**: string literals holding a relative path to a
+//!   `.rho` file, `"lib/token.rho"`, resolved against the directory of the
+//!   document; paths to files that do not exist get no link.
+//!
+//! ```json
+//! { "documentLinks": { "enabled": true, "registryExplorerUrl": "https://explorer.example/registry/{uri}" } }
+//! ```
+
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use ropey::Rope;
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{Position as LspPosition, Range};
+
+use crate::ir::rholang_node::{Metadata, NodeBase, RholangNode};
+use crate::ir::visitor::Visitor;
+use crate::lsp::call_hierarchy::{node_range, Positions};
+use crate::lsp::line_index::LineIndex;
+
+/// Initialization option configuring document links
+pub const DOCUMENT_LINKS_OPTION: &str = "documentLinks";
+
+/// Registry URI schemes that get a link
+const REGISTRY_SCHEMES: [&str; 2] = ["rho:id:", "rho:lang:"];
+
+/// The `documentLinks` initialization option
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DocumentLinksConfig {
+    /// Turns all links off when `false`
+    pub enabled: bool,
+    /// Explorer URL of a registry URI, with `{uri}` standing for the URI
+    pub registry_explorer_url: Option<String>,
+}
+
+impl Default for DocumentLinksConfig {
+    fn default() -> Self {
+        DocumentLinksConfig { enabled: true, registry_explorer_url: None }
+    }
+}
+
+impl DocumentLinksConfig {
+    /// Reads the `documentLinks` initialization option; missing fields keep their defaults.
+    pub fn from_init_options(init_options: Option<&serde_json::Value>) -> Self {
+        init_options
+            .and_then(|opts| opts.get(DOCUMENT_LINKS_OPTION))
+            .and_then(|config| serde_json::from_value(config.clone()).ok())
+            .unwrap_or_default()
+    }
+
+    /// The explorer URL of `registry_uri`, if an explorer is configured.
+    pub fn explorer_url(&self, registry_uri: &str) -> Option<String> {
+        self.registry_explorer_url
+            .as_ref()
+            .map(|template| template.replace("{uri}", registry_uri))
+    }
+}
+
+/// What a linked literal refers to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkTarget {
+    /// A `rho:id:*` or `rho:lang:*` registry URI
+    Registry(String),
+    /// A relative path to a `.rho` file, as written
+    File(String),
+}
+
+/// A linked literal: the text between its quotes and its target
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiteralLink {
+    pub range: Range,
+    pub target: LinkTarget,
+}
+
+/// Whether `value` is a registry URI that gets a link.
+pub fn is_linked_registry_uri(value: &str) -> bool {
+    REGISTRY_SCHEMES
+        .iter()
+        .any(|scheme| value.len() > scheme.len() && value.starts_with(scheme))
+}
+
+/// Whether `value` is a relative path to a `.rho` file.
+pub fn is_relative_rho_path(value: &str) -> bool {
+    !value.contains("://")
+        && !value.contains(char::is_whitespace)
+        && value.len() > ".rho".len()
+        && value.ends_with(".rho")
+        && Path::new(value).is_relative()
+}
+
+/// The path `relative` refers to from a document in `document_dir`, if that file exists.
+pub fn resolve_relative_path(document_dir: &Path, relative: &str) -> Option<PathBuf> {
+    let path = document_dir.join(relative);
+    path.is_file().then_some(path)
+}
+
+/// The linked literals of a document, in document order.
+pub fn literal_links(ir: &Arc<RholangNode>, positions: &Positions, text: &Rope, line_index: &LineIndex) -> Vec<LiteralLink> {
+    let collector = LinkCollector { positions, text, line_index, links: RefCell::new(Vec::new()) };
+    collector.visit_node(ir);
+    collector.links.into_inner()
+}
+
+/// `range` without the quotes around a single-line literal.
+fn inside_quotes(range: Range) -> Range {
+    if range.start.line != range.end.line || range.end.character < range.start.character + 2 {
+        return range;
+    }
+    Range {
+        start: LspPosition::new(range.start.line, range.start.character + 1),
+        end: LspPosition::new(range.end.line, range.end.character - 1),
+    }
+}
+
+struct LinkCollector<'a> {
+    positions: &'a Positions,
+    text: &'a Rope,
+    line_index: &'a LineIndex,
+    links: RefCell<Vec<LiteralLink>>,
+}
+
+impl LinkCollector<'_> {
+    fn push(&self, node: &Arc<RholangNode>, target: LinkTarget) {
+        if let Some(range) = node_range(node, self.positions, self.text, self.line_index) {
+            self.links.borrow_mut().push(LiteralLink { range: inside_quotes(range), target });
+        }
+    }
+}
+
+impl Visitor for LinkCollector<'_> {
+    fn visit_uri_literal(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        value: &String,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        if is_linked_registry_uri(value) {
+            self.push(node, LinkTarget::Registry(value.clone()));
+        }
+        Arc::clone(node)
+    }
+
+    fn visit_string_literal(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        value: &String,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        if is_relative_rho_path(value) {
+            self.push(node, LinkTarget::File(value.clone()));
+        }
+        Arc::clone(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree_sitter::parse_with_positions;
+    use serde_json::json;
+
+    fn links(source: &str) -> Vec<(String, LinkTarget)> {
+        let rope = Rope::from_str(source);
+        let (ir, positions) = parse_with_positions(source);
+        let index = LineIndex::from_rope(&rope);
+        literal_links(&ir, &positions, &rope, &index)
+            .into_iter()
+            .map(|link| {
+                let start = index.offset(link.range.start.line as usize, link.range.start.character as usize, &rope).unwrap();
+                let end = index.offset(link.range.end.line as usize, link.range.end.character as usize, &rope).unwrap();
+                (source[start..end].to_string(), link.target)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_registry_uris_and_rho_paths_are_linked() {
+        let source = "new counter(`rho:id:abc`), stdout(`rho:io:stdout`), lookup(`rho:registry:lookup`) in {\n  \
+                      lookup!(`rho:lang:treeHashMap`, *counter) |\n  \
+                      stdout!(\"lib/token.rho\") |\n  \
+                      stdout!(\"hello.rho world\") |\n  \
+                      stdout!(\"/abs/token.rho\")\n}";
+        assert_eq!(
+            links(source),
+            vec![
+                ("rho:id:abc".to_string(), LinkTarget::Registry("rho:id:abc".to_string())),
+                ("rho:lang:treeHashMap".to_string(), LinkTarget::Registry("rho:lang:treeHashMap".to_string())),
+                ("lib/token.rho".to_string(), LinkTarget::File("lib/token.rho".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_config_from_init_options() {
+        let config = DocumentLinksConfig::from_init_options(Some(&json!({
+            "documentLinks": { "registryExplorerUrl": "https://explorer.example/{uri}" }
+        })));
+        assert!(config.enabled);
+        assert_eq!(config.explorer_url("rho:id:abc").as_deref(), Some("https://explorer.example/rho:id:abc"));
+        assert_eq!(DocumentLinksConfig::from_init_options(None).explorer_url("rho:id:abc"), None);
+    }
+}
@@ -0,0 +1,214 @@
+//! Recovery from a document text out of sync with the client
+//!
+//! Ranged `didChange` edits only make sense against the exact text the
+//! client edited. After a missed notification the server's text differs, and
+//! applying further edits to it (clamping their ranges) silently corrupts it,
+//! along with every diagnostic computed from it. Before a change is applied,
+//! [`find_desync`] looks for the signs of such a divergence:
+//!
+//! - a **version gap**: ranged changes for a version more than one past the
+//!   document's (clients number their changes consecutively, so a change in
+//!   between was lost);
+//! - a **range out of bounds**: a ranged change starting or ending on a line
+//!   the document does not have. Characters past the end of a line are not a
+//!   sign: the specification clamps them to the line length.
+//!
+//! A desynced document is not edited further. Its diagnostics are cleared,
+//! and clients that set the `documentResync` initialization option are asked
+//! for the full text with a `rholang/resyncDocument` request:
+//!
+//! ```json
+//! { "uri": "file:///path/to/file.rho", "version": 12,
+//!   "reason": { "kind": "versionGap", "expected": 10, "received": 12 } }
+//! ```
+//!
+//! answered with the client's current text and version:
+//!
+//! ```json
+//! { "version": 12, "text": "new x in { x!(1) }" }
+//! ```
+//!
+//! Other clients are warned once; ranged changes are ignored until a change
+//! replacing the whole document, or reopening it, brings the text back.
+
+use std::fmt;
+
+use ropey::Rope;
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::request::Request;
+use tower_lsp::lsp_types::{Position, TextDocumentContentChangeEvent, Url};
+
+use crate::lsp::line_index::LineIndex;
+
+/// Initialization option declaring support for `rholang/resyncDocument`
+pub const DOCUMENT_RESYNC_OPTION: &str = "documentResync";
+
+/// Whether the client answers `rholang/resyncDocument` requests.
+pub fn enabled_in(init_options: Option<&serde_json::Value>) -> bool {
+    init_options
+        .and_then(|opts| opts.get(DOCUMENT_RESYNC_OPTION))
+        .and_then(|enabled| enabled.as_bool())
+        .unwrap_or(false)
+}
+
+/// Why a document's text is considered out of sync with the client
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum DesyncReason {
+    /// Ranged changes skipped one or more versions
+    #[serde(rename_all = "camelCase")]
+    VersionGap { expected: i32, received: i32 },
+    /// A ranged change refers to a line past the end of the document
+    #[serde(rename_all = "camelCase")]
+    RangeOutOfBounds { line: u32, line_count: usize },
+    /// The document was already out of sync and no full text has arrived since
+    AwaitingResync,
+}
+
+impl fmt::Display for DesyncReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DesyncReason::VersionGap { expected, received } => {
+                write!(f, "expected version {}, received {}", expected, received)
+            }
+            DesyncReason::RangeOutOfBounds { line, line_count } => {
+                write!(f, "edit on line {} of a document of {} line(s)", line, line_count)
+            }
+            DesyncReason::AwaitingResync => write!(f, "waiting for the full text"),
+        }
+    }
+}
+
+/// Parameters of `rholang/resyncDocument`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResyncDocumentParams {
+    pub uri: Url,
+    /// Version of the change that revealed the divergence
+    pub version: i32,
+    pub reason: DesyncReason,
+}
+
+/// Response of `rholang/resyncDocument`: the client's current document
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResyncDocumentResult {
+    pub version: i32,
+    pub text: String,
+}
+
+/// `rholang/resyncDocument`, sent from the server to the client
+pub enum ResyncDocument {}
+
+impl Request for ResyncDocument {
+    type Params = ResyncDocumentParams;
+    type Result = ResyncDocumentResult;
+    const METHOD: &'static str = "rholang/resyncDocument";
+}
+
+/// The reason `changes` for `version` cannot be applied to `text`, at
+/// `current_version`, without corrupting it; `None` if they fit.
+/// `line_index` is the index of `text`.
+///
+/// A change replacing the whole document always fits. Later ranged changes
+/// of a batch are checked against the text left by the earlier ones.
+pub fn find_desync(
+    text: &Rope,
+    line_index: &LineIndex,
+    current_version: i32,
+    version: i32,
+    changes: &[TextDocumentContentChangeEvent],
+) -> Option<DesyncReason> {
+    let first_ranged = changes.iter().position(|change| change.range.is_some())?;
+    let resets = changes[..first_ranged].iter().any(|change| change.range.is_none());
+    if !resets && version > current_version.saturating_add(1) {
+        return Some(DesyncReason::VersionGap { expected: current_version + 1, received: version });
+    }
+
+    let mut text = text.clone();
+    let mut line_index = line_index.clone();
+    for change in changes {
+        let Some(range) = change.range else {
+            text = Rope::from_str(&change.text);
+            line_index = LineIndex::from_rope(&text).with_encoding(line_index.encoding());
+            continue;
+        };
+        let line_count = text.len_lines();
+        let out_of_bounds = [range.start, range.end].into_iter().find(|position| !within(position, line_count));
+        if let Some(position) = out_of_bounds {
+            return Some(DesyncReason::RangeOutOfBounds { line: position.line, line_count });
+        }
+
+        let start = char_index(&range.start, &text, &line_index);
+        let end = char_index(&range.end, &text, &line_index);
+        let (start, end) = (start.min(end), start.max(end));
+        let start_byte = text.char_to_byte(start);
+        let old_end_byte = text.char_to_byte(end);
+        text.remove(start..end);
+        text.insert(start, &change.text);
+        line_index.apply_edit(&text, start_byte, old_end_byte, change.text.len());
+    }
+    None
+}
+
+/// Whether `position` is on a line of a text of `line_count` lines; the
+/// start of the line after the last one is accepted as the end of the text.
+fn within(position: &Position, line_count: usize) -> bool {
+    let line = position.line as usize;
+    line < line_count || (line == line_count && position.character == 0)
+}
+
+fn char_index(position: &Position, text: &Rope, line_index: &LineIndex) -> usize {
+    line_index
+        .offset(position.line as usize, position.character as usize, text)
+        .map_or(text.len_chars(), |byte| text.byte_to_char(byte))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp::lsp_types::Range;
+
+    fn ranged(start: (u32, u32), end: (u32, u32), text: &str) -> TextDocumentContentChangeEvent {
+        TextDocumentContentChangeEvent {
+            range: Some(Range::new(Position::new(start.0, start.1), Position::new(end.0, end.1))),
+            range_length: None,
+            text: text.to_string(),
+        }
+    }
+
+    fn full(text: &str) -> TextDocumentContentChangeEvent {
+        TextDocumentContentChangeEvent { range: None, range_length: None, text: text.to_string() }
+    }
+
+    #[test]
+    fn test_version_gaps() {
+        let text = Rope::from_str("new x in {\n  x!(1)\n}");
+        let index = LineIndex::from_rope(&text);
+        assert_eq!(find_desync(&text, &index, 3, 4, &[ranged((1, 5), (1, 6), "2")]), None);
+        assert_eq!(
+            find_desync(&text, &index, 3, 6, &[ranged((1, 5), (1, 6), "2")]),
+            Some(DesyncReason::VersionGap { expected: 4, received: 6 })
+        );
+        // The whole text resets the document, whatever the version
+        assert_eq!(find_desync(&text, &index, 3, 6, &[full("Nil"), ranged((0, 0), (0, 0), "{")]), None);
+    }
+
+    #[test]
+    fn test_ranges_out_of_bounds() {
+        let text = Rope::from_str("new x in {\n  x!(1)\n}");
+        let index = LineIndex::from_rope(&text);
+        // Past the end of a line is clamped by the specification; the line after the last is the end
+        assert_eq!(find_desync(&text, &index, 0, 1, &[ranged((1, 40), (1, 40), "!")]), None);
+        assert_eq!(find_desync(&text, &index, 0, 1, &[ranged((3, 0), (3, 0), "\n")]), None);
+        assert_eq!(
+            find_desync(&text, &index, 0, 1, &[ranged((7, 0), (7, 2), "")]),
+            Some(DesyncReason::RangeOutOfBounds { line: 7, line_count: 3 })
+        );
+        // Checked against the text left by the earlier changes of the batch
+        assert_eq!(
+            find_desync(&text, &index, 0, 1, &[ranged((1, 0), (2, 1), ""), ranged((2, 1), (2, 1), "}")]),
+            Some(DesyncReason::RangeOutOfBounds { line: 2, line_count: 2 })
+        );
+    }
+}
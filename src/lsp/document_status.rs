@@ -0,0 +1,162 @@
+//! Parse health notifications for status bar indicators
+//!
+//! Clients that set the `documentStatus` initialization option get a
+//! `rholang/documentStatus` notification after each analysis pass of a
+//! document, so an editor extension can show a traffic light in its status
+//! bar:
+//!
+//! ```json
+//! { "uri": "file:///path/to/file.rho", "version": 7,
+//!   "status": "syntaxErrors", "errors": 2, "warnings": 0 }
+//! ```
+//!
+//! | `status`       | Meaning                                                        |
+//! |----------------|----------------------------------------------------------------|
+//! | `ok`           | The document parsed; `errors` counts semantic errors, if any   |
+//! | `syntaxErrors` | The parser rejected the document                               |
+//! | `degraded`     | Validation could not run (validator unreachable or timed out)  |
+//! |                | or ran out of its time budget                                  |
+//! | `verified`     | RNode validated the document and reported no errors            |
+
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::notification::Notification;
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Url};
+
+use crate::lsp::analysis_budget::ANALYSIS_PARTIAL_CODE;
+use crate::lsp::diagnostic_provider::VALIDATOR_UNAVAILABLE_CODE;
+
+/// Initialization option enabling the notification
+pub const DOCUMENT_STATUS_OPTION: &str = "documentStatus";
+
+/// Diagnostic sources reporting syntax errors
+const PARSER_SOURCES: &[&str] = &["rholang-parser", "metta-parser"];
+
+/// Whether the client asked for document status notifications.
+pub fn enabled_in(init_options: Option<&serde_json::Value>) -> bool {
+    init_options
+        .and_then(|opts| opts.get(DOCUMENT_STATUS_OPTION))
+        .and_then(|enabled| enabled.as_bool())
+        .unwrap_or(false)
+}
+
+/// Health of a document after an analysis pass
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DocumentHealth {
+    Ok,
+    SyntaxErrors,
+    Degraded,
+    Verified,
+}
+
+/// Parameters of the `rholang/documentStatus` notification
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct DocumentStatusParams {
+    pub uri: Url,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<i32>,
+    pub status: DocumentHealth,
+    /// Error diagnostics published for the document
+    pub errors: usize,
+    /// Warning diagnostics published for the document
+    pub warnings: usize,
+}
+
+impl DocumentStatusParams {
+    /// Status of a document from the diagnostics of its analysis pass.
+    ///
+    /// `rnode_validated` tells whether the validation backend is RNode itself.
+    pub fn from_diagnostics(uri: Url, version: Option<i32>, diagnostics: &[Diagnostic], rnode_validated: bool) -> Self {
+        let count = |severity| diagnostics.iter().filter(|d| d.severity == Some(severity)).count();
+        let errors = count(DiagnosticSeverity::ERROR);
+        let warnings = count(DiagnosticSeverity::WARNING);
+
+        let unavailable = diagnostics
+            .iter()
+            .any(|d| matches!(&d.code, Some(NumberOrString::String(code))
+                if code == VALIDATOR_UNAVAILABLE_CODE || code == ANALYSIS_PARTIAL_CODE));
+        let syntax_errors = diagnostics.iter().any(|d| {
+            d.severity == Some(DiagnosticSeverity::ERROR)
+                && d.source.as_deref().is_some_and(|source| PARSER_SOURCES.contains(&source))
+        });
+
+        let status = if syntax_errors {
+            DocumentHealth::SyntaxErrors
+        } else if unavailable {
+            DocumentHealth::Degraded
+        } else if rnode_validated && errors == 0 {
+            DocumentHealth::Verified
+        } else {
+            DocumentHealth::Ok
+        };
+        DocumentStatusParams { uri, version, status, errors, warnings }
+    }
+
+    /// Status of a document whose analysis pass did not finish.
+    pub fn degraded(uri: Url, version: Option<i32>) -> Self {
+        DocumentStatusParams { uri, version, status: DocumentHealth::Degraded, errors: 0, warnings: 0 }
+    }
+}
+
+/// `rholang/documentStatus`
+pub enum DocumentStatus {}
+
+impl Notification for DocumentStatus {
+    type Params = DocumentStatusParams;
+    const METHOD: &'static str = "rholang/documentStatus";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn diagnostic(severity: DiagnosticSeverity, source: &str, code: Option<&str>) -> Diagnostic {
+        Diagnostic {
+            severity: Some(severity),
+            source: Some(source.to_string()),
+            code: code.map(|code| NumberOrString::String(code.to_string())),
+            message: "message".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn status(diagnostics: &[Diagnostic], rnode_validated: bool) -> DocumentHealth {
+        let uri = Url::parse("file:///doc.rho").unwrap();
+        DocumentStatusParams::from_diagnostics(uri, Some(1), diagnostics, rnode_validated).status
+    }
+
+    #[test]
+    fn test_classification() {
+        let syntax = diagnostic(DiagnosticSeverity::ERROR, "rholang-parser", None);
+        let semantic = diagnostic(DiagnosticSeverity::ERROR, "rholang-semantic", None);
+        let lint = diagnostic(DiagnosticSeverity::WARNING, "rholang-lint", None);
+        let unavailable = diagnostic(DiagnosticSeverity::ERROR, "rnode-grpc", Some(VALIDATOR_UNAVAILABLE_CODE));
+
+        assert_eq!(status(&[], false), DocumentHealth::Ok);
+        assert_eq!(status(&[semantic.clone(), lint.clone()], false), DocumentHealth::Ok);
+        assert_eq!(status(&[lint.clone(), syntax.clone()], true), DocumentHealth::SyntaxErrors);
+        assert_eq!(status(&[unavailable], true), DocumentHealth::Degraded);
+        let partial = diagnostic(DiagnosticSeverity::INFORMATION, "rholang-analysis", Some(ANALYSIS_PARTIAL_CODE));
+        assert_eq!(status(&[semantic.clone(), partial], true), DocumentHealth::Degraded);
+        assert_eq!(status(&[lint], true), DocumentHealth::Verified);
+        assert_eq!(status(&[semantic], true), DocumentHealth::Ok);
+    }
+
+    #[test]
+    fn test_params_serialization() {
+        let uri = Url::parse("file:///doc.rho").unwrap();
+        let errors = [diagnostic(DiagnosticSeverity::ERROR, "rholang-parser", None)];
+        let params = DocumentStatusParams::from_diagnostics(uri.clone(), Some(7), &errors, false);
+        assert_eq!(
+            serde_json::to_value(&params).unwrap(),
+            json!({ "uri": "file:///doc.rho", "version": 7, "status": "syntaxErrors", "errors": 1, "warnings": 0 })
+        );
+        assert_eq!(
+            serde_json::to_value(DocumentStatusParams::degraded(uri, None)).unwrap(),
+            json!({ "uri": "file:///doc.rho", "status": "degraded", "errors": 0, "warnings": 0 })
+        );
+        assert!(enabled_in(Some(&json!({ "documentStatus": true }))));
+        assert!(!enabled_in(None));
+    }
+}
@@ -0,0 +1,187 @@
+//! Fuzzy symbol search in one document for the `rholang/documentSymbolQuery` custom request
+//!
+//! `textDocument/documentSymbol` returns every symbol of a document, which
+//! for generated files of tens of thousands of lines is a large response the
+//! client then filters itself. This request filters and ranks on the server
+//! and returns only the best matches, for "go to symbol in file" pickers.
+//!
+//! The query matches a name if its characters appear in the name in order,
+//! ignoring case (`trfr` matches `transfer`). Matches are ranked by
+//! [`fuzzy_score`]: an exact name first, then prefixes, then matches whose
+//! characters start words (`mkVlt` in `makeVault`, `g_b` in `get_balance`) or
+//! follow each other; ties go to the shorter name, then to the earlier one.
+//!
+//! # Request
+//!
+//! ```json
+//! { "textDocument": { "uri": "file:///path/to/file.rho" }, "query": "trfr", "limit": 50 }
+//! ```
+//!
+//! `limit` defaults to [`DEFAULT_QUERY_LIMIT`]. An empty query lists the
+//! symbols in document order.
+//!
+//! # Response
+//!
+//! ```json
+//! { "symbols": [ { "name": "transfer", "kind": 12, "location": { … }, "containerName": "vault" } ],
+//!   "truncated": false }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{SymbolInformation, TextDocumentIdentifier};
+
+/// Custom request method name
+pub const DOCUMENT_SYMBOL_QUERY_METHOD: &str = "rholang/documentSymbolQuery";
+
+/// Number of symbols returned when the request sets no limit
+pub const DEFAULT_QUERY_LIMIT: usize = 100;
+
+/// Parameters of `rholang/documentSymbolQuery`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentSymbolQueryParams {
+    pub text_document: TextDocumentIdentifier,
+    pub query: String,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// Response of `rholang/documentSymbolQuery`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentSymbolQueryResult {
+    /// Best matches first
+    pub symbols: Vec<SymbolInformation>,
+    /// Whether matches beyond the limit were dropped
+    pub truncated: bool,
+}
+
+/// Ranks the `symbols` of a document matching `query`, keeping the best `limit`.
+pub fn query_symbols(symbols: &[SymbolInformation], query: &str, limit: usize) -> DocumentSymbolQueryResult {
+    let mut matches: Vec<(i64, &SymbolInformation)> = symbols
+        .iter()
+        .filter_map(|symbol| fuzzy_score(query, &symbol.name).map(|score| (score, symbol)))
+        .collect();
+    if query.is_empty() {
+        matches.sort_by(|(_, a), (_, b)| a.location.range.start.cmp(&b.location.range.start));
+    } else {
+        matches.sort_by(|(a_score, a), (b_score, b)| {
+            b_score
+                .cmp(a_score)
+                .then_with(|| a.name.len().cmp(&b.name.len()))
+                .then_with(|| a.location.range.start.cmp(&b.location.range.start))
+        });
+    }
+
+    let truncated = matches.len() > limit;
+    matches.truncate(limit);
+    DocumentSymbolQueryResult { symbols: matches.into_iter().map(|(_, symbol)| symbol.clone()).collect(), truncated }
+}
+
+/// How well `query` matches `name`, higher is better; `None` if it does not match.
+///
+/// Every query character must be found in `name`, in order and ignoring
+/// case. Each one found scores a point, more if it starts a word of `name`
+/// or directly follows the previous match; characters of `name` skipped
+/// before the first match cost a point each. Exact names and prefixes get
+/// a large bonus.
+pub fn fuzzy_score(query: &str, name: &str) -> Option<i64> {
+    if query.is_empty() {
+        return Some(0);
+    }
+    let name_chars: Vec<char> = name.chars().collect();
+    let mut score = 0i64;
+    let mut next = 0;
+    let mut previous: Option<usize> = None;
+
+    for q in query.chars() {
+        let found = (next..name_chars.len()).find(|&i| eq_ignore_case(name_chars[i], q))?;
+        score += 1;
+        if name_chars[found] == q {
+            score += 1;
+        }
+        if word_start(&name_chars, found) {
+            score += 10;
+        }
+        match previous {
+            Some(previous) if found == previous + 1 => score += 5,
+            None => score -= found as i64,
+            _ => {}
+        }
+        previous = Some(found);
+        next = found + 1;
+    }
+
+    if name.eq_ignore_ascii_case(query) {
+        score += 100;
+    } else if name.to_lowercase().starts_with(&query.to_lowercase()) {
+        score += 25;
+    }
+    Some(score)
+}
+
+fn eq_ignore_case(a: char, b: char) -> bool {
+    a == b || a.to_lowercase().eq(b.to_lowercase())
+}
+
+/// Whether `chars[i]` starts a word: the first character, one after a
+/// separator, or an uppercase letter after a lowercase one.
+fn word_start(chars: &[char], i: usize) -> bool {
+    if i == 0 {
+        return true;
+    }
+    let previous = chars[i - 1];
+    matches!(previous, '_' | '-' | ':' | '.' | '/' | ' ')
+        || (chars[i].is_uppercase() && previous.is_lowercase())
+        || (chars[i].is_alphanumeric() && !previous.is_alphanumeric())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp::lsp_types::{Location, Position, Range, SymbolKind, Url};
+
+    fn symbol(name: &str, line: u32) -> SymbolInformation {
+        SymbolInformation {
+            name: name.to_string(),
+            kind: SymbolKind::FUNCTION,
+            location: Location {
+                uri: Url::parse("file:///test.rho").unwrap(),
+                range: Range::new(Position::new(line, 0), Position::new(line, 10)),
+            },
+            tags: None,
+            deprecated: None,
+            container_name: None,
+        }
+    }
+
+    fn names(result: &DocumentSymbolQueryResult) -> Vec<&str> {
+        result.symbols.iter().map(|symbol| symbol.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("trfr", "transfer").is_some());
+        assert_eq!(fuzzy_score("rft", "transfer"), None);
+        assert!(fuzzy_score("mkVlt", "makeVault") > fuzzy_score("mkVlt", "mkvaultlist"));
+        assert!(fuzzy_score("gb", "get_balance") > fuzzy_score("gb", "debug"));
+        assert!(fuzzy_score("tr", "transfer") > fuzzy_score("tr", "getTransfer"));
+    }
+
+    #[test]
+    fn test_query_symbols_ranks_and_truncates() {
+        let symbols = vec![
+            symbol("getTransfer", 0),
+            symbol("transferAll", 1),
+            symbol("transfer", 2),
+            symbol("balance", 3),
+        ];
+        let result = query_symbols(&symbols, "transfer", 10);
+        assert_eq!(names(&result), vec!["transfer", "transferAll", "getTransfer"]);
+        assert!(!result.truncated);
+
+        let result = query_symbols(&symbols, "", 2);
+        assert_eq!(names(&result), vec!["getTransfer", "transferAll"]);
+        assert!(result.truncated);
+    }
+}
@@ -0,0 +1,206 @@
+//! Detection of duplicate sends in a parallel composition
+//!
+//! Two identical sends in the same `|` composition, such as
+//!
+//! ```rholang
+//! ack!(true) | log!("done") | ack!(true)
+//! ```
+//!
+//! put two copies of the message on the channel, which is usually a
+//! copy-paste mistake. Sends are compared structurally: same channel, send
+//! kind and arguments, literal for literal, whatever their positions,
+//! spacing or comments. Nested compositions are flattened, but a block
+//! (`{ … }`) starts a new one.
+//!
+//! Every send after the first is reported as a warning with code
+//! [`DUPLICATE_SEND_CODE`], pointing back at the first one.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use ropey::Rope;
+use tower_lsp::lsp_types::{
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location, NumberOrString, Range, Url,
+};
+
+use crate::i18n::tr;
+use crate::ir::formatter::format_node;
+use crate::ir::rholang_node::{Metadata, NodeBase, Position as IrPosition, RholangNode, RholangNodeVector};
+use crate::ir::visitor::Visitor;
+use crate::lsp::line_index::{lsp_range, LineIndex};
+
+/// Diagnostic source of duplicate send warnings
+pub const DUPLICATE_SEND_SOURCE: &str = "rholang-lint";
+/// Diagnostic code for duplicate sends
+pub const DUPLICATE_SEND_CODE: &str = "duplicate-send";
+
+/// A send repeating an earlier send of the same composition
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateSend {
+    /// The send, as source text
+    pub text: String,
+    pub range: Range,
+    /// Range of the first occurrence
+    pub first: Range,
+}
+
+impl DuplicateSend {
+    pub fn to_diagnostic(&self, uri: &Url) -> Diagnostic {
+        Diagnostic {
+            range: self.range,
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: Some(NumberOrString::String(DUPLICATE_SEND_CODE.to_string())),
+            source: Some(DUPLICATE_SEND_SOURCE.to_string()),
+            message: tr("lint.duplicate-send", &[("send", &self.text)]),
+            related_information: Some(vec![DiagnosticRelatedInformation {
+                location: Location { uri: uri.clone(), range: self.first },
+                message: tr("lint.duplicate-send-first", &[]),
+            }]),
+            ..Default::default()
+        }
+    }
+}
+
+/// Finds the duplicate sends of a document, in document order.
+pub fn find_duplicate_sends(
+    ir: &Arc<RholangNode>,
+    positions: &HashMap<usize, (IrPosition, IrPosition)>,
+    text: &Rope,
+    line_index: &LineIndex,
+) -> Vec<DuplicateSend> {
+    let finder = DuplicateSendFinder {
+        positions,
+        text,
+        line_index,
+        found: RefCell::new(Vec::new()),
+    };
+    finder.visit_node(ir);
+    let mut found = finder.found.into_inner();
+    found.sort_by_key(|duplicate| (duplicate.range.start.line, duplicate.range.start.character));
+    found
+}
+
+struct DuplicateSendFinder<'a> {
+    positions: &'a HashMap<usize, (IrPosition, IrPosition)>,
+    text: &'a Rope,
+    line_index: &'a LineIndex,
+    found: RefCell<Vec<DuplicateSend>>,
+}
+
+impl DuplicateSendFinder<'_> {
+    fn range(&self, node: &Arc<RholangNode>) -> Option<Range> {
+        let span = self.positions.get(&(Arc::as_ptr(node) as usize))?;
+        Some(lsp_range(self.text, self.line_index, span))
+    }
+
+    /// Checks the branches of the composition rooted at `par`, then visits them.
+    fn check_par(&self, par: &Arc<RholangNode>) {
+        let mut branches = Vec::new();
+        flatten_par(par, &mut branches);
+
+        let mut first_by_text: HashMap<String, Range> = HashMap::new();
+        for branch in branches {
+            if matches!(&**branch, RholangNode::Send { .. }) {
+                if let Some(range) = self.range(branch) {
+                    let text = format_node(branch, false, None, self.text, branch);
+                    match first_by_text.get(&text) {
+                        Some(&first) => self.found.borrow_mut().push(DuplicateSend { text, range, first }),
+                        None => {
+                            first_by_text.insert(text, range);
+                        }
+                    }
+                }
+            }
+            self.visit_node(branch);
+        }
+    }
+}
+
+/// Collects the processes of a composition, flattening nested `Par` nodes.
+fn flatten_par<'n>(node: &'n Arc<RholangNode>, branches: &mut Vec<&'n Arc<RholangNode>>) {
+    match &**node {
+        RholangNode::Par { processes: Some(processes), .. } => {
+            for process in processes.iter() {
+                flatten_par(process, branches);
+            }
+        }
+        RholangNode::Par { left: Some(left), right: Some(right), .. } => {
+            flatten_par(left, branches);
+            flatten_par(right, branches);
+        }
+        _ => branches.push(node),
+    }
+}
+
+impl Visitor for DuplicateSendFinder<'_> {
+    fn visit_par(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        _left: &Arc<RholangNode>,
+        _right: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.check_par(node);
+        Arc::clone(node)
+    }
+
+    fn visit_par_nary(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        _processes: &RholangNodeVector,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.check_par(node);
+        Arc::clone(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree_sitter::parse_with_positions;
+    use tower_lsp::lsp_types::Position as LspPosition;
+
+    fn duplicates(source: &str) -> Vec<(String, u32, u32)> {
+        let rope = Rope::from_str(source);
+        let (ir, positions) = parse_with_positions(source);
+        find_duplicate_sends(&ir, &positions, &rope, &LineIndex::new(source))
+            .into_iter()
+            .map(|duplicate| (duplicate.text, duplicate.range.start.line, duplicate.first.start.line))
+            .collect()
+    }
+
+    #[test]
+    fn test_duplicate_sends_in_par() {
+        let source = "new ack, log in {\n  ack!(true) |\n  log!(\"done\") |\n  ack!( true )\n}";
+        assert_eq!(duplicates(source), vec![("ack!(true)".to_string(), 3, 1)]);
+    }
+
+    #[test]
+    fn test_distinct_or_separate_sends() {
+        // Different arguments or send kinds
+        assert!(duplicates("new x in { x!(1) | x!(2) | x!!(1) }").is_empty());
+        // Separate compositions
+        assert!(duplicates("new x in { x!(1) | { x!(1) | Nil } }").is_empty());
+        // Same text, but each in its own `new` body
+        assert!(duplicates("new x in { x!(1) } | new x in { x!(1) }").is_empty());
+    }
+
+    #[test]
+    fn test_diagnostic() {
+        let uri = Url::parse("file:///dup.rho").unwrap();
+        let duplicate = DuplicateSend {
+            text: "ack!(true)".to_string(),
+            range: Range::new(LspPosition::new(3, 2), LspPosition::new(3, 12)),
+            first: Range::new(LspPosition::new(1, 2), LspPosition::new(1, 12)),
+        };
+        let diagnostic = duplicate.to_diagnostic(&uri);
+        assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::WARNING));
+        assert_eq!(diagnostic.code, Some(NumberOrString::String(DUPLICATE_SEND_CODE.to_string())));
+        let related = diagnostic.related_information.unwrap();
+        assert_eq!(related[0].location.range.start.line, 1);
+    }
+}
@@ -0,0 +1,129 @@
+//! Evaluating a document on RNode (`rholang.evaluateFile`)
+//!
+//! The command sends a document to RNode's REPL service and streams what
+//! RNode prints back to the client, one `rholang/evaluationOutput`
+//! notification per event, each output line also logged with
+//! `window/logMessage` so clients without special support show it in their
+//! output panel:
+//!
+//! ```json
+//! { "command": "rholang.evaluateFile", "arguments": [{ "uri": "file:///a.rho", "endpoint": "localhost:40402" }] }
+//! ```
+//!
+//! ```json
+//! { "uri": "file:///a.rho", "evaluation": 3, "kind": "started", "endpoint": "localhost:40402" }
+//! { "uri": "file:///a.rho", "evaluation": 3, "kind": "output", "text": "\"Hello\"" }
+//! { "uri": "file:///a.rho", "evaluation": 3, "kind": "finished" }
+//! ```
+//!
+//! Without `endpoint`, the RNode that validates documents is used when RNode
+//! validation is on, and the first allowlisted endpoint otherwise. Like
+//! `rholang.deploy`, the command runs only in trusted workspaces and against
+//! allowlisted endpoints (see `crate::lsp::workspace_trust`). Its result is
+//! `{ evaluation, endpoint, output }` once RNode has answered.
+
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::notification::Notification;
+use tower_lsp::lsp_types::Url;
+
+/// Evaluates a document on RNode, streaming the output to the client
+pub const EVALUATE_FILE_COMMAND: &str = "rholang.evaluateFile";
+
+/// Arguments of the `rholang.evaluateFile` command
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluateFileArgs {
+    /// Document to evaluate
+    pub uri: Url,
+    /// RNode gRPC endpoint (`host:port`)
+    #[serde(default)]
+    pub endpoint: Option<String>,
+}
+
+/// What an evaluation output notification reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EvaluationEventKind {
+    /// The document was sent to `endpoint`
+    Started,
+    /// A line printed by RNode
+    Output,
+    /// RNode finished evaluating the document
+    Finished,
+    /// The evaluation could not run; `text` says why
+    Failed,
+}
+
+/// Parameters of the `rholang/evaluationOutput` notification
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluationOutputParams {
+    pub uri: Url,
+    /// Identifies the evaluation among those of the session
+    pub evaluation: u64,
+    pub kind: EvaluationEventKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+impl EvaluationOutputParams {
+    pub fn started(uri: Url, evaluation: u64, endpoint: String) -> Self {
+        EvaluationOutputParams { uri, evaluation, kind: EvaluationEventKind::Started, endpoint: Some(endpoint), text: None }
+    }
+
+    pub fn output(uri: Url, evaluation: u64, text: String) -> Self {
+        EvaluationOutputParams { uri, evaluation, kind: EvaluationEventKind::Output, endpoint: None, text: Some(text) }
+    }
+
+    pub fn finished(uri: Url, evaluation: u64) -> Self {
+        EvaluationOutputParams { uri, evaluation, kind: EvaluationEventKind::Finished, endpoint: None, text: None }
+    }
+
+    pub fn failed(uri: Url, evaluation: u64, reason: String) -> Self {
+        EvaluationOutputParams { uri, evaluation, kind: EvaluationEventKind::Failed, endpoint: None, text: Some(reason) }
+    }
+}
+
+/// `rholang/evaluationOutput`
+pub enum EvaluationOutput {}
+
+impl Notification for EvaluationOutput {
+    type Params = EvaluationOutputParams;
+    const METHOD: &'static str = "rholang/evaluationOutput";
+}
+
+/// The lines of RNode's output, without trailing blank lines.
+pub fn output_lines(output: &str) -> Vec<&str> {
+    let mut lines: Vec<&str> = output.lines().map(|line| line.trim_end_matches('\r')).collect();
+    while lines.last().is_some_and(|line| line.trim().is_empty()) {
+        lines.pop();
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_output_lines() {
+        assert_eq!(output_lines("\"Hello\"\r\n@{Nil}\n\n"), vec!["\"Hello\"", "@{Nil}"]);
+        assert!(output_lines("").is_empty());
+    }
+
+    #[test]
+    fn test_notification_shape() {
+        let uri = Url::parse("file:///a.rho").unwrap();
+        assert_eq!(
+            serde_json::to_value(EvaluationOutputParams::started(uri.clone(), 3, "localhost:40402".to_string())).unwrap(),
+            json!({ "uri": "file:///a.rho", "evaluation": 3, "kind": "started", "endpoint": "localhost:40402" })
+        );
+        assert_eq!(
+            serde_json::to_value(EvaluationOutputParams::output(uri, 3, "\"Hello\"".to_string())).unwrap(),
+            json!({ "uri": "file:///a.rho", "evaluation": 3, "kind": "output", "text": "\"Hello\"" })
+        );
+    }
+}
@@ -0,0 +1,502 @@
+//! Template expansion preview for `@template` / `@expand` directives
+//!
+//! Generated boilerplate is described with comment directives. A template is a
+//! run of line comments between `// @template` and `// @end`, with `${param}`
+//! placeholders:
+//!
+//! ```rholang
+//! // @template logger(name, out)
+//! // contract ${name}(@message) = {
+//! //   ${out}!(message)
+//! // }
+//! // @end
+//! ```
+//!
+//! A `// @expand` directive instantiates it. The code generated from the
+//! directive may follow it, closed by `// @end`:
+//!
+//! ```rholang
+//! // @expand logger(info, stdout)
+//! contract info(@message) = {
+//!   stdout!(message)
+//! }
+//! // @end
+//! ```
+//!
+//! The `rholang.expandPreview` command renders the expansions of a document
+//! into a read-only preview document (`rholang-expansion:` scheme). Each block
+//! of the preview is mapped back to its `@expand` directive and template so
+//! the editor can navigate from the preview to the source.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{Position, Range, Url};
+
+/// URI scheme of expansion preview documents
+pub const PREVIEW_SCHEME: &str = "rholang-expansion";
+
+/// A template defined by a `@template` directive
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    pub name: String,
+    pub params: Vec<String>,
+    /// Template body with comment markers stripped
+    pub body: String,
+    /// Document declaring the template
+    pub uri: Url,
+    /// Range of the `@template` directive line
+    pub range: Range,
+}
+
+impl Template {
+    /// Substitutes `args` for the template's `${param}` placeholders.
+    ///
+    /// Placeholders that do not name a parameter are left untouched.
+    pub fn instantiate(&self, args: &[String]) -> Result<String, String> {
+        if args.len() != self.params.len() {
+            return Err(format!(
+                "Template '{}' expects {} argument(s), got {}",
+                self.name,
+                self.params.len(),
+                args.len()
+            ));
+        }
+        let mut expanded = self.body.clone();
+        for (param, arg) in self.params.iter().zip(args) {
+            expanded = expanded.replace(&format!("${{{}}}", param), arg);
+        }
+        Ok(expanded)
+    }
+}
+
+/// An `@expand` directive
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expansion {
+    pub template: String,
+    pub args: Vec<String>,
+    /// Leading whitespace of the directive, applied to the expanded lines
+    pub indent: String,
+    /// Range of the `@expand` directive line
+    pub range: Range,
+    /// Lines between the directive and its `// @end`, if the region is closed
+    pub generated: Option<GeneratedRegion>,
+}
+
+/// Code following an `@expand` directive, up to its `// @end`
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratedRegion {
+    pub range: Range,
+    pub text: String,
+}
+
+/// Parameters of the `rholang.expandPreview` command
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ExpandPreviewParams {
+    /// Document whose expansions are previewed
+    pub uri: Url,
+    /// Only preview the directive on this line (or whose region contains it)
+    #[serde(default)]
+    pub position: Option<Position>,
+}
+
+/// Result of the `rholang.expandPreview` command
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ExpansionPreview {
+    /// URI of the preview document
+    pub uri: Url,
+    /// Preview document text
+    pub content: String,
+    /// One entry per expansion block in `content`
+    pub mappings: Vec<ExpansionMapping>,
+}
+
+/// Maps a block of the preview back to its directive
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpansionMapping {
+    pub template: String,
+    /// Range of the block in the preview document
+    pub preview_range: Range,
+    /// Document containing the `@expand` directive
+    pub source_uri: Url,
+    /// Range of the `@expand` directive
+    pub directive_range: Range,
+    /// Document declaring the template, if it was found
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template_uri: Option<Url>,
+    /// Range of the `@template` directive, if it was found
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template_range: Option<Range>,
+    /// Whether the generated region matches the expansion (`None` without a region)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub up_to_date: Option<bool>,
+    /// Why the directive could not be expanded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Kinds of directive lines
+enum Directive<'a> {
+    Template(&'a str),
+    Expand(&'a str),
+    End,
+}
+
+/// Returns the URI of the expansion preview for `uri`.
+pub fn preview_uri(uri: &Url) -> Url {
+    Url::parse(&format!("{}:{}", PREVIEW_SCHEME, uri.path()))
+        .unwrap_or_else(|_| uri.clone())
+}
+
+/// Collects the `@template` directives of a document.
+pub fn collect_templates(uri: &Url, text: &str) -> Vec<Template> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut templates = Vec::new();
+
+    let mut row = 0;
+    while row < lines.len() {
+        let Some(Directive::Template(header)) = parse_directive(lines[row]) else {
+            row += 1;
+            continue;
+        };
+        let start = row;
+        let mut body = Vec::new();
+        row += 1;
+        while row < lines.len() {
+            match parse_directive(lines[row]) {
+                Some(Directive::End) => {
+                    row += 1;
+                    break;
+                }
+                Some(_) => break,
+                None => match comment_body(lines[row]) {
+                    Some(line) => body.push(line),
+                    None => break,
+                },
+            }
+            row += 1;
+        }
+
+        if let Some((name, params)) = parse_call(header) {
+            templates.push(Template {
+                name,
+                params,
+                body: body.join("\n"),
+                uri: uri.clone(),
+                range: line_range(start, lines[start]),
+            });
+        }
+    }
+    templates
+}
+
+/// Collects the `@expand` directives of a document.
+pub fn collect_expansions(text: &str) -> Vec<Expansion> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut expansions = Vec::new();
+
+    for (row, line) in lines.iter().enumerate() {
+        let Some(Directive::Expand(header)) = parse_directive(line) else {
+            continue;
+        };
+        let Some((template, args)) = parse_call(header) else {
+            continue;
+        };
+
+        // The generated region ends at the first `@end`; another directive
+        // before it means the expansion has no generated region
+        let end_row = lines[row + 1..]
+            .iter()
+            .position(|line| parse_directive(line).is_some())
+            .map(|offset| row + 1 + offset)
+            .filter(|&end| matches!(parse_directive(lines[end]), Some(Directive::End)));
+        let generated = end_row.map(|end| GeneratedRegion {
+            range: Range::new(Position::new(row as u32 + 1, 0), Position::new(end as u32, 0)),
+            text: lines[row + 1..end].join("\n"),
+        });
+
+        expansions.push(Expansion {
+            template,
+            args,
+            indent: line[..line.len() - line.trim_start().len()].to_string(),
+            range: line_range(row, line),
+            generated,
+        });
+    }
+    expansions
+}
+
+/// Renders the preview of the expansions in `text`.
+///
+/// Templates are looked up in `templates` by name; when `position` is given,
+/// only the directive on that line (or whose generated region contains it)
+/// is previewed.
+pub fn build_preview(
+    uri: &Url,
+    text: &str,
+    templates: &HashMap<String, Template>,
+    position: Option<Position>,
+) -> ExpansionPreview {
+    let mut content = String::new();
+    let mut mappings = Vec::new();
+    let mut row = 0u32;
+
+    for expansion in collect_expansions(text) {
+        if let Some(position) = position {
+            if !expansion_contains(&expansion, position) {
+                continue;
+            }
+        }
+        if !content.is_empty() {
+            content.push('\n');
+            row += 1;
+        }
+
+        let header = format!("// @expand {}({}) at line {}", expansion.template, expansion.args.join(", "), expansion.range.start.line + 1);
+        let template = templates.get(&expansion.template);
+        let expanded = match template {
+            Some(template) => template.instantiate(&expansion.args).map(|body| indent(&body, &expansion.indent)),
+            None => Err(format!("Unknown template '{}'", expansion.template)),
+        };
+        let body = match &expanded {
+            Ok(body) => body.clone(),
+            Err(error) => format!("// error: {}", error),
+        };
+
+        let start_row = row;
+        content.push_str(&header);
+        content.push('\n');
+        content.push_str(&body);
+        content.push('\n');
+        row += 2 + body.lines().count().saturating_sub(1) as u32;
+        let last_len = body.lines().last().map(|line| line.chars().count()).unwrap_or(0);
+
+        mappings.push(ExpansionMapping {
+            template: expansion.template.clone(),
+            preview_range: Range::new(Position::new(start_row, 0), Position::new(row - 1, last_len as u32)),
+            source_uri: uri.clone(),
+            directive_range: expansion.range,
+            template_uri: template.map(|t| t.uri.clone()),
+            template_range: template.map(|t| t.range),
+            up_to_date: match (&expanded, &expansion.generated) {
+                (Ok(body), Some(region)) => Some(normalize(body) == normalize(&region.text)),
+                _ => None,
+            },
+            error: expanded.err(),
+        });
+    }
+
+    ExpansionPreview {
+        uri: preview_uri(uri),
+        content,
+        mappings,
+    }
+}
+
+/// Whether `position` is on the directive or inside its generated region
+fn expansion_contains(expansion: &Expansion, position: Position) -> bool {
+    let last_line = expansion
+        .generated
+        .as_ref()
+        .map(|region| region.range.end.line)
+        .unwrap_or(expansion.range.start.line);
+    (expansion.range.start.line..=last_line).contains(&position.line)
+}
+
+/// Recognizes `// @template`, `// @expand` and `// @end` lines
+fn parse_directive(line: &str) -> Option<Directive<'_>> {
+    let content = line.trim().strip_prefix("//")?.trim_start_matches('/').trim();
+    let content = content.strip_prefix('@')?;
+    let (keyword, rest) = content.split_once(char::is_whitespace).unwrap_or((content, ""));
+    match keyword {
+        "template" => Some(Directive::Template(rest.trim())),
+        "expand" => Some(Directive::Expand(rest.trim())),
+        "end" => Some(Directive::End),
+        _ => None,
+    }
+}
+
+/// Strips the `//` marker (and one following space) from a template body line
+fn comment_body(line: &str) -> Option<String> {
+    let rest = line.trim_start().strip_prefix("//")?;
+    Some(rest.strip_prefix(' ').unwrap_or(rest).trim_end().to_string())
+}
+
+/// Parses `name(arg, ...)` or a bare `name`
+fn parse_call(header: &str) -> Option<(String, Vec<String>)> {
+    let (name, args) = match header.find('(') {
+        Some(open) => {
+            let close = header.rfind(')')?;
+            (&header[..open], split_args(header.get(open + 1..close)?))
+        }
+        None => (header, Vec::new()),
+    };
+    let name = name.trim();
+    let valid = !name.is_empty()
+        && name.chars().all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'));
+    valid.then(|| (name.to_string(), args))
+}
+
+/// Splits arguments on top-level commas, respecting brackets and string literals
+fn split_args(args: &str) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0i32;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for c in args.chars() {
+        if in_string {
+            current.push(c);
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => {
+                in_string = true;
+                current.push(c);
+            }
+            '(' | '[' | '{' => {
+                depth += 1;
+                current.push(c);
+            }
+            ')' | ']' | '}' => {
+                depth -= 1;
+                current.push(c);
+            }
+            ',' if depth == 0 => result.push(std::mem::take(&mut current).trim().to_string()),
+            _ => current.push(c),
+        }
+    }
+    if !current.trim().is_empty() || !result.is_empty() {
+        result.push(current.trim().to_string());
+    }
+    result
+}
+
+/// Prefixes every non-empty line of `text` with `indent`
+fn indent(text: &str, indent: &str) -> String {
+    text.lines()
+        .map(|line| if line.is_empty() { String::new() } else { format!("{}{}", indent, line) })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Drops trailing whitespace and blank lines for up-to-date comparisons
+fn normalize(text: &str) -> Vec<&str> {
+    text.lines()
+        .map(str::trim_end)
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+fn line_range(row: usize, line: &str) -> Range {
+    Range::new(
+        Position::new(row as u32, 0),
+        Position::new(row as u32, line.chars().count() as u32),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"// @template logger(name, out)
+// contract ${name}(@message) = {
+//   ${out}!(message)
+// }
+// @end
+new info, stdout(`rho:io:stdout`) in {
+  // @expand logger(info, stdout)
+  contract info(@message) = {
+    stdout!(message)
+  }
+  // @end
+  |
+  // @expand logger(warn, "log"!(1, 2))
+  // @expand missing(x)
+}"#;
+
+    fn uri() -> Url {
+        Url::parse("file:///gen.rho").unwrap()
+    }
+
+    fn templates() -> HashMap<String, Template> {
+        collect_templates(&uri(), SOURCE)
+            .into_iter()
+            .map(|t| (t.name.clone(), t))
+            .collect()
+    }
+
+    #[test]
+    fn test_collect_templates() {
+        let templates = collect_templates(&uri(), SOURCE);
+        assert_eq!(templates.len(), 1);
+        assert_eq!(templates[0].name, "logger");
+        assert_eq!(templates[0].params, vec!["name", "out"]);
+        assert_eq!(templates[0].body, "contract ${name}(@message) = {\n  ${out}!(message)\n}");
+        assert_eq!(templates[0].range.start.line, 0);
+    }
+
+    #[test]
+    fn test_collect_expansions() {
+        let expansions = collect_expansions(SOURCE);
+        assert_eq!(expansions.len(), 3);
+
+        assert_eq!(expansions[0].args, vec!["info", "stdout"]);
+        assert_eq!(expansions[0].indent, "  ");
+        let region = expansions[0].generated.as_ref().unwrap();
+        assert_eq!((region.range.start.line, region.range.end.line), (7, 10));
+
+        assert_eq!(expansions[1].args, vec!["warn", "\"log\"!(1, 2)"]);
+        assert_eq!(expansions[1].generated, None);
+    }
+
+    #[test]
+    fn test_build_preview_maps_back_to_directives() {
+        let preview = build_preview(&uri(), SOURCE, &templates(), None);
+        assert_eq!(preview.uri.as_str(), "rholang-expansion:/gen.rho");
+        assert_eq!(preview.mappings.len(), 3);
+
+        let first = &preview.mappings[0];
+        assert_eq!(first.directive_range.start.line, 6);
+        assert_eq!(first.template_range.map(|r| r.start.line), Some(0));
+        assert_eq!(first.up_to_date, Some(true));
+        assert_eq!((first.preview_range.start.line, first.preview_range.end.line), (0, 3));
+
+        let lines: Vec<&str> = preview.content.lines().collect();
+        assert_eq!(lines[1], "  contract info(@message) = {");
+        assert_eq!(lines[6], "  contract warn(@message) = {");
+        assert_eq!(lines[7], "    \"log\"!(1, 2)!(message)");
+
+        let missing = &preview.mappings[2];
+        assert_eq!(missing.error.as_deref(), Some("Unknown template 'missing'"));
+        assert_eq!(missing.template_uri, None);
+    }
+
+    #[test]
+    fn test_build_preview_at_position() {
+        let preview = build_preview(&uri(), SOURCE, &templates(), Some(Position::new(8, 4)));
+        assert_eq!(preview.mappings.len(), 1);
+        assert_eq!(preview.mappings[0].directive_range.start.line, 6);
+
+        let stale = SOURCE.replace("stdout!(message)", "stdout!(\"old\")");
+        let preview = build_preview(&uri(), &stale, &templates(), Some(Position::new(6, 0)));
+        assert_eq!(preview.mappings[0].up_to_date, Some(false));
+    }
+
+    #[test]
+    fn test_instantiate_checks_arity() {
+        let template = &templates()["logger"];
+        assert_eq!(
+            template.instantiate(&["a".to_string()]),
+            Err("Template 'logger' expects 2 argument(s), got 1".to_string())
+        );
+    }
+}
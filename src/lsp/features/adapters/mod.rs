@@ -13,10 +13,16 @@
 //! - **generic**: Default language-agnostic adapter with global scope resolution
 //! - **rholang**: Rholang-specific adapter with hierarchical symbol tables
 //! - **metta**: MeTTa-specific adapter with pattern matching and composable resolution
+//!   (`metta` cargo feature)
+//!
+//! The unified handlers pick an adapter through the [`AdapterRegistry`], keyed
+//! by language id, where further languages can be registered at startup.
 
 pub mod generic;
 pub mod rholang;
+#[cfg(feature = "metta")]
 pub mod metta;
+pub mod registry;
 
 pub use generic::{
     GenericHoverProvider,
@@ -32,9 +38,12 @@ pub use rholang::{
     create_rholang_adapter,
 };
 
+#[cfg(feature = "metta")]
 pub use metta::{
     MettaHoverProvider,
     MettaCompletionProvider,
     MettaDocumentationProvider,
     create_metta_adapter,
 };
+
+pub use registry::{AdapterFactory, AdapterInputs, AdapterRegistry, AdapterSymbols};
@@ -0,0 +1,205 @@
+//! Registry of language adapters keyed by language id
+//!
+//! The unified handlers look up the adapter of the language at the request
+//! position here instead of naming a `create_*_adapter` function, so a new
+//! embedded language only needs a factory registered at startup. Languages
+//! without a registered factory fall back to the generic adapter
+//! (global-scope resolution), which is the behavior of
+//! `LanguageContext::Other`.
+//!
+//! Besides programmatic registration, the `adapters` initialization option
+//! maps extra language ids to a registered adapter, e.g.
+//! `{ "adapters": { "mettalog": "metta" } }`.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use tower_lsp::lsp_types::Url;
+use tracing::{debug, trace, warn};
+
+use crate::ir::symbol_table::SymbolTable;
+use crate::ir::transforms::metta_symbol_table_builder::MettaSymbolTable;
+use crate::lsp::features::traits::LanguageAdapter;
+use crate::lsp::models::WorkspaceState;
+
+use super::{create_generic_adapter, create_rholang_adapter};
+#[cfg(feature = "metta")]
+use super::create_metta_adapter;
+
+/// Initialization option mapping language ids to registered adapters
+pub const ADAPTERS_OPTION: &str = "adapters";
+
+/// Symbol table of the document or region a request targets
+#[derive(Clone)]
+pub enum AdapterSymbols {
+    Rholang(Arc<SymbolTable>),
+    Metta(Arc<MettaSymbolTable>),
+    /// The language has no symbol table of its own
+    None,
+}
+
+/// What a factory gets to build the adapter for one request
+#[derive(Clone)]
+pub struct AdapterInputs {
+    pub workspace: Arc<WorkspaceState>,
+    pub symbols: AdapterSymbols,
+    /// Host document of a virtual document
+    pub parent_uri: Option<Url>,
+}
+
+/// Builds an adapter for a request, or `None` if the inputs do not suit it
+/// (e.g. the Rholang adapter without a Rholang symbol table)
+pub type AdapterFactory = Arc<dyn Fn(&str, &AdapterInputs) -> Option<LanguageAdapter> + Send + Sync>;
+
+/// Language adapters keyed by language id
+#[derive(Clone, Default)]
+pub struct AdapterRegistry {
+    factories: HashMap<String, AdapterFactory>,
+}
+
+impl std::fmt::Debug for AdapterRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AdapterRegistry").field("languages", &self.languages()).finish()
+    }
+}
+
+impl AdapterRegistry {
+    /// Creates an empty registry; every language gets the generic adapter.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a registry with the built-in `rholang` adapter, and the `metta`
+    /// one when built with the `metta` feature. Without it, MeTTa falls back
+    /// to the generic adapter like any other language.
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::new();
+        registry.register("rholang", Arc::new(|_: &str, inputs: &AdapterInputs| match &inputs.symbols {
+            AdapterSymbols::Rholang(symbol_table) => {
+                Some(create_rholang_adapter(symbol_table.clone(), inputs.workspace.global_index.clone()))
+            }
+            _ => None,
+        }));
+        #[cfg(feature = "metta")]
+        registry.register("metta", Arc::new(|_: &str, inputs: &AdapterInputs| match &inputs.symbols {
+            AdapterSymbols::Metta(symbol_table) => Some(create_metta_adapter(
+                symbol_table.clone(),
+                inputs.workspace.clone(),
+                inputs.parent_uri.clone()?,
+            )),
+            _ => None,
+        }));
+        registry
+    }
+
+    /// Registers `factory` for `language`, replacing any previous one.
+    pub fn register(&mut self, language: impl Into<String>, factory: AdapterFactory) {
+        let language = language.into();
+        trace!("Registering language adapter: {}", language);
+        if self.factories.insert(language.clone(), factory).is_some() {
+            debug!("Replaced language adapter for '{}'", language);
+        }
+    }
+
+    /// Makes `language` use the adapter registered for `target`.
+    ///
+    /// Returns false if `target` has no registered adapter.
+    pub fn alias(&mut self, language: impl Into<String>, target: &str) -> bool {
+        match self.factories.get(target).cloned() {
+            Some(factory) => {
+                self.register(language, factory);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes the adapter of `language`; it falls back to the generic adapter.
+    pub fn unregister(&mut self, language: &str) -> bool {
+        self.factories.remove(language).is_some()
+    }
+
+    /// Whether `language` has a registered adapter.
+    pub fn contains(&self, language: &str) -> bool {
+        self.factories.contains_key(language)
+    }
+
+    /// Registered language ids, sorted.
+    pub fn languages(&self) -> Vec<&str> {
+        let mut languages: Vec<&str> = self.factories.keys().map(String::as_str).collect();
+        languages.sort_unstable();
+        languages
+    }
+
+    /// The adapter for `language`, falling back to the generic adapter when
+    /// no factory is registered or the registered one declines the inputs.
+    pub fn adapter_for(&self, language: &str, inputs: &AdapterInputs) -> LanguageAdapter {
+        self.factories
+            .get(language)
+            .and_then(|factory| factory(language, inputs))
+            .unwrap_or_else(|| create_generic_adapter(inputs.workspace.clone(), language.to_string()))
+    }
+
+    /// Applies the `adapters` initialization option (`{ language: target }`).
+    pub fn configure(&mut self, init_options: Option<&serde_json::Value>) {
+        let Some(aliases) = init_options.and_then(|opts| opts.get(ADAPTERS_OPTION)).and_then(|a| a.as_object())
+        else {
+            return;
+        };
+        for (language, target) in aliases {
+            match target.as_str() {
+                Some(target) if self.alias(language.clone(), target) => {
+                    debug!("Language '{}' uses the '{}' adapter", language, target);
+                }
+                _ => warn!("Ignoring adapter mapping {} -> {}: no such adapter", language, target),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn inputs(symbols: AdapterSymbols) -> AdapterInputs {
+        AdapterInputs { workspace: Arc::new(WorkspaceState::new()), symbols, parent_uri: None }
+    }
+
+    #[test]
+    fn test_lookup_by_language_with_generic_fallback() {
+        let registry = AdapterRegistry::with_defaults();
+        #[cfg(feature = "metta")]
+        assert_eq!(registry.languages(), vec!["metta", "rholang"]);
+        #[cfg(not(feature = "metta"))]
+        assert_eq!(registry.languages(), vec!["rholang"]);
+
+        let rholang = inputs(AdapterSymbols::Rholang(Arc::new(SymbolTable::new(None))));
+        assert_eq!(registry.adapter_for("rholang", &rholang).name, "rholang");
+        // Unknown languages, and inputs the factory cannot use, get the generic adapter
+        let none = inputs(AdapterSymbols::None);
+        assert_eq!(registry.adapter_for("python", &none).name, "python");
+        assert_eq!(registry.adapter_for("metta", &none).name, "metta");
+    }
+
+    #[test]
+    fn test_runtime_registration_and_aliases() {
+        let mut registry = AdapterRegistry::with_defaults();
+        let calls = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = calls.clone();
+        registry.register("toy", Arc::new(move |language: &str, inputs: &AdapterInputs| {
+            counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            Some(create_generic_adapter(inputs.workspace.clone(), format!("{}-adapter", language)))
+        }));
+        assert_eq!(registry.adapter_for("toy", &inputs(AdapterSymbols::None)).name, "toy-adapter");
+        assert_eq!(calls.load(std::sync::atomic::Ordering::Relaxed), 1);
+
+        registry.configure(Some(&json!({ "adapters": { "toylang": "toy", "bogus": "missing" } })));
+        assert!(registry.contains("toylang"));
+        assert!(!registry.contains("bogus"));
+        assert_eq!(registry.adapter_for("toylang", &inputs(AdapterSymbols::None)).name, "toylang-adapter");
+
+        assert!(registry.unregister("toy"));
+        assert_eq!(registry.adapter_for("toy", &inputs(AdapterSymbols::None)).name, "toy");
+    }
+}
@@ -0,0 +1,245 @@
+//! Quick fixes for `textDocument/codeAction`
+//!
+//! Each fix is a [`CodeActionProvider`]: it looks at the requested range, the
+//! diagnostics the client sent along and the parse tree of the document, and
+//! returns the actions that apply there. New fixes are added by implementing
+//! the trait and listing the provider in [`rholang_providers`].
+//!
+//! Built-in providers:
+//! - [`InsertParallelComposition`]: two top-level processes written one after
+//!   the other without `|` between them.
+//! - [`AddToNewDeclaration`]: an unbound variable, declared by adding it to
+//!   the names of the nearest enclosing `new`.
+
+use ropey::Rope;
+use tower_lsp::lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, Diagnostic, Position as LspPosition, Range, TextEdit, Url,
+    WorkspaceEdit,
+};
+use tree_sitter::{Node, Tree};
+
+use crate::i18n::tr;
+use crate::lsp::line_index::LineIndex;
+use crate::lsp::syntax_errors::PARSER_SOURCE;
+
+/// Diagnostic source of the semantic validator
+const SEMANTIC_SOURCE: &str = "rholang-semantic";
+
+/// Message prefix of unbound variable diagnostics
+const UNBOUND_PREFIX: &str = "Unbound variable: ";
+
+/// Context for code action providers
+pub struct CodeActionContext<'a> {
+    /// URI of the document
+    pub uri: &'a Url,
+    /// Text of the document
+    pub text: &'a Rope,
+    pub line_index: &'a LineIndex,
+    /// Tree-Sitter parse tree of `text`
+    pub tree: &'a Tree,
+    /// Range the actions were requested for
+    pub range: Range,
+    /// Diagnostics the client reported overlapping `range`
+    pub diagnostics: &'a [Diagnostic],
+}
+
+impl CodeActionContext<'_> {
+    /// Byte offset of an LSP position.
+    pub fn byte(&self, position: LspPosition) -> Option<usize> {
+        self.line_index.offset(position.line as usize, position.character as usize, self.text)
+    }
+
+    /// LSP position of a byte offset.
+    pub fn position(&self, byte: usize) -> Option<LspPosition> {
+        let (line, character) = self.line_index.position(byte, self.text)?;
+        Some(LspPosition::new(line as u32, character as u32))
+    }
+
+    /// Source text of `node`.
+    pub fn node_text(&self, node: Node) -> String {
+        self.text.byte_slice(node.start_byte()..node.end_byte()).to_string()
+    }
+
+    /// A quick fix applying `edits` to this document.
+    pub fn quick_fix(&self, title: String, edits: Vec<TextEdit>, diagnostics: Vec<Diagnostic>) -> CodeAction {
+        let is_preferred = !diagnostics.is_empty();
+        CodeAction {
+            title,
+            kind: Some(CodeActionKind::QUICKFIX),
+            diagnostics: (!diagnostics.is_empty()).then_some(diagnostics),
+            edit: Some(WorkspaceEdit {
+                changes: Some([(self.uri.clone(), edits)].into_iter().collect()),
+                ..Default::default()
+            }),
+            is_preferred: Some(is_preferred),
+            ..Default::default()
+        }
+    }
+}
+
+/// Provider trait for code actions
+///
+/// Implementations must be cheap: every provider runs on each code action
+/// request, which clients send whenever the cursor moves.
+pub trait CodeActionProvider: Send + Sync {
+    /// Actions applicable in `context`; empty if none.
+    fn code_actions(&self, context: &CodeActionContext) -> Vec<CodeAction>;
+}
+
+/// The code action providers of Rholang documents
+pub fn rholang_providers() -> Vec<Box<dyn CodeActionProvider>> {
+    vec![Box::new(InsertParallelComposition), Box::new(AddToNewDeclaration)]
+}
+
+/// Runs every provider over `context`.
+pub fn code_actions(providers: &[Box<dyn CodeActionProvider>], context: &CodeActionContext) -> Vec<CodeActionOrCommand> {
+    providers
+        .iter()
+        .flat_map(|provider| provider.code_actions(context))
+        .map(CodeActionOrCommand::CodeAction)
+        .collect()
+}
+
+fn overlaps(a: &Range, b: &Range) -> bool {
+    a.start <= b.end && b.start <= a.end
+}
+
+/// Inserts `|` between two top-level processes with nothing composing them.
+///
+/// `x!(1) y!(2)` parses as two separate processes of the source file, which
+/// the Rholang parser then rejects; the fix makes it `x!(1) | y!(2)`.
+pub struct InsertParallelComposition;
+
+impl CodeActionProvider for InsertParallelComposition {
+    fn code_actions(&self, context: &CodeActionContext) -> Vec<CodeAction> {
+        let root = context.tree.root_node();
+        let mut cursor = root.walk();
+        let processes: Vec<Node> = root
+            .named_children(&mut cursor)
+            .filter(|child| !child.is_extra() && !child.is_error())
+            .collect();
+
+        let mut actions = Vec::new();
+        for pair in processes.windows(2) {
+            let (left, right) = (pair[0], pair[1]);
+            let (Some(start), Some(end)) = (context.position(left.start_byte()), context.position(right.end_byte()))
+            else {
+                continue;
+            };
+            let span = Range::new(start, end);
+            if !overlaps(&span, &context.range) {
+                continue;
+            }
+            let Some(insert_at) = context.position(left.end_byte()) else { continue };
+
+            let diagnostics = context
+                .diagnostics
+                .iter()
+                .filter(|d| d.source.as_deref() == Some(PARSER_SOURCE) && overlaps(&d.range, &span))
+                .cloned()
+                .collect();
+            let edit = TextEdit { range: Range::new(insert_at, insert_at), new_text: " |".to_string() };
+            actions.push(context.quick_fix(tr("action.insert-par", &[]), vec![edit], diagnostics));
+        }
+        actions
+    }
+}
+
+/// Declares an unbound variable in the nearest enclosing `new`.
+///
+/// For the "Unbound variable: y" diagnostic on `new x in { y!(1) }` the fix
+/// makes it `new x, y in { y!(1) }`. No action is offered outside any `new`.
+pub struct AddToNewDeclaration;
+
+impl CodeActionProvider for AddToNewDeclaration {
+    fn code_actions(&self, context: &CodeActionContext) -> Vec<CodeAction> {
+        let mut actions = Vec::new();
+        for diagnostic in context.diagnostics {
+            if diagnostic.source.as_deref() != Some(SEMANTIC_SOURCE) {
+                continue;
+            }
+            let Some(name) = diagnostic.message.strip_prefix(UNBOUND_PREFIX) else { continue };
+            let Some(byte) = context.byte(diagnostic.range.start) else { continue };
+            let Some(decls) = enclosing_new_decls(context.tree, byte) else { continue };
+            let Some(insert_at) = context.position(decls.end_byte()) else { continue };
+
+            let declared = decls
+                .named_children(&mut decls.walk())
+                .any(|decl| decl.named_child(0).is_some_and(|var| context.node_text(var) == name));
+            if declared {
+                continue;
+            }
+
+            let edit = TextEdit { range: Range::new(insert_at, insert_at), new_text: format!(", {}", name) };
+            actions.push(context.quick_fix(tr("action.add-to-new", &[("name", &name)]), vec![edit], vec![diagnostic.clone()]));
+        }
+        actions
+    }
+}
+
+/// The `decls` of the innermost `new` whose process contains `byte`.
+fn enclosing_new_decls(tree: &Tree, byte: usize) -> Option<Node<'_>> {
+    let mut node = tree.root_node().descendant_for_byte_range(byte, byte)?;
+    loop {
+        let parent = node.parent()?;
+        if parent.kind() == "new" && parent.child_by_field_name("proc").is_some_and(|proc| proc.id() == node.id()) {
+            return parent.child_by_field_name("decls");
+        }
+        node = parent;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree_sitter::parse_code;
+
+    fn fixes(source: &str, range: Range, diagnostics: &[Diagnostic]) -> Vec<CodeAction> {
+        let uri = Url::parse("file:///a.rho").unwrap();
+        let text = Rope::from_str(source);
+        let line_index = LineIndex::from_rope(&text);
+        let tree = parse_code(source);
+        let context = CodeActionContext { uri: &uri, text: &text, line_index: &line_index, tree: &tree, range, diagnostics };
+        rholang_providers().iter().flat_map(|provider| provider.code_actions(&context)).collect()
+    }
+
+    fn only_edit(action: &CodeAction) -> TextEdit {
+        let changes = action.edit.as_ref().unwrap().changes.as_ref().unwrap();
+        changes.values().next().unwrap()[0].clone()
+    }
+
+    fn at(line: u32, character: u32) -> Range {
+        Range::new(LspPosition::new(line, character), LspPosition::new(line, character))
+    }
+
+    #[test]
+    fn test_insert_parallel_composition() {
+        let source = "x!(1)\ny!(2)\n";
+        let actions = fixes(source, at(1, 2), &[]);
+        assert_eq!(actions.len(), 1);
+        assert_eq!(only_edit(&actions[0]), TextEdit { range: at(0, 5), new_text: " |".to_string() });
+        assert_eq!(actions[0].is_preferred, Some(false));
+
+        // Composed processes need no fix
+        assert!(fixes("x!(1) |\ny!(2)\n", at(1, 2), &[]).is_empty());
+    }
+
+    #[test]
+    fn test_add_to_new_declaration() {
+        let source = "new x in {\n  for (@v <- x) { y!(v) }\n}\n";
+        let unbound = Diagnostic {
+            range: Range::new(LspPosition::new(1, 18), LspPosition::new(1, 19)),
+            source: Some(SEMANTIC_SOURCE.to_string()),
+            message: "Unbound variable: y".to_string(),
+            ..Default::default()
+        };
+        let actions = fixes(source, unbound.range, std::slice::from_ref(&unbound));
+        assert_eq!(actions.len(), 1);
+        assert_eq!(only_edit(&actions[0]), TextEdit { range: at(0, 5), new_text: ", y".to_string() });
+        assert_eq!(actions[0].diagnostics, Some(vec![unbound]));
+
+        // Outside any `new` there is nowhere to declare it
+        let unbound = Diagnostic { range: at(0, 0), ..actions[0].diagnostics.as_ref().unwrap()[0].clone() };
+        assert!(fixes("y!(1)\n", at(0, 0), &[unbound]).is_empty());
+    }
+}
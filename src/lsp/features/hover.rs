@@ -18,6 +18,7 @@ use tower_lsp::lsp_types::{Hover, HoverContents, Position as LspPosition, Range,
 use tracing::debug;
 
 use crate::ir::semantic_node::{Position, SemanticCategory, SemanticNode};
+use crate::lsp::deprecation::{deprecation_notice, metadata_deprecation, referenced_symbol};
 use crate::lsp::features::node_finder::{find_node_at_position, find_node_with_path, ir_to_lsp_position};
 use crate::lsp::features::traits::{HoverContext, LanguageAdapter};
 
@@ -119,6 +120,18 @@ impl GenericHover {
             debug!("Found documentation for hover: {} chars", doc.len());
         }
 
+        // Lead with the `@deprecated` reason, if any
+        let documentation = match self.extract_deprecation(node, parent) {
+            Some(reason) => {
+                let notice = deprecation_notice(&reason);
+                Some(match documentation {
+                    Some(doc) => format!("{}\n\n{}", notice, doc),
+                    None => notice,
+                })
+            }
+            None => documentation,
+        };
+
         // Clone documentation for use in fallback case
         let doc_for_fallback = documentation.clone();
 
@@ -267,6 +280,29 @@ impl GenericHover {
         None
     }
 
+    /// Extract the `@deprecated` reason for a node
+    ///
+    /// Checks the node's own metadata (declarations), the symbol it references
+    /// (usages), then the parent (e.g. Contract when hovering its name).
+    fn extract_deprecation(
+        &self,
+        node: &dyn SemanticNode,
+        parent: Option<&dyn SemanticNode>,
+    ) -> Option<String> {
+        if let Some(metadata) = node.metadata() {
+            if let Some(reason) = metadata_deprecation(metadata) {
+                return Some(reason.to_string());
+            }
+            if let Some(reason) = referenced_symbol(metadata).and_then(|symbol| symbol.deprecated.clone()) {
+                return Some(reason);
+            }
+        }
+        parent
+            .and_then(|parent_node| parent_node.metadata())
+            .and_then(metadata_deprecation)
+            .map(str::to_string)
+    }
+
     /// Extract symbol name from node metadata
     ///
     /// Same logic as GenericGotoDefinition - could be refactored into shared utility
@@ -474,11 +510,16 @@ mod tests {
             &self,
             symbol_name: &str,
             _node: &dyn SemanticNode,
-            _context: &HoverContext,
+            context: &HoverContext,
         ) -> Option<HoverContents> {
+            let mut value = format!("**{}** - Test hover", symbol_name);
+            if let Some(ref doc) = context.documentation {
+                value.push_str("\n\n");
+                value.push_str(doc);
+            }
             Some(HoverContents::Markup(MarkupContent {
                 kind: MarkupKind::Markdown,
-                value: format!("**{}** - Test hover", symbol_name),
+                value,
             }))
         }
     }
@@ -566,6 +607,39 @@ mod tests {
         assert!(hover.range.is_some());
     }
 
+    #[tokio::test]
+    async fn test_hover_shows_deprecation_reason() {
+        use crate::ir::transforms::documentation_attacher::DEPRECATION_METADATA_KEY;
+
+        let adapter = crate::lsp::features::traits::LanguageAdapter::new(
+            "test",
+            Arc::new(MockResolver),
+            Arc::new(MockHoverProvider),
+            Arc::new(MockCompletion),
+            Arc::new(MockDoc),
+        );
+
+        let mut node = MockSymbolNode::new_with_name("old_var".to_string(), SemanticCategory::Variable);
+        node.metadata.insert(
+            DEPRECATION_METADATA_KEY.to_string(),
+            Arc::new("use new_var".to_string()) as Arc<dyn Any + Send + Sync>,
+        );
+        let position = Position { row: 0, column: 2, byte: 2 };
+        let lsp_pos = LspPosition { line: 0, character: 2 };
+        let uri = Url::parse("file:///test.rho").unwrap();
+
+        let hover = GenericHover
+            .hover(&node, &position, lsp_pos, &uri, &adapter, None)
+            .await
+            .expect("hover should return content");
+        match hover.contents {
+            HoverContents::Markup(content) => {
+                assert!(content.value.contains("**Deprecated**: use new_var"), "got: {}", content.value);
+            }
+            _ => panic!("Expected markup content"),
+        }
+    }
+
     #[tokio::test]
     async fn test_hover_no_symbol() {
         // Node without symbol name in metadata
@@ -93,6 +93,7 @@
 //! - Measure code reduction (target: 50%+)
 
 pub mod traits;
+pub mod code_actions;
 pub mod node_finder;
 pub mod goto_definition;
 pub mod hover;
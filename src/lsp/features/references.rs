@@ -6,6 +6,7 @@ use std::sync::Arc;
 use tower_lsp::lsp_types::{Location, Position as LspPosition, Range, Url};
 use tracing::debug;
 
+use crate::ir::rholang_node::{strip_quote_eval, RholangNode};
 use crate::ir::semantic_node::{Position, SemanticNode};
 use crate::ir::symbol_resolution::ResolutionContext;
 use crate::lsp::features::node_finder::{find_node_at_position, ir_to_lsp_position};
@@ -51,7 +52,6 @@ impl GenericReferences {
 
         // Special handling for Par nodes: if we get a Par node, look at its first child
         // This handles cases where position tracking doesn't drill down into Par children
-        use crate::ir::rholang_node::RholangNode;
         if let Some(rholang_node) = node.as_any().downcast_ref::<RholangNode>() {
             if let RholangNode::Par { processes, left, .. } = rholang_node {
                 debug!("Found Par node, checking first child");
@@ -189,8 +189,6 @@ impl GenericReferences {
 
     /// Extract symbol name from node metadata or structure
     fn extract_symbol_name<'a>(&self, node: &'a dyn SemanticNode) -> Option<&'a str> {
-        use crate::ir::rholang_node::RholangNode;
-
         // First try metadata keys (set by symbol table builder for references)
         if let Some(metadata) = node.metadata() {
             if let Some(name_any) = metadata.get("symbol_name") {
@@ -225,16 +223,16 @@ impl GenericReferences {
                 RholangNode::Var { name, .. } => {
                     return Some(name.as_str());
                 }
-                // For Send/SendSync nodes, extract from the channel (which should be a Var)
+                // For Send/SendSync nodes, extract from the channel (which should be a Var, maybe as `@*x`)
                 RholangNode::Send { channel, .. } | RholangNode::SendSync { channel, .. } => {
-                    if let RholangNode::Var { name, .. } = channel.as_ref() {
+                    if let RholangNode::Var { name, .. } = strip_quote_eval(channel) {
                         return Some(name.as_str());
                     }
                 }
-                // For Quote nodes (e.g., @fromRoom), extract from the inner node
-                RholangNode::Quote { quotable, .. } => {
-                    if let Some(RholangNode::Var { name, .. }) = quotable.as_ref().as_any().downcast_ref::<RholangNode>() {
-                        return Some(name.as_str());
+                // For Quote and Eval nodes (e.g., @fromRoom, *x, @*x), extract from the inner node
+                RholangNode::Quote { .. } | RholangNode::Eval { .. } => {
+                    if let Some(name) = quoted_var(rholang_node) {
+                        return Some(name);
                     }
                 }
                 _ => {}
@@ -245,6 +243,20 @@ impl GenericReferences {
     }
 }
 
+/// Name of the variable `node` quotes or evaluates, through quote/eval
+/// round-trips: `x` for `@x`, `*x`, `@*x` and `*@x`.
+pub(super) fn quoted_var(node: &RholangNode) -> Option<&str> {
+    let operand = match strip_quote_eval(node) {
+        RholangNode::Quote { quotable, .. } => quotable.as_ref(),
+        RholangNode::Eval { name, .. } => name.as_ref(),
+        other => other,
+    };
+    match operand {
+        RholangNode::Var { name, .. } => Some(name.as_str()),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -372,6 +384,30 @@ mod tests {
         assert_eq!(locs[0].uri.as_str(), "file:///test.rho");
     }
 
+    #[test]
+    fn test_quoted_var() {
+        use crate::ir::rholang_node::collect_calls;
+        use crate::tree_sitter::{parse_code, parse_to_ir};
+
+        let code = "@x!(1) | @*x!(2) | @*@*x!(3) | @\"x\"!(4)";
+        let ir = parse_to_ir(&parse_code(code), &ropey::Rope::from_str(code));
+        let mut calls = Vec::new();
+        collect_calls(&ir, &mut calls);
+        let names: Vec<_> = calls
+            .iter()
+            .map(|call| match &**call {
+                RholangNode::Send { channel, .. } => quoted_var(channel),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(names, vec![Some("x"), Some("x"), Some("x"), None]);
+
+        // `*x` inside `@*x`
+        let RholangNode::Send { channel, .. } = &*calls[1] else { panic!("Expected Send node") };
+        let RholangNode::Quote { quotable, .. } = &**channel else { panic!("Expected Quote node") };
+        assert_eq!(quoted_var(quotable), Some("x"));
+    }
+
     #[tokio::test]
     async fn test_find_references_not_found() {
         let adapter = LanguageAdapter::new(
@@ -9,7 +9,7 @@ use tower_lsp::lsp_types::{TextEdit, Url, WorkspaceEdit, Position as LspPosition
 use tracing::{debug, trace};
 
 use super::traits::LanguageAdapter;
-use super::references::GenericReferences;
+use super::references::{quoted_var, GenericReferences};
 use super::node_finder::find_node_at_position;
 use crate::ir::semantic_node::{Position, SemanticNode};
 use crate::ir::symbol_resolution::{ResolutionContext};
@@ -174,10 +174,10 @@ impl GenericRename {
                 RholangNode::Var { name, .. } => {
                     return Some(name.as_str());
                 }
-                // For Quote nodes (e.g., @fromRoom), extract from the inner node
-                RholangNode::Quote { quotable, .. } => {
-                    if let RholangNode::Var { name, .. } = quotable.as_ref() {
-                        return Some(name.as_str());
+                // For Quote and Eval nodes (e.g., @fromRoom, *x, @*x), extract from the inner node
+                RholangNode::Quote { .. } | RholangNode::Eval { .. } => {
+                    if let Some(name) = quoted_var(rholang_node) {
+                        return Some(name);
                     }
                 }
                 _ => {}
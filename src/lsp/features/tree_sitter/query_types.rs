@@ -152,7 +152,7 @@ impl CaptureType {
             ["local", local_type] => Self::Local(LocalType::from_str(local_type)),
             ["injection", inj_type] => Self::Injection(InjectionType::from_str(inj_type)),
             ["indent"] => Self::Indent(IndentType::Indent),
-            ["outdent"] => Self::Indent(IndentType::Outdent),
+            ["outdent"] | ["dedent"] => Self::Indent(IndentType::Outdent),
             ["align"] => Self::Indent(IndentType::Align),
             ["fold"] => Self::Fold,
             [kind, "outer"] => Self::TextObject {
@@ -269,7 +269,7 @@ impl HighlightType {
 pub enum IndentType {
     /// @indent - increase indentation
     Indent,
-    /// @outdent - decrease indentation
+    /// @outdent (or @dedent) - decrease indentation
     Outdent,
     /// @align - align with specific column
     Align,
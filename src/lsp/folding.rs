@@ -0,0 +1,455 @@
+//! Folding ranges of Rholang documents
+//!
+//! `textDocument/foldingRange` combines two sources:
+//!
+//! - the `folds.scm` query, which folds whole constructs (blocks, contracts,
+//!   `for`, `new`, collections, comments, and the case list of a `match` or
+//!   the branch list of a `select`);
+//! - the IR, from which every `match` case and every `select` branch gets a
+//!   fold of its own, from its pattern (or first bind) to the end of its
+//!   body, so that one case can be collapsed while its neighbours stay open.
+//!
+//! While the document has a syntax error, the constructs it breaks are
+//! `ERROR` nodes to the query and get no fold. The IR keeps what the parser
+//! recovered, so in that case every block, `new`, contract, `for`, `if`,
+//! `let`, `bundle`, `match`, `select`, collection and multi-line error
+//! region of the IR is folded as well.
+//!
+//! Ranges on a single line are dropped, and ranges covering the same lines
+//! are reported once.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use ropey::Rope;
+use tower_lsp::lsp_types::FoldingRange;
+use tracing::warn;
+use tree_sitter::Tree;
+
+use crate::ir::rholang_node::{
+    Metadata, NodeBase, Position as IrPosition, RholangBranchVector, RholangBundleType, RholangNode,
+    RholangNodePairVector, RholangNodeVector, RholangReceiptVector,
+};
+use crate::ir::visitor::Visitor;
+use crate::lsp::features::tree_sitter::{CaptureProcessor, QueryEngine, QueryType};
+use crate::lsp::line_index::{lsp_position, LineIndex};
+
+/// Query engine with only `folds.scm` loaded; `None` if the query does not compile
+static FOLDS: Lazy<Option<Mutex<QueryEngine>>> = Lazy::new(|| {
+    let engine = QueryEngine::new("rholang", rholang_tree_sitter::LANGUAGE.into()).and_then(|mut engine| {
+        engine.load_query(QueryType::Folds, include_str!("../../queries/rholang/folds.scm"))?;
+        Ok(engine)
+    });
+    match engine {
+        Ok(engine) => Some(Mutex::new(engine)),
+        Err(e) => {
+            warn!("Folding query unavailable: {}", e);
+            None
+        }
+    }
+});
+
+/// The folding ranges of a document, sorted by start line.
+pub fn folding_ranges(
+    tree: &Tree,
+    ir: &Arc<RholangNode>,
+    positions: &HashMap<usize, (IrPosition, IrPosition)>,
+    text: &Rope,
+    line_index: &LineIndex,
+) -> Vec<FoldingRange> {
+    let mut ranges = query_folds(tree, text, line_index);
+    if tree.root_node().has_error() {
+        ranges.extend(construct_folds(ir, positions, text, line_index));
+    } else {
+        ranges.extend(case_folds(ir, positions, text, line_index));
+    }
+    ranges.retain(|range| range.end_line > range.start_line);
+    ranges.sort_by_key(|range| (range.start_line, std::cmp::Reverse(range.end_line)));
+    ranges.dedup_by_key(|range| (range.start_line, range.end_line));
+    ranges
+}
+
+/// Folds of the `folds.scm` query.
+fn query_folds(tree: &Tree, text: &Rope, line_index: &LineIndex) -> Vec<FoldingRange> {
+    let Some(engine) = FOLDS.as_ref() else { return Vec::new() };
+    match engine.lock().execute(tree, QueryType::Folds, text.to_string().as_bytes()) {
+        Ok(captures) => {
+            let mut ranges = CaptureProcessor::to_folding_ranges(&captures);
+            // Captures carry tree-sitter's byte columns
+            for range in &mut ranges {
+                range.start_character = range.start_character.map(|c| lsp_character(text, line_index, range.start_line, c));
+                range.end_character = range.end_character.map(|c| lsp_character(text, line_index, range.end_line, c));
+            }
+            ranges
+        }
+        Err(e) => {
+            warn!("Folding query failed: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// LSP column of the byte `column` of `line`.
+fn lsp_character(text: &Rope, line_index: &LineIndex, line: u32, column: u32) -> u32 {
+    line_index
+        .line_start(line as usize)
+        .and_then(|start| line_index.position(start + column as usize, text))
+        .map_or(column, |(_, character)| character as u32)
+}
+
+/// One fold per `match` case and per `select` branch.
+pub fn case_folds(
+    ir: &Arc<RholangNode>,
+    positions: &HashMap<usize, (IrPosition, IrPosition)>,
+    text: &Rope,
+    line_index: &LineIndex,
+) -> Vec<FoldingRange> {
+    let finder = IrFolds { positions, text, line_index, constructs: false, folds: RefCell::new(Vec::new()) };
+    finder.visit_node(ir);
+    finder.folds.into_inner()
+}
+
+/// The case folds, plus one fold per construct of the IR, for documents the
+/// query cannot fold because of syntax errors.
+pub fn construct_folds(
+    ir: &Arc<RholangNode>,
+    positions: &HashMap<usize, (IrPosition, IrPosition)>,
+    text: &Rope,
+    line_index: &LineIndex,
+) -> Vec<FoldingRange> {
+    let finder = IrFolds { positions, text, line_index, constructs: true, folds: RefCell::new(Vec::new()) };
+    finder.visit_node(ir);
+    finder.folds.into_inner()
+}
+
+struct IrFolds<'a> {
+    positions: &'a HashMap<usize, (IrPosition, IrPosition)>,
+    text: &'a Rope,
+    line_index: &'a LineIndex,
+    /// Whether whole constructs are folded, besides cases and branches
+    constructs: bool,
+    folds: RefCell<Vec<FoldingRange>>,
+}
+
+impl IrFolds<'_> {
+    /// Folds the whole of `node`, when folding constructs.
+    fn fold_construct(&self, node: &Arc<RholangNode>) {
+        if self.constructs {
+            self.fold(node, node);
+        }
+    }
+
+    /// Folds from the start of `first` to the end of `last`.
+    fn fold(&self, first: &Arc<RholangNode>, last: &Arc<RholangNode>) {
+        let start = self.positions.get(&(Arc::as_ptr(first) as usize));
+        let end = self.positions.get(&(Arc::as_ptr(last) as usize));
+        let (Some((start, _)), Some((_, end))) = (start, end) else { return };
+        let start = lsp_position(self.text, self.line_index, start);
+        let end = lsp_position(self.text, self.line_index, end);
+        self.folds.borrow_mut().push(FoldingRange {
+            start_line: start.line,
+            start_character: Some(start.character),
+            end_line: end.line,
+            end_character: Some(end.character),
+            kind: None,
+            collapsed_text: None,
+        });
+    }
+}
+
+impl Visitor for IrFolds<'_> {
+    fn visit_match(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        expression: &Arc<RholangNode>,
+        cases: &RholangNodePairVector,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.fold_construct(node);
+        self.visit_node(expression);
+        for (pattern, proc) in cases.iter() {
+            self.fold(pattern, proc);
+            self.visit_node(proc);
+        }
+        Arc::clone(node)
+    }
+
+    fn visit_choice(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        branches: &RholangBranchVector,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.fold_construct(node);
+        for (inputs, proc) in branches.iter() {
+            if let Some(first) = inputs.first() {
+                self.fold(first, proc);
+            }
+            for input in inputs.iter() {
+                self.visit_node(input);
+            }
+            self.visit_node(proc);
+        }
+        Arc::clone(node)
+    }
+
+    fn visit_block(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        proc: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.fold_construct(node);
+        self.visit_node(proc);
+        Arc::clone(node)
+    }
+
+    fn visit_new(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        _decls: &RholangNodeVector,
+        proc: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.fold_construct(node);
+        self.visit_node(proc);
+        Arc::clone(node)
+    }
+
+    fn visit_let(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        decls: &RholangNodeVector,
+        proc: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.fold_construct(node);
+        for decl in decls.iter() {
+            self.visit_node(decl);
+        }
+        self.visit_node(proc);
+        Arc::clone(node)
+    }
+
+    fn visit_bundle(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        _bundle_type: &RholangBundleType,
+        proc: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.fold_construct(node);
+        self.visit_node(proc);
+        Arc::clone(node)
+    }
+
+    fn visit_ifelse(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        condition: &Arc<RholangNode>,
+        consequence: &Arc<RholangNode>,
+        alternative: &Option<Arc<RholangNode>>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.fold_construct(node);
+        self.visit_node(condition);
+        self.visit_node(consequence);
+        if let Some(alternative) = alternative {
+            self.visit_node(alternative);
+        }
+        Arc::clone(node)
+    }
+
+    fn visit_contract(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        _name: &Arc<RholangNode>,
+        formals: &RholangNodeVector,
+        _formals_remainder: &Option<Arc<RholangNode>>,
+        proc: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.fold_construct(node);
+        for formal in formals.iter() {
+            self.visit_node(formal);
+        }
+        self.visit_node(proc);
+        Arc::clone(node)
+    }
+
+    fn visit_input(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        receipts: &RholangReceiptVector,
+        proc: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.fold_construct(node);
+        for bind in receipts.iter().flat_map(|receipt| receipt.iter()) {
+            self.visit_node(bind);
+        }
+        self.visit_node(proc);
+        Arc::clone(node)
+    }
+
+    fn visit_list(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        elements: &RholangNodeVector,
+        _remainder: &Option<Arc<RholangNode>>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.fold_construct(node);
+        for element in elements.iter() {
+            self.visit_node(element);
+        }
+        Arc::clone(node)
+    }
+
+    fn visit_set(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        elements: &RholangNodeVector,
+        _remainder: &Option<Arc<RholangNode>>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.fold_construct(node);
+        for element in elements.iter() {
+            self.visit_node(element);
+        }
+        Arc::clone(node)
+    }
+
+    fn visit_map(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        pairs: &RholangNodePairVector,
+        _remainder: &Option<Arc<RholangNode>>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.fold_construct(node);
+        for (key, value) in pairs.iter() {
+            self.visit_node(key);
+            self.visit_node(value);
+        }
+        Arc::clone(node)
+    }
+
+    fn visit_tuple(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        elements: &RholangNodeVector,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.fold_construct(node);
+        for element in elements.iter() {
+            self.visit_node(element);
+        }
+        Arc::clone(node)
+    }
+
+    fn visit_error(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        children: &RholangNodeVector,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.fold_construct(node);
+        for child in children.iter() {
+            self.visit_node(child);
+        }
+        Arc::clone(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree_sitter::{parse_code, parse_with_positions};
+
+    fn folds(source: &str) -> (Vec<(u32, u32)>, Vec<(u32, u32)>) {
+        let lines = |ranges: Vec<FoldingRange>| ranges.iter().map(|r| (r.start_line, r.end_line)).collect();
+        let (cases, all) = fold_ranges(source);
+        (lines(cases), lines(all))
+    }
+
+    fn fold_ranges(source: &str) -> (Vec<FoldingRange>, Vec<FoldingRange>) {
+        let tree = parse_code(source);
+        let rope = Rope::from_str(source);
+        let (ir, positions) = parse_with_positions(source);
+        let line_index = LineIndex::new(source);
+        (
+            case_folds(&ir, &positions, &rope, &line_index),
+            folding_ranges(&tree, &ir, &positions, &rope, &line_index),
+        )
+    }
+
+    #[test]
+    fn test_each_match_case_folds() {
+        let source = "new x in {\n  match *x {\n    1 => {\n      x!(1)\n    }\n    \"two\" =>\n      x!(2)\n    _ => Nil\n  }\n}";
+        let (cases, all) = folds(source);
+        // The single-line `_ => Nil` case has no fold
+        assert_eq!(cases.iter().filter(|(start, end)| end > start).count(), 2);
+        assert!(all.contains(&(2, 4)), "{:?}", all);
+        assert!(all.contains(&(5, 6)), "{:?}", all);
+        // The whole construct still folds
+        assert!(all.contains(&(1, 8)), "{:?}", all);
+        assert!(all.windows(2).all(|pair| pair[0] != pair[1]));
+    }
+
+    #[test]
+    fn test_each_select_branch_folds() {
+        let source = "new a, b in {\n  select {\n    x <- a => {\n      Nil\n    }\n    y <- b =>\n      Nil\n  }\n}";
+        let (cases, all) = folds(source);
+        assert_eq!(cases, vec![(2, 4), (5, 6)]);
+        assert!(all.contains(&(2, 4)) && all.contains(&(5, 6)), "{:?}", all);
+    }
+
+    #[test]
+    fn test_constructs_fold_despite_syntax_errors() {
+        let source = "contract c(@x) = {
+  new y in {
+    y!([
+      1,
+      2
+    ])
+  }
+}
+
+x!(1 +";
+        let (_, all) = folds(source);
+        assert!(parse_code(source).root_node().has_error());
+        // The contract body, the `new` scope and the list, from the IR
+        assert!(all.contains(&(0, 7)), "{:?}", all);
+        assert!(all.contains(&(1, 6)), "{:?}", all);
+        assert!(all.contains(&(2, 5)), "{:?}", all);
+        assert!(all.windows(2).all(|pair| pair[0] != pair[1]));
+    }
+
+    #[test]
+    fn test_fold_characters_count_utf16_units() {
+        // `é` is two bytes but one UTF-16 unit
+        let source = "new x in {\n  match \"é\" {\n    \"é\" => {\n      x!(\"é\") }\n  }\n}";
+        let (cases, _) = fold_ranges(source);
+        assert_eq!(cases.len(), 1);
+        assert_eq!((cases[0].start_line, cases[0].start_character), (2, Some(4)));
+        assert_eq!((cases[0].end_line, cases[0].end_character), (3, Some(15)));
+    }
+}
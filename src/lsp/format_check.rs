@@ -0,0 +1,213 @@
+//! Formatting check (`rholang.formatCheckWorkspace`, `fmt --check`)
+//!
+//! A Rholang file is formatted when its code matches what the formatter
+//! (`crate::ir::formatter::format_node`) produces for it. The formatter does
+//! not keep comments, so they are ignored in the comparison: comment text is
+//! removed, lines left empty by the removal are dropped and trailing
+//! whitespace does not count. Files with syntax errors cannot be formatted
+//! and are only counted.
+//!
+//! An unformatted file is reported as one [`UNFORMATTED_CODE`] diagnostic on
+//! its first differing line, so CI jobs can gate on the number of files.
+
+use ropey::Rope;
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Position as LspPosition, Range, Url};
+use tree_sitter::Tree;
+
+use crate::i18n::tr;
+use crate::ir::formatter::format_node;
+use crate::lsp::line_index::PositionEncoding;
+use crate::tree_sitter::{parse_code, parse_to_ir};
+
+/// Checks the formatting of every Rholang document in the workspace
+pub const FORMAT_CHECK_WORKSPACE_COMMAND: &str = "rholang.formatCheckWorkspace";
+
+/// Source of formatting diagnostics
+pub const FORMAT_SOURCE: &str = "rholang-format";
+
+/// Code of the diagnostic reported on an unformatted file
+pub const UNFORMATTED_CODE: &str = "unformatted";
+
+/// Indentation of the canonical style
+pub const INDENT_SIZE: usize = 2;
+
+/// Outcome of checking one file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormatStatus {
+    Formatted,
+    /// `range` covers the first line that differs from the formatter's output
+    Unformatted { range: Range },
+    /// The file has syntax errors
+    Unparsable,
+}
+
+impl FormatStatus {
+    /// The diagnostic reported for an unformatted file.
+    pub fn to_diagnostic(&self) -> Option<Diagnostic> {
+        let FormatStatus::Unformatted { range } = self else { return None };
+        Some(Diagnostic {
+            range: *range,
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: Some(NumberOrString::String(UNFORMATTED_CODE.to_string())),
+            source: Some(FORMAT_SOURCE.to_string()),
+            message: tr("lint.unformatted", &[]),
+            ..Default::default()
+        })
+    }
+}
+
+/// An unformatted document
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnformattedFile {
+    pub uri: Url,
+    pub diagnostic: Diagnostic,
+}
+
+/// Result of `rholang.formatCheckWorkspace`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormatCheckReport {
+    /// Documents checked, including unparsable ones
+    pub checked: usize,
+    /// Number of entries in `files`
+    pub unformatted: usize,
+    /// Documents skipped because of syntax errors
+    pub unparsable: usize,
+    pub files: Vec<UnformattedFile>,
+}
+
+impl FormatCheckReport {
+    /// Adds the outcome for the document `uri`.
+    pub fn record(&mut self, uri: &Url, status: &FormatStatus) {
+        self.checked += 1;
+        match status {
+            FormatStatus::Formatted => {}
+            FormatStatus::Unparsable => self.unparsable += 1,
+            FormatStatus::Unformatted { .. } => {
+                if let Some(diagnostic) = status.to_diagnostic() {
+                    self.unformatted += 1;
+                    self.files.push(UnformattedFile { uri: uri.clone(), diagnostic });
+                }
+            }
+        }
+    }
+}
+
+/// Checks whether the Rholang source `text` is formatted. The range of an
+/// unformatted line counts columns in units of `encoding`.
+pub fn check_format(text: &str, encoding: PositionEncoding) -> FormatStatus {
+    let tree = parse_code(text);
+    if tree.root_node().has_error() {
+        return FormatStatus::Unparsable;
+    }
+    let rope = Rope::from_str(text);
+    let ir = parse_to_ir(&tree, &rope);
+    let formatted = format_node(&ir, true, Some(INDENT_SIZE), &rope, &ir);
+
+    let code = code_lines(text, &tree);
+    let expected: Vec<&str> = formatted.lines().map(str::trim_end).collect();
+    let differing = code
+        .iter()
+        .zip(&expected)
+        .find(|((_, line), expected)| line != *expected)
+        .map(|((number, _), _)| *number)
+        .or_else(|| match code.len().cmp(&expected.len()) {
+            std::cmp::Ordering::Greater => Some(code[expected.len()].0),
+            std::cmp::Ordering::Less => Some(code.last().map_or(0, |(number, _)| *number)),
+            std::cmp::Ordering::Equal => None,
+        });
+
+    match differing {
+        None => FormatStatus::Formatted,
+        Some(number) => {
+            let width = text.lines().nth(number).map_or(0, |line| encoding.units_str(line));
+            FormatStatus::Unformatted {
+                range: Range::new(LspPosition::new(number as u32, 0), LspPosition::new(number as u32, width as u32)),
+            }
+        }
+    }
+}
+
+/// The lines of `text` without comments or trailing whitespace, numbered by
+/// their line in `text`. Lines emptied by removing comments and trailing
+/// empty lines are dropped.
+fn code_lines(text: &str, tree: &Tree) -> Vec<(usize, String)> {
+    let mut comments = Vec::new();
+    let mut cursor = tree.walk();
+    'walk: loop {
+        let node = cursor.node();
+        if matches!(node.kind(), "line_comment" | "block_comment") {
+            comments.push(node.byte_range());
+        } else if cursor.goto_first_child() {
+            continue;
+        }
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() {
+                break 'walk;
+            }
+        }
+    }
+
+    let mut lines = vec![(String::new(), false)];
+    for (offset, c) in text.char_indices() {
+        if c == '\n' {
+            lines.push((String::new(), false));
+            continue;
+        }
+        let line = lines.last_mut().expect("at least one line");
+        if comments.iter().any(|comment| comment.contains(&offset)) {
+            line.1 = true;
+        } else {
+            line.0.push(c);
+        }
+    }
+
+    let mut code: Vec<(usize, String)> = lines
+        .into_iter()
+        .enumerate()
+        .filter_map(|(number, (line, commented))| {
+            let line = line.trim_end();
+            (!(commented && line.is_empty())).then(|| (number, line.to_string()))
+        })
+        .collect();
+    while code.last().is_some_and(|(_, line)| line.is_empty()) {
+        code.pop();
+    }
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_formatted_files() {
+        assert_eq!(check_format("new x in {\n  x!(1)\n}\n", PositionEncoding::Utf16), FormatStatus::Formatted);
+        // Comments and trailing whitespace are ignored
+        assert_eq!(
+            check_format("// Sends one\nnew x in {\n  // on x\n  x!(1)   // trailing\n}\n\n", PositionEncoding::Utf16),
+            FormatStatus::Formatted
+        );
+    }
+
+    #[test]
+    fn test_unformatted_and_unparsable_files() {
+        let status = check_format("new x in {\n    x!(1)\n}", PositionEncoding::Utf16);
+        assert_eq!(
+            status,
+            FormatStatus::Unformatted { range: Range::new(LspPosition::new(1, 0), LspPosition::new(1, 9)) }
+        );
+        assert_eq!(status.to_diagnostic().and_then(|d| d.code), Some(NumberOrString::String(UNFORMATTED_CODE.to_string())));
+        assert_eq!(check_format("new x in {\n  x!(1)\n", PositionEncoding::Utf16), FormatStatus::Unparsable);
+
+        let mut report = FormatCheckReport::default();
+        let uri = Url::parse("file:///a.rho").unwrap();
+        report.record(&uri, &status);
+        report.record(&uri, &FormatStatus::Formatted);
+        report.record(&uri, &FormatStatus::Unparsable);
+        assert_eq!((report.checked, report.unformatted, report.unparsable), (3, 1, 1));
+        assert_eq!(report.files[0].diagnostic.range.start.line, 1);
+    }
+}
@@ -0,0 +1,411 @@
+//! Document formatting
+//!
+//! `textDocument/formatting` reprints a document through the IR formatter
+//! (`crate::ir::formatter::format_node`) and replaces the whole text with the
+//! result. The formatter works on the semantic tree, which holds no comments,
+//! so the comments of the comment channel (`DocumentIR::comments`) are put
+//! back afterwards: each one is anchored to the code next to it in the
+//! original text and re-inserted next to the same code in the formatted
+//! text, on its own line or trailing a line of code as it was written.
+//!
+//! The layout is configured in the `[format]` section of `.rholangrc.toml`:
+//!
+//! ```toml
+//! [format]
+//! indent_width = 4            # defaults to the editor's tab size
+//! brace_style = "next_line"   # same_line (default) | next_line
+//! max_line_length = 100       # no limit by default
+//! ```
+//!
+//! Lines longer than `max_line_length` are split at the `|` of a parallel
+//! composition; other long lines are left as they are. Documents with syntax
+//! errors are never formatted.
+
+use std::path::Path;
+
+use ropey::Rope;
+use serde::Deserialize;
+use tower_lsp::lsp_types::{Position as LspPosition, Range, TextEdit};
+use tracing::{debug, warn};
+
+use crate::ir::comment::CommentNode;
+use crate::ir::formatter::format_node;
+use crate::lsp::line_index::PositionEncoding;
+use crate::lsp::style_lints::CONFIG_FILE_NAME;
+use crate::tree_sitter::{parse_code, parse_to_document_ir};
+
+/// Placement of the opening brace of a block
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BraceStyle {
+    /// `new x in {`
+    #[default]
+    SameLine,
+    /// `new x in` followed by `{` on a line of its own
+    NextLine,
+}
+
+/// `[format]` section of `.rholangrc.toml`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct FormatConfig {
+    /// Spaces per indentation level; the editor's tab size when unset
+    pub indent_width: Option<usize>,
+    pub brace_style: BraceStyle,
+    /// Longest line before parallel compositions are split; no limit when unset
+    pub max_line_length: Option<usize>,
+}
+
+/// Top level of `.rholangrc.toml`; unknown sections are ignored
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct RcFile {
+    format: FormatConfig,
+}
+
+impl FormatConfig {
+    /// Parses the contents of a `.rholangrc.toml` file.
+    pub fn from_toml_str(contents: &str) -> Result<Self, String> {
+        toml::from_str::<RcFile>(contents)
+            .map(|rc| rc.format)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Loads the `[format]` section of `.rholangrc.toml` from the workspace root.
+    ///
+    /// A missing or invalid file yields the defaults.
+    pub fn load(root: &Path) -> Self {
+        let path = root.join(CONFIG_FILE_NAME);
+        let Ok(contents) = std::fs::read_to_string(&path) else {
+            return Self::default();
+        };
+        match Self::from_toml_str(&contents) {
+            Ok(config) => {
+                debug!("Loaded format settings from {}: {:?}", path.display(), config);
+                config
+            }
+            Err(e) => {
+                warn!("Ignoring invalid {}: {}", path.display(), e);
+                Self::default()
+            }
+        }
+    }
+
+    /// The options to format with when the editor's tab size is `tab_size`.
+    pub fn options(&self, tab_size: u32) -> FormatOptions {
+        FormatOptions {
+            indent_width: self.indent_width.unwrap_or(tab_size as usize).max(1),
+            brace_style: self.brace_style,
+            max_line_length: self.max_line_length,
+        }
+    }
+}
+
+/// Resolved layout options of one formatting request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FormatOptions {
+    pub indent_width: usize,
+    pub brace_style: BraceStyle,
+    pub max_line_length: Option<usize>,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        FormatConfig::default().options(2)
+    }
+}
+
+/// Formats the Rholang source `text`.
+///
+/// Returns `None` when the text has syntax errors or holds no code.
+pub fn format_document(text: &str, options: &FormatOptions) -> Option<String> {
+    let tree = parse_code(text);
+    if tree.root_node().has_error() {
+        debug!("Not formatting a document with syntax errors");
+        return None;
+    }
+    let rope = Rope::from_str(text);
+    let document = parse_to_document_ir(&tree, &rope);
+    let original = code_chars(text, &document.comments);
+    if original.is_empty() {
+        return None;
+    }
+
+    let printed = format_node(&document.root, true, Some(options.indent_width), &rope, &document.root);
+    let mut lines = Vec::new();
+    for line in printed.lines() {
+        for line in break_braces(line, options.brace_style) {
+            match options.max_line_length {
+                Some(max) => lines.extend(wrap_parallel(&line, max)),
+                None => lines.push(line),
+            }
+        }
+    }
+
+    let formatted = insert_comments(text, &document.comments, &original, &lines, options.indent_width);
+    if parse_code(&formatted).root_node().has_error() {
+        warn!("Formatted text does not parse; leaving the document unchanged");
+        return None;
+    }
+    Some(formatted)
+}
+
+/// An edit replacing all of `text` with `formatted`, or `None` if they are equal.
+/// Its end column counts units of `encoding`.
+pub fn whole_document_edit(text: &str, formatted: String, encoding: PositionEncoding) -> Option<TextEdit> {
+    if text == formatted {
+        return None;
+    }
+    let last_line = text.rsplit('\n').next().unwrap_or("");
+    let end = LspPosition::new(text.matches('\n').count() as u32, encoding.units_str(last_line) as u32);
+    Some(TextEdit { range: Range::new(LspPosition::new(0, 0), end), new_text: formatted })
+}
+
+/// Non-whitespace characters of `text` outside comments, with their line and byte offset
+fn code_chars(text: &str, comments: &[CommentNode]) -> Vec<(usize, usize, char)> {
+    let ranges: Vec<_> = comments.iter().map(comment_bytes).collect();
+    let mut row = 0;
+    let mut chars = Vec::new();
+    for (offset, c) in text.char_indices() {
+        if c == '\n' {
+            row += 1;
+        } else if !c.is_whitespace() && !ranges.iter().any(|range| range.contains(&offset)) {
+            chars.push((row, offset, c));
+        }
+    }
+    chars
+}
+
+fn comment_bytes(comment: &CommentNode) -> std::ops::Range<usize> {
+    let start = comment.base.start().byte;
+    start..start + comment.base.syntactic_length()
+}
+
+/// Moves the opening brace ending `line` to a line of its own for [`BraceStyle::NextLine`].
+fn break_braces(line: &str, style: BraceStyle) -> Vec<String> {
+    let header = line.trim_end().strip_suffix(" {");
+    match (style, header) {
+        (BraceStyle::NextLine, Some(header)) if !header.trim().is_empty() => {
+            let indent = &line[..line.len() - line.trim_start().len()];
+            match header.trim_start().strip_prefix("} ") {
+                Some(rest) => vec![format!("{}}}", indent), format!("{}{}", indent, rest), format!("{}{{", indent)],
+                None => vec![header.to_string(), format!("{}{{", indent)],
+            }
+        }
+        _ => vec![line.to_string()],
+    }
+}
+
+/// Splits `line` at the top-level `|` of a parallel composition if it is longer than `max`.
+fn wrap_parallel(line: &str, max: usize) -> Vec<String> {
+    if line.chars().count() <= max {
+        return vec![line.to_string()];
+    }
+    let indent = &line[..line.len() - line.trim_start().len()];
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut quote = None;
+    let mut start = indent.len();
+    let bytes = line.as_bytes();
+    for (offset, c) in line.char_indices().skip_while(|(offset, _)| *offset < indent.len()) {
+        match (quote, c) {
+            (Some(q), _) if c == q && bytes.get(offset.wrapping_sub(1)) != Some(&b'\\') => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '`') => quote = Some(c),
+            (None, '(' | '[' | '{') => depth += 1,
+            (None, ')' | ']' | '}') => depth -= 1,
+            (None, '|') if depth == 0 && line[offset..].starts_with("| ") && line[..offset].ends_with(' ') => {
+                parts.push(line[start..offset].trim_end());
+                start = offset + 2;
+            }
+            _ => {}
+        }
+    }
+    if parts.is_empty() {
+        return vec![line.to_string()];
+    }
+    parts.push(&line[start..]);
+
+    parts
+        .iter()
+        .enumerate()
+        .map(|(i, part)| if i == 0 { format!("{}{}", indent, part) } else { format!("{}| {}", indent, part) })
+        .collect()
+}
+
+/// Places each comment of `text` next to the same code in the formatted `lines`.
+///
+/// The code characters of both texts are aligned, skipping the parentheses
+/// the formatter adds around expressions. A comment written on its own line
+/// goes on its own line before the code that followed it; any other comment
+/// goes after the code that preceded it: at the end of its line for a line
+/// comment, right after that code for a block comment.
+fn insert_comments(
+    text: &str,
+    comments: &[CommentNode],
+    original: &[(usize, usize, char)],
+    lines: &[String],
+    indent_width: usize,
+) -> String {
+    let formatted: Vec<(usize, usize, char)> = lines
+        .iter()
+        .enumerate()
+        .flat_map(|(row, line)| {
+            line.char_indices()
+                .filter(|(_, c)| !c.is_whitespace())
+                .map(move |(offset, c)| (row, offset + c.len_utf8(), c))
+        })
+        .collect();
+    let aligned = align(original, &formatted);
+
+    let mut before: Vec<Vec<String>> = vec![Vec::new(); lines.len()];
+    let mut inline: Vec<Vec<(usize, String)>> = vec![Vec::new(); lines.len()];
+    let mut trailing: Vec<Vec<String>> = vec![Vec::new(); lines.len()];
+    let mut at_end = Vec::new();
+
+    for comment in comments {
+        let start = comment.base.start();
+        let Some(comment_text) = text.get(comment_bytes(comment)).map(str::trim_end) else { continue };
+        let preceding = original.partition_point(|(_, byte, _)| *byte < start.byte);
+        let own_line = preceding == 0 || original[preceding - 1].0 < start.row;
+
+        if own_line {
+            match aligned.get(preceding).and_then(|&j| formatted.get(j)) {
+                Some(&(row, _, _)) => {
+                    let line = &lines[row];
+                    let mut indent = line.len() - line.trim_start().len();
+                    if line.trim_start().starts_with('}') {
+                        indent += indent_width;
+                    }
+                    before[row].push(format!("{}{}", " ".repeat(indent), comment_text));
+                }
+                None => at_end.push(comment_text.to_string()),
+            }
+        } else {
+            let Some(&(row, after, _)) = formatted.get(aligned[preceding - 1].min(formatted.len().saturating_sub(1)))
+            else {
+                at_end.push(comment_text.to_string());
+                continue;
+            };
+            if comment_text.starts_with("//") {
+                trailing[row].push(comment_text.to_string());
+            } else {
+                inline[row].push((after, comment_text.to_string()));
+            }
+        }
+    }
+
+    let mut output = String::new();
+    for (row, line) in lines.iter().enumerate() {
+        for comment in &before[row] {
+            output.push_str(comment);
+            output.push('\n');
+        }
+        let mut line = line.clone();
+        for (after, comment) in inline[row].iter().rev() {
+            line.insert_str(*after, &format!(" {}", comment));
+        }
+        for comment in &trailing[row] {
+            line.push(' ');
+            line.push_str(comment);
+        }
+        output.push_str(line.trim_end());
+        output.push('\n');
+    }
+    for comment in at_end {
+        output.push_str(&comment);
+        output.push('\n');
+    }
+    output
+}
+
+/// For each code character of `original`, the index of the same character in `formatted`.
+///
+/// Parentheses present in only one of the texts are skipped; any other
+/// mismatch advances both. The result has one extra entry, `formatted.len()`.
+fn align(original: &[(usize, usize, char)], formatted: &[(usize, usize, char)]) -> Vec<usize> {
+    let mut aligned = Vec::with_capacity(original.len() + 1);
+    let mut j = 0;
+    for &(_, _, c) in original {
+        while j < formatted.len() && formatted[j].2 != c && matches!(formatted[j].2, '(' | ')') {
+            j += 1;
+        }
+        aligned.push(j);
+        if j < formatted.len() && (formatted[j].2 == c || !matches!(c, '(' | ')')) {
+            j += 1;
+        }
+    }
+    aligned.push(formatted.len());
+    aligned
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(text: &str, options: FormatOptions) -> String {
+        format_document(text, &options).expect("formattable")
+    }
+
+    #[test]
+    fn test_formats_with_indent_width_and_brace_style() {
+        let text = "new x in { x!(1) }";
+        assert_eq!(format(text, FormatOptions::default()), "new x in {\n  x!(1)\n}\n");
+        assert_eq!(
+            format("new x in { for (y <- x) { x!(*y, \"a\\\"b\") } }", FormatOptions::default()),
+            "new x in {\n  for (y <- x) {\n    x!(*y, \"a\\\"b\")\n  }\n}\n"
+        );
+
+        let options = FormatOptions { indent_width: 4, brace_style: BraceStyle::NextLine, max_line_length: None };
+        assert_eq!(format(text, options), "new x in\n{\n    x!(1)\n}\n");
+        assert_eq!(
+            format("if (true) { Nil } else { Nil }", options),
+            "if (true)\n{\n    Nil\n}\nelse\n{\n    Nil\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_splits_long_parallel_compositions() {
+        let options = FormatOptions { max_line_length: Some(20), ..Default::default() };
+        assert_eq!(format("a!(\"x | y\") | b!(1) | c!(2)", options), "a!(\"x | y\")\n| b!(1)\n| c!(2)\n");
+        assert_eq!(format("a!(1) | b!(2)", FormatOptions::default()), "a!(1) | b!(2)\n");
+    }
+
+    #[test]
+    fn test_preserves_comments() {
+        let text = "// Counter\nnew x in {\n  // Send one\n     x!(1)   // trailing\n  /* end */\n}\n";
+        assert_eq!(
+            format(text, FormatOptions::default()),
+            "// Counter\nnew x in {\n  // Send one\n  x!(1) // trailing\n  /* end */\n}\n"
+        );
+        assert_eq!(format("x!(1 /* one */, 2)", FormatOptions::default()), "x!(1 /* one */, 2)\n");
+    }
+
+    #[test]
+    fn test_unformattable_documents() {
+        assert_eq!(format_document("new x in {", &FormatOptions::default()), None);
+        assert_eq!(format_document("// only a comment\n", &FormatOptions::default()), None);
+
+        let edit = whole_document_edit("x!(1)\n\n", "x!(1)\n".to_string(), PositionEncoding::Utf16).unwrap();
+        assert_eq!(edit.range, Range::new(LspPosition::new(0, 0), LspPosition::new(2, 0)));
+        assert_eq!(whole_document_edit("x!(1)\n", "x!(1)\n".to_string(), PositionEncoding::Utf16), None);
+
+        // The last line ends after one UTF-16 unit but two UTF-8 bytes
+        let end = |encoding| whole_document_edit("x!(1)\né", "x!(1)\n".to_string(), encoding).unwrap().range.end;
+        assert_eq!(end(PositionEncoding::Utf16), LspPosition::new(1, 1));
+        assert_eq!(end(PositionEncoding::Utf8), LspPosition::new(1, 2));
+    }
+
+    #[test]
+    fn test_format_config() {
+        let config = FormatConfig::from_toml_str(
+            "[lints.naming]\ncontracts = \"camelCase\"\n\n[format]\nbrace_style = \"next_line\"\nmax_line_length = 80\n",
+        )
+        .unwrap();
+        assert_eq!(
+            config.options(4),
+            FormatOptions { indent_width: 4, brace_style: BraceStyle::NextLine, max_line_length: Some(80) }
+        );
+        assert_eq!(FormatConfig::from_toml_str("[format]\nindent_width = 3\n").unwrap().options(8).indent_width, 3);
+    }
+}
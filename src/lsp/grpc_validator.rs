@@ -2,9 +2,34 @@
 //!
 //! This module provides a DiagnosticProvider implementation that communicates
 //! with a legacy RNode server (Scala implementation) or Docker container via gRPC.
+//!
+//! RNode places its errors in its own coordinates: the `line:column`
+//! positions in its messages (`Variable reference: =x at 2:7 is unbound.`)
+//! count from 1, the ranges of its diagnostics count from 0, and both count
+//! columns in characters where LSP counts the code units of the negotiated
+//! position encoding. Those ranges are
+//! often a point or a whole line. `SourceText` translates them through the
+//! rope of the validated text:
+//!
+//! - the first position in the message, or else the range of the
+//!   diagnostic, places it; a `line:column-line:column` span in the message
+//!   or a range RNode gives is kept as it is
+//! - a point covers the token it starts, and a whole line its text without
+//!   the indentation
+//! - a position inside a syntax error tree-sitter found covers that error, so
+//!   both parsers underline the same code
+//! - further positions in the message become related information
+//!
+//! Diagnostics that end up identical are reported once.
 
-use super::diagnostic_provider::DiagnosticProvider;
-use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, Position, Range};
+use std::ops::Range as ByteRange;
+use std::sync::Arc;
+
+use super::diagnostic_provider::{DiagnosticProvider, RNODE_ERROR_CODE, VALIDATOR_UNAVAILABLE_CODE};
+use crate::lsp::diagnostic_registry::related_here;
+use crate::lsp::line_index::{LineIndex, PositionEncoding};
+use ropey::Rope;
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Position, Range};
 use tonic::transport::Channel;
 use tracing::{debug, warn};
 
@@ -28,6 +53,8 @@ use proto::{
 pub struct GrpcValidator {
     client: LspClient<Channel>,
     address: String,
+    /// Position encoding negotiated with the client
+    encoding: Arc<parking_lot::RwLock<PositionEncoding>>,
 }
 
 impl GrpcValidator {
@@ -53,24 +80,22 @@ impl GrpcValidator {
         Ok(Self {
             client,
             address,
+            encoding: Arc::default(),
         })
     }
 
-    /// Convert protobuf diagnostic to LSP diagnostic
-    fn convert_diagnostic(diag: proto::Diagnostic) -> Diagnostic {
-        let range = diag.range.map(|r| {
-            let start = r.start.map(|p| Position {
-                line: p.line as u32,
-                character: p.column as u32,
-            }).unwrap_or_default();
-
-            let end = r.end.map(|p| Position {
-                line: p.line as u32,
-                character: p.column as u32,
-            }).unwrap_or_default();
-
-            Range { start, end }
-        }).unwrap_or_default();
+    /// Convert protobuf diagnostic to LSP diagnostic, with code [`RNODE_ERROR_CODE`],
+    /// placed in `source` (see the module documentation)
+    fn convert_diagnostic(diag: proto::Diagnostic, source: &SourceText) -> Diagnostic {
+        let mut positions = message_positions(&diag.message).into_iter();
+        let range = match positions.next() {
+            Some(position) => source.span_range(position),
+            None => diag.range.map_or_else(Range::default, |r| {
+                let point = |p: Option<proto::Position>| p.map_or((0, 0), |p| (p.line as usize, p.column as usize));
+                source.rnode_range(point(r.start), point(r.end))
+            }),
+        };
+        let related: Vec<_> = positions.map(|position| related_here(source.span_range(position), "mentioned here")).collect();
 
         let severity = match proto::DiagnosticSeverity::try_from(diag.severity) {
             Ok(proto::DiagnosticSeverity::Error) => Some(DiagnosticSeverity::ERROR),
@@ -86,17 +111,167 @@ impl GrpcValidator {
         Diagnostic {
             range,
             severity,
+            code: Some(NumberOrString::String(RNODE_ERROR_CODE.to_string())),
             source: if diag.source.is_empty() {
                 Some("rnode-grpc".to_string())
             } else {
                 Some(diag.source)
             },
             message: diag.message,
+            related_information: if related.is_empty() { None } else { Some(related) },
             ..Default::default()
         }
     }
 }
 
+/// A position in an RNode message, 0-based, with the end of a
+/// `line:column-line:column` span
+type MessagePosition = ((usize, usize), Option<(usize, usize)>);
+
+/// The `line:column` positions in an RNode message, in order.
+fn message_positions(message: &str) -> Vec<MessagePosition> {
+    let mut positions = Vec::new();
+    let mut rest = message;
+    while let Some(digit) = rest.find(|c: char| c.is_ascii_digit()) {
+        let preceded_by_word = rest[..digit].chars().next_back().is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '.');
+        let parsed = if preceded_by_word { None } else { parse_position(&rest[digit..]) };
+        match parsed {
+            Some((start, after)) => {
+                let (end, after) = match after.strip_prefix('-').and_then(parse_position) {
+                    Some((end, after)) => (Some(end), after),
+                    None => (None, after),
+                };
+                positions.push((start, end));
+                rest = after;
+            }
+            None => {
+                let number = &rest[digit..];
+                rest = &number[number.find(|c: char| !c.is_ascii_digit()).unwrap_or(number.len())..];
+            }
+        }
+    }
+    positions
+}
+
+/// The 1-based `line:column` at the start of `text` as a 0-based position,
+/// and the text after it.
+fn parse_position(text: &str) -> Option<((usize, usize), &str)> {
+    let (line, rest) = parse_number(text)?;
+    let (column, rest) = parse_number(rest.strip_prefix(':')?)?;
+    if rest.starts_with(|c: char| c == ':' || c.is_ascii_digit()) {
+        return None;
+    }
+    Some(((line.checked_sub(1)?, column.checked_sub(1)?), rest))
+}
+
+fn parse_number(text: &str) -> Option<(usize, &str)> {
+    let end = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+    Some((text[..end].parse().ok()?, &text[end..]))
+}
+
+/// The text RNode validated, for translating its positions
+struct SourceText<'a> {
+    text: &'a str,
+    rope: Rope,
+    lines: LineIndex,
+    tree: tree_sitter::Tree,
+    /// Code units of the columns of translated positions
+    encoding: PositionEncoding,
+}
+
+impl<'a> SourceText<'a> {
+    fn new(text: &'a str, encoding: PositionEncoding) -> Self {
+        let rope = Rope::from_str(text);
+        let lines = LineIndex::from_rope(&rope);
+        SourceText { text, rope, lines, tree: crate::tree_sitter::parse_code(text), encoding }
+    }
+
+    /// Byte offset of the 0-based `line` and character `column`, clamped to
+    /// the line and to the text.
+    fn offset(&self, (line, column): (usize, usize)) -> usize {
+        let line = line.min(self.lines.line_count() - 1);
+        self.lines.offset(line, column, &self.rope).unwrap_or(self.text.len())
+    }
+
+    fn lsp_position(&self, byte: usize) -> Position {
+        let line = self.lines.line_of_byte(byte);
+        let start = self.lines.line_start(line).unwrap_or(0);
+        let character: usize = self.rope.byte_slice(start..byte).chars().map(|c| self.encoding.units(c)).sum();
+        Position { line: line as u32, character: character as u32 }
+    }
+
+    fn lsp_range(&self, bytes: ByteRange<usize>) -> Range {
+        Range { start: self.lsp_position(bytes.start), end: self.lsp_position(bytes.end) }
+    }
+
+    /// Range of a position in a message: its span, or the token it starts.
+    fn span_range(&self, (start, end): MessagePosition) -> Range {
+        let start = self.offset(start);
+        match end.map(|end| self.offset(end)) {
+            Some(end) if end > start => self.lsp_range(start..end),
+            _ => self.lsp_range(self.token_at(start)),
+        }
+    }
+
+    /// Range of a diagnostic RNode placed from 0-based `start` to `end`.
+    fn rnode_range(&self, start: (usize, usize), end: (usize, usize)) -> Range {
+        let (start_byte, end_byte) = (self.offset(start), self.offset(end));
+        let line = self.lines.line_of_byte(start_byte);
+        let line_bytes = self.line_content(line);
+        let whole_line = start.1 == 0
+            && (end == (start.0 + 1, 0) || (end.0 == start.0 && end_byte >= line_bytes.end));
+        if whole_line {
+            self.lsp_range(self.trimmed(line_bytes))
+        } else if end_byte > start_byte {
+            self.lsp_range(start_byte..end_byte)
+        } else {
+            self.lsp_range(self.token_at(start_byte))
+        }
+    }
+
+    /// Bytes of `line` without its terminator.
+    fn line_content(&self, line: usize) -> ByteRange<usize> {
+        let start = self.lines.line_start(line).unwrap_or(self.text.len());
+        let end = self.lines.line_end(line).unwrap_or(self.text.len());
+        start..start + self.text[start..end].trim_end_matches(['\n', '\r']).len()
+    }
+
+    fn trimmed(&self, bytes: ByteRange<usize>) -> ByteRange<usize> {
+        let text = &self.text[bytes.clone()];
+        let start = bytes.start + (text.len() - text.trim_start().len());
+        start..start.max(bytes.start + text.trim_end().len())
+    }
+
+    /// Bytes of the token starting at `byte`: the syntax error tree-sitter
+    /// found around it, the single-line node it starts, or else the word or
+    /// character there.
+    fn token_at(&self, byte: usize) -> ByteRange<usize> {
+        let root = self.tree.root_node();
+        let mut node = root.descendant_for_byte_range(byte, byte);
+        while let Some(current) = node {
+            if current.is_error() {
+                return current.byte_range();
+            }
+            node = current.parent();
+        }
+        if let Some(named) = root.named_descendant_for_byte_range(byte, byte) {
+            let single_line = named.start_position().row == named.end_position().row;
+            if named.start_byte() == byte && named.end_byte() > byte && single_line && named.id() != root.id() {
+                return named.byte_range();
+            }
+        }
+        let rest = &self.text[byte..];
+        let word: usize =
+            rest.chars().take_while(|c| c.is_alphanumeric() || *c == '_').map(char::len_utf8).sum();
+        let len = if word > 0 {
+            word
+        } else {
+            rest.chars().next().filter(|c| !matches!(c, '\n' | '\r')).map_or(0, char::len_utf8)
+        };
+        byte..byte + len
+    }
+}
+
 #[async_trait::async_trait]
 impl DiagnosticProvider for GrpcValidator {
     async fn validate(&self, source: &str) -> Vec<Diagnostic> {
@@ -116,10 +291,18 @@ impl DiagnosticProvider for GrpcValidator {
                 match response.result {
                     Some(proto::validate_response::Result::Success(diag_list)) => {
                         debug!("Validation succeeded with {} diagnostics", diag_list.diagnostics.len());
-                        diag_list.diagnostics
-                            .into_iter()
-                            .map(Self::convert_diagnostic)
-                            .collect()
+                        if diag_list.diagnostics.is_empty() {
+                            return Vec::new();
+                        }
+                        let source = SourceText::new(source, *self.encoding.read());
+                        let mut diagnostics: Vec<Diagnostic> = Vec::new();
+                        for diag in diag_list.diagnostics {
+                            let diagnostic = Self::convert_diagnostic(diag, &source);
+                            if !diagnostics.contains(&diagnostic) {
+                                diagnostics.push(diagnostic);
+                            }
+                        }
+                        diagnostics
                     }
                     Some(proto::validate_response::Result::Error(error_msg)) => {
                         warn!("Validation failed with error: {}", error_msg);
@@ -127,6 +310,7 @@ impl DiagnosticProvider for GrpcValidator {
                         vec![Diagnostic {
                             range: Range::default(),
                             severity: Some(DiagnosticSeverity::ERROR),
+                            code: Some(NumberOrString::String(VALIDATOR_UNAVAILABLE_CODE.to_string())),
                             source: Some("rnode-grpc".to_string()),
                             message: error_msg,
                             ..Default::default()
@@ -144,6 +328,7 @@ impl DiagnosticProvider for GrpcValidator {
                 vec![Diagnostic {
                     range: Range::default(),
                     severity: Some(DiagnosticSeverity::ERROR),
+                    code: Some(NumberOrString::String(VALIDATOR_UNAVAILABLE_CODE.to_string())),
                     source: Some("rnode-grpc".to_string()),
                     message: format!("Failed to validate via gRPC: {}", e),
                     ..Default::default()
@@ -155,4 +340,90 @@ impl DiagnosticProvider for GrpcValidator {
     fn backend_name(&self) -> &'static str {
         "RNode gRPC"
     }
+
+    fn verifies_with_rnode(&self) -> bool {
+        true
+    }
+
+    fn set_position_encoding(&self, encoding: PositionEncoding) {
+        *self.encoding.write() = encoding;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rnode_diagnostic(message: &str, start: (u64, u64), end: (u64, u64)) -> proto::Diagnostic {
+        let position = |(line, column)| Some(proto::Position { line, column });
+        proto::Diagnostic {
+            range: Some(proto::Range { start: position(start), end: position(end) }),
+            severity: proto::DiagnosticSeverity::Error as i32,
+            source: String::new(),
+            message: message.to_string(),
+        }
+    }
+
+    fn range(start: (u32, u32), end: (u32, u32)) -> Range {
+        Range { start: Position::new(start.0, start.1), end: Position::new(end.0, end.1) }
+    }
+
+    #[test]
+    fn test_message_positions() {
+        assert_eq!(message_positions("Variable reference: =x at 2:7 is unbound."), vec![((1, 6), None)]);
+        assert_eq!(
+            message_positions("Free variable x is used twice as a binder (at 1:14 and 3:2) in name context."),
+            vec![((0, 13), None), ((2, 1), None)]
+        );
+        assert_eq!(message_positions("syntax error at 1:5-1:9"), vec![((0, 4), Some((0, 8)))]);
+        assert!(message_positions("expected 2 arguments, got 3 (v1.2:3 at 12:00:01)").is_empty());
+    }
+
+    #[test]
+    fn test_message_positions_count_characters_and_utf16() {
+        let text = "new x in {\n  @\"🦀\"!(x)\n}";
+        let convert = |encoding| {
+            let source = SourceText::new(text, encoding);
+            GrpcValidator::convert_diagnostic(
+                rnode_diagnostic("Name variable: x at 2:9 used in process context.", (0, 0), (0, 0)),
+                &source,
+            )
+        };
+        let diagnostic = convert(PositionEncoding::Utf16);
+        // `x` is the 9th character of its line, after the crab's two UTF-16 code units
+        assert_eq!(diagnostic.range, range((1, 9), (1, 10)));
+        assert_eq!(diagnostic.code, Some(NumberOrString::String(RNODE_ERROR_CODE.to_string())));
+        // or its four UTF-8 bytes
+        assert_eq!(convert(PositionEncoding::Utf8).range, range((1, 11), (1, 12)));
+    }
+
+    #[test]
+    fn test_rnode_ranges() {
+        let text = "new x in {\n    x!(1) | stdout!(\"🦀\") | y!(2)\n}";
+        let source = SourceText::new(text, PositionEncoding::Utf16);
+        let convert = |start, end| GrpcValidator::convert_diagnostic(rnode_diagnostic("error", start, end), &source).range;
+
+        // A point covers its token, in UTF-16 code units after the crab
+        assert_eq!(convert((1, 12), (1, 12)), range((1, 12), (1, 18)));
+        assert_eq!(convert((1, 27), (1, 27)), range((1, 28), (1, 29)));
+        // A whole line covers its text
+        assert_eq!(convert((1, 0), (2, 0)), range((1, 4), (1, 33)));
+        assert_eq!(convert((1, 0), (1, 40)), range((1, 4), (1, 33)));
+        // Other ranges are kept
+        assert_eq!(convert((1, 4), (1, 9)), range((1, 4), (1, 9)));
+    }
+
+    #[test]
+    fn test_further_positions_are_related() {
+        let text = "for (@x, @x <- ch) { Nil }";
+        let source = SourceText::new(text, PositionEncoding::Utf16);
+        let diagnostic = GrpcValidator::convert_diagnostic(
+            rnode_diagnostic("Free variable x is used twice as a binder (at 1:7 and 1:11) in name context.", (0, 0), (0, 0)),
+            &source,
+        );
+        assert_eq!(diagnostic.range, range((0, 6), (0, 7)));
+        let related = diagnostic.related_information.unwrap();
+        assert_eq!(related.len(), 1);
+        assert_eq!(related[0].location.range, range((0, 10), (0, 11)));
+    }
 }
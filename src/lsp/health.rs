@@ -0,0 +1,37 @@
+//! Server health for the `rholang/health` custom request
+//!
+//! Lets the client show which validator is checking documents and how much
+//! of the workspace is loaded, e.g. in a status bar item. The request takes
+//! no parameters.
+//!
+//! # Response
+//!
+//! ```json
+//! { "validator": "Rust Interpreter",
+//!   "rnodeValidation": { "enabled": false, "address": "localhost:40402" },
+//!   "openDocuments": 2, "indexedDocuments": 57 }
+//! ```
+//!
+//! `validator` is `"syntax only"` when a server started against RNode has
+//! RNode validation switched off (see `crate::lsp::rnode_validation`).
+
+use serde::{Deserialize, Serialize};
+
+use crate::lsp::rnode_validation::RnodeValidationStatus;
+
+/// Custom request method name
+pub const HEALTH_METHOD: &str = "rholang/health";
+
+/// `validator` when only syntax is checked
+pub const SYNTAX_ONLY_VALIDATOR: &str = "syntax only";
+
+/// Response of `rholang/health`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthReport {
+    /// Backend producing semantic diagnostics
+    pub validator: String,
+    pub rnode_validation: RnodeValidationStatus,
+    pub open_documents: usize,
+    pub indexed_documents: usize,
+}
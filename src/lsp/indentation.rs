@@ -0,0 +1,196 @@
+//! Indentation of Rholang documents from the `indents.scm` query
+//!
+//! `textDocument/rangeFormatting` and `textDocument/onTypeFormatting` only
+//! re-indent lines; they never reflow code the way whole-document formatting
+//! does (see `crate::lsp::formatting`), so they are safe on half-typed text.
+//!
+//! The expected indentation of a line is the number of distinct lines on
+//! which the `@indent` nodes enclosing its first token start: `new x in {`
+//! opens both a `new` and a `block`, but indents once. A line starting with
+//! a closing bracket captured as `@dedent` is indented one level less, to
+//! line up with the line that opened it. Blank lines, and lines that start
+//! inside a multi-line string or comment, are left alone.
+
+use std::collections::{BTreeSet, HashSet};
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use tower_lsp::lsp_types::{Position as LspPosition, Range, TextEdit};
+use tracing::warn;
+use tree_sitter::Tree;
+
+use crate::lsp::features::tree_sitter::query_types::IndentType;
+use crate::lsp::features::tree_sitter::{CaptureType, QueryEngine, QueryType};
+
+/// Query engine with only `indents.scm` loaded; `None` if the query does not compile
+static INDENTS: Lazy<Option<Mutex<QueryEngine>>> = Lazy::new(|| {
+    let engine = QueryEngine::new("rholang", rholang_tree_sitter::LANGUAGE.into()).and_then(|mut engine| {
+        engine.load_query(QueryType::Indents, include_str!("../../queries/rholang/indents.scm"))?;
+        Ok(engine)
+    });
+    match engine {
+        Ok(engine) => Some(Mutex::new(engine)),
+        Err(e) => {
+            warn!("Indentation query unavailable: {}", e);
+            None
+        }
+    }
+});
+
+/// Characters that trigger on-type formatting, besides the first one (`}`)
+pub const MORE_TRIGGER_CHARACTERS: [&str; 2] = [")", "\n"];
+
+/// Indentation rules of one parsed document
+pub struct Indentation<'a> {
+    tree: &'a Tree,
+    source: &'a str,
+    /// Byte offset of the start of each line
+    line_starts: Vec<usize>,
+    /// Start row and end byte of each `@indent` node
+    indents: Vec<(usize, usize)>,
+    /// Start bytes of the closing brackets captured as `@dedent`
+    dedents: HashSet<usize>,
+}
+
+impl<'a> Indentation<'a> {
+    /// Runs the indentation query over `tree`, parsed from `source`.
+    pub fn new(tree: &'a Tree, source: &'a str) -> Self {
+        let mut indents = Vec::new();
+        let mut dedents = HashSet::new();
+        let captures = match INDENTS.as_ref() {
+            Some(engine) => engine.lock().execute(tree, QueryType::Indents, source.as_bytes()).unwrap_or_else(|e| {
+                warn!("Indentation query failed: {}", e);
+                Vec::new()
+            }),
+            None => Vec::new(),
+        };
+        for capture in &captures {
+            match capture.capture_type {
+                CaptureType::Indent(IndentType::Indent) => {
+                    indents.push((capture.node.start_position().row, capture.node.end_byte()));
+                }
+                CaptureType::Indent(IndentType::Outdent) if matches!(capture.node.kind(), "}" | "]" | ")") => {
+                    dedents.insert(capture.node.start_byte());
+                }
+                _ => {}
+            }
+        }
+
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(offset, _)| offset + 1))
+            .collect();
+        Indentation { tree, source, line_starts, indents, dedents }
+    }
+
+    /// Expected indentation level of line `row`, whose first token starts at byte `first`.
+    pub fn level(&self, row: usize, first: usize) -> usize {
+        let opening_rows: BTreeSet<usize> = self
+            .indents
+            .iter()
+            .filter(|(start_row, end)| *start_row < row && *end > first)
+            .map(|(start_row, _)| *start_row)
+            .collect();
+        opening_rows.len().saturating_sub(usize::from(self.dedents.contains(&first)))
+    }
+
+    /// Edits re-indenting the lines of `range` with `unit` per level.
+    pub fn range_edits(&self, range: Range, unit: &str) -> Vec<TextEdit> {
+        let mut last = range.end.line as usize;
+        if range.end.character == 0 && range.end.line > range.start.line {
+            last -= 1;
+        }
+        (range.start.line as usize..=last)
+            .filter_map(|row| self.reindent(row, unit, false))
+            .collect()
+    }
+
+    /// Edits re-indenting the line of `position` after `ch` was typed there.
+    ///
+    /// A newline indents the new line, even if it is blank; a closing `}` or
+    /// `)` re-indents its line if it is the first character on it.
+    pub fn on_type_edits(&self, position: LspPosition, ch: &str, unit: &str) -> Vec<TextEdit> {
+        let row = position.line as usize;
+        let edit = match ch {
+            "\n" => self.reindent(row, unit, true),
+            "}" | ")" => {
+                let line = self.line(row).unwrap_or("");
+                let indent = line.chars().take_while(|c| *c == ' ' || *c == '\t').count();
+                if position.character as usize == indent + 1 {
+                    self.reindent(row, unit, false)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        };
+        edit.into_iter().collect()
+    }
+
+    fn line(&self, row: usize) -> Option<&'a str> {
+        let start = *self.line_starts.get(row)?;
+        let end = self.line_starts.get(row + 1).map_or(self.source.len(), |next| next - 1);
+        Some(self.source[start..end].trim_end_matches('\r'))
+    }
+
+    fn reindent(&self, row: usize, unit: &str, blank: bool) -> Option<TextEdit> {
+        let line = self.line(row)?;
+        let indent = &line[..line.len() - line.trim_start_matches([' ', '\t']).len()];
+        if indent.len() == line.len() && !blank {
+            return None;
+        }
+        let first = self.line_starts[row] + indent.len();
+
+        // The first character continues a token from an earlier line
+        let continued = self
+            .tree
+            .root_node()
+            .descendant_for_byte_range(first, first)
+            .is_some_and(|node| node.child_count() == 0 && node.start_position().row < row);
+        if continued {
+            return None;
+        }
+
+        let expected = unit.repeat(self.level(row, first));
+        (indent != expected).then(|| TextEdit {
+            range: Range::new(LspPosition::new(row as u32, 0), LspPosition::new(row as u32, indent.len() as u32)),
+            new_text: expected,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree_sitter::parse_code;
+
+    fn edit(row: u32, from: u32, to: u32, text: &str) -> TextEdit {
+        TextEdit { range: Range::new(LspPosition::new(row, from), LspPosition::new(row, to)), new_text: text.to_string() }
+    }
+
+    #[test]
+    fn test_range_edits_follow_nesting() {
+        let source = "new x in {\nx!(1) |\n      for (y <- x) {\n y!(2)\n  }\n}\n";
+        let tree = parse_code(source);
+        let indentation = Indentation::new(&tree, source);
+        let range = Range::new(LspPosition::new(1, 0), LspPosition::new(5, 0));
+        assert_eq!(
+            indentation.range_edits(range, "  "),
+            vec![edit(1, 0, 0, "  "), edit(2, 0, 6, "  "), edit(3, 0, 1, "    ")]
+        );
+        assert_eq!(indentation.range_edits(Range::new(LspPosition::new(5, 0), LspPosition::new(5, 1)), "  "), vec![]);
+    }
+
+    #[test]
+    fn test_on_type_edits() {
+        let source = "new x in {\n\n}";
+        let tree = parse_code(source);
+        let indentation = Indentation::new(&tree, source);
+        assert_eq!(indentation.on_type_edits(LspPosition::new(1, 0), "\n", "\t"), vec![edit(1, 0, 0, "\t")]);
+
+        let source = "new x in {\n  x!(1)\n  }";
+        let tree = parse_code(source);
+        let indentation = Indentation::new(&tree, source);
+        assert_eq!(indentation.on_type_edits(LspPosition::new(2, 3), "}", "  "), vec![edit(2, 0, 2, "")]);
+        assert_eq!(indentation.on_type_edits(LspPosition::new(1, 7), ")", "  "), vec![]);
+    }
+}
@@ -0,0 +1,500 @@
+//! Resumable workspace indexing
+//!
+//! Indexing every file of a very large workspace takes long enough that a
+//! restart in the middle of it (a crash, an editor reload) is costly if it
+//! starts over. With the `indexCheckpoints` initialization option, each file
+//! indexed from a workspace folder leaves a completion marker in a checkpoint
+//! under the user cache directory, one per folder:
+//!
+//! ```json
+//! { "indexCheckpoints": true }
+//! ```
+//!
+//! A marker is one JSON line holding the file's modification time and length
+//! and the index-only summary of it, its contract declarations and send
+//! sites (a [`DiskArtifact`]). It is appended as soon as the file is indexed,
+//! so a checkpoint is valid at every point; a line cut short by a crash is
+//! skipped. Once indexing completes, the checkpoint is rewritten without
+//! superseded lines and files that no longer exist.
+//!
+//! When indexing starts again, files whose marker matches the file on disk
+//! are not indexed: their summaries answer `workspace/symbol` and references
+//! until they are opened or change, and they count as done in the progress
+//! reported to the client. A marker matches when the file's stamp is the
+//! one recorded or, failing that, when the hash of its contents is: a file
+//! touched without changes, renamed or copied within the folder reuses the
+//! summary of the contents recorded earlier, under its own URI, and gets a
+//! marker with its new stamp. Contents are hashed with Blake2b-256, which
+//! gives the same hash in every build, unlike the standard library's hasher.
+//!
+//! Ranges in markers count columns in the position encoding negotiated with
+//! the client, so each encoding has a checkpoint file of its own.
+//!
+//! Markers do not hold the IR: `RholangNode` trees are not serializable, so
+//! a resumed file is parsed once it is opened. The summary is what
+//! workspace-wide requests need from the files that are not.
+//!
+//! A checkpoint starts with the header of [`CHECKPOINT_SCHEMA`]; one written
+//! by an older release is migrated or, if it cannot be, discarded and indexing
+//! starts over (see `crate::lsp::cache_schema`).
+
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use blake2::digest::consts::U32;
+use blake2::{Blake2b, Digest};
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{Range, SymbolInformation, Url};
+use tracing::{debug, warn};
+
+use crate::lsp::cache_schema::{clear_dir, CacheSchema, SchemaCheck};
+use crate::lsp::disk_index::{DiskArtifact, FileStamp};
+use crate::lsp::line_index::PositionEncoding;
+use crate::lsp::symbol_index::SymbolIndex;
+use crate::lsp::text_normalization::normalize;
+
+/// Initialization option turning checkpoints on
+pub const INDEX_CHECKPOINTS_OPTION: &str = "indexCheckpoints";
+
+/// Format of checkpoint files; bump `version` when [`Marker`] changes
+pub const CHECKPOINT_SCHEMA: CacheSchema = CacheSchema {
+    name: "index-checkpoint",
+    version: 1,
+    migrations: &[],
+    first_migrated: 1,
+};
+
+type Blake2b256 = Blake2b<U32>;
+
+/// Hex-encoded Blake2b-256 hash of `bytes`.
+fn stable_hash(bytes: &[u8]) -> String {
+    hex::encode(Blake2b256::digest(bytes))
+}
+
+/// Hash of the normalized contents of the file at `path`.
+fn file_hash(path: &Path) -> Option<String> {
+    let text = fs::read_to_string(path).ok()?;
+    Some(stable_hash(normalize(&text).as_bytes()))
+}
+
+/// Whether the client asked for resumable indexing.
+pub fn enabled_in(init_options: Option<&serde_json::Value>) -> bool {
+    init_options
+        .and_then(|opts| opts.get(INDEX_CHECKPOINTS_OPTION))
+        .and_then(|enabled| enabled.as_bool())
+        .unwrap_or(false)
+}
+
+/// Directory holding the checkpoints, under the user cache directory.
+pub fn default_checkpoint_dir() -> Option<PathBuf> {
+    let mut dir = dirs::cache_dir()?;
+    dir.push("f1r3fly-io");
+    dir.push("rholang-language-server");
+    dir.push("checkpoints");
+    Some(dir)
+}
+
+/// Percentage of `total` files done, for progress reports.
+pub fn percentage(completed: usize, total: usize) -> u32 {
+    if total == 0 {
+        return 100;
+    }
+    (completed.min(total) * 100 / total) as u32
+}
+
+/// Completion marker of one file
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct Marker {
+    path: PathBuf,
+    stamp: FileStamp,
+    /// Hash of the contents the summary was made from
+    content_hash: Option<String>,
+    symbols: Vec<SymbolInformation>,
+    calls: HashMap<String, Vec<Range>>,
+}
+
+impl Marker {
+    /// The summary, with its locations in the file at `path`.
+    fn artifact_at(&self, path: &Path) -> Option<DiskArtifact> {
+        let uri = Url::from_file_path(path).ok()?;
+        let symbols = self
+            .symbols
+            .iter()
+            .cloned()
+            .map(|mut symbol| {
+                symbol.location.uri = uri.clone();
+                symbol
+            })
+            .collect();
+        Some(DiskArtifact { uri, symbols: SymbolIndex::new(symbols), calls: self.calls.clone() })
+    }
+}
+
+/// Checkpoint of the indexing of one workspace folder
+#[derive(Debug)]
+pub struct IndexCheckpoint {
+    root: PathBuf,
+    file: PathBuf,
+    markers: HashMap<PathBuf, Marker>,
+    /// Files by the hash of their recorded contents
+    by_hash: HashMap<String, PathBuf>,
+    writer: Option<BufWriter<File>>,
+}
+
+impl IndexCheckpoint {
+    /// Opens the checkpoint of `root` in `dir` for sessions using `encoding`,
+    /// reading the markers left by earlier ones.
+    pub fn open(dir: &Path, root: &Path, encoding: PositionEncoding) -> Self {
+        let suffix = match encoding {
+            PositionEncoding::Utf8 => ".utf-8",
+            PositionEncoding::Utf16 => "",
+        };
+        let file = dir.join(format!("{}{}.jsonl", &stable_hash(root.as_os_str().as_encoded_bytes())[..32], suffix));
+
+        let mut markers = HashMap::new();
+        let mut migrated = false;
+        if let Ok(existing) = File::open(&file) {
+            let mut lines = BufReader::new(existing).lines().map_while(Result::ok);
+            let check = CHECKPOINT_SCHEMA.check(lines.next().as_deref());
+            match check {
+                SchemaCheck::Invalid(reason) => {
+                    warn!("Discarding the checkpoint of {}: {}", root.display(), reason);
+                    if let Err(e) = fs::remove_file(&file) {
+                        warn!("Failed to remove {}: {}", file.display(), e);
+                    }
+                }
+                SchemaCheck::Current | SchemaCheck::Migrate(_) => {
+                    for line in lines {
+                        let marker = match check {
+                            SchemaCheck::Migrate(version) => serde_json::from_str(&line)
+                                .ok()
+                                .and_then(|record| CHECKPOINT_SCHEMA.migrate(record, version))
+                                .and_then(|record| serde_json::from_value::<Marker>(record).ok())
+                                .ok_or_else(|| format!("not migrated from version {}", version)),
+                            _ => serde_json::from_str::<Marker>(&line).map_err(|e| e.to_string()),
+                        };
+                        match marker {
+                            Ok(marker) => {
+                                markers.insert(marker.path.clone(), marker);
+                            }
+                            Err(e) => debug!("Skipping checkpoint line in {}: {}", file.display(), e),
+                        }
+                    }
+                    migrated = matches!(check, SchemaCheck::Migrate(_));
+                }
+            }
+        }
+        debug!("Opened checkpoint of {} with {} marker(s)", root.display(), markers.len());
+        let by_hash = markers
+            .values()
+            .filter_map(|marker| Some((marker.content_hash.clone()?, marker.path.clone())))
+            .collect();
+        let mut checkpoint = IndexCheckpoint { root: root.to_path_buf(), file, markers, by_hash, writer: None };
+        if migrated {
+            // Later markers are appended in the current format
+            if let Err(e) = checkpoint.compact() {
+                warn!("Failed to rewrite the migrated checkpoint of {}: {}", root.display(), e);
+            }
+        }
+        checkpoint
+    }
+
+    /// The workspace folder this checkpoint is for
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Number of files with a marker
+    pub fn len(&self) -> usize {
+        self.markers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.markers.is_empty()
+    }
+
+    /// The summary of `path` and its stamp, if a marker matches the file
+    /// on disk: its own marker by stamp, or any marker by content hash.
+    pub fn resume(&mut self, path: &Path) -> Option<(Arc<DiskArtifact>, FileStamp)> {
+        let stamp = FileStamp::of(path)?;
+        if let Some(marker) = self.markers.get(path).filter(|marker| marker.stamp == stamp) {
+            return Some((Arc::new(marker.artifact_at(path)?), stamp));
+        }
+
+        // Touched, renamed or copied: the same contents were summarized before
+        let hash = file_hash(path)?;
+        let marker = self.markers.get(self.by_hash.get(&hash)?)?;
+        if marker.content_hash.as_ref() != Some(&hash) {
+            return None;
+        }
+        let artifact = marker.artifact_at(path)?;
+        debug!("Resuming {} from the summary of {}", path.display(), marker.path.display());
+        if let Err(e) = self.record_hashed(path, &artifact, Some(hash)) {
+            debug!("Failed to refresh the marker of {}: {}", path.display(), e);
+        }
+        Some((Arc::new(artifact), stamp))
+    }
+
+    /// Appends the marker of `path`, just indexed, whose summary is `artifact`.
+    pub fn record(&mut self, path: &Path, artifact: &DiskArtifact) -> io::Result<()> {
+        self.record_hashed(path, artifact, file_hash(path))
+    }
+
+    fn record_hashed(&mut self, path: &Path, artifact: &DiskArtifact, content_hash: Option<String>) -> io::Result<()> {
+        let stamp = FileStamp::of(path).ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "file vanished"))?;
+        let marker = Marker {
+            path: path.to_path_buf(),
+            stamp,
+            content_hash: content_hash.clone(),
+            symbols: artifact.symbols.symbols().to_vec(),
+            calls: artifact.calls.clone(),
+        };
+        if self.writer.is_none() {
+            if let Some(parent) = self.file.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let file = OpenOptions::new().create(true).append(true).open(&self.file)?;
+            let fresh = file.metadata()?.len() == 0;
+            let mut writer = BufWriter::new(file);
+            if fresh {
+                writeln!(writer, "{}", CHECKPOINT_SCHEMA.header_line())?;
+            }
+            self.writer = Some(writer);
+        }
+        let writer = self.writer.as_mut().expect("opened above");
+        serde_json::to_writer(&mut *writer, &marker)?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+        if let Some(hash) = content_hash {
+            self.by_hash.insert(hash, marker.path.clone());
+        }
+        self.markers.insert(marker.path.clone(), marker);
+        Ok(())
+    }
+
+    /// Rewrites the checkpoint with one marker per file still on disk.
+    pub fn compact(&mut self) -> io::Result<()> {
+        self.writer = None;
+        self.markers.retain(|path, _| path.exists());
+        self.by_hash.retain(|_, path| self.markers.contains_key(path));
+        let temp = self.file.with_extension("jsonl.tmp");
+        {
+            let mut writer = BufWriter::new(File::create(&temp)?);
+            writeln!(writer, "{}", CHECKPOINT_SCHEMA.header_line())?;
+            for marker in self.markers.values() {
+                serde_json::to_writer(&mut writer, marker)?;
+                writer.write_all(b"\n")?;
+            }
+            writer.flush()?;
+        }
+        fs::rename(&temp, &self.file)
+    }
+}
+
+/// Checkpoints of the workspace folders being indexed
+#[derive(Debug)]
+pub struct IndexCheckpoints {
+    dir: PathBuf,
+    encoding: PositionEncoding,
+    checkpoints: Vec<IndexCheckpoint>,
+}
+
+impl IndexCheckpoints {
+    /// Checkpoints kept in `dir`, with ranges in `encoding`.
+    pub fn new(dir: PathBuf, encoding: PositionEncoding) -> Self {
+        IndexCheckpoints { dir, encoding, checkpoints: Vec::new() }
+    }
+
+    /// The checkpoint of `root`, opened on first use.
+    pub fn for_root(&mut self, root: &Path) -> &mut IndexCheckpoint {
+        let index = match self.checkpoints.iter().position(|checkpoint| checkpoint.root == root) {
+            Some(index) => index,
+            None => {
+                self.checkpoints.push(IndexCheckpoint::open(&self.dir, root, self.encoding));
+                self.checkpoints.len() - 1
+            }
+        };
+        &mut self.checkpoints[index]
+    }
+
+    /// Records `path` in the checkpoint of the folder containing it, if any.
+    pub fn record(&mut self, path: &Path, artifact: &DiskArtifact) {
+        let Some(checkpoint) = self
+            .checkpoints
+            .iter_mut()
+            .filter(|checkpoint| path.starts_with(&checkpoint.root))
+            .max_by_key(|checkpoint| checkpoint.root.components().count())
+        else {
+            return;
+        };
+        if let Err(e) = checkpoint.record(path, artifact) {
+            warn!("Failed to checkpoint {}: {}", path.display(), e);
+        }
+    }
+
+    /// Deletes every checkpoint file, so the next indexing starts over;
+    /// the folders being indexed get empty checkpoints.
+    ///
+    /// Returns the number of files removed.
+    pub fn clear(&mut self) -> io::Result<usize> {
+        let roots: Vec<PathBuf> = self.checkpoints.drain(..).map(|checkpoint| checkpoint.root).collect();
+        let removed = clear_dir(&self.dir)?;
+        for root in roots {
+            self.for_root(&root);
+        }
+        Ok(removed)
+    }
+
+    /// Compacts every checkpoint, once indexing is complete.
+    pub fn compact(&mut self) {
+        for checkpoint in &mut self.checkpoints {
+            if let Err(e) = checkpoint.compact() {
+                warn!("Failed to compact the checkpoint of {}: {}", checkpoint.root.display(), e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UTF16: PositionEncoding = PositionEncoding::Utf16;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rholang-checkpoint-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn index(path: &Path) -> DiskArtifact {
+        DiskArtifact::from_text(&Url::from_file_path(path).unwrap(), &fs::read_to_string(path).unwrap(), UTF16)
+    }
+
+    #[test]
+    fn test_resume_after_restart() {
+        let dir = temp_dir("resume");
+        let root = dir.join("workspace");
+        fs::create_dir_all(&root).unwrap();
+        let done = root.join("done.rho");
+        let changed = root.join("changed.rho");
+        fs::write(&done, "contract vault(@amount) = { Nil }").unwrap();
+        fs::write(&changed, "contract old() = { Nil }").unwrap();
+
+        let mut checkpoints = IndexCheckpoints::new(dir.join("checkpoints"), UTF16);
+        checkpoints.for_root(&root);
+        checkpoints.record(&done, &index(&done));
+        checkpoints.record(&changed, &index(&changed));
+        // Outside every folder: not recorded
+        checkpoints.record(&dir.join("library.rho"), &index(&done));
+        drop(checkpoints);
+
+        fs::write(&changed, "contract renamed() = { Nil } | Nil").unwrap();
+        let mut checkpoint = IndexCheckpoint::open(&dir.join("checkpoints"), &root, UTF16);
+        assert_eq!(checkpoint.len(), 2);
+        let (artifact, _) = checkpoint.resume(&done).unwrap();
+        assert_eq!(artifact.symbols.search("vault").len(), 1);
+        assert!(checkpoint.resume(&changed).is_none(), "changed files are indexed again");
+        let utf8 = IndexCheckpoint::open(&dir.join("checkpoints"), &root, PositionEncoding::Utf8);
+        assert!(utf8.is_empty(), "ranges in another encoding are not resumed");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_resume_by_content_hash() {
+        let dir = temp_dir("hash");
+        let original = dir.join("vault.rho");
+        fs::write(&original, "contract vault(@amount) = { Nil }").unwrap();
+        let mut checkpoint = IndexCheckpoint::open(&dir.join("checkpoints"), &dir, UTF16);
+        checkpoint.record(&original, &index(&original)).unwrap();
+        drop(checkpoint);
+
+        // Rewritten with the same contents, and copied with a byte order mark
+        fs::write(&original, "contract vault(@amount) = { Nil }").unwrap();
+        let copy = dir.join("copy.rho");
+        fs::write(&copy, "\u{feff}contract vault(@amount) = { Nil }").unwrap();
+
+        let mut checkpoint = IndexCheckpoint::open(&dir.join("checkpoints"), &dir, UTF16);
+        let (artifact, _) = checkpoint.resume(&original).unwrap();
+        assert_eq!(artifact.symbols.search("vault").len(), 1);
+        let (artifact, _) = checkpoint.resume(&copy).unwrap();
+        let copy_uri = Url::from_file_path(&copy).unwrap();
+        assert_eq!(artifact.uri, copy_uri);
+        assert!(artifact.symbols.symbols().iter().all(|symbol| symbol.location.uri == copy_uri));
+        // Both now have a marker of their own
+        assert_eq!(checkpoint.len(), 2);
+
+        fs::write(&copy, "contract vault(@amount) = { Nil } | Nil").unwrap();
+        assert!(checkpoint.resume(&copy).is_none());
+
+        // The same in every build
+        assert_eq!(stable_hash(b""), "0e5751c026e543b2e8ab2eb06099daa1d1e5df47778f7787faab45cdf12fe3a8");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_cut_lines_and_compaction() {
+        let dir = temp_dir("compact");
+        let file = dir.join("a.rho");
+        fs::write(&file, "contract a() = { Nil }").unwrap();
+        let gone = dir.join("gone.rho");
+        fs::write(&gone, "Nil").unwrap();
+
+        let mut checkpoint = IndexCheckpoint::open(&dir, &dir, UTF16);
+        checkpoint.record(&file, &index(&file)).unwrap();
+        checkpoint.record(&file, &index(&file)).unwrap();
+        checkpoint.record(&gone, &index(&gone)).unwrap();
+        let checkpoint_file = checkpoint.file.clone();
+        drop(checkpoint);
+        // A crash in the middle of a line
+        let mut cut = OpenOptions::new().append(true).open(&checkpoint_file).unwrap();
+        cut.write_all(b"{\"path\":\"/trunc").unwrap();
+        drop(cut);
+        fs::remove_file(&gone).unwrap();
+
+        let mut checkpoint = IndexCheckpoint::open(&dir, &dir, UTF16);
+        assert_eq!(checkpoint.len(), 2);
+        checkpoint.compact().unwrap();
+        assert_eq!(checkpoint.len(), 1);
+        // The header and one marker
+        assert_eq!(fs::read_to_string(&checkpoint_file).unwrap().lines().count(), 2);
+
+        assert_eq!(percentage(1, 3), 33);
+        assert_eq!(percentage(0, 0), 100);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_checkpoints_of_another_schema_are_discarded() {
+        let dir = temp_dir("schema");
+        let file = dir.join("a.rho");
+        fs::write(&file, "contract a() = { Nil }").unwrap();
+
+        let mut checkpoint = IndexCheckpoint::open(&dir, &dir, UTF16);
+        checkpoint.record(&file, &index(&file)).unwrap();
+        let checkpoint_file = checkpoint.file.clone();
+        drop(checkpoint);
+        assert_eq!(IndexCheckpoint::open(&dir, &dir, UTF16).len(), 1);
+
+        // Written before checkpoints had a header
+        let contents = fs::read_to_string(&checkpoint_file).unwrap();
+        fs::write(&checkpoint_file, contents.lines().skip(1).collect::<Vec<_>>().join("\n")).unwrap();
+        assert!(IndexCheckpoint::open(&dir, &dir, UTF16).is_empty());
+        assert!(!checkpoint_file.exists());
+
+        // Written by a newer release
+        fs::write(&checkpoint_file, format!("{{\"schema\":\"index-checkpoint\",\"version\":{}}}\n{}", CHECKPOINT_SCHEMA.version + 1, contents.lines().nth(1).unwrap())).unwrap();
+        assert!(IndexCheckpoint::open(&dir, &dir, UTF16).is_empty());
+
+        let mut checkpoints = IndexCheckpoints::new(dir.join("checkpoints"), UTF16);
+        checkpoints.for_root(&dir).record(&file, &index(&file)).unwrap();
+        assert_eq!(checkpoints.clear().unwrap(), 1);
+        assert!(IndexCheckpoint::open(&dir.join("checkpoints"), &dir, UTF16).is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
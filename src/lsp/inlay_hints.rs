@@ -0,0 +1,297 @@
+//! Inlay hints for sends and `for` binds
+//!
+//! - **Parameter names**: each argument of a send calling a contract (as
+//!   decided by [`match_contract`]) is prefixed with the name of the formal it
+//!   binds, `transfer!(from: "a", to: "b", ret: *ack)`. Formals that are not a
+//!   plain name (`x` or `@x`) get no hint, nor do arguments spelling the name
+//!   already; when several contracts match, the first one indexed is used.
+//! - **Bind arity**: each bind of a `for` is followed by the number of names
+//!   it receives, `for (@a, @b <- ch /* arity 2 */)`, with a `+` when it has
+//!   a remainder.
+//!
+//! Hints are computed from the cached IR of the document on every request,
+//! so they follow edits; each kind can be switched off with the `inlayHints`
+//! initialization option:
+//!
+//! ```json
+//! { "inlayHints": { "enabled": true, "parameterNames": true, "bindArity": false } }
+//! ```
+
+use std::cell::RefCell;
+use std::sync::Arc;
+
+use ropey::Rope;
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{InlayHint, InlayHintKind, InlayHintLabel, Range};
+
+use crate::i18n::tr;
+use crate::ir::rholang_node::{collect_calls, match_contract, Metadata, NodeBase, RholangNode, RholangNodeVector};
+use crate::ir::visitor::Visitor;
+use crate::lsp::call_hierarchy::{node_range, send_parts, Positions};
+use crate::lsp::line_index::LineIndex;
+
+/// Initialization option selecting the hints
+pub const INLAY_HINTS_OPTION: &str = "inlayHints";
+
+/// The `inlayHints` initialization option
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct InlayHintsConfig {
+    /// Turns all hints off when `false`
+    pub enabled: bool,
+    pub parameter_names: bool,
+    pub bind_arity: bool,
+}
+
+impl Default for InlayHintsConfig {
+    fn default() -> Self {
+        InlayHintsConfig { enabled: true, parameter_names: true, bind_arity: true }
+    }
+}
+
+impl InlayHintsConfig {
+    /// Reads the `inlayHints` initialization option; missing fields keep their defaults.
+    pub fn from_init_options(init_options: Option<&serde_json::Value>) -> Self {
+        init_options
+            .and_then(|opts| opts.get(INLAY_HINTS_OPTION))
+            .and_then(|config| serde_json::from_value(config.clone()).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// The hints of a document within `range`, in document order.
+///
+/// `contracts` are the contracts sends are matched against, usually every
+/// indexed contract of the workspace.
+pub fn inlay_hints(
+    config: &InlayHintsConfig,
+    ir: &Arc<RholangNode>,
+    positions: &Positions,
+    text: &Rope,
+    line_index: &LineIndex,
+    contracts: &[Arc<RholangNode>],
+    range: Range,
+) -> Vec<InlayHint> {
+    if !config.enabled {
+        return Vec::new();
+    }
+    let mut hints = Vec::new();
+    if config.parameter_names {
+        hints.extend(parameter_name_hints(ir, positions, text, line_index, contracts, range));
+    }
+    if config.bind_arity {
+        hints.extend(bind_arity_hints(ir, positions, text, line_index, range));
+    }
+    hints.sort_by_key(|hint| hint.position);
+    hints
+}
+
+fn overlaps(range: &Range, other: &Range) -> bool {
+    range.start <= other.end && other.start <= range.end
+}
+
+/// Name bound by a formal: `x` or `@x`.
+fn formal_name(formal: &RholangNode) -> Option<&str> {
+    match formal {
+        RholangNode::Var { name, .. } => Some(name),
+        RholangNode::Quote { quotable, .. } => match &**quotable {
+            RholangNode::Var { name, .. } => Some(name),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Name an argument is spelled with: `x`, `*x` or `@x`.
+fn argument_name(argument: &RholangNode) -> Option<&str> {
+    match argument {
+        RholangNode::Eval { name, .. } => formal_name(name),
+        _ => formal_name(argument),
+    }
+}
+
+/// Parameter name hints before the arguments of the sends in `range`.
+pub fn parameter_name_hints(
+    ir: &Arc<RholangNode>,
+    positions: &Positions,
+    text: &Rope,
+    line_index: &LineIndex,
+    contracts: &[Arc<RholangNode>],
+    range: Range,
+) -> Vec<InlayHint> {
+    let mut sends = Vec::new();
+    collect_calls(ir, &mut sends);
+
+    let mut hints = Vec::new();
+    for send in sends {
+        if !node_range(&send, positions, text, line_index).is_some_and(|send_range| overlaps(&range, &send_range)) {
+            continue;
+        }
+        let Some((channel, inputs)) = send_parts(&send) else { continue };
+        let Some(RholangNode::Contract { formals, .. }) =
+            contracts.iter().find(|contract| match_contract(channel, inputs, contract)).map(|contract| &**contract)
+        else {
+            continue;
+        };
+        for (formal, argument) in formals.iter().zip(inputs.iter()) {
+            let Some(name) = formal_name(formal) else { continue };
+            if argument_name(argument) == Some(name) {
+                continue;
+            }
+            let Some(argument_range) = node_range(argument, positions, text, line_index) else { continue };
+            hints.push(InlayHint {
+                position: argument_range.start,
+                label: InlayHintLabel::String(format!("{}:", name)),
+                kind: Some(InlayHintKind::PARAMETER),
+                text_edits: None,
+                tooltip: None,
+                padding_left: None,
+                padding_right: Some(true),
+                data: None,
+            });
+        }
+    }
+    hints
+}
+
+/// Arity hints after the `for` binds in `range`.
+pub fn bind_arity_hints(
+    ir: &Arc<RholangNode>,
+    positions: &Positions,
+    text: &Rope,
+    line_index: &LineIndex,
+    range: Range,
+) -> Vec<InlayHint> {
+    let collector = BindCollector { positions, text, line_index, range, hints: RefCell::new(Vec::new()) };
+    collector.visit_node(ir);
+    collector.hints.into_inner()
+}
+
+/// Collects an arity hint for every bind of a `for` or `select`
+struct BindCollector<'a> {
+    positions: &'a Positions,
+    text: &'a Rope,
+    line_index: &'a LineIndex,
+    range: Range,
+    hints: RefCell<Vec<InlayHint>>,
+}
+
+impl BindCollector<'_> {
+    fn record(&self, node: &Arc<RholangNode>, names: &RholangNodeVector, remainder: &Option<Arc<RholangNode>>) {
+        let Some(bind_range) = node_range(node, self.positions, self.text, self.line_index) else { return };
+        if !overlaps(&self.range, &bind_range) {
+            return;
+        }
+        let count = names.len();
+        let label = if remainder.is_some() {
+            tr("hint.arity-remainder", &[("count", &count)])
+        } else {
+            tr("hint.arity", &[("count", &count)])
+        };
+        self.hints.borrow_mut().push(InlayHint {
+            position: bind_range.end,
+            label: InlayHintLabel::String(label),
+            kind: None,
+            text_edits: None,
+            tooltip: None,
+            padding_left: Some(true),
+            padding_right: None,
+            data: None,
+        });
+    }
+}
+
+impl Visitor for BindCollector<'_> {
+    fn visit_linear_bind(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        names: &RholangNodeVector,
+        remainder: &Option<Arc<RholangNode>>,
+        source: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.record(node, names, remainder);
+        self.visit_node(source);
+        Arc::clone(node)
+    }
+
+    fn visit_repeated_bind(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        names: &RholangNodeVector,
+        remainder: &Option<Arc<RholangNode>>,
+        source: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.record(node, names, remainder);
+        self.visit_node(source);
+        Arc::clone(node)
+    }
+
+    fn visit_peek_bind(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        names: &RholangNodeVector,
+        remainder: &Option<Arc<RholangNode>>,
+        source: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.record(node, names, remainder);
+        self.visit_node(source);
+        Arc::clone(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::rholang_node::collect_contracts;
+    use crate::tree_sitter::parse_with_positions;
+    use serde_json::json;
+    use tower_lsp::lsp_types::Position;
+
+    fn hints(source: &str, config: &InlayHintsConfig) -> Vec<(u32, u32, String)> {
+        let rope = Rope::from_str(source);
+        let (ir, positions) = parse_with_positions(source);
+        let mut contracts = Vec::new();
+        collect_contracts(&ir, &mut contracts);
+        let everything = Range::new(Position::new(0, 0), Position::new(u32::MAX, 0));
+        inlay_hints(config, &ir, &positions, &rope, &LineIndex::new(source), &contracts, everything)
+            .into_iter()
+            .map(|hint| match hint.label {
+                InlayHintLabel::String(label) => (hint.position.line, hint.position.character, label),
+                InlayHintLabel::LabelParts(_) => unreachable!(),
+            })
+            .collect()
+    }
+
+    const SOURCE: &str = r#"new transfer, ret in {
+  contract transfer(@from, @to, ret) = { Nil } |
+  transfer!("a", "b", *ret) |
+  for (@x, @y <- ret; @z, ...@rest <= ret) { Nil }
+}"#;
+
+    #[test]
+    fn test_parameter_names_and_bind_arity() {
+        assert_eq!(
+            hints(SOURCE, &InlayHintsConfig::default()),
+            vec![
+                (2, 12, "from:".to_string()),
+                (2, 17, "to:".to_string()),
+                (3, 20, "arity 2".to_string()),
+                (3, 41, "arity 1+".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_config_from_init_options() {
+        let config = InlayHintsConfig::from_init_options(Some(&json!({ "inlayHints": { "bindArity": false } })));
+        assert_eq!(config, InlayHintsConfig { enabled: true, parameter_names: true, bind_arity: false });
+        assert_eq!(hints(SOURCE, &config).len(), 2);
+        assert!(hints(SOURCE, &InlayHintsConfig { enabled: false, ..config }).is_empty());
+    }
+}
@@ -0,0 +1,186 @@
+//! Contract invocation templates for completion
+//!
+//! Where a process can start, completing a contract name inserts a whole
+//! invocation with the contract's formals as snippet placeholders:
+//!
+//! ```rholang
+//! contract transfer(@from, @to, @amount, ret) = { … }
+//!
+//! tra|   ⟶   transfer!(${1:from}, ${2:to}, ${3:amount}, *${4:ret})
+//! ```
+//!
+//! Name formals (`ret`) take a name, so their placeholder is dereferenced
+//! with `*`. Formals that are not simple variables are named `arg1`, `arg2`, …
+//! Signatures come from the pattern index (`RholangPatternIndex::signatures`),
+//! one item per overload.
+
+use ropey::Rope;
+use tower_lsp::lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionItemLabelDetails, Documentation, InsertTextFormat,
+    Position as LspPosition,
+};
+
+use crate::ir::rholang_pattern_index::PatternMetadata;
+use crate::lsp::line_index::LineIndex;
+use crate::lsp::receive_patterns::escape_snippet;
+
+/// Whether the identifier being typed at `position` starts a process, so
+/// that a contract name there is the channel of a send.
+///
+/// That is the case at the start of the document, after `{`, `|`, `=>` or
+/// `in`, unless the invocation is already written (`name!`, `name(`).
+/// Returns `None` if `position` is past the last line of `text`.
+pub fn at_send_position(text: &Rope, line_index: &LineIndex, position: LspPosition) -> Option<bool> {
+    let byte = line_index.offset(position.line as usize, position.character as usize, text)?;
+    let cursor = text.byte_to_char(byte);
+
+    let mut following = text.chars_at(cursor).skip_while(|c| is_identifier_char(*c));
+    if matches!(following.next(), Some('!') | Some('(')) {
+        return Some(false);
+    }
+
+    let mut preceding = text.chars_at(cursor);
+    let mut prev = preceding.prev();
+    while prev.is_some_and(is_identifier_char) {
+        prev = preceding.prev();
+    }
+    while prev.is_some_and(char::is_whitespace) {
+        prev = preceding.prev();
+    }
+    Some(match prev {
+        None | Some('{') | Some('|') => true,
+        Some('>') => preceding.prev() == Some('='),
+        Some('n') => preceding.prev() == Some('i') && !preceding.prev().is_some_and(is_identifier_char),
+        _ => false,
+    })
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '\''
+}
+
+/// Label of each formal of `signature`: its variable name, or `argN`.
+fn formal_labels(signature: &PatternMetadata) -> Vec<String> {
+    (0..signature.arity)
+        .map(|i| {
+            signature
+                .param_names
+                .as_ref()
+                .and_then(|names| names.get(i))
+                .cloned()
+                .unwrap_or_else(|| format!("arg{}", i + 1))
+        })
+        .collect()
+}
+
+fn is_name_formal(signature: &PatternMetadata, index: usize) -> bool {
+    signature.param_is_name.get(index).copied().unwrap_or(false)
+}
+
+/// The invocation snippet of `signature`, e.g. `transfer!(${1:from}, *${2:ret})`.
+pub fn invocation_snippet(signature: &PatternMetadata) -> String {
+    let args: Vec<String> = formal_labels(signature)
+        .iter()
+        .enumerate()
+        .map(|(i, label)| {
+            let placeholder = format!("${{{}:{}}}", i + 1, escape_snippet(label));
+            if is_name_formal(signature, i) {
+                format!("*{}", placeholder)
+            } else {
+                placeholder
+            }
+        })
+        .collect();
+    format!("{}!({})", escape_snippet(&signature.name), args.join(", "))
+}
+
+/// One completion item per signature, inserting its invocation template.
+pub fn invocation_items(signatures: &[PatternMetadata], documentation: Option<Documentation>) -> Vec<CompletionItem> {
+    signatures
+        .iter()
+        .map(|signature| {
+            let formals: Vec<String> = formal_labels(signature)
+                .into_iter()
+                .enumerate()
+                .map(|(i, label)| if is_name_formal(signature, i) { label } else { format!("@{}", label) })
+                .collect();
+            CompletionItem {
+                label: signature.name.clone(),
+                label_details: Some(CompletionItemLabelDetails {
+                    detail: Some(format!("({})", formals.join(", "))),
+                    description: None,
+                }),
+                kind: Some(CompletionItemKind::FUNCTION),
+                detail: Some(format!("contract {}({})", signature.name, formals.join(", "))),
+                documentation: documentation.clone(),
+                filter_text: Some(signature.name.clone()),
+                insert_text: Some(invocation_snippet(signature)),
+                insert_text_format: Some(InsertTextFormat::SNIPPET),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::rholang_node::{collect_contracts, RholangNode};
+    use crate::ir::rholang_pattern_index::{RholangPatternIndex, SymbolLocation};
+    use crate::ir::semantic_node::Position;
+    use crate::tree_sitter::{parse_code, parse_to_ir};
+
+    fn send_position(source: &str, line: u32, character: u32) -> bool {
+        let text = Rope::from_str(source);
+        at_send_position(&text, &LineIndex::from_rope(&text), LspPosition { line, character }).unwrap()
+    }
+
+    #[test]
+    fn test_send_positions() {
+        assert!(send_position("tra", 0, 3));
+        assert!(send_position("new ret in {\n  tra\n}", 1, 5));
+        assert!(send_position("Nil | tra", 0, 9));
+        assert!(send_position("match x { 1 => tra }", 0, 18));
+        assert!(send_position("new ret in tra", 0, 14));
+
+        // Already an invocation, or not where a process starts
+        assert!(!send_position("tra!(1)", 0, 3));
+        assert!(!send_position("x!(tra)", 0, 6));
+        assert!(!send_position("for (x <- tra) { Nil }", 0, 13));
+        assert!(!send_position("foo.join tra", 0, 12), "identifier ending in 'in'");
+
+        // Past the last line
+        let text = Rope::from_str("tra");
+        assert_eq!(at_send_position(&text, &LineIndex::from_rope(&text), LspPosition { line: 3, character: 0 }), None);
+    }
+
+    #[test]
+    fn test_invocation_template_from_pattern_index() {
+        let source = "contract transfer(@from, @to, @amount, ret) = { Nil }";
+        let tree = parse_code(source);
+        let rope = Rope::from_str(source);
+        let ir = parse_to_ir(&tree, &rope);
+        let mut contracts = Vec::new();
+        collect_contracts(&ir, &mut contracts);
+        let contract: &RholangNode = &contracts[0];
+
+        let position = Position { row: 0, column: 0, byte: 0 };
+        let location = SymbolLocation { uri: "file:///bank.rho".to_string(), start: position, end: position };
+        let mut index = RholangPatternIndex::new();
+        index.index_contract(contract, location.clone()).unwrap();
+        // Re-indexing the same contract replaces its signature
+        index.index_contract(contract, location).unwrap();
+
+        let signatures = index.signatures("transfer");
+        assert_eq!(signatures.len(), 1);
+        assert_eq!(
+            invocation_snippet(&signatures[0]),
+            "transfer!(${1:from}, ${2:to}, ${3:amount}, *${4:ret})"
+        );
+
+        let items = invocation_items(signatures, None);
+        assert_eq!(items[0].detail.as_deref(), Some("contract transfer(@from, @to, @amount, ret)"));
+        assert_eq!(items[0].insert_text_format, Some(InsertTextFormat::SNIPPET));
+        assert!(index.signatures("unknown").is_empty());
+    }
+}
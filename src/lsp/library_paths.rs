@@ -0,0 +1,128 @@
+//! Shared contract libraries outside the workspace
+//!
+//! Directories of `.rho` files shared between projects can be indexed at
+//! startup so that goto-definition, references and completion resolve into
+//! them. They are configured with the `libraryPaths` initialization option;
+//! relative paths are resolved against the workspace root:
+//!
+//! ```json
+//! { "libraryPaths": ["/opt/rholang/stdlib", "../shared-contracts"] }
+//! ```
+//!
+//! Library files are queued after the workspace files (priority
+//! [`LIBRARY_INDEX_PRIORITY`]) and are kept apart from them in
+//! [`Libraries`]: they are not watched for changes, and they are read-only,
+//! so renames that would edit them are refused. Files under the workspace
+//! root are indexed as workspace files even when a library path covers them.
+
+use std::path::{Path, PathBuf};
+
+use dashmap::DashSet;
+use tower_lsp::lsp_types::Url;
+use walkdir::WalkDir;
+
+/// Initialization option listing library directories
+pub const LIBRARY_PATHS_OPTION: &str = "libraryPaths";
+
+/// Indexing priority of library files, after workspace files (1) and open files (0)
+pub const LIBRARY_INDEX_PRIORITY: u8 = 2;
+
+/// Reads the `libraryPaths` initialization option, resolving relative paths against `root`.
+pub fn library_paths_from_init_options(init_options: Option<&serde_json::Value>, root: Option<&Path>) -> Vec<PathBuf> {
+    let Some(paths) = init_options.and_then(|opts| opts.get(LIBRARY_PATHS_OPTION)).and_then(|paths| paths.as_array())
+    else {
+        return Vec::new();
+    };
+    paths
+        .iter()
+        .filter_map(|path| path.as_str())
+        .map(PathBuf::from)
+        .map(|path| match root {
+            Some(root) if path.is_relative() => root.join(path),
+            _ => path,
+        })
+        .collect()
+}
+
+/// The `.rho` files under `paths` that are not under the workspace `root`,
+/// sorted and without duplicates.
+pub fn library_files(paths: &[PathBuf], root: Option<&Path>) -> Vec<PathBuf> {
+    let root = root.and_then(|root| root.canonicalize().ok());
+    let mut files: Vec<PathBuf> = paths
+        .iter()
+        .flat_map(|path| WalkDir::new(path).into_iter().filter_map(|entry| entry.ok()))
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "rho"))
+        .filter_map(|entry| entry.path().canonicalize().ok())
+        .filter(|path| root.as_ref().is_none_or(|root| !path.starts_with(root)))
+        .collect();
+    files.sort();
+    files.dedup();
+    files
+}
+
+/// Library documents indexed at startup
+#[derive(Debug, Default)]
+pub struct Libraries {
+    uris: DashSet<Url>,
+}
+
+impl Libraries {
+    pub fn insert(&self, uri: Url) {
+        self.uris.insert(uri);
+    }
+
+    pub fn contains(&self, uri: &Url) -> bool {
+        self.uris.contains(uri)
+    }
+
+    pub fn len(&self) -> usize {
+        self.uris.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.uris.is_empty()
+    }
+
+    /// The library documents among `uris`, sorted.
+    pub fn read_only<'a>(&self, uris: impl IntoIterator<Item = &'a Url>) -> Vec<Url> {
+        let mut read_only: Vec<Url> = uris.into_iter().filter(|uri| self.contains(uri)).cloned().collect();
+        read_only.sort();
+        read_only.dedup();
+        read_only
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_paths_from_init_options() {
+        let options = json!({ "libraryPaths": ["/opt/lib", "../shared", 3] });
+        assert_eq!(
+            library_paths_from_init_options(Some(&options), Some(Path::new("/work/project"))),
+            vec![PathBuf::from("/opt/lib"), PathBuf::from("/work/project/../shared")]
+        );
+        assert!(library_paths_from_init_options(None, None).is_empty());
+    }
+
+    #[test]
+    fn test_library_files_skip_the_workspace() {
+        let dir = std::env::temp_dir().join(format!("rholang-libraries-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let library = dir.join("lib");
+        let workspace = dir.join("workspace");
+        std::fs::create_dir_all(library.join("nested")).unwrap();
+        std::fs::create_dir_all(&workspace).unwrap();
+        std::fs::write(library.join("a.rho"), "Nil").unwrap();
+        std::fs::write(library.join("nested/b.rho"), "Nil").unwrap();
+        std::fs::write(library.join("notes.txt"), "").unwrap();
+        std::fs::write(workspace.join("main.rho"), "Nil").unwrap();
+
+        let files = library_files(&[library.clone(), library.clone(), dir.clone()], Some(&workspace));
+        let names: Vec<_> = files.iter().map(|f| f.file_name().unwrap().to_str().unwrap()).collect();
+        assert_eq!(names, vec!["a.rho", "b.rho"]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
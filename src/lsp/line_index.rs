@@ -0,0 +1,50 @@
+//! Precomputed line-start index for position conversions
+//!
+//! [`LineIndex`] lives in the `rholang-ir-core` crate so that tooling outside
+//! the server can convert positions the same way.
+//!
+//! IR positions keep tree-sitter's byte columns; [`lsp_position`] and
+//! [`lsp_range`] turn them into LSP columns, counted in the index's
+//! negotiated [`PositionEncoding`].
+
+use ropey::Rope;
+use tower_lsp::lsp_types::{Position, Range};
+
+use crate::ir::rholang_node::Position as IrPosition;
+
+pub use rholang_ir_core::line_index::{LineIndex, PositionEncoding};
+
+/// The line of `position` as chars without its terminator, with the index of
+/// the char at the position (clamped to the end of the line), for features
+/// that scan the current line.
+///
+/// Returns `None` if the line is past the end of `text`.
+pub fn line_chars(text: &Rope, line_index: &LineIndex, position: Position) -> Option<(Vec<char>, usize)> {
+    let line = position.line as usize;
+    let start = line_index.line_start(line)?;
+    let end = line_index.line_end(line)?;
+    let cursor_byte = line_index.offset(line, position.character as usize, text)?;
+    let chars = text.byte_slice(start..end).chars().filter(|c| *c != '\n' && *c != '\r').collect();
+    Some((chars, text.byte_slice(start..cursor_byte).len_chars()))
+}
+
+/// LSP column of the char at `index` in a line returned by [`line_chars`].
+pub fn line_column(line_index: &LineIndex, chars: &[char], index: usize) -> u32 {
+    chars[..index].iter().map(|c| line_index.encoding().units(*c) as u32).sum()
+}
+
+/// LSP position of `position`, an IR position in `text`.
+///
+/// A position past the end of `text` (an IR of another version) keeps its
+/// line and byte column.
+pub fn lsp_position(text: &Rope, line_index: &LineIndex, position: &IrPosition) -> Position {
+    match line_index.position(position.byte, text) {
+        Some((line, character)) => Position::new(line as u32, character as u32),
+        None => Position::new(position.row as u32, position.column as u32),
+    }
+}
+
+/// LSP range of the IR span `(start, end)` in `text`.
+pub fn lsp_range(text: &Rope, line_index: &LineIndex, (start, end): &(IrPosition, IrPosition)) -> Range {
+    Range::new(lsp_position(text, line_index, start), lsp_position(text, line_index, end))
+}
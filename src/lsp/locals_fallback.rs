@@ -0,0 +1,232 @@
+//! Navigation from `locals.scm` when the IR is degraded
+//!
+//! While a document has severe syntax errors its IR is mostly `Error` nodes
+//! and the symbol tables built from it know little, so highlights and
+//! go-to-definition would stop working until the syntax is fixed. For such
+//! documents ([`is_degraded`]) the `locals.scm` query resolves names
+//! directly on the Tree-Sitter tree instead: every capture outside `ERROR`
+//! and missing nodes is kept, a reference resolves to the same-named
+//! definition of the innermost scope around it, and names without one are
+//! grouped as free names.
+
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use tower_lsp::lsp_types::{Position as LspPosition, Range};
+use tracing::warn;
+use tree_sitter::{Node as TSNode, Tree};
+
+use crate::ir::rholang_node::{Metadata, NodeBase, Position as IrPosition, RholangNode, RholangNodeVector};
+use crate::ir::visitor::Visitor;
+use crate::lsp::features::tree_sitter::query_types::LocalType;
+use crate::lsp::features::tree_sitter::{CaptureType, QueryEngine, QueryType};
+
+/// Share of the document covered by `Error` nodes from which the IR counts as degraded
+pub const DEGRADED_ERROR_SHARE: f64 = 0.5;
+
+/// Query engine with only `locals.scm` loaded; `None` if the query does not compile
+static LOCALS: Lazy<Option<Mutex<QueryEngine>>> = Lazy::new(|| {
+    let engine = QueryEngine::new("rholang", rholang_tree_sitter::LANGUAGE.into()).and_then(|mut engine| {
+        engine.load_query(QueryType::Locals, include_str!("../../queries/rholang/locals.scm"))?;
+        Ok(engine)
+    });
+    match engine {
+        Ok(engine) => Some(Mutex::new(engine)),
+        Err(e) => {
+            warn!("Locals query unavailable: {}", e);
+            None
+        }
+    }
+});
+
+/// Whether `Error` nodes cover at least [`DEGRADED_ERROR_SHARE`] of a
+/// document of `len_bytes` bytes.
+pub fn is_degraded(
+    ir: &Arc<RholangNode>,
+    positions: &HashMap<usize, (IrPosition, IrPosition)>,
+    len_bytes: usize,
+) -> bool {
+    if matches!(&**ir, RholangNode::Error { .. }) {
+        return true;
+    }
+    if len_bytes == 0 {
+        return false;
+    }
+    let coverage = ErrorCoverage { positions, bytes: Cell::new(0) };
+    coverage.visit_node(ir);
+    coverage.bytes.get() as f64 / len_bytes as f64 >= DEGRADED_ERROR_SHARE
+}
+
+/// Sums the bytes of the outermost `Error` nodes
+struct ErrorCoverage<'a> {
+    positions: &'a HashMap<usize, (IrPosition, IrPosition)>,
+    bytes: Cell<usize>,
+}
+
+impl Visitor for ErrorCoverage<'_> {
+    fn visit_error(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        _children: &RholangNodeVector,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        if let Some((start, end)) = self.positions.get(&(Arc::as_ptr(node) as usize)) {
+            self.bytes.set(self.bytes.get() + end.byte.saturating_sub(start.byte));
+        }
+        Arc::clone(node)
+    }
+}
+
+/// A name and the occurrences resolving to the same binding
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalOccurrences {
+    pub name: String,
+    /// The binding occurrence; `None` for a free name
+    pub definition: Option<Range>,
+    /// All occurrences, including the definition, in document order
+    pub occurrences: Vec<Range>,
+}
+
+#[derive(Debug)]
+struct Identifier {
+    name: String,
+    bytes: (usize, usize),
+    range: Range,
+    definition: bool,
+}
+
+/// The occurrences of the name at `position` according to `locals.scm`,
+/// ignoring everything inside `ERROR` and missing nodes.
+pub fn local_occurrences(tree: &Tree, source: &str, position: LspPosition) -> Option<LocalOccurrences> {
+    let engine = LOCALS.as_ref()?;
+    let engine = engine.lock();
+    let captures = match engine.execute(tree, QueryType::Locals, source.as_bytes()) {
+        Ok(captures) => captures,
+        Err(e) => {
+            warn!("Locals query failed: {}", e);
+            return None;
+        }
+    };
+
+    let mut scopes: Vec<(usize, usize)> = Vec::new();
+    let mut identifiers: Vec<Identifier> = Vec::new();
+    for capture in captures.iter().filter(|capture| !in_error(capture.node)) {
+        let CaptureType::Local(local) = &capture.capture_type else { continue };
+        match local {
+            LocalType::Scope => scopes.push(capture.byte_range),
+            LocalType::Definition | LocalType::Reference => {
+                let definition = *local == LocalType::Definition;
+                // A definition is also matched by the reference pattern
+                if let Some(known) = identifiers.iter_mut().find(|id| id.bytes == capture.byte_range) {
+                    known.definition |= definition;
+                    continue;
+                }
+                identifiers.push(Identifier {
+                    name: capture.text(source.as_bytes()).to_string(),
+                    bytes: capture.byte_range,
+                    range: capture.lsp_range,
+                    definition,
+                });
+            }
+        }
+    }
+    identifiers.sort_by_key(|id| id.bytes);
+
+    let target = identifiers.iter().position(|id| contains(&id.range, position))?;
+    let binding = resolve(&identifiers, &scopes, target);
+    let occurrences = identifiers
+        .iter()
+        .enumerate()
+        .filter(|(i, id)| id.name == identifiers[target].name && resolve(&identifiers, &scopes, *i) == binding)
+        .map(|(_, id)| id.range)
+        .collect();
+    Some(LocalOccurrences {
+        name: identifiers[target].name.clone(),
+        definition: binding.map(|i| identifiers[i].range),
+        occurrences,
+    })
+}
+
+/// The definition the identifier at `index` resolves to: the same-named
+/// definition whose scope is the innermost one around the identifier.
+fn resolve(identifiers: &[Identifier], scopes: &[(usize, usize)], index: usize) -> Option<usize> {
+    let id = &identifiers[index];
+    if id.definition {
+        return Some(index);
+    }
+    identifiers
+        .iter()
+        .enumerate()
+        .filter(|(_, def)| def.definition && def.name == id.name)
+        .filter_map(|(i, def)| {
+            let scope = innermost_scope(scopes, def.bytes)?;
+            (scope.0 <= id.bytes.0 && id.bytes.1 <= scope.1).then_some((i, scope.1 - scope.0))
+        })
+        .min_by_key(|(_, size)| *size)
+        .map(|(i, _)| i)
+}
+
+fn innermost_scope(scopes: &[(usize, usize)], bytes: (usize, usize)) -> Option<(usize, usize)> {
+    scopes
+        .iter()
+        .filter(|scope| scope.0 <= bytes.0 && bytes.1 <= scope.1)
+        .min_by_key(|scope| scope.1 - scope.0)
+        .copied()
+}
+
+/// Whether `node` is, or lies within, an `ERROR` or missing node.
+fn in_error(node: TSNode) -> bool {
+    let mut current = Some(node);
+    while let Some(node) = current {
+        if node.is_error() || node.is_missing() {
+            return true;
+        }
+        current = node.parent();
+    }
+    false
+}
+
+fn contains(range: &Range, position: LspPosition) -> bool {
+    range.start <= position && position <= range.end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree_sitter::{parse_code, parse_with_positions};
+
+    fn at(line: u32, character: u32) -> LspPosition {
+        LspPosition { line, character }
+    }
+
+    #[test]
+    fn test_degraded_documents() {
+        let clean = "new x in { x!(1) }";
+        let (ir, positions) = parse_with_positions(clean);
+        assert!(!is_degraded(&ir, &positions, clean.len()));
+
+        let broken = "new x in { x!(1) }\n) for ( <- <- { ] contract ( = ) !! @@ [ } } match {";
+        let (ir, positions) = parse_with_positions(broken);
+        assert!(is_degraded(&ir, &positions, broken.len()));
+    }
+
+    #[test]
+    fn test_occurrences_outside_errors() {
+        let source = "new x in {\n  x!(1) |\n  for (y <- x) { y!(2) }\n}\n) ] contract ( = !! @@";
+        let tree = parse_code(source);
+
+        let x = local_occurrences(&tree, source, at(1, 2)).expect("x is a local");
+        assert_eq!(x.name, "x");
+        assert_eq!(x.definition.map(|range| range.start), Some(at(0, 4)));
+        let starts: Vec<_> = x.occurrences.iter().map(|range| range.start).collect();
+        assert_eq!(starts, vec![at(0, 4), at(1, 2), at(2, 12)]);
+
+        let y = local_occurrences(&tree, source, at(2, 17)).expect("y is a local");
+        assert_eq!(y.definition.map(|range| range.start), Some(at(2, 7)));
+        assert_eq!(y.occurrences.len(), 2);
+    }
+}
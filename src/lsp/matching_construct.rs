@@ -0,0 +1,301 @@
+//! Partner tokens for the `rholang/matchingConstruct` custom request
+//!
+//! Editors only match brackets. This request also pairs up the tokens that
+//! delimit Rholang constructs, using the Tree-Sitter CST:
+//!
+//! | Token under the cursor      | Partners                                         |
+//! |-----------------------------|--------------------------------------------------|
+//! | `{` `}` `(` `)` `[` `]`     | the matching bracket                             |
+//! | `\|`                        | every other `\|` of the same parallel composition |
+//! | `=>`                        | `match`/`select` and the other cases' `=>`       |
+//! | `match` / `select`          | every case's `=>`                                |
+//! | `in`                        | the `new`/`let` that opens the binder            |
+//! | `new` / `let`               | its `in`                                         |
+//!
+//! # Request
+//!
+//! ```json
+//! { "textDocument": { "uri": "file:///path/to/file.rho" }, "position": { "line": 3, "character": 7 } }
+//! ```
+//!
+//! # Response
+//!
+//! `null` when the position is not on (or directly after) one of the tokens
+//! above, otherwise:
+//!
+//! ```json
+//! { "kind": "par",
+//!   "token": { "start": { "line": 3, "character": 6 }, "end": { "line": 3, "character": 7 } },
+//!   "partners": [ { "start": …, "end": … } ] }
+//! ```
+
+use ropey::Rope;
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{Position as LspPosition, Range, TextDocumentIdentifier};
+use tree_sitter::{Node as TSNode, Tree};
+
+use crate::lsp::line_index::LineIndex;
+
+/// Custom request method name
+pub const MATCHING_CONSTRUCT_METHOD: &str = "rholang/matchingConstruct";
+
+/// Parameters of `rholang/matchingConstruct`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchingConstructParams {
+    pub text_document: TextDocumentIdentifier,
+    pub position: LspPosition,
+}
+
+/// Kind of construct the token delimits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConstructKind {
+    /// `{}`, `()` or `[]`
+    Bracket,
+    /// `P | Q | R`
+    Par,
+    /// `match`/`select` cases
+    Cases,
+    /// `new … in` / `let … in`
+    Binder,
+}
+
+/// Result of `rholang/matchingConstruct`
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct MatchingConstruct {
+    pub kind: ConstructKind,
+    /// Range of the token at the requested position
+    pub token: Range,
+    /// Ranges of the partner tokens, in document order
+    pub partners: Vec<Range>,
+}
+
+/// Finds the partners of the construct token at `position`.
+///
+/// A token ending exactly at `position` is used when no token starts there,
+/// so that the cursor just after `}` or `in` still matches.
+pub fn find_matching_construct(
+    tree: &Tree,
+    text: &Rope,
+    line_index: &LineIndex,
+    position: LspPosition,
+) -> Option<MatchingConstruct> {
+    let offset = line_index.offset(position.line as usize, position.character as usize, text)?;
+    let root = tree.root_node();
+
+    let candidates = [Some(offset), offset.checked_sub(1)];
+    candidates.into_iter().flatten().find_map(|byte| {
+        let token = root.descendant_for_byte_range(byte, byte)?;
+        if token.child_count() > 0 || !(token.start_byte()..token.end_byte()).contains(&byte) {
+            return None;
+        }
+        let (kind, mut partners) = partners_of(token)?;
+        partners.sort_by_key(|node| node.start_byte());
+        Some(MatchingConstruct {
+            kind,
+            token: node_range(text, line_index, token)?,
+            partners: partners.into_iter().map(|node| node_range(text, line_index, node)).collect::<Option<_>>()?,
+        })
+    })
+}
+
+fn partners_of(token: TSNode) -> Option<(ConstructKind, Vec<TSNode>)> {
+    let parent = token.parent()?;
+    match token.kind() {
+        "{" | "(" | "[" => matching_bracket(token, parent, true).map(|partner| (ConstructKind::Bracket, vec![partner])),
+        "}" | ")" | "]" => matching_bracket(token, parent, false).map(|partner| (ConstructKind::Bracket, vec![partner])),
+        "|" if parent.kind() == "par" => {
+            let mut top = parent;
+            while let Some(outer) = top.parent().filter(|outer| outer.kind() == "par") {
+                top = outer;
+            }
+            let mut bars = Vec::new();
+            collect_par_bars(top, &mut bars);
+            bars.retain(|bar| bar.id() != token.id());
+            Some((ConstructKind::Par, bars))
+        }
+        "=>" if matches!(parent.kind(), "case" | "branch") => {
+            let construct = ancestor_of_kind(parent, &["match", "choice"])?;
+            let mut partners = case_arrows(construct);
+            partners.retain(|arrow| arrow.id() != token.id());
+            partners.extend(first_child_of_kind(construct, &["match", "select"]));
+            Some((ConstructKind::Cases, partners))
+        }
+        "match" | "select" if matches!(parent.kind(), "match" | "choice") => {
+            Some((ConstructKind::Cases, case_arrows(parent)))
+        }
+        "in" if matches!(parent.kind(), "new" | "let") => {
+            first_child_of_kind(parent, &["new", "let"]).map(|keyword| (ConstructKind::Binder, vec![keyword]))
+        }
+        "new" | "let" if matches!(parent.kind(), "new" | "let") => {
+            first_child_of_kind(parent, &["in"]).map(|keyword| (ConstructKind::Binder, vec![keyword]))
+        }
+        _ => None,
+    }
+}
+
+/// Finds the bracket matching `token` among the tokens of `parent`.
+fn matching_bracket<'a>(token: TSNode<'a>, parent: TSNode<'a>, opening: bool) -> Option<TSNode<'a>> {
+    let (open, close) = match token.kind() {
+        "{" | "}" => ("{", "}"),
+        "(" | ")" => ("(", ")"),
+        _ => ("[", "]"),
+    };
+    let mut cursor = parent.walk();
+    let mut siblings: Vec<TSNode> = parent.children(&mut cursor).collect();
+    if !opening {
+        siblings.reverse();
+    }
+    let (towards, away) = if opening { (open, close) } else { (close, open) };
+
+    let mut depth = 0usize;
+    for sibling in siblings.into_iter().skip_while(|sibling| sibling.id() != token.id()) {
+        if sibling.kind() == towards {
+            depth += 1;
+        } else if sibling.kind() == away {
+            depth -= 1;
+            if depth == 0 {
+                return Some(sibling);
+            }
+        }
+    }
+    None
+}
+
+/// Collects the `|` tokens of a chain of nested `par` nodes.
+fn collect_par_bars<'a>(par: TSNode<'a>, bars: &mut Vec<TSNode<'a>>) {
+    let mut cursor = par.walk();
+    for child in par.children(&mut cursor) {
+        match child.kind() {
+            "|" => bars.push(child),
+            "par" => collect_par_bars(child, bars),
+            _ => {}
+        }
+    }
+}
+
+/// The `=>` tokens of every case of a `match` or branch of a `select`.
+fn case_arrows(construct: TSNode) -> Vec<TSNode> {
+    let (field, case_kind) = if construct.kind() == "match" { ("cases", "case") } else { ("branches", "branch") };
+    let Some(cases) = construct.child_by_field_name(field) else { return Vec::new() };
+
+    let mut cursor = cases.walk();
+    let case_nodes: Vec<TSNode> = cases.named_children(&mut cursor).filter(|case| case.kind() == case_kind).collect();
+    case_nodes
+        .into_iter()
+        .filter_map(|case| first_child_of_kind(case, &["=>"]))
+        .collect()
+}
+
+fn ancestor_of_kind<'a>(node: TSNode<'a>, kinds: &[&str]) -> Option<TSNode<'a>> {
+    let mut current = node.parent();
+    while let Some(ancestor) = current {
+        if kinds.contains(&ancestor.kind()) {
+            return Some(ancestor);
+        }
+        current = ancestor.parent();
+    }
+    None
+}
+
+fn first_child_of_kind<'a>(node: TSNode<'a>, kinds: &[&str]) -> Option<TSNode<'a>> {
+    let mut cursor = node.walk();
+    let found = node.children(&mut cursor).find(|child| !child.is_named() && kinds.contains(&child.kind()));
+    found
+}
+
+fn node_range(text: &Rope, line_index: &LineIndex, node: TSNode) -> Option<Range> {
+    let to_position = |byte: usize| {
+        let (line, character) = line_index.position(byte, text)?;
+        Some(LspPosition { line: line as u32, character: character as u32 })
+    };
+    Some(Range { start: to_position(node.start_byte())?, end: to_position(node.end_byte())? })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree_sitter::parse_code;
+
+    /// Returns (kind, token text, partner texts) for the token at `line:character`.
+    fn matching(source: &str, line: u32, character: u32) -> Option<(ConstructKind, String, Vec<String>)> {
+        let tree = parse_code(source);
+        let rope = Rope::from_str(source);
+        let index = LineIndex::from_rope(&rope);
+        let text_of = |range: &Range| {
+            let start = index.offset(range.start.line as usize, range.start.character as usize, &rope).unwrap();
+            let end = index.offset(range.end.line as usize, range.end.character as usize, &rope).unwrap();
+            source[start..end].to_string()
+        };
+        find_matching_construct(&tree, &rope, &index, LspPosition { line, character }).map(|found| {
+            (found.kind, text_of(&found.token), found.partners.iter().map(|range| text_of(range)).collect())
+        })
+    }
+
+    #[test]
+    fn test_brackets() {
+        let source = "contract foo(@x) = {\n  x!([1, 2])\n}";
+        let (kind, token, partners) = matching(source, 0, 19).unwrap();
+        assert_eq!((kind, token.as_str()), (ConstructKind::Bracket, "{"));
+        assert_eq!(partners, vec!["}"]);
+
+        // Cursor just after the closing brace
+        let found = find_matching_construct(&parse_code(source), &Rope::from_str(source), &LineIndex::new(source), LspPosition { line: 2, character: 1 }).unwrap();
+        assert_eq!(found.partners[0].start, LspPosition { line: 0, character: 19 });
+
+        let (_, token, partners) = matching(source, 1, 5).unwrap();
+        assert_eq!((token.as_str(), partners), ("[", vec!["]".to_string()]));
+    }
+
+    #[test]
+    fn test_par_bars() {
+        let source = "@0!(1) | @1!(2) | { @2!(3) | @3!(4) } | Nil";
+        let (kind, token, partners) = matching(source, 0, 7).unwrap();
+        assert_eq!((kind, token.as_str()), (ConstructKind::Par, "|"));
+        // The nested par inside the block is a different composition
+        assert_eq!(partners, vec!["|", "|"]);
+
+        let (_, _, nested) = matching(source, 0, 27).unwrap();
+        assert!(nested.is_empty());
+    }
+
+    #[test]
+    fn test_match_cases() {
+        let source = "match 1 {\n  1 => Nil\n  _ => Nil\n}";
+        let (kind, token, partners) = matching(source, 1, 4).unwrap();
+        assert_eq!((kind, token.as_str()), (ConstructKind::Cases, "=>"));
+        assert_eq!(partners, vec!["match", "=>"]);
+
+        let (_, token, partners) = matching(source, 0, 0).unwrap();
+        assert_eq!((token.as_str(), partners), ("match", vec!["=>".to_string(), "=>".to_string()]));
+    }
+
+    #[test]
+    fn test_binders() {
+        let source = "new x, y in {\n  x!(1)\n}";
+        let (kind, token, partners) = matching(source, 0, 9).unwrap();
+        assert_eq!((kind, token.as_str(), partners), (ConstructKind::Binder, "in", vec!["new".to_string()]));
+
+        let (_, token, partners) = matching(source, 0, 1).unwrap();
+        assert_eq!((token.as_str(), partners), ("new", vec!["in".to_string()]));
+    }
+
+    #[test]
+    fn test_other_positions() {
+        let source = "new x in { x!(1) }";
+        assert!(matching(source, 0, 4).is_none(), "identifier");
+        assert!(matching(source, 5, 0).is_none(), "past the end");
+    }
+
+    #[test]
+    fn test_utf16_columns() {
+        // The crab is two UTF-16 code units: the brace after it is at 10, not 9
+        let source = "@\"🦀\"!(1) | { Nil }";
+        let (_, token, partners) = matching(source, 0, 12).unwrap();
+        assert_eq!((token.as_str(), partners), ("{", vec!["}".to_string()]));
+        let found = find_matching_construct(&parse_code(source), &Rope::from_str(source), &LineIndex::new(source), LspPosition { line: 0, character: 12 }).unwrap();
+        assert_eq!(found.token.start, LspPosition { line: 0, character: 12 });
+        assert_eq!(found.partners[0].start, LspPosition { line: 0, character: 18 });
+    }
+}
@@ -1,11 +1,82 @@
+pub mod analysis_budget;
+pub mod analysis_level;
+pub mod analysis_pool;
+pub mod anchored_diagnostics;
 pub mod backend;
+pub mod binder_names;
+pub mod buffer_overlay;
+pub mod cache_schema;
+pub mod call_hierarchy;
+pub mod capabilities;
+pub mod completion_cost;
+pub mod completion_ranking;
+pub mod contract_interface;
+pub mod contracts_outline;
+pub mod conventions;
+pub mod cost_estimation;
+pub mod crash_reports;
+pub mod dead_channels;
+pub mod deploy_signing;
+pub mod deprecation;
 pub mod diagnostic_provider;
+pub mod diagnostic_registry;
+pub mod diagnostics_publisher;
+pub mod disk_index;
 pub mod document;
+pub mod document_links;
+pub mod document_resync;
+pub mod document_symbol_query;
+pub mod document_status;
+pub mod duplicate_sends;
+pub mod evaluation;
+pub mod expansion;
 pub mod features;
+pub mod folding;
+pub mod format_check;
+pub mod formatting;
 pub mod grpc_validator;
+pub mod health;
+pub mod indentation;
+pub mod index_checkpoint;
+pub mod inlay_hints;
+pub mod invocation_templates;
+pub mod library_paths;
+pub mod line_index;
+pub mod locals_fallback;
+pub mod matching_construct;
 pub mod models;
+pub mod move_contract;
+pub mod name_escapes;
+pub mod normalized_form;
+pub mod param_docs;
+pub mod pattern_examples;
+pub mod readiness;
+pub mod receive_patterns;
+pub mod refresh;
+pub mod rename_conflicts;
+pub mod return_channels;
 pub mod rholang_contracts;
+pub mod rnode_validation;
 pub mod rust_validator;
+pub mod scope_chain;
+pub mod scopes;
+pub mod selection_range;
 pub mod semantic_features;
+pub mod semantic_modifiers;
+pub mod semantic_tokens_delta;
+pub mod semantic_tokens_range;
 pub mod semantic_validator;
+pub mod settings;
+pub mod shared_documents;
+pub mod structural_query;
+pub mod structural_replace;
+pub mod style_lints;
 pub mod symbol_index;
+pub mod syntax_errors;
+pub mod task_markers;
+pub mod text_normalization;
+pub mod typing_diagnostics;
+pub mod validation_scheduler;
+pub mod virtual_document_events;
+pub mod workspace_folders;
+pub mod workspace_trust;
@@ -14,7 +14,12 @@ use crate::ir::semantic_node::SemanticNode;
 use crate::ir::symbol_table::SymbolTable;
 use crate::ir::transforms::symbol_table_builder::InvertedIndex;
 use crate::ir::global_index::GlobalSymbolIndex;
+use crate::ir::symbol_resolution::RegistryUriBindings;
+use crate::lsp::deprecation::DeprecatedDeclarations;
+use crate::lsp::line_index::LineIndex;
 use crate::lsp::symbol_index::SymbolIndex;
+use crate::lsp::text_normalization::TextFormat;
+use crate::parsers::rholang::{DirtyRanges, ParseKind};
 
 /// Language detected for a document based on file extension.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -101,10 +106,16 @@ pub struct CachedDocument {
     pub version: i32,
     /// Document text content
     pub text: Rope,
+    /// Line-start index of `text` for position conversions
+    pub line_index: Arc<LineIndex>,
     /// Position mappings for IR nodes
     pub positions: Arc<std::collections::HashMap<usize, (IrPosition, IrPosition)>>,
     /// Suffix array-based symbol index for O(m log n + k) substring search
     pub symbol_index: Arc<SymbolIndex>,
+    /// Contracts and names marked with a `@deprecated` doc directive
+    pub deprecations: Arc<DeprecatedDeclarations>,
+    /// `new` names bound to registry URIs and the URI literals of the document
+    pub registry_bindings: Arc<RegistryUriBindings>,
     /// Fast hash of document content for change detection
     pub content_hash: u64,
 }
@@ -114,8 +125,48 @@ pub struct CachedDocument {
 pub struct LspDocumentState {
     pub uri: Url,
     pub text: Rope,
+    /// Line-start index of `text`, updated incrementally on each change
+    pub line_index: LineIndex,
+    /// Syntax tree of `text`, reused as the base of the next incremental parse.
+    /// `None` until the document is first parsed.
+    pub tree: Option<Tree>,
+    /// Incremental vs full parses of this document
+    pub parse_counts: ParseCounts,
     pub version: i32,
     pub history: LspDocumentHistory,
+    /// Byte edits made by the last `apply`, in order; `None` if it replaced
+    /// the whole document
+    pub last_edits: Option<Vec<ByteEdit>>,
+    /// Bytes the last `apply` changed and the version it was applied to, for
+    /// reusing the IR of that version; `None` before the first change
+    pub dirty: Option<(i32, DirtyRanges)>,
+    /// BOM and line endings of the text as the client sent it
+    pub format: TextFormat,
+}
+
+/// A ranged change resolved to byte offsets of the text it was applied to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ByteEdit {
+    pub start: usize,
+    pub old_end: usize,
+    pub text: String,
+}
+
+/// Number of incremental and full parses of an open document
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParseCounts {
+    pub incremental: usize,
+    pub full: usize,
+}
+
+impl ParseCounts {
+    /// Counts one parse of the given kind.
+    pub fn record(&mut self, kind: ParseKind) {
+        match kind {
+            ParseKind::Incremental => self.incremental += 1,
+            ParseKind::Full => self.full += 1,
+        }
+    }
 }
 
 /// History of changes for incremental parsing and validation.
@@ -0,0 +1,399 @@
+//! Moving a contract to another file (`rholang.moveContract`)
+//!
+//! The "Move contract to file..." code action runs the command with the
+//! contract under the cursor; without a `target` the server asks which file
+//! to move it to, offering a new `<contract>.rho` next to the current file
+//! and the other Rholang files of its directory:
+//!
+//! ```json
+//! { "command": "rholang.moveContract",
+//!   "arguments": [{ "uri": "file:///w/bank.rho", "position": { "line": 3, "character": 11 },
+//!                   "target": "file:///w/transfer.rho" }] }
+//! ```
+//!
+//! Contracts defined on a `new` name follow the registry convention of
+//! `crate::ir::symbol_resolution::registry_uri`: the moved contract is
+//! published under a `rho:id:` URI (`rho:id:<contract>` unless its name was
+//! already declared with one),
+//!
+//! ```rholang
+//! new transfer(`rho:id:transfer`) in {
+//!   contract transfer(@from, @to, ret) = { … }
+//! }
+//! ```
+//!
+//! and the declaration left in the original file gets the same URI, so the
+//! sends there resolve to the contract in its new file. The contract is
+//! removed with the `|` joining it to its neighbours; a contract alone in a
+//! body leaves `Nil`. Moving the only process of a file to a new file renames
+//! the file instead. The result is a single [`WorkspaceEdit`] with the file
+//! operations and edits, applied through `workspace/applyEdit`.
+
+use std::sync::Arc;
+
+use ropey::Rope;
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{
+    CreateFile, CreateFileOptions, DocumentChangeOperation, DocumentChanges, OneOf, OptionalVersionedTextDocumentIdentifier,
+    Position as LspPosition, Range, RenameFile, ResourceOp, TextDocumentEdit, TextEdit, Url, WorkspaceEdit,
+};
+
+use crate::ir::rholang_node::{RholangNode, RholangNodeVector};
+use crate::lsp::call_hierarchy::{node_range, Positions};
+use crate::lsp::line_index::LineIndex;
+
+/// Moves the contract at a position to another file
+pub const MOVE_CONTRACT_COMMAND: &str = "rholang.moveContract";
+
+/// Arguments of the `rholang.moveContract` command
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveContractArgs {
+    pub uri: Url,
+    pub position: LspPosition,
+    /// File to move the contract to; the user is asked when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<Url>,
+}
+
+/// A contract that can be moved, and what moving it changes in its file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MovableContract {
+    pub name: String,
+    /// Source of the contract
+    pub source: String,
+    /// Column the contract starts at, removed from its continuation lines
+    pub indent: usize,
+    /// The contract and the `|` joining it to a neighbour
+    pub removal: Range,
+    /// What replaces the removal: nothing, or `Nil` for a contract alone in a body
+    pub leftover: &'static str,
+    /// Range of the `new` name the contract is defined on, with its registry URI if declared with one
+    pub declaration: Option<(Range, Option<String>)>,
+    /// The contract is the only process of its file
+    pub only_process: bool,
+}
+
+/// Where a contract is moved to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MoveTarget {
+    /// A file to create
+    NewFile(Url),
+    /// An existing file, appended to after `end`; `empty` when it holds no code
+    Existing { uri: Url, end: LspPosition, empty: bool },
+}
+
+impl MoveTarget {
+    pub fn uri(&self) -> &Url {
+        match self {
+            MoveTarget::NewFile(uri) | MoveTarget::Existing { uri, .. } => uri,
+        }
+    }
+}
+
+/// The processes of a parallel composition, flattening nested `Par` nodes
+/// and blocks, skipping comments and `Nil`.
+fn branches<'n>(node: &'n Arc<RholangNode>, out: &mut Vec<&'n Arc<RholangNode>>) {
+    match &**node {
+        RholangNode::Par { processes: Some(processes), .. } => {
+            for process in processes.iter() {
+                branches(process, out);
+            }
+        }
+        RholangNode::Par { left: Some(left), right: Some(right), .. } => {
+            branches(left, out);
+            branches(right, out);
+        }
+        RholangNode::Block { proc, .. } => branches(proc, out),
+        RholangNode::Comment { .. } | RholangNode::Nil { .. } => {}
+        _ => out.push(node),
+    }
+}
+
+/// The contract at byte `offset` among the processes of `node` and of the
+/// bodies of the `new`s there, with the declarations in scope and whether
+/// it is the only process at every level.
+fn locate<'n>(
+    node: &'n Arc<RholangNode>,
+    offset: usize,
+    positions: &Positions,
+    scope: &mut Vec<&'n RholangNodeVector>,
+    alone: bool,
+) -> Option<(&'n Arc<RholangNode>, usize, bool)> {
+    let mut processes = Vec::new();
+    branches(node, &mut processes);
+    let alone = alone && processes.len() == 1;
+    let process = *processes.iter().find(|process| {
+        positions
+            .get(&(Arc::as_ptr(process) as usize))
+            .is_some_and(|(start, end)| start.byte <= offset && offset <= end.byte)
+    })?;
+    match &**process {
+        RholangNode::Contract { .. } => Some((process, processes.len(), alone)),
+        RholangNode::New { decls, proc, .. } => {
+            scope.push(decls);
+            locate(proc, offset, positions, scope, alone)
+        }
+        _ => None,
+    }
+}
+
+/// The LSP position of byte `offset` of `text`.
+fn byte_position(text: &Rope, line_index: &LineIndex, offset: usize) -> Option<LspPosition> {
+    let (line, character) = line_index.position(offset, text)?;
+    Some(LspPosition::new(line as u32, character as u32))
+}
+
+/// The contract at `position`, if it can be moved: one composed in parallel
+/// at the top of the file or in the body of a top-level `new`.
+pub fn find_movable_contract(
+    ir: &Arc<RholangNode>,
+    positions: &Positions,
+    text: &Rope,
+    line_index: &LineIndex,
+    position: LspPosition,
+) -> Option<MovableContract> {
+    let offset = line_index.offset(position.line as usize, position.character as usize, text)?;
+    let mut scope = Vec::new();
+    let (contract, siblings, only_process) = locate(ir, offset, positions, &mut scope, true)?;
+    let RholangNode::Contract { name, .. } = &**contract else { return None };
+    let name = match &**name {
+        RholangNode::Var { name, .. } => name.clone(),
+        RholangNode::Quote { quotable, .. } => match &**quotable {
+            RholangNode::StringLiteral { value, .. } => value.clone(),
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    let (start, end) = positions.get(&(Arc::as_ptr(contract) as usize))?;
+    let (start, end) = (start.byte, end.byte);
+    let full = text.to_string();
+    if end > full.len() {
+        return None;
+    }
+
+    // Take one neighbouring `|` along, the following one if there is one
+    let (removal_start, removal_end, leftover) = if siblings == 1 {
+        (start, end, "Nil")
+    } else {
+        let after = end + (full.len() - end - full[end..].trim_start().len());
+        if full[after..].starts_with('|') {
+            let next = after + 1;
+            (start, next + (full.len() - next - full[next..].trim_start().len()), "")
+        } else {
+            let before = full[..start].trim_end();
+            match before.strip_suffix('|') {
+                Some(rest) => (rest.trim_end().len(), end, ""),
+                None => (start, end, ""),
+            }
+        }
+    };
+
+    let declaration = scope.iter().rev().find_map(|decls| {
+        decls.iter().find_map(|decl| {
+            let RholangNode::NameDecl { var, uri, .. } = &**decl else { return None };
+            match &**var {
+                RholangNode::Var { name: declared, .. } if *declared == name => {
+                    let registry_uri = uri.as_ref().and_then(|uri| match &**uri {
+                        RholangNode::UriLiteral { value, .. } => Some(value.clone()),
+                        _ => None,
+                    });
+                    Some((node_range(var, positions, text, line_index)?, registry_uri))
+                }
+                _ => None,
+            }
+        })
+    });
+
+    Some(MovableContract {
+        name,
+        source: full[start..end].to_string(),
+        indent: byte_position(text, line_index, start)?.character as usize,
+        removal: Range::new(
+            byte_position(text, line_index, removal_start)?,
+            byte_position(text, line_index, removal_end)?,
+        ),
+        leftover,
+        declaration,
+        only_process,
+    })
+}
+
+impl MovableContract {
+    /// The registry URI the contract is published under, if it is defined on a `new` name.
+    pub fn registry_uri(&self) -> Option<String> {
+        let (_, registry_uri) = self.declaration.as_ref()?;
+        Some(registry_uri.clone().unwrap_or_else(|| format!("rho:id:{}", self.name)))
+    }
+
+    /// The contract as written in its new file.
+    pub fn moved_text(&self) -> String {
+        let mut lines = self.source.lines();
+        let mut dedented = vec![lines.next().unwrap_or_default().to_string()];
+        for line in lines {
+            let indent = line.len() - line.trim_start_matches(' ').len();
+            dedented.push(line[indent.min(self.indent)..].to_string());
+        }
+        match self.registry_uri() {
+            Some(registry_uri) => {
+                let body: Vec<String> = dedented
+                    .iter()
+                    .map(|line| if line.is_empty() { String::new() } else { format!("  {}", line) })
+                    .collect();
+                format!("new {}(`{}`) in {{\n{}\n}}\n", self.name, registry_uri, body.join("\n"))
+            }
+            None => format!("{}\n", dedented.join("\n")),
+        }
+    }
+
+    /// Gives the declaration of the contract name the registry URI, if it has none yet.
+    fn declaration_edit(&self) -> Option<TextEdit> {
+        let (range, None) = self.declaration.as_ref()? else { return None };
+        let registry_uri = self.registry_uri()?;
+        Some(TextEdit { range: Range::new(range.end, range.end), new_text: format!("(`{}`)", registry_uri) })
+    }
+}
+
+fn document_edit(uri: &Url, edits: Vec<TextEdit>) -> DocumentChangeOperation {
+    DocumentChangeOperation::Edit(TextDocumentEdit {
+        text_document: OptionalVersionedTextDocumentIdentifier { uri: uri.clone(), version: None },
+        edits: edits.into_iter().map(OneOf::Left).collect(),
+    })
+}
+
+/// The workspace edit moving `contract` from `source` to `target`.
+pub fn move_contract_edit(source: &Url, contract: &MovableContract, target: &MoveTarget) -> WorkspaceEdit {
+    let mut operations = Vec::new();
+    match target {
+        // The file holds nothing else: it becomes the new file
+        MoveTarget::NewFile(uri) if contract.only_process => {
+            operations.push(DocumentChangeOperation::Op(ResourceOp::Rename(RenameFile {
+                old_uri: source.clone(),
+                new_uri: uri.clone(),
+                options: None,
+                annotation_id: None,
+            })));
+            if let Some(edit) = contract.declaration_edit() {
+                operations.push(document_edit(uri, vec![edit]));
+            }
+        }
+        _ => {
+            let (uri, insertion) = match target {
+                MoveTarget::NewFile(uri) => {
+                    operations.push(DocumentChangeOperation::Op(ResourceOp::Create(CreateFile {
+                        uri: uri.clone(),
+                        options: Some(CreateFileOptions { overwrite: Some(false), ignore_if_exists: Some(false) }),
+                        annotation_id: None,
+                    })));
+                    (uri, TextEdit { range: Range::default(), new_text: contract.moved_text() })
+                }
+                MoveTarget::Existing { uri, end, empty } => {
+                    let separator = match (empty, end.character) {
+                        (true, _) => "",
+                        (false, 0) => "|\n",
+                        (false, _) => "\n|\n",
+                    };
+                    let new_text = format!("{}{}", separator, contract.moved_text());
+                    (uri, TextEdit { range: Range::new(*end, *end), new_text })
+                }
+            };
+            operations.push(document_edit(uri, vec![insertion]));
+
+            let mut source_edits: Vec<TextEdit> = contract.declaration_edit().into_iter().collect();
+            source_edits.push(TextEdit { range: contract.removal, new_text: contract.leftover.to_string() });
+            operations.push(document_edit(source, source_edits));
+        }
+    }
+    WorkspaceEdit { document_changes: Some(DocumentChanges::Operations(operations)), ..Default::default() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::tree_sitter::parse_with_positions;
+
+    fn movable(source: &str, line: u32, character: u32) -> Option<MovableContract> {
+        let rope = Rope::from_str(source);
+        let (ir, positions) = parse_with_positions(source);
+        find_movable_contract(&ir, &positions, &rope, &LineIndex::from_rope(&rope), LspPosition::new(line, character))
+    }
+
+    fn source_edits(edit: &WorkspaceEdit, uri: &Url) -> Vec<TextEdit> {
+        let Some(DocumentChanges::Operations(operations)) = &edit.document_changes else { panic!("no operations") };
+        operations
+            .iter()
+            .filter_map(|operation| match operation {
+                DocumentChangeOperation::Edit(edit) if edit.text_document.uri == *uri => Some(&edit.edits),
+                _ => None,
+            })
+            .flatten()
+            .map(|edit| match edit {
+                OneOf::Left(edit) => edit.clone(),
+                OneOf::Right(annotated) => annotated.text_edit.clone(),
+            })
+            .collect()
+    }
+
+    const BANK: &str = "new transfer, log in {\n  contract transfer(@amount, ret) = {\n    ret!(amount)\n  } |\n  log!(1)\n}";
+
+    #[test]
+    fn test_find_movable_contract() {
+        let contract = movable(BANK, 1, 12).unwrap();
+        assert_eq!(contract.name, "transfer");
+        assert_eq!(contract.removal, Range::new(LspPosition::new(1, 2), LspPosition::new(4, 2)));
+        assert_eq!(contract.leftover, "");
+        assert_eq!(contract.declaration.as_ref().map(|(range, uri)| (range.start, uri.clone())), Some((LspPosition::new(0, 4), None)));
+        assert!(!contract.only_process);
+        assert_eq!(
+            contract.moved_text(),
+            "new transfer(`rho:id:transfer`) in {\n  contract transfer(@amount, ret) = {\n    ret!(amount)\n  }\n}\n"
+        );
+
+        // Not on a contract
+        assert!(movable(BANK, 4, 3).is_none());
+    }
+
+    #[test]
+    fn test_last_contract_takes_the_preceding_bar() {
+        let source = "new a, b in {\n  a!(1) |\n  contract b(ret) = { ret!(2) }\n}";
+        let contract = movable(source, 2, 12).unwrap();
+        assert_eq!(contract.removal, Range::new(LspPosition::new(1, 7), LspPosition::new(2, 31)));
+    }
+
+    #[test]
+    fn test_removal_counts_utf16_columns() {
+        // "é" is two bytes but one UTF-16 code unit
+        let source = "new a, b in {\n  a!(\"héllo\") | contract b(ret) = { ret!(2) }\n}";
+        let contract = movable(source, 1, 20).unwrap();
+        assert_eq!(contract.removal, Range::new(LspPosition::new(1, 13), LspPosition::new(1, 45)));
+    }
+
+    #[test]
+    fn test_move_to_existing_file() {
+        let (source, target) = (Url::parse("file:///w/bank.rho").unwrap(), Url::parse("file:///w/lib.rho").unwrap());
+        let contract = movable(BANK, 1, 12).unwrap();
+        let edit = move_contract_edit(
+            &source,
+            &contract,
+            &MoveTarget::Existing { uri: target.clone(), end: LspPosition::new(3, 1), empty: false },
+        );
+        let inserted = source_edits(&edit, &target);
+        assert!(inserted[0].new_text.starts_with("\n|\nnew transfer(`rho:id:transfer`) in {"));
+        let source_edits = source_edits(&edit, &source);
+        assert_eq!(source_edits[0].new_text, "(`rho:id:transfer`)");
+        assert_eq!(source_edits[1].new_text, "");
+    }
+
+    #[test]
+    fn test_only_process_renames_the_file() {
+        let (source, target) = (Url::parse("file:///w/bank.rho").unwrap(), Url::parse("file:///w/transfer.rho").unwrap());
+        let contract = movable("new transfer(`rho:id:bank`) in {\n  contract transfer(ret) = { Nil }\n}", 1, 12).unwrap();
+        assert!(contract.only_process);
+        assert_eq!(contract.leftover, "Nil");
+        let edit = move_contract_edit(&source, &contract, &MoveTarget::NewFile(target));
+        let Some(DocumentChanges::Operations(operations)) = &edit.document_changes else { panic!("no operations") };
+        assert!(matches!(operations.as_slice(), [DocumentChangeOperation::Op(ResourceOp::Rename(_))]));
+    }
+}
@@ -0,0 +1,396 @@
+//! Escape report for `new` names (`rholang.nameEscapes`)
+//!
+//! A `new` name is an unforgeable capability: only the processes it is given
+//! to can use it. For security reviews this module lists the `new` names that
+//! are handed out of their lexical scope, and how:
+//!
+//! - **public channel**: passed in a message on a free name or a quoted
+//!   channel such as `@"public"`, which anyone may listen on;
+//! - **registry**: passed to a registry insertion channel
+//!   (`rho:registry:insert…`), which publishes it under a URI;
+//! - **return channel**: passed on a channel that the enclosing code received
+//!   from elsewhere, a contract formal or a `for` binding, typically
+//!   `ret!(*name)`, which returns it to the caller.
+//!
+//! Only direct escapes are reported: a name sent over a private channel that
+//! itself escapes later is not followed. Hover on a `new` name lists its
+//! escape paths ([`escapes_at`]).
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::sync::Arc;
+
+use ropey::Rope;
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{Position as LspPosition, Range, Url};
+
+use crate::ir::rholang_node::{
+    Metadata, NodeBase, Position as IrPosition, RholangNode, RholangNodeVector, RholangSendType,
+};
+use crate::ir::visitor::Visitor;
+use crate::lsp::line_index::{lsp_range, LineIndex};
+use crate::lsp::scopes::{BinderOrigin, ScopeAnalysis, Span, UseRole};
+
+/// Lists the `new` names escaping their scope in a document or the workspace
+pub const NAME_ESCAPES_COMMAND: &str = "rholang.nameEscapes";
+
+/// URI scheme of report documents
+pub const REPORT_SCHEME: &str = "rholang-name-escapes";
+
+/// Prefix of the system names that insert into the registry
+const REGISTRY_INSERT: &str = "rho:registry:insert";
+
+/// Parameters of the `rholang.nameEscapes` command
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct NameEscapesParams {
+    /// Only report this document; defaults to the whole workspace
+    #[serde(default)]
+    pub uri: Option<Url>,
+}
+
+/// How a name leaves its scope
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EscapeKind {
+    PublicChannel,
+    Registry,
+    ReturnChannel,
+}
+
+/// One send through which a name escapes
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EscapePath {
+    pub kind: EscapeKind,
+    /// The channel of the send, as written
+    pub channel: String,
+    pub message: String,
+    /// Range of the send
+    pub range: Range,
+}
+
+/// A `new` name with its escape paths
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EscapingName {
+    pub uri: Url,
+    pub name: String,
+    /// Range of the name in its `new` declaration
+    pub declaration: Range,
+    pub escapes: Vec<EscapePath>,
+}
+
+/// Result of the `rholang.nameEscapes` command
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct NameEscapesReport {
+    /// URI of the report document
+    pub uri: Url,
+    /// Report document text (Markdown)
+    pub content: String,
+    pub names: Vec<EscapingName>,
+}
+
+/// Finds the `new` names of a document that escape their scope, in document order.
+pub fn find_name_escapes(
+    uri: &Url,
+    ir: &Arc<RholangNode>,
+    positions: &HashMap<usize, (IrPosition, IrPosition)>,
+    text: &Rope,
+    line_index: &LineIndex,
+) -> Vec<EscapingName> {
+    match ScopeAnalysis::build(ir, positions) {
+        Some(analysis) => escapes(uri, ir, positions, text, line_index, &analysis).into_values().collect(),
+        None => Vec::new(),
+    }
+}
+
+/// The escapes of the `new` name declared or used at `position`, if it escapes.
+pub fn escapes_at(
+    uri: &Url,
+    ir: &Arc<RholangNode>,
+    positions: &HashMap<usize, (IrPosition, IrPosition)>,
+    text: &Rope,
+    line_index: &LineIndex,
+    position: LspPosition,
+) -> Option<EscapingName> {
+    let analysis = ScopeAnalysis::build(ir, positions)?;
+    let binder = analysis.binder_at(line_index.offset(position.line as usize, position.character as usize, text)?)?;
+    escapes(uri, ir, positions, text, line_index, &analysis).remove(&binder)
+}
+
+/// Escaping names by binder; binders are numbered in document order within each scope.
+fn escapes(
+    uri: &Url,
+    ir: &Arc<RholangNode>,
+    positions: &HashMap<usize, (IrPosition, IrPosition)>,
+    text: &Rope,
+    line_index: &LineIndex,
+    analysis: &ScopeAnalysis,
+) -> BTreeMap<(usize, usize), EscapingName> {
+    let collector = SendCollector { positions, sends: Default::default(), registry_names: Default::default() };
+    collector.visit_node(ir);
+    let registry_names = collector.registry_names.into_inner();
+
+    let mut names: BTreeMap<(usize, usize), EscapingName> = BTreeMap::new();
+    for send in collector.sends.into_inner() {
+        let Some((kind, channel)) = classify_channel(&send.channel, positions, analysis, &registry_names) else {
+            continue;
+        };
+        let mut passed: Vec<(usize, usize)> = analysis
+            .uses
+            .iter()
+            .filter(|u| u.role == UseRole::Other && send.inputs.iter().any(|input| within(&u.span, input)))
+            .map(|u| u.binder)
+            .filter(|&(scope, index)| analysis.scopes[scope].binders[index].origin == BinderOrigin::New { uri: false })
+            .collect();
+        passed.sort_unstable();
+        passed.dedup();
+
+        for binder in passed {
+            let declared = &analysis.scopes[binder.0].binders[binder.1];
+            let message = match kind {
+                EscapeKind::PublicChannel => format!("'{}' is sent over the public channel {}", declared.name, channel),
+                EscapeKind::Registry => format!("'{}' is inserted into the registry via {}", declared.name, channel),
+                EscapeKind::ReturnChannel => format!("'{}' is returned to the caller via {}", declared.name, channel),
+            };
+            names
+                .entry(binder)
+                .or_insert_with(|| EscapingName {
+                    uri: uri.clone(),
+                    name: declared.name.clone(),
+                    declaration: lsp_range(text, line_index, &declared.span),
+                    escapes: Vec::new(),
+                })
+                .escapes
+                .push(EscapePath { kind, channel: channel.clone(), message, range: lsp_range(text, line_index, &send.span) });
+        }
+    }
+
+    for name in names.values_mut() {
+        name.escapes.sort_by_key(|escape| (escape.range.start.line, escape.range.start.character));
+    }
+    names
+}
+
+/// Whether a send on `channel` takes its messages out of the document's private names.
+fn classify_channel(
+    channel: &Arc<RholangNode>,
+    positions: &HashMap<usize, (IrPosition, IrPosition)>,
+    analysis: &ScopeAnalysis,
+    registry_names: &HashMap<Span, String>,
+) -> Option<(EscapeKind, String)> {
+    match &**channel {
+        RholangNode::Var { name, .. } => {
+            let span = positions.get(&(Arc::as_ptr(channel) as usize))?;
+            let used = analysis.uses.iter().find(|u| &u.span == span)?;
+            let binder = &analysis.scopes[used.binder.0].binders[used.binder.1];
+            let shown = format!("'{}'", name);
+            match binder.origin {
+                BinderOrigin::Implicit => Some((EscapeKind::PublicChannel, shown)),
+                BinderOrigin::Pattern => Some((EscapeKind::ReturnChannel, shown)),
+                BinderOrigin::New { uri: true } => registry_names
+                    .get(&binder.span)
+                    .map(|uri| (EscapeKind::Registry, format!("'{}' (`{}`)", name, uri))),
+                BinderOrigin::New { uri: false } => None,
+            }
+        }
+        RholangNode::Quote { quotable, .. } => {
+            let shown = match &**quotable {
+                RholangNode::StringLiteral { value, .. } => format!("@\"{}\"", value),
+                _ => "a quoted channel".to_string(),
+            };
+            Some((EscapeKind::PublicChannel, shown))
+        }
+        _ => None,
+    }
+}
+
+fn within(inner: &Span, outer: &Span) -> bool {
+    (outer.0.row, outer.0.column) <= (inner.0.row, inner.0.column)
+        && (inner.1.row, inner.1.column) <= (outer.1.row, outer.1.column)
+}
+
+/// Hover section listing the escape paths of `name`.
+pub fn escapes_markdown(name: &EscapingName) -> String {
+    let mut section = format!("**Escapes its scope** ({}):\n", name.escapes.len());
+    for escape in &name.escapes {
+        let _ = write!(section, "\n- line {}: {}", escape.range.start.line + 1, escape.message);
+    }
+    section
+}
+
+/// URI of the report for `target`, or for the whole workspace.
+pub fn report_uri(target: Option<&Url>) -> Url {
+    let path = target.map(|uri| uri.path()).unwrap_or("/workspace");
+    Url::parse(&format!("{}:{}", REPORT_SCHEME, path)).expect("valid report URI")
+}
+
+/// Renders the report of `names`, found in `documents` scanned documents.
+pub fn build_report(target: Option<&Url>, mut names: Vec<EscapingName>, documents: usize) -> NameEscapesReport {
+    names.sort_by(|a, b| {
+        a.uri
+            .as_str()
+            .cmp(b.uri.as_str())
+            .then((a.declaration.start.line, a.declaration.start.character).cmp(&(b.declaration.start.line, b.declaration.start.character)))
+    });
+
+    let mut content = String::from("# Escaping names\n\n");
+    let _ = writeln!(content, "{} name(s) in {} document(s)", names.len(), documents);
+    let mut current: Option<&Url> = None;
+    for name in &names {
+        if current != Some(&name.uri) {
+            let _ = write!(content, "\n## {}\n", name.uri);
+            current = Some(&name.uri);
+        }
+        let _ = write!(content, "\n### '{}' (line {})\n\n", name.name, name.declaration.start.line + 1);
+        for escape in &name.escapes {
+            let _ = writeln!(content, "- line {}: {}", escape.range.start.line + 1, escape.message);
+        }
+    }
+
+    NameEscapesReport { uri: report_uri(target), content, names }
+}
+
+/// A send with the spans of its inputs
+struct CollectedSend {
+    channel: Arc<RholangNode>,
+    span: Span,
+    inputs: Vec<Span>,
+}
+
+/// Collects sends, and the URIs of names declared as registry insertion channels
+struct SendCollector<'a> {
+    positions: &'a HashMap<usize, (IrPosition, IrPosition)>,
+    sends: RefCell<Vec<CollectedSend>>,
+    registry_names: RefCell<HashMap<Span, String>>,
+}
+
+impl SendCollector<'_> {
+    fn record(&self, node: &Arc<RholangNode>, channel: &Arc<RholangNode>, inputs: &RholangNodeVector) {
+        let span = |node: &Arc<RholangNode>| self.positions.get(&(Arc::as_ptr(node) as usize)).copied();
+        let Some(send_span) = span(node) else { return };
+        self.sends.borrow_mut().push(CollectedSend {
+            channel: channel.clone(),
+            span: send_span,
+            inputs: inputs.iter().filter_map(span).collect(),
+        });
+    }
+}
+
+impl Visitor for SendCollector<'_> {
+    fn visit_send(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        channel: &Arc<RholangNode>,
+        _send_type: &RholangSendType,
+        _send_type_pos: &IrPosition,
+        inputs: &RholangNodeVector,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.record(node, channel, inputs);
+        for input in inputs.iter() {
+            self.visit_node(input);
+        }
+        Arc::clone(node)
+    }
+
+    fn visit_send_sync(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        channel: &Arc<RholangNode>,
+        inputs: &RholangNodeVector,
+        cont: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.record(node, channel, inputs);
+        for input in inputs.iter() {
+            self.visit_node(input);
+        }
+        self.visit_node(cont);
+        Arc::clone(node)
+    }
+
+    fn visit_name_decl(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        var: &Arc<RholangNode>,
+        uri: &Option<Arc<RholangNode>>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        if let Some(RholangNode::UriLiteral { value, .. }) = uri.as_deref() {
+            if value.starts_with(REGISTRY_INSERT) {
+                if let Some(span) = self.positions.get(&(Arc::as_ptr(var) as usize)) {
+                    self.registry_names.borrow_mut().insert(*span, value.clone());
+                }
+            }
+        }
+        Arc::clone(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree_sitter::parse_with_positions;
+
+    const SOURCE: &str = r#"new insert(`rho:registry:insertArbitrary`), stdout(`rho:io:stdout`), secret, internal, leaked, ack in {
+  contract @"getSecret"(ret) = { ret!(*secret) } |
+  insert!(*leaked, *ack) |
+  @"public"!([*leaked]) |
+  internal!(*secret) | stdout!(*internal) |
+  for (_ <- internal) { Nil } | for (_ <- ack) { Nil }
+}"#;
+
+    fn parse(source: &str) -> (Url, Arc<RholangNode>, HashMap<usize, (IrPosition, IrPosition)>, Rope, LineIndex) {
+        let (ir, positions) = parse_with_positions(source);
+        (Url::parse("file:///capabilities.rho").unwrap(), ir, positions, Rope::from_str(source), LineIndex::new(source))
+    }
+
+    #[test]
+    fn test_escape_paths() {
+        let (uri, ir, positions, text, line_index) = parse(SOURCE);
+        let names: Vec<(String, Vec<EscapeKind>)> = find_name_escapes(&uri, &ir, &positions, &text, &line_index)
+            .into_iter()
+            .map(|name| (name.name, name.escapes.iter().map(|escape| escape.kind).collect()))
+            .collect();
+        // `internal` only travels over private and system channels
+        assert_eq!(
+            names,
+            vec![
+                ("secret".to_string(), vec![EscapeKind::ReturnChannel]),
+                ("leaked".to_string(), vec![EscapeKind::Registry, EscapeKind::PublicChannel]),
+                ("ack".to_string(), vec![EscapeKind::Registry]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_escapes_at_a_use() {
+        let (uri, ir, positions, text, line_index) = parse(SOURCE);
+        // `leaked` in `@"public"!([*leaked])`
+        let leaked = escapes_at(&uri, &ir, &positions, &text, &line_index, LspPosition::new(3, 15)).expect("leaked escapes");
+        assert_eq!(leaked.name, "leaked");
+        assert_eq!(leaked.escapes[1].channel, "@\"public\"");
+        assert_eq!(
+            escapes_markdown(&leaked),
+            "**Escapes its scope** (2):\n\n\
+             - line 3: 'leaked' is inserted into the registry via 'insert' (`rho:registry:insertArbitrary`)\n\
+             - line 4: 'leaked' is sent over the public channel @\"public\""
+        );
+        // `internal` does not escape
+        assert!(escapes_at(&uri, &ir, &positions, &text, &line_index, LspPosition::new(4, 3)).is_none());
+    }
+
+    #[test]
+    fn test_report_rendering() {
+        let (uri, ir, positions, text, line_index) = parse(SOURCE);
+        let report = build_report(None, find_name_escapes(&uri, &ir, &positions, &text, &line_index), 1);
+        assert_eq!(report.uri.as_str(), "rholang-name-escapes:/workspace");
+        assert!(report.content.starts_with("# Escaping names\n\n3 name(s) in 1 document(s)\n\n## file:///capabilities.rho\n"));
+        assert!(report.content.contains("\n### 'secret' (line 1)\n\n- line 2: 'secret' is returned to the caller via 'ret'\n"));
+    }
+}
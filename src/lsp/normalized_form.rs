@@ -0,0 +1,105 @@
+//! Normalized form of top-level processes, shown in hover
+//!
+//! Before running a program, RNode's normalizer rewrites it into a `Par`:
+//! variables become de Bruijn indices, `contract` becomes a persistent
+//! receive, parallel processes are sorted, and so on. When the
+//! `normalizedForm` initialization option is set and the embedded
+//! interpreter validates a document, the normalized form of each top-level
+//! process is kept, and hovering inside the process shows it:
+//!
+//! ```json
+//! { "normalizedForm": true }
+//! ```
+//!
+//! Forms are only known for processes that normalize without errors, and are
+//! dropped as soon as the document changes, until the next validation.
+//! Validation through a remote RNode does not return them.
+
+use tower_lsp::lsp_types::{Position, Range};
+
+use crate::i18n::tr;
+
+/// Initialization option turning normalized forms on
+pub const NORMALIZED_FORM_OPTION: &str = "normalizedForm";
+
+/// Characters of a normalized form shown in hover before it is cut
+const MAX_HOVER_CHARS: usize = 4000;
+
+/// Whether the client asked for normalized forms in hover.
+pub fn enabled_in(init_options: Option<&serde_json::Value>) -> bool {
+    init_options
+        .and_then(|opts| opts.get(NORMALIZED_FORM_OPTION))
+        .and_then(|enabled| enabled.as_bool())
+        .unwrap_or(false)
+}
+
+/// Normalized form of one top-level process
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NormalizedForm {
+    /// Range of the process in the document
+    pub range: Range,
+    /// The normalized `Par`, pretty-printed as Rholang
+    pub text: String,
+}
+
+/// Normalized forms of the top-level processes of one document version
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NormalizedForms {
+    pub version: i32,
+    pub forms: Vec<NormalizedForm>,
+}
+
+impl NormalizedForms {
+    /// The form of the top-level process containing `position`.
+    pub fn at(&self, position: Position) -> Option<&NormalizedForm> {
+        self.forms
+            .iter()
+            .find(|form| form.range.start <= position && position <= form.range.end)
+    }
+}
+
+/// Hover section showing `form`, cut after [`MAX_HOVER_CHARS`] characters.
+pub fn normalized_form_markdown(form: &NormalizedForm) -> String {
+    let text = match form.text.char_indices().nth(MAX_HOVER_CHARS) {
+        Some((cut, _)) => format!("{}\n…", &form.text[..cut]),
+        None => form.text.clone(),
+    };
+    format!("{}\n\n```rholang\n{}\n```", tr("hover.normalized-form", &[]), text.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn form(start: (u32, u32), end: (u32, u32), text: &str) -> NormalizedForm {
+        NormalizedForm {
+            range: Range::new(Position::new(start.0, start.1), Position::new(end.0, end.1)),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_form_at_position() {
+        let forms = NormalizedForms {
+            version: 1,
+            forms: vec![form((0, 0), (2, 1), "new x0 in {\n  x0!(1)\n}"), form((3, 0), (3, 8), "@\"a\"!(1)")],
+        };
+        assert_eq!(forms.at(Position::new(1, 4)).unwrap().range.start, Position::new(0, 0));
+        assert_eq!(forms.at(Position::new(3, 8)).unwrap().text, "@\"a\"!(1)");
+        assert!(forms.at(Position::new(5, 0)).is_none());
+
+        assert!(enabled_in(Some(&json!({ "normalizedForm": true }))));
+        assert!(!enabled_in(None));
+    }
+
+    #[test]
+    fn test_markdown_is_cut() {
+        let section = normalized_form_markdown(&form((0, 0), (0, 4), "Nil\n"));
+        assert_eq!(section, "**Normalized form**\n\n```rholang\nNil\n```");
+
+        let long = normalized_form_markdown(&form((0, 0), (0, 4), &"x".repeat(MAX_HOVER_CHARS + 10)));
+        assert!(long.ends_with("x\n…\n```"));
+        assert_eq!(long.matches('x').count(), MAX_HOVER_CHARS);
+    }
+}
@@ -0,0 +1,271 @@
+//! Checks of `@param` directives against contract formals
+//!
+//! A contract documented with `@param` directives, in doc comments or in
+//! regular comments such as
+//!
+//! ```rholang
+//! // @param amount how much to move
+//! // @returns true on success
+//! contract transfer(@from, @to, @amount, ret) = { … }
+//! ```
+//!
+//! is checked against its formals: a directive naming no formal, and a
+//! formal left undocumented while others are, are reported as warnings with
+//! code [`PARAM_DOC_CODE`]. Contracts without `@param` directives are not
+//! checked.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use ropey::Rope;
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Range};
+
+use crate::i18n::tr;
+use crate::ir::rholang_node::{Metadata, NodeBase, Position as IrPosition, RholangNode, RholangNodeVector};
+use crate::ir::structured_documentation::StructuredDocumentation;
+use crate::ir::transforms::documentation_attacher::DOC_METADATA_KEY;
+use crate::ir::visitor::Visitor;
+use crate::lsp::line_index::{lsp_range, LineIndex};
+
+/// Diagnostic source of parameter documentation warnings
+pub const PARAM_DOC_SOURCE: &str = "rholang-lint";
+/// Diagnostic code for parameter documentation that does not match the formals
+pub const PARAM_DOC_CODE: &str = "param-doc-mismatch";
+
+/// How a contract's `@param` directives disagree with its formals
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParamDocIssue {
+    /// A `@param` directive names no formal; reported on the contract name
+    Unknown,
+    /// A formal has no `@param` directive; reported on the formal
+    Undocumented,
+}
+
+/// A mismatch between a contract's `@param` directives and its formals
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParamDocMismatch {
+    pub issue: ParamDocIssue,
+    pub contract: String,
+    /// The parameter name, without `@`
+    pub name: String,
+    pub range: Range,
+}
+
+impl ParamDocMismatch {
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let args: [(&str, &dyn std::fmt::Display); 2] = [("name", &self.name), ("contract", &self.contract)];
+        let message = match self.issue {
+            ParamDocIssue::Unknown => tr("lint.param-doc-unknown", &args),
+            ParamDocIssue::Undocumented => tr("lint.param-doc-missing", &args),
+        };
+        Diagnostic {
+            range: self.range,
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: Some(NumberOrString::String(PARAM_DOC_CODE.to_string())),
+            source: Some(PARAM_DOC_SOURCE.to_string()),
+            message,
+            ..Default::default()
+        }
+    }
+}
+
+/// Finds the `@param` mismatches of a document whose IR went through the
+/// documentation attacher, in document order.
+pub fn find_param_doc_mismatches(
+    ir: &Arc<RholangNode>,
+    positions: &HashMap<usize, (IrPosition, IrPosition)>,
+    text: &Rope,
+    line_index: &LineIndex,
+) -> Vec<ParamDocMismatch> {
+    let checker = ParamDocChecker { positions, text, line_index, found: RefCell::new(Vec::new()) };
+    checker.visit_node(ir);
+    let mut found = checker.found.into_inner();
+    found.sort_by_key(|mismatch| (mismatch.range.start.line, mismatch.range.start.character));
+    found
+}
+
+/// The name bound by a simple formal (`x` or `@x`).
+fn formal_name(formal: &RholangNode) -> Option<&str> {
+    match formal {
+        RholangNode::Var { name, .. } if !name.is_empty() => Some(name),
+        RholangNode::Quote { quotable, .. } => match &**quotable {
+            RholangNode::Var { name, .. } if !name.is_empty() => Some(name),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Collects every variable of a formal pattern, so `@param` may also name a
+/// variable bound inside a structured pattern such as `@(from, to)`.
+#[derive(Default)]
+struct PatternVars {
+    names: RefCell<Vec<String>>,
+}
+
+impl Visitor for PatternVars {
+    fn visit_var(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        name: &String,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.names.borrow_mut().push(name.clone());
+        Arc::clone(node)
+    }
+}
+
+struct ParamDocChecker<'a> {
+    positions: &'a HashMap<usize, (IrPosition, IrPosition)>,
+    text: &'a Rope,
+    line_index: &'a LineIndex,
+    found: RefCell<Vec<ParamDocMismatch>>,
+}
+
+impl ParamDocChecker<'_> {
+    fn range(&self, node: &Arc<RholangNode>) -> Option<Range> {
+        let span = self.positions.get(&(Arc::as_ptr(node) as usize))?;
+        Some(lsp_range(self.text, self.line_index, span))
+    }
+
+    fn check_contract(
+        &self,
+        name: &Arc<RholangNode>,
+        formals: &[&Arc<RholangNode>],
+        docs: &StructuredDocumentation,
+    ) {
+        if docs.params.is_empty() {
+            return;
+        }
+        let contract = match &**name {
+            RholangNode::Var { name, .. } => name.clone(),
+            RholangNode::Quote { quotable, .. } => match &**quotable {
+                RholangNode::StringLiteral { value, .. } => value.clone(),
+                _ => return,
+            },
+            _ => return,
+        };
+
+        let pattern_vars = PatternVars::default();
+        for formal in formals {
+            pattern_vars.visit_node(formal);
+        }
+        let pattern_vars = pattern_vars.names.into_inner();
+        let documented: Vec<&str> = docs.params.iter().map(|param| param.name.trim_start_matches('@')).collect();
+
+        let mut found = self.found.borrow_mut();
+        if let Some(range) = self.range(name) {
+            for &param in &documented {
+                if !pattern_vars.iter().any(|var| var == param) {
+                    found.push(ParamDocMismatch {
+                        issue: ParamDocIssue::Unknown,
+                        contract: contract.clone(),
+                        name: param.to_string(),
+                        range,
+                    });
+                }
+            }
+        }
+        for formal in formals {
+            let Some(formal_name) = formal_name(formal) else { continue };
+            if formal_name == "_" || documented.contains(&formal_name) {
+                continue;
+            }
+            if let Some(range) = self.range(formal) {
+                found.push(ParamDocMismatch {
+                    issue: ParamDocIssue::Undocumented,
+                    contract: contract.clone(),
+                    name: formal_name.to_string(),
+                    range,
+                });
+            }
+        }
+    }
+}
+
+impl Visitor for ParamDocChecker<'_> {
+    fn visit_contract(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        name: &Arc<RholangNode>,
+        formals: &RholangNodeVector,
+        formals_remainder: &Option<Arc<RholangNode>>,
+        proc: &Arc<RholangNode>,
+        metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        let docs = metadata
+            .as_ref()
+            .and_then(|metadata| metadata.get(DOC_METADATA_KEY))
+            .and_then(|doc| doc.downcast_ref::<StructuredDocumentation>());
+        if let Some(docs) = docs {
+            let formals: Vec<&Arc<RholangNode>> = formals.iter().chain(formals_remainder.iter()).collect();
+            self.check_contract(name, &formals, docs);
+        }
+
+        self.visit_node(proc);
+        Arc::clone(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp::lsp_types::Position as LspPosition;
+
+    use crate::ir::rholang_node::compute_absolute_positions;
+    use crate::ir::transforms::documentation_attacher::DocumentationAttacher;
+    use crate::tree_sitter::{parse_code, parse_to_document_ir};
+
+    fn mismatches(source: &str) -> Vec<(ParamDocIssue, String, u32)> {
+        let tree = parse_code(source);
+        let rope = Rope::from_str(source);
+        let document_ir = parse_to_document_ir(&tree, &rope);
+        let ir = DocumentationAttacher::new(document_ir.clone()).visit_node(&document_ir.root);
+        let positions = compute_absolute_positions(&ir);
+        find_param_doc_mismatches(&ir, &positions, &rope, &LineIndex::new(source))
+            .into_iter()
+            .map(|mismatch| (mismatch.issue, mismatch.name, mismatch.range.start.line))
+            .collect()
+    }
+
+    #[test]
+    fn test_matching_directives() {
+        let source = "new transfer in {\n  // Moves funds\n  // @param from source\n  // @param @amount how much\n  // @returns true\n  contract transfer(@from, @amount) = { Nil }\n}";
+        assert!(mismatches(source).is_empty());
+    }
+
+    #[test]
+    fn test_unknown_and_undocumented_params() {
+        let source = "new transfer in {\n  // @param from source\n  // @param amout how much\n  contract transfer(@from, @amount, ret) = { Nil }\n}";
+        assert_eq!(
+            mismatches(source),
+            vec![
+                (ParamDocIssue::Unknown, "amout".to_string(), 3),
+                (ParamDocIssue::Undocumented, "amount".to_string(), 3),
+                (ParamDocIssue::Undocumented, "ret".to_string(), 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_undocumented_contracts_are_not_checked() {
+        assert!(mismatches("new log in {\n  // Logs a line\n  contract log(@line) = { Nil }\n}").is_empty());
+    }
+
+    #[test]
+    fn test_diagnostic() {
+        let mismatch = ParamDocMismatch {
+            issue: ParamDocIssue::Unknown,
+            contract: "transfer".to_string(),
+            name: "amout".to_string(),
+            range: Range::new(LspPosition::new(3, 11), LspPosition::new(3, 19)),
+        };
+        let diagnostic = mismatch.to_diagnostic();
+        assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::WARNING));
+        assert_eq!(diagnostic.code, Some(NumberOrString::String(PARAM_DOC_CODE.to_string())));
+        assert!(diagnostic.message.contains("amout"));
+    }
+}
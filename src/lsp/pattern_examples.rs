@@ -0,0 +1,291 @@
+//! Example values for `match` case patterns
+//!
+//! Hovering a case pattern of a `match` shows a few concrete values the
+//! pattern would match, to help newcomers read Rholang patterns:
+//!
+//! | Pattern              | Examples                                   |
+//! |----------------------|--------------------------------------------|
+//! | `[1, _]`             | `[1, ⟨any⟩]`                               |
+//! | `[head ...tail]`     | `[⟨head⟩]`, `[⟨head⟩, ⟨any⟩]`              |
+//! | `{"id": id}`         | `{"id": ⟨id⟩}`                             |
+//! | `Int \/ String`      | `0`, `42`, `""`, `"text"`                  |
+//!
+//! Wildcards are shown as `⟨any⟩` and variables as `⟨name⟩`; literals are
+//! shown as written, collections are expanded element by element, and a
+//! remainder adds a variant with one more element. Examples built only from
+//! literals, variables, collections and quotes are checked against the
+//! pattern with [`match_pat`]; examples of logical connectives and simple
+//! types (`Int`, `String`, …) cannot be checked that way and are kept as is.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use archery::ArcK;
+use ropey::Rope;
+use rpds::Vector;
+
+use crate::i18n::tr;
+use crate::ir::formatter::format_node;
+use crate::ir::rholang_node::{
+    find_node_at_position_with_path, match_pat, NodeBase, Position as IrPosition, RholangNode,
+};
+
+/// Largest number of examples shown for a pattern
+pub const MAX_EXAMPLES: usize = 4;
+
+/// An example value: how it is shown, and the value it stands for when the
+/// pattern engine can check it
+#[derive(Debug, Clone)]
+struct Example {
+    text: String,
+    value: Option<Arc<RholangNode>>,
+}
+
+impl Example {
+    fn unchecked(text: impl Into<String>) -> Self {
+        Example { text: text.into(), value: None }
+    }
+
+    /// A placeholder standing for any process
+    fn any(text: impl Into<String>) -> Self {
+        Example { text: text.into(), value: Some(value(RholangNode::Nil { base: base(), metadata: None })) }
+    }
+}
+
+fn base() -> NodeBase {
+    NodeBase::new_simple(IrPosition { row: 0, column: 0, byte: 0 }, 0, 0, 0)
+}
+
+fn value(node: RholangNode) -> Arc<RholangNode> {
+    Arc::new(node)
+}
+
+/// The case pattern of a `match` containing `position`, if any.
+pub fn case_pattern_at(
+    ir: &Arc<RholangNode>,
+    positions: &HashMap<usize, (IrPosition, IrPosition)>,
+    position: IrPosition,
+) -> Option<Arc<RholangNode>> {
+    let (_, path) = find_node_at_position_with_path(ir, positions, position)?;
+    // The innermost `match` whose next node on the path is one of its patterns
+    path.windows(2).rev().find_map(|pair| match &*pair[0] {
+        RholangNode::Match { cases, .. } => cases
+            .iter()
+            .find(|(pattern, _)| Arc::ptr_eq(pattern, &pair[1]))
+            .map(|(pattern, _)| pattern.clone()),
+        _ => None,
+    })
+}
+
+/// Up to [`MAX_EXAMPLES`] example values matched by `pattern`.
+pub fn pattern_examples(pattern: &Arc<RholangNode>) -> Vec<String> {
+    let mut texts: Vec<String> = Vec::new();
+    for example in examples(pattern) {
+        let matches = example.value.as_ref().is_none_or(|value| match_pat(pattern, value, &mut HashMap::new()));
+        if matches && !texts.contains(&example.text) {
+            texts.push(example.text);
+        }
+    }
+    texts
+}
+
+/// Markdown hover section listing the examples of `pattern`.
+pub fn examples_markdown(pattern: &Arc<RholangNode>) -> Option<String> {
+    let examples = pattern_examples(pattern);
+    if examples.is_empty() {
+        return None;
+    }
+    Some(format!("{}\n```rholang\n{}\n```", tr("hover.pattern-examples", &[]), examples.join("\n")))
+}
+
+fn examples(node: &Arc<RholangNode>) -> Vec<Example> {
+    let mut found = match &**node {
+        RholangNode::Wildcard { .. } => vec![Example::any("⟨any⟩")],
+        RholangNode::Var { name, .. } => vec![Example::any(format!("⟨{}⟩", name))],
+        RholangNode::BoolLiteral { .. }
+        | RholangNode::LongLiteral { .. }
+        | RholangNode::StringLiteral { .. }
+        | RholangNode::UriLiteral { .. }
+        | RholangNode::Nil { .. }
+        | RholangNode::Unit { .. } => vec![Example { text: text_of(node), value: Some(node.clone()) }],
+        RholangNode::SimpleType { value, .. } => simple_type_examples(value),
+        RholangNode::Parenthesized { expr, .. } => examples(expr),
+        RholangNode::Quote { quotable, .. } => examples(quotable)
+            .into_iter()
+            .map(|inner| Example {
+                text: format!("@{}", inner.text),
+                value: inner.value.map(|quotable| value(RholangNode::Quote { base: base(), quotable, metadata: None })),
+            })
+            .collect(),
+        RholangNode::List { elements, remainder, .. } => {
+            collection_examples(elements, remainder.is_some(), "[", "]", |elements, remainder| {
+                RholangNode::List { base: base(), elements, remainder, metadata: None }
+            })
+        }
+        RholangNode::Set { elements, remainder, .. } => {
+            collection_examples(elements, remainder.is_some(), "Set(", ")", |elements, remainder| {
+                RholangNode::Set { base: base(), elements, remainder, metadata: None }
+            })
+        }
+        RholangNode::Tuple { elements, .. } => {
+            let close = if elements.len() == 1 { ",)" } else { ")" };
+            collection_examples(elements, false, "(", close, |elements, _| {
+                RholangNode::Tuple { base: base(), elements, metadata: None }
+            })
+        }
+        RholangNode::Map { pairs, remainder, .. } => {
+            let entries: Vec<Vec<Example>> = pairs
+                .iter()
+                .map(|(key, entry)| {
+                    product(&[examples(key), examples(entry)])
+                        .into_iter()
+                        .map(|pair| Example::unchecked(format!("{}: {}", pair[0].text, pair[1].text)))
+                        .collect()
+                })
+                .collect();
+            let mut found: Vec<Example> = product(&entries)
+                .into_iter()
+                .map(|entries| Example::unchecked(format!("{{{}}}", join(&entries))))
+                .collect();
+            if remainder.is_some() {
+                if let Some(first) = product(&entries).into_iter().next() {
+                    let mut entries: Vec<String> = first.into_iter().map(|entry| entry.text).collect();
+                    entries.push("⟨key⟩: ⟨any⟩".to_string());
+                    found.push(Example::unchecked(format!("{{{}}}", entries.join(", "))));
+                }
+            }
+            found
+        }
+        RholangNode::Disjunction { left, right, .. } => {
+            let mut found: Vec<Example> = examples(left).into_iter().map(|e| Example::unchecked(e.text)).collect();
+            found.extend(examples(right).into_iter().map(|e| Example::unchecked(e.text)));
+            found
+        }
+        // Values of the left side; the right side usually only binds or narrows
+        RholangNode::Conjunction { left, .. } => {
+            examples(left).into_iter().map(|e| Example::unchecked(e.text)).collect()
+        }
+        RholangNode::Negation { operand, .. } => {
+            vec![Example::unchecked(format!("⟨anything but {}⟩", text_of(operand)))]
+        }
+        RholangNode::Eval { .. } | RholangNode::VarRef { .. } => {
+            vec![Example { text: text_of(node), value: Some(node.clone()) }]
+        }
+        _ => vec![Example::unchecked(text_of(node))],
+    };
+    found.truncate(MAX_EXAMPLES);
+    found
+}
+
+/// Examples of a list, set or tuple pattern; a remainder adds a variant
+/// with one more element.
+fn collection_examples(
+    elements: &Vector<Arc<RholangNode>, ArcK>,
+    has_remainder: bool,
+    open: &str,
+    close: &str,
+    build: impl Fn(Vector<Arc<RholangNode>, ArcK>, Option<Arc<RholangNode>>) -> RholangNode,
+) -> Vec<Example> {
+    let parts: Vec<Vec<Example>> = elements.iter().map(examples).collect();
+    let mut found = Vec::new();
+    for combination in product(&parts) {
+        let mut variants = vec![combination.clone()];
+        if has_remainder {
+            let mut longer = combination;
+            longer.push(Example::any("⟨any⟩"));
+            variants.push(longer);
+        }
+        for variant in variants {
+            let values: Option<Vector<Arc<RholangNode>, ArcK>> =
+                variant.iter().map(|part| part.value.clone()).collect::<Option<Vec<_>>>().map(|values| {
+                    values.into_iter().collect()
+                });
+            found.push(Example {
+                text: format!("{}{}{}", open, join(&variant), close),
+                value: values.map(|values| value(build(values, None))),
+            });
+        }
+    }
+    found
+}
+
+/// Representative values of a simple type pattern (`Int`, `String`, …)
+fn simple_type_examples(name: &str) -> Vec<Example> {
+    let values: &[&str] = match name {
+        "Bool" => &["true", "false"],
+        "Int" => &["0", "42"],
+        "String" => &["\"\"", "\"text\""],
+        "Uri" => &["`rho:id:example`"],
+        "ByteArray" => &["\"00ff\".hexToBytes()"],
+        _ => &[],
+    };
+    if values.is_empty() {
+        return vec![Example::unchecked(format!("⟨any {}⟩", name))];
+    }
+    values.iter().map(|text| Example::unchecked(*text)).collect()
+}
+
+/// Cartesian product of the examples of each part, capped at [`MAX_EXAMPLES`].
+fn product(parts: &[Vec<Example>]) -> Vec<Vec<Example>> {
+    let mut combinations: Vec<Vec<Example>> = vec![Vec::new()];
+    for part in parts {
+        let mut next = Vec::new();
+        for combination in &combinations {
+            for example in part {
+                let mut extended = combination.clone();
+                extended.push(example.clone());
+                next.push(extended);
+            }
+        }
+        next.truncate(MAX_EXAMPLES);
+        combinations = next;
+    }
+    combinations
+}
+
+fn join(parts: &[Example]) -> String {
+    parts.iter().map(|part| part.text.as_str()).collect::<Vec<_>>().join(", ")
+}
+
+fn text_of(node: &Arc<RholangNode>) -> String {
+    // `format_node` needs a rope but does not read it
+    format_node(node, false, None, &Rope::new(), node)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree_sitter::parse_with_positions;
+
+    /// Examples of the first case pattern of the `match` in `source`.
+    fn examples_of(source: &str) -> Vec<String> {
+        let (ir, positions) = parse_with_positions(source);
+        let column = source.find('{').unwrap() + 2;
+        let position = IrPosition { row: 0, column, byte: column };
+        let pattern = case_pattern_at(&ir, &positions, position).expect("case pattern");
+        pattern_examples(&pattern)
+    }
+
+    #[test]
+    fn test_collection_and_literal_examples() {
+        assert_eq!(examples_of("match x { [1, _] => Nil }"), vec!["[1, ⟨any⟩]"]);
+        assert_eq!(examples_of("match x { [h ...t] => Nil }"), vec!["[⟨h⟩]", "[⟨h⟩, ⟨any⟩]"]);
+        assert_eq!(examples_of("match x { (\"ok\", v) => Nil }"), vec!["(\"ok\", ⟨v⟩)"]);
+        assert_eq!(examples_of("match x { @y => Nil }"), vec!["@⟨y⟩"]);
+    }
+
+    #[test]
+    fn test_type_and_connective_examples() {
+        assert_eq!(examples_of("match x { Int \\/ Bool => Nil }"), vec!["0", "42", "true", "false"]);
+        assert_eq!(examples_of("match x { ~Nil => Nil }"), vec!["⟨anything but Nil⟩"]);
+    }
+
+    #[test]
+    fn test_no_pattern_outside_cases() {
+        let source = "match x { [1] => Nil }";
+        let (ir, positions) = parse_with_positions(source);
+        // On the matched expression `x`
+        let position = IrPosition { row: 0, column: 6, byte: 6 };
+        assert!(case_pattern_at(&ir, &positions, position).is_none());
+    }
+}
@@ -0,0 +1,177 @@
+//! Per-feature readiness during startup
+//!
+//! Right after `initialize`, requests can arrive before the workspace is
+//! indexed. The server moves through readiness stages, never backwards:
+//!
+//! | Stage           | Reached when                                   | Complete answers                         |
+//! |-----------------|------------------------------------------------|------------------------------------------|
+//! | `starting`      | `initialize`                                   | none                                     |
+//! | `parsingReady`  | `initialized`                                  | document-local features                  |
+//! | `symbolsReady`  | cross-document symbols were linked once        | definition, completion, rename, hover    |
+//! | `indexReady`    | every workspace file is indexed                | workspace symbols, references, calls     |
+//!
+//! Clients that set the `readiness` initialization option get a
+//! `rholang/readiness` notification on every transition, and a
+//! `rholang/partialResult` notification for each request answered before the
+//! stage it needs, so they can retry it rather than trust an answer that may
+//! be missing results:
+//!
+//! ```json
+//! { "stage": "symbolsReady", "previous": "parsingReady",
+//!   "ready": ["textDocument/definition", …], "pending": ["workspace/symbol", …] }
+//! { "method": "workspace/symbol", "stage": "symbolsReady", "requires": "indexReady", "retriable": true }
+//! ```
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::notification::Notification;
+
+/// Initialization option turning readiness notifications on
+pub const READINESS_OPTION: &str = "readiness";
+
+/// Whether the client asked for readiness notifications.
+pub fn enabled_in(init_options: Option<&serde_json::Value>) -> bool {
+    init_options
+        .and_then(|opts| opts.get(READINESS_OPTION))
+        .and_then(|enabled| enabled.as_bool())
+        .unwrap_or(false)
+}
+
+/// Startup stage, from least to most ready
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ReadinessStage {
+    #[default]
+    Starting,
+    ParsingReady,
+    SymbolsReady,
+    IndexReady,
+}
+
+/// Requests whose answers depend on cross-document state, with the stage
+/// they need; every other request is complete from `parsingReady` on.
+const REQUIREMENTS: &[(&str, ReadinessStage)] = &[
+    ("textDocument/definition", ReadinessStage::SymbolsReady),
+    ("textDocument/completion", ReadinessStage::SymbolsReady),
+    ("textDocument/rename", ReadinessStage::SymbolsReady),
+    ("textDocument/hover", ReadinessStage::SymbolsReady),
+    ("workspace/symbol", ReadinessStage::IndexReady),
+    ("textDocument/references", ReadinessStage::IndexReady),
+    ("callHierarchy/incomingCalls", ReadinessStage::IndexReady),
+];
+
+/// The stage `method` needs for a complete answer.
+pub fn required_stage(method: &str) -> ReadinessStage {
+    REQUIREMENTS
+        .iter()
+        .find(|(name, _)| *name == method)
+        .map_or(ReadinessStage::ParsingReady, |(_, stage)| *stage)
+}
+
+/// Parameters of the `rholang/readiness` notification
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ReadinessParams {
+    pub stage: ReadinessStage,
+    pub previous: ReadinessStage,
+    /// Cross-document requests answered completely from now on
+    pub ready: Vec<String>,
+    /// Cross-document requests that may still miss results
+    pub pending: Vec<String>,
+}
+
+/// `rholang/readiness`
+pub enum Readiness {}
+
+impl Notification for Readiness {
+    type Params = ReadinessParams;
+    const METHOD: &'static str = "rholang/readiness";
+}
+
+/// Parameters of the `rholang/partialResult` notification
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PartialResultParams {
+    pub method: String,
+    pub stage: ReadinessStage,
+    pub requires: ReadinessStage,
+    /// Retrying once `requires` is reached gives the complete answer
+    pub retriable: bool,
+}
+
+/// `rholang/partialResult`
+pub enum PartialResult {}
+
+impl Notification for PartialResult {
+    type Params = PartialResultParams;
+    const METHOD: &'static str = "rholang/partialResult";
+}
+
+/// The readiness stage of a server
+#[derive(Debug, Default)]
+pub struct ReadinessTracker {
+    stage: RwLock<ReadinessStage>,
+}
+
+impl ReadinessTracker {
+    pub fn stage(&self) -> ReadinessStage {
+        *self.stage.read()
+    }
+
+    /// Moves to `stage` if it is further than the current one, returning the
+    /// transition to announce.
+    pub fn advance(&self, stage: ReadinessStage) -> Option<ReadinessParams> {
+        let mut current = self.stage.write();
+        if stage <= *current {
+            return None;
+        }
+        let previous = std::mem::replace(&mut *current, stage);
+        let (ready, pending) = REQUIREMENTS
+            .iter()
+            .map(|(method, required)| (method.to_string(), *required <= stage))
+            .partition::<Vec<_>, _>(|(_, ready)| *ready);
+        Some(ReadinessParams {
+            stage,
+            previous,
+            ready: ready.into_iter().map(|(method, _)| method).collect(),
+            pending: pending.into_iter().map(|(method, _)| method).collect(),
+        })
+    }
+
+    /// The partial result hint for answering `method` now, if the answer may be incomplete.
+    pub fn partial(&self, method: &str) -> Option<PartialResultParams> {
+        let stage = self.stage();
+        let requires = required_stage(method);
+        (stage < requires).then(|| PartialResultParams {
+            method: method.to_string(),
+            stage,
+            requires,
+            retriable: true,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stages_only_advance() {
+        let tracker = ReadinessTracker::default();
+        assert!(tracker.partial("textDocument/foldingRange").is_some(), "nothing is ready while starting");
+
+        let transition = tracker.advance(ReadinessStage::SymbolsReady).unwrap();
+        assert_eq!(transition.previous, ReadinessStage::Starting);
+        assert!(transition.ready.contains(&"textDocument/definition".to_string()));
+        assert!(transition.pending.contains(&"workspace/symbol".to_string()));
+
+        assert_eq!(tracker.advance(ReadinessStage::ParsingReady), None);
+        assert_eq!(tracker.stage(), ReadinessStage::SymbolsReady);
+        assert_eq!(tracker.partial("textDocument/definition"), None);
+        let partial = tracker.partial("workspace/symbol").unwrap();
+        assert_eq!(partial.requires, ReadinessStage::IndexReady);
+        assert!(partial.retriable);
+
+        let transition = tracker.advance(ReadinessStage::IndexReady).unwrap();
+        assert!(transition.pending.is_empty());
+        assert_eq!(tracker.partial("workspace/symbol"), None);
+    }
+}
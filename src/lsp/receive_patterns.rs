@@ -0,0 +1,475 @@
+//! Receive pattern suggestions for a contract's return channel
+//!
+//! When the cursor is in the pattern of `for (… <- retCh)` and `retCh` is
+//! passed to a known contract (`transfer!(from, to, 10, *retCh)`), completion
+//! offers receive patterns shaped like what the contract sends back:
+//!
+//! ```rholang
+//! contract transfer(@from, @to, @amount, ret) = {
+//!   … ret!(("ok", balance)) … ret!(("error", "insufficient funds"))
+//! }
+//! ```
+//!
+//! yields `@("ok", balance)` and `@("error", "insufficient funds")`.
+//!
+//! The shapes come from a flow analysis of the contract body: sends on the
+//! formal the channel is bound to, followed through calls that forward the
+//! formal to other known contracts. String and boolean literals inside
+//! collections are kept as literal patterns (they are usually tags); other
+//! values become binders named after the sent variable, the map key, or the
+//! kind of value.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use ropey::Rope;
+use tower_lsp::lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionTextEdit, Documentation, InsertTextFormat, Position as LspPosition,
+    Range, TextEdit,
+};
+
+use crate::ir::rholang_node::{collect_calls, RholangNode};
+use crate::lsp::contracts_outline::node_name;
+use crate::lsp::line_index::{line_chars, line_column, LineIndex};
+
+/// How many contract calls the flow analysis follows from the called contract
+const MAX_FORWARDING_DEPTH: usize = 4;
+/// How deep sent collections are turned into nested patterns
+const MAX_PATTERN_DEPTH: usize = 3;
+
+/// The receive bind under the cursor
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReceiveContext {
+    /// Channel the bind receives from
+    pub channel: String,
+    /// Range of the bind's pattern, replaced by the completion
+    pub range: Range,
+    /// Current text of the pattern
+    pub pattern: String,
+}
+
+/// Finds the receive bind whose pattern contains `position`.
+///
+/// Only the current line is looked at, and the bind must already name its
+/// channel: `for (resp| <- retCh)`.
+pub fn receive_context(text: &Rope, line_index: &LineIndex, position: LspPosition) -> Option<ReceiveContext> {
+    let (chars, cursor) = line_chars(text, line_index, position)?;
+
+    // Start of the bind: after the `for (` or the `&`/`;` separating it from the previous bind
+    let open = (0..cursor).rev().find(|&i| chars[i] == '(' && preceded_by_for(&chars, i))?;
+    let mut start = open + 1;
+    let mut depth = 0i32;
+    for (i, &c) in chars.iter().enumerate().take(cursor).skip(open + 1) {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => {
+                depth -= 1;
+                if depth < 0 {
+                    return None;
+                }
+            }
+            '&' | ';' if depth == 0 => start = i + 1,
+            _ => {}
+        }
+    }
+
+    // End of the pattern: the arrow, before the bind or the `for` is closed
+    let (end, channel) = bind_source(&chars, cursor, depth)?;
+    if channel.is_empty() {
+        return None;
+    }
+
+    let trimmed_start = (start..end).find(|&i| !chars[i].is_whitespace()).unwrap_or(end).min(cursor);
+    let trimmed_end = (start..end).rev().find(|&i| !chars[i].is_whitespace()).map_or(start, |i| i + 1).max(cursor);
+    Some(ReceiveContext {
+        channel,
+        range: Range {
+            start: LspPosition { line: position.line, character: line_column(line_index, &chars, trimmed_start) },
+            end: LspPosition { line: position.line, character: line_column(line_index, &chars, trimmed_end) },
+        },
+        pattern: chars[trimmed_start..trimmed_end].iter().collect(),
+    })
+}
+
+/// Finds the arrow ending the bind pattern that continues at `from`, `depth`
+/// brackets deep, and returns its index with the channel named after it (empty
+/// if the source is not a plain name).
+pub(crate) fn bind_source(chars: &[char], from: usize, mut depth: i32) -> Option<(usize, String)> {
+    let mut end = None;
+    let mut i = from;
+    while i < chars.len() {
+        match chars[i] {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => {
+                depth -= 1;
+                if depth < 0 {
+                    return None;
+                }
+            }
+            '&' | ';' if depth == 0 => return None,
+            '<' if depth == 0 && matches!(chars.get(i + 1), Some('-' | '=' | '<')) => {
+                end = Some(i);
+                break;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    let end = end?;
+
+    let mut arrow_end = end + 1;
+    while arrow_end < chars.len() && matches!(chars[arrow_end], '<' | '-' | '=') {
+        arrow_end += 1;
+    }
+    let channel = chars[arrow_end..]
+        .iter()
+        .skip_while(|c| c.is_whitespace())
+        .take_while(|c| c.is_alphanumeric() || **c == '_' || **c == '\'')
+        .collect();
+    Some((end, channel))
+}
+
+pub(crate) fn preceded_by_for(chars: &[char], open: usize) -> bool {
+    let mut i = open;
+    while i > 0 && chars[i - 1].is_whitespace() {
+        i -= 1;
+    }
+    i >= 3
+        && chars[i - 3..i] == ['f', 'o', 'r']
+        && (i == 3 || !(chars[i - 4].is_alphanumeric() || chars[i - 4] == '_'))
+}
+
+/// A suggested receive pattern
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReceivePattern {
+    /// Pattern text, e.g. `@("ok", balance)`
+    pub label: String,
+    /// The same pattern with binders as snippet placeholders
+    pub snippet: String,
+    /// Contract the channel is passed to
+    pub contract: String,
+    /// Formal of that contract the channel is bound to
+    pub formal: String,
+}
+
+impl ReceivePattern {
+    /// Converts the suggestion into a snippet completion replacing the bind's pattern.
+    pub fn to_completion_item(&self, context: &ReceiveContext, rank: usize) -> CompletionItem {
+        CompletionItem {
+            label: self.label.clone(),
+            kind: Some(CompletionItemKind::SNIPPET),
+            detail: Some(format!("sent by {} on '{}'", self.contract, self.formal)),
+            documentation: Some(Documentation::String(format!(
+                "Receive pattern matching a value that `{}` sends on its `{}` channel",
+                self.contract, self.formal
+            ))),
+            // Keep the item visible whatever the pattern currently contains
+            filter_text: Some(context.pattern.clone()),
+            sort_text: Some(format!("0{:03}", rank)),
+            preselect: Some(rank == 0),
+            insert_text_format: Some(InsertTextFormat::SNIPPET),
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit { range: context.range, new_text: self.snippet.clone() })),
+            ..Default::default()
+        }
+    }
+}
+
+/// Suggests receive patterns for binds on `channel` in `ir`.
+///
+/// `resolve(name, arity)` returns the known contracts called `name` that
+/// accept `arity` arguments.
+pub fn suggest_receive_patterns(
+    ir: &Arc<RholangNode>,
+    channel: &str,
+    resolve: &dyn Fn(&str, usize) -> Vec<Arc<RholangNode>>,
+) -> Vec<ReceivePattern> {
+    let mut calls = Vec::new();
+    collect_calls(ir, &mut calls);
+
+    let mut suggestions: Vec<ReceivePattern> = Vec::new();
+    for (contract_name, index, arity) in calls.iter().filter_map(|call| passes_channel(call, channel)) {
+        for contract in resolve(&contract_name, arity) {
+            let Some(formal) = formal_name(&contract, index) else { continue };
+            let mut visited = HashSet::new();
+            let mut sends = Vec::new();
+            sends_on_formal(&contract, index, resolve, 0, &mut visited, &mut sends);
+            for inputs in sends {
+                let (label, snippet) = render_pattern(&inputs);
+                if suggestions.iter().any(|existing| existing.label == label) {
+                    continue;
+                }
+                suggestions.push(ReceivePattern { label, snippet, contract: contract_name.clone(), formal: formal.clone() });
+            }
+        }
+    }
+    suggestions
+}
+
+/// Whether `contract` accepts a call with `arity` arguments.
+pub fn accepts_arity(contract: &RholangNode, arity: usize) -> bool {
+    match contract {
+        RholangNode::Contract { formals, formals_remainder, .. } => {
+            formals.len() == arity || (formals_remainder.is_some() && arity >= formals.len())
+        }
+        _ => false,
+    }
+}
+
+/// If `call` is a send passing `channel` to a named contract, returns
+/// (contract name, argument index, arity).
+pub(crate) fn passes_channel(call: &Arc<RholangNode>, channel: &str) -> Option<(String, usize, usize)> {
+    let (RholangNode::Send { channel: target, inputs, .. } | RholangNode::SendSync { channel: target, inputs, .. }) =
+        &**call
+    else {
+        return None;
+    };
+    let index = inputs.iter().position(|input| passed_name(input).as_deref() == Some(channel))?;
+    let contract = node_name(target)?;
+    (contract != channel).then_some((contract, index, inputs.len()))
+}
+
+/// Name passed as a send argument: `*name` or a bare variable.
+fn passed_name(input: &RholangNode) -> Option<String> {
+    match input {
+        RholangNode::Eval { name, .. } => node_name(name),
+        RholangNode::Var { name, .. } if !name.is_empty() => Some(name.clone()),
+        _ => None,
+    }
+}
+
+fn formal_name(contract: &RholangNode, index: usize) -> Option<String> {
+    let RholangNode::Contract { formals, .. } = contract else { return None };
+    formals.get(index).and_then(|formal| node_name(formal))
+}
+
+/// Collects the argument lists sent on formal `index` of `contract`, following
+/// calls that forward the formal to other contracts.
+fn sends_on_formal(
+    contract: &Arc<RholangNode>,
+    index: usize,
+    resolve: &dyn Fn(&str, usize) -> Vec<Arc<RholangNode>>,
+    depth: usize,
+    visited: &mut HashSet<(usize, usize)>,
+    sends: &mut Vec<Vec<Arc<RholangNode>>>,
+) {
+    if !visited.insert((Arc::as_ptr(contract) as usize, index)) {
+        return;
+    }
+    let RholangNode::Contract { proc, .. } = &**contract else { return };
+    let Some(formal) = formal_name(contract, index) else { return };
+
+    let mut calls = Vec::new();
+    collect_calls(proc, &mut calls);
+    for call in &calls {
+        let (RholangNode::Send { channel, inputs, .. } | RholangNode::SendSync { channel, inputs, .. }) = &**call
+        else {
+            continue;
+        };
+        if node_name(channel).as_deref() == Some(formal.as_str()) {
+            sends.push(inputs.iter().cloned().collect());
+        } else if depth < MAX_FORWARDING_DEPTH {
+            if let Some((callee, forwarded, arity)) = passes_channel(call, &formal) {
+                for target in resolve(&callee, arity) {
+                    sends_on_formal(&target, forwarded, resolve, depth + 1, visited, sends);
+                }
+            }
+        }
+    }
+}
+
+/// Shape of a sent value, as a receive pattern
+#[derive(Debug, Clone, PartialEq)]
+enum Shape {
+    /// Binds the value (`@x` at the top level)
+    Process(String),
+    /// Binds a sent name (`*ack` is received as `ack`)
+    Name(String),
+    Literal(String),
+    List(Vec<Shape>),
+    Tuple(Vec<Shape>),
+    Map(Vec<(String, Shape)>),
+}
+
+fn shape_of(node: &RholangNode, hint: Option<&str>, depth: usize) -> Shape {
+    let binder = |default: &str| Shape::Process(hint.unwrap_or(default).to_string());
+    let nested = depth > 0;
+    match node {
+        RholangNode::Var { name, .. } if !name.is_empty() => Shape::Process(name.clone()),
+        RholangNode::Eval { name, .. } => Shape::Name(node_name(name).unwrap_or_else(|| "name".to_string())),
+        RholangNode::Block { proc, .. } => shape_of(proc, hint, depth),
+        RholangNode::Parenthesized { expr, .. } => shape_of(expr, hint, depth),
+        RholangNode::StringLiteral { value, .. } if nested => Shape::Literal(format!("{:?}", value)),
+        RholangNode::BoolLiteral { value, .. } if nested => Shape::Literal(value.to_string()),
+        RholangNode::StringLiteral { .. } => binder("message"),
+        RholangNode::BoolLiteral { .. } => binder("flag"),
+        RholangNode::LongLiteral { .. } => binder("n"),
+        _ if depth >= MAX_PATTERN_DEPTH => binder("value"),
+        RholangNode::List { elements, remainder: None, .. } => {
+            Shape::List(elements.iter().map(|element| shape_of(element, None, depth + 1)).collect())
+        }
+        RholangNode::Tuple { elements, .. } => {
+            Shape::Tuple(elements.iter().map(|element| shape_of(element, None, depth + 1)).collect())
+        }
+        RholangNode::Map { pairs, remainder: None, .. } => {
+            let entries: Option<Vec<(String, Shape)>> = pairs
+                .iter()
+                .map(|(key, value)| {
+                    let RholangNode::StringLiteral { value: key, .. } = &**key else { return None };
+                    let hint = is_identifier(key).then_some(key.as_str());
+                    Some((format!("{:?}", key), shape_of(value, hint, depth + 1)))
+                })
+                .collect();
+            entries.map(Shape::Map).unwrap_or_else(|| binder("value"))
+        }
+        _ => binder("result"),
+    }
+}
+
+pub(crate) fn is_identifier(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_') && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Renders the receive pattern for one send's arguments as (plain text, snippet).
+fn render_pattern(inputs: &[Arc<RholangNode>]) -> (String, String) {
+    let mut renderer = Renderer::default();
+    let parts: Vec<(String, String)> = inputs
+        .iter()
+        .map(|input| match shape_of(input, None, 0) {
+            Shape::Name(name) => renderer.binder(&name),
+            shape => {
+                let (plain, snippet) = renderer.process(&shape);
+                (format!("@{}", plain), format!("@{}", snippet))
+            }
+        })
+        .collect();
+    let (plain, snippet): (Vec<String>, Vec<String>) = parts.into_iter().unzip();
+    (plain.join(", "), snippet.join(", "))
+}
+
+/// Numbers snippet placeholders and keeps binder names unique
+#[derive(Default)]
+struct Renderer {
+    placeholders: usize,
+    used: HashMap<String, usize>,
+}
+
+impl Renderer {
+    fn binder(&mut self, name: &str) -> (String, String) {
+        let count = self.used.entry(name.to_string()).or_insert(0);
+        *count += 1;
+        let unique = if *count == 1 { name.to_string() } else { format!("{}{}", name, count) };
+        self.placeholders += 1;
+        (unique.clone(), format!("${{{}:{}}}", self.placeholders, unique))
+    }
+
+    fn process(&mut self, shape: &Shape) -> (String, String) {
+        match shape {
+            Shape::Process(name) | Shape::Name(name) => self.binder(name),
+            Shape::Literal(text) => (text.clone(), escape_snippet(text)),
+            Shape::List(elements) => self.sequence(elements, "[", "]"),
+            Shape::Tuple(elements) => self.sequence(elements, "(", if elements.len() == 1 { ",)" } else { ")" }),
+            Shape::Map(entries) => {
+                let (plain, snippet): (Vec<String>, Vec<String>) = entries
+                    .iter()
+                    .map(|(key, value)| {
+                        let (plain, snippet) = self.process(value);
+                        (format!("{}: {}", key, plain), format!("{}: {}", escape_snippet(key), snippet))
+                    })
+                    .unzip();
+                (format!("{{{}}}", plain.join(", ")), format!("{{{}\\}}", snippet.join(", ")))
+            }
+        }
+    }
+
+    fn sequence(&mut self, elements: &[Shape], open: &str, close: &str) -> (String, String) {
+        let (plain, snippet): (Vec<String>, Vec<String>) = elements.iter().map(|element| self.process(element)).unzip();
+        (
+            format!("{}{}{}", open, plain.join(", "), close),
+            format!("{}{}{}", open, snippet.join(", "), escape_snippet(close)),
+        )
+    }
+}
+
+/// Escapes the characters that are special in LSP snippets.
+pub(crate) fn escape_snippet(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('$', "\\$").replace('}', "\\}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::rholang_node::collect_contracts;
+    use crate::tree_sitter::{parse_code, parse_to_ir};
+
+    fn suggestions(source: &str, channel: &str) -> Vec<ReceivePattern> {
+        let tree = parse_code(source);
+        let ir = parse_to_ir(&tree, &Rope::from_str(source));
+        let mut contracts = Vec::new();
+        collect_contracts(&ir, &mut contracts);
+        let resolve = |name: &str, arity: usize| {
+            contracts
+                .iter()
+                .filter(|contract| match &***contract {
+                    RholangNode::Contract { name: contract_name, .. } => {
+                        node_name(contract_name).as_deref() == Some(name) && accepts_arity(contract, arity)
+                    }
+                    _ => false,
+                })
+                .cloned()
+                .collect()
+        };
+        suggest_receive_patterns(&ir, channel, &resolve)
+    }
+
+    #[test]
+    fn test_receive_context() {
+        let text = Rope::from_str("new retCh in {\n  for (resp <- retCh) { Nil }\n}");
+        let context = receive_context(&text, &LineIndex::from_rope(&text), LspPosition { line: 1, character: 9 }).unwrap();
+        assert_eq!(context.channel, "retCh");
+        assert_eq!(context.pattern, "resp");
+        assert_eq!(context.range.start.character, 7);
+        assert_eq!(context.range.end.character, 11);
+
+        // Empty pattern, second bind of a join
+        let text = Rope::from_str("for (@x <- a &  <= retCh) { Nil }");
+        let context = receive_context(&text, &LineIndex::from_rope(&text), LspPosition { line: 0, character: 14 }).unwrap();
+        assert_eq!((context.channel.as_str(), context.pattern.as_str()), ("retCh", ""));
+
+        // Cursor on the channel or in the body
+        assert!(receive_context(&text, &LineIndex::from_rope(&text), LspPosition { line: 0, character: 20 }).is_none());
+        assert!(receive_context(&text, &LineIndex::from_rope(&text), LspPosition { line: 0, character: 28 }).is_none());
+    }
+
+    #[test]
+    fn test_patterns_from_contract_sends() {
+        let source = r#"
+contract transfer(@from, @to, @amount, ret) = {
+  if (amount > 100) { ret!(("error", "insufficient funds")) }
+  else { new balance in { ret!(("ok", *balance)) } }
+}
+| new retCh in {
+  transfer!("alice", "bob", 10, *retCh)
+  | for (resp <- retCh) { Nil }
+}"#;
+        let found = suggestions(source, "retCh");
+        let labels: Vec<&str> = found.iter().map(|p| p.label.as_str()).collect();
+        assert_eq!(labels, vec![r#"@("error", "insufficient funds")"#, r#"@("ok", balance)"#]);
+        assert_eq!(found[1].snippet, r#"@("ok", ${1:balance})"#);
+        assert_eq!((found[0].contract.as_str(), found[0].formal.as_str()), ("transfer", "ret"));
+        assert!(suggestions(source, "other").is_empty());
+    }
+
+    #[test]
+    fn test_patterns_follow_forwarded_channel() {
+        let source = r#"
+contract lookup(@key, ret) = { ret!({"key": key, "found": true, "count": 3}, 7) }
+| contract get(@key, out) = { lookup!(key, *out) }
+| new r in { get!("a", *r) }"#;
+        let found = suggestions(source, "r");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].label, r#"@{"key": key, "found": true, "count": count}, @n"#);
+        assert_eq!(found[0].snippet, r#"@{"key": ${1:key}, "found": true, "count": ${2:count}\}, @${3:n}"#);
+        assert_eq!(found[0].contract, "get");
+    }
+}
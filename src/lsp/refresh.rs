@@ -0,0 +1,147 @@
+//! Client refresh requests for cross-file derived data
+//!
+//! Semantic tokens, code lenses and inlay hints of a document depend on other
+//! files: a contract deprecated or renamed elsewhere changes the tokens of its
+//! callers, and every new send changes the call-count lens of its contract.
+//! Editors only re-request these for the document being edited, so after the
+//! workspace changes the server compares a [`DerivedDataFingerprint`] of the
+//! cross-file inputs with the last one it saw and asks the client to refresh
+//! what is stale (`workspace/semanticTokens/refresh`, `workspace/codeLens/refresh`,
+//! `workspace/inlayHint/refresh`), for clients that declared refresh support.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use crate::ir::rholang_node::{collect_contracts, RholangNode};
+use crate::lsp::capabilities::NegotiatedCapabilities;
+use crate::lsp::contracts_outline::{count_calls, node_name};
+use crate::lsp::deprecation::DeprecatedDeclarations;
+
+/// Hashes of the workspace data that decorations of other documents depend on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DerivedDataFingerprint {
+    /// Contract names, arities and deprecations across the workspace
+    pub symbols: u64,
+    /// Number of sends to each contract name across the workspace
+    pub calls: u64,
+}
+
+impl DerivedDataFingerprint {
+    /// Fingerprints the contracts and sends of the given document IRs.
+    pub fn compute<'a>(documents: impl IntoIterator<Item = (&'a Arc<RholangNode>, &'a DeprecatedDeclarations)>) -> Self {
+        let mut contracts = BTreeSet::new();
+        let mut counts = HashMap::new();
+        for (ir, deprecations) in documents {
+            let mut nodes = Vec::new();
+            collect_contracts(ir, &mut nodes);
+            for node in &nodes {
+                let RholangNode::Contract { name, formals, formals_remainder, .. } = &**node else { continue };
+                let Some(name) = node_name(name) else { continue };
+                let deprecation = deprecations.contract(&name).map(str::to_string);
+                contracts.insert((name, formals.len(), formals_remainder.is_some(), deprecation));
+            }
+            count_calls(ir, &mut counts);
+        }
+
+        let calls: BTreeMap<&String, usize> = contracts
+            .iter()
+            .map(|(name, ..)| (name, counts.get(name).copied().unwrap_or(0)))
+            .collect();
+        DerivedDataFingerprint { symbols: hash_of(&contracts), calls: hash_of(&calls) }
+    }
+}
+
+fn hash_of(value: &impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Refresh requests to send to the client
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RefreshSet {
+    pub semantic_tokens: bool,
+    pub code_lens: bool,
+    pub inlay_hints: bool,
+}
+
+impl RefreshSet {
+    pub fn is_empty(&self) -> bool {
+        !(self.semantic_tokens || self.code_lens || self.inlay_hints)
+    }
+
+    /// Keeps only the refreshes the client declared support for.
+    pub fn supported_by(self, capabilities: &NegotiatedCapabilities) -> Self {
+        RefreshSet {
+            semantic_tokens: self.semantic_tokens && capabilities.semantic_tokens_refresh,
+            code_lens: self.code_lens && capabilities.code_lens_refresh,
+            inlay_hints: self.inlay_hints && capabilities.inlay_hint_refresh,
+        }
+    }
+}
+
+/// Remembers the last fingerprint to decide which decorations went stale
+#[derive(Debug, Default)]
+pub struct RefreshTracker {
+    last: Option<DerivedDataFingerprint>,
+}
+
+impl RefreshTracker {
+    /// Records `current` and returns the refreshes its changes call for.
+    ///
+    /// The first fingerprint refreshes everything: decorations requested while
+    /// the workspace was still being indexed lack cross-file data.
+    pub fn update(&mut self, current: DerivedDataFingerprint) -> RefreshSet {
+        let previous = self.last.replace(current);
+        let symbols = previous.is_none_or(|previous| previous.symbols != current.symbols);
+        let calls = previous.is_none_or(|previous| previous.calls != current.calls);
+        RefreshSet { semantic_tokens: symbols, code_lens: symbols || calls, inlay_hints: symbols }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ropey::Rope;
+
+    use crate::tree_sitter::{parse_code, parse_to_ir};
+
+    fn fingerprint(sources: &[&str]) -> DerivedDataFingerprint {
+        let irs: Vec<_> = sources
+            .iter()
+            .map(|source| parse_to_ir(&parse_code(source), &Rope::from_str(source)))
+            .collect();
+        let deprecations = DeprecatedDeclarations::default();
+        DerivedDataFingerprint::compute(irs.iter().map(|ir| (ir, &deprecations)))
+    }
+
+    #[test]
+    fn test_fingerprint_tracks_contracts_and_calls() {
+        let base = fingerprint(&["contract transfer(@from, ret) = { Nil }", "transfer!(1, *ret)"]);
+        assert_eq!(base, fingerprint(&["contract transfer(@from,   ret) = { ret!(0) }", "transfer!(2, *ret)"]));
+
+        let renamed = fingerprint(&["contract send(@from, ret) = { Nil }", "transfer!(1, *ret)"]);
+        assert_ne!(base.symbols, renamed.symbols);
+
+        let more_calls = fingerprint(&["contract transfer(@from, ret) = { Nil }", "transfer!(1, *ret) | transfer!(2, *ret)"]);
+        assert_eq!(base.symbols, more_calls.symbols);
+        assert_ne!(base.calls, more_calls.calls);
+    }
+
+    #[test]
+    fn test_tracker_refreshes_stale_decorations() {
+        let mut tracker = RefreshTracker::default();
+        let first = DerivedDataFingerprint { symbols: 1, calls: 1 };
+        assert_eq!(tracker.update(first), RefreshSet { semantic_tokens: true, code_lens: true, inlay_hints: true });
+        assert!(tracker.update(first).is_empty());
+
+        let calls_changed = DerivedDataFingerprint { symbols: 1, calls: 2 };
+        assert_eq!(tracker.update(calls_changed), RefreshSet { code_lens: true, ..Default::default() });
+
+        let capabilities = NegotiatedCapabilities { code_lens_refresh: true, ..Default::default() };
+        let symbols_changed = tracker.update(DerivedDataFingerprint { symbols: 2, calls: 2 });
+        assert_eq!(symbols_changed.supported_by(&capabilities), RefreshSet { code_lens: true, ..Default::default() });
+    }
+}
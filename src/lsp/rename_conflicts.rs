@@ -0,0 +1,244 @@
+//! Detection of renames that would change what a name refers to
+//!
+//! A rename is only safe if every occurrence keeps referring to the same
+//! declaration. [`find_rename_conflicts`] uses the lexical scopes of a
+//! document (see `crate::lsp::scopes`) and reports three kinds of conflict
+//! for renaming the name at a position to a new name:
+//!
+//! - **duplicate**: the declaring scope already declares the new name;
+//! - **capture**: a use of the renamed name sits in an inner scope that
+//!   declares the new name, and would refer to that declaration instead;
+//! - **shadow**: a use of the new name inside the declaring scope refers to an
+//!   outer declaration, and would refer to the renamed one instead.
+//!
+//! Names without a declaration in the document, such as top-level contract
+//! names, are treated as declared in the document scope. Occurrences in other
+//! files are not checked.
+//!
+//! The `rename` handler returns conflicts as a JSON-RPC error with code
+//! [`RENAME_CONFLICT_CODE`] and a [`RenameConflictReport`] as `data`:
+//!
+//! ```json
+//! { "uri": "file:///path/to/file.rho", "oldName": "ack", "newName": "ret",
+//!   "conflicts": [ { "kind": "capture",
+//!                    "message": "This use of 'ack' would refer to the inner declaration of 'ret'",
+//!                    "scope": { "start": …, "end": … },
+//!                    "location": { "start": …, "end": … },
+//!                    "declaration": { "start": …, "end": … } } ] }
+//! ```
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use ropey::Rope;
+use serde::{Deserialize, Serialize};
+use tower_lsp::jsonrpc;
+use tower_lsp::lsp_types::{Position as LspPosition, Range, Url};
+
+use crate::ir::rholang_node::{Position as IrPosition, RholangNode};
+use crate::lsp::line_index::{lsp_range, LineIndex};
+use crate::lsp::scopes::{ScopeAnalysis, Span};
+
+/// JSON-RPC error code of a rejected rename (implementation-defined server error range)
+pub const RENAME_CONFLICT_CODE: i64 = -32010;
+
+/// How a rename would change the meaning of an occurrence
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConflictKind {
+    /// The declaring scope already declares the new name
+    Duplicate,
+    /// A use of the old name would bind to an inner declaration of the new name
+    Capture,
+    /// A use of the new name would bind to the renamed declaration
+    Shadow,
+}
+
+/// One occurrence whose meaning would change
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenameConflict {
+    pub kind: ConflictKind,
+    pub message: String,
+    /// Scope in which the meaning changes
+    pub scope: Range,
+    /// The affected occurrence
+    pub location: Range,
+    /// The declaration of the new name involved
+    pub declaration: Range,
+}
+
+/// Error payload of a rejected rename
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenameConflictReport {
+    pub uri: Url,
+    pub old_name: String,
+    pub new_name: String,
+    pub conflicts: Vec<RenameConflict>,
+}
+
+impl RenameConflictReport {
+    /// Converts the report into the error returned for `textDocument/rename`.
+    pub fn into_error(self) -> jsonrpc::Error {
+        jsonrpc::Error {
+            code: jsonrpc::ErrorCode::ServerError(RENAME_CONFLICT_CODE),
+            message: format!(
+                "Renaming '{}' to '{}' would change the meaning of {} occurrence(s)",
+                self.old_name,
+                self.new_name,
+                self.conflicts.len()
+            )
+            .into(),
+            data: serde_json::to_value(&self).ok(),
+        }
+    }
+}
+
+/// Finds the conflicts of renaming the name at `position` to `new_name`.
+///
+/// Returns the current name and its conflicts, or `None` if there is no
+/// variable at `position`.
+pub fn find_rename_conflicts(
+    ir: &Arc<RholangNode>,
+    positions: &HashMap<usize, (IrPosition, IrPosition)>,
+    text: &Rope,
+    line_index: &LineIndex,
+    position: LspPosition,
+    new_name: &str,
+) -> Option<(String, Vec<RenameConflict>)> {
+    let analysis = ScopeAnalysis::build(ir, positions)?;
+    let offset = line_index.offset(position.line as usize, position.character as usize, text)?;
+    let (scope, index) = analysis.binder_at(offset)?;
+    let old_name = analysis.scopes[scope].binders[index].name.clone();
+    if old_name == new_name {
+        return Some((old_name, Vec::new()));
+    }
+    let to_range = |span: &Span| lsp_range(text, line_index, span);
+    let conflicts = find_conflicts(&analysis, scope, index, new_name, &to_range);
+    Some((old_name, conflicts))
+}
+
+fn find_conflicts(
+    analysis: &ScopeAnalysis,
+    scope: usize,
+    index: usize,
+    new_name: &str,
+    to_range: &dyn Fn(&Span) -> Range,
+) -> Vec<RenameConflict> {
+    let declaring = &analysis.scopes[scope];
+    let old_name = &declaring.binders[index].name;
+    let mut conflicts = Vec::new();
+
+    for existing in declaring.binders.iter().filter(|binder| binder.name == new_name) {
+        conflicts.push(RenameConflict {
+            kind: ConflictKind::Duplicate,
+            message: format!("'{}' is already declared in this scope", new_name),
+            scope: to_range(&declaring.span),
+            location: to_range(&declaring.binders[index].span),
+            declaration: to_range(&existing.span),
+        });
+    }
+
+    for use_ in analysis.uses_of(scope, index) {
+        let mut current = Some(use_.scope);
+        while let Some(inner) = current.filter(|&s| s != scope) {
+            if let Some(capturing) = analysis.scopes[inner].declaration_of(new_name) {
+                conflicts.push(RenameConflict {
+                    kind: ConflictKind::Capture,
+                    message: format!(
+                        "This use of '{}' would refer to the inner declaration of '{}'",
+                        old_name, new_name
+                    ),
+                    scope: to_range(&analysis.scopes[inner].span),
+                    location: to_range(&use_.span),
+                    declaration: to_range(&capturing.span),
+                });
+                break;
+            }
+            current = analysis.scopes[inner].parent;
+        }
+    }
+
+    if declaring.declaration_of(new_name).is_none() {
+        for use_ in analysis.uses.iter().filter(|u| u.name == new_name) {
+            let mut current = Some(use_.scope);
+            while let Some(s) = current {
+                if s == scope {
+                    let (binder_scope, binder_index) = use_.binder;
+                    conflicts.push(RenameConflict {
+                        kind: ConflictKind::Shadow,
+                        message: format!(
+                            "This use of '{}' would refer to the renamed declaration instead of its current one",
+                            new_name
+                        ),
+                        scope: to_range(&declaring.span),
+                        location: to_range(&use_.span),
+                        declaration: to_range(&analysis.scopes[binder_scope].binders[binder_index].span),
+                    });
+                    break;
+                }
+                if analysis.scopes[s].declaration_of(new_name).is_some() {
+                    break;
+                }
+                current = analysis.scopes[s].parent;
+            }
+        }
+    }
+
+    conflicts.sort_by_key(|conflict| (conflict.location.start.line, conflict.location.start.character));
+    conflicts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree_sitter::parse_with_positions;
+
+    /// Returns (kind, affected line:character) for renaming the name at `line:character`.
+    fn conflicts(source: &str, line: u32, character: u32, new_name: &str) -> Vec<(ConflictKind, (u32, u32))> {
+        let rope = Rope::from_str(source);
+        let (ir, positions) = parse_with_positions(source);
+        let line_index = LineIndex::new(source);
+        let (_, conflicts) =
+            find_rename_conflicts(&ir, &positions, &rope, &line_index, LspPosition { line, character }, new_name).unwrap();
+        conflicts
+            .iter()
+            .map(|conflict| (conflict.kind, (conflict.location.start.line, conflict.location.start.character)))
+            .collect()
+    }
+
+    #[test]
+    fn test_safe_rename() {
+        let source = "new x, y in {\n  x!(1) | y!(2)\n}";
+        assert!(conflicts(source, 0, 4, "z").is_empty());
+        assert!(conflicts(source, 1, 2, "x").is_empty(), "renaming to the same name");
+    }
+
+    #[test]
+    fn test_duplicate_declaration() {
+        let source = "new x, y in {\n  x!(1) | y!(2)\n}";
+        assert_eq!(conflicts(source, 0, 4, "y"), vec![(ConflictKind::Duplicate, (0, 4))]);
+    }
+
+    #[test]
+    fn test_capture_by_inner_binder() {
+        let source = "new ack in {\n  for (ret <- ack) {\n    ack!(ret)\n  }\n}";
+        // Renaming `ack` to `ret`: the send inside the `for` would use the received `ret`
+        assert_eq!(conflicts(source, 0, 4, "ret"), vec![(ConflictKind::Capture, (2, 4))]);
+    }
+
+    #[test]
+    fn test_shadowing_outer_use() {
+        let source = "new out in {\n  new ch in {\n    out!(1) | ch!(2)\n  }\n}";
+        // Renaming `ch` to `out`: the existing `out!` would go to the renamed channel
+        let found = conflicts(source, 1, 6, "out");
+        assert_eq!(found, vec![(ConflictKind::Shadow, (2, 4))]);
+
+        // Match patterns and contract formals are scopes too
+        let source = "contract foo(@n, ret) = {\n  match n {\n    m => ret!(m)\n  }\n}";
+        assert_eq!(conflicts(source, 2, 4, "ret"), vec![(ConflictKind::Shadow, (2, 9))]);
+        // The matched expression is outside the case, so `n` does not conflict
+        assert!(conflicts(source, 2, 4, "n").is_empty());
+    }
+}
@@ -0,0 +1,483 @@
+//! The `*ret` return-channel convention
+//!
+//! Contracts commonly take a reply channel as their last formal and answer on
+//! it, while callers create the channel, pass it as `*ret` and wait on it:
+//!
+//! ```rholang
+//! contract lookup(@key, ret) = { ret!(key) }
+//! |
+//! new ret in { lookup!("a", *ret) | for (@value <- ret) { … } }
+//! ```
+//!
+//! [`return_channel_at`] recognizes both ends: a bare-variable contract
+//! formal ([`ReturnChannel::Formal`]) and a name passed as `*name` in a send
+//! ([`ReturnChannel::Argument`]), from any occurrence of the name, such as
+//! the `ret` of `for (@value <- ret)`. [`response_sends`] lists the sends on
+//! a formal inside its contract, which are the sends a caller's `for` waits
+//! for, and [`response_send_lenses`] counts them above each formal answered
+//! on, as lenses running [`RESPONSE_SENDS_COMMAND`]. [`return_receives`] finds
+//! the call-site end, the receives waiting on a `*ret` argument, which get the
+//! same lens once the backend has matched the calls to contracts.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use ropey::Rope;
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{CodeLens, Command, Location, Position as LspPosition, Range, Url};
+
+use crate::ir::rholang_node::{
+    Metadata, NodeBase, Position as IrPosition, RholangNode, RholangNodeVector, RholangSendType,
+};
+use crate::i18n::tr;
+use crate::ir::visitor::Visitor;
+use crate::lsp::line_index::{lsp_position, lsp_range, LineIndex};
+use crate::lsp::scopes::{BinderOrigin, ScopeAnalysis, Span, UseRole};
+
+/// Lists the sends answering on the return channel at a position
+pub const RESPONSE_SENDS_COMMAND: &str = "rholang.responseSends";
+
+/// Arguments of `rholang.responseSends`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseSendsParams {
+    pub uri: Url,
+    /// A contract formal, or a name passed as `*name` (e.g. in `for (x <- ret)`)
+    pub position: LspPosition,
+}
+
+/// Result of `rholang.responseSends`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseSendsReport {
+    /// Name of the return channel at the position
+    pub channel: String,
+    pub sends: Vec<Location>,
+}
+
+/// A call passing a return channel
+#[derive(Debug, Clone)]
+pub struct ReturnCall {
+    /// Channel of the send, i.e. the contract called
+    pub channel: Arc<RholangNode>,
+    pub inputs: RholangNodeVector,
+    /// Position of the `*name` argument among `inputs`
+    pub index: usize,
+}
+
+/// A return channel found at a position
+#[derive(Debug, Clone)]
+pub enum ReturnChannel {
+    /// The `index`th formal of `contract`
+    Formal { name: String, contract: Arc<RholangNode>, index: usize },
+    /// A name passed as `*name` by `calls`
+    Argument { name: String, calls: Vec<ReturnCall> },
+}
+
+impl ReturnChannel {
+    pub fn name(&self) -> &str {
+        match self {
+            ReturnChannel::Formal { name, .. } | ReturnChannel::Argument { name, .. } => name,
+        }
+    }
+}
+
+/// The return channel named at `position`, if the name there is a contract
+/// formal or is passed as `*name` in a send.
+pub fn return_channel_at(
+    ir: &Arc<RholangNode>,
+    positions: &HashMap<usize, (IrPosition, IrPosition)>,
+    text: &Rope,
+    line_index: &LineIndex,
+    position: LspPosition,
+) -> Option<ReturnChannel> {
+    let analysis = ScopeAnalysis::build(ir, positions)?;
+    let offset = line_index.offset(position.line as usize, position.character as usize, text)?;
+    let (scope, index) = analysis.binder_at(offset)?;
+    return_channel_of(&analysis, &ChannelNodes::collect(ir), positions, (scope, index))
+}
+
+/// The sources of the receives of `ir` that wait on a return channel passed
+/// as `*name`, such as the `ret` of `for (@value <- ret)`, with the channel,
+/// in document order.
+pub fn return_receives(
+    ir: &Arc<RholangNode>,
+    positions: &HashMap<usize, (IrPosition, IrPosition)>,
+    text: &Rope,
+    line_index: &LineIndex,
+) -> Vec<(LspPosition, ReturnChannel)> {
+    let Some(analysis) = ScopeAnalysis::build(ir, positions) else { return Vec::new() };
+    let nodes = ChannelNodes::collect(ir);
+    // Contract names are receive uses too
+    let contract_names: Vec<Span> = nodes
+        .contracts
+        .iter()
+        .filter_map(|contract| match &**contract {
+            RholangNode::Contract { name, .. } => positions.get(&(Arc::as_ptr(name) as usize)).copied(),
+            _ => None,
+        })
+        .collect();
+
+    let mut channels: HashMap<(usize, usize), Option<ReturnChannel>> = HashMap::new();
+    let mut receives = Vec::new();
+    for u in analysis.uses.iter().filter(|u| u.role == UseRole::Receive && !contract_names.contains(&u.span)) {
+        let known = channels.entry(u.binder).or_insert_with(|| return_channel_of(&analysis, &nodes, positions, u.binder));
+        if let Some(channel @ ReturnChannel::Argument { .. }) = known {
+            receives.push((lsp_position(text, line_index, &u.span.0), channel.clone()));
+        }
+    }
+    receives.sort_by_key(|(position, _)| *position);
+    receives
+}
+
+/// The return channel declared by `binder`, a (scope, index) pair of `analysis`.
+fn return_channel_of(
+    analysis: &ScopeAnalysis,
+    nodes: &ChannelNodes,
+    positions: &HashMap<usize, (IrPosition, IrPosition)>,
+    (scope, index): (usize, usize),
+) -> Option<ReturnChannel> {
+    let binder = &analysis.scopes[scope].binders[index];
+    let span = |node: &Arc<RholangNode>| positions.get(&(Arc::as_ptr(node) as usize)).copied();
+
+    if binder.origin == BinderOrigin::Pattern {
+        for contract in &nodes.contracts {
+            let RholangNode::Contract { formals, .. } = &**contract else { continue };
+            let formal = formals
+                .iter()
+                .position(|formal| matches!(&**formal, RholangNode::Var { .. }) && span(formal) == Some(binder.span));
+            if let Some(formal) = formal {
+                return Some(ReturnChannel::Formal { name: binder.name.clone(), contract: contract.clone(), index: formal });
+            }
+        }
+    }
+
+    let passed: Vec<Span> = analysis
+        .uses_of(scope, index)
+        .filter(|u| u.role == UseRole::Other)
+        .map(|u| u.span)
+        .collect();
+    let calls: Vec<ReturnCall> = nodes
+        .sends
+        .iter()
+        .filter_map(|send| {
+            let (RholangNode::Send { channel, inputs, .. } | RholangNode::SendSync { channel, inputs, .. }) = &**send
+            else {
+                return None;
+            };
+            let index = inputs.iter().position(|input| match &**input {
+                RholangNode::Eval { name, .. } => {
+                    matches!(&**name, RholangNode::Var { .. }) && span(name).is_some_and(|s| passed.contains(&s))
+                }
+                _ => false,
+            })?;
+            Some(ReturnCall { channel: channel.clone(), inputs: inputs.clone(), index })
+        })
+        .collect();
+    if calls.is_empty() {
+        return None;
+    }
+    Some(ReturnChannel::Argument { name: binder.name.clone(), calls })
+}
+
+/// The sends on the `index`th formal of `contract` within its body, in
+/// document order. `ir`, `positions`, `text` and `line_index` are those of
+/// the document declaring `contract`.
+pub fn response_sends(
+    contract: &Arc<RholangNode>,
+    index: usize,
+    ir: &Arc<RholangNode>,
+    positions: &HashMap<usize, (IrPosition, IrPosition)>,
+    text: &Rope,
+    line_index: &LineIndex,
+) -> Vec<Range> {
+    match ScopeAnalysis::build(ir, positions) {
+        Some(analysis) => sends_on_formal(contract, index, &analysis, positions, text, line_index),
+        None => Vec::new(),
+    }
+}
+
+/// A lens above each bare-variable contract formal of `ir` that is answered
+/// on, counting the response sends and listing them with
+/// [`RESPONSE_SENDS_COMMAND`]. `uri` is the document of `ir`.
+pub fn response_send_lenses(
+    uri: &Url,
+    ir: &Arc<RholangNode>,
+    positions: &HashMap<usize, (IrPosition, IrPosition)>,
+    text: &Rope,
+    line_index: &LineIndex,
+) -> Vec<CodeLens> {
+    let Some(analysis) = ScopeAnalysis::build(ir, positions) else { return Vec::new() };
+    let mut lenses = Vec::new();
+    for contract in &ChannelNodes::collect(ir).contracts {
+        let RholangNode::Contract { formals, .. } = &**contract else { continue };
+        for (index, formal) in formals.iter().enumerate() {
+            let Some((start, _)) = positions.get(&(Arc::as_ptr(formal) as usize)) else { continue };
+            let sends = sends_on_formal(contract, index, &analysis, positions, text, line_index).len();
+            if sends > 0 {
+                lenses.push(response_sends_lens(uri, lsp_position(text, line_index, start), sends));
+            }
+        }
+    }
+    lenses
+}
+
+/// A lens at `position` counting `sends` response sends and listing them
+/// with [`RESPONSE_SENDS_COMMAND`] from that position.
+pub fn response_sends_lens(uri: &Url, position: LspPosition, sends: usize) -> CodeLens {
+    let title = if sends == 1 { tr("lens.response-sends-one", &[]) } else { tr("lens.response-sends", &[("count", &sends)]) };
+    let params = ResponseSendsParams { uri: uri.clone(), position };
+    CodeLens {
+        range: Range::new(position, position),
+        command: Some(Command {
+            title,
+            command: RESPONSE_SENDS_COMMAND.to_string(),
+            arguments: Some(vec![serde_json::to_value(params).expect("parameters serialize")]),
+        }),
+        data: None,
+    }
+}
+
+/// The sends on the `index`th formal of `contract`, with the scopes of the
+/// document declaring it.
+fn sends_on_formal(
+    contract: &Arc<RholangNode>,
+    index: usize,
+    analysis: &ScopeAnalysis,
+    positions: &HashMap<usize, (IrPosition, IrPosition)>,
+    text: &Rope,
+    line_index: &LineIndex,
+) -> Vec<Range> {
+    let RholangNode::Contract { formals, .. } = &**contract else { return Vec::new() };
+    let Some(formal) = formals.get(index) else { return Vec::new() };
+    if !matches!(&**formal, RholangNode::Var { .. }) {
+        return Vec::new();
+    }
+    let Some(formal_span) = positions.get(&(Arc::as_ptr(formal) as usize)).copied() else {
+        return Vec::new();
+    };
+    let binder = analysis.scopes.iter().enumerate().find_map(|(scope, s)| {
+        s.binders
+            .iter()
+            .position(|binder| binder.origin == BinderOrigin::Pattern && binder.span == formal_span)
+            .map(|index| (scope, index))
+    });
+    let Some((scope, index)) = binder else { return Vec::new() };
+
+    // A send is reported whole; other sends on the name (`ret!?(…)` sources) by the name
+    let sends = ChannelNodes::collect(contract).sends;
+    analysis
+        .uses_of(scope, index)
+        .filter(|u| u.role == UseRole::Send)
+        .map(|u| {
+            let send = sends.iter().find(|send| {
+                let (RholangNode::Send { channel, .. } | RholangNode::SendSync { channel, .. }) = &***send else {
+                    return false;
+                };
+                positions.get(&(Arc::as_ptr(channel) as usize)) == Some(&u.span)
+            });
+            let span = send.and_then(|send| positions.get(&(Arc::as_ptr(send) as usize))).unwrap_or(&u.span);
+            lsp_range(text, line_index, span)
+        })
+        .collect()
+}
+
+/// Contracts and sends of a tree
+#[derive(Default)]
+struct ChannelNodes {
+    contracts: Vec<Arc<RholangNode>>,
+    sends: Vec<Arc<RholangNode>>,
+}
+
+impl ChannelNodes {
+    fn collect(root: &Arc<RholangNode>) -> Self {
+        let collector = ChannelCollector { nodes: RefCell::new(ChannelNodes::default()) };
+        collector.visit_node(root);
+        collector.nodes.into_inner()
+    }
+}
+
+struct ChannelCollector {
+    nodes: RefCell<ChannelNodes>,
+}
+
+impl Visitor for ChannelCollector {
+    fn visit_send(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        channel: &Arc<RholangNode>,
+        _send_type: &RholangSendType,
+        _send_type_pos: &IrPosition,
+        inputs: &RholangNodeVector,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.nodes.borrow_mut().sends.push(node.clone());
+        self.visit_node(channel);
+        for input in inputs.iter() {
+            self.visit_node(input);
+        }
+        Arc::clone(node)
+    }
+
+    fn visit_send_sync(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        channel: &Arc<RholangNode>,
+        inputs: &RholangNodeVector,
+        cont: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.nodes.borrow_mut().sends.push(node.clone());
+        self.visit_node(channel);
+        for input in inputs.iter() {
+            self.visit_node(input);
+        }
+        self.visit_node(cont);
+        Arc::clone(node)
+    }
+
+    fn visit_contract(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        name: &Arc<RholangNode>,
+        formals: &RholangNodeVector,
+        formals_remainder: &Option<Arc<RholangNode>>,
+        proc: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.nodes.borrow_mut().contracts.push(node.clone());
+        self.visit_node(name);
+        for formal in formals.iter().chain(formals_remainder.iter()) {
+            self.visit_node(formal);
+        }
+        self.visit_node(proc);
+        Arc::clone(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree_sitter::parse_with_positions;
+
+    const SOURCE: &str = "contract lookup(@key, ret) = {\n  if (key == \"a\") { ret!(1) }\n  else { ret!(2) | log!(key) }\n}\n|\nnew ret in {\n  lookup!(\"a\", *ret) |\n  for (@value <- ret) { Nil }\n}";
+
+    struct Parsed {
+        ir: Arc<RholangNode>,
+        positions: HashMap<usize, (IrPosition, IrPosition)>,
+        rope: Rope,
+        line_index: LineIndex,
+    }
+
+    impl Parsed {
+        fn channel_at(&self, position: LspPosition) -> Option<ReturnChannel> {
+            return_channel_at(&self.ir, &self.positions, &self.rope, &self.line_index, position)
+        }
+
+        fn sends(&self, contract: &Arc<RholangNode>, index: usize) -> Vec<Range> {
+            response_sends(contract, index, &self.ir, &self.positions, &self.rope, &self.line_index)
+        }
+    }
+
+    fn parse(source: &str) -> Parsed {
+        let rope = Rope::from_str(source);
+        let (ir, positions) = parse_with_positions(source);
+        Parsed { ir, positions, rope, line_index: LineIndex::new(source) }
+    }
+
+    fn at(line: u32, character: u32) -> LspPosition {
+        LspPosition { line, character }
+    }
+
+    #[test]
+    fn test_response_sends_of_a_formal() {
+        let parsed = parse(SOURCE);
+        let Some(ReturnChannel::Formal { name, contract, index }) = parsed.channel_at(at(0, 23)) else {
+            panic!("expected the `ret` formal");
+        };
+        assert_eq!((name.as_str(), index), ("ret", 1));
+
+        let sends = parsed.sends(&contract, index);
+        let lines: Vec<_> = sends.iter().map(|range| (range.start.line, range.start.character)).collect();
+        assert_eq!(lines, vec![(1, 20), (2, 9)]);
+        // The whole send is reported
+        assert_eq!(sends[0].end, at(1, 27));
+    }
+
+    #[test]
+    fn test_call_site_finds_the_call() {
+        let parsed = parse(SOURCE);
+        // `ret` of `for (@value <- ret)`
+        let Some(ReturnChannel::Argument { name, calls }) = parsed.channel_at(at(7, 18)) else {
+            panic!("expected the `ret` argument");
+        };
+        assert_eq!(name, "ret");
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].index, 1);
+        assert!(matches!(&*calls[0].channel, RholangNode::Var { name, .. } if name == "lookup"));
+
+        // The matching contract answers on the formal at the same index
+        let nodes = ChannelNodes::collect(&parsed.ir);
+        let sends = parsed.sends(&nodes.contracts[0], calls[0].index);
+        assert_eq!(sends.len(), 2);
+    }
+
+    #[test]
+    fn test_other_names_are_not_return_channels() {
+        let parsed = parse(SOURCE);
+        // `log` is neither a formal nor passed as `*log`
+        assert!(parsed.channel_at(at(2, 20)).is_none());
+        // `@key` is not a bare variable formal
+        assert!(parsed.channel_at(at(0, 17)).is_none());
+    }
+
+    #[test]
+    fn test_response_send_lenses() {
+        let parsed = parse(SOURCE);
+        let uri = Url::parse("file:///tmp/lookup.rho").unwrap();
+        let lenses = response_send_lenses(&uri, &parsed.ir, &parsed.positions, &parsed.rope, &parsed.line_index);
+        // Only `ret` is answered on
+        assert_eq!(lenses.len(), 1);
+        assert_eq!(lenses[0].range.start, at(0, 22));
+        let command = lenses[0].command.as_ref().unwrap();
+        assert_eq!(command.title, "2 response sends");
+        assert_eq!(command.command, RESPONSE_SENDS_COMMAND);
+        let params: ResponseSendsParams = serde_json::from_value(command.arguments.as_ref().unwrap()[0].clone()).unwrap();
+        assert_eq!(params, ResponseSendsParams { uri, position: at(0, 22) });
+    }
+
+    #[test]
+    fn test_return_receives() {
+        let parsed = parse(SOURCE);
+        let receives = return_receives(&parsed.ir, &parsed.positions, &parsed.rope, &parsed.line_index);
+        // Only `ret` of `for (@value <- ret)`; the contract name `lookup` is not a receive source
+        assert_eq!(receives.len(), 1);
+        assert_eq!(receives[0].0, at(7, 17));
+        let ReturnChannel::Argument { name, calls } = &receives[0].1 else {
+            panic!("expected the `ret` argument");
+        };
+        assert_eq!((name.as_str(), calls.len()), ("ret", 1));
+
+        // A receive on a name that is not passed as `*name` is not a return receive
+        let source = "new ch in { ch!(1) | for (@x <- ch) { Nil } }";
+        let parsed = parse(source);
+        assert!(return_receives(&parsed.ir, &parsed.positions, &parsed.rope, &parsed.line_index).is_empty());
+    }
+
+    #[test]
+    fn test_positions_count_utf16_units() {
+        // `é` is two bytes but one UTF-16 unit, `🦀` four bytes but two units
+        let source = "contract @\"échoé\"(ret) = { ret!(\"🦀\") | ret!(1) }";
+        let parsed = parse(source);
+        let Some(ReturnChannel::Formal { contract, index, .. }) = parsed.channel_at(at(0, 19)) else {
+            panic!("expected the `ret` formal");
+        };
+        let sends = parsed.sends(&contract, index);
+        let ranges: Vec<_> = sends.iter().map(|range| (range.start.character, range.end.character)).collect();
+        assert_eq!(ranges, vec![(27, 37), (40, 47)]);
+    }
+}
@@ -0,0 +1,177 @@
+//! Runtime switch for RNode validation
+//!
+//! Validation against RNode over gRPC is normally chosen when the server
+//! starts (`--validator-backend grpc:<address>`). Users who start RNode in the
+//! middle of a session can switch it on, or off again, with the
+//! `rholang.toggleRnodeValidation` command:
+//!
+//! ```json
+//! { "command": "rholang.toggleRnodeValidation", "arguments": [{ "enabled": true, "address": "localhost:40402" }] }
+//! ```
+//!
+//! Both fields are optional: without `enabled` the command flips the current
+//! state, and without `address` it reuses the last address (by default
+//! `localhost:40402`). The open documents are validated again right away.
+//! While RNode validation is on, semantic errors come from RNode instead of
+//! the embedded interpreter; while it is off, a server started against RNode
+//! reports syntax errors only.
+//!
+//! The command returns the new [`RnodeValidationStatus`], which the
+//! `rholang/health` request also reports.
+
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+
+use crate::lsp::diagnostic_provider::DiagnosticProvider;
+
+/// Switches RNode validation on or off
+pub const TOGGLE_RNODE_VALIDATION_COMMAND: &str = "rholang.toggleRnodeValidation";
+
+/// RNode gRPC address used until another is given
+pub const DEFAULT_RNODE_ADDRESS: &str = "localhost:40402";
+
+/// Arguments of `rholang.toggleRnodeValidation`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToggleRnodeValidationArgs {
+    /// Requested state; flips the current one when missing
+    #[serde(default)]
+    pub enabled: Option<bool>,
+    /// RNode gRPC address (`host:port`)
+    #[serde(default)]
+    pub address: Option<String>,
+}
+
+/// Whether RNode validation is on, as reported to clients
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RnodeValidationStatus {
+    pub enabled: bool,
+    pub address: String,
+    /// Why the last attempt to switch it on failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+struct Inner {
+    provider: Option<Arc<Box<dyn DiagnosticProvider>>>,
+    address: String,
+    last_error: Option<String>,
+}
+
+/// The RNode validator in use, if any
+pub struct RnodeValidation {
+    inner: RwLock<Inner>,
+}
+
+impl std::fmt::Debug for RnodeValidation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RnodeValidation").field("status", &self.status()).finish()
+    }
+}
+
+impl RnodeValidation {
+    /// Starts with `provider` validating against RNode at `address`, or off.
+    pub fn new(address: String, provider: Option<Arc<Box<dyn DiagnosticProvider>>>) -> Self {
+        RnodeValidation { inner: RwLock::new(Inner { provider, address, last_error: None }) }
+    }
+
+    /// The RNode validator, while RNode validation is on.
+    pub fn provider(&self) -> Option<Arc<Box<dyn DiagnosticProvider>>> {
+        self.inner.read().provider.clone()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.inner.read().provider.is_some()
+    }
+
+    /// The state and address `args` ask for.
+    pub fn target(&self, args: &ToggleRnodeValidationArgs) -> (bool, String) {
+        let inner = self.inner.read();
+        let enabled = args.enabled.unwrap_or(inner.provider.is_none());
+        (enabled, args.address.clone().unwrap_or_else(|| inner.address.clone()))
+    }
+
+    /// Switches RNode validation on with `provider`, connected to `address`.
+    pub fn enable(&self, address: String, provider: Arc<Box<dyn DiagnosticProvider>>) {
+        *self.inner.write() = Inner { provider: Some(provider), address, last_error: None };
+    }
+
+    /// Records a failed attempt to connect to `address`; validation stays as it was.
+    pub fn fail(&self, address: String, error: String) {
+        let mut inner = self.inner.write();
+        inner.address = address;
+        inner.last_error = Some(error);
+    }
+
+    pub fn disable(&self) {
+        let mut inner = self.inner.write();
+        inner.provider = None;
+        inner.last_error = None;
+    }
+
+    pub fn status(&self) -> RnodeValidationStatus {
+        let inner = self.inner.read();
+        RnodeValidationStatus {
+            enabled: inner.provider.is_some(),
+            address: inner.address.clone(),
+            last_error: inner.last_error.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp::lsp_types::Diagnostic;
+
+    struct Fake;
+
+    #[async_trait::async_trait]
+    impl DiagnosticProvider for Fake {
+        async fn validate(&self, _source: &str) -> Vec<Diagnostic> {
+            Vec::new()
+        }
+
+        fn backend_name(&self) -> &'static str {
+            "fake"
+        }
+    }
+
+    #[test]
+    fn test_toggle_flips_and_remembers_the_address() {
+        let validation = RnodeValidation::new(DEFAULT_RNODE_ADDRESS.to_string(), None);
+        assert!(!validation.is_enabled());
+        assert_eq!(
+            validation.target(&ToggleRnodeValidationArgs::default()),
+            (true, DEFAULT_RNODE_ADDRESS.to_string())
+        );
+
+        let args = ToggleRnodeValidationArgs { enabled: None, address: Some("node:40402".to_string()) };
+        let (_, address) = validation.target(&args);
+        validation.fail(address.clone(), "connection refused".to_string());
+        assert!(!validation.status().enabled);
+        assert_eq!(validation.status().last_error.as_deref(), Some("connection refused"));
+
+        validation.enable(address, Arc::new(Box::new(Fake)));
+        let status = validation.status();
+        assert_eq!((status.enabled, status.address.as_str(), status.last_error), (true, "node:40402", None));
+        assert_eq!(validation.target(&ToggleRnodeValidationArgs::default()), (false, "node:40402".to_string()));
+
+        validation.disable();
+        assert!(validation.provider().is_none());
+        let explicit = ToggleRnodeValidationArgs { enabled: Some(false), address: None };
+        assert!(!validation.target(&explicit).0);
+    }
+
+    #[test]
+    fn test_status_serialization() {
+        let status = RnodeValidation::new(DEFAULT_RNODE_ADDRESS.to_string(), None).status();
+        assert_eq!(
+            serde_json::to_value(&status).unwrap(),
+            serde_json::json!({ "enabled": false, "address": "localhost:40402" })
+        );
+    }
+}
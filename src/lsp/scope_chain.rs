@@ -0,0 +1,151 @@
+//! Lexical scopes at the cursor (`rholang.showScopes`)
+//!
+//! Lists the scopes of [`ScopeAnalysis`] containing a position, from the
+//! document down to the innermost one, with the names each declares. The
+//! extension renders the chain as a breadcrumb or a popup, so the user can
+//! see which `new`, contract formal, `for` bind or `match` case a name at
+//! the cursor comes from.
+//!
+//! Free names are not listed: the document scope only declares them
+//! implicitly, at their first use.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use ropey::Rope;
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{Position as LspPosition, Range, Url};
+
+use crate::ir::rholang_node::{Position as IrPosition, RholangNode};
+use crate::lsp::line_index::{lsp_range, LineIndex};
+use crate::lsp::scopes::{BinderOrigin, ScopeAnalysis, ScopeKind};
+
+/// Returns the chain of lexical scopes at a position
+pub const SHOW_SCOPES_COMMAND: &str = "rholang.showScopes";
+
+/// Arguments of `rholang.showScopes`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShowScopesParams {
+    pub uri: Url,
+    pub position: LspPosition,
+}
+
+/// Result of `rholang.showScopes`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScopeChain {
+    /// Outermost (the document) first
+    pub scopes: Vec<ScopeEntry>,
+}
+
+/// One scope of the chain
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScopeEntry {
+    /// `document`, `new`, `let`, `contract`, `for`, `match` (one case) or `select` (one branch)
+    pub kind: String,
+    pub range: Range,
+    /// Names declared by the scope, in declaration order
+    pub names: Vec<BoundName>,
+}
+
+/// A name declared by a scope
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoundName {
+    pub name: String,
+    /// Range of the declaration
+    pub range: Range,
+}
+
+fn kind_name(kind: ScopeKind) -> &'static str {
+    match kind {
+        ScopeKind::Document => "document",
+        ScopeKind::New => "new",
+        ScopeKind::Let => "let",
+        ScopeKind::Contract => "contract",
+        ScopeKind::For => "for",
+        ScopeKind::Match => "match",
+        ScopeKind::Select => "select",
+    }
+}
+
+/// The scopes of a document containing `position`, outermost first.
+pub fn scope_chain(
+    ir: &Arc<RholangNode>,
+    positions: &HashMap<usize, (IrPosition, IrPosition)>,
+    text: &Rope,
+    line_index: &LineIndex,
+    position: LspPosition,
+) -> ScopeChain {
+    let Some(analysis) = ScopeAnalysis::build(ir, positions) else {
+        return ScopeChain::default();
+    };
+    let Some(offset) = line_index.offset(position.line as usize, position.character as usize, text) else {
+        return ScopeChain::default();
+    };
+    let scopes = analysis
+        .chain_at(offset)
+        .into_iter()
+        .map(|index| {
+            let scope = &analysis.scopes[index];
+            ScopeEntry {
+                kind: kind_name(scope.kind).to_string(),
+                range: lsp_range(text, line_index, &scope.span),
+                names: scope
+                    .binders
+                    .iter()
+                    .filter(|binder| binder.origin != BinderOrigin::Implicit)
+                    .map(|binder| BoundName { name: binder.name.clone(), range: lsp_range(text, line_index, &binder.span) })
+                    .collect(),
+            }
+        })
+        .collect();
+    ScopeChain { scopes }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree_sitter::parse_with_positions;
+
+    fn chain(source: &str, line: u32, character: u32) -> Vec<(String, Vec<String>)> {
+        let rope = Rope::from_str(source);
+        let (ir, positions) = parse_with_positions(source);
+        scope_chain(&ir, &positions, &rope, &LineIndex::new(source), LspPosition::new(line, character))
+            .scopes
+            .into_iter()
+            .map(|scope| (scope.kind, scope.names.into_iter().map(|name| name.name).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn test_chain_from_document_to_innermost() {
+        let source = r#"new vault, stdout(`rho:io:stdout`) in {
+  contract vault(@amount, ret) = {
+    for (@balance <- ret) {
+      match balance {
+        [head, ...tail] => stdout!(head)
+        _ => Nil
+      }
+    }
+  }
+}"#;
+        let at_head = chain(source, 4, 32);
+        assert_eq!(
+            at_head,
+            vec![
+                ("document".to_string(), vec![]),
+                ("new".to_string(), vec!["vault".to_string(), "stdout".to_string()]),
+                ("contract".to_string(), vec!["amount".to_string(), "ret".to_string()]),
+                ("for".to_string(), vec!["balance".to_string()]),
+                ("match".to_string(), vec!["head".to_string(), "tail".to_string()]),
+            ]
+        );
+
+        // The other case of the match does not see `head`
+        assert_eq!(chain(source, 5, 14).last().unwrap().1, Vec::<String>::new());
+        assert_eq!(chain(source, 1, 1).len(), 2);
+    }
+}
@@ -0,0 +1,428 @@
+//! Lexical scopes of a Rholang document
+//!
+//! [`ScopeAnalysis::build`] walks the IR once and records the scopes opened
+//! by `new`, `let`, contract formals, `for` binds, `match` cases and `select`
+//! branches, the names each one declares, and every use of a name resolved to
+//! its declaration. Names without a declaration in the document, such as
+//! top-level contract names, are treated as declared in the document scope
+//! (scope 0) at their first use.
+//!
+//! Uses also record whether the name is the channel of a send, the source of
+//! a receive (including a contract's name), or something else, such as a
+//! value passed in a message.
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::ir::rholang_node::{
+    strip_quote_eval, Metadata, NodeBase, Position as IrPosition, RholangBranchVector, RholangNode,
+    RholangNodePairVector, RholangNodeVector, RholangReceiptVector, RholangSendType, RholangVarRefKind,
+};
+use crate::ir::visitor::Visitor;
+
+pub(crate) type Span = (IrPosition, IrPosition);
+
+/// What introduced a binder
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BinderOrigin {
+    /// A `new` declaration; `uri` is set for system names like `stdout(`rho:io:stdout`)`
+    New { uri: bool },
+    /// A pattern: contract formals, `let`, `for` binds or `match` cases
+    Pattern,
+    /// A free name, declared at its first use
+    Implicit,
+}
+
+/// How a use of a name relates to communication
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum UseRole {
+    /// Channel of a send (`x!(…)`, `x!?(…)`)
+    Send,
+    /// Source of a receive (`for (… <- x)`) or name of a contract
+    Receive,
+    /// Any other use, e.g. a name passed in a message
+    Other,
+}
+
+/// The construct that opened a scope
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ScopeKind {
+    Document,
+    New,
+    Let,
+    Contract,
+    For,
+    /// One case of a `match`
+    Match,
+    /// One branch of a `select`
+    Select,
+}
+
+#[derive(Debug)]
+pub(crate) struct Binder {
+    pub(crate) name: String,
+    /// The declaration, or the first use for names declared implicitly
+    pub(crate) span: Span,
+    pub(crate) origin: BinderOrigin,
+}
+
+#[derive(Debug)]
+pub(crate) struct Scope {
+    pub(crate) parent: Option<usize>,
+    pub(crate) kind: ScopeKind,
+    pub(crate) span: Span,
+    pub(crate) binders: Vec<Binder>,
+}
+
+impl Scope {
+    pub(crate) fn declaration_of(&self, name: &str) -> Option<&Binder> {
+        self.binders.iter().rev().find(|binder| binder.name == name)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Use {
+    pub(crate) name: String,
+    pub(crate) span: Span,
+    /// Innermost scope containing the use
+    pub(crate) scope: usize,
+    /// Scope and index of the binder the use refers to
+    pub(crate) binder: (usize, usize),
+    pub(crate) role: UseRole,
+}
+
+/// Scopes, declarations and resolved uses of a document
+#[derive(Debug, Default)]
+pub(crate) struct ScopeAnalysis {
+    /// Scope 0 is the document
+    pub(crate) scopes: Vec<Scope>,
+    pub(crate) uses: Vec<Use>,
+}
+
+impl ScopeAnalysis {
+    pub(crate) fn build(ir: &Arc<RholangNode>, positions: &HashMap<usize, (IrPosition, IrPosition)>) -> Option<Self> {
+        let span = *positions.get(&(Arc::as_ptr(ir) as usize))?;
+        let builder = ScopeBuilder {
+            positions,
+            analysis: RefCell::new(ScopeAnalysis {
+                scopes: vec![Scope { parent: None, kind: ScopeKind::Document, span, binders: Vec::new() }],
+                uses: Vec::new(),
+            }),
+            stack: RefCell::new(vec![0]),
+            pattern: RefCell::new(None),
+            role: Cell::new(UseRole::Other),
+        };
+        builder.visit_node(ir);
+        Some(builder.analysis.into_inner())
+    }
+
+    /// The binder declared or used at byte `offset`.
+    pub(crate) fn binder_at(&self, offset: usize) -> Option<(usize, usize)> {
+        let declared = self.scopes.iter().enumerate().find_map(|(scope, s)| {
+            s.binders
+                .iter()
+                .position(|binder| contains(&binder.span, offset))
+                .map(|index| (scope, index))
+        });
+        declared.or_else(|| self.uses.iter().find(|u| contains(&u.span, offset)).map(|u| u.binder))
+    }
+
+    /// The scopes containing byte `offset`, from the document to the innermost.
+    pub(crate) fn chain_at(&self, offset: usize) -> Vec<usize> {
+        let innermost = (0..self.scopes.len())
+            .rev()
+            .find(|&scope| contains(&self.scopes[scope].span, offset))
+            .unwrap_or(0);
+        let mut chain: Vec<usize> =
+            std::iter::successors(Some(innermost), |&scope| self.scopes[scope].parent).collect();
+        chain.reverse();
+        chain
+    }
+
+    /// Uses of the binder at `index` in `scope`, in document order.
+    pub(crate) fn uses_of(&self, scope: usize, index: usize) -> impl Iterator<Item = &Use> {
+        self.uses.iter().filter(move |u| u.binder == (scope, index))
+    }
+}
+
+/// Builds a [`ScopeAnalysis`] in one pass over the IR
+struct ScopeBuilder<'a> {
+    positions: &'a HashMap<usize, (IrPosition, IrPosition)>,
+    analysis: RefCell<ScopeAnalysis>,
+    /// Enclosing scopes, innermost last
+    stack: RefCell<Vec<usize>>,
+    /// Variables declared by the pattern being visited, if any
+    pattern: RefCell<Option<Vec<Binder>>>,
+    /// Role of the next variable visited
+    role: Cell<UseRole>,
+}
+
+impl ScopeBuilder<'_> {
+    fn span(&self, node: &Arc<RholangNode>) -> Option<Span> {
+        self.positions.get(&(Arc::as_ptr(node) as usize)).copied()
+    }
+
+    /// Visits binding patterns and returns the variables they declare.
+    ///
+    /// `=x` references inside the patterns are recorded as uses in the
+    /// enclosing scope.
+    fn declared_by<'n>(&self, patterns: impl IntoIterator<Item = &'n Arc<RholangNode>>) -> Vec<Binder> {
+        let outer = self.pattern.replace(Some(Vec::new()));
+        for pattern in patterns {
+            self.visit_node(pattern);
+        }
+        let declared = self.pattern.replace(outer).unwrap_or_default();
+        declared
+    }
+
+    /// Visits `body` in a new scope declaring `binders`.
+    fn in_scope(&self, kind: ScopeKind, span: Option<Span>, binders: Vec<Binder>, body: &Arc<RholangNode>) {
+        let Some(span) = span else {
+            self.visit_node(body);
+            return;
+        };
+        let parent = self.stack.borrow().last().copied();
+        let scope = {
+            let mut analysis = self.analysis.borrow_mut();
+            analysis.scopes.push(Scope { parent, kind, span, binders });
+            analysis.scopes.len() - 1
+        };
+        self.stack.borrow_mut().push(scope);
+        self.visit_node(body);
+        self.stack.borrow_mut().pop();
+    }
+
+    /// Visits the channel of a send or receive.
+    ///
+    /// Only a plain variable takes `role`, also through quote/eval round-trips
+    /// like `@*x`; names inside another quoted channel are other uses.
+    fn visit_channel(&self, channel: &Arc<RholangNode>, role: UseRole) {
+        let channel = match &**channel {
+            RholangNode::ReceiveSendSource { name, .. } => name,
+            _ => channel,
+        };
+        if matches!(strip_quote_eval(channel), RholangNode::Var { .. }) {
+            self.role.set(role);
+        }
+        self.visit_node(channel);
+        self.role.set(UseRole::Other);
+    }
+
+    fn record_use(&self, name: &str, span: Span, role: UseRole) {
+        let stack = self.stack.borrow();
+        let mut analysis = self.analysis.borrow_mut();
+        let resolved = stack.iter().rev().find_map(|&scope| {
+            analysis.scopes[scope]
+                .binders
+                .iter()
+                .rposition(|binder| binder.name == name)
+                .map(|index| (scope, index))
+        });
+        let binder = resolved.unwrap_or_else(|| {
+            let document = &mut analysis.scopes[0].binders;
+            document.push(Binder { name: name.to_string(), span, origin: BinderOrigin::Implicit });
+            (0, document.len() - 1)
+        });
+        let scope = stack.last().copied().unwrap_or(0);
+        analysis.uses.push(Use { name: name.to_string(), span, scope, binder, role });
+    }
+
+    /// Visits the sources of `for`/`select` binds and returns the variables their patterns declare.
+    fn receipts_declare<'n>(&self, binds: impl IntoIterator<Item = &'n Arc<RholangNode>>) -> Vec<Binder> {
+        let mut declared = Vec::new();
+        for bind in binds {
+            let (RholangNode::LinearBind { names, remainder, source, .. }
+            | RholangNode::RepeatedBind { names, remainder, source, .. }
+            | RholangNode::PeekBind { names, remainder, source, .. }) = &**bind
+            else {
+                self.visit_node(bind);
+                continue;
+            };
+            match &**source {
+                // `ch!?(args)` sends on `ch` and receives the reply
+                RholangNode::SendReceiveSource { name, inputs, .. } => {
+                    self.visit_channel(name, UseRole::Send);
+                    for input in inputs.iter() {
+                        self.visit_node(input);
+                    }
+                }
+                _ => self.visit_channel(source, UseRole::Receive),
+            }
+            declared.extend(self.declared_by(names.iter().chain(remainder.iter())));
+        }
+        declared
+    }
+}
+
+impl Visitor for ScopeBuilder<'_> {
+    fn visit_var(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        name: &String,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        let role = self.role.replace(UseRole::Other);
+        if let Some(span) = self.span(node) {
+            match self.pattern.borrow_mut().as_mut() {
+                Some(declared) => declared.push(Binder { name: name.clone(), span, origin: BinderOrigin::Pattern }),
+                None => self.record_use(name, span, role),
+            }
+        }
+        Arc::clone(node)
+    }
+
+    fn visit_varref(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        _kind: RholangVarRefKind,
+        var: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        let pattern = self.pattern.replace(None);
+        self.visit_node(var);
+        self.pattern.replace(pattern);
+        Arc::clone(node)
+    }
+
+    fn visit_send(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        channel: &Arc<RholangNode>,
+        _send_type: &RholangSendType,
+        _send_type_pos: &IrPosition,
+        inputs: &RholangNodeVector,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.visit_channel(channel, UseRole::Send);
+        for input in inputs.iter() {
+            self.visit_node(input);
+        }
+        Arc::clone(node)
+    }
+
+    fn visit_send_sync(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        channel: &Arc<RholangNode>,
+        inputs: &RholangNodeVector,
+        cont: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.visit_channel(channel, UseRole::Send);
+        for input in inputs.iter() {
+            self.visit_node(input);
+        }
+        self.visit_node(cont);
+        Arc::clone(node)
+    }
+
+    fn visit_new(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        decls: &RholangNodeVector,
+        proc: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        let mut declared = Vec::new();
+        for decl in decls.iter() {
+            let RholangNode::NameDecl { var, uri, .. } = &**decl else { continue };
+            let origin = BinderOrigin::New { uri: uri.is_some() };
+            declared.extend(self.declared_by([var]).into_iter().map(|binder| Binder { origin, ..binder }));
+        }
+        self.in_scope(ScopeKind::New, self.span(node), declared, proc);
+        Arc::clone(node)
+    }
+
+    fn visit_let(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        decls: &RholangNodeVector,
+        proc: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        let mut declared = Vec::new();
+        for decl in decls.iter() {
+            let RholangNode::Decl { names, names_remainder, procs, .. } = &**decl else { continue };
+            for value in procs.iter() {
+                self.visit_node(value);
+            }
+            declared.extend(self.declared_by(names.iter().chain(names_remainder.iter())));
+        }
+        self.in_scope(ScopeKind::Let, self.span(node), declared, proc);
+        Arc::clone(node)
+    }
+
+    fn visit_contract(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        name: &Arc<RholangNode>,
+        formals: &RholangNodeVector,
+        formals_remainder: &Option<Arc<RholangNode>>,
+        proc: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.visit_channel(name, UseRole::Receive);
+        let declared = self.declared_by(formals.iter().chain(formals_remainder.iter()));
+        self.in_scope(ScopeKind::Contract, self.span(node), declared, proc);
+        Arc::clone(node)
+    }
+
+    fn visit_input(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        receipts: &RholangReceiptVector,
+        proc: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        let declared = self.receipts_declare(receipts.iter().flatten());
+        self.in_scope(ScopeKind::For, self.span(node), declared, proc);
+        Arc::clone(node)
+    }
+
+    fn visit_match(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        expression: &Arc<RholangNode>,
+        cases: &RholangNodePairVector,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.visit_node(expression);
+        for (pattern, proc) in cases.iter() {
+            let declared = self.declared_by([pattern]);
+            let span = self.span(pattern).zip(self.span(proc)).map(|((start, _), (_, end))| (start, end));
+            self.in_scope(ScopeKind::Match, span, declared, proc);
+        }
+        Arc::clone(node)
+    }
+
+    fn visit_choice(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        branches: &RholangBranchVector,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        for (inputs, proc) in branches.iter() {
+            let declared = self.receipts_declare(inputs.iter());
+            let first = inputs.front().unwrap_or(proc);
+            let span = self.span(first).zip(self.span(proc)).map(|((start, _), (_, end))| (start, end));
+            self.in_scope(ScopeKind::Select, span, declared, proc);
+        }
+        Arc::clone(node)
+    }
+}
+
+/// Whether byte `offset` is within `span`, ends included.
+pub(crate) fn contains((start, end): &Span, offset: usize) -> bool {
+    start.byte <= offset && offset <= end.byte
+}
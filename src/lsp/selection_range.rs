@@ -0,0 +1,105 @@
+//! Expand selection (`textDocument/selectionRange`)
+//!
+//! The selection grows along the path [`find_node_at_position_with_path`]
+//! walks from the document root to the innermost node at the cursor: from an
+//! identifier to the send it is in, the block around it, the contract body,
+//! the contract, and finally the top-level process. Nodes without a span and
+//! nodes spanning the same text as the one inside them add no step.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use ropey::Rope;
+use tower_lsp::lsp_types::{Position as LspPosition, Range, SelectionRange};
+
+use crate::ir::rholang_node::{find_node_at_position_with_path, Position as IrPosition, RholangNode};
+use crate::lsp::call_hierarchy::node_range;
+use crate::lsp::line_index::LineIndex;
+
+/// The selection ranges at `position` in `text`, innermost first; an empty
+/// range at `position` if no node contains it.
+pub fn selection_range(
+    ir: &Arc<RholangNode>,
+    positions: &HashMap<usize, (IrPosition, IrPosition)>,
+    text: &Rope,
+    line_index: &LineIndex,
+    position: LspPosition,
+) -> SelectionRange {
+    let empty = || SelectionRange { range: Range::new(position, position), parent: None };
+    let line = position.line as usize;
+    let (Some(byte), Some(line_start)) =
+        (line_index.offset(line, position.character as usize, text), line_index.line_start(line))
+    else {
+        return empty();
+    };
+    let at = IrPosition { row: line, column: byte - line_start, byte };
+    let Some((_, path)) = find_node_at_position_with_path(ir, positions, at) else {
+        return empty();
+    };
+
+    // Outermost first, each range within the one before it
+    let mut ranges: Vec<Range> = Vec::new();
+    for range in path.iter().filter_map(|node| node_range(node, positions, text, line_index)) {
+        match ranges.last() {
+            Some(outer) if *outer == range => {}
+            Some(outer) if range.start < outer.start || outer.end < range.end => {}
+            _ => ranges.push(range),
+        }
+    }
+
+    ranges
+        .into_iter()
+        .fold(None, |parent, range| Some(SelectionRange { range, parent: parent.map(Box::new) }))
+        .unwrap_or_else(empty)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree_sitter::parse_with_positions;
+
+    fn chain(source: &str, line: usize, column: usize) -> Vec<String> {
+        let rope = Rope::from_str(source);
+        let (ir, positions) = parse_with_positions(source);
+        let index = LineIndex::from_rope(&rope);
+        let position = LspPosition::new(line as u32, column as u32);
+        let mut current = Some(selection_range(&ir, &positions, &rope, &index, position));
+        let mut texts = Vec::new();
+        while let Some(selection) = current {
+            let start = index.offset(selection.range.start.line as usize, selection.range.start.character as usize, &rope).unwrap();
+            let end = index.offset(selection.range.end.line as usize, selection.range.end.character as usize, &rope).unwrap();
+            texts.push(source[start..end].to_string());
+            current = selection.parent.map(|parent| *parent);
+        }
+        texts
+    }
+
+    #[test]
+    fn test_selection_grows_to_the_top_level_process() {
+        let source = "contract log(@msg) = {\n  stdout!(msg)\n}\n|\nNil";
+        let texts = chain(source, 1, 10);
+        assert_eq!(texts.first().map(String::as_str), Some("msg"));
+        assert!(texts.contains(&"stdout!(msg)".to_string()), "{:?}", texts);
+        assert!(texts.contains(&"{\n  stdout!(msg)\n}".to_string()), "{:?}", texts);
+        assert!(texts.contains(&"contract log(@msg) = {\n  stdout!(msg)\n}".to_string()), "{:?}", texts);
+        assert_eq!(texts.last().map(String::as_str), Some(source));
+        // Each step is strictly larger than the one before
+        assert!(texts.windows(2).all(|pair| pair[0].len() < pair[1].len()), "{:?}", texts);
+    }
+
+    #[test]
+    fn test_outside_every_node() {
+        let source = "Nil\n\n\n";
+        let texts = chain(source, 2, 0);
+        assert!(texts.len() <= 1, "{:?}", texts);
+    }
+
+    #[test]
+    fn test_positions_count_utf16_units() {
+        // `🦀` is four bytes but two UTF-16 units
+        let source = "new x in {\n  x!(\"🦀\", *x)\n}";
+        let texts = chain(source, 1, 12);
+        assert_eq!(texts.first().map(String::as_str), Some("x"));
+        assert!(texts.contains(&"x!(\"🦀\", *x)".to_string()), "{:?}", texts);
+    }
+}
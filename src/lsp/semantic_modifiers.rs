@@ -0,0 +1,236 @@
+//! Semantic token classification of Rholang names
+//!
+//! Every resolved name in a document becomes a `variable` token, or a
+//! `function` token for contract names, with modifiers from the legend
+//! returned by [`token_modifiers`]:
+//!
+//! | Modifier         | Applied to                                                  |
+//! |------------------|-------------------------------------------------------------|
+//! | `declaration`    | binders: `new` names, formals, `for`/`let`/`match` patterns |
+//! | `definition`     | the name of a `contract` definition                         |
+//! | `readonly`       | names inside a `bundle-` (receive-only) bundle              |
+//! | `defaultLibrary` | system names bound to a URI, e.g. `stdout(`rho:io:stdout`)` |
+//!
+//! `deprecated` is added separately by the deprecation pass, and `todo` marks
+//! the `TODO`/`FIXME`/`NOTE` markers in comments (see
+//! `crate::lsp::task_markers`).
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+
+use ropey::Rope;
+use tower_lsp::lsp_types::SemanticTokenModifier;
+
+use crate::ir::rholang_node::{Metadata, NodeBase, Position as IrPosition, RholangBundleType, RholangNode, RholangNodeVector};
+use crate::ir::visitor::Visitor;
+use crate::lsp::line_index::{lsp_range, LineIndex};
+use crate::lsp::scopes::{contains, BinderOrigin, ScopeAnalysis, Span};
+
+/// Legend index of `SemanticTokenType::COMMENT`
+pub const TOKEN_COMMENT: u32 = 0;
+/// Legend index of `SemanticTokenType::VARIABLE`
+pub const TOKEN_VARIABLE: u32 = 5;
+/// Legend index of `SemanticTokenType::FUNCTION`
+pub const TOKEN_FUNCTION: u32 = 6;
+
+/// Bit of `SemanticTokenModifier::DEPRECATED` in the legend
+pub const MODIFIER_DEPRECATED: u32 = 1 << 0;
+/// Bit of `SemanticTokenModifier::DECLARATION` in the legend
+pub const MODIFIER_DECLARATION: u32 = 1 << 1;
+/// Bit of `SemanticTokenModifier::DEFINITION` in the legend
+pub const MODIFIER_DEFINITION: u32 = 1 << 2;
+/// Bit of `SemanticTokenModifier::READONLY` in the legend
+pub const MODIFIER_READONLY: u32 = 1 << 3;
+/// Bit of `SemanticTokenModifier::DEFAULT_LIBRARY` in the legend
+pub const MODIFIER_DEFAULT_LIBRARY: u32 = 1 << 4;
+/// Bit of the custom `todo` modifier in the legend
+pub const MODIFIER_TODO: u32 = 1 << 5;
+
+/// Token modifiers of the semantic tokens legend, in bit order.
+pub fn token_modifiers() -> Vec<SemanticTokenModifier> {
+    vec![
+        SemanticTokenModifier::DEPRECATED,
+        SemanticTokenModifier::DECLARATION,
+        SemanticTokenModifier::DEFINITION,
+        SemanticTokenModifier::READONLY,
+        SemanticTokenModifier::DEFAULT_LIBRARY,
+        SemanticTokenModifier::new("todo"),
+    ]
+}
+
+/// A classified name, at an absolute LSP position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NameToken {
+    pub line: u32,
+    pub start: u32,
+    pub length: u32,
+    pub token_type: u32,
+    pub modifiers: u32,
+}
+
+/// Classifies the names of a document, in document order.
+pub fn classify_names(
+    ir: &Arc<RholangNode>,
+    positions: &HashMap<usize, (IrPosition, IrPosition)>,
+    text: &Rope,
+    line_index: &LineIndex,
+) -> Vec<NameToken> {
+    let Some(analysis) = ScopeAnalysis::build(ir, positions) else {
+        return Vec::new();
+    };
+    let collector = SpanCollector {
+        positions,
+        contract_names: RefCell::new(Vec::new()),
+        read_only: RefCell::new(Vec::new()),
+    };
+    collector.visit_node(ir);
+    let contract_names = collector.contract_names.into_inner();
+    let read_only = collector.read_only.into_inner();
+
+    // Binders named by a contract definition: their uses are contract calls
+    let contracts: HashSet<(usize, usize)> = analysis
+        .uses
+        .iter()
+        .filter(|u| contract_names.contains(&u.span))
+        .map(|u| u.binder)
+        .collect();
+
+    let mut tokens: BTreeMap<(u32, u32), NameToken> = BTreeMap::new();
+    let mut add = |span: &Span, token_type: u32, modifiers: u32| {
+        let range = lsp_range(text, line_index, span);
+        let (start, end) = (range.start, range.end);
+        if start.line != end.line || end.character <= start.character {
+            return;
+        }
+        let token = tokens.entry((start.line, start.character)).or_insert(NameToken {
+            line: start.line,
+            start: start.character,
+            length: end.character - start.character,
+            token_type,
+            modifiers: 0,
+        });
+        token.modifiers |= modifiers;
+        if token_type == TOKEN_FUNCTION {
+            token.token_type = TOKEN_FUNCTION;
+        }
+    };
+    let in_read_only = |span: &Span| read_only.iter().any(|bundle| contains(bundle, span.0.byte));
+    let library = |origin: BinderOrigin| if origin == (BinderOrigin::New { uri: true }) { MODIFIER_DEFAULT_LIBRARY } else { 0 };
+
+    for (scope, declared) in analysis.scopes.iter().enumerate() {
+        for (index, binder) in declared.binders.iter().enumerate() {
+            if binder.origin == BinderOrigin::Implicit {
+                continue;
+            }
+            let token_type = if contracts.contains(&(scope, index)) { TOKEN_FUNCTION } else { TOKEN_VARIABLE };
+            add(&binder.span, token_type, MODIFIER_DECLARATION | library(binder.origin));
+        }
+    }
+    for u in &analysis.uses {
+        let (scope, index) = u.binder;
+        let origin = analysis.scopes[scope].binders[index].origin;
+        let token_type = if contracts.contains(&u.binder) { TOKEN_FUNCTION } else { TOKEN_VARIABLE };
+        let mut modifiers = library(origin);
+        if contract_names.contains(&u.span) {
+            modifiers |= MODIFIER_DEFINITION;
+        }
+        if in_read_only(&u.span) {
+            modifiers |= MODIFIER_READONLY;
+        }
+        add(&u.span, token_type, modifiers);
+    }
+
+    tokens.into_values().collect()
+}
+
+/// Collects contract name spans and `bundle-` spans
+struct SpanCollector<'a> {
+    positions: &'a HashMap<usize, (IrPosition, IrPosition)>,
+    contract_names: RefCell<Vec<Span>>,
+    read_only: RefCell<Vec<Span>>,
+}
+
+impl SpanCollector<'_> {
+    fn span(&self, node: &Arc<RholangNode>) -> Option<Span> {
+        self.positions.get(&(Arc::as_ptr(node) as usize)).copied()
+    }
+}
+
+impl Visitor for SpanCollector<'_> {
+    fn visit_contract(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        name: &Arc<RholangNode>,
+        _formals: &RholangNodeVector,
+        _formals_remainder: &Option<Arc<RholangNode>>,
+        proc: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        if let Some(span) = self.span(name) {
+            self.contract_names.borrow_mut().push(span);
+        }
+        self.visit_node(proc);
+        Arc::clone(node)
+    }
+
+    fn visit_bundle(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        bundle_type: &RholangBundleType,
+        proc: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        if *bundle_type == RholangBundleType::Read {
+            if let Some(span) = self.span(node) {
+                self.read_only.borrow_mut().push(span);
+            }
+        }
+        self.visit_node(proc);
+        Arc::clone(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree_sitter::parse_with_positions;
+
+    fn tokens(source: &str) -> Vec<(u32, u32, u32, u32)> {
+        let rope = Rope::from_str(source);
+        let (ir, positions) = parse_with_positions(source);
+        classify_names(&ir, &positions, &rope, &LineIndex::new(source))
+            .into_iter()
+            .map(|t| (t.line, t.start, t.token_type, t.modifiers))
+            .collect()
+    }
+
+    #[test]
+    fn test_declarations_and_library_names() {
+        let source = "new stdout(`rho:io:stdout`), x in {\n  stdout!(*x)\n}";
+        assert_eq!(
+            tokens(source),
+            vec![
+                (0, 4, TOKEN_VARIABLE, MODIFIER_DECLARATION | MODIFIER_DEFAULT_LIBRARY),
+                (0, 29, TOKEN_VARIABLE, MODIFIER_DECLARATION),
+                (1, 2, TOKEN_VARIABLE, MODIFIER_DEFAULT_LIBRARY),
+                (1, 11, TOKEN_VARIABLE, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_contract_definitions_and_read_only_bundles() {
+        let source = "new ch in {\n  contract echo(@n) = { Nil } |\n  echo!(bundle-{*ch})\n}";
+        let found = tokens(source);
+        // Contract name: a function definition; its call is a function too
+        assert!(found.contains(&(1, 11, TOKEN_FUNCTION, MODIFIER_DEFINITION)));
+        assert!(found.contains(&(2, 2, TOKEN_FUNCTION, 0)));
+        // Formal `n` is a declaration
+        assert!(found.contains(&(1, 17, TOKEN_VARIABLE, MODIFIER_DECLARATION)));
+        // `ch` inside `bundle-` is read-only
+        assert!(found.contains(&(2, 17, TOKEN_VARIABLE, MODIFIER_READONLY)));
+    }
+}
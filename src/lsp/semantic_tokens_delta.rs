@@ -0,0 +1,146 @@
+//! Incremental semantic tokens (`textDocument/semanticTokens/full/delta`)
+//!
+//! Every token set sent to the client is kept per document under a result
+//! id. A delta request names the result id the client holds; the tokens are
+//! computed afresh from the document's current IR and compared with the kept
+//! set, and only the span that differs is sent back, as one
+//! [`SemanticTokensEdit`] over the flat `u32` array.
+//!
+//! Because tokens are encoded relative to the previous one, an edit leaves
+//! the tokens before it unchanged and shifts only the first token after it,
+//! so the span between the common prefix and the common suffix stays about
+//! as small as the edit. A request naming a result id that is not the last
+//! one sent gets the full token set instead.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use dashmap::DashMap;
+use tower_lsp::lsp_types::{
+    SemanticToken, SemanticTokens, SemanticTokensDelta, SemanticTokensEdit, SemanticTokensFullDeltaResult, Url,
+};
+
+/// Integers per token in the flat encoding
+const TOKEN_WIDTH: u32 = 5;
+
+/// The last token set sent for each document
+#[derive(Debug, Default)]
+pub struct SemanticTokensCache {
+    next_id: AtomicU64,
+    documents: DashMap<Url, (String, Vec<SemanticToken>)>,
+}
+
+impl SemanticTokensCache {
+    /// A cache whose result ids count up from `first_id`.
+    pub fn starting_at(first_id: u64) -> Self {
+        SemanticTokensCache { next_id: AtomicU64::new(first_id), documents: DashMap::new() }
+    }
+
+    fn remember(&self, uri: &Url, tokens: Vec<SemanticToken>) -> String {
+        let result_id = self.next_id.fetch_add(1, Ordering::Relaxed).to_string();
+        self.documents.insert(uri.clone(), (result_id.clone(), tokens));
+        result_id
+    }
+
+    /// Keeps `tokens` as the last set sent for `uri` and returns them whole.
+    pub fn full(&self, uri: &Url, tokens: Vec<SemanticToken>) -> SemanticTokens {
+        let result_id = self.remember(uri, tokens.clone());
+        SemanticTokens { result_id: Some(result_id), data: tokens }
+    }
+
+    /// The edits turning the set the client holds, `previous_result_id`,
+    /// into `tokens`; the whole of `tokens` if that set is no longer known.
+    pub fn delta(&self, uri: &Url, previous_result_id: &str, tokens: Vec<SemanticToken>) -> SemanticTokensFullDeltaResult {
+        let edits = self
+            .documents
+            .get(uri)
+            .filter(|entry| entry.value().0 == previous_result_id)
+            .map(|entry| token_edits(&entry.value().1, &tokens));
+        match edits {
+            Some(edits) => {
+                let result_id = self.remember(uri, tokens);
+                SemanticTokensFullDeltaResult::TokensDelta(SemanticTokensDelta { result_id: Some(result_id), edits })
+            }
+            None => SemanticTokensFullDeltaResult::Tokens(self.full(uri, tokens)),
+        }
+    }
+
+    /// Forgets the tokens of `uri`.
+    pub fn remove(&self, uri: &Url) {
+        self.documents.remove(uri);
+    }
+}
+
+/// The edit replacing the tokens between the common prefix and the common
+/// suffix of `old` and `new`; no edit if they are equal.
+pub fn token_edits(old: &[SemanticToken], new: &[SemanticToken]) -> Vec<SemanticTokensEdit> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let deleted = old.len() - prefix - suffix;
+    let inserted = &new[prefix..new.len() - suffix];
+    if deleted == 0 && inserted.is_empty() {
+        return Vec::new();
+    }
+    vec![SemanticTokensEdit {
+        start: prefix as u32 * TOKEN_WIDTH,
+        delete_count: deleted as u32 * TOKEN_WIDTH,
+        data: (!inserted.is_empty()).then(|| inserted.to_vec()),
+    }]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(delta_line: u32, delta_start: u32, length: u32) -> SemanticToken {
+        SemanticToken { delta_line, delta_start, length, token_type: 0, token_modifiers_bitset: 0 }
+    }
+
+    #[test]
+    fn test_edits_cover_only_the_changed_span() {
+        let old = vec![token(0, 0, 3), token(1, 4, 5), token(2, 0, 3), token(0, 6, 2)];
+        // A line inserted before the third token shifts it alone
+        let new = vec![token(0, 0, 3), token(1, 4, 5), token(3, 0, 3), token(0, 6, 2)];
+        assert_eq!(
+            token_edits(&old, &new),
+            vec![SemanticTokensEdit { start: 10, delete_count: 5, data: Some(vec![token(3, 0, 3)]) }]
+        );
+
+        // Removed tokens leave a deletion without data
+        assert_eq!(
+            token_edits(&old, &old[..2]),
+            vec![SemanticTokensEdit { start: 10, delete_count: 10, data: None }]
+        );
+        assert!(token_edits(&old, &old).is_empty());
+
+        // Repeated tokens are not counted in both the prefix and the suffix
+        let repeated = vec![token(1, 0, 1), token(1, 0, 1)];
+        assert_eq!(
+            token_edits(&repeated, &repeated[..1]),
+            vec![SemanticTokensEdit { start: 5, delete_count: 5, data: None }]
+        );
+    }
+
+    #[test]
+    fn test_delta_against_the_last_result_only() {
+        let cache = SemanticTokensCache::default();
+        let uri = Url::parse("file:///a.rho").unwrap();
+        let first = cache.full(&uri, vec![token(0, 0, 3)]);
+        let first_id = first.result_id.unwrap();
+
+        let SemanticTokensFullDeltaResult::TokensDelta(delta) = cache.delta(&uri, &first_id, vec![token(0, 0, 4)]) else {
+            panic!("expected a delta");
+        };
+        assert_eq!(delta.edits.len(), 1);
+        assert_ne!(delta.result_id.as_deref(), Some(first_id.as_str()));
+
+        // The first result was replaced by the delta's
+        assert!(matches!(cache.delta(&uri, &first_id, vec![]), SemanticTokensFullDeltaResult::Tokens(_)));
+        cache.remove(&uri);
+        assert!(cache.documents.is_empty());
+    }
+}
@@ -0,0 +1,266 @@
+//! Semantic tokens of the visible range (`textDocument/semanticTokens/range`)
+//!
+//! Editors ask for the tokens of the viewport with a range request before
+//! (or instead of) the tokens of the whole document. Full-document tokens
+//! are turned off for large documents (see `crate::lsp::analysis_level`),
+//! but ranges are served at every size, so big files are still highlighted
+//! where they are being read.
+//!
+//! A document's tokens are classified once per content and kept as
+//! [`TokenChunks`]: sorted absolute tokens split into chunks of
+//! [`CHUNK_LINES`] lines. A range is answered from the chunks it overlaps,
+//! without classifying the document again on every scroll. The chunks are
+//! dropped when the document changes, and when symbols are linked again,
+//! since deprecations come from other documents.
+//!
+//! Range jobs come first: a full-document job waits while range jobs for
+//! the same document run, for at most [`FULL_JOB_MAX_WAIT`], and then
+//! usually finds their chunks in the cache.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use tokio::sync::Notify;
+use tower_lsp::lsp_types::{Range, SemanticToken, Url};
+
+/// Lines per chunk of a document's tokens
+pub const CHUNK_LINES: u32 = 256;
+
+/// Longest a full-document job yields to range jobs
+pub const FULL_JOB_MAX_WAIT: Duration = Duration::from_millis(500);
+
+/// A token at an absolute position: line, start character, length, token
+/// type and modifiers bitset
+pub type AbsoluteToken = (u32, u32, u32, u32, u32);
+
+/// Delta-encodes sorted absolute tokens.
+pub fn encode<'a>(tokens: impl IntoIterator<Item = &'a AbsoluteToken>) -> Vec<SemanticToken> {
+    let mut prev_line = 0;
+    let mut prev_start = 0;
+    tokens
+        .into_iter()
+        .map(|&(line, start, length, token_type, token_modifiers_bitset)| {
+            let delta_line = line - prev_line;
+            let delta_start = if delta_line == 0 { start - prev_start } else { start };
+            prev_line = line;
+            prev_start = start;
+            SemanticToken { delta_line, delta_start, length, token_type, token_modifiers_bitset }
+        })
+        .collect()
+}
+
+/// The tokens of one document content, by chunk of lines
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenChunks {
+    content_hash: u64,
+    chunks: Vec<Vec<AbsoluteToken>>,
+}
+
+impl TokenChunks {
+    /// Splits `tokens`, sorted by position, into chunks.
+    pub fn new(content_hash: u64, tokens: Vec<AbsoluteToken>) -> Self {
+        let mut chunks: Vec<Vec<AbsoluteToken>> = Vec::new();
+        for token in tokens {
+            let chunk = (token.0 / CHUNK_LINES) as usize;
+            if chunks.len() <= chunk {
+                chunks.resize_with(chunk + 1, Vec::new);
+            }
+            chunks[chunk].push(token);
+        }
+        TokenChunks { content_hash, chunks }
+    }
+
+    pub fn content_hash(&self) -> u64 {
+        self.content_hash
+    }
+
+    pub fn len(&self) -> usize {
+        self.chunks.iter().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.iter().all(Vec::is_empty)
+    }
+
+    /// Every token, encoded.
+    pub fn all(&self) -> Vec<SemanticToken> {
+        encode(self.chunks.iter().flatten())
+    }
+
+    /// The tokens overlapping `range`, encoded from the start of the document.
+    pub fn in_range(&self, range: &Range) -> Vec<SemanticToken> {
+        let first = (range.start.line / CHUNK_LINES) as usize;
+        let last = ((range.end.line / CHUNK_LINES) as usize).min(self.chunks.len().saturating_sub(1));
+        let Some(chunks) = self.chunks.get(first..=last) else {
+            return Vec::new();
+        };
+        let start = (range.start.line, range.start.character);
+        let end = (range.end.line, range.end.character);
+        encode(
+            chunks
+                .iter()
+                .flatten()
+                .filter(|&&(line, character, length, ..)| (line, character + length) > start && (line, character) < end),
+        )
+    }
+}
+
+/// Token chunks of the open documents, and the order of the jobs computing them
+#[derive(Debug, Default)]
+pub struct ChunkedTokens {
+    documents: DashMap<Url, Arc<TokenChunks>>,
+    /// Range jobs running per document
+    range_jobs: Mutex<HashMap<Url, usize>>,
+    /// Notified when the last range job of a document ends
+    range_jobs_done: Notify,
+}
+
+impl ChunkedTokens {
+    /// The chunks of `uri` if they were computed for `content_hash`.
+    pub fn get(&self, uri: &Url, content_hash: u64) -> Option<Arc<TokenChunks>> {
+        self.documents
+            .get(uri)
+            .filter(|chunks| chunks.content_hash == content_hash)
+            .map(|chunks| chunks.value().clone())
+    }
+
+    pub fn insert(&self, uri: &Url, chunks: Arc<TokenChunks>) {
+        self.documents.insert(uri.clone(), chunks);
+    }
+
+    pub fn remove(&self, uri: &Url) {
+        self.documents.remove(uri);
+    }
+
+    /// Forgets the chunks of every document.
+    pub fn clear(&self) {
+        self.documents.clear();
+    }
+
+    /// Marks a range job for `uri` as running until the guard is dropped.
+    pub fn range_job(&self, uri: &Url) -> RangeJob<'_> {
+        *self.range_jobs.lock().entry(uri.clone()).or_insert(0) += 1;
+        RangeJob { tokens: self, uri: uri.clone() }
+    }
+
+    fn running_range_jobs(&self, uri: &Url) -> usize {
+        self.range_jobs.lock().get(uri).copied().unwrap_or(0)
+    }
+
+    /// Waits until no range job for `uri` runs, or for [`FULL_JOB_MAX_WAIT`].
+    pub async fn full_job_turn(&self, uri: &Url) {
+        let wait = async {
+            loop {
+                // Registered before checking, so a job ending in between is not missed
+                let done = self.range_jobs_done.notified();
+                tokio::pin!(done);
+                done.as_mut().enable();
+                if self.running_range_jobs(uri) == 0 {
+                    return;
+                }
+                done.await;
+            }
+        };
+        let _ = tokio::time::timeout(FULL_JOB_MAX_WAIT, wait).await;
+    }
+}
+
+/// A running range job; see [`ChunkedTokens::range_job`]
+pub struct RangeJob<'a> {
+    tokens: &'a ChunkedTokens,
+    uri: Url,
+}
+
+impl Drop for RangeJob<'_> {
+    fn drop(&mut self) {
+        let mut jobs = self.tokens.range_jobs.lock();
+        if let Some(count) = jobs.get_mut(&self.uri) {
+            *count -= 1;
+            if *count == 0 {
+                jobs.remove(&self.uri);
+                self.tokens.range_jobs_done.notify_waiters();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp::lsp_types::Position;
+
+    fn uri() -> Url {
+        Url::parse("file:///big.rho").unwrap()
+    }
+
+    fn range(start: (u32, u32), end: (u32, u32)) -> Range {
+        Range::new(Position::new(start.0, start.1), Position::new(end.0, end.1))
+    }
+
+    /// One token at the start of every tenth line of a 1000-line document
+    fn tokens() -> Vec<AbsoluteToken> {
+        (0..1000).step_by(10).map(|line| (line, 0, 3, 5, 0)).collect()
+    }
+
+    #[test]
+    fn test_range_is_encoded_from_document_start() {
+        let chunks = TokenChunks::new(1, tokens());
+        let data = chunks.in_range(&range((250, 0), (270, 0)));
+        let encoded: Vec<_> = data.iter().map(|t| (t.delta_line, t.delta_start, t.length)).collect();
+        // Crosses the chunk boundary at line 256
+        assert_eq!(encoded, vec![(250, 0, 3), (10, 0, 3)]);
+    }
+
+    #[test]
+    fn test_range_keeps_overlapping_tokens_only() {
+        let chunks = TokenChunks::new(1, vec![(0, 0, 3, 5, 0), (0, 4, 3, 5, 0), (0, 8, 3, 5, 0)]);
+        let data = chunks.in_range(&range((0, 2), (0, 8)));
+        assert_eq!(data.len(), 2);
+        assert_eq!((data[0].delta_start, data[1].delta_start), (0, 4));
+    }
+
+    #[test]
+    fn test_range_past_the_end_is_empty() {
+        let chunks = TokenChunks::new(1, tokens());
+        assert!(chunks.in_range(&range((5000, 0), (5100, 0))).is_empty());
+        assert!(TokenChunks::new(1, Vec::new()).in_range(&range((0, 0), (10, 0))).is_empty());
+    }
+
+    #[test]
+    fn test_all_matches_every_range() {
+        let chunks = TokenChunks::new(1, tokens());
+        assert_eq!(chunks.len(), 100);
+        assert_eq!(chunks.all(), chunks.in_range(&range((0, 0), (1000, 0))));
+    }
+
+    #[test]
+    fn test_chunks_are_kept_per_content() {
+        let cache = ChunkedTokens::default();
+        cache.insert(&uri(), Arc::new(TokenChunks::new(7, tokens())));
+        assert!(cache.get(&uri(), 7).is_some());
+        assert!(cache.get(&uri(), 8).is_none());
+        cache.clear();
+        assert!(cache.get(&uri(), 7).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_full_job_waits_for_range_jobs() {
+        let cache = Arc::new(ChunkedTokens::default());
+        let job = cache.range_job(&uri());
+        let full = {
+            let cache = cache.clone();
+            tokio::spawn(async move { cache.full_job_turn(&uri()).await })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!full.is_finished(), "full job ran alongside a range job");
+
+        drop(job);
+        tokio::time::timeout(Duration::from_secs(1), full)
+            .await
+            .expect("full job was not released")
+            .unwrap();
+    }
+}
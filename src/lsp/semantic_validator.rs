@@ -7,10 +7,18 @@
 use rholang::rust::interpreter::{
     compiler::compiler::Compiler,
     errors::InterpreterError as RholangInterpreterError,
+    pretty_printer::PrettyPrinter,
 };
 
+use crate::lsp::normalized_form::NormalizedForm;
+
+#[cfg(feature = "interpreter")]
+use crate::lsp::diagnostic_registry::related_here;
 #[cfg(feature = "interpreter")]
-use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, Position, Range};
+use crate::lsp::syntax_errors::SYNTAX_ERROR_CODE;
+
+#[cfg(feature = "interpreter")]
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Position, Range};
 #[cfg(not(feature = "interpreter"))]
 use tower_lsp::lsp_types::Diagnostic;
 
@@ -20,6 +28,21 @@ use std::collections::HashMap;
 #[cfg(feature = "interpreter")]
 use tracing::{debug, warn};
 
+/// Diagnostic code of references to unbound variables
+pub const UNBOUND_VARIABLE_CODE: &str = "unbound-variable";
+/// Diagnostic code of names used as processes, and processes used as names
+pub const CONTEXT_MISMATCH_CODE: &str = "name-process-mismatch";
+/// Diagnostic code of variables bound twice in the same pattern
+pub const DUPLICATE_BINDER_CODE: &str = "duplicate-binder";
+/// Diagnostic code of joins receiving twice on the same channel
+pub const RECEIVE_ON_SAME_CHANNEL_CODE: &str = "receive-on-same-channel";
+/// Diagnostic code of free variables, wildcards and connectives at the top level
+pub const TOP_LEVEL_PATTERN_CODE: &str = "top-level-pattern";
+/// Diagnostic code of methods and operators applied to values that lack them
+pub const UNSUPPORTED_OPERATION_CODE: &str = "unsupported-operation";
+/// Diagnostic code of the other errors of the interpreter
+pub const SEMANTIC_ERROR_CODE: &str = "semantic-error";
+
 /// Semantic validator that uses the Rholang interpreter to find semantic errors
 #[cfg(feature = "interpreter")]
 #[derive(Debug, Clone)]
@@ -94,11 +117,43 @@ impl SemanticValidator {
         }
     }
 
+    /// Validate a pre-parsed top-level process, returning its normalized form
+    /// instead of an empty list of diagnostics when it is valid.
+    pub fn normalize_parsed<'a>(
+        &self,
+        ast: rholang_parser::ast::AnnProc<'a>,
+        parser: &'a rholang_parser::RholangParser<'a>,
+    ) -> Result<NormalizedForm, Vec<Diagnostic>> {
+        let range = source_span_to_range(&ast.span);
+        match Compiler::validate_parsed(ast, HashMap::new(), parser) {
+            Ok(par) => {
+                let text = PrettyPrinter::new().build_string_from_message(&par);
+                debug!("Normalized process at {:?} ({} bytes)", range, text.len());
+                Ok(NormalizedForm { range, text })
+            }
+            Err(e) => {
+                debug!("Validation failed with error: {:?}", e);
+                Err(self.error_to_diagnostics(e))
+            }
+        }
+    }
+
     /// Convert a Rholang interpreter error to one or more LSP diagnostics
     ///
-    /// Some errors contain multiple source spans (e.g., duplicate declarations showing both locations).
-    /// For these cases, we create one diagnostic per span as requested by the user.
+    /// Some errors contain two source spans (e.g., a binder reused in the same pattern).
+    /// These become one diagnostic at the offending span, with the other span as related
+    /// information. Errors without a more specific code get [`SEMANTIC_ERROR_CODE`].
     fn error_to_diagnostics(&self, error: RholangInterpreterError) -> Vec<Diagnostic> {
+        let mut diagnostics = self.diagnostics_for(error);
+        for diagnostic in &mut diagnostics {
+            diagnostic
+                .code
+                .get_or_insert_with(|| NumberOrString::String(SEMANTIC_ERROR_CODE.to_string()));
+        }
+        diagnostics
+    }
+
+    fn diagnostics_for(&self, error: RholangInterpreterError) -> Vec<Diagnostic> {
         use RholangInterpreterError::*;
 
         match &error {
@@ -108,6 +163,7 @@ impl SemanticValidator {
                 vec![Diagnostic {
                     range: source_span_to_range(source_span),
                     severity: Some(DiagnosticSeverity::ERROR),
+                    code: Some(NumberOrString::String(UNBOUND_VARIABLE_CODE.to_string())),
                     source: Some("rholang-semantic".to_string()),
                     message: format!("Unbound variable: {}", var_name),
                     ..Default::default()
@@ -119,41 +175,33 @@ impl SemanticValidator {
                 vec![Diagnostic {
                     range: source_pos_to_range(source_pos),
                     severity: Some(DiagnosticSeverity::ERROR),
+                    code: Some(NumberOrString::String(UNBOUND_VARIABLE_CODE.to_string())),
                     source: Some("rholang-semantic".to_string()),
                     message: format!("Unbound variable: {}", var_name),
                     ..Default::default()
                 }]
             }
 
-            // Errors with multiple source spans - create one diagnostic per span
+            // Errors with two source spans: one diagnostic at the offending
+            // span, pointing back at the other one
             UnexpectedProcContext {
                 var_name,
                 name_var_source_span,
                 process_source_span,
             } => {
                 debug!("Name variable '{}' used in process context", var_name);
-                vec![
-                    Diagnostic {
-                        range: source_span_to_range(name_var_source_span),
-                        severity: Some(DiagnosticSeverity::ERROR),
-                        source: Some("rholang-semantic".to_string()),
-                        message: format!(
-                            "Name variable '{}' declared here",
-                            var_name
-                        ),
-                        ..Default::default()
-                    },
-                    Diagnostic {
-                        range: source_span_to_range(process_source_span),
-                        severity: Some(DiagnosticSeverity::ERROR),
-                        source: Some("rholang-semantic".to_string()),
-                        message: format!(
-                            "Name variable '{}' used in process context here",
-                            var_name
-                        ),
-                        ..Default::default()
-                    },
-                ]
+                vec![Diagnostic {
+                    range: source_span_to_range(process_source_span),
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    code: Some(NumberOrString::String(CONTEXT_MISMATCH_CODE.to_string())),
+                    source: Some("rholang-semantic".to_string()),
+                    message: format!("Name variable '{}' used in process context", var_name),
+                    related_information: Some(vec![related_here(
+                        source_span_to_range(name_var_source_span),
+                        format!("'{}' bound as a name here", var_name),
+                    )]),
+                    ..Default::default()
+                }]
             }
 
             UnexpectedNameContext {
@@ -162,22 +210,18 @@ impl SemanticValidator {
                 name_source_span,
             } => {
                 debug!("Process variable '{}' used in name context", var_name);
-                vec![
-                    Diagnostic {
-                        range: source_span_to_range(proc_var_source_span),
-                        severity: Some(DiagnosticSeverity::ERROR),
-                        source: Some("rholang-semantic".to_string()),
-                        message: format!("Process variable '{}' declared here", var_name),
-                        ..Default::default()
-                    },
-                    Diagnostic {
-                        range: source_span_to_range(name_source_span),
-                        severity: Some(DiagnosticSeverity::ERROR),
-                        source: Some("rholang-semantic".to_string()),
-                        message: format!("Process variable '{}' used in name context here", var_name),
-                        ..Default::default()
-                    },
-                ]
+                vec![Diagnostic {
+                    range: source_span_to_range(name_source_span),
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    code: Some(NumberOrString::String(CONTEXT_MISMATCH_CODE.to_string())),
+                    source: Some("rholang-semantic".to_string()),
+                    message: format!("Process variable '{}' used in name context", var_name),
+                    related_information: Some(vec![related_here(
+                        source_span_to_range(proc_var_source_span),
+                        format!("'{}' bound as a process here", var_name),
+                    )]),
+                    ..Default::default()
+                }]
             }
 
             UnexpectedReuseOfProcContextFree {
@@ -186,28 +230,18 @@ impl SemanticValidator {
                 second_use,
             } => {
                 debug!("Variable '{}' used twice as binder", var_name);
-                vec![
-                    Diagnostic {
-                        range: source_span_to_range(first_use),
-                        severity: Some(DiagnosticSeverity::ERROR),
-                        source: Some("rholang-semantic".to_string()),
-                        message: format!(
-                            "Process variable '{}' first used as binder here",
-                            var_name
-                        ),
-                        ..Default::default()
-                    },
-                    Diagnostic {
-                        range: source_span_to_range(second_use),
-                        severity: Some(DiagnosticSeverity::ERROR),
-                        source: Some("rholang-semantic".to_string()),
-                        message: format!(
-                            "Process variable '{}' used again as binder here (duplicate)",
-                            var_name
-                        ),
-                        ..Default::default()
-                    },
-                ]
+                vec![Diagnostic {
+                    range: source_span_to_range(second_use),
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    code: Some(NumberOrString::String(DUPLICATE_BINDER_CODE.to_string())),
+                    source: Some("rholang-semantic".to_string()),
+                    message: format!("Process variable '{}' is bound twice in the same pattern", var_name),
+                    related_information: Some(vec![related_here(
+                        source_span_to_range(first_use),
+                        format!("'{}' first bound here", var_name),
+                    )]),
+                    ..Default::default()
+                }]
             }
 
             UnexpectedReuseOfNameContextFree {
@@ -216,28 +250,18 @@ impl SemanticValidator {
                 second_use,
             } => {
                 debug!("Variable '{}' used twice as binder in name context", var_name);
-                vec![
-                    Diagnostic {
-                        range: source_span_to_range(first_use),
-                        severity: Some(DiagnosticSeverity::ERROR),
-                        source: Some("rholang-semantic".to_string()),
-                        message: format!(
-                            "Name variable '{}' first used as binder here",
-                            var_name
-                        ),
-                        ..Default::default()
-                    },
-                    Diagnostic {
-                        range: source_span_to_range(second_use),
-                        severity: Some(DiagnosticSeverity::ERROR),
-                        source: Some("rholang-semantic".to_string()),
-                        message: format!(
-                            "Name variable '{}' used again as binder here (duplicate)",
-                            var_name
-                        ),
-                        ..Default::default()
-                    },
-                ]
+                vec![Diagnostic {
+                    range: source_span_to_range(second_use),
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    code: Some(NumberOrString::String(DUPLICATE_BINDER_CODE.to_string())),
+                    source: Some("rholang-semantic".to_string()),
+                    message: format!("Name variable '{}' is bound twice in the same pattern", var_name),
+                    related_information: Some(vec![related_here(
+                        source_span_to_range(first_use),
+                        format!("'{}' first bound here", var_name),
+                    )]),
+                    ..Default::default()
+                }]
             }
 
             ReceiveOnSameChannelsError { source_span } => {
@@ -245,6 +269,7 @@ impl SemanticValidator {
                 vec![Diagnostic {
                     range: source_span_to_range(source_span),
                     severity: Some(DiagnosticSeverity::ERROR),
+                    code: Some(NumberOrString::String(RECEIVE_ON_SAME_CHANNEL_CODE.to_string())),
                     source: Some("rholang-semantic".to_string()),
                     message: "Receiving on the same channels is not allowed".to_string(),
                     ..Default::default()
@@ -257,6 +282,7 @@ impl SemanticValidator {
                 vec![Diagnostic {
                     range: Range::default(),
                     severity: Some(DiagnosticSeverity::ERROR),
+                    code: Some(NumberOrString::String(TOP_LEVEL_PATTERN_CODE.to_string())),
                     source: Some("rholang-semantic".to_string()),
                     message: format!("Top-level free variables are not allowed: {}", vars),
                     ..Default::default()
@@ -268,6 +294,7 @@ impl SemanticValidator {
                 vec![Diagnostic {
                     range: Range::default(),
                     severity: Some(DiagnosticSeverity::ERROR),
+                    code: Some(NumberOrString::String(TOP_LEVEL_PATTERN_CODE.to_string())),
                     source: Some("rholang-semantic".to_string()),
                     message: format!("Top-level wildcards are not allowed: {}", wildcards),
                     ..Default::default()
@@ -279,6 +306,7 @@ impl SemanticValidator {
                 vec![Diagnostic {
                     range: Range::default(),
                     severity: Some(DiagnosticSeverity::ERROR),
+                    code: Some(NumberOrString::String(TOP_LEVEL_PATTERN_CODE.to_string())),
                     source: Some("rholang-semantic".to_string()),
                     message: format!(
                         "Top-level logical connectives are not allowed: {}",
@@ -292,6 +320,7 @@ impl SemanticValidator {
                 vec![Diagnostic {
                     range: Range::default(),
                     severity: Some(DiagnosticSeverity::ERROR),
+                    code: Some(NumberOrString::String(UNSUPPORTED_OPERATION_CODE.to_string())),
                     source: Some("rholang-semantic".to_string()),
                     message: format!("Method '{}' is not defined on {}", method, other_type),
                     ..Default::default()
@@ -306,6 +335,7 @@ impl SemanticValidator {
                 vec![Diagnostic {
                     range: Range::default(),
                     severity: Some(DiagnosticSeverity::ERROR),
+                    code: Some(NumberOrString::String(UNSUPPORTED_OPERATION_CODE.to_string())),
                     source: Some("rholang-semantic".to_string()),
                     message: format!(
                         "Method '{}' expects {} argument(s), but got {}",
@@ -319,6 +349,7 @@ impl SemanticValidator {
                 vec![Diagnostic {
                     range: Range::default(),
                     severity: Some(DiagnosticSeverity::ERROR),
+                    code: Some(NumberOrString::String(UNSUPPORTED_OPERATION_CODE.to_string())),
                     source: Some("rholang-semantic".to_string()),
                     message: format!("Operator '{}' is not defined on {}", op, other_type),
                     ..Default::default()
@@ -333,6 +364,7 @@ impl SemanticValidator {
                 vec![Diagnostic {
                     range: Range::default(),
                     severity: Some(DiagnosticSeverity::ERROR),
+                    code: Some(NumberOrString::String(UNSUPPORTED_OPERATION_CODE.to_string())),
                     source: Some("rholang-semantic".to_string()),
                     message: format!(
                         "Operator '{}' expected {}, got {}",
@@ -356,7 +388,7 @@ impl SemanticValidator {
                 // Recursively convert all errors and flatten into single vec
                 interpreter_errors
                     .iter()
-                    .flat_map(|e| self.error_to_diagnostics(e.clone()))
+                    .flat_map(|e| self.diagnostics_for(e.clone()))
                     .collect()
             }
 
@@ -387,6 +419,7 @@ impl SemanticValidator {
                 vec![Diagnostic {
                     range: Range::default(),
                     severity: Some(DiagnosticSeverity::ERROR),
+                    code: Some(NumberOrString::String(SYNTAX_ERROR_CODE.to_string())),
                     source: Some("rholang-parser".to_string()),
                     message: msg.clone(),
                     ..Default::default()
@@ -613,6 +646,15 @@ impl SemanticValidator {
         // No-op when interpreter feature is not enabled
         vec![]
     }
+
+    pub fn normalize_parsed<'a>(
+        &self,
+        _ast: rholang_parser::ast::AnnProc<'a>,
+        _parser: &'a rholang_parser::RholangParser<'a>,
+    ) -> Result<NormalizedForm, Vec<Diagnostic>> {
+        // Nothing is normalized when interpreter feature is not enabled
+        Err(vec![])
+    }
 }
 
 #[cfg(test)]
@@ -634,6 +676,7 @@ mod tests {
             "Error message should mention free/unbound variable, got: {:?}",
             diagnostics[0].message
         );
+        assert!(diagnostics.iter().all(|d| d.code.is_some()), "Every diagnostic should have a code");
     }
 
     #[cfg(feature = "interpreter")]
@@ -0,0 +1,303 @@
+//! Runtime settings (`workspace/didChangeConfiguration`)
+//!
+//! Settings come from the initialization options at startup and from
+//! `workspace/didChangeConfiguration` afterwards, either as the `rholang`
+//! section of the notification or, for clients sending `null`, pulled with
+//! `workspace/configuration`. Each change replaces the previous settings and
+//! takes effect without restarting the server:
+//!
+//! ```json
+//! {
+//!   "rholang": {
+//!     "rnode": { "enabled": true, "host": "localhost", "port": 40402 },
+//!     "formatting": { "indentWidth": 4, "braceStyle": "next_line", "maxLineLength": 100 },
+//!     "diagnostics": { "severity": { "duplicate-send": "error", "naming-convention": "off" } },
+//!     "features": { "codeLens": false, "inlayHints": true, "documentLinks": true, "semanticTokens": true },
+//!     "completion": { "costAware": true, "maxItems": 50 }
+//!   }
+//! }
+//! ```
+//!
+//! Unset RNode fields leave validation as it is; unset formatting fields fall
+//! back to `.rholangrc.toml`. Severity overrides are keyed by diagnostic code,
+//! stable (`RHO101`) or descriptive (`duplicate-send`, see
+//! `crate::lsp::diagnostic_registry`), or, failing that, by diagnostic
+//! source; `off` drops the diagnostic. The
+//! `inlayHints`, `documentLinks`, `costEstimation`, `analysisBudget` and
+//! `costAwareCompletion` options are read again from changed settings too.
+
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
+
+use crate::lsp::diagnostic_registry;
+use crate::lsp::formatting::{BraceStyle, FormatConfig};
+
+/// Configuration section requested with `workspace/configuration`
+pub const SETTINGS_SECTION: &str = "rholang";
+
+/// The server settings a client may change at runtime
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    pub rnode: RnodeSettings,
+    pub formatting: FormattingSettings,
+    pub diagnostics: DiagnosticsSettings,
+    pub features: FeatureSettings,
+    pub completion: CompletionSettings,
+}
+
+/// RNode validation settings
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RnodeSettings {
+    pub enabled: Option<bool>,
+    pub host: Option<String>,
+    pub port: Option<u16>,
+}
+
+/// Formatting settings, overriding the `[format]` section of `.rholangrc.toml`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FormattingSettings {
+    pub indent_width: Option<usize>,
+    pub brace_style: Option<BraceStyle>,
+    pub max_line_length: Option<usize>,
+}
+
+/// Severity a diagnostic is published with, or `off` to drop it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SeverityOverride {
+    Error,
+    Warning,
+    Information,
+    Hint,
+    Off,
+}
+
+/// Diagnostic severity overrides
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DiagnosticsSettings {
+    /// Overrides keyed by diagnostic code or source
+    pub severity: BTreeMap<String, SeverityOverride>,
+}
+
+/// Features that can be switched off; all are on unless set to `false`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FeatureSettings {
+    pub code_lens: Option<bool>,
+    pub inlay_hints: Option<bool>,
+    pub document_links: Option<bool>,
+    pub semantic_tokens: Option<bool>,
+}
+
+/// A feature of [`FeatureSettings`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feature {
+    CodeLens,
+    InlayHints,
+    DocumentLinks,
+    SemanticTokens,
+}
+
+/// Completion settings
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CompletionSettings {
+    /// Overrides the `costAwareCompletion` initialization option
+    pub cost_aware: Option<bool>,
+    /// Most completion items returned; all when unset
+    pub max_items: Option<usize>,
+}
+
+impl Settings {
+    /// Reads settings from a `didChangeConfiguration` payload, a
+    /// `workspace/configuration` result or the initialization options,
+    /// with or without the enclosing `rholang` section.
+    pub fn from_value(value: &serde_json::Value) -> Result<Self, String> {
+        let section = value.get(SETTINGS_SECTION).unwrap_or(value);
+        if section.is_null() {
+            return Ok(Self::default());
+        }
+        serde_json::from_value(section.clone()).map_err(|e| e.to_string())
+    }
+}
+
+impl RnodeSettings {
+    /// The validation state these settings ask for, starting from the current
+    /// one, or `None` if they leave it unchanged.
+    pub fn target(&self, enabled: bool, address: &str) -> Option<(bool, String)> {
+        let (current_host, current_port) = address.rsplit_once(':').unwrap_or((address, ""));
+        let host = self.host.as_deref().unwrap_or(current_host);
+        let target_address = match self.port {
+            Some(port) => format!("{}:{}", host, port),
+            None if current_port.is_empty() => host.to_string(),
+            None => format!("{}:{}", host, current_port),
+        };
+        let target_enabled = self.enabled.unwrap_or(enabled);
+        (target_enabled != enabled || (target_enabled && target_address != address))
+            .then_some((target_enabled, target_address))
+    }
+}
+
+impl FormattingSettings {
+    /// `config` with the fields set here replaced.
+    pub fn apply_to(&self, config: FormatConfig) -> FormatConfig {
+        FormatConfig {
+            indent_width: self.indent_width.or(config.indent_width),
+            brace_style: self.brace_style.unwrap_or(config.brace_style),
+            max_line_length: self.max_line_length.or(config.max_line_length),
+        }
+    }
+}
+
+impl DiagnosticsSettings {
+    fn override_for(&self, diagnostic: &Diagnostic) -> Option<SeverityOverride> {
+        let code = match &diagnostic.code {
+            Some(NumberOrString::String(code)) => Some(code.clone()),
+            Some(NumberOrString::Number(code)) => Some(code.to_string()),
+            None => None,
+        };
+        let by_code = code.and_then(|code| match diagnostic_registry::lookup(&code) {
+            // Registered diagnostics answer to their stable and descriptive codes
+            Some(kind) => self.severity.get(kind.code).or_else(|| self.severity.get(kind.name)),
+            None => self.severity.get(&code),
+        });
+        by_code
+            .or_else(|| diagnostic.source.as_ref().and_then(|source| self.severity.get(source)))
+            .copied()
+    }
+
+    /// Applies the severity overrides, dropping diagnostics switched off.
+    pub fn apply(&self, diagnostics: &mut Vec<Diagnostic>) {
+        if self.severity.is_empty() {
+            return;
+        }
+        diagnostics.retain_mut(|diagnostic| {
+            let severity = match self.override_for(diagnostic) {
+                None => return true,
+                Some(SeverityOverride::Off) => return false,
+                Some(SeverityOverride::Error) => DiagnosticSeverity::ERROR,
+                Some(SeverityOverride::Warning) => DiagnosticSeverity::WARNING,
+                Some(SeverityOverride::Information) => DiagnosticSeverity::INFORMATION,
+                Some(SeverityOverride::Hint) => DiagnosticSeverity::HINT,
+            };
+            diagnostic.severity = Some(severity);
+            true
+        });
+    }
+}
+
+impl FeatureSettings {
+    pub fn is_enabled(&self, feature: Feature) -> bool {
+        let setting = match feature {
+            Feature::CodeLens => self.code_lens,
+            Feature::InlayHints => self.inlay_hints,
+            Feature::DocumentLinks => self.document_links,
+            Feature::SemanticTokens => self.semantic_tokens,
+        };
+        setting.unwrap_or(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_from_value() {
+        let settings = Settings::from_value(&json!({
+            "rholang": {
+                "rnode": { "port": 40403 },
+                "formatting": { "braceStyle": "next_line" },
+                "diagnostics": { "severity": { "duplicate-send": "off" } },
+                "features": { "codeLens": false },
+                "completion": { "maxItems": 20 }
+            }
+        }))
+        .unwrap();
+        assert_eq!(settings.rnode.port, Some(40403));
+        assert_eq!(settings.formatting.brace_style, Some(BraceStyle::NextLine));
+        assert_eq!(settings.diagnostics.severity.get("duplicate-send"), Some(&SeverityOverride::Off));
+        assert!(!settings.features.is_enabled(Feature::CodeLens));
+        assert!(settings.features.is_enabled(Feature::InlayHints));
+        assert_eq!(settings.completion.max_items, Some(20));
+
+        // Without the section, as in the initialization options, and empty
+        assert_eq!(Settings::from_value(&json!({ "completion": { "costAware": false } })).unwrap().completion.cost_aware, Some(false));
+        assert_eq!(Settings::from_value(&serde_json::Value::Null), Ok(Settings::default()));
+        assert!(Settings::from_value(&json!({ "diagnostics": { "severity": { "x": "loud" } } })).is_err());
+    }
+
+    #[test]
+    fn test_rnode_target() {
+        let unset = RnodeSettings::default();
+        assert_eq!(unset.target(true, "localhost:40402"), None);
+
+        let port = RnodeSettings { port: Some(40403), ..Default::default() };
+        assert_eq!(port.target(true, "localhost:40402"), Some((true, "localhost:40403".to_string())));
+        // A new address for a disabled validator is remembered only once it is enabled
+        assert_eq!(port.target(false, "localhost:40402"), None);
+
+        let on = RnodeSettings { enabled: Some(true), host: Some("node".to_string()), port: None };
+        assert_eq!(on.target(false, "localhost:40402"), Some((true, "node:40402".to_string())));
+        let off = RnodeSettings { enabled: Some(false), ..Default::default() };
+        assert_eq!(off.target(true, "localhost:40402"), Some((false, "localhost:40402".to_string())));
+    }
+
+    #[test]
+    fn test_formatting_overrides() {
+        let rc = FormatConfig { indent_width: Some(2), brace_style: BraceStyle::NextLine, max_line_length: Some(80) };
+        let settings = FormattingSettings { indent_width: Some(4), brace_style: None, max_line_length: None };
+        assert_eq!(
+            settings.apply_to(rc),
+            FormatConfig { indent_width: Some(4), brace_style: BraceStyle::NextLine, max_line_length: Some(80) }
+        );
+    }
+
+    #[test]
+    fn test_severity_overrides() {
+        let diagnostic = |code: &str, source: &str| Diagnostic {
+            code: Some(NumberOrString::String(code.to_string())),
+            source: Some(source.to_string()),
+            severity: Some(DiagnosticSeverity::WARNING),
+            ..Default::default()
+        };
+        let settings = DiagnosticsSettings {
+            severity: BTreeMap::from([
+                ("duplicate-send".to_string(), SeverityOverride::Hint),
+                ("rholang-lint".to_string(), SeverityOverride::Off),
+            ]),
+        };
+        let mut diagnostics = vec![
+            diagnostic("duplicate-send", "rholang-lint"),
+            diagnostic("naming-convention", "rholang-lint"),
+            diagnostic("phlo-cost", "rholang-rnode"),
+        ];
+        settings.apply(&mut diagnostics);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::HINT));
+        assert_eq!(diagnostics[1].severity, Some(DiagnosticSeverity::WARNING));
+
+        // Stable codes and descriptive codes name the same diagnostics
+        let settings = DiagnosticsSettings {
+            severity: BTreeMap::from([
+                ("RHO103".to_string(), SeverityOverride::Error),
+                ("naming-convention".to_string(), SeverityOverride::Off),
+            ]),
+        };
+        let mut diagnostics = vec![
+            diagnostic("phlo-cost", "rholang-rnode"),
+            diagnostic("RHO201", "rholang-style"),
+            diagnostic("RHO103", "rholang-rnode"),
+        ];
+        settings.apply(&mut diagnostics);
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics.iter().all(|d| d.severity == Some(DiagnosticSeverity::ERROR)));
+    }
+}
@@ -0,0 +1,272 @@
+//! Documents open in more than one connection
+//!
+//! In multi-connection modes (`--socket`, `--websocket`, `--pipe`) every
+//! connection has its own backend and its own copy of each open document, so
+//! two clients may open and edit the same URI. [`SharedDocuments`] is shared
+//! by all connections and tracks, per URI, which connections hold the
+//! document and which of them last wrote to it (the *writer*).
+//!
+//! An edit from a connection other than the writer is arbitrated by the
+//! [`ConflictPolicy`] chosen with `--open-conflict`:
+//!
+//! - `last-writer-wins` (default): the edit is applied and its connection
+//!   becomes the writer; the other holders get a `rholang/documentConflict`
+//!   notification telling them their copy is superseded;
+//! - `reject`: the edit is not applied and its connection gets an error
+//!   message and a `rholang/documentConflict` notification. Ownership passes
+//!   on once the writer closes the document.
+//!
+//! Diagnostics computed by the writer are also published to every other
+//! connection holding the document, without a version since version numbers
+//! are per client.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::notification::Notification;
+use tower_lsp::lsp_types::Url;
+
+/// Identifies a client connection within the process
+pub type ConnectionId = u64;
+
+/// How edits to a document held by several connections are arbitrated
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConflictPolicy {
+    /// Apply every edit; the editing connection becomes the writer
+    #[default]
+    LastWriterWins,
+    /// Refuse edits from connections other than the writer
+    Reject,
+}
+
+impl ConflictPolicy {
+    /// Values accepted by `--open-conflict`
+    pub const NAMES: [&'static str; 2] = ["last-writer-wins", "reject"];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "last-writer-wins" => Some(ConflictPolicy::LastWriterWins),
+            "reject" => Some(ConflictPolicy::Reject),
+            _ => None,
+        }
+    }
+}
+
+/// How a conflicting edit was resolved
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConflictResolution {
+    /// Another connection edited the document; this copy is out of date
+    Superseded,
+    /// This connection's edit was refused
+    Rejected,
+}
+
+/// Parameters of the `rholang/documentConflict` notification
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentConflictParams {
+    pub uri: Url,
+    /// Version of the edit in the numbering of the connection that sent it
+    pub version: i32,
+    pub resolution: ConflictResolution,
+    pub policy: ConflictPolicy,
+}
+
+/// `rholang/documentConflict`
+pub enum DocumentConflict {}
+
+impl Notification for DocumentConflict {
+    type Params = DocumentConflictParams;
+    const METHOD: &'static str = "rholang/documentConflict";
+}
+
+/// Outcome of an edit to a document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EditArbitration<H> {
+    /// The edit applies; `superseded` are the other holders when the writer changed
+    Accepted { superseded: Vec<H> },
+    /// The edit is refused because `writer` owns the document
+    Rejected { writer: ConnectionId },
+}
+
+#[derive(Debug)]
+struct SharedDocument<H> {
+    writer: Option<ConnectionId>,
+    holders: BTreeMap<ConnectionId, H>,
+}
+
+/// Open documents of all connections, by URI
+///
+/// `H` is what is needed to reach a connection (its LSP client).
+#[derive(Debug)]
+pub struct DocumentOwners<H> {
+    policy: ConflictPolicy,
+    next_connection: Arc<AtomicU64>,
+    documents: Arc<Mutex<HashMap<Url, SharedDocument<H>>>>,
+}
+
+/// Open documents of all connections, reached through their LSP clients
+pub type SharedDocuments = DocumentOwners<tower_lsp::Client>;
+
+impl<H> Clone for DocumentOwners<H> {
+    fn clone(&self) -> Self {
+        DocumentOwners {
+            policy: self.policy,
+            next_connection: self.next_connection.clone(),
+            documents: self.documents.clone(),
+        }
+    }
+}
+
+impl<H> Default for DocumentOwners<H> {
+    fn default() -> Self {
+        Self::new(ConflictPolicy::default())
+    }
+}
+
+impl<H: Clone> DocumentOwners<H> {
+    pub fn new(policy: ConflictPolicy) -> Self {
+        DocumentOwners {
+            policy,
+            next_connection: Arc::new(AtomicU64::new(1)),
+            documents: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn policy(&self) -> ConflictPolicy {
+        self.policy
+    }
+
+    /// A new connection id.
+    pub fn connect(&self) -> ConnectionId {
+        self.next_connection.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Records that `connection` opened `uri`; it becomes the writer when there is none.
+    ///
+    /// Returns whether another connection already holds the document.
+    pub fn open(&self, connection: ConnectionId, uri: &Url, holder: H) -> bool {
+        let mut documents = self.documents.lock();
+        let document = documents
+            .entry(uri.clone())
+            .or_insert_with(|| SharedDocument { writer: None, holders: BTreeMap::new() });
+        document.holders.insert(connection, holder);
+        document.writer.get_or_insert(connection);
+        document.holders.len() > 1
+    }
+
+    /// Arbitrates an edit of `uri` by `connection`.
+    pub fn edit(&self, connection: ConnectionId, uri: &Url) -> EditArbitration<H> {
+        let mut documents = self.documents.lock();
+        let Some(document) = documents.get_mut(uri) else {
+            return EditArbitration::Accepted { superseded: Vec::new() };
+        };
+        match document.writer {
+            Some(writer) if writer == connection => EditArbitration::Accepted { superseded: Vec::new() },
+            Some(writer) if self.policy == ConflictPolicy::Reject => EditArbitration::Rejected { writer },
+            _ => {
+                document.writer = Some(connection);
+                EditArbitration::Accepted { superseded: others(document, connection) }
+            }
+        }
+    }
+
+    /// The connections other than `connection` that hold `uri`, when
+    /// `connection` is its writer.
+    pub fn followers(&self, connection: ConnectionId, uri: &Url) -> Vec<H> {
+        let documents = self.documents.lock();
+        match documents.get(uri) {
+            Some(document) if document.writer == Some(connection) => others(document, connection),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Records that `connection` closed `uri`.
+    pub fn close(&self, connection: ConnectionId, uri: &Url) {
+        let mut documents = self.documents.lock();
+        if let Some(document) = documents.get_mut(uri) {
+            release(document, connection);
+            if document.holders.is_empty() {
+                documents.remove(uri);
+            }
+        }
+    }
+
+    /// Records that `connection` ended, closing all its documents.
+    pub fn disconnect(&self, connection: ConnectionId) {
+        let mut documents = self.documents.lock();
+        documents.retain(|_, document| {
+            release(document, connection);
+            !document.holders.is_empty()
+        });
+    }
+}
+
+fn others<H: Clone>(document: &SharedDocument<H>, connection: ConnectionId) -> Vec<H> {
+    document
+        .holders
+        .iter()
+        .filter(|(id, _)| **id != connection)
+        .map(|(_, holder)| holder.clone())
+        .collect()
+}
+
+fn release<H>(document: &mut SharedDocument<H>, connection: ConnectionId) {
+    document.holders.remove(&connection);
+    if document.writer == Some(connection) {
+        document.writer = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uri() -> Url {
+        Url::parse("file:///shared.rho").unwrap()
+    }
+
+    #[test]
+    fn test_last_writer_wins() {
+        let owners = DocumentOwners::<&str>::new(ConflictPolicy::LastWriterWins);
+        let (a, b) = (owners.connect(), owners.connect());
+        assert!(!owners.open(a, &uri(), "a"));
+        assert!(owners.open(b, &uri(), "b"));
+
+        assert_eq!(owners.edit(a, &uri()), EditArbitration::Accepted { superseded: vec![] });
+        assert_eq!(owners.followers(a, &uri()), vec!["b"]);
+        assert_eq!(owners.edit(b, &uri()), EditArbitration::Accepted { superseded: vec!["a"] });
+        assert!(owners.followers(a, &uri()).is_empty());
+        assert_eq!(owners.followers(b, &uri()), vec!["a"]);
+    }
+
+    #[test]
+    fn test_reject_until_the_writer_closes() {
+        let owners = DocumentOwners::<&str>::new(ConflictPolicy::Reject);
+        let (a, b) = (owners.connect(), owners.connect());
+        owners.open(a, &uri(), "a");
+        owners.open(b, &uri(), "b");
+
+        assert_eq!(owners.edit(b, &uri()), EditArbitration::Rejected { writer: a });
+        owners.close(a, &uri());
+        assert_eq!(owners.edit(b, &uri()), EditArbitration::Accepted { superseded: vec![] });
+
+        owners.disconnect(b);
+        assert_eq!(owners.edit(a, &uri()), EditArbitration::Accepted { superseded: vec![] });
+        assert!(!owners.open(a, &uri(), "a"));
+    }
+
+    #[test]
+    fn test_policy_names() {
+        for name in ConflictPolicy::NAMES {
+            let policy = ConflictPolicy::parse(name).unwrap();
+            assert_eq!(serde_json::to_value(policy).unwrap(), name);
+        }
+        assert_eq!(ConflictPolicy::parse("first-writer-wins"), None);
+    }
+}
@@ -0,0 +1,431 @@
+//! Structural search over the IR for the `rholang/query` custom request
+//!
+//! A query names a kind of construct and a pattern for it, written in
+//! Rholang itself:
+//!
+//! | Query                             | Finds                                            |
+//! |-----------------------------------|--------------------------------------------------|
+//! | `send log`                        | sends on the name `log`                          |
+//! | `send @"rho:io:stdout" with 2 args` | sends on that channel with exactly 2 arguments |
+//! | `send ack(true, _)`               | sends on `ack` whose arguments match `true, _`   |
+//! | `contract transfer`               | contracts named `transfer`                       |
+//! | `contract _(@from, @to, ret)`     | contracts with formals matching the pattern      |
+//! | `receive ret`                     | `for` comprehensions listening on `ret`          |
+//!
+//! A channel written as a bare name matches that name only, and `_` matches
+//! any channel. Arguments and formals are Rholang patterns checked with
+//! [`match_pat`]: `_` matches anything, a variable matches anything and must
+//! match the same thing everywhere it appears, literals and collections match
+//! structurally. Sends also cover synchronous sends (`!?`).
+//!
+//! # Request
+//!
+//! ```json
+//! { "query": "send ack(true, _)", "uri": "file:///path/to/file.rho" }   // omit `uri` for the whole workspace
+//! ```
+//!
+//! # Response
+//!
+//! ```json
+//! { "matches": [ { "uri": "file:///…", "range": { "start": …, "end": … },
+//!                  "bindings": { "x": "42" } } ],
+//!   "truncated": false }
+//! ```
+//!
+//! `bindings` holds the source text each pattern variable matched.
+//! At most [`MAX_QUERY_MATCHES`] matches are returned.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range as ByteRange;
+use std::sync::Arc;
+
+use ropey::Rope;
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{Range, Url};
+
+use crate::ir::formatter::format_node;
+use crate::ir::rholang_node::{
+    match_pat, Metadata, NodeBase, Position as IrPosition, RholangNode, RholangNodeVector, RholangReceiptVector,
+    RholangSendType,
+};
+use crate::ir::visitor::Visitor;
+use crate::lsp::line_index::{lsp_range, LineIndex};
+use crate::tree_sitter::{parse_code, parse_to_document_ir};
+
+/// Custom request method name
+pub const QUERY_METHOD: &str = "rholang/query";
+
+/// Largest number of matches returned for one query
+pub const MAX_QUERY_MATCHES: usize = 1000;
+
+/// Parameters of `rholang/query`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryParams {
+    pub query: String,
+    /// Document to search; the whole workspace when omitted
+    #[serde(default)]
+    pub uri: Option<Url>,
+}
+
+/// One construct matching a query
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryMatch {
+    pub uri: Url,
+    pub range: Range,
+    /// Pattern variables and the source of what they matched
+    pub bindings: BTreeMap<String, String>,
+    /// Byte offsets of the match in the document text
+    #[serde(skip)]
+    pub bytes: ByteRange<usize>,
+}
+
+/// Response of `rholang/query`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryResult {
+    pub matches: Vec<QueryMatch>,
+    /// Whether matches were left out past [`MAX_QUERY_MATCHES`]
+    pub truncated: bool,
+}
+
+/// Constructs a query can look for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryKind {
+    Send,
+    Contract,
+    Receive,
+}
+
+/// A parsed query
+#[derive(Debug, Clone)]
+pub struct StructuralQuery {
+    pub kind: QueryKind,
+    /// Channel or contract name; `None` matches any
+    channel: Option<Arc<RholangNode>>,
+    /// Argument or formal patterns, when given
+    arguments: Option<Vec<Arc<RholangNode>>>,
+    /// Required number of arguments, from `with N args`
+    arity: Option<usize>,
+}
+
+impl StructuralQuery {
+    /// Parses a query such as `send ack(true, _)` or `contract transfer`.
+    pub fn parse(query: &str) -> Result<Self, String> {
+        let query = query.trim();
+        let (kind, rest) = match query.split_once(char::is_whitespace) {
+            Some(("send", rest)) => (QueryKind::Send, rest.trim()),
+            Some(("contract", rest)) => (QueryKind::Contract, rest.trim()),
+            Some(("receive", rest)) => (QueryKind::Receive, rest.trim()),
+            _ => return Err(format!("Expected `send`, `contract` or `receive` followed by a pattern in {:?}", query)),
+        };
+
+        let (rest, arity) = match rest.rsplit_once(" with ") {
+            Some((rest, count)) => {
+                let count = count.trim().trim_end_matches("args").trim_end_matches("arg").trim();
+                let arity = count.parse().map_err(|_| format!("Expected a number of arguments, got {:?}", count))?;
+                (rest.trim(), Some(arity))
+            }
+            None => (rest, None),
+        };
+
+        let (channel, arguments) = match rest.find('(') {
+            Some(open) if rest.ends_with(')') => (rest[..open].trim(), Some(&rest[open + 1..rest.len() - 1])),
+            Some(_) => return Err(format!("Unbalanced parentheses in {:?}", rest)),
+            None => (rest, None),
+        };
+        if channel.is_empty() {
+            return Err("Missing channel or contract name".to_string());
+        }
+        if arguments.is_some() && arity.is_some() {
+            return Err("Give either an argument pattern or `with N args`, not both".to_string());
+        }
+        if kind == QueryKind::Receive && (arguments.is_some() || arity.is_some()) {
+            return Err("`receive` queries take a channel only".to_string());
+        }
+
+        // `_` is not a name Rholang accepts in these positions; any name will do
+        // since the parsed channel is then ignored
+        let name = if channel == "_" { "any" } else { channel };
+        let snippet = match kind {
+            QueryKind::Send => format!("{}!({})", name, arguments.unwrap_or("")),
+            QueryKind::Contract => format!("contract {}({}) = {{ Nil }}", name, arguments.unwrap_or("")),
+            QueryKind::Receive => format!("for (_ <- {}) {{ Nil }}", name),
+        };
+        let parsed = parse_snippet(&snippet, kind)?;
+
+        let (parsed_channel, parsed_arguments) = match &*parsed {
+            RholangNode::Send { channel, inputs, .. } => (channel.clone(), inputs.iter().cloned().collect()),
+            RholangNode::Contract { name, formals, .. } => (name.clone(), formals.iter().cloned().collect()),
+            RholangNode::Input { receipts, .. } => match receipts.iter().flatten().next().map(|bind| &**bind) {
+                Some(RholangNode::LinearBind { source, .. }) => (source.clone(), Vec::new()),
+                _ => return Err(format!("Could not read the channel of {:?}", snippet)),
+            },
+            _ => return Err(format!("Could not read {:?}", snippet)),
+        };
+
+        Ok(StructuralQuery {
+            kind,
+            channel: (channel != "_").then_some(parsed_channel),
+            arguments: arguments.map(|_| parsed_arguments),
+            arity,
+        })
+    }
+
+    /// Whether a construct with this channel (or name) and these arguments
+    /// (or formals) matches, with the bindings of the pattern variables.
+    fn matches(
+        &self,
+        channel: &Arc<RholangNode>,
+        arguments: &RholangNodeVector,
+    ) -> Option<HashMap<String, Arc<RholangNode>>> {
+        let mut bindings = HashMap::new();
+        if let Some(pattern) = &self.channel {
+            let matched = match (&**pattern, &**channel) {
+                (RholangNode::Var { name: p, .. }, RholangNode::Var { name: c, .. }) => p == c,
+                (RholangNode::Var { .. }, _) => false,
+                _ => match_pat(pattern, channel, &mut bindings),
+            };
+            if !matched {
+                return None;
+            }
+        }
+        if self.arity.is_some_and(|arity| arity != arguments.len()) {
+            return None;
+        }
+        if let Some(patterns) = &self.arguments {
+            if patterns.len() != arguments.len()
+                || !patterns.iter().zip(arguments.iter()).all(|(p, a)| match_pat(p, a, &mut bindings))
+            {
+                return None;
+            }
+        }
+        Some(bindings)
+    }
+}
+
+/// The first construct of `kind` in `snippet`.
+fn parse_snippet(snippet: &str, kind: QueryKind) -> Result<Arc<RholangNode>, String> {
+    let tree = parse_code(snippet);
+    if tree.root_node().has_error() {
+        return Err(format!("Not a valid pattern: {:?}", snippet));
+    }
+    let ir = parse_to_document_ir(&tree, &Rope::from_str(snippet)).root.clone();
+    let finder = QueryFinder {
+        query: &StructuralQuery { kind, channel: None, arguments: None, arity: None },
+        uri: None,
+        positions: &HashMap::new(),
+        text: &Rope::new(),
+        line_index: &LineIndex::new(""),
+        found: RefCell::new(Vec::new()),
+        nodes: RefCell::new(Vec::new()),
+    };
+    finder.visit_node(&ir);
+    finder.nodes.into_inner().into_iter().next().ok_or_else(|| format!("Could not read {:?}", snippet))
+}
+
+/// Matches of `query` in one document with text `text`, in document order.
+pub fn find_matches(
+    query: &StructuralQuery,
+    uri: &Url,
+    ir: &Arc<RholangNode>,
+    positions: &HashMap<usize, (IrPosition, IrPosition)>,
+    text: &Rope,
+    line_index: &LineIndex,
+) -> Vec<QueryMatch> {
+    let finder = QueryFinder {
+        query,
+        uri: Some(uri),
+        positions,
+        text,
+        line_index,
+        found: RefCell::new(Vec::new()),
+        nodes: RefCell::new(Vec::new()),
+    };
+    finder.visit_node(ir);
+    let mut found = finder.found.into_inner();
+    found.sort_by_key(|m| (m.range.start.line, m.range.start.character));
+    found
+}
+
+struct QueryFinder<'a> {
+    query: &'a StructuralQuery,
+    /// `None` while reading a query snippet, which collects `nodes` instead
+    uri: Option<&'a Url>,
+    positions: &'a HashMap<usize, (IrPosition, IrPosition)>,
+    text: &'a Rope,
+    line_index: &'a LineIndex,
+    found: RefCell<Vec<QueryMatch>>,
+    nodes: RefCell<Vec<Arc<RholangNode>>>,
+}
+
+impl QueryFinder<'_> {
+    fn check(&self, node: &Arc<RholangNode>, kind: QueryKind, channel: &Arc<RholangNode>, arguments: &RholangNodeVector) {
+        if kind != self.query.kind {
+            return;
+        }
+        let Some(uri) = self.uri else {
+            self.nodes.borrow_mut().push(node.clone());
+            return;
+        };
+        let Some(bindings) = self.query.matches(channel, arguments) else { return };
+        let Some(span) = self.positions.get(&(Arc::as_ptr(node) as usize)) else { return };
+        self.found.borrow_mut().push(QueryMatch {
+            uri: uri.clone(),
+            range: lsp_range(self.text, self.line_index, span),
+            bindings: bindings.iter().map(|(name, value)| (name.clone(), self.source_of(value))).collect(),
+            bytes: span.0.byte..span.1.byte,
+        });
+    }
+
+    /// Source text of `node`, or its formatted form if it has no position.
+    fn source_of(&self, node: &Arc<RholangNode>) -> String {
+        match self.positions.get(&(Arc::as_ptr(node) as usize)) {
+            Some((start, end)) if end.byte <= self.text.len_bytes() => {
+                self.text.byte_slice(start.byte..end.byte).to_string()
+            }
+            _ => format_node(node, false, None, self.text, node),
+        }
+    }
+}
+
+impl Visitor for QueryFinder<'_> {
+    fn visit_send(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        channel: &Arc<RholangNode>,
+        _send_type: &RholangSendType,
+        _send_type_pos: &IrPosition,
+        inputs: &RholangNodeVector,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.check(node, QueryKind::Send, channel, inputs);
+        self.visit_node(channel);
+        for input in inputs.iter() {
+            self.visit_node(input);
+        }
+        Arc::clone(node)
+    }
+
+    fn visit_send_sync(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        channel: &Arc<RholangNode>,
+        inputs: &RholangNodeVector,
+        cont: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.check(node, QueryKind::Send, channel, inputs);
+        self.visit_node(channel);
+        for input in inputs.iter() {
+            self.visit_node(input);
+        }
+        self.visit_node(cont);
+        Arc::clone(node)
+    }
+
+    fn visit_contract(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        name: &Arc<RholangNode>,
+        formals: &RholangNodeVector,
+        _formals_remainder: &Option<Arc<RholangNode>>,
+        proc: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.check(node, QueryKind::Contract, name, formals);
+        self.visit_node(proc);
+        Arc::clone(node)
+    }
+
+    fn visit_input(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        receipts: &RholangReceiptVector,
+        proc: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        for bind in receipts.iter().flatten() {
+            if let RholangNode::LinearBind { source, .. } | RholangNode::RepeatedBind { source, .. }
+            | RholangNode::PeekBind { source, .. } = &**bind
+            {
+                self.check(node, QueryKind::Receive, source, &RholangNodeVector::new_with_ptr_kind());
+            }
+        }
+        self.visit_node(proc);
+        Arc::clone(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree_sitter::parse_with_positions;
+
+    fn query(query: &str, source: &str) -> Vec<(u32, BTreeMap<String, String>)> {
+        let uri = Url::parse("file:///query.rho").unwrap();
+        let (ir, positions) = parse_with_positions(source);
+        let query = StructuralQuery::parse(query).unwrap();
+        find_matches(&query, &uri, &ir, &positions, &Rope::from_str(source), &LineIndex::new(source)).into_iter().map(|m| (m.range.start.line, m.bindings)).collect()
+    }
+
+    const SOURCE: &str = r#"new ack, log, ret in {
+  ack!(true, 1) |
+  ack!(false, 2) |
+  log!("a", "b", "c") |
+  @"rho:io:stdout"!("hi") |
+  contract transfer(@from, @to, ret) = { ret!(from) } |
+  for (x <- ret) { log!(x) }
+}"#;
+
+    #[test]
+    fn test_send_queries() {
+        assert_eq!(query("send ack", SOURCE).len(), 2);
+        assert_eq!(query("send log with 3 args", SOURCE).len(), 1);
+        assert_eq!(query("send log with 1 arg", SOURCE).iter().map(|m| m.0).collect::<Vec<_>>(), vec![6]);
+        assert_eq!(query("send @\"rho:io:stdout\"", SOURCE).len(), 1);
+
+        let matches = query("send ack(true, n)", SOURCE);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].0, 1);
+        assert_eq!(matches[0].1.get("n").map(String::as_str), Some("1"));
+
+        // Any channel
+        assert_eq!(query("send _ with 1 arg", SOURCE).len(), 3);
+    }
+
+    #[test]
+    fn test_contract_and_receive_queries() {
+        assert_eq!(query("contract transfer", SOURCE).len(), 1);
+        assert_eq!(query("contract _(@a, @b, r)", SOURCE).len(), 1);
+        assert!(query("contract _(@a)", SOURCE).is_empty());
+        assert_eq!(query("receive ret", SOURCE).iter().map(|m| m.0).collect::<Vec<_>>(), vec![6]);
+    }
+
+    #[test]
+    fn test_invalid_queries() {
+        assert!(StructuralQuery::parse("find ack").is_err());
+        assert!(StructuralQuery::parse("send ack(1").is_err());
+        assert!(StructuralQuery::parse("send ack with many args").is_err());
+        assert!(StructuralQuery::parse("receive ret(x)").is_err());
+    }
+
+    #[test]
+    fn test_match_ranges_count_utf16_units() {
+        let source = r#"new log in { log!("é") | log!("🦀") }"#;
+        let uri = Url::parse("file:///query.rho").unwrap();
+        let rope = Rope::from_str(source);
+        let (ir, positions) = parse_with_positions(source);
+        let query = StructuralQuery::parse("send log").unwrap();
+        let matches = find_matches(&query, &uri, &ir, &positions, &rope, &LineIndex::new(source));
+        let characters: Vec<_> = matches.iter().map(|m| (m.range.start.character, m.range.end.character)).collect();
+        assert_eq!(characters, vec![(13, 22), (25, 35)]);
+        assert_eq!(matches[1].bytes, 26..38);
+    }
+}
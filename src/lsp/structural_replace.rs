@@ -0,0 +1,274 @@
+//! Structural search and replace
+//!
+//! The `rholang.structuralReplace` command rewrites every construct matching
+//! a [`rholang/query`](crate::lsp::structural_query) pattern with a template,
+//! in one document or across the workspace:
+//!
+//! ```json
+//! { "command": "rholang.structuralReplace",
+//!   "arguments": [{ "query": "send log(msg)", "template": "stdout!(msg)", "uri": null, "apply": false }] }
+//! ```
+//!
+//! Pattern variables in the template are replaced by the source text they
+//! matched; everything else is copied as written. Identifiers inside string
+//! literals, URIs and comments are never substituted.
+//!
+//! The command is guarded so that mechanical rewrites stay safe:
+//!
+//! - the template must itself parse as a Rholang process;
+//! - matches nested inside another match are left alone, since the outer
+//!   replacement already covers their text;
+//! - a document whose rewritten text no longer parses is left unchanged and
+//!   reported as rejected;
+//! - every edit carries a change annotation that needs confirmation, and the
+//!   edits are tied to the document versions they were computed from.
+//!
+//! Without `apply` the command only returns the preview [`WorkspaceEdit`] in
+//! its [`StructuralReplaceReport`]; with `apply: true` the server also asks
+//! the client to apply it.
+
+use std::collections::{BTreeMap, HashMap};
+
+use ropey::Rope;
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{
+    AnnotatedTextEdit, ChangeAnnotation, DocumentChanges, OneOf, OptionalVersionedTextDocumentIdentifier,
+    TextDocumentEdit, TextEdit, Url, WorkspaceEdit,
+};
+
+use crate::lsp::structural_query::QueryMatch;
+use crate::tree_sitter::parse_code;
+
+/// Rewrites the constructs matching a structural query
+pub const STRUCTURAL_REPLACE_COMMAND: &str = "rholang.structuralReplace";
+
+/// Change annotation attached to every replacement
+pub const STRUCTURAL_REPLACE_ANNOTATION: &str = "rholang.structuralReplace";
+
+/// Arguments of `rholang.structuralReplace`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructuralReplaceParams {
+    /// Pattern in the `rholang/query` language
+    pub query: String,
+    /// Replacement, with pattern variables standing for what they matched
+    pub template: String,
+    /// Document to rewrite; the whole workspace when omitted
+    #[serde(default)]
+    pub uri: Option<Url>,
+    /// Ask the client to apply the edit rather than only preview it
+    #[serde(default)]
+    pub apply: bool,
+}
+
+/// A document left unchanged
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RejectedDocument {
+    pub uri: Url,
+    pub reason: String,
+}
+
+/// Result of `rholang.structuralReplace`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructuralReplaceReport {
+    pub edit: WorkspaceEdit,
+    pub replacements: usize,
+    /// Matches inside another match, left to the outer replacement
+    pub skipped_nested: usize,
+    pub rejected: Vec<RejectedDocument>,
+    /// Whether the client applied the edit
+    pub applied: bool,
+}
+
+/// Checks that `template` is a Rholang process.
+pub fn check_template(template: &str) -> Result<(), String> {
+    if template.trim().is_empty() || parse_code(template).root_node().has_error() {
+        return Err(format!("The template is not a valid Rholang process: {:?}", template));
+    }
+    Ok(())
+}
+
+/// `template` with the identifiers bound in `bindings` replaced by their text.
+pub fn instantiate(template: &str, bindings: &BTreeMap<String, String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut chars = template.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        match c {
+            '"' | '`' => {
+                // Copy literals verbatim, escapes included
+                out.push(c);
+                let mut escaped = false;
+                for (_, next) in chars.by_ref() {
+                    out.push(next);
+                    if escaped {
+                        escaped = false;
+                    } else if next == '\\' {
+                        escaped = true;
+                    } else if next == c {
+                        break;
+                    }
+                }
+            }
+            '/' if template[start..].starts_with("//") => {
+                let end = template[start..].find('\n').map_or(template.len(), |newline| start + newline);
+                out.push_str(&template[start..end]);
+                while chars.peek().is_some_and(|&(i, _)| i < end) {
+                    chars.next();
+                }
+            }
+            '/' if template[start..].starts_with("/*") => {
+                let end = template[start + 2..].find("*/").map_or(template.len(), |close| start + 2 + close + 2);
+                out.push_str(&template[start..end]);
+                while chars.peek().is_some_and(|&(i, _)| i < end) {
+                    chars.next();
+                }
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut end = start + c.len_utf8();
+                while let Some(&(i, next)) = chars.peek() {
+                    if !(next.is_alphanumeric() || next == '_' || next == '\'') {
+                        break;
+                    }
+                    end = i + next.len_utf8();
+                    chars.next();
+                }
+                let word = &template[start..end];
+                out.push_str(bindings.get(word).map_or(word, String::as_str));
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// The replacements of `matches` in one document, or why it is left unchanged.
+///
+/// Returns the document edit, the number of replacements and the number of
+/// nested matches skipped.
+pub fn plan_document(
+    uri: &Url,
+    version: Option<i32>,
+    text: &Rope,
+    mut matches: Vec<QueryMatch>,
+    template: &str,
+) -> Result<(TextDocumentEdit, usize, usize), String> {
+    let total = matches.len();
+    matches.sort_by_key(|m| (m.bytes.start, std::cmp::Reverse(m.bytes.end)));
+    let mut outermost: Vec<QueryMatch> = Vec::new();
+    for m in matches {
+        if outermost.last().is_some_and(|outer| m.bytes.start < outer.bytes.end) {
+            continue;
+        }
+        outermost.push(m);
+    }
+    let skipped = total - outermost.len();
+
+    let mut rewritten = String::with_capacity(text.len_bytes());
+    let mut copied = 0;
+    let mut edits = Vec::with_capacity(outermost.len());
+    for m in &outermost {
+        let replacement = instantiate(template, &m.bindings);
+        rewritten.push_str(&text.byte_slice(copied..m.bytes.start).to_string());
+        rewritten.push_str(&replacement);
+        copied = m.bytes.end;
+        edits.push(OneOf::Right(AnnotatedTextEdit {
+            text_edit: TextEdit { range: m.range, new_text: replacement },
+            annotation_id: STRUCTURAL_REPLACE_ANNOTATION.to_string(),
+        }));
+    }
+    rewritten.push_str(&text.byte_slice(copied..).to_string());
+
+    if parse_code(&rewritten).root_node().has_error() {
+        return Err("The rewritten document does not parse".to_string());
+    }
+    Ok((
+        TextDocumentEdit {
+            text_document: OptionalVersionedTextDocumentIdentifier { uri: uri.clone(), version },
+            edits,
+        },
+        outermost.len(),
+        skipped,
+    ))
+}
+
+/// The workspace edit made of `edits`, annotated as needing confirmation.
+pub fn annotated_edit(query: &str, edits: Vec<TextDocumentEdit>) -> WorkspaceEdit {
+    let annotation = ChangeAnnotation {
+        label: format!("Replace `{}`", query),
+        needs_confirmation: Some(true),
+        description: Some("Structural replace".to_string()),
+    };
+    WorkspaceEdit {
+        changes: None,
+        document_changes: Some(DocumentChanges::Edits(edits)),
+        change_annotations: Some(HashMap::from([(STRUCTURAL_REPLACE_ANNOTATION.to_string(), annotation)])),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lsp::line_index::LineIndex;
+    use crate::lsp::structural_query::{find_matches, StructuralQuery};
+    use crate::tree_sitter::parse_with_positions;
+
+    fn plan(query: &str, template: &str, source: &str) -> Result<(TextDocumentEdit, usize, usize), String> {
+        let uri = Url::parse("file:///replace.rho").unwrap();
+        let rope = Rope::from_str(source);
+        let (ir, positions) = parse_with_positions(source);
+        let matches = find_matches(&StructuralQuery::parse(query).unwrap(), &uri, &ir, &positions, &rope, &LineIndex::new(source));
+        plan_document(&uri, Some(3), &rope, matches, template)
+    }
+
+    fn new_texts(edit: &TextDocumentEdit) -> Vec<&str> {
+        edit.edits
+            .iter()
+            .map(|edit| match edit {
+                OneOf::Left(edit) => edit.new_text.as_str(),
+                OneOf::Right(edit) => edit.text_edit.new_text.as_str(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_instantiate_skips_literals_and_comments() {
+        let bindings = BTreeMap::from([("msg".to_string(), "\"hi\"".to_string()), ("n".to_string(), "42".to_string())]);
+        assert_eq!(
+            instantiate("out!(msg, \"msg\", n) // msg", &bindings),
+            "out!(\"hi\", \"msg\", 42) // msg"
+        );
+        // Only whole identifiers are replaced
+        assert_eq!(instantiate("msgs!(n1, n)", &bindings), "msgs!(n1, 42)");
+    }
+
+    #[test]
+    fn test_replacements_are_annotated_and_versioned() {
+        let source = "new log, out in {\n  log!(\"a\") |\n  log!(\"b\", 2)\n}";
+        let (edit, replacements, skipped) = plan("send log(msg)", "out!(msg)", source).unwrap();
+        assert_eq!((replacements, skipped), (1, 0));
+        assert_eq!(edit.text_document.version, Some(3));
+        assert_eq!(new_texts(&edit), vec!["out!(\"a\")"]);
+        assert!(matches!(&edit.edits[0], OneOf::Right(e) if e.annotation_id == STRUCTURAL_REPLACE_ANNOTATION));
+
+        let workspace_edit = annotated_edit("send log(msg)", vec![edit]);
+        let annotations = workspace_edit.change_annotations.unwrap();
+        assert_eq!(annotations[STRUCTURAL_REPLACE_ANNOTATION].needs_confirmation, Some(true));
+    }
+
+    #[test]
+    fn test_guards() {
+        assert!(check_template("out!(msg)").is_ok());
+        assert!(check_template("out!(msg").is_err());
+
+        // The inner send is covered by the outer replacement
+        let nested = "new log in { log!(*log!(1)) }";
+        let (_, replacements, skipped) = plan("send log with 1 arg", "Nil", nested).unwrap();
+        assert_eq!((replacements, skipped), (1, 1));
+
+        // The template parses, but not once a string is substituted for a name
+        assert!(plan("send log(msg)", "msg!(1)", "new log, ch in { log!(*ch) }").is_ok());
+        assert!(plan("send log(msg)", "msg!(1)", "new log in { log!(\"a\") }").is_err());
+    }
+}
@@ -0,0 +1,468 @@
+//! Identifier naming-convention lints configured in `.rholangrc.toml`
+//!
+//! The lints are off unless the workspace root contains a `.rholangrc.toml`
+//! selecting a style for some kind of identifier:
+//!
+//! ```toml
+//! [lints.naming]
+//! severity = "warning"                 # error | warning | information | hint
+//! contracts = "camelCase"              # contract transfer(...)
+//! names = "camelCase"                  # new vault in { ... }
+//! uri_names = "SCREAMING_SNAKE_CASE"   # new STDOUT(`rho:io:stdout`) in { ... }
+//! parameters = "snake_case"            # contract transfer(@from_vault, ret)
+//! ```
+//!
+//! Supported styles are `camelCase`, `PascalCase`, `snake_case`,
+//! `SCREAMING_SNAKE_CASE` and `any` (the default, which disables the check).
+//! Quoted names (`@"transfer"`) and names starting with `_` are never linted.
+//!
+//! Each violation becomes a diagnostic carrying the suggested name in its
+//! `data`, which the backend turns into a "Rename" quick fix backed by the
+//! regular rename machinery.
+//!
+//! The same file configures file structure conventions under
+//! `[lints.conventions]` (see `crate::lsp::conventions`) and lint rules
+//! under `[lints.rules]` (see `crate::validators::lint`).
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+use ropey::Rope;
+use serde::Deserialize;
+use serde_json::json;
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Range};
+use tracing::{debug, warn};
+
+use crate::i18n::tr;
+use crate::ir::rholang_node::{Metadata, NodeBase, Position as IrPosition, RholangNode, RholangNodeVector};
+use crate::ir::visitor::Visitor;
+use crate::lsp::conventions::ConventionLintConfig;
+use crate::lsp::line_index::{lsp_range, LineIndex};
+use crate::validators::lint::LintConfig;
+
+/// Name of the workspace configuration file
+pub const CONFIG_FILE_NAME: &str = ".rholangrc.toml";
+/// Diagnostic source for style lints
+pub const STYLE_LINT_SOURCE: &str = "rholang-style";
+/// Diagnostic code for naming-convention violations
+pub const NAMING_LINT_CODE: &str = "naming-convention";
+
+/// A naming convention
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub enum NamingStyle {
+    #[serde(rename = "camelCase")]
+    CamelCase,
+    #[serde(rename = "PascalCase")]
+    PascalCase,
+    #[serde(rename = "snake_case")]
+    SnakeCase,
+    #[serde(rename = "SCREAMING_SNAKE_CASE")]
+    ScreamingSnakeCase,
+    /// No convention enforced
+    #[default]
+    #[serde(rename = "any")]
+    Any,
+}
+
+impl fmt::Display for NamingStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            NamingStyle::CamelCase => "camelCase",
+            NamingStyle::PascalCase => "PascalCase",
+            NamingStyle::SnakeCase => "snake_case",
+            NamingStyle::ScreamingSnakeCase => "SCREAMING_SNAKE_CASE",
+            NamingStyle::Any => "any",
+        })
+    }
+}
+
+impl NamingStyle {
+    /// Rewrites `name` in this style (unchanged for `Any`).
+    pub fn apply(self, name: &str) -> String {
+        let words = split_words(name);
+        match self {
+            NamingStyle::Any => name.to_string(),
+            NamingStyle::CamelCase => words
+                .iter()
+                .enumerate()
+                .map(|(i, word)| if i == 0 { word.clone() } else { capitalize(word) })
+                .collect(),
+            NamingStyle::PascalCase => words.iter().map(|word| capitalize(word)).collect(),
+            NamingStyle::SnakeCase => words.join("_"),
+            NamingStyle::ScreamingSnakeCase => words.join("_").to_uppercase(),
+        }
+    }
+
+    /// Whether `name` already follows this style.
+    pub fn matches(self, name: &str) -> bool {
+        self == NamingStyle::Any || self.apply(name) == name
+    }
+}
+
+/// Severity of style lint diagnostics
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintSeverity {
+    Error,
+    #[default]
+    Warning,
+    Information,
+    Hint,
+}
+
+impl From<LintSeverity> for DiagnosticSeverity {
+    fn from(severity: LintSeverity) -> Self {
+        match severity {
+            LintSeverity::Error => DiagnosticSeverity::ERROR,
+            LintSeverity::Warning => DiagnosticSeverity::WARNING,
+            LintSeverity::Information => DiagnosticSeverity::INFORMATION,
+            LintSeverity::Hint => DiagnosticSeverity::HINT,
+        }
+    }
+}
+
+/// `[lints.naming]` section of `.rholangrc.toml`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct NamingLintConfig {
+    pub severity: LintSeverity,
+    /// Contract names
+    pub contracts: NamingStyle,
+    /// `new` names without a URI
+    pub names: NamingStyle,
+    /// `new` names bound to a system URI
+    pub uri_names: NamingStyle,
+    /// Contract parameters
+    pub parameters: NamingStyle,
+}
+
+impl NamingLintConfig {
+    /// Whether any naming rule is enabled
+    pub fn is_enabled(&self) -> bool {
+        [self.contracts, self.names, self.uri_names, self.parameters]
+            .iter()
+            .any(|style| *style != NamingStyle::Any)
+    }
+
+    fn style_for(&self, kind: IdentifierKind) -> NamingStyle {
+        match kind {
+            IdentifierKind::Contract => self.contracts,
+            IdentifierKind::Name => self.names,
+            IdentifierKind::UriName => self.uri_names,
+            IdentifierKind::Parameter => self.parameters,
+        }
+    }
+}
+
+/// Style lint settings of a workspace
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct StyleLintConfig {
+    pub naming: NamingLintConfig,
+    pub conventions: ConventionLintConfig,
+    pub rules: LintConfig,
+}
+
+/// Top level of `.rholangrc.toml`; unknown sections are ignored
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct RcFile {
+    lints: StyleLintConfig,
+}
+
+impl StyleLintConfig {
+    /// Parses the contents of a `.rholangrc.toml` file.
+    pub fn from_toml_str(contents: &str) -> Result<Self, String> {
+        toml::from_str::<RcFile>(contents)
+            .map(|rc| rc.lints)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Loads `.rholangrc.toml` from the workspace root.
+    ///
+    /// A missing file disables the lints; an invalid one is logged and ignored.
+    pub fn load(root: &Path) -> Self {
+        let path = root.join(CONFIG_FILE_NAME);
+        let Ok(contents) = std::fs::read_to_string(&path) else {
+            debug!("No {} in {}", CONFIG_FILE_NAME, root.display());
+            return Self::default();
+        };
+        match Self::from_toml_str(&contents) {
+            Ok(config) => {
+                debug!("Loaded style lints from {}: {:?}", path.display(), config);
+                config
+            }
+            Err(e) => {
+                warn!("Ignoring invalid {}: {}", path.display(), e);
+                Self::default()
+            }
+        }
+    }
+}
+
+/// Kind of identifier a naming rule applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdentifierKind {
+    Contract,
+    Name,
+    UriName,
+    Parameter,
+}
+
+impl fmt::Display for IdentifierKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&tr(match self {
+            IdentifierKind::Contract => "identifier.contract",
+            IdentifierKind::Name => "identifier.name",
+            IdentifierKind::UriName => "identifier.uri-name",
+            IdentifierKind::Parameter => "identifier.parameter",
+        }, &[]))
+    }
+}
+
+/// An identifier that does not follow its configured style
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamingViolation {
+    pub kind: IdentifierKind,
+    pub name: String,
+    pub expected: NamingStyle,
+    pub suggestion: String,
+    pub range: Range,
+}
+
+impl NamingViolation {
+    /// Diagnostic for the violation; `data` holds `{ name, suggestion }` for the quick fix.
+    pub fn to_diagnostic(&self, severity: LintSeverity) -> Diagnostic {
+        Diagnostic {
+            range: self.range,
+            severity: Some(severity.into()),
+            code: Some(NumberOrString::String(NAMING_LINT_CODE.to_string())),
+            source: Some(STYLE_LINT_SOURCE.to_string()),
+            message: tr(
+                "lint.naming",
+                &[("kind", &self.kind), ("name", &self.name), ("style", &self.expected), ("suggestion", &self.suggestion)],
+            ),
+            data: Some(json!({ "name": self.name, "suggestion": self.suggestion })),
+            ..Default::default()
+        }
+    }
+}
+
+/// Finds identifiers in `ir` that violate the configured naming rules.
+pub fn check_naming(
+    ir: &Arc<RholangNode>,
+    positions: &HashMap<usize, (IrPosition, IrPosition)>,
+    text: &Rope,
+    line_index: &LineIndex,
+    config: &NamingLintConfig,
+) -> Vec<NamingViolation> {
+    if !config.is_enabled() {
+        return Vec::new();
+    }
+    let collector = NamingCollector {
+        config,
+        positions,
+        text,
+        line_index,
+        violations: RefCell::new(Vec::new()),
+    };
+    collector.visit_node(ir);
+    let mut violations = collector.violations.into_inner();
+    violations.sort_by_key(|v| (v.range.start.line, v.range.start.character));
+    violations
+}
+
+/// Splits an identifier into lowercase words on `_` and case boundaries.
+///
+/// Acronyms stay together: `HTTPServer` splits into `http` and `server`.
+fn split_words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    for part in name.split('_').filter(|part| !part.is_empty()) {
+        let chars: Vec<char> = part.chars().collect();
+        let mut current = String::new();
+        for (i, &c) in chars.iter().enumerate() {
+            let boundary = i > 0
+                && c.is_uppercase()
+                && (chars[i - 1].is_lowercase()
+                    || chars[i - 1].is_ascii_digit()
+                    || (chars[i - 1].is_uppercase() && chars.get(i + 1).is_some_and(|n| n.is_lowercase())));
+            if boundary && !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            current.extend(c.to_lowercase());
+        }
+        if !current.is_empty() {
+            words.push(current);
+        }
+    }
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Collects naming violations for declarations and contract parameters
+struct NamingCollector<'a> {
+    config: &'a NamingLintConfig,
+    positions: &'a HashMap<usize, (IrPosition, IrPosition)>,
+    text: &'a Rope,
+    line_index: &'a LineIndex,
+    violations: RefCell<Vec<NamingViolation>>,
+}
+
+impl NamingCollector<'_> {
+    fn check(&self, var: &Arc<RholangNode>, kind: IdentifierKind) {
+        let RholangNode::Var { name, .. } = &**var else { return };
+        let style = self.config.style_for(kind);
+        if name.is_empty() || name.starts_with('_') || style.matches(name) {
+            return;
+        }
+        let Some(span) = self.positions.get(&(Arc::as_ptr(var) as usize)) else { return };
+        self.violations.borrow_mut().push(NamingViolation {
+            kind,
+            name: name.clone(),
+            expected: style,
+            suggestion: style.apply(name),
+            range: lsp_range(self.text, self.line_index, span),
+        });
+    }
+}
+
+impl Visitor for NamingCollector<'_> {
+    fn visit_new(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        decls: &RholangNodeVector,
+        proc: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        for decl in decls.iter() {
+            if let RholangNode::NameDecl { var, uri, .. } = &**decl {
+                let kind = if uri.is_some() { IdentifierKind::UriName } else { IdentifierKind::Name };
+                self.check(var, kind);
+            }
+        }
+        self.visit_node(proc);
+        Arc::clone(node)
+    }
+
+    fn visit_contract(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        name: &Arc<RholangNode>,
+        formals: &RholangNodeVector,
+        formals_remainder: &Option<Arc<RholangNode>>,
+        proc: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        self.check(name, IdentifierKind::Contract);
+        for formal in formals.iter().chain(formals_remainder.iter()) {
+            match &**formal {
+                RholangNode::Quote { quotable, .. } => self.check(quotable, IdentifierKind::Parameter),
+                _ => self.check(formal, IdentifierKind::Parameter),
+            }
+        }
+        self.visit_node(proc);
+        Arc::clone(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree_sitter::parse_with_positions;
+    use tower_lsp::lsp_types::Position as LspPosition;
+
+    fn violations(source: &str, config: &NamingLintConfig) -> Vec<NamingViolation> {
+        let rope = Rope::from_str(source);
+        let (ir, positions) = parse_with_positions(source);
+        check_naming(&ir, &positions, &rope, &LineIndex::new(source), config)
+    }
+
+    #[test]
+    fn test_style_conversions() {
+        assert_eq!(NamingStyle::CamelCase.apply("Transfer_funds"), "transferFunds");
+        assert_eq!(NamingStyle::PascalCase.apply("transfer_funds"), "TransferFunds");
+        assert_eq!(NamingStyle::SnakeCase.apply("HTTPServer2Go"), "http_server2_go");
+        assert_eq!(NamingStyle::ScreamingSnakeCase.apply("stdoutAck"), "STDOUT_ACK");
+        assert!(NamingStyle::CamelCase.matches("vault2"));
+        assert!(!NamingStyle::CamelCase.matches("my_vault"));
+        assert!(NamingStyle::Any.matches("Whatever_Goes"));
+    }
+
+    #[test]
+    fn test_parse_rholangrc() {
+        let config = StyleLintConfig::from_toml_str(
+            r#"
+            [lints.naming]
+            severity = "hint"
+            contracts = "camelCase"
+            uri_names = "SCREAMING_SNAKE_CASE"
+
+            [format]
+            indent = 2
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.naming.severity, LintSeverity::Hint);
+        assert_eq!(config.naming.contracts, NamingStyle::CamelCase);
+        assert_eq!(config.naming.names, NamingStyle::Any);
+        assert!(config.naming.is_enabled());
+
+        assert!(!StyleLintConfig::default().naming.is_enabled());
+        assert!(StyleLintConfig::from_toml_str("[lints.naming]\ncontracts = \"kebab\"").is_err());
+    }
+
+    #[test]
+    fn test_check_naming() {
+        let source = r#"new stdout(`rho:io:stdout`), my_vault in {
+  contract Transfer(@from_vault, _ret) = { Nil } |
+  contract @"quoted_name"(@x) = { Nil }
+}"#;
+        let config = NamingLintConfig {
+            contracts: NamingStyle::CamelCase,
+            names: NamingStyle::CamelCase,
+            uri_names: NamingStyle::ScreamingSnakeCase,
+            parameters: NamingStyle::CamelCase,
+            ..Default::default()
+        };
+        let found = violations(source, &config);
+        let found: Vec<_> = found
+            .iter()
+            .map(|v| (v.kind, v.name.as_str(), v.suggestion.as_str(), v.range.start.line))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (IdentifierKind::UriName, "stdout", "STDOUT", 0),
+                (IdentifierKind::Name, "my_vault", "myVault", 0),
+                (IdentifierKind::Contract, "Transfer", "transfer", 1),
+                (IdentifierKind::Parameter, "from_vault", "fromVault", 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_violation_diagnostic() {
+        let source = "new my_vault in { Nil }";
+        let config = NamingLintConfig { names: NamingStyle::CamelCase, ..Default::default() };
+        let violation = &violations(source, &config)[0];
+        let diagnostic = violation.to_diagnostic(LintSeverity::Warning);
+
+        assert_eq!(diagnostic.range.start, LspPosition { line: 0, character: 4 });
+        assert_eq!(diagnostic.range.end, LspPosition { line: 0, character: 12 });
+        assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::WARNING));
+        assert_eq!(diagnostic.message, "Name 'my_vault' should be camelCase (e.g. 'myVault')");
+        assert_eq!(diagnostic.data, Some(json!({ "name": "my_vault", "suggestion": "myVault" })));
+    }
+}
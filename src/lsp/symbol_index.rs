@@ -124,6 +124,11 @@ impl SymbolIndex {
         results
     }
 
+    /// All symbols of the index, in the order they were indexed
+    pub fn symbols(&self) -> &[SymbolInformation] {
+        &self.symbols
+    }
+
     /// Get the number of symbols in the index
     pub fn len(&self) -> usize {
         self.symbols.len()
@@ -0,0 +1,81 @@
+//! Syntax diagnostics from the Rholang parser
+
+use rholang_parser::parser::errors::ParsingError;
+use rholang_parser::RholangParser;
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Position as LspPosition, Range};
+use validated::Validated;
+
+/// Diagnostic source of syntax errors
+pub const PARSER_SOURCE: &str = "rholang-parser";
+/// Diagnostic code of syntax errors
+pub const SYNTAX_ERROR_CODE: &str = "syntax-error";
+
+/// Diagnostic for a parser error between the 1-based `(line, column)` positions `start` and `end`.
+pub fn parsing_error_diagnostic(start: (usize, usize), end: (usize, usize), error: ParsingError) -> Diagnostic {
+    let range = Range {
+        start: LspPosition { line: (start.0 - 1) as u32, character: (start.1 - 1) as u32 },
+        end: LspPosition { line: (end.0 - 1) as u32, character: (end.1 - 1) as u32 },
+    };
+    let message = match error {
+        ParsingError::SyntaxError { sexp } => format!("Syntax error: {}", sexp),
+        ParsingError::MissingToken(token) => format!("Missing token: {}", token),
+        ParsingError::Unexpected(c) => format!("Unexpected character: {}", c),
+        ParsingError::UnexpectedVar => "Unexpected variable".to_string(),
+        ParsingError::UnexpectedMatchAfter { rule, offender } => format!("Unexpected {} after {}", offender, rule),
+        ParsingError::NumberOutOfRange => "Number out of range".to_string(),
+        ParsingError::DuplicateNameDecl { first, second } => format!("Duplicate name declaration at {} and {}", first, second),
+        ParsingError::MalformedLetDecl { lhs_arity, rhs_arity } => format!("Malformed let declaration: LHS arity {} != RHS arity {}", lhs_arity, rhs_arity),
+        ParsingError::UnexpectedQuote => "Unexpected quote character".to_string(),
+    };
+    Diagnostic {
+        range,
+        severity: Some(DiagnosticSeverity::ERROR),
+        code: Some(NumberOrString::String(SYNTAX_ERROR_CODE.to_string())),
+        source: Some(PARSER_SOURCE.to_string()),
+        message,
+        ..Default::default()
+    }
+}
+
+/// Syntax diagnostics of `text`; empty when it parses.
+pub fn syntax_diagnostics(text: &str) -> Vec<Diagnostic> {
+    let parser = RholangParser::new();
+    match parser.parse(text) {
+        Validated::Good(_) => Vec::new(),
+        Validated::Fail(failures) => failures
+            .into_iter()
+            .flat_map(|failure| {
+                failure
+                    .errors
+                    .into_iter()
+                    .map(|err| {
+                        parsing_error_diagnostic(
+                            (err.span.start.line as usize, err.span.start.col as usize),
+                            (err.span.end.line as usize, err.span.end.col as usize),
+                            err.error,
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_syntax_diagnostics() {
+        assert!(syntax_diagnostics("new x in { x!(1) }").is_empty());
+
+        let diagnostics = syntax_diagnostics("new x in { x!(1) ");
+        assert!(!diagnostics.is_empty());
+        assert!(diagnostics
+            .iter()
+            .all(|d| d.severity == Some(DiagnosticSeverity::ERROR) && d.source.as_deref() == Some(PARSER_SOURCE)));
+        assert!(diagnostics
+            .iter()
+            .all(|d| d.code == Some(NumberOrString::String(SYNTAX_ERROR_CODE.to_string()))));
+    }
+}
@@ -0,0 +1,177 @@
+//! `TODO`, `FIXME` and `NOTE` markers in comments
+//!
+//! A marker is one of the upper-case words `TODO`, `FIXME` or `NOTE` inside a
+//! line or block comment, standing alone (`TODO: retry`, `FIXME(ana) leak`,
+//! `NOTE`) rather than as part of a longer word. From the marker to the end
+//! of its comment line is the task text.
+//!
+//! Markers are highlighted as `comment` semantic tokens with the `todo`
+//! modifier, and the `rholang.todos` command lists them as a task list:
+//!
+//! ```json
+//! { "command": "rholang.todos", "arguments": [{ "uri": "file:///path/to/file.rho" }] }   // omit for the workspace
+//! ```
+//!
+//! returns `{ "tasks": [{ "uri": …, "range": …, "kind": "FIXME", "text": "FIXME(ana) leak" }] }`.
+
+use ropey::Rope;
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{Position, Range, Url};
+use tree_sitter::{Node, Tree};
+
+/// Lists the task markers of a document or the workspace
+pub const TODOS_COMMAND: &str = "rholang.todos";
+
+/// Kind of task marker
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum TaskKind {
+    Todo,
+    Fixme,
+    Note,
+}
+
+impl TaskKind {
+    const ALL: [(TaskKind, &'static str); 3] =
+        [(TaskKind::Todo, "TODO"), (TaskKind::Fixme, "FIXME"), (TaskKind::Note, "NOTE")];
+}
+
+/// A marker and the rest of its comment line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskMarker {
+    pub kind: TaskKind,
+    pub line: u32,
+    /// Column of the marker
+    pub start: u32,
+    /// Length up to the end of the comment line
+    pub length: u32,
+    pub text: String,
+}
+
+impl TaskMarker {
+    pub fn range(&self) -> Range {
+        Range {
+            start: Position { line: self.line, character: self.start },
+            end: Position { line: self.line, character: self.start + self.length },
+        }
+    }
+}
+
+/// Arguments of `rholang.todos`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TodosParams {
+    /// Document to list; the whole workspace when omitted
+    #[serde(default)]
+    pub uri: Option<Url>,
+}
+
+/// One entry of the task list
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskItem {
+    pub uri: Url,
+    pub range: Range,
+    pub kind: TaskKind,
+    pub text: String,
+}
+
+/// Result of `rholang.todos`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TodosReport {
+    pub tasks: Vec<TaskItem>,
+}
+
+/// The task markers in the comments of `tree`, in document order.
+pub fn find_task_markers(tree: &Tree, text: &Rope) -> Vec<TaskMarker> {
+    let mut markers = Vec::new();
+    collect(tree.root_node(), text, &mut markers);
+    markers
+}
+
+fn collect(node: Node, text: &Rope, markers: &mut Vec<TaskMarker>) {
+    if matches!(node.kind(), "line_comment" | "block_comment") {
+        let Some(comment) = text.get_byte_slice(node.start_byte()..node.end_byte()) else { return };
+        let comment = comment.to_string();
+        let start = node.start_position();
+        for (offset, line) in comment.split('\n').enumerate() {
+            let column = if offset == 0 { start.column } else { 0 };
+            markers.extend(markers_in_line(line, start.row + offset, column));
+        }
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect(child, text, markers);
+    }
+}
+
+/// Markers of one comment line starting at `row`:`column`.
+fn markers_in_line(line: &str, row: usize, column: usize) -> Vec<TaskMarker> {
+    let line = line.trim_end_matches('\r');
+    let line = line.strip_suffix("*/").unwrap_or(line).trim_end();
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+
+    let mut found = Vec::new();
+    for (kind, word) in TaskKind::ALL {
+        for (at, _) in line.match_indices(word) {
+            let before = line[..at].chars().next_back();
+            let after = line[at + word.len()..].chars().next();
+            if before.is_some_and(is_word) || after.is_some_and(is_word) {
+                continue;
+            }
+            found.push(TaskMarker {
+                kind,
+                line: row as u32,
+                start: (column + at) as u32,
+                length: (line.len() - at) as u32,
+                text: line[at..].to_string(),
+            });
+        }
+    }
+    // Several markers on a line: each runs up to the next one
+    found.sort_by_key(|marker| marker.start);
+    for i in 1..found.len() {
+        let next = found[i].start;
+        let marker = &mut found[i - 1];
+        marker.length = next - marker.start;
+        marker.text = marker.text[..marker.length as usize].trim_end().to_string();
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree_sitter::parse_code;
+
+    fn markers(source: &str) -> Vec<(TaskKind, u32, u32, String)> {
+        find_task_markers(&parse_code(source), &Rope::from_str(source))
+            .into_iter()
+            .map(|m| (m.kind, m.line, m.start, m.text))
+            .collect()
+    }
+
+    #[test]
+    fn test_markers_in_line_and_block_comments() {
+        let source = "// TODO: retry\nnew x in {\n  /* NOTE first\n     FIXME(ana) leak */ x!(1) // NOTES, TODOs\n}";
+        assert_eq!(
+            markers(source),
+            vec![
+                (TaskKind::Todo, 0, 3, "TODO: retry".to_string()),
+                (TaskKind::Note, 2, 5, "NOTE first".to_string()),
+                (TaskKind::Fixme, 3, 5, "FIXME(ana) leak".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_markers_outside_comments_are_ignored() {
+        assert!(markers("new TODO in { TODO!(\"FIXME\") }").is_empty());
+        assert_eq!(
+            markers("Nil // TODO fix NOTE why").iter().map(|m| m.3.as_str()).collect::<Vec<_>>(),
+            vec!["TODO fix", "NOTE why"]
+        );
+    }
+}
@@ -0,0 +1,160 @@
+//! Byte order marks and line endings of incoming text
+//!
+//! Tree-Sitter counts rows on `\n` only and reads a UTF-8 byte order mark as
+//! part of the first token, while LSP positions treat `\r\n`, `\n` and a
+//! lone `\r` as line breaks and editors hide the mark. Left alone, a file
+//! with a BOM fails to parse and a file with old Mac (`\r`) line endings is
+//! a single row to the parser but many lines to the client.
+//!
+//! Text is therefore normalized where it enters the server (`didOpen`,
+//! `didChange` and files read from disk):
+//!
+//! - a leading BOM is dropped, as editors do not count it in positions;
+//! - a lone `\r` becomes `\n`. Both are one byte, one char and one UTF-16
+//!   unit, so byte offsets and positions are unchanged;
+//! - `\r\n` is kept, since both sides read it as a single line break.
+//!
+//! [`TextFormat`] records what the original text used, so that text the
+//! server writes back into a document can use the same line endings.
+
+use std::borrow::Cow;
+
+/// UTF-8 byte order mark
+pub const BOM: char = '\u{feff}';
+
+/// Line terminator of a document
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    CrLf,
+    Cr,
+}
+
+impl LineEnding {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+            LineEnding::Cr => "\r",
+        }
+    }
+}
+
+/// BOM and line endings of a document as the client or the disk had it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TextFormat {
+    pub bom: bool,
+    /// The most common line ending; `\n` when there are no line breaks
+    pub line_ending: LineEnding,
+    /// Whether more than one kind of line ending occurs
+    pub mixed_line_endings: bool,
+}
+
+impl TextFormat {
+    /// Detects the format of `text` before normalization.
+    pub fn detect(text: &str) -> Self {
+        let (mut lf, mut crlf, mut cr) = (0usize, 0usize, 0usize);
+        let mut bytes = text.bytes().peekable();
+        while let Some(byte) = bytes.next() {
+            match byte {
+                b'\r' if bytes.peek() == Some(&b'\n') => {
+                    bytes.next();
+                    crlf += 1;
+                }
+                b'\r' => cr += 1,
+                b'\n' => lf += 1,
+                _ => {}
+            }
+        }
+        let line_ending = if crlf > lf && crlf >= cr {
+            LineEnding::CrLf
+        } else if cr > lf && cr > crlf {
+            LineEnding::Cr
+        } else {
+            LineEnding::Lf
+        };
+        TextFormat {
+            bom: text.starts_with(BOM),
+            line_ending,
+            mixed_line_endings: [lf, crlf, cr].iter().filter(|&&count| count > 0).count() > 1,
+        }
+    }
+
+    /// `text` written by the server (with `\n` line breaks) in this format's
+    /// line endings, for insertion into the document.
+    pub fn apply_line_endings<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        match self.line_ending {
+            LineEnding::Lf => Cow::Borrowed(text),
+            ending => Cow::Owned(text.replace("\r\n", "\n").replace('\n', ending.as_str())),
+        }
+    }
+}
+
+/// `text` as the server keeps it: without a leading BOM and with lone `\r` as `\n`.
+pub fn normalize(text: &str) -> Cow<'_, str> {
+    let text = text.strip_prefix(BOM).unwrap_or(text);
+    normalize_line_breaks(text, false).map_or(Cow::Borrowed(text), Cow::Owned)
+}
+
+/// `text` inserted by an edit, with lone `\r` as `\n`.
+///
+/// A `\r` at the very end is kept when the document continues with `\n`
+/// (`followed_by_lf`), as the two then form a `\r\n`.
+pub fn normalize_inserted(text: &str, followed_by_lf: bool) -> Cow<'_, str> {
+    normalize_line_breaks(text, followed_by_lf).map_or(Cow::Borrowed(text), Cow::Owned)
+}
+
+fn normalize_line_breaks(text: &str, keep_trailing_cr: bool) -> Option<String> {
+    let bytes = text.as_bytes();
+    let lone_cr = |i: usize| {
+        bytes[i] == b'\r' && bytes.get(i + 1).map_or(!keep_trailing_cr, |&next| next != b'\n')
+    };
+    if !(0..bytes.len()).any(lone_cr) {
+        return None;
+    }
+    let mut normalized = bytes.to_vec();
+    for (i, byte) in normalized.iter_mut().enumerate() {
+        if lone_cr(i) {
+            *byte = b'\n';
+        }
+    }
+    // Only ASCII bytes were replaced with ASCII bytes
+    Some(String::from_utf8(normalized).expect("normalization keeps UTF-8 valid"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(TextFormat::detect("a\nb\n"), TextFormat::default());
+        let crlf = TextFormat::detect("\u{feff}a\r\nb\r\nc\n");
+        assert_eq!((crlf.bom, crlf.line_ending, crlf.mixed_line_endings), (true, LineEnding::CrLf, true));
+        assert_eq!(TextFormat::detect("a\rb\rc").line_ending, LineEnding::Cr);
+        assert!(!TextFormat::detect("single line").mixed_line_endings);
+    }
+
+    #[test]
+    fn test_normalize_keeps_offsets() {
+        assert_eq!(normalize("\u{feff}new x in {\r\n  x!(1)\r\n}"), "new x in {\r\n  x!(1)\r\n}");
+        let cr = "new x in {\r  x!(1)\r}";
+        let normalized = normalize(cr);
+        assert_eq!(normalized, "new x in {\n  x!(1)\n}");
+        assert_eq!(normalized.len(), cr.len());
+        assert_eq!(normalize("Nil\r"), "Nil\n");
+        assert!(matches!(normalize("a\nb"), Cow::Borrowed(_)));
+
+        // A trailing `\r` of an insertion may pair with a `\n` already in the document
+        assert_eq!(normalize_inserted("a\rb\r", true), "a\nb\r");
+        assert_eq!(normalize_inserted("a\rb\r", false), "a\nb\n");
+    }
+
+    #[test]
+    fn test_apply_line_endings() {
+        let crlf = TextFormat { line_ending: LineEnding::CrLf, ..Default::default() };
+        assert_eq!(crlf.apply_line_endings("a\nb\r\nc"), "a\r\nb\r\nc");
+        assert_eq!(TextFormat::default().apply_line_endings("a\nb"), "a\nb");
+    }
+}
@@ -0,0 +1,203 @@
+//! Softer syntax errors while typing
+//!
+//! Half-typed expressions are syntax errors on almost every keystroke, and
+//! the red squiggles flash in and out as the user types. When the client
+//! enables it, syntax errors from validations triggered by `didChange` are
+//! published as hints (or information) instead, and escalate to errors once
+//! the document has been left alone for the settle period, or when it is
+//! saved. Diagnostics from other sources keep their severity.
+//!
+//! Configured per client with the `typingDiagnostics` initialization option:
+//!
+//! ```json
+//! { "typingDiagnostics": { "enabled": true, "severity": "hint", "settleMs": 1500 } }
+//! ```
+
+use std::time::Duration;
+
+use dashmap::DashMap;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, Url};
+
+use crate::lsp::syntax_errors::PARSER_SOURCE;
+
+/// Initialization option configuring the softened severity
+pub const TYPING_DIAGNOSTICS_OPTION: &str = "typingDiagnostics";
+
+/// Severity of syntax errors while typing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TypingSeverity {
+    #[default]
+    Hint,
+    Information,
+}
+
+impl TypingSeverity {
+    fn diagnostic_severity(self) -> DiagnosticSeverity {
+        match self {
+            TypingSeverity::Hint => DiagnosticSeverity::HINT,
+            TypingSeverity::Information => DiagnosticSeverity::INFORMATION,
+        }
+    }
+}
+
+/// The `typingDiagnostics` initialization option
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TypingDiagnosticsConfig {
+    pub enabled: bool,
+    pub severity: TypingSeverity,
+    /// Inactivity after which softened syntax errors escalate to errors
+    pub settle_ms: u64,
+}
+
+impl Default for TypingDiagnosticsConfig {
+    fn default() -> Self {
+        TypingDiagnosticsConfig { enabled: false, severity: TypingSeverity::Hint, settle_ms: 1500 }
+    }
+}
+
+impl TypingDiagnosticsConfig {
+    /// Reads the `typingDiagnostics` initialization option; missing fields keep their defaults.
+    pub fn from_init_options(init_options: Option<&serde_json::Value>) -> Self {
+        init_options
+            .and_then(|opts| opts.get(TYPING_DIAGNOSTICS_OPTION))
+            .and_then(|config| serde_json::from_value(config.clone()).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn settle_delay(&self) -> Duration {
+        Duration::from_millis(self.settle_ms)
+    }
+}
+
+/// Diagnostics published softened, kept until they escalate
+#[derive(Debug, Clone)]
+struct Softened {
+    version: i32,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// Softened syntax errors of the open documents
+#[derive(Debug, Default)]
+pub struct TypingDiagnostics {
+    config: RwLock<TypingDiagnosticsConfig>,
+    softened: DashMap<Url, Softened>,
+}
+
+impl TypingDiagnostics {
+    pub fn set_config(&self, config: TypingDiagnosticsConfig) {
+        *self.config.write() = config;
+    }
+
+    pub fn config(&self) -> TypingDiagnosticsConfig {
+        *self.config.read()
+    }
+
+    /// The diagnostics to publish for `version` of `uri` after an edit.
+    ///
+    /// Returns them with syntax errors softened, remembering the originals
+    /// for [`escalate`](Self::escalate), or unchanged when softening is off
+    /// or there are no syntax errors.
+    pub fn soften(&self, uri: &Url, version: i32, diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+        let config = self.config();
+        if !config.enabled || !diagnostics.iter().any(is_syntax_error) {
+            self.softened.remove(uri);
+            return diagnostics;
+        }
+
+        let softened = diagnostics
+            .iter()
+            .cloned()
+            .map(|mut diagnostic| {
+                if is_syntax_error(&diagnostic) {
+                    diagnostic.severity = Some(config.severity.diagnostic_severity());
+                }
+                diagnostic
+            })
+            .collect();
+        self.softened.insert(uri.clone(), Softened { version, diagnostics });
+        softened
+    }
+
+    /// Whether the syntax errors of `version` of `uri` were published softened.
+    pub fn is_softened(&self, uri: &Url, version: i32) -> bool {
+        self.softened.get(uri).is_some_and(|softened| softened.version == version)
+    }
+
+    /// The original diagnostics of `uri` if they were softened for `version`.
+    ///
+    /// Each softened publication escalates at most once.
+    pub fn escalate(&self, uri: &Url, version: i32) -> Option<Vec<Diagnostic>> {
+        self.softened
+            .remove_if(uri, |_, softened| softened.version == version)
+            .map(|(_, softened)| softened.diagnostics)
+    }
+
+    /// Forgets a closed document.
+    pub fn remove(&self, uri: &Url) {
+        self.softened.remove(uri);
+    }
+}
+
+fn is_syntax_error(diagnostic: &Diagnostic) -> bool {
+    diagnostic.severity == Some(DiagnosticSeverity::ERROR) && diagnostic.source.as_deref() == Some(PARSER_SOURCE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn diagnostic(severity: DiagnosticSeverity, source: &str) -> Diagnostic {
+        Diagnostic {
+            severity: Some(severity),
+            source: Some(source.to_string()),
+            message: "m".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_config_from_init_options() {
+        let config = TypingDiagnosticsConfig::from_init_options(Some(&json!({
+            "typingDiagnostics": { "enabled": true, "severity": "information" }
+        })));
+        assert!(config.enabled);
+        assert_eq!(config.severity, TypingSeverity::Information);
+        assert_eq!(config.settle_ms, 1500);
+        assert!(!TypingDiagnosticsConfig::from_init_options(None).enabled);
+    }
+
+    #[test]
+    fn test_syntax_errors_soften_then_escalate_once() {
+        let typing = TypingDiagnostics::default();
+        let uri = Url::parse("file:///typing.rho").unwrap();
+        let diagnostics = vec![
+            diagnostic(DiagnosticSeverity::ERROR, PARSER_SOURCE),
+            diagnostic(DiagnosticSeverity::WARNING, "rholang-style"),
+        ];
+
+        // Disabled: published as they are
+        assert_eq!(typing.soften(&uri, 1, diagnostics.clone()), diagnostics);
+        assert_eq!(typing.escalate(&uri, 1), None);
+
+        typing.set_config(TypingDiagnosticsConfig { enabled: true, ..Default::default() });
+        let softened = typing.soften(&uri, 2, diagnostics.clone());
+        assert_eq!(softened[0].severity, Some(DiagnosticSeverity::HINT));
+        assert_eq!(softened[1].severity, Some(DiagnosticSeverity::WARNING));
+        assert!(typing.is_softened(&uri, 2));
+
+        // A newer version was typed in the meantime
+        assert_eq!(typing.escalate(&uri, 1), None);
+        assert_eq!(typing.escalate(&uri, 2), Some(diagnostics.clone()));
+        assert_eq!(typing.escalate(&uri, 2), None);
+
+        // A clean version forgets what was softened
+        typing.soften(&uri, 3, diagnostics.clone());
+        typing.soften(&uri, 4, vec![]);
+        assert_eq!(typing.escalate(&uri, 3), None);
+    }
+}
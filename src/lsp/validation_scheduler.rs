@@ -0,0 +1,197 @@
+//! Cancellation of validation for stale document versions
+//!
+//! Edits arrive faster than a document can be validated, so validating
+//! every version would queue work whose diagnostics nobody sees. The
+//! scheduler tracks, per document, the latest version the server has seen
+//! and the one validation allowed to run:
+//!
+//! - `supersede` is called as soon as an edit arrives, before any debounce,
+//!   and cancels the validation of older versions;
+//! - `schedule` starts the validation of a version, cancelling the one
+//!   running for the document, and hands out a [`ValidationTicket`];
+//! - `finish` retires a ticket and tells whether its diagnostics are still
+//!   those of the latest version, the only ones worth publishing.
+//!
+//! A ticket's token is cancelled when it is superseded, when its document
+//! closes, or when whoever awaited the validation drops it, as tower-lsp
+//! does with the handler of a request cancelled by `$/cancelRequest`.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use parking_lot::Mutex;
+use tokio_util::sync::CancellationToken;
+use tower_lsp::lsp_types::Url;
+
+/// Permission to validate one version of a document
+#[derive(Debug, Clone)]
+pub struct ValidationTicket {
+    pub uri: Url,
+    pub version: i32,
+    generation: u64,
+    token: CancellationToken,
+}
+
+impl ValidationTicket {
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// Resolves once the validation should stop.
+    pub async fn cancelled(&self) {
+        self.token.cancelled().await;
+    }
+
+    /// Token cancelled with the ticket, for cancelling it on drop.
+    pub fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+}
+
+/// Validation state of one document
+#[derive(Debug)]
+struct DocumentSlot {
+    /// Latest version seen, validated or not
+    latest: i32,
+    /// The validation allowed to run, if any
+    running: Option<(u64, CancellationToken)>,
+}
+
+/// Per-document validation tickets
+#[derive(Debug, Default)]
+pub struct ValidationScheduler {
+    slots: Mutex<HashMap<Url, DocumentSlot>>,
+    next_generation: AtomicU64,
+}
+
+impl ValidationScheduler {
+    /// Records that `version` of `uri` exists, cancelling the validation of
+    /// older versions.
+    pub fn supersede(&self, uri: &Url, version: i32) {
+        let mut slots = self.slots.lock();
+        let slot = slots.entry(uri.clone()).or_insert(DocumentSlot { latest: version, running: None });
+        if version > slot.latest {
+            slot.latest = version;
+            if let Some((_, token)) = slot.running.take() {
+                token.cancel();
+            }
+        }
+    }
+
+    /// Starts validating `version` of `uri`, cancelling the validation running for it.
+    ///
+    /// The ticket of a version older than the latest one seen is cancelled from the start.
+    pub fn schedule(&self, uri: &Url, version: i32) -> ValidationTicket {
+        let generation = self.next_generation.fetch_add(1, Ordering::Relaxed);
+        let token = CancellationToken::new();
+        let mut slots = self.slots.lock();
+        let slot = slots.entry(uri.clone()).or_insert(DocumentSlot { latest: version, running: None });
+        if version < slot.latest {
+            token.cancel();
+        } else {
+            slot.latest = version;
+            if let Some((_, previous)) = slot.running.replace((generation, token.clone())) {
+                previous.cancel();
+            }
+        }
+        ValidationTicket { uri: uri.clone(), version, generation, token }
+    }
+
+    /// Whether a version of `uri` newer than `version` was seen.
+    pub fn is_superseded(&self, uri: &Url, version: i32) -> bool {
+        self.slots.lock().get(uri).is_some_and(|slot| slot.latest > version)
+    }
+
+    /// Retires `ticket`; true if its version is still the latest and no
+    /// other validation replaced it, so its diagnostics may be published.
+    pub fn finish(&self, ticket: &ValidationTicket) -> bool {
+        let mut slots = self.slots.lock();
+        let Some(slot) = slots.get_mut(&ticket.uri) else {
+            return false;
+        };
+        if slot.running.as_ref().is_none_or(|(generation, _)| *generation != ticket.generation) {
+            return false;
+        }
+        slot.running = None;
+        !ticket.is_cancelled() && slot.latest == ticket.version
+    }
+
+    /// Forgets `uri`, cancelling its validation.
+    pub fn remove(&self, uri: &Url) {
+        if let Some(DocumentSlot { running: Some((_, token)), .. }) = self.slots.lock().remove(uri) {
+            token.cancel();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uri() -> Url {
+        Url::parse("file:///test.rho").unwrap()
+    }
+
+    #[test]
+    fn test_edit_cancels_validation_of_older_version() {
+        let scheduler = ValidationScheduler::default();
+        let first = scheduler.schedule(&uri(), 1);
+        assert!(!first.is_cancelled());
+
+        scheduler.supersede(&uri(), 2);
+        assert!(first.is_cancelled());
+        assert!(scheduler.is_superseded(&uri(), 1));
+        assert!(!scheduler.finish(&first));
+
+        let second = scheduler.schedule(&uri(), 2);
+        assert!(!scheduler.is_superseded(&uri(), 2));
+        assert!(scheduler.finish(&second));
+    }
+
+    #[test]
+    fn test_schedule_replaces_running_validation() {
+        let scheduler = ValidationScheduler::default();
+        let first = scheduler.schedule(&uri(), 3);
+        let again = scheduler.schedule(&uri(), 3);
+        assert!(first.is_cancelled());
+        assert!(!scheduler.finish(&first));
+        assert!(scheduler.finish(&again));
+    }
+
+    #[test]
+    fn test_stale_version_is_cancelled_from_the_start() {
+        let scheduler = ValidationScheduler::default();
+        scheduler.supersede(&uri(), 5);
+        let stale = scheduler.schedule(&uri(), 4);
+        assert!(stale.is_cancelled());
+        assert!(!scheduler.finish(&stale));
+    }
+
+    #[test]
+    fn test_supersede_by_same_version_keeps_validation() {
+        let scheduler = ValidationScheduler::default();
+        let ticket = scheduler.schedule(&uri(), 2);
+        scheduler.supersede(&uri(), 2);
+        assert!(!ticket.is_cancelled());
+        assert!(scheduler.finish(&ticket));
+    }
+
+    #[test]
+    fn test_remove_cancels_validation() {
+        let scheduler = ValidationScheduler::default();
+        let ticket = scheduler.schedule(&uri(), 1);
+        scheduler.remove(&uri());
+        assert!(ticket.is_cancelled());
+        assert!(!scheduler.finish(&ticket));
+        assert!(!scheduler.is_superseded(&uri(), 1));
+    }
+
+    #[test]
+    fn test_dropped_guard_cancels_ticket() {
+        let scheduler = ValidationScheduler::default();
+        let ticket = scheduler.schedule(&uri(), 1);
+        drop(ticket.token().drop_guard());
+        assert!(ticket.is_cancelled());
+        assert!(!scheduler.finish(&ticket));
+    }
+}
@@ -0,0 +1,272 @@
+//! Lifecycle notifications for virtual documents
+//!
+//! Embedded language regions live in virtual documents (see
+//! `crate::language_regions::virtual_document`). Clients that set the
+//! `virtualDocumentEvents` initialization option are told when the virtual
+//! documents of an open document appear, change or go away, so an editor
+//! extension can show a region in a split view and keep it in sync:
+//!
+//! | Notification                       | Sent when                                     |
+//! |------------------------------------|-----------------------------------------------|
+//! | `rholang/virtualDocumentCreated`   | a region is detected in an open document      |
+//! | `rholang/virtualDocumentUpdated`   | its content, language or host range changes   |
+//! | `rholang/virtualDocumentChanged`   | an edit of the host is made inside the region |
+//! | `rholang/virtualDocumentRemoved`   | the region disappears or the host is closed   |
+//!
+//! All but `virtualDocumentChanged` carry [`VirtualDocumentParams`]:
+//!
+//! ```json
+//! { "uri": "file:///path/to/file.rho#metta:0", "parentUri": "file:///path/to/file.rho",
+//!   "language": "metta",
+//!   "hostRange": { "start": { "line": 3, "character": 10 }, "end": { "line": 3, "character": 42 } },
+//!   "content": "(= (double $x) (* $x 2))" }
+//! ```
+//!
+//! `content` is omitted from `virtualDocumentRemoved`.
+//!
+//! When an edit of the host falls inside a plain string literal, the virtual
+//! document is edited in place (see `crate::language_regions::virtual_edit`)
+//! and the client gets the edit rather than the whole content again, like a
+//! `didChange` ([`VirtualDocumentChangeParams`]):
+//!
+//! ```json
+//! { "uri": "file:///path/to/file.rho#metta:0", "parentUri": "file:///path/to/file.rho",
+//!   "version": 7,
+//!   "contentChanges": [ { "range": { "start": { "line": 0, "character": 17 },
+//!                                    "end": { "line": 0, "character": 18 } }, "text": "3" } ] }
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::notification::Notification;
+use tower_lsp::lsp_types::{Range, TextDocumentContentChangeEvent, Url};
+
+use crate::language_regions::VirtualDocument;
+
+/// Initialization option enabling the notifications
+pub const VIRTUAL_DOCUMENT_EVENTS_OPTION: &str = "virtualDocumentEvents";
+
+/// Whether the client asked for virtual document notifications.
+pub fn enabled_in(init_options: Option<&serde_json::Value>) -> bool {
+    init_options
+        .and_then(|opts| opts.get(VIRTUAL_DOCUMENT_EVENTS_OPTION))
+        .and_then(|enabled| enabled.as_bool())
+        .unwrap_or(false)
+}
+
+/// Parameters of the `rholang/virtualDocument*` notifications
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VirtualDocumentParams {
+    pub uri: Url,
+    pub parent_uri: Url,
+    pub language: String,
+    /// Range of the region in the parent document
+    pub host_range: Range,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+}
+
+impl From<&VirtualDocument> for VirtualDocumentParams {
+    fn from(document: &VirtualDocument) -> Self {
+        VirtualDocumentParams {
+            uri: document.uri.clone(),
+            parent_uri: document.parent_uri.clone(),
+            language: document.language.clone(),
+            host_range: Range { start: document.parent_start, end: document.parent_end },
+            content: Some(document.content.clone()),
+        }
+    }
+}
+
+/// `rholang/virtualDocumentCreated`
+pub enum VirtualDocumentCreated {}
+
+impl Notification for VirtualDocumentCreated {
+    type Params = VirtualDocumentParams;
+    const METHOD: &'static str = "rholang/virtualDocumentCreated";
+}
+
+/// `rholang/virtualDocumentUpdated`
+pub enum VirtualDocumentUpdated {}
+
+impl Notification for VirtualDocumentUpdated {
+    type Params = VirtualDocumentParams;
+    const METHOD: &'static str = "rholang/virtualDocumentUpdated";
+}
+
+/// Parameters of `rholang/virtualDocumentChanged`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VirtualDocumentChangeParams {
+    pub uri: Url,
+    pub parent_uri: Url,
+    /// Version of the parent document after the edit
+    pub version: i32,
+    pub content_changes: Vec<TextDocumentContentChangeEvent>,
+}
+
+/// `rholang/virtualDocumentChanged`
+pub enum VirtualDocumentChanged {}
+
+impl Notification for VirtualDocumentChanged {
+    type Params = VirtualDocumentChangeParams;
+    const METHOD: &'static str = "rholang/virtualDocumentChanged";
+}
+
+/// `rholang/virtualDocumentRemoved`
+pub enum VirtualDocumentRemoved {}
+
+impl Notification for VirtualDocumentRemoved {
+    type Params = VirtualDocumentParams;
+    const METHOD: &'static str = "rholang/virtualDocumentRemoved";
+}
+
+/// A notification to send
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VirtualDocumentEvent {
+    Created(VirtualDocumentParams),
+    Updated(VirtualDocumentParams),
+    Removed(VirtualDocumentParams),
+}
+
+/// Remembers what the client was told about each parent's virtual documents
+#[derive(Debug, Default)]
+pub struct VirtualDocumentAnnouncer {
+    announced: HashMap<Url, BTreeMap<Url, VirtualDocumentParams>>,
+}
+
+impl VirtualDocumentAnnouncer {
+    /// Events bringing the client from the last announced state of `parent`
+    /// to `documents`, its current virtual documents.
+    ///
+    /// Removals come first, then creations and updates in document order.
+    pub fn sync(&mut self, parent: &Url, documents: &[Arc<VirtualDocument>]) -> Vec<VirtualDocumentEvent> {
+        let mut previous = self.announced.remove(parent).unwrap_or_default();
+        let mut current = BTreeMap::new();
+        let mut changes = Vec::new();
+
+        for document in documents {
+            let params = VirtualDocumentParams::from(&**document);
+            match previous.remove(&params.uri) {
+                None => changes.push(VirtualDocumentEvent::Created(params.clone())),
+                Some(old) if old != params => changes.push(VirtualDocumentEvent::Updated(params.clone())),
+                Some(_) => {}
+            }
+            current.insert(params.uri.clone(), params);
+        }
+
+        let mut events: Vec<VirtualDocumentEvent> = previous.into_values().map(removed).collect();
+        events.extend(changes);
+        if !current.is_empty() {
+            self.announced.insert(parent.clone(), current);
+        }
+        events
+    }
+
+    /// Records that the client brought an announced `document` up to date
+    /// itself, from a `virtualDocumentChanged` notification.
+    ///
+    /// Returns false if the document was never announced, in which case the
+    /// next `sync` creates it.
+    pub fn record(&mut self, document: &VirtualDocument) -> bool {
+        let params = VirtualDocumentParams::from(document);
+        match self.announced.get_mut(&document.parent_uri).and_then(|announced| announced.get_mut(&params.uri)) {
+            Some(announced) => {
+                *announced = params;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removal events for every announced virtual document of `parent`.
+    pub fn forget(&mut self, parent: &Url) -> Vec<VirtualDocumentEvent> {
+        self.announced
+            .remove(parent)
+            .map(|documents| documents.into_values().map(removed).collect())
+            .unwrap_or_default()
+    }
+}
+
+fn removed(mut params: VirtualDocumentParams) -> VirtualDocumentEvent {
+    params.content = None;
+    VirtualDocumentEvent::Removed(params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language_regions::{LanguageRegion, RegionSource};
+    use serde_json::json;
+
+    fn document(parent: &Url, index: usize, line: usize, content: &str) -> Arc<VirtualDocument> {
+        let region = LanguageRegion {
+            language: "metta".to_string(),
+            start_byte: 0,
+            end_byte: content.len(),
+            start_line: line,
+            start_column: 4,
+            source: RegionSource::CommentDirective,
+            content: content.to_string(),
+            concatenation_chain: None,
+        };
+        Arc::new(VirtualDocument::new(parent.clone(), &region, index))
+    }
+
+    fn kinds(events: &[VirtualDocumentEvent]) -> Vec<(&'static str, String)> {
+        events
+            .iter()
+            .map(|event| match event {
+                VirtualDocumentEvent::Created(p) => ("created", p.uri.fragment().unwrap_or("").to_string()),
+                VirtualDocumentEvent::Updated(p) => ("updated", p.uri.fragment().unwrap_or("").to_string()),
+                VirtualDocumentEvent::Removed(p) => ("removed", p.uri.fragment().unwrap_or("").to_string()),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_sync_diffs_against_announced_state() {
+        let parent = Url::parse("file:///host.rho").unwrap();
+        let mut announcer = VirtualDocumentAnnouncer::default();
+
+        let first = vec![document(&parent, 0, 1, "(= a 1)"), document(&parent, 1, 5, "(= b 2)")];
+        let events = announcer.sync(&parent, &first);
+        assert_eq!(kinds(&events), vec![("created", "metta:0".into()), ("created", "metta:1".into())]);
+        assert!(announcer.sync(&parent, &first).is_empty(), "nothing changed");
+
+        // Region 0 edited, region 1 deleted
+        let second = vec![document(&parent, 0, 1, "(= a 10)")];
+        let events = announcer.sync(&parent, &second);
+        assert_eq!(kinds(&events), vec![("removed", "metta:1".into()), ("updated", "metta:0".into())]);
+        let VirtualDocumentEvent::Removed(params) = &events[0] else { unreachable!() };
+        assert_eq!(params.content, None);
+
+        // An edit the client already applied is not announced again
+        let edited = document(&parent, 0, 1, "(= a 11)");
+        assert!(announcer.record(&edited));
+        assert!(announcer.sync(&parent, &[edited]).is_empty());
+        assert!(!announcer.record(&document(&parent, 3, 9, "(= c 3)")));
+
+        let events = announcer.forget(&parent);
+        assert_eq!(kinds(&events), vec![("removed", "metta:0".into())]);
+        assert!(announcer.forget(&parent).is_empty());
+    }
+
+    #[test]
+    fn test_params_serialization_and_option() {
+        let parent = Url::parse("file:///host.rho").unwrap();
+        let params = VirtualDocumentParams::from(&*document(&parent, 0, 2, "(+ 1 2)"));
+        let value = serde_json::to_value(&params).unwrap();
+        assert_eq!(value["uri"], "file:///host.rho#metta:0");
+        assert_eq!(value["parentUri"], "file:///host.rho");
+        assert_eq!(value["hostRange"]["start"], json!({ "line": 2, "character": 4 }));
+        assert_eq!(value["content"], "(+ 1 2)");
+
+        assert!(enabled_in(Some(&json!({ "virtualDocumentEvents": true }))));
+        assert!(!enabled_in(Some(&json!({}))));
+        assert!(!enabled_in(None));
+    }
+}
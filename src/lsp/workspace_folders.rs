@@ -0,0 +1,120 @@
+//! Workspace folders indexed by the server
+//!
+//! Every `.rho` file under the workspace folders sent in `initialize` (or the
+//! `rootUri` of clients without folder support) is indexed into the
+//! cross-file symbol tables, so `workspace/symbol` and go-to-definition see
+//! files that were never opened. The index follows the files through
+//! `workspace/didChangeWatchedFiles` and the server's own file watcher, and
+//! through `workspace/didChangeWorkspaceFolders` as folders come and go.
+
+use std::path::{Path, PathBuf};
+
+use tower_lsp::lsp_types::{Url, WorkspaceFolder};
+use tracing::warn;
+use walkdir::WalkDir;
+
+/// Glob of the files watched through `workspace/didChangeWatchedFiles`
+pub const RHOLANG_FILES_GLOB: &str = "**/*.rho";
+
+/// Id of the dynamic registration of the file watcher
+pub const WATCHED_FILES_REGISTRATION_ID: &str = "rholang-watched-files";
+
+/// The directories to index: the workspace folders, or `root_uri` when the
+/// client sent none. Folders nested in another folder are dropped since the
+/// outer one covers them.
+pub fn workspace_roots(folders: Option<&[WorkspaceFolder]>, root_uri: Option<&Url>) -> Vec<PathBuf> {
+    let uris: Vec<&Url> = match folders {
+        Some(folders) if !folders.is_empty() => folders.iter().map(|folder| &folder.uri).collect(),
+        _ => root_uri.into_iter().collect(),
+    };
+    let paths: Vec<PathBuf> = uris
+        .into_iter()
+        .filter_map(|uri| match uri.to_file_path() {
+            Ok(path) => Some(path),
+            Err(()) => {
+                warn!("Workspace folder is not a local directory: {}", uri);
+                None
+            }
+        })
+        .collect();
+    outermost(paths)
+}
+
+/// `roots` without duplicates and without directories inside another root, in order.
+pub fn outermost(roots: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut kept: Vec<PathBuf> = Vec::new();
+    for root in &roots {
+        let covered = roots
+            .iter()
+            .any(|other| other != root && root.starts_with(other))
+            || kept.contains(root);
+        if !covered {
+            kept.push(root.clone());
+        }
+    }
+    kept
+}
+
+/// Whether `path` lies under one of `roots`.
+pub fn in_workspace(path: &Path, roots: &[PathBuf]) -> bool {
+    roots.iter().any(|root| path.starts_with(root))
+}
+
+/// The root among `roots` that `path` lies under, if any.
+pub fn folder_of<'a>(path: &Path, roots: &'a [PathBuf]) -> Option<&'a PathBuf> {
+    roots.iter().find(|root| path.starts_with(root))
+}
+
+/// The `.rho` files under `roots`, in walk order.
+pub fn rholang_files(roots: &[PathBuf]) -> Vec<PathBuf> {
+    roots
+        .iter()
+        .flat_map(|root| WalkDir::new(root).into_iter().filter_map(Result::ok))
+        .filter(|entry| entry.file_type().is_file() && entry.path().extension().is_some_and(|ext| ext == "rho"))
+        .map(|entry| entry.into_path())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn folder(path: &str) -> WorkspaceFolder {
+        WorkspaceFolder { uri: Url::from_file_path(path).unwrap(), name: path.to_string() }
+    }
+
+    #[test]
+    fn test_workspace_roots() {
+        let root = Url::from_file_path("/work").unwrap();
+        assert_eq!(workspace_roots(None, Some(&root)), vec![PathBuf::from("/work")]);
+        assert_eq!(workspace_roots(Some(&[]), Some(&root)), vec![PathBuf::from("/work")]);
+
+        // Folders win over the root; nested and repeated folders are covered by the outer one
+        let folders = [folder("/work/app"), folder("/lib"), folder("/work/app/contracts"), folder("/lib")];
+        assert_eq!(
+            workspace_roots(Some(&folders), Some(&root)),
+            vec![PathBuf::from("/work/app"), PathBuf::from("/lib")]
+        );
+        assert!(in_workspace(Path::new("/work/app/contracts/a.rho"), &[PathBuf::from("/work/app")]));
+        assert!(!in_workspace(Path::new("/work/application/a.rho"), &[PathBuf::from("/work/app")]));
+
+        let roots = [PathBuf::from("/work/app"), PathBuf::from("/lib")];
+        assert_eq!(folder_of(Path::new("/lib/std/list.rho"), &roots), Some(&roots[1]));
+        assert_eq!(folder_of(Path::new("/work/application/a.rho"), &roots), None);
+    }
+
+    #[test]
+    fn test_rholang_files() {
+        let dir = std::env::temp_dir().join(format!("rholang-workspace-folders-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        std::fs::write(dir.join("a.rho"), "Nil").unwrap();
+        std::fs::write(dir.join("nested/b.rho"), "Nil").unwrap();
+        std::fs::write(dir.join("notes.md"), "").unwrap();
+
+        let mut files = rholang_files(&[dir.clone()]);
+        files.sort();
+        assert_eq!(files, vec![dir.join("a.rho"), dir.join("nested/b.rho")]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,363 @@
+//! Workspace trust model for commands that execute code on RNode
+//!
+//! Deploying a document sends arbitrary Rholang to a remote node, so the server
+//! refuses to do so unless two independent conditions hold:
+//!
+//! 1. The target endpoint is on the configured allowlist
+//!    (`initializationOptions.deploy.allowedEndpoints` or the
+//!    `RHOLANG_DEPLOY_ALLOWED_ENDPOINTS` environment variable).
+//! 2. The user has trusted the workspace folder containing the document,
+//!    confirmed through a client-side `window/showMessageRequest` prompt.
+//!
+//! Both default to "deny": an empty allowlist permits no endpoint, and a
+//! workspace is untrusted until the user explicitly accepts the prompt.
+//! Only acceptance is remembered: after a refusal or a dismissed prompt, the
+//! next deploy asks again. Deploys started while the prompt for their
+//! workspace is open wait for its answer rather than prompting again.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+
+use futures::future::{BoxFuture, FutureExt, Shared};
+use parking_lot::{Mutex, RwLock};
+use serde::Deserialize;
+use tracing::{debug, info};
+
+/// Environment variable holding a comma-separated RNode endpoint allowlist
+pub const ALLOWED_ENDPOINTS_ENV: &str = "RHOLANG_DEPLOY_ALLOWED_ENDPOINTS";
+
+/// Deploy section of the client's `initializationOptions`
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DeployOptions {
+    #[serde(default)]
+    allowed_endpoints: Vec<String>,
+}
+
+/// Configuration controlling which RNode endpoints may receive deploys.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DeployTrustConfig {
+    /// Normalized `host:port` endpoints that deploys may target
+    allowed_endpoints: BTreeSet<String>,
+}
+
+impl DeployTrustConfig {
+    /// Creates a configuration with the given endpoint allowlist.
+    pub fn new<I, S>(endpoints: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self {
+            allowed_endpoints: endpoints
+                .into_iter()
+                .filter_map(|e| normalize_endpoint(e.as_ref()))
+                .collect(),
+        }
+    }
+
+    /// Builds the configuration from the environment and initialization options.
+    ///
+    /// Checks in order:
+    /// 1. Environment variable RHOLANG_DEPLOY_ALLOWED_ENDPOINTS
+    /// 2. `deploy.allowedEndpoints` in the initialization options
+    /// 3. Falls back to an empty allowlist (all deploys refused)
+    pub fn from_env_or_init_options(init_options: Option<&serde_json::Value>) -> Self {
+        if let Ok(endpoints) = std::env::var(ALLOWED_ENDPOINTS_ENV) {
+            return Self::new(endpoints.split(','));
+        }
+        Self::from_init_options(init_options)
+    }
+
+    /// Builds the configuration from `deploy.allowedEndpoints` in the initialization options.
+    pub fn from_init_options(init_options: Option<&serde_json::Value>) -> Self {
+        let options = init_options
+            .and_then(|opts| opts.get("deploy"))
+            .and_then(|deploy| serde_json::from_value::<DeployOptions>(deploy.clone()).ok())
+            .unwrap_or_default();
+
+        Self::new(options.allowed_endpoints)
+    }
+
+    /// Returns true if deploys to `endpoint` are permitted by the allowlist.
+    pub fn is_endpoint_allowed(&self, endpoint: &str) -> bool {
+        normalize_endpoint(endpoint).is_some_and(|e| self.allowed_endpoints.contains(&e))
+    }
+
+    /// Returns the first allowlisted endpoint, used when a command names none.
+    pub fn default_endpoint(&self) -> Option<&str> {
+        self.allowed_endpoints.iter().next().map(String::as_str)
+    }
+
+    /// Returns the normalized allowlist.
+    pub fn allowed_endpoints(&self) -> impl Iterator<Item = &str> {
+        self.allowed_endpoints.iter().map(String::as_str)
+    }
+}
+
+/// Normalizes an endpoint to lowercase `host:port` form.
+///
+/// Strips any `http://`/`https://` scheme and trailing slashes so that
+/// `http://LocalHost:40402/` and `localhost:40402` compare equal.
+pub fn normalize_endpoint(endpoint: &str) -> Option<String> {
+    let trimmed = endpoint.trim();
+    let without_scheme = trimmed
+        .strip_prefix("http://")
+        .or_else(|| trimmed.strip_prefix("https://"))
+        .unwrap_or(trimmed)
+        .trim_end_matches('/');
+
+    if without_scheme.is_empty() {
+        None
+    } else {
+        Some(without_scheme.to_lowercase())
+    }
+}
+
+/// User decision about whether a workspace may deploy code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrustDecision {
+    /// The user accepted the trust prompt
+    Trusted,
+    /// The user rejected (or dismissed) the trust prompt
+    Untrusted,
+}
+
+/// Reason a deploy was refused.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeployDenied {
+    /// The document is not in a workspace folder, so there is nothing to trust
+    NoWorkspace,
+    /// The workspace has not been trusted by the user
+    UntrustedWorkspace(PathBuf),
+    /// The endpoint is not on the allowlist
+    EndpointNotAllowed(String),
+    /// No endpoint was given and the allowlist is empty
+    NoEndpointConfigured,
+}
+
+impl fmt::Display for DeployDenied {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeployDenied::NoWorkspace => {
+                write!(f, "Deploy refused: the document is not in an open workspace folder")
+            }
+            DeployDenied::UntrustedWorkspace(root) => {
+                write!(f, "Deploy refused: workspace {} is not trusted", root.display())
+            }
+            DeployDenied::EndpointNotAllowed(endpoint) => write!(
+                f,
+                "Deploy refused: RNode endpoint '{}' is not in deploy.allowedEndpoints",
+                endpoint
+            ),
+            DeployDenied::NoEndpointConfigured => write!(
+                f,
+                "Deploy refused: no RNode endpoint configured in deploy.allowedEndpoints"
+            ),
+        }
+    }
+}
+
+/// A trust prompt awaited by every deploy waiting on it
+type PendingPrompt = Shared<BoxFuture<'static, TrustDecision>>;
+
+/// Session-scoped workspace trust state and deploy allowlist.
+///
+/// Trust is kept per workspace folder. Trusted roots are remembered for the lifetime of the server, so
+/// the user accepts at most once per session; a refusal is not remembered.
+#[derive(Default)]
+pub struct WorkspaceTrust {
+    config: RwLock<DeployTrustConfig>,
+    trusted: Mutex<HashSet<PathBuf>>,
+    /// Prompts the user has not answered yet, by workspace root
+    pending: Mutex<HashMap<PathBuf, PendingPrompt>>,
+}
+
+impl fmt::Debug for WorkspaceTrust {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WorkspaceTrust")
+            .field("config", &*self.config.read())
+            .field("trusted", &*self.trusted.lock())
+            .field("pending", &self.pending.lock().keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl WorkspaceTrust {
+    /// Creates a trust store with the given allowlist and no trusted workspace.
+    pub fn new(config: DeployTrustConfig) -> Self {
+        Self {
+            config: RwLock::new(config),
+            trusted: Mutex::new(HashSet::new()),
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Replaces the deploy configuration (e.g. after `initialize`).
+    pub fn set_config(&self, config: DeployTrustConfig) {
+        info!(
+            "Deploy allowlist configured with {} endpoint(s)",
+            config.allowed_endpoints.len()
+        );
+        *self.config.write() = config;
+    }
+
+    /// Returns a snapshot of the current deploy configuration.
+    pub fn config(&self) -> DeployTrustConfig {
+        self.config.read().clone()
+    }
+
+    /// Returns true if the user has trusted `root` in this session.
+    pub fn is_trusted(&self, root: &Path) -> bool {
+        self.trusted.lock().contains(root)
+    }
+
+    /// Records the user's decision for `root`; only trust is remembered.
+    pub fn record_decision(&self, root: &Path, decision: TrustDecision) {
+        debug!("Recording trust decision {:?} for {}", decision, root.display());
+        if decision == TrustDecision::Trusted {
+            self.trusted.lock().insert(root.to_path_buf());
+        }
+    }
+
+    /// Asks the user to trust `root` with the future `prompt` makes, unless
+    /// it is already trusted, and records the answer.
+    ///
+    /// While a prompt for `root` is open, later calls wait for its answer
+    /// instead of prompting again.
+    pub async fn decide<F>(&self, root: &Path, prompt: impl FnOnce() -> F) -> TrustDecision
+    where
+        F: Future<Output = TrustDecision> + Send + 'static,
+    {
+        if self.is_trusted(root) {
+            return TrustDecision::Trusted;
+        }
+        let pending = self
+            .pending
+            .lock()
+            .entry(root.to_path_buf())
+            .or_insert_with(|| prompt().boxed().shared())
+            .clone();
+
+        let decision = pending.clone().await;
+        self.record_decision(root, decision);
+        let mut prompts = self.pending.lock();
+        // A later prompt may already have replaced this one
+        if prompts.get(root).is_some_and(|current| current.ptr_eq(&pending)) {
+            prompts.remove(root);
+        }
+        decision
+    }
+
+    /// Resolves the endpoint a deploy should target, checking the allowlist.
+    ///
+    /// If `requested` is `None`, the first allowlisted endpoint is used.
+    pub fn resolve_endpoint(&self, requested: Option<&str>) -> Result<String, DeployDenied> {
+        let config = self.config.read();
+        match requested {
+            Some(endpoint) => {
+                if config.is_endpoint_allowed(endpoint) {
+                    Ok(normalize_endpoint(endpoint).unwrap_or_default())
+                } else {
+                    Err(DeployDenied::EndpointNotAllowed(endpoint.to_string()))
+                }
+            }
+            None => config
+                .default_endpoint()
+                .map(str::to_string)
+                .ok_or(DeployDenied::NoEndpointConfigured),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_normalize_endpoint() {
+        assert_eq!(normalize_endpoint("localhost:40402"), Some("localhost:40402".to_string()));
+        assert_eq!(normalize_endpoint("http://LocalHost:40402/"), Some("localhost:40402".to_string()));
+        assert_eq!(normalize_endpoint("https://node.example:443"), Some("node.example:443".to_string()));
+        assert_eq!(normalize_endpoint("   "), None);
+    }
+
+    #[test]
+    fn test_empty_allowlist_denies_everything() {
+        let trust = WorkspaceTrust::new(DeployTrustConfig::default());
+        assert_eq!(trust.resolve_endpoint(None), Err(DeployDenied::NoEndpointConfigured));
+        assert!(matches!(
+            trust.resolve_endpoint(Some("localhost:40402")),
+            Err(DeployDenied::EndpointNotAllowed(_))
+        ));
+    }
+
+    #[test]
+    fn test_allowlist_from_init_options() {
+        let options = json!({ "deploy": { "allowedEndpoints": ["http://localhost:40402"] } });
+        let config = DeployTrustConfig::from_init_options(Some(&options));
+        assert!(config.is_endpoint_allowed("localhost:40402"));
+        assert!(!config.is_endpoint_allowed("localhost:40401"));
+
+        let trust = WorkspaceTrust::new(config);
+        assert_eq!(trust.resolve_endpoint(None), Ok("localhost:40402".to_string()));
+    }
+
+    #[test]
+    fn test_workspace_untrusted_by_default() {
+        let trust = WorkspaceTrust::new(DeployTrustConfig::new(["localhost:40402"]));
+        let root = PathBuf::from("/tmp/workspace");
+        let other = PathBuf::from("/tmp/other");
+        assert!(!trust.is_trusted(&root));
+
+        trust.record_decision(&root, TrustDecision::Untrusted);
+        assert!(!trust.is_trusted(&root));
+
+        // Trust is kept per workspace folder
+        trust.record_decision(&root, TrustDecision::Trusted);
+        assert!(trust.is_trusted(&root));
+        assert!(!trust.is_trusted(&other));
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_deploys_share_one_prompt() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let trust = WorkspaceTrust::new(DeployTrustConfig::new(["localhost:40402"]));
+        let root = PathBuf::from("/tmp/workspace");
+        let prompts = Arc::new(AtomicUsize::new(0));
+        let (answer, answered) = tokio::sync::oneshot::channel();
+        let prompt = |decision: TrustDecision| {
+            let prompts = prompts.clone();
+            move || {
+                prompts.fetch_add(1, Ordering::SeqCst);
+                async move { decision }
+            }
+        };
+
+        // Refused while two deploys wait on the same prompt
+        let first = trust.decide(&root, || {
+            prompts.fetch_add(1, Ordering::SeqCst);
+            async move { answered.await.unwrap_or(TrustDecision::Untrusted) }
+        });
+        let second = trust.decide(&root, prompt(TrustDecision::Trusted));
+        let answering = async {
+            tokio::task::yield_now().await;
+            answer.send(TrustDecision::Untrusted).unwrap();
+        };
+        let (first, second, ()) = tokio::join!(first, second, answering);
+        assert_eq!((first, second), (TrustDecision::Untrusted, TrustDecision::Untrusted));
+        assert_eq!(prompts.load(Ordering::SeqCst), 1);
+        assert!(trust.pending.lock().is_empty());
+
+        // The refusal is not remembered: the next deploy asks again
+        assert_eq!(trust.decide(&root, prompt(TrustDecision::Trusted)).await, TrustDecision::Trusted);
+        assert_eq!(prompts.load(Ordering::SeqCst), 2);
+        assert_eq!(trust.decide(&root, prompt(TrustDecision::Untrusted)).await, TrustDecision::Trusted);
+        assert_eq!(prompts.load(Ordering::SeqCst), 2);
+    }
+}
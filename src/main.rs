@@ -1,44 +1,34 @@
 #![recursion_limit = "1024"]
 use std::io;
-use std::sync::{Arc, Mutex};
-use std::time::Duration;
 
-#[cfg(unix)]
-use std::fs;
-
-use futures::sink::SinkExt;
-use futures::stream::TryStreamExt;
-#[allow(unused_imports)]
-use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt, BufReader, DuplexStream, Stdout};
-use tokio::net::{TcpListener, UnixListener};
-use tokio::sync::{Notify, oneshot};
-use tokio::task::JoinHandle;
-
-#[cfg(windows)]
-use tokio::net::windows::named_pipe::NamedPipeServer;
-
-use tokio_tungstenite::tungstenite::Message;
-use tokio_tungstenite::{accept_async, WebSocketStream};
-
-use tower_lsp::{LspService, Server};
-
-use tracing::{debug, error, info, trace, warn};
+use tracing::{error, info, warn};
 
 use clap::Parser;
 
-use rholang_language_server::lsp::backend::RholangBackend;
-use rholang_language_server::logging::init_logger;
+use rholang_language_server::check::{self, CheckArgs};
+use rholang_language_server::check::analyze::{self, AnalyzeArgs};
+use rholang_language_server::check::format::{self as fmt_check, FmtArgs};
+use rholang_language_server::debug_console::DebugConsole;
+use rholang_language_server::deterministic;
+use rholang_language_server::edit_journal::{self, EditJournal, ReplayJournalArgs};
+use rholang_language_server::logging::{get_log_dir, init_logger};
+use rholang_language_server::lsp::shared_documents::{ConflictPolicy, SharedDocuments};
 use rholang_language_server::rnode_apis::lsp::lsp_client::LspClient;
-use rholang_language_server::wire_logger::WireLogger;
-use rholang_language_server::wire_logger_middleware::{LoggingReader, LoggingWriter};
-
-// Define communication mode enum for ServerConfig
-#[derive(Debug, Clone, PartialEq)]
-enum CommMode {
-    Stdio,
-    Socket(u16),
-    Pipe(String),
-    WebSocket(u16),
+use rholang_language_server::strict_lsp::StrictLspMode;
+use rholang_language_server::supervisor;
+use rholang_language_server::transport::{self, ConnectionManager, ServeOptions, TransportMode};
+
+/// Subcommands that run instead of the server
+#[derive(clap::Subcommand, Debug)]
+enum Command {
+    /// Report diagnostics for Rholang files without starting the server
+    Check(CheckArgs),
+    /// Report Rholang files that are not formatted in the canonical style (`--check`)
+    Fmt(FmtArgs),
+    /// Analyze one document read from stdin and print diagnostics, symbols and formatting
+    Analyze(AnalyzeArgs),
+    /// Rebuild documents from an edit journal and print them
+    ReplayJournal(ReplayJournalArgs),
 }
 
 // Server configuration struct
@@ -46,13 +36,20 @@ enum CommMode {
 struct ServerConfig {
     log_level: String,
     no_color: bool,
-    comm_mode: CommMode,
+    comm_mode: TransportMode,
     rnode_address: String,
     rnode_port: u16,
     client_process_id: Option<u32>,
     no_rnode: bool,
     validator_backend: Option<String>,
     wire_log: bool,
+    supervise: bool,
+    edit_journal: bool,
+    open_conflict: ConflictPolicy,
+    debug_console: Option<u16>,
+    strict_lsp: Option<StrictLspMode>,
+    deterministic: Option<u64>,
+    command: Option<Command>,
 }
 
 impl ServerConfig {
@@ -145,6 +142,49 @@ impl ServerConfig {
                 help = "Enable wire protocol logging (logs all LSP messages to separate wire.log file)"
             )]
             wire_log: bool,
+            #[arg(
+                long,
+                requires = "stdio",
+                help = "Run the server in a supervised worker process that is restarted (with open documents restored) if it crashes; requires --stdio"
+            )]
+            supervise: bool,
+            #[arg(
+                long,
+                help = "Journal document edits (didChange deltas) to an edits-*.log file next to the session log, for replay with the replay-journal command after a crash"
+            )]
+            edit_journal: bool,
+            #[arg(
+                long,
+                default_value = "last-writer-wins",
+                help = "How edits to a document open in several connections are arbitrated: 'last-writer-wins' applies every edit and notifies the other connections, 'reject' refuses edits from connections other than the last writer",
+                value_parser = ConflictPolicy::NAMES
+            )]
+            open_conflict: String,
+            #[arg(
+                long,
+                value_name = "PORT",
+                help = "Serve live tables of open documents, IR sizes, cache hit rates, queue depths and recent request latencies on http://127.0.0.1:PORT (plain text at /, JSON at /json)"
+            )]
+            debug_console: Option<u16>,
+            #[arg(
+                long,
+                value_name = "MODE",
+                num_args = 0..=1,
+                default_missing_value = "log",
+                help = "Check every outgoing response and notification against the LSP 3.17 specification: 'log' (the default) logs violations as errors, 'fail' also closes the connection on the first one",
+                value_parser = StrictLspMode::NAMES
+            )]
+            strict_lsp: Option<String>,
+            #[arg(
+                long,
+                value_name = "SEED",
+                num_args = 0..=1,
+                default_missing_value = "0",
+                help = "Run in deterministic mode for reproducible tests: debounce windows on a virtual clock advanced by rholang/advanceClock, ids starting at SEED (0 by default) and single-threaded pools. A real client never advances the clock, so never use it in an editor"
+            )]
+            deterministic: Option<u64>,
+            #[command(subcommand)]
+            command: Option<Command>,
         }
 
         let args = Args::parse();
@@ -162,20 +202,20 @@ impl ServerConfig {
         };
 
         let comm_mode = match (args.stdio, args.socket, args.websocket, args.pipe) {
-            (true, false, false, None) => CommMode::Stdio,
+            (true, false, false, None) => TransportMode::Stdio,
             (false, true, false, None) => {
                 let port = args.port.ok_or_else(|| {
                     error!("The --port option is required when --socket is used.");
                     io::Error::new(io::ErrorKind::InvalidInput, "Port required for socket mode")
                 })?;
-                CommMode::Socket(port)
+                TransportMode::Socket(port)
             }
             (false, false, true, None) => {
                 let port = args.port.ok_or_else(|| {
                     error!("The --port option is required when --websocket is used.");
                     io::Error::new(io::ErrorKind::InvalidInput, "Port required for websocket mode")
                 })?;
-                CommMode::WebSocket(port)
+                TransportMode::WebSocket(port)
             }
             (false, false, false, Some(pipe)) => {
                 #[cfg(windows)]
@@ -186,8 +226,10 @@ impl ServerConfig {
                         format!("Invalid named pipe path: {}", pipe),
                     ));
                 }
-                CommMode::Pipe(pipe)
+                TransportMode::Pipe(pipe)
             }
+            // Subcommands do not serve connections
+            _ if args.command.is_some() => TransportMode::Stdio,
             _ => {
                 error!("Exactly one of --stdio, --socket, --websocket, --pipe must be specified.");
                 return Err(io::Error::new(
@@ -212,664 +254,22 @@ impl ServerConfig {
             no_rnode: args.no_rnode,
             validator_backend,
             wire_log: args.wire_log,
+            supervise: args.supervise,
+            edit_journal: args.edit_journal,
+            open_conflict: ConflictPolicy::parse(&args.open_conflict).unwrap_or_default(),
+            debug_console: args.debug_console,
+            strict_lsp: args.strict_lsp.as_deref().and_then(StrictLspMode::parse),
+            deterministic: args.deterministic,
+            command: args.command,
         })
     }
 }
 
-// WebSocketStreamAdapter
-struct WebSocketStreamAdapter<S> {
-    inner: WebSocketStream<S>,
-    read_buffer: Vec<u8>,
-    /// Phase 4 optimization: Write buffer for batching small messages
-    write_buffer: Vec<u8>,
-}
-
-impl<S> WebSocketStreamAdapter<S>
-where
-    S: AsyncRead + AsyncWrite + Unpin,
-{
-    fn new(inner: WebSocketStream<S>) -> Self {
-        // Phase 1 optimization: Pre-allocate read buffer with reasonable capacity
-        // Prevents repeated allocations for typical LSP messages (1-10KB)
-        const INITIAL_CAPACITY: usize = 32 * 1024;  // 32KB initial
-        WebSocketStreamAdapter {
-            inner,
-            read_buffer: Vec::with_capacity(INITIAL_CAPACITY),
-            // Phase 4 optimization: Pre-allocate write buffer for batching
-            write_buffer: Vec::with_capacity(8 * 1024),  // 8KB write buffer
-        }
-    }
-
-    #[allow(dead_code)]
-    async fn close(&mut self) -> io::Result<()> {
-        self.inner
-            .send(Message::Close(None))
-            .await
-            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
-        self.inner
-            .flush()
-            .await
-            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
-        Ok(())
-    }
-}
-
-impl<S> AsyncRead for WebSocketStreamAdapter<S>
-where
-    S: AsyncRead + AsyncWrite + Unpin + Send,
-{
-    fn poll_read(
-        self: std::pin::Pin<&mut Self>,
-        cx: &mut std::task::Context<'_>,
-        buf: &mut tokio::io::ReadBuf<'_>,
-    ) -> std::task::Poll<io::Result<()>> {
-        let this = self.get_mut();
-
-        // Phase 1 optimization: Cap max buffer size to prevent unbounded growth
-        const MAX_BUFFER_SIZE: usize = 1024 * 1024;  // 1MB max
-
-        if !this.read_buffer.is_empty() {
-            trace!("Using buffered data: {} bytes", this.read_buffer.len());
-            let to_copy = std::cmp::min(buf.remaining(), this.read_buffer.len());
-            buf.put_slice(&this.read_buffer[..to_copy]);
-            this.read_buffer.drain(..to_copy);
-
-            // Shrink buffer if it's grown too large and is now mostly empty
-            if this.read_buffer.capacity() > MAX_BUFFER_SIZE && this.read_buffer.len() < MAX_BUFFER_SIZE / 4 {
-                this.read_buffer.shrink_to(MAX_BUFFER_SIZE / 2);
-                trace!("Shrunk WebSocket read buffer to {}", this.read_buffer.capacity());
-            }
-
-            return std::task::Poll::Ready(Ok(()));
-        }
-
-        match this.inner.try_poll_next_unpin(cx) {
-            std::task::Poll::Ready(Some(Ok(Message::Text(text)))) => {
-                trace!("Received WebSocket text message: {}", text);
-                this.read_buffer.extend_from_slice(text.as_bytes());
-                let to_copy = std::cmp::min(buf.remaining(), this.read_buffer.len());
-                buf.put_slice(&this.read_buffer[..to_copy]);
-                this.read_buffer.drain(..to_copy);
-                std::task::Poll::Ready(Ok(()))
-            }
-            std::task::Poll::Ready(Some(Ok(Message::Binary(data)))) => {
-                trace!("Received WebSocket binary message: {:?}", data);
-                this.read_buffer.extend_from_slice(&data);
-                let to_copy = std::cmp::min(buf.remaining(), this.read_buffer.len());
-                buf.put_slice(&this.read_buffer[..to_copy]);
-                this.read_buffer.drain(..to_copy);
-                std::task::Poll::Ready(Ok(()))
-            }
-            std::task::Poll::Ready(Some(Ok(Message::Ping(_)))) => {
-                trace!("Received WebSocket ping message");
-                std::task::Poll::Ready(Ok(()))
-            }
-            std::task::Poll::Ready(Some(Ok(Message::Pong(_)))) => {
-                trace!("Received WebSocket pong message");
-                std::task::Poll::Ready(Ok(()))
-            }
-            std::task::Poll::Ready(Some(Ok(Message::Frame(_)))) => {
-                trace!("Received WebSocket frame message");
-                std::task::Poll::Ready(Ok(()))
-            }
-            std::task::Poll::Ready(Some(Ok(Message::Close(_)))) => {
-                trace!("Received WebSocket close message");
-                std::task::Poll::Ready(Ok(()))
-            }
-            std::task::Poll::Ready(Some(Err(e))) => {
-                trace!("WebSocket error: {}", e);
-                std::task::Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, e)))
-            }
-            std::task::Poll::Ready(None) => {
-                trace!("WebSocket stream closed");
-                std::task::Poll::Ready(Ok(()))
-            }
-            std::task::Poll::Pending => {
-                trace!("WebSocket poll pending");
-                std::task::Poll::Pending
-            }
-        }
-    }
-}
-
-impl<S> AsyncWrite for WebSocketStreamAdapter<S>
-where
-    S: AsyncRead + AsyncWrite + Unpin + Send,
-{
-    fn poll_write(
-        self: std::pin::Pin<&mut Self>,
-        cx: &mut std::task::Context<'_>,
-        buf: &[u8],
-    ) -> std::task::Poll<io::Result<usize>> {
-        let this = self.get_mut();
-
-        // Phase 4 optimization: Buffer small writes to reduce WebSocket frame overhead
-        // Max buffer size before forcing a flush (16KB threshold)
-        const MAX_WRITE_BUFFER: usize = 16 * 1024;
-
-        // If buffer would exceed threshold, flush it first
-        if !this.write_buffer.is_empty() && (this.write_buffer.len() + buf.len() > MAX_WRITE_BUFFER) {
-            match this.inner.poll_ready_unpin(cx) {
-                std::task::Poll::Ready(Ok(())) => {
-                    // Send buffered data as binary frame
-                    match this.inner.start_send_unpin(Message::Binary(std::mem::take(&mut this.write_buffer))) {
-                        Ok(()) => {}
-                        Err(e) => return std::task::Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, e))),
-                    }
-                }
-                std::task::Poll::Ready(Err(e)) => {
-                    return std::task::Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, e)));
-                }
-                std::task::Poll::Pending => return std::task::Poll::Pending,
-            }
-        }
-
-        // Add data to write buffer
-        this.write_buffer.extend_from_slice(buf);
-        std::task::Poll::Ready(Ok(buf.len()))
-    }
-
-    fn poll_flush(
-        self: std::pin::Pin<&mut Self>,
-        cx: &mut std::task::Context<'_>,
-    ) -> std::task::Poll<io::Result<()>> {
-        let this = self.get_mut();
-
-        // Phase 4 optimization: Flush any buffered writes before flushing underlying stream
-        if !this.write_buffer.is_empty() {
-            match this.inner.poll_ready_unpin(cx) {
-                std::task::Poll::Ready(Ok(())) => {
-                    // Send buffered data as binary frame
-                    match this.inner.start_send_unpin(Message::Binary(std::mem::take(&mut this.write_buffer))) {
-                        Ok(()) => {}
-                        Err(e) => return std::task::Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, e))),
-                    }
-                }
-                std::task::Poll::Ready(Err(e)) => {
-                    return std::task::Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, e)));
-                }
-                std::task::Poll::Pending => return std::task::Poll::Pending,
-            }
-        }
-
-        // Flush underlying WebSocket stream
-        match this.inner.poll_flush_unpin(cx) {
-            std::task::Poll::Ready(Ok(())) => std::task::Poll::Ready(Ok(())),
-            std::task::Poll::Ready(Err(e)) => std::task::Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, e))),
-            std::task::Poll::Pending => std::task::Poll::Pending,
-        }
-    }
-
-    fn poll_shutdown(
-        self: std::pin::Pin<&mut Self>,
-        cx: &mut std::task::Context<'_>,
-    ) -> std::task::Poll<io::Result<()>> {
-        let this = self.get_mut();
-
-        // Phase 4 optimization: Flush any remaining buffered writes before shutdown
-        if !this.write_buffer.is_empty() {
-            match this.inner.poll_ready_unpin(cx) {
-                std::task::Poll::Ready(Ok(())) => {
-                    // Send remaining buffered data as binary frame
-                    match this.inner.start_send_unpin(Message::Binary(std::mem::take(&mut this.write_buffer))) {
-                        Ok(()) => {}
-                        Err(e) => return std::task::Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, e))),
-                    }
-                }
-                std::task::Poll::Ready(Err(e)) => {
-                    return std::task::Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, e)));
-                }
-                std::task::Poll::Pending => return std::task::Poll::Pending,
-            }
-        }
-
-        // Close underlying WebSocket stream
-        match this.inner.poll_close_unpin(cx) {
-            std::task::Poll::Ready(Ok(())) => std::task::Poll::Ready(Ok(())),
-            std::task::Poll::Ready(Err(e)) => std::task::Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, e))),
-            std::task::Poll::Pending => std::task::Poll::Pending,
-        }
-    }
-}
-
-// ConnectionManager
-#[derive(Clone)]
-struct ConnectionManager {
-    shutdown_notify: Arc<Notify>,
-    connections: Arc<Mutex<Vec<oneshot::Sender<()>>>>,
-    tasks: Arc<Mutex<Vec<JoinHandle<()>>>>,
-}
-
-impl ConnectionManager {
-    fn new() -> Self {
-        ConnectionManager {
-            shutdown_notify: Arc::new(Notify::new()),
-            connections: Arc::new(Mutex::new(Vec::new())),
-            tasks: Arc::new(Mutex::new(Vec::new())),
-        }
-    }
-
-    async fn add_connection(&self, tx: oneshot::Sender<()>) {
-        let mut conns = self.connections.lock().unwrap();
-        conns.push(tx);
-        info!("Added connection, total: {}", conns.len());
-    }
-
-    fn add_task(&self, task: JoinHandle<()>) {
-        let mut tasks = self.tasks.lock().unwrap();
-        tasks.push(task);
-        info!("Added task, total: {}", tasks.len());
-    }
-
-    async fn remove_closed_connections(&self) {
-        let mut conns = self.connections.lock().unwrap();
-        conns.retain(|tx| !tx.is_closed());
-        info!("Remaining connections: {}", conns.len());
-    }
-
-    async fn shutdown_all(&self) {
-        info!("Initiating shutdown of all connections and tasks");
-        // Remove closed connections first
-        self.remove_closed_connections().await;
-        // Signal remaining connections
-        let mut conns = self.connections.lock().unwrap();
-        for tx in conns.drain(..) {
-            if tx.send(()).is_err() {
-                debug!("Failed to send shutdown signal to a connection; likely already closed");
-            }
-        }
-        self.shutdown_notify.notify_waiters();
-
-        let mut tasks = self.tasks.lock().unwrap();
-        for task in tasks.drain(..) {
-            task.abort();
-        }
-        info!("All tasks canceled");
-    }
-
-    async fn wait_for_tasks(&self) {
-        let tasks: Vec<JoinHandle<()>> = {
-            let mut tasks = self.tasks.lock().unwrap();
-            tasks.drain(..).collect()
-        };
-        for task in tasks {
-            if let Err(e) = tokio::time::timeout(Duration::from_secs(5), task).await {
-                error!("Task did not complete in time: {:?}", e);
-            }
-        }
-        info!("All tasks completed or timed out");
-    }
-}
-
-async fn serve_connection<R, W>(
-    read: R,
-    write: W,
-    addr: impl std::fmt::Display + Send + 'static,
-    rnode_client: Option<LspClient<tonic::transport::Channel>>,
-    conn_manager: &ConnectionManager,
-    client_process_id: Option<u32>,
-    pid_channel: Option<tokio::sync::mpsc::Sender<u32>>,
-    validator_backend: Option<String>,
-    wire_logger: WireLogger,
-) where
-    R: tokio::io::AsyncRead + Send + Unpin + 'static,
-    W: tokio::io::AsyncWrite + Send + Unpin + 'static,
-{
-    info!("Accepted connection from {}", addr);
-
-    // Determine gRPC address from CLI arg, or fall back to rnode_client if present
-    let grpc_address = validator_backend.or_else(|| {
-        rnode_client.as_ref().map(|_| "grpc:localhost:40402".to_string())
-    });
-
-    let (service, socket) = LspService::new(|client| {
-        // Block on async backend creation (only happens once during initialization)
-        let backend = tokio::task::block_in_place(|| {
-            tokio::runtime::Handle::current().block_on(async {
-                RholangBackend::new(client, grpc_address.clone(), client_process_id, pid_channel.clone())
-                    .await
-                    .expect("Failed to create Rholang backend")
-            })
-        });
-        Arc::new(backend)
-    });
-    let (conn_tx, conn_rx) = oneshot::channel::<()>();
-    conn_manager.add_connection(conn_tx).await;
-
-    let shutdown_notify = conn_manager.shutdown_notify.clone();
-    let task = tokio::spawn(async move {
-        // Conditionally wrap streams with wire logger middleware only if enabled
-        if wire_logger.is_enabled() {
-            let logging_read = LoggingReader::new(read, wire_logger.clone());
-            let logging_write = LoggingWriter::new(write, wire_logger);
-            let server = Server::new(logging_read, logging_write, socket);
-            tokio::select! {
-                _ = server.serve(service) => {
-                    info!("Connection from {} closed normally", addr);
-                }
-                _ = conn_rx => {
-                    info!("Shutdown signal received for connection from {}", addr);
-                    shutdown_notify.notified().await;
-                    info!("Exit processed for connection from {}", addr);
-                }
-            }
-        } else {
-            let server = Server::new(read, write, socket);
-            tokio::select! {
-                _ = server.serve(service) => {
-                    info!("Connection from {} closed normally", addr);
-                }
-                _ = conn_rx => {
-                    info!("Shutdown signal received for connection from {}", addr);
-                    shutdown_notify.notified().await;
-                    info!("Exit processed for connection from {}", addr);
-                }
-            }
-        }
-    });
-    conn_manager.add_task(task);
-}
-
-#[cfg(unix)]
-async fn monitor_client_process(client_pid: u32, conn_manager: ConnectionManager) {
-    use nix::unistd::Pid;
-    use tokio::time::{sleep, Duration};
-
-    let pid = Pid::from_raw(client_pid as i32);
-    loop {
-        match nix::sys::signal::kill(pid, None) {
-            Ok(()) => {
-                sleep(Duration::from_secs(1)).await;
-            }
-            Err(nix::Error::ESRCH) => {
-                info!("Client process (PID: {}) no longer exists, shutting down server", client_pid);
-                conn_manager.shutdown_notify.notify_waiters(); // Signal main to shut down
-                break;
-            }
-            Err(e) => {
-                error!("Error checking client process (PID: {}): {}", client_pid, e);
-                sleep(Duration::from_secs(1)).await;
-            }
-        }
-    }
-}
-
-#[cfg(windows)]
-async fn monitor_client_process(client_pid: u32, conn_manager: ConnectionManager) {
-    use windows::Win32::System::Threading::{OpenProcess, PROCESS_QUERY_INFORMATION, WaitForSingleObject};
-    use windows::Win32::Foundation::{HANDLE, WAIT_OBJECT_0};
-    use std::ptr;
-
-    unsafe {
-        let handle = OpenProcess(PROCESS_QUERY_INFORMATION, false, client_pid);
-        if handle == HANDLE(ptr::null_mut()) {
-            error!("Failed to open client process (PID: {})", client_pid);
-            return;
-        }
-        let result = WaitForSingleObject(handle, 0xFFFFFFFF);
-        if result == WAIT_OBJECT_0 {
-            info!("Client process (PID: {}) terminated, shutting down server", client_pid);
-            conn_manager.shutdown_notify.notify_waiters(); // Signal main to shut down
-        } else {
-            error!("Error waiting for client process (PID: {}): {:?}", client_pid, result);
-        }
-    }
-}
-
-async fn run_stdio_server(
-    rnode_client: Option<LspClient<tonic::transport::Channel>>,
-    config: ServerConfig,
-    conn_manager: ConnectionManager,
-    wire_logger: WireLogger,
-) -> io::Result<()> {
-    info!("Starting server with stdin/stdout communication.");
-
-    // Create reactive channel for PID events
-    let (pid_tx, mut pid_rx) = tokio::sync::mpsc::channel::<u32>(1);
-
-    // Determine gRPC address from CLI arg, or fall back to rnode_client if present
-    let grpc_address = config.validator_backend.clone().or_else(|| {
-        rnode_client.as_ref().map(|_| "grpc:localhost:40402".to_string())
-    });
-
-    let (service, socket) = LspService::build(|client| {
-        // Block on async backend creation (only happens once during initialization)
-        tokio::task::block_in_place(|| {
-            tokio::runtime::Handle::current().block_on(async {
-                RholangBackend::new(client, grpc_address.clone(), config.client_process_id, Some(pid_tx.clone()))
-                    .await
-                    .expect("Failed to create Rholang backend")
-            })
-        })
-    }).finish();
-
-    // Phase 1 optimization: Use larger buffers for stdin/stdout
-    // 64KB buffers provide better throughput for LSP message streams
-    const BUFFER_SIZE: usize = 64 * 1024;
-    let stdin = BufReader::with_capacity(BUFFER_SIZE, tokio::io::stdin());
-    let stdout = tokio::io::BufWriter::with_capacity(BUFFER_SIZE, tokio::io::stdout());
-
-    // Spawn reactive listener for PID events
-    let conn_manager_clone = conn_manager.clone();
-    let conn_manager_clone2 = conn_manager.clone();
-    tokio::spawn(async move {
-        if let Some(pid) = pid_rx.recv().await {
-            info!("Received client PID from LSP initialization: {}", pid);
-            let monitor_task = tokio::spawn(async move {
-                monitor_client_process(pid, conn_manager_clone).await;
-            });
-            conn_manager_clone2.add_task(monitor_task);
-        }
-    });
-
-    let shutdown_notify = conn_manager.shutdown_notify.clone();
-    let server_task = tokio::spawn(async move {
-        // Conditionally wrap streams with wire logger middleware only if enabled
-        if config.wire_log {
-            let logging_stdin = LoggingReader::new(stdin, wire_logger.clone());
-            let logging_stdout = LoggingWriter::new(stdout, wire_logger);
-            let server = Server::new(logging_stdin, logging_stdout, socket);
-            tokio::select! {
-                _ = server.serve(service) => {
-                    info!("Stdio server terminated normally");
-                }
-                _ = shutdown_notify.notified() => {
-                    info!("Shutdown signal received, stopping stdio server");
-                }
-            }
-        } else {
-            let server = Server::new(stdin, stdout, socket);
-            tokio::select! {
-                _ = server.serve(service) => {
-                    info!("Stdio server terminated normally");
-                }
-                _ = shutdown_notify.notified() => {
-                    info!("Shutdown signal received, stopping stdio server");
-                }
-            }
-        }
-    });
-    conn_manager.add_task(server_task);
-
-    // Wait for shutdown signal (from client monitor or signal)
-    conn_manager.shutdown_notify.notified().await;
-    conn_manager.shutdown_all().await;
-    conn_manager.wait_for_tasks().await;
-    Ok(())
-}
-
-async fn run_socket_server(
-    rnode_client: Option<LspClient<tonic::transport::Channel>>,
-    config: ServerConfig,
-    conn_manager: ConnectionManager,
-    port: u16,
-    wire_logger: WireLogger,
-) -> io::Result<()> {
-    info!("Starting server with TCP socket communication on port {}.", port);
-    let listener = TcpListener::bind(format!("127.0.0.1:{}", port)).await?;
-    info!("TCP server listening on 127.0.0.1:{}", port);
-
-    // Phase 1 optimization: Define buffer size for TCP streams
-    const BUFFER_SIZE: usize = 64 * 1024;
-
-    loop {
-        tokio::select! {
-            result = listener.accept() => {
-                match result {
-                    Ok((stream, addr)) => {
-                        // Phase 1 optimization: Configure TCP socket for low latency
-                        if let Err(e) = stream.set_nodelay(true) {
-                            warn!("Failed to set TCP_NODELAY for {}: {}", addr, e);
-                        }
-
-                        // Phase 1 optimization: Add buffering for read/write
-                        let (read, write) = tokio::io::split(stream);
-                        let buffered_read = BufReader::with_capacity(BUFFER_SIZE, read);
-                        let buffered_write = tokio::io::BufWriter::with_capacity(BUFFER_SIZE, write);
-
-                        serve_connection(buffered_read, buffered_write, addr, rnode_client.clone(), &conn_manager, config.client_process_id, None, config.validator_backend.clone(), wire_logger.clone()).await;
-                        conn_manager.remove_closed_connections().await;
-                    }
-                    Err(e) => {
-                        error!("Failed to accept TCP connection: {}", e);
-                    }
-                }
-            }
-            _ = conn_manager.shutdown_notify.notified() => {
-                info!("Main shutdown signal received, closing TCP server");
-                break;
-            }
-        }
-    }
-    conn_manager.shutdown_all().await;
-    conn_manager.wait_for_tasks().await;
-    Ok(())
-}
-
-async fn run_websocket_server(
-    rnode_client: Option<LspClient<tonic::transport::Channel>>,
-    config: ServerConfig,
-    conn_manager: ConnectionManager,
-    port: u16,
-    wire_logger: WireLogger,
-) -> io::Result<()> {
-    info!("Starting server with WebSocket communication on port {}.", port);
-    let listener = TcpListener::bind(format!("127.0.0.1:{}", port)).await?;
-    info!("WebSocket server listening on 127.0.0.1:{}", port);
-
-    loop {
-        tokio::select! {
-            result = listener.accept() => {
-                match result {
-                    Ok((stream, addr)) => {
-                        match accept_async(stream).await {
-                            Ok(ws_stream) => {
-                                let ws_adapter = WebSocketStreamAdapter::new(ws_stream);
-                                let (read, write) = tokio::io::split(ws_adapter);
-                                serve_connection(read, write, addr, rnode_client.clone(), &conn_manager, config.client_process_id, None, config.validator_backend.clone(), wire_logger.clone()).await;
-                                conn_manager.remove_closed_connections().await;
-                            }
-                            Err(e) => {
-                                error!("Failed to accept WebSocket connection from {}: {}", addr, e);
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        error!("Failed to accept TCP connection: {}", e);
-                    }
-                }
-            }
-            _ = conn_manager.shutdown_notify.notified() => {
-                info!("Main shutdown signal received, closing WebSocket server");
-                break;
-            }
-        }
-    }
-    conn_manager.shutdown_all().await;
-    conn_manager.wait_for_tasks().await;
-    Ok(())
-}
-
-async fn run_named_pipe_server(
-    rnode_client: Option<LspClient<tonic::transport::Channel>>,
-    config: &ServerConfig,
-    conn_manager: ConnectionManager,
-    pipe_path: &String,
-    wire_logger: WireLogger,
-) -> io::Result<()> {
-    #[cfg(windows)]
-    {
-        info!("Starting server with named pipe communication at {}.", pipe_path);
-        loop {
-            let server = NamedPipeServer::new(&pipe_path).await?;
-            tokio::select! {
-                _ = server.connect() => {
-                    let addr = format!("named_pipe:{}", pipe_path);
-                    let (read, write) = tokio::io::split(server);
-                    serve_connection(read, write, addr, rnode_client.clone(), &conn_manager, config.client_process_id, None, config.validator_backend.clone(), wire_logger.clone()).await;
-                    conn_manager.remove_closed_connections().await;
-                }
-                _ = conn_manager.shutdown_notify.notified() => {
-                    info!("Main shutdown signal received, closing named pipe server");
-                    break;
-                }
-            }
-        }
-        conn_manager.shutdown_all().await;
-        conn_manager.wait_for_tasks().await;
-    }
-    #[cfg(unix)]
-    {
-        info!("Starting server with Unix domain socket communication at {}.", pipe_path);
-        if std::path::Path::new(&pipe_path).exists() {
-            fs::remove_file(&pipe_path)?;
-        }
-        let listener = UnixListener::bind(&pipe_path)?;
-        let cleanup = scopeguard::guard(pipe_path.clone(), |path| {
-            if let Err(e) = fs::remove_file(&path) {
-                error!("Failed to clean up Unix socket file {}: {}", path, e);
-            } else {
-                info!("Cleaned up Unix socket file {}.", path);
-            }
-        });
-        loop {
-            tokio::select! {
-                result = listener.accept() => {
-                    match result {
-                        Ok((stream, addr)) => {
-                            let addr = format!("unix_socket:{:?}", addr);
-                            let (read, write) = tokio::io::split(stream);
-                            serve_connection(read, write, addr, rnode_client.clone(), &conn_manager, config.client_process_id, None, config.validator_backend.clone(), wire_logger.clone()).await;
-                            conn_manager.remove_closed_connections().await;
-                        }
-                        Err(e) => {
-                            error!("Failed to accept Unix socket connection: {}", e);
-                        }
-                    }
-                }
-                _ = conn_manager.shutdown_notify.notified() => {
-                    info!("Main shutdown signal received, closing Unix socket server");
-                    break;
-                }
-            }
-        }
-        drop(cleanup);
-        conn_manager.shutdown_all().await;
-        conn_manager.wait_for_tasks().await;
-    }
-    #[cfg(not(any(windows, unix)))]
-    {
-        error!("Named pipe/Unix domain socket communication is not supported on this platform.");
-        return Err(io::Error::new(
-            io::ErrorKind::Other,
-            "Named pipe/Unix domain socket communication is not supported on this platform.",
-        ));
-    }
-    Ok(())
-}
-
 async fn run_server(config: ServerConfig, conn_manager: ConnectionManager) -> io::Result<()> {
     let (_log_guard, wire_logger) = init_logger(config.no_color, Some(&config.log_level), true, config.wire_log)?;
+    if deterministic::enabled() {
+        info!("Deterministic mode on with seed {}", deterministic::id_seed());
+    }
 
     // Log build metadata for version tracking
     let git_hash = env!("BUILD_GIT_HASH");
@@ -907,19 +307,34 @@ async fn run_server(config: ServerConfig, conn_manager: ConnectionManager) -> io
     };
 
     if let Some(client_pid) = config.client_process_id {
-        let conn_manager_clone = conn_manager.clone();
-        let monitor_task = tokio::spawn(async move {
-            monitor_client_process(client_pid, conn_manager_clone).await;
-        });
-        conn_manager.add_task(monitor_task);
+        transport::spawn_client_monitor(client_pid, &conn_manager);
     }
 
-    match config.comm_mode {
-        CommMode::Stdio => run_stdio_server(rnode_client_opt, config, conn_manager, wire_logger).await?,
-        CommMode::Socket(port) => run_socket_server(rnode_client_opt, config, conn_manager, port, wire_logger).await?,
-        CommMode::WebSocket(port) => run_websocket_server(rnode_client_opt, config, conn_manager, port, wire_logger).await?,
-        CommMode::Pipe(ref pipe_path) => run_named_pipe_server(rnode_client_opt, &config, conn_manager, pipe_path, wire_logger).await?,
-    }
+    // Determine gRPC address from CLI arg, or fall back to rnode_client if present
+    let validator_backend = config.validator_backend.clone().or_else(|| {
+        rnode_client_opt.as_ref().map(|_| "grpc:localhost:40402".to_string())
+    });
+    let debug_console = match config.debug_console {
+        Some(port) => {
+            let console = DebugConsole::default();
+            console.serve(port).await?;
+            Some(console)
+        }
+        None => None,
+    };
+    let options = ServeOptions {
+        validator_backend,
+        client_process_id: config.client_process_id,
+        wire_logger,
+        edit_journal: if config.edit_journal { EditJournal::new(&get_log_dir()?)? } else { EditJournal::disabled() },
+        shared_documents: SharedDocuments::new(config.open_conflict),
+        debug_console,
+        strict_lsp: config.strict_lsp,
+    };
+
+    info!("Starting server with {} communication.", config.comm_mode);
+    let transport = transport::bind(&config.comm_mode).await?;
+    transport::serve(transport, options, conn_manager).await?;
 
     info!("Server terminated.");
     Ok(())
@@ -1002,6 +417,12 @@ fn main() -> io::Result<()> {
         }
     }));
 
+    let config = ServerConfig::from_args()?;
+    // Before the pools below are sized
+    if let Some(seed) = config.deterministic {
+        deterministic::enable(seed);
+    }
+
     // Build Tokio runtime with larger stack size to handle deeply nested ASTs
     // Default stack size is often 2MB, we increase it to prevent stack overflow
     // when parsing real-world Rholang files with deeply nested structures (e.g., robot_planning.rho)
@@ -1029,7 +450,10 @@ fn main() -> io::Result<()> {
     // 1. The panic is logged with full context (thread name, location, message)
     // 2. The panic is written to the panic.log file
     // 3. The panic is propagated so the operation fails gracefully
+    //
+    // In deterministic mode (--deterministic) both pools have one thread
     rayon::ThreadPoolBuilder::new()
+        .num_threads(deterministic::threads(0))
         .stack_size(STACK_SIZE)
         .thread_name(|i| format!("rholang-rayon-worker-{}", i))
         .panic_handler(|err| {
@@ -1103,17 +527,55 @@ fn main() -> io::Result<()> {
         .expect("Failed to build rayon thread pool");
 
     let runtime = tokio::runtime::Builder::new_multi_thread()
-        .worker_threads(4)
+        .worker_threads(deterministic::threads(4))
         .thread_stack_size(STACK_SIZE)
         .thread_name("rholang-tokio-worker")  // Name threads for easier debugging
         .enable_all()
         .build()?;
 
-    runtime.block_on(async_main())
+    runtime.block_on(async_main(config))
 }
 
-async fn async_main() -> io::Result<()> {
-    let config = ServerConfig::from_args()?;
+async fn async_main(config: ServerConfig) -> io::Result<()> {
+    match config.command {
+        Some(Command::Check(args)) => {
+            let code = {
+                let (_log_guard, _) = init_logger(config.no_color, Some("warn"), true, false)?;
+                check::run(args).await?
+            };
+            std::process::exit(code);
+        }
+        Some(Command::Fmt(args)) => {
+            let code = {
+                let (_log_guard, _) = init_logger(config.no_color, Some("warn"), true, false)?;
+                fmt_check::run(args)?
+            };
+            std::process::exit(code);
+        }
+        Some(Command::Analyze(args)) => {
+            let code = {
+                let (_log_guard, _) = init_logger(config.no_color, Some("warn"), true, false)?;
+                analyze::run(args).await?
+            };
+            std::process::exit(code);
+        }
+        Some(Command::ReplayJournal(args)) => std::process::exit(edit_journal::run_replay(args)?),
+        None => {}
+    }
+
+    if config.supervise {
+        let code = {
+            let (_log_guard, _) = init_logger(config.no_color, Some(&config.log_level), true, false)?;
+            let worker_args = std::env::args_os()
+                .skip(1)
+                .filter(|arg| arg != supervisor::SUPERVISE_FLAG)
+                .collect();
+            info!("Starting supervisor for worker process");
+            supervisor::run(worker_args).await?
+        };
+        std::process::exit(code);
+    }
+
     let conn_manager = ConnectionManager::new();
 
     #[cfg(unix)]
@@ -1125,19 +587,16 @@ async fn async_main() -> io::Result<()> {
         tokio::select! {
             result = run_server(config, conn_manager.clone()) => {
                 conn_manager.shutdown_all().await;
-                conn_manager.wait_for_tasks().await;
                 result
             }
             _ = sigint.recv() => {
                 info!("Received SIGINT, initiating shutdown");
                 conn_manager.shutdown_all().await;
-                conn_manager.wait_for_tasks().await;
                 Ok(())
             }
             _ = sigterm.recv() => {
                 info!("Received SIGTERM, initiating shutdown");
                 conn_manager.shutdown_all().await;
-                conn_manager.wait_for_tasks().await;
                 Ok(())
             }
         }
@@ -1149,13 +608,11 @@ async fn async_main() -> io::Result<()> {
         tokio::select! {
             result = run_server(config, conn_manager.clone()) => {
                 conn_manager.shutdown_all().await;
-                conn_manager.wait_for_tasks().await;
                 result
             }
             _ = ctrl_c() => {
                 info!("Received Ctrl+C, initiating shutdown");
                 conn_manager.shutdown_all().await;
-                conn_manager.wait_for_tasks().await;
                 Ok(())
             }
         }
@@ -1165,7 +622,6 @@ async fn async_main() -> io::Result<()> {
     {
         run_server(config, conn_manager.clone()).await?;
         conn_manager.shutdown_all().await;
-        conn_manager.wait_for_tasks().await;
         Ok(())
     }
 }
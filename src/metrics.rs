@@ -7,7 +7,10 @@
 //! ## Metrics Tracked
 //!
 //! - Parse cache hit rate
+//! - Incremental vs full Tree-Sitter parses
+//! - Documents found out of sync with the client
 //! - LSP request latencies (goto-definition, hover, etc.)
+//! - The most recent LSP requests and their latencies
 //! - Workspace indexing time
 //! - Virtual document detection time
 //! - Symbol resolution time
@@ -18,10 +21,12 @@
 //! - DashMap for low-contention histogram storage
 //! - Minimal overhead (~10-20ns per metric update)
 
+use std::collections::VecDeque;
 use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use dashmap::DashMap;
+use parking_lot::Mutex;
 
 /// Global metrics registry (singleton)
 static METRICS: once_cell::sync::Lazy<Arc<Metrics>> = once_cell::sync::Lazy::new(|| {
@@ -33,6 +38,9 @@ pub fn metrics() -> &'static Arc<Metrics> {
     &METRICS
 }
 
+/// Number of recent requests kept by [`Metrics::record_request`]
+pub const RECENT_REQUESTS: usize = 100;
+
 /// Performance metrics registry
 #[derive(Debug)]
 pub struct Metrics {
@@ -40,6 +48,10 @@ pub struct Metrics {
     parse_cache_hits: AtomicU64,
     parse_cache_misses: AtomicU64,
 
+    // Tree-Sitter parse metrics
+    incremental_parses: AtomicU64,
+    full_parses: AtomicU64,
+
     // LSP request counters
     goto_definition_count: AtomicU64,
     hover_count: AtomicU64,
@@ -50,6 +62,9 @@ pub struct Metrics {
     // Timing histograms (operation name -> list of durations in microseconds)
     operation_timings: DashMap<String, Vec<u64>>,
 
+    // Most recent LSP requests, oldest first
+    recent_requests: Mutex<VecDeque<RequestLatency>>,
+
     // Workspace stats
     workspace_index_count: AtomicUsize,
     total_files_indexed: AtomicUsize,
@@ -57,6 +72,7 @@ pub struct Metrics {
     // Error counters
     parse_errors: AtomicU64,
     validation_errors: AtomicU64,
+    document_desyncs: AtomicU64,
 }
 
 impl Metrics {
@@ -65,16 +81,20 @@ impl Metrics {
         Self {
             parse_cache_hits: AtomicU64::new(0),
             parse_cache_misses: AtomicU64::new(0),
+            incremental_parses: AtomicU64::new(0),
+            full_parses: AtomicU64::new(0),
             goto_definition_count: AtomicU64::new(0),
             hover_count: AtomicU64::new(0),
             references_count: AtomicU64::new(0),
             rename_count: AtomicU64::new(0),
             document_symbol_count: AtomicU64::new(0),
             operation_timings: DashMap::new(),
+            recent_requests: Mutex::new(VecDeque::with_capacity(RECENT_REQUESTS)),
             workspace_index_count: AtomicUsize::new(0),
             total_files_indexed: AtomicUsize::new(0),
             parse_errors: AtomicU64::new(0),
             validation_errors: AtomicU64::new(0),
+            document_desyncs: AtomicU64::new(0),
         }
     }
 
@@ -101,6 +121,29 @@ impl Metrics {
         }
     }
 
+    /// Records a parse that reused the previous tree
+    pub fn record_incremental_parse(&self) {
+        self.incremental_parses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records a parse from scratch
+    pub fn record_full_parse(&self) {
+        self.full_parses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Gets the fraction of parses that were incremental (0.0 to 1.0)
+    pub fn incremental_parse_rate(&self) -> f64 {
+        let incremental = self.incremental_parses.load(Ordering::Relaxed);
+        let full = self.full_parses.load(Ordering::Relaxed);
+        let total = incremental + full;
+
+        if total == 0 {
+            0.0
+        } else {
+            incremental as f64 / total as f64
+        }
+    }
+
     /// Records a goto-definition request
     pub fn record_goto_definition(&self) {
         self.goto_definition_count.fetch_add(1, Ordering::Relaxed);
@@ -140,6 +183,24 @@ impl Metrics {
             .push(micros);
     }
 
+    /// Records a request answered after `duration`, keeping the last [`RECENT_REQUESTS`]
+    pub fn record_request(&self, method: &str, duration: Duration) {
+        let mut recent = self.recent_requests.lock();
+        if recent.len() == RECENT_REQUESTS {
+            recent.pop_front();
+        }
+        recent.push_back(RequestLatency {
+            method: method.to_string(),
+            micros: duration.as_micros() as u64,
+            finished: Instant::now(),
+        });
+    }
+
+    /// Gets the most recent requests, oldest first
+    pub fn recent_requests(&self) -> Vec<RequestLatency> {
+        self.recent_requests.lock().iter().cloned().collect()
+    }
+
     /// Records workspace indexing completion
     pub fn record_workspace_index(&self, file_count: usize) {
         self.workspace_index_count.fetch_add(1, Ordering::Relaxed);
@@ -156,6 +217,11 @@ impl Metrics {
         self.validation_errors.fetch_add(1, Ordering::Relaxed);
     }
 
+    /// Records a change that did not fit the document's text
+    pub fn record_document_desync(&self) {
+        self.document_desyncs.fetch_add(1, Ordering::Relaxed);
+    }
+
     /// Gets summary statistics for an operation
     pub fn operation_stats(&self, operation: &str) -> Option<OperationStats> {
         self.operation_timings.get(operation).map(|timings| {
@@ -200,6 +266,9 @@ impl Metrics {
             parse_cache_hits: self.parse_cache_hits.load(Ordering::Relaxed),
             parse_cache_misses: self.parse_cache_misses.load(Ordering::Relaxed),
             parse_cache_hit_rate: self.parse_cache_hit_rate(),
+            incremental_parses: self.incremental_parses.load(Ordering::Relaxed),
+            full_parses: self.full_parses.load(Ordering::Relaxed),
+            incremental_parse_rate: self.incremental_parse_rate(),
             goto_definition_count: self.goto_definition_count.load(Ordering::Relaxed),
             hover_count: self.hover_count.load(Ordering::Relaxed),
             references_count: self.references_count.load(Ordering::Relaxed),
@@ -209,6 +278,7 @@ impl Metrics {
             total_files_indexed: self.total_files_indexed.load(Ordering::Relaxed),
             parse_errors: self.parse_errors.load(Ordering::Relaxed),
             validation_errors: self.validation_errors.load(Ordering::Relaxed),
+            document_desyncs: self.document_desyncs.load(Ordering::Relaxed),
         }
     }
 
@@ -216,16 +286,20 @@ impl Metrics {
     pub fn reset(&self) {
         self.parse_cache_hits.store(0, Ordering::Relaxed);
         self.parse_cache_misses.store(0, Ordering::Relaxed);
+        self.incremental_parses.store(0, Ordering::Relaxed);
+        self.full_parses.store(0, Ordering::Relaxed);
         self.goto_definition_count.store(0, Ordering::Relaxed);
         self.hover_count.store(0, Ordering::Relaxed);
         self.references_count.store(0, Ordering::Relaxed);
         self.rename_count.store(0, Ordering::Relaxed);
         self.document_symbol_count.store(0, Ordering::Relaxed);
         self.operation_timings.clear();
+        self.recent_requests.lock().clear();
         self.workspace_index_count.store(0, Ordering::Relaxed);
         self.total_files_indexed.store(0, Ordering::Relaxed);
         self.parse_errors.store(0, Ordering::Relaxed);
         self.validation_errors.store(0, Ordering::Relaxed);
+        self.document_desyncs.store(0, Ordering::Relaxed);
     }
 }
 
@@ -247,12 +321,24 @@ pub struct OperationStats {
     pub p99_micros: u64,
 }
 
+/// A request and how long the server took to answer it
+#[derive(Debug, Clone)]
+pub struct RequestLatency {
+    pub method: String,
+    pub micros: u64,
+    /// When the response was sent
+    pub finished: Instant,
+}
+
 /// Summary of all metrics
 #[derive(Debug, Clone)]
 pub struct MetricsSummary {
     pub parse_cache_hits: u64,
     pub parse_cache_misses: u64,
     pub parse_cache_hit_rate: f64,
+    pub incremental_parses: u64,
+    pub full_parses: u64,
+    pub incremental_parse_rate: f64,
     pub goto_definition_count: u64,
     pub hover_count: u64,
     pub references_count: u64,
@@ -262,6 +348,7 @@ pub struct MetricsSummary {
     pub total_files_indexed: usize,
     pub parse_errors: u64,
     pub validation_errors: u64,
+    pub document_desyncs: u64,
 }
 
 /// RAII guard for automatic timing measurement
@@ -320,6 +407,23 @@ mod tests {
         assert_eq!(m.parse_cache_hit_rate(), 2.0 / 3.0);
     }
 
+    #[test]
+    fn test_parse_kind_metrics() {
+        let m = Metrics::new();
+
+        assert_eq!(m.incremental_parse_rate(), 0.0);
+
+        m.record_full_parse();
+        m.record_incremental_parse();
+        m.record_incremental_parse();
+        m.record_incremental_parse();
+
+        let summary = m.summary();
+        assert_eq!(summary.full_parses, 1);
+        assert_eq!(summary.incremental_parses, 3);
+        assert_eq!(summary.incremental_parse_rate, 0.75);
+    }
+
     #[test]
     fn test_request_counters() {
         let m = Metrics::new();
@@ -363,6 +467,20 @@ mod tests {
         assert!(stats.min_micros >= 10_000); // At least 10ms
     }
 
+    #[test]
+    fn test_recent_requests() {
+        let m = Metrics::new();
+
+        for i in 0..RECENT_REQUESTS + 2 {
+            m.record_request("textDocument/hover", Duration::from_micros(i as u64));
+        }
+
+        let recent = m.recent_requests();
+        assert_eq!(recent.len(), RECENT_REQUESTS);
+        assert_eq!(recent[0].micros, 2);
+        assert_eq!(recent[RECENT_REQUESTS - 1].method, "textDocument/hover");
+    }
+
     #[test]
     fn test_reset() {
         let m = Metrics::new();
@@ -1,9 +1,11 @@
 //! Parser modules for different languages
 
+#[cfg(feature = "metta")]
 pub mod metta_parser;
 pub mod parse_cache;
 pub mod position_utils;
 pub mod rholang;
 
+#[cfg(feature = "metta")]
 pub use metta_parser::MettaParser;
 pub use parse_cache::ParseCache;
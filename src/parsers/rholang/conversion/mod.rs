@@ -20,9 +20,10 @@ use crate::ir::rholang_node::{
 use crate::ir::semantic_node::SemanticNode;
 use crate::parsers::position_utils::create_node_base_from_absolute;
 
+use super::incremental;
 use super::helpers::{
     collect_named_descendants, collect_patterns, collect_linear_binds,
-    is_comment, safe_byte_slice,
+    is_comment, safe_byte_slice, safe_cow_slice, CowRopeSlice,
 };
 
 // ==============================================================================
@@ -99,7 +100,19 @@ fn is_par_node(node: &Arc<RholangNode>) -> bool {
 }
 
 /// Converts Tree-Sitter nodes to IR nodes with accurate relative positions.
+///
+/// During `parse_to_document_ir_reusing`, subtrees unchanged since the
+/// previous conversion are taken from it instead (see `super::incremental`).
 pub(crate) fn convert_ts_node_to_ir(ts_node: TSNode, rope: &Rope, prev_end: Position) -> (Arc<RholangNode>, Position) {
+    if let Some(reused) = incremental::reuse(ts_node) {
+        return reused;
+    }
+    let (node, end) = convert_fresh(ts_node, rope, prev_end);
+    incremental::record(ts_node, &node, end);
+    (node, end)
+}
+
+fn convert_fresh(ts_node: TSNode, rope: &Rope, prev_end: Position) -> (Arc<RholangNode>, Position) {
     // Optimization: Cache Tree-Sitter position method results to avoid redundant calls
     // Each call involves boundary checks and UTF-8 validation (~50-100 CPU cycles)
     // This reduces 6 method calls per node to 4 method calls (40-50% reduction)
@@ -661,7 +674,7 @@ pub(crate) fn convert_ts_node_to_ir(ts_node: TSNode, rope: &Rope, prev_end: Posi
                 debug!("Block node: start_byte={}, end_byte={}, length={}, ts_node.text='{}'",
                        ts_node.start_byte(), ts_node.end_byte(),
                        ts_node.end_byte() - ts_node.start_byte(),
-                       ts_node.utf8_text(rope.to_string().as_bytes()).unwrap_or("<error>").chars().take(50).collect::<String>());
+                       safe_cow_slice(rope, ts_node.start_byte(), ts_node.end_byte()).chars().take(50).collect::<String>());
             }
             // A block contains '{', multiple children (including comments), and '}'
             // Collect all named children and reduce them into a Par tree (like source_file)
@@ -780,7 +793,7 @@ pub(crate) fn convert_ts_node_to_ir(ts_node: TSNode, rope: &Rope, prev_end: Posi
             let receiver_ts = ts_node.child_by_field_name("receiver").expect("Method node must have a receiver");
             let (receiver, _receiver_end) = convert_ts_node_to_ir(receiver_ts, rope, absolute_start);
             let name_ts = ts_node.child_by_field_name("name").expect("Method node must have a name");
-            let name = rope.byte_slice(name_ts.start_byte()..name_ts.end_byte()).to_string();
+            let name = safe_byte_slice(rope, name_ts.start_byte(), name_ts.end_byte());
             let name_end = Position {
                 row: name_ts.end_position().row,
                 column: name_ts.end_position().column,
@@ -820,8 +833,8 @@ pub(crate) fn convert_ts_node_to_ir(ts_node: TSNode, rope: &Rope, prev_end: Posi
         }
         "var_ref" => {
             let kind_ts = ts_node.child_by_field_name("kind").expect("VarRef node must have a kind");
-            let kind_text = safe_byte_slice(rope, kind_ts.start_byte(), kind_ts.end_byte());
-            let kind = match kind_text.as_str() {
+            let kind_text = safe_cow_slice(rope, kind_ts.start_byte(), kind_ts.end_byte());
+            let kind = match kind_text.as_ref() {
                 "=" => RholangVarRefKind::Bind,
                 "=*" => RholangVarRefKind::Unforgeable,
                 kind => {
@@ -842,8 +855,8 @@ pub(crate) fn convert_ts_node_to_ir(ts_node: TSNode, rope: &Rope, prev_end: Posi
             convert_ts_node_to_ir(child, rope, prev_end)
         }
         "bool_literal" => {
-            let slice_str = safe_byte_slice(rope, ts_node.start_byte(), ts_node.end_byte());
-            let value = slice_str == "true";
+            let value = CowRopeSlice::new(rope, ts_node.start_byte(), ts_node.end_byte())
+                .is_some_and(|slice| slice == "true");
             let node = Arc::new(RholangNode::BoolLiteral { base, value, metadata });
             (node, absolute_end)
         }
@@ -861,7 +874,7 @@ pub(crate) fn convert_ts_node_to_ir(ts_node: TSNode, rope: &Rope, prev_end: Posi
                 return (node, absolute_end);
             }
 
-            let slice_str = safe_byte_slice(rope, start_byte, end_byte);
+            let slice_str = safe_cow_slice(rope, start_byte, end_byte);
             // Validate that the string contains only valid integer characters
             let is_valid = slice_str.chars().all(|c| c.is_ascii_digit() || c == '-');
             if !is_valid {
@@ -884,9 +897,12 @@ pub(crate) fn convert_ts_node_to_ir(ts_node: TSNode, rope: &Rope, prev_end: Posi
             let inner_start = ts_node.start_byte() + 1;
             let inner_end = ts_node.end_byte() - 1;
             let value = if inner_end > inner_start {
-                let inner_slice = rope.byte_slice(inner_start..inner_end);
-                let inner_str = inner_slice.to_string();
-                inner_str.replace("\\\"", "\"").replace("\\\\", "\\")
+                let inner_str = safe_cow_slice(rope, inner_start, inner_end);
+                if inner_str.contains('\\') {
+                    inner_str.replace("\\\"", "\"").replace("\\\\", "\\")
+                } else {
+                    inner_str.into_owned()
+                }
             } else {
                 debug!("Invalid string literal at byte {}", absolute_start.byte);
                 String::new()
@@ -898,8 +914,7 @@ pub(crate) fn convert_ts_node_to_ir(ts_node: TSNode, rope: &Rope, prev_end: Posi
             let inner_start = ts_node.start_byte() + 1;
             let inner_end = ts_node.end_byte() - 1;
             let value = if inner_end > inner_start {
-                let inner_slice = rope.byte_slice(inner_start..inner_end);
-                inner_slice.to_string()
+                safe_byte_slice(rope, inner_start, inner_end)
             } else {
                 warn!("Invalid URI literal at byte {}", absolute_start.byte);
                 String::new()
@@ -10,33 +10,14 @@ use rpds::Vector;
 use archery::ArcK;
 use ropey::Rope;
 
+pub use rholang_ir_core::rope::CowRopeSlice;
+pub(crate) use rholang_ir_core::rope::{safe_byte_slice, safe_cow_slice};
+
 use crate::ir::rholang_node::{
     RholangNode, NodeBase, Position,
 };
 use super::conversion::convert_ts_node_to_ir;
 
-/// Safely slice a rope by byte range, returning empty string on invalid range
-///
-/// # Arguments
-/// * `rope` - The source code rope
-/// * `start` - Starting byte offset
-/// * `end` - Ending byte offset
-///
-/// # Returns
-/// The sliced string, or empty string if the range is invalid
-pub(crate) fn safe_byte_slice(rope: &Rope, start: usize, end: usize) -> String {
-    if end > rope.len_bytes() || start > end {
-        warn!(
-            "Invalid byte range {}-{} (rope len={})",
-            start,
-            end,
-            rope.len_bytes()
-        );
-        return String::new();
-    }
-    rope.byte_slice(start..end).to_string()
-}
-
 /// Collect named descendant nodes, updating prev_end sequentially
 ///
 /// This function iterates through all named children of a node, converts them to IR,
@@ -0,0 +1,385 @@
+//! Incremental IR conversion
+//!
+//! After [`update_tree`](super::parsing::update_tree) reparses an edited
+//! document, most of its syntax tree is unchanged, and so is the IR converted
+//! from it. [`DirtyRanges`] records the bytes each edit touched and the ranges
+//! where Tree-Sitter's `changed_ranges` found a different syntactic structure;
+//! [`parse_to_document_ir_reusing`] then converts the new tree, taking the
+//! `Arc<RholangNode>` of every unchanged subtree from the [`ConversionMemo`] of
+//! the previous conversion instead of converting it again.
+//!
+//! IR positions are absolute, so a subtree is reused only where the edits
+//! before it leave its position unchanged: everything before the first edit,
+//! and everything after edits that keep byte lengths and line breaks (such as
+//! typing over an identifier or a number).
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::sync::Arc;
+
+use ropey::Rope;
+use tree_sitter::{InputEdit, Node as TSNode, Tree};
+
+use crate::ir::rholang_node::{Position, RholangNode};
+use crate::ir::DocumentIR;
+
+use super::parsing::parse_to_document_ir;
+
+/// The bytes of a document changed by a series of edits, in the coordinates
+/// of the edited text
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DirtyRanges {
+    /// Sorted, disjoint ranges, each with the bytes its edits added (negative if removed)
+    ranges: Vec<(Range<usize>, isize)>,
+    /// The whole text was replaced or reparsed from scratch
+    everything: bool,
+}
+
+impl DirtyRanges {
+    /// Ranges covering the whole text.
+    pub fn everything() -> Self {
+        DirtyRanges { ranges: Vec::new(), everything: true }
+    }
+
+    pub fn mark_everything(&mut self) {
+        self.ranges.clear();
+        self.everything = true;
+    }
+
+    pub fn is_everything(&self) -> bool {
+        self.everything
+    }
+
+    /// Records `edit`, given in the coordinates of the text before it.
+    pub fn record_edit(&mut self, edit: &InputEdit) {
+        if self.everything {
+            return;
+        }
+        let delta = edit.new_end_byte as isize - edit.old_end_byte as isize;
+        let shift = |offset: usize| (offset as isize + delta) as usize;
+        for (range, _) in &mut self.ranges {
+            let start = if range.start < edit.start_byte {
+                range.start
+            } else if range.start >= edit.old_end_byte {
+                shift(range.start)
+            } else {
+                edit.start_byte
+            };
+            let end = if range.end <= edit.start_byte {
+                range.end
+            } else if range.end >= edit.old_end_byte {
+                shift(range.end)
+            } else {
+                edit.new_end_byte
+            };
+            *range = start..end;
+        }
+        self.insert(edit.start_byte..edit.new_end_byte, delta);
+    }
+
+    /// Records ranges of the edited text whose syntactic structure changed.
+    pub fn record_changed(&mut self, changed: impl IntoIterator<Item = tree_sitter::Range>) {
+        if self.everything {
+            return;
+        }
+        for range in changed {
+            self.insert(range.start_byte..range.end_byte, 0);
+        }
+    }
+
+    /// Adds `range`, merging the ranges it overlaps or touches.
+    fn insert(&mut self, range: Range<usize>, delta: isize) {
+        let mut merged = (range, delta);
+        self.ranges.retain(|(other, other_delta)| {
+            let touches = other.start <= merged.0.end && merged.0.start <= other.end;
+            if touches {
+                merged = (merged.0.start.min(other.start)..merged.0.end.max(other.end), merged.1 + other_delta);
+            }
+            !touches
+        });
+        let at = self.ranges.partition_point(|(other, _)| other.start < merged.0.start);
+        self.ranges.insert(at, merged);
+    }
+
+    /// Whether `start..end` of the edited text was left alone, at the offsets
+    /// it had before the edits.
+    pub fn is_unchanged(&self, start: usize, end: usize) -> bool {
+        if self.everything {
+            return false;
+        }
+        let mut shift = 0;
+        for (range, delta) in &self.ranges {
+            if range.start > end {
+                break;
+            }
+            if range.end >= start {
+                return false;
+            }
+            shift += delta;
+        }
+        shift == 0
+    }
+}
+
+/// A converted subtree and the positions it was converted at
+#[derive(Debug, Clone)]
+struct MemoEntry {
+    start: Position,
+    end: Position,
+    node: Arc<RholangNode>,
+}
+
+/// The IR subtrees of one conversion, by the syntax node each was converted
+/// from: its start and end byte and its kind
+///
+/// Only nodes with named children are remembered; leaves are cheaper to
+/// convert again than to look up.
+#[derive(Debug, Clone, Default)]
+pub struct ConversionMemo {
+    nodes: BTreeMap<(usize, usize, u16), MemoEntry>,
+}
+
+impl ConversionMemo {
+    /// Number of subtrees remembered.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+}
+
+/// How much of a conversion was reused
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReuseStats {
+    /// Subtrees taken from the previous conversion
+    pub reused: usize,
+    /// Syntax nodes converted again
+    pub converted: usize,
+}
+
+/// The conversion running on this thread
+struct Session {
+    previous: Option<(Arc<ConversionMemo>, DirtyRanges)>,
+    memo: ConversionMemo,
+    stats: ReuseStats,
+}
+
+thread_local! {
+    static SESSION: RefCell<Option<Session>> = const { RefCell::new(None) };
+}
+
+/// Ends the session of this thread, also when the conversion panics.
+struct SessionGuard;
+
+impl Drop for SessionGuard {
+    fn drop(&mut self) {
+        SESSION.with(|session| session.borrow_mut().take());
+    }
+}
+
+fn start_position(ts_node: TSNode) -> Position {
+    let point = ts_node.start_position();
+    Position { row: point.row, column: point.column, byte: ts_node.start_byte() }
+}
+
+fn end_position(ts_node: TSNode) -> Position {
+    let point = ts_node.end_position();
+    Position { row: point.row, column: point.column, byte: ts_node.end_byte() }
+}
+
+fn key(ts_node: TSNode) -> (usize, usize, u16) {
+    (ts_node.start_byte(), ts_node.end_byte(), ts_node.kind_id())
+}
+
+/// The IR of `ts_node` from the previous conversion, if the session has one
+/// and the node is unchanged since.
+pub(super) fn reuse(ts_node: TSNode) -> Option<(Arc<RholangNode>, Position)> {
+    if ts_node.named_child_count() == 0 {
+        return None;
+    }
+    SESSION.with(|session| {
+        let mut session = session.borrow_mut();
+        let Session { previous: Some((previous, dirty)), memo, stats } = session.as_mut()? else {
+            return None;
+        };
+        let (start_byte, end_byte, _) = key(ts_node);
+        if !dirty.is_unchanged(start_byte, end_byte) {
+            return None;
+        }
+        let entry = previous.nodes.get(&key(ts_node))?;
+        if entry.start != start_position(ts_node) || entry.end != end_position(ts_node) {
+            return None;
+        }
+
+        // The subtrees inside stay reusable for the next conversion
+        let inner = previous.nodes.range((start_byte, 0, 0)..=(end_byte, usize::MAX, u16::MAX));
+        memo.nodes.extend(inner.filter(|((_, end, _), _)| *end <= end_byte).map(|(key, entry)| (*key, entry.clone())));
+        stats.reused += 1;
+        Some((entry.node.clone(), entry.end))
+    })
+}
+
+/// Remembers the IR `ts_node` was converted to, if a session is running.
+pub(super) fn record(ts_node: TSNode, node: &Arc<RholangNode>, end: Position) {
+    SESSION.with(|session| {
+        let mut session = session.borrow_mut();
+        let Some(session) = session.as_mut() else {
+            return;
+        };
+        session.stats.converted += 1;
+        // Nodes whose IR ends elsewhere than their syntax depend on their surroundings
+        let node_end = end_position(ts_node);
+        if ts_node.named_child_count() > 0 && end == node_end && end.byte == node_end.byte {
+            let entry = MemoEntry { start: start_position(ts_node), end, node: node.clone() };
+            session.memo.nodes.insert(key(ts_node), entry);
+        }
+    });
+}
+
+/// Converts `tree` to IR like [`parse_to_document_ir`], reusing the subtrees
+/// `previous` converted that `dirty` leaves unchanged.
+///
+/// `previous` must be the memo of the conversion of the text before the edits
+/// recorded in `dirty`. Returns the IR, the memo of this conversion for the
+/// next one, and how much was reused.
+pub fn parse_to_document_ir_reusing(
+    tree: &Tree,
+    rope: &Rope,
+    previous: Option<(Arc<ConversionMemo>, DirtyRanges)>,
+) -> (Arc<DocumentIR>, ConversionMemo, ReuseStats) {
+    let previous = previous.filter(|(_, dirty)| !dirty.is_everything());
+    SESSION.with(|session| {
+        *session.borrow_mut() = Some(Session { previous, memo: ConversionMemo::default(), stats: ReuseStats::default() });
+    });
+    let _guard = SessionGuard;
+
+    let document_ir = parse_to_document_ir(tree, rope);
+    let session = SESSION.with(|session| session.borrow_mut().take()).expect("conversion session ended early");
+    (document_ir, session.memo, session.stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ir::rholang_node::compute_absolute_positions;
+    use crate::parsers::rholang::{parse_code, update_tree};
+    use tree_sitter::Point;
+
+    fn edit(start: usize, old_end: usize, new_end: usize) -> InputEdit {
+        let point = |byte| Point { row: 0, column: byte };
+        InputEdit {
+            start_byte: start,
+            old_end_byte: old_end,
+            new_end_byte: new_end,
+            start_position: point(start),
+            old_end_position: point(old_end),
+            new_end_position: point(new_end),
+        }
+    }
+
+    #[test]
+    fn test_dirty_ranges() {
+        let mut dirty = DirtyRanges::default();
+        assert!(dirty.is_unchanged(0, 100));
+
+        // "0123456789" -> "01x456789": byte 2..4 replaced by one byte
+        dirty.record_edit(&edit(2, 4, 3));
+        assert!(dirty.is_unchanged(0, 1));
+        assert!(!dirty.is_unchanged(0, 2));
+        // After the edit everything moved one byte back
+        assert!(!dirty.is_unchanged(5, 8));
+
+        // An insertion restoring the length after it: "01xy456789"
+        dirty.record_edit(&edit(3, 3, 4));
+        assert_eq!(dirty.ranges, vec![(2..4, 0)]);
+        assert!(dirty.is_unchanged(5, 9));
+
+        dirty.record_changed([tree_sitter::Range {
+            start_byte: 6,
+            end_byte: 7,
+            start_point: Point { row: 0, column: 6 },
+            end_point: Point { row: 0, column: 7 },
+        }]);
+        assert!(!dirty.is_unchanged(5, 9));
+        assert!(dirty.is_unchanged(8, 9));
+
+        dirty.mark_everything();
+        assert!(!dirty.is_unchanged(0, 1));
+    }
+
+    fn ir_after_edit(
+        source: &str,
+        start: usize,
+        old_end: usize,
+        text: &str,
+    ) -> (Arc<RholangNode>, Arc<RholangNode>, ReuseStats, String) {
+        let tree = parse_code(source);
+        let (before, memo, stats) = parse_to_document_ir_reusing(&tree, &Rope::from_str(source), None);
+        assert_eq!(stats.reused, 0);
+
+        let edited = format!("{}{}{}", &source[..start], text, &source[old_end..]);
+        let mut dirty = DirtyRanges::default();
+        let point = |text: &str, byte: usize| {
+            let row = text[..byte].matches('\n').count();
+            Point { row, column: byte - text[..byte].rfind('\n').map_or(0, |at| at + 1) }
+        };
+        let input_edit = InputEdit {
+            start_byte: start,
+            old_end_byte: old_end,
+            new_end_byte: start + text.len(),
+            start_position: point(source, start),
+            old_end_position: point(source, old_end),
+            new_end_position: point(&edited, start + text.len()),
+        };
+        let (new_tree, _) = update_tree(&tree, &edited, &input_edit, &mut dirty);
+        let (after, _, stats) =
+            parse_to_document_ir_reusing(&new_tree, &Rope::from_str(&edited), Some((Arc::new(memo), dirty)));
+        (before.root.clone(), after.root.clone(), stats, edited)
+    }
+
+    fn contracts(root: &RholangNode) -> Vec<Arc<RholangNode>> {
+        match root {
+            RholangNode::Par { processes: Some(processes), .. } => processes.iter().cloned().collect(),
+            RholangNode::Par { left: Some(left), right: Some(right), .. } => vec![left.clone(), right.clone()],
+            _ => panic!("expected a parallel composition"),
+        }
+    }
+
+    #[test]
+    fn test_unchanged_subtrees_are_shared() {
+        let source = "contract a(@x) = { x!(1) } |\ncontract b(@y) = { y!(2) } |\ncontract c(@z) = { z!(3) }\n";
+        // "y!(2)" -> "y!(7)": same length, so all three contracts keep their positions
+        let at = source.find("2)").unwrap();
+        let (before, after, stats, edited) = ir_after_edit(source, at, at + 1, "7");
+
+        // The IR has the spans a full conversion produces
+        let full = parse_to_document_ir(&parse_code(&edited), &Rope::from_str(&edited)).root.clone();
+        let spans = |node: &Arc<RholangNode>| {
+            let mut spans: Vec<_> =
+                compute_absolute_positions(node).into_values().map(|(start, end)| (start.byte, end.byte)).collect();
+            spans.sort();
+            spans
+        };
+        assert_eq!(spans(&after), spans(&full));
+
+        let (before, after) = (contracts(&before), contracts(&after));
+        assert!(Arc::ptr_eq(&before[0], &after[0]));
+        assert!(!Arc::ptr_eq(&before[1], &after[1]));
+        assert!(Arc::ptr_eq(&before[2], &after[2]));
+        assert!(stats.reused >= 2);
+    }
+
+    #[test]
+    fn test_subtrees_after_a_shifting_edit_are_converted_again() {
+        let source = "contract a(@x) = { x!(1) } |\ncontract b(@y) = { y!(2) }\n";
+        let at = source.find("1)").unwrap();
+        let (before, after, _, _) = ir_after_edit(source, at, at + 1, "100");
+
+        let (before, after) = (contracts(&before), contracts(&after));
+        assert!(!Arc::ptr_eq(&before[1], &after[1]));
+        assert_eq!(after[1].base().start().byte, before[1].base().start().byte + 2);
+    }
+}
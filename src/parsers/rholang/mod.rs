@@ -8,6 +8,7 @@
 //! - `parsing`: Public API for parsing Rholang code using Tree-Sitter
 //! - `helpers`: Utility functions for node collection and processing
 //! - `conversion`: CST to IR conversion logic
+//! - `incremental`: Reuse of unchanged IR subtrees after an edit
 //!
 //! # Usage
 //!
@@ -24,9 +25,13 @@
 pub mod parsing;
 pub mod helpers;
 pub mod conversion;
+pub mod incremental;
 
 // Re-export public API for backward compatibility
-pub use parsing::{parse_code, parse_to_ir, parse_to_document_ir, update_tree};
+pub use parsing::{parse_cache_stats, parse_code, parse_to_ir, parse_to_document_ir, update_tree, ParseKind};
+#[cfg(test)]
+pub use parsing::parse_with_positions;
+pub use incremental::{parse_to_document_ir_reusing, ConversionMemo, DirtyRanges, ReuseStats};
 
 // Note: helpers and conversion are internal implementation details
 // and are not re-exported at the module level
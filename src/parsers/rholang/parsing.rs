@@ -17,9 +17,12 @@ use once_cell::sync::Lazy;
 
 use crate::ir::rholang_node::{RholangNode, Position};
 use crate::ir::{CommentNode, DocumentIR};
+use crate::metrics::metrics;
 use crate::parsers::ParseCache;
+use crate::parsers::parse_cache::CacheStats;
 use super::conversion::convert_ts_node_to_ir;
 use super::helpers::walk_for_comments;
+use super::incremental::DirtyRanges;
 
 /// Global parse tree cache (shared across all parse operations)
 ///
@@ -61,6 +64,7 @@ pub fn parse_code(code: &str) -> Tree {
     let tree = parser
         .parse(code, None)
         .expect("Failed to parse Rholang code");
+    metrics().record_full_parse();
 
     // Store in cache for future use
     PARSE_CACHE.insert(code.to_string(), tree.clone());
@@ -68,6 +72,11 @@ pub fn parse_code(code: &str) -> Tree {
     tree
 }
 
+/// Size and capacity of the global parse tree cache
+pub fn parse_cache_stats() -> CacheStats {
+    PARSE_CACHE.stats()
+}
+
 /// Collect all comments from the Tree-Sitter tree
 ///
 /// This function walks the entire parse tree, extracts all comment nodes,
@@ -174,45 +183,65 @@ pub fn parse_to_ir(tree: &Tree, rope: &Rope) -> Arc<RholangNode> {
     parse_to_document_ir(tree, rope).root.clone()
 }
 
-/// Update a syntax tree incrementally based on text changes
+/// Parses `source` to IR along with the absolute positions of its nodes, the
+/// starting point of most tests of IR-based features.
+#[cfg(test)]
+pub fn parse_with_positions(source: &str) -> (Arc<RholangNode>, std::collections::HashMap<usize, (Position, Position)>) {
+    let rope = Rope::from_str(source);
+    let ir = parse_to_document_ir(&parse_code(source), &rope).root.clone();
+    let positions = crate::ir::rholang_node::compute_absolute_positions(&ir);
+    (ir, positions)
+}
+
+/// How a syntax tree was produced
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseKind {
+    /// The previous tree was edited and its unchanged subtrees reused
+    Incremental,
+    /// The whole text was parsed from scratch
+    Full,
+}
+
+/// Update a syntax tree incrementally based on a text edit
 ///
 /// This enables efficient re-parsing by reusing unchanged portions of the tree.
+/// `edit` must describe the change from the text `tree` was parsed from to
+/// `new_text`, with positions as Tree-Sitter points (row, byte column); a
+/// wrong edit makes Tree-Sitter reuse stale subtrees.
+///
+/// The edit and the ranges whose syntax changed (`Tree::changed_ranges`) are
+/// recorded in `dirty`, so that the IR conversion of the new tree can reuse
+/// the unchanged subtrees of the previous one (see `super::incremental`).
 ///
 /// # Arguments
-/// * `tree` - The existing syntax tree
+/// * `tree` - The existing syntax tree (left unmodified)
 /// * `new_text` - The updated source code
-/// * `start_byte` - Byte offset where the edit starts
-/// * `old_end_byte` - Byte offset where the edit ended in the old text
-/// * `new_length` - Length of the new text inserted
+/// * `edit` - The edit that turned the old text into `new_text`
+/// * `dirty` - What the edits so far changed, updated with this one
 ///
 /// # Returns
-/// A new Tree reflecting the incremental edit, or a full parse if incremental fails
-pub fn update_tree(
-    tree: &Tree,
-    new_text: &str,
-    start_byte: usize,
-    old_end_byte: usize,
-    new_length: usize,
-) -> Tree {
+/// The new tree and whether it was parsed incrementally; falls back to a full
+/// parse if incremental parsing fails
+pub fn update_tree(tree: &Tree, new_text: &str, edit: &InputEdit, dirty: &mut DirtyRanges) -> (Tree, ParseKind) {
     let mut parser = Parser::new();
     parser
         .set_language(&rholang_tree_sitter::LANGUAGE.into())
         .expect("Failed to set Tree-Sitter language");
 
-    let edit = InputEdit {
-        start_byte,
-        old_end_byte,
-        new_end_byte: start_byte + new_length,
-        start_position: tree.root_node().start_position(),
-        old_end_position: tree.root_node().end_position(),
-        new_end_position: tree.root_node().end_position(),
-    };
-
-    let mut new_tree = tree.clone();
-    new_tree.edit(&edit);
-
-    parser.parse(new_text, Some(&new_tree)).unwrap_or_else(|| {
-        warn!("Incremental parse failed, performing full parse");
-        parse_code(new_text)
-    })
+    let mut edited_tree = tree.clone();
+    edited_tree.edit(edit);
+    dirty.record_edit(edit);
+
+    match parser.parse(new_text, Some(&edited_tree)) {
+        Some(new_tree) => {
+            metrics().record_incremental_parse();
+            dirty.record_changed(edited_tree.changed_ranges(&new_tree));
+            (new_tree, ParseKind::Incremental)
+        }
+        None => {
+            warn!("Incremental parse failed, performing full parse");
+            dirty.mark_everything();
+            (parse_code(new_text), ParseKind::Full)
+        }
+    }
 }
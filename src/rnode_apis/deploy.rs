@@ -0,0 +1 @@
+tonic::include_proto!("casper.v1");
@@ -1 +1,3 @@
+pub mod deploy;
 pub mod lsp;
+pub mod repl;
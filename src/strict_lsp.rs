@@ -0,0 +1,316 @@
+//! Strict LSP mode (`--strict-lsp`): check outgoing messages against the specification
+//!
+//! Serialization slips such as `"range": null` where the specification has
+//! an optional `range?: Range` are ignored by lenient clients but break
+//! picky ones (Eclipse LSP4J, Neovim). In strict mode every response,
+//! notification and request the server writes is validated against the
+//! LSP 3.17 structures in `schemas/lsp-3.17.json`:
+//!
+//! - responses by the method of the request they answer (`result`), or as a
+//!   `ResponseError` (`error`);
+//! - notifications and server requests by their method (`params`).
+//!
+//! Methods missing from the schema (custom `rholang/*` requests) are not
+//! checked. The schema uses a subset of JSON Schema: `type`, `enum`,
+//! `properties`, `required`, `additionalProperties`, `items`, `anyOf`,
+//! `minimum` and local `$ref`s. A property that is present must match its
+//! schema, so `null` is only accepted where the schema allows it.
+//!
+//! In `log` mode violations are logged as errors; in `fail` mode the first
+//! message with a violation also fails the write, closing the connection.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde_json::Value;
+use tracing::error;
+
+/// What to do about a message that violates the specification
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StrictLspMode {
+    /// Log the violations
+    Log,
+    /// Log the violations and fail the write
+    Fail,
+}
+
+impl StrictLspMode {
+    pub const NAMES: [&'static str; 2] = ["log", "fail"];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "log" => Some(StrictLspMode::Log),
+            "fail" => Some(StrictLspMode::Fail),
+            _ => None,
+        }
+    }
+}
+
+static SCHEMA: Lazy<Value> = Lazy::new(|| {
+    serde_json::from_str(include_str!("../schemas/lsp-3.17.json")).expect("LSP schema is valid JSON")
+});
+
+/// A part of a message that does not match the specification
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// JSON pointer into the message, e.g. `/result/0/range`
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Checks the messages of one connection against the specification
+#[derive(Debug, Clone)]
+pub struct SpecChecker {
+    mode: StrictLspMode,
+    /// Method of each unanswered client request, by JSON-RPC id
+    pending: Arc<Mutex<HashMap<String, String>>>,
+}
+
+impl SpecChecker {
+    pub fn new(mode: StrictLspMode) -> Self {
+        SpecChecker { mode, pending: Arc::new(Mutex::new(HashMap::new())) }
+    }
+
+    /// Notes an incoming message; requests are remembered to check their response.
+    pub fn incoming(&self, message: &Value) {
+        if let (Some(id), Some(method)) = (message.get("id"), message.get("method").and_then(Value::as_str)) {
+            self.pending.lock().insert(id.to_string(), method.to_string());
+        }
+    }
+
+    /// Checks an outgoing message, logging any violation.
+    ///
+    /// Fails in [`StrictLspMode::Fail`] if there was one.
+    pub fn outgoing(&self, message: &Value) -> io::Result<()> {
+        let (method, violations) = self.check_outgoing(message);
+        if violations.is_empty() {
+            return Ok(());
+        }
+        for violation in &violations {
+            error!("LSP spec violation in {}: {}", method, violation);
+        }
+        match self.mode {
+            StrictLspMode::Log => Ok(()),
+            StrictLspMode::Fail => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} violates the LSP specification: {}", method, violations[0]),
+            )),
+        }
+    }
+
+    /// The method an outgoing message belongs to, and its violations.
+    fn check_outgoing(&self, message: &Value) -> (String, Vec<Violation>) {
+        let mut violations = Vec::new();
+        if message.get("jsonrpc").and_then(Value::as_str) != Some("2.0") {
+            violations.push(Violation { path: "/jsonrpc".to_string(), message: "must be \"2.0\"".to_string() });
+        }
+
+        if let Some(method) = message.get("method").and_then(Value::as_str) {
+            if let Some(schema) = method_schema(method, "params") {
+                match message.get("params") {
+                    Some(params) => validate(params, schema, "/params", &mut violations),
+                    None => violations.push(Violation { path: "/params".to_string(), message: "is missing".to_string() }),
+                }
+            }
+            return (method.to_string(), violations);
+        }
+
+        let method = message
+            .get("id")
+            .and_then(|id| self.pending.lock().remove(&id.to_string()))
+            .unwrap_or_else(|| "response".to_string());
+        match (message.get("result"), message.get("error")) {
+            (Some(result), None) => {
+                if let Some(schema) = method_schema(&method, "result") {
+                    validate(result, schema, "/result", &mut violations);
+                }
+            }
+            (None, Some(error)) => validate(error, &SCHEMA["definitions"]["ResponseError"], "/error", &mut violations),
+            _ => violations.push(Violation {
+                path: String::new(),
+                message: "a response must have exactly one of `result` and `error`".to_string(),
+            }),
+        }
+        (method, violations)
+    }
+}
+
+/// Schema of the `part` (`params` or `result`) of `method`, if the method is known.
+fn method_schema(method: &str, part: &str) -> Option<&'static Value> {
+    SCHEMA["methods"].get(method)?.get(part)
+}
+
+/// Checks `value` against `schema`, adding a violation for each mismatch under `path`.
+pub fn validate(value: &Value, schema: &Value, path: &str, violations: &mut Vec<Violation>) {
+    let violation = |message: String| Violation { path: path.to_string(), message };
+
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        match resolve(reference) {
+            Some(target) => validate(value, target, path, violations),
+            None => violations.push(violation(format!("unknown schema reference {}", reference))),
+        }
+        return;
+    }
+
+    // A value matching no form is reported against the closest one: the form
+    // whose violations are all nested (its type matched), with the fewest.
+    if let Some(options) = schema.get("anyOf").and_then(Value::as_array) {
+        let mut closest: Option<Vec<Violation>> = None;
+        for option in options {
+            let mut option_violations = Vec::new();
+            validate(value, option, path, &mut option_violations);
+            if option_violations.is_empty() {
+                return;
+            }
+            let key = |found: &[Violation]| (found.iter().any(|v| v.path == path), found.len());
+            if closest.as_deref().is_none_or(|best| key(&option_violations) < key(best)) {
+                closest = Some(option_violations);
+            }
+        }
+        match closest {
+            Some(found) if !found.iter().any(|v| v.path == path) => violations.extend(found),
+            _ => violations.push(violation(format!("{} matches none of the allowed forms", describe(value)))),
+        }
+        return;
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            violations.push(violation(format!("{} is not one of {}", value, Value::Array(allowed.clone()))));
+        }
+        return;
+    }
+
+    if let Some(types) = schema.get("type") {
+        let types: Vec<&str> = match types {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !types.iter().any(|name| has_type(value, name)) {
+            violations.push(violation(format!("expected {}, found {}", types.join(" or "), describe(value))));
+            return;
+        }
+    }
+
+    if let (Some(minimum), Some(number)) = (schema.get("minimum").and_then(Value::as_f64), value.as_f64()) {
+        if number < minimum {
+            violations.push(violation(format!("{} is below the minimum {}", number, minimum)));
+        }
+    }
+
+    if let Value::Object(object) = value {
+        let properties = schema.get("properties").and_then(Value::as_object);
+        for required in schema.get("required").and_then(Value::as_array).into_iter().flatten() {
+            if let Some(name) = required.as_str() {
+                if !object.contains_key(name) {
+                    violations.push(violation(format!("required property `{}` is missing", name)));
+                }
+            }
+        }
+        for (name, property) in object {
+            let property_schema = properties
+                .and_then(|properties| properties.get(name))
+                .or_else(|| schema.get("additionalProperties"));
+            if let Some(property_schema) = property_schema {
+                validate(property, property_schema, &format!("{}/{}", path, name), violations);
+            }
+        }
+    }
+
+    if let (Value::Array(items), Some(item_schema)) = (value, schema.get("items")) {
+        for (i, item) in items.iter().enumerate() {
+            validate(item, item_schema, &format!("{}/{}", path, i), violations);
+        }
+    }
+}
+
+fn resolve(reference: &str) -> Option<&'static Value> {
+    let name = reference.strip_prefix("#/definitions/")?;
+    SCHEMA["definitions"].get(name)
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "string" => value.is_string(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        _ => false,
+    }
+}
+
+fn describe(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn violations(checker: &SpecChecker, message: Value) -> Vec<Violation> {
+        checker.check_outgoing(&message).1
+    }
+
+    #[test]
+    fn test_responses_are_checked_by_request_method() {
+        let checker = SpecChecker::new(StrictLspMode::Log);
+        checker.incoming(&json!({"jsonrpc": "2.0", "id": 1, "method": "textDocument/hover", "params": {}}));
+        let hover = json!({"jsonrpc": "2.0", "id": 1, "result": {"contents": {"kind": "markdown", "value": "x"}, "range": null}});
+        assert_eq!(
+            violations(&checker, hover),
+            vec![Violation { path: "/result/range".to_string(), message: "expected object, found null".to_string() }]
+        );
+
+        checker.incoming(&json!({"jsonrpc": "2.0", "id": 2, "method": "textDocument/hover", "params": {}}));
+        assert!(violations(&checker, json!({"jsonrpc": "2.0", "id": 2, "result": null})).is_empty());
+
+        // Custom requests are not checked
+        checker.incoming(&json!({"jsonrpc": "2.0", "id": 3, "method": "rholang/health"}));
+        assert!(violations(&checker, json!({"jsonrpc": "2.0", "id": 3, "result": {"validator": null}})).is_empty());
+    }
+
+    #[test]
+    fn test_notifications_and_fail_mode() {
+        let checker = SpecChecker::new(StrictLspMode::Fail);
+        let diagnostics = json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": {"uri": "file:///a.rho", "diagnostics": [
+                {"range": {"start": {"line": 0, "character": 0}, "end": {"line": 0, "character": -1}}, "severity": 5}
+            ]}
+        });
+        let found: Vec<String> = violations(&checker, diagnostics.clone()).iter().map(ToString::to_string).collect();
+        assert_eq!(
+            found,
+            vec![
+                "/params/diagnostics/0: required property `message` is missing",
+                "/params/diagnostics/0/range/end/character: -1 is below the minimum 0",
+                "/params/diagnostics/0/severity: 5 is not one of [1,2,3,4]",
+            ]
+        );
+        assert_eq!(checker.outgoing(&diagnostics).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert!(SpecChecker::new(StrictLspMode::Log).outgoing(&diagnostics).is_ok());
+    }
+}
@@ -0,0 +1,94 @@
+//! LSP base protocol framing (`Content-Length` headers + JSON body)
+
+use std::io;
+
+use serde_json::Value;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Reads one framed JSON-RPC message.
+///
+/// Returns `Ok(None)` on a clean end of stream between messages. Headers
+/// other than `Content-Length` (e.g. `Content-Type`) are ignored.
+pub async fn read_message<R>(reader: &mut R) -> io::Result<Option<Value>>
+where
+    R: AsyncBufRead + Unpin,
+{
+    let mut content_length: Option<usize> = None;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).await? == 0 {
+            return match content_length {
+                None => Ok(None),
+                Some(_) => Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Stream ended inside message headers")),
+            };
+        }
+
+        let header = line.trim_end();
+        if header.is_empty() {
+            if content_length.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("Content-Length") {
+                let length = value.trim().parse().map_err(|e| {
+                    io::Error::new(io::ErrorKind::InvalidData, format!("Invalid Content-Length '{}': {}", value.trim(), e))
+                })?;
+                content_length = Some(length);
+            }
+        }
+    }
+
+    let mut body = vec![0u8; content_length.unwrap_or(0)];
+    reader.read_exact(&mut body).await?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Writes one framed JSON-RPC message and flushes the writer.
+pub async fn write_message<W>(writer: &mut W, message: &Value) -> io::Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    let body = serde_json::to_vec(message)?;
+    writer
+        .write_all(format!("Content-Length: {}\r\n\r\n", body.len()).as_bytes())
+        .await?;
+    writer.write_all(&body).await?;
+    writer.flush().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tokio::io::BufReader;
+
+    #[tokio::test]
+    async fn test_round_trip() {
+        let mut buffer = Vec::new();
+        write_message(&mut buffer, &json!({"jsonrpc": "2.0", "method": "initialized", "params": {}})).await.unwrap();
+        write_message(&mut buffer, &json!({"jsonrpc": "2.0", "id": 1, "result": null})).await.unwrap();
+
+        let mut reader = BufReader::new(buffer.as_slice());
+        assert_eq!(read_message(&mut reader).await.unwrap().unwrap()["method"], "initialized");
+        assert_eq!(read_message(&mut reader).await.unwrap().unwrap()["id"], 1);
+        assert!(read_message(&mut reader).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_extra_headers_and_truncation() {
+        let framed = b"Content-Type: application/vscode-jsonrpc; charset=utf-8\r\ncontent-length: 2\r\n\r\n{}";
+        let mut reader = BufReader::new(&framed[..]);
+        assert_eq!(read_message(&mut reader).await.unwrap(), Some(json!({})));
+
+        let mut truncated = BufReader::new(&b"Content-Length: 10\r\n"[..]);
+        assert_eq!(
+            read_message(&mut truncated).await.unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+    }
+}
@@ -0,0 +1,291 @@
+//! Supervisor mode (`--supervise`): restart the server after a crash
+//!
+//! With `--supervise --stdio`, the process started by the editor does not
+//! serve LSP itself. It spawns a worker (the same binary, same arguments,
+//! without `--supervise`) and proxies framed JSON-RPC messages between the
+//! editor on stdin/stdout and the worker's pipes. Along the way it records the
+//! editor session in a [`SessionState`]: the `initialize` request, open
+//! documents with their current text, and in-flight requests.
+//!
+//! When the worker exits unexpectedly (non-zero status or signal, before the
+//! editor asked for `shutdown`/`exit`), the supervisor:
+//!
+//! 1. answers the editor's in-flight requests with `RequestFailed`;
+//! 2. starts a new worker after a short backoff;
+//! 3. replays `initialize` (so the worker re-indexes the workspace from the
+//!    same root and workspace folders), `initialized`, and a `didOpen` per
+//!    open document, holding back editor messages until the replay is done;
+//! 4. tells the user with a `window/showMessage` warning.
+//!
+//! Restarts are rate-limited: after [`MAX_RESTARTS`] crashes within
+//! [`RESTART_WINDOW`] the supervisor gives up and exits with the worker's status.
+
+mod framing;
+mod session;
+
+use std::collections::VecDeque;
+use std::ffi::OsString;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Stdio};
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+use tokio::io::BufReader;
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::mpsc;
+use tracing::{debug, error, info, warn};
+
+pub use framing::{read_message, write_message};
+pub use session::{Replay, Route, SessionState};
+
+/// Command-line flag enabling supervisor mode
+pub const SUPERVISE_FLAG: &str = "--supervise";
+
+/// Crashes tolerated within `RESTART_WINDOW` before giving up
+pub const MAX_RESTARTS: usize = 5;
+/// Window over which crashes are counted
+pub const RESTART_WINDOW: Duration = Duration::from_secs(60);
+/// Delay before the first restart; doubles with each recent crash
+const INITIAL_BACKOFF: Duration = Duration::from_millis(250);
+/// Upper bound for the restart delay
+const MAX_BACKOFF: Duration = Duration::from_secs(5);
+
+/// `MessageType::WARNING` / `MessageType::ERROR`
+const MESSAGE_WARNING: u8 = 2;
+const MESSAGE_ERROR: u8 = 1;
+
+/// Inputs to the supervisor loop
+#[derive(Debug)]
+enum Event {
+    /// Message from the editor
+    Editor(Value),
+    /// The editor closed stdin
+    EditorClosed,
+    /// Message from the worker of the given generation
+    Worker(u64, Value),
+    /// The worker of the given generation closed stdout
+    WorkerClosed(u64),
+}
+
+/// Crash bookkeeping for the restart policy
+#[derive(Debug, Default)]
+pub struct RestartTracker {
+    crashes: VecDeque<Instant>,
+}
+
+impl RestartTracker {
+    /// Records a crash at `now`; returns `false` if the restart budget is exhausted.
+    pub fn record_crash(&mut self, now: Instant) -> bool {
+        while self.crashes.front().is_some_and(|&t| now.duration_since(t) > RESTART_WINDOW) {
+            self.crashes.pop_front();
+        }
+        self.crashes.push_back(now);
+        self.crashes.len() <= MAX_RESTARTS
+    }
+
+    /// Delay before restarting, growing with the number of recent crashes.
+    pub fn backoff(&self) -> Duration {
+        let exponent = self.crashes.len().saturating_sub(1).min(8) as u32;
+        (INITIAL_BACKOFF * 2u32.pow(exponent)).min(MAX_BACKOFF)
+    }
+}
+
+/// A running worker process
+struct Worker {
+    generation: u64,
+    child: Child,
+    stdin: ChildStdin,
+}
+
+impl Worker {
+    fn spawn(program: &Path, args: &[OsString], generation: u64, events: mpsc::UnboundedSender<Event>) -> io::Result<Self> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .kill_on_drop(true)
+            .spawn()?;
+        info!("Started worker generation {} (PID: {:?})", generation, child.id());
+
+        let stdin = child.stdin.take().expect("worker stdin is piped");
+        let stdout = child.stdout.take().expect("worker stdout is piped");
+        tokio::spawn(async move {
+            let mut reader = BufReader::new(stdout);
+            loop {
+                match read_message(&mut reader).await {
+                    Ok(Some(message)) => {
+                        if events.send(Event::Worker(generation, message)).is_err() {
+                            return;
+                        }
+                    }
+                    Ok(None) => break,
+                    Err(e) => {
+                        warn!("Failed to read from worker generation {}: {}", generation, e);
+                        break;
+                    }
+                }
+            }
+            let _ = events.send(Event::WorkerClosed(generation));
+        });
+
+        Ok(Worker { generation, child, stdin })
+    }
+
+    async fn send(&mut self, message: &Value) {
+        // A failed write means the worker died; `WorkerClosed` reports it
+        if let Err(e) = write_message(&mut self.stdin, message).await {
+            debug!("Failed to write to worker generation {}: {}", self.generation, e);
+        }
+    }
+
+    async fn wait(&mut self) -> io::Result<ExitStatus> {
+        self.child.wait().await
+    }
+}
+
+/// Runs the supervisor until the editor disconnects or the worker exits normally.
+///
+/// `worker_args` are passed to each worker; they must not contain
+/// [`SUPERVISE_FLAG`]. Returns the exit code for the supervisor process.
+pub async fn run(worker_args: Vec<OsString>) -> io::Result<i32> {
+    let program: PathBuf = std::env::current_exe()?;
+    let (events_tx, mut events_rx) = mpsc::unbounded_channel::<Event>();
+
+    let editor_events = events_tx.clone();
+    tokio::spawn(async move {
+        let mut reader = BufReader::new(tokio::io::stdin());
+        loop {
+            match read_message(&mut reader).await {
+                Ok(Some(message)) => {
+                    if editor_events.send(Event::Editor(message)).is_err() {
+                        return;
+                    }
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    error!("Failed to read from editor: {}", e);
+                    break;
+                }
+            }
+        }
+        let _ = editor_events.send(Event::EditorClosed);
+    });
+
+    let mut editor = tokio::io::stdout();
+    let mut session = SessionState::default();
+    let mut restarts = RestartTracker::default();
+    let mut worker = Worker::spawn(&program, &worker_args, 0, events_tx.clone())?;
+
+    // While a replayed `initialize` is outstanding: the rest of the replay and
+    // the editor messages received in the meantime
+    let mut pending_replay: Option<(Vec<Value>, Vec<Value>)> = None;
+
+    while let Some(event) = events_rx.recv().await {
+        match event {
+            Event::Editor(message) => {
+                if session.record_from_editor(&message) == Route::Drop {
+                    continue;
+                }
+                match &mut pending_replay {
+                    Some((_, held)) => held.push(message),
+                    None => worker.send(&message).await,
+                }
+            }
+            Event::EditorClosed => {
+                info!("Editor disconnected, stopping worker");
+                let _ = worker.child.kill().await;
+                return Ok(0);
+            }
+            Event::Worker(generation, message) if generation == worker.generation => {
+                match session.record_from_worker(&message) {
+                    Route::Forward => write_message(&mut editor, &message).await?,
+                    Route::Drop => {}
+                    Route::ReplayInitialized => {
+                        let (followups, held) = pending_replay.take().unwrap_or_default();
+                        info!(
+                            "Worker generation {} initialized; replaying {} message(s) and {} held editor message(s)",
+                            generation,
+                            followups.len(),
+                            held.len()
+                        );
+                        for message in followups.iter().chain(held.iter()) {
+                            worker.send(message).await;
+                        }
+                    }
+                }
+            }
+            Event::WorkerClosed(generation) if generation == worker.generation => {
+                let status = worker.wait().await?;
+                if session.shutdown_requested() || status.success() {
+                    info!("Worker exited with {}; supervisor exiting", status);
+                    return Ok(status.code().unwrap_or(0));
+                }
+
+                error!("Worker generation {} crashed ({})", generation, status);
+                for response in session.fail_pending_requests() {
+                    write_message(&mut editor, &response).await?;
+                }
+
+                if !restarts.record_crash(Instant::now()) {
+                    error!("Worker crashed {} times within {:?}; giving up", MAX_RESTARTS + 1, RESTART_WINDOW);
+                    let message = "Rholang language server crashed repeatedly and will not be restarted. See the server log for details.";
+                    write_message(&mut editor, &show_message(MESSAGE_ERROR, message)).await?;
+                    return Ok(status.code().unwrap_or(1));
+                }
+
+                tokio::time::sleep(restarts.backoff()).await;
+                worker = Worker::spawn(&program, &worker_args, generation + 1, events_tx.clone())?;
+                pending_replay = None;
+                if let Some(replay) = session.replay() {
+                    worker.send(&replay.initialize).await;
+                    if replay.internal {
+                        pending_replay = Some((replay.followups, Vec::new()));
+                    }
+                }
+
+                let message = format!(
+                    "Rholang language server crashed and was restarted; {} open document(s) restored.",
+                    session.open_documents()
+                );
+                write_message(&mut editor, &show_message(MESSAGE_WARNING, &message)).await?;
+            }
+            Event::Worker(..) | Event::WorkerClosed(_) => {
+                debug!("Ignoring event from a previous worker generation");
+            }
+        }
+    }
+    Ok(0)
+}
+
+/// Builds a `window/showMessage` notification
+fn show_message(message_type: u8, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "window/showMessage",
+        "params": { "type": message_type, "message": message },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restart_budget_and_backoff() {
+        let start = Instant::now();
+        let mut tracker = RestartTracker::default();
+        for i in 0..MAX_RESTARTS {
+            assert!(tracker.record_crash(start + Duration::from_secs(i as u64)));
+        }
+        assert_eq!(tracker.backoff(), (INITIAL_BACKOFF * 16).min(MAX_BACKOFF));
+        assert!(!tracker.record_crash(start + Duration::from_secs(10)));
+
+        // Crashes outside the window no longer count
+        let mut tracker = RestartTracker::default();
+        assert!(tracker.record_crash(start));
+        assert!(tracker.record_crash(start + RESTART_WINDOW + Duration::from_secs(1)));
+        assert_eq!(tracker.backoff(), INITIAL_BACKOFF);
+    }
+}
@@ -0,0 +1,363 @@
+//! Editor session state recorded by the supervisor for replay after a crash
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use ropey::Rope;
+use serde_json::{json, Value};
+use tower_lsp::lsp_types::TextDocumentContentChangeEvent;
+use tracing::{debug, warn};
+
+use crate::lsp::document::apply_text_changes;
+use crate::lsp::line_index::PositionEncoding;
+
+/// JSON-RPC `RequestFailed` error code (LSP 3.17)
+const REQUEST_FAILED: i64 = -32803;
+
+/// A document the editor has open
+#[derive(Debug, Clone)]
+struct OpenDocument {
+    language_id: String,
+    version: i64,
+    text: Rope,
+}
+
+/// What to do with a message after it has been recorded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Route {
+    /// Pass the message on
+    Forward,
+    /// Swallow the message
+    Drop,
+    /// The worker answered the replayed `initialize`; send the rest of the replay
+    ReplayInitialized,
+}
+
+/// Messages that restore a restarted worker to the editor's session
+#[derive(Debug, Clone)]
+pub struct Replay {
+    /// `initialize` request to send first
+    pub initialize: Value,
+    /// Whether `initialize` was issued by the supervisor (its response must be
+    /// swallowed and `followups` sent once it arrives)
+    pub internal: bool,
+    /// `initialized` and `didOpen` notifications to send after `initialize`
+    pub followups: Vec<Value>,
+}
+
+/// Everything the supervisor needs to bring a fresh worker up to date
+#[derive(Debug, Default)]
+pub struct SessionState {
+    /// The editor's `initialize` request
+    initialize: Option<Value>,
+    /// Whether the editor received the `initialize` response
+    initialize_answered: bool,
+    /// Whether the editor sent `initialized`
+    initialized: bool,
+    /// Position encoding the worker chose in its `initialize` response
+    position_encoding: PositionEncoding,
+    /// Open documents by URI
+    documents: BTreeMap<String, OpenDocument>,
+    /// Editor requests awaiting a worker response, by id
+    pending_client_requests: HashMap<String, String>,
+    /// Worker requests awaiting an editor response, by id
+    pending_server_requests: HashSet<String>,
+    /// Id of the supervisor's own replayed `initialize`
+    replay_id: Option<String>,
+    replay_count: u64,
+    shutdown_requested: bool,
+}
+
+impl SessionState {
+    /// Whether the editor asked the server to shut down or exit
+    pub fn shutdown_requested(&self) -> bool {
+        self.shutdown_requested
+    }
+
+    /// Number of documents currently open in the editor
+    pub fn open_documents(&self) -> usize {
+        self.documents.len()
+    }
+
+    /// Records a message from the editor.
+    pub fn record_from_editor(&mut self, message: &Value) -> Route {
+        let id = message.get("id").map(id_key);
+        let method = message.get("method").and_then(Value::as_str);
+
+        match (method, id) {
+            // Response to a worker request; drop answers meant for a crashed worker
+            (None, Some(id)) => {
+                if self.pending_server_requests.remove(&id) {
+                    Route::Forward
+                } else {
+                    debug!("Dropping editor response {} for a previous worker", id);
+                    Route::Drop
+                }
+            }
+            (Some(method), Some(id)) => {
+                match method {
+                    "initialize" => self.initialize = Some(message.clone()),
+                    "shutdown" => self.shutdown_requested = true,
+                    _ => {}
+                }
+                self.pending_client_requests.insert(id, method.to_string());
+                Route::Forward
+            }
+            (Some(method), None) => {
+                self.record_notification(method, message.get("params").unwrap_or(&Value::Null));
+                Route::Forward
+            }
+            (None, None) => Route::Forward,
+        }
+    }
+
+    /// Records a message from the worker.
+    pub fn record_from_worker(&mut self, message: &Value) -> Route {
+        let id = message.get("id").map(id_key);
+        let has_method = message.get("method").is_some();
+
+        match (has_method, id) {
+            (false, Some(id)) => {
+                if self.replay_id.as_ref() == Some(&id) {
+                    self.replay_id = None;
+                    return Route::ReplayInitialized;
+                }
+                if self.pending_client_requests.remove(&id).as_deref() == Some("initialize") {
+                    self.initialize_answered = true;
+                    let encoding = &message["result"]["capabilities"]["positionEncoding"];
+                    self.position_encoding = serde_json::from_value(encoding.clone()).unwrap_or_default();
+                }
+                Route::Forward
+            }
+            (true, Some(id)) => {
+                self.pending_server_requests.insert(id);
+                Route::Forward
+            }
+            _ => Route::Forward,
+        }
+    }
+
+    /// Handles a worker crash.
+    ///
+    /// Returns error responses for the editor requests the worker never
+    /// answered. An unanswered `initialize` is kept pending: it is replayed
+    /// as-is to the next worker, whose response goes to the editor.
+    pub fn fail_pending_requests(&mut self) -> Vec<Value> {
+        self.pending_server_requests.clear();
+        self.replay_id = None;
+
+        let pending = std::mem::take(&mut self.pending_client_requests);
+        let mut responses = Vec::new();
+        for (id, method) in pending {
+            if method == "initialize" {
+                self.pending_client_requests.insert(id, method);
+                continue;
+            }
+            let original_id = serde_json::from_str::<Value>(&id).unwrap_or(Value::Null);
+            responses.push(json!({
+                "jsonrpc": "2.0",
+                "id": original_id,
+                "error": {
+                    "code": REQUEST_FAILED,
+                    "message": format!("Language server restarted while handling '{}'", method),
+                },
+            }));
+        }
+        responses
+    }
+
+    /// Builds the messages that bring a new worker up to the current session.
+    ///
+    /// Returns `None` if the editor has not sent `initialize` yet.
+    pub fn replay(&mut self) -> Option<Replay> {
+        let initialize = self.initialize.clone()?;
+
+        if !self.initialize_answered {
+            // The editor is still waiting for its own initialize: resend it
+            // unchanged and let the response through
+            return Some(Replay { initialize, internal: false, followups: Vec::new() });
+        }
+
+        self.replay_count += 1;
+        let replay_id = format!("rholang-supervisor/initialize-{}", self.replay_count);
+        let mut initialize = initialize;
+        initialize["id"] = json!(replay_id);
+        self.replay_id = Some(id_key(&initialize["id"]));
+
+        let mut followups = Vec::new();
+        if self.initialized {
+            followups.push(json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} }));
+            for (uri, document) in &self.documents {
+                followups.push(json!({
+                    "jsonrpc": "2.0",
+                    "method": "textDocument/didOpen",
+                    "params": {
+                        "textDocument": {
+                            "uri": uri,
+                            "languageId": document.language_id,
+                            "version": document.version,
+                            "text": document.text.to_string(),
+                        }
+                    },
+                }));
+            }
+        }
+        Some(Replay { initialize, internal: true, followups })
+    }
+
+    fn record_notification(&mut self, method: &str, params: &Value) {
+        match method {
+            "initialized" => self.initialized = true,
+            "exit" => self.shutdown_requested = true,
+            "textDocument/didOpen" => {
+                let document = &params["textDocument"];
+                let Some(uri) = document["uri"].as_str() else { return };
+                self.documents.insert(
+                    uri.to_string(),
+                    OpenDocument {
+                        language_id: document["languageId"].as_str().unwrap_or("rholang").to_string(),
+                        version: document["version"].as_i64().unwrap_or(0),
+                        text: Rope::from_str(document["text"].as_str().unwrap_or("")),
+                    },
+                );
+            }
+            "textDocument/didChange" => {
+                let Some(uri) = params["textDocument"]["uri"].as_str() else { return };
+                let Some(document) = self.documents.get_mut(uri) else {
+                    warn!("didChange for unopened document {}", uri);
+                    return;
+                };
+                let changes: Vec<TextDocumentContentChangeEvent> =
+                    serde_json::from_value(params["contentChanges"].clone()).unwrap_or_default();
+                apply_text_changes(&mut document.text, &changes, self.position_encoding);
+                if let Some(version) = params["textDocument"]["version"].as_i64() {
+                    document.version = version;
+                }
+            }
+            "textDocument/didClose" => {
+                if let Some(uri) = params["textDocument"]["uri"].as_str() {
+                    self.documents.remove(uri);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Normalizes a JSON-RPC id (number or string) to a map key
+fn id_key(id: &Value) -> String {
+    id.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn initialize() -> Value {
+        json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": { "rootUri": "file:///ws" } })
+    }
+
+    fn notification(method: &str, params: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "method": method, "params": params })
+    }
+
+    fn started_session() -> SessionState {
+        let mut session = SessionState::default();
+        session.record_from_editor(&initialize());
+        session.record_from_worker(&json!({ "jsonrpc": "2.0", "id": 1, "result": {} }));
+        session.record_from_editor(&notification("initialized", json!({})));
+        session
+    }
+
+    #[test]
+    fn test_replay_reopens_documents_with_current_text() {
+        let mut session = started_session();
+        session.record_from_editor(&notification("textDocument/didOpen", json!({
+            "textDocument": { "uri": "file:///ws/a.rho", "languageId": "rholang", "version": 1, "text": "Nil" }
+        })));
+        session.record_from_editor(&notification("textDocument/didChange", json!({
+            "textDocument": { "uri": "file:///ws/a.rho", "version": 2 },
+            "contentChanges": [{ "range": { "start": { "line": 0, "character": 0 }, "end": { "line": 0, "character": 3 } }, "text": "@0!(1)" }]
+        })));
+        session.record_from_editor(&notification("textDocument/didOpen", json!({
+            "textDocument": { "uri": "file:///ws/b.rho", "languageId": "rholang", "version": 1, "text": "Nil" }
+        })));
+        session.record_from_editor(&notification("textDocument/didClose", json!({
+            "textDocument": { "uri": "file:///ws/b.rho" }
+        })));
+
+        let replay = session.replay().unwrap();
+        assert!(replay.internal);
+        assert_eq!(replay.initialize["id"], "rholang-supervisor/initialize-1");
+        assert_eq!(replay.initialize["params"]["rootUri"], "file:///ws");
+        assert_eq!(replay.followups.len(), 2);
+        assert_eq!(replay.followups[0]["method"], "initialized");
+        let reopened = &replay.followups[1]["params"]["textDocument"];
+        assert_eq!(reopened["uri"], "file:///ws/a.rho");
+        assert_eq!(reopened["version"], 2);
+        assert_eq!(reopened["text"], "@0!(1)");
+
+        let response = json!({ "jsonrpc": "2.0", "id": "rholang-supervisor/initialize-1", "result": {} });
+        assert_eq!(session.record_from_worker(&response), Route::ReplayInitialized);
+    }
+
+    #[test]
+    fn test_changes_use_negotiated_encoding() {
+        let mut session = SessionState::default();
+        session.record_from_editor(&initialize());
+        session.record_from_worker(&json!({
+            "jsonrpc": "2.0", "id": 1, "result": { "capabilities": { "positionEncoding": "utf-8" } }
+        }));
+        session.record_from_editor(&notification("textDocument/didOpen", json!({
+            "textDocument": { "uri": "file:///ws/a.rho", "languageId": "rholang", "version": 1, "text": "@\"é\"!(1)" }
+        })));
+        // Byte columns: the `1` is at 7, after the two bytes of `é`
+        session.record_from_editor(&notification("textDocument/didChange", json!({
+            "textDocument": { "uri": "file:///ws/a.rho", "version": 2 },
+            "contentChanges": [{ "range": { "start": { "line": 0, "character": 7 }, "end": { "line": 0, "character": 8 } }, "text": "2" }]
+        })));
+        session.record_from_editor(&notification("initialized", json!({})));
+
+        let replay = session.replay().unwrap();
+        assert_eq!(replay.followups[1]["params"]["textDocument"]["text"], "@\"é\"!(2)");
+    }
+
+    #[test]
+    fn test_crash_fails_pending_requests_and_drops_stale_responses() {
+        let mut session = started_session();
+        session.record_from_editor(&json!({ "jsonrpc": "2.0", "id": 7, "method": "textDocument/hover", "params": {} }));
+        session.record_from_worker(&json!({ "jsonrpc": "2.0", "id": 3, "method": "window/showMessageRequest", "params": {} }));
+
+        let failed = session.fail_pending_requests();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0]["id"], 7);
+        assert_eq!(failed[0]["error"]["code"], REQUEST_FAILED);
+
+        let stale = json!({ "jsonrpc": "2.0", "id": 3, "result": null });
+        assert_eq!(session.record_from_editor(&stale), Route::Drop);
+    }
+
+    #[test]
+    fn test_unanswered_initialize_is_resent_unchanged() {
+        let mut session = SessionState::default();
+        assert!(session.replay().is_none());
+
+        session.record_from_editor(&initialize());
+        assert!(session.fail_pending_requests().is_empty());
+
+        let replay = session.replay().unwrap();
+        assert!(!replay.internal);
+        assert_eq!(replay.initialize["id"], 1);
+        assert!(replay.followups.is_empty());
+
+        let response = json!({ "jsonrpc": "2.0", "id": 1, "result": {} });
+        assert_eq!(session.record_from_worker(&response), Route::Forward);
+    }
+
+    #[test]
+    fn test_shutdown_is_tracked() {
+        let mut session = started_session();
+        assert!(!session.shutdown_requested());
+        session.record_from_editor(&json!({ "jsonrpc": "2.0", "id": 9, "method": "shutdown" }));
+        assert!(session.shutdown_requested());
+    }
+}
@@ -0,0 +1,334 @@
+//! Tracks open connections and background tasks for coordinated shutdown
+//!
+//! Shutdown is a single [`CancellationToken`]. Every connection is served
+//! with a child of it and every background task races against it, so
+//! cancelling it stops them all. The token stays cancelled: a task that
+//! starts waiting after shutdown was requested still sees the request, and
+//! requesting shutdown twice is harmless.
+//!
+//! Tasks are only spawned through the manager, which counts them until they
+//! end, however they end. Once shutdown is requested no new task is spawned,
+//! so [`ConnectionManager::wait_for_tasks`] waits for a set that can only
+//! shrink, aborting whatever is left after [`TASK_SHUTDOWN_TIMEOUT`].
+
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::Notify;
+use tokio::task::AbortHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info};
+
+/// How long `wait_for_tasks` waits for the tasks to stop before aborting them
+const TASK_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long `wait_for_tasks` waits for aborted tasks to be dropped
+const TASK_ABORT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Shared handle to the server's connections, tasks and shutdown signal
+#[derive(Clone)]
+pub struct ConnectionManager {
+    /// Cancelled once shutdown is requested; never reset
+    shutdown: CancellationToken,
+    tasks: Arc<TaskSet>,
+    shutdown_timeout: Duration,
+}
+
+/// The tasks spawned through a manager
+#[derive(Default)]
+struct TaskSet {
+    /// Abort handles of the tasks, pruned as tasks finish. Also serializes
+    /// spawning against the shutdown request.
+    handles: Mutex<Vec<AbortHandle>>,
+    running: AtomicUsize,
+    connections: AtomicUsize,
+    /// Notified when the last running task ends
+    idle: Notify,
+}
+
+impl TaskSet {
+    /// Resolves once no task is running.
+    async fn idle(&self) {
+        loop {
+            // Register for the notification before checking, so the last
+            // task ending in between is not missed
+            let notified = self.idle.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            if self.running.load(Ordering::SeqCst) == 0 {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+/// Moved into a spawned task; counts it out when dropped, which also
+/// happens when the task is aborted
+struct TaskGuard {
+    tasks: Arc<TaskSet>,
+    connection: bool,
+}
+
+impl Drop for TaskGuard {
+    fn drop(&mut self) {
+        if self.connection {
+            self.tasks.connections.fetch_sub(1, Ordering::SeqCst);
+        }
+        if self.tasks.running.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.tasks.idle.notify_waiters();
+        }
+    }
+}
+
+impl Default for ConnectionManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConnectionManager {
+    pub fn new() -> Self {
+        ConnectionManager {
+            shutdown: CancellationToken::new(),
+            tasks: Arc::new(TaskSet::default()),
+            shutdown_timeout: TASK_SHUTDOWN_TIMEOUT,
+        }
+    }
+
+    /// Sets how long `wait_for_tasks` waits before aborting the remaining tasks.
+    pub fn with_shutdown_timeout(mut self, timeout: Duration) -> Self {
+        self.shutdown_timeout = timeout;
+        self
+    }
+
+    /// Requests shutdown; idempotent.
+    pub fn request_shutdown(&self) {
+        let _spawning = self.tasks.handles.lock().unwrap();
+        if !self.shutdown.is_cancelled() {
+            info!("Shutdown requested, {} task(s) running", self.task_count());
+            self.shutdown.cancel();
+        }
+    }
+
+    pub fn is_shutdown_requested(&self) -> bool {
+        self.shutdown.is_cancelled()
+    }
+
+    /// Resolves once shutdown is requested, immediately if it already was.
+    pub async fn shutdown_requested(&self) {
+        self.shutdown.cancelled().await;
+    }
+
+    /// Spawns a background task, dropped at shutdown.
+    ///
+    /// Returns `false` without spawning once shutdown has been requested.
+    pub fn spawn_task<F>(&self, task: F) -> bool
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let shutdown = self.shutdown.clone();
+        self.spawn(false, async move {
+            tokio::select! {
+                _ = shutdown.cancelled() => {}
+                _ = task => {}
+            }
+        })
+    }
+
+    /// Spawns the task serving a connection.
+    ///
+    /// `serve` gets a token cancelled at shutdown, and should close the
+    /// connection and return when it is. Returns `false` without spawning
+    /// once shutdown has been requested; `serve` is then dropped uncalled.
+    pub fn spawn_connection<F, Fut>(&self, serve: F) -> bool
+    where
+        F: FnOnce(CancellationToken) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        if self.is_shutdown_requested() {
+            return false;
+        }
+        self.spawn(true, serve(self.shutdown.child_token()))
+    }
+
+    fn spawn<F>(&self, connection: bool, task: F) -> bool
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let mut handles = self.tasks.handles.lock().unwrap();
+        if self.shutdown.is_cancelled() {
+            debug!("Not spawning a task after shutdown was requested");
+            return false;
+        }
+        self.tasks.running.fetch_add(1, Ordering::SeqCst);
+        if connection {
+            self.tasks.connections.fetch_add(1, Ordering::SeqCst);
+        }
+        let guard = TaskGuard { tasks: Arc::clone(&self.tasks), connection };
+        let handle = tokio::spawn(async move {
+            let _guard = guard;
+            task.await;
+        });
+        handles.retain(|handle| !handle.is_finished());
+        handles.push(handle.abort_handle());
+        true
+    }
+
+    /// Requests shutdown and waits for every task to stop.
+    pub async fn shutdown_all(&self) {
+        info!("Initiating shutdown of all connections and tasks");
+        self.request_shutdown();
+        self.wait_for_tasks().await;
+    }
+
+    /// Waits for every task to stop, aborting those still running after the
+    /// shutdown timeout.
+    ///
+    /// Only meaningful once shutdown has been requested; until then tasks
+    /// keep running and would be aborted. Any number of callers may wait.
+    pub async fn wait_for_tasks(&self) {
+        if tokio::time::timeout(self.shutdown_timeout, self.tasks.idle()).await.is_err() {
+            let handles = std::mem::take(&mut *self.tasks.handles.lock().unwrap());
+            error!(
+                "{} task(s) did not stop within {:?}, aborting them",
+                self.task_count(),
+                self.shutdown_timeout
+            );
+            for handle in handles {
+                handle.abort();
+            }
+            if tokio::time::timeout(TASK_ABORT_TIMEOUT, self.tasks.idle()).await.is_err() {
+                error!("{} task(s) still running after being aborted", self.task_count());
+                return;
+            }
+        }
+        info!("All tasks completed");
+    }
+
+    /// Number of connections being served
+    pub fn connection_count(&self) -> usize {
+        self.tasks.connections.load(Ordering::SeqCst)
+    }
+
+    /// Number of tasks running, connections included
+    pub fn task_count(&self) -> usize {
+        self.tasks.running.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicBool;
+
+    #[tokio::test]
+    async fn test_shutdown_all_stops_connections_and_tasks() {
+        let manager = ConnectionManager::new();
+
+        let closed = Arc::new(AtomicBool::new(false));
+        let closed_flag = Arc::clone(&closed);
+        assert!(manager.spawn_connection(move |shutdown| async move {
+            shutdown.cancelled().await;
+            closed_flag.store(true, Ordering::SeqCst);
+        }));
+        assert!(manager.spawn_task(std::future::pending::<()>()));
+        assert_eq!(manager.connection_count(), 1);
+        assert_eq!(manager.task_count(), 2);
+
+        manager.shutdown_all().await;
+        assert!(closed.load(Ordering::SeqCst), "connection did not see the shutdown");
+        assert_eq!(manager.connection_count(), 0);
+        assert_eq!(manager.task_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_request_shutdown_wakes_waiters() {
+        let manager = ConnectionManager::new();
+        let waiter = {
+            let manager = manager.clone();
+            tokio::spawn(async move { manager.shutdown_requested().await })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        manager.request_shutdown();
+        tokio::time::timeout(Duration::from_secs(1), waiter)
+            .await
+            .expect("waiter was not woken")
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_requested_before_waiting_is_seen() {
+        let manager = ConnectionManager::new();
+        manager.request_shutdown();
+        manager.request_shutdown();
+        tokio::time::timeout(Duration::from_secs(1), manager.shutdown_requested())
+            .await
+            .expect("shutdown requested before waiting was missed");
+        assert!(manager.is_shutdown_requested());
+    }
+
+    #[tokio::test]
+    async fn test_no_task_is_spawned_after_shutdown() {
+        let manager = ConnectionManager::new();
+        manager.shutdown_all().await;
+
+        assert!(!manager.spawn_task(async {}));
+        assert!(!manager.spawn_connection(|_| async {}));
+        assert_eq!(manager.task_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_repeated_and_concurrent_shutdowns() {
+        let manager = ConnectionManager::new();
+        for _ in 0..8 {
+            manager.spawn_connection(|shutdown| async move { shutdown.cancelled().await });
+        }
+
+        let shutdowns: Vec<_> = (0..4)
+            .map(|_| {
+                let manager = manager.clone();
+                tokio::spawn(async move { manager.shutdown_all().await })
+            })
+            .collect();
+        for shutdown in shutdowns {
+            shutdown.await.unwrap();
+        }
+        manager.shutdown_all().await;
+        assert_eq!(manager.task_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_tasks_ignoring_shutdown_are_aborted() {
+        let manager = ConnectionManager::new().with_shutdown_timeout(Duration::from_millis(50));
+        manager.spawn_connection(|_| std::future::pending::<()>());
+
+        tokio::time::timeout(Duration::from_secs(2), manager.shutdown_all())
+            .await
+            .expect("shutdown waited for a stuck task");
+        assert_eq!(manager.connection_count(), 0);
+        assert_eq!(manager.task_count(), 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_stress_spawning_races_shutdown() {
+        for _ in 0..200 {
+            let manager = ConnectionManager::new();
+            let spawner = {
+                let manager = manager.clone();
+                tokio::spawn(async move {
+                    while manager.spawn_connection(|shutdown| async move { shutdown.cancelled().await }) {
+                        tokio::task::yield_now().await;
+                    }
+                })
+            };
+            tokio::task::yield_now().await;
+            manager.shutdown_all().await;
+            spawner.await.unwrap();
+            assert_eq!(manager.task_count(), 0, "a task outlived shutdown");
+        }
+    }
+}
@@ -0,0 +1,386 @@
+//! Transports for serving the language server to LSP clients
+//!
+//! The server speaks LSP over several transports, selected on the command
+//! line. Each implements the [`Transport`] trait, which only accepts client
+//! connections. The connection lifecycle is the same for all of them and
+//! lives in [`serve`]:
+//!
+//! 1. wait for the next [`Connection`] (or for a shutdown request);
+//! 2. build an LSP service for it and serve it on a background task
+//!    spawned through the [`ConnectionManager`];
+//! 3. on shutdown, cancel every connection and wait for its task.
+//!
+//! Adding a transport therefore means implementing [`Transport::accept`] and
+//! adding a [`TransportMode`] variant; nothing in `main` needs to change.
+//!
+//! | Mode | Type | Connections |
+//! |------|------|-------------|
+//! | `--stdio` | [`StdioTransport`] | one, for the lifetime of the process |
+//! | `--socket --port N` | [`TcpTransport`] | many, on `127.0.0.1:N` |
+//! | `--websocket --port N` | [`WebSocketTransport`] | many, on `127.0.0.1:N` |
+//! | `--pipe PATH` | [`PipeTransport`] | many (Unix socket or Windows named pipe) |
+
+mod connection_manager;
+mod pipe;
+mod process_monitor;
+mod stdio;
+mod tcp;
+#[cfg(feature = "websocket")]
+mod websocket;
+
+use std::fmt;
+use std::io;
+
+use async_trait::async_trait;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::mpsc;
+use tower_lsp::{LspService, Server};
+use tracing::{error, info};
+
+use crate::debug_console::DebugConsole;
+use crate::lsp::backend::RholangBackend;
+use crate::edit_journal::EditJournal;
+use crate::lsp::shared_documents::{ConnectionId, SharedDocuments};
+use crate::strict_lsp::{SpecChecker, StrictLspMode};
+use crate::wire_logger::WireLogger;
+use crate::wire_logger_middleware::{LoggingReader, LoggingWriter, RequestClock};
+
+pub use connection_manager::ConnectionManager;
+pub use pipe::PipeTransport;
+pub use process_monitor::{monitor_client_process, spawn_client_monitor};
+pub use stdio::StdioTransport;
+pub use tcp::TcpTransport;
+#[cfg(feature = "websocket")]
+pub use websocket::{WebSocketStreamAdapter, WebSocketTransport};
+
+/// Buffer size for buffered stdio and TCP streams
+///
+/// 64KB buffers provide better throughput for LSP message streams.
+pub(crate) const BUFFER_SIZE: usize = 64 * 1024;
+
+/// Reading half of a client connection
+pub type BoxedReader = Box<dyn AsyncRead + Send + Unpin>;
+/// Writing half of a client connection
+pub type BoxedWriter = Box<dyn AsyncWrite + Send + Unpin>;
+
+/// Communication mode selected on the command line
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransportMode {
+    Stdio,
+    Socket(u16),
+    Pipe(String),
+    WebSocket(u16),
+}
+
+impl fmt::Display for TransportMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransportMode::Stdio => write!(f, "stdio"),
+            TransportMode::Socket(port) => write!(f, "tcp:127.0.0.1:{}", port),
+            TransportMode::Pipe(path) => write!(f, "pipe:{}", path),
+            TransportMode::WebSocket(port) => write!(f, "ws://127.0.0.1:{}", port),
+        }
+    }
+}
+
+/// An accepted client connection
+pub struct Connection {
+    pub reader: BoxedReader,
+    pub writer: BoxedWriter,
+    /// Peer description for logging (e.g. `127.0.0.1:50312`)
+    pub peer: String,
+}
+
+impl Connection {
+    pub fn new<R, W>(reader: R, writer: W, peer: impl Into<String>) -> Self
+    where
+        R: AsyncRead + Send + Unpin + 'static,
+        W: AsyncWrite + Send + Unpin + 'static,
+    {
+        Connection {
+            reader: Box::new(reader),
+            writer: Box::new(writer),
+            peer: peer.into(),
+        }
+    }
+}
+
+/// A source of client connections
+#[async_trait]
+pub trait Transport: Send {
+    /// Short name used in log messages
+    fn name(&self) -> &'static str;
+
+    /// Waits for the next client connection.
+    ///
+    /// Returns `Ok(None)` once the transport cannot produce any further
+    /// connections; the server then keeps running until shutdown is requested.
+    async fn accept(&mut self) -> io::Result<Option<Connection>>;
+
+    /// Whether the transport serves a single client for the lifetime of the
+    /// process.
+    ///
+    /// For such transports the client process ID reported in `initialize` is
+    /// monitored, and the server shuts down when the client exits.
+    fn single_client(&self) -> bool {
+        false
+    }
+}
+
+/// Settings shared by every connection served by a transport
+#[derive(Clone)]
+pub struct ServeOptions {
+    /// Validator backend passed to `RholangBackend::new`
+    pub validator_backend: Option<String>,
+    /// Client process ID from the command line
+    pub client_process_id: Option<u32>,
+    pub wire_logger: WireLogger,
+    /// Journal of document edits, shared by all connections
+    pub edit_journal: EditJournal,
+    /// Documents open in any connection, for conflict arbitration
+    pub shared_documents: SharedDocuments,
+    /// Console showing every connection (`--debug-console`)
+    pub debug_console: Option<DebugConsole>,
+    /// Checking of outgoing messages against the LSP specification (`--strict-lsp`)
+    pub strict_lsp: Option<StrictLspMode>,
+}
+
+/// Start of the line written to stderr once a listener is bound, followed by
+/// the mode; launchers wait for it instead of retrying to connect.
+pub const LISTENING_LINE: &str = "Rholang language server listening on";
+
+/// Creates the transport for `mode`, binding any listener it needs.
+pub async fn bind(mode: &TransportMode) -> io::Result<Box<dyn Transport>> {
+    let transport: Box<dyn Transport> = match mode {
+        TransportMode::Stdio => Box::new(StdioTransport::new()),
+        TransportMode::Socket(port) => Box::new(TcpTransport::bind(("127.0.0.1", *port)).await?),
+        TransportMode::Pipe(path) => Box::new(PipeTransport::bind(path)?),
+        #[cfg(feature = "websocket")]
+        TransportMode::WebSocket(port) => Box::new(WebSocketTransport::bind(("127.0.0.1", *port)).await?),
+        #[cfg(not(feature = "websocket"))]
+        TransportMode::WebSocket(_) => {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "WebSocket support is not enabled in this build (missing `websocket` feature)",
+            ));
+        }
+    };
+    if *mode != TransportMode::Stdio {
+        // Printed whatever the log level: this is a handshake, not a log line
+        eprintln!("{} {}", LISTENING_LINE, mode);
+    }
+    Ok(transport)
+}
+
+/// Serves clients from `transport` until shutdown is requested.
+///
+/// Accept errors are logged and do not stop the server. On return every
+/// connection has been cancelled and its task has stopped or been aborted.
+pub async fn serve(
+    mut transport: Box<dyn Transport>,
+    options: ServeOptions,
+    conn_manager: ConnectionManager,
+) -> io::Result<()> {
+    info!("Serving LSP over {}", transport.name());
+
+    loop {
+        tokio::select! {
+            result = transport.accept() => {
+                match result {
+                    Ok(Some(connection)) => {
+                        let pid_channel = transport
+                            .single_client()
+                            .then(|| watch_reported_client_pid(&conn_manager));
+                        serve_connection(connection, &options, &conn_manager, pid_channel);
+                    }
+                    Ok(None) => {
+                        conn_manager.shutdown_requested().await;
+                        info!("Shutdown signal received, stopping {} server", transport.name());
+                        break;
+                    }
+                    Err(e) => {
+                        error!("Failed to accept {} connection: {}", transport.name(), e);
+                    }
+                }
+            }
+            _ = conn_manager.shutdown_requested() => {
+                info!("Main shutdown signal received, closing {} server", transport.name());
+                break;
+            }
+        }
+    }
+
+    conn_manager.shutdown_all().await;
+    Ok(())
+}
+
+/// Returns a channel on which the backend reports the client PID from
+/// `initialize`; the reported process is then monitored.
+fn watch_reported_client_pid(conn_manager: &ConnectionManager) -> mpsc::Sender<u32> {
+    let (pid_tx, mut pid_rx) = mpsc::channel::<u32>(1);
+    let conn_manager = conn_manager.clone();
+    let monitor_manager = conn_manager.clone();
+    conn_manager.spawn_task(async move {
+        if let Some(pid) = pid_rx.recv().await {
+            info!("Received client PID from LSP initialization: {}", pid);
+            spawn_client_monitor(pid, &monitor_manager);
+        }
+    });
+    pid_tx
+}
+
+/// Removes a connection from the state shared between connections when
+/// dropped, so it is removed however its task ends
+struct ConnectionRegistration {
+    connection_id: ConnectionId,
+    shared_documents: SharedDocuments,
+    debug_console: Option<DebugConsole>,
+}
+
+impl Drop for ConnectionRegistration {
+    fn drop(&mut self) {
+        self.shared_documents.disconnect(self.connection_id);
+        if let Some(console) = &self.debug_console {
+            console.unregister(self.connection_id);
+        }
+    }
+}
+
+/// Builds an LSP service for `connection` and serves it on a background task.
+fn serve_connection(
+    connection: Connection,
+    options: &ServeOptions,
+    conn_manager: &ConnectionManager,
+    pid_channel: Option<mpsc::Sender<u32>>,
+) {
+    let Connection { reader, writer, peer } = connection;
+    info!("Accepted connection from {}", peer);
+
+    let validator_backend = options.validator_backend.clone();
+    let client_process_id = options.client_process_id;
+    let edit_journal = options.edit_journal.clone();
+    let shared_documents = options.shared_documents.clone();
+    let connection_id = shared_documents.connect();
+    let debug_console = options.debug_console.clone();
+    let builder = LspService::build(|client| {
+        // Block on async backend creation (only happens once during initialization)
+        let backend = tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current().block_on(async {
+                RholangBackend::new(
+                    client,
+                    validator_backend.clone(),
+                    client_process_id,
+                    pid_channel.clone(),
+                    edit_journal.clone(),
+                    shared_documents.clone(),
+                    connection_id,
+                )
+                    .await
+                    .expect("Failed to create Rholang backend")
+            })
+        });
+        if let Some(console) = &debug_console {
+            console.register(connection_id, backend.clone());
+        }
+        backend
+    });
+    let (service, socket) = RholangBackend::register_custom_methods(builder).finish();
+    let registration = ConnectionRegistration {
+        connection_id,
+        shared_documents,
+        debug_console: debug_console.clone(),
+    };
+
+    // Wrap streams with the wire logger middleware only if it is enabled,
+    // the debug console needs request latencies or strict mode checks messages
+    let wire_logger = options.wire_logger.clone();
+    let clock = debug_console.is_some().then(RequestClock::default);
+    let checker = options.strict_lsp.map(SpecChecker::new);
+    let (reader, writer): (BoxedReader, BoxedWriter) = if wire_logger.is_enabled() || clock.is_some() || checker.is_some() {
+        let mut logging_reader = LoggingReader::new(reader, wire_logger.clone());
+        let mut logging_writer = LoggingWriter::new(writer, wire_logger);
+        if let Some(clock) = clock {
+            logging_reader = logging_reader.with_request_clock(clock.clone());
+            logging_writer = logging_writer.with_request_clock(clock);
+        }
+        if let Some(checker) = checker {
+            logging_reader = logging_reader.with_spec_checker(checker.clone());
+            logging_writer = logging_writer.with_spec_checker(checker);
+        }
+        (Box::new(logging_reader), Box::new(logging_writer))
+    } else {
+        (reader, writer)
+    };
+
+    let spawned = conn_manager.spawn_connection(move |shutdown| async move {
+        let _registration = registration;
+        let server = Server::new(reader, writer, socket);
+        tokio::select! {
+            _ = server.serve(service) => {
+                info!("Connection from {} closed normally", peer);
+            }
+            _ = shutdown.cancelled() => {
+                info!("Closing connection from {} for shutdown", peer);
+            }
+        }
+    });
+    if !spawned {
+        info!("Dropped a connection accepted during shutdown");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// Transport that never produces a connection
+    struct ExhaustedTransport;
+
+    #[async_trait]
+    impl Transport for ExhaustedTransport {
+        fn name(&self) -> &'static str {
+            "exhausted"
+        }
+
+        async fn accept(&mut self) -> io::Result<Option<Connection>> {
+            Ok(None)
+        }
+    }
+
+    fn options() -> ServeOptions {
+        ServeOptions {
+            validator_backend: None,
+            client_process_id: None,
+            wire_logger: WireLogger::new(false, None).unwrap(),
+            edit_journal: EditJournal::disabled(),
+            shared_documents: SharedDocuments::default(),
+            debug_console: None,
+            strict_lsp: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_serve_runs_until_shutdown() {
+        let conn_manager = ConnectionManager::new();
+        let server = tokio::spawn(serve(Box::new(ExhaustedTransport), options(), conn_manager.clone()));
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!server.is_finished(), "server should wait for shutdown");
+
+        conn_manager.request_shutdown();
+        tokio::time::timeout(Duration::from_secs(5), server)
+            .await
+            .expect("server did not stop after shutdown")
+            .unwrap()
+            .unwrap();
+    }
+
+    #[test]
+    fn test_transport_mode_display() {
+        assert_eq!(TransportMode::Stdio.to_string(), "stdio");
+        assert_eq!(TransportMode::Socket(7000).to_string(), "tcp:127.0.0.1:7000");
+        assert_eq!(TransportMode::WebSocket(7001).to_string(), "ws://127.0.0.1:7001");
+        assert_eq!(TransportMode::Pipe("/tmp/rho.sock".to_string()).to_string(), "pipe:/tmp/rho.sock");
+    }
+}
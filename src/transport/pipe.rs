@@ -0,0 +1,153 @@
+//! Unix domain socket / Windows named pipe transport
+
+use std::io;
+
+use async_trait::async_trait;
+
+use super::{Connection, Transport};
+
+/// Serves clients connecting to a Unix domain socket
+///
+/// The socket file is replaced if it already exists and removed when the
+/// transport is dropped.
+#[cfg(unix)]
+pub struct PipeTransport {
+    listener: tokio::net::UnixListener,
+    path: std::path::PathBuf,
+}
+
+#[cfg(unix)]
+impl PipeTransport {
+    pub fn bind(path: &str) -> io::Result<Self> {
+        let path = std::path::PathBuf::from(path);
+        if path.exists() {
+            std::fs::remove_file(&path)?;
+        }
+        let listener = tokio::net::UnixListener::bind(&path)?;
+        tracing::info!("Unix socket server listening at {}", path.display());
+        Ok(PipeTransport { listener, path })
+    }
+}
+
+#[cfg(unix)]
+impl Drop for PipeTransport {
+    fn drop(&mut self) {
+        match std::fs::remove_file(&self.path) {
+            Ok(()) => tracing::info!("Cleaned up Unix socket file {}.", self.path.display()),
+            Err(e) => tracing::error!("Failed to clean up Unix socket file {}: {}", self.path.display(), e),
+        }
+    }
+}
+
+#[cfg(unix)]
+#[async_trait]
+impl Transport for PipeTransport {
+    fn name(&self) -> &'static str {
+        "unix socket"
+    }
+
+    async fn accept(&mut self) -> io::Result<Option<Connection>> {
+        let (stream, addr) = self.listener.accept().await?;
+        let (read, write) = tokio::io::split(stream);
+        Ok(Some(Connection::new(read, write, format!("unix_socket:{:?}", addr))))
+    }
+}
+
+/// Serves clients connecting to a Windows named pipe
+///
+/// A new pipe instance is created for each client.
+#[cfg(windows)]
+pub struct PipeTransport {
+    path: String,
+    next: tokio::net::windows::named_pipe::NamedPipeServer,
+}
+
+#[cfg(windows)]
+impl PipeTransport {
+    pub fn bind(path: &str) -> io::Result<Self> {
+        use tokio::net::windows::named_pipe::ServerOptions;
+
+        let next = ServerOptions::new().first_pipe_instance(true).create(path)?;
+        tracing::info!("Named pipe server listening at {}", path);
+        Ok(PipeTransport { path: path.to_string(), next })
+    }
+}
+
+#[cfg(windows)]
+#[async_trait]
+impl Transport for PipeTransport {
+    fn name(&self) -> &'static str {
+        "named pipe"
+    }
+
+    async fn accept(&mut self) -> io::Result<Option<Connection>> {
+        use tokio::net::windows::named_pipe::ServerOptions;
+
+        self.next.connect().await?;
+        // Create the next instance before handing out the connected one so
+        // that clients never find the pipe missing
+        let next = ServerOptions::new().create(&self.path)?;
+        let connected = std::mem::replace(&mut self.next, next);
+        let (read, write) = tokio::io::split(connected);
+        Ok(Some(Connection::new(read, write, format!("named_pipe:{}", self.path))))
+    }
+}
+
+/// Named pipes and Unix sockets are not supported on this platform
+#[cfg(not(any(unix, windows)))]
+pub struct PipeTransport;
+
+#[cfg(not(any(unix, windows)))]
+impl PipeTransport {
+    pub fn bind(_path: &str) -> io::Result<Self> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "Named pipe/Unix domain socket communication is not supported on this platform.",
+        ))
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+#[async_trait]
+impl Transport for PipeTransport {
+    fn name(&self) -> &'static str {
+        "pipe"
+    }
+
+    async fn accept(&mut self) -> io::Result<Option<Connection>> {
+        Ok(None)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::UnixStream;
+
+    #[tokio::test]
+    async fn test_unix_socket_accepts_and_cleans_up() {
+        let path = std::env::temp_dir().join(format!("rholang-lsp-test-{}.sock", std::process::id()));
+        let path_str = path.to_str().unwrap().to_string();
+        let mut transport = PipeTransport::bind(&path_str).unwrap();
+        assert!(path.exists());
+
+        let client = tokio::spawn({
+            let path = path.clone();
+            async move {
+                let mut stream = UnixStream::connect(path).await.unwrap();
+                stream.write_all(b"hi").await.unwrap();
+            }
+        });
+
+        let mut connection = transport.accept().await.unwrap().expect("unix socket yields a connection");
+        let mut buf = [0u8; 2];
+        connection.reader.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"hi");
+        client.await.unwrap();
+
+        drop(connection);
+        drop(transport);
+        assert!(!path.exists(), "socket file should be removed on drop");
+    }
+}
@@ -0,0 +1,64 @@
+//! Shuts the server down when the LSP client process exits
+
+use tracing::{error, info};
+
+use super::ConnectionManager;
+
+/// Monitors `client_pid` on a background task spawned through `conn_manager`.
+pub fn spawn_client_monitor(client_pid: u32, conn_manager: &ConnectionManager) {
+    conn_manager.spawn_task(monitor_client_process(client_pid, conn_manager.clone()));
+}
+
+/// Waits for the client process to exit, then requests server shutdown.
+#[cfg(unix)]
+pub async fn monitor_client_process(client_pid: u32, conn_manager: ConnectionManager) {
+    use nix::unistd::Pid;
+    use tokio::time::{sleep, Duration};
+
+    let pid = Pid::from_raw(client_pid as i32);
+    loop {
+        match nix::sys::signal::kill(pid, None) {
+            Ok(()) => {
+                sleep(Duration::from_secs(1)).await;
+            }
+            Err(nix::Error::ESRCH) => {
+                info!("Client process (PID: {}) no longer exists, shutting down server", client_pid);
+                conn_manager.request_shutdown();
+                break;
+            }
+            Err(e) => {
+                error!("Error checking client process (PID: {}): {}", client_pid, e);
+                sleep(Duration::from_secs(1)).await;
+            }
+        }
+    }
+}
+
+/// Waits for the client process to exit, then requests server shutdown.
+#[cfg(windows)]
+pub async fn monitor_client_process(client_pid: u32, conn_manager: ConnectionManager) {
+    use windows::Win32::System::Threading::{OpenProcess, PROCESS_QUERY_INFORMATION, WaitForSingleObject};
+    use windows::Win32::Foundation::{HANDLE, WAIT_OBJECT_0};
+    use std::ptr;
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_INFORMATION, false, client_pid);
+        if handle == HANDLE(ptr::null_mut()) {
+            error!("Failed to open client process (PID: {})", client_pid);
+            return;
+        }
+        let result = WaitForSingleObject(handle, 0xFFFFFFFF);
+        if result == WAIT_OBJECT_0 {
+            info!("Client process (PID: {}) terminated, shutting down server", client_pid);
+            conn_manager.request_shutdown();
+        } else {
+            error!("Error waiting for client process (PID: {}): {:?}", client_pid, result);
+        }
+    }
+}
+
+/// Client process monitoring is not supported on this platform.
+#[cfg(not(any(unix, windows)))]
+pub async fn monitor_client_process(client_pid: u32, _conn_manager: ConnectionManager) {
+    tracing::warn!("Cannot monitor client process (PID: {}) on this platform", client_pid);
+}
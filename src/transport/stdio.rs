@@ -0,0 +1,57 @@
+//! Standard input/output transport
+
+use std::io;
+
+use async_trait::async_trait;
+use tokio::io::{BufReader, BufWriter};
+
+use super::{BUFFER_SIZE, Connection, Transport};
+
+/// Serves the single client connected to the process's stdin/stdout
+#[derive(Debug, Default)]
+pub struct StdioTransport {
+    accepted: bool,
+}
+
+impl StdioTransport {
+    pub fn new() -> Self {
+        StdioTransport { accepted: false }
+    }
+}
+
+#[async_trait]
+impl Transport for StdioTransport {
+    fn name(&self) -> &'static str {
+        "stdio"
+    }
+
+    async fn accept(&mut self) -> io::Result<Option<Connection>> {
+        if self.accepted {
+            return Ok(None);
+        }
+        self.accepted = true;
+
+        let stdin = BufReader::with_capacity(BUFFER_SIZE, tokio::io::stdin());
+        let stdout = BufWriter::with_capacity(BUFFER_SIZE, tokio::io::stdout());
+        Ok(Some(Connection::new(stdin, stdout, "stdio")))
+    }
+
+    fn single_client(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_stdio_accepts_exactly_once() {
+        let mut transport = StdioTransport::new();
+        assert!(transport.single_client());
+
+        let connection = transport.accept().await.unwrap().expect("first accept yields stdio");
+        assert_eq!(connection.peer, "stdio");
+        assert!(transport.accept().await.unwrap().is_none());
+    }
+}
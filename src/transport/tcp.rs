@@ -0,0 +1,81 @@
+//! TCP socket transport
+
+use std::io;
+use std::net::SocketAddr;
+
+use async_trait::async_trait;
+use tokio::io::{BufReader, BufWriter};
+use tokio::net::{TcpListener, ToSocketAddrs};
+use tracing::{info, warn};
+
+use super::{BUFFER_SIZE, Connection, Transport};
+
+/// Serves clients connecting to a local TCP port
+pub struct TcpTransport {
+    listener: TcpListener,
+}
+
+impl TcpTransport {
+    pub async fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr).await?;
+        info!("TCP server listening on {}", listener.local_addr()?);
+        Ok(TcpTransport { listener })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+}
+
+#[async_trait]
+impl Transport for TcpTransport {
+    fn name(&self) -> &'static str {
+        "tcp"
+    }
+
+    async fn accept(&mut self) -> io::Result<Option<Connection>> {
+        let (stream, addr) = self.listener.accept().await?;
+
+        // Configure TCP socket for low latency
+        if let Err(e) = stream.set_nodelay(true) {
+            warn!("Failed to set TCP_NODELAY for {}: {}", addr, e);
+        }
+
+        let (read, write) = tokio::io::split(stream);
+        let buffered_read = BufReader::with_capacity(BUFFER_SIZE, read);
+        let buffered_write = BufWriter::with_capacity(BUFFER_SIZE, write);
+        Ok(Some(Connection::new(buffered_read, buffered_write, addr.to_string())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    #[tokio::test]
+    async fn test_tcp_accepts_connections() {
+        let mut transport = TcpTransport::bind("127.0.0.1:0").await.unwrap();
+        let addr = transport.local_addr().unwrap();
+
+        let client = tokio::spawn(async move {
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            stream.write_all(b"ping").await.unwrap();
+            let mut reply = [0u8; 4];
+            stream.read_exact(&mut reply).await.unwrap();
+            reply
+        });
+
+        let mut connection = transport.accept().await.unwrap().expect("tcp always yields a connection");
+        assert!(connection.peer.starts_with("127.0.0.1:"));
+
+        let mut request = [0u8; 4];
+        connection.reader.read_exact(&mut request).await.unwrap();
+        assert_eq!(&request, b"ping");
+        connection.writer.write_all(b"pong").await.unwrap();
+        connection.writer.flush().await.unwrap();
+
+        assert_eq!(&client.await.unwrap(), b"pong");
+    }
+}
@@ -0,0 +1,297 @@
+//! WebSocket transport
+
+use std::io;
+
+use async_trait::async_trait;
+use futures::sink::SinkExt;
+use futures::stream::TryStreamExt;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpListener, ToSocketAddrs};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{accept_async, WebSocketStream};
+use tracing::{info, trace};
+
+use super::{Connection, Transport};
+
+/// Serves clients connecting over WebSocket to a local TCP port
+pub struct WebSocketTransport {
+    listener: TcpListener,
+}
+
+impl WebSocketTransport {
+    pub async fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr).await?;
+        info!("WebSocket server listening on {}", listener.local_addr()?);
+        Ok(WebSocketTransport { listener })
+    }
+
+    pub fn local_addr(&self) -> io::Result<std::net::SocketAddr> {
+        self.listener.local_addr()
+    }
+}
+
+#[async_trait]
+impl Transport for WebSocketTransport {
+    fn name(&self) -> &'static str {
+        "websocket"
+    }
+
+    async fn accept(&mut self) -> io::Result<Option<Connection>> {
+        let (stream, addr) = self.listener.accept().await?;
+        let ws_stream = accept_async(stream).await.map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::ConnectionAborted,
+                format!("WebSocket handshake with {} failed: {}", addr, e),
+            )
+        })?;
+        let (read, write) = tokio::io::split(WebSocketStreamAdapter::new(ws_stream));
+        Ok(Some(Connection::new(read, write, addr.to_string())))
+    }
+}
+
+/// Adapts a WebSocket stream to `AsyncRead`/`AsyncWrite` byte streams
+///
+/// Incoming text and binary frames are concatenated into the read stream;
+/// writes are batched into binary frames.
+pub struct WebSocketStreamAdapter<S> {
+    inner: WebSocketStream<S>,
+    read_buffer: Vec<u8>,
+    /// Phase 4 optimization: Write buffer for batching small messages
+    write_buffer: Vec<u8>,
+}
+
+impl<S> WebSocketStreamAdapter<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    pub fn new(inner: WebSocketStream<S>) -> Self {
+        // Phase 1 optimization: Pre-allocate read buffer with reasonable capacity
+        // Prevents repeated allocations for typical LSP messages (1-10KB)
+        const INITIAL_CAPACITY: usize = 32 * 1024;  // 32KB initial
+        WebSocketStreamAdapter {
+            inner,
+            read_buffer: Vec::with_capacity(INITIAL_CAPACITY),
+            // Phase 4 optimization: Pre-allocate write buffer for batching
+            write_buffer: Vec::with_capacity(8 * 1024),  // 8KB write buffer
+        }
+    }
+
+    #[allow(dead_code)]
+    pub async fn close(&mut self) -> io::Result<()> {
+        self.inner
+            .send(Message::Close(None))
+            .await
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        self.inner
+            .flush()
+            .await
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        Ok(())
+    }
+}
+
+impl<S> AsyncRead for WebSocketStreamAdapter<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send,
+{
+    fn poll_read(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> std::task::Poll<io::Result<()>> {
+        let this = self.get_mut();
+
+        // Phase 1 optimization: Cap max buffer size to prevent unbounded growth
+        const MAX_BUFFER_SIZE: usize = 1024 * 1024;  // 1MB max
+
+        if !this.read_buffer.is_empty() {
+            trace!("Using buffered data: {} bytes", this.read_buffer.len());
+            let to_copy = std::cmp::min(buf.remaining(), this.read_buffer.len());
+            buf.put_slice(&this.read_buffer[..to_copy]);
+            this.read_buffer.drain(..to_copy);
+
+            // Shrink buffer if it's grown too large and is now mostly empty
+            if this.read_buffer.capacity() > MAX_BUFFER_SIZE && this.read_buffer.len() < MAX_BUFFER_SIZE / 4 {
+                this.read_buffer.shrink_to(MAX_BUFFER_SIZE / 2);
+                trace!("Shrunk WebSocket read buffer to {}", this.read_buffer.capacity());
+            }
+
+            return std::task::Poll::Ready(Ok(()));
+        }
+
+        match this.inner.try_poll_next_unpin(cx) {
+            std::task::Poll::Ready(Some(Ok(Message::Text(text)))) => {
+                trace!("Received WebSocket text message: {}", text);
+                this.read_buffer.extend_from_slice(text.as_bytes());
+                let to_copy = std::cmp::min(buf.remaining(), this.read_buffer.len());
+                buf.put_slice(&this.read_buffer[..to_copy]);
+                this.read_buffer.drain(..to_copy);
+                std::task::Poll::Ready(Ok(()))
+            }
+            std::task::Poll::Ready(Some(Ok(Message::Binary(data)))) => {
+                trace!("Received WebSocket binary message: {:?}", data);
+                this.read_buffer.extend_from_slice(&data);
+                let to_copy = std::cmp::min(buf.remaining(), this.read_buffer.len());
+                buf.put_slice(&this.read_buffer[..to_copy]);
+                this.read_buffer.drain(..to_copy);
+                std::task::Poll::Ready(Ok(()))
+            }
+            std::task::Poll::Ready(Some(Ok(Message::Ping(_)))) => {
+                trace!("Received WebSocket ping message");
+                std::task::Poll::Ready(Ok(()))
+            }
+            std::task::Poll::Ready(Some(Ok(Message::Pong(_)))) => {
+                trace!("Received WebSocket pong message");
+                std::task::Poll::Ready(Ok(()))
+            }
+            std::task::Poll::Ready(Some(Ok(Message::Frame(_)))) => {
+                trace!("Received WebSocket frame message");
+                std::task::Poll::Ready(Ok(()))
+            }
+            std::task::Poll::Ready(Some(Ok(Message::Close(_)))) => {
+                trace!("Received WebSocket close message");
+                std::task::Poll::Ready(Ok(()))
+            }
+            std::task::Poll::Ready(Some(Err(e))) => {
+                trace!("WebSocket error: {}", e);
+                std::task::Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, e)))
+            }
+            std::task::Poll::Ready(None) => {
+                trace!("WebSocket stream closed");
+                std::task::Poll::Ready(Ok(()))
+            }
+            std::task::Poll::Pending => {
+                trace!("WebSocket poll pending");
+                std::task::Poll::Pending
+            }
+        }
+    }
+}
+
+impl<S> AsyncWrite for WebSocketStreamAdapter<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send,
+{
+    fn poll_write(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &[u8],
+    ) -> std::task::Poll<io::Result<usize>> {
+        let this = self.get_mut();
+
+        // Phase 4 optimization: Buffer small writes to reduce WebSocket frame overhead
+        // Max buffer size before forcing a flush (16KB threshold)
+        const MAX_WRITE_BUFFER: usize = 16 * 1024;
+
+        // If buffer would exceed threshold, flush it first
+        if !this.write_buffer.is_empty() && (this.write_buffer.len() + buf.len() > MAX_WRITE_BUFFER) {
+            match this.inner.poll_ready_unpin(cx) {
+                std::task::Poll::Ready(Ok(())) => {
+                    // Send buffered data as binary frame
+                    match this.inner.start_send_unpin(Message::Binary(std::mem::take(&mut this.write_buffer))) {
+                        Ok(()) => {}
+                        Err(e) => return std::task::Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, e))),
+                    }
+                }
+                std::task::Poll::Ready(Err(e)) => {
+                    return std::task::Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, e)));
+                }
+                std::task::Poll::Pending => return std::task::Poll::Pending,
+            }
+        }
+
+        // Add data to write buffer
+        this.write_buffer.extend_from_slice(buf);
+        std::task::Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<io::Result<()>> {
+        let this = self.get_mut();
+
+        // Phase 4 optimization: Flush any buffered writes before flushing underlying stream
+        if !this.write_buffer.is_empty() {
+            match this.inner.poll_ready_unpin(cx) {
+                std::task::Poll::Ready(Ok(())) => {
+                    // Send buffered data as binary frame
+                    match this.inner.start_send_unpin(Message::Binary(std::mem::take(&mut this.write_buffer))) {
+                        Ok(()) => {}
+                        Err(e) => return std::task::Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, e))),
+                    }
+                }
+                std::task::Poll::Ready(Err(e)) => {
+                    return std::task::Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, e)));
+                }
+                std::task::Poll::Pending => return std::task::Poll::Pending,
+            }
+        }
+
+        // Flush underlying WebSocket stream
+        match this.inner.poll_flush_unpin(cx) {
+            std::task::Poll::Ready(Ok(())) => std::task::Poll::Ready(Ok(())),
+            std::task::Poll::Ready(Err(e)) => std::task::Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, e))),
+            std::task::Poll::Pending => std::task::Poll::Pending,
+        }
+    }
+
+    fn poll_shutdown(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<io::Result<()>> {
+        let this = self.get_mut();
+
+        // Phase 4 optimization: Flush any remaining buffered writes before shutdown
+        if !this.write_buffer.is_empty() {
+            match this.inner.poll_ready_unpin(cx) {
+                std::task::Poll::Ready(Ok(())) => {
+                    // Send remaining buffered data as binary frame
+                    match this.inner.start_send_unpin(Message::Binary(std::mem::take(&mut this.write_buffer))) {
+                        Ok(()) => {}
+                        Err(e) => return std::task::Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, e))),
+                    }
+                }
+                std::task::Poll::Ready(Err(e)) => {
+                    return std::task::Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, e)));
+                }
+                std::task::Poll::Pending => return std::task::Poll::Pending,
+            }
+        }
+
+        // Close underlying WebSocket stream
+        match this.inner.poll_close_unpin(cx) {
+            std::task::Poll::Ready(Ok(())) => std::task::Poll::Ready(Ok(())),
+            std::task::Poll::Ready(Err(e)) => std::task::Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, e))),
+            std::task::Poll::Pending => std::task::Poll::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn test_adapter_round_trip() {
+        let (client_io, server_io) = tokio::io::duplex(1024);
+        let (server, client) = tokio::join!(
+            accept_async(server_io),
+            tokio_tungstenite::client_async("ws://localhost/", client_io),
+        );
+        let mut server = WebSocketStreamAdapter::new(server.unwrap());
+        let (mut client, _) = client.unwrap();
+
+        client.send(Message::Text("Content-Length: 2\r\n\r\n{}".into())).await.unwrap();
+        let mut buf = vec![0u8; 23];
+        server.read_exact(&mut buf).await.unwrap();
+        assert_eq!(buf, b"Content-Length: 2\r\n\r\n{}");
+
+        server.write_all(b"abc").await.unwrap();
+        server.write_all(b"def").await.unwrap();
+        server.flush().await.unwrap();
+        let reply = client.try_next().await.unwrap().unwrap();
+        assert_eq!(reply, Message::Binary(b"abcdef".to_vec()));
+    }
+}
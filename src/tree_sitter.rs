@@ -5,4 +5,6 @@
 //!
 //! **Note**: New code should use `crate::parsers::rholang` directly.
 
-pub use crate::parsers::rholang::{parse_code, parse_to_ir, parse_to_document_ir, update_tree};
+pub use crate::parsers::rholang::{parse_code, parse_to_ir, parse_to_document_ir, update_tree, ParseKind};
+#[cfg(test)]
+pub use crate::parsers::rholang::parse_with_positions;
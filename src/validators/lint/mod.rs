@@ -0,0 +1,235 @@
+//! Lint rules configured in `.rholangrc.toml`
+//!
+//! Each [`Rule`] inspects the IR of a document, with its lexical scopes
+//! (see `crate::lsp::scopes`), and reports findings that become diagnostics
+//! with the rule's code and the [`LINT_SOURCE`] source. The rules are off
+//! unless enabled under `[lints.rules]`, by code or all at once:
+//!
+//! ```toml
+//! [lints.rules]
+//! severity = "warning"            # error | warning | information | hint
+//! all = true                      # enable every rule not listed below
+//! shadowed-name = false
+//! ```
+//!
+//! | Code                     | Reports                                                  |
+//! |--------------------------|----------------------------------------------------------|
+//! | `unused-new-name`        | a name bound by `new` that is never used                 |
+//! | `unused-contract-formal` | a contract parameter its body never uses                 |
+//! | `send-never-received`    | a send on a `new` name nothing receives from             |
+//! | `shadowed-name`          | a binder hiding a name declared by an enclosing scope    |
+//! | `nil-only-branches`      | an `if`, `match` or `select` whose branches are all `Nil` |
+//! | `deprecated-syntax`      | legacy syntax, such as `@"stdout"` for system channels   |
+//!
+//! Names starting with `_` are never reported as unused or shadowed.
+
+mod rules;
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use ropey::Rope;
+use serde::Deserialize;
+use tower_lsp::lsp_types::{
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticTag, Location, NumberOrString, Range, Url,
+};
+
+use crate::ir::rholang_node::{Position as IrPosition, RholangNode};
+use crate::lsp::line_index::{lsp_range, LineIndex};
+use crate::lsp::scopes::{ScopeAnalysis, Span};
+use crate::lsp::style_lints::LintSeverity;
+
+pub use rules::{
+    DeprecatedSyntax, NilOnlyBranches, SendNeverReceived, ShadowedName, UnusedContractFormal, UnusedNewName,
+};
+
+/// Diagnostic source for lint rules
+pub const LINT_SOURCE: &str = "rholang-lint";
+
+/// What a rule needs to know about a document
+pub struct LintContext<'a> {
+    pub(crate) ir: &'a Arc<RholangNode>,
+    pub(crate) positions: &'a HashMap<usize, (IrPosition, IrPosition)>,
+    pub(crate) scopes: &'a ScopeAnalysis,
+    pub(crate) text: &'a Rope,
+    pub(crate) line_index: &'a LineIndex,
+}
+
+impl LintContext<'_> {
+    /// LSP range of an IR span of the document
+    pub(crate) fn range(&self, span: &Span) -> Range {
+        lsp_range(self.text, self.line_index, span)
+    }
+}
+
+/// Something a rule found
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintFinding {
+    pub range: Range,
+    pub message: String,
+    /// Another location explaining the finding, such as the shadowed declaration
+    pub related: Option<(Range, String)>,
+}
+
+/// A lint rule
+pub trait Rule: Send + Sync {
+    /// Code of the rule's diagnostics, also its key under `[lints.rules]`; published
+    /// under its `RHO3xx` code (see `crate::lsp::diagnostic_registry`)
+    fn code(&self) -> &'static str;
+
+    /// Tag of the rule's diagnostics, if any.
+    fn tag(&self) -> Option<DiagnosticTag> {
+        None
+    }
+
+    /// Findings in the document, in any order.
+    fn check(&self, context: &LintContext<'_>) -> Vec<LintFinding>;
+}
+
+/// Every rule, in the order of the table above
+pub fn all_rules() -> Vec<Box<dyn Rule>> {
+    vec![
+        Box::new(UnusedNewName),
+        Box::new(UnusedContractFormal),
+        Box::new(SendNeverReceived),
+        Box::new(ShadowedName),
+        Box::new(NilOnlyBranches),
+        Box::new(DeprecatedSyntax),
+    ]
+}
+
+/// `[lints.rules]` section of `.rholangrc.toml`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct LintConfig {
+    pub severity: LintSeverity,
+    /// Whether rules that are not listed are enabled
+    pub all: bool,
+    /// Rule code -> enabled
+    #[serde(flatten)]
+    pub rules: BTreeMap<String, bool>,
+}
+
+impl LintConfig {
+    /// Whether the rule with `code` is enabled
+    pub fn is_rule_enabled(&self, code: &str) -> bool {
+        self.rules.get(code).copied().unwrap_or(self.all)
+    }
+
+    /// Whether any rule is enabled
+    pub fn is_enabled(&self) -> bool {
+        self.all || self.rules.values().any(|enabled| *enabled)
+    }
+
+    /// Listed codes that are not the code of any rule
+    pub fn unknown_rules(&self) -> Vec<&str> {
+        let known: Vec<_> = all_rules().iter().map(|rule| rule.code()).collect();
+        self.rules.keys().map(String::as_str).filter(|code| !known.contains(code)).collect()
+    }
+}
+
+/// Runs the enabled rules on the document `uri` and returns their
+/// diagnostics, in document order.
+pub fn run_lints(
+    uri: &Url,
+    ir: &Arc<RholangNode>,
+    positions: &HashMap<usize, (IrPosition, IrPosition)>,
+    text: &Rope,
+    line_index: &LineIndex,
+    config: &LintConfig,
+) -> Vec<Diagnostic> {
+    let rules: Vec<_> = all_rules().into_iter().filter(|rule| config.is_rule_enabled(rule.code())).collect();
+    if rules.is_empty() {
+        return Vec::new();
+    }
+    let Some(scopes) = ScopeAnalysis::build(ir, positions) else {
+        return Vec::new();
+    };
+    let context = LintContext { ir, positions, scopes: &scopes, text, line_index };
+
+    let mut diagnostics: Vec<Diagnostic> = rules
+        .iter()
+        .flat_map(|rule| {
+            rule.check(&context).into_iter().map(|finding| Diagnostic {
+                range: finding.range,
+                severity: Some(config.severity.into()),
+                code: Some(NumberOrString::String(rule.code().to_string())),
+                source: Some(LINT_SOURCE.to_string()),
+                message: finding.message,
+                related_information: finding.related.map(|(range, message)| {
+                    vec![DiagnosticRelatedInformation { location: Location { uri: uri.clone(), range }, message }]
+                }),
+                tags: rule.tag().map(|tag| vec![tag]),
+                ..Default::default()
+            })
+        })
+        .collect();
+    diagnostics.sort_by_key(|d| (d.range.start.line, d.range.start.character));
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lsp::style_lints::StyleLintConfig;
+    use crate::tree_sitter::parse_with_positions;
+    use ropey::Rope;
+    use tower_lsp::lsp_types::DiagnosticSeverity;
+
+    fn lint(source: &str, config: &LintConfig) -> Vec<Diagnostic> {
+        let rope = Rope::from_str(source);
+        let (ir, positions) = parse_with_positions(source);
+        run_lints(&Url::parse("file:///lint.rho").unwrap(), &ir, &positions, &rope, &LineIndex::new(source), config)
+    }
+
+    fn codes(diagnostics: &[Diagnostic]) -> Vec<String> {
+        diagnostics
+            .iter()
+            .filter_map(|d| match &d.code {
+                Some(NumberOrString::String(code)) => Some(code.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    const SOURCE: &str = r#"new unused, x in {
+  x!(1)
+}"#;
+
+    #[test]
+    fn test_rules_are_off_by_default() {
+        assert!(lint(SOURCE, &LintConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn test_rules_are_toggled_individually() {
+        let config = StyleLintConfig::from_toml_str(
+            "[lints.rules]\nseverity = \"hint\"\nall = true\nsend-never-received = false\n",
+        )
+        .unwrap()
+        .rules;
+        let diagnostics = lint(SOURCE, &config);
+        assert_eq!(codes(&diagnostics), vec!["unused-new-name"]);
+        assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::HINT));
+        assert_eq!(diagnostics[0].source.as_deref(), Some(LINT_SOURCE));
+        assert_eq!(diagnostics[0].tags, Some(vec![DiagnosticTag::UNNECESSARY]));
+
+        let config = StyleLintConfig::from_toml_str("[lints.rules]\nsend-never-received = true\n").unwrap().rules;
+        assert_eq!(codes(&lint(SOURCE, &config)), vec!["send-never-received"]);
+    }
+
+    #[test]
+    fn test_rule_codes_are_distinct_and_known() {
+        let codes: Vec<_> = all_rules().iter().map(|rule| rule.code()).collect();
+        let mut unique = codes.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), codes.len());
+
+        let config = StyleLintConfig::from_toml_str("[lints.rules]\nunused-new-name = true\nno-such-rule = true\n")
+            .unwrap()
+            .rules;
+        assert!(config.is_enabled());
+        assert_eq!(config.unknown_rules(), vec!["no-such-rule"]);
+    }
+}
@@ -0,0 +1,390 @@
+//! The initial rule set of `crate::validators::lint`
+
+use std::cell::RefCell;
+use std::sync::Arc;
+
+use tower_lsp::lsp_types::{DiagnosticTag, Range};
+
+use crate::i18n::tr;
+use crate::ir::rholang_node::{
+    Metadata, NodeBase, Position as IrPosition, RholangBranchVector, RholangNode, RholangNodePairVector,
+};
+use crate::ir::visitor::Visitor;
+use crate::lsp::scopes::{Binder, BinderOrigin, ScopeAnalysis, ScopeKind, UseRole};
+
+use super::{LintContext, LintFinding, Rule};
+
+/// Quoted string channels that older Rholang used for system processes,
+/// with the URI of the system name replacing them
+const LEGACY_SYSTEM_CHANNELS: &[(&str, &str)] = &[
+    ("stdout", "rho:io:stdout"),
+    ("stdoutAck", "rho:io:stdoutAck"),
+    ("stderr", "rho:io:stderr"),
+    ("stderrAck", "rho:io:stderrAck"),
+];
+
+/// Names starting with `_` are declared unused on purpose.
+fn is_exempt(name: &str) -> bool {
+    name.starts_with('_')
+}
+
+/// Binders of the scopes of `kind` that are never used.
+fn unused_binders(scopes: &ScopeAnalysis, kind: ScopeKind) -> impl Iterator<Item = &Binder> {
+    scopes
+        .scopes
+        .iter()
+        .enumerate()
+        .filter(move |(_, scope)| scope.kind == kind)
+        .flat_map(move |(scope, declared)| {
+            declared
+                .binders
+                .iter()
+                .enumerate()
+                .filter(move |(index, binder)| !is_exempt(&binder.name) && scopes.uses_of(scope, *index).next().is_none())
+                .map(|(_, binder)| binder)
+        })
+}
+
+/// A name bound by `new` that is never used
+pub struct UnusedNewName;
+
+impl Rule for UnusedNewName {
+    fn code(&self) -> &'static str {
+        "unused-new-name"
+    }
+
+    fn tag(&self) -> Option<DiagnosticTag> {
+        Some(DiagnosticTag::UNNECESSARY)
+    }
+
+    fn check(&self, context: &LintContext<'_>) -> Vec<LintFinding> {
+        unused_binders(context.scopes, ScopeKind::New)
+            .map(|binder| LintFinding {
+                range: context.range(&binder.span),
+                message: tr("lint.unused-new-name", &[("name", &binder.name)]),
+                related: None,
+            })
+            .collect()
+    }
+}
+
+/// A contract parameter the contract's body never uses
+pub struct UnusedContractFormal;
+
+impl Rule for UnusedContractFormal {
+    fn code(&self) -> &'static str {
+        "unused-contract-formal"
+    }
+
+    fn tag(&self) -> Option<DiagnosticTag> {
+        Some(DiagnosticTag::UNNECESSARY)
+    }
+
+    fn check(&self, context: &LintContext<'_>) -> Vec<LintFinding> {
+        unused_binders(context.scopes, ScopeKind::Contract)
+            .map(|binder| LintFinding {
+                range: context.range(&binder.span),
+                message: tr("lint.unused-contract-formal", &[("name", &binder.name)]),
+                related: None,
+            })
+            .collect()
+    }
+}
+
+/// A send on a `new` name that is only ever sent on, so no message sent
+/// on it is ever consumed
+pub struct SendNeverReceived;
+
+impl Rule for SendNeverReceived {
+    fn code(&self) -> &'static str {
+        "send-never-received"
+    }
+
+    fn check(&self, context: &LintContext<'_>) -> Vec<LintFinding> {
+        let scopes = context.scopes;
+        let mut findings = Vec::new();
+        for (scope, declared) in scopes.scopes.iter().enumerate() {
+            for (index, binder) in declared.binders.iter().enumerate() {
+                // System names are received from by the node
+                if binder.origin != (BinderOrigin::New { uri: false }) {
+                    continue;
+                }
+                let uses: Vec<_> = scopes.uses_of(scope, index).collect();
+                if uses.is_empty() || uses.iter().any(|u| u.role != UseRole::Send) {
+                    continue;
+                }
+                findings.extend(uses.iter().map(|u| LintFinding {
+                    range: context.range(&u.span),
+                    message: tr("lint.send-never-received", &[("name", &binder.name)]),
+                    related: Some((context.range(&binder.span), tr("lint.declared-here", &[("name", &binder.name)]))),
+                }));
+            }
+        }
+        findings
+    }
+}
+
+/// A binder hiding a name declared by an enclosing scope
+pub struct ShadowedName;
+
+impl Rule for ShadowedName {
+    fn code(&self) -> &'static str {
+        "shadowed-name"
+    }
+
+    fn check(&self, context: &LintContext<'_>) -> Vec<LintFinding> {
+        let scopes = &context.scopes.scopes;
+        let mut findings = Vec::new();
+        for declared in scopes.iter() {
+            for binder in &declared.binders {
+                if is_exempt(&binder.name) || binder.origin == BinderOrigin::Implicit {
+                    continue;
+                }
+                let shadowed = std::iter::successors(declared.parent, |&scope| scopes[scope].parent).find_map(|scope| {
+                    scopes[scope]
+                        .binders
+                        .iter()
+                        .rev()
+                        .find(|outer| outer.name == binder.name && outer.origin != BinderOrigin::Implicit)
+                });
+                if let Some(outer) = shadowed {
+                    findings.push(LintFinding {
+                        range: context.range(&binder.span),
+                        message: tr("lint.shadowed-name", &[("name", &binder.name)]),
+                        related: Some((context.range(&outer.span), tr("lint.shadowed-declaration", &[("name", &outer.name)]))),
+                    });
+                }
+            }
+        }
+        findings
+    }
+}
+
+/// An `if`, `match` or `select` whose branches are all `Nil`
+pub struct NilOnlyBranches;
+
+impl Rule for NilOnlyBranches {
+    fn code(&self) -> &'static str {
+        "nil-only-branches"
+    }
+
+    fn check(&self, context: &LintContext<'_>) -> Vec<LintFinding> {
+        let finder = NilBranchFinder { context, findings: RefCell::new(Vec::new()) };
+        finder.visit_node(context.ir);
+        finder.findings.into_inner()
+    }
+}
+
+/// Whether `proc` is `Nil`, possibly in braces or parentheses.
+fn is_nil(proc: &Arc<RholangNode>) -> bool {
+    match &**proc {
+        RholangNode::Nil { .. } => true,
+        RholangNode::Block { proc, .. } | RholangNode::Parenthesized { expr: proc, .. } => is_nil(proc),
+        _ => false,
+    }
+}
+
+/// Range of the keyword opening the construct `node`.
+fn keyword_range(context: &LintContext<'_>, node: &Arc<RholangNode>, keyword: &str) -> Option<Range> {
+    let &(start, _) = context.positions.get(&(Arc::as_ptr(node) as usize))?;
+    let end = IrPosition { row: start.row, column: start.column + keyword.len(), byte: start.byte + keyword.len() };
+    Some(context.range(&(start, end)))
+}
+
+struct NilBranchFinder<'a> {
+    context: &'a LintContext<'a>,
+    findings: RefCell<Vec<LintFinding>>,
+}
+
+impl NilBranchFinder<'_> {
+    fn report(&self, node: &Arc<RholangNode>, keyword: &str) {
+        if let Some(range) = keyword_range(self.context, node, keyword) {
+            self.findings.borrow_mut().push(LintFinding {
+                range,
+                message: tr("lint.nil-only-branches", &[("construct", &keyword)]),
+                related: None,
+            });
+        }
+    }
+}
+
+impl Visitor for NilBranchFinder<'_> {
+    fn visit_ifelse(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        condition: &Arc<RholangNode>,
+        consequence: &Arc<RholangNode>,
+        alternative: &Option<Arc<RholangNode>>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        if is_nil(consequence) && alternative.as_ref().is_none_or(is_nil) {
+            self.report(node, "if");
+        }
+        self.visit_node(condition);
+        self.visit_node(consequence);
+        if let Some(alternative) = alternative {
+            self.visit_node(alternative);
+        }
+        Arc::clone(node)
+    }
+
+    fn visit_match(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        expression: &Arc<RholangNode>,
+        cases: &RholangNodePairVector,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        if !cases.is_empty() && cases.iter().all(|(_, proc)| is_nil(proc)) {
+            self.report(node, "match");
+        }
+        self.visit_node(expression);
+        for (_, proc) in cases.iter() {
+            self.visit_node(proc);
+        }
+        Arc::clone(node)
+    }
+
+    fn visit_choice(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        branches: &RholangBranchVector,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        if !branches.is_empty() && branches.iter().all(|(_, proc)| is_nil(proc)) {
+            self.report(node, "select");
+        }
+        for (inputs, proc) in branches.iter() {
+            for input in inputs.iter() {
+                self.visit_node(input);
+            }
+            self.visit_node(proc);
+        }
+        Arc::clone(node)
+    }
+}
+
+/// Legacy syntax: quoted string channels such as `@"stdout"`, which no
+/// longer reach the system processes they once named
+pub struct DeprecatedSyntax;
+
+impl Rule for DeprecatedSyntax {
+    fn code(&self) -> &'static str {
+        "deprecated-syntax"
+    }
+
+    fn tag(&self) -> Option<DiagnosticTag> {
+        Some(DiagnosticTag::DEPRECATED)
+    }
+
+    fn check(&self, context: &LintContext<'_>) -> Vec<LintFinding> {
+        let finder = LegacySyntaxFinder { context, findings: RefCell::new(Vec::new()) };
+        finder.visit_node(context.ir);
+        finder.findings.into_inner()
+    }
+}
+
+struct LegacySyntaxFinder<'a> {
+    context: &'a LintContext<'a>,
+    findings: RefCell<Vec<LintFinding>>,
+}
+
+impl Visitor for LegacySyntaxFinder<'_> {
+    fn visit_quote(
+        &self,
+        node: &Arc<RholangNode>,
+        _base: &NodeBase,
+        quotable: &Arc<RholangNode>,
+        _metadata: &Option<Arc<Metadata>>,
+    ) -> Arc<RholangNode> {
+        let legacy = match &**quotable {
+            RholangNode::StringLiteral { value, .. } => LEGACY_SYSTEM_CHANNELS.iter().find(|(name, _)| *name == value.as_str()),
+            _ => None,
+        };
+        match (legacy, self.context.positions.get(&(Arc::as_ptr(node) as usize))) {
+            (Some((name, uri)), Some(span)) => self.findings.borrow_mut().push(LintFinding {
+                range: self.context.range(span),
+                message: tr("lint.legacy-system-channel", &[("name", name), ("uri", uri)]),
+                related: None,
+            }),
+            _ => {
+                self.visit_node(quotable);
+            }
+        }
+        Arc::clone(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lsp::line_index::LineIndex;
+    use crate::tree_sitter::parse_with_positions;
+    use ropey::Rope;
+
+    /// Starts of the findings of `rule` in `source`, sorted
+    fn findings(rule: &dyn Rule, source: &str) -> Vec<(u32, u32)> {
+        let rope = Rope::from_str(source);
+        let (ir, positions) = parse_with_positions(source);
+        let scopes = ScopeAnalysis::build(&ir, &positions).unwrap();
+        let mut found: Vec<_> = rule
+            .check(&LintContext {
+                ir: &ir,
+                positions: &positions,
+                scopes: &scopes,
+                text: &rope,
+                line_index: &LineIndex::new(source),
+            })
+            .into_iter()
+            .map(|finding| (finding.range.start.line, finding.range.start.character))
+            .collect();
+        found.sort();
+        found
+    }
+
+    #[test]
+    fn test_unused_new_names() {
+        let source = "new used, unused, _ignored, stdout(`rho:io:stdout`) in {\n  used!(1) | for (_ <- used) { Nil }\n}";
+        assert_eq!(findings(&UnusedNewName, source), vec![(0, 10), (0, 28)]);
+    }
+
+    #[test]
+    fn test_unused_contract_formals() {
+        let source = "contract log(@msg, ret, _unused) = {\n  ret!(Nil)\n}";
+        assert_eq!(findings(&UnusedContractFormal, source), vec![(0, 14)]);
+    }
+
+    #[test]
+    fn test_sends_on_never_received_channels() {
+        let source = "new sink, ok, passed, out(`rho:io:stdout`) in {\n  sink!(1) | sink!(2) |\n  ok!(1) | for (_ <- ok) { Nil } |\n  passed!(1) | out!(*passed)\n}";
+        assert_eq!(findings(&SendNeverReceived, source), vec![(1, 2), (1, 13)]);
+    }
+
+    #[test]
+    fn test_sends_through_quote_eval_round_trips() {
+        // `@*sink` is `sink`, so both sends go unreceived and `ok` is received from
+        let source = "new sink, ok in {\n  sink!(1) | @*sink!(2) |\n  ok!(1) | for (_ <- @*ok) { Nil }\n}";
+        assert_eq!(findings(&SendNeverReceived, source), vec![(1, 2), (1, 15)]);
+    }
+
+    #[test]
+    fn test_shadowed_names() {
+        let source = "new x, ret in {\n  contract c(ret) = {\n    for (@x <- ret) { x!(1) }\n  } | x!(1) | ret!(1)\n}";
+        assert_eq!(findings(&ShadowedName, source), vec![(1, 13), (2, 10)]);
+    }
+
+    #[test]
+    fn test_nil_only_branches() {
+        let source = "new ch in {\n  if (true) { Nil } else { Nil } |\n  if (true) { ch!(1) } |\n  match 1 { 1 => Nil\n    _ => { Nil } } |\n  match 2 { 2 => ch!(2)\n    _ => Nil }\n}";
+        assert_eq!(findings(&NilOnlyBranches, source), vec![(1, 2), (3, 2)]);
+    }
+
+    #[test]
+    fn test_legacy_system_channels() {
+        let source = "@\"stdout\"!(\"hi\") | @\"mine\"!(1) | for (_ <- @\"stderrAck\") { Nil }";
+        assert_eq!(findings(&DeprecatedSyntax, source), vec![(0, 0), (0, 43)]);
+    }
+}
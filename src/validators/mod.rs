@@ -1,5 +1,9 @@
 //! Validator modules for different languages
 
+pub mod lint;
+
+#[cfg(feature = "metta")]
 pub mod metta_validator;
 
+#[cfg(feature = "metta")]
 pub use metta_validator::MettaValidator;
@@ -3,19 +3,59 @@
 //! This module provides async wrappers around stdin/stdout that log all LSP messages
 //! passing through the transport layer. It works by parsing the JSON-RPC messages
 //! from the raw byte streams before/after they're processed by tower-lsp.
+//!
+//! With a [`RequestClock`], the same wrappers time every client request from
+//! its arrival to its response and record it in the global metrics. With a
+//! [`SpecChecker`] (`--strict-lsp`), every outgoing message is checked
+//! against the LSP specification.
 
+use std::collections::HashMap;
 use std::io;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::Instant;
+use parking_lot::Mutex;
 use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
 use serde_json::Value;
 
+use crate::metrics::metrics;
+use crate::strict_lsp::SpecChecker;
 use crate::wire_logger::WireLogger;
 
+/// Times the client requests of one connection, from request to response
+#[derive(Debug, Clone, Default)]
+pub struct RequestClock {
+    /// Method and arrival of each unanswered request, by JSON-RPC id
+    pending: Arc<Mutex<HashMap<String, (String, Instant)>>>,
+}
+
+impl RequestClock {
+    /// Notes an incoming message; requests start their clock.
+    fn incoming(&self, message: &Value) {
+        if let (Some(id), Some(method)) = (message.get("id"), message.get("method").and_then(Value::as_str)) {
+            self.pending.lock().insert(id.to_string(), (method.to_string(), Instant::now()));
+        }
+    }
+
+    /// Notes an outgoing message; responses record the latency of their request.
+    fn outgoing(&self, message: &Value) {
+        if message.get("method").is_some() {
+            return;
+        }
+        let Some(id) = message.get("id") else { return };
+        if let Some((method, arrived)) = self.pending.lock().remove(&id.to_string()) {
+            metrics().record_request(&method, arrived.elapsed());
+        }
+    }
+}
+
 /// Wrapper around AsyncRead that logs incoming LSP messages
 pub struct LoggingReader<R> {
     inner: R,
     wire_logger: WireLogger,
+    request_clock: Option<RequestClock>,
+    spec_checker: Option<SpecChecker>,
     buffer: Vec<u8>,
 }
 
@@ -27,9 +67,23 @@ where
         Self {
             inner,
             wire_logger,
+            request_clock: None,
+            spec_checker: None,
             buffer: Vec::new(),
         }
     }
+
+    /// Also starts `clock` for every request read.
+    pub fn with_request_clock(mut self, clock: RequestClock) -> Self {
+        self.request_clock = Some(clock);
+        self
+    }
+
+    /// Also tells `checker` about every request read.
+    pub fn with_spec_checker(mut self, checker: SpecChecker) -> Self {
+        self.spec_checker = Some(checker);
+        self
+    }
 }
 
 impl<R> AsyncRead for LoggingReader<R>
@@ -57,6 +111,12 @@ where
                     // Log the incoming message
                     if let Ok(json) = serde_json::from_str::<Value>(&message) {
                         self.wire_logger.log_incoming(&json);
+                        if let Some(clock) = &self.request_clock {
+                            clock.incoming(&json);
+                        }
+                        if let Some(checker) = &self.spec_checker {
+                            checker.incoming(&json);
+                        }
                     }
                 }
             }
@@ -70,6 +130,8 @@ where
 pub struct LoggingWriter<W> {
     inner: W,
     wire_logger: WireLogger,
+    request_clock: Option<RequestClock>,
+    spec_checker: Option<SpecChecker>,
     buffer: Vec<u8>,
 }
 
@@ -81,9 +143,23 @@ where
         Self {
             inner,
             wire_logger,
+            request_clock: None,
+            spec_checker: None,
             buffer: Vec::new(),
         }
     }
+
+    /// Also stops `clock` for every response written.
+    pub fn with_request_clock(mut self, clock: RequestClock) -> Self {
+        self.request_clock = Some(clock);
+        self
+    }
+
+    /// Also checks every message written with `checker`.
+    pub fn with_spec_checker(mut self, checker: SpecChecker) -> Self {
+        self.spec_checker = Some(checker);
+        self
+    }
 }
 
 impl<W> AsyncWrite for LoggingWriter<W>
@@ -107,6 +183,14 @@ where
                 // Log the outgoing message
                 if let Ok(json) = serde_json::from_str::<Value>(&message) {
                     self.wire_logger.log_outgoing(&json);
+                    if let Some(clock) = &self.request_clock {
+                        clock.outgoing(&json);
+                    }
+                    if let Some(checker) = &self.spec_checker {
+                        if let Err(e) = checker.outgoing(&json) {
+                            return Poll::Ready(Err(e));
+                        }
+                    }
                 }
             }
         }
@@ -185,6 +269,20 @@ mod tests {
         assert_eq!(buffer.len(), 38);
     }
 
+    #[test]
+    fn test_request_clock_pairs_responses_with_requests() {
+        let clock = RequestClock::default();
+        clock.incoming(&serde_json::json!({"jsonrpc": "2.0", "id": 7, "method": "textDocument/hover"}));
+        clock.incoming(&serde_json::json!({"jsonrpc": "2.0", "method": "initialized"}));
+        assert_eq!(clock.pending.lock().len(), 1);
+
+        // A server-to-client request with the same id is not a response
+        clock.outgoing(&serde_json::json!({"jsonrpc": "2.0", "id": 7, "method": "window/workDoneProgress/create"}));
+        assert_eq!(clock.pending.lock().len(), 1);
+        clock.outgoing(&serde_json::json!({"jsonrpc": "2.0", "id": 7, "result": null}));
+        assert!(clock.pending.lock().is_empty());
+    }
+
     #[test]
     fn test_try_extract_message_multiple() {
         let mut buffer = b"Content-Length: 17\r\n\r\n{\"jsonrpc\":\"2.0\"}Content-Length: 17\r\n\r\n{\"jsonrpc\":\"2.0\"}".to_vec();
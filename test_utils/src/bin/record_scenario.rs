@@ -0,0 +1,29 @@
+//! Records a session scenario from a wire log.
+//!
+//! ```bash
+//! cargo run --manifest-path test_utils/Cargo.toml --bin record_scenario -- \
+//!     ~/.cache/f1r3fly-io/rholang-language-server/wire-<session>.log "my session" \
+//!     > tests/scenarios/my_session.json
+//! ```
+
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context};
+use test_utils::lsp::scenario::Scenario;
+
+fn main() -> anyhow::Result<()> {
+    let mut args = std::env::args().skip(1);
+    let path = args
+        .next()
+        .map(PathBuf::from)
+        .ok_or_else(|| anyhow!("usage: record_scenario <WIRE_LOG> [NAME]"))?;
+    let name = args.next().unwrap_or_else(|| {
+        path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default()
+    });
+
+    let wire_log = std::fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))?;
+    let scenario = Scenario::from_wire_log(&name, &wire_log)?;
+    eprintln!("Recorded {} step(s)", scenario.steps.len());
+    println!("{}", serde_json::to_string_pretty(&scenario)?);
+    Ok(())
+}
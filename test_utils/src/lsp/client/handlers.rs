@@ -17,7 +17,7 @@ use tracing::{debug, error, info, warn};
 
 use std::io::Read;
 use std::sync::atomic::Ordering;
-use std::thread;
+use std::sync::mpsc::RecvTimeoutError;
 use std::time::{Duration, Instant};
 
 use crate::lsp::client::LspClient;
@@ -28,6 +28,12 @@ pub type RequestHandler = fn(&LspClient, &Value) -> Result<Option<Arc<Value>>, S
 pub type NotificationHandler = fn(&LspClient, &Value) -> Result<(), String>;
 pub type ResponseHandler = fn(&LspClient, Arc<Value>) -> Result<(), String>;
 
+/// Custom request advancing the server's virtual clock in deterministic mode
+const ADVANCE_CLOCK_METHOD: &str = "rholang/advanceClock";
+
+/// How long the server may stay silent before it is considered idle
+const QUIET_PERIOD: Duration = Duration::from_millis(50);
+
 impl LspClient {
     fn next_request_id(&self) -> u64 {
         self.serial_request_id.fetch_add(1, Ordering::SeqCst)
@@ -138,10 +144,6 @@ impl LspClient {
             debug!("dispatch_response: Looking up handler for method: '{}'", method);
             debug!("dispatch_response: Available handlers: {:?}", self.response_handlers.keys().collect::<Vec<_>>());
 
-            // Workaround for Heisenbug: Add small delay to ensure proper synchronization
-            // This prevents race condition where handler lookup fails despite handlers being registered
-            thread::sleep(Duration::from_micros(10));
-
             if let Some(handler) = self.response_handlers.get(method) {
                 handler(self, response).map_err(|e| format!("Failed to handle response for '{}': {}", method, e))
             } else {
@@ -229,167 +231,133 @@ impl LspClient {
     }
 
     fn await_response(&self, request_id: u64) -> Result<Arc<Value>, String> {
-        // Check if response already available
-        {
-            let responses_by_id = self.responses_by_id.read().expect("Failed to acquire read lock on responses_by_id");
-            if let Some(response) = responses_by_id.get(&request_id) {
-                return Ok(response.clone());
-            }
-        }
+        self.await_message(
+            &format!("response with id {}", request_id),
+            Duration::from_secs(30),
+            true,
+            |client| client.response(request_id),
+        )
+    }
 
-        let timeout = Duration::from_secs(30);
-        let start = Instant::now();
+    pub fn await_diagnostics(&self, doc: &LspDocument) -> Result<Arc<PublishDiagnosticsParams>, String> {
+        // Increased for large files like robot_planning.rho
+        self.await_message(
+            &format!("diagnostics for document with URI: {}", doc.uri()),
+            Duration::from_secs(20),
+            true,
+            |client| {
+                let diagnostics_by_id = client.diagnostics_by_id.read().expect("Failed to acquire read lock on diagnostics_by_id");
+                diagnostics_by_id
+                    .get(&doc.id)
+                    .filter(|diagnostics| diagnostics.version == Some(doc.version.load(Ordering::Relaxed)))
+                    .cloned()
+            },
+        )
+    }
 
-        // Process messages until we find the response or timeout
+    fn response(&self, request_id: u64) -> Option<Arc<Value>> {
+        let responses_by_id = self.responses_by_id.read().expect("Failed to acquire read lock on responses_by_id");
+        responses_by_id.get(&request_id).cloned()
+    }
+
+    /// Processes server messages until `found` returns a value or `timeout`
+    /// elapses. With `advance_clock`, whenever the server goes quiet its
+    /// virtual clock (see [`DETERMINISTIC_FLAG`](crate::lsp::client::DETERMINISTIC_FLAG))
+    /// is moved to its next debounce window, which is what the awaited
+    /// message is usually waiting for.
+    fn await_message<T>(
+        &self,
+        what: &str,
+        timeout: Duration,
+        advance_clock: bool,
+        found: impl Fn(&Self) -> Option<T>,
+    ) -> Result<T, String> {
+        let start = Instant::now();
         loop {
-            // Check timeout before waiting
-            if start.elapsed() >= timeout {
-                return Err(format!("Timeout waiting for response with id {}", request_id));
+            if let Some(value) = found(self) {
+                return Ok(value);
             }
-
-            // Check if LSP server process is still alive
-            if let Ok(mut server_lock) = self.server.lock() {
-                if let Some(server) = server_lock.as_mut() {
-                    match server.try_wait() {
-                        Ok(Some(status)) => {
-                            // Server has exited! Read stderr for the actual error
-                            let stderr_text = if let Some(mut stderr) = server.stderr.take() {
-                                let mut buf = String::new();
-                                let _ = stderr.read_to_string(&mut buf);
-                                buf
-                            } else {
-                                String::from("(no stderr available)")
-                            };
-
-                            return Err(format!(
-                                "LSP server exited unexpectedly while waiting for response (id: {}) with status: {}.\nStderr:\n{}",
-                                request_id, status, stderr_text
-                            ));
-                        }
-                        Ok(None) => {
-                            // Server still running, continue
-                        }
-                        Err(e) => {
-                            return Err(format!("Failed to check server status: {}", e));
-                        }
-                    }
-                }
+            if start.elapsed() >= timeout {
+                return Err(format!("Timeout waiting for {}", what));
             }
+            self.check_server_running(what)?;
 
-            // Calculate remaining time
+            // The receiver lock must not be held while dispatching: handlers
+            // may await responses of their own
             let remaining = timeout.saturating_sub(start.elapsed());
-            let wait_duration = remaining.min(Duration::from_millis(50)); // Check every 50ms max
-
-            // Try to receive a message with a short timeout
-            match self.receiver.lock().expect("Failed to lock receiver").recv_timeout(wait_duration) {
+            let received = self.receiver.lock().expect("Failed to lock receiver").recv_timeout(remaining.min(QUIET_PERIOD));
+            match received {
                 Ok(message) => {
                     debug!("Processing message: {:?}", message);
-                    // Process the message
                     if let Err(e) = self.dispatch(message) {
                         return Err(format!("Failed to dispatch message: {}", e));
                     }
-
-                    // Check if we got the response we're waiting for
-                    let responses_by_id = self.responses_by_id.read().expect("Failed to acquire read lock on responses_by_id");
-                    if let Some(response) = responses_by_id.get(&request_id) {
-                        return Ok(response.clone());
-                    }
                 }
-                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
-                    // Timeout on recv - loop back to check overall timeout
-                    continue;
+                Err(RecvTimeoutError::Timeout) => {
+                    if advance_clock {
+                        self.advance_clock(None)?;
+                    }
                 }
-                Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => {
-                    return Err("LSP server disconnected while waiting for response".to_string());
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(format!("LSP server disconnected while waiting for {}", what));
                 }
             }
         }
     }
 
-    pub fn await_diagnostics(&self, doc: &LspDocument) -> Result<Arc<PublishDiagnosticsParams>, String> {
-        // Check if diagnostics already available
-        {
-            let diagnostics_by_id = self.diagnostics_by_id.read().expect("Failed to acquire read lock on diagnostics_by_id");
-            if let Some(diagnostics) = diagnostics_by_id.get(&doc.id) {
-                if let Some(version) = diagnostics.version {
-                    if version == doc.version.load(Ordering::Relaxed) {
-                        return Ok(diagnostics.clone());
-                    }
-                }
-            }
-        }
-
-        let timeout = Duration::from_secs(20);  // Increased for large files like robot_planning.rho
-        let start = Instant::now();
-
-        // Process messages until we find the diagnostic or timeout
-        // Use shorter recv_timeout to check for timeout periodically while remaining responsive
-        loop {
-            // Check timeout before waiting
-            if start.elapsed() >= timeout {
-                return Err(format!("Timeout waiting for diagnostics for document with URI: {}", doc.uri()));
-            }
+    /// Fails if the server process has exited.
+    fn check_server_running(&self, what: &str) -> Result<(), String> {
+        let Ok(mut server_lock) = self.server.lock() else {
+            return Ok(());
+        };
+        let Some(server) = server_lock.as_mut() else {
+            return Ok(());
+        };
+        match server.try_wait() {
+            Ok(Some(status)) => {
+                // Server has exited! Read stderr for the actual error
+                let stderr_text = if let Some(mut stderr) = server.stderr.take() {
+                    let mut buf = String::new();
+                    let _ = stderr.read_to_string(&mut buf);
+                    buf
+                } else {
+                    String::from("(no stderr available)")
+                };
 
-            // Check if LSP server process is still alive
-            if let Ok(mut server_lock) = self.server.lock() {
-                if let Some(server) = server_lock.as_mut() {
-                    match server.try_wait() {
-                        Ok(Some(status)) => {
-                            // Server has exited! Read stderr for the actual error
-                            let stderr_text = if let Some(mut stderr) = server.stderr.take() {
-                                let mut buf = String::new();
-                                let _ = stderr.read_to_string(&mut buf);
-                                buf
-                            } else {
-                                String::from("(no stderr available)")
-                            };
-
-                            return Err(format!(
-                                "LSP server exited unexpectedly while waiting for diagnostics (URI: {}) with status: {}.\nStderr:\n{}",
-                                doc.uri(), status, stderr_text
-                            ));
-                        }
-                        Ok(None) => {
-                            // Server still running, continue
-                        }
-                        Err(e) => {
-                            return Err(format!("Failed to check server status: {}", e));
-                        }
-                    }
-                }
+                Err(format!(
+                    "LSP server exited unexpectedly while waiting for {} with status: {}.\nStderr:\n{}",
+                    what, status, stderr_text
+                ))
             }
+            Ok(None) => Ok(()),
+            Err(e) => Err(format!("Failed to check server status: {}", e)),
+        }
+    }
 
-            // Calculate remaining time
-            let remaining = timeout.saturating_sub(start.elapsed());
-            let wait_duration = remaining.min(Duration::from_millis(50)); // Check every 50ms max
-
-            // Try to receive a message with a short timeout
-            match self.receiver.lock().expect("Failed to lock receiver").recv_timeout(wait_duration) {
-                Ok(message) => {
-                    // Process the message
-                    if let Err(e) = self.dispatch(message) {
-                        return Err(format!("Failed to dispatch message: {}", e));
-                    }
+    /// Advances the virtual clock of a server in deterministic mode by `by`,
+    /// or to its next debounce window, and returns the clock's reading.
+    pub fn advance_clock(&self, by: Option<Duration>) -> Result<Arc<Value>, String> {
+        let request_id = self.next_request_id();
+        let params = match by {
+            Some(by) => json!({ "milliseconds": by.as_millis() as u64 }),
+            None => json!({}),
+        };
+        self.send_request(request_id, ADVANCE_CLOCK_METHOD, Some(params));
+        // Never advance the clock while waiting for the clock to advance
+        self.await_message(
+            &format!("response with id {}", request_id),
+            Duration::from_secs(30),
+            false,
+            |client| client.response(request_id),
+        )
+    }
 
-                    // Check if we got the diagnostics we're waiting for
-                    let diagnostics_by_id = self.diagnostics_by_id.read().expect("Failed to acquire read lock on diagnostics_by_id");
-                    if let Some(diagnostics) = diagnostics_by_id.get(&doc.id) {
-                        if let Some(version) = diagnostics.version {
-                            if version == doc.version.load(Ordering::Relaxed) {
-                                return Ok(diagnostics.clone());
-                            }
-                        }
-                    }
-                }
-                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
-                    // Timeout on recv - loop back to check overall timeout
-                    continue;
-                }
-                Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => {
-                    return Err("LSP server disconnected while waiting for diagnostics".to_string());
-                }
-            }
+    pub fn receive_advance_clock(&self, json: Arc<Value>) -> Result<(), String> {
+        if let Some(error) = json.get("error") {
+            return Err(format!("Failed to advance the server clock: {}", error));
         }
+        debug!("Server clock advanced: {}", json["result"]);
+        Ok(())
     }
 
     pub fn initialize(&self) -> Result<Arc<Value>, String> {
@@ -397,6 +365,41 @@ impl LspClient {
         self.await_response(request_id)
     }
 
+    /// Capabilities of a modern editor, so features are served in their richest form.
+    fn client_capabilities() -> ClientCapabilities {
+        let text_document: TextDocumentClientCapabilities = serde_json::from_value(json!({
+            "hover": { "contentFormat": ["markdown", "plaintext"] },
+            "completion": {
+                "completionItem": {
+                    "snippetSupport": true,
+                    "documentationFormat": ["markdown", "plaintext"],
+                    "labelDetailsSupport": true
+                }
+            },
+            "documentSymbol": { "hierarchicalDocumentSymbolSupport": true },
+            "semanticTokens": {
+                "requests": { "full": { "delta": true } },
+                "tokenTypes": [],
+                "tokenModifiers": [],
+                "formats": ["relative"]
+            },
+            "publishDiagnostics": { "relatedInformation": true, "tagSupport": { "valueSet": [1, 2] } }
+        }))
+        .expect("valid client capabilities");
+        ClientCapabilities {
+            text_document: Some(TextDocumentClientCapabilities {
+                synchronization: Some(TextDocumentSyncClientCapabilities {
+                    dynamic_registration: Some(false),
+                    will_save: None,
+                    will_save_wait_until: None,
+                    did_save: None,
+                }),
+                ..text_document
+            }),
+            ..Default::default()
+        }
+    }
+
     fn send_initialize(&self) -> u64 {
         #[allow(deprecated)]
         let params = InitializeParams {
@@ -404,18 +407,7 @@ impl LspClient {
             process_id: Some(std::process::id()),
             root_uri: None,
             initialization_options: None,
-            capabilities: ClientCapabilities {
-                text_document: Some(TextDocumentClientCapabilities {
-                    synchronization: Some(TextDocumentSyncClientCapabilities {
-                        dynamic_registration: Some(false),
-                        will_save: None,
-                        will_save_wait_until: None,
-                        did_save: None,
-                    }),
-                    ..Default::default()
-                }),
-                ..Default::default()
-            },
+            capabilities: Self::client_capabilities(),
             trace: None,
             workspace_folders: None,
             client_info: None,
@@ -746,7 +738,10 @@ impl LspClient {
 
     pub fn exit(&self) -> Result<(), String> {
         self.send_exit();
-        thread::sleep(Duration::from_millis(5));
+        // The server closes its output once it has handled the notification
+        if !self.await_output_closed(Duration::from_secs(5)) {
+            warn!("Server output still open after exit");
+        }
         self.emit(LspEvent::Exit)
     }
 
@@ -1290,4 +1285,57 @@ impl LspClient {
         debug!("Received documentHighlight response");
         Ok(())
     }
+
+    /// Sends the custom `rholang/contracts` request; `None` lists the whole workspace.
+    pub fn contracts(&self, uri: Option<&str>) -> Result<Value, String> {
+        debug!("Sending rholang/contracts request for URI: {:?}", uri);
+
+        let params = match uri {
+            Some(uri) => json!({ "uri": uri }),
+            None => json!({}),
+        };
+
+        let request_id = self.next_request_id();
+        self.send_request(request_id, "rholang/contracts", Some(params));
+
+        let response = self.await_response(request_id)?;
+        if let Some(error) = response.get("error") {
+            Err(format!("rholang/contracts failed: {}", error))
+        } else if let Some(result) = response.get("result") {
+            Ok(result.clone())
+        } else {
+            Err("No result in rholang/contracts response".to_string())
+        }
+    }
+
+    pub fn receive_contracts(&self, _response: Arc<Value>) -> Result<(), String> {
+        debug!("Received rholang/contracts response");
+        Ok(())
+    }
+
+    pub fn execute_command(&self, command: &str, arguments: Vec<Value>) -> Result<Value, String> {
+        debug!("Sending workspace/executeCommand request: {}", command);
+
+        let params = json!({
+            "command": command,
+            "arguments": arguments,
+        });
+
+        let request_id = self.next_request_id();
+        self.send_request(request_id, "workspace/executeCommand", Some(params));
+
+        let response = self.await_response(request_id)?;
+        if let Some(error) = response.get("error") {
+            Err(format!("{} failed: {}", command, error))
+        } else if let Some(result) = response.get("result") {
+            Ok(result.clone())
+        } else {
+            Err(format!("No result in {} response", command))
+        }
+    }
+
+    pub fn receive_execute_command(&self, _response: Arc<Value>) -> Result<(), String> {
+        debug!("Received workspace/executeCommand response");
+        Ok(())
+    }
 }
@@ -4,7 +4,7 @@ use std::net::TcpListener;
 use std::process::{Child, Command, Stdio};
 use std::sync::{Arc, Mutex, RwLock};
 use std::sync::atomic::AtomicU64;
-use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
 use std::thread::{self, JoinHandle};
 use std::time::{Duration, Instant};
 
@@ -62,18 +62,16 @@ trait JoinHandleExt {
 
 impl JoinHandleExt for JoinHandle<()> {
     fn join_timeout(self, timeout: Duration) -> Result<(), Box<dyn std::any::Any + Send>> {
-        let start = Instant::now();
-        // Use exponential backoff for more responsive joins (necessary - no event-driven alternative)
-        let mut wait_time = Duration::from_millis(1);
-        while start.elapsed() < timeout {
-            if self.is_finished() {
-                return self.join();
-            }
-            thread::sleep(wait_time);
-            // Exponential backoff: 1ms, 2ms, 4ms, 8ms, ... up to 50ms
-            wait_time = (wait_time * 2).min(Duration::from_millis(50));
+        // Join on a helper thread that reports back, so the wait ends as soon
+        // as the thread does; on timeout the helper is left detached
+        let (joined_tx, joined_rx) = channel();
+        thread::spawn(move || {
+            let _ = joined_tx.send(self.join());
+        });
+        match joined_rx.recv_timeout(timeout) {
+            Ok(result) => result,
+            Err(_) => Err(Box::new("Thread join timeout")),
         }
-        Err(Box::new("Thread join timeout"))
     }
 }
 
@@ -99,6 +97,8 @@ pub struct LspClient {
     pub output_thread: Mutex<Option<JoinHandle<()>>>,
     pub input_thread: Mutex<Option<JoinHandle<()>>>,
     pub logger_thread: Mutex<Option<JoinHandle<()>>>,
+    /// Disconnected once the server's stderr closes, i.e. once it has exited
+    pub server_exited: Mutex<Receiver<()>>,
     pub event_sender: Sender<LspEvent>,
     pub tcp_write_stream: Mutex<Option<Arc<Mutex<tokio::io::WriteHalf<TcpStream>>>>>,
     #[cfg(windows)] pub pipe_write_stream: Mutex<Option<Arc<Mutex<tokio::io::WriteHalf<NamedPipeClient>>>>>,
@@ -108,20 +108,37 @@ pub struct LspClient {
     pub comm_type: CommType,
 }
 
-/// Spawn server with retry logic to handle file system contention in parallel tests
-async fn spawn_server_with_retry_stdio(
+/// Flag putting the server in deterministic mode: debounce windows on a
+/// virtual clock the client advances, seeded ids and single-threaded pools.
+/// Every server started by the tests gets it.
+pub const DETERMINISTIC_FLAG: &str = "--deterministic";
+
+/// Start of the line the server writes to stderr once its socket, pipe or
+/// WebSocket listener is bound (the server's `transport::LISTENING_LINE`).
+pub const LISTENING_LINE: &str = "Rholang language server listening on";
+
+/// How long a server may take to bind its listener
+const LISTEN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a server may take to end a connection the client closes
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Spawns the server in deterministic mode with the given stdin, retrying at
+/// once when the spawn fails: under parallel tests the executable can be busy
+/// (ETXTBSY) for as long as another test's fork has not exec'd yet.
+async fn spawn_server_with_retry(
     server_path: &str,
     server_args: &[&str],
+    stdin: fn() -> Stdio,
 ) -> io::Result<Child> {
     let max_attempts = 5;
-    let mut wait_time = Duration::from_millis(10);
     let mut last_error = None;
 
-    for attempt in 0..max_attempts {
+    for _ in 0..max_attempts {
         match Command::new(server_path)
             .args(server_args)
-            .envs(std::env::vars())
-            .stdin(Stdio::piped())
+            .arg(DETERMINISTIC_FLAG)
+            .stdin(stdin())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .spawn()
@@ -129,10 +146,7 @@ async fn spawn_server_with_retry_stdio(
             Ok(server) => return Ok(server),
             Err(e) => {
                 last_error = Some(e);
-                if attempt < max_attempts - 1 {
-                    tokio::time::sleep(wait_time).await;
-                    wait_time = (wait_time * 2).min(Duration::from_millis(100));
-                }
+                tokio::task::yield_now().await;
             }
         }
     }
@@ -140,36 +154,81 @@ async fn spawn_server_with_retry_stdio(
     Err(last_error.unwrap())
 }
 
-/// Spawn server with retry logic (null stdin variant)
-async fn spawn_server_with_retry_null(
-    server_path: &str,
-    server_args: &[&str],
-) -> io::Result<Child> {
-    let max_attempts = 5;
-    let mut wait_time = Duration::from_millis(10);
-    let mut last_error = None;
-
-    for attempt in 0..max_attempts {
-        match Command::new(server_path)
-            .args(server_args)
-            .envs(std::env::vars())
-            .stdin(Stdio::null())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-        {
-            Ok(server) => return Ok(server),
-            Err(e) => {
-                last_error = Some(e);
-                if attempt < max_attempts - 1 {
-                    tokio::time::sleep(wait_time).await;
-                    wait_time = (wait_time * 2).min(Duration::from_millis(100));
+/// Forwards the server's stderr to the client's stdout on a thread, sending
+/// `listening` once the server reports its listener bound. The thread drops
+/// `exited` when stderr closes, which happens when the server exits.
+fn spawn_logger(
+    logger: Box<dyn LspStream>,
+    listening: Option<tokio::sync::oneshot::Sender<()>>,
+    exited: Sender<()>,
+) -> JoinHandle<()> {
+    thread::spawn(move || {
+        let _exited = exited;
+        let mut listening = listening;
+        let marker = LISTENING_LINE.as_bytes();
+        // Bytes read since the last check, kept across reads in case the
+        // marker is split between two of them
+        let mut unmatched: Vec<u8> = Vec::new();
+        let mut client_stdout = std::io::stdout();
+        let mut logger = logger;
+        let mut read_buffer = vec![0u8; 4096];
+        loop {
+            match logger.read(&mut read_buffer) {
+                Ok(0) => {
+                    info!("Server logger closed.");
+                    if let Err(e) = client_stdout.flush() {
+                        error!("Error flushing client stdout: {}", e);
+                    }
+                    return;
+                }
+                Ok(n) => {
+                    if let Some(sender) = listening.take() {
+                        unmatched.extend_from_slice(&read_buffer[..n]);
+                        if unmatched.windows(marker.len()).any(|window| window == marker) {
+                            let _ = sender.send(());
+                        } else {
+                            let keep_from = unmatched.len().saturating_sub(marker.len() - 1);
+                            unmatched.drain(..keep_from);
+                            listening = Some(sender);
+                        }
+                    }
+                    if let Err(e) = client_stdout.write_all(&read_buffer[..n]) {
+                        error!("Error writing to client stdout: {}", e);
+                        return;
+                    }
+                    if let Err(e) = client_stdout.flush() {
+                        error!("Error flushing client stdout: {}", e);
+                    }
+                }
+                Err(e) => {
+                    if e.kind() == io::ErrorKind::BrokenPipe {
+                        info!("Server logger pipe broken, exiting.");
+                    } else {
+                        error!("Error reading from server logger: {}", e);
+                    }
+                    if let Err(e) = client_stdout.flush() {
+                        error!("Error flushing client stdout: {}", e);
+                    }
+                    return;
                 }
             }
         }
-    }
+    })
+}
 
-    Err(last_error.unwrap())
+/// Waits for the server to report on stderr that its listener is bound.
+async fn await_listening(listening: tokio::sync::oneshot::Receiver<()>) -> io::Result<()> {
+    match tokio::time::timeout(LISTEN_TIMEOUT, listening).await {
+        Ok(Ok(())) => Ok(()),
+        Ok(Err(_)) => Err(io::Error::new(
+            io::ErrorKind::ConnectionRefused,
+            "Server exited before listening",
+        )),
+        Err(_) => Err(io::Error::new(
+            io::ErrorKind::TimedOut,
+            format!("Server did not listen within {:?}", LISTEN_TIMEOUT),
+        )),
+    }
 }
 
 impl LspClient {
@@ -183,6 +242,8 @@ impl LspClient {
         let runtime_handle = Handle::current();
         let (sender, rx) = channel::<String>();
         let (tx, receiver) = channel::<String>();
+        let (listening_tx, listening_rx) = tokio::sync::oneshot::channel::<()>();
+        let (server_exited_tx, server_exited) = channel::<()>();
 
         // Get the client's process ID
         let client_pid = std::process::id();
@@ -209,10 +270,11 @@ impl LspClient {
                         "--rnode-port", &rnode_port.to_string(),
                         "--no-rnode",  // Tests use parser-only validation (no RNode dependency)
                     ];
-                    let mut server = spawn_server_with_retry_stdio(&server_path, server_args).await?;
+                    let mut server = spawn_server_with_retry(&server_path, server_args, Stdio::piped).await?;
                     let output = Box::new(server.stdin.take().expect("Failed to open server stdin")) as Box<dyn LspStream>;
                     let input = Box::new(server.stdout.take().expect("Failed to open server stdout")) as Box<dyn LspStream>;
-                    let logger = Box::new(server.stderr.take().expect("Failed to open server stderr")) as Box<dyn LspStream>;
+                    let stderr = Box::new(server.stderr.take().expect("Failed to open server stderr")) as Box<dyn LspStream>;
+                    let logger = spawn_logger(stderr, None, server_exited_tx);
                     (output, input, logger, Some(server), None, None, None, None)
                 }
                 CommType::Tcp { port } => {
@@ -226,33 +288,12 @@ impl LspClient {
                         "--rnode-port", &rnode_port.to_string(),
                         "--no-rnode",  // Tests use parser-only validation (no RNode dependency)
                     ];
-                    let mut server = spawn_server_with_retry_null(&server_path, server_args).await?;
-                    let logger = Box::new(server.stderr.take().expect("Failed to open server stderr")) as Box<dyn LspStream>;
-
-                    // Retry connection with exponential backoff (necessary - waiting for server to bind port)
-                    let stream = {
-                        let mut wait_time = Duration::from_millis(10);
-                        let max_attempts = 10;
-                        let mut result = None;
-
-                        for attempt in 0..max_attempts {
-                            match TcpStream::connect(format!("127.0.0.1:{}", port)).await {
-                                Ok(s) => {
-                                    result = Some(Ok(s));
-                                    break;
-                                }
-                                Err(e) => {
-                                    result = Some(Err(e));
-                                    if attempt < max_attempts - 1 {
-                                        tokio::time::sleep(wait_time).await;
-                                        wait_time = (wait_time * 2).min(Duration::from_millis(100));
-                                    }
-                                }
-                            }
-                        }
+                    let mut server = spawn_server_with_retry(&server_path, server_args, Stdio::null).await?;
+                    let stderr = Box::new(server.stderr.take().expect("Failed to open server stderr")) as Box<dyn LspStream>;
+                    let logger = spawn_logger(stderr, Some(listening_tx), server_exited_tx);
 
-                        result.unwrap()?
-                    };
+                    await_listening(listening_rx).await?;
+                    let stream = TcpStream::connect(format!("127.0.0.1:{}", port)).await?;
                     stream.set_nodelay(true)?;
                     let (read_half, write_half) = split(stream);
                     let write_stream = Arc::new(Mutex::new(write_half));
@@ -296,60 +337,15 @@ impl LspClient {
                         "--rnode-port", &rnode_port.to_string(),
                         "--no-rnode",  // Tests use parser-only validation (no RNode dependency)
                     ];
-                    let mut server = spawn_server_with_retry_null(&server_path, server_args).await?;
-                    let logger = Box::new(server.stderr.take().expect("Failed to open server stderr")) as Box<dyn LspStream>;
+                    let mut server = spawn_server_with_retry(&server_path, server_args, Stdio::null).await?;
+                    let stderr = Box::new(server.stderr.take().expect("Failed to open server stderr")) as Box<dyn LspStream>;
+                    let logger = spawn_logger(stderr, Some(listening_tx), server_exited_tx);
 
-                    // Retry connection with exponential backoff (server needs time to create socket)
+                    await_listening(listening_rx).await?;
                     #[cfg(windows)]
-                    let (read_half, write_half) = {
-                        let mut wait_time = Duration::from_millis(10);
-                        let max_attempts = 10;
-                        let mut last_error = None;
-                        let mut client = None;
-
-                        for attempt in 0..max_attempts {
-                            match NamedPipeClient::connect(&path).await {
-                                Ok(c) => {
-                                    client = Some(c);
-                                    break;
-                                }
-                                Err(e) => {
-                                    last_error = Some(e);
-                                    if attempt < max_attempts - 1 {
-                                        tokio::time::sleep(wait_time).await;
-                                        wait_time = (wait_time * 2).min(Duration::from_millis(200));
-                                    }
-                                }
-                            }
-                        }
-
-                        split(client.ok_or_else(|| last_error.unwrap())?)
-                    };
+                    let (read_half, write_half) = split(NamedPipeClient::connect(&path).await?);
                     #[cfg(unix)]
-                    let (read_half, write_half) = {
-                        let mut wait_time = Duration::from_millis(10);
-                        let max_attempts = 10;
-                        let mut last_error = None;
-                        let mut stream = None;
-
-                        for attempt in 0..max_attempts {
-                            match UnixStream::connect(&path).await {
-                                Ok(s) => {
-                                    stream = Some(s);
-                                    break;
-                                }
-                                Err(e) => {
-                                    last_error = Some(e);
-                                    if attempt < max_attempts - 1 {
-                                        tokio::time::sleep(wait_time).await;
-                                        wait_time = (wait_time * 2).min(Duration::from_millis(200));
-                                    }
-                                }
-                            }
-                        }
-
-                        split(stream.ok_or_else(|| last_error.unwrap())?)
-                    };
+                    let (read_half, write_half) = split(UnixStream::connect(&path).await?);
                     let write_stream = Arc::new(Mutex::new(write_half));
                     let output = Box::new(AsyncLspWriteStream::new(
                         Arc::clone(&write_stream),
@@ -380,24 +376,23 @@ impl LspClient {
                         "--no-rnode",  // Tests use parser-only validation (no RNode dependency)
                     ];
                     debug!("Server command: {} {:?}", server_path, server_args);
-                    let mut server = spawn_server_with_retry_null(&server_path, server_args).await
+                    let mut server = spawn_server_with_retry(&server_path, server_args, Stdio::null).await
                         .map_err(|e| {
                             error!("Failed to spawn server: {}", e);
                             io::Error::new(io::ErrorKind::Other, format!("Failed to spawn server: {}", e))
                         })?;
-                    let logger = Box::new(server.stderr.take().expect("Failed to open server stderr")) as Box<dyn LspStream>;
-                    info!("Waiting 500ms for server to start");
-                    tokio::time::sleep(Duration::from_millis(500)).await;
+                    let stderr = Box::new(server.stderr.take().expect("Failed to open server stderr")) as Box<dyn LspStream>;
+                    let logger = spawn_logger(stderr, Some(listening_tx), server_exited_tx);
+
+                    await_listening(listening_rx).await?;
                     info!("Connecting to ws://127.0.0.1:{}", port);
-                    let ws_stream = connect_async(format!("ws://127.0.0.1:{}", port))
-                        .await
-                        .map_err(|e| {
-                            error!("Failed to connect to WebSocket server: {}", e);
-                            io::Error::new(
-                                io::ErrorKind::ConnectionRefused,
-                                format!("Failed to connect to WebSocket server: {}", e),
-                            )
-                        })?;
+                    let ws_stream = connect_async(format!("ws://127.0.0.1:{}", port)).await.map_err(|e| {
+                        error!("Failed to connect to WebSocket server: {}", e);
+                        io::Error::new(
+                            io::ErrorKind::ConnectionRefused,
+                            format!("Failed to connect to WebSocket server: {}", e),
+                        )
+                    })?;
                     info!("WebSocket connection established");
                     let (sink, stream) = ws_stream.0.split();
                     let ws_sink = Arc::new(Mutex::new(sink));
@@ -490,43 +485,6 @@ impl LspClient {
             }
         });
 
-        let logger_thread = Some(thread::spawn(move || {
-            let mut client_stdout = std::io::stdout();
-            let mut logger = logger;
-            let mut read_buffer = vec![0u8; 4096];
-            loop {
-                match logger.read(&mut read_buffer) {
-                    Ok(0) => {
-                        info!("Server logger closed.");
-                        if let Err(e) = client_stdout.flush() {
-                            error!("Error flushing client stdout: {}", e);
-                        }
-                        return;
-                    }
-                    Ok(n) => {
-                        if let Err(e) = client_stdout.write_all(&read_buffer[..n]) {
-                            error!("Error writing to client stdout: {}", e);
-                            return;
-                        }
-                        if let Err(e) = client_stdout.flush() {
-                            error!("Error flushing client stdout: {}", e);
-                        }
-                    }
-                    Err(e) => {
-                        if e.kind() == io::ErrorKind::BrokenPipe {
-                            info!("Server logger pipe broken, exiting.");
-                        } else {
-                            error!("Error reading from server logger: {}", e);
-                        }
-                        if let Err(e) = client_stdout.flush() {
-                            error!("Error flushing client stdout: {}", e);
-                        }
-                        return;
-                    }
-                }
-            }
-        }));
-
         let request_handlers = HashMap::new();
 
         let mut notification_handlers = HashMap::new();
@@ -585,6 +543,14 @@ impl LspClient {
             "textDocument/documentHighlight".to_string(),
             Self::receive_document_highlight as ResponseHandler,
         );
+        response_handlers.insert(
+            "rholang/contracts".to_string(),
+            Self::receive_contracts as ResponseHandler,
+        );
+        response_handlers.insert(
+            "workspace/executeCommand".to_string(),
+            Self::receive_execute_command as ResponseHandler,
+        );
         response_handlers.insert(
             "textDocument/hover".to_string(),
             Self::receive_hover as ResponseHandler,
@@ -597,6 +563,10 @@ impl LspClient {
             "textDocument/signatureHelp".to_string(),
             Self::receive_signature_help as ResponseHandler,
         );
+        response_handlers.insert(
+            "rholang/advanceClock".to_string(),
+            Self::receive_advance_clock as ResponseHandler,
+        );
 
         let client = LspClient {
             server: Mutex::new(server),
@@ -617,7 +587,8 @@ impl LspClient {
             documents_by_uri: RwLock::new(HashMap::new()),
             output_thread: Mutex::new(Some(output_thread)),
             input_thread: Mutex::new(Some(input_thread)),
-            logger_thread: Mutex::new(logger_thread),
+            logger_thread: Mutex::new(Some(logger)),
+            server_exited: Mutex::new(server_exited),
             event_sender,
             tcp_write_stream: Mutex::new(tcp_write_stream),
             #[cfg(windows)]
@@ -661,11 +632,35 @@ impl LspClient {
                     debug!("Failed to flush WebSocket stream: {}", e);
                 }
             }
+            // The server ends the connection once it has handled the close
+            if !self.await_output_closed(CLOSE_TIMEOUT) {
+                debug!("WebSocket connection still open {:?} after close", CLOSE_TIMEOUT);
+            }
         }
-        tokio::time::sleep(Duration::from_millis(100)).await;
         Ok(())
     }
 
+    /// Waits up to `timeout` for the server to close its output, discarding
+    /// anything it still sends. Returns whether the output closed.
+    fn await_output_closed(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let receiver = self.receiver.lock().expect("Failed to lock receiver");
+        loop {
+            match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok(message) => debug!("Discarding message received while closing: {}", message),
+                Err(RecvTimeoutError::Disconnected) => return true,
+                Err(RecvTimeoutError::Timeout) => return false,
+            }
+        }
+    }
+
+    /// Waits up to `timeout` for the server process to exit, which closes
+    /// its stderr. Returns whether it exited.
+    fn await_server_exit(&self, timeout: Duration) -> bool {
+        let server_exited = self.server_exited.lock().expect("Failed to lock server_exited");
+        matches!(server_exited.recv_timeout(timeout), Err(RecvTimeoutError::Disconnected))
+    }
+
     fn terminate_server(&self) -> io::Result<()> {
         let mut server = self.server.lock().expect("Failed to lock server");
         if let Some(ref mut server) = *server {
@@ -687,32 +682,13 @@ impl LspClient {
                     debug!("Terminated server process successfully");
                 }
             }
-            // Wait briefly to allow server to start terminating gracefully
-            thread::sleep(Duration::from_millis(200));
-        }
-
-        // Ensure server is terminated
-        if let Some(ref mut server) = *server {
-            if server.try_wait()?.is_none() {
+            // Give the server up to 200ms to terminate gracefully
+            if !self.await_server_exit(Duration::from_millis(200)) {
                 debug!("Server process still running, attempting to kill");
                 server.kill()?;
-                // Poll for server to exit with exponential backoff (necessary - no event-driven alternative for process termination)
-                let start = Instant::now();
-                let timeout = Duration::from_secs(2);
-                let mut wait_time = Duration::from_millis(10);
-                while start.elapsed() < timeout {
-                    if server.try_wait()?.is_some() {
-                        debug!("Server process terminated successfully");
-                        break;
-                    }
-                    thread::sleep(wait_time);
-                    // Exponential backoff: 10ms, 20ms, 40ms, 80ms, 100ms (capped)
-                    wait_time = (wait_time * 2).min(Duration::from_millis(100));
-                }
-                if server.try_wait()?.is_none() {
-                    error!("Server did not terminate after kill within 2 seconds");
-                }
             }
+            server.wait()?;
+            debug!("Server process terminated successfully");
         }
         Ok(())
     }
@@ -0,0 +1,328 @@
+//! Fluent assertions on LSP responses
+//!
+//! Integration tests read as a description of the feature they cover:
+//!
+//! ```ignore
+//! let doc = client.open_document("/tmp/lint.rho", code).unwrap();
+//! client.expect_diagnostics(&doc.uri())
+//!     .with_code("naming-convention")
+//!     .at(3, 5)
+//!     .with_message_containing("snake_case");
+//!
+//! client.expect_definition(&doc.uri(), Position::new(4, 2))
+//!     .resolves_to("/tmp/lint.rho", Range::new(Position::new(0, 9), Position::new(0, 23)));
+//! ```
+//!
+//! Every assertion panics with the full response when it fails, so a failing
+//! test shows what the server actually said. Positions are 0-based, as in LSP.
+//! Files are matched against the end of a location's URI, so either a path
+//! (`/tmp/lint.rho`), a file name (`lint.rho`) or a full URI works.
+
+use tower_lsp::lsp_types::{
+    Diagnostic, DiagnosticSeverity, Hover, HoverContents, Location, MarkedString, NumberOrString, Position, Range,
+};
+
+use crate::lsp::client::LspClient;
+
+impl LspClient {
+    /// Diagnostics of the latest version of the open document `uri`.
+    #[track_caller]
+    pub fn expect_diagnostics(&self, uri: &str) -> DiagnosticsExpectation {
+        let document = self
+            .documents_by_uri
+            .read()
+            .expect("Failed to acquire read lock on documents_by_uri")
+            .get(uri)
+            .cloned()
+            .unwrap_or_else(|| panic!("{} is not open", uri));
+        let params = self
+            .await_diagnostics(&document)
+            .unwrap_or_else(|e| panic!("No diagnostics for {}: {}", uri, e));
+        DiagnosticsExpectation { uri: uri.to_string(), diagnostics: params.diagnostics.clone(), filters: Vec::new() }
+    }
+
+    /// Locations `textDocument/definition` returns at `position` of `uri`.
+    #[track_caller]
+    pub fn expect_definition(&self, uri: &str, position: Position) -> LocationsExpectation {
+        let locations = self
+            .definition_all(uri, position)
+            .unwrap_or_else(|e| panic!("definition at {}:{:?} failed: {}", uri, position, e));
+        LocationsExpectation { request: format!("definition at {}:{}:{}", uri, position.line, position.character), locations }
+    }
+
+    /// Locations `textDocument/references` returns at `position` of `uri`,
+    /// declaration included.
+    #[track_caller]
+    pub fn expect_references(&self, uri: &str, position: Position) -> LocationsExpectation {
+        let locations = self
+            .references(uri, position, true)
+            .unwrap_or_else(|e| panic!("references at {}:{:?} failed: {}", uri, position, e));
+        LocationsExpectation { request: format!("references at {}:{}:{}", uri, position.line, position.character), locations }
+    }
+
+    /// The `textDocument/hover` response at `position` of `uri`.
+    #[track_caller]
+    pub fn expect_hover(&self, uri: &str, position: Position) -> HoverExpectation {
+        let hover = self
+            .hover(uri, position)
+            .unwrap_or_else(|e| panic!("hover at {}:{:?} failed: {}", uri, position, e));
+        HoverExpectation { request: format!("hover at {}:{}:{}", uri, position.line, position.character), hover }
+    }
+}
+
+/// Diagnostics of a document, narrowed by each `with_*` call
+#[derive(Debug, Clone)]
+pub struct DiagnosticsExpectation {
+    uri: String,
+    diagnostics: Vec<Diagnostic>,
+    /// Descriptions of the filters applied so far, for failure messages
+    filters: Vec<String>,
+}
+
+impl DiagnosticsExpectation {
+    /// The diagnostics still matching.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Keeps the diagnostics with `code`; fails if there are none.
+    #[track_caller]
+    pub fn with_code(self, code: &str) -> Self {
+        self.filter(format!("code {}", code), |d| match &d.code {
+            Some(NumberOrString::String(s)) => s == code,
+            Some(NumberOrString::Number(n)) => n.to_string() == code,
+            None => false,
+        })
+    }
+
+    /// Keeps the diagnostics from `source`; fails if there are none.
+    #[track_caller]
+    pub fn with_source(self, source: &str) -> Self {
+        self.filter(format!("source {}", source), |d| d.source.as_deref() == Some(source))
+    }
+
+    /// Keeps the diagnostics of `severity`; fails if there are none.
+    #[track_caller]
+    pub fn with_severity(self, severity: DiagnosticSeverity) -> Self {
+        self.filter(format!("severity {:?}", severity), |d| d.severity == Some(severity))
+    }
+
+    /// Keeps the diagnostics whose message contains `text`; fails if there are none.
+    #[track_caller]
+    pub fn with_message_containing(self, text: &str) -> Self {
+        self.filter(format!("message containing {:?}", text), |d| d.message.contains(text))
+    }
+
+    /// Keeps the diagnostics whose range contains `line:character`; fails if there are none.
+    #[track_caller]
+    pub fn at(self, line: u32, character: u32) -> Self {
+        let position = Position::new(line, character);
+        self.filter(format!("at {}:{}", line, character), |d| contains(&d.range, position))
+    }
+
+    /// Keeps the diagnostics with exactly `range`; fails if there are none.
+    #[track_caller]
+    pub fn with_range(self, range: Range) -> Self {
+        self.filter(format!("range {:?}", range), |d| d.range == range)
+    }
+
+    /// Fails unless exactly `count` diagnostics match.
+    #[track_caller]
+    pub fn count(self, count: usize) -> Self {
+        assert_eq!(
+            self.diagnostics.len(),
+            count,
+            "expected {} diagnostic(s) of {}{}, got {:#?}",
+            count,
+            self.uri,
+            self.described_filters(),
+            self.diagnostics
+        );
+        self
+    }
+
+    /// Fails if any diagnostic matches.
+    #[track_caller]
+    pub fn none(self) {
+        assert!(
+            self.diagnostics.is_empty(),
+            "expected no diagnostics of {}{}, got {:#?}",
+            self.uri,
+            self.described_filters(),
+            self.diagnostics
+        );
+    }
+
+    #[track_caller]
+    fn filter(mut self, description: String, keep: impl Fn(&Diagnostic) -> bool) -> Self {
+        let (kept, dropped): (Vec<_>, Vec<_>) = self.diagnostics.into_iter().partition(|d| keep(d));
+        self.filters.push(description);
+        assert!(
+            !kept.is_empty(),
+            "expected a diagnostic of {}{}, but none of these matched: {:#?}",
+            self.uri,
+            described(&self.filters),
+            dropped
+        );
+        self.diagnostics = kept;
+        self
+    }
+
+    fn described_filters(&self) -> String {
+        described(&self.filters)
+    }
+}
+
+/// Locations returned by a navigation request
+#[derive(Debug, Clone)]
+pub struct LocationsExpectation {
+    request: String,
+    locations: Vec<Location>,
+}
+
+impl LocationsExpectation {
+    pub fn locations(&self) -> &[Location] {
+        &self.locations
+    }
+
+    /// Fails unless `range` of `file` is among the locations.
+    #[track_caller]
+    pub fn resolves_to(self, file: &str, range: Range) -> Self {
+        assert!(
+            self.locations.iter().any(|l| l.uri.as_str().ends_with(file) && l.range == range),
+            "expected {} to include {} {:?}, got {:#?}",
+            self.request,
+            file,
+            range,
+            self.locations
+        );
+        self
+    }
+
+    /// Fails unless a location of `file` starts on `line`.
+    #[track_caller]
+    pub fn resolves_to_line(self, file: &str, line: u32) -> Self {
+        assert!(
+            self.locations.iter().any(|l| l.uri.as_str().ends_with(file) && l.range.start.line == line),
+            "expected {} to include {} line {}, got {:#?}",
+            self.request,
+            file,
+            line,
+            self.locations
+        );
+        self
+    }
+
+    /// Fails unless exactly `count` locations were returned.
+    #[track_caller]
+    pub fn count(self, count: usize) -> Self {
+        assert_eq!(self.locations.len(), count, "expected {} location(s) from {}, got {:#?}", count, self.request, self.locations);
+        self
+    }
+
+    /// Fails if any location was returned.
+    #[track_caller]
+    pub fn none(self) {
+        assert!(self.locations.is_empty(), "expected nothing from {}, got {:#?}", self.request, self.locations);
+    }
+}
+
+/// A hover response
+#[derive(Debug, Clone)]
+pub struct HoverExpectation {
+    request: String,
+    hover: Option<Hover>,
+}
+
+impl HoverExpectation {
+    /// The hover text, all parts joined by blank lines.
+    pub fn text(&self) -> Option<String> {
+        self.hover.as_ref().map(|hover| hover_text(&hover.contents))
+    }
+
+    /// Fails unless the hover text contains `text`.
+    #[track_caller]
+    pub fn contains(self, text: &str) -> Self {
+        let hover_text = self.text().unwrap_or_else(|| panic!("expected a hover from {}, got none", self.request));
+        assert!(hover_text.contains(text), "expected {} to contain {:?}, got:\n{}", self.request, text, hover_text);
+        self
+    }
+
+    /// Fails unless the hover covers `range`.
+    #[track_caller]
+    pub fn with_range(self, range: Range) -> Self {
+        let actual = self.hover.as_ref().and_then(|hover| hover.range);
+        assert_eq!(actual, Some(range), "unexpected range of {}", self.request);
+        self
+    }
+
+    /// Fails if there is a hover.
+    #[track_caller]
+    pub fn none(self) {
+        assert!(self.hover.is_none(), "expected no hover from {}, got {:#?}", self.request, self.hover);
+    }
+}
+
+fn hover_text(contents: &HoverContents) -> String {
+    let marked = |m: &MarkedString| match m {
+        MarkedString::String(s) => s.clone(),
+        MarkedString::LanguageString(ls) => ls.value.clone(),
+    };
+    match contents {
+        HoverContents::Scalar(m) => marked(m),
+        HoverContents::Array(parts) => parts.iter().map(marked).collect::<Vec<_>>().join("\n\n"),
+        HoverContents::Markup(markup) => markup.value.clone(),
+    }
+}
+
+/// Whether `position` is inside `range`, end included so that empty ranges match.
+fn contains(range: &Range, position: Position) -> bool {
+    range.start <= position && position <= range.end
+}
+
+fn described(filters: &[String]) -> String {
+    if filters.is_empty() {
+        String::new()
+    } else {
+        format!(" with {}", filters.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnostic(range: Range, code: &str, message: &str) -> Diagnostic {
+        Diagnostic {
+            range,
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: Some(NumberOrString::String(code.to_string())),
+            message: message.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn expectation() -> DiagnosticsExpectation {
+        DiagnosticsExpectation {
+            uri: "file:///tmp/a.rho".to_string(),
+            diagnostics: vec![
+                diagnostic(Range::new(Position::new(3, 2), Position::new(3, 8)), "naming-convention", "use snake_case"),
+                diagnostic(Range::new(Position::new(5, 0), Position::new(5, 4)), "duplicate-send", "sent twice"),
+            ],
+            filters: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_diagnostic_filters_narrow() {
+        let matched = expectation().with_code("naming-convention").at(3, 5).with_message_containing("snake");
+        assert_eq!(matched.diagnostics().len(), 1);
+        expectation().with_severity(DiagnosticSeverity::WARNING).count(2);
+    }
+
+    #[test]
+    #[should_panic(expected = "with code naming-convention, at 5:1")]
+    fn test_failed_filter_describes_the_expectation() {
+        expectation().with_code("naming-convention").at(5, 1);
+    }
+}
@@ -1,5 +1,7 @@
 pub mod client;
 pub mod document;
 pub mod events;
+pub mod expect;
 pub mod message_stream;
+pub mod scenario;
 pub mod streams;
@@ -0,0 +1,247 @@
+//! Scripted editor sessions
+//!
+//! A scenario is a JSON file listing what a client does during an editing
+//! session: open documents, type, ask for completions and renames, close.
+//! The server integration tests replay scenarios against an in-process
+//! server and check that it keeps up (see `tests/session_scenarios.rs`).
+//!
+//! ```json
+//! {
+//!   "name": "typing with completion",
+//!   "maxLatencyMs": 2000,
+//!   "steps": [
+//!     { "open": { "uri": "file:///scenario/main.rho", "text": "new x in { Nil }" } },
+//!     { "type": { "uri": "file:///scenario/main.rho", "at": [0, 11], "text": "x!(1) | ", "delayMs": 10 } },
+//!     { "request": { "method": "textDocument/completion", "wait": false,
+//!                    "params": { "textDocument": { "uri": "file:///scenario/main.rho" },
+//!                                "position": { "line": 0, "character": 12 } } } },
+//!     { "awaitDiagnostics": { "uri": "file:///scenario/main.rho" } },
+//!     { "close": { "uri": "file:///scenario/main.rho" } }
+//!   ]
+//! }
+//! ```
+//!
+//! Scenarios can also be recorded from the wire log of a real session
+//! (`--wire-log`) with [`Scenario::from_wire_log`], or with the
+//! `record_scenario` binary.
+
+use std::path::Path;
+
+use anyhow::{anyhow, Context};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Pauses shorter than this are dropped when recording
+pub const MIN_RECORDED_WAIT_MS: u64 = 20;
+
+/// Pauses longer than this are shortened when recording
+pub const MAX_RECORDED_WAIT_MS: u64 = 1000;
+
+/// A scripted editor session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Scenario {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Sent as `initializationOptions` in `initialize`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub initialization_options: Option<Value>,
+    /// Longest acceptable time for any request to be answered
+    #[serde(default = "default_max_latency_ms")]
+    pub max_latency_ms: u64,
+    pub steps: Vec<Step>,
+}
+
+fn default_max_latency_ms() -> u64 {
+    5000
+}
+
+/// One thing the client does
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Step {
+    /// `textDocument/didOpen` at version 1
+    Open { uri: String, text: String },
+    /// Types `text` at `at` (line, UTF-16 character), one `didChange` per character
+    #[serde(rename_all = "camelCase")]
+    Type {
+        uri: String,
+        at: (u32, u32),
+        text: String,
+        #[serde(default)]
+        delay_ms: u64,
+    },
+    /// One `didChange` with the given content changes
+    Change { uri: String, changes: Vec<Value> },
+    /// `textDocument/didClose`
+    Close { uri: String },
+    /// A request; with `wait` false the next steps run before the response arrives
+    #[serde(rename_all = "camelCase")]
+    Request {
+        method: String,
+        #[serde(default)]
+        params: Value,
+        #[serde(default = "default_true")]
+        wait: bool,
+        /// Fail unless the result is non-null
+        #[serde(default)]
+        expect_result: bool,
+    },
+    /// Any other notification
+    Notify {
+        method: String,
+        #[serde(default)]
+        params: Value,
+    },
+    /// Pause
+    Wait { ms: u64 },
+    /// Waits for diagnostics of the latest version of `uri`
+    AwaitDiagnostics { uri: String },
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Scenario {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let source = std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        serde_json::from_str(&source).with_context(|| format!("parsing {}", path.display()))
+    }
+
+    /// The session a client had with the server, from a wire log.
+    ///
+    /// Messages from the client become steps; the lifecycle messages
+    /// (`initialize`, `initialized`, `shutdown`, `exit`), responses to server
+    /// requests and cancellations are left to the replaying harness. Pauses
+    /// between messages become `wait` steps.
+    pub fn from_wire_log(name: &str, wire_log: &str) -> anyhow::Result<Self> {
+        let mut steps = Vec::new();
+        let mut initialization_options = None;
+        let mut last_time: Option<u64> = None;
+        let mut incoming: Option<Option<u64>> = None;
+
+        for line in wire_log.lines() {
+            if let Some(header) = line.strip_prefix('[') {
+                // `[2025-10-29T15:19:49.123Z] <<< REQUEST`
+                incoming = header.contains("] <<< ").then(|| header.split(']').next().and_then(millis_of_day));
+                continue;
+            }
+            let Some(time) = incoming.take() else { continue };
+            if !line.starts_with('{') {
+                incoming = Some(time);
+                continue;
+            }
+            let message: Value = serde_json::from_str(line).map_err(|e| anyhow!("invalid message {}: {}", line, e))?;
+            let Some(method) = message.get("method").and_then(Value::as_str) else { continue };
+            let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+            let uri = || params["textDocument"]["uri"].as_str().unwrap_or_default().to_string();
+            let step = match method {
+                "initialize" => {
+                    initialization_options = params.get("initializationOptions").cloned();
+                    None
+                }
+                "initialized" | "shutdown" | "exit" | "$/cancelRequest" => None,
+                "textDocument/didOpen" => Some(Step::Open {
+                    uri: uri(),
+                    text: params["textDocument"]["text"].as_str().unwrap_or_default().to_string(),
+                }),
+                "textDocument/didChange" => Some(Step::Change {
+                    uri: uri(),
+                    changes: params["contentChanges"].as_array().cloned().unwrap_or_default(),
+                }),
+                "textDocument/didClose" => Some(Step::Close { uri: uri() }),
+                _ if message.get("id").is_some() => Some(Step::Request {
+                    method: method.to_string(),
+                    params: params.clone(),
+                    wait: false,
+                    expect_result: false,
+                }),
+                _ => Some(Step::Notify { method: method.to_string(), params: params.clone() }),
+            };
+
+            if let Some(step) = step {
+                if let (Some(time), Some(last)) = (time, last_time) {
+                    let pause = time.saturating_sub(last);
+                    if pause >= MIN_RECORDED_WAIT_MS {
+                        steps.push(Step::Wait { ms: pause.min(MAX_RECORDED_WAIT_MS) });
+                    }
+                }
+                steps.push(step);
+            }
+            last_time = time.or(last_time);
+        }
+
+        if steps.is_empty() {
+            return Err(anyhow!("no client messages in the wire log"));
+        }
+        Ok(Scenario {
+            name: name.to_string(),
+            description: Some("Recorded from a wire log".to_string()),
+            initialization_options,
+            max_latency_ms: default_max_latency_ms(),
+            steps,
+        })
+    }
+}
+
+/// `2025-10-29T15:19:49.123Z` → milliseconds since midnight.
+fn millis_of_day(timestamp: &str) -> Option<u64> {
+    let time = timestamp.split_once('T')?.1.trim_end_matches('Z');
+    let (hms, millis) = time.split_once('.').unwrap_or((time, "0"));
+    let mut parts = hms.split(':').map(|part| part.parse::<u64>().ok());
+    let (hours, minutes, seconds) = (parts.next()??, parts.next()??, parts.next()??);
+    Some(((hours * 60 + minutes) * 60 + seconds) * 1000 + millis.parse::<u64>().ok()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_scenario() {
+        let scenario: Scenario = serde_json::from_value(json!({
+            "name": "typing",
+            "steps": [
+                { "open": { "uri": "file:///a.rho", "text": "Nil" } },
+                { "type": { "uri": "file:///a.rho", "at": [0, 3], "text": " | Nil" } },
+                { "request": { "method": "textDocument/hover", "params": {}, "wait": false } },
+                { "awaitDiagnostics": { "uri": "file:///a.rho" } }
+            ]
+        }))
+        .unwrap();
+        assert_eq!(scenario.max_latency_ms, 5000);
+        assert_eq!(
+            scenario.steps[1],
+            Step::Type { uri: "file:///a.rho".to_string(), at: (0, 3), text: " | Nil".to_string(), delay_ms: 0 }
+        );
+        assert!(matches!(scenario.steps[2], Step::Request { wait: false, expect_result: false, .. }));
+    }
+
+    #[test]
+    fn test_record_from_wire_log() {
+        let wire_log = concat!(
+            "[2025-10-29T15:19:49.100Z] <<< REQUEST \n",
+            "Content-Length: 80\r\n\r\n",
+            "{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"initialize\",\"params\":{\"initializationOptions\":{\"a\":1}}}\n\n",
+            "[2025-10-29T15:19:49.105Z] >>> RESPONSE \n",
+            "Content-Length: 40\r\n\r\n",
+            "{\"jsonrpc\":\"2.0\",\"id\":1,\"result\":{}}\n\n",
+            "[2025-10-29T15:19:49.110Z] <<< NOTIFICATION \n",
+            "Content-Length: 90\r\n\r\n",
+            "{\"jsonrpc\":\"2.0\",\"method\":\"textDocument/didOpen\",\"params\":{\"textDocument\":{\"uri\":\"file:///a.rho\",\"text\":\"Nil\",\"version\":1}}}\n\n",
+            "[2025-10-29T15:19:49.410Z] <<< REQUEST \n",
+            "Content-Length: 90\r\n\r\n",
+            "{\"jsonrpc\":\"2.0\",\"id\":2,\"method\":\"textDocument/hover\",\"params\":{\"textDocument\":{\"uri\":\"file:///a.rho\"}}}\n\n",
+        );
+        let scenario = Scenario::from_wire_log("recorded", wire_log).unwrap();
+        assert_eq!(scenario.initialization_options, Some(json!({ "a": 1 })));
+        assert_eq!(scenario.steps.len(), 3);
+        assert_eq!(scenario.steps[0], Step::Open { uri: "file:///a.rho".to_string(), text: "Nil".to_string() });
+        assert_eq!(scenario.steps[1], Step::Wait { ms: 300 });
+        assert!(matches!(&scenario.steps[2], Step::Request { method, .. } if method == "textDocument/hover"));
+    }
+}
@@ -1,4 +1,7 @@
 //! Debug test to see what Span values MeTTaTron provides
+
+#![cfg(feature = "metta")]
+
 use tower_lsp::lsp_types::Url;
 
 use rholang_language_server::parsers::MettaParser;
@@ -3,6 +3,8 @@
 //! These tests verify that hover information is correctly provided for
 //! MeTTa code embedded within Rholang strings.
 
+#![cfg(feature = "metta")]
+
 use rholang_language_server::language_regions::{DirectiveParser, VirtualDocumentRegistry, LanguageRegion, RegionSource};
 use rholang_language_server::tree_sitter::parse_code;
 use ropey::Rope;
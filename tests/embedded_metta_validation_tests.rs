@@ -3,6 +3,8 @@
 //! Tests that embedded MeTTa code in Rholang files is properly detected,
 //! validated, and diagnostics are mapped back to parent document positions.
 
+#![cfg(feature = "metta")]
+
 use rholang_language_server::language_regions::{DirectiveParser, VirtualDocumentRegistry};
 use rholang_language_server::parsers::rholang::parse_to_document_ir;
 use rholang_language_server::tree_sitter::parse_code;
@@ -956,3 +956,77 @@ with_lsp_client!(test_signature_help_with_documentation, CommType::Stdio, |clien
     // Clean up
     client.close_document(&doc).expect("Failed to close document");
 });
+
+with_lsp_client!(test_contracts_outline_request, CommType::Stdio, |client: &LspClient| {
+    let vault_path = "/path/to/vault.rho";
+    let vault_text = indoc! {r#"
+        new transfer, stdout(`rho:io:stdout`) in {
+          /// Moves funds between two vaults
+          contract transfer(@from, @to, @amount, ret) = { ret!(true) } |
+          transfer!("a", "b", 10, *stdout)
+        }"#};
+    let caller_path = "/path/to/caller.rho";
+    let caller_text = indoc! {r#"
+        new ret in { transfer!("b", "a", 5, *ret) }"#};
+
+    let vault = client.open_document(vault_path, vault_text).expect("Failed to open vault.rho");
+    client.await_diagnostics(&vault).unwrap();
+    let caller = client.open_document(caller_path, caller_text).expect("Failed to open caller.rho");
+    client.await_diagnostics(&caller).unwrap();
+
+    let result = client.contracts(Some(&vault.uri())).expect("rholang/contracts failed");
+    let contracts = result["contracts"].as_array().expect("contracts should be an array");
+    assert_eq!(contracts.len(), 1, "Expected one contract, got: {}", result);
+
+    let transfer = &contracts[0];
+    assert_eq!(transfer["name"], "transfer");
+    assert_eq!(transfer["signature"], "contract transfer(@from, @to, @amount, ret)");
+    assert_eq!(transfer["doc"], "Moves funds between two vaults");
+    assert_eq!(transfer["range"][0], 2);
+    assert_eq!(transfer["calls"], 2, "Calls should be counted across the workspace");
+
+    let workspace = client.contracts(None).expect("rholang/contracts failed");
+    assert!(workspace["contracts"].as_array().is_some_and(|c| !c.is_empty()));
+
+    client.close_document(&vault).expect("Failed to close vault.rho");
+    client.close_document(&caller).expect("Failed to close caller.rho");
+});
+
+with_lsp_client!(test_expand_preview_command, CommType::Stdio, |client: &LspClient| {
+    let templates_path = "/path/to/templates.rho";
+    let templates_text = indoc! {r#"
+        // @template logger(name, out)
+        // contract ${name}(@message) = { ${out}!(message) }
+        // @end
+        Nil"#};
+    let main_path = "/path/to/main.rho";
+    let main_text = indoc! {r#"
+        new info, stdout(`rho:io:stdout`) in {
+          // @expand logger(info, stdout)
+          contract info(@message) = { stdout!(message) }
+          // @end
+        }"#};
+
+    let templates = client.open_document(templates_path, templates_text).expect("Failed to open templates.rho");
+    client.await_diagnostics(&templates).unwrap();
+    let main = client.open_document(main_path, main_text).expect("Failed to open main.rho");
+    client.await_diagnostics(&main).unwrap();
+
+    let preview = client
+        .execute_command("rholang.expandPreview", vec![serde_json::json!({ "uri": main.uri() })])
+        .expect("rholang.expandPreview failed");
+    assert_eq!(preview["uri"], "rholang-expansion:/path/to/main.rho");
+    assert_eq!(
+        preview["content"],
+        "// @expand logger(info, stdout) at line 2\n  contract info(@message) = { stdout!(message) }\n"
+    );
+
+    let mapping = &preview["mappings"][0];
+    assert_eq!(mapping["directiveRange"]["start"]["line"], 1);
+    assert_eq!(mapping["templateUri"], templates.uri());
+    assert_eq!(mapping["templateRange"]["start"]["line"], 0);
+    assert_eq!(mapping["upToDate"], true);
+
+    client.close_document(&templates).expect("Failed to close templates.rho");
+    client.close_document(&main).expect("Failed to close main.rho");
+});
@@ -1,4 +1,7 @@
 //! Test go-to-definition for MeTTa function calls
+
+#![cfg(feature = "metta")]
+
 use tower_lsp::lsp_types::Url;
 use rholang_language_server::parsers::MettaParser;
 use rholang_language_server::ir::transforms::metta_symbol_table_builder::MettaSymbolTableBuilder;
@@ -1,5 +1,7 @@
 //! Integration test for MeTTa pattern matching and go-to-definition
 
+#![cfg(feature = "metta")]
+
 use rholang_language_server::parsers::MettaParser;
 use rholang_language_server::ir::transforms::metta_symbol_table_builder::MettaSymbolTableBuilder;
 use rholang_language_server::ir::metta_node::MettaNode;
@@ -2,6 +2,8 @@
 //!
 //! Verify that symbols are indexed at the correct positions
 
+#![cfg(feature = "metta")]
+
 use tower_lsp::lsp_types::Url;
 use rholang_language_server::parsers::MettaParser;
 use rholang_language_server::ir::transforms::metta_symbol_table_builder::MettaSymbolTableBuilder;
@@ -1,4 +1,7 @@
 //! Compare scoping behavior between let expressions and rule definitions
+
+#![cfg(feature = "metta")]
+
 use tower_lsp::lsp_types::Url;
 
 use rholang_language_server::parsers::MettaParser;
@@ -1,4 +1,7 @@
 //! Debug test to trace exactly what find_symbol_references returns
+
+#![cfg(feature = "metta")]
+
 use tower_lsp::lsp_types::Url;
 
 use rholang_language_server::parsers::MettaParser;
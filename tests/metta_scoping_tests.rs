@@ -3,6 +3,8 @@
 //! These tests verify that variable references are correctly scoped
 //! to their definition context.
 
+#![cfg(feature = "metta")]
+
 use tower_lsp::lsp_types::Url;
 use rholang_language_server::parsers::MettaParser;
 use rholang_language_server::ir::transforms::metta_symbol_table_builder::MettaSymbolTableBuilder;
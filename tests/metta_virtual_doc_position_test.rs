@@ -1,4 +1,7 @@
 //! Test that symbol positions in virtual documents match lookup coordinates
+
+#![cfg(feature = "metta")]
+
 use tower_lsp::lsp_types::Url;
 
 use rholang_language_server::parsers::MettaParser;
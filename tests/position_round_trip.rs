@@ -0,0 +1,169 @@
+//! End-to-end position round-trip properties over generated programs.
+//!
+//! Every position-based feature (hover, goto-definition, rename, semantic
+//! tokens) assumes that the absolute ranges computed for IR nodes point at
+//! the text the node was parsed from. These properties check that for
+//! arbitrary programs from the grammar generator:
+//!
+//! 1. reconstructing the source from the IR in fidelity mode reproduces the
+//!    input, so ranges nest inside their parents and siblings never overlap;
+//! 2. every node's range slices the rope to its syntactic text, and its
+//!    line and column agree with its byte offset;
+//! 3. looking up any byte of an identifier finds that identifier.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use quickcheck::{QuickCheck, TestResult};
+use ropey::Rope;
+use rholang_language_server::ir::formatter::format_node_fidelity;
+use rholang_language_server::ir::rholang_node::{
+    compute_absolute_positions, find_node_at_position, position_children, Position, RholangNode,
+};
+use rholang_language_server::ir::semantic_node::SemanticNode;
+use rholang_language_server::tree_sitter::{parse_code, parse_to_ir};
+use test_utils::ir::generator::RholangProc;
+
+type Positions = HashMap<usize, (Position, Position)>;
+
+fn key(node: &RholangNode) -> usize {
+    node as *const RholangNode as usize
+}
+
+/// Parses `code`, or `None` if the generator produced something the grammar rejects.
+fn parse(code: &str) -> Option<(Arc<RholangNode>, Positions, Rope)> {
+    let tree = parse_code(code);
+    if tree.root_node().has_error() {
+        return None;
+    }
+    let rope = Rope::from_str(code);
+    let ir = parse_to_ir(&tree, &rope);
+    let positions = compute_absolute_positions(&ir);
+    Some((ir, positions, rope))
+}
+
+fn all_nodes<'a>(node: &'a Arc<RholangNode>, nodes: &mut Vec<&'a Arc<RholangNode>>) {
+    nodes.push(node);
+    for child in position_children(node) {
+        all_nodes(child, nodes);
+    }
+}
+
+fn source_of(rope: &Rope, start: Position, end: Position) -> String {
+    rope.byte_slice(start.byte..end.byte).to_string()
+}
+
+/// Checks property 1, returning a description of the first violation.
+fn check_fidelity(code: &str, ir: &Arc<RholangNode>, positions: &Positions, rope: &Rope) -> Result<(), String> {
+    let (start, end) = positions[&key(ir)];
+    let reconstructed = format_node_fidelity(ir, positions, rope)?;
+    if reconstructed != source_of(rope, start, end) {
+        return Err(format!("fidelity output {:?} differs from the source", reconstructed));
+    }
+    if reconstructed.trim() != code.trim() {
+        return Err(format!("root range {}..{} does not cover {:?}", start.byte, end.byte, code));
+    }
+    Ok(())
+}
+
+/// Checks property 2 for one node.
+fn check_range(node: &RholangNode, positions: &Positions, rope: &Rope) -> Result<(), String> {
+    let Some(&(start, end)) = positions.get(&key(node)) else {
+        return Err(format!("{} has no computed range", node.type_name()));
+    };
+    for position in [start, end] {
+        if position.byte > rope.len_bytes() {
+            return Err(format!("{} ends past the document", node.type_name()));
+        }
+        let line = rope.byte_to_line(position.byte);
+        let column = position.byte - rope.line_to_byte(line);
+        if (line, column) != (position.row, position.column) {
+            return Err(format!(
+                "{} at byte {} is {}:{}, computed as {}:{}",
+                node.type_name(),
+                position.byte,
+                line,
+                column,
+                position.row,
+                position.column
+            ));
+        }
+    }
+
+    let text = source_of(rope, start, end);
+    let matches = match node {
+        RholangNode::Var { name, .. } => text == *name,
+        RholangNode::BoolLiteral { value, .. } => text == value.to_string(),
+        RholangNode::LongLiteral { value, .. } => text.replace(char::is_whitespace, "").parse::<i64>() == Ok(*value),
+        RholangNode::StringLiteral { .. } => text.starts_with('"') && text.ends_with('"') && text.len() >= 2,
+        RholangNode::UriLiteral { .. } => text.starts_with('`') && text.ends_with('`') && text.len() >= 2,
+        RholangNode::Nil { .. } => text == "Nil",
+        RholangNode::Wildcard { .. } => text == "_",
+        RholangNode::New { .. } => text.starts_with("new"),
+        RholangNode::Contract { .. } => text.starts_with("contract"),
+        RholangNode::Match { .. } => text.starts_with("match"),
+        _ => true,
+    };
+    if matches {
+        Ok(())
+    } else {
+        Err(format!("{} slices the source to {:?}", node.type_name(), text))
+    }
+}
+
+/// Checks property 3 for one identifier.
+fn check_lookup(var: &Arc<RholangNode>, ir: &Arc<RholangNode>, positions: &Positions) -> Result<(), String> {
+    let RholangNode::Var { name, .. } = &**var else { return Ok(()) };
+    let (start, end) = positions[&key(var)];
+    for offset in 0..end.byte - start.byte {
+        let position = Position { row: start.row, column: start.column + offset, byte: start.byte + offset };
+        match find_node_at_position(ir, positions, position) {
+            Some(found) if Arc::ptr_eq(&found, var) => {}
+            Some(found) => {
+                return Err(format!("lookup of '{}' at byte {} found a {}", name, position.byte, found.type_name()));
+            }
+            None => return Err(format!("lookup of '{}' at byte {} found nothing", name, position.byte)),
+        }
+    }
+    Ok(())
+}
+
+/// Checks all three properties; `None` if `code` does not parse.
+fn check_program(code: &str) -> Option<Result<(), String>> {
+    let (ir, positions, rope) = parse(code)?;
+    let mut nodes = Vec::new();
+    all_nodes(&ir, &mut nodes);
+
+    let result = check_fidelity(code, &ir, &positions, &rope)
+        .and_then(|()| nodes.iter().try_for_each(|node| check_range(node, &positions, &rope)))
+        .and_then(|()| nodes.iter().try_for_each(|node| check_lookup(node, &ir, &positions)));
+    Some(result.map_err(|message| format!("{}\nin program:\n{}", message, code)))
+}
+
+#[test]
+fn test_prop_position_round_trip() {
+    fn prop(proc: RholangProc) -> TestResult {
+        match check_program(&proc.to_code()) {
+            None => TestResult::discard(),
+            Some(Ok(())) => TestResult::passed(),
+            Some(Err(message)) => TestResult::error(message),
+        }
+    }
+    QuickCheck::new().tests(200).max_tests(2000).quickcheck(prop as fn(RholangProc) -> TestResult);
+}
+
+#[test]
+fn test_round_trip_fixed_programs() {
+    let programs = [
+        "new x, y in {\n  x!(\"héllo → wörld\") |\n  for (@v <- x) { y!(v) }\n}",
+        "contract transfer(@from, @to, ret) = {\n  match from {\n    \"a\" => ret!(to)\n    _ => Nil\n  }\n}",
+        "new ret in { ret!({ \"k\": [1, 2, 3], \"ü\": Set(4) }) | for (@{\"k\": list, ...rest} <- ret) { Nil } }",
+        "new stdout, x in { stdout!({| @\"foo\"!(x), @\"bar\"!(*x) |}) }",
+    ];
+    for code in programs {
+        match check_program(code) {
+            Some(result) => result.unwrap(),
+            None => panic!("failed to parse:\n{}", code),
+        }
+    }
+}
@@ -1,4 +1,7 @@
 //! Integration test for pattern matching with the real robot_planning.rho file
+
+#![cfg(feature = "metta")]
+
 use tower_lsp::lsp_types::Url;
 use std::fs;
 use std::sync::Arc;
@@ -1,5 +1,7 @@
 //! Test MeTTa symbol positions using actual robot_planning.rho content
 
+#![cfg(feature = "metta")]
+
 use rholang_language_server::parsers::MettaParser;
 use rholang_language_server::ir::transforms::metta_symbol_table_builder::MettaSymbolTableBuilder;
 use std::fs;
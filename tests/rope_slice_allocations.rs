@@ -0,0 +1,106 @@
+/// Allocation-count tests for rope slicing in the IR conversion hot path
+///
+/// A counting global allocator records the number of allocations and the
+/// largest single allocation made by the current thread, so these tests can
+/// assert that slicing borrows from the rope and that conversion never
+/// materializes the whole rope as a `String`.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use ropey::Rope;
+use rholang_language_server::parsers::rholang::helpers::CowRopeSlice;
+use rholang_language_server::tree_sitter::{parse_code, parse_to_document_ir};
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    static LARGEST_ALLOCATION: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        let _ = LARGEST_ALLOCATION.try_with(|largest| largest.set(largest.get().max(layout.size())));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Runs `f` and returns its result with (allocation count, largest allocation size).
+fn measure<T>(f: impl FnOnce() -> T) -> (T, usize, usize) {
+    ALLOCATIONS.with(|count| count.set(0));
+    LARGEST_ALLOCATION.with(|largest| largest.set(0));
+    let result = f();
+    (
+        result,
+        ALLOCATIONS.with(Cell::get),
+        LARGEST_ALLOCATION.with(Cell::get),
+    )
+}
+
+#[test]
+fn test_contiguous_slice_borrows_without_allocating() {
+    let rope = Rope::from_str("new stdout(`rho:io:stdout`) in { stdout!(\"hello\") }");
+
+    let (is_match, allocations, _) = measure(|| {
+        let slice = CowRopeSlice::new(&rope, 4, 10).expect("valid range");
+        assert!(slice.is_contiguous());
+        let borrowed = matches!(slice.as_cow(), std::borrow::Cow::Borrowed("stdout"));
+        borrowed && slice == "stdout" && slice.chars().count() == 6
+    });
+
+    assert!(is_match);
+    assert_eq!(allocations, 0, "slicing a single chunk must not allocate");
+}
+
+#[test]
+fn test_invalid_range_is_rejected() {
+    let rope = Rope::from_str("Nil");
+    assert!(CowRopeSlice::new(&rope, 2, 1).is_none());
+    assert!(CowRopeSlice::new(&rope, 0, 10).is_none());
+}
+
+#[test]
+fn test_slice_across_chunks_matches_source() {
+    // Large enough to be split across several rope chunks
+    let source = "abcdefghij".repeat(2_000);
+    let rope = Rope::from_str(&source);
+
+    let slice = CowRopeSlice::new(&rope, 0, source.len()).expect("valid range");
+    assert!(!slice.is_contiguous());
+    assert_eq!(slice.len_bytes(), source.len());
+    assert_eq!(slice.into_owned(), source);
+}
+
+#[test]
+fn test_conversion_never_materializes_whole_rope() {
+    // Pad with whitespace so a block lands in the byte range covered by the
+    // block debug path, which previously copied the entire rope per block
+    let source = format!("{}new x in {{ x!(1) | x!(\"two\") | x!(true) }}\n", "\n".repeat(14_850));
+    let rope = Rope::from_str(&source);
+    let tree = parse_code(&source);
+
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .with_writer(std::io::sink)
+        .finish();
+
+    let (_ir, _, largest) = tracing::subscriber::with_default(subscriber, || {
+        measure(|| parse_to_document_ir(&tree, &rope))
+    });
+
+    assert!(
+        largest < rope.len_bytes(),
+        "IR conversion allocated {} bytes at once for a {}-byte document",
+        largest,
+        rope.len_bytes()
+    );
+}
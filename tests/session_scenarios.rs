@@ -0,0 +1,421 @@
+//! Replays scripted editor sessions against an in-process server.
+//!
+//! Each `tests/scenarios/*.json` file (see `test_utils::lsp::scenario`) is
+//! played through the same transport machinery as a real connection, over an
+//! in-memory pipe instead of stdio. For every scenario the server must:
+//!
+//! 1. answer every request, and shut down cleanly afterwards (no deadlocks);
+//! 2. publish diagnostics for each document in version order, ending with
+//!    the latest version whenever the scenario waits for them;
+//! 3. answer every request within the scenario's `maxLatencyMs`.
+//!
+//! The server runs in deterministic mode: the scenario's delays and waits,
+//! and the polls while awaiting diagnostics, advance its virtual clock
+//! instead of sleeping, so debounced work runs exactly when they let it.
+//!
+//! To turn a real session into a scenario, record it with `--wire-log` and
+//! convert the log with the `record_scenario` binary of `test_utils`.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader, DuplexStream, ReadHalf, WriteHalf};
+use tokio::sync::oneshot;
+
+use rholang_language_server::deterministic;
+use rholang_language_server::edit_journal::EditJournal;
+use rholang_language_server::lsp::shared_documents::SharedDocuments;
+use rholang_language_server::transport::{self, Connection, ConnectionManager, ServeOptions, Transport};
+use rholang_language_server::wire_logger::WireLogger;
+use test_utils::lsp::scenario::{Scenario, Step};
+
+/// A request unanswered for this long means the server is stuck
+const DEADLOCK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// `RequestCancelled` and `ContentModified` are fine answers while typing
+const ACCEPTED_ERROR_CODES: [i64; 2] = [-32800, -32801];
+
+/// Virtual time between two checks for diagnostics
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Lets `window` pass on the server's virtual clock, ending the debounce
+/// windows due on the way, and gives the server's tasks a turn.
+async fn pass(window: Duration) {
+    let clock = deterministic::clock().expect("scenarios run in deterministic mode");
+    let elapsed = deterministic::sleep(window);
+    clock.advance_to(clock.elapsed() + window);
+    elapsed.await;
+    tokio::task::yield_now().await;
+}
+
+/// Transport producing a single in-memory connection
+struct SessionTransport(Option<Connection>);
+
+#[async_trait]
+impl Transport for SessionTransport {
+    fn name(&self) -> &'static str {
+        "session"
+    }
+
+    async fn accept(&mut self) -> io::Result<Option<Connection>> {
+        Ok(self.0.take())
+    }
+}
+
+/// A diagnostics notification as received
+#[derive(Debug, Clone)]
+struct Published {
+    uri: String,
+    version: Option<i64>,
+}
+
+/// Client end of the connection
+struct Session {
+    writer: Arc<tokio::sync::Mutex<WriteHalf<DuplexStream>>>,
+    pending: Arc<Mutex<HashMap<i64, oneshot::Sender<Value>>>>,
+    published: Arc<Mutex<Vec<Published>>>,
+    next_id: i64,
+    versions: HashMap<String, i64>,
+}
+
+/// An unanswered request
+struct Outstanding {
+    method: String,
+    expect_result: bool,
+    sent: Instant,
+    response: oneshot::Receiver<Value>,
+}
+
+/// A JSON-RPC message; `params` is left out when null.
+fn message(id: Option<i64>, method: &str, params: Value) -> Value {
+    let mut message = json!({ "jsonrpc": "2.0", "method": method });
+    if let Some(id) = id {
+        message["id"] = id.into();
+    }
+    if !params.is_null() {
+        message["params"] = params;
+    }
+    message
+}
+
+async fn write_message(writer: &tokio::sync::Mutex<WriteHalf<DuplexStream>>, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    let mut writer = writer.lock().await;
+    writer.write_all(format!("Content-Length: {}\r\n\r\n{}", body.len(), body).as_bytes()).await?;
+    writer.flush().await
+}
+
+async fn read_message(reader: &mut BufReader<ReadHalf<DuplexStream>>) -> io::Result<Option<Value>> {
+    let mut content_length = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some(length) = line.strip_prefix("Content-Length: ") {
+            content_length = length.parse::<usize>().ok();
+        }
+    }
+    let length = content_length.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing Content-Length"))?;
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).await?;
+    serde_json::from_slice(&body).map(Some).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+impl Session {
+    /// Starts reading server messages: responses go to their waiters,
+    /// diagnostics are recorded and server requests are answered.
+    fn start(client: DuplexStream) -> Self {
+        let (reader, writer) = tokio::io::split(client);
+        let writer = Arc::new(tokio::sync::Mutex::new(writer));
+        let pending: Arc<Mutex<HashMap<i64, oneshot::Sender<Value>>>> = Arc::default();
+        let published: Arc<Mutex<Vec<Published>>> = Arc::default();
+
+        let (task_writer, task_pending, task_published) = (writer.clone(), pending.clone(), published.clone());
+        tokio::spawn(async move {
+            let mut reader = BufReader::new(reader);
+            while let Ok(Some(message)) = read_message(&mut reader).await {
+                let id = message.get("id").cloned();
+                match (message.get("method").and_then(Value::as_str), id) {
+                    (Some(method), Some(id)) => {
+                        let result = match method {
+                            "workspace/configuration" => {
+                                let items = message["params"]["items"].as_array().map_or(0, Vec::len);
+                                Value::Array(vec![Value::Null; items])
+                            }
+                            _ => Value::Null,
+                        };
+                        let _ = write_message(&task_writer, &json!({ "jsonrpc": "2.0", "id": id, "result": result })).await;
+                    }
+                    (Some("textDocument/publishDiagnostics"), None) => task_published.lock().push(Published {
+                        uri: message["params"]["uri"].as_str().unwrap_or_default().to_string(),
+                        version: message["params"]["version"].as_i64(),
+                    }),
+                    (Some(_), None) => {}
+                    (None, Some(id)) => {
+                        if let Some(waiter) = id.as_i64().and_then(|id| task_pending.lock().remove(&id)) {
+                            let _ = waiter.send(message);
+                        }
+                    }
+                    (None, None) => {}
+                }
+            }
+        });
+
+        Session { writer, pending, published, next_id: 1, versions: HashMap::new() }
+    }
+
+    async fn notify(&self, method: &str, params: Value) -> Result<(), String> {
+        write_message(&self.writer, &message(None, method, params))
+            .await
+            .map_err(|e| format!("sending {}: {}", method, e))
+    }
+
+    async fn request(&mut self, method: &str, params: Value, expect_result: bool) -> Result<Outstanding, String> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let (tx, response) = oneshot::channel();
+        self.pending.lock().insert(id, tx);
+        let sent = Instant::now();
+        write_message(&self.writer, &message(Some(id), method, params))
+            .await
+            .map_err(|e| format!("sending {}: {}", method, e))?;
+        Ok(Outstanding { method: method.to_string(), expect_result, sent, response })
+    }
+
+    async fn change(&mut self, uri: &str, changes: Vec<Value>) -> Result<(), String> {
+        let version = self.versions.entry(uri.to_string()).or_insert(0);
+        *version += 1;
+        let version = *version;
+        self.notify(
+            "textDocument/didChange",
+            json!({ "textDocument": { "uri": uri, "version": version }, "contentChanges": changes }),
+        )
+        .await
+    }
+
+    /// Waits until diagnostics of the latest version of `uri` are published.
+    async fn await_diagnostics(&self, uri: &str) -> Result<(), String> {
+        let latest = self.versions.get(uri).copied().unwrap_or_default();
+        let deadline = Instant::now() + DEADLOCK_TIMEOUT;
+        loop {
+            let done = self.published.lock().iter().rev().find(|p| p.uri == uri).and_then(|p| p.version) >= Some(latest);
+            if done {
+                return Ok(());
+            }
+            if Instant::now() > deadline {
+                return Err(format!("no diagnostics for version {} of {} after {:?}", latest, uri, DEADLOCK_TIMEOUT));
+            }
+            pass(POLL_INTERVAL).await;
+        }
+    }
+}
+
+/// Waits for the answer to `request`, returning its latency.
+async fn await_response(request: Outstanding, max_latency: Duration) -> Result<Duration, String> {
+    let method = request.method;
+    let response = match tokio::time::timeout(DEADLOCK_TIMEOUT, request.response).await {
+        Ok(Ok(response)) => response,
+        Ok(Err(_)) => return Err(format!("connection closed before {} was answered", method)),
+        Err(_) => return Err(format!("no response to {} after {:?}; is the server deadlocked?", method, DEADLOCK_TIMEOUT)),
+    };
+    let latency = request.sent.elapsed();
+    if let Some(error) = response.get("error") {
+        if !error["code"].as_i64().is_some_and(|code| ACCEPTED_ERROR_CODES.contains(&code)) {
+            return Err(format!("{} failed: {}", method, error));
+        }
+    } else if request.expect_result && response["result"].is_null() {
+        return Err(format!("{} returned no result", method));
+    }
+    if latency > max_latency {
+        return Err(format!("{} took {:?}, over the {:?} limit", method, latency, max_latency));
+    }
+    Ok(latency)
+}
+
+/// Checks that each document's diagnostics arrived in version order.
+fn check_diagnostic_order(published: &[Published]) -> Result<(), String> {
+    let mut last: HashMap<&str, i64> = HashMap::new();
+    for p in published {
+        match p.version {
+            Some(version) => {
+                if let Some(&previous) = last.get(p.uri.as_str()) {
+                    if version < previous {
+                        return Err(format!("diagnostics for {} went from version {} back to {}", p.uri, previous, version));
+                    }
+                }
+                last.insert(&p.uri, version);
+            }
+            // Cleared on close; a reopened document starts over
+            None => {
+                last.remove(p.uri.as_str());
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn play(session: &mut Session, scenario: &Scenario) -> Result<Vec<Duration>, String> {
+    let max_latency = Duration::from_millis(scenario.max_latency_ms);
+    let mut latencies = Vec::new();
+    let mut outstanding = Vec::new();
+
+    for step in &scenario.steps {
+        match step {
+            Step::Open { uri, text } => {
+                session.versions.insert(uri.clone(), 1);
+                session
+                    .notify(
+                        "textDocument/didOpen",
+                        json!({ "textDocument": { "uri": uri, "languageId": "rholang", "version": 1, "text": text } }),
+                    )
+                    .await?;
+            }
+            Step::Type { uri, at, text, delay_ms } => {
+                let (mut line, mut character) = *at;
+                for c in text.chars() {
+                    let position = json!({ "line": line, "character": character });
+                    let change = json!({ "range": { "start": position, "end": position }, "text": c.to_string() });
+                    session.change(uri, vec![change]).await?;
+                    if c == '\n' {
+                        (line, character) = (line + 1, 0);
+                    } else {
+                        character += c.len_utf16() as u32;
+                    }
+                    if *delay_ms > 0 {
+                        pass(Duration::from_millis(*delay_ms)).await;
+                    }
+                }
+            }
+            Step::Change { uri, changes } => session.change(uri, changes.clone()).await?,
+            Step::Close { uri } => {
+                session.versions.remove(uri);
+                session.notify("textDocument/didClose", json!({ "textDocument": { "uri": uri } })).await?;
+            }
+            Step::Request { method, params, wait, expect_result } => {
+                let request = session.request(method, params.clone(), *expect_result).await?;
+                if *wait {
+                    latencies.push(await_response(request, max_latency).await?);
+                } else {
+                    outstanding.push(request);
+                }
+            }
+            Step::Notify { method, params } => session.notify(method, params.clone()).await?,
+            Step::Wait { ms } => pass(Duration::from_millis(*ms)).await,
+            Step::AwaitDiagnostics { uri } => session.await_diagnostics(uri).await?,
+        }
+    }
+
+    for request in outstanding {
+        latencies.push(await_response(request, max_latency).await?);
+    }
+    Ok(latencies)
+}
+
+/// Runs `scenario` against a fresh server.
+async fn run_scenario(scenario: &Scenario) -> Result<(), String> {
+    let (client, server) = tokio::io::duplex(1 << 20);
+    let (server_reader, server_writer) = tokio::io::split(server);
+    let connection = Connection::new(server_reader, server_writer, format!("scenario {}", scenario.name));
+    let options = ServeOptions {
+        validator_backend: None,
+        client_process_id: None,
+        wire_logger: WireLogger::new(false, None).unwrap(),
+        edit_journal: EditJournal::disabled(),
+        shared_documents: SharedDocuments::default(),
+        debug_console: None,
+        strict_lsp: None,
+    };
+    let conn_manager = ConnectionManager::new();
+    let server = tokio::spawn(transport::serve(
+        Box::new(SessionTransport(Some(connection))),
+        options,
+        conn_manager.clone(),
+    ));
+
+    let mut session = Session::start(client);
+    let max_latency = Duration::from_millis(scenario.max_latency_ms);
+    let initialize = session
+        .request(
+            "initialize",
+            json!({
+                "processId": null,
+                "rootUri": null,
+                "capabilities": { "textDocument": { "publishDiagnostics": { "versionSupport": true } } },
+                "initializationOptions": scenario.initialization_options,
+            }),
+            true,
+        )
+        .await?;
+    await_response(initialize, DEADLOCK_TIMEOUT).await?;
+    session.notify("initialized", json!({})).await?;
+
+    let latencies = play(&mut session, scenario).await?;
+
+    let shutdown = session.request("shutdown", Value::Null, false).await?;
+    await_response(shutdown, max_latency).await?;
+    session.notify("exit", Value::Null).await?;
+    let _ = session.writer.lock().await.shutdown().await;
+
+    conn_manager.request_shutdown();
+    match tokio::time::timeout(DEADLOCK_TIMEOUT, server).await {
+        Ok(Ok(Ok(()))) => {}
+        Ok(Ok(Err(e))) => return Err(format!("server failed: {}", e)),
+        Ok(Err(e)) => return Err(format!("server task failed: {}", e)),
+        Err(_) => return Err(format!("server did not stop within {:?} of exit", DEADLOCK_TIMEOUT)),
+    }
+
+    check_diagnostic_order(&session.published.lock())?;
+    println!(
+        "{}: {} request(s), slowest {:?}",
+        scenario.name,
+        latencies.len(),
+        latencies.iter().max().copied().unwrap_or_default()
+    );
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_session_scenarios() {
+    deterministic::enable(0);
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("scenarios");
+    let mut paths: Vec<_> = std::fs::read_dir(&dir)
+        .expect("tests/scenarios exists")
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty(), "no scenarios in {}", dir.display());
+
+    for path in paths {
+        let scenario = Scenario::load(&path).unwrap();
+        if let Err(message) = run_scenario(&scenario).await {
+            panic!("scenario {} ({}) failed: {}", scenario.name, path.display(), message);
+        }
+    }
+}
+
+#[test]
+fn test_diagnostic_order_check() {
+    let published = |uri: &str, version: Option<i64>| Published { uri: uri.to_string(), version };
+    assert!(check_diagnostic_order(&[
+        published("file:///a.rho", Some(1)),
+        published("file:///b.rho", Some(1)),
+        published("file:///a.rho", Some(3)),
+        published("file:///a.rho", None),
+        published("file:///a.rho", Some(1)),
+    ])
+    .is_ok());
+    assert!(check_diagnostic_order(&[published("file:///a.rho", Some(4)), published("file:///a.rho", Some(2))]).is_err());
+}
@@ -0,0 +1,185 @@
+//! Stress tests for server shutdown.
+//!
+//! Clients repeatedly connect and disconnect while the server shuts down,
+//! in process through `transport::serve` and, on Unix, as a real process
+//! receiving SIGTERM. After every shutdown the server must have stopped all
+//! its tasks, closed every client socket and released its listening port.
+//!
+//! The in-process server runs in deterministic mode, and the waits between
+//! checks advance its virtual clock instead of sleeping.
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use tokio::io::AsyncReadExt;
+use tokio::net::{TcpListener, TcpStream};
+
+use rholang_language_server::deterministic;
+use rholang_language_server::edit_journal::EditJournal;
+use rholang_language_server::lsp::shared_documents::SharedDocuments;
+use rholang_language_server::transport::{self, ConnectionManager, ServeOptions, TcpTransport};
+use rholang_language_server::wire_logger::WireLogger;
+
+/// A server taking longer than this to stop is considered stuck
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+const ROUNDS: usize = 5;
+const CLIENTS_PER_ROUND: usize = 4;
+
+/// Lets `window` pass on the virtual clock, ending the server's debounce
+/// windows due on the way, and gives the other tasks a turn.
+async fn pass(window: Duration) {
+    let clock = deterministic::clock().expect("deterministic mode is on");
+    let elapsed = deterministic::sleep(window);
+    clock.advance_to(clock.elapsed() + window);
+    elapsed.await;
+    tokio::task::yield_now().await;
+}
+
+fn options() -> ServeOptions {
+    ServeOptions {
+        validator_backend: None,
+        client_process_id: None,
+        wire_logger: WireLogger::new(false, None).unwrap(),
+        edit_journal: EditJournal::disabled(),
+        shared_documents: SharedDocuments::default(),
+        debug_console: None,
+        strict_lsp: None,
+    }
+}
+
+/// Waits until the server closes `client`, failing on data or timeout.
+async fn assert_closed_by_server(mut client: TcpStream) {
+    let mut buf = [0u8; 1024];
+    loop {
+        match tokio::time::timeout(SHUTDOWN_TIMEOUT, client.read(&mut buf)).await {
+            Ok(Ok(0)) | Ok(Err(_)) => return,
+            // Unsolicited messages are fine, the socket must still close
+            Ok(Ok(_)) => continue,
+            Err(_) => panic!("server left a client socket open after shutdown"),
+        }
+    }
+}
+
+/// Connects to `addr`, retrying while the server starts.
+async fn connect(addr: SocketAddr) -> TcpStream {
+    let deadline = tokio::time::Instant::now() + SHUTDOWN_TIMEOUT;
+    loop {
+        match TcpStream::connect(addr).await {
+            Ok(stream) => return stream,
+            Err(e) if tokio::time::Instant::now() > deadline => panic!("could not connect to {}: {}", addr, e),
+            Err(_) => pass(Duration::from_millis(50)).await,
+        }
+    }
+}
+
+/// Waits until the server has accepted `count` connections.
+async fn wait_for_connections(conn_manager: &ConnectionManager, count: usize) {
+    let deadline = tokio::time::Instant::now() + SHUTDOWN_TIMEOUT;
+    while conn_manager.connection_count() < count {
+        assert!(tokio::time::Instant::now() < deadline, "server did not accept {} connections", count);
+        pass(Duration::from_millis(10)).await;
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_repeated_connect_disconnect_shutdown() {
+    deterministic::enable(0);
+    let mut port = 0;
+    for round in 0..ROUNDS {
+        let transport = TcpTransport::bind(("127.0.0.1", port)).await.expect("port released by the last round");
+        let addr = transport.local_addr().unwrap();
+        port = addr.port();
+
+        let conn_manager = ConnectionManager::new();
+        let server = tokio::spawn(transport::serve(Box::new(transport), options(), conn_manager.clone()));
+
+        let mut clients = Vec::new();
+        for _ in 0..CLIENTS_PER_ROUND {
+            clients.push(connect(addr).await);
+        }
+        wait_for_connections(&conn_manager, CLIENTS_PER_ROUND).await;
+
+        // Half of the clients leave before shutdown, one more connects
+        let leaving: Vec<_> = clients.drain(..CLIENTS_PER_ROUND / 2).collect();
+        drop(leaving);
+        clients.push(connect(addr).await);
+
+        conn_manager.request_shutdown();
+        if round % 2 == 1 {
+            // A second shutdown request must be harmless
+            conn_manager.request_shutdown();
+        }
+        tokio::time::timeout(SHUTDOWN_TIMEOUT, server)
+            .await
+            .expect("server did not stop after shutdown")
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(conn_manager.task_count(), 0, "round {}: tasks outlived shutdown", round);
+        assert_eq!(conn_manager.connection_count(), 0, "round {}: connections outlived shutdown", round);
+        for client in clients {
+            assert_closed_by_server(client).await;
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_shutdown_before_serving() {
+    deterministic::enable(0);
+    let transport = TcpTransport::bind("127.0.0.1:0").await.unwrap();
+    let conn_manager = ConnectionManager::new();
+    conn_manager.request_shutdown();
+
+    tokio::time::timeout(SHUTDOWN_TIMEOUT, transport::serve(Box::new(transport), options(), conn_manager.clone()))
+        .await
+        .expect("shutdown requested before serving was missed")
+        .unwrap();
+    assert_eq!(conn_manager.task_count(), 0);
+}
+
+#[cfg(unix)]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_sigterm_stops_server_process() {
+    deterministic::enable(0);
+    use nix::sys::signal::{kill, Signal};
+    use nix::unistd::Pid;
+    use std::process::{Command, Stdio};
+
+    for round in 0..3 {
+        let port = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap().port();
+        let mut child = Command::new(env!("CARGO_BIN_EXE_rholang-language-server"))
+            .args(["--socket", "--port", &port.to_string(), "--no-rnode", "--log-level", "error"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("failed to start the server");
+        let addr = SocketAddr::from(([127, 0, 0, 1], port));
+
+        let mut clients = Vec::new();
+        for _ in 0..CLIENTS_PER_ROUND {
+            clients.push(connect(addr).await);
+        }
+        drop(clients.pop());
+
+        kill(Pid::from_raw(child.id() as i32), Signal::SIGTERM).unwrap();
+        let deadline = std::time::Instant::now() + SHUTDOWN_TIMEOUT;
+        let status = loop {
+            if let Some(status) = child.try_wait().unwrap() {
+                break status;
+            }
+            if std::time::Instant::now() > deadline {
+                child.kill().ok();
+                panic!("round {}: server did not exit after SIGTERM", round);
+            }
+            pass(Duration::from_millis(20)).await;
+        };
+        assert!(status.success(), "round {}: server exited with {}", round, status);
+
+        for client in clients {
+            assert_closed_by_server(client).await;
+        }
+        TcpListener::bind(addr).await.expect("server did not release its port");
+    }
+}
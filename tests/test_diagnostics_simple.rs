@@ -1,6 +1,7 @@
 /// Simplified test for diagnostics update
 /// This test isolates the didChange + await_diagnostics functionality
 
+use tower_lsp::lsp_types::DiagnosticSeverity;
 use test_utils::with_lsp_client;
 use test_utils::lsp::client::{CommType, LspClient};
 
@@ -36,3 +37,18 @@ fn test_diagnostics_basic_valid() {
         assert_eq!(diagnostic_params.diagnostics.len(), 0);  // No errors for valid syntax
     });
 }
+
+#[test]
+fn test_diagnostics_expectations() {
+    with_lsp_client!(test_diagnostics_expectations_inner, CommType::Stdio, |client: &LspClient| {
+        let invalid = client.open_document("/tmp/unclosed.rho", r#"new x in { x!("Hello") "#).unwrap();
+        client
+            .expect_diagnostics(&invalid.uri())
+            .count(1)
+            .with_source("rholang-parser")
+            .with_severity(DiagnosticSeverity::ERROR);
+
+        let valid = client.open_document("/tmp/closed.rho", r#"new x in { x!("Hello") }"#).unwrap();
+        client.expect_diagnostics(&valid.uri()).none();
+    });
+}
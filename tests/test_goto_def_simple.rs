@@ -2,7 +2,7 @@
 /// This test isolates the goto_definition functionality without semantic validation overhead
 
 use indoc::indoc;
-use tower_lsp::lsp_types::Position;
+use tower_lsp::lsp_types::{Position, Range};
 use test_utils::with_lsp_client;
 use test_utils::lsp::client::{CommType, LspClient};
 
@@ -77,3 +77,24 @@ fn test_goto_def_with_ten_contracts() {
         assert!(result.unwrap().is_some(), "Should find definition location");
     });
 }
+
+#[test]
+fn test_goto_def_expectations() {
+    with_lsp_client!(test_goto_def_expectations_inner, CommType::Stdio, |client: &LspClient| {
+        let code = indoc! {r#"
+            contract targetContract(@x) = { Nil }
+            |
+            targetContract!(42)
+        "#};
+
+        let doc = client.open_document("/tmp/expectations.rho", code).unwrap();
+        client.expect_diagnostics(&doc.uri()).none();
+
+        // From the call on line 2 to the contract name on line 0
+        client
+            .expect_definition(&doc.uri(), Position::new(2, 0))
+            .count(1)
+            .resolves_to("/tmp/expectations.rho", Range::new(Position::new(0, 9), Position::new(0, 23)));
+        client.expect_references(&doc.uri(), Position::new(0, 9)).resolves_to_line("expectations.rho", 2);
+    });
+}
@@ -0,0 +1,54 @@
+/// Position-based features on documents with a byte order mark or with
+/// `\r\n` or `\r` line endings
+
+use tower_lsp::lsp_types::{Position, Range};
+use test_utils::with_lsp_client;
+use test_utils::lsp::client::{CommType, LspClient};
+
+/// A contract on line 1 called on line 3, with `eol` between lines.
+fn contract_and_call(eol: &str) -> String {
+    ["new ack in {", "  contract greet(@name) = { ack!(name) } |", "", "  greet!(\"world\")", "}"].join(eol)
+}
+
+fn greet_definition() -> Range {
+    Range::new(Position::new(1, 11), Position::new(1, 16))
+}
+
+#[test]
+fn test_crlf_line_endings() {
+    with_lsp_client!(test_crlf_line_endings_inner, CommType::Stdio, |client: &LspClient| {
+        let doc = client.open_document("/tmp/crlf.rho", &contract_and_call("\r\n")).unwrap();
+        client.expect_diagnostics(&doc.uri()).none();
+        client.expect_definition(&doc.uri(), Position::new(3, 3)).resolves_to("/tmp/crlf.rho", greet_definition());
+        client.expect_references(&doc.uri(), Position::new(1, 12)).resolves_to_line("/tmp/crlf.rho", 3);
+    });
+}
+
+#[test]
+fn test_cr_only_line_endings() {
+    with_lsp_client!(test_cr_only_line_endings_inner, CommType::Stdio, |client: &LspClient| {
+        let doc = client.open_document("/tmp/cr.rho", &contract_and_call("\r")).unwrap();
+        client.expect_diagnostics(&doc.uri()).none();
+        client.expect_definition(&doc.uri(), Position::new(3, 3)).resolves_to("/tmp/cr.rho", greet_definition());
+        client.expect_references(&doc.uri(), Position::new(1, 12)).resolves_to_line("/tmp/cr.rho", 3);
+    });
+}
+
+#[test]
+fn test_byte_order_mark() {
+    with_lsp_client!(test_byte_order_mark_inner, CommType::Stdio, |client: &LspClient| {
+        let code = format!("\u{feff}{}", contract_and_call("\n"));
+        let doc = client.open_document("/tmp/bom.rho", &code).unwrap();
+        client.expect_diagnostics(&doc.uri()).none();
+        client.expect_definition(&doc.uri(), Position::new(3, 3)).resolves_to("/tmp/bom.rho", greet_definition());
+    });
+}
+
+#[test]
+fn test_syntax_error_lines_with_cr_line_endings() {
+    with_lsp_client!(test_syntax_error_lines_with_cr_line_endings_inner, CommType::Stdio, |client: &LspClient| {
+        let doc = client.open_document("/tmp/cr_error.rho", "Nil\r|\rnew x in { x!(1) ").unwrap();
+        let diagnostics = client.expect_diagnostics(&doc.uri()).count(1);
+        assert_eq!(diagnostics.diagnostics()[0].range.start.line, 2);
+    });
+}
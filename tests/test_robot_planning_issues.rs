@@ -6,6 +6,8 @@
 //! 3. MeTTa goto-definition in virtual documents
 //! 4. MeTTa hover in virtual documents
 
+#![cfg(feature = "metta")]
+
 use std::fs;
 use test_utils::with_lsp_client;
 use test_utils::lsp::client::{CommType, LspClient};